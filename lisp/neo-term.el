@@ -16,6 +16,8 @@
 ;; Usage:
 ;;   M-x neo-term          -- open a terminal in the current window
 ;;   M-x neo-term-floating -- open a floating terminal overlay
+;;   M-x neo-term-tmux     -- attach to tmux, one buffer per tmux pane
+;;   M-x neo-term-ssh      -- open a terminal on an SSH host, no local shell
 
 ;;; Code:
 
@@ -41,6 +43,50 @@
   :type 'integer
   :group 'neo-term)
 
+(defcustom neo-term-font-family nil
+  "Font family for terminals.  nil means use the frame's font family."
+  :type '(choice (const :tag "Frame font" nil) string)
+  :group 'neo-term)
+
+(defcustom neo-term-font-size nil
+  "Font size for terminals in pixels.  nil means use the frame's font size."
+  :type '(choice (const :tag "Frame font" nil) number)
+  :group 'neo-term)
+
+(defcustom neo-term-bell-functions nil
+  "Functions called with the terminal ID when a terminal rings its bell.
+The visual bell itself is configured with the `terminal-visual-bell'
+display option."
+  :type 'hook
+  :group 'neo-term)
+
+(defcustom neo-term-prompt-heuristics nil
+  "Whether `neo-term-repl-mode' guesses prompts by their look.
+Shells that mark prompts with OSC 133 (shell integration) are tracked
+exactly.  For others, non-nil takes the first \"$ \", \"# \", \"> \" or
+similar on the cursor line as the end of the prompt."
+  :type 'boolean
+  :group 'neo-term)
+
+(defcustom neo-term-local-echo 'auto
+  "Whether terminals draw typed characters before the program echoes them.
+Printable characters typed at the end of the line are drawn at once,
+underlined, until the echo replaces them, as mosh does.  t always
+guesses, `auto' only while the echo takes over 30ms to come back, as
+over a slow SSH link, and nil never.  Use `neo-term-set-local-echo' to
+change it for one terminal."
+  :type '(choice (const :tag "While the echo is slow" auto)
+                 (const :tag "Always" t)
+                 (const :tag "Never" nil))
+  :group 'neo-term)
+
+(defcustom neo-term-tmux-command "tmux -C new-session -A -s neomacs"
+  "Default shell command for `neo-term-tmux'.
+It must start tmux in control mode (-C); prefix it with ssh to attach
+to a remote session."
+  :type 'string
+  :group 'neo-term)
+
 (defvar neo-term--terminals (make-hash-table :test 'eql)
   "Hash table mapping terminal-id to terminal info plists.")
 
@@ -50,16 +96,40 @@
 ;; These are C DEFUN primitives defined in neomacsterm.c
 (declare-function neomacs-terminal-create "neomacsterm.c"
                   (cols rows mode &optional shell))
+(declare-function neomacs-terminal-create-ssh "neomacsterm.c"
+                  (cols rows mode destination &optional command identity))
 (declare-function neomacs-terminal-write "neomacsterm.c"
                   (terminal-id string))
+(declare-function neomacs-terminal-write-many "neomacsterm.c"
+                  (terminal-id strings))
+(declare-function neomacs-terminal-cancel-input "neomacsterm.c"
+                  (terminal-id))
 (declare-function neomacs-terminal-resize "neomacsterm.c"
                   (terminal-id cols rows))
 (declare-function neomacs-terminal-destroy "neomacsterm.c"
                   (terminal-id))
+(declare-function neomacs-terminal-detach "neomacsterm.c" (terminal-id))
+(declare-function neomacs-terminal-attach "neomacsterm.c" (terminal-id mode))
 (declare-function neomacs-terminal-set-float "neomacsterm.c"
                   (terminal-id x y opacity))
+(declare-function neomacs-terminal-set-font "neomacsterm.c"
+                  (terminal-id family size))
+(declare-function neomacs-terminal-set-background "neomacsterm.c"
+                  (terminal-id style &optional arg amount blur))
+(declare-function neomacs-image-load "neomacsterm.c" (path))
+(declare-function neomacs-terminal-changed-lines "neomacsterm.c"
+                  (terminal-id &optional all))
 (declare-function neomacs-terminal-get-text "neomacsterm.c"
                   (terminal-id))
+(declare-function neomacs-terminal-input-region "neomacsterm.c"
+                  (terminal-id))
+(declare-function neomacs-terminal-set-prompt-heuristics "neomacsterm.c"
+                  (terminal-id on))
+(declare-function neomacs-terminal-set-local-echo "neomacsterm.c"
+                  (terminal-id mode))
+(declare-function neomacs-tmux-connect "neomacsterm.c" (command))
+(declare-function neomacs-tmux-command "neomacsterm.c" (client command))
+(declare-function neomacs-tmux-disconnect "neomacsterm.c" (client))
 
 (defun neo-term--shell-path ()
   "Return shell program to use."
@@ -75,6 +145,9 @@ Returns terminal ID or nil on failure."
     (condition-case err
         (let ((id (neomacs-terminal-create cols rows mode shell-path)))
           (when (and id (> id 0))
+            (when (or neo-term-font-family neo-term-font-size)
+              (neomacs-terminal-set-font id neo-term-font-family neo-term-font-size))
+            (neomacs-terminal-set-local-echo id neo-term-local-echo)
             (puthash id (list :id id :cols cols :rows rows :mode mode
                               :shell shell-path)
                      neo-term--terminals)
@@ -83,6 +156,23 @@ Returns terminal ID or nil on failure."
        (message "neo-term: failed to create terminal: %s" (error-message-string err))
        nil))))
 
+(defun neo-term--create-ssh (cols rows mode destination &optional command)
+  "Create a terminal on SSH host DESTINATION, running COMMAND or its shell.
+MODE is as for `neo-term--create'.  Returns terminal ID or nil on failure."
+  (condition-case err
+      (let ((id (neomacs-terminal-create-ssh cols rows mode destination command)))
+        (when (and id (> id 0))
+          (when (or neo-term-font-family neo-term-font-size)
+            (neomacs-terminal-set-font id neo-term-font-family neo-term-font-size))
+          (neomacs-terminal-set-local-echo id neo-term-local-echo)
+          (puthash id (list :id id :cols cols :rows rows :mode mode
+                            :ssh destination)
+                   neo-term--terminals)
+          id))
+    (error
+     (message "neo-term: failed to create SSH terminal: %s" (error-message-string err))
+     nil)))
+
 (defun neo-term--destroy (terminal-id)
   "Destroy a terminal."
   (when terminal-id
@@ -90,9 +180,11 @@ Returns terminal ID or nil on failure."
     (remhash terminal-id neo-term--terminals)))
 
 (defun neo-term--write (terminal-id string)
-  "Send STRING to the terminal."
+  "Send STRING to the terminal.
+Signal an error if the terminal still has too much input queued."
   (when (and terminal-id string)
-    (neomacs-terminal-write terminal-id string)))
+    (unless (neomacs-terminal-write terminal-id string)
+      (user-error "neo-term: terminal is busy, input not sent (C-c C-k cancels queued input)"))))
 
 (defun neo-term--resize (terminal-id cols rows)
   "Resize a terminal."
@@ -138,6 +230,9 @@ Returns terminal ID or nil on failure."
     (define-key map (kbd "C-c C-z") #'neo-term-send-ctrl-z)
     (define-key map (kbd "C-c C-\\") #'neo-term-send-ctrl-backslash)
     (define-key map (kbd "C-c C-q") #'neo-term-quit)
+    (define-key map (kbd "C-c C-y") #'neo-term-yank)
+    (define-key map (kbd "C-c C-k") #'neo-term-cancel-input)
+    (define-key map (kbd "C-c C-j") #'neo-term-detach)
     map)
   "Keymap for `neo-term-mode'.")
 
@@ -153,6 +248,12 @@ Returns terminal ID or nil on failure."
 (defvar-local neo-term--id nil
   "Terminal ID for this buffer.")
 
+(defvar-local neo-term--tmux nil
+  "(CLIENT . PANE) if this buffer shows a tmux pane.")
+
+(defvar neo-term--tmux-clients nil
+  "IDs of connected tmux clients, newest first.")
+
 (defun neo-term-send-key ()
   "Send the current key to the terminal."
   (interactive)
@@ -240,6 +341,151 @@ Returns terminal ID or nil on failure."
   (interactive)
   (when neo-term--id (neo-term--write neo-term--id "\034")))
 
+(defun neo-term-yank ()
+  "Paste the most recent kill into the terminal.
+Large text is queued and written in the background; use
+`neo-term-cancel-input' to abort it."
+  (interactive)
+  (when neo-term--id
+    (let* ((text (encode-coding-string (current-kill 0) 'utf-8))
+           (chunks (let ((pos 0) (acc nil))
+                     (while (< pos (length text))
+                       (push (substring text pos (min (length text) (+ pos 65536)))
+                             acc)
+                       (setq pos (+ pos 65536)))
+                     (nreverse acc))))
+      (unless (neomacs-terminal-write-many neo-term--id chunks)
+        (user-error "neo-term: terminal is busy, paste not sent")))))
+
+(defun neo-term-cancel-input ()
+  "Drop input queued for the terminal but not yet written, such as the
+rest of a large paste."
+  (interactive)
+  (when neo-term--id
+    (let ((dropped (neomacs-terminal-cancel-input neo-term--id)))
+      (message "neo-term: dropped %s bytes of queued input" (or dropped 0)))))
+
+(defun neo-term-set-font (family size)
+  "Set the font of this buffer's terminal to FAMILY at SIZE pixels.
+Empty FAMILY or SIZE follow the frame font."
+  (interactive
+   (list (read-string "Font family (empty for frame font): ")
+         (read-string "Font size in pixels (empty for frame font): ")))
+  (when neo-term--id
+    (neomacs-terminal-set-font
+     neo-term--id
+     (and (stringp family) (not (string-empty-p family)) family)
+     (cond ((numberp size) size)
+           ((and (stringp size) (not (string-empty-p size)))
+            (string-to-number size))))))
+
+(defun neo-term-set-background-image (file &optional dim)
+  "Draw image FILE behind this buffer's terminal.
+DIM is the opacity of the background color drawn over the image,
+0.7 by default.  With FILE nil or empty, go back to a solid background."
+  (interactive
+   (list (read-file-name "Background image (empty for none): " nil "")))
+  (when neo-term--id
+    (if (or (null file) (string-empty-p file))
+        (neomacs-terminal-set-background neo-term--id nil)
+      (let ((image (neomacs-image-load (expand-file-name file))))
+        (unless image
+          (user-error "Cannot load image %s" file))
+        (neomacs-terminal-set-background neo-term--id 'image image dim)))))
+
+(defun neo-term-set-acrylic (opacity &optional blur)
+  "Make this buffer's terminal background translucent at OPACITY.
+With BLUR non-nil, interactively with a prefix argument, blur what
+shows through.  OPACITY 1 or more goes back to a solid background."
+  (interactive
+   (list (read-number "Background opacity (0.0-1.0): " 0.8)
+         current-prefix-arg))
+  (when neo-term--id
+    (if (>= opacity 1)
+        (neomacs-terminal-set-background neo-term--id nil)
+      (neomacs-terminal-set-background neo-term--id 'acrylic nil opacity blur))))
+
+(defun neo-term-set-local-echo (mode)
+  "Set whether this buffer's terminal draws typed characters early.
+MODE is as for `neo-term-local-echo'.  Interactively, turn guessing
+on, or off with a prefix argument."
+  (interactive (list (not current-prefix-arg)))
+  (when neo-term--id
+    (neomacs-terminal-set-local-echo neo-term--id mode)))
+
+(defun neo-term-detach ()
+  "Close this buffer but keep its terminal running in the background.
+Use `neo-term-attach' to show it again."
+  (interactive)
+  (let ((id neo-term--id))
+    (when id
+      (neomacs-terminal-detach id)
+      (let ((info (gethash id neo-term--terminals)))
+        (puthash id (plist-put info :detached t) neo-term--terminals))
+      (setq neo-term--id nil)
+      (message "neo-term: terminal %d detached" id))
+    (kill-buffer)))
+
+(defun neo-term--detached-ids ()
+  "IDs of terminals detached with `neo-term-detach'."
+  (let (ids)
+    (maphash (lambda (id info)
+               (when (plist-get info :detached)
+                 (push id ids)))
+             neo-term--terminals)
+    (sort ids #'<)))
+
+(defun neo-term-attach (terminal-id)
+  "Show detached terminal TERMINAL-ID in the current window."
+  (interactive
+   (let ((ids (neo-term--detached-ids)))
+     (unless ids
+       (user-error "No detached terminals"))
+     (list (string-to-number
+            (completing-read "Attach terminal: "
+                             (mapcar #'number-to-string ids) nil t)))))
+  (neomacs-terminal-attach terminal-id 0) ; mode=0 (Window)
+  (let ((info (gethash terminal-id neo-term--terminals)))
+    (puthash terminal-id (plist-put (plist-put info :detached nil) :mode 0)
+             neo-term--terminals))
+  (neo-term--show terminal-id))
+
+(defun neo-term--show (terminal-id)
+  "Show terminal TERMINAL-ID in a new buffer in the current window."
+  (let ((buf (get-buffer-create
+              (format "*neo-term-%d*" neo-term--next-buffer-num))))
+    (cl-incf neo-term--next-buffer-num)
+    (switch-to-buffer buf)
+    (neo-term-mode)
+    (setq-local neo-term--id terminal-id)))
+
+(defun neo-term-mirror (terminal-id buffer &optional all)
+  "Copy the screen of terminal TERMINAL-ID into BUFFER.
+Only lines changed since the last call are rewritten, with their
+colors as faces.  BUFFER holds one line per screen line.  With ALL
+non-nil, rewrite every line, as needed the first time or after
+editing BUFFER."
+  (require 'ansi-color)
+  (pcase-let ((`(,rows . ,changes)
+               (neomacs-terminal-changed-lines terminal-id all)))
+    (when rows
+      (with-current-buffer buffer
+        (let ((inhibit-read-only t))
+          (save-excursion
+            (goto-char (point-min))
+            ;; Make the buffer exactly ROWS lines long
+            (let ((lines (count-lines (point-min) (point-max))))
+              (if (< lines rows)
+                  (progn (goto-char (point-max))
+                         (insert (make-string (- rows lines) ?\n)))
+                (forward-line rows)
+                (delete-region (point) (point-max))))
+            (pcase-dolist (`(,row . ,text) changes)
+              (goto-char (point-min))
+              (forward-line row)
+              (delete-region (point) (line-end-position))
+              (insert (ansi-color-apply text)))))))))
+
 (defun neo-term-quit ()
   "Kill the terminal and close the buffer."
   (interactive)
@@ -260,6 +506,10 @@ Returns terminal ID or nil on failure."
           (insert "\n[Process exited]\n"))
         (message "neo-term: terminal %d exited" terminal-id)))))
 
+(defun neo-term--handle-bell (terminal-id)
+  "Handle a bell from terminal TERMINAL-ID."
+  (run-hook-with-args 'neo-term-bell-functions terminal-id))
+
 (defun neo-term--handle-title-changed (terminal-id title)
   "Handle terminal TERMINAL-ID title change to TITLE."
   (dolist (buf (buffer-list))
@@ -274,20 +524,54 @@ Returns terminal ID or nil on failure."
 (defun neo-term ()
   "Open a new GPU-accelerated terminal in the current window."
   (interactive)
-  (let* ((buf-name (format "*neo-term-%d*" neo-term--next-buffer-num))
-         (buf (get-buffer-create buf-name))
-         (id (neo-term--create neo-term-default-cols neo-term-default-rows
-                               0))) ; mode=0 (Window)
+  (let ((id (neo-term--create neo-term-default-cols neo-term-default-rows
+                             0))) ; mode=0 (Window)
     (unless id
-      (kill-buffer buf)
       (error "Failed to create terminal"))
-    (cl-incf neo-term--next-buffer-num)
-    (switch-to-buffer buf)
-    (neo-term-mode)
-    (setq-local neo-term--id id)
+    (neo-term--show id)
     (message "neo-term: terminal %d created (%dx%d)"
              id neo-term-default-cols neo-term-default-rows)))
 
+;;;###autoload
+(defun neo-term-ssh (destination &optional command)
+  "Open a terminal in the current window on SSH host DESTINATION.
+DESTINATION is \"[USER@]HOST[:PORT]\".  The display engine logs in
+itself, with the SSH agent or the default keys, so no local shell is
+involved; the host must already be in ~/.ssh/known_hosts.  COMMAND,
+if non-nil, is run instead of the login shell.
+
+Interactively, in a buffer visiting a file over TRAMP's ssh methods,
+the default is that host, with the shell started in the remote
+directory."
+  (interactive
+   (let* ((remote (and (file-remote-p default-directory)
+                       (member (file-remote-p default-directory 'method)
+                               '("ssh" "sshx" "scp" "scpx" "rsync"))))
+          (default (and remote (neo-term--tramp-destination default-directory)))
+          (destination (read-string (if default
+                                        (format "SSH destination (default %s): " default)
+                                      "SSH destination: ")
+                                    nil nil default)))
+     (list destination
+           (and remote (equal destination default)
+                (format "cd %s && exec \"$SHELL\" -l"
+                        (shell-quote-argument
+                         (file-remote-p default-directory 'localname)))))))
+  (let ((id (neo-term--create-ssh neo-term-default-cols neo-term-default-rows
+                                  0 destination command))) ; mode=0 (Window)
+    (unless id
+      (error "Failed to create SSH terminal"))
+    (neo-term--show id)
+    (message "neo-term: terminal %d connecting to %s" id destination)))
+
+(defun neo-term--tramp-destination (file)
+  "The \"[USER@]HOST[:PORT]\" of remote FILE."
+  (let ((user (file-remote-p file 'user))
+        (host (file-remote-p file 'host)))
+    ;; TRAMP writes a port as HOST#PORT
+    (concat (if user (concat user "@") "")
+            (replace-regexp-in-string "#" ":" host))))
+
 ;;;###autoload
 (defun neo-term-floating (&optional x y cols rows)
   "Open a floating GPU terminal overlay.
@@ -305,5 +589,150 @@ Optional COLS, ROWS set the terminal size."
     (message "neo-term: floating terminal %d created (%dx%d)" id cols rows)
     id))
 
+;;;###autoload
+(defun neo-term-insert-inline (&optional cols rows)
+  "Insert a GPU terminal at point, COLS by ROWS frame characters.
+The terminal flows with the text like an image, sitting on the
+baseline of its line, so it can live in an org-babel results block.
+Its grid is fitted to the space it gets and it is clipped to the
+window.  Returns the terminal ID."
+  (interactive)
+  (let* ((cols (or cols neo-term-default-cols))
+         (rows (or rows neo-term-default-rows))
+         (id (neo-term--create cols rows 1))) ; mode=1 (Inline)
+    (unless id
+      (error "Failed to create inline terminal"))
+    (insert (propertize " " 'display (neo-term-inline-spec id cols rows)
+                        'neo-term-id id
+                        'rear-nonsticky t))
+    id))
+
+(defun neo-term-inline-spec (terminal-id cols rows)
+  "Display spec showing terminal TERMINAL-ID inline as COLS by ROWS."
+  (list 'terminal :id terminal-id :cols cols :rows rows))
+
+;;; REPL input editing
+
+(defun neo-term-input ()
+  "Return the input being typed at the prompt, as (TEXT . CURSOR).
+Returns nil if the shell is not known to be reading input."
+  (when neo-term--id
+    (pcase (neomacs-terminal-input-region neo-term--id)
+      (`(,_row ,_col ,cursor ,text) (cons text cursor)))))
+
+(defvar neo-term-repl-mode-map
+  (let ((map (make-sparse-keymap)))
+    (define-key map (kbd "C-c C-e") #'neo-term-edit-input)
+    map)
+  "Keymap for `neo-term-repl-mode'.")
+
+(define-minor-mode neo-term-repl-mode
+  "Track the shell prompt so the input line can be edited with Emacs.
+The terminal finds where input starts from OSC 133 marks sent by the
+shell, or, with `neo-term-prompt-heuristics', by the look of the
+prompt.  \\[neo-term-edit-input] then edits the input in the
+minibuffer while the output above stays an ordinary terminal.
+
+\\{neo-term-repl-mode-map}"
+  :lighter " REPL"
+  :keymap neo-term-repl-mode-map
+  (when neo-term--id
+    (neomacs-terminal-set-prompt-heuristics
+     neo-term--id (and neo-term-repl-mode neo-term-prompt-heuristics))))
+
+(defun neo-term-edit-input (&optional no-run)
+  "Edit the input at the prompt in the minibuffer, then run it.
+With prefix argument NO-RUN, put the edited input back at the prompt
+without running it."
+  (interactive "P")
+  (let ((input (or (neo-term-input)
+                   (user-error "No input at the prompt%s"
+                               (if neo-term-repl-mode "" "; try `neo-term-repl-mode'")))))
+    (let ((text (read-from-minibuffer "Input: " (car input))))
+      ;; C-e C-u empties the line in readline and zle
+      (unless (neomacs-terminal-write-many
+               neo-term--id (list "\C-e\C-u" text (if no-run "" "\r")))
+        (user-error "neo-term: terminal is busy, input not sent")))))
+
+;;; tmux control mode
+
+(defun neo-term--read-tmux-client ()
+  "Read a connected tmux client, defaulting to the newest."
+  (unless neo-term--tmux-clients
+    (user-error "No tmux clients"))
+  (if (cdr neo-term--tmux-clients)
+      (string-to-number
+       (completing-read "tmux client: "
+                        (mapcar #'number-to-string neo-term--tmux-clients)
+                        nil t nil nil
+                        (number-to-string (car neo-term--tmux-clients))))
+    (car neo-term--tmux-clients)))
+
+;;;###autoload
+(defun neo-term-tmux (command)
+  "Attach to tmux by running COMMAND, which starts tmux in control mode.
+Instead of drawing tmux's status line and borders in one terminal,
+each tmux pane gets a buffer of its own, named after the pane.  New
+panes and windows get buffers as they appear.  Returns the client ID."
+  (interactive (list (read-string "tmux command: " neo-term-tmux-command)))
+  (let ((client (neomacs-tmux-connect command)))
+    (unless client
+      (error "Failed to start tmux"))
+    (push client neo-term--tmux-clients)
+    (message "neo-term: tmux client %d started" client)
+    client))
+
+(defun neo-term-tmux-command (client command)
+  "Send COMMAND, a tmux command line such as \"split-window\", to CLIENT."
+  (interactive
+   (let ((client (or (car neo-term--tmux) (neo-term--read-tmux-client))))
+     (list client (read-string "tmux command: "))))
+  (neomacs-tmux-command client command))
+
+(defun neo-term-tmux-disconnect (client)
+  "Detach tmux CLIENT, closing its pane buffers.
+The tmux session keeps running and can be attached again."
+  (interactive (list (or (car neo-term--tmux) (neo-term--read-tmux-client))))
+  (neomacs-tmux-disconnect client))
+
+(defun neo-term--tmux-kill-pane ()
+  "Kill the tmux pane shown in the current buffer, for `kill-buffer-hook'."
+  (when (and neo-term--tmux (memq (car neo-term--tmux) neo-term--tmux-clients))
+    (neomacs-tmux-command (car neo-term--tmux)
+                          (format "kill-pane -t %%%d" (cdr neo-term--tmux)))))
+
+(defun neo-term--tmux-pane-added (client pane _window terminal-id cols rows)
+  "Show terminal TERMINAL-ID of pane PANE of tmux CLIENT, COLS by ROWS."
+  (puthash terminal-id (list :id terminal-id :cols cols :rows rows :mode 1
+                             :tmux client :pane pane)
+           neo-term--terminals)
+  (neomacs-terminal-set-local-echo terminal-id neo-term-local-echo)
+  (let ((buf (generate-new-buffer (format "*tmux %%%d*" pane))))
+    (with-current-buffer buf
+      (neo-term-mode)
+      (setq-local neo-term--id terminal-id)
+      (setq-local neo-term--tmux (cons client pane))
+      (add-hook 'kill-buffer-hook #'neo-term--tmux-kill-pane nil t)
+      (let ((inhibit-read-only t))
+        (insert (propertize " " 'display (neo-term-inline-spec terminal-id cols rows)
+                            'neo-term-id terminal-id
+                            'rear-nonsticky t))))
+    (display-buffer buf)))
+
+(defun neo-term--tmux-pane-closed (terminal-id)
+  "Close the buffer of tmux pane terminal TERMINAL-ID."
+  (dolist (buf (buffer-list))
+    (with-current-buffer buf
+      (when (and neo-term--tmux (eql neo-term--id terminal-id))
+        ;; The pane is already gone
+        (setq neo-term--tmux nil)
+        (kill-buffer buf))))
+  (neo-term--destroy terminal-id))
+
+(defun neo-term--tmux-exited (client)
+  "Forget tmux CLIENT, which exited or was detached."
+  (setq neo-term--tmux-clients (delq client neo-term--tmux-clients))
+  (message "neo-term: tmux client %d exited" client))
+
 (provide 'neo-term)
 ;;; neo-term.el ends here
@@ -0,0 +1,73 @@
+;;; neomacs-charts.el --- Sparklines and charts drawn by the GPU -*- lexical-binding: t -*-
+
+;; Copyright (C) 2024-2026 Free Software Foundation, Inc.
+
+;; Author: Neomacs Contributors
+;; Keywords: multimedia, data
+
+;; This file is part of GNU Emacs.
+
+;; GNU Emacs is free software: you can redistribute it and/or modify
+;; it under the terms of the GNU General Public License as published by
+;; the Free Software Foundation, either version 3 of the License, or
+;; (at your option) any later version.
+
+;; GNU Emacs is distributed in the hope that it will be useful,
+;; but WITHOUT ANY WARRANTY; without even the implied warranty of
+;; MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+;; GNU General Public License for more details.
+
+;; You should have received a copy of the GNU General Public License
+;; along with GNU Emacs.  If not, see <https://www.gnu.org/licenses/>.
+
+;;; Commentary:
+
+;; Neomacs draws line, area and bar charts of a series of numbers with
+;; the GPU, in the text like images, without going through gnuplot.
+;; A chart keeps its image id, and updating its values animates it.
+;;
+;; Basic usage:
+;;   (insert (neomacs-sparkline '(3 5 2 8 6 9)))
+;;   (insert (neomacs-chart-string [4 1 7] :type 'bar :width 120 :height 60))
+;;
+;; API functions:
+;;   `neomacs-chart' - Image spec for a chart
+;;   `neomacs-chart-update' - Animate a chart to new values
+;;   `neomacs-sparkline' - String showing a sparkline, one line high
+;;   `neomacs-chart-string' - String showing a chart
+
+;;; Code:
+
+(declare-function neomacs-chart "neomacsterm.c" (values &optional props))
+(declare-function neomacs-chart-update "neomacsterm.c"
+                  (image-id values &optional duration))
+
+(defgroup neomacs-charts nil
+  "Charts of numbers drawn by the GPU."
+  :group 'frames
+  :prefix "neomacs-chart-")
+
+(defcustom neomacs-sparkline-width 8
+  "Width of sparklines from `neomacs-sparkline', in columns."
+  :type 'natnum
+  :group 'neomacs-charts)
+
+(defun neomacs-chart-string (values &rest props)
+  "Return a string showing a chart of VALUES.
+PROPS are those of `neomacs-chart'.  Without a graphic display, return
+the empty string."
+  (let ((spec (and (display-graphic-p) (neomacs-chart values props))))
+    (if spec
+        (propertize " " 'display spec)
+      "")))
+
+(defun neomacs-sparkline (values &rest props)
+  "Return a string showing VALUES as a sparkline one line high.
+The sparkline is `neomacs-sparkline-width' columns wide.  PROPS are
+those of `neomacs-chart' and take precedence."
+  (apply #'neomacs-chart-string values
+         (append props
+                 (list :width (* neomacs-sparkline-width (frame-char-width))))))
+
+(provide 'neomacs-charts)
+;;; neomacs-charts.el ends here
@@ -0,0 +1,208 @@
+;;; neomacs-highlight.el --- Background syntax highlighting for Neomacs -*- lexical-binding: t -*-
+
+;; Copyright (C) 2024-2026 Free Software Foundation, Inc.
+
+;; Author: Neomacs Contributors
+;; Keywords: faces, languages
+
+;; This file is part of GNU Emacs.
+
+;; GNU Emacs is free software: you can redistribute it and/or modify
+;; it under the terms of the GNU General Public License as published by
+;; the Free Software Foundation, either version 3 of the License, or
+;; (at your option) any later version.
+
+;; GNU Emacs is distributed in the hope that it will be useful,
+;; but WITHOUT ANY WARRANTY; without even the implied warranty of
+;; MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+;; GNU General Public License for more details.
+
+;; You should have received a copy of the GNU General Public License
+;; along with GNU Emacs.  If not, see <https://www.gnu.org/licenses/>.
+
+;;; Commentary:
+
+;; Fontifying a huge file from Lisp can stall the editor.  With
+;; `neomacs-highlight-mode' the display engine parses the buffer with
+;; tree-sitter on a background thread instead, and colors the text as
+;; it is drawn.  Text that still carries faces from Lisp keeps them.
+;; The spans are merged by the Rust layout engine, so this has no effect
+;; after `neomacs-set-rust-display' turned it off.
+;;
+;; Basic usage:
+;;   M-x neomacs-highlight-mode
+;;
+;; API functions:
+;;   `neomacs-highlight-region' - Highlight one code block as a language
+;;   `neomacs-highlight-request' - Queue buffer text for highlighting
+;;   `neomacs-highlight-forget' - Drop the highlights of a buffer
+
+;;; Code:
+
+(declare-function neomacs-highlight-request "neomacsterm.c"
+                  (buffer language &optional beg end))
+(declare-function neomacs-highlight-edit "neomacsterm.c"
+                  (buffer beg old-end new-end))
+(declare-function neomacs-highlight-forget "neomacsterm.c" (buffer))
+(declare-function neomacs-highlight-set-face "neomacsterm.c"
+                  (kind color &optional bold italic))
+(declare-function neomacs-highlight-poll "neomacsterm.c" ())
+
+(defgroup neomacs-highlight nil
+  "Syntax highlighting on a background thread."
+  :group 'font-lock
+  :prefix "neomacs-highlight-")
+
+(defcustom neomacs-highlight-languages
+  '((rust-mode . "rust") (rust-ts-mode . "rust")
+    (c-mode . "c") (c-ts-mode . "c")
+    (python-mode . "python") (python-ts-mode . "python")
+    (js-mode . "javascript") (js-ts-mode . "javascript")
+    (sh-mode . "bash") (bash-ts-mode . "bash")
+    (js-json-mode . "json") (json-ts-mode . "json"))
+  "Alist mapping major modes to highlighter language names."
+  :type '(alist :key-type symbol :value-type string)
+  :group 'neomacs-highlight)
+
+(defcustom neomacs-highlight-faces
+  '((keyword . font-lock-keyword-face)
+    (string . font-lock-string-face)
+    (comment . font-lock-comment-face)
+    (function . font-lock-function-name-face)
+    (type . font-lock-type-face)
+    (constant . font-lock-constant-face)
+    (number . font-lock-number-face)
+    (property . font-lock-property-name-face)
+    (attribute . font-lock-preprocessor-face)
+    (label . font-lock-constant-face)
+    (escape . font-lock-escape-face)
+    (constructor . font-lock-type-face)
+    (builtin . font-lock-builtin-face))
+  "Alist mapping highlight categories to the faces they borrow colors from.
+Categories are `keyword', `string', `comment', `function', `type',
+`constant', `number', `variable', `property', `operator',
+`punctuation', `attribute', `label', `escape', `constructor' and
+`builtin'; those not listed are drawn in the default face."
+  :type '(alist :key-type symbol :value-type face)
+  :group 'neomacs-highlight
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (fboundp 'neomacs-highlight-set-face)
+           (neomacs-highlight--apply-faces))))
+
+(defcustom neomacs-highlight-delay 0.3
+  "Idle seconds after an edit before the buffer is highlighted again."
+  :type 'number
+  :group 'neomacs-highlight)
+
+(defconst neomacs-highlight--kinds
+  '(keyword string comment function type constant number variable
+    property operator punctuation attribute label escape constructor
+    builtin)
+  "All highlight categories.")
+
+(defvar-local neomacs-highlight--language nil
+  "Highlighter language of the current buffer.")
+
+(defvar-local neomacs-highlight--timer nil
+  "Idle timer re-highlighting the current buffer after edits.")
+
+(defvar-local neomacs-highlight--font-lock nil
+  "Whether `font-lock-mode' was on before `neomacs-highlight-mode'.")
+
+(defvar neomacs-highlight--poll-timer nil
+  "Timer merging finished highlight jobs while any are pending.")
+
+(defun neomacs-highlight--apply-faces ()
+  "Send the colors of `neomacs-highlight-faces' to the display engine."
+  (dolist (kind neomacs-highlight--kinds)
+    (let ((face (alist-get kind neomacs-highlight-faces)))
+      (if (and face (facep face))
+          (neomacs-highlight-set-face
+           kind (face-foreground face nil 'default)
+           (memq (face-attribute face :weight nil 'default)
+                 '(bold extra-bold ultra-bold semi-bold))
+           (memq (face-attribute face :slant nil 'default)
+                 '(italic oblique)))
+        (neomacs-highlight-set-face kind nil)))))
+
+(defun neomacs-highlight--poll ()
+  "Merge finished highlight jobs; stop polling once none are pending."
+  (when (zerop (neomacs-highlight-poll))
+    (cancel-timer neomacs-highlight--poll-timer)
+    (setq neomacs-highlight--poll-timer nil)))
+
+(defun neomacs-highlight--start-polling ()
+  "Poll for highlight results until the queue drains."
+  (unless neomacs-highlight--poll-timer
+    (setq neomacs-highlight--poll-timer
+          (run-with-timer 0.05 0.05 #'neomacs-highlight--poll))))
+
+(defun neomacs-highlight-region (beg end language)
+  "Highlight the text between BEG and END as LANGUAGE in the background.
+Useful for code blocks embedded in another mode's buffer.  Returns
+non-nil if LANGUAGE is supported."
+  (when (neomacs-highlight-request (current-buffer) language beg end)
+    (neomacs-highlight--start-polling)
+    t))
+
+(defun neomacs-highlight--refresh (buffer)
+  "Highlight all of BUFFER again."
+  (when (buffer-live-p buffer)
+    (with-current-buffer buffer
+      (setq neomacs-highlight--timer nil)
+      (when neomacs-highlight--language
+        (save-restriction
+          (widen)
+          (neomacs-highlight-region (point-min) (point-max)
+                                    neomacs-highlight--language))))))
+
+(defun neomacs-highlight--after-change (beg end old-len)
+  "Shift highlights past an edit of BEG..END that replaced OLD-LEN chars."
+  (neomacs-highlight-edit (current-buffer) beg (+ beg old-len) end)
+  (when neomacs-highlight--timer
+    (cancel-timer neomacs-highlight--timer))
+  (setq neomacs-highlight--timer
+        (run-with-idle-timer neomacs-highlight-delay nil
+                             #'neomacs-highlight--refresh (current-buffer))))
+
+;;;###autoload
+(define-minor-mode neomacs-highlight-mode
+  "Highlight syntax on a background thread instead of with font-lock.
+The language comes from `neomacs-highlight-languages'.  While the mode
+is on, `font-lock-mode' is turned off in the buffer."
+  :lighter " NHl"
+  (cond
+   ((not neomacs-highlight-mode)
+    (remove-hook 'after-change-functions #'neomacs-highlight--after-change t)
+    (remove-hook 'kill-buffer-hook #'neomacs-highlight--forget t)
+    (neomacs-highlight--forget)
+    (when neomacs-highlight--font-lock
+      (font-lock-mode 1)))
+   ((not (fboundp 'neomacs-highlight-request))
+    (setq neomacs-highlight-mode nil)
+    (user-error "Background highlighting needs a Neomacs display"))
+   ((not (setq neomacs-highlight--language
+               (alist-get major-mode neomacs-highlight-languages)))
+    (setq neomacs-highlight-mode nil)
+    (user-error "No highlighter language for %s" major-mode))
+   (t
+    (setq neomacs-highlight--font-lock font-lock-mode)
+    (when font-lock-mode
+      (font-lock-mode -1))
+    (neomacs-highlight--apply-faces)
+    (add-hook 'after-change-functions #'neomacs-highlight--after-change nil t)
+    (add-hook 'kill-buffer-hook #'neomacs-highlight--forget nil t)
+    (neomacs-highlight--refresh (current-buffer)))))
+
+(defun neomacs-highlight--forget ()
+  "Drop the background highlights of the current buffer."
+  (when neomacs-highlight--timer
+    (cancel-timer neomacs-highlight--timer)
+    (setq neomacs-highlight--timer nil))
+  (setq neomacs-highlight--language nil)
+  (when (fboundp 'neomacs-highlight-forget)
+    (neomacs-highlight-forget (current-buffer))))
+
+(provide 'neomacs-highlight)
+;;; neomacs-highlight.el ends here
@@ -0,0 +1,131 @@
+;;; neomacs-html.el --- Lightweight HTML previews for Neomacs -*- lexical-binding: t -*-
+
+;; Copyright (C) 2024-2026 Free Software Foundation, Inc.
+
+;; Author: Neomacs Contributors
+;; Keywords: hypermedia, multimedia
+
+;; This file is part of GNU Emacs.
+
+;; GNU Emacs is free software: you can redistribute it and/or modify
+;; it under the terms of the GNU General Public License as published by
+;; the Free Software Foundation, either version 3 of the License, or
+;; (at your option) any later version.
+
+;; GNU Emacs is distributed in the hope that it will be useful,
+;; but WITHOUT ANY WARRANTY; without even the implied warranty of
+;; MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+;; GNU General Public License for more details.
+
+;; You should have received a copy of the GNU General Public License
+;; along with GNU Emacs.  If not, see <https://www.gnu.org/licenses/>.
+
+;;; Commentary:
+
+;; The Neomacs display engine can lay out simple HTML -- headings,
+;; paragraphs, lists, emphasis, code, links, quotes and images -- itself,
+;; without the wpe-webkit feature.  A window whose `neomacs-html'
+;; parameter is a string shows that HTML in place of its buffer text,
+;; keeping its mode line.
+;;
+;; Basic usage:
+;;   M-x neomacs-html-preview-mode   show the buffer's HTML rendered
+;;
+;; API functions:
+;;   `neomacs-html-show' - Show HTML in a window
+;;   `neomacs-html-hide' - Show the window's buffer text again
+
+;;; Code:
+
+(declare-function neomacs-html-link-at "neomacsterm.c" (x y))
+
+(defun neomacs-html-show (html &optional window)
+  "Show the string HTML rendered in WINDOW instead of its buffer text.
+WINDOW defaults to the selected window.  Images are written as
+<img src=\"neomacs-image:ID\"> with IDs of images loaded by Neomacs."
+  (let ((window (window-normalize-window window t)))
+    (set-window-parameter window 'neomacs-html html)
+    (force-window-update window)))
+
+(defun neomacs-html-hide (&optional window)
+  "Show the buffer text of WINDOW again instead of its HTML."
+  (let ((window (window-normalize-window window t)))
+    (set-window-parameter window 'neomacs-html nil)
+    (force-window-update window)))
+
+(defun neomacs-html-follow-link (event)
+  "Browse the target of the HTML link clicked in EVENT."
+  (interactive "e")
+  (let* ((posn (event-start event))
+         (window (posn-window posn))
+         (edges (window-inside-pixel-edges window))
+         (xy (posn-x-y posn))
+         (href (neomacs-html-link-at (+ (nth 0 edges) (car xy))
+                                     (+ (nth 1 edges) (cdr xy)))))
+    (if href
+        (browse-url href)
+      (message "No link here"))))
+
+(defvar-keymap neomacs-html-preview-mode-map
+  :doc "Keymap for `neomacs-html-preview-mode'."
+  "<mouse-1>" #'neomacs-html-follow-link)
+
+(defun neomacs-html--refresh (&rest _)
+  "Show the current buffer's text as HTML in the windows showing it."
+  (let ((html (buffer-substring-no-properties (point-min) (point-max))))
+    (dolist (window (get-buffer-window-list nil nil t))
+      (neomacs-html-show html window))))
+
+(defun neomacs-html--window-buffer-changed (frame)
+  "Update the HTML shown by windows of FRAME that changed buffers."
+  (dolist (window (window-list frame 'never))
+    (let ((buffer (window-buffer window)))
+      (cond
+       ((buffer-local-value 'neomacs-html-preview-mode buffer)
+        (with-current-buffer buffer
+          (neomacs-html-show (buffer-substring-no-properties
+                              (point-min) (point-max))
+                             window)))
+       ((window-parameter window 'neomacs-html)
+        (neomacs-html-hide window)))))
+  (neomacs-html--maybe-remove-hook))
+
+(defun neomacs-html--maybe-remove-hook ()
+  "Stop watching window buffer changes once nothing shows HTML.
+That is when no buffer is previewed and no window has a
+`neomacs-html' parameter left."
+  (unless (or (seq-some (lambda (buffer)
+                          (buffer-local-value 'neomacs-html-preview-mode
+                                              buffer))
+                        (buffer-list))
+              (seq-some (lambda (window)
+                          (window-parameter window 'neomacs-html))
+                        (window-list-1 nil 'never t)))
+    (remove-hook 'window-buffer-change-functions
+                 #'neomacs-html--window-buffer-changed)))
+
+;;;###autoload
+(define-minor-mode neomacs-html-preview-mode
+  "Show the text of the current buffer rendered as HTML.
+The windows showing the buffer display the rendered HTML instead of
+its text, updated as the buffer changes.  Clicking a link browses its
+target."
+  :lighter " HTML"
+  (unless (fboundp 'neomacs-html-link-at)
+    (setq neomacs-html-preview-mode nil)
+    (user-error "HTML previews need a Neomacs display"))
+  (if neomacs-html-preview-mode
+      (progn
+        (add-hook 'after-change-functions #'neomacs-html--refresh nil t)
+        (add-hook 'window-buffer-change-functions
+                  #'neomacs-html--window-buffer-changed)
+        (setq-local cursor-type nil)
+        (neomacs-html--refresh))
+    (remove-hook 'after-change-functions #'neomacs-html--refresh t)
+    (kill-local-variable 'cursor-type)
+    (dolist (window (get-buffer-window-list nil nil t))
+      (neomacs-html-hide window))
+    (neomacs-html--maybe-remove-hook)))
+
+(provide 'neomacs-html)
+;;; neomacs-html.el ends here
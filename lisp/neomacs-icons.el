@@ -0,0 +1,92 @@
+;;; neomacs-icons.el --- Named icons for mode lines and tab bars -*- lexical-binding: t -*-
+
+;; Copyright (C) 2024-2026 Free Software Foundation, Inc.
+
+;; Author: Neomacs Contributors
+;; Keywords: multimedia, faces
+
+;; This file is part of GNU Emacs.
+
+;; GNU Emacs is free software: you can redistribute it and/or modify
+;; it under the terms of the GNU General Public License as published by
+;; the Free Software Foundation, either version 3 of the License, or
+;; (at your option) any later version.
+
+;; GNU Emacs is distributed in the hope that it will be useful,
+;; but WITHOUT ANY WARRANTY; without even the implied warranty of
+;; MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+;; GNU General Public License for more details.
+
+;; You should have received a copy of the GNU General Public License
+;; along with GNU Emacs.  If not, see <https://www.gnu.org/licenses/>.
+
+;;; Commentary:
+
+;; Neomacs loads SVG icon sets and icon fonts once and rasterizes their
+;; icons on demand, at the size and color asked for and at the display's
+;; scale, so mode lines and tab bars get crisp icons without PNGs.
+;;
+;; Basic usage:
+;;   (setq neomacs-icon-sets '(("mdi" "~/icons/mdi/")))
+;;   (neomacs-icon-load-sets)
+;;   (setq-default mode-line-format
+;;                 (cons '(:eval (neomacs-icon "mdi:file")) mode-line-format))
+;;
+;; API functions:
+;;   `neomacs-icon-set-load' - Load an icon set from a directory or font
+;;   `neomacs-icon-image' - Image spec for an icon
+;;   `neomacs-icon-clear-cache' - Free all icon images
+;;   `neomacs-icon' - String showing an icon, for mode lines
+
+;;; Code:
+
+(declare-function neomacs-icon-set-load "neomacsterm.c"
+                  (name path &optional codepoints))
+(declare-function neomacs-icon-image "neomacsterm.c"
+                  (name &optional size color))
+(declare-function neomacs-icon-clear-cache "neomacsterm.c" ())
+
+(defgroup neomacs-icons nil
+  "Named icons from SVG icon sets and icon fonts."
+  :group 'frames
+  :prefix "neomacs-icon-")
+
+(defcustom neomacs-icon-sets nil
+  "Icon sets loaded by `neomacs-icon-load-sets'.
+Each element is (NAME PATH) or (NAME PATH CODEPOINTS), as the arguments
+of `neomacs-icon-set-load'."
+  :type '(repeat (list (string :tag "Name")
+                       (file :tag "Directory or font")
+                       (choice :tag "Codepoints" (const nil) file)))
+  :group 'neomacs-icons)
+
+(defcustom neomacs-icon-scale 1.0
+  "Size of icons from `neomacs-icon' relative to the default font."
+  :type 'number
+  :group 'neomacs-icons)
+
+(defun neomacs-icon-load-sets ()
+  "Load the icon sets of `neomacs-icon-sets'.
+Sets that fail to load are reported and skipped."
+  (interactive)
+  (dolist (set neomacs-icon-sets)
+    (condition-case err
+        (apply #'neomacs-icon-set-load set)
+      (error (message "Icon set %s: %s" (car set) (error-message-string err)))))
+  (neomacs-icon-clear-cache))
+
+(defun neomacs-icon (name &optional color fallback)
+  "Return a string showing icon NAME, for mode lines and tab bars.
+NAME is \"SET:ICON\" or an ICON of any loaded set.  COLOR is a color
+string, or t for the icon's own colors; by default the frame
+foreground.  If no loaded set has the icon, return FALLBACK, or the
+empty string."
+  (let* ((size (and (display-graphic-p)
+                    (round (* (frame-char-height) neomacs-icon-scale))))
+         (spec (and size (neomacs-icon-image name size color))))
+    (if spec
+        (propertize " " 'display spec)
+      (or fallback ""))))
+
+(provide 'neomacs-icons)
+;;; neomacs-icons.el ends here
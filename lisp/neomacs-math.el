@@ -0,0 +1,150 @@
+;;; neomacs-math.el --- Inline TeX math previews for Neomacs -*- lexical-binding: t -*-
+
+;; Copyright (C) 2024-2026 Free Software Foundation, Inc.
+
+;; Author: Neomacs Contributors
+;; Keywords: tex, multimedia
+
+;; This file is part of GNU Emacs.
+
+;; GNU Emacs is free software: you can redistribute it and/or modify
+;; it under the terms of the GNU General Public License as published by
+;; the Free Software Foundation, either version 3 of the License, or
+;; (at your option) any later version.
+
+;; GNU Emacs is distributed in the hope that it will be useful,
+;; but WITHOUT ANY WARRANTY; without even the implied warranty of
+;; MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+;; GNU General Public License for more details.
+
+;; You should have received a copy of the GNU General Public License
+;; along with GNU Emacs.  If not, see <https://www.gnu.org/licenses/>.
+
+;;; Commentary:
+
+;; Neomacs typesets TeX math in-process, without an external LaTeX
+;; installation, and uploads each snippet to the GPU as an image whose
+;; baseline lines up with the surrounding text.
+;;
+;; Basic usage:
+;;   M-x neomacs-math-preview        preview math in the region or buffer
+;;   M-x neomacs-math-preview-clear  remove the previews
+;;
+;; API functions:
+;;   `neomacs-math-image' - Image spec for a TeX snippet
+;;   `neomacs-math-clear-cache' - Free all snippet images
+
+;;; Code:
+
+(declare-function neomacs-math-image "neomacsterm.c"
+                  (tex &optional display size color))
+(declare-function neomacs-math-clear-cache "neomacsterm.c" ())
+
+(defgroup neomacs-math nil
+  "Inline TeX math previews."
+  :group 'frames
+  :prefix "neomacs-math-")
+
+(defcustom neomacs-math-scale 1.0
+  "Size of math previews relative to the default font."
+  :type 'number
+  :group 'neomacs-math)
+
+(defconst neomacs-math--delimiters
+  '(("$$" "$$" t) ("\\[" "\\]" t) ("$" "$" nil) ("\\(" "\\)" nil))
+  "Math delimiters as (OPEN CLOSE DISPLAY), longest first.")
+
+(defun neomacs-math--escaped-p (pos)
+  "Return non-nil if the character at POS is preceded by an odd backslash run."
+  (let ((n 0))
+    (while (and (> (- pos n) (point-min))
+                (eq (char-before (- pos n)) ?\\))
+      (setq n (1+ n)))
+    (= (% n 2) 1)))
+
+(defun neomacs-math--size ()
+  "Font size in pixels for math previews in the current buffer."
+  (* neomacs-math-scale
+     (or (let ((font (face-attribute 'default :font nil t)))
+           (and (fontp font) (font-get font :size)))
+         (frame-char-height))))
+
+(defun neomacs-math--next (end)
+  "Find the next math snippet before END.
+Return (BEG END BODY DISPLAY) and move past it, or nil."
+  (let ((open-re (regexp-opt (mapcar #'car neomacs-math--delimiters)))
+        found)
+    (while (and (not found) (re-search-forward open-re end t))
+      (let* ((open (match-string 0))
+             (beg (match-beginning 0))
+             (delim (assoc open neomacs-math--delimiters))
+             (close (nth 1 delim))
+             (body-beg (point)))
+        (unless (neomacs-math--escaped-p beg)
+          (let (close-beg)
+            (while (and (not close-beg) (search-forward close end t))
+              (unless (neomacs-math--escaped-p (match-beginning 0))
+                (setq close-beg (match-beginning 0))))
+            (if (and close-beg (> close-beg body-beg))
+                (setq found (list beg (point)
+                                  (buffer-substring-no-properties
+                                   body-beg close-beg)
+                                  (nth 2 delim)))
+              (goto-char body-beg))))))
+    found))
+
+;;;###autoload
+(defun neomacs-math-preview (&optional beg end)
+  "Show TeX math between BEG and END as typeset images.
+Interactively, use the region if active, otherwise the whole buffer.
+Recognizes $...$, $$...$$, \\(...\\) and \\=\\[...\\]; editing a snippet
+removes its preview."
+  (interactive
+   (if (use-region-p)
+       (list (region-beginning) (region-end))
+     (list (point-min) (point-max))))
+  (unless (fboundp 'neomacs-math-image)
+    (user-error "Math previews need a Neomacs display"))
+  (let ((beg (or beg (point-min)))
+        (end (or end (point-max)))
+        (size (neomacs-math--size))
+        (count 0)
+        (errors 0))
+    (neomacs-math-preview-clear beg end)
+    (save-excursion
+      (goto-char beg)
+      (let (snippet)
+        (while (setq snippet (neomacs-math--next end))
+          (pcase-let ((`(,mbeg ,mend ,body ,display) snippet))
+            (condition-case err
+                (let ((spec (neomacs-math-image body display size))
+                      (ov (make-overlay mbeg mend nil t nil)))
+                  (overlay-put ov 'neomacs-math t)
+                  (overlay-put ov 'display spec)
+                  (overlay-put ov 'evaporate t)
+                  (overlay-put ov 'modification-hooks
+                               (list #'neomacs-math--invalidate))
+                  (overlay-put ov 'help-echo body)
+                  (setq count (1+ count)))
+              (error
+               (setq errors (1+ errors))
+               (message "Math at %d: %s" mbeg (error-message-string err))))))))
+    (message "Previewed %d math snippet%s%s" count (if (= count 1) "" "s")
+             (if (> errors 0) (format ", %d failed" errors) ""))))
+
+(defun neomacs-math--invalidate (ov after &rest _)
+  "Drop preview overlay OV once its text is edited."
+  (when after
+    (delete-overlay ov)))
+
+(defun neomacs-math-preview-clear (&optional beg end)
+  "Remove math previews between BEG and END (default the whole buffer)."
+  (interactive
+   (if (use-region-p)
+       (list (region-beginning) (region-end))
+     (list (point-min) (point-max))))
+  (remove-overlays (or beg (point-min)) (or end (point-max))
+                   'neomacs-math t))
+
+(provide 'neomacs-math)
+;;; neomacs-math.el ends here
@@ -33,6 +33,8 @@
 ;;   `neomacs-video-pause' - Pause playback
 ;;   `neomacs-video-stop' - Stop playback
 ;;   `neomacs-video-insert' - Insert video display at point
+;;   `neomacs-video-pip-at-point' - Detach the video at point into a
+;;                                  picture-in-picture player
 
 ;;; Code:
 
@@ -214,5 +216,72 @@ Like `neomacs-video-show-floating' but with automatic looping."
       (neomacs-video-loop video-id t))
     video-id))
 
+;;; Picture-in-picture
+
+(declare-function neomacs-video-pip "neomacsterm.c" (video-id &optional corner width))
+
+(defvar neomacs-video-pip-corner 'bottom-right
+  "Frame corner new picture-in-picture players are pinned to.
+One of `top-left', `top-right', `bottom-left' or `bottom-right'.")
+
+(defvar neomacs-video-pip-width 320
+  "Width in pixels of new picture-in-picture players.")
+
+(defvar neomacs-video--pip nil
+  "The video in the picture-in-picture player, as (VIDEO-ID MARKER DISPLAY).
+MARKER is where the video was in its buffer and DISPLAY the display
+property it had there.")
+
+(defun neomacs-video--pip-restore ()
+  "Put the video of the picture-in-picture player back in its buffer."
+  (pcase-let ((`(,_id ,marker ,display) neomacs-video--pip))
+    (setq neomacs-video--pip nil)
+    (when (buffer-live-p (marker-buffer marker))
+      (with-current-buffer (marker-buffer marker)
+        (with-silent-modifications
+          (put-text-property marker (1+ marker) 'display display)))
+      (set-marker marker nil))))
+
+(defun neomacs-video-pip-at-point ()
+  "Detach the video at point into a picture-in-picture player.
+The player is pinned to `neomacs-video-pip-corner' and stays there
+while you switch buffers.  Drag it to move it to another corner, or
+by the grip on its top edge to resize it.  Closing the player, or
+calling this command again, puts the video back in its buffer."
+  (interactive)
+  (let ((video-id (neomacs-video-at-point)))
+    (cond
+     ((null video-id) (message "No video at point"))
+     (t
+      (when neomacs-video--pip
+        (neomacs-video-pip nil)
+        (neomacs-video--pip-restore))
+      (when (neomacs-video-pip video-id neomacs-video-pip-corner
+                               neomacs-video-pip-width)
+        (let ((pos (point)))
+          (setq neomacs-video--pip
+                (list video-id (copy-marker pos)
+                      (get-text-property pos 'display)))
+          (with-silent-modifications
+            (put-text-property pos (1+ pos) 'display
+                               "[picture-in-picture]")))
+        (neomacs-video-play video-id))))))
+
+(defun neomacs-video-pip-close ()
+  "Close the picture-in-picture player, putting its video back."
+  (interactive)
+  (when neomacs-video--pip
+    (neomacs-video-pip nil)
+    (neomacs-video--pip-restore)))
+
+(defun neomacs-video--pip-event (kind id _arg)
+  "Put the video back when its picture-in-picture player is closed.
+KIND and ID are as in `neomacs-display-event-functions'."
+  (when (and (eq kind 'pip-closed)
+             (eql id (car neomacs-video--pip)))
+    (neomacs-video--pip-restore)))
+
+(add-hook 'neomacs-display-event-functions #'neomacs-video--pip-event)
+
 (provide 'neomacs-video)
 ;;; neomacs-video.el ends here
@@ -64,6 +64,14 @@ If set to 0 or nil, auto-calculate from width using aspect ratio."
   :type 'boolean
   :group 'neomacs-webkit)
 
+(defcustom neomacs-webkit-default-profile nil
+  "Name of the WebKit profile used for new browser views.
+Profiles are defined with `neomacs-webkit-define-profile' and keep
+their cookies, local storage and cache separate from each other.
+nil means the default profile."
+  :type '(choice (const :tag "Default profile" nil) string)
+  :group 'neomacs-webkit)
+
 (defvar neomacs-webkit--views (make-hash-table :test 'eq)
   "Hash table mapping view IDs to their metadata.")
 
@@ -174,7 +182,7 @@ Returns the view ID on success, nil on failure."
                 (or height neomacs-webkit-default-height)))
          (w (car dims))
          (h (cdr dims))
-         (view-id (neomacs-webkit-create w h)))
+         (view-id (neomacs-webkit-create w h neomacs-webkit-default-profile)))
     (when view-id
       (neomacs-webkit-load-uri view-id url)
       (puthash view-id `(:url ,url :width ,w :height ,h) neomacs-webkit--views)
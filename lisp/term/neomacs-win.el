@@ -110,9 +110,16 @@ DISPLAY is the name of the display Emacs should connect to."
   ;; Emacs-side blink timer (which would fight with the render-thread blink).
   (neomacs--setup-cursor-blink)
 
+  ;; Animated images out of sight or in unfocused frames advance slower
+  (advice-add 'image-animate-timeout :around #'neomacs--throttle-image-animation
+              '((name . neomacs-throttle-image-animation)))
+
   ;; Set up animations (smooth cursor, crossfade, scroll slide)
   (neomacs--setup-animations)
 
+  ;; Define a user option for each display engine option
+  (neomacs-define-display-options)
+
   ;; Clipboard integration via Rust arboard crate
   (setq interprogram-cut-function #'neomacs--clipboard-cut)
   (setq interprogram-paste-function #'neomacs--clipboard-paste)
@@ -145,6 +152,8 @@ DISPLAY is the name of the display Emacs should connect to."
 
 ;; Animation configuration
 (declare-function neomacs-set-cursor-animation "neomacsterm.c" (enabled &optional speed))
+(declare-function neomacs-set-cursor-animation-mode "neomacsterm.c" (mode))
+(declare-function neomacs-set-frame-zoom "neomacsterm.c" (scale &optional x y duration notify-id))
 (declare-function neomacs-set-animation-config "neomacsterm.c"
                   (cursor-enabled cursor-speed cursor-style cursor-duration
                    crossfade-enabled crossfade-duration
@@ -174,6 +183,66 @@ Also suppresses the Emacs-side blink timer since the render thread handles it."
               (lambda () nil)
               '((name . neomacs-suppress-blink-timer))))
 
+;; Animated images follow the render thread's throttling of terminals,
+;; videos and web views: full rate on screen in a focused frame, reduced
+;; in an unfocused one, paused when not shown at all.
+
+(defcustom neomacs-throttle-image-animation t
+  "Non-nil means animated images nobody is looking at advance slower.
+Images such as GIFs in buffers not shown in any visible window stop
+until they are shown again, and those in frames without focus wait
+`neomacs-unfocused-image-frame-delay' seconds longer per frame."
+  :type 'boolean
+  :group 'frames)
+
+(defcustom neomacs-unfocused-image-frame-delay 0.1
+  "Extra seconds per animation frame of images in unfocused frames."
+  :type 'number
+  :group 'frames)
+
+(defconst neomacs--hidden-image-recheck 0.5
+  "Seconds between checks whether a paused animated image is shown again.")
+
+(defvar neomacs--delayed-images (make-hash-table :test 'eq :weakness 'key)
+  "Animated images whose next frame has already waited for an unfocused frame.")
+
+(defun neomacs--throttle-image-animation (orig image n count time-elapsed limit target-time)
+  "Around advice for `image-animate-timeout', calling ORIG.
+Show frame N of IMAGE at once when it is on screen in a focused frame,
+later when its frame lacks focus, and not at all while no visible window
+shows its buffer.  COUNT, TIME-ELAPSED, LIMIT and TARGET-TIME are passed on."
+  (let* ((buffer (plist-get (cdr image) :animate-buffer))
+         (windows (and neomacs-throttle-image-animation
+                       (buffer-live-p buffer)
+                       (get-buffer-window-list buffer nil 'visible)))
+         (delay (cond
+                 ((or (not neomacs-throttle-image-animation)
+                      (not (buffer-live-p buffer)))
+                  nil)
+                 ((null windows) neomacs--hidden-image-recheck)
+                 ((gethash image neomacs--delayed-images)
+                  (remhash image neomacs--delayed-images)
+                  nil)
+                 ((let (focused)
+                    (dolist (window windows focused)
+                      (when (frame-focus-state (window-frame window))
+                        (setq focused t))))
+                  nil)
+                 (t neomacs-unfocused-image-frame-delay))))
+    (cond
+     ((null delay)
+      (funcall orig image n count time-elapsed limit target-time))
+     ((null windows)
+      ;; Check again later without advancing, through the advice
+      (run-with-timer delay nil #'image-animate-timeout
+                      image n count time-elapsed limit (+ (float-time) delay)))
+     (t
+      ;; Wait once, through the advice so `image-animate-timer' still
+      ;; finds the timer, then advance on the next call
+      (puthash image t neomacs--delayed-images)
+      (run-with-timer delay nil #'image-animate-timeout
+                      image n count time-elapsed limit (+ (float-time) delay))))))
+
 ;; Animation setup
 (defun neomacs--setup-animations ()
   "Set up render-thread animations (smooth cursor, crossfade, scroll slide).
@@ -208,6 +277,9 @@ Scroll effects (scroll-effect parameter, symbol or integer):
   `crt-scanlines'        - retro scanline overlay
   `depth-of-field'       - center sharp, edges dim
   `typewriter-reveal'    - lines appear left-to-right
+  `slide-left'           - content slides out to the left
+  `slide-right'          - content slides out to the right
+  `push'                 - new content covers the old from the right
 
 Scroll easing (scroll-easing parameter, symbol or integer):
   `ease-out-quad'        - standard deceleration (default)
@@ -389,6 +461,48 @@ _FRAME is ignored; the menu opens on the selected frame."
   :init-value t
   (neomacs-set-scroll-indicators neomacs-scroll-indicator-mode))
 
+;;; Typewriter scrolling
+
+(declare-function neomacs-scroll-hint "neomacsterm.c" (pixels))
+
+(defcustom neomacs-typewriter-position 0.5
+  "Where `neomacs-typewriter-mode' keeps the cursor line.
+A fraction of the window's height: 0.5 centers the cursor line,
+0.33 keeps it a third of the way down."
+  :type 'number
+  :group 'frames)
+
+(defvar-local neomacs-typewriter--line nil
+  "Line number of point after the previous command.")
+
+(defun neomacs-typewriter--recenter ()
+  "Scroll the cursor line toward `neomacs-typewriter-position'.
+The scroll animation slides the text by the lines it moves."
+  (let ((line (line-number-at-pos)))
+    (unless (or (eql line neomacs-typewriter--line)
+                (minibufferp)
+                (not (eq (current-buffer) (window-buffer))))
+      (setq neomacs-typewriter--line line)
+      (let* ((height (window-body-height))
+             (target (floor (* (1- height)
+                               (min 1.0 (max 0.0 neomacs-typewriter-position)))))
+             (posn (posn-at-point))
+             (row (and posn (cdr (posn-col-row posn)))))
+        (when (and row (/= row target) (fboundp 'neomacs-scroll-hint))
+          (neomacs-scroll-hint (* (- row target) (default-line-height))))
+        (recenter target)))))
+
+(define-minor-mode neomacs-typewriter-mode
+  "Keep the cursor line at a fixed screen position, easing the text to it.
+When the cursor moves to another line the window scrolls so that the
+line sits at `neomacs-typewriter-position', and the scroll animation
+slides the text there."
+  :global t
+  :group 'frames
+  (if neomacs-typewriter-mode
+      (add-hook 'post-command-hook #'neomacs-typewriter--recenter)
+    (remove-hook 'post-command-hook #'neomacs-typewriter--recenter)))
+
 ;;; Desktop notifications
 
 (defun neomacs-notify (title body &optional urgency)
@@ -514,6 +628,35 @@ Set to nil to disable.  Colors are strings like \"#1a1a2e\"."
                (neomacs-set-background-gradient (car val) (cdr val))
              (neomacs-set-background-gradient nil nil)))))
 
+;;; Face background styles
+
+(declare-function neomacs-set-face-background-style "neomacsterm.c"
+  (face style))
+
+(defcustom neomacs-face-background-styles nil
+  "Gradient or image backgrounds for faces.
+An alist of (FACE . STYLE), where STYLE is (gradient FROM TO ANGLE)
+or (image ID); see `neomacs-set-face-background-style'.  Useful for
+`header-line' or `org-block'."
+  :type '(alist :key-type face
+                :value-type (choice (list :tag "Gradient"
+                                          (const gradient)
+                                          (color :tag "From")
+                                          (color :tag "To")
+                                          (number :tag "Angle"))
+                                    (list :tag "Image"
+                                          (const image)
+                                          (integer :tag "Image id"))))
+  :group 'frames
+  :set (lambda (sym val)
+         (when (fboundp 'neomacs-set-face-background-style)
+           (dolist (entry (and (boundp sym) (default-value sym)))
+             (unless (assq (car entry) val)
+               (neomacs-set-face-background-style (car entry) nil)))
+           (dolist (entry val)
+             (neomacs-set-face-background-style (car entry) (cdr entry))))
+         (set-default sym val)))
+
 ;;; Scroll bar appearance
 
 (declare-function neomacs-set-scroll-bar-config "neomacsterm.c"
@@ -721,6 +864,9 @@ Non-nil draws a dark overlay on non-selected windows."
             val
             (if (boundp 'neomacs-inactive-dim-opacity)
                 neomacs-inactive-dim-opacity
+              nil)
+            (if (boundp 'neomacs-inactive-dim-saturation)
+                neomacs-inactive-dim-saturation
               nil)))))
 
 (defcustom neomacs-inactive-dim-opacity 0.15
@@ -733,7 +879,23 @@ A number between 0.0 (no dimming) and 1.0 (fully black)."
          (when (and (fboundp 'neomacs-set-inactive-dim)
                     (boundp 'neomacs-inactive-dim)
                     neomacs-inactive-dim)
-           (neomacs-set-inactive-dim t val))))
+           (neomacs-set-inactive-dim
+            t val
+            (if (boundp 'neomacs-inactive-dim-saturation)
+                neomacs-inactive-dim-saturation
+              nil)))))
+
+(defcustom neomacs-inactive-dim-saturation 1.0
+  "Saturation kept by inactive windows when dimming is enabled.
+A number between 0.0 (gray) and 1.0 (colors unchanged)."
+  :type '(number :tag "Saturation")
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (and (fboundp 'neomacs-set-inactive-dim)
+                    (boundp 'neomacs-inactive-dim)
+                    neomacs-inactive-dim)
+           (neomacs-set-inactive-dim t neomacs-inactive-dim-opacity val))))
 
 (defcustom neomacs-mode-line-separator nil
   "Style for mode-line separator decoration.
@@ -904,22 +1066,167 @@ non-focused paragraphs."
 ;;; Minimap
 
 (declare-function neomacs-set-minimap "neomacsterm.c"
-  (&optional enabled width))
+  (&optional enabled width line-height))
+(declare-function neomacs-minimap-update-lines "neomacsterm.c"
+  (buffer first removed lines &optional pos-delta))
+(declare-function neomacs-minimap-forget "neomacsterm.c" (buffer))
+(declare-function jit-lock-register "jit-lock" (fun &optional contextual))
+(declare-function jit-lock-unregister "jit-lock" (fun))
+
+(defcustom neomacs-minimap-max-lines 50000
+  "Largest buffer, in lines, summarized for the minimap."
+  :type 'integer
+  :group 'frames)
+
+(defvar neomacs-minimap--buffers nil
+  "Buffers whose line summaries are kept up to date for the minimap.")
+
+(defvar neomacs-minimap--colors (make-hash-table :test #'equal)
+  "Cache mapping color names to 0xRRGGBB integers.")
+
+(defvar-local neomacs-minimap--pending nil
+  "Cons of the first line and line count of the text about to change.")
+
+(defun neomacs-minimap--color (pos)
+  "Return the foreground at POS as an integer 0xRRGGBB."
+  (let* ((face (get-char-property pos 'face))
+         (face (if (consp face) (car face) face))
+         (color (or (face-foreground (if (facep face) face 'default)
+                                     nil 'default)
+                    "white")))
+    (or (gethash color neomacs-minimap--colors)
+        (puthash color
+                 (let ((v (color-values color)))
+                   (if v
+                       (logior (ash (ash (nth 0 v) -8) 16)
+                               (ash (ash (nth 1 v) -8) 8)
+                               (ash (nth 2 v) -8))
+                     #xffffff))
+                 neomacs-minimap--colors))))
+
+(defun neomacs-minimap--summaries (beg end)
+  "Return a vector of minimap summaries for the lines from BEG to END."
+  (save-excursion
+    (save-restriction
+      (widen)
+      (goto-char beg)
+      (forward-line 0)
+      (let (lines)
+        (while (let* ((start (point))
+                      (indent (progn (skip-chars-forward " \t")
+                                     (current-column)))
+                      (text (point)))
+                 (end-of-line)
+                 (push (vector start indent
+                               (max 0 (- (current-column) indent))
+                               (if (< text (point))
+                                   (neomacs-minimap--color text)
+                                 0))
+                       lines)
+                 (and (< (point) end) (not (eobp))
+                      (progn (forward-char 1) t))))
+        (vconcat (nreverse lines))))))
+
+(defun neomacs-minimap--line-index (pos)
+  "Return the zero-based line number of POS in the whole buffer."
+  (1- (line-number-at-pos pos t)))
+
+(defun neomacs-minimap--before-change (beg end)
+  "Remember the lines between BEG and END before they change."
+  (let ((first (neomacs-minimap--line-index beg)))
+    (setq neomacs-minimap--pending
+          (cons first (- (1+ (neomacs-minimap--line-index end)) first)))))
+
+(defun neomacs-minimap--after-change (beg end old-len)
+  "Resend the minimap lines from BEG to END replacing OLD-LEN characters."
+  (when neomacs-minimap--pending
+    (neomacs-minimap-update-lines
+     (current-buffer)
+     (car neomacs-minimap--pending) (cdr neomacs-minimap--pending)
+     (neomacs-minimap--summaries beg end)
+     (- (- end beg) old-len))
+    (setq neomacs-minimap--pending nil)))
+
+(defun neomacs-minimap--fontified (beg end)
+  "Resend the minimap lines from BEG to END after fontification."
+  (let ((first (neomacs-minimap--line-index beg))
+        (lines (neomacs-minimap--summaries beg end)))
+    (neomacs-minimap-update-lines (current-buffer) first (length lines)
+                                  lines))
+  nil)
+
+(defun neomacs-minimap--forget-buffer ()
+  "Stop tracking the current buffer for the minimap."
+  (remove-hook 'before-change-functions #'neomacs-minimap--before-change t)
+  (remove-hook 'after-change-functions #'neomacs-minimap--after-change t)
+  (remove-hook 'kill-buffer-hook #'neomacs-minimap--forget-buffer t)
+  (when (bound-and-true-p jit-lock-mode)
+    (jit-lock-unregister #'neomacs-minimap--fontified))
+  (setq neomacs-minimap--buffers
+        (delq (current-buffer) neomacs-minimap--buffers))
+  (neomacs-minimap-forget (current-buffer)))
+
+(defun neomacs-minimap--track-buffer (buffer)
+  "Send the line summaries of BUFFER and keep them up to date."
+  (with-current-buffer buffer
+    (unless (or (memq buffer neomacs-minimap--buffers)
+                (minibufferp)
+                (> (line-number-at-pos (point-max) t)
+                   neomacs-minimap-max-lines))
+      (push buffer neomacs-minimap--buffers)
+      (neomacs-minimap-forget buffer)
+      (neomacs-minimap-update-lines
+       buffer 0 0 (neomacs-minimap--summaries 1 (1+ (buffer-size))))
+      (add-hook 'before-change-functions #'neomacs-minimap--before-change nil t)
+      (add-hook 'after-change-functions #'neomacs-minimap--after-change nil t)
+      (add-hook 'kill-buffer-hook #'neomacs-minimap--forget-buffer nil t)
+      (when (bound-and-true-p font-lock-mode)
+        (jit-lock-register #'neomacs-minimap--fontified)))))
+
+(defun neomacs-minimap--track-windows (&optional frame)
+  "Track the buffers shown in the windows of FRAME."
+  (dolist (window (window-list frame 'nomini))
+    (neomacs-minimap--track-buffer (window-buffer window))))
+
+(defun neomacs-minimap--track (on)
+  "Start tracking displayed buffers if ON, else forget all of them."
+  (if on
+      (progn
+        (add-hook 'window-buffer-change-functions
+                  #'neomacs-minimap--track-windows)
+        (dolist (frame (frame-list))
+          (neomacs-minimap--track-windows frame)))
+    (remove-hook 'window-buffer-change-functions
+                 #'neomacs-minimap--track-windows)
+    (dolist (buffer neomacs-minimap--buffers)
+      (when (buffer-live-p buffer)
+        (with-current-buffer buffer
+          (neomacs-minimap--forget-buffer))))
+    (setq neomacs-minimap--buffers nil)))
+
+(defun neomacs--apply-minimap ()
+  "Send the minimap settings to the display engine."
+  (when (fboundp 'neomacs-set-minimap)
+    (neomacs-set-minimap
+     (and (boundp 'neomacs-minimap) neomacs-minimap)
+     (if (boundp 'neomacs-minimap-width) neomacs-minimap-width nil)
+     (if (boundp 'neomacs-minimap-line-height)
+         neomacs-minimap-line-height
+       nil)))
+  (when (fboundp 'neomacs-minimap-update-lines)
+    (neomacs-minimap--track (and (boundp 'neomacs-minimap) neomacs-minimap))))
 
 (defcustom neomacs-minimap nil
   "Enable minimap code overview column on the right side of windows.
-Non-nil renders a zoomed-out view of visible code using tiny colored
-blocks representing syntax-highlighted characters."
+Non-nil renders a zoomed-out view of the whole buffer, one colored
+block per line, with a viewport indicator that can be dragged with
+the mouse to scroll.  Buffers longer than `neomacs-minimap-max-lines'
+show only their visible text."
   :type 'boolean
   :group 'frames
   :set (lambda (sym val)
          (set-default sym val)
-         (when (fboundp 'neomacs-set-minimap)
-           (neomacs-set-minimap
-            val
-            (if (boundp 'neomacs-minimap-width)
-                neomacs-minimap-width
-              nil)))))
+         (neomacs--apply-minimap)))
 
 (defcustom neomacs-minimap-width 80
   "Width of the minimap column in pixels."
@@ -927,10 +1234,17 @@ blocks representing syntax-highlighted characters."
   :group 'frames
   :set (lambda (sym val)
          (set-default sym val)
-         (when (and (fboundp 'neomacs-set-minimap)
-                    (boundp 'neomacs-minimap)
-                    neomacs-minimap)
-           (neomacs-set-minimap t val))))
+         (when (and (boundp 'neomacs-minimap) neomacs-minimap)
+           (neomacs--apply-minimap))))
+
+(defcustom neomacs-minimap-line-height 2
+  "Height in pixels of one buffer line in the minimap."
+  :type '(integer :tag "Line height")
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (and (boundp 'neomacs-minimap) neomacs-minimap)
+           (neomacs--apply-minimap))))
 
 ;;; Typing ripple
 
@@ -984,6 +1298,46 @@ whenever it moves, providing visual feedback during typing."
               nil)
             val))))
 
+;;; Backdrop blur
+
+(declare-function neomacs-set-backdrop-blur "neomacsterm.c"
+  (&optional enabled radius passes))
+
+(defun neomacs--apply-backdrop-blur ()
+  "Send the current backdrop blur settings to the display."
+  (when (fboundp 'neomacs-set-backdrop-blur)
+    (neomacs-set-backdrop-blur
+     (and (boundp 'neomacs-backdrop-blur) neomacs-backdrop-blur)
+     (and (boundp 'neomacs-backdrop-blur-radius) neomacs-backdrop-blur-radius)
+     (and (boundp 'neomacs-backdrop-blur-passes) neomacs-backdrop-blur-passes))))
+
+(defcustom neomacs-backdrop-blur nil
+  "Blur the content behind translucent floating layers.
+Non-nil blurs what shows through floating terminals and other
+floating content whose background is not fully opaque."
+  :type 'boolean
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-backdrop-blur)))
+
+(defcustom neomacs-backdrop-blur-radius 12
+  "Radius of the backdrop blur in pixels."
+  :type '(integer :tag "Radius")
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-backdrop-blur)))
+
+(defcustom neomacs-backdrop-blur-passes 2
+  "Number of backdrop blur iterations, from 1 to 3.
+More passes give a smoother blur at a higher GPU cost."
+  :type '(integer :tag "Passes")
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-backdrop-blur)))
+
 ;;; Search highlight pulse
 
 (declare-function neomacs-set-search-pulse "neomacsterm.c"
@@ -1427,6 +1781,34 @@ on mode-line areas to simulate a blurred glass appearance."
                 neomacs-frosted-glass-opacity nil)
             val))))
 
+;; --- Merged selection runs ---
+(declare-function neomacs-set-selection-runs "neomacsterm.c"
+  (&optional enabled face-id radius))
+
+(defcustom neomacs-selection-runs nil
+  "Draw the region highlight as merged runs with rounded ends.
+Non-nil merges the highlighted characters of each line into a single
+rectangle; corners where wrapped lines meet stay square."
+  :type 'boolean
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (fboundp 'neomacs-set-selection-runs)
+           (neomacs-set-selection-runs val nil
+            (if (boundp 'neomacs-selection-runs-radius)
+                neomacs-selection-runs-radius nil)))))
+
+(defcustom neomacs-selection-runs-radius 4
+  "Corner radius in pixels for merged selection runs."
+  :type '(integer :tag "Radius")
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (and (fboundp 'neomacs-set-selection-runs)
+                    (boundp 'neomacs-selection-runs)
+                    neomacs-selection-runs)
+           (neomacs-set-selection-runs t nil val))))
+
 ;; --- Selection region glow ---
 (declare-function neomacs-set-region-glow "neomacsterm.c"
   (&optional enabled face-id radius opacity))
@@ -2004,6 +2386,66 @@ during frame resize that eases to zero, creating a smooth transition."
                 neomacs-resize-padding-duration nil)
             val))))
 
+;; --- Text gamma correction ---
+
+(declare-function neomacs-set-text-gamma "neomacsterm.c"
+  (&optional enabled gamma contrast))
+
+(defcustom neomacs-text-gamma nil
+  "Coverage gamma applied to antialiased edges of dark text, or nil.
+Text is blended in linear light, which makes dark text on light
+backgrounds look thin and fringy; values above 1.0 thicken it, 1.7
+being a good start.  Light text is unaffected.  nil, the default,
+leaves text uncorrected."
+  :type '(choice (const :tag "Off" nil) (number :tag "Gamma"))
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (fboundp 'neomacs-set-text-gamma)
+           (neomacs-set-text-gamma val val
+            (if (boundp 'neomacs-text-contrast)
+                neomacs-text-contrast nil)))))
+
+(defcustom neomacs-text-contrast 0.0
+  "Extra coverage boost for text edges, between 0.0 and 1.0."
+  :type '(number :tag "Contrast")
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (and (fboundp 'neomacs-set-text-gamma)
+                    (boundp 'neomacs-text-gamma)
+                    neomacs-text-gamma)
+           (neomacs-set-text-gamma t neomacs-text-gamma val))))
+
+;; --- Cursor text inversion ---
+
+(declare-function neomacs-set-cursor-inversion "neomacsterm.c"
+  (&optional enabled complement-color))
+
+(defcustom neomacs-cursor-inversion t
+  "Draw the character under a box cursor in an inverse color.
+This also applies while the cursor animates between positions."
+  :type 'boolean
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (fboundp 'neomacs-set-cursor-inversion)
+           (neomacs-set-cursor-inversion val
+            (if (boundp 'neomacs-cursor-complement-color)
+                neomacs-cursor-complement-color nil)))))
+
+(defcustom neomacs-cursor-complement-color nil
+  "Text color under a box cursor as hex RGB string.
+nil derives it from the face of the character under the cursor."
+  :type '(choice (const :tag "From face" nil)
+                 (string :tag "Color (#RRGGBB)"))
+  :group 'frames
+  :set (lambda (sym val)
+         (set-default sym val)
+         (when (and (fboundp 'neomacs-set-cursor-inversion)
+                    (boundp 'neomacs-cursor-inversion))
+           (neomacs-set-cursor-inversion neomacs-cursor-inversion val))))
+
 ;; --- Cursor error pulse ---
 (declare-function neomacs-set-cursor-error-pulse "neomacsterm.c"
   (&optional enabled color duration-ms))
@@ -9918,6 +10360,248 @@ Non-nil renders animated flowing color bands at the top of the frame."
                 neomacs-cursor-pendulum-damping nil)
             val))))
 
+;;; Fallback glyph fitting
+
+(declare-function neomacs-set-fallback-glyph-metrics "neomacsterm.c"
+  (face enabled &optional scale baseline-offset))
+
+(defun neomacs--apply-fallback-glyph-metrics ()
+  "Send the fallback glyph settings to the display engine."
+  (when (fboundp 'neomacs-set-fallback-glyph-metrics)
+    (neomacs-set-fallback-glyph-metrics
+     nil
+     (and (boundp 'neomacs-fallback-glyph-fit) neomacs-fallback-glyph-fit)
+     (and (boundp 'neomacs-fallback-glyph-scale) neomacs-fallback-glyph-scale)
+     (and (boundp 'neomacs-fallback-glyph-baseline-offset)
+          neomacs-fallback-glyph-baseline-offset))
+    (when (boundp 'neomacs-fallback-glyph-face-metrics)
+      (dolist (entry neomacs-fallback-glyph-face-metrics)
+        (when (facep (car entry))
+          (neomacs-set-fallback-glyph-metrics
+           (car entry) t (nth 1 entry) (nth 2 entry)))))))
+
+(defcustom neomacs-fallback-glyph-fit t
+  "Fit emoji and symbols from fallback fonts to the text font.
+Non-nil resizes color emoji and symbols that the text font lacks to
+the height of the text font and puts them on its baseline, so they do
+not stretch the line or float above it."
+  :type 'boolean
+  :group 'neomacs
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-fallback-glyph-metrics)))
+
+(defcustom neomacs-fallback-glyph-scale 1.0
+  "Size of fitted fallback glyphs relative to the text font height."
+  :type 'number
+  :group 'neomacs
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-fallback-glyph-metrics)))
+
+(defcustom neomacs-fallback-glyph-baseline-offset 0
+  "Pixels to raise fitted fallback glyphs above the text baseline."
+  :type 'number
+  :group 'neomacs
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-fallback-glyph-metrics)))
+
+(defcustom neomacs-fallback-glyph-face-metrics nil
+  "Per-face fallback glyph fitting, overriding the global settings.
+Each entry is (FACE SCALE BASELINE-OFFSET), where SCALE and
+BASELINE-OFFSET are as in `neomacs-fallback-glyph-scale' and
+`neomacs-fallback-glyph-baseline-offset'."
+  :type '(repeat (list (face :tag "Face")
+                       (number :tag "Scale")
+                       (number :tag "Baseline offset")))
+  :group 'neomacs
+  :set (lambda (sym val)
+         (set-default sym val)
+         (neomacs--apply-fallback-glyph-metrics)))
+
+;;; Display engine events
+
+(defvar neomacs-display-event-functions nil
+  "Functions called with events from the display engine.
+Each function is called with three arguments KIND, ID and ARG, where
+KIND is one of these symbols:
+  `terminal-exited'            - terminal ID exited; ARG is nil
+  `terminal-title-changed'     - ARG is the new title of terminal ID
+  `terminal-bell'              - terminal ID rang its bell; ARG is nil
+  `tmux-pane-added'            - tmux client ARG gave a pane terminal ID
+  `tmux-pane-closed'           - terminal ID of a pane of tmux client ARG
+                                 no longer gets output
+  `tmux-exited'                - tmux client ID exited; ARG is nil
+  `video-ended'                - video ID played to the end; ARG is nil
+  `webkit-title-changed'       - ARG is the new title of WebKit view ID
+  `webkit-url-changed'         - ARG is the new URL of WebKit view ID
+  `webkit-progress-changed'    - ARG is the load progress of view ID, 0-100
+  `webkit-load-finished'       - WebKit view ID finished loading
+  `buffer-transition-finished' - the crossfade of window ID finished
+  `scroll-animation-finished'  - the scroll animation of window ID finished
+  `animation-completed'        - the animation started with notify id ID
+                                 ended; ARG is nil
+  `pip-closed'                 - the picture-in-picture player of video
+                                 ID was closed; ARG is nil
+  `audio-levels'               - new audio levels of video ID, see
+                                 `neomacs-video-audio-levels'; ARG is nil
+  `display-error'              - errors were reported; ID is the newest
+                                 error id, see `neomacs-display-errors'
+  `appearance-changed'         - the system switched between dark and
+                                 light; ARG is `dark' or `light', ID nil
+ID is nil for an animation of a window that no longer exists.")
+
+(declare-function neomacs-display-errors "neomacsterm.c" (&optional after))
+
+(defcustom neomacs-display-error-level 'error
+  "Minimum severity of display engine errors shown in the echo area.
+One of the symbols `error', `warning' or `info', or nil to show none.
+`neomacs-display-errors' lists every error the engine kept."
+  :type '(choice (const :tag "Errors" error)
+                 (const :tag "Warnings and errors" warning)
+                 (const :tag "Everything" info)
+                 (const :tag "Nothing" nil))
+  :group 'neomacs)
+
+(defvar neomacs--display-error-last-id 0
+  "Id of the newest display engine error already handled.")
+
+(defun neomacs--show-display-errors (kind _id _arg)
+  "Show new display engine errors in the echo area.
+Runs from `neomacs-display-event-functions' for `display-error' events
+and obeys `neomacs-display-error-level'."
+  (when (eq kind 'display-error)
+    (let ((shown (memq neomacs-display-error-level '(info warning error))))
+      (dolist (err (neomacs-display-errors neomacs--display-error-last-id))
+        (setq neomacs--display-error-last-id (plist-get err :id))
+        (when (memq (plist-get err :severity) shown)
+          (message "Display %s %s: %s"
+                   (plist-get err :kind)
+                   (plist-get err :severity)
+                   (plist-get err :message)))))))
+
+(add-hook 'neomacs-display-event-functions #'neomacs--show-display-errors)
+
+(defvar neomacs-system-appearance nil
+  "Appearance the system prefers, `dark' or `light'.
+Nil until the display engine has reported it.")
+
+(defvar neomacs-appearance-change-functions nil
+  "Functions called with `dark' or `light' when the system appearance changes.
+They can switch the Emacs theme and, with `neomacs-set-display-theme',
+the colors the display draws on its own.  Also called once with the
+appearance at startup, when the system reports one.")
+
+(defun neomacs--track-appearance (kind _id arg)
+  "Record the system appearance and run `neomacs-appearance-change-functions'.
+Runs from `neomacs-display-event-functions' for `appearance-changed'
+events."
+  (when (and (eq kind 'appearance-changed)
+             (not (eq arg neomacs-system-appearance)))
+    (setq neomacs-system-appearance arg)
+    (run-hook-with-args 'neomacs-appearance-change-functions arg)))
+
+(add-hook 'neomacs-display-event-functions #'neomacs--track-appearance)
+
+(declare-function neomacs-display-memory-report "neomacsterm.c" ())
+
+(defun neomacs-display-memory ()
+  "Show how much memory each subsystem of the display engine holds.
+Lists video memory (glyph atlas, images, videos, transition snapshots)
+and main memory (terminal grids with their scrollback, and the cell
+snapshots drawn from them), with the number of items each holds."
+  (interactive)
+  (let ((report (neomacs-display-memory-report)))
+    (unless report
+      (error "The display engine did not answer"))
+    (with-help-window "*Neomacs Display Memory*"
+      (dolist (gpu '(t nil))
+        (let ((total 0))
+          (princ (if gpu "Video memory\n" "\nMain memory\n"))
+          (dolist (entry report)
+            (when (eq (plist-get entry :gpu) gpu)
+              (setq total (+ total (plist-get entry :bytes)))
+              (princ (format "  %-22s %10s %8d\n"
+                             (plist-get entry :subsystem)
+                             (file-size-human-readable (plist-get entry :bytes))
+                             (plist-get entry :count)))))
+          (princ (format "  %-22s %10s\n" "total"
+                         (file-size-human-readable total))))))))
+
+;;; Display options
+
+(declare-function neomacs-display-options "neomacsterm.c" ())
+(declare-function neomacs-set-animation-option "neomacsterm.c" (option value))
+
+(defgroup neomacs-display nil
+  "Options of the Neomacs display engine.
+These can also be set in the display config file, see
+`neomacs-set-animation-option'."
+  :group 'neomacs
+  :prefix "neomacs-display-")
+
+(defun neomacs--display-option-type (option)
+  "Return the customization type of display OPTION.
+OPTION is an element of the list returned by `neomacs-display-options'."
+  (pcase (plist-get option :type)
+    ('boolean 'boolean)
+    ('integer 'integer)
+    ('float 'number)
+    ('choice `(choice ,@(mapcar (lambda (c) (list 'const c))
+                               (plist-get option :choices))))
+    ('color 'color)
+    ('color-list '(repeat color))
+    (_ 'string)))
+
+(defun neomacs--display-option-value (option string)
+  "Convert STRING, a value of display OPTION, to its Lisp form."
+  (pcase (plist-get option :type)
+    ('boolean (equal string "t"))
+    ((or 'integer 'float) (string-to-number string))
+    ('color-list (split-string string))
+    (_ string)))
+
+(defun neomacs--display-option-color (color)
+  "Return COLOR as a \"#rrggbb\" string."
+  (let ((v (and (not (string-prefix-p "#" color)) (color-values color))))
+    (if v
+        (apply #'format "#%02x%02x%02x" (mapcar (lambda (c) (ash c -8)) v))
+      color)))
+
+(defun neomacs--display-option-string (option value)
+  "Convert VALUE of display OPTION to the string the display engine takes."
+  (pcase (plist-get option :type)
+    ('boolean (if value "t" "nil"))
+    ((or 'integer 'float) (number-to-string value))
+    ('color (neomacs--display-option-color value))
+    ('color-list (mapconcat #'neomacs--display-option-color value " "))
+    (_ value)))
+
+(defun neomacs-define-display-options ()
+  "Define a user option for each option of the display engine.
+The display option NAME becomes `neomacs-display-NAME', with the type,
+default and documentation reported by `neomacs-display-options'.
+Customizing it sends the new value to the display engine."
+  (when (fboundp 'neomacs-display-options)
+    (dolist (option (neomacs-display-options))
+      (let ((name (plist-get option :name)))
+        (custom-declare-variable
+         (intern (concat "neomacs-display-" name))
+         (list 'quote (neomacs--display-option-value
+                       option (plist-get option :default)))
+         (plist-get option :doc)
+         :type (neomacs--display-option-type option)
+         :group 'neomacs-display
+         ;; Leave the engine's value alone until the option is customized,
+         ;; so the display config file keeps effect
+         :initialize #'custom-initialize-default
+         :set (lambda (sym val)
+                (unless (neomacs-set-animation-option
+                         name (neomacs--display-option-string option val))
+                  (error "Invalid value for `%s': %S" sym val))
+                (set-default sym val)))))))
+
 ;; Provide the feature
 (provide 'neomacs-win)
 (provide 'term/neomacs-win)
@@ -0,0 +1,235 @@
+//! Benchmarks of the rendering hot paths: `cargo bench --bench render`.
+//!
+//! Frames are drawn by a headless renderer into an offscreen texture and
+//! waited for, so the times include the GPU's share.  The GPU benchmarks
+//! need an adapter (a software one such as lavapipe will do) and are
+//! skipped without one; turning a terminal into glyphs runs on the CPU
+//! alone.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use alacritty_terminal::event::VoidListener;
+use alacritty_terminal::term::{Config, Term};
+use alacritty_terminal::vte::ansi;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use neomacs_display::backend::wgpu::{GlyphKey, TerminalRenderer, WgpuGlyphAtlas, WgpuRenderer};
+use neomacs_display::core::effect_preview::{sample_frame, CELL_HEIGHT, CELL_WIDTH};
+use neomacs_display::core::frame_glyphs::FrameGlyphBuffer;
+use neomacs_display::core::types::{AnimatedCursor, Color};
+use neomacs_display::terminal::{CellSize, TermGridSize, TerminalContent};
+use neomacs_display::terminal::colors::TerminalTheme;
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 800;
+const FONT_SIZE: f32 = 14.0;
+
+/// Characters rasterized into an empty atlas: code, prose and CJK text
+const GLYPHS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789\
+    (){}[]<>;:'\",.?!@#$%^&*-_=+/\\|`~αβγδεζηθλμπσφψω漢字仮名交じり文日本語中文한국어";
+
+struct Gpu {
+    renderer: WgpuRenderer,
+    atlas: WgpuGlyphAtlas,
+    view: wgpu::TextureView,
+    _target: wgpu::Texture,
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        let renderer = match WgpuRenderer::new(None, WIDTH, HEIGHT) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("skipping GPU benchmarks: {}", e);
+                return None;
+            }
+        };
+        let atlas = WgpuGlyphAtlas::new(renderer.device());
+        let (target, view) = renderer.create_offscreen_texture(WIDTH, HEIGHT);
+        Some(Self { renderer, atlas, view, _target: target })
+    }
+
+    /// Draw `frame` and wait for the GPU to finish it
+    fn draw(&mut self, frame: &FrameGlyphBuffer, cursor: Option<AnimatedCursor>) {
+        self.renderer.render_frame_glyphs(
+            &self.view, None, frame, &mut self.atlas, &HashMap::new(),
+            WIDTH, HEIGHT, true, cursor, (-1.0, -1.0), None,
+        );
+        self.renderer.device().poll(wgpu::Maintain::Wait);
+    }
+}
+
+fn logical_size() -> (f32, f32) {
+    (WIDTH as f32, HEIGHT as f32)
+}
+
+/// An Org buffer: headings, prose and two inline images
+fn org_frame(gpu: &mut Gpu) -> FrameGlyphBuffer {
+    let (width, height) = logical_size();
+    let mut frame = FrameGlyphBuffer::with_size(width, height);
+    frame.char_width = CELL_WIDTH;
+    frame.char_height = CELL_HEIGHT;
+    frame.set_font_size(FONT_SIZE);
+    let background = Color::rgb(0.98, 0.97, 0.94);
+    frame.background = background;
+    frame.add_background(0.0, 0.0, width, height, background);
+
+    // Two 400x160 images with a gradient, shown every few lines
+    for id in [1, 2] {
+        let data: Vec<u8> = (0..400 * 160)
+            .flat_map(|i| [(i % 400 * 255 / 400) as u8, (i / 400 * 255 / 160) as u8, 96 * id as u8, 255])
+            .collect();
+        gpu.renderer.upload_image_rgba(id, 400, 160, data);
+    }
+
+    let heading = Color::rgb(0.2, 0.3, 0.6);
+    let text = Color::rgb(0.15, 0.15, 0.15);
+    let rows = (height / CELL_HEIGHT) as usize;
+    let mut row = 0;
+    let mut section = 0;
+    while row < rows {
+        let (line, color, bold) = match row % 12 {
+            0 => (format!("* Section {} of the notes", section), heading, true),
+            1 => ("  :PROPERTIES: :CREATED: [2026-01-01 Thu] :END:".to_string(), text, false),
+            _ => ("  Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor \
+                   incididunt ut labore et dolore magna aliqua."
+                .to_string(), text, false),
+        };
+        frame.set_face(0, color, None, bold, false, 0, None, 0, None, 0, None);
+        let y = row as f32 * CELL_HEIGHT;
+        for (col, c) in line.chars().take((width / CELL_WIDTH) as usize).enumerate() {
+            frame.add_char(c, col as f32 * CELL_WIDTH, y, CELL_WIDTH, CELL_HEIGHT, 14.0, false);
+        }
+        row += 1;
+        if row % 12 == 4 && row + 9 < rows {
+            frame.add_image(1 + section % 2, 2.0 * CELL_WIDTH, row as f32 * CELL_HEIGHT, 400.0, 160.0);
+            row += 9;
+            section += 1;
+        }
+    }
+    frame
+}
+
+/// A terminal filling the frame with colored compiler-like output
+fn terminal_content() -> TerminalContent {
+    let cell = CellSize::default();
+    let (width, height) = logical_size();
+    let (cols, rows) = ((width / cell.width) as u16, (height / cell.height) as u16);
+    let mut term = Term::new(Config::default(), &TermGridSize::new(cols, rows), VoidListener);
+    let mut parser = ansi::Processor::<ansi::StdSyncHandler>::new();
+    for i in 0..rows as usize * 2 {
+        let line = format!(
+            "\x1b[1;32m   Compiling\x1b[0m crate-{} v0.{}.0 \x1b[2m(/src/crate-{})\x1b[0m \
+             \x1b[38;5;{}mwarning\x1b[0m: \x1b[4munused\x1b[24m variable `x{}` \x1b[7m{}\x1b[27m\r\n",
+            i, i % 10, i, 160 + i % 60, i, "█".repeat(i % 20),
+        );
+        parser.advance(&mut term, line.as_bytes());
+    }
+    TerminalContent::from_term(&term, &TerminalTheme::default())
+}
+
+fn terminal_renderer() -> TerminalRenderer {
+    TerminalRenderer { cell: CellSize::default(), face_id: 0, is_overlay: false, opacity: 1.0, clip: None }
+}
+
+fn terminal_frame(content: &TerminalContent) -> FrameGlyphBuffer {
+    let (width, height) = logical_size();
+    let mut frame = FrameGlyphBuffer::with_size(width, height);
+    frame.set_font_size(CellSize::default().font_size);
+    frame.add_background(0.0, 0.0, width, height, content.default_bg);
+    terminal_renderer().push(content, 0.0, 0.0, &mut frame.glyphs);
+    frame
+}
+
+fn bench_terminal_glyphs(c: &mut Criterion) {
+    let content = terminal_content();
+    let renderer = terminal_renderer();
+    c.bench_function("terminal/cells_to_glyphs", |b| {
+        let mut glyphs = Vec::new();
+        b.iter(|| {
+            glyphs.clear();
+            renderer.push(&content, 0.0, 0.0, &mut glyphs);
+            glyphs.len()
+        })
+    });
+}
+
+fn bench_glyph_atlas(c: &mut Criterion) {
+    let Some(mut gpu) = Gpu::new() else { return };
+    let keys: Vec<GlyphKey> = GLYPHS
+        .chars()
+        .map(|ch| GlyphKey { charcode: ch as u32, face_id: 0, font_size_bits: FONT_SIZE.to_bits() })
+        .collect();
+    let (device, queue) = (gpu.renderer.device().clone(), gpu.renderer.queue().clone());
+
+    c.bench_function("glyph_atlas/rasterize_and_insert", |b| {
+        // Empty the atlas before each pass, outside the timed part
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                gpu.atlas.clear();
+                let start = Instant::now();
+                for key in &keys {
+                    gpu.atlas.get_or_create(&device, &queue, key, None);
+                }
+                total += start.elapsed();
+            }
+            total
+        })
+    });
+    c.bench_function("glyph_atlas/cached_lookup", |b| {
+        b.iter(|| {
+            for key in &keys {
+                gpu.atlas.get_or_create(&device, &queue, key, None);
+            }
+        })
+    });
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let Some(mut gpu) = Gpu::new() else { return };
+    let (width, height) = logical_size();
+    let code = sample_frame(width, height, 0, (4, 2));
+    let org = org_frame(&mut gpu);
+    let terminal = terminal_frame(&terminal_content());
+
+    let mut group = c.benchmark_group("frame");
+    for (name, frame) in [("code_buffer", &code), ("org_with_images", &org), ("terminal", &terminal)] {
+        // The first frame fills the glyph atlas and row cache
+        gpu.draw(frame, None);
+        group.bench_function(name, |b| b.iter(|| gpu.draw(frame, None)));
+    }
+    group.finish();
+}
+
+fn bench_cursor_particles(c: &mut Criterion) {
+    let Some(mut gpu) = Gpu::new() else { return };
+    let (width, height) = logical_size();
+    let frame = sample_frame(width, height, 0, (4, 2));
+    gpu.renderer.effects.cursor_particles.enabled = true;
+    gpu.renderer.effects.cursor_particles.count = 30;
+    gpu.renderer.effects.cursor_particles.lifetime_ms = 2000;
+    gpu.draw(&frame, None);
+
+    // The cursor moves every frame, so each one emits particles while
+    // those of the last two seconds keep flying
+    let mut step = 0usize;
+    c.bench_function("cursor_particles/moving_cursor", |b| {
+        b.iter(|| {
+            step += 1;
+            let cursor = AnimatedCursor {
+                window_id: 1,
+                x: (step % 80) as f32 * CELL_WIDTH,
+                y: (step / 80 % 30) as f32 * CELL_HEIGHT,
+                width: CELL_WIDTH,
+                height: CELL_HEIGHT,
+                corners: None,
+            };
+            gpu.draw(&frame, Some(cursor));
+        })
+    });
+}
+
+criterion_group!(benches, bench_terminal_glyphs, bench_glyph_atlas, bench_frames, bench_cursor_particles);
+criterion_main!(benches);
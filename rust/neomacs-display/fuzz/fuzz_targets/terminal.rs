@@ -0,0 +1,10 @@
+//! Program output fed to a terminal: `cargo fuzz run terminal`.
+//! See `neomacs_display::terminal::fuzz`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    neomacs_display::terminal::fuzz::feed(data);
+});
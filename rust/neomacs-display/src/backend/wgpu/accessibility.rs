@@ -0,0 +1,216 @@
+//! Screen reader bridge for the winit window.
+//!
+//! Publishes an AccessKit tree (AT-SPI on Linux) built from each frame's
+//! `AccessibilitySnapshot`: one node per window body, minibuffer and mode
+//! line, with a text run per visual line and the cursor exposed as the
+//! text selection of the focused window.  Trees are only built while an
+//! assistive technology is listening.
+
+use accesskit::{
+    ActionHandler, ActionRequest, ActivationHandler, DeactivationHandler, Node, NodeId, Rect,
+    Role, TextPosition, TextSelection, Tree, TreeUpdate,
+};
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use crate::core::accessibility::{AccessibilitySnapshot, AccessibleRole};
+use crate::core::frame_glyphs::FrameGlyphBuffer;
+use crate::core::types::Rect as FrameRect;
+
+/// Node ID of the window root
+const ROOT_ID: NodeId = NodeId(0);
+
+/// Node IDs per region: the region itself plus one per visual line
+const REGION_ID_STRIDE: u64 = 1 << 16;
+
+fn region_id(index: usize) -> NodeId {
+    NodeId((index as u64 + 1) * REGION_ID_STRIDE)
+}
+
+fn line_id(region: usize, line: usize) -> NodeId {
+    NodeId((region as u64 + 1) * REGION_ID_STRIDE + line as u64 + 1)
+}
+
+fn to_bounds(rect: &FrameRect, scale: f64) -> Rect {
+    Rect::new(
+        rect.x as f64 * scale,
+        rect.y as f64 * scale,
+        (rect.x + rect.width) as f64 * scale,
+        (rect.y + rect.height) as f64 * scale,
+    )
+}
+
+/// Build a full tree update for a frame snapshot.
+///
+/// Bounds are converted from logical to physical window coordinates.
+pub fn build_tree_update(snapshot: &AccessibilitySnapshot, title: &str, scale: f64) -> TreeUpdate {
+    let mut nodes = Vec::new();
+    let mut focus = ROOT_ID;
+    let mut children = Vec::with_capacity(snapshot.regions.len());
+
+    for (ri, region) in snapshot.regions.iter().enumerate() {
+        let id = region_id(ri);
+        children.push(id);
+
+        let mut node = Node::new(match region.role {
+            AccessibleRole::TextArea => Role::MultilineTextInput,
+            AccessibleRole::Minibuffer => Role::TextInput,
+            AccessibleRole::ModeLine => Role::Label,
+        });
+        node.set_bounds(to_bounds(&region.bounds, scale));
+        if !region.name.is_empty() {
+            node.set_label(region.name.as_str());
+        }
+
+        if region.role == AccessibleRole::ModeLine {
+            node.set_value(region.text());
+            nodes.push((id, node));
+            continue;
+        }
+
+        let mut line_ids = Vec::with_capacity(region.lines.len());
+        for (li, line) in region.lines.iter().enumerate() {
+            let lid = line_id(ri, li);
+            line_ids.push(lid);
+            let mut run = Node::new(Role::TextRun);
+            run.set_value(line.text.as_str());
+            run.set_bounds(to_bounds(&line.bounds, scale));
+            let lengths: Vec<u8> = line.text.chars().map(|c| c.len_utf8() as u8).collect();
+            run.set_character_lengths(lengths);
+            nodes.push((lid, run));
+        }
+
+        if let Some(cursor) = region.cursor.filter(|c| c.line < line_ids.len()) {
+            let position = TextPosition {
+                node: line_ids[cursor.line],
+                character_index: cursor.column,
+            };
+            node.set_text_selection(TextSelection { anchor: position, focus: position });
+        }
+        node.set_children(line_ids);
+        if region.focused {
+            focus = id;
+        }
+        nodes.push((id, node));
+    }
+
+    let mut root = Node::new(Role::Window);
+    root.set_label(title);
+    root.set_children(children);
+    nodes.insert(0, (ROOT_ID, root));
+
+    let mut tree = Tree::new(ROOT_ID);
+    tree.toolkit_name = Some("neomacs".to_string());
+    tree.toolkit_version = Some(env!("CARGO_PKG_VERSION").to_string());
+
+    TreeUpdate { nodes, tree: Some(tree), focus }
+}
+
+/// Answers the first tree request with an empty window until a frame arrives
+struct InitialTree {
+    title: String,
+}
+
+impl ActivationHandler for InitialTree {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(build_tree_update(&AccessibilitySnapshot::default(), &self.title, 1.0))
+    }
+}
+
+/// Text is read-only from the accessibility side; actions are ignored
+struct NoActions;
+
+impl ActionHandler for NoActions {
+    fn do_action(&mut self, request: ActionRequest) {
+        log::debug!("Ignoring accessibility action {:?} on {:?}", request.action, request.target);
+    }
+}
+
+impl DeactivationHandler for NoActions {
+    fn deactivate_accessibility(&mut self) {
+        log::debug!("Accessibility client disconnected");
+    }
+}
+
+/// AccessKit adapter for the main window.
+pub struct AccessibilityBridge {
+    adapter: accesskit_winit::Adapter,
+    title: String,
+}
+
+impl AccessibilityBridge {
+    /// Attach to `window`.  Must be called before the window is first shown.
+    pub fn new(window: &Window, title: &str) -> Self {
+        let adapter = accesskit_winit::Adapter::with_direct_handlers(
+            window,
+            InitialTree { title: title.to_string() },
+            NoActions,
+            NoActions,
+        );
+        Self { adapter, title: title.to_string() }
+    }
+
+    /// Forward a window event; call before handling the event.
+    pub fn process_event(&mut self, window: &Window, event: &WindowEvent) {
+        self.adapter.process_event(window, event);
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    /// Publish the text of a new frame, if a screen reader is listening.
+    pub fn update(&mut self, frame: &FrameGlyphBuffer, scale: f64) {
+        let title = &self.title;
+        self.adapter.update_if_active(|| {
+            build_tree_update(&AccessibilitySnapshot::from_frame(frame), title, scale)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::accessibility::{AccessibleCursor, AccessibleLine, AccessibleRegion};
+
+    #[test]
+    fn test_tree_update_exposes_lines_and_cursor() {
+        let region = AccessibleRegion {
+            window_id: 1,
+            role: AccessibleRole::TextArea,
+            name: "notes.org".into(),
+            bounds: FrameRect::new(0.0, 0.0, 100.0, 32.0),
+            lines: vec![
+                AccessibleLine {
+                    text: "héllo".into(),
+                    bounds: FrameRect::new(0.0, 0.0, 40.0, 16.0),
+                    selected: Vec::new(),
+                },
+                AccessibleLine {
+                    text: "world".into(),
+                    bounds: FrameRect::new(0.0, 16.0, 40.0, 16.0),
+                    selected: Vec::new(),
+                },
+            ],
+            cursor: Some(AccessibleCursor { line: 1, column: 2 }),
+            focused: true,
+        };
+        let snapshot = AccessibilitySnapshot { regions: vec![region] };
+        let update = build_tree_update(&snapshot, "emacs", 2.0);
+
+        assert_eq!(update.focus, region_id(0));
+        assert_eq!(update.nodes.len(), 4);
+        let (_, root) = &update.nodes[0];
+        assert_eq!(root.children(), &[region_id(0)]);
+
+        let (_, text_area) = update.nodes.iter().find(|(id, _)| *id == region_id(0)).unwrap();
+        assert_eq!(text_area.role(), Role::MultilineTextInput);
+        let selection = text_area.text_selection().unwrap();
+        assert_eq!(selection.focus.node, line_id(0, 1));
+        assert_eq!(selection.focus.character_index, 2);
+
+        let (_, first) = update.nodes.iter().find(|(id, _)| *id == line_id(0, 0)).unwrap();
+        assert_eq!(first.character_lengths(), &[1, 2, 1, 1, 1]);
+        assert_eq!(first.bounds().unwrap().y1, 32.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! Shelf packing for glyph atlas pages.
+//!
+//! Each page is cut into horizontal shelves as glyphs arrive.  Freed glyph
+//! slots are kept per shelf and reused by later glyphs that fit, so a page
+//! only needs repacking once its shelves are riddled with holes.
+
+/// A horizontal strip of a page holding glyphs of similar height
+#[derive(Debug, Clone)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// X where the untouched tail of the shelf starts
+    cursor: u32,
+    /// Freed spans (x, width) left of `cursor`, sorted by x
+    free: Vec<(u32, u32)>,
+}
+
+/// Allocator for one square atlas page
+#[derive(Debug, Clone)]
+pub struct PagePacker {
+    size: u32,
+    shelves: Vec<Shelf>,
+    /// Y where the next shelf starts
+    next_y: u32,
+    /// Area covered by live allocations
+    used: u64,
+}
+
+impl PagePacker {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            shelves: Vec::new(),
+            next_y: 0,
+            used: 0,
+        }
+    }
+
+    /// Reserve a `w` x `h` rectangle, returning its top-left corner
+    pub fn allocate(&mut self, w: u32, h: u32) -> Option<(u32, u32)> {
+        if w == 0 || h == 0 || w > self.size || h > self.size {
+            return None;
+        }
+
+        // Tightest shelf that is tall enough without wasting more than
+        // half its height
+        let mut best: Option<(usize, Option<usize>)> = None;
+        let mut best_height = u32::MAX;
+        for (i, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < h || shelf.height > h + h / 2 + 2 || shelf.height >= best_height {
+                continue;
+            }
+            if let Some(j) = shelf.free.iter().position(|&(_, fw)| fw >= w) {
+                best = Some((i, Some(j)));
+                best_height = shelf.height;
+            } else if self.size - shelf.cursor >= w {
+                best = Some((i, None));
+                best_height = shelf.height;
+            }
+        }
+
+        let pos = match best {
+            Some((i, Some(j))) => {
+                let shelf = &mut self.shelves[i];
+                let (fx, fw) = shelf.free[j];
+                if fw == w {
+                    shelf.free.remove(j);
+                } else {
+                    shelf.free[j] = (fx + w, fw - w);
+                }
+                (fx, shelf.y)
+            }
+            Some((i, None)) => {
+                let shelf = &mut self.shelves[i];
+                shelf.cursor += w;
+                (shelf.cursor - w, shelf.y)
+            }
+            None => {
+                if self.size - self.next_y < h {
+                    return None;
+                }
+                let y = self.next_y;
+                self.shelves.push(Shelf { y, height: h, cursor: w, free: Vec::new() });
+                self.next_y += h;
+                (0, y)
+            }
+        };
+        self.used += w as u64 * h as u64;
+        Some(pos)
+    }
+
+    /// Return a rectangle handed out by `allocate`
+    pub fn free(&mut self, x: u32, y: u32, w: u32, h: u32) {
+        let Some(i) = self.shelves.iter().position(|s| s.y == y) else { return };
+        self.used = self.used.saturating_sub(w as u64 * h as u64);
+
+        let shelf = &mut self.shelves[i];
+        let at = shelf.free.partition_point(|&(fx, _)| fx < x);
+        shelf.free.insert(at, (x, w));
+        // Merge with the neighbouring spans
+        if at + 1 < shelf.free.len() && shelf.free[at].0 + shelf.free[at].1 == shelf.free[at + 1].0 {
+            shelf.free[at].1 += shelf.free[at + 1].1;
+            shelf.free.remove(at + 1);
+        }
+        if at > 0 && shelf.free[at - 1].0 + shelf.free[at - 1].1 == shelf.free[at].0 {
+            shelf.free[at - 1].1 += shelf.free[at].1;
+            shelf.free.remove(at);
+        }
+        // A span reaching the cursor goes back to the untouched tail
+        if let Some(&(fx, fw)) = shelf.free.last() {
+            if fx + fw == shelf.cursor {
+                shelf.cursor = fx;
+                shelf.free.pop();
+            }
+        }
+
+        // Drop empty shelves at the bottom of the page
+        while self.shelves.last().is_some_and(|s| s.cursor == 0) {
+            let shelf = self.shelves.pop().expect("checked above");
+            self.next_y = shelf.y;
+        }
+    }
+
+    /// Forget every allocation
+    pub fn reset(&mut self) {
+        self.shelves.clear();
+        self.next_y = 0;
+        self.used = 0;
+    }
+
+    /// Area not covered by live allocations
+    pub fn free_area(&self) -> u64 {
+        (self.size as u64 * self.size as u64).saturating_sub(self.used)
+    }
+
+    /// Share of the page lost to holes: area claimed by shelves but not
+    /// covered by live allocations, over the page area
+    pub fn fragmentation(&self) -> f32 {
+        let claimed: u64 = self
+            .shelves
+            .iter()
+            .map(|s| s.cursor as u64 * s.height as u64)
+            .sum();
+        claimed.saturating_sub(self.used) as f32 / (self.size as f32 * self.size as f32)
+    }
+
+    /// True if nothing is allocated
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shelves_reuse_freed_slots() {
+        let mut p = PagePacker::new(64);
+        let a = p.allocate(10, 12).unwrap();
+        let b = p.allocate(10, 12).unwrap();
+        assert_eq!((a, b), ((0, 0), (10, 0)));
+        // Much shorter glyphs get their own shelf
+        assert_eq!(p.allocate(10, 4), Some((0, 12)));
+
+        p.free(a.0, a.1, 10, 12);
+        assert_eq!(p.allocate(8, 11), Some((0, 0)));
+        assert_eq!(p.allocate(2, 12), Some((8, 0)));
+        assert_eq!(p.allocate(64, 64), None);
+    }
+
+    #[test]
+    fn test_free_area_and_fragmentation() {
+        let mut p = PagePacker::new(32);
+        let slots: Vec<_> = (0..4).map(|_| p.allocate(8, 8).unwrap()).collect();
+        assert_eq!(p.free_area(), 32 * 32 - 4 * 64);
+        assert_eq!(p.fragmentation(), 0.0);
+
+        p.free(slots[1].0, slots[1].1, 8, 8);
+        assert!((p.fragmentation() - 64.0 / 1024.0).abs() < 1e-6);
+
+        for s in [slots[0], slots[2], slots[3]] {
+            p.free(s.0, s.1, 8, 8);
+        }
+        assert!(p.is_empty());
+        assert_eq!(p.fragmentation(), 0.0);
+        // The emptied page can take a full-height glyph again
+        assert_eq!(p.allocate(32, 32), Some((0, 0)));
+    }
+}
@@ -0,0 +1,143 @@
+//! One audio output shared by all videos
+//!
+//! Rather than an audio sink per video pipeline, each video's audio
+//! branch ends in an appsink whose samples are pushed into an appsrc of
+//! a single mixer pipeline:
+//!
+//! ```text
+//! appsrc (video 1) --\
+//! appsrc (video 2) ----> audiomixer -> audioconvert -> audioresample -> autoaudiosink
+//! appsrc (video n) --/
+//! ```
+//!
+//! The appsrcs are live and timestamp what they get on arrival, so a
+//! paused or parked video simply stops feeding the mixer.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+
+/// Format every video's audio is converted to before mixing
+pub const MIX_CAPS: &str = "audio/x-raw,format=F32LE,layout=interleaved,rate=48000,channels=2";
+
+/// About a second of audio per source, beyond which a stalled mixer
+/// drops rather than queues
+const SOURCE_MAX_BYTES: u64 = 48000 * 2 * 4;
+
+/// The mixer pipeline and its inputs
+pub struct AudioMixer {
+    pipeline: gst::Pipeline,
+    mixer: gst::Element,
+    /// Appsrc and mixer pad of each video with audio
+    sources: Mutex<HashMap<u32, (gst_app::AppSrc, gst::Pad)>>,
+}
+
+impl AudioMixer {
+    /// Build the mixer pipeline; None when the needed elements are missing,
+    /// in which case videos play through sinks of their own
+    pub fn new() -> Option<Self> {
+        let make = gst::ElementFactory::make;
+        let build = || -> Result<(gst::Pipeline, gst::Element), gst::glib::BoolError> {
+            let pipeline = gst::Pipeline::with_name("neomacs-audio-mixer");
+            let mixer = make("audiomixer").name("mix").build()?;
+            let convert = make("audioconvert").build()?;
+            let resample = make("audioresample").build()?;
+            let sink = make("autoaudiosink").build()?;
+            let elements = [&mixer, &convert, &resample, &sink];
+            pipeline.add_many(elements)?;
+            gst::Element::link_many(elements)?;
+            Ok((pipeline, mixer))
+        };
+        match build() {
+            Ok((pipeline, mixer)) => Some(Self { pipeline, mixer, sources: Mutex::new(HashMap::new()) }),
+            Err(e) => {
+                log::info!("No shared audio mixer ({}), videos get their own audio sinks", e);
+                None
+            }
+        }
+    }
+
+    /// Appsink to end the audio branch of video `id` with; what reaches
+    /// it is mixed into the shared output
+    pub fn add_source(&self, id: u32) -> Result<gst_app::AppSink, gst::glib::BoolError> {
+        let caps: gst::Caps = MIX_CAPS.parse().map_err(|_| gst::glib::bool_error!("bad mixer caps"))?;
+        let appsrc = gst_app::AppSrc::builder()
+            .name(format!("video-{}", id))
+            .caps(&caps)
+            .format(gst::Format::Time)
+            .is_live(true)
+            .do_timestamp(true)
+            .max_bytes(SOURCE_MAX_BYTES)
+            .build();
+        self.pipeline.add(&appsrc)?;
+        let mixer_pad = self
+            .mixer
+            .request_pad_simple("sink_%u")
+            .ok_or_else(|| gst::glib::bool_error!("audiomixer gave no pad"))?;
+        let src_pad = appsrc
+            .static_pad("src")
+            .ok_or_else(|| gst::glib::bool_error!("appsrc has no src pad"))?;
+        src_pad
+            .link(&mixer_pad)
+            .map_err(|e| gst::glib::bool_error!("cannot link to mixer: {:?}", e))?;
+        appsrc.sync_state_with_parent()?;
+        if self.pipeline.current_state() != gst::State::Playing {
+            self.pipeline
+                .set_state(gst::State::Playing)
+                .map_err(|e| gst::glib::bool_error!("cannot start mixer: {}", e))?;
+        }
+
+        // Samples are copied across as they come, at the video's pace
+        let target = appsrc.clone();
+        let appsink = gst_app::AppSink::builder()
+            .caps(&caps)
+            .sync(true)
+            .callbacks(
+                gst_app::AppSinkCallbacks::builder()
+                    .new_sample(move |sink| {
+                        let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                        if let Some(mut buffer) = sample.buffer_owned() {
+                            // The appsrc stamps it with the mixer's clock
+                            let buffer_mut = buffer.make_mut();
+                            buffer_mut.set_pts(gst::ClockTime::NONE);
+                            buffer_mut.set_dts(gst::ClockTime::NONE);
+                            let _ = target.push_buffer(buffer);
+                        }
+                        Ok(gst::FlowSuccess::Ok)
+                    })
+                    .build(),
+            )
+            .build();
+
+        if let Some((old, pad)) = self.lock().insert(id, (appsrc, mixer_pad)) {
+            self.detach(&old, &pad);
+        }
+        Ok(appsink)
+    }
+
+    /// Stop mixing in the audio of video `id`
+    pub fn remove_source(&self, id: u32) {
+        if let Some((appsrc, pad)) = self.lock().remove(&id) {
+            self.detach(&appsrc, &pad);
+        }
+    }
+
+    fn detach(&self, appsrc: &gst_app::AppSrc, pad: &gst::Pad) {
+        let _ = appsrc.set_state(gst::State::Null);
+        let _ = self.pipeline.remove(appsrc);
+        self.mixer.release_request_pad(pad);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, (gst_app::AppSrc, gst::Pad)>> {
+        self.sources.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for AudioMixer {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
@@ -13,6 +13,7 @@ use winit::keyboard::{Key, NamedKey};
 use winit::platform::pump_events::EventLoopExtPumpEvents;
 use winit::window::{Window, WindowId};
 
+use super::device_loss::DeviceLossWatch;
 use super::events::*;
 use super::glyph_atlas::WgpuGlyphAtlas;
 
@@ -114,6 +115,8 @@ pub struct WinitBackend {
     glyph_atlas: Option<WgpuGlyphAtlas>,
     /// Adapter info for GPU device identification.
     adapter_info: Option<wgpu::AdapterInfo>,
+    /// Set when the device is lost and has to be recreated.
+    device_loss: DeviceLossWatch,
 }
 
 impl WinitBackend {
@@ -145,6 +148,7 @@ impl WinitBackend {
             wgpu_initialized: false,
             glyph_atlas: None,
             adapter_info: None,
+            device_loss: DeviceLossWatch::default(),
         }
     }
 
@@ -187,6 +191,7 @@ impl WinitBackend {
         ))
         .map_err(|e| DisplayError::InitFailed(format!("Failed to create device: {}", e)))?;
 
+        self.device_loss = DeviceLossWatch::new(&device);
         let device = Arc::new(device);
         let queue = Arc::new(queue);
 
@@ -352,6 +357,7 @@ impl WinitBackend {
         ))
         .map_err(|e| DisplayError::InitFailed(format!("Failed to create device: {}", e)))?;
 
+        self.device_loss = DeviceLossWatch::new(&device);
         let device = Arc::new(device);
         let queue = Arc::new(queue);
 
@@ -435,8 +441,75 @@ impl WinitBackend {
         self.scene = scene;
     }
 
+    /// Recreate the device, renderer and glyph atlas if the device was
+    /// lost.  The instance and surfaces survive and are reconfigured for
+    /// the new device.
+    fn recover_lost_device(&mut self) -> DisplayResult<()> {
+        if !self.device_loss.is_lost() {
+            return Ok(());
+        }
+        let Some(instance) = &self.instance else {
+            return Ok(());
+        };
+        log::warn!("GPU device lost, recreating device");
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: crate::gpu_power_preference(),
+            compatible_surface: self.surface.as_ref(),
+            force_fallback_adapter: false,
+        }))
+        .ok_or_else(|| DisplayError::Render("Failed to find a GPU adapter after device loss".to_string()))?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Neomacs Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+                memory_hints: Default::default(),
+            },
+            None,
+        ))
+        .map_err(|e| DisplayError::Render(format!("Failed to recreate device: {}", e)))?;
+
+        self.device_loss = DeviceLossWatch::new(&device);
+        let device = Arc::new(device);
+        let queue = Arc::new(queue);
+
+        if let (Some(surface), Some(config)) = (&self.surface, &self.surface_config) {
+            surface.configure(&device, config);
+        }
+        for state in self.windows.values() {
+            state.surface.configure(&device, &state.config);
+        }
+
+        let mut renderer = WgpuRenderer::with_device(
+            device.clone(),
+            queue.clone(),
+            self.width,
+            self.height,
+            self.surface_format,
+            1.0,
+        );
+        if let Some(lost) = self.renderer.take() {
+            renderer.adopt_from(lost);
+        }
+        let mut glyph_atlas = WgpuGlyphAtlas::new(&device);
+        if let Some(lost) = self.glyph_atlas.take() {
+            glyph_atlas.adopt_settings(&lost);
+        }
+
+        self.adapter_info = Some(adapter.get_info());
+        self.renderer = Some(renderer);
+        self.glyph_atlas = Some(glyph_atlas);
+        self.device = Some(device);
+        self.queue = Some(queue);
+        Ok(())
+    }
+
     /// Perform the actual rendering.
     pub fn do_render(&mut self) -> DisplayResult<()> {
+        self.recover_lost_device()?;
+
         let surface = match &self.surface {
             Some(s) => s,
             None => return Ok(()),
@@ -686,6 +759,11 @@ impl WinitBackend {
     ) {
         log::debug!("end_frame_for_window: window_id={}, glyphs={}", window_id, frame_glyphs.glyphs.len());
 
+        if let Err(e) = self.recover_lost_device() {
+            log::error!("end_frame_for_window: {}", e);
+            return;
+        }
+
         let renderer = match &mut self.renderer {
             Some(r) => r,
             None => {
@@ -724,6 +802,7 @@ impl WinitBackend {
             log::debug!("end_frame_for_window: calling render_frame_glyphs");
             renderer.render_frame_glyphs(
                 &view,
+                None, // no backdrop blur in legacy path
                 frame_glyphs,
                 glyph_atlas,
                 faces,
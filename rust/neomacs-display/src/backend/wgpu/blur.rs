@@ -0,0 +1,408 @@
+//! Gaussian blur of screen regions with compute shaders.
+//!
+//! Anything drawn over the frame (floating terminals, popups) can ask for
+//! the content behind it to be blurred by adding a `BlurRegion` to the
+//! frame.  Before overlay content is drawn, the renderer copies each region
+//! out of the render target, blurs it with separable compute passes and
+//! draws the result back.  A region keeps its blurred texture and reuses it
+//! while the content behind it is unchanged.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use wgpu::util::DeviceExt;
+
+use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
+use crate::core::types::{Color, Rect};
+
+/// Largest blur radius in physical pixels
+const MAX_RADIUS: u32 = 64;
+/// Most blur iterations per region
+const MAX_PASSES: u32 = 3;
+/// Format of the intermediate blur textures
+const BLUR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// How strongly a region is blurred
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurSettings {
+    /// Kernel radius in physical pixels
+    pub radius: u32,
+    /// Horizontal + vertical iterations; more is smoother and slower
+    pub passes: u32,
+}
+
+/// A region's blurred backdrop, kept across frames
+struct BlurredRegion {
+    /// Backdrop signature and settings the texture was made from
+    signature: Option<u64>,
+    size: (u32, u32),
+    /// Copy of the render target under the region
+    source: wgpu::Texture,
+    /// Ping-pong targets of the blur passes; the result ends in the second
+    _targets: [wgpu::Texture; 2],
+    /// Kernel parameters for the horizontal and vertical passes
+    params: [wgpu::Buffer; 2],
+    /// Pass inputs: source → ping, ping → pong, pong → ping
+    steps: [wgpu::BindGroup; 3],
+    /// The blurred result, for drawing with the image pipelines
+    bind_group: wgpu::BindGroup,
+    /// Frame the region was last requested in
+    last_used: u64,
+}
+
+/// Compute pipeline and per-region textures for backdrop blur
+pub struct BlurPass {
+    pipeline: wgpu::ComputePipeline,
+    layout: wgpu::BindGroupLayout,
+    regions: HashMap<u64, BlurredRegion>,
+    frame: u64,
+}
+
+impl BlurPass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blur Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/blur.wgsl").into()),
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Blur Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: BLUR_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blur Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Blur Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            layout,
+            regions: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    /// Start a frame, dropping regions nobody asked for last frame
+    pub fn begin_frame(&mut self) {
+        let frame = self.frame;
+        self.regions.retain(|_, r| r.last_used >= frame);
+        self.frame += 1;
+    }
+
+    /// Blur `rect` (physical pixels, inside `target`) for region `id`.
+    ///
+    /// The copy and compute passes are recorded into `encoder`, unless the
+    /// region was blurred before with the same `signature`; None means the
+    /// backdrop changes every frame.  Returns false if the region cannot be
+    /// blurred, e.g. because `target` does not allow copies.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blur(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        id: u64,
+        rect: [u32; 4],
+        signature: Option<u64>,
+        settings: BlurSettings,
+        draw_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+    ) -> bool {
+        let [x, y, w, h] = rect;
+        if w == 0
+            || h == 0
+            || x + w > target.width()
+            || y + h > target.height()
+            || !target.usage().contains(wgpu::TextureUsages::COPY_SRC)
+        {
+            return false;
+        }
+        let signature = signature.map(|s| {
+            let mut hasher = DefaultHasher::new();
+            (s, settings.radius, settings.passes).hash(&mut hasher);
+            hasher.finish()
+        });
+
+        let frame = self.frame;
+        let stale = self.regions.get(&id).is_none_or(|r| {
+            r.size != (w, h) || r.source.format() != target.format()
+        });
+        if stale {
+            let region = self.create_region(device, target.format(), w, h, draw_layout, sampler);
+            self.regions.insert(id, region);
+        }
+        let Some(region) = self.regions.get_mut(&id) else { return false };
+        region.last_used = frame;
+        if signature.is_some() && region.signature == signature {
+            return true;
+        }
+        region.signature = signature;
+
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: target,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &region.source,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d { width: w, height: h, depth_or_array_layers: 1 },
+        );
+
+        let radius = settings.radius.clamp(1, MAX_RADIUS);
+        for (buffer, direction) in region.params.iter().zip([(1, 0), (0, 1)]) {
+            queue.write_buffer(buffer, 0, &kernel_params(direction, radius));
+        }
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Blur Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        let groups = (w.div_ceil(8), h.div_ceil(8));
+        for i in 0..settings.passes.clamp(1, MAX_PASSES) {
+            let steps = if i == 0 { [0, 1] } else { [2, 1] };
+            for step in steps {
+                pass.set_bind_group(0, &region.steps[step], &[]);
+                pass.dispatch_workgroups(groups.0, groups.1, 1);
+            }
+        }
+        drop(pass);
+
+        true
+    }
+
+    /// Bind group for `draw_layout` sampling region `id`'s blurred backdrop
+    pub fn bind_group(&self, id: u64) -> Option<&wgpu::BindGroup> {
+        self.regions.get(&id).map(|r| &r.bind_group)
+    }
+
+    fn create_region(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        draw_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+    ) -> BlurredRegion {
+        let texture = |label, format, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let source = texture(
+            "Blur Source",
+            format,
+            wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let usage = wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING;
+        let ping = texture("Blur Ping", BLUR_FORMAT, usage);
+        let pong = texture("Blur Pong", BLUR_FORMAT, usage);
+
+        let params = [0, 1].map(|_| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Blur Params"),
+                contents: &[0; 16],
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        });
+
+        let view = |t: &wgpu::Texture| t.create_view(&wgpu::TextureViewDescriptor::default());
+        let (source_view, ping_view, pong_view) = (view(&source), view(&ping), view(&pong));
+        let step = |src: &wgpu::TextureView, dst: &wgpu::TextureView, params: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Blur Step"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(src) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(dst) },
+                    wgpu::BindGroupEntry { binding: 2, resource: params.as_entire_binding() },
+                ],
+            })
+        };
+        let steps = [
+            step(&source_view, &ping_view, &params[0]),
+            step(&ping_view, &pong_view, &params[1]),
+            step(&pong_view, &ping_view, &params[0]),
+        ];
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Blurred Backdrop"),
+            layout: draw_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&pong_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            ],
+        });
+
+        BlurredRegion {
+            signature: None,
+            size: (width, height),
+            source,
+            _targets: [ping, pong],
+            params,
+            steps,
+            bind_group,
+            last_used: self.frame,
+        }
+    }
+}
+
+/// Uniform block for one blur direction: direction, radius, sigma
+fn kernel_params(direction: (i32, i32), radius: u32) -> [u8; 16] {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let mut out = [0u8; 16];
+    out[0..4].copy_from_slice(&direction.0.to_le_bytes());
+    out[4..8].copy_from_slice(&direction.1.to_le_bytes());
+    out[8..12].copy_from_slice(&(radius as i32).to_le_bytes());
+    out[12..16].copy_from_slice(&sigma.to_le_bytes());
+    out
+}
+
+/// Fingerprint of the non-overlay content under `rect`, or None if live
+/// content (video, web views, terminals) shows through it.
+pub fn backdrop_signature(frame: &FrameGlyphBuffer, rect: &Rect) -> Option<u64> {
+    let mut h = DefaultHasher::new();
+    let hash_f = |h: &mut DefaultHasher, v: &[f32]| v.iter().for_each(|f| f.to_bits().hash(h));
+    let hash_color = |h: &mut DefaultHasher, c: &Color| [c.r, c.g, c.b, c.a].iter().for_each(|f| f.to_bits().hash(h));
+
+    hash_f(&mut h, &[rect.x, rect.y, rect.width, rect.height]);
+    hash_color(&mut h, &frame.background);
+    for glyph in &frame.glyphs {
+        if glyph.is_overlay() {
+            continue;
+        }
+        let bounds = match glyph {
+            FrameGlyph::Char { x, y, width, height, .. }
+            | FrameGlyph::Stretch { x, y, width, height, .. }
+            | FrameGlyph::Image { x, y, width, height, .. }
+            | FrameGlyph::Video { x, y, width, height, .. }
+            | FrameGlyph::WebKit { x, y, width, height, .. }
+            | FrameGlyph::Cursor { x, y, width, height, .. }
+            | FrameGlyph::Border { x, y, width, height, .. }
+            | FrameGlyph::ScrollBar { x, y, width, height, .. } => {
+                Rect::new(*x, *y, *width, *height)
+            }
+            #[cfg(feature = "neo-term")]
+            FrameGlyph::Terminal { x, y, width, height, .. } => Rect::new(*x, *y, *width, *height),
+            FrameGlyph::Background { bounds, .. } => *bounds,
+        };
+        if !bounds.intersects(rect) {
+            continue;
+        }
+        hash_f(&mut h, &[bounds.x, bounds.y, bounds.width, bounds.height]);
+        match glyph {
+            FrameGlyph::Char { char, composed, fg, bg, face_id, .. } => {
+                (0u8, *char, composed, *face_id).hash(&mut h);
+                hash_color(&mut h, fg);
+                if let Some(bg) = bg {
+                    hash_color(&mut h, bg);
+                }
+            }
+            FrameGlyph::Stretch { bg, .. } => {
+                1u8.hash(&mut h);
+                hash_color(&mut h, bg);
+            }
+            FrameGlyph::Image { image_id, .. } => (2u8, *image_id).hash(&mut h),
+            FrameGlyph::Cursor { style, color, .. } => {
+                (3u8, *style).hash(&mut h);
+                hash_color(&mut h, color);
+            }
+            FrameGlyph::Background { color, .. } | FrameGlyph::Border { color, .. } => {
+                4u8.hash(&mut h);
+                hash_color(&mut h, color);
+            }
+            FrameGlyph::ScrollBar { thumb_start, thumb_size, track_color, thumb_color, .. } => {
+                5u8.hash(&mut h);
+                hash_f(&mut h, &[*thumb_start, *thumb_size]);
+                hash_color(&mut h, track_color);
+                hash_color(&mut h, thumb_color);
+            }
+            _ => return None,
+        }
+    }
+    Some(h.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stretch(x: f32, bg: Color, is_overlay: bool) -> FrameGlyph {
+        FrameGlyph::Stretch { x, y: 0.0, width: 10.0, height: 10.0, bg, face_id: 0, is_overlay }
+    }
+
+    #[test]
+    fn test_backdrop_signature_tracks_content_under_region() {
+        let region = Rect::new(0.0, 0.0, 20.0, 20.0);
+        let mut frame = FrameGlyphBuffer::new();
+        frame.glyphs.push(stretch(0.0, Color::BLACK, false));
+        let base = backdrop_signature(&frame, &region);
+        assert!(base.is_some());
+
+        // Content outside the region or drawn over it does not count
+        frame.glyphs.push(stretch(100.0, Color::WHITE, false));
+        frame.glyphs.push(stretch(5.0, Color::WHITE, true));
+        assert_eq!(backdrop_signature(&frame, &region), base);
+
+        frame.glyphs[0] = stretch(0.0, Color::WHITE, false);
+        assert_ne!(backdrop_signature(&frame, &region), base);
+
+        // Video under the region changes every frame
+        frame.glyphs.push(FrameGlyph::Video { video_id: 1, x: 0.0, y: 0.0, width: 5.0, height: 5.0 });
+        assert_eq!(backdrop_signature(&frame, &region), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Detection of GPU device loss.
+//!
+//! A driver reset, GPU hang or eGPU unplug leaves the wgpu device lost:
+//! every later submission fails and wgpu's default error handler panics.
+//! `DeviceLossWatch` hooks a device's lost callback and error handler so
+//! the backend can notice the loss, log instead of panicking, and rebuild
+//! its device, surface and textures.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::core::error_report::{self, ErrorKind};
+
+/// Watches one device for loss
+#[derive(Debug, Clone, Default)]
+pub struct DeviceLossWatch {
+    lost: Arc<AtomicBool>,
+}
+
+impl DeviceLossWatch {
+    /// Start watching `device`.  Each device needs its own watch: dropping
+    /// an old device after recovery fires its lost callback too.
+    pub fn new(device: &wgpu::Device) -> Self {
+        let watch = Self::default();
+
+        let lost = Arc::clone(&watch.lost);
+        device.set_device_lost_callback(move |reason, message| {
+            if reason_is_loss(reason) {
+                error_report::error(ErrorKind::Gpu, None, format!("device lost ({:?}): {}", reason, message));
+                lost.store(true, Ordering::Release);
+            }
+        });
+
+        let lost = Arc::clone(&watch.lost);
+        device.on_uncaptured_error(Box::new(move |error| match error {
+            wgpu::Error::Validation { description, .. } => {
+                // Shader compile failures surface as validation errors
+                // naming the shader module
+                let kind = if description.contains("Shader") { ErrorKind::Shader } else { ErrorKind::Gpu };
+                error_report::error(kind, None, description);
+            }
+            error => {
+                error_report::error(ErrorKind::Gpu, None, format!("device error, recreating device: {}", error));
+                lost.store(true, Ordering::Release);
+            }
+        }));
+
+        watch
+    }
+
+    /// Whether the device was lost and has to be recreated
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+}
+
+/// Whether a lost-callback `reason` means the device is gone, as opposed
+/// to being dropped or having its callback replaced by us
+fn reason_is_loss(reason: wgpu::DeviceLostReason) -> bool {
+    matches!(reason, wgpu::DeviceLostReason::Unknown | wgpu::DeviceLostReason::Destroyed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_driver_loss_counts() {
+        assert!(reason_is_loss(wgpu::DeviceLostReason::Unknown));
+        assert!(reason_is_loss(wgpu::DeviceLostReason::Destroyed));
+        assert!(!reason_is_loss(wgpu::DeviceLostReason::Dropped));
+        assert!(!reason_is_loss(wgpu::DeviceLostReason::ReplacedCallback));
+        assert!(!DeviceLossWatch::default().is_lost());
+    }
+}
@@ -19,6 +19,21 @@ pub enum EventKind {
     MenuSelection = 13,
     FileDrop = 14,
     TerminalTitleChanged = 15,
+    VideoEnded = 16,
+    WebKitTitleChanged = 17,
+    WebKitUrlChanged = 18,
+    WebKitProgressChanged = 19,
+    WebKitLoadFinished = 20,
+    AnimationFinished = 21,
+    DisplayError = 22,
+    TerminalBell = 23,
+    TmuxPaneAdded = 24,
+    TmuxPaneClosed = 25,
+    TmuxExited = 26,
+    AnimationCompleted = 27,
+    PipClosed = 28,
+    AudioLevels = 29,
+    AppearanceChanged = 30,
 }
 
 /// Modifier flags matching Emacs.
@@ -43,6 +58,21 @@ pub const NEOMACS_EVENT_TERMINAL_EXITED: u32 = EventKind::TerminalExited as u32;
 pub const NEOMACS_EVENT_MENU_SELECTION: u32 = EventKind::MenuSelection as u32;
 pub const NEOMACS_EVENT_FILE_DROP: u32 = EventKind::FileDrop as u32;
 pub const NEOMACS_EVENT_TERMINAL_TITLE_CHANGED: u32 = EventKind::TerminalTitleChanged as u32;
+pub const NEOMACS_EVENT_VIDEO_ENDED: u32 = EventKind::VideoEnded as u32;
+pub const NEOMACS_EVENT_WEBKIT_TITLE_CHANGED: u32 = EventKind::WebKitTitleChanged as u32;
+pub const NEOMACS_EVENT_WEBKIT_URL_CHANGED: u32 = EventKind::WebKitUrlChanged as u32;
+pub const NEOMACS_EVENT_WEBKIT_PROGRESS_CHANGED: u32 = EventKind::WebKitProgressChanged as u32;
+pub const NEOMACS_EVENT_WEBKIT_LOAD_FINISHED: u32 = EventKind::WebKitLoadFinished as u32;
+pub const NEOMACS_EVENT_ANIMATION_FINISHED: u32 = EventKind::AnimationFinished as u32;
+pub const NEOMACS_EVENT_DISPLAY_ERROR: u32 = EventKind::DisplayError as u32;
+pub const NEOMACS_EVENT_TERMINAL_BELL: u32 = EventKind::TerminalBell as u32;
+pub const NEOMACS_EVENT_TMUX_PANE_ADDED: u32 = EventKind::TmuxPaneAdded as u32;
+pub const NEOMACS_EVENT_TMUX_PANE_CLOSED: u32 = EventKind::TmuxPaneClosed as u32;
+pub const NEOMACS_EVENT_TMUX_EXITED: u32 = EventKind::TmuxExited as u32;
+pub const NEOMACS_EVENT_ANIMATION_COMPLETED: u32 = EventKind::AnimationCompleted as u32;
+pub const NEOMACS_EVENT_PIP_CLOSED: u32 = EventKind::PipClosed as u32;
+pub const NEOMACS_EVENT_AUDIO_LEVELS: u32 = EventKind::AudioLevels as u32;
+pub const NEOMACS_EVENT_APPEARANCE_CHANGED: u32 = EventKind::AppearanceChanged as u32;
 
 /// Input event structure passed to C.
 #[repr(C)]
@@ -1,14 +1,42 @@
 //! Glyph texture atlas for wgpu GPU rendering
 //!
-//! Caches rasterized glyphs as individual wgpu textures with bind groups.
+//! Rasterized glyphs are packed into the layers ("pages") of two texture
+//! arrays, one for alpha masks and one for color glyphs.  An array gains
+//! layers when its pages fill up instead of the whole cache being thrown
+//! away, and badly fragmented pages are repacked while the display is idle.
 
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use cosmic_text::{
     Attrs, Buffer, Family, FontSystem, Metrics, ShapeBuffer, SwashCache, Style, Weight,
 };
 
-use crate::core::face::Face;
+use crate::core::face::{FallbackMetrics, Face};
+use crate::core::types::Color;
+use crate::core::error_report::{self, ErrorKind};
+use crate::text::clusters::{ClusterKey, ClusterOffsets, SharedClusterOffsets, ShapedCluster};
+
+use super::atlas_packer::PagePacker;
+
+/// Side of a square atlas page in texels
+const PAGE_SIZE: u32 = 1024;
+/// Empty texels around each glyph so linear sampling does not pick up
+/// its neighbours
+const GUTTER: u32 = 1;
+/// Most pages of alpha-mask glyphs (1 MiB each)
+const MAX_MASK_PAGES: u32 = 32;
+/// Most pages of color glyphs (4 MiB each)
+const MAX_COLOR_PAGES: u32 = 8;
+/// Share of a page lost to holes before it is repacked
+const DEFRAG_THRESHOLD: f32 = 0.25;
+/// Glyph generations kept for scale factors other than the current one,
+/// so moving a window between monitors does not re-rasterize everything
+const MAX_PARKED_SCALES: usize = 2;
+/// Distance in texels a signed distance field extends either side of a
+/// glyph's outline
+const SDF_SPREAD: u32 = 4;
 
 /// Key for glyph cache lookup
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -27,21 +55,42 @@ pub struct GlyphKey {
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct ComposedGlyphKey {
     /// The full text of the composed grapheme cluster
-    pub text: Box<str>,
+    pub text: Arc<str>,
     /// Face ID (determines font, style)
     pub face_id: u32,
     /// Font size in pixels (using u32 bits of f32 for hashing)
     pub font_size_bits: u32,
 }
 
-/// A cached glyph with its wgpu texture and bind group
+/// Where a glyph sits in its texture array, gutter included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AtlasSlot {
+    layer: u32,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+impl AtlasSlot {
+    /// Texture coordinates (u0, v0, u1, v1) of the glyph inside the gutter
+    fn uv(&self) -> [f32; 4] {
+        let page = PAGE_SIZE as f32;
+        [
+            (self.x + GUTTER) as f32 / page,
+            (self.y + GUTTER) as f32 / page,
+            (self.x + self.w - GUTTER) as f32 / page,
+            (self.y + self.h - GUTTER) as f32 / page,
+        ]
+    }
+}
+
+/// A cached glyph: its place in an atlas page and a bind group for it
 pub struct CachedGlyph {
-    /// Texture containing this glyph
-    pub texture: wgpu::Texture,
-    /// Texture view for sampling
-    pub view: wgpu::TextureView,
-    /// Bind group for this glyph's texture
+    /// Bind group for the atlas page holding this glyph
     pub bind_group: wgpu::BindGroup,
+    /// Texture coordinates (u0, v0, u1, v1) of the glyph in its page
+    pub uv: [f32; 4],
     /// Width in pixels
     pub width: u32,
     /// Height in pixels
@@ -54,14 +103,186 @@ pub struct CachedGlyph {
     /// Color glyphs should be rendered with the image pipeline (direct RGBA),
     /// not the glyph pipeline (alpha-mask tinted with foreground color).
     pub is_color: bool,
+    /// True if the texels hold a signed distance field rather than
+    /// coverage; drawn with the SDF glyph pipeline
+    pub is_sdf: bool,
     /// Frame generation when this glyph was last accessed
     last_accessed: u64,
+    /// Position in the atlas
+    slot: AtlasSlot,
+    /// Pixels with gutter, kept to re-upload when pages grow or repack
+    pixels: Vec<u8>,
+}
+
+impl CachedGlyph {
+    /// Map (u, v) across the glyph's unit square into its atlas page
+    pub fn tex_coord(&self, u: f32, v: f32) -> [f32; 2] {
+        let [u0, v0, u1, v1] = self.uv;
+        [u0 + (u1 - u0) * u, v0 + (v1 - v0) * v]
+    }
+}
+
+/// Key into either glyph cache
+enum EntryKey {
+    Single(GlyphKey),
+    Composed(ComposedGlyphKey),
+}
+
+/// A texture array of atlas pages sharing one pixel format
+struct AtlasPages {
+    format: wgpu::TextureFormat,
+    bytes_per_pixel: u32,
+    texture: wgpu::Texture,
+    /// A 2D view of each layer, bound by the glyphs stored in it
+    views: Vec<wgpu::TextureView>,
+    packers: Vec<PagePacker>,
+    max_layers: u32,
+}
+
+impl AtlasPages {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat, bytes_per_pixel: u32, max_layers: u32) -> Self {
+        let (texture, views) = Self::create_texture(device, format, 1);
+        Self {
+            format,
+            bytes_per_pixel,
+            texture,
+            views,
+            packers: vec![PagePacker::new(PAGE_SIZE)],
+            max_layers,
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        layers: u32,
+    ) -> (wgpu::Texture, Vec<wgpu::TextureView>) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas Pages"),
+            size: wgpu::Extent3d {
+                width: PAGE_SIZE,
+                height: PAGE_SIZE,
+                depth_or_array_layers: layers,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let views = (0..layers)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Glyph Atlas Page"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        (texture, views)
+    }
+
+    /// First page with room for a `w` x `h` slot
+    fn allocate(&mut self, w: u32, h: u32) -> Option<AtlasSlot> {
+        self.packers.iter_mut().enumerate().find_map(|(layer, packer)| {
+            packer
+                .allocate(w, h)
+                .map(|(x, y)| AtlasSlot { layer: layer as u32, x, y, w, h })
+        })
+    }
+
+    fn free(&mut self, slot: AtlasSlot) {
+        if let Some(packer) = self.packers.get_mut(slot.layer as usize) {
+            packer.free(slot.x, slot.y, slot.w, slot.h);
+        }
+    }
+
+    /// Double the number of pages, up to `max_layers`.  The new texture
+    /// starts empty, so every glyph must be uploaded again.
+    fn grow(&mut self, device: &wgpu::Device) -> bool {
+        let layers = self.packers.len() as u32;
+        if layers >= self.max_layers {
+            return false;
+        }
+        let layers = (layers * 2).min(self.max_layers);
+        let (texture, views) = Self::create_texture(device, self.format, layers);
+        self.texture = texture;
+        self.views = views;
+        self.packers.resize_with(layers as usize, || PagePacker::new(PAGE_SIZE));
+        true
+    }
+
+    /// Page most in need of repacking, if any passes the threshold
+    fn most_fragmented(&self) -> Option<u32> {
+        self.packers
+            .iter()
+            .enumerate()
+            .map(|(layer, p)| (layer as u32, p.fragmentation()))
+            .filter(|(_, f)| *f > DEFRAG_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(layer, _)| layer)
+    }
+
+    fn write(&self, queue: &wgpu::Queue, slot: AtlasSlot, pixels: &[u8]) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: slot.x, y: slot.y, z: slot.layer },
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(slot.w * self.bytes_per_pixel),
+                rows_per_image: Some(slot.h),
+            },
+            wgpu::Extent3d {
+                width: slot.w,
+                height: slot.h,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    fn reset(&mut self) {
+        for packer in &mut self.packers {
+            packer.reset();
+        }
+    }
+}
+
+/// A layout epoch no atlas has used yet
+fn next_layout_epoch() -> u64 {
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+    NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Glyphs rasterized for a scale factor the atlas is not drawing at.
+/// They keep their page space until evicted or the scale comes back.
+struct ScaleGeneration {
+    scale_factor: f32,
+    cache: HashMap<GlyphKey, CachedGlyph>,
+    composed_cache: HashMap<ComposedGlyphKey, CachedGlyph>,
+}
+
+impl ScaleGeneration {
+    fn glyphs(&self) -> impl Iterator<Item = &CachedGlyph> {
+        self.cache.values().chain(self.composed_cache.values())
+    }
 }
 
 /// Wgpu-based glyph atlas for text rendering
 pub struct WgpuGlyphAtlas {
     /// Cached glyphs: (charcode, face_id) -> CachedGlyph
     cache: HashMap<GlyphKey, CachedGlyph>,
+    /// Pages of alpha-mask glyphs
+    mask_pages: AtlasPages,
+    /// Pages of color glyphs (RGBA, e.g. color emoji)
+    color_pages: AtlasPages,
     /// Cached composed glyphs (multi-codepoint grapheme clusters)
     composed_cache: HashMap<ComposedGlyphKey, CachedGlyph>,
     /// Font system for text rendering
@@ -81,12 +302,30 @@ pub struct WgpuGlyphAtlas {
     default_line_height: f32,
     /// Display scale factor for HiDPI rasterization
     scale_factor: f32,
+    /// Glyphs of other scale factors, most recently used last
+    parked: Vec<ScaleGeneration>,
     /// Maximum cache size
     max_size: usize,
     /// Interned font family names (avoids Box::leak memory growth)
     interned_families: HashSet<&'static str>,
     /// Frame generation counter (incremented each frame)
     generation: u64,
+    /// Changes whenever cached glyphs move or leave the atlas, so vertices
+    /// built from glyph positions stay valid while it holds.  Unique across
+    /// atlases, as a recreated atlas must not match an old one.
+    layout_epoch: u64,
+    /// Caret offsets of composed glyphs, published for the Emacs thread
+    cluster_offsets: Option<SharedClusterOffsets>,
+    /// Fallback glyph fitting for faces without an override
+    fallback_default: FallbackMetrics,
+    /// Per-face fallback glyph fitting, by face ID
+    fallback_faces: HashMap<u32, FallbackMetrics>,
+    /// Font drawing plain text for a (family, weight, italic) combination
+    text_fonts: HashMap<(String, u16, bool), Option<cosmic_text::fontdb::ID>>,
+    /// (ascent, descent) in em of each font seen by fallback fitting
+    font_boxes: HashMap<cosmic_text::fontdb::ID, (f32, f32)>,
+    /// Store mask glyphs as signed distance fields
+    sdf: bool,
 }
 
 impl WgpuGlyphAtlas {
@@ -130,6 +369,8 @@ impl WgpuGlyphAtlas {
 
         Self {
             cache: HashMap::new(),
+            mask_pages: AtlasPages::new(device, wgpu::TextureFormat::R8Unorm, 1, MAX_MASK_PAGES),
+            color_pages: AtlasPages::new(device, wgpu::TextureFormat::Rgba8UnormSrgb, 4, MAX_COLOR_PAGES),
             composed_cache: HashMap::new(),
             font_system: FontSystem::new(),
             swash_cache: SwashCache::new(),
@@ -139,9 +380,17 @@ impl WgpuGlyphAtlas {
             default_font_size: 13.0,
             default_line_height: 17.0,
             scale_factor: 1.0,
+            parked: Vec::new(),
             max_size: 4096,
             interned_families: HashSet::new(),
             generation: 0,
+            layout_epoch: next_layout_epoch(),
+            cluster_offsets: None,
+            fallback_default: FallbackMetrics::default(),
+            fallback_faces: HashMap::new(),
+            text_fonts: HashMap::new(),
+            font_boxes: HashMap::new(),
+            sdf: false,
         }
     }
 
@@ -152,6 +401,57 @@ impl WgpuGlyphAtlas {
         atlas
     }
 
+    /// The fonts glyphs are drawn from, to draw text elsewhere
+    pub fn fonts_mut(&mut self) -> (&mut FontSystem, &mut SwashCache) {
+        (&mut self.font_system, &mut self.swash_cache)
+    }
+
+    /// Publish caret offsets of composed glyphs into `shared` as they are shaped
+    pub fn set_cluster_offsets(&mut self, shared: SharedClusterOffsets) {
+        self.cluster_offsets = Some(shared);
+    }
+
+    /// Set how fallback-font glyphs are fitted for `face_id`, or for all
+    /// faces without an override when `face_id` is None.  Passing None
+    /// metrics for a face drops its override.  The fitting is baked into
+    /// the rasterized glyphs, so the cache is cleared.
+    pub fn set_fallback_metrics(&mut self, face_id: Option<u32>, metrics: Option<FallbackMetrics>) {
+        match (face_id, metrics) {
+            (Some(id), Some(m)) => {
+                self.fallback_faces.insert(id, m);
+            }
+            (Some(id), None) => {
+                self.fallback_faces.remove(&id);
+            }
+            (None, m) => self.fallback_default = m.unwrap_or_default(),
+        }
+        self.clear();
+    }
+
+    /// Take over the settings of an atlas whose device was lost.  Glyphs
+    /// are not copied; they are rasterized again as they are drawn.
+    pub fn adopt_settings(&mut self, lost: &WgpuGlyphAtlas) {
+        self.cluster_offsets = lost.cluster_offsets.clone();
+        self.fallback_default = lost.fallback_default;
+        self.fallback_faces = lost.fallback_faces.clone();
+        self.sdf = lost.sdf;
+    }
+
+    /// Store mask glyphs as signed distance fields, which stay sharp when
+    /// drawn scaled (zoom animations, fractional scales).  Glyphs already
+    /// rasterized are dropped.
+    pub fn set_sdf(&mut self, sdf: bool) {
+        if self.sdf != sdf {
+            self.sdf = sdf;
+            self.clear();
+        }
+    }
+
+    /// Whether mask glyphs are stored as signed distance fields
+    pub fn sdf(&self) -> bool {
+        self.sdf
+    }
+
     /// Get the bind group layout for glyph textures
     pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
         &self.bind_group_layout
@@ -186,11 +486,16 @@ impl WgpuGlyphAtlas {
 
         let rasterize_result = self.rasterize_glyph(c, face);
         if rasterize_result.is_none() {
-            log::warn!("glyph_atlas: failed to rasterize '{}' (U+{:04X}) face_id={} has_face={}",
-                c, key.charcode, key.face_id, face.is_some());
+            let family = face.map_or("default", |f| f.font_family.as_str());
+            error_report::warning(
+                ErrorKind::Font,
+                None,
+                format!("no font for '{}' (U+{:04X}) in family {}", c, key.charcode, family),
+            );
             return None;
         }
-        let (width, height, pixel_data, bearing_x, bearing_y, is_color) = rasterize_result?;
+        let raster = rasterize_result?;
+        let (width, height, _, bearing_x, bearing_y, is_color) = raster;
 
         if width == 0 || height == 0 {
             log::debug!("glyph_atlas: skipping empty glyph '{}' ({}x{})", c, width, height);
@@ -200,68 +505,6 @@ impl WgpuGlyphAtlas {
         log::debug!("glyph_atlas: rasterized '{}' {}x{} bearing ({:.1},{:.1}) color={}",
             c, width, height, bearing_x, bearing_y, is_color);
 
-        // Color glyphs use Rgba8UnormSrgb (4 bytes/pixel), mask glyphs use R8Unorm (1 byte/pixel)
-        let (format, bytes_per_pixel) = if is_color {
-            (wgpu::TextureFormat::Rgba8UnormSrgb, 4u32)
-        } else {
-            (wgpu::TextureFormat::R8Unorm, 1u32)
-        };
-
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some(if is_color { "Color Glyph Texture" } else { "Glyph Texture" }),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-
-        // Upload pixel data
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &pixel_data,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(width * bytes_per_pixel),
-                rows_per_image: Some(height),
-            },
-            wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-        );
-
-        // Create texture view
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Create bind group
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Glyph Bind Group"),
-            layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.sampler),
-                },
-            ],
-        });
-
         // Evict least-recently-used entries if cache is full
         if self.cache.len() >= self.max_size {
             let mut entries: Vec<_> = self.cache.iter()
@@ -270,23 +513,11 @@ impl WgpuGlyphAtlas {
             entries.sort_by_key(|(_, gen)| *gen);
             let evict_count = self.max_size / 4;
             for (k, _) in entries.into_iter().take(evict_count) {
-                self.cache.remove(&k);
+                self.remove_entry(&EntryKey::Single(k));
             }
         }
 
-        // Insert into cache
-        let gen = self.generation;
-        let cached_glyph = CachedGlyph {
-            texture,
-            view,
-            bind_group,
-            width,
-            height,
-            bearing_x,
-            bearing_y,
-            is_color,
-            last_accessed: gen,
-        };
+        let cached_glyph = self.store(device, queue, raster)?;
         self.cache.insert(key.clone(), cached_glyph);
         self.cache.get(key)
     }
@@ -298,13 +529,13 @@ impl WgpuGlyphAtlas {
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        text: &str,
+        text: &Arc<str>,
         face_id: u32,
         font_size_bits: u32,
         face: Option<&Face>,
     ) -> Option<&CachedGlyph> {
         let key = ComposedGlyphKey {
-            text: text.into(),
+            text: text.clone(),
             face_id,
             font_size_bits,
         };
@@ -319,63 +550,303 @@ impl WgpuGlyphAtlas {
         // Rasterize the composed text
         let rasterize_result = self.rasterize_text(text, face);
         if rasterize_result.is_none() {
-            log::warn!("glyph_atlas: failed to rasterize composed text '{}'", text);
+            error_report::warning(ErrorKind::Font, None, format!("no font for composed text '{}'", text));
+            return None;
+        }
+        let raster = rasterize_result?;
+        if raster.0 == 0 || raster.1 == 0 {
             return None;
         }
-        let (width, height, pixel_data, bearing_x, bearing_y, is_color) = rasterize_result?;
 
-        if width == 0 || height == 0 {
+        let cached_glyph = self.store(device, queue, raster)?;
+        self.composed_cache.insert(key.clone(), cached_glyph);
+        self.publish_cluster_offsets(&key, face);
+        self.composed_cache.get(&key)
+    }
+
+    /// Pack rasterized pixels into an atlas page and build the cache entry.
+    /// When the pages are full, adds pages or evicts glyphs not drawn this
+    /// frame.
+    fn store(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        raster: (u32, u32, Vec<u8>, f32, f32, bool),
+    ) -> Option<CachedGlyph> {
+        let (mut width, mut height, mut pixel_data, mut bearing_x, mut bearing_y, is_color) = raster;
+        let is_sdf = self.sdf && !is_color;
+        if is_sdf {
+            pixel_data = coverage_to_sdf(&pixel_data, width, height, SDF_SPREAD);
+            width += 2 * SDF_SPREAD;
+            height += 2 * SDF_SPREAD;
+            bearing_x -= SDF_SPREAD as f32;
+            bearing_y += SDF_SPREAD as f32;
+        }
+        let (w, h) = (width + 2 * GUTTER, height + 2 * GUTTER);
+        if w > PAGE_SIZE || h > PAGE_SIZE {
+            log::warn!("glyph_atlas: {}x{} glyph does not fit an atlas page", width, height);
             return None;
         }
+        let bpp = if is_color { 4 } else { 1 };
+        let pixels = pad_pixels(&pixel_data, width, height, bpp);
 
-        // Create GPU texture (same logic as single-char path)
-        let (format, bytes_per_pixel) = if is_color {
-            (wgpu::TextureFormat::Rgba8UnormSrgb, 4u32)
-        } else {
-            (wgpu::TextureFormat::R8Unorm, 1u32)
+        let slot = loop {
+            let pages = if is_color { &mut self.color_pages } else { &mut self.mask_pages };
+            if let Some(slot) = pages.allocate(w, h) {
+                break slot;
+            }
+            if !self.grow_pages(device, queue, is_color) && !self.evict_unused(is_color) {
+                log::warn!("glyph_atlas: all {} atlas pages are in use",
+                    if is_color { "color" } else { "mask" });
+                return None;
+            }
         };
 
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Composed Glyph Texture"),
-            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture, mip_level: 0,
-                origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All,
-            },
-            &pixel_data,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(width * bytes_per_pixel),
-                rows_per_image: Some(height),
-            },
-            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        let pages = if is_color { &self.color_pages } else { &self.mask_pages };
+        pages.write(queue, slot, &pixels);
+        let bind_group = glyph_bind_group(
+            device,
+            &self.bind_group_layout,
+            &pages.views[slot.layer as usize],
+            &self.sampler,
         );
+        Some(CachedGlyph {
+            bind_group,
+            uv: slot.uv(),
+            width,
+            height,
+            bearing_x,
+            bearing_y,
+            is_color,
+            is_sdf,
+            last_accessed: self.generation,
+            slot,
+            pixels,
+        })
+    }
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Composed Glyph Bind Group"),
-            layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
-                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
-            ],
-        });
+    /// Add pages for color or mask glyphs, re-uploading the glyphs they
+    /// hold.  Returns false once the page limit is reached.
+    fn grow_pages(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, color: bool) -> bool {
+        let pages = if color { &mut self.color_pages } else { &mut self.mask_pages };
+        if !pages.grow(device) {
+            return false;
+        }
+        let parked = self
+            .parked
+            .iter_mut()
+            .flat_map(|g| g.cache.values_mut().chain(g.composed_cache.values_mut()));
+        for glyph in self.cache.values_mut().chain(self.composed_cache.values_mut()).chain(parked) {
+            if glyph.is_color == color {
+                pages.write(queue, glyph.slot, &glyph.pixels);
+                glyph.bind_group = glyph_bind_group(
+                    device,
+                    &self.bind_group_layout,
+                    &pages.views[glyph.slot.layer as usize],
+                    &self.sampler,
+                );
+            }
+        }
+        self.layout_epoch = next_layout_epoch();
+        log::info!("glyph_atlas: {} pages -> {}",
+            if color { "color" } else { "mask" }, pages.packers.len());
+        true
+    }
+
+    /// Evict the glyphs of other scale factors or, without those, the
+    /// least recently used quarter of the color or mask glyphs not drawn
+    /// this frame.  Returns false if every glyph is in use.
+    fn evict_unused(&mut self, color: bool) -> bool {
+        if self.drop_parked(|g| g.is_color == color) > 0 {
+            self.prune_cluster_offsets();
+            return true;
+        }
 
         let gen = self.generation;
-        self.composed_cache.insert(key.clone(), CachedGlyph {
-            texture, view, bind_group, width, height,
-            bearing_x, bearing_y, is_color, last_accessed: gen,
-        });
-        self.composed_cache.get(&key)
+        let mut victims: Vec<(EntryKey, u64)> = self
+            .cache
+            .iter()
+            .filter(|(_, g)| g.is_color == color && g.last_accessed < gen)
+            .map(|(k, g)| (EntryKey::Single(k.clone()), g.last_accessed))
+            .chain(
+                self.composed_cache
+                    .iter()
+                    .filter(|(_, g)| g.is_color == color && g.last_accessed < gen)
+                    .map(|(k, g)| (EntryKey::Composed(k.clone()), g.last_accessed)),
+            )
+            .collect();
+        if victims.is_empty() {
+            return false;
+        }
+        victims.sort_by_key(|(_, gen)| *gen);
+        let count = victims.len().div_ceil(4);
+        for (key, _) in victims.into_iter().take(count) {
+            self.remove_entry(&key);
+        }
+        self.prune_cluster_offsets();
+        true
+    }
+
+    /// Drop the parked glyphs matching `pred` and free their page space.
+    /// Returns how many were dropped.
+    fn drop_parked(&mut self, pred: impl Fn(&CachedGlyph) -> bool) -> usize {
+        let mut dropped = 0;
+        for generation in &mut self.parked {
+            let (mask_pages, color_pages) = (&mut self.mask_pages, &mut self.color_pages);
+            let mut free = |g: &CachedGlyph| {
+                let keep = !pred(g);
+                if !keep {
+                    let pages = if g.is_color { &mut *color_pages } else { &mut *mask_pages };
+                    pages.free(g.slot);
+                    dropped += 1;
+                }
+                keep
+            };
+            generation.cache.retain(|_, g| free(g));
+            generation.composed_cache.retain(|_, g| free(g));
+        }
+        self.parked.retain(|g| g.glyphs().next().is_some());
+        dropped
+    }
+
+    /// Drop a glyph from its cache and free its page space
+    fn remove_entry(&mut self, key: &EntryKey) {
+        let glyph = match key {
+            EntryKey::Single(k) => self.cache.remove(k),
+            EntryKey::Composed(k) => self.composed_cache.remove(k),
+        };
+        if let Some(glyph) = glyph {
+            let pages = if glyph.is_color { &mut self.color_pages } else { &mut self.mask_pages };
+            pages.free(glyph.slot);
+            self.layout_epoch = next_layout_epoch();
+        }
+    }
+
+    /// Repack the most fragmented atlas page, once holes left by evicted
+    /// glyphs waste a good part of it.  Meant for idle frames; returns true
+    /// if glyphs moved.
+    pub fn defragment(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        for color in [false, true] {
+            let pages = if color { &mut self.color_pages } else { &mut self.mask_pages };
+            let Some(layer) = pages.most_fragmented() else { continue };
+            // Glyphs of other scales on the page are not worth moving
+            self.drop_parked(|g| g.is_color == color && g.slot.layer == layer);
+            let pages = if color { &mut self.color_pages } else { &mut self.mask_pages };
+            pages.packers[layer as usize].reset();
+
+            // Re-place the page's glyphs tallest first, on the same page
+            // when they fit
+            let mut keys: Vec<(EntryKey, u32)> = self
+                .cache
+                .iter()
+                .filter(|(_, g)| g.is_color == color && g.slot.layer == layer)
+                .map(|(k, g)| (EntryKey::Single(k.clone()), g.slot.h))
+                .chain(
+                    self.composed_cache
+                        .iter()
+                        .filter(|(_, g)| g.is_color == color && g.slot.layer == layer)
+                        .map(|(k, g)| (EntryKey::Composed(k.clone()), g.slot.h)),
+                )
+                .collect();
+            keys.sort_by_key(|(_, h)| Reverse(*h));
+
+            let mut lost = Vec::new();
+            for (key, _) in keys {
+                let glyph = match &key {
+                    EntryKey::Single(k) => self.cache.get_mut(k),
+                    EntryKey::Composed(k) => self.composed_cache.get_mut(k),
+                };
+                let Some(glyph) = glyph else { continue };
+                let (w, h) = (glyph.slot.w, glyph.slot.h);
+                let slot = pages.packers[layer as usize]
+                    .allocate(w, h)
+                    .map(|(x, y)| AtlasSlot { layer, x, y, w, h })
+                    .or_else(|| pages.allocate(w, h));
+                let Some(slot) = slot else {
+                    lost.push(key);
+                    continue;
+                };
+                pages.write(queue, slot, &glyph.pixels);
+                if slot.layer != layer {
+                    glyph.bind_group = glyph_bind_group(
+                        device,
+                        &self.bind_group_layout,
+                        &pages.views[slot.layer as usize],
+                        &self.sampler,
+                    );
+                }
+                glyph.slot = slot;
+                glyph.uv = slot.uv();
+            }
+
+            // Glyphs that no longer fit anywhere are rasterized again on
+            // next use
+            for key in &lost {
+                match key {
+                    EntryKey::Single(k) => { self.cache.remove(k); }
+                    EntryKey::Composed(k) => { self.composed_cache.remove(k); }
+                }
+            }
+            if !lost.is_empty() {
+                self.prune_cluster_offsets();
+            }
+            log::debug!("glyph_atlas: repacked {} page {}, {} glyphs dropped",
+                if color { "color" } else { "mask" }, layer, lost.len());
+            self.layout_epoch = next_layout_epoch();
+            return true;
+        }
+        false
+    }
+
+    /// Shape a composed glyph's text and publish the caret offset of each
+    /// character, so the cursor can be placed inside ligatures.
+    fn publish_cluster_offsets(&mut self, key: &ComposedGlyphKey, face: Option<&Face>) {
+        let Some(shared) = self.cluster_offsets.clone() else { return };
+        let attrs = self.face_to_attrs(face);
+        let font_size = face.map(|f| f.font_size).unwrap_or(self.default_font_size);
+        let mut buffer = Buffer::new(&mut self.font_system, Metrics::new(font_size, font_size * 1.3));
+        buffer.set_size(&mut self.font_system, None, None);
+        buffer.set_text(&mut self.font_system, &key.text, attrs, cosmic_text::Shaping::Advanced);
+        buffer.shape_until_scroll(&mut self.font_system, false);
+
+        let clusters: Vec<ShapedCluster> = buffer
+            .layout_runs()
+            .flat_map(|run| run.glyphs.iter())
+            .map(|g| ShapedCluster {
+                start: g.start,
+                end: g.end,
+                x: g.x,
+                width: g.w,
+                rtl: g.level.is_rtl(),
+            })
+            .collect();
+        let offsets = ClusterOffsets::from_clusters(&key.text, clusters);
+        let Ok(mut map) = shared.lock() else { return };
+        map.insert(
+            ClusterKey {
+                text: (*key.text).into(),
+                face_id: key.face_id,
+                font_size_bits: key.font_size_bits,
+            },
+            offsets,
+        );
+    }
+
+    /// Drop published caret offsets whose composed glyph left the cache
+    fn prune_cluster_offsets(&self) {
+        let Some(shared) = self.cluster_offsets.as_ref() else { return };
+        if let Ok(mut map) = shared.lock() {
+            map.retain(|k, _| {
+                let key = ComposedGlyphKey {
+                    text: (*k.text).into(),
+                    face_id: k.face_id,
+                    font_size_bits: k.font_size_bits,
+                };
+                // Caret offsets do not depend on the scale factor
+                self.composed_cache.contains_key(&key)
+                    || self.parked.iter().any(|g| g.composed_cache.contains_key(&key))
+            });
+        }
     }
 
     /// Get a cached composed glyph without creating it
@@ -415,13 +886,40 @@ impl WgpuGlyphAtlas {
         );
         buffer.shape_until_scroll(&mut self.font_system, false);
 
+        let fallback = face
+            .and_then(|f| self.fallback_faces.get(&f.id))
+            .copied()
+            .unwrap_or(self.fallback_default);
+        let text_font = if fallback.enabled {
+            self.text_font_box(face, attrs, metrics)
+        } else {
+            None
+        };
+
         // For multi-glyph sequences (e.g. emoji ZWJ), we need to composite
         // all sub-glyphs into a single texture. Collect them first.
         let mut sub_glyphs: Vec<(f32, f32, u32, u32, Vec<u8>, bool)> = Vec::new();
 
         for run in buffer.layout_runs() {
             for glyph in run.glyphs.iter() {
-                let physical_glyph = glyph.physical((0.0, 0.0), self.scale_factor);
+                // Emoji and symbols from a fallback font are resized to the
+                // height of the text font and moved onto its baseline
+                let (fit_scale, fit_shift) = match text_font {
+                    Some((text_id, text_box))
+                        if glyph.font_id != text_id
+                            && !run.text[glyph.start..glyph.end].chars().any(char::is_alphanumeric) =>
+                    {
+                        match self.font_box(glyph.font_id) {
+                            Some(fallback_box) => {
+                                let (scale, shift) = fallback.fit(text_box, fallback_box);
+                                (scale, (shift * font_size + fallback.baseline_offset) * self.scale_factor)
+                            }
+                            None => (1.0, 0.0),
+                        }
+                    }
+                    _ => (1.0, 0.0),
+                };
+                let physical_glyph = glyph.physical((0.0, 0.0), self.scale_factor * fit_scale);
 
                 if let Some(image) = self
                     .swash_cache
@@ -435,7 +933,7 @@ impl WgpuGlyphAtlas {
                     }
 
                     let bearing_x = image.placement.left as f32;
-                    let bearing_y = image.placement.top as f32;
+                    let bearing_y = image.placement.top as f32 + fit_shift;
 
                     let font_family_str = face.map(|f| f.font_family.as_str()).unwrap_or("(none)");
                     log::debug!(
@@ -518,36 +1016,21 @@ impl WgpuGlyphAtlas {
                         continue;
                     }
                     let dst_idx = ((dy as u32 * total_w + dx as u32) * bpp) as usize;
-                    if *is_color {
+                    let src = if *is_color {
                         // RGBA source
                         let src_idx = ((py * *w + px) * 4) as usize;
-                        if src_idx + 3 < data.len() {
-                            let sa = data[src_idx + 3] as u32;
-                            if sa > 0 {
-                                // Alpha composite (premultiplied)
-                                let da = composite[dst_idx + 3] as u32;
-                                let inv_sa = 255 - sa;
-                                composite[dst_idx] = ((data[src_idx] as u32 * sa + composite[dst_idx] as u32 * inv_sa) / 255) as u8;
-                                composite[dst_idx + 1] = ((data[src_idx + 1] as u32 * sa + composite[dst_idx + 1] as u32 * inv_sa) / 255) as u8;
-                                composite[dst_idx + 2] = ((data[src_idx + 2] as u32 * sa + composite[dst_idx + 2] as u32 * inv_sa) / 255) as u8;
-                                composite[dst_idx + 3] = (sa + da * inv_sa / 255) as u8;
-                            }
+                        match data.get(src_idx..src_idx + 4) {
+                            Some(p) => [p[0], p[1], p[2], p[3]],
+                            None => continue,
                         }
                     } else {
                         // Alpha mask source — treat as white text with alpha
-                        let src_idx = (py * *w + px) as usize;
-                        if src_idx < data.len() {
-                            let sa = data[src_idx] as u32;
-                            if sa > 0 {
-                                let da = composite[dst_idx + 3] as u32;
-                                let inv_sa = 255 - sa;
-                                composite[dst_idx] = ((255 * sa + composite[dst_idx] as u32 * inv_sa) / 255) as u8;
-                                composite[dst_idx + 1] = ((255 * sa + composite[dst_idx + 1] as u32 * inv_sa) / 255) as u8;
-                                composite[dst_idx + 2] = ((255 * sa + composite[dst_idx + 2] as u32 * inv_sa) / 255) as u8;
-                                composite[dst_idx + 3] = (sa + da * inv_sa / 255) as u8;
-                            }
+                        match data.get((py * *w + px) as usize) {
+                            Some(&a) => [255, 255, 255, a],
+                            None => continue,
                         }
-                    }
+                    };
+                    blend_over_srgb(&mut composite[dst_idx..dst_idx + 4], src);
                 }
             }
         }
@@ -556,6 +1039,73 @@ impl WgpuGlyphAtlas {
         Some((total_w, total_h, composite, min_x, -min_y, any_color || sub_glyphs.len() > 1))
     }
 
+    /// The font that draws plain text for `face`, with its (ascent, descent)
+    /// in em.  Found by shaping "M", which any text font covers.
+    fn text_font_box(
+        &mut self,
+        face: Option<&Face>,
+        attrs: Attrs<'static>,
+        metrics: Metrics,
+    ) -> Option<(cosmic_text::fontdb::ID, (f32, f32))> {
+        let key = face.map_or((String::new(), 400, false), |f| {
+            (f.font_family.clone(), f.font_weight, f.is_italic())
+        });
+        let id = match self.text_fonts.get(&key) {
+            Some(id) => *id,
+            None => {
+                let mut buffer = Buffer::new(&mut self.font_system, metrics);
+                buffer.set_text(&mut self.font_system, "M", attrs, cosmic_text::Shaping::Advanced);
+                buffer.shape_until_scroll(&mut self.font_system, false);
+                let id = buffer
+                    .layout_runs()
+                    .next()
+                    .and_then(|run| run.glyphs.first())
+                    .map(|g| g.font_id);
+                self.text_fonts.insert(key, id);
+                id
+            }
+        }?;
+        Some((id, self.font_box(id)?))
+    }
+
+    /// Character cell of a monospace font: (advance, line height, ascent)
+    /// in pixels at `font_size`, measured from the font that draws plain
+    /// text in `family`.
+    pub fn cell_metrics(&mut self, family: &str, font_size: f32) -> Option<(f32, f32, f32)> {
+        let mut face = Face::new(0);
+        face.font_family = family.to_string();
+        face.font_size = font_size;
+        face.font_weight = 400;
+        let attrs = self.face_to_attrs(Some(&face));
+        let metrics = Metrics::new(font_size, font_size);
+        let (id, (ascent, descent)) = self.text_font_box(Some(&face), attrs, metrics)?;
+        let font = self.font_system.get_font(id)?;
+        let font = font.as_swash();
+        let upem = font.metrics(&[]).units_per_em as f32;
+        let glyph = font.charmap().map('M');
+        let advance = font.glyph_metrics(&[]).advance_width(glyph) / upem * font_size;
+        if advance <= 0.0 {
+            return None;
+        }
+        Some((advance, ((ascent + descent) * font_size).ceil(), (ascent * font_size).round()))
+    }
+
+    /// (ascent, descent) of font `id` in em
+    fn font_box(&mut self, id: cosmic_text::fontdb::ID) -> Option<(f32, f32)> {
+        if let Some(b) = self.font_boxes.get(&id) {
+            return Some(*b);
+        }
+        let font = self.font_system.get_font(id)?;
+        let m = font.as_swash().metrics(&[]);
+        if m.units_per_em == 0 {
+            return None;
+        }
+        let upem = m.units_per_em as f32;
+        let b = (m.ascent / upem, m.descent.abs() / upem);
+        self.font_boxes.insert(id, b);
+        Some(b)
+    }
+
     /// Rasterize a single glyph and return pixel data (convenience wrapper)
     fn rasterize_glyph(
         &mut self,
@@ -612,21 +1162,116 @@ impl WgpuGlyphAtlas {
         self.cache.get(key)
     }
 
+    /// Drop the glyphs rasterized for `face_id`, at every scale, after
+    /// its font changed.  Returns how many were dropped.
+    pub fn invalidate_face(&mut self, face_id: u32) -> usize {
+        let keys: Vec<EntryKey> = self
+            .cache
+            .keys()
+            .filter(|k| k.face_id == face_id)
+            .map(|k| EntryKey::Single(k.clone()))
+            .chain(
+                self.composed_cache
+                    .keys()
+                    .filter(|k| k.face_id == face_id)
+                    .map(|k| EntryKey::Composed(k.clone())),
+            )
+            .collect();
+        let mut dropped = keys.len();
+        for key in &keys {
+            self.remove_entry(key);
+        }
+        for generation in &mut self.parked {
+            let (mask_pages, color_pages) = (&mut self.mask_pages, &mut self.color_pages);
+            let mut free = |matches: bool, g: &CachedGlyph| {
+                if matches {
+                    let pages = if g.is_color { &mut *color_pages } else { &mut *mask_pages };
+                    pages.free(g.slot);
+                    dropped += 1;
+                }
+                !matches
+            };
+            generation.cache.retain(|k, g| free(k.face_id == face_id, g));
+            generation.composed_cache.retain(|k, g| free(k.face_id == face_id, g));
+        }
+        self.parked.retain(|g| g.glyphs().next().is_some());
+        if dropped > 0 {
+            self.prune_cluster_offsets();
+        }
+        dropped
+    }
+
+    /// Current layout epoch; see `layout_epoch` on the struct
+    pub fn layout_epoch(&self) -> u64 {
+        self.layout_epoch
+    }
+
     /// Clear the cache
     pub fn clear(&mut self) {
+        self.layout_epoch = next_layout_epoch();
         self.cache.clear();
         self.composed_cache.clear();
+        self.parked.clear();
+        self.mask_pages.reset();
+        self.color_pages.reset();
+        self.prune_cluster_offsets();
     }
 
-    /// Update the scale factor and clear the cache so glyphs are
-    /// re-rasterized at the new DPI.
+    /// Update the scale factor, e.g. when the window moves to a monitor
+    /// with a different (possibly fractional) scale.  Glyphs of the old
+    /// scale are parked rather than thrown away, and glyphs parked for the
+    /// new scale are brought back.
     pub fn set_scale_factor(&mut self, scale_factor: f32) {
-        if (self.scale_factor - scale_factor).abs() > 0.001 {
-            self.scale_factor = scale_factor;
-            self.cache.clear();
-            self.composed_cache.clear();
-            log::info!("Glyph atlas: scale factor -> {}, cache cleared", scale_factor);
+        if (self.scale_factor - scale_factor).abs() <= 0.001 {
+            return;
         }
+        let current = ScaleGeneration {
+            scale_factor: self.scale_factor,
+            cache: std::mem::take(&mut self.cache),
+            composed_cache: std::mem::take(&mut self.composed_cache),
+        };
+        if current.glyphs().next().is_some() {
+            self.parked.push(current);
+        }
+        if let Some(i) = self
+            .parked
+            .iter()
+            .position(|g| (g.scale_factor - scale_factor).abs() <= 0.001)
+        {
+            let generation = self.parked.remove(i);
+            self.cache = generation.cache;
+            self.composed_cache = generation.composed_cache;
+        }
+        // Oldest generations go first
+        while self.parked.len() > MAX_PARKED_SCALES {
+            let oldest = self.parked.remove(0);
+            for glyph in oldest.glyphs() {
+                let pages = if glyph.is_color { &mut self.color_pages } else { &mut self.mask_pages };
+                pages.free(glyph.slot);
+            }
+        }
+        self.prune_cluster_offsets();
+        self.scale_factor = scale_factor;
+        self.layout_epoch = next_layout_epoch();
+        log::info!("Glyph atlas: scale factor -> {}, {} glyphs reused",
+            scale_factor, self.len());
+    }
+
+    /// Bytes of GPU memory held by the atlas pages
+    pub fn memory_usage(&self) -> usize {
+        [&self.mask_pages, &self.color_pages]
+            .iter()
+            .map(|p| p.packers.len() * (PAGE_SIZE * PAGE_SIZE * p.bytes_per_pixel) as usize)
+            .sum()
+    }
+
+    /// Drop mask and color glyphs not drawn this frame to make room
+    /// under a memory budget.  Pages are only released by the next
+    /// `defragment`.  Returns false if every glyph is in use.
+    pub fn trim(&mut self) -> bool {
+        let mask = self.evict_unused(false);
+        let color = self.evict_unused(true);
+        mask || color
     }
 
     /// Get the number of cached glyphs
@@ -669,7 +1314,162 @@ impl WgpuGlyphAtlas {
         // Evict stale composed glyphs (they're less likely to be reused)
         if self.composed_cache.len() > 256 {
             let cutoff = self.generation.saturating_sub(60);
-            self.composed_cache.retain(|_, v| v.last_accessed >= cutoff);
+            let stale: Vec<_> = self.composed_cache.iter()
+                .filter(|(_, v)| v.last_accessed < cutoff)
+                .map(|(k, _)| EntryKey::Composed(k.clone()))
+                .collect();
+            for key in &stale {
+                self.remove_entry(key);
+            }
+            self.prune_cluster_offsets();
         }
     }
 }
+
+/// Bind group sampling one atlas page
+fn glyph_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    view: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Glyph Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+/// Copy `width` x `height` pixels of `bpp` bytes into a buffer with a
+/// transparent gutter around them
+fn pad_pixels(data: &[u8], width: u32, height: u32, bpp: u32) -> Vec<u8> {
+    let row = (width * bpp) as usize;
+    let padded_row = ((width + 2 * GUTTER) * bpp) as usize;
+    let mut out = vec![0u8; padded_row * (height + 2 * GUTTER) as usize];
+    for y in 0..height as usize {
+        let Some(src) = data.get(y * row..(y + 1) * row) else { break };
+        let start = (y + GUTTER as usize) * padded_row + (GUTTER * bpp) as usize;
+        out[start..start + row].copy_from_slice(src);
+    }
+    out
+}
+
+/// Turn a coverage bitmap into a signed distance field `spread` texels
+/// larger on every side.  Texels hold 0.5 on the outline, rising to 1.0
+/// `spread` texels inside and falling to 0.0 `spread` texels outside.
+fn coverage_to_sdf(coverage: &[u8], width: u32, height: u32, spread: u32) -> Vec<u8> {
+    let (w, h, s) = (width as i32, height as i32, spread as i32);
+    let at = |x: i32, y: i32| -> f32 {
+        if x < 0 || y < 0 || x >= w || y >= h {
+            0.0
+        } else {
+            coverage.get((y * w + x) as usize).map_or(0.0, |&c| c as f32 / 255.0)
+        }
+    };
+    let (out_w, out_h) = (w + 2 * s, h + 2 * s);
+    let mut out = Vec::with_capacity((out_w * out_h) as usize);
+    for oy in 0..out_h {
+        for ox in 0..out_w {
+            let (x, y) = (ox - s, oy - s);
+            let inside = at(x, y) >= 0.5;
+            // Nearest texel on the other side of the outline; partly
+            // covered texels place the outline inside them
+            let mut dist = (at(x, y) - 0.5).abs().min(0.5);
+            if dist >= 0.5 {
+                dist = s as f32;
+                for dy in -s..=s {
+                    for dx in -s..=s {
+                        let c = at(x + dx, y + dy);
+                        if (c >= 0.5) != inside {
+                            let d = ((dx * dx + dy * dy) as f32).sqrt() - (c - 0.5).abs();
+                            dist = dist.min(d.max(0.0));
+                        }
+                    }
+                }
+            }
+            let signed = if inside { dist } else { -dist };
+            let v = 0.5 + signed / (2.0 * s as f32);
+            out.push((v.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    out
+}
+
+/// Composite straight-alpha sRGB `src` over straight-alpha sRGB `dst`.
+///
+/// Colors are mixed in linear light and the result is stored with straight
+/// (non-premultiplied) alpha, matching how the `Rgba8UnormSrgb` atlas
+/// texture is sampled and blended.
+fn blend_over_srgb(dst: &mut [u8], src: [u8; 4]) {
+    let sa = src[3] as f32 / 255.0;
+    if sa <= 0.0 {
+        return;
+    }
+    let da = dst[3] as f32 / 255.0;
+    let out_a = sa + da * (1.0 - sa);
+    for i in 0..3 {
+        let s = Color::srgb_component_to_linear(src[i] as f32 / 255.0);
+        let d = Color::srgb_component_to_linear(dst[i] as f32 / 255.0);
+        let c = (s * sa + d * da * (1.0 - sa)) / out_a;
+        dst[i] = (Color::linear_component_to_srgb(c) * 255.0).round() as u8;
+    }
+    dst[3] = (out_a * 255.0).round() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blend_over_srgb_keeps_straight_alpha() {
+        // Half-covered white over nothing stays white, not premultiplied grey
+        let mut dst = [0, 0, 0, 0];
+        blend_over_srgb(&mut dst, [255, 255, 255, 128]);
+        assert_eq!(dst, [255, 255, 255, 128]);
+
+        // Half white over opaque black mixes in linear light (brighter than 50% sRGB)
+        let mut dst = [0, 0, 0, 255];
+        blend_over_srgb(&mut dst, [255, 255, 255, 128]);
+        assert!(dst[0] > 180 && dst[0] < 195, "got {}", dst[0]);
+        assert_eq!(dst[3], 255);
+    }
+
+    #[test]
+    fn test_coverage_to_sdf() {
+        // A 4x4 solid square grows by the spread on every side
+        let sdf = coverage_to_sdf(&[255; 16], 4, 4, 2);
+        assert_eq!(sdf.len(), 8 * 8);
+        let at = |x: usize, y: usize| sdf[y * 8 + x];
+        // Far outside is empty, the middle is deep inside
+        assert_eq!(at(0, 0), 0);
+        assert!(at(4, 4) > 190, "got {}", at(4, 4));
+        // Texels either side of the outline straddle one half
+        assert!(at(1, 4) < 128 && at(2, 4) > 128, "{} {}", at(1, 4), at(2, 4));
+        // Symmetric about the square's center
+        assert_eq!(at(2, 4), at(5, 4));
+    }
+
+    #[test]
+    fn test_padded_glyph_uv_skips_gutter() {
+        let padded = pad_pixels(&[1, 2, 3, 4], 2, 2, 1);
+        assert_eq!(padded, vec![
+            0, 0, 0, 0,
+            0, 1, 2, 0,
+            0, 3, 4, 0,
+            0, 0, 0, 0,
+        ]);
+
+        let slot = AtlasSlot { layer: 3, x: 10, y: 20, w: 4, h: 4 };
+        let page = PAGE_SIZE as f32;
+        assert_eq!(slot.uv(), [11.0 / page, 21.0 / page, 13.0 / page, 23.0 / page]);
+    }
+}
@@ -0,0 +1,128 @@
+//! GPU memory budget shared by the texture caches.
+//!
+//! The glyph atlas, image cache, video textures and transition snapshots
+//! each manage their own textures.  After every frame the render thread
+//! adds up what they hold and, when the total is over the budget, frees
+//! memory in order of how cheap it is to lose:
+//!
+//! 1. snapshots of old window contents kept for transitions,
+//! 2. images not drawn in the current frame,
+//! 3. images that are on screen,
+//! 4. glyphs not drawn in the current frame.
+//!
+//! Video textures are counted but never evicted, since a playing video
+//! would upload a new one on its next frame anyway.  The last measured
+//! usage is kept process-wide so Emacs can query it.
+
+use std::sync::Mutex;
+
+/// Default budget in megabytes
+pub const DEFAULT_BUDGET_MB: usize = 512;
+
+/// Bytes of GPU memory held by each cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuMemoryUsage {
+    pub glyphs: usize,
+    /// All image textures, including the visible ones
+    pub images: usize,
+    /// Image textures drawn in the current frame
+    pub visible_images: usize,
+    pub videos: usize,
+    /// Offscreen frame buffers and transition snapshots
+    pub snapshots: usize,
+    pub budget: usize,
+}
+
+impl GpuMemoryUsage {
+    pub fn total(&self) -> usize {
+        self.glyphs + self.images + self.videos + self.snapshots
+    }
+}
+
+/// How many bytes to free from each cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvictionPlan {
+    pub snapshots: usize,
+    pub offscreen_images: usize,
+    pub visible_images: usize,
+    /// Trim unused glyphs from the atlas
+    pub glyphs: bool,
+}
+
+impl EvictionPlan {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Decide what to free to bring `usage` back under its budget.
+/// `evictable_snapshots` is the part of `usage.snapshots` that can be
+/// dropped (the offscreen buffers are always needed).
+pub fn plan_eviction(usage: &GpuMemoryUsage, evictable_snapshots: usize) -> EvictionPlan {
+    let mut plan = EvictionPlan::default();
+    let mut excess = usage.total().saturating_sub(usage.budget);
+    if excess == 0 {
+        return plan;
+    }
+
+    let mut take = |available: usize| {
+        let n = excess.min(available);
+        excess -= n;
+        n
+    };
+    plan.snapshots = take(evictable_snapshots.min(usage.snapshots));
+    plan.offscreen_images = take(usage.images.saturating_sub(usage.visible_images));
+    plan.visible_images = take(usage.visible_images);
+    plan.glyphs = excess > 0;
+    plan
+}
+
+static LAST_USAGE: Mutex<GpuMemoryUsage> = Mutex::new(GpuMemoryUsage {
+    glyphs: 0,
+    images: 0,
+    visible_images: 0,
+    videos: 0,
+    snapshots: 0,
+    budget: DEFAULT_BUDGET_MB * 1024 * 1024,
+});
+
+/// Record the usage measured after a frame.
+pub fn publish(usage: GpuMemoryUsage) {
+    *LAST_USAGE.lock().unwrap_or_else(|e| e.into_inner()) = usage;
+}
+
+/// Usage measured after the most recent frame.
+pub fn last_usage() -> GpuMemoryUsage {
+    *LAST_USAGE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction_order() {
+        let usage = GpuMemoryUsage {
+            glyphs: 40,
+            images: 50,
+            visible_images: 20,
+            videos: 10,
+            snapshots: 30,
+            budget: 100,
+        };
+        // 130 over a budget of 100: snapshots go first
+        assert_eq!(
+            plan_eviction(&usage, 25),
+            EvictionPlan { snapshots: 25, offscreen_images: 5, visible_images: 0, glyphs: false }
+        );
+
+        let tight = GpuMemoryUsage { budget: 20, ..usage };
+        assert_eq!(
+            plan_eviction(&tight, 25),
+            EvictionPlan { snapshots: 25, offscreen_images: 30, visible_images: 20, glyphs: true }
+        );
+
+        let roomy = GpuMemoryUsage { budget: 200, ..usage };
+        assert!(plan_eviction(&roomy, 25).is_empty());
+    }
+}
@@ -6,23 +6,30 @@
 //! - GPU texture upload when ready
 //! - LRU cache with memory limits
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
-use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 #[cfg(target_os = "linux")]
 use super::external_buffer::DmaBufBuffer;
+use crate::core::error_report::{self, ErrorKind};
+use crate::core::image_swap::{ImageSwap, ImageSwapEffect};
+use crate::core::image_view::ImageTransform;
+use crate::core::types::ImageSampling;
 
 /// Maximum texture dimension (width or height)
 const MAX_TEXTURE_SIZE: u32 = 4096;
 
-/// Maximum total cache memory in bytes (64MB)
+/// Default maximum total cache memory in bytes (64MB)
 const MAX_CACHE_MEMORY: usize = 64 * 1024 * 1024;
 
+/// Bytes of decoded images uploaded per frame; the rest wait for idle time
+const UPLOAD_BUDGET_PER_FRAME: usize = 16 * 1024 * 1024;
+
 /// Get number of decoder threads (use all available CPU cores)
 fn decoder_thread_count() -> usize {
     std::thread::available_parallelism()
@@ -48,6 +55,8 @@ pub struct CachedImage {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub bind_group: wgpu::BindGroup,
+    /// Same texture with nearest-neighbor sampling
+    pub nearest_bind_group: wgpu::BindGroup,
     pub width: u32,
     pub height: u32,
     /// Memory size in bytes
@@ -71,8 +80,6 @@ pub struct ImageDimensions {
 
 /// Async image cache
 pub struct ImageCache {
-    /// Next image ID
-    next_id: AtomicU32,
     /// Cached textures: id -> CachedImage
     textures: HashMap<u32, CachedImage>,
     /// Image states: id -> state
@@ -81,14 +88,31 @@ pub struct ImageCache {
     pending_dimensions: HashMap<u32, ImageDimensions>,
     /// Channel to receive decoded images
     decoded_rx: mpsc::Receiver<DecodedImage>,
+    /// Decoded images not uploaded yet because a frame's budget ran out
+    deferred: VecDeque<DecodedImage>,
     /// Channel to send decode requests
     decode_tx: mpsc::Sender<DecodeRequest>,
     /// Bind group layout for image textures
     bind_group_layout: wgpu::BindGroupLayout,
     /// Sampler for image textures
     sampler: wgpu::Sampler,
+    /// Sampler for images drawn without smoothing
+    nearest_sampler: wgpu::Sampler,
+    /// Sampling of images that do not use `ImageSampling::Auto`
+    sampling: HashMap<u32, ImageSampling>,
+    /// Zoom and pan of images opened for viewing
+    views: HashMap<u32, ImageTransform>,
+    /// Pictures going out of images whose picture was swapped, and how
+    swaps: HashMap<u32, (CachedImage, ImageSwap)>,
+    /// Swaps waiting for their new picture: (id, new id, effect, duration)
+    pending_swaps: Vec<(u32, u32, ImageSwapEffect, Duration)>,
     /// Total cached memory
     total_memory: usize,
+    /// Memory limit before old textures are evicted
+    max_memory: usize,
+    /// CPU-side source and size limits of each image, kept so textures
+    /// can be rebuilt on a new device after the old one is lost
+    sources: HashMap<u32, (ImageSource, u32, u32)>,
 }
 
 /// Request to decode an image
@@ -100,23 +124,30 @@ struct DecodeRequest {
 }
 
 /// Image source
+#[derive(Clone)]
 enum ImageSource {
     File(String),
-    Data(Vec<u8>),
+    Data(Arc<[u8]>),
     /// Raw ARGB32 pixel data (A,R,G,B byte order, 4 bytes per pixel)
     RawArgb32 {
-        data: Vec<u8>,
+        data: Arc<[u8]>,
         width: u32,
         height: u32,
         stride: u32,
     },
     /// Raw RGB24 pixel data (R,G,B byte order, 3 bytes per pixel)
     RawRgb24 {
-        data: Vec<u8>,
+        data: Arc<[u8]>,
         width: u32,
         height: u32,
         stride: u32,
     },
+    /// Already decoded RGBA pixels, uploaded as is
+    Rgba {
+        data: Arc<[u8]>,
+        width: u32,
+        height: u32,
+    },
 }
 
 impl ImageCache {
@@ -156,6 +187,16 @@ impl ImageCache {
             mipmap_filter: wgpu::FilterMode::Linear,
             ..Default::default()
         });
+        let nearest_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Image Nearest Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
 
         // Create channels for async decoding
         let (decode_tx, decode_rx) = mpsc::channel::<DecodeRequest>();
@@ -176,15 +217,22 @@ impl ImageCache {
         }
 
         Self {
-            next_id: AtomicU32::new(1),
             textures: HashMap::new(),
             states: HashMap::new(),
             pending_dimensions: HashMap::new(),
             decoded_rx,
+            deferred: VecDeque::new(),
             decode_tx,
             bind_group_layout,
             sampler,
+            nearest_sampler,
+            sampling: HashMap::new(),
+            views: HashMap::new(),
+            swaps: HashMap::new(),
+            pending_swaps: Vec::new(),
             total_memory: 0,
+            max_memory: MAX_CACHE_MEMORY,
+            sources: HashMap::new(),
         }
     }
 
@@ -214,19 +262,27 @@ impl ImageCache {
                         }
                         ImageSource::RawArgb32 { data, width, height, stride } => {
                             Self::convert_argb32_to_rgba(&data, width, height, stride, request.max_width, request.max_height)
+                                .ok_or_else(|| "invalid ARGB32 pixel data".to_string())
                         }
                         ImageSource::RawRgb24 { data, width, height, stride } => {
                             Self::convert_rgb24_to_rgba(&data, width, height, stride, request.max_width, request.max_height)
+                                .ok_or_else(|| "invalid RGB24 pixel data".to_string())
                         }
+                        ImageSource::Rgba { data, width, height } => Ok((width, height, data.to_vec())),
                     };
 
-                    if let Some((width, height, data)) = result {
-                        let _ = tx.send(DecodedImage {
-                            id: request.id,
-                            width,
-                            height,
-                            data,
-                        });
+                    match result {
+                        Ok((width, height, data)) => {
+                            let _ = tx.send(DecodedImage {
+                                id: request.id,
+                                width,
+                                height,
+                                data,
+                            });
+                        }
+                        Err(msg) => {
+                            error_report::error(ErrorKind::Image, Some(request.id), msg);
+                        }
                     }
                 }
                 Err(_) => {
@@ -239,15 +295,15 @@ impl ImageCache {
     }
 
     /// Decode image file with size constraints
-    fn decode_file(path: &str, max_width: u32, max_height: u32) -> Option<(u32, u32, Vec<u8>)> {
-        let img = image::open(path).ok()?;
-        Self::process_image(img, max_width, max_height)
+    fn decode_file(path: &str, max_width: u32, max_height: u32) -> Result<(u32, u32, Vec<u8>), String> {
+        let img = image::open(path).map_err(|e| format!("cannot decode {}: {}", path, e))?;
+        Self::process_image(img, max_width, max_height).ok_or_else(|| format!("cannot convert {}", path))
     }
 
     /// Decode image data with size constraints
-    fn decode_data(data: &[u8], max_width: u32, max_height: u32) -> Option<(u32, u32, Vec<u8>)> {
-        let img = image::load_from_memory(data).ok()?;
-        Self::process_image(img, max_width, max_height)
+    fn decode_data(data: &[u8], max_width: u32, max_height: u32) -> Result<(u32, u32, Vec<u8>), String> {
+        let img = image::load_from_memory(data).map_err(|e| format!("cannot decode image data: {}", e))?;
+        Self::process_image(img, max_width, max_height).ok_or_else(|| "cannot convert image data".to_string())
     }
 
     /// Process decoded image: resize if needed, convert to RGBA
@@ -436,7 +492,9 @@ impl ImageCache {
     /// Load image from file (async)
     /// Returns image ID immediately, texture loads in background
     pub fn load_file(&mut self, path: &str, max_width: u32, max_height: u32) -> u32 {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
         self.load_file_with_id(id, path, max_width, max_height);
         id
     }
@@ -452,10 +510,16 @@ impl ImageCache {
         }
 
         // Queue for async decode
+        self.queue_decode(id, ImageSource::File(path.to_string()), max_width, max_height);
+    }
+
+    /// Queue `source` for decoding under `id`, remembering it for reloads
+    fn queue_decode(&mut self, id: u32, source: ImageSource, max_width: u32, max_height: u32) {
         self.states.insert(id, ImageState::Pending);
+        self.sources.insert(id, (source.clone(), max_width, max_height));
         let _ = self.decode_tx.send(DecodeRequest {
             id,
-            source: ImageSource::File(path.to_string()),
+            source,
             max_width,
             max_height,
         });
@@ -464,12 +528,14 @@ impl ImageCache {
     /// Allocate the next available image ID without loading anything.
     /// Used by threaded mode to pre-allocate IDs before sending commands.
     pub fn allocate_id(&self) -> u32 {
-        self.next_id.fetch_add(1, Ordering::SeqCst)
+        crate::core::handle::IMAGES.alloc().unwrap_or(0)
     }
 
     /// Load image from data (async)
     pub fn load_data(&mut self, data: &[u8], max_width: u32, max_height: u32) -> u32 {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
 
         // Query dimensions first (fast)
         if let Some(dims) = Self::query_data_dimensions(data) {
@@ -478,13 +544,7 @@ impl ImageCache {
         }
 
         // Queue for async decode
-        self.states.insert(id, ImageState::Pending);
-        let _ = self.decode_tx.send(DecodeRequest {
-            id,
-            source: ImageSource::Data(data.to_vec()),
-            max_width,
-            max_height,
-        });
+        self.queue_decode(id, ImageSource::Data(data.into()), max_width, max_height);
 
         id
     }
@@ -501,7 +561,9 @@ impl ImageCache {
         max_width: u32,
         max_height: u32,
     ) -> u32 {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
 
         // Store pending dimensions immediately (we know the exact size)
         let (w, h) = Self::constrain_dimensions(width, height, max_width, max_height);
@@ -509,18 +571,13 @@ impl ImageCache {
             .insert(id, ImageDimensions { width: w, height: h });
 
         // Queue for async conversion
-        self.states.insert(id, ImageState::Pending);
-        let _ = self.decode_tx.send(DecodeRequest {
-            id,
-            source: ImageSource::RawArgb32 {
-                data: data.to_vec(),
-                width,
-                height,
-                stride,
-            },
-            max_width,
-            max_height,
-        });
+        let source = ImageSource::RawArgb32 {
+            data: data.into(),
+            width,
+            height,
+            stride,
+        };
+        self.queue_decode(id, source, max_width, max_height);
 
         id
     }
@@ -537,7 +594,9 @@ impl ImageCache {
         max_width: u32,
         max_height: u32,
     ) -> u32 {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
 
         // Store pending dimensions immediately (we know the exact size)
         let (w, h) = Self::constrain_dimensions(width, height, max_width, max_height);
@@ -545,22 +604,39 @@ impl ImageCache {
             .insert(id, ImageDimensions { width: w, height: h });
 
         // Queue for async conversion
-        self.states.insert(id, ImageState::Pending);
-        let _ = self.decode_tx.send(DecodeRequest {
-            id,
-            source: ImageSource::RawRgb24 {
-                data: data.to_vec(),
-                width,
-                height,
-                stride,
-            },
-            max_width,
-            max_height,
-        });
+        let source = ImageSource::RawRgb24 {
+            data: data.into(),
+            width,
+            height,
+            stride,
+        };
+        self.queue_decode(id, source, max_width, max_height);
 
         id
     }
 
+    /// Upload already-decoded RGBA pixels under a pre-allocated ID,
+    /// replacing any previous texture with that ID.
+    pub fn insert_rgba(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        id: u32,
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) {
+        if data.len() < (width as usize) * (height as usize) * 4 {
+            self.states.insert(id, ImageState::Failed("RGBA data too short".into()));
+            return;
+        }
+        self.free(id);
+        let data: Arc<[u8]> = data.into();
+        self.upload_texture(device, queue, id, width, height, &data);
+        self.sources.insert(id, (ImageSource::Rgba { data, width, height }, 0, 0));
+        self.evict_if_needed();
+    }
+
     /// Import image from DMA-BUF (zero-copy if supported)
     #[cfg(target_os = "linux")]
     pub fn import_dmabuf(
@@ -569,26 +645,16 @@ impl ImageCache {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
     ) -> u32 {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
         let (width, height) = dmabuf.dimensions();
 
         // Try zero-copy import
         if let Some(texture) = dmabuf.to_wgpu_texture(device, queue) {
             let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("DMA-BUF Image Bind Group"),
-                layout: &self.bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&self.sampler),
-                    },
-                ],
-            });
+            let bind_group = self.create_bind_group(device, &view, &self.sampler);
+            let nearest_bind_group = self.create_bind_group(device, &view, &self.nearest_sampler);
 
             let memory_size = (width * height * 4) as usize;
             self.total_memory += memory_size;
@@ -597,6 +663,7 @@ impl ImageCache {
                 texture,
                 view,
                 bind_group,
+                nearest_bind_group,
                 width,
                 height,
                 memory_size,
@@ -634,22 +701,91 @@ impl ImageCache {
 
     /// Process pending decoded images (call each frame)
     pub fn process_pending(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.upload_decoded(device, queue, UPLOAD_BUDGET_PER_FRAME);
+    }
+
+    /// Upload every decoded image still waiting, however large.  Meant
+    /// for idle time.  Returns true if anything was uploaded.
+    pub fn flush_pending(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        self.upload_decoded(device, queue, usize::MAX)
+    }
+
+    /// Upload decoded images in arrival order until `budget` bytes have
+    /// been uploaded.  At least one image is uploaded per call, so a
+    /// large image is never starved.
+    fn upload_decoded(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, budget: usize) -> bool {
         // Drain decoded images from channel
         while let Ok(decoded) = self.decoded_rx.try_recv() {
-            self.upload_texture(device, queue, decoded);
+            self.deferred.push_back(decoded);
+        }
+
+        let mut spent = 0;
+        let mut uploaded = false;
+        while let Some(decoded) = self.deferred.front() {
+            // Drop results for images freed while they were decoding
+            if !self.sources.contains_key(&decoded.id) {
+                self.deferred.pop_front();
+                continue;
+            }
+            if uploaded && spent + decoded.data.len() > budget {
+                break;
+            }
+            let decoded = self.deferred.pop_front().expect("checked by front");
+            spent += decoded.data.len();
+            self.upload_texture(device, queue, decoded.id, decoded.width, decoded.height, &decoded.data);
+            uploaded = true;
+        }
+        if uploaded {
+            self.apply_swaps(Instant::now());
         }
 
         // Evict if over memory limit
         self.evict_if_needed();
+        uploaded
+    }
+
+    /// Whether decoded images are waiting to be uploaded
+    pub fn has_deferred(&self) -> bool {
+        !self.deferred.is_empty()
+    }
+
+    fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Image Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
     }
 
     /// Upload decoded image to GPU texture
-    fn upload_texture(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, decoded: DecodedImage) {
+    fn upload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        id: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Image Texture"),
             size: wgpu::Extent3d {
-                width: decoded.width,
-                height: decoded.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
@@ -667,68 +803,90 @@ impl ImageCache {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            &decoded.data,
+            data,
             wgpu::ImageDataLayout {
                 offset: 0,
-                bytes_per_row: Some(decoded.width * 4),
-                rows_per_image: Some(decoded.height),
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
             },
             wgpu::Extent3d {
-                width: decoded.width,
-                height: decoded.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
         );
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.create_bind_group(device, &view, &self.sampler);
+        let nearest_bind_group = self.create_bind_group(device, &view, &self.nearest_sampler);
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Image Bind Group"),
-            layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.sampler),
-                },
-            ],
-        });
-
-        let memory_size = (decoded.width * decoded.height * 4) as usize;
+        let memory_size = (width * height * 4) as usize;
         self.total_memory += memory_size;
 
-        self.textures.insert(decoded.id, CachedImage {
+        self.textures.insert(id, CachedImage {
             texture,
             view,
             bind_group,
-            width: decoded.width,
-            height: decoded.height,
+            nearest_bind_group,
+            width,
+            height,
             memory_size,
         });
 
-        self.states.insert(decoded.id, ImageState::Ready);
-        self.pending_dimensions.remove(&decoded.id);
+        self.states.insert(id, ImageState::Ready);
+        self.pending_dimensions.remove(&id);
 
         log::debug!("Uploaded image {} ({}x{}, {}KB)",
-                   decoded.id, decoded.width, decoded.height, memory_size / 1024);
+                   id, width, height, memory_size / 1024);
+    }
+
+    /// Set the memory limit, evicting old textures if it is exceeded
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.max_memory = bytes;
+        self.evict_if_needed();
     }
 
     /// Evict old textures if over memory limit
     fn evict_if_needed(&mut self) {
-        // Simple strategy: remove oldest entries until under limit
-        while self.total_memory > MAX_CACHE_MEMORY && !self.textures.is_empty() {
+        let excess = self.total_memory.saturating_sub(self.max_memory);
+        if excess > 0 {
+            self.evict(excess, &HashSet::new());
+        }
+    }
+
+    /// Evict textures, oldest first, until `bytes` have been freed.
+    /// Images in `keep` are spared.  Returns the bytes freed.
+    pub fn evict(&mut self, bytes: usize, keep: &HashSet<u32>) -> usize {
+        let mut freed = 0;
+        while freed < bytes {
             // Find smallest ID (oldest)
-            if let Some(&id) = self.textures.keys().min() {
-                if let Some(cached) = self.textures.remove(&id) {
-                    self.total_memory -= cached.memory_size;
-                    self.states.remove(&id);
-                    log::debug!("Evicted image {} to free {}KB", id, cached.memory_size / 1024);
-                }
+            let Some(id) = self.textures.keys().filter(|id| !keep.contains(id)).min().copied() else {
+                break;
+            };
+            if let Some(cached) = self.textures.remove(&id) {
+                self.total_memory -= cached.memory_size;
+                self.states.remove(&id);
+                self.sources.remove(&id);
+                freed += cached.memory_size;
+                log::debug!("Evicted image {} to free {}KB", id, cached.memory_size / 1024);
             }
         }
+        freed
+    }
+
+    /// Bytes held by image textures
+    pub fn memory_usage(&self) -> usize {
+        self.total_memory
+    }
+
+    /// Number of images with a texture
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Bytes held by the textures of the images in `ids`
+    pub fn memory_of(&self, ids: &HashSet<u32>) -> usize {
+        ids.iter().filter_map(|id| self.textures.get(id)).map(|c| c.memory_size).sum()
     }
 
     /// Get cached image if ready
@@ -736,6 +894,99 @@ impl ImageCache {
         self.textures.get(&id)
     }
 
+    /// Set how an image is filtered when drawn at another size
+    pub fn set_sampling(&mut self, id: u32, sampling: ImageSampling) {
+        if sampling == ImageSampling::Auto {
+            self.sampling.remove(&id);
+        } else {
+            self.sampling.insert(id, sampling);
+        }
+    }
+
+    /// Let an image be zoomed and panned with the mouse, or stop it and
+    /// show all of it again
+    pub fn set_interactive(&mut self, id: u32, interactive: bool) {
+        if interactive {
+            self.views.entry(id).or_default();
+        } else {
+            self.views.remove(&id);
+        }
+    }
+
+    /// The part of an interactive image shown, None for other images
+    pub fn transform(&self, id: u32) -> Option<&ImageTransform> {
+        self.views.get(&id)
+    }
+
+    pub fn transform_mut(&mut self, id: u32) -> Option<&mut ImageTransform> {
+        self.views.get_mut(&id)
+    }
+
+    /// Give image `id` the picture of image `new_id`, which goes away,
+    /// moving from the old picture to the new by `effect` over
+    /// `duration`.  If `new_id` is still loading, the swap waits for it.
+    pub fn swap(&mut self, id: u32, new_id: u32, effect: ImageSwapEffect, duration: Duration) {
+        self.pending_swaps.retain(|(pending, ..)| *pending != id);
+        self.pending_swaps.push((id, new_id, effect, duration));
+        self.apply_swaps(Instant::now());
+    }
+
+    /// Carry out the swaps whose new picture is ready, and drop those
+    /// whose new picture failed
+    fn apply_swaps(&mut self, now: Instant) {
+        let pending = std::mem::take(&mut self.pending_swaps);
+        for (id, new_id, effect, duration) in pending {
+            if matches!(self.states.get(&new_id), Some(ImageState::Failed(_)) | None) {
+                log::warn!("Image {} not swapped: image {} did not load", id, new_id);
+                self.free(new_id);
+                continue;
+            }
+            let Some(new) = self.textures.remove(&new_id) else {
+                self.pending_swaps.push((id, new_id, effect, duration));
+                continue;
+            };
+            if let Some(source) = self.sources.remove(&new_id) {
+                self.sources.insert(id, source);
+            }
+            self.states.remove(&new_id);
+            self.pending_dimensions.remove(&new_id);
+            self.sampling.remove(&new_id);
+            self.views.remove(&new_id);
+            self.states.insert(id, ImageState::Ready);
+            self.pending_dimensions.remove(&id);
+            if let Some(old) = self.textures.insert(id, new) {
+                self.total_memory -= old.memory_size;
+                if !duration.is_zero() {
+                    self.swaps.insert(id, (old, ImageSwap { effect, started: now, duration }));
+                }
+            }
+        }
+    }
+
+    /// The picture going out of image `id` and its swap, while one is
+    /// under way
+    pub fn swap_state(&self, id: u32) -> Option<(&CachedImage, &ImageSwap)> {
+        self.swaps.get(&id).map(|(old, swap)| (old, swap))
+    }
+
+    /// Drop the pictures of swaps finished by `now`.  Returns whether
+    /// any swap is still under way.
+    pub fn finish_swaps(&mut self, now: Instant) -> bool {
+        self.swaps.retain(|_, (_, swap)| !swap.finished(now));
+        !self.swaps.is_empty()
+    }
+
+    /// Bind group for drawing a ready image at `width` x `height`
+    pub fn bind_group(&self, id: u32, width: f32, height: f32) -> Option<&wgpu::BindGroup> {
+        let cached = self.textures.get(&id)?;
+        let sampling = self.sampling.get(&id).copied().unwrap_or_default();
+        Some(if sampling.smooth(cached.width, cached.height, width, height) {
+            &cached.bind_group
+        } else {
+            &cached.nearest_bind_group
+        })
+    }
+
     /// Get image dimensions (pending or loaded)
     pub fn get_dimensions(&self, id: u32) -> Option<ImageDimensions> {
         // Check loaded textures first
@@ -766,6 +1017,11 @@ impl ImageCache {
         }
         self.states.remove(&id);
         self.pending_dimensions.remove(&id);
+        self.sources.remove(&id);
+        self.sampling.remove(&id);
+        self.views.remove(&id);
+        self.swaps.remove(&id);
+        self.pending_swaps.retain(|(pending, ..)| *pending != id);
     }
 
     /// Clear entire cache
@@ -773,8 +1029,37 @@ impl ImageCache {
         self.textures.clear();
         self.states.clear();
         self.pending_dimensions.clear();
+        self.deferred.clear();
+        self.sources.clear();
+        self.sampling.clear();
+        self.views.clear();
+        self.swaps.clear();
+        self.pending_swaps.clear();
         self.total_memory = 0;
     }
+
+    /// Take over the images of a cache whose device was lost, decoding
+    /// them again under the same IDs.  Images without a CPU-side source
+    /// (DMA-BUF imports) cannot be rebuilt and are dropped.
+    pub fn reload_from(&mut self, lost: ImageCache) {
+        self.max_memory = lost.max_memory;
+        self.sampling = lost.sampling;
+        self.views = lost.views;
+        // Pictures going out were on the lost device; swaps just end
+        self.pending_swaps = lost.pending_swaps;
+        for (id, (source, max_width, max_height)) in lost.sources {
+            if let Some(dims) = lost
+                .textures
+                .get(&id)
+                .map(|c| ImageDimensions { width: c.width, height: c.height })
+                .or_else(|| lost.pending_dimensions.get(&id).copied())
+            {
+                self.pending_dimensions.insert(id, dims);
+            }
+            self.queue_decode(id, source, max_width, max_height);
+        }
+        log::info!("Reloading {} images on the new device", self.sources.len());
+    }
 }
 
 #[cfg(test)]
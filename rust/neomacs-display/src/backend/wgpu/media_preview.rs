@@ -0,0 +1,241 @@
+//! Waveform images and thumbnail strips of media files
+//!
+//! Works like PdfCache: requests go to a worker thread, which decodes the
+//! file with GStreamer apart from any playing video, and the render
+//! thread uploads what comes back as ordinary images under IDs the Emacs
+//! thread allocated when it asked.
+//! - Waveforms decode the whole audio track as fast as possible, mixed
+//!   down to mono at a low rate, and draw its peaks
+//! - Thumbnails preroll the video paused and seek to each point in turn
+
+use std::sync::mpsc;
+use std::thread;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+
+use crate::core::error_report::{self, ErrorKind};
+use crate::core::media_preview::{self, WaveformPeaks};
+
+/// Rate the audio is resampled to for waveforms; peaks don't need more
+const WAVEFORM_RATE: i32 = 8000;
+/// How long to wait for the pipeline to preroll or finish a seek
+const PREROLL_TIMEOUT_SECONDS: u64 = 10;
+
+/// A preview image waiting for GPU upload
+pub struct RenderedPreview {
+    /// Image ID the preview should be uploaded as
+    pub image_id: u32,
+    pub width: u32,
+    pub height: u32,
+    /// RGBA pixel data
+    pub data: Vec<u8>,
+}
+
+/// Request for the preview worker
+enum PreviewRequest {
+    Waveform {
+        path: String,
+        width: u32,
+        height: u32,
+        color: u32,
+        image_id: u32,
+    },
+    Thumbnails {
+        path: String,
+        width: u32,
+        image_ids: Vec<u32>,
+    },
+}
+
+/// Async media preview generator
+pub struct MediaPreviewCache {
+    request_tx: mpsc::Sender<PreviewRequest>,
+    rendered_rx: mpsc::Receiver<RenderedPreview>,
+}
+
+impl MediaPreviewCache {
+    /// Create the cache and start its worker thread
+    pub fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<PreviewRequest>();
+        let (rendered_tx, rendered_rx) = mpsc::channel::<RenderedPreview>();
+        thread::Builder::new()
+            .name("media-preview".into())
+            .spawn(move || Self::worker_thread(request_rx, rendered_tx))
+            .expect("Failed to spawn media preview thread");
+        Self { request_tx, rendered_rx }
+    }
+
+    /// Draw the waveform of the audio of `path` as image `image_id`,
+    /// `width` x `height` pixels in `color` (0xAARRGGBB)
+    pub fn waveform(&mut self, path: &str, width: u32, height: u32, color: u32, image_id: u32) {
+        let _ = self.request_tx.send(PreviewRequest::Waveform {
+            path: path.to_string(),
+            width,
+            height,
+            color,
+            image_id,
+        });
+    }
+
+    /// Take one thumbnail `width` pixels wide of the video of `path` per
+    /// ID in `image_ids`, at even intervals
+    pub fn thumbnails(&mut self, path: &str, width: u32, image_ids: Vec<u32>) {
+        let _ = self.request_tx.send(PreviewRequest::Thumbnails {
+            path: path.to_string(),
+            width,
+            image_ids,
+        });
+    }
+
+    /// Take all previews made since the last call (call each frame)
+    pub fn take_rendered(&mut self) -> Vec<RenderedPreview> {
+        self.rendered_rx.try_iter().collect()
+    }
+
+    fn worker_thread(rx: mpsc::Receiver<PreviewRequest>, tx: mpsc::Sender<RenderedPreview>) {
+        log::debug!("Media preview worker started");
+        while let Ok(request) = rx.recv() {
+            let (path, result) = match request {
+                PreviewRequest::Waveform { path, width, height, color, image_id } => {
+                    let result = Self::waveform_image(&path, width, height, color).map(|data| {
+                        vec![RenderedPreview { image_id, width, height, data }]
+                    });
+                    (path, result)
+                }
+                PreviewRequest::Thumbnails { path, width, image_ids } => {
+                    let result = Self::thumbnail_images(&path, width, &image_ids);
+                    (path, result)
+                }
+            };
+            match result {
+                Ok(previews) => {
+                    for preview in previews {
+                        if tx.send(preview).is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    error_report::error(ErrorKind::Video, None, format!("cannot preview {}: {}", path, e));
+                }
+            }
+        }
+        log::debug!("Media preview worker exiting");
+    }
+
+    /// Pipeline decoding `path` into `sink_caps`, ending in an appsink
+    /// that does not wait for the clock
+    fn launch(path: &str, convert: &str, sink_caps: &str) -> Result<(gst::Pipeline, gst_app::AppSink), String> {
+        let path = path.strip_prefix("file://").unwrap_or(path);
+        let description = format!(
+            "filesrc location=\"{}\" ! decodebin ! {} ! {} ! appsink name=sink sync=false",
+            path.replace('"', "\\\""),
+            convert,
+            sink_caps,
+        );
+        let pipeline = gst::parse::launch(&description)
+            .map_err(|e| e.to_string())?
+            .dynamic_cast::<gst::Pipeline>()
+            .map_err(|_| "not a pipeline".to_string())?;
+        let sink = pipeline
+            .by_name("sink")
+            .and_then(|s| s.dynamic_cast::<gst_app::AppSink>().ok())
+            .ok_or("no appsink")?;
+        Ok((pipeline, sink))
+    }
+
+    /// The first error posted on the bus of `pipeline`, if any
+    fn bus_error(pipeline: &gst::Pipeline) -> Option<String> {
+        let msg = pipeline.bus()?.pop_filtered(&[gst::MessageType::Error])?;
+        match msg.view() {
+            gst::MessageView::Error(err) => Some(err.error().to_string()),
+            _ => None,
+        }
+    }
+
+    fn waveform_image(path: &str, width: u32, height: u32, color: u32) -> Result<Vec<u8>, String> {
+        let caps = format!("audio/x-raw,format=F32LE,channels=1,rate={}", WAVEFORM_RATE);
+        let (pipeline, sink) = Self::launch(path, "audioconvert ! audioresample", &caps)?;
+        pipeline.set_state(gst::State::Playing).map_err(|e| e.to_string())?;
+
+        let mut peaks = WaveformPeaks::new();
+        // pull_sample fails at the end of the stream, and on errors
+        while let Ok(sample) = sink.pull_sample() {
+            let Some(buffer) = sample.buffer() else { continue };
+            let Ok(map) = buffer.map_readable() else { continue };
+            let samples: Vec<f32> = map
+                .as_slice()
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            peaks.push(&samples);
+        }
+        let error = Self::bus_error(&pipeline);
+        let _ = pipeline.set_state(gst::State::Null);
+        if peaks.is_empty() {
+            return Err(error.unwrap_or_else(|| "no audio".into()));
+        }
+        Ok(media_preview::waveform_rgba(&peaks, width, height, color))
+    }
+
+    fn thumbnail_images(path: &str, width: u32, image_ids: &[u32]) -> Result<Vec<RenderedPreview>, String> {
+        // The height follows from the width and the shape of the video
+        let caps = format!("video/x-raw,format=RGBA,width={},pixel-aspect-ratio=1/1", width);
+        let (pipeline, sink) = Self::launch(path, "videoconvert ! videoscale", &caps)?;
+        let timeout = gst::ClockTime::from_seconds(PREROLL_TIMEOUT_SECONDS);
+
+        let result = (|| -> Result<Vec<RenderedPreview>, String> {
+            pipeline.set_state(gst::State::Paused).map_err(|e| e.to_string())?;
+            let (state, _, _) = pipeline.state(timeout);
+            if state.is_err() {
+                return Err(Self::bus_error(&pipeline).unwrap_or_else(|| "cannot preroll".into()));
+            }
+            let duration = pipeline
+                .query_duration::<gst::ClockTime>()
+                .ok_or("unknown duration")?
+                .nseconds();
+
+            let times = media_preview::thumbnail_times(duration, image_ids.len() as u32);
+            let mut previews = Vec::with_capacity(image_ids.len());
+            for (&image_id, time) in image_ids.iter().zip(times) {
+                pipeline
+                    .seek_simple(
+                        gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+                        gst::ClockTime::from_nseconds(time),
+                    )
+                    .map_err(|e| e.to_string())?;
+                let sample = sink.try_pull_preroll(timeout).ok_or("seek timed out")?;
+                previews.push(Self::sample_rgba(&sample, image_id)?);
+            }
+            Ok(previews)
+        })();
+        let _ = pipeline.set_state(gst::State::Null);
+        result
+    }
+
+    /// Pixels of a prerolled RGBA `sample`, without row padding
+    fn sample_rgba(sample: &gst::Sample, image_id: u32) -> Result<RenderedPreview, String> {
+        let caps = sample.caps().ok_or("no caps")?;
+        let info = gst_video::VideoInfo::from_caps(caps).map_err(|e| e.to_string())?;
+        let buffer = sample.buffer().ok_or("no buffer")?;
+        let map = buffer.map_readable().map_err(|e| e.to_string())?;
+        let (width, height) = (info.width(), info.height());
+        let stride = info.stride()[0] as usize;
+        let row = width as usize * 4;
+        let mut data = Vec::with_capacity(row * height as usize);
+        for y in 0..height as usize {
+            let start = y * stride;
+            data.extend_from_slice(map.as_slice().get(start..start + row).ok_or("short buffer")?);
+        }
+        Ok(RenderedPreview { image_id, width, height, data })
+    }
+}
+
+impl Default for MediaPreviewCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,6 +7,12 @@ mod renderer;
 #[cfg(feature = "winit-backend")]
 mod backend;
 #[cfg(feature = "winit-backend")]
+mod atlas_packer;
+#[cfg(feature = "winit-backend")]
+mod blur;
+#[cfg(feature = "winit-backend")]
+mod device_loss;
+#[cfg(feature = "winit-backend")]
 mod glyph_atlas;
 #[cfg(any(feature = "winit-backend", feature = "wpe-webkit"))]
 pub(crate) mod external_buffer;
@@ -20,6 +26,8 @@ mod window_state;
 mod events;
 #[cfg(feature = "winit-backend")]
 mod image_cache;
+#[cfg(all(feature = "winit-backend", feature = "neo-term"))]
+mod terminal_renderer;
 
 #[cfg(all(feature = "video", target_os = "linux"))]
 mod vulkan_dmabuf;
@@ -30,21 +38,49 @@ mod va_dmabuf_export;
 #[cfg(feature = "video")]
 mod video_cache;
 
+#[cfg(feature = "video")]
+mod media_preview;
+
+#[cfg(feature = "video")]
+mod audio_mixer;
+
+#[cfg(feature = "pdf")]
+mod pdf_cache;
+
+#[cfg(feature = "accessibility")]
+mod accessibility;
+
 pub mod media_budget;
+pub mod gpu_budget;
 
 #[cfg(feature = "video")]
 pub use video_cache::{VideoCache, CachedVideo, VideoState, DecodedFrame};
+#[cfg(feature = "video")]
+pub use media_preview::{MediaPreviewCache, RenderedPreview};
+
+#[cfg(feature = "pdf")]
+pub use pdf_cache::{
+    PdfCache, PdfDocumentInfo, PdfLinkRect, PdfPageContent, PdfPageSize, PdfState,
+    RenderedPdfPage, SharedPdfDocuments,
+};
+
+#[cfg(feature = "accessibility")]
+pub use accessibility::{build_tree_update, AccessibilityBridge};
 
 #[cfg(feature = "winit-backend")]
 pub use renderer::WgpuRenderer;
 #[cfg(feature = "winit-backend")]
 pub use backend::{WinitBackend, UserEvent, Callbacks, NeomacsApp, run_event_loop};
 #[cfg(feature = "winit-backend")]
+pub use device_loss::DeviceLossWatch;
+#[cfg(feature = "winit-backend")]
 pub use glyph_atlas::{WgpuGlyphAtlas, GlyphKey, CachedGlyph};
 #[cfg(feature = "winit-backend")]
 pub use image_cache::{ImageCache, CachedImage, ImageDimensions, ImageState};
 #[cfg(feature = "winit-backend")]
 pub use vertex::GlyphVertex;
+#[cfg(all(feature = "winit-backend", feature = "neo-term"))]
+pub use terminal_renderer::TerminalRenderer;
 
 #[cfg(feature = "winit-backend")]
 pub use external_buffer::{ExternalBuffer, SharedMemoryBuffer, BufferFormat, PlatformBuffer};
@@ -71,6 +107,21 @@ pub use events::{
     NEOMACS_EVENT_MENU_SELECTION,
     NEOMACS_EVENT_FILE_DROP,
     NEOMACS_EVENT_TERMINAL_TITLE_CHANGED,
+    NEOMACS_EVENT_VIDEO_ENDED,
+    NEOMACS_EVENT_WEBKIT_TITLE_CHANGED,
+    NEOMACS_EVENT_WEBKIT_URL_CHANGED,
+    NEOMACS_EVENT_WEBKIT_PROGRESS_CHANGED,
+    NEOMACS_EVENT_WEBKIT_LOAD_FINISHED,
+    NEOMACS_EVENT_ANIMATION_FINISHED,
+    NEOMACS_EVENT_DISPLAY_ERROR,
+    NEOMACS_EVENT_TERMINAL_BELL,
+    NEOMACS_EVENT_TMUX_PANE_ADDED,
+    NEOMACS_EVENT_TMUX_PANE_CLOSED,
+    NEOMACS_EVENT_TMUX_EXITED,
+    NEOMACS_EVENT_ANIMATION_COMPLETED,
+    NEOMACS_EVENT_PIP_CLOSED,
+    NEOMACS_EVENT_AUDIO_LEVELS,
+    NEOMACS_EVENT_APPEARANCE_CHANGED,
 };
 
 #[cfg(all(feature = "wpe-webkit", target_os = "linux"))]
@@ -0,0 +1,438 @@
+//! Async PDF document loading and page rasterization for wgpu renderer
+//!
+//! Works like ImageCache, but for paged documents:
+//! - Documents are opened on a dedicated pdfium worker thread
+//! - Pages are rasterized in the background at the requested scale
+//! - Rendered pages are uploaded as ordinary image textures
+//! - Page sizes, text and link rectangles are published through a
+//!   shared map so the Emacs thread can query them without blocking
+
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use pdfium_render::prelude::*;
+use crate::core::error_report::{self, ErrorKind};
+
+/// Maximum rasterized page dimension (matches the image texture limit)
+const MAX_PAGE_PIXELS: u32 = 4096;
+
+/// Shared document info: document id -> info
+pub type SharedPdfDocuments = Arc<Mutex<HashMap<u32, PdfDocumentInfo>>>;
+
+/// Document loading state
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfState {
+    /// Queued for opening
+    Loading,
+    /// Open, page sizes known
+    Ready,
+    /// Failed to open
+    Failed(String),
+}
+
+/// Page size in PDF points (1/72 inch)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfPageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PdfPageSize {
+    /// Size in pixels of the page at `scale` pixels per point, scaled down
+    /// to fit `MAX_PAGE_PIXELS` on each side as rasterized pages are
+    pub fn pixels(&self, scale: f32) -> (u32, u32) {
+        fit_pixels(self.width * scale, self.height * scale)
+    }
+}
+
+/// Round a size to whole pixels, scaled down evenly to fit `MAX_PAGE_PIXELS`
+fn fit_pixels(width: f32, height: f32) -> (u32, u32) {
+    let fit = (MAX_PAGE_PIXELS as f32 / width.max(height).max(1.0)).min(1.0);
+    let round = |n: f32| ((n * fit).round() as u32).clamp(1, MAX_PAGE_PIXELS);
+    (round(width), round(height))
+}
+
+/// A link annotation on a page.
+///
+/// Coordinates are in PDF points with the origin at the top-left corner
+/// of the page, so they scale directly with the rendered image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfLinkRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// External target (http:, mailto:, ...)
+    pub uri: Option<String>,
+    /// Internal target page (0-based)
+    pub dest_page: Option<u32>,
+}
+
+/// Extracted text and links for one page
+#[derive(Debug, Clone, Default)]
+pub struct PdfPageContent {
+    pub text: String,
+    pub links: Vec<PdfLinkRect>,
+}
+
+/// Everything the Emacs thread can ask about a document
+#[derive(Debug, Clone)]
+pub struct PdfDocumentInfo {
+    pub state: PdfState,
+    /// Page sizes, indexed by 0-based page number
+    pub pages: Vec<PdfPageSize>,
+    /// Extracted content, filled in per page on request
+    pub content: HashMap<u32, PdfPageContent>,
+}
+
+impl PdfDocumentInfo {
+    fn loading() -> Self {
+        Self {
+            state: PdfState::Loading,
+            pages: Vec::new(),
+            content: HashMap::new(),
+        }
+    }
+
+    /// Number of pages (0 until the document is ready)
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+/// A rasterized page waiting for GPU upload
+pub struct RenderedPdfPage {
+    /// Image ID the page should be uploaded as
+    pub image_id: u32,
+    /// Raster size in physical pixels
+    pub width: u32,
+    pub height: u32,
+    /// Size in logical pixels (raster size divided by the display scale)
+    pub logical_width: u32,
+    pub logical_height: u32,
+    /// RGBA pixel data
+    pub data: Vec<u8>,
+}
+
+/// Request for the pdfium worker
+enum PdfRequest {
+    Open {
+        id: u32,
+        path: String,
+        password: Option<String>,
+    },
+    Render {
+        id: u32,
+        page: u32,
+        zoom: f32,
+        display_scale: f32,
+        image_id: u32,
+    },
+    Extract {
+        id: u32,
+        page: u32,
+    },
+    Close {
+        id: u32,
+    },
+}
+
+/// Async PDF cache
+pub struct PdfCache {
+    /// Channel to send requests to the worker
+    request_tx: mpsc::Sender<PdfRequest>,
+    /// Channel to receive rasterized pages
+    rendered_rx: mpsc::Receiver<RenderedPdfPage>,
+    /// Document info shared with the Emacs thread
+    documents: SharedPdfDocuments,
+}
+
+impl PdfCache {
+    /// Create a new PDF cache and start its worker thread
+    pub fn new(documents: SharedPdfDocuments) -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<PdfRequest>();
+        let (rendered_tx, rendered_rx) = mpsc::channel::<RenderedPdfPage>();
+
+        let shared = Arc::clone(&documents);
+        thread::Builder::new()
+            .name("pdfium".into())
+            .spawn(move || Self::worker_thread(request_rx, rendered_tx, shared))
+            .expect("Failed to spawn pdfium worker thread");
+
+        Self {
+            request_tx,
+            rendered_rx,
+            documents,
+        }
+    }
+
+    /// Open a document (async).  Progress is reported through the shared map.
+    pub fn open(&mut self, id: u32, path: &str, password: Option<String>) {
+        if let Ok(mut docs) = self.documents.lock() {
+            docs.insert(id, PdfDocumentInfo::loading());
+        }
+        let _ = self.request_tx.send(PdfRequest::Open {
+            id,
+            path: path.to_string(),
+            password,
+        });
+    }
+
+    /// Rasterize a page (async).
+    ///
+    /// `zoom` is logical pixels per PDF point; the page is rasterized at
+    /// `zoom * display_scale` so it stays sharp on HiDPI outputs.
+    pub fn render_page(&mut self, id: u32, page: u32, zoom: f32, display_scale: f32, image_id: u32) {
+        let _ = self.request_tx.send(PdfRequest::Render {
+            id,
+            page,
+            zoom,
+            display_scale,
+            image_id,
+        });
+    }
+
+    /// Extract text and link rectangles for a page (async)
+    pub fn extract_page(&mut self, id: u32, page: u32) {
+        let _ = self.request_tx.send(PdfRequest::Extract { id, page });
+    }
+
+    /// Close a document and forget its info
+    pub fn close(&mut self, id: u32) {
+        if let Ok(mut docs) = self.documents.lock() {
+            docs.remove(&id);
+        }
+        let _ = self.request_tx.send(PdfRequest::Close { id });
+    }
+
+    /// Take all pages rasterized since the last call (call each frame)
+    pub fn take_rendered(&mut self) -> Vec<RenderedPdfPage> {
+        self.rendered_rx.try_iter().collect()
+    }
+
+    /// Background worker owning the pdfium library and open documents
+    fn worker_thread(
+        rx: mpsc::Receiver<PdfRequest>,
+        tx: mpsc::Sender<RenderedPdfPage>,
+        shared: SharedPdfDocuments,
+    ) {
+        let pdfium = match Pdfium::bind_to_system_library() {
+            Ok(bindings) => Pdfium::new(bindings),
+            Err(e) => {
+                let msg = format!("libpdfium not available: {:?}", e);
+                error_report::error(ErrorKind::Pdf, None, msg.clone());
+                // Keep draining so callers see a failure instead of a hang
+                while let Ok(request) = rx.recv() {
+                    if let PdfRequest::Open { id, .. } = request {
+                        Self::set_state(&shared, id, PdfState::Failed(msg.clone()));
+                    }
+                }
+                return;
+            }
+        };
+        log::debug!("pdfium worker started");
+
+        let mut documents: HashMap<u32, PdfDocument> = HashMap::new();
+
+        while let Ok(request) = rx.recv() {
+            match request {
+                PdfRequest::Open { id, path, password } => {
+                    // Loading from a file ties the password to the lifetime of
+                    // the document; loading from memory only borrows it for the
+                    // call, so encrypted documents are read in whole
+                    let loaded = match password {
+                        None => pdfium.load_pdf_from_file(&path, None),
+                        Some(password) => std::fs::read(&path)
+                            .map_err(PdfiumError::IoError)
+                            .and_then(|bytes| pdfium.load_pdf_from_byte_vec(bytes, Some(&password))),
+                    };
+                    match loaded {
+                        Ok(doc) => {
+                            let pages: Vec<PdfPageSize> = doc
+                                .pages()
+                                .page_sizes()
+                                .unwrap_or_default()
+                                .iter()
+                                .map(|r| PdfPageSize {
+                                    width: r.width().value,
+                                    height: r.height().value,
+                                })
+                                .collect();
+                            log::info!("Opened PDF {}: {} ({} pages)", id, path, pages.len());
+                            if let Ok(mut docs) = shared.lock() {
+                                if let Some(info) = docs.get_mut(&id) {
+                                    info.pages = pages;
+                                    info.state = PdfState::Ready;
+                                }
+                            }
+                            documents.insert(id, doc);
+                        }
+                        Err(e) => {
+                            error_report::error(ErrorKind::Pdf, Some(id), format!("cannot open {}: {:?}", path, e));
+                            Self::set_state(&shared, id, PdfState::Failed(format!("{:?}", e)));
+                        }
+                    }
+                }
+                PdfRequest::Render { id, page, zoom, display_scale, image_id } => {
+                    let Some(doc) = documents.get(&id) else { continue };
+                    match Self::render(doc, page, zoom, display_scale.max(0.1), image_id) {
+                        Ok(rendered) => {
+                            let _ = tx.send(rendered);
+                        }
+                        Err(e) => {
+                            error_report::error(ErrorKind::Pdf, Some(id), format!("cannot render page {}: {:?}", page, e));
+                        }
+                    }
+                }
+                PdfRequest::Extract { id, page } => {
+                    let Some(doc) = documents.get(&id) else { continue };
+                    match Self::extract(doc, page) {
+                        Ok(content) => {
+                            if let Ok(mut docs) = shared.lock() {
+                                if let Some(info) = docs.get_mut(&id) {
+                                    info.content.insert(page, content);
+                                }
+                            }
+                        }
+                        Err(e) => log::warn!("Failed to extract PDF {} page {}: {:?}", id, page, e),
+                    }
+                }
+                PdfRequest::Close { id } => {
+                    documents.remove(&id);
+                    log::debug!("Closed PDF {}", id);
+                }
+            }
+        }
+
+        log::debug!("pdfium worker exiting");
+    }
+
+    fn set_state(shared: &SharedPdfDocuments, id: u32, state: PdfState) {
+        if let Ok(mut docs) = shared.lock() {
+            if let Some(info) = docs.get_mut(&id) {
+                info.state = state;
+            }
+        }
+    }
+
+    /// Rasterize one page to RGBA at `zoom`, with `display_scale` device
+    /// pixels per logical pixel.  Its logical size is the one
+    /// `PdfPageSize::pixels` gives.
+    fn render(
+        doc: &PdfDocument,
+        page: u32,
+        zoom: f32,
+        display_scale: f32,
+        image_id: u32,
+    ) -> Result<RenderedPdfPage, PdfiumError> {
+        let page = doc.pages().get(page_index(page)?)?;
+        let size = PdfPageSize { width: page.width().value, height: page.height().value };
+        let logical = size.pixels(zoom.max(0.01));
+        // At high zoom the raster may hold fewer pixels than the page
+        // covers on screen; the logical size stays that of the layout
+        let (width, height) = fit_pixels(logical.0 as f32 * display_scale, logical.1 as f32 * display_scale);
+        let config = PdfRenderConfig::new()
+            .set_target_size(width as Pixels, height as Pixels)
+            .render_annotations(true)
+            .render_form_data(true);
+        let bitmap = page.render_with_config(&config)?;
+        Ok(RenderedPdfPage {
+            image_id,
+            width: bitmap.width() as u32,
+            height: bitmap.height() as u32,
+            logical_width: logical.0,
+            logical_height: logical.1,
+            data: bitmap.as_rgba_bytes(),
+        })
+    }
+
+    /// Extract text and links from one page
+    fn extract(doc: &PdfDocument, page: u32) -> Result<PdfPageContent, PdfiumError> {
+        let page = doc.pages().get(page_index(page)?)?;
+        let page_height = page.height().value;
+        let text = page.text()?.all();
+
+        let links = page
+            .links()
+            .iter()
+            .filter_map(|link| {
+                let rect = link.rect().ok()?;
+                let uri = link
+                    .action()
+                    .and_then(|action| action.as_uri_action().and_then(|a| a.uri().ok()));
+                let dest_page = link
+                    .destination()
+                    .and_then(|dest| dest.page_index().ok())
+                    .map(|p| p as u32);
+                Some(link_rect_from_pdf(
+                    rect.left().value,
+                    rect.bottom().value,
+                    rect.right().value,
+                    rect.top().value,
+                    page_height,
+                    uri,
+                    dest_page,
+                ))
+            })
+            .collect();
+
+        Ok(PdfPageContent { text, links })
+    }
+}
+
+/// Convert a 0-based page number to a pdfium page index
+fn page_index(page: u32) -> Result<PdfPageIndex, PdfiumError> {
+    PdfPageIndex::try_from(page).map_err(|_| PdfiumError::PageIndexOutOfBounds)
+}
+
+/// Convert a bottom-left-origin PDF rectangle to a top-left-origin link rect
+fn link_rect_from_pdf(
+    left: f32,
+    bottom: f32,
+    right: f32,
+    top: f32,
+    page_height: f32,
+    uri: Option<String>,
+    dest_page: Option<u32>,
+) -> PdfLinkRect {
+    PdfLinkRect {
+        x: left.min(right),
+        y: page_height - top.max(bottom),
+        width: (right - left).abs(),
+        height: (top - bottom).abs(),
+        uri,
+        dest_page,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_rect_flips_y_axis() {
+        let link = link_rect_from_pdf(72.0, 700.0, 172.0, 720.0, 792.0, None, Some(3));
+        assert_eq!(link.x, 72.0);
+        assert_eq!(link.y, 72.0);
+        assert_eq!(link.width, 100.0);
+        assert_eq!(link.height, 20.0);
+        assert_eq!(link.dest_page, Some(3));
+    }
+
+    #[test]
+    fn test_page_pixels_fit_raster_limit() {
+        let letter = PdfPageSize { width: 612.0, height: 792.0 };
+        assert_eq!(letter.pixels(1.0), (612, 792));
+        assert_eq!(letter.pixels(2.0), (1224, 1584));
+        // Zoomed past the limit the page shrinks evenly to fit it
+        assert_eq!(letter.pixels(10.0), (3165, MAX_PAGE_PIXELS));
+        assert_eq!(fit_pixels(0.2, 0.0), (1, 1));
+    }
+
+    #[test]
+    fn test_page_index_bounds() {
+        assert_eq!(page_index(0).unwrap(), 0);
+        assert!(page_index(u32::MAX).is_err());
+    }
+}
@@ -7,6 +7,7 @@ use super::{LineAnimEntry, EdgeSnapEntry, ClickHaloEntry, HeatMapEntry,
     RainDrop, RippleWaveEntry, CursorParticle, WindowFadeEntry,
     TitleFadeEntry, ModeLineFadeEntry, TextFadeEntry, ScrollSpacingEntry};
 use crate::core::types::{Color, Rect};
+use std::time::{Duration, Instant};
 
 impl WgpuRenderer {
     /// Update inactive window dim config
@@ -26,14 +27,15 @@ impl WgpuRenderer {
             window_bounds,
             edit_y,
             initial_offset: offset,
-            started: std::time::Instant::now(),
-            duration: std::time::Duration::from_millis(duration_ms as u64),
+            started: self.clock.now(),
+            duration: Duration::from_millis(duration_ms as u64),
         });
         self.needs_continuous_redraw = true;
     }
 
     /// Compute Y offset for a glyph due to active line animations
     pub(super) fn line_y_offset(&self, gx: f32, gy: f32) -> f32 {
+        let now = self.clock.now();
         let mut offset = 0.0;
         for anim in &self.active_line_anims {
             let b = &anim.window_bounds;
@@ -42,34 +44,16 @@ impl WgpuRenderer {
                 && gy >= b.y && gy < b.y + b.height
                 && gy >= anim.edit_y
             {
-                let elapsed = anim.started.elapsed();
-                let t = (elapsed.as_secs_f32() / anim.duration.as_secs_f32()).min(1.0);
-                // Ease-out quadratic: t * (2 - t)
-                let eased = t * (2.0 - t);
-                offset += anim.initial_offset * (1.0 - eased);
+                offset += anim.initial_offset * ease_out_remaining(anim.started, anim.duration, now);
             }
         }
         // Scroll line spacing accordion effect
-        let now = std::time::Instant::now();
         for entry in &self.active_scroll_spacings {
             let b = &entry.bounds;
             if gx >= b.x && gx < b.x + b.width
                 && gy >= b.y && gy < b.y + b.height
             {
-                let elapsed = now.duration_since(entry.started).as_secs_f32();
-                let total = entry.duration.as_secs_f32();
-                if elapsed < total {
-                    let progress = elapsed / total;
-                    let decay = 1.0 - progress;
-                    let decay = decay * decay;
-                    let norm = ((gy - b.y) / b.height).clamp(0.0, 1.0);
-                    let edge_factor = if entry.direction > 0 {
-                        1.0 - norm
-                    } else {
-                        norm
-                    };
-                    offset += self.effects.scroll_line_spacing.max * decay * edge_factor;
-                }
+                offset += scroll_spacing_offset(entry, self.effects.scroll_line_spacing.max, gy, now);
             }
         }
         offset
@@ -86,15 +70,10 @@ impl WgpuRenderer {
             return 1.0;
         }
         if let Some(started) = self.cursor_wake_started {
-            let elapsed = started.elapsed().as_millis() as f32;
-            let duration = self.effects.cursor_wake.duration_ms as f32;
-            if elapsed >= duration {
-                return 1.0;
-            }
-            let t = elapsed / duration;
+            let duration = Duration::from_millis(self.effects.cursor_wake.duration_ms as u64);
             // Ease-out: scale starts large and settles to 1.0
-            let ease = t * (2.0 - t); // quadratic ease-out
-            1.0 + (self.effects.cursor_wake.scale - 1.0) * (1.0 - ease)
+            let remaining = ease_out_remaining(started, duration, self.clock.now());
+            1.0 + (self.effects.cursor_wake.scale - 1.0) * remaining
         } else {
             1.0
         }
@@ -155,14 +134,8 @@ impl WgpuRenderer {
     /// Get current resize padding amount (eases from max to 0)
     pub(super) fn resize_padding_amount(&self) -> f32 {
         if let Some(started) = self.resize_padding_started {
-            let elapsed = started.elapsed().as_millis() as f32;
-            let duration = self.effects.resize_padding.duration_ms as f32;
-            if elapsed >= duration {
-                return 0.0;
-            }
-            let t = elapsed / duration;
-            let ease = t * (2.0 - t); // quadratic ease-out
-            self.effects.resize_padding.max * (1.0 - ease)
+            let duration = Duration::from_millis(self.effects.resize_padding.duration_ms as u64);
+            self.effects.resize_padding.max * ease_out_remaining(started, duration, self.clock.now())
         } else {
             0.0
         }
@@ -178,20 +151,13 @@ impl WgpuRenderer {
         if !self.effects.cursor_error_pulse.enabled {
             return None;
         }
-        if let Some(started) = self.cursor_error_pulse_started {
-            let elapsed = started.elapsed().as_millis() as f32;
-            let duration = self.effects.cursor_error_pulse.duration_ms as f32;
-            if elapsed >= duration {
-                return None;
-            }
-            let t = elapsed / duration;
-            // Flash: bright at start, fade out
-            let alpha = (1.0 - t) * (1.0 - t);
-            let (r, g, b) = self.effects.cursor_error_pulse.color;
-            Some(Color::new(r, g, b, alpha))
-        } else {
-            None
-        }
+        let started = self.cursor_error_pulse_started?;
+        let duration = Duration::from_millis(self.effects.cursor_error_pulse.duration_ms as u64);
+        let t = progress(started, duration, self.clock.now())?;
+        // Flash: bright at start, fade out
+        let alpha = (1.0 - t) * (1.0 - t);
+        let (r, g, b) = self.effects.cursor_error_pulse.color;
+        Some(Color::new(r, g, b, alpha))
     }
 
     /// Trigger a scroll momentum indicator for a window
@@ -253,15 +219,12 @@ impl WgpuRenderer {
         if !self.effects.mode_line_transition.enabled || self.active_mode_line_fades.is_empty() {
             return 1.0;
         }
-        let now = std::time::Instant::now();
+        let now = self.clock.now();
         for entry in &self.active_mode_line_fades {
             if gx >= entry.bounds_x && gx < entry.bounds_x + entry.bounds_w
                 && gy >= entry.mode_line_y && gy < entry.mode_line_y + entry.mode_line_h
             {
-                let elapsed = now.duration_since(entry.started).as_secs_f32();
-                let total = entry.duration.as_secs_f32();
-                if elapsed < total {
-                    let t = elapsed / total;
+                if let Some(t) = progress(entry.started, entry.duration, now) {
                     return t; // linear fade-in
                 }
             }
@@ -288,17 +251,14 @@ impl WgpuRenderer {
         if !self.effects.text_fade_in.enabled || self.active_text_fades.is_empty() {
             return 1.0;
         }
-        let now = std::time::Instant::now();
+        let now = self.clock.now();
         for entry in &self.active_text_fades {
             let b = &entry.bounds;
             if gx >= b.x && gx < b.x + b.width
                 && gy >= b.y && gy < b.y + b.height
             {
-                let elapsed = now.duration_since(entry.started).as_secs_f32();
-                let total = entry.duration.as_secs_f32();
-                if elapsed < total {
-                    // Ease-in: start at 0, end at 1
-                    let t = elapsed / total;
+                // Ease-in: start at 0, end at 1
+                if let Some(t) = progress(entry.started, entry.duration, now) {
                     return t * t; // quadratic ease-in for smooth appearance
                 }
             }
@@ -326,7 +286,7 @@ impl WgpuRenderer {
         let dist = ((x - self.cursor_trail_last_pos.0).powi(2)
             + (y - self.cursor_trail_last_pos.1).powi(2)).sqrt();
         if dist < 2.0 { return; } // Skip tiny movements
-        self.cursor_trail_positions.push((x, y, w, h, std::time::Instant::now()));
+        self.cursor_trail_positions.push((x, y, w, h, self.clock.now()));
         self.cursor_trail_last_pos = (x, y);
         // Trim to max length
         while self.cursor_trail_positions.len() > self.effects.cursor_trail_fade.length {
@@ -346,8 +306,8 @@ impl WgpuRenderer {
         self.active_window_fades.push(WindowFadeEntry {
             window_id,
             bounds,
-            started: std::time::Instant::now(),
-            duration: std::time::Duration::from_millis(self.effects.window_switch_fade.duration_ms as u64),
+            started: self.clock.now(),
+            duration: Duration::from_millis(self.effects.window_switch_fade.duration_ms as u64),
             intensity: self.effects.window_switch_fade.intensity,
         });
     }
@@ -380,7 +340,7 @@ impl WgpuRenderer {
     /// Spawn a new ripple at the given position
     pub fn spawn_ripple(&mut self, cx: f32, cy: f32) {
         if self.effects.typing_ripple.enabled {
-            self.active_ripples.push((cx, cy, std::time::Instant::now()));
+            self.active_ripples.push((cx, cy, self.clock.now()));
         }
     }
 
@@ -402,3 +362,120 @@ impl WgpuRenderer {
         self.effects.indent_guides.rainbow_colors = colors;
     }
 }
+
+/// How far an effect started at `started` and lasting `duration` has got
+/// at `now`, from 0 up to 1, or `None` once it is over
+fn progress(started: Instant, duration: Duration, now: Instant) -> Option<f32> {
+    let elapsed = now.saturating_duration_since(started);
+    (elapsed < duration).then(|| elapsed.as_secs_f32() / duration.as_secs_f32())
+}
+
+/// What is left at `now` of an effect that eases out (quadratically)
+/// over `duration` from `started`: 1 at the start, 0 once it is over
+fn ease_out_remaining(started: Instant, duration: Duration, now: Instant) -> f32 {
+    progress(started, duration, now).map_or(0.0, |t| 1.0 - t * (2.0 - t))
+}
+
+impl CursorParticle {
+    /// Position, size and opacity of the particle at `now`, falling under
+    /// `gravity`, or `None` once its lifetime is over
+    pub(super) fn at(&self, now: Instant, gravity: f32) -> Option<(f32, f32, f32, f32)> {
+        let t = progress(self.started, self.lifetime, now)?;
+        let elapsed = now.saturating_duration_since(self.started).as_secs_f32();
+        let x = self.x + self.vx * elapsed;
+        let y = self.y + self.vy * elapsed + 0.5 * gravity * elapsed * elapsed;
+        // Shrinks and fades over its lifetime
+        Some((x, y, 2.0 * (1.0 - t) + 0.5, (1.0 - t) * 0.8))
+    }
+}
+
+/// Extra spacing at height `gy` from a scroll line spacing animation at
+/// `now`, largest (`max`) at the edge the text scrolls away from
+fn scroll_spacing_offset(entry: &ScrollSpacingEntry, max: f32, gy: f32, now: Instant) -> f32 {
+    let Some(t) = progress(entry.started, entry.duration, now) else {
+        return 0.0;
+    };
+    let decay = (1.0 - t) * (1.0 - t);
+    let norm = ((gy - entry.bounds.y) / entry.bounds.height).clamp(0.0, 1.0);
+    let edge_factor = if entry.direction > 0 { 1.0 - norm } else { norm };
+    max * decay * edge_factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{Clock, ScriptedClock};
+
+    #[test]
+    fn test_effect_progress_follows_the_clock() {
+        let clock = ScriptedClock::new();
+        let started = clock.now();
+        let duration = Duration::from_millis(200);
+
+        assert_eq!(progress(started, duration, clock.now()), Some(0.0));
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(progress(started, duration, clock.now()), Some(0.25));
+        clock.advance(Duration::from_millis(150));
+        assert_eq!(progress(started, duration, clock.now()), None);
+
+        // No time has to pass for an effect without duration to be over
+        assert_eq!(progress(started, Duration::ZERO, started), None);
+    }
+
+    #[test]
+    fn test_ease_out_steps_deterministically() {
+        let clock = ScriptedClock::new();
+        let started = clock.now();
+        let duration = Duration::from_millis(100);
+
+        let mut steps = Vec::new();
+        for _ in 0..4 {
+            steps.push(ease_out_remaining(started, duration, clock.now()));
+            clock.advance(Duration::from_millis(25));
+        }
+        for (step, t) in steps.iter().zip([0.0f32, 0.25, 0.5, 0.75]) {
+            assert!((step - (1.0 - t * (2.0 - t))).abs() < 1e-6);
+        }
+        assert_eq!(ease_out_remaining(started, duration, clock.now()), 0.0);
+    }
+
+    #[test]
+    fn test_scroll_spacing_decays_with_the_clock() {
+        let clock = ScriptedClock::new();
+        let entry = ScrollSpacingEntry {
+            window_id: 1,
+            bounds: Rect::new(0.0, 100.0, 400.0, 200.0),
+            direction: 1,
+            started: clock.now(),
+            duration: Duration::from_millis(100),
+        };
+
+        // Scrolling down spaces the top of the window out the most
+        assert_eq!(scroll_spacing_offset(&entry, 8.0, 100.0, clock.now()), 8.0);
+        assert_eq!(scroll_spacing_offset(&entry, 8.0, 300.0, clock.now()), 0.0);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(scroll_spacing_offset(&entry, 8.0, 100.0, clock.now()), 2.0);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(scroll_spacing_offset(&entry, 8.0, 100.0, clock.now()), 0.0);
+    }
+
+    #[test]
+    fn test_cursor_particle_steps_with_the_clock() {
+        let clock = ScriptedClock::new();
+        let particle = CursorParticle {
+            x: 100.0,
+            y: 100.0,
+            vx: 40.0,
+            vy: -20.0,
+            started: clock.now(),
+            lifetime: Duration::from_millis(500),
+        };
+
+        assert_eq!(particle.at(clock.now(), 100.0), Some((100.0, 100.0, 2.5, 0.8)));
+        clock.advance(Duration::from_millis(250));
+        // Halfway: drifted and fallen, half as big and bright
+        assert_eq!(particle.at(clock.now(), 100.0), Some((110.0, 98.125, 1.5, 0.4)));
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(particle.at(clock.now(), 100.0), None);
+    }
+}
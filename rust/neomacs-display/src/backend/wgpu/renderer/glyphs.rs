@@ -7,21 +7,64 @@ use super::{LineAnimEntry, EdgeSnapEntry, ClickHaloEntry, HeatMapEntry,
     RainDrop, RippleWaveEntry, CursorParticle, WindowFadeEntry,
     TitleFadeEntry, ModeLineFadeEntry, TextFadeEntry, ScrollSpacingEntry};
 use wgpu::util::DeviceExt;
-use std::collections::HashMap;
-use super::super::vertex::{GlyphVertex, RectVertex, RoundedRectVertex, Uniforms};
-use crate::core::types::{Color, Rect, AnimatedCursor};
+use std::collections::{HashMap, HashSet};
+use super::super::vertex::{GlyphVertex, RectVertex, RoundedRectVertex};
+use crate::core::types::{snap_to_device, Color, Rect, AnimatedCursor};
 use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
-use crate::core::face::{BoxType, Face, FaceAttributes};
+use crate::core::scene_dump::DebugOverlay;
+use crate::core::selection::merge_selection_runs;
+use crate::core::prepare::prepare_backgrounds;
+use super::row_cache::{hash_rows, row_key, RowKey, RowVertices};
+use crate::core::minimap::{minimap_line_at_pos, MinimapLayout};
+use crate::core::nine_patch::{ChromeElement, ChromeRegion};
+use crate::core::face::{cover_tex_coords, merge_background_runs, BackgroundStyle, BoxType, Face, FaceAttributes};
+use super::super::blur::{backdrop_signature, BlurSettings};
 use super::super::glyph_atlas::{ComposedGlyphKey, GlyphKey, WgpuGlyphAtlas};
 
+/// Vertices drawing the part `uv` of a texture over the part `rect` of
+/// `area`, given as fractions (x0, y0, x1, y1), cut off below `clip_bottom`
+fn image_quad(
+    area: Rect,
+    clip_bottom: f32,
+    rect: (f32, f32, f32, f32),
+    uv: (f32, f32, f32, f32),
+    alpha: f32,
+) -> Option<[GlyphVertex; 6]> {
+    let (x0, x1) = (area.x + rect.0 * area.width, area.x + rect.2 * area.width);
+    let (y0, mut y1) = (area.y + rect.1 * area.height, area.y + rect.3 * area.height);
+    let (u0, v0, u1, mut v1) = uv;
+    if x1 <= x0 || y1 <= y0 || alpha <= 0.0 {
+        return None;
+    }
+    if y1 > clip_bottom {
+        v1 = v0 + (v1 - v0) * ((clip_bottom - y0) / (y1 - y0)).max(0.0);
+        y1 = clip_bottom;
+        if y1 <= y0 {
+            return None;
+        }
+    }
+    // White color = no tinting
+    let color = [1.0, 1.0, 1.0, alpha];
+    Some([
+        GlyphVertex { position: [x0, y0], tex_coords: [u0, v0], color },
+        GlyphVertex { position: [x1, y0], tex_coords: [u1, v0], color },
+        GlyphVertex { position: [x1, y1], tex_coords: [u1, v1], color },
+        GlyphVertex { position: [x0, y0], tex_coords: [u0, v0], color },
+        GlyphVertex { position: [x1, y1], tex_coords: [u1, v1], color },
+        GlyphVertex { position: [x0, y1], tex_coords: [u0, v1], color },
+    ])
+}
+
 impl WgpuRenderer {
     /// Render frame glyphs to a texture view
     ///
     /// `surface_width` and `surface_height` should be the actual surface dimensions
-    /// for correct coordinate transformation.
+    /// for correct coordinate transformation.  `backdrop` is the texture behind
+    /// `view`; without it the frame's blur regions are not blurred.
     pub fn render_frame_glyphs(
         &mut self,
         view: &wgpu::TextureView,
+        backdrop: Option<&wgpu::Texture>,
         frame_glyphs: &FrameGlyphBuffer,
         glyph_atlas: &mut WgpuGlyphAtlas,
         faces: &HashMap<u32, Face>,
@@ -46,13 +89,24 @@ impl WgpuRenderer {
         self.needs_continuous_redraw = false;
 
         // Clean up expired line animations
-        self.active_line_anims.retain(|a| a.started.elapsed() < a.duration);
+        let now = self.clock.now();
+        self.active_line_anims.retain(|a| now.duration_since(a.started) < a.duration);
         if !self.active_line_anims.is_empty() {
             self.needs_continuous_redraw = true;
         }
 
+        // Images whose picture is being swapped animate until it is done
+        if self.image_cache.finish_swaps(now) {
+            self.needs_continuous_redraw = true;
+        }
+
+        // Charts move to new values until they get there
+        if self.charts.values().any(|chart| chart.animating(now)) {
+            self.needs_continuous_redraw = true;
+        }
+
         // Clean up expired mode-line transition fades
-        self.active_mode_line_fades.retain(|e| e.started.elapsed() < e.duration);
+        self.active_mode_line_fades.retain(|e| now.duration_since(e.started) < e.duration);
         if !self.active_mode_line_fades.is_empty() {
             self.needs_continuous_redraw = true;
         }
@@ -61,7 +115,6 @@ impl WgpuRenderer {
         if self.effects.mode_line_transition.enabled {
             use std::collections::hash_map::DefaultHasher;
             use std::hash::{Hash, Hasher};
-            let now_ml = std::time::Instant::now();
             for info in &frame_glyphs.window_infos {
                 if info.mode_line_height < 1.0 || info.is_minibuffer {
                     continue;
@@ -89,7 +142,7 @@ impl WgpuRenderer {
                             mode_line_h: info.mode_line_height,
                             bounds_x: info.bounds.x,
                             bounds_w: info.bounds.width,
-                            started: now_ml,
+                            started: now,
                             duration: std::time::Duration::from_millis(self.effects.mode_line_transition.duration_ms as u64),
                         });
                         self.needs_continuous_redraw = true;
@@ -99,15 +152,14 @@ impl WgpuRenderer {
         }
 
         // Clean up expired text fade-in animations
-        self.active_text_fades.retain(|e| e.started.elapsed() < e.duration);
+        self.active_text_fades.retain(|e| now.duration_since(e.started) < e.duration);
         if !self.active_text_fades.is_empty() {
             self.needs_continuous_redraw = true;
         }
 
         // Clean up expired scroll line spacing animations
-        let now_spacing = std::time::Instant::now();
         self.active_scroll_spacings.retain(|e| {
-            now_spacing.duration_since(e.started) < e.duration
+            now.duration_since(e.started) < e.duration
         });
         if !self.active_scroll_spacings.is_empty() {
             self.needs_continuous_redraw = true;
@@ -116,7 +168,7 @@ impl WgpuRenderer {
         // Clear expired cursor wake animation
         if let Some(started) = self.cursor_wake_started {
             let dur = std::time::Duration::from_millis(self.effects.cursor_wake.duration_ms as u64);
-            if started.elapsed() >= dur {
+            if now.duration_since(started) >= dur {
                 self.cursor_wake_started = None;
             } else {
                 self.needs_continuous_redraw = true;
@@ -126,7 +178,7 @@ impl WgpuRenderer {
         // Clear expired cursor error pulse
         if let Some(started) = self.cursor_error_pulse_started {
             let dur = std::time::Duration::from_millis(self.effects.cursor_error_pulse.duration_ms as u64);
-            if started.elapsed() >= dur {
+            if now.duration_since(started) >= dur {
                 self.cursor_error_pulse_started = None;
             } else {
                 self.needs_continuous_redraw = true;
@@ -134,7 +186,7 @@ impl WgpuRenderer {
         }
 
         // Clean up expired scroll momentum entries
-        self.active_scroll_momentums.retain(|e| e.started.elapsed() < e.duration);
+        self.active_scroll_momentums.retain(|e| now.duration_since(e.started) < e.duration);
         if !self.active_scroll_momentums.is_empty() {
             self.needs_continuous_redraw = true;
         }
@@ -148,12 +200,13 @@ impl WgpuRenderer {
         // ensures glyph positions (which are relative to the frame) map correctly.
         let logical_w = if frame_glyphs.width > 0.0 { frame_glyphs.width } else { surface_width as f32 / self.scale_factor };
         let logical_h = if frame_glyphs.height > 0.0 { frame_glyphs.height } else { surface_height as f32 / self.scale_factor };
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        if self.effects.ambient.is_active() {
+            let ambient = self.ambient_uniforms(logical_w, logical_h);
+            self.queue.write_buffer(&self.ambient_buffer, 0, bytemuck::cast_slice(&[ambient]));
+        }
 
         // Rendering order for correct z-layering (inverse video cursor):
         //   1. Non-overlay backgrounds (window bg, stretches, char bg)
@@ -349,38 +402,93 @@ impl WgpuRenderer {
         }
 
         // Window backgrounds
+        let mut window_bgs: Vec<(Rect, Color)> = Vec::new();
         for glyph in &frame_glyphs.glyphs {
             if let FrameGlyph::Background { bounds, color } = glyph {
                 self.add_rect(
                     &mut non_overlay_rect_vertices,
                     bounds.x, bounds.y, bounds.width, bounds.height, color,
                 );
+                window_bgs.push((*bounds, *color));
             }
         }
+        // Cell backgrounds, drawn after the overdraw pass
+        let mut cell_bgs: Vec<(Rect, Color)> = Vec::new();
+        // Region cells drawn as merged selection runs instead of per-cell rects
+        let selection_face = if self.effects.selection_runs.enabled {
+            Some(self.effects.selection_runs.face_id).filter(|id| *id > 0)
+        } else {
+            None
+        };
+        // Region merged with other faces gets a new face id but keeps its background
+        let selection_bg: Option<Color> = selection_face
+            .and_then(|id| faces.get(&id))
+            .map(|f| f.background);
+        let is_selection = |face_id: u32, bg: &Color| {
+            selection_face == Some(face_id) || selection_bg.is_some_and(|s| s == *bg)
+        };
+        let mut selection_cells: Vec<Rect> = Vec::new();
+        let mut selection_color: Option<Color> = selection_bg;
+        // Backgrounds of faces with a gradient or image, drawn as merged runs
+        let has_bg_style = |face_id: u32| {
+            faces.get(&face_id).is_some_and(|f| f.background_style.is_some())
+        };
+        let mut styled_cells: Vec<(u32, Rect)> = Vec::new();
+
         // Non-overlay stretches (skip those inside a box span)
         let has_line_anims = !self.active_line_anims.is_empty() || !self.active_scroll_spacings.is_empty();
         for glyph in &frame_glyphs.glyphs {
-            if let FrameGlyph::Stretch { x, y, width, height, bg, is_overlay, .. } = glyph {
+            if let FrameGlyph::Stretch { x, y, width, height, bg, face_id, is_overlay } = glyph {
+                if !*is_overlay && is_selection(*face_id, bg) {
+                    selection_cells.push(Rect::new(*x, *y, *width, *height));
+                    selection_color.get_or_insert(*bg);
+                    continue;
+                }
                 if !*is_overlay && !overlaps_rounded_box_span(*x, *y, false, &box_spans) {
                     let ya = if has_line_anims { *y + self.line_y_offset(*x, *y) } else { *y };
-                    self.add_rect(&mut non_overlay_rect_vertices, *x, ya, *width, *height, bg);
+                    if has_bg_style(*face_id) {
+                        styled_cells.push((*face_id, Rect::new(*x, ya, *width, *height)));
+                    } else {
+                        cell_bgs.push((Rect::new(*x, ya, *width, *height), *bg));
+                    }
                 }
             }
         }
         // Non-overlay char backgrounds (skip boxed chars — they get rounded bg instead)
         for glyph in &frame_glyphs.glyphs {
-            if let FrameGlyph::Char { x, y, width, height, bg, is_overlay, .. } = glyph {
+            if let FrameGlyph::Char { x, y, width, height, bg, face_id, is_overlay, .. } = glyph {
                 if !*is_overlay {
                     if let Some(bg_color) = bg {
+                        if is_selection(*face_id, bg_color) {
+                            selection_cells.push(Rect::new(*x, *y, *width, *height));
+                            selection_color.get_or_insert(*bg_color);
+                            continue;
+                        }
                         if !overlaps_rounded_box_span(*x, *y, false, &box_spans) {
                             let ya = if has_line_anims { *y + self.line_y_offset(*x, *y) } else { *y };
-                            self.add_rect(&mut non_overlay_rect_vertices, *x, ya, *width, *height, bg_color);
+                            if has_bg_style(*face_id) {
+                                styled_cells.push((*face_id, Rect::new(*x, ya, *width, *height)));
+                            } else {
+                                cell_bgs.push((Rect::new(*x, ya, *width, *height), *bg_color));
+                            }
                         }
                     }
                 }
             }
         }
 
+        let (cell_bgs, overdraw) = prepare_backgrounds(&window_bgs, cell_bgs);
+        for (r, color) in &cell_bgs {
+            self.add_rect(&mut non_overlay_rect_vertices, r.x, r.y, r.width, r.height, color);
+        }
+        self.overdraw_stats = overdraw;
+        self.debug_rects.clear();
+        if self.effects.debug_overlay.mode == DebugOverlay::Overdraw {
+            self.debug_rects.extend(window_bgs.iter().chain(&cell_bgs).map(|(r, _)| *r));
+        }
+        self.row_cache.hits = 0;
+        self.row_cache.misses = 0;
+
         // --- Current line highlight ---
         if self.effects.line_highlight.enabled {
             let (lr, lg, lb, la) = self.effects.line_highlight.color;
@@ -545,25 +653,63 @@ impl WgpuRenderer {
         let mut overlay_rect_vertices: Vec<RectVertex> = Vec::new();
 
         // Overlay stretches (skip those inside a box span)
+        let mut overlay_styled_cells: Vec<(u32, Rect)> = Vec::new();
         for glyph in &frame_glyphs.glyphs {
-            if let FrameGlyph::Stretch { x, y, width, height, bg, is_overlay, .. } = glyph {
+            if let FrameGlyph::Stretch { x, y, width, height, bg, face_id, is_overlay } = glyph {
                 if *is_overlay && !overlaps_rounded_box_span(*x, *y, true, &box_spans) {
-                    self.add_rect(&mut overlay_rect_vertices, *x, *y, *width, *height, bg);
+                    if has_bg_style(*face_id) {
+                        overlay_styled_cells.push((*face_id, Rect::new(*x, *y, *width, *height)));
+                    } else {
+                        self.add_rect(&mut overlay_rect_vertices, *x, *y, *width, *height, bg);
+                    }
                 }
             }
         }
         // Overlay char backgrounds (skip those inside a box span)
         for glyph in &frame_glyphs.glyphs {
-            if let FrameGlyph::Char { x, y, width, height, bg, is_overlay, .. } = glyph {
+            if let FrameGlyph::Char { x, y, width, height, bg, face_id, is_overlay, .. } = glyph {
                 if *is_overlay {
                     if let Some(bg_color) = bg {
                         if !overlaps_rounded_box_span(*x, *y, true, &box_spans) {
-                            self.add_rect(&mut overlay_rect_vertices, *x, *y, *width, *height, bg_color);
+                            if has_bg_style(*face_id) {
+                                overlay_styled_cells.push((*face_id, Rect::new(*x, *y, *width, *height)));
+                            } else {
+                                self.add_rect(&mut overlay_rect_vertices, *x, *y, *width, *height, bg_color);
+                            }
                         }
                     }
                 }
             }
         }
+        // Styled backgrounds: gradients join the plain rects, images are
+        // drawn right after them
+        let non_overlay_bg_images = self.add_styled_backgrounds(
+            &mut non_overlay_rect_vertices, styled_cells, faces,
+        );
+        let overlay_bg_images = self.add_styled_backgrounds(
+            &mut overlay_rect_vertices, overlay_styled_cells, faces,
+        );
+
+        // === Text inversion under the filled box cursor ===
+        // At rest, the character under the cursor is drawn with the inverse fg.
+        // While the cursor animates, its moving rect is re-filled after text and
+        // the glyphs it covers are re-rendered inverted, clipped to the rect.
+        let inversion_enabled = self.effects.cursor_inversion.enabled && cursor_visible;
+        let inverse_fg: Option<Color> = frame_glyphs.cursor_inverse.as_ref().map(|inv| {
+            match self.effects.cursor_inversion.complement_fg {
+                Some((r, g, b)) => Color::new(r, g, b, 1.0).srgb_to_linear(),
+                None => inv.cursor_fg,
+            }
+        });
+        let moving_inversion_rect: Option<Rect> = match (&frame_glyphs.cursor_inverse, &animated_cursor) {
+            (Some(inv), Some(anim)) if inversion_enabled => {
+                let has_box_cursor = frame_glyphs.glyphs.iter().any(|g| matches!(g,
+                    FrameGlyph::Cursor { window_id, style: 0, .. } if *window_id == anim.window_id));
+                if has_box_cursor { inv.moving_rect(anim) } else { None }
+            }
+            _ => None,
+        };
+        let mut moving_inversion_fill: Option<Color> = None;
 
         // === Collect cursor bg rect for inverse video (drawn before text) ===
         // For filled box cursor (style 0), we draw the cursor background BEFORE text
@@ -649,7 +795,7 @@ impl WgpuRenderer {
                     // Compute effective cursor color (possibly overridden by color cycling)
                     let cycle_color;
                     let effective_color = if self.effects.cursor_color_cycle.enabled && *style != 3 {
-                        let elapsed = self.cursor_color_cycle_start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(self.cursor_color_cycle_start).as_secs_f32();
                         let hue = (elapsed * self.effects.cursor_color_cycle.speed) % 1.0;
                         cycle_color = Self::hsl_to_color(hue, self.effects.cursor_color_cycle.saturation, self.effects.cursor_color_cycle.lightness);
                         self.needs_continuous_redraw = true;
@@ -688,7 +834,10 @@ impl WgpuRenderer {
                                 } else {
                                     &inv.cursor_bg
                                 };
-                                if wake_active {
+                                if moving_inversion_rect.is_some() {
+                                    // Moving: the rect is filled after text by the inversion pass
+                                    moving_inversion_fill = Some(*inv_color);
+                                } else if wake_active {
                                     let (sx, sy, sw, sh) = Self::scale_rect(inv.x, inv.y, inv.width, inv.height, wake);
                                     self.add_rect(&mut cursor_bg_vertices, sx, sy, sw, sh, inv_color);
                                 } else {
@@ -788,6 +937,8 @@ impl WgpuRenderer {
             }
         }
 
+        self.blur.begin_frame();
+
         // Create command encoder
         let mut encoder = self
             .device
@@ -835,6 +986,70 @@ impl WgpuRenderer {
                 render_pass.set_vertex_buffer(0, rect_buffer.slice(..));
                 render_pass.draw(0..non_overlay_rect_vertices.len() as u32, 0..1);
             }
+            #[cfg(feature = "video")]
+            self.draw_background_videos(
+                &mut render_pass, frame_glyphs, Rect::new(0.0, 0.0, logical_w, logical_h),
+            );
+            self.draw_background_images(&mut render_pass, &non_overlay_bg_images);
+            self.draw_chrome_images(&mut render_pass, &frame_glyphs.chrome_regions);
+
+            // === Step 1 (cont.): Ambient layer over the window backgrounds ===
+            if self.effects.ambient.is_active() {
+                render_pass.set_pipeline(&self.ambient_pipeline);
+                render_pass.set_bind_group(0, &self.ambient_bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+
+            // === Step 1 (cont.): Region highlight as merged selection runs ===
+            if let (Some(color), false) = (selection_color, selection_cells.is_empty()) {
+                let runs = merge_selection_runs(&selection_cells);
+                let mut run_vertices: Vec<RoundedRectVertex> = Vec::new();
+                let mut joint_vertices: Vec<RectVertex> = Vec::new();
+                for run in &runs {
+                    let radius = self.effects.selection_runs.radius
+                        .min(run.height * 0.45)
+                        .min(run.width * 0.45)
+                        .max(0.0);
+                    self.add_rounded_rect(
+                        &mut run_vertices,
+                        run.x, run.y, run.width, run.height,
+                        run.width.max(run.height), radius, &color,
+                    );
+                    // Square off corners that join the line above or below
+                    if radius > 0.0 {
+                        let corners = [
+                            (run.x, run.y),
+                            (run.x + run.width - radius, run.y),
+                            (run.x + run.width - radius, run.y + run.height - radius),
+                            (run.x, run.y + run.height - radius),
+                        ];
+                        for (i, (cx, cy)) in corners.iter().enumerate() {
+                            if !run.rounded[i] {
+                                self.add_rect(&mut joint_vertices, *cx, *cy, radius, radius, &color);
+                            }
+                        }
+                    }
+                }
+                let run_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Selection Run Buffer"),
+                    contents: bytemuck::cast_slice(&run_vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+                render_pass.set_pipeline(&self.rounded_rect_pipeline);
+                render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                render_pass.set_vertex_buffer(0, run_buffer.slice(..));
+                render_pass.draw(0..run_vertices.len() as u32, 0..1);
+                if !joint_vertices.is_empty() {
+                    let joint_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Selection Joint Buffer"),
+                        contents: bytemuck::cast_slice(&joint_vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+                    render_pass.set_pipeline(&self.rect_pipeline);
+                    render_pass.set_vertex_buffer(0, joint_buffer.slice(..));
+                    render_pass.draw(0..joint_vertices.len() as u32, 0..1);
+                }
+            }
 
             // === Step 1a: Background pattern (dots/grid/crosshatch) ===
             if self.effects.bg_pattern.style > 0 {
@@ -987,7 +1202,7 @@ impl WgpuRenderer {
 
                     // Apply pulse modulation if enabled
                     if self.effects.cursor_pulse.enabled {
-                        let elapsed = self.cursor_pulse_start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(self.cursor_pulse_start).as_secs_f32();
                         let phase = elapsed * self.effects.cursor_pulse.speed * 2.0 * std::f32::consts::PI;
                         // Sine wave: maps [min_opacity..1.0] range
                         let t = (phase.sin() + 1.0) / 2.0; // 0.0 to 1.0
@@ -1114,7 +1329,7 @@ impl WgpuRenderer {
 
             // === Step 1f: Typing heat map overlay ===
             if self.effects.typing_heatmap.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let fade_dur = std::time::Duration::from_millis(self.effects.typing_heatmap.fade_ms as u64);
 
                 // Detect cursor movement and record heat entry
@@ -1380,7 +1595,7 @@ impl WgpuRenderer {
 
             // === Step 1i_magnetism: Cursor magnetism effect ===
             if self.effects.cursor_magnetism.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let dur = std::time::Duration::from_millis(self.effects.cursor_magnetism.duration_ms as u64);
 
                 // Detect cursor jump (large movement) and record
@@ -1525,9 +1740,9 @@ impl WgpuRenderer {
             // === Step 1i3: Line number pulse on cursor line ===
             if self.effects.line_number_pulse.enabled {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now();
+                    let now = self.clock.now();
                     let cycle = self.effects.line_number_pulse.cycle_ms as f64 / 1000.0;
-                    let elapsed = now.elapsed().as_secs_f64();
+                    let elapsed = now.duration_since(self.aurora_start).as_secs_f64();
                     let phase = (elapsed % cycle) / cycle;
                     let pulse = ((phase * std::f64::consts::TAU).sin() * 0.5 + 0.5) as f32;
                     let alpha = self.effects.line_number_pulse.intensity * pulse;
@@ -1561,9 +1776,9 @@ impl WgpuRenderer {
 
             // === Step 1i4: Window breathing border animation ===
             if self.effects.breathing_border.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let cycle = self.effects.breathing_border.cycle_ms as f64 / 1000.0;
-                let elapsed = now.elapsed().as_secs_f64();
+                let elapsed = now.duration_since(self.aurora_start).as_secs_f64();
                 let phase = (elapsed % cycle) / cycle;
                 let breath = ((phase * std::f64::consts::TAU).sin() * 0.5 + 0.5) as f32;
                 let alpha = self.effects.breathing_border.min_opacity + breath * (self.effects.breathing_border.max_opacity - self.effects.breathing_border.min_opacity);
@@ -1662,7 +1877,7 @@ impl WgpuRenderer {
 
             // === Step 1k: Cursor comet tail effect ===
             if self.effects.cursor_comet.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let fade_dur = std::time::Duration::from_millis(self.effects.cursor_comet.fade_ms as u64);
 
                 // Record cursor position
@@ -1723,7 +1938,7 @@ impl WgpuRenderer {
 
             // === Step 1l: Cursor particle trail effect ===
             if self.effects.cursor_particles.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let lifetime = std::time::Duration::from_millis(self.effects.cursor_particles.lifetime_ms as u64);
 
                 // Detect cursor movement and emit particles
@@ -1734,7 +1949,7 @@ impl WgpuRenderer {
                         let dy = (cur_pos.1 - prev_pos.1).abs();
                         if dx > 1.0 || dy > 1.0 {
                             // Emit particles from cursor center
-                            let seed = (now.elapsed().subsec_nanos() as u64).wrapping_mul(2654435761);
+                            let seed = (now.duration_since(self.aurora_start).subsec_nanos() as u64).wrapping_mul(2654435761);
                             for i in 0..self.effects.cursor_particles.count {
                                 // Simple hash-based pseudo-random
                                 let h = seed.wrapping_add(i as u64).wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
@@ -1763,13 +1978,10 @@ impl WgpuRenderer {
                     let gravity = self.effects.cursor_particles.gravity;
                     let mut part_verts: Vec<RectVertex> = Vec::new();
                     for p in &self.cursor_particles {
-                        let elapsed = now.duration_since(p.started).as_secs_f32();
-                        let t = (elapsed / p.lifetime.as_secs_f32()).min(1.0);
-                        let alpha = (1.0 - t) * 0.8;
+                        let Some((px, py, size, alpha)) = p.at(now, gravity) else {
+                            continue;
+                        };
                         if alpha > 0.001 {
-                            let px = p.x + p.vx * elapsed;
-                            let py = p.y + p.vy * elapsed + 0.5 * gravity * elapsed * elapsed;
-                            let size = 2.0 * (1.0 - t) + 0.5; // shrink over time
                             let c = Color::new(pr, pg, pb, alpha);
                             self.add_rect(&mut part_verts, px - size / 2.0, py - size / 2.0, size, size, &c);
                         }
@@ -1798,7 +2010,7 @@ impl WgpuRenderer {
                 let fw = self.width as f32 / self.scale_factor;
                 let fh = self.height as f32 / self.scale_factor;
                 let dt = 1.0 / 60.0_f32;
-                let now_ns = std::time::Instant::now().elapsed().subsec_nanos() as u64;
+                let now_ns = self.clock.now().duration_since(self.aurora_start).subsec_nanos() as u64;
 
                 // Spawn columns if needed
                 while self.matrix_rain_columns.len() < self.effects.matrix_rain.column_count as usize {
@@ -1864,7 +2076,7 @@ impl WgpuRenderer {
                 let (fr, fg, fb) = self.effects.frost_border.color;
                 let bw = self.effects.frost_border.width;
                 let base_alpha = self.effects.frost_border.opacity;
-                let now_ns = std::time::Instant::now().elapsed().subsec_nanos();
+                let now_ns = self.clock.now().duration_since(self.aurora_start).subsec_nanos();
                 let mut frost_verts: Vec<RectVertex> = Vec::new();
                 for info in &frame_glyphs.window_infos {
                     let b = &info.bounds;
@@ -1933,7 +2145,7 @@ impl WgpuRenderer {
 
             // Cursor ghost afterimage effect
             if self.effects.cursor_ghost.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let fade_dur = std::time::Duration::from_millis(self.effects.cursor_ghost.fade_ms as u64);
 
                 // Detect cursor movement and spawn ghost
@@ -1998,7 +2210,7 @@ impl WgpuRenderer {
 
             // Edge glow on scroll boundaries
             if self.effects.edge_glow.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 self.edge_glow_entries.retain(|e| now.duration_since(e.started) < e.duration);
                 if !self.edge_glow_entries.is_empty() {
                     let (gr, gg, gb) = self.effects.edge_glow.color;
@@ -2042,14 +2254,14 @@ impl WgpuRenderer {
 
             // Rain/drip ambient effect
             if self.effects.rain_effect.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let fw = self.width as f32 / self.scale_factor;
                 let fh = self.height as f32 / self.scale_factor;
                 let dt = 1.0 / 60.0_f32; // approximate frame delta
 
                 // Spawn drops if needed
                 while self.rain_drops.len() < self.effects.rain_effect.drop_count as usize {
-                    let seed = now.elapsed().subsec_nanos() as u64;
+                    let seed = now.duration_since(self.aurora_start).subsec_nanos() as u64;
                     let h = seed.wrapping_mul(2654435761).wrapping_add(self.rain_drops.len() as u64 * 6364136223846793005);
                     let x = ((h >> 16) & 0xFFFF) as f32 / 65535.0 * fw;
                     let y = -(((h >> 32) & 0xFFFF) as f32) / 65535.0 * fh * 0.5; // start above screen
@@ -2068,7 +2280,7 @@ impl WgpuRenderer {
                 for drop in &mut self.rain_drops {
                     drop.y += drop.speed * dt;
                     if drop.y > fh {
-                        let seed = now.elapsed().subsec_nanos() as u64;
+                        let seed = now.duration_since(self.aurora_start).subsec_nanos() as u64;
                         let h = seed.wrapping_mul(2654435761).wrapping_add((drop.x * 1000.0) as u64);
                         drop.x = ((h >> 16) & 0xFFFF) as f32 / 65535.0 * fw;
                         drop.y = -drop.length;
@@ -2102,7 +2314,7 @@ impl WgpuRenderer {
 
             // Cursor ripple wave effect
             if self.effects.cursor_ripple_wave.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
 
                 // Detect cursor movement and spawn ripple
                 if let Some(ref anim) = animated_cursor {
@@ -2179,7 +2391,7 @@ impl WgpuRenderer {
 
             // Aurora/northern lights effect
             if self.effects.aurora.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let elapsed = now.duration_since(self.aurora_start).as_secs_f64() * self.effects.aurora.speed as f64;
                 let fw = self.width as f32 / self.scale_factor;
                 let ah = self.effects.aurora.height;
@@ -2229,7 +2441,7 @@ impl WgpuRenderer {
 
             // === Heat distortion effect ===
             if self.effects.heat_distortion.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let ew = self.effects.heat_distortion.edge_width;
                 let intensity = self.effects.heat_distortion.intensity;
                 let spd = self.effects.heat_distortion.speed;
@@ -2276,7 +2488,7 @@ impl WgpuRenderer {
             // === Cursor lighthouse beam effect ===
             if self.effects.cursor_lighthouse.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let center_x = anim.x + anim.width / 2.0;
                     let center_y = anim.y + anim.height / 2.0;
                     let angle = now * self.effects.cursor_lighthouse.rotation_speed * std::f32::consts::PI * 2.0;
@@ -2318,7 +2530,7 @@ impl WgpuRenderer {
 
             // === Neon border effect ===
             if self.effects.neon_border.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (nr, ng, nb) = self.effects.neon_border.color;
                 let thick = self.effects.neon_border.thickness;
                 let intensity = self.effects.neon_border.intensity;
@@ -2364,7 +2576,7 @@ impl WgpuRenderer {
 
             // === Cursor sonar ping effect ===
             if self.effects.cursor_sonar_ping.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 self.cursor_sonar_ping_entries.retain(|e| now.duration_since(e.started) < e.duration);
                 let (pr, pg, pb) = self.effects.cursor_sonar_ping.color;
                 let ring_count = self.effects.cursor_sonar_ping.ring_count;
@@ -2418,7 +2630,7 @@ impl WgpuRenderer {
 
             // === Lightning bolt effect ===
             if self.effects.lightning_bolt.enabled {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let dt = now.duration_since(self.lightning_bolt_last).as_secs_f32();
                 self.lightning_bolt_last = now;
                 self.lightning_bolt_age += dt;
@@ -2487,7 +2699,7 @@ impl WgpuRenderer {
             // === Cursor orbit particles effect ===
             if self.effects.cursor_orbit_particles.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (pr, pg, pb) = self.effects.cursor_orbit_particles.color;
@@ -2528,7 +2740,7 @@ impl WgpuRenderer {
 
             // === Plasma border effect ===
             if self.effects.plasma_border.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (r1, g1, b1) = self.effects.plasma_border.color1;
                 let (r2, g2, b2) = self.effects.plasma_border.color2;
                 let bw = self.effects.plasma_border.width;
@@ -2587,7 +2799,7 @@ impl WgpuRenderer {
             // === Cursor heartbeat pulse effect ===
             if self.effects.cursor_heartbeat.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (hr, hg, hb) = self.effects.cursor_heartbeat.color;
@@ -2640,7 +2852,7 @@ impl WgpuRenderer {
 
             // === Topographic contour effect ===
             if self.effects.topo_contour.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (tr, tg, tb) = self.effects.topo_contour.color;
                 let top = self.effects.topo_contour.opacity;
                 let spacing = self.effects.topo_contour.spacing.max(5.0);
@@ -2689,12 +2901,12 @@ impl WgpuRenderer {
                     let cy = anim.y;
                     // Detect cursor move
                     if (cx - self.cursor_metronome_last_x).abs() > 1.0 || (cy - self.cursor_metronome_last_y).abs() > 1.0 {
-                        self.cursor_metronome_tick_start = Some(std::time::Instant::now());
+                        self.cursor_metronome_tick_start = Some(self.clock.now());
                         self.cursor_metronome_last_x = cx;
                         self.cursor_metronome_last_y = cy;
                     }
                     if let Some(start) = self.cursor_metronome_tick_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let duration = self.effects.cursor_metronome.fade_ms as f32 / 1000.0;
                         if elapsed < duration {
                             let t = elapsed / duration;
@@ -2733,7 +2945,7 @@ impl WgpuRenderer {
 
             // === Constellation overlay effect ===
             if self.effects.constellation.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (sr, sg, sb) = self.effects.constellation.color;
                 let sop = self.effects.constellation.opacity;
                 let count = self.effects.constellation.star_count.min(200);
@@ -2794,7 +3006,7 @@ impl WgpuRenderer {
             // === Cursor radar sweep effect ===
             if self.effects.cursor_radar.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (rr, rg, rb) = self.effects.cursor_radar.color;
@@ -2861,7 +3073,7 @@ impl WgpuRenderer {
 
             // === Kaleidoscope overlay effect ===
             if self.effects.kaleidoscope.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (kr, kg, kb) = self.effects.kaleidoscope.color;
                 let kop = self.effects.kaleidoscope.opacity;
                 let segs = self.effects.kaleidoscope.segments.max(3).min(12);
@@ -2911,12 +3123,12 @@ impl WgpuRenderer {
                     let cy = anim.y + anim.height / 2.0;
                     // Detect cursor move
                     if (cx - self.cursor_ripple_ring_last_x).abs() > 1.0 || (cy - self.cursor_ripple_ring_last_y).abs() > 1.0 {
-                        self.cursor_ripple_ring_start = Some(std::time::Instant::now());
+                        self.cursor_ripple_ring_start = Some(self.clock.now());
                         self.cursor_ripple_ring_last_x = cx;
                         self.cursor_ripple_ring_last_y = cy;
                     }
                     if let Some(start) = self.cursor_ripple_ring_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let max_r = self.effects.cursor_ripple_ring.max_radius;
                         let duration = max_r / (self.effects.cursor_ripple_ring.speed * 60.0);
                         if elapsed < duration {
@@ -2924,21 +3136,14 @@ impl WgpuRenderer {
                             let (rr, rg, rb) = self.effects.cursor_ripple_ring.color;
                             let rop = self.effects.cursor_ripple_ring.opacity;
                             let count = self.effects.cursor_ripple_ring.count.max(1).min(8);
-                            let mut ring_verts: Vec<RectVertex> = Vec::new();
+                            let mut ring_verts: Vec<RoundedRectVertex> = Vec::new();
                             for ring in 0..count {
                                 let ring_t = (t - ring as f32 * 0.15).max(0.0).min(1.0);
                                 if ring_t <= 0.0 { continue; }
                                 let radius = ring_t * max_r;
                                 let fade = (1.0 - ring_t) * rop;
-                                let ring_segs = 32;
-                                for seg in 0..ring_segs {
-                                    let a = seg as f32 / ring_segs as f32 * std::f32::consts::PI * 2.0;
-                                    let px = cx + a.cos() * radius;
-                                    let py = cy + a.sin() * radius;
-                                    let dot = 2.0;
-                                    let c = Color::new(rr, rg, rb, fade);
-                                    self.add_rect(&mut ring_verts, px - dot / 2.0, py - dot / 2.0, dot, dot, &c);
-                                }
+                                let c = Color::new(rr, rg, rb, fade);
+                                self.add_ring(&mut ring_verts, cx, cy, radius, 2.0, &c);
                             }
                             if !ring_verts.is_empty() {
                                 let rr_buf = self.device.create_buffer_init(
@@ -2948,7 +3153,7 @@ impl WgpuRenderer {
                                         usage: wgpu::BufferUsages::VERTEX,
                                     },
                                 );
-                                render_pass.set_pipeline(&self.rect_pipeline);
+                                render_pass.set_pipeline(&self.rounded_rect_pipeline);
                                 render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
                                 render_pass.set_vertex_buffer(0, rr_buf.slice(..));
                                 render_pass.draw(0..ring_verts.len() as u32, 0..1);
@@ -2963,7 +3168,7 @@ impl WgpuRenderer {
 
             // === Noise field overlay effect ===
             if self.effects.noise_field.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (nr, ng, nb) = self.effects.noise_field.color;
                 let nop = self.effects.noise_field.opacity;
                 let scale = self.effects.noise_field.scale.max(10.0);
@@ -3054,7 +3259,7 @@ impl WgpuRenderer {
 
             // === Spiral vortex overlay effect ===
             if self.effects.spiral_vortex.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (vr, vg, vb) = self.effects.spiral_vortex.color;
                 let vop = self.effects.spiral_vortex.opacity;
                 let arms = self.effects.spiral_vortex.arms.max(2).min(12);
@@ -3103,12 +3308,12 @@ impl WgpuRenderer {
                     let cy = anim.y + anim.height / 2.0;
                     // Detect cursor move
                     if (cx - self.cursor_shockwave_last_x).abs() > 1.0 || (cy - self.cursor_shockwave_last_y).abs() > 1.0 {
-                        self.cursor_shockwave_start = Some(std::time::Instant::now());
+                        self.cursor_shockwave_start = Some(self.clock.now());
                         self.cursor_shockwave_last_x = cx;
                         self.cursor_shockwave_last_y = cy;
                     }
                     if let Some(start) = self.cursor_shockwave_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let max_r = self.effects.cursor_shockwave.radius;
                         let duration = 1.0 / self.effects.cursor_shockwave.decay;
                         if elapsed < duration {
@@ -3117,26 +3322,14 @@ impl WgpuRenderer {
                             let (sr, sg, sb) = self.effects.cursor_shockwave.color;
                             let sop = self.effects.cursor_shockwave.opacity;
                             let fade = (1.0 - t) * (1.0 - t);
-                            let mut sw_verts: Vec<RectVertex> = Vec::new();
+                            let mut sw_verts: Vec<RoundedRectVertex> = Vec::new();
                             // Ring at current radius
                             let ring_thick = 3.0 * (1.0 - t * 0.5);
-                            let ring_segs = 48;
-                            for seg in 0..ring_segs {
-                                let a = seg as f32 / ring_segs as f32 * std::f32::consts::PI * 2.0;
-                                let px = cx + a.cos() * radius;
-                                let py = cy + a.sin() * radius;
-                                let c = Color::new(sr, sg, sb, sop * fade);
-                                self.add_rect(&mut sw_verts, px - ring_thick / 2.0, py - ring_thick / 2.0, ring_thick, ring_thick, &c);
-                            }
+                            let c = Color::new(sr, sg, sb, sop * fade);
+                            self.add_ring(&mut sw_verts, cx, cy, radius, ring_thick, &c);
                             // Inner glow
-                            let inner_r = radius * 0.7;
-                            for seg in 0..24 {
-                                let a = seg as f32 / 24.0 * std::f32::consts::PI * 2.0;
-                                let px = cx + a.cos() * inner_r;
-                                let py = cy + a.sin() * inner_r;
-                                let c = Color::new(sr, sg, sb, sop * fade * 0.3);
-                                self.add_rect(&mut sw_verts, px - 1.0, py - 1.0, 2.0, 2.0, &c);
-                            }
+                            let c = Color::new(sr, sg, sb, sop * fade * 0.3);
+                            self.add_ring(&mut sw_verts, cx, cy, radius * 0.7, 2.0, &c);
                             if !sw_verts.is_empty() {
                                 let sw_buf = self.device.create_buffer_init(
                                     &wgpu::util::BufferInitDescriptor {
@@ -3145,7 +3338,7 @@ impl WgpuRenderer {
                                         usage: wgpu::BufferUsages::VERTEX,
                                     },
                                 );
-                                render_pass.set_pipeline(&self.rect_pipeline);
+                                render_pass.set_pipeline(&self.rounded_rect_pipeline);
                                 render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
                                 render_pass.set_vertex_buffer(0, sw_buf.slice(..));
                                 render_pass.draw(0..sw_verts.len() as u32, 0..1);
@@ -3160,7 +3353,7 @@ impl WgpuRenderer {
 
             // === Diamond lattice overlay effect ===
             if self.effects.diamond_lattice.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (dr, dg, db) = self.effects.diamond_lattice.color;
                 let dop = self.effects.diamond_lattice.opacity;
                 let cell = self.effects.diamond_lattice.cell_size.max(10.0);
@@ -3222,7 +3415,7 @@ impl WgpuRenderer {
                     let gop = self.effects.cursor_gravity_well.opacity;
                     let field_r = self.effects.cursor_gravity_well.field_radius;
                     let lines = self.effects.cursor_gravity_well.line_count.max(4).min(24);
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let mut gw_verts: Vec<RectVertex> = Vec::new();
                     for line in 0..lines {
                         let base_angle = line as f32 * std::f32::consts::PI * 2.0 / lines as f32 + now * 0.2;
@@ -3260,7 +3453,7 @@ impl WgpuRenderer {
 
             // === Wave interference overlay effect ===
             if self.effects.wave_interference.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (wr, wg, wb) = self.effects.wave_interference.color;
                 let wop = self.effects.wave_interference.opacity;
                 let wl = self.effects.wave_interference.wavelength.max(10.0);
@@ -3321,7 +3514,7 @@ impl WgpuRenderer {
             // === Cursor portal effect ===
             if self.effects.cursor_portal.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (pr, pg, pb) = self.effects.cursor_portal.color;
@@ -3373,7 +3566,7 @@ impl WgpuRenderer {
 
             // === Chevron pattern overlay effect ===
             if self.effects.chevron_pattern.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.chevron_pattern.color;
                 let cop = self.effects.chevron_pattern.opacity;
                 let spacing = self.effects.chevron_pattern.spacing.max(15.0);
@@ -3432,12 +3625,12 @@ impl WgpuRenderer {
                     let cy = anim.y + anim.height / 2.0;
                     // Detect cursor move
                     if (cx - self.cursor_bubble_last_x).abs() > 1.0 || (cy - self.cursor_bubble_last_y).abs() > 1.0 {
-                        self.cursor_bubble_spawn_time = Some(std::time::Instant::now());
+                        self.cursor_bubble_spawn_time = Some(self.clock.now());
                         self.cursor_bubble_last_x = cx;
                         self.cursor_bubble_last_y = cy;
                     }
                     if let Some(spawn) = self.cursor_bubble_spawn_time {
-                        let elapsed = spawn.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(spawn).as_secs_f32();
                         let duration = 1.5;
                         if elapsed < duration {
                             let (br, bg, bb) = self.effects.cursor_bubble.color;
@@ -3492,7 +3685,7 @@ impl WgpuRenderer {
 
             // === Sunburst pattern overlay effect ===
             if self.effects.sunburst_pattern.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.sunburst_pattern.color;
                 let ray_count = self.effects.sunburst_pattern.ray_count.max(4) as f32;
                 let speed = self.effects.sunburst_pattern.speed;
@@ -3555,12 +3748,12 @@ impl WgpuRenderer {
                     let dx = cx - self.cursor_firework_last_x;
                     let dy = cy - self.cursor_firework_last_y;
                     if dx.abs() > 1.0 || dy.abs() > 1.0 {
-                        self.cursor_firework_start = Some(std::time::Instant::now());
+                        self.cursor_firework_start = Some(self.clock.now());
                         self.cursor_firework_last_x = cx;
                         self.cursor_firework_last_y = cy;
                     }
                     if let Some(start) = self.cursor_firework_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let duration = 0.6;
                         if elapsed < duration {
                             let t = elapsed / duration;
@@ -3603,7 +3796,7 @@ impl WgpuRenderer {
 
             // === Honeycomb dissolve overlay effect ===
             if self.effects.honeycomb_dissolve.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.honeycomb_dissolve.color;
                 let cell = self.effects.honeycomb_dissolve.cell_size.max(8.0);
                 let speed = self.effects.honeycomb_dissolve.speed;
@@ -3652,7 +3845,7 @@ impl WgpuRenderer {
                 if let Some(ref anim) = animated_cursor {
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (cr, cg, cb) = self.effects.cursor_tornado.color;
                     let radius = self.effects.cursor_tornado.radius;
                     let opacity = self.effects.cursor_tornado.opacity;
@@ -3702,7 +3895,7 @@ impl WgpuRenderer {
 
             // === Moiré pattern overlay effect ===
             if self.effects.moire_pattern.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.moire_pattern.color;
                 let spacing = self.effects.moire_pattern.line_spacing.max(4.0);
                 let angle_off = self.effects.moire_pattern.angle_offset * std::f32::consts::PI / 180.0;
@@ -3757,12 +3950,12 @@ impl WgpuRenderer {
                     let dx = cx - self.cursor_lightning_last_x;
                     let dy = cy - self.cursor_lightning_last_y;
                     if dx.abs() > 1.0 || dy.abs() > 1.0 {
-                        self.cursor_lightning_start = Some(std::time::Instant::now());
+                        self.cursor_lightning_start = Some(self.clock.now());
                         self.cursor_lightning_last_x = cx;
                         self.cursor_lightning_last_y = cy;
                     }
                     if let Some(start) = self.cursor_lightning_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let duration = 0.4;
                         if elapsed < duration {
                             let t = elapsed / duration;
@@ -3815,7 +4008,7 @@ impl WgpuRenderer {
 
             // === Dot matrix overlay effect ===
             if self.effects.dot_matrix.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.dot_matrix.color;
                 let spacing = self.effects.dot_matrix.spacing.max(4.0);
                 let pulse = self.effects.dot_matrix.pulse_speed;
@@ -3858,12 +4051,12 @@ impl WgpuRenderer {
                     let dx = cx - self.cursor_snowflake_last_x;
                     let dy = cy - self.cursor_snowflake_last_y;
                     if dx.abs() > 1.0 || dy.abs() > 1.0 {
-                        self.cursor_snowflake_start = Some(std::time::Instant::now());
+                        self.cursor_snowflake_start = Some(self.clock.now());
                         self.cursor_snowflake_last_x = cx;
                         self.cursor_snowflake_last_y = cy;
                     }
                     if let Some(start) = self.cursor_snowflake_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let duration = 2.0;
                         if elapsed < duration {
                             let t = elapsed / duration;
@@ -3911,7 +4104,7 @@ impl WgpuRenderer {
 
             // === Concentric rings overlay effect ===
             if self.effects.concentric_rings.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.concentric_rings.color;
                 let spacing = self.effects.concentric_rings.spacing.max(10.0);
                 let speed = self.effects.concentric_rings.expansion_speed;
@@ -3959,7 +4152,7 @@ impl WgpuRenderer {
                 if let Some(ref anim) = animated_cursor {
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y;
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (cr, cg, cb) = self.effects.cursor_flame.color;
                     let opacity = self.effects.cursor_flame.opacity;
                     let flame_h = self.effects.cursor_flame.height;
@@ -3998,7 +4191,7 @@ impl WgpuRenderer {
 
             // === Zigzag pattern overlay effect ===
             if self.effects.zigzag_pattern.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.zigzag_pattern.color;
                 let amplitude = self.effects.zigzag_pattern.amplitude;
                 let freq = self.effects.zigzag_pattern.frequency;
@@ -4040,7 +4233,7 @@ impl WgpuRenderer {
                 if let Some(ref anim) = animated_cursor {
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (cr, cg, cb) = self.effects.cursor_crystal.color;
                     let opacity = self.effects.cursor_crystal.opacity;
                     let radius = self.effects.cursor_crystal.radius;
@@ -4163,7 +4356,7 @@ impl WgpuRenderer {
             // === Cursor water drop effect ===
             if self.effects.cursor_water_drop.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (wr, wg, wb) = self.effects.cursor_water_drop.color;
                     let ripple_count = self.effects.cursor_water_drop.ripple_count;
                     let speed = self.effects.cursor_water_drop.expand_speed;
@@ -4207,7 +4400,7 @@ impl WgpuRenderer {
             if self.effects.guilloche.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (gr, gg, gb) = self.effects.guilloche.color;
                 let curves = self.effects.guilloche.curve_count;
                 let freq = self.effects.guilloche.wave_freq;
@@ -4253,7 +4446,7 @@ impl WgpuRenderer {
             // === Cursor pixel dust effect ===
             if self.effects.cursor_pixel_dust.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (pr, pg, pb) = self.effects.cursor_pixel_dust.color;
                     let dust_count = self.effects.cursor_pixel_dust.count;
                     let scatter = self.effects.cursor_pixel_dust.scatter_speed;
@@ -4300,7 +4493,7 @@ impl WgpuRenderer {
             if self.effects.celtic_knot.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (kr, kg, kb) = self.effects.celtic_knot.color;
                 let scale = self.effects.celtic_knot.scale;
                 let speed = self.effects.celtic_knot.weave_speed;
@@ -4359,7 +4552,7 @@ impl WgpuRenderer {
             // === Cursor candle flame effect ===
             if self.effects.cursor_candle_flame.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (fr, fg, fb) = self.effects.cursor_candle_flame.color;
                     let flame_h = self.effects.cursor_candle_flame.height as f32;
                     let flicker = self.effects.cursor_candle_flame.flicker_speed;
@@ -4484,7 +4677,7 @@ impl WgpuRenderer {
             // === Cursor moth flame effect ===
             if self.effects.cursor_moth_flame.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (mr, mg, mb) = self.effects.cursor_moth_flame.color;
                     let moth_count = self.effects.cursor_moth_flame.moth_count;
                     let orbit = self.effects.cursor_moth_flame.orbit_speed;
@@ -4581,7 +4774,7 @@ impl WgpuRenderer {
             // === Cursor sparkler effect ===
             if self.effects.cursor_sparkler.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (sr, sg, sb) = self.effects.cursor_sparkler.color;
                     let spark_count = self.effects.cursor_sparkler.spark_count;
                     let burn = self.effects.cursor_sparkler.burn_speed;
@@ -4673,7 +4866,7 @@ impl WgpuRenderer {
             // === Cursor plasma ball effect ===
             if self.effects.cursor_plasma_ball.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (pr, pg, pb) = self.effects.cursor_plasma_ball.color;
                     let tendril_count = self.effects.cursor_plasma_ball.tendril_count;
                     let arc_speed = self.effects.cursor_plasma_ball.arc_speed;
@@ -4728,7 +4921,7 @@ impl WgpuRenderer {
             if self.effects.trefoil_knot.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (kr, kg, kb) = self.effects.trefoil_knot.color;
                 let knot_size = self.effects.trefoil_knot.size;
                 let rot_speed = self.effects.trefoil_knot.rotation_speed;
@@ -4772,7 +4965,7 @@ impl WgpuRenderer {
             // === Cursor quill pen effect ===
             if self.effects.cursor_quill_pen.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (qr, qg, qb) = self.effects.cursor_quill_pen.color;
                     let trail_len = self.effects.cursor_quill_pen.trail_length;
                     let ink_speed = self.effects.cursor_quill_pen.ink_speed;
@@ -4902,7 +5095,7 @@ impl WgpuRenderer {
             // === Cursor aurora borealis effect ===
             if self.effects.cursor_aurora_borealis.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let (ar, ag, ab) = self.effects.cursor_aurora_borealis.color;
                     let band_count = self.effects.cursor_aurora_borealis.band_count;
                     let shimmer = self.effects.cursor_aurora_borealis.shimmer_speed;
@@ -4956,7 +5149,7 @@ impl WgpuRenderer {
             if self.effects.target_reticle.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (tr, tg, tb) = self.effects.target_reticle.color;
                 let ring_count = self.effects.target_reticle.ring_count;
                 let pulse = self.effects.target_reticle.pulse_speed;
@@ -4998,7 +5191,7 @@ impl WgpuRenderer {
             // === Cursor feather effect ===
             if self.effects.cursor_feather.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (fr, fg, fb) = self.effects.cursor_feather.color;
@@ -5088,7 +5281,7 @@ impl WgpuRenderer {
             // === Cursor stardust effect ===
             if self.effects.cursor_stardust.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (sr, sg, sb) = self.effects.cursor_stardust.color;
@@ -5163,7 +5356,7 @@ impl WgpuRenderer {
             // === Cursor compass needle effect ===
             if self.effects.cursor_compass_needle.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (nr, ng, nb) = self.effects.cursor_compass_needle.color;
@@ -5212,7 +5405,7 @@ impl WgpuRenderer {
             if self.effects.sine_wave.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (sr, sg, sb) = self.effects.sine_wave.color;
                 let amplitude = self.effects.sine_wave.amplitude;
                 let wavelength = self.effects.sine_wave.wavelength;
@@ -5250,7 +5443,7 @@ impl WgpuRenderer {
             // === Cursor galaxy effect ===
             if self.effects.cursor_galaxy.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (gr, gg, gb) = self.effects.cursor_galaxy.color;
@@ -5291,7 +5484,7 @@ impl WgpuRenderer {
             if self.effects.rotating_gear.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (gr, gg, gb) = self.effects.rotating_gear.color;
                 let gear_size = self.effects.rotating_gear.size;
                 let speed = self.effects.rotating_gear.speed;
@@ -5344,7 +5537,7 @@ impl WgpuRenderer {
             // === Cursor prism effect ===
             if self.effects.cursor_prism.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let ray_count = self.effects.cursor_prism.ray_count;
@@ -5387,7 +5580,7 @@ impl WgpuRenderer {
             if self.effects.crosshatch_pattern.enabled {
                 let width = self.width() as f32;
                 let height = self.height() as f32;
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.crosshatch_pattern.color;
                 let spacing = self.effects.crosshatch_pattern.line_spacing;
                 let angle_deg = self.effects.crosshatch_pattern.angle;
@@ -5456,7 +5649,7 @@ impl WgpuRenderer {
             // === Cursor moth effect ===
             if self.effects.cursor_moth.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let moth_count = self.effects.cursor_moth.count;
@@ -5500,7 +5693,7 @@ impl WgpuRenderer {
 
             // === Hex grid overlay effect ===
             if self.effects.hex_grid.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (hr, hg, hb) = self.effects.hex_grid.color;
                 let hop = self.effects.hex_grid.opacity;
                 let cell = self.effects.hex_grid.cell_size.max(10.0);
@@ -5556,6 +5749,7 @@ impl WgpuRenderer {
             // === Cursor sparkle burst effect ===
             if self.effects.cursor_sparkle_burst.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
+                    let now = self.clock.now();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     // Detect cursor movement to spawn burst
@@ -5568,11 +5762,11 @@ impl WgpuRenderer {
                         };
                         if should_spawn {
                             let seed = (cx as u32).wrapping_mul(31).wrapping_add(cy as u32).wrapping_mul(17).wrapping_add(
-                                std::time::Instant::now().elapsed().subsec_nanos()
+                                now.duration_since(self.aurora_start).subsec_nanos()
                             );
                             self.cursor_sparkle_burst_entries.push(SparkleBurstEntry {
                                 cx, cy,
-                                started: std::time::Instant::now(),
+                                started: now,
                                 seed,
                             });
                             if self.cursor_sparkle_burst_entries.len() > 20 {
@@ -5586,9 +5780,9 @@ impl WgpuRenderer {
                     let radius = self.effects.cursor_sparkle_burst.radius;
                     let mut sparkle_verts: Vec<RectVertex> = Vec::new();
                     let duration = 0.4_f32;
-                    self.cursor_sparkle_burst_entries.retain(|e| e.started.elapsed().as_secs_f32() < duration);
+                    self.cursor_sparkle_burst_entries.retain(|e| now.duration_since(e.started).as_secs_f32() < duration);
                     for entry in &self.cursor_sparkle_burst_entries {
-                        let t = entry.started.elapsed().as_secs_f32() / duration;
+                        let t = now.duration_since(entry.started).as_secs_f32() / duration;
                         let fade = 1.0 - t;
                         for i in 0..count {
                             let mut h = entry.seed.wrapping_add(i * 2654435761);
@@ -5624,7 +5818,7 @@ impl WgpuRenderer {
 
             // === Circuit board trace effect ===
             if self.effects.circuit_trace.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (cr, cg, cb) = self.effects.circuit_trace.color;
                 let cop = self.effects.circuit_trace.opacity;
                 let tw = self.effects.circuit_trace.width;
@@ -5696,7 +5890,7 @@ impl WgpuRenderer {
             // === Cursor compass rose effect ===
             if self.effects.cursor_compass.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (cr, cg, cb) = self.effects.cursor_compass.color;
@@ -5759,7 +5953,7 @@ impl WgpuRenderer {
 
             // === Warp/distortion grid effect ===
             if self.effects.warp_grid.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let (wr, wg, wb) = self.effects.warp_grid.color;
                 let wop = self.effects.warp_grid.opacity;
                 let density = self.effects.warp_grid.density.max(2) as f32;
@@ -5812,7 +6006,7 @@ impl WgpuRenderer {
             // === Cursor DNA helix trail effect ===
             if self.effects.cursor_dna_helix.enabled && cursor_visible {
                 if let Some(ref anim) = animated_cursor {
-                    let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                    let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                     let cx = anim.x + anim.width / 2.0;
                     let cy = anim.y + anim.height / 2.0;
                     let (c1r, c1g, c1b) = self.effects.cursor_dna_helix.color1;
@@ -5865,7 +6059,7 @@ impl WgpuRenderer {
 
             // === Prism/rainbow edge effect ===
             if self.effects.prism_edge.enabled {
-                let now = std::time::Instant::now().duration_since(self.aurora_start).as_secs_f32();
+                let now = self.clock.now().duration_since(self.aurora_start).as_secs_f32();
                 let pw = self.effects.prism_edge.width;
                 let pop = self.effects.prism_edge.opacity;
                 let sat = self.effects.prism_edge.saturation;
@@ -5954,12 +6148,12 @@ impl WgpuRenderer {
                     let cy = anim.y + anim.height / 2.0;
                     // Detect cursor move
                     if (cx - self.cursor_pendulum_last_x).abs() > 1.0 || (cy - self.cursor_pendulum_last_y).abs() > 1.0 {
-                        self.cursor_pendulum_swing_start = Some(std::time::Instant::now());
+                        self.cursor_pendulum_swing_start = Some(self.clock.now());
                         self.cursor_pendulum_last_x = cx;
                         self.cursor_pendulum_last_y = cy;
                     }
                     if let Some(start) = self.cursor_pendulum_swing_start {
-                        let elapsed = start.elapsed().as_secs_f32();
+                        let elapsed = self.clock.now().duration_since(start).as_secs_f32();
                         let (pr, pg, pb) = self.effects.cursor_pendulum.color;
                         let pop = self.effects.cursor_pendulum.opacity;
                         let arc_len = self.effects.cursor_pendulum.arc_length;
@@ -6079,6 +6273,125 @@ impl WgpuRenderer {
             for overlay_pass in 0..2 {
                 let want_overlay = overlay_pass == 1;
 
+                // Blur what is behind overlay content: the render pass is
+                // split so the compute passes can read the target
+                let blur_regions = match backdrop {
+                    Some(_) if want_overlay && self.effects.backdrop_blur.enabled => {
+                        frame_glyphs.blur_regions.as_slice()
+                    }
+                    _ => &[],
+                };
+                if !blur_regions.is_empty() {
+                    drop(render_pass);
+                    let target = backdrop.expect("checked above");
+                    let settings = BlurSettings {
+                        radius: (self.effects.backdrop_blur.radius * self.scale_factor).round() as u32,
+                        passes: self.effects.backdrop_blur.passes,
+                    };
+                    let mut blurred = Vec::new();
+                    for region in blur_regions {
+                        let b = &region.bounds;
+                        let x0 = (b.x * self.scale_factor).floor().max(0.0) as u32;
+                        let y0 = (b.y * self.scale_factor).floor().max(0.0) as u32;
+                        let x1 = (((b.x + b.width) * self.scale_factor).ceil().max(0.0) as u32).min(target.width());
+                        let y1 = (((b.y + b.height) * self.scale_factor).ceil().max(0.0) as u32).min(target.height());
+                        if x1 <= x0 || y1 <= y0 {
+                            continue;
+                        }
+                        let ok = self.blur.blur(
+                            &self.device, &self.queue, &mut encoder, target,
+                            region.id, [x0, y0, x1 - x0, y1 - y0],
+                            backdrop_signature(frame_glyphs, b), settings,
+                            self.image_cache.bind_group_layout(), self.image_cache.sampler(),
+                        );
+                        if ok {
+                            // Draw over the pixels actually blurred
+                            let s = self.scale_factor;
+                            let rect = Rect::new(x0 as f32 / s, y0 as f32 / s, (x1 - x0) as f32 / s, (y1 - y0) as f32 / s);
+                            blurred.push((region.id, rect, region.corner_radius));
+                        }
+                    }
+
+                    render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Frame Glyphs Overlay Pass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+
+                    if !blurred.is_empty() {
+                        let mut blur_vertices: Vec<RoundedRectVertex> = Vec::with_capacity(blurred.len() * 6);
+                        for (_, rect, corner_radius) in &blurred {
+                            self.add_rounded_rect(
+                                &mut blur_vertices, rect.x, rect.y, rect.width, rect.height,
+                                0.0, corner_radius.max(0.0), &Color::WHITE,
+                            );
+                        }
+                        let blur_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Backdrop Blur Buffer"),
+                            contents: bytemuck::cast_slice(&blur_vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        });
+                        render_pass.set_pipeline(&self.rounded_image_pipeline);
+                        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, blur_buffer.slice(..));
+                        for (i, (id, _, _)) in blurred.iter().enumerate() {
+                            if let Some(bind_group) = self.blur.bind_group(*id) {
+                                render_pass.set_bind_group(1, bind_group, &[]);
+                                let start = (i * 6) as u32;
+                                render_pass.draw(start..start + 6, 0..1);
+                            }
+                        }
+                    }
+                }
+
+                // Backdrop images sit under overlay backgrounds, which may
+                // tint them
+                if want_overlay && !frame_glyphs.backdrop_images.is_empty() {
+                    render_pass.set_pipeline(&self.image_pipeline);
+                    render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                    for backdrop in &frame_glyphs.backdrop_images {
+                        let Some(cached) = self.image_cache.get(backdrop.image_id) else {
+                            continue;
+                        };
+                        let b = &backdrop.bounds;
+                        if b.width <= 0.0 || b.height <= 0.0 || cached.width == 0 || cached.height == 0 {
+                            continue;
+                        }
+                        // Crop the image to the aspect ratio of the bounds
+                        let scale = (b.width / cached.width as f32).max(b.height / cached.height as f32);
+                        let u = (b.width / (cached.width as f32 * scale)).min(1.0);
+                        let v = (b.height / (cached.height as f32 * scale)).min(1.0);
+                        let (u0, v0) = ((1.0 - u) / 2.0, (1.0 - v) / 2.0);
+                        let (u1, v1) = (u0 + u, v0 + v);
+                        let white = [1.0, 1.0, 1.0, 1.0];
+                        let vertices = [
+                            GlyphVertex { position: [b.x, b.y], tex_coords: [u0, v0], color: white },
+                            GlyphVertex { position: [b.x + b.width, b.y], tex_coords: [u1, v0], color: white },
+                            GlyphVertex { position: [b.x + b.width, b.y + b.height], tex_coords: [u1, v1], color: white },
+                            GlyphVertex { position: [b.x, b.y], tex_coords: [u0, v0], color: white },
+                            GlyphVertex { position: [b.x + b.width, b.y + b.height], tex_coords: [u1, v1], color: white },
+                            GlyphVertex { position: [b.x, b.y + b.height], tex_coords: [u0, v1], color: white },
+                        ];
+                        let backdrop_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Backdrop Image Buffer"),
+                            contents: bytemuck::cast_slice(&vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        });
+                        render_pass.set_bind_group(1, &cached.bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, backdrop_buffer.slice(..));
+                        render_pass.draw(0..6, 0..1);
+                    }
+                }
+
                 // === Step 3: Draw overlay backgrounds before overlay text ===
                 if want_overlay && !overlay_rect_vertices.is_empty() {
                     let rect_buffer =
@@ -6094,6 +6407,9 @@ impl WgpuRenderer {
                     render_pass.set_vertex_buffer(0, rect_buffer.slice(..));
                     render_pass.draw(0..overlay_rect_vertices.len() as u32, 0..1);
                 }
+                if want_overlay {
+                    self.draw_background_images(&mut render_pass, &overlay_bg_images);
+                }
 
                 // Draw filled rounded rect backgrounds for overlay ROUNDED boxed spans.
                 if want_overlay {
@@ -6137,12 +6453,48 @@ impl WgpuRenderer {
                 // Composed glyphs rendered individually (each is unique, no batching)
                 let mut composed_mask_data: Vec<(ComposedGlyphKey, [GlyphVertex; 6])> = Vec::new();
                 let mut composed_color_data: Vec<(ComposedGlyphKey, [GlyphVertex; 6])> = Vec::new();
+                // Inverted copies of glyphs under the moving box cursor
+                let mut inverted_data: Vec<(GlyphKey, [GlyphVertex; 6])> = Vec::new();
+                let mut inverted_composed_data: Vec<(ComposedGlyphKey, [GlyphVertex; 6])> = Vec::new();
+
+                // Rows unchanged since the last frame reuse their vertices,
+                // unless an effect animates text or the cursor sits on them
+                let row_hashes = hash_rows(&frame_glyphs.glyphs, want_overlay);
+                let cache_rows = !has_line_anims
+                    && moving_inversion_rect.is_none()
+                    && (!self.effects.text_fade_in.enabled || self.active_text_fades.is_empty())
+                    && (!self.effects.mode_line_transition.enabled || self.active_mode_line_fades.is_empty());
+                let cursor_row = |row: &RowKey| frame_glyphs.cursor_inverse.as_ref()
+                    .is_some_and(|inv| (f32::from_bits(row.1) - inv.y).abs() < 1.0);
+                let mut cached_rows: HashSet<RowKey> = HashSet::new();
+                let mut built_rows: HashMap<RowKey, RowVertices> = HashMap::new();
+                if cache_rows {
+                    self.row_cache.validate(glyph_atlas.layout_epoch(), self.scale_factor);
+                    for (row, hash) in &row_hashes {
+                        if cursor_row(row) {
+                            continue;
+                        }
+                        if let Some(cached) = self.row_cache.get(row, *hash) {
+                            mask_data.extend_from_slice(&cached.mask);
+                            color_data.extend_from_slice(&cached.color);
+                            composed_mask_data.extend_from_slice(&cached.composed_mask);
+                            composed_color_data.extend_from_slice(&cached.composed_color);
+                            cached_rows.insert(*row);
+                        }
+                    }
+                } else {
+                    self.row_cache.clear();
+                }
 
                 for glyph in &frame_glyphs.glyphs {
-                    if let FrameGlyph::Char { char, composed, x, y, width, ascent, fg, face_id, font_size, is_overlay, .. } = glyph {
+                    if let FrameGlyph::Char { char, composed, x, y, width, height, ascent, fg, face_id, font_size, is_overlay, .. } = glyph {
                         if *is_overlay != want_overlay {
                             continue;
                         }
+                        let row = row_key(*y, want_overlay);
+                        if cached_rows.contains(&row) {
+                            continue;
+                        }
 
                         let face = faces.get(face_id);
 
@@ -6166,11 +6518,12 @@ impl WgpuRenderer {
                         if let Some(cached) = cached_opt {
                             // Cached glyphs are rasterized at physical resolution (scale_factor).
                             // Divide bearing/size by scale_factor to get logical pixel positions
-                            // that match Emacs coordinate space.
+                            // that match Emacs coordinate space.  The pen position is snapped to
+                            // a device pixel so glyphs are not resampled at fractional scales.
                             let sf = self.scale_factor;
                             let ya = if has_line_anims { *y + self.line_y_offset(*x, *y) } else { *y };
-                            let glyph_x = *x + cached.bearing_x / sf;
-                            let baseline = ya + *ascent;
+                            let glyph_x = snap_to_device(*x, sf) + cached.bearing_x / sf;
+                            let baseline = snap_to_device(ya + *ascent, sf);
                             let glyph_y = baseline - cached.bearing_y / sf;
                             let glyph_w = cached.width as f32 / sf;
                             let glyph_h = cached.height as f32 / sf;
@@ -6178,19 +6531,12 @@ impl WgpuRenderer {
                             // Determine effective foreground color.
                             // For the character under a filled box cursor, swap to
                             // cursor_fg (inverse video) when cursor is visible.
-                            let effective_fg = if cursor_visible {
-                                if let Some(ref inv) = frame_glyphs.cursor_inverse {
-                                    // Match if char cell overlaps cursor inverse position
-                                    if (*x - inv.x).abs() < 1.0 && (*y - inv.y).abs() < 1.0 {
-                                        &inv.cursor_fg
-                                    } else {
-                                        fg
-                                    }
-                                } else {
-                                    fg
-                                }
-                            } else {
-                                fg
+                            let effective_fg = match (&frame_glyphs.cursor_inverse, &inverse_fg) {
+                                (Some(inv), Some(inv_fg))
+                                    if inversion_enabled
+                                        && moving_inversion_rect.is_none()
+                                        && inv.is_at(*x, *y) => inv_fg,
+                                _ => fg,
                             };
 
                             // Color glyphs use white vertex color (no tinting),
@@ -6226,20 +6572,45 @@ impl WgpuRenderer {
                             }
 
                             let vertices = [
-                                GlyphVertex { position: [glyph_x, glyph_y], tex_coords: [0.0, 0.0], color },
-                                GlyphVertex { position: [glyph_x + glyph_w, glyph_y], tex_coords: [1.0, 0.0], color },
-                                GlyphVertex { position: [glyph_x + glyph_w, glyph_y + glyph_h], tex_coords: [1.0, 1.0], color },
-                                GlyphVertex { position: [glyph_x, glyph_y], tex_coords: [0.0, 0.0], color },
-                                GlyphVertex { position: [glyph_x + glyph_w, glyph_y + glyph_h], tex_coords: [1.0, 1.0], color },
-                                GlyphVertex { position: [glyph_x, glyph_y + glyph_h], tex_coords: [0.0, 1.0], color },
+                                GlyphVertex { position: [glyph_x, glyph_y], tex_coords: cached.tex_coord(0.0, 0.0), color },
+                                GlyphVertex { position: [glyph_x + glyph_w, glyph_y], tex_coords: cached.tex_coord(1.0, 0.0), color },
+                                GlyphVertex { position: [glyph_x + glyph_w, glyph_y + glyph_h], tex_coords: cached.tex_coord(1.0, 1.0), color },
+                                GlyphVertex { position: [glyph_x, glyph_y], tex_coords: cached.tex_coord(0.0, 0.0), color },
+                                GlyphVertex { position: [glyph_x + glyph_w, glyph_y + glyph_h], tex_coords: cached.tex_coord(1.0, 1.0), color },
+                                GlyphVertex { position: [glyph_x, glyph_y + glyph_h], tex_coords: cached.tex_coord(0.0, 1.0), color },
                             ];
 
+                            // Keep an inverted copy of mask glyphs the moving cursor covers
+                            let inverted_vertices = match (moving_inversion_rect, inverse_fg) {
+                                (Some(r), Some(inv_fg))
+                                    if !want_overlay && !cached.is_color
+                                        && *x < r.x + r.width && *x + *width > r.x
+                                        && *y < r.y + r.height && *y + *height > r.y =>
+                                {
+                                    let c = [inv_fg.r, inv_fg.g, inv_fg.b, inv_fg.a * fade_alpha];
+                                    let mut inv_vertices = vertices;
+                                    for v in &mut inv_vertices {
+                                        v.color = c;
+                                    }
+                                    Some(inv_vertices)
+                                }
+                                _ => None,
+                            };
+
                             if composed.is_some() {
                                 let ckey = ComposedGlyphKey {
                                     text: composed.as_ref().unwrap().clone(),
                                     face_id: *face_id,
                                     font_size_bits: font_size.to_bits(),
                                 };
+                                if let Some(inv_vertices) = inverted_vertices {
+                                    inverted_composed_data.push((ckey.clone(), inv_vertices));
+                                }
+                                if cache_rows {
+                                    let built = built_rows.entry(row).or_default();
+                                    let list = if cached.is_color { &mut built.composed_color } else { &mut built.composed_mask };
+                                    list.push((ckey.clone(), vertices));
+                                }
                                 if cached.is_color {
                                     composed_color_data.push((ckey, vertices));
                                 } else {
@@ -6251,14 +6622,39 @@ impl WgpuRenderer {
                                     face_id: *face_id,
                                     font_size_bits: font_size.to_bits(),
                                 };
+                                if let Some(inv_vertices) = inverted_vertices {
+                                    inverted_data.push((key.clone(), inv_vertices));
+                                }
+                                if cache_rows {
+                                    let built = built_rows.entry(row).or_default();
+                                    let list = if cached.is_color { &mut built.color } else { &mut built.mask };
+                                    list.push((key.clone(), vertices));
+                                }
                                 if cached.is_color {
                                     color_data.push((key, vertices));
                                 } else {
                                     mask_data.push((key, vertices));
                                 }
                             }
+                        } else {
+                            // Retried next frame rather than cached as missing
+                            cached_rows.insert(row);
+                            built_rows.remove(&row);
+                        }
+                    }
+                }
+                if cache_rows {
+                    self.row_cache.hits += cached_rows.len();
+                    self.row_cache.misses += built_rows.len();
+                    if self.effects.debug_overlay.mode == DebugOverlay::Damage {
+                        self.debug_rects.extend(built_rows.values().filter_map(|v| v.bounds()));
+                    }
+                    for (row, vertices) in built_rows {
+                        if let Some(hash) = row_hashes.get(&row).filter(|_| !cursor_row(&row)) {
+                            self.row_cache.insert(row, *hash, vertices);
                         }
                     }
+                    self.row_cache.retain(want_overlay, &row_hashes);
                 }
 
                 log::trace!("render_frame_glyphs: overlay={} {} mask glyphs, {} color glyphs",
@@ -6283,7 +6679,7 @@ impl WgpuRenderer {
                             .then(a.charcode.cmp(&b.charcode))
                     });
 
-                    render_pass.set_pipeline(&self.glyph_pipeline);
+                    render_pass.set_pipeline(self.mask_glyph_pipeline(glyph_atlas));
                     render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
 
                     let all_vertices: Vec<GlyphVertex> = mask_data.iter()
@@ -6363,7 +6759,7 @@ impl WgpuRenderer {
 
                 // Draw composed mask glyphs (each unique, no batching)
                 if !composed_mask_data.is_empty() {
-                    render_pass.set_pipeline(&self.glyph_pipeline);
+                    render_pass.set_pipeline(self.mask_glyph_pipeline(glyph_atlas));
                     render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
 
                     for (ref ckey, verts) in &composed_mask_data {
@@ -6399,6 +6795,55 @@ impl WgpuRenderer {
                     }
                 }
 
+                // === Re-render text under the moving box cursor, inverted ===
+                if let (Some(r), Some(fill)) = (moving_inversion_rect, moving_inversion_fill) {
+                    let sf = self.scale_factor;
+                    let sx = (r.x * sf).max(0.0).min(surface_width as f32) as u32;
+                    let sy = (r.y * sf).max(0.0).min(surface_height as f32) as u32;
+                    let ex = ((r.x + r.width) * sf).ceil().max(0.0).min(surface_width as f32) as u32;
+                    let ey = ((r.y + r.height) * sf).ceil().max(0.0).min(surface_height as f32) as u32;
+                    if !want_overlay && ex > sx && ey > sy {
+                        render_pass.set_scissor_rect(sx, sy, ex - sx, ey - sy);
+
+                        let mut fill_vertices: Vec<RectVertex> = Vec::new();
+                        self.add_rect(&mut fill_vertices, r.x, r.y, r.width, r.height, &fill);
+                        let fill_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Cursor Inversion Fill"),
+                            contents: bytemuck::cast_slice(&fill_vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        });
+                        render_pass.set_pipeline(&self.rect_pipeline);
+                        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, fill_buffer.slice(..));
+                        render_pass.draw(0..fill_vertices.len() as u32, 0..1);
+
+                        let bind_groups: Vec<(&wgpu::BindGroup, &[GlyphVertex; 6])> = inverted_data.iter()
+                            .filter_map(|(key, verts)| glyph_atlas.get(key).map(|c| (&c.bind_group, verts)))
+                            .chain(inverted_composed_data.iter()
+                                .filter_map(|(ckey, verts)| glyph_atlas.get_composed(ckey).map(|c| (&c.bind_group, verts))))
+                            .collect();
+                        if !bind_groups.is_empty() {
+                            let all_vertices: Vec<GlyphVertex> = bind_groups.iter()
+                                .flat_map(|(_, verts)| verts.iter().copied())
+                                .collect();
+                            let inverted_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                                label: Some("Cursor Inversion Glyphs"),
+                                contents: bytemuck::cast_slice(&all_vertices),
+                                usage: wgpu::BufferUsages::VERTEX,
+                            });
+                            render_pass.set_pipeline(self.mask_glyph_pipeline(glyph_atlas));
+                            render_pass.set_vertex_buffer(0, inverted_buffer.slice(..));
+                            for (i, (bind_group, _)) in bind_groups.iter().enumerate() {
+                                let start = (i * 6) as u32;
+                                render_pass.set_bind_group(1, *bind_group, &[]);
+                                render_pass.draw(start..start + 6, 0..1);
+                            }
+                        }
+
+                        render_pass.set_scissor_rect(0, 0, surface_width, surface_height);
+                    }
+                }
+
                 // === Draw text decorations (underline, overline, strike-through) ===
                 // Rendered after text so decorations appear on top of glyphs.
                 // Box borders are handled separately via merged box_spans below.
@@ -6422,7 +6867,7 @@ impl WgpuRenderer {
                             let baseline_y = ya + *ascent;
 
                             // Get per-face font metrics for proper decoration positioning
-                            let (ul_pos, ul_thick) = frame_glyphs.faces.get(face_id)
+                            let (ul_pos, ul_thick) = faces.get(face_id)
                                 .map(|f| (f.underline_position as f32, f.underline_thickness as f32))
                                 .unwrap_or((1.0, 1.0));
 
@@ -6644,9 +7089,20 @@ impl WgpuRenderer {
             // Draw inline images
             render_pass.set_pipeline(&self.image_pipeline);
             render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            let now = self.clock.now();
+            // Charts are shapes, drawn once the images are
+            let mut chart_vertices: Vec<RectVertex> = Vec::new();
 
             for glyph in &frame_glyphs.glyphs {
                 if let FrameGlyph::Image { image_id, x, y, width, height } = glyph {
+                    if let Some(chart) = self.charts.get(image_id) {
+                        for quad in chart.quads(Rect::new(*x, *y, *width, *height), now) {
+                            let color = [quad.color.r, quad.color.g, quad.color.b, quad.color.a];
+                            chart_vertices.extend(quad.triangles().map(|position| RectVertex { position, color }));
+                        }
+                        continue;
+                    }
+
                     // Clip to mode-line boundary if needed
                     let (clipped_height, tex_v_max) = if let Some(oy) = overlay_y {
                         if *y + *height > oy {
@@ -6668,30 +7124,62 @@ impl WgpuRenderer {
                     log::debug!("Rendering image {} at ({}, {}) size {}x{} (clipped to {})",
                         image_id, x, y, width, height, clipped_height);
                     // Check if image texture is ready
-                    if let Some(cached) = self.image_cache.get(*image_id) {
-                        // Create vertices for image quad (white color = no tinting)
-                        let vertices = [
-                            GlyphVertex { position: [*x, *y], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-                            GlyphVertex { position: [*x + *width, *y], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-                            GlyphVertex { position: [*x + *width, *y + clipped_height], tex_coords: [1.0, tex_v_max], color: [1.0, 1.0, 1.0, 1.0] },
-                            GlyphVertex { position: [*x, *y], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-                            GlyphVertex { position: [*x + *width, *y + clipped_height], tex_coords: [1.0, tex_v_max], color: [1.0, 1.0, 1.0, 1.0] },
-                            GlyphVertex { position: [*x, *y + clipped_height], tex_coords: [0.0, tex_v_max], color: [1.0, 1.0, 1.0, 1.0] },
-                        ];
+                    if let Some(bind_group) = self.image_cache.bind_group(*image_id, *width, *height) {
+                        // Part of the image shown: all of it unless zoomed
+                        let (u0, v0, u1, v1) = self.image_cache.transform(*image_id)
+                            .map_or((0.0, 0.0, 1.0, 1.0), |t| t.uv_rect());
+                        let full = (0.0, 0.0, 1.0, 1.0);
+                        // While its picture is swapped, the old one gives
+                        // way through part of the glyph
+                        let layers: Vec<_> = match self.image_cache.swap_state(*image_id) {
+                            Some((old, swap)) => crate::core::image_swap::layers(swap.effect, swap.progress(now))
+                                .iter()
+                                .map(|l| (if l.old { &old.bind_group } else { bind_group }, l.rect, l.uv, l.alpha))
+                                .collect(),
+                            None => vec![(bind_group, full, full, 1.0)],
+                        };
+                        let area = Rect::new(*x, *y, *width, *height);
+                        for (layer_bind_group, rect, uv, alpha) in layers {
+                            let uv = (u0 + uv.0 * (u1 - u0), v0 + uv.1 * (v1 - v0),
+                                      u0 + uv.2 * (u1 - u0), v0 + uv.3 * (v1 - v0));
+                            let Some(vertices) = image_quad(area, *y + clipped_height, rect, uv, alpha) else {
+                                continue;
+                            };
 
-                        let image_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Image Vertex Buffer"),
-                            contents: bytemuck::cast_slice(&vertices),
-                            usage: wgpu::BufferUsages::VERTEX,
-                        });
+                            let image_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                                label: Some("Image Vertex Buffer"),
+                                contents: bytemuck::cast_slice(&vertices),
+                                usage: wgpu::BufferUsages::VERTEX,
+                            });
 
-                        render_pass.set_bind_group(1, &cached.bind_group, &[]);
-                        render_pass.set_vertex_buffer(0, image_buffer.slice(..));
-                        render_pass.draw(0..6, 0..1);
+                            render_pass.set_bind_group(1, layer_bind_group, &[]);
+                            render_pass.set_vertex_buffer(0, image_buffer.slice(..));
+                            render_pass.draw(0..6, 0..1);
+                        }
                     }
                 }
             }
 
+            if !chart_vertices.is_empty() {
+                // Cut off at the mode line, as images are
+                let clip_height = overlay_y.map_or(surface_height, |oy| {
+                    ((oy * self.scale_factor).max(0.0) as u32).min(surface_height)
+                });
+                if clip_height > 0 {
+                    let chart_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Chart Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&chart_vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+                    render_pass.set_scissor_rect(0, 0, surface_width, clip_height);
+                    render_pass.set_pipeline(&self.rect_pipeline);
+                    render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, chart_buffer.slice(..));
+                    render_pass.draw(0..chart_vertices.len() as u32, 0..1);
+                    render_pass.set_scissor_rect(0, 0, surface_width, surface_height);
+                }
+            }
+
             // Draw inline videos
             #[cfg(feature = "video")]
             for glyph in &frame_glyphs.glyphs {
@@ -6735,6 +7223,7 @@ impl WgpuRenderer {
                                 usage: wgpu::BufferUsages::VERTEX,
                             });
 
+                            self.set_video_pipeline(&mut render_pass, cached);
                             render_pass.set_bind_group(1, bind_group, &[]);
                             render_pass.set_vertex_buffer(0, video_buffer.slice(..));
                             render_pass.draw(0..6, 0..1);
@@ -6752,6 +7241,9 @@ impl WgpuRenderer {
             {
                 render_pass.set_pipeline(&self.opaque_image_pipeline);
                 render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                // Load progress bars; views without a frame get their
+                // placeholders in `render_placeholders`
+                let mut bar_vertices: Vec<RectVertex> = Vec::new();
 
                 for glyph in &frame_glyphs.glyphs {
                     if let FrameGlyph::WebKit { webkit_id, x, y, width, height } = glyph {
@@ -6777,6 +7269,14 @@ impl WgpuRenderer {
                             continue;
                         }
 
+                        if let Some(progress) = self.webkit_cache.loading(*webkit_id) {
+                            let bar_h = 3.0_f32.min(clipped_height);
+                            let track = Color::new(0.5, 0.5, 0.5, 0.25);
+                            let bar = Color::new(0.25, 0.55, 1.0, 0.9);
+                            self.add_rect(&mut bar_vertices, *x, *y, *width, bar_h, &track);
+                            self.add_rect(&mut bar_vertices, *x, *y, *width * progress, bar_h, &bar);
+                        }
+
                         // Check if webkit texture is ready
                         if let Some(cached) = self.webkit_cache.get(*webkit_id) {
                             log::debug!("Rendering webkit {} at ({}, {}) size {}x{} (clipped to {})",
@@ -6805,6 +7305,18 @@ impl WgpuRenderer {
                         }
                     }
                 }
+
+                if !bar_vertices.is_empty() {
+                    let loading_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("WebKit Loading Buffer"),
+                        contents: bytemuck::cast_slice(&bar_vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+                    render_pass.set_pipeline(&self.rect_pipeline);
+                    render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, loading_buffer.slice(..));
+                    render_pass.draw(0..bar_vertices.len() as u32, 0..1);
+                }
             }
 
             // Draw cursors and borders (after text)
@@ -6970,7 +7482,7 @@ impl WgpuRenderer {
 
             // === Animated focus ring (marching ants) around selected window ===
             if self.effects.focus_ring.enabled {
-                let elapsed = self.focus_ring_start.elapsed().as_secs_f32();
+                let elapsed = self.clock.now().duration_since(self.focus_ring_start).as_secs_f32();
                 let offset = (elapsed * self.effects.focus_ring.speed) % (self.effects.focus_ring.dash_length * 2.0);
                 let dash = self.effects.focus_ring.dash_length;
                 let period = dash * 2.0;
@@ -7091,7 +7603,7 @@ impl WgpuRenderer {
 
             // === Smooth border color transition on focus ===
             if self.effects.border_transition.enabled && frame_glyphs.window_infos.len() > 1 {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let (ar, ag, ab) = self.effects.border_transition.active_color;
                 let duration = self.border_transition_duration;
 
@@ -7425,31 +7937,32 @@ impl WgpuRenderer {
                 }
             }
 
-            // === Draw inactive window dimming overlays (with smooth fade) ===
+            // === Dim and desaturate inactive windows (with smooth fade) ===
             if self.effects.inactive_dim.enabled && frame_glyphs.window_infos.len() > 1 {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let dt = now.duration_since(self.last_dim_tick).as_secs_f32().min(0.1);
                 self.last_dim_tick = now;
                 // Exponential interpolation speed (higher = faster fade)
                 let fade_speed = 8.0;
 
-                let mut dim_vertices: Vec<RectVertex> = Vec::new();
+                // per_window_dim holds how far each window has faded out:
+                // 0 selected, 1 inactive
+                let mut dimmed: Vec<(Rect, f32, f32)> = Vec::new();
                 let mut any_transitioning = false;
                 for info in &frame_glyphs.window_infos {
-                    let target = if info.selected { 0.0 } else { self.effects.inactive_dim.opacity };
+                    let target = if info.selected { 0.0 } else { 1.0 };
                     let current = self.per_window_dim.get(&info.window_id).copied().unwrap_or(target);
                     // Exponential interpolation toward target
-                    let new_opacity = current + (target - current) * (1.0 - (-fade_speed * dt).exp());
+                    let amount = current + (target - current) * (1.0 - (-fade_speed * dt).exp());
                     // Snap to target when close enough
-                    let new_opacity = if (new_opacity - target).abs() < 0.001 { target } else { new_opacity };
-                    self.per_window_dim.insert(info.window_id, new_opacity);
-                    if (new_opacity - target).abs() > 0.0005 {
+                    let amount = if (amount - target).abs() < 0.005 { target } else { amount };
+                    self.per_window_dim.insert(info.window_id, amount);
+                    if amount != target {
                         any_transitioning = true;
                     }
-                    if new_opacity > 0.001 {
-                        let dim_color = Color::new(0.0, 0.0, 0.0, new_opacity);
-                        let b = &info.bounds;
-                        self.add_rect(&mut dim_vertices, b.x, b.y, b.width, b.height, &dim_color);
+                    let (opacity, saturation) = self.effects.inactive_dim.at(amount);
+                    if opacity > 0.001 || saturation < 0.999 {
+                        dimmed.push((info.bounds, opacity, saturation));
                     }
                 }
                 // Clean up windows that no longer exist
@@ -7457,6 +7970,71 @@ impl WgpuRenderer {
                     .map(|i| i.window_id).collect();
                 self.per_window_dim.retain(|k, _| valid_ids.contains(k));
 
+                // Desaturation redraws the windows from a copy of the
+                // target: the render pass is split so the copy can be made
+                let target_texture = backdrop
+                    .filter(|t| t.usage().contains(wgpu::TextureUsages::COPY_SRC));
+                if let (Some(target), true) = (target_texture, dimmed.iter().any(|d| d.2 < 0.999)) {
+                    drop(render_pass);
+                    self.ensure_frame_copy(target);
+                    let (copy, bind_group) = self.frame_copy.as_ref().expect("created above");
+                    encoder.copy_texture_to_texture(
+                        target.as_image_copy(),
+                        copy.as_image_copy(),
+                        target.size(),
+                    );
+
+                    render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Inactive Desaturate Pass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+
+                    let (tw, th) = (target.width() as f32 / self.scale_factor, target.height() as f32 / self.scale_factor);
+                    let mut vertices: Vec<GlyphVertex> = Vec::new();
+                    for (b, _, saturation) in dimmed.iter().filter(|d| d.2 < 0.999) {
+                        let color = [*saturation, 1.0, 1.0, 1.0];
+                        let (x0, y0, x1, y1) = (b.x, b.y, b.x + b.width, b.y + b.height);
+                        let (u0, v0, u1, v1) = (x0 / tw, y0 / th, x1 / tw, y1 / th);
+                        vertices.extend_from_slice(&[
+                            GlyphVertex { position: [x0, y0], tex_coords: [u0, v0], color },
+                            GlyphVertex { position: [x1, y0], tex_coords: [u1, v0], color },
+                            GlyphVertex { position: [x1, y1], tex_coords: [u1, v1], color },
+                            GlyphVertex { position: [x0, y0], tex_coords: [u0, v0], color },
+                            GlyphVertex { position: [x1, y1], tex_coords: [u1, v1], color },
+                            GlyphVertex { position: [x0, y1], tex_coords: [u0, v1], color },
+                        ]);
+                    }
+                    let desaturate_buffer = self.device.create_buffer_init(
+                        &wgpu::util::BufferInitDescriptor {
+                            label: Some("Inactive Desaturate Buffer"),
+                            contents: bytemuck::cast_slice(&vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        },
+                    );
+                    render_pass.set_pipeline(&self.desaturate_pipeline);
+                    render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                    render_pass.set_bind_group(1, bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, desaturate_buffer.slice(..));
+                    render_pass.draw(0..vertices.len() as u32, 0..1);
+                }
+
+                let mut dim_vertices: Vec<RectVertex> = Vec::new();
+                for (b, opacity, _) in &dimmed {
+                    if *opacity > 0.001 {
+                        let dim_color = Color::new(0.0, 0.0, 0.0, *opacity);
+                        self.add_rect(&mut dim_vertices, b.x, b.y, b.width, b.height, &dim_color);
+                    }
+                }
                 if !dim_vertices.is_empty() {
                     let dim_buffer = self.device.create_buffer_init(
                         &wgpu::util::BufferInitDescriptor {
@@ -7554,7 +8132,7 @@ impl WgpuRenderer {
 
             // === Cursor trail fade (afterimage ghost) ===
             if self.effects.cursor_trail_fade.enabled && !self.cursor_trail_positions.is_empty() {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let fade_dur = self.cursor_trail_fade_duration;
                 let mut trail_vertices: Vec<RectVertex> = Vec::new();
 
@@ -7615,7 +8193,7 @@ impl WgpuRenderer {
                 }
 
                 if found {
-                    let elapsed = self.search_pulse_start.elapsed().as_secs_f32();
+                    let elapsed = self.clock.now().duration_since(self.search_pulse_start).as_secs_f32();
                     let phase = elapsed * 3.0 * std::f32::consts::PI; // 1.5 Hz
                     let pulse = (phase.sin() + 1.0) / 2.0; // 0..1
 
@@ -7733,7 +8311,7 @@ impl WgpuRenderer {
 
             // === Typing ripple effect ===
             if self.effects.typing_ripple.enabled && !self.active_ripples.is_empty() {
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let duration = self.typing_ripple_duration;
                 let max_r = self.effects.typing_ripple.max_radius;
 
@@ -7816,45 +8394,77 @@ impl WgpuRenderer {
                     let mut minimap_vertices: Vec<RectVertex> = Vec::new();
                     self.add_rect(&mut minimap_vertices, map_x, map_y, minimap_w, map_h, &bg_color);
 
-                    // Collect glyphs belonging to this window's content area
-                    // and render each as a tiny colored rectangle
-                    for glyph in &frame_glyphs.glyphs {
-                        if let FrameGlyph::Char { x, y, width, height, fg, char: ch, is_overlay, .. } = glyph {
-                            if *is_overlay { continue; }
-                            if *ch == ' ' || *ch == '\t' || *ch == '\n' { continue; }
-                            // Check glyph is in this window's content area
-                            if *x < b.x || *x >= b.x + b.width - minimap_w { continue; }
-                            if *y < b.y || *y >= b.y + content_h { continue; }
-
-                            // Map glyph position to minimap coordinates
-                            let rel_x = (*x - b.x) / char_w;
-                            let rel_y = (*y - b.y) / char_h;
-                            let mini_x = map_x + 2.0 + rel_x * scale_x;
-                            let mini_y = map_y + rel_y * scale_y;
-
-                            // Skip if outside minimap bounds
-                            if mini_x >= map_x + minimap_w - 1.0 { continue; }
-                            if mini_y >= map_y + map_h - 1.0 { continue; }
-
-                            // Draw tiny colored block for each character
-                            let dot_w = ((*width / char_w) * scale_x).max(1.0).min(scale_x * 2.0);
-                            let dot_h = scale_y;
-                            let dot_color = Color::new(fg.r, fg.g, fg.b, 0.7);
-                            self.add_rect(&mut minimap_vertices, mini_x, mini_y, dot_w, dot_h, &dot_color);
-                        }
-                    }
-
-                    // Viewport indicator: show where the visible portion is relative to full buffer
-                    if info.buffer_size > 0 {
-                        let start_frac = info.window_start as f32 / info.buffer_size as f32;
-                        let end_frac = (info.window_end as f32 / info.buffer_size as f32).min(1.0);
-                        let vp_y = map_y + start_frac * map_h;
-                        let vp_h = ((end_frac - start_frac) * map_h).max(4.0);
+                    let lines = self.minimap.lines(info.buffer_id);
+                    if !lines.is_empty() {
+                        // Whole-buffer overview from line summaries sent by Lisp
+                        let lh = self.effects.minimap.line_height;
+                        let view_first = minimap_line_at_pos(lines, info.window_start);
+                        let view_lines = (content_h / info.char_height.max(1.0)) as usize;
+                        let layout = MinimapLayout::new(lines.len(), view_first, view_lines, map_h, lh);
+                        let col_w = layout.line_height * 0.5;
+                        let block_h = (layout.line_height * 0.75).max(1.0);
+                        let text_right = map_x + minimap_w - 2.0;
+                        for idx in layout.visible(lines.len()) {
+                            let line = &lines[idx];
+                            if line.length == 0 { continue; }
+                            let bx = map_x + 2.0 + line.indent as f32 * col_w;
+                            if bx >= text_right { continue; }
+                            let bw = (line.length as f32 * col_w).min(text_right - bx);
+                            let c = Color::from_pixel(line.color);
+                            let dot_color = Color::new(c.r, c.g, c.b, 0.7);
+                            self.add_rect(&mut minimap_vertices, bx, map_y + layout.line_y(idx),
+                                bw, block_h, &dot_color);
+                        }
+
+                        // Viewport indicator spans the window's lines
+                        let vp_y = map_y + layout.line_y(view_first).max(0.0);
+                        let vp_h = (view_lines as f32 * layout.line_height)
+                            .min(map_y + map_h - vp_y).max(4.0);
                         let vp_color = Color::new(1.0, 1.0, 1.0, 0.1);
                         self.add_rect(&mut minimap_vertices, map_x, vp_y, minimap_w, vp_h, &vp_color);
-                        // Left edge highlight for viewport indicator
                         let edge_color = Color::new(0.5, 0.7, 1.0, 0.4);
                         self.add_rect(&mut minimap_vertices, map_x, vp_y, 2.0, vp_h, &edge_color);
+                    } else {
+                        // Collect glyphs belonging to this window's content area
+                        // and render each as a tiny colored rectangle
+                        for glyph in &frame_glyphs.glyphs {
+                            if let FrameGlyph::Char { x, y, width, height, fg, char: ch, is_overlay, .. } = glyph {
+                                if *is_overlay { continue; }
+                                if *ch == ' ' || *ch == '\t' || *ch == '\n' { continue; }
+                                // Check glyph is in this window's content area
+                                if *x < b.x || *x >= b.x + b.width - minimap_w { continue; }
+                                if *y < b.y || *y >= b.y + content_h { continue; }
+
+                                // Map glyph position to minimap coordinates
+                                let rel_x = (*x - b.x) / char_w;
+                                let rel_y = (*y - b.y) / char_h;
+                                let mini_x = map_x + 2.0 + rel_x * scale_x;
+                                let mini_y = map_y + rel_y * scale_y;
+
+                                // Skip if outside minimap bounds
+                                if mini_x >= map_x + minimap_w - 1.0 { continue; }
+                                if mini_y >= map_y + map_h - 1.0 { continue; }
+
+                                // Draw tiny colored block for each character
+                                let dot_w = ((*width / char_w) * scale_x).max(1.0).min(scale_x * 2.0);
+                                let dot_h = scale_y;
+                                let dot_color = Color::new(fg.r, fg.g, fg.b, 0.7);
+                                self.add_rect(&mut minimap_vertices, mini_x, mini_y, dot_w, dot_h, &dot_color);
+                            }
+                        }
+
+                        // Viewport indicator: show where the visible portion is relative to full buffer
+                        if info.buffer_size > 0 {
+                            let start_frac = info.window_start as f32 / info.buffer_size as f32;
+                            let end_frac = (info.window_end as f32 / info.buffer_size as f32).min(1.0);
+                            let vp_y = map_y + start_frac * map_h;
+                            let vp_h = ((end_frac - start_frac) * map_h).max(4.0);
+                            let vp_color = Color::new(1.0, 1.0, 1.0, 0.1);
+                            self.add_rect(&mut minimap_vertices, map_x, vp_y, minimap_w, vp_h, &vp_color);
+                            // Left edge highlight for viewport indicator
+                            let edge_color = Color::new(0.5, 0.7, 1.0, 0.4);
+                            self.add_rect(&mut minimap_vertices, map_x, vp_y, 2.0, vp_h, &edge_color);
+                        }
                     }
 
                     if !minimap_vertices.is_empty() {
@@ -8215,9 +8825,10 @@ impl WgpuRenderer {
             if !self.scroll_velocity_fades.is_empty() {
                 let max_op = self.effects.scroll_velocity_fade.max_opacity.clamp(0.0, 1.0);
                 let mut fade_vertices: Vec<RectVertex> = Vec::new();
+                let now = self.clock.now();
 
                 for entry in &self.scroll_velocity_fades {
-                    let elapsed = entry.started.elapsed().as_millis() as f32;
+                    let elapsed = now.duration_since(entry.started).as_millis() as f32;
                     let duration = entry.duration.as_millis() as f32;
                     if elapsed >= duration { continue; }
 
@@ -8247,7 +8858,7 @@ impl WgpuRenderer {
 
                 // Cleanup expired entries
                 self.scroll_velocity_fades.retain(|e| {
-                    e.started.elapsed() < e.duration
+                    now.duration_since(e.started) < e.duration
                 });
                 if !self.scroll_velocity_fades.is_empty() {
                     self.needs_continuous_redraw = true;
@@ -8260,9 +8871,10 @@ impl WgpuRenderer {
                 let max_r = self.effects.click_halo.max_radius;
                 let mut halo_vertices: Vec<RectVertex> = Vec::new();
                 let ring_steps = 8;
+                let now = self.clock.now();
 
                 for entry in &self.click_halos {
-                    let elapsed = entry.started.elapsed().as_millis() as f32;
+                    let elapsed = now.duration_since(entry.started).as_millis() as f32;
                     let duration = entry.duration.as_millis() as f32;
                     if elapsed >= duration { continue; }
 
@@ -8294,7 +8906,7 @@ impl WgpuRenderer {
                     render_pass.draw(0..halo_vertices.len() as u32, 0..1);
                 }
 
-                self.click_halos.retain(|e| e.started.elapsed() < e.duration);
+                self.click_halos.retain(|e| now.duration_since(e.started) < e.duration);
                 if !self.click_halos.is_empty() {
                     self.needs_continuous_redraw = true;
                 }
@@ -8306,9 +8918,10 @@ impl WgpuRenderer {
                 let mut snap_vertices: Vec<RectVertex> = Vec::new();
                 let bar_h = 4.0_f32;
                 let steps = 3;
+                let now = self.clock.now();
 
                 for entry in &self.edge_snaps {
-                    let elapsed = entry.started.elapsed().as_millis() as f32;
+                    let elapsed = now.duration_since(entry.started).as_millis() as f32;
                     let duration = entry.duration.as_millis() as f32;
                     if elapsed >= duration { continue; }
 
@@ -8355,7 +8968,7 @@ impl WgpuRenderer {
                     render_pass.draw(0..snap_vertices.len() as u32, 0..1);
                 }
 
-                self.edge_snaps.retain(|e| e.started.elapsed() < e.duration);
+                self.edge_snaps.retain(|e| now.duration_since(e.started) < e.duration);
                 if !self.edge_snaps.is_empty() {
                     self.needs_continuous_redraw = true;
                 }
@@ -8415,7 +9028,7 @@ impl WgpuRenderer {
             if !self.active_scroll_momentums.is_empty() {
                 let bar_w = self.effects.scroll_momentum.width.max(1.0);
                 let mut momentum_vertices: Vec<RectVertex> = Vec::new();
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
 
                 for entry in &self.active_scroll_momentums {
                     let elapsed = now.duration_since(entry.started);
@@ -8531,7 +9144,7 @@ impl WgpuRenderer {
             // === Window switch highlight fade ===
             if !self.active_window_fades.is_empty() {
                 let mut fade_vertices: Vec<RectVertex> = Vec::new();
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
 
                 for fade in &self.active_window_fades {
                     let elapsed = now.duration_since(fade.started);
@@ -8552,7 +9165,7 @@ impl WgpuRenderer {
 
                 // Clean up completed fades
                 self.active_window_fades.retain(|f| {
-                    f.started.elapsed().as_secs_f32() < f.duration.as_secs_f32()
+                    now.duration_since(f.started) < f.duration
                 });
 
                 if !self.active_window_fades.is_empty() {
@@ -8577,4 +9190,164 @@ impl WgpuRenderer {
 
         self.queue.submit(std::iter::once(encoder.finish()));
     }
+
+    /// Merge `cells` of faces with a background style into runs, adding
+    /// gradient runs to `vertices`.  Returns the image runs as
+    /// `(image_id, rect)` for `draw_background_images`.
+    fn add_styled_backgrounds(
+        &self,
+        vertices: &mut Vec<RectVertex>,
+        cells: Vec<(u32, Rect)>,
+        faces: &HashMap<u32, Face>,
+    ) -> Vec<(u32, Rect)> {
+        let mut images = Vec::new();
+        if cells.is_empty() {
+            return images;
+        }
+        for (face_id, run) in merge_background_runs(cells) {
+            let Some(style) = faces.get(&face_id).and_then(|f| f.background_style) else {
+                continue;
+            };
+            match style {
+                BackgroundStyle::Gradient { .. } => {
+                    if let Some(corners) = style.gradient_corners(&run) {
+                        self.add_gradient_rect(vertices, &run, &corners);
+                    }
+                }
+                BackgroundStyle::Image { image_id } => images.push((image_id, run)),
+            }
+        }
+        images
+    }
+
+    /// Draw the background videos over the window backgrounds, each scaled
+    /// to cover its window and darkened by its dim.  Videos without a
+    /// decoded frame yet leave the plain background.
+    #[cfg(feature = "video")]
+    fn draw_background_videos(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        frame_glyphs: &FrameGlyphBuffer,
+        frame: Rect,
+    ) {
+        if self.background_videos.is_empty() {
+            return;
+        }
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        for bg in &self.background_videos {
+            let Some(r) = bg.rect(&frame_glyphs.window_infos, frame) else {
+                continue;
+            };
+            let Some(cached) = self.video_cache.get(bg.video_id) else {
+                continue;
+            };
+            let Some(ref bind_group) = cached.bind_group else {
+                continue;
+            };
+            let [u0, v0, u1, v1] = cover_tex_coords(
+                r.width, r.height, cached.width as f32, cached.height as f32,
+            );
+            let tint = bg.tint();
+            let vertices = [
+                GlyphVertex { position: [r.x, r.y], tex_coords: [u0, v0], color: tint },
+                GlyphVertex { position: [r.right(), r.y], tex_coords: [u1, v0], color: tint },
+                GlyphVertex { position: [r.right(), r.bottom()], tex_coords: [u1, v1], color: tint },
+                GlyphVertex { position: [r.x, r.y], tex_coords: [u0, v0], color: tint },
+                GlyphVertex { position: [r.right(), r.bottom()], tex_coords: [u1, v1], color: tint },
+                GlyphVertex { position: [r.x, r.bottom()], tex_coords: [u0, v1], color: tint },
+            ];
+            let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Background Video Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            self.set_video_pipeline(render_pass, cached);
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.set_vertex_buffer(0, buffer.slice(..));
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+
+    /// Draw image background runs, each image scaled to cover its run
+    fn draw_background_images(&self, render_pass: &mut wgpu::RenderPass<'_>, images: &[(u32, Rect)]) {
+        if images.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.image_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        let white = [1.0, 1.0, 1.0, 1.0];
+        for (image_id, r) in images {
+            let Some(cached) = self.image_cache.get(*image_id) else {
+                continue;
+            };
+            let [u0, v0, u1, v1] = cover_tex_coords(
+                r.width, r.height, cached.width as f32, cached.height as f32,
+            );
+            let vertices = [
+                GlyphVertex { position: [r.x, r.y], tex_coords: [u0, v0], color: white },
+                GlyphVertex { position: [r.right(), r.y], tex_coords: [u1, v0], color: white },
+                GlyphVertex { position: [r.right(), r.bottom()], tex_coords: [u1, v1], color: white },
+                GlyphVertex { position: [r.x, r.y], tex_coords: [u0, v0], color: white },
+                GlyphVertex { position: [r.right(), r.bottom()], tex_coords: [u1, v1], color: white },
+                GlyphVertex { position: [r.x, r.bottom()], tex_coords: [u0, v1], color: white },
+            ];
+            let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Background Image Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            render_pass.set_bind_group(1, &cached.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, buffer.slice(..));
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+
+    /// Vertices of the nine-patch image of chrome `element` over `area`,
+    /// with the bind group to draw them; None when the element has no
+    /// image or it is not loaded yet
+    pub(super) fn chrome_image_vertices(
+        &self,
+        element: ChromeElement,
+        area: Rect,
+    ) -> Option<(&wgpu::BindGroup, Vec<GlyphVertex>)> {
+        let patch = self.chrome_images.get(&element)?;
+        let cached = self.image_cache.get(patch.image_id)?;
+        let white = [1.0, 1.0, 1.0, 1.0];
+        let mut vertices = Vec::with_capacity(9 * 6);
+        for (r, (u0, v0, u1, v1)) in patch.slices(area, cached.width, cached.height) {
+            vertices.extend_from_slice(&[
+                GlyphVertex { position: [r.x, r.y], tex_coords: [u0, v0], color: white },
+                GlyphVertex { position: [r.right(), r.y], tex_coords: [u1, v0], color: white },
+                GlyphVertex { position: [r.right(), r.bottom()], tex_coords: [u1, v1], color: white },
+                GlyphVertex { position: [r.x, r.y], tex_coords: [u0, v0], color: white },
+                GlyphVertex { position: [r.right(), r.bottom()], tex_coords: [u1, v1], color: white },
+                GlyphVertex { position: [r.x, r.bottom()], tex_coords: [u0, v1], color: white },
+            ]);
+        }
+        (!vertices.is_empty()).then_some((&cached.bind_group, vertices))
+    }
+
+    /// Draw the nine-patch images of tab bars and child frames over their
+    /// backgrounds
+    fn draw_chrome_images(&self, render_pass: &mut wgpu::RenderPass<'_>, regions: &[ChromeRegion]) {
+        let mut pipeline_set = false;
+        for region in regions {
+            let Some((bind_group, vertices)) = self.chrome_image_vertices(region.element, region.bounds) else {
+                continue;
+            };
+            if !pipeline_set {
+                render_pass.set_pipeline(&self.image_pipeline);
+                render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                pipeline_set = true;
+            }
+            let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Chrome Image Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.set_vertex_buffer(0, buffer.slice(..));
+            render_pass.draw(0..vertices.len() as u32, 0..1);
+        }
+    }
 }
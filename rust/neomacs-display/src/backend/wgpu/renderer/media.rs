@@ -2,11 +2,11 @@
 
 use super::WgpuRenderer;
 use wgpu::util::DeviceExt;
-use super::super::vertex::{GlyphVertex};
+use super::super::vertex::{GlyphVertex, RoundedRectVertex};
 use crate::core::types::{Color};
 use super::super::image_cache::ImageCache;
 #[cfg(feature = "video")]
-use super::super::video_cache::VideoCache;
+use super::super::video_cache::{CachedVideo, VideoCache};
 use crate::core::scene::FloatingWebKit;
 
 impl WgpuRenderer {
@@ -58,9 +58,104 @@ impl WgpuRenderer {
 
     /// Free an image from cache
     pub fn free_image(&mut self, id: u32) {
+        self.charts.remove(&id);
         self.image_cache.free(id)
     }
 
+    /// Set how an image is filtered when drawn scaled
+    pub fn set_image_sampling(&mut self, id: u32, sampling: crate::core::types::ImageSampling) {
+        self.image_cache.set_sampling(id, sampling)
+    }
+
+    /// Let an image be zoomed and panned with the mouse, or stop it
+    pub fn set_image_interactive(&mut self, id: u32, interactive: bool) {
+        self.image_cache.set_interactive(id, interactive)
+    }
+
+    /// Give image `id` the picture of image `new_id`, which goes away,
+    /// with an animated transition
+    pub fn swap_image(
+        &mut self,
+        id: u32,
+        new_id: u32,
+        effect: crate::core::image_swap::ImageSwapEffect,
+        duration: std::time::Duration,
+    ) {
+        self.image_cache.swap(id, new_id, effect, duration)
+    }
+
+    /// Draw chrome `element` with a nine-patch image, or with its own look
+    /// again when `patch` is None
+    pub fn set_chrome_image(
+        &mut self,
+        element: crate::core::nine_patch::ChromeElement,
+        patch: Option<crate::core::nine_patch::NinePatch>,
+    ) {
+        match patch {
+            Some(patch) => self.chrome_images.insert(element, patch),
+            None => self.chrome_images.remove(&element),
+        };
+    }
+
+    /// Draw a chart of `values` in place of image `id`
+    pub fn set_chart(&mut self, id: u32, values: Vec<f32>, style: crate::core::chart::ChartStyle) {
+        self.charts.insert(id, crate::core::chart::Chart::new(values, style));
+    }
+
+    /// Move chart `id` to `values` over `duration`
+    pub fn update_chart(&mut self, id: u32, values: Vec<f32>, duration: std::time::Duration) {
+        if let Some(chart) = self.charts.get_mut(&id) {
+            chart.update(values, duration, self.clock.now());
+        }
+    }
+
+    /// Zoom and pan of an interactive image, None for other images
+    pub fn image_transform_mut(&mut self, id: u32) -> Option<&mut crate::core::image_view::ImageTransform> {
+        self.image_cache.transform_mut(id)
+    }
+
+    /// Upload decoded RGBA pixels as an image with a pre-allocated ID
+    pub fn upload_image_rgba(&mut self, id: u32, width: u32, height: u32, data: Vec<u8>) {
+        self.image_cache.insert_rgba(&self.device, &self.queue, id, width, height, data)
+    }
+
+    /// Limit the memory used by image textures
+    pub fn set_image_cache_limit(&mut self, bytes: usize) {
+        self.image_cache.set_memory_limit(bytes)
+    }
+
+    /// Bytes held by image textures, and by those of the images in `visible`
+    pub fn image_memory_usage(&self, visible: &std::collections::HashSet<u32>) -> (usize, usize) {
+        (self.image_cache.memory_usage(), self.image_cache.memory_of(visible))
+    }
+
+    /// Number of images and of videos with a texture
+    pub fn texture_counts(&self) -> (usize, usize) {
+        #[cfg(feature = "video")]
+        let videos = self.video_cache.texture_count();
+        #[cfg(not(feature = "video"))]
+        let videos = 0;
+        (self.image_cache.texture_count(), videos)
+    }
+
+    /// Evict image textures, oldest first, sparing the images in `keep`.
+    /// Returns the bytes freed.
+    pub fn evict_images(&mut self, bytes: usize, keep: &std::collections::HashSet<u32>) -> usize {
+        self.image_cache.evict(bytes, keep)
+    }
+
+    /// Bytes held by video frame textures
+    #[cfg(feature = "video")]
+    pub fn video_memory_usage(&self) -> usize {
+        self.video_cache.memory_usage()
+    }
+
+    /// Upload decoded images deferred by `process_pending_images`.
+    /// Returns true if any were uploaded.
+    pub fn flush_pending_images(&mut self) -> bool {
+        self.image_cache.flush_pending(&self.device, &self.queue)
+    }
+
     /// Process pending decoded images (call each frame before rendering)
     pub fn process_pending_images(&mut self) {
         self.image_cache.process_pending(&self.device, &self.queue);
@@ -68,11 +163,30 @@ impl WgpuRenderer {
 
     /// Load video from file path (async - returns immediately)
     /// Returns video ID, frames decode in background
+    /// Set the pipeline that draws `video`: the image pipeline, or the
+    /// video pipeline with the frames' color correction when they need one
+    #[cfg(feature = "video")]
+    pub(super) fn set_video_pipeline(&self, pass: &mut wgpu::RenderPass<'_>, video: &CachedVideo) {
+        match video.color_bind_group {
+            Some(ref color) => {
+                pass.set_pipeline(&self.video_pipeline);
+                pass.set_bind_group(2, color, &[]);
+            }
+            None => pass.set_pipeline(&self.image_pipeline),
+        }
+    }
+
     #[cfg(feature = "video")]
     pub fn load_video_file(&mut self, path: &str) -> u32 {
         self.video_cache.load_file(path)
     }
 
+    /// Set how many decoded frames are buffered for videos loaded from now on
+    #[cfg(feature = "video")]
+    pub fn set_video_frame_buffers(&mut self, count: u32) {
+        self.video_cache.set_frame_buffers(count)
+    }
+
     /// Get video dimensions
     #[cfg(feature = "video")]
     pub fn get_video_size(&self, id: u32) -> Option<(u32, u32)> {
@@ -109,6 +223,36 @@ impl WgpuRenderer {
         self.video_cache.set_loop(id, count)
     }
 
+    /// How far a video has played (0.0-1.0), once its length is known
+    #[cfg(feature = "video")]
+    pub fn video_progress(&self, id: u32) -> Option<f32> {
+        self.video_cache.progress(id)
+    }
+
+    /// Length of a video in nanoseconds, once known
+    #[cfg(feature = "video")]
+    pub fn video_duration(&self, id: u32) -> Option<u64> {
+        self.video_cache.duration(id)
+    }
+
+    /// Parts of a video available for playback, as fractions of its length
+    #[cfg(feature = "video")]
+    pub fn video_buffered(&self, id: u32) -> Vec<(f32, f32)> {
+        self.video_cache.buffered(id)
+    }
+
+    /// Set the audio level of a video, 0.0-1.0
+    #[cfg(feature = "video")]
+    pub fn video_set_volume(&mut self, id: u32, volume: f32) {
+        self.video_cache.set_volume(id, volume)
+    }
+
+    /// Jump to `fraction` (0.0-1.0) of a video
+    #[cfg(feature = "video")]
+    pub fn video_seek(&mut self, id: u32, fraction: f32) {
+        self.video_cache.seek(id, fraction)
+    }
+
     /// Free a video from cache
     #[cfg(feature = "video")]
     pub fn free_video(&mut self, id: u32) {
@@ -132,6 +276,65 @@ impl WgpuRenderer {
         self.video_cache.has_playing_videos()
     }
 
+    /// Let at most `count` videos decode at once (0 for no limit)
+    #[cfg(feature = "video")]
+    pub fn set_video_max_active(&mut self, count: u32) {
+        self.video_cache.set_max_active(count)
+    }
+
+    /// Videos drawn for `frame_glyphs` in a frame of `frame`, with the
+    /// area of each on screen: inline videos and window backgrounds
+    #[cfg(feature = "video")]
+    pub fn visible_videos(
+        &self,
+        frame_glyphs: &crate::core::frame_glyphs::FrameGlyphBuffer,
+        frame: crate::core::types::Rect,
+    ) -> Vec<(u32, f32)> {
+        use crate::core::frame_glyphs::FrameGlyph;
+        use crate::core::types::Rect;
+        let mut visible: Vec<(u32, f32)> = frame_glyphs
+            .glyphs
+            .iter()
+            .filter_map(|g| match g {
+                FrameGlyph::Video { video_id, x, y, width, height } => Rect::new(*x, *y, *width, *height)
+                    .intersection(&frame)
+                    .map(|r| (*video_id, r.width * r.height)),
+                _ => None,
+            })
+            .collect();
+        visible.extend(self.background_videos.iter().filter_map(|bg| {
+            bg.rect(&frame_glyphs.window_infos, frame).map(|r| (bg.video_id, r.width * r.height))
+        }));
+        visible
+    }
+
+    /// Park the videos not among `visible` and resume those that are,
+    /// within the limit on videos decoding at once
+    #[cfg(feature = "video")]
+    pub fn update_video_visibility(&mut self, visible: &[(u32, f32)]) {
+        self.video_cache.update_visibility(visible)
+    }
+
+    /// IDs of videos that reached the end since the last call
+    #[cfg(feature = "video")]
+    pub fn take_ended_videos(&mut self) -> Vec<u32> {
+        self.video_cache.take_ended()
+    }
+
+    /// Show `video_id` beneath the text of `window_id` (`None` for the
+    /// whole frame), darkened by `dim`.  A `video_id` of 0 removes it.
+    pub fn set_background_video(&mut self, window_id: Option<i64>, video_id: u32, dim: f32) {
+        self.background_videos.retain(|v| v.window_id != window_id);
+        if video_id != 0 {
+            self.background_videos.push(crate::core::scene::BackgroundVideo { window_id, video_id, dim });
+        }
+    }
+
+    /// Ids of the videos shown as backgrounds
+    pub fn background_video_ids(&self) -> Vec<u32> {
+        self.background_videos.iter().map(|v| v.video_id).collect()
+    }
+
     /// Get cached video for rendering
     #[cfg(feature = "video")]
     pub fn get_video(&self, id: u32) -> Option<&super::super::video_cache::CachedVideo> {
@@ -162,6 +365,14 @@ impl WgpuRenderer {
         self.webkit_cache.update_view_from_pixels(view_id, width, height, pixels, &self.device, &self.queue)
     }
 
+    /// Record a webkit view's load progress, None once loaded.  Views
+    /// show a placeholder until their first frame arrives and a progress
+    /// bar while loading.
+    #[cfg(feature = "wpe-webkit")]
+    pub fn set_webkit_loading(&mut self, view_id: u32, progress: Option<f32>) {
+        self.webkit_cache.set_loading(view_id, progress);
+    }
+
     /// Remove a webkit view from the cache.
     #[cfg(feature = "wpe-webkit")]
     pub fn remove_webkit_view(&mut self, view_id: u32) {
@@ -186,19 +397,79 @@ impl WgpuRenderer {
         view: &wgpu::TextureView,
         floating_webkits: &[crate::core::scene::FloatingWebKit],
     ) {
-        use wgpu::util::DeviceExt;
+        let mut layers = Vec::with_capacity(floating_webkits.len());
+        for fw in floating_webkits {
+            log::debug!("Rendering floating webkit {} at ({}, {}) size {}x{}",
+                       fw.webkit_id, fw.x, fw.y, fw.width, fw.height);
+            match self.webkit_cache.get(fw.webkit_id) {
+                Some(cached) => layers.push(FloatingLayer {
+                    x: fw.x, y: fw.y, width: fw.width, height: fw.height,
+                    opacity: fw.opacity,
+                    corner_radius: fw.corner_radius,
+                    bind_group: &cached.bind_group,
+                }),
+                None => log::debug!("WebKit {} not found in cache", fw.webkit_id),
+            }
+        }
+        // WebKit buffers are XRGB/BGRX: their alpha channel is meaningless
+        self.draw_floating_layers(view, "Floating WebKit", &layers, false);
+    }
+
+    /// Render floating images (opacity and rounded corners applied).
+    pub fn render_floating_images(
+        &self,
+        view: &wgpu::TextureView,
+        floating_images: &[crate::core::scene::FloatingImage],
+    ) {
+        let layers: Vec<FloatingLayer> = floating_images
+            .iter()
+            .filter_map(|fi| {
+                let bind_group = self.image_cache.bind_group(fi.image_id, fi.width, fi.height)?;
+                Some(FloatingLayer {
+                    x: fi.x, y: fi.y, width: fi.width, height: fi.height,
+                    opacity: fi.opacity,
+                    corner_radius: fi.corner_radius,
+                    bind_group,
+                })
+            })
+            .collect();
+        self.draw_floating_layers(view, "Floating Image", &layers, true);
+    }
 
-        if floating_webkits.is_empty() {
+    /// Draw textured floating layers with the rounded image pipeline.
+    fn draw_floating_layers(
+        &self,
+        view: &wgpu::TextureView,
+        label: &str,
+        layers: &[FloatingLayer],
+        use_texture_alpha: bool,
+    ) {
+        let layers: Vec<&FloatingLayer> = layers.iter().filter(|l| l.opacity > 0.0).collect();
+        if layers.is_empty() {
             return;
         }
 
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Floating WebKit Encoder"),
+        let mut vertices: Vec<RoundedRectVertex> = Vec::with_capacity(layers.len() * 6);
+        let alpha_weight = if use_texture_alpha { 1.0 } else { 0.0 };
+        for layer in &layers {
+            let tint = Color::new(1.0, 1.0, 1.0, layer.opacity.clamp(0.0, 1.0));
+            self.add_rounded_rect(
+                &mut vertices, layer.x, layer.y, layer.width, layer.height,
+                alpha_weight, layer.corner_radius.max(0.0), &tint,
+            );
+        }
+        let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
         });
 
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(label),
+        });
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Floating WebKit Render Pass"),
+                label: Some(label),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view,
                     resolve_target: None,
@@ -212,38 +483,27 @@ impl WgpuRenderer {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.opaque_image_pipeline);
+            render_pass.set_pipeline(&self.rounded_image_pipeline);
             render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-
-            for fw in floating_webkits {
-                log::debug!("Rendering floating webkit {} at ({}, {}) size {}x{}",
-                           fw.webkit_id, fw.x, fw.y, fw.width, fw.height);
-
-                if let Some(cached) = self.webkit_cache.get(fw.webkit_id) {
-                    let vertices = [
-                        GlyphVertex { position: [fw.x, fw.y], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-                        GlyphVertex { position: [fw.x + fw.width, fw.y], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-                        GlyphVertex { position: [fw.x + fw.width, fw.y + fw.height], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0] },
-                        GlyphVertex { position: [fw.x, fw.y], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-                        GlyphVertex { position: [fw.x + fw.width, fw.y + fw.height], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0] },
-                        GlyphVertex { position: [fw.x, fw.y + fw.height], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0] },
-                    ];
-
-                    let webkit_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: Some("Floating WebKit Vertex Buffer"),
-                        contents: bytemuck::cast_slice(&vertices),
-                        usage: wgpu::BufferUsages::VERTEX,
-                    });
-
-                    render_pass.set_bind_group(1, &cached.bind_group, &[]);
-                    render_pass.set_vertex_buffer(0, webkit_buffer.slice(..));
-                    render_pass.draw(0..6, 0..1);
-                } else {
-                    log::debug!("WebKit {} not found in cache", fw.webkit_id);
-                }
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            for (i, layer) in layers.iter().enumerate() {
+                render_pass.set_bind_group(1, layer.bind_group, &[]);
+                let start = (i * 6) as u32;
+                render_pass.draw(start..start + 6, 0..1);
             }
         }
 
         self.queue.submit(Some(encoder.finish()));
     }
 }
+
+/// A textured floating layer ready to draw
+struct FloatingLayer<'a> {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    opacity: f32,
+    corner_radius: f32,
+    bind_group: &'a wgpu::BindGroup,
+}
@@ -5,24 +5,29 @@ use std::sync::Arc;
 
 use wgpu::util::DeviceExt;
 
+use crate::core::clock::SharedClock;
 use crate::core::face::{BoxType, Face, FaceAttributes};
 use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
 use crate::core::scene::{CursorStyle, Scene};
+use crate::core::overdraw::OverdrawStats;
 use crate::core::types::{AnimatedCursor, Color, Rect};
 
+use super::blur::BlurPass;
 use super::glyph_atlas::{GlyphKey, WgpuGlyphAtlas};
 use super::image_cache::ImageCache;
 #[cfg(feature = "video")]
 use super::video_cache::VideoCache;
 #[cfg(feature = "wpe-webkit")]
 use super::webkit_cache::WgpuWebKitCache;
-use super::vertex::{GlyphVertex, RectVertex, RoundedRectVertex, Uniforms};
+use super::vertex::{AmbientUniforms, GlyphVertex, RectVertex, RoundedRectVertex, Uniforms};
 
 mod media;
 mod effects_state;
 mod glyphs;
 mod transitions;
 mod overlays;
+mod row_cache;
+mod shader_effects;
 
 /// GPU-accelerated renderer using wgpu.
 pub struct WgpuRenderer {
@@ -35,8 +40,41 @@ pub struct WgpuRenderer {
     pub(super) rounded_rect_pipeline: wgpu::RenderPipeline,
     pub(super) corner_mask_pipeline: wgpu::RenderPipeline,
     pub(super) glyph_pipeline: wgpu::RenderPipeline,
+    /// Glyph pipeline for atlases storing signed distance fields
+    pub(super) sdf_glyph_pipeline: wgpu::RenderPipeline,
     pub(super) image_pipeline: wgpu::RenderPipeline,
     pub(super) opaque_image_pipeline: wgpu::RenderPipeline,
+    /// Video frames whose colors need correcting (fs_video, with the
+    /// correction in group 2)
+    #[cfg(feature = "video")]
+    pub(super) video_pipeline: wgpu::RenderPipeline,
+    /// Procedural ambient background layer, with its parameters
+    pub(super) ambient_pipeline: wgpu::RenderPipeline,
+    pub(super) ambient_buffer: wgpu::Buffer,
+    pub(super) ambient_bind_group: wgpu::BindGroup,
+    /// Time origin of the ambient layer's animation
+    pub(super) ambient_epoch: std::time::Instant,
+    /// Cell backgrounds saved by the overdraw pass on the last frame
+    pub overdraw_stats: OverdrawStats,
+    /// Text vertices of the rows drawn last frame
+    row_cache: row_cache::RowCache,
+    /// Image pipeline that streaks its texture vertically (scroll motion blur)
+    pub(super) motion_blur_pipeline: wgpu::RenderPipeline,
+    /// Redraws part of a frame copy with reduced saturation
+    pub(super) desaturate_pipeline: wgpu::RenderPipeline,
+    /// Frame copy read by passes redrawing part of the frame from it
+    /// (desaturated windows, shader effects), reused while the target's
+    /// size and format stay the same
+    pub(super) frame_copy: Option<(wgpu::Texture, wgpu::BindGroup)>,
+    /// Frame copy through the whole-frame color matrix and smart invert
+    pub(super) color_filter_pipeline: wgpu::RenderPipeline,
+    /// Images, videos and web views the smart invert leaves alone, in
+    /// logical pixels, set with each frame
+    pub smart_invert_keep: Vec<Rect>,
+    /// Textured quads with rounded corners and opacity (floating layers)
+    pub(super) rounded_image_pipeline: wgpu::RenderPipeline,
+    /// Compute blur of backdrops behind floating content
+    pub(super) blur: BlurPass,
     pub(super) glyph_bind_group_layout: wgpu::BindGroupLayout,
     pub(super) uniform_buffer: wgpu::Buffer,
     pub(super) uniform_bind_group: wgpu::BindGroup,
@@ -52,12 +90,35 @@ pub struct WgpuRenderer {
 
     // All visual effect configurations
     pub effects: crate::effect_config::EffectsConfig,
+    /// Buffer line summaries drawn by the minimap
+    pub minimap: crate::core::minimap::MinimapStore,
+    /// Colors the display draws with besides those of faces
+    pub theme: crate::core::display_theme::DisplayTheme,
+    /// Loading style of placeholders of each kind of content
+    pub placeholders: crate::core::placeholder::PlaceholderStyles,
+    /// Start of placeholder animations
+    pub(super) placeholder_epoch: std::time::Instant,
+    /// Nine-patch images drawn for chrome instead of its own look
+    pub chrome_images: HashMap<crate::core::nine_patch::ChromeElement, crate::core::nine_patch::NinePatch>,
+    /// Charts drawn in place of the images with their ids
+    pub charts: HashMap<u32, crate::core::chart::Chart>,
+    /// Pipelines of shader effects by name, and whether each changes over
+    /// time; None for effects that failed to compile
+    pub(super) shader_effects: HashMap<String, Option<(wgpu::RenderPipeline, bool)>>,
+    /// Layout of the `effect` uniform of shader effect pipelines
+    pub(super) shader_effect_layout: wgpu::BindGroupLayout,
+    /// Time origin of shader effects
+    pub(super) shader_effect_epoch: std::time::Instant,
+    /// Rects the debug overlay draws over the last frame
+    pub(super) debug_rects: Vec<Rect>,
     /// Per-window dim opacity for smooth fade transitions
     pub(super) per_window_dim: std::collections::HashMap<i64, f32>,
     /// Last dim update time for smooth interpolation
     pub(super) last_dim_tick: std::time::Instant,
     /// Flag: renderer needs continuous redraws (e.g. dim fade in progress)
     pub needs_continuous_redraw: bool,
+    /// Where triggered effects read the time from
+    pub(super) clock: SharedClock,
     /// Start time for pulse phase calculation
     pub(super) cursor_pulse_start: std::time::Instant,
     /// Ripple duration in seconds
@@ -98,6 +159,8 @@ pub struct WgpuRenderer {
     /// Timestamp of last cursor wake trigger
     pub(super) cursor_wake_started: Option<std::time::Instant>,
     pub(super) click_halos: Vec<ClickHaloEntry>,
+    /// Videos drawn beneath the text of windows or the frame
+    pub(super) background_videos: Vec<crate::core::scene::BackgroundVideo>,
     pub(super) edge_snaps: Vec<EdgeSnapEntry>,
     pub(super) cursor_magnetism_entries: Vec<(f32, f32, std::time::Instant)>, // x, y, time
     pub(super) cursor_comet_positions: Vec<(f32, f32, f32, f32, std::time::Instant)>, // x, y, w, h, time
@@ -378,7 +441,12 @@ impl WgpuRenderer {
         let logical_h = height as f32 / scale_factor;
         let uniforms = Uniforms {
             screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
+            text_gamma: crate::effect_config::TextGammaConfig::default().params(),
+            antialias: crate::effect_config::AntialiasConfig::default().width,
+            _padding: [0.0; 3],
+            color_matrix: crate::effect_config::ColorFilterConfig::default().rows(),
+            invert: [0.0; 4],
+            invert_keep: [[0.0; 4]; crate::effect_config::SMART_INVERT_MAX_KEEP],
         };
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Uniform Buffer"),
@@ -391,7 +459,7 @@ impl WgpuRenderer {
             label: Some("Uniform Bind Group Layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -642,6 +710,45 @@ impl WgpuRenderer {
             cache: None,
         });
 
+        // Glyph pipeline for signed distance field glyphs
+        let sdf_glyph_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("SDF Glyph Pipeline"),
+            layout: Some(&glyph_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &glyph_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[GlyphVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &glyph_shader,
+                entry_point: Some("fs_sdf"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
         // Create image cache (also creates its bind group layout)
         let image_cache = ImageCache::new(&device);
 
@@ -708,6 +815,85 @@ impl WgpuRenderer {
             cache: None,
         });
 
+        // Color filter pipeline — the last copy of a frame onto the surface
+        let color_filter_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Color Filter Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/color_filter.wgsl").into()),
+        });
+        let color_filter_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Color Filter Pipeline"),
+            layout: Some(&image_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &color_filter_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[GlyphVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &color_filter_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Rounded image pipeline — floating layers with opacity and corner radius
+        let rounded_image_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Rounded Image Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/rounded_image.wgsl").into()),
+        });
+        let rounded_image_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Rounded Image Pipeline"),
+            layout: Some(&image_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &rounded_image_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[RoundedRectVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &rounded_image_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        let blur = BlurPass::new(&device);
+
         // Opaque image pipeline — for XRGB/BGRX DMA-BUF textures where alpha=0x00.
         // Uses fs_main_opaque which ignores texture alpha and uses vertex alpha instead.
         let opaque_image_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -748,6 +934,188 @@ impl WgpuRenderer {
             cache: None,
         });
 
+        // Video pipeline — image pipeline plus the color correction of the frame
+        #[cfg(feature = "video")]
+        let video_pipeline = {
+            let color_layout = video_cache
+                .color_bind_group_layout()
+                .expect("video cache GPU resources are initialized");
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Video Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout, image_cache.bind_group_layout(), color_layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Video Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &image_shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[GlyphVertex::desc()],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &image_shader,
+                    entry_point: Some("fs_video"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: target_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        // Ambient pipeline — one procedural triangle over the window backgrounds
+        let ambient_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Ambient Uniform Buffer"),
+            size: std::mem::size_of::<AmbientUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let ambient_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Ambient Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let ambient_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Ambient Bind Group"),
+            layout: &ambient_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: ambient_buffer.as_entire_binding(),
+            }],
+        });
+        let ambient_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ambient Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/ambient.wgsl").into()),
+        });
+        let ambient_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ambient Pipeline Layout"),
+            bind_group_layouts: &[&ambient_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let ambient_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ambient Pipeline"),
+            layout: Some(&ambient_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &ambient_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &ambient_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Motion blur pipeline — scroll slides streaked along their motion
+        let motion_blur_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Motion Blur Pipeline"),
+            layout: Some(&image_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &image_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[GlyphVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &image_shader,
+                entry_point: Some("fs_motion_blur"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Desaturate pipeline — redraws inactive windows from a copy of the frame
+        let desaturate_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Desaturate Pipeline"),
+            layout: Some(&image_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &image_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[GlyphVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &image_shader,
+                entry_point: Some("fs_desaturate"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Shader effect pipelines are built when effects are registered
+        let shader_effect_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shader Effect Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
         // Create surface_config from format if we have a surface
         let surface_config = if let Some(ref s) = surface {
             let config = wgpu::SurfaceConfiguration {
@@ -776,8 +1144,24 @@ impl WgpuRenderer {
             rounded_rect_pipeline,
             corner_mask_pipeline,
             glyph_pipeline,
+            sdf_glyph_pipeline,
             image_pipeline,
             opaque_image_pipeline,
+            #[cfg(feature = "video")]
+            video_pipeline,
+            color_filter_pipeline,
+            smart_invert_keep: Vec::new(),
+            ambient_pipeline,
+            ambient_buffer,
+            ambient_bind_group,
+            ambient_epoch: std::time::Instant::now(),
+            overdraw_stats: OverdrawStats::default(),
+            row_cache: row_cache::RowCache::default(),
+            motion_blur_pipeline,
+            desaturate_pipeline,
+            frame_copy: None,
+            rounded_image_pipeline,
+            blur,
             glyph_bind_group_layout,
             uniform_buffer,
             uniform_bind_group,
@@ -790,9 +1174,20 @@ impl WgpuRenderer {
             height,
             scale_factor,
             effects: crate::effect_config::EffectsConfig::default(),
+            minimap: crate::core::minimap::MinimapStore::default(),
+            theme: crate::core::display_theme::DisplayTheme::default(),
+            placeholders: crate::core::placeholder::PlaceholderStyles::default(),
+            placeholder_epoch: std::time::Instant::now(),
+            chrome_images: HashMap::new(),
+            charts: HashMap::new(),
+            shader_effects: HashMap::new(),
+            shader_effect_layout,
+            shader_effect_epoch: std::time::Instant::now(),
+            debug_rects: Vec::new(),
             per_window_dim: std::collections::HashMap::new(),
             last_dim_tick: std::time::Instant::now(),
             needs_continuous_redraw: false,
+            clock: crate::core::clock::system_clock(),
             cursor_pulse_start: std::time::Instant::now(),
             typing_ripple_duration: 0.3,
             active_ripples: Vec::new(),
@@ -818,6 +1213,7 @@ impl WgpuRenderer {
             active_scroll_spacings: Vec::new(),
             cursor_wake_started: None,
             click_halos: Vec::new(),
+            background_videos: Vec::new(),
             edge_snaps: Vec::new(),
             cursor_magnetism_entries: Vec::new(),
             cursor_comet_positions: Vec::new(),
@@ -920,6 +1316,40 @@ impl WgpuRenderer {
         Ok(Self::create_renderer_internal(device, queue, surface, surface_format, width, height, 1.0))
     }
 
+    /// Take over the state of a renderer whose device was lost: effect
+    /// settings, placeholder styles, minimap summaries, charts, chrome
+    /// nine-patches, images (decoded again from their sources, which also
+    /// gives the nine-patches their textures back), videos (re-uploaded
+    /// with their next frame) and where videos are drawn as backgrounds.
+    pub fn adopt_from(&mut self, lost: WgpuRenderer) {
+        let WgpuRenderer {
+            effects,
+            placeholders,
+            minimap,
+            charts,
+            chrome_images,
+            image_cache,
+            #[cfg(feature = "video")]
+            mut video_cache,
+            background_videos,
+            ..
+        } = lost;
+        self.effects = effects;
+        self.placeholders = placeholders;
+        self.minimap = minimap;
+        self.charts = charts;
+        self.chrome_images = chrome_images;
+        self.image_cache.reload_from(image_cache);
+        self.background_videos = background_videos;
+        #[cfg(feature = "video")]
+        {
+            video_cache.reset_gpu(&self.device);
+            self.video_cache = video_cache;
+        }
+        // Rewrite the uniforms, which depend on the effect settings
+        self.resize(self.width, self.height);
+    }
+
     /// Resize the renderer's surface.
     pub fn resize(&mut self, width: u32, height: u32) {
         if width == 0 || height == 0 {
@@ -939,10 +1369,7 @@ impl WgpuRenderer {
         // Update uniform buffer with logical size so vertex positions from Emacs map correctly
         let logical_w = width as f32 / self.scale_factor;
         let logical_h = height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
     }
@@ -952,6 +1379,24 @@ impl WgpuRenderer {
         self.scale_factor = scale_factor;
     }
 
+    /// Time effects with `clock`, the render thread's own.  Their time
+    /// origins restart on it, so a scripted clock starts them at zero.
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        let now = clock.now();
+        self.clock = clock;
+        self.ambient_epoch = now;
+        self.placeholder_epoch = now;
+        self.shader_effect_epoch = now;
+        self.last_dim_tick = now;
+        self.cursor_pulse_start = now;
+        self.search_pulse_start = now;
+        self.cursor_color_cycle_start = now;
+        self.focus_ring_start = now;
+        self.lightning_bolt_last = now;
+        self.rain_last_spawn = now;
+        self.aurora_start = now;
+    }
+
     /// Get the glyph bind group layout for creating glyph bind groups
     pub fn glyph_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
         &self.glyph_bind_group_layout
@@ -1187,6 +1632,40 @@ impl WgpuRenderer {
         texture
     }
 
+    /// Shader uniforms for a frame of `logical_w` x `logical_h`
+    pub(super) fn frame_uniforms(&self, logical_w: f32, logical_h: f32) -> Uniforms {
+        let (invert, invert_keep) = self.effects.smart_invert.params(&self.smart_invert_keep);
+        Uniforms {
+            screen_size: [logical_w, logical_h],
+            text_gamma: self.effects.text_gamma.params(),
+            antialias: self.effects.antialias.width.max(0.0),
+            _padding: [0.0; 3],
+            color_matrix: self.effects.color_filter.rows(),
+            invert,
+            invert_keep,
+        }
+    }
+
+    /// Ambient shader parameters for a frame of `logical_w` x `logical_h`
+    pub(super) fn ambient_uniforms(&self, logical_w: f32, logical_h: f32) -> AmbientUniforms {
+        let ambient = &self.effects.ambient;
+        let (r, g, b) = ambient.color;
+        AmbientUniforms {
+            color: [r, g, b, ambient.opacity.clamp(0.0, 1.0)],
+            screen_size: [logical_w, logical_h],
+            time: (self.clock.now().duration_since(self.ambient_epoch).as_secs_f64() % 3600.0) as f32,
+            kind: ambient.effect.shader_kind(),
+            density: ambient.density.clamp(0.0, 1.0),
+            speed: ambient.speed.max(0.0),
+            _padding: [0.0; 2],
+        }
+    }
+
+    /// Pipeline drawing the mask glyphs of `atlas`
+    pub(super) fn mask_glyph_pipeline(&self, atlas: &WgpuGlyphAtlas) -> &wgpu::RenderPipeline {
+        if atlas.sdf() { &self.sdf_glyph_pipeline } else { &self.glyph_pipeline }
+    }
+
     /// Add a rectangle to the vertex list (6 vertices = 2 triangles).
     fn add_rect(
         &self,
@@ -1233,11 +1712,45 @@ impl WgpuRenderer {
         });
     }
 
+    /// Add a rect shaded from its top-left, top-right, bottom-right and
+    /// bottom-left `corners`
+    fn add_gradient_rect(&self, vertices: &mut Vec<RectVertex>, rect: &Rect, corners: &[Color; 4]) {
+        let (x0, y0, x1, y1) = (rect.x, rect.y, rect.right(), rect.bottom());
+        let [tl, tr, br, bl] = corners.map(|c| [c.r, c.g, c.b, c.a]);
+        for (position, color) in [
+            ([x0, y0], tl), ([x1, y0], tr), ([x0, y1], bl),
+            ([x1, y0], tr), ([x1, y1], br), ([x0, y1], bl),
+        ] {
+            vertices.push(RectVertex { position, color });
+        }
+    }
+
     /// Emit a single rounded-rectangle border as 6 vertices (one oversized quad).
     ///
     /// The quad is padded by 1px on each side so the SDF fragment shader has
     /// room for anti-aliased edges.  The shader carves out the interior, leaving
     /// only the border ring with rounded corners.
+    /// Add an anti-aliased ring of `thickness` centered on its
+    /// `radius`, for the rounded rect pipeline.
+    fn add_ring(
+        &self,
+        vertices: &mut Vec<RoundedRectVertex>,
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        thickness: f32,
+        color: &Color,
+    ) {
+        let outer = radius + thickness / 2.0;
+        if outer <= 0.0 {
+            return;
+        }
+        self.add_rounded_rect(
+            vertices, cx - outer, cy - outer, 2.0 * outer, 2.0 * outer,
+            thickness.min(outer), outer, color,
+        );
+    }
+
     fn add_rounded_rect(
         &self,
         vertices: &mut Vec<RoundedRectVertex>,
@@ -1319,6 +1832,11 @@ impl WgpuRenderer {
         self.height
     }
 
+    /// Text rows reused from and rebuilt into the row cache last frame
+    pub fn row_cache_stats(&self) -> (usize, usize) {
+        (self.row_cache.hits, self.row_cache.misses)
+    }
+
     // =========== Image Loading Methods ===========
 
     // =========== Video Loading Methods ===========
@@ -1375,6 +1893,29 @@ impl WgpuRenderer {
         (tex, view)
     }
 
+    /// Make `frame_copy` a texture `target` can be copied into
+    pub(super) fn ensure_frame_copy(&mut self, target: &wgpu::Texture) {
+        let stale = self.frame_copy.as_ref().is_none_or(|(t, _)| {
+            t.size() != target.size() || t.format() != target.format()
+        });
+        if stale {
+            let copy = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Frame Copy"),
+                size: target.size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: target.format(),
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let bind_group = self.create_texture_bind_group(
+                &copy.create_view(&wgpu::TextureViewDescriptor::default()),
+            );
+            self.frame_copy = Some((copy, bind_group));
+        }
+    }
+
     /// Create a bind group for a texture view (usable with image_pipeline)
     pub fn create_texture_bind_group(&self, view: &wgpu::TextureView) -> wgpu::BindGroup {
         self.device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -1393,6 +1934,66 @@ impl WgpuRenderer {
         })
     }
 
+    /// Copy a texture made by `create_offscreen_texture` back from the
+    /// GPU as tightly packed RGBA rows, waiting for the copy to finish.
+    /// None if the buffer cannot be mapped, e.g. after a device loss.
+    pub fn read_texture_rgba(&self, texture: &wgpu::Texture) -> Option<Vec<u8>> {
+        let (width, height) = (texture.width(), texture.height());
+        let row_bytes = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = row_bytes.div_ceil(align) * align;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        let _ = self.device.poll(wgpu::Maintain::Wait);
+        rx.recv().ok()?.ok()?;
+
+        let bgra = matches!(
+            texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+        {
+            let mapped = slice.get_mapped_range();
+            for row in mapped.chunks(padded_row as usize) {
+                pixels.extend_from_slice(&row[..row_bytes as usize]);
+            }
+        }
+        buffer.unmap();
+        if bgra {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+        }
+        Some(pixels)
+    }
+
     /// Blit a texture to a target view (fullscreen quad)
     pub fn blit_texture_to_view(
         &self,
@@ -1400,18 +2001,54 @@ impl WgpuRenderer {
         dst_view: &wgpu::TextureView,
         width: u32,
         height: u32,
+    ) {
+        let w = width as f32 / self.scale_factor;
+        let h = height as f32 / self.scale_factor;
+        self.blit(&self.image_pipeline, src_bind_group, dst_view, width, height, Rect::new(0.0, 0.0, w, h));
+    }
+
+    /// Copy a finished frame onto a target view: the `src` part (in logical
+    /// pixels) is stretched over the whole view, through the color filter
+    /// and smart invert when either is on
+    pub fn blit_post_processed(
+        &self,
+        src_bind_group: &wgpu::BindGroup,
+        dst_view: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+        src: Rect,
+    ) {
+        let pipeline = if self.effects.color_filter.is_active() || self.effects.smart_invert.enabled {
+            &self.color_filter_pipeline
+        } else {
+            &self.image_pipeline
+        };
+        self.blit(pipeline, src_bind_group, dst_view, width, height, src);
+    }
+
+    /// Stretch the `src` part (in logical pixels) of a frame-sized texture
+    /// over a whole target view with `pipeline`
+    fn blit(
+        &self,
+        pipeline: &wgpu::RenderPipeline,
+        src_bind_group: &wgpu::BindGroup,
+        dst_view: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+        src: Rect,
     ) {
         // Use logical dimensions for vertex positions since screen_size uniform is logical
         let w = width as f32 / self.scale_factor;
         let h = height as f32 / self.scale_factor;
+        let (u0, v0, u1, v1) = (src.x / w, src.y / h, src.right() / w, src.bottom() / h);
 
         let vertices = [
-            GlyphVertex { position: [0.0, 0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-            GlyphVertex { position: [w, 0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-            GlyphVertex { position: [w, h], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0] },
-            GlyphVertex { position: [0.0, 0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0] },
-            GlyphVertex { position: [w, h], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0] },
-            GlyphVertex { position: [0.0, h], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0] },
+            GlyphVertex { position: [0.0, 0.0], tex_coords: [u0, v0], color: [1.0, 1.0, 1.0, 1.0] },
+            GlyphVertex { position: [w, 0.0], tex_coords: [u1, v0], color: [1.0, 1.0, 1.0, 1.0] },
+            GlyphVertex { position: [w, h], tex_coords: [u1, v1], color: [1.0, 1.0, 1.0, 1.0] },
+            GlyphVertex { position: [0.0, 0.0], tex_coords: [u0, v0], color: [1.0, 1.0, 1.0, 1.0] },
+            GlyphVertex { position: [w, h], tex_coords: [u1, v1], color: [1.0, 1.0, 1.0, 1.0] },
+            GlyphVertex { position: [0.0, h], tex_coords: [u0, v1], color: [1.0, 1.0, 1.0, 1.0] },
         ];
 
         let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -1440,7 +2077,7 @@ impl WgpuRenderer {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.image_pipeline);
+            render_pass.set_pipeline(pipeline);
             render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
             render_pass.set_bind_group(1, src_bind_group, &[]);
             render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
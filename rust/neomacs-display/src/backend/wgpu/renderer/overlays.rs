@@ -3,13 +3,26 @@
 use super::WgpuRenderer;
 use super::TitleFadeEntry;
 use wgpu::util::DeviceExt;
-use super::super::vertex::{GlyphVertex, RectVertex, RoundedRectVertex, Uniforms};
+use super::super::vertex::{GlyphVertex, RectVertex, RoundedRectVertex};
 use crate::core::types::{Color, Rect};
 use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
-use super::super::glyph_atlas::{GlyphKey, WgpuGlyphAtlas};
+use super::super::glyph_atlas::{ComposedGlyphKey, GlyphKey, WgpuGlyphAtlas};
+use crate::core::face::Face;
+use crate::core::nine_patch::ChromeElement;
+use crate::render_thread::CharGridState;
 use crate::render_thread::PopupMenuState;
 use crate::render_thread::TooltipState;
 
+/// Face ID reserved for character grid glyphs, so their sizes never
+/// collide with buffer text in the glyph cache.
+const CHAR_GRID_FACE_ID: u32 = u32::MAX - 1;
+
+/// A glyph cache entry referenced by the character grid
+enum CharGridGlyph {
+    Single(GlyphKey),
+    Composed(ComposedGlyphKey),
+}
+
 impl WgpuRenderer {
     /// Render floating videos from the scene.
     ///
@@ -46,7 +59,6 @@ impl WgpuRenderer {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.image_pipeline);
             render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
 
             for fv in floating_videos {
@@ -70,6 +82,7 @@ impl WgpuRenderer {
                             usage: wgpu::BufferUsages::VERTEX,
                         });
 
+                        self.set_video_pipeline(&mut render_pass, cached);
                         render_pass.set_bind_group(1, bind_group, &[]);
                         render_pass.set_vertex_buffer(0, video_buffer.slice(..));
                         render_pass.draw(0..6, 0..1);
@@ -85,6 +98,255 @@ impl WgpuRenderer {
         self.queue.submit(std::iter::once(encoder.finish()));
     }
 
+    /// Render the picture-in-picture player at `rect`, with its control
+    /// bar while the pointer is over it.  `progress` is how far the video
+    /// has played, when known.
+    #[cfg(feature = "video")]
+    pub fn render_pip(
+        &self,
+        view: &wgpu::TextureView,
+        pip: &crate::core::pip::PipPlayer,
+        rect: Rect,
+        playing: bool,
+        progress: Option<f32>,
+    ) {
+        let Some(cached) = self.video_cache.get(pip.video_id) else {
+            return;
+        };
+
+        let mut rect_vertices: Vec<RectVertex> = Vec::new();
+        let shadow = Color::new(0.0, 0.0, 0.0, 0.35);
+        self.add_rect(&mut rect_vertices, rect.x + 2.0, rect.y + 3.0, rect.width, rect.height, &shadow);
+        if cached.bind_group.is_none() {
+            // Nothing decoded yet: the theme's background, mostly opaque
+            let bg = self.theme.background;
+            let placeholder = Color::new(bg.r, bg.g, bg.b, 0.8);
+            self.add_rect(&mut rect_vertices, rect.x, rect.y, rect.width, rect.height, &placeholder);
+        }
+
+        let mut control_vertices: Vec<RectVertex> = Vec::new();
+        if pip.controls_shown() {
+            let c = pip.controls(rect);
+            let white = Color::new(1.0, 1.0, 1.0, 0.9);
+            let dim = Color::new(1.0, 1.0, 1.0, 0.3);
+            let bar_bg = Color::new(0.0, 0.0, 0.0, 0.55);
+            self.add_rect(&mut control_vertices, c.bar.x, c.bar.y, c.bar.width, c.bar.height, &bar_bg);
+
+            let p = c.play;
+            if playing {
+                // Pause: two bars
+                let w = p.width * 0.25;
+                self.add_rect(&mut control_vertices, p.x + w * 0.8, p.y + 3.0, w, p.height - 6.0, &white);
+                self.add_rect(&mut control_vertices, p.right() - w * 1.8, p.y + 3.0, w, p.height - 6.0, &white);
+            } else {
+                // Play: a triangle pointing right
+                let color = [white.r, white.g, white.b, white.a];
+                for position in [[p.x + 5.0, p.y + 3.0], [p.right() - 3.0, p.y + p.height / 2.0], [p.x + 5.0, p.bottom() - 3.0]] {
+                    control_vertices.push(RectVertex { position, color });
+                }
+            }
+
+            let s = c.seek;
+            let track_h = 4.0;
+            let track_y = s.y + (s.height - track_h) / 2.0;
+            self.add_rect(&mut control_vertices, s.x, track_y, s.width, track_h, &dim);
+            if let Some(progress) = progress {
+                self.add_rect(&mut control_vertices, s.x, track_y, s.width * progress, track_h, &white);
+                let knob = 10.0;
+                let kx = s.x + s.width * progress - knob / 2.0;
+                self.add_rect(&mut control_vertices, kx, s.y + (s.height - knob) / 2.0, knob, knob, &white);
+            }
+
+            // Close: an X of two thin slanted quads
+            let x = c.close;
+            let (x0, y0, x1, y1, t) = (x.x + 5.0, x.y + 5.0, x.right() - 5.0, x.bottom() - 5.0, 1.5);
+            let color = [white.r, white.g, white.b, white.a];
+            for [a, b] in [[[x0, y0], [x1, y1]], [[x1, y0], [x0, y1]]] {
+                let quad = [
+                    [a[0] - t, a[1]], [a[0] + t, a[1]], [b[0] + t, b[1]],
+                    [a[0] - t, a[1]], [b[0] + t, b[1]], [b[0] - t, b[1]],
+                ];
+                control_vertices.extend(quad.into_iter().map(|position| RectVertex { position, color }));
+            }
+
+            // Resize grip: a small triangle in the corner
+            let g = c.grip;
+            let color = [dim.r, dim.g, dim.b, 0.6];
+            let corner = if g.x > rect.x { [g.right(), g.y] } else { [g.x, g.y] };
+            let across = if g.x > rect.x { [g.x + 6.0, g.y] } else { [g.right() - 6.0, g.y] };
+            for position in [corner, across, [corner[0], g.bottom() - 6.0]] {
+                control_vertices.push(RectVertex { position, color });
+            }
+        }
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Picture-in-picture Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Picture-in-picture Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            let rect_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Picture-in-picture Rect Buffer"),
+                contents: bytemuck::cast_slice(&rect_vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            pass.set_pipeline(&self.rect_pipeline);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            pass.set_vertex_buffer(0, rect_buffer.slice(..));
+            pass.draw(0..rect_vertices.len() as u32, 0..1);
+
+            if let Some(ref bind_group) = cached.bind_group {
+                let white = [1.0, 1.0, 1.0, 1.0];
+                let vertices = [
+                    GlyphVertex { position: [rect.x, rect.y], tex_coords: [0.0, 0.0], color: white },
+                    GlyphVertex { position: [rect.right(), rect.y], tex_coords: [1.0, 0.0], color: white },
+                    GlyphVertex { position: [rect.right(), rect.bottom()], tex_coords: [1.0, 1.0], color: white },
+                    GlyphVertex { position: [rect.x, rect.y], tex_coords: [0.0, 0.0], color: white },
+                    GlyphVertex { position: [rect.right(), rect.bottom()], tex_coords: [1.0, 1.0], color: white },
+                    GlyphVertex { position: [rect.x, rect.bottom()], tex_coords: [0.0, 1.0], color: white },
+                ];
+                let video_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Picture-in-picture Video Buffer"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+                self.set_video_pipeline(&mut pass, cached);
+                pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                pass.set_bind_group(1, bind_group, &[]);
+                pass.set_vertex_buffer(0, video_buffer.slice(..));
+                pass.draw(0..6, 0..1);
+            }
+
+            if !control_vertices.is_empty() {
+                let control_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Picture-in-picture Controls Buffer"),
+                    contents: bytemuck::cast_slice(&control_vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+                pass.set_pipeline(&self.rect_pipeline);
+                pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                pass.set_vertex_buffer(0, control_buffer.slice(..));
+                pass.draw(0..control_vertices.len() as u32, 0..1);
+            }
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Render the control bar of an inline video
+    #[cfg(feature = "video")]
+    pub fn render_video_controls(
+        &self,
+        view: &wgpu::TextureView,
+        layout: &crate::core::video_controls::VideoControlsLayout,
+        status: &crate::core::video_controls::VideoStatus,
+        glyph_atlas: &mut WgpuGlyphAtlas,
+    ) {
+        let white = Color::new(1.0, 1.0, 1.0, 0.9);
+        let track = Color::new(1.0, 1.0, 1.0, 0.25);
+        let buffered = Color::new(1.0, 1.0, 1.0, 0.45);
+        let bar = layout.bar;
+        let mut rect_vertices: Vec<RectVertex> = Vec::new();
+        self.add_rect(&mut rect_vertices, bar.x, bar.y, bar.width, bar.height, &Color::new(0.0, 0.0, 0.0, 0.6));
+
+        let p = layout.play;
+        if status.playing {
+            let w = p.width * 0.25;
+            self.add_rect(&mut rect_vertices, p.x + w * 0.8, p.y + 4.0, w, p.height - 8.0, &white);
+            self.add_rect(&mut rect_vertices, p.right() - w * 1.8, p.y + 4.0, w, p.height - 8.0, &white);
+        } else {
+            let color = [white.r, white.g, white.b, white.a];
+            for position in [[p.x + 6.0, p.y + 4.0], [p.right() - 4.0, p.y + p.height / 2.0], [p.x + 6.0, p.bottom() - 4.0]] {
+                rect_vertices.push(RectVertex { position, color });
+            }
+        }
+
+        // Scrubber: track, buffered ranges, played part and knob
+        let s = layout.scrub;
+        let track_h = 4.0;
+        let track_y = s.y + (s.height - track_h) / 2.0;
+        self.add_rect(&mut rect_vertices, s.x, track_y, s.width, track_h, &track);
+        for &(start, end) in &status.buffered {
+            self.add_rect(&mut rect_vertices, s.x + s.width * start, track_y, s.width * (end - start), track_h, &buffered);
+        }
+        if let Some(progress) = status.progress {
+            self.add_rect(&mut rect_vertices, s.x, track_y, s.width * progress, track_h, &white);
+            let knob = 10.0;
+            self.add_rect(&mut rect_vertices, s.x + s.width * progress - knob / 2.0,
+                          s.y + (s.height - knob) / 2.0, knob, knob, &white);
+        }
+
+        // Volume: a wedge filled up to the level
+        if let Some(v) = layout.volume {
+            let color = |c: &Color| [c.r, c.g, c.b, c.a];
+            let wedge = |w: f32| [[v.x, v.bottom() - 4.0], [v.x + w, v.bottom() - 4.0], [v.x + w, v.bottom() - 4.0 - (v.height - 8.0) * w / v.width]];
+            for position in wedge(v.width) {
+                rect_vertices.push(RectVertex { position, color: color(&track) });
+            }
+            for position in wedge(v.width * status.volume.clamp(0.0, 1.0)) {
+                rect_vertices.push(RectVertex { position, color: color(&white) });
+            }
+        }
+
+        let rect_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Video Controls Rect Buffer"),
+            contents: bytemuck::cast_slice(&rect_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Video Controls Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Video Controls Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.rect_pipeline);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            pass.set_vertex_buffer(0, rect_buffer.slice(..));
+            pass.draw(0..rect_vertices.len() as u32, 0..1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+
+        // Time label, centered in its area
+        if let (Some(area), Some(text)) = (layout.time, status.time.as_deref()) {
+            let char_width = glyph_atlas.default_font_size() * 0.6;
+            let text_w = text.chars().count() as f32 * char_width;
+            let x0 = area.x + ((area.width - text_w) / 2.0).max(0.0);
+            let y = area.y + (area.height - glyph_atlas.default_line_height()) / 2.0;
+            let font_size_bits = 0.0_f32.to_bits();
+            let mut text_glyphs: Vec<(GlyphKey, f32, f32, [f32; 4])> = Vec::new();
+            for (i, ch) in text.chars().enumerate() {
+                let key = GlyphKey { charcode: ch as u32, face_id: 0, font_size_bits };
+                glyph_atlas.get_or_create(&self.device, &self.queue, &key, None);
+                text_glyphs.push((key, x0 + i as f32 * char_width, y, [white.r, white.g, white.b, white.a]));
+            }
+            self.render_overlay_glyphs(view, &mut text_glyphs, glyph_atlas);
+        }
+    }
+
     /// Render a WebKit view texture at the given bounds.
     ///
     /// This method renders the WebKit view content (from a wgpu texture)
@@ -119,10 +381,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -193,25 +452,28 @@ impl WgpuRenderer {
 
             // === Pass 1: Background rectangles ===
             let mut rect_vertices: Vec<RectVertex> = Vec::new();
+            let bw = 1.0_f32;
 
-            // Drop shadow
-            let shadow_layers = 4;
-            for i in 1..=shadow_layers {
-                let offset = i as f32 * 1.5;
-                let alpha = 0.12 * (1.0 - (i - 1) as f32 / shadow_layers as f32);
-                let shadow = Color::new(0.0, 0.0, 0.0, alpha);
-                self.add_rect(&mut rect_vertices, mx + offset, my + offset, mw, mh, &shadow);
-            }
+            // A themed image replaces the shadow, background and border
+            if !self.render_chrome_image(view, ChromeElement::PopupMenu, Rect::new(mx, my, mw, mh)) {
+                // Drop shadow
+                let shadow_layers = 4;
+                for i in 1..=shadow_layers {
+                    let offset = i as f32 * 1.5;
+                    let alpha = 0.12 * (1.0 - (i - 1) as f32 / shadow_layers as f32);
+                    let shadow = Color::new(0.0, 0.0, 0.0, alpha);
+                    self.add_rect(&mut rect_vertices, mx + offset, my + offset, mw, mh, &shadow);
+                }
 
-            // Background
-            self.add_rect(&mut rect_vertices, mx, my, mw, mh, &bg_color);
+                // Background
+                self.add_rect(&mut rect_vertices, mx, my, mw, mh, &bg_color);
 
-            // Border
-            let bw = 1.0_f32;
-            self.add_rect(&mut rect_vertices, mx, my, mw, bw, &border_color);
-            self.add_rect(&mut rect_vertices, mx, my + mh - bw, mw, bw, &border_color);
-            self.add_rect(&mut rect_vertices, mx, my, bw, mh, &border_color);
-            self.add_rect(&mut rect_vertices, mx + mw - bw, my, bw, mh, &border_color);
+                // Border
+                self.add_rect(&mut rect_vertices, mx, my, mw, bw, &border_color);
+                self.add_rect(&mut rect_vertices, mx, my + mh - bw, mw, bw, &border_color);
+                self.add_rect(&mut rect_vertices, mx, my, bw, mh, &border_color);
+                self.add_rect(&mut rect_vertices, mx + mw - bw, my, bw, mh, &border_color);
+            }
 
             // Hover highlight
             if panel.hover_index >= 0 && (panel.hover_index as usize) < panel.item_indices.len() {
@@ -373,12 +635,12 @@ impl WgpuRenderer {
                 let gy = *y - cached.bearing_y + 14.0;
 
                 vertices.extend_from_slice(&[
-                    GlyphVertex { position: [gx, gy], tex_coords: [0.0, 0.0], color: *color },
-                    GlyphVertex { position: [gx + gw, gy], tex_coords: [1.0, 0.0], color: *color },
-                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: [1.0, 1.0], color: *color },
-                    GlyphVertex { position: [gx, gy], tex_coords: [0.0, 0.0], color: *color },
-                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: [1.0, 1.0], color: *color },
-                    GlyphVertex { position: [gx, gy + gh], tex_coords: [0.0, 1.0], color: *color },
+                    GlyphVertex { position: [gx, gy], tex_coords: cached.tex_coord(0.0, 0.0), color: *color },
+                    GlyphVertex { position: [gx + gw, gy], tex_coords: cached.tex_coord(1.0, 0.0), color: *color },
+                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: cached.tex_coord(1.0, 1.0), color: *color },
+                    GlyphVertex { position: [gx, gy], tex_coords: cached.tex_coord(0.0, 0.0), color: *color },
+                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: cached.tex_coord(1.0, 1.0), color: *color },
+                    GlyphVertex { position: [gx, gy + gh], tex_coords: cached.tex_coord(0.0, 1.0), color: *color },
                 ]);
                 valid.push(true);
             } else {
@@ -430,6 +692,8 @@ impl WgpuRenderer {
                 if let Some(cached) = glyph_atlas.get(key) {
                     if cached.is_color {
                         pass.set_pipeline(&self.opaque_image_pipeline);
+                    } else if cached.is_sdf {
+                        pass.set_pipeline(&self.sdf_glyph_pipeline);
                     } else {
                         pass.set_pipeline(&self.image_pipeline);
                     }
@@ -534,12 +798,12 @@ impl WgpuRenderer {
                 let gy = *y + (char_height * 0.7) - cached.bearing_y / sf * s;
 
                 vertices.extend_from_slice(&[
-                    GlyphVertex { position: [gx, gy], tex_coords: [0.0, 0.0], color: *color },
-                    GlyphVertex { position: [gx + gw, gy], tex_coords: [1.0, 0.0], color: *color },
-                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: [1.0, 1.0], color: *color },
-                    GlyphVertex { position: [gx, gy], tex_coords: [0.0, 0.0], color: *color },
-                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: [1.0, 1.0], color: *color },
-                    GlyphVertex { position: [gx, gy + gh], tex_coords: [0.0, 1.0], color: *color },
+                    GlyphVertex { position: [gx, gy], tex_coords: cached.tex_coord(0.0, 0.0), color: *color },
+                    GlyphVertex { position: [gx + gw, gy], tex_coords: cached.tex_coord(1.0, 0.0), color: *color },
+                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: cached.tex_coord(1.0, 1.0), color: *color },
+                    GlyphVertex { position: [gx, gy], tex_coords: cached.tex_coord(0.0, 0.0), color: *color },
+                    GlyphVertex { position: [gx + gw, gy + gh], tex_coords: cached.tex_coord(1.0, 1.0), color: *color },
+                    GlyphVertex { position: [gx, gy + gh], tex_coords: cached.tex_coord(0.0, 1.0), color: *color },
                 ]);
                 valid.push(true);
             } else {
@@ -588,6 +852,8 @@ impl WgpuRenderer {
                 if let Some(cached) = glyph_atlas.get(key) {
                     if cached.is_color {
                         pass.set_pipeline(&self.opaque_image_pipeline);
+                    } else if cached.is_sdf {
+                        pass.set_pipeline(&self.sdf_glyph_pipeline);
                     } else {
                         pass.set_pipeline(&self.image_pipeline);
                     }
@@ -608,6 +874,198 @@ impl WgpuRenderer {
         self.queue.submit(Some(encoder.finish()));
     }
 
+    /// Draw placeholders over images, videos and web views that have
+    /// nothing to show, in the styles of `self.placeholders`.  Animated
+    /// ones ask for continuous redraws.
+    pub fn render_placeholders(
+        &mut self,
+        view: &wgpu::TextureView,
+        frame_glyphs: &FrameGlyphBuffer,
+        glyph_atlas: &mut WgpuGlyphAtlas,
+        surface_width: u32,
+        surface_height: u32,
+    ) {
+        use crate::backend::wgpu::image_cache::ImageState;
+        use crate::core::placeholder::{self, PlaceholderContent, PlaceholderState};
+
+        let mut pending: Vec<(PlaceholderContent, PlaceholderState, Rect)> = Vec::new();
+        for glyph in &frame_glyphs.glyphs {
+            let (content, state, x, y, width, height) = match glyph {
+                FrameGlyph::Image { image_id, x, y, width, height } => {
+                    let state = match self.image_cache.get_state(*image_id) {
+                        Some(ImageState::Failed(_)) => PlaceholderState::Broken,
+                        _ if self.charts.contains_key(image_id) => continue,
+                        _ if self.image_cache.bind_group(*image_id, *width, *height).is_some() => continue,
+                        _ => PlaceholderState::Loading,
+                    };
+                    (PlaceholderContent::Image, state, x, y, width, height)
+                }
+                #[cfg(feature = "video")]
+                FrameGlyph::Video { video_id, x, y, width, height } => {
+                    let state = match self.video_cache.get(*video_id) {
+                        Some(cached) if cached.state == crate::backend::wgpu::video_cache::VideoState::Error => {
+                            PlaceholderState::Broken
+                        }
+                        Some(cached) if cached.bind_group.is_some() => continue,
+                        _ => PlaceholderState::Loading,
+                    };
+                    (PlaceholderContent::Video, state, x, y, width, height)
+                }
+                #[cfg(feature = "wpe-webkit")]
+                FrameGlyph::WebKit { webkit_id, x, y, width, height } => {
+                    if self.webkit_cache.get(*webkit_id).is_some() {
+                        continue;
+                    }
+                    (PlaceholderContent::WebKit, PlaceholderState::Loading, x, y, width, height)
+                }
+                _ => continue,
+            };
+            // Keep to the text area of the window, off its mode line
+            let mut area = Rect::new(*x, *y, *width, *height);
+            if let Some(info) = frame_glyphs.window_infos.iter().find(|info| info.bounds.contains(area.origin())) {
+                let b = &info.bounds;
+                let text_area = Rect::new(b.x, b.y, b.width, b.height - info.mode_line_height);
+                match area.intersection(&text_area) {
+                    Some(clipped) => area = clipped,
+                    None => continue,
+                }
+            }
+            pending.push((content, state, area));
+        }
+        if pending.is_empty() {
+            return;
+        }
+
+        let logical_w = surface_width as f32 / self.scale_factor;
+        let logical_h = surface_height as f32 / self.scale_factor;
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let time = self.clock.now().duration_since(self.placeholder_epoch).as_secs_f32();
+        let base = self.theme.placeholder;
+        let fg = self.theme.foreground;
+        let loading_text = [fg.r, fg.g, fg.b, 0.7];
+        let warning = Color::new(1.0, 0.7, 0.2, 1.0).srgb_to_linear();
+        let broken_text = [warning.r, warning.g, warning.b, warning.a];
+        let char_width = glyph_atlas.default_font_size() * 0.6;
+        let line_height = glyph_atlas.default_line_height();
+        let font_size_bits = 0.0_f32.to_bits();
+
+        let mut animated = false;
+        let mut rect_vertices: Vec<RectVertex> = Vec::new();
+        let mut overlay_glyphs: Vec<(GlyphKey, f32, f32, [f32; 4])> = Vec::new();
+        for (content, state, area) in pending {
+            let style = self.placeholders.get(content);
+            let shapes = placeholder::layout(content, state, style, area, time);
+            animated |= shapes.animated;
+            for (rect, alpha) in &shapes.rects {
+                let color = Color::new(base.r, base.g, base.b, (base.a * alpha).min(1.0));
+                self.add_rect(&mut rect_vertices, rect.x, rect.y, rect.width, rect.height, &color);
+            }
+
+            let Some((icon, message)) = shapes.label else { continue };
+            if area.height < line_height {
+                continue;
+            }
+            // The message goes first when there is no room for both
+            let full = format!("{} {}", icon, message);
+            let text = if (full.chars().count() as f32 + 1.0) * char_width <= area.width {
+                full
+            } else if 2.0 * char_width <= area.width {
+                icon.to_string()
+            } else {
+                continue;
+            };
+            let color = if state == PlaceholderState::Broken { broken_text } else { loading_text };
+            let text_x = area.x + (area.width - text.chars().count() as f32 * char_width) / 2.0;
+            let text_y = area.y + (area.height - line_height) / 2.0;
+            for (ci, ch) in text.chars().enumerate() {
+                if ch == ' ' {
+                    continue;
+                }
+                let key = GlyphKey { charcode: ch as u32, face_id: 0, font_size_bits };
+                glyph_atlas.get_or_create(&self.device, &self.queue, &key, None);
+                overlay_glyphs.push((key, text_x + ci as f32 * char_width, text_y, color));
+            }
+        }
+
+        if !rect_vertices.is_empty() {
+            let rect_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Placeholder Rect Buffer"),
+                contents: bytemuck::cast_slice(&rect_vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Placeholder Rect Encoder"),
+            });
+            {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Placeholder Rect Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                pass.set_pipeline(&self.rect_pipeline);
+                pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                pass.set_vertex_buffer(0, rect_buffer.slice(..));
+                pass.draw(0..rect_vertices.len() as u32, 0..1);
+            }
+            self.queue.submit(Some(encoder.finish()));
+        }
+        self.render_overlay_glyphs(view, &mut overlay_glyphs, glyph_atlas);
+        if animated {
+            self.needs_continuous_redraw = true;
+        }
+    }
+
+    /// Draw the nine-patch image of chrome `element` over `area` in a pass
+    /// of its own.  Returns whether the element has one to draw.
+    fn render_chrome_image(&self, view: &wgpu::TextureView, element: ChromeElement, area: Rect) -> bool {
+        let Some((bind_group, vertices)) = self.chrome_image_vertices(element, area) else {
+            return false;
+        };
+        let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Chrome Image Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Chrome Image Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Chrome Image Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.image_pipeline);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            pass.set_bind_group(1, bind_group, &[]);
+            pass.set_vertex_buffer(0, buffer.slice(..));
+            pass.draw(0..vertices.len() as u32, 0..1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+        true
+    }
+
     /// Render a tooltip overlay on top of the scene.
     pub fn render_tooltip(
         &self,
@@ -621,10 +1079,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -646,25 +1101,28 @@ impl WgpuRenderer {
         // === Pass 1: Background and border rectangles ===
         let mut rect_vertices: Vec<RectVertex> = Vec::new();
 
-        // Drop shadow (layered for soft edge)
-        let shadow_layers = 3;
-        for i in 1..=shadow_layers {
-            let offset = i as f32 * 1.0;
-            let alpha = 0.10 * (1.0 - (i - 1) as f32 / shadow_layers as f32);
-            let shadow = Color::new(0.0, 0.0, 0.0, alpha);
-            self.add_rect(&mut rect_vertices,
-                          tx + offset, ty + offset, tw, th, &shadow);
-        }
+        // A themed image replaces the shadow, background and border
+        if !self.render_chrome_image(view, ChromeElement::Tooltip, Rect::new(tx, ty, tw, th)) {
+            // Drop shadow (layered for soft edge)
+            let shadow_layers = 3;
+            for i in 1..=shadow_layers {
+                let offset = i as f32 * 1.0;
+                let alpha = 0.10 * (1.0 - (i - 1) as f32 / shadow_layers as f32);
+                let shadow = Color::new(0.0, 0.0, 0.0, alpha);
+                self.add_rect(&mut rect_vertices,
+                              tx + offset, ty + offset, tw, th, &shadow);
+            }
 
-        // Background
-        self.add_rect(&mut rect_vertices, tx, ty, tw, th, &bg_color);
+            // Background
+            self.add_rect(&mut rect_vertices, tx, ty, tw, th, &bg_color);
 
-        // Border (1px)
-        let bw = 1.0_f32;
-        self.add_rect(&mut rect_vertices, tx, ty, tw, bw, &border_color); // top
-        self.add_rect(&mut rect_vertices, tx, ty + th - bw, tw, bw, &border_color); // bottom
-        self.add_rect(&mut rect_vertices, tx, ty, bw, th, &border_color); // left
-        self.add_rect(&mut rect_vertices, tx + tw - bw, ty, bw, th, &border_color); // right
+            // Border (1px)
+            let bw = 1.0_f32;
+            self.add_rect(&mut rect_vertices, tx, ty, tw, bw, &border_color); // top
+            self.add_rect(&mut rect_vertices, tx, ty + th - bw, tw, bw, &border_color); // bottom
+            self.add_rect(&mut rect_vertices, tx, ty, bw, th, &border_color); // left
+            self.add_rect(&mut rect_vertices, tx + tw - bw, ty, bw, th, &border_color); // right
+        }
 
         if !rect_vertices.is_empty() {
             let rect_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -722,6 +1180,176 @@ impl WgpuRenderer {
         self.render_overlay_glyphs(view, &mut overlay_glyphs, glyph_atlas);
     }
 
+    /// Render a character grid overlay (emoji picker, glyph table).
+    ///
+    /// Only the rows inside the viewport are rasterized and drawn, so grids
+    /// with thousands of color glyphs cost the same as a single screenful.
+    pub(crate) fn render_char_grid(
+        &self,
+        view: &wgpu::TextureView,
+        grid: &CharGridState,
+        glyph_atlas: &mut WgpuGlyphAtlas,
+        surface_width: u32,
+        surface_height: u32,
+    ) {
+        let logical_w = surface_width as f32 / self.scale_factor;
+        let logical_h = surface_height as f32 / self.scale_factor;
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let layout = &grid.layout;
+        let cell = layout.cell_size;
+        let (gw, gh) = layout.size();
+
+        // === Pass 1: panel, border and selection highlight ===
+        let bg_color = Color::new(grid.bg.0, grid.bg.1, grid.bg.2, 0.97).srgb_to_linear();
+        let border_color = Color::new(
+            (grid.bg.0 * 0.6 + 0.15).min(1.0),
+            (grid.bg.1 * 0.6 + 0.15).min(1.0),
+            (grid.bg.2 * 0.6 + 0.15).min(1.0),
+            1.0,
+        ).srgb_to_linear();
+        let sel_color = Color::new(grid.selected_bg.0, grid.selected_bg.1, grid.selected_bg.2, 1.0)
+            .srgb_to_linear();
+
+        let mut rect_vertices: Vec<RectVertex> = Vec::new();
+        self.add_rect(&mut rect_vertices, layout.x, layout.y, gw, gh, &bg_color);
+        let bw = 1.0_f32;
+        self.add_rect(&mut rect_vertices, layout.x, layout.y, gw, bw, &border_color);
+        self.add_rect(&mut rect_vertices, layout.x, layout.y + gh - bw, gw, bw, &border_color);
+        self.add_rect(&mut rect_vertices, layout.x, layout.y, bw, gh, &border_color);
+        self.add_rect(&mut rect_vertices, layout.x + gw - bw, layout.y, bw, gh, &border_color);
+        if let Some((sx, sy)) = grid.selected.and_then(|i| layout.cell_origin(i)) {
+            self.add_rect(&mut rect_vertices, sx + 1.0, sy + 1.0, cell - 2.0, cell - 2.0, &sel_color);
+        }
+
+        let rect_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Char Grid Rect Buffer"),
+            contents: bytemuck::cast_slice(&rect_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        // === Pass 2: glyphs for the visible rows only ===
+        let sf = self.scale_factor;
+        let font_size = (cell * 0.7).max(1.0);
+        let font_size_bits = font_size.to_bits();
+        let face = Face {
+            font_size,
+            ..Face::new(CHAR_GRID_FACE_ID)
+        };
+        let text_color = {
+            let c = Color::new(grid.fg.0, grid.fg.1, grid.fg.2, 1.0).srgb_to_linear();
+            [c.r, c.g, c.b, c.a]
+        };
+
+        let mut vertices: Vec<GlyphVertex> = Vec::new();
+        let mut draws: Vec<(CharGridGlyph, bool)> = Vec::new();
+
+        for index in layout.visible_range() {
+            let Some((cx, cy)) = layout.cell_origin(index) else { continue };
+            let text = grid.items[index].as_str();
+            let mut chars = text.chars();
+            let (glyph_ref, cached) = match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    let key = GlyphKey {
+                        charcode: c as u32,
+                        face_id: CHAR_GRID_FACE_ID,
+                        font_size_bits,
+                    };
+                    let cached = glyph_atlas.get_or_create(&self.device, &self.queue, &key, Some(&face));
+                    let dims = cached.map(|g| (g.width, g.height, g.is_color, g.uv));
+                    (CharGridGlyph::Single(key), dims)
+                }
+                (Some(_), Some(_)) => {
+                    let text: std::sync::Arc<str> = text.into();
+                    let cached = glyph_atlas.get_or_create_composed(
+                        &self.device, &self.queue, &text, CHAR_GRID_FACE_ID, font_size_bits, Some(&face),
+                    );
+                    let dims = cached.map(|g| (g.width, g.height, g.is_color, g.uv));
+                    let key = ComposedGlyphKey {
+                        text,
+                        face_id: CHAR_GRID_FACE_ID,
+                        font_size_bits,
+                    };
+                    (CharGridGlyph::Composed(key), dims)
+                }
+                _ => continue,
+            };
+            let Some((w, h, is_color, [u0, v0, u1, v1])) = cached else { continue };
+
+            // Center the glyph bitmap in its cell
+            let w = w as f32 / sf;
+            let h = h as f32 / sf;
+            let gx = cx + (cell - w) / 2.0;
+            let gy = cy + (cell - h) / 2.0;
+            let color = if is_color { [1.0, 1.0, 1.0, 1.0] } else { text_color };
+            vertices.extend_from_slice(&[
+                GlyphVertex { position: [gx, gy], tex_coords: [u0, v0], color },
+                GlyphVertex { position: [gx + w, gy], tex_coords: [u1, v0], color },
+                GlyphVertex { position: [gx + w, gy + h], tex_coords: [u1, v1], color },
+                GlyphVertex { position: [gx, gy], tex_coords: [u0, v0], color },
+                GlyphVertex { position: [gx + w, gy + h], tex_coords: [u1, v1], color },
+                GlyphVertex { position: [gx, gy + h], tex_coords: [u0, v1], color },
+            ]);
+            draws.push((glyph_ref, is_color));
+        }
+
+        let glyph_buffer = (!vertices.is_empty()).then(|| {
+            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Char Grid Glyph Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Char Grid Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Char Grid Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.rect_pipeline);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            pass.set_vertex_buffer(0, rect_buffer.slice(..));
+            pass.draw(0..rect_vertices.len() as u32, 0..1);
+
+            if let Some(ref buffer) = glyph_buffer {
+                pass.set_vertex_buffer(0, buffer.slice(..));
+                for (i, (glyph_ref, is_color)) in draws.iter().enumerate() {
+                    let cached = match glyph_ref {
+                        CharGridGlyph::Single(key) => glyph_atlas.get(key),
+                        CharGridGlyph::Composed(key) => glyph_atlas.get_composed(key),
+                    };
+                    let Some(cached) = cached else { continue };
+                    pass.set_pipeline(if *is_color {
+                        &self.opaque_image_pipeline
+                    } else if cached.is_sdf {
+                        &self.sdf_glyph_pipeline
+                    } else {
+                        &self.image_pipeline
+                    });
+                    pass.set_bind_group(1, &cached.bind_group, &[]);
+                    let start = (i * 6) as u32;
+                    pass.draw(start..start + 6, 0..1);
+                }
+            }
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+
     /// Render a custom title bar overlay for borderless/undecorated windows.
     /// Draws a dark bar at the top with the window title and close/maximize/minimize buttons.
     pub fn render_custom_titlebar(
@@ -739,10 +1367,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -754,7 +1379,8 @@ impl WgpuRenderer {
             // Slightly darken the frame bg for the title bar
             Color::new(r * 0.85, g * 0.85, b * 0.85, 0.95)
         } else {
-            Color::new(0.12, 0.12, 0.14, 0.95).srgb_to_linear()
+            let bg = self.theme.background;
+            Color::new(bg.r, bg.g, bg.b, 0.95)
         };
         // Determine if theme is light or dark based on luminance
         let luminance = bg_color.r * 0.299 + bg_color.g * 0.587 + bg_color.b * 0.114;
@@ -923,10 +1549,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -1043,10 +1666,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -1137,10 +1757,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -1282,6 +1899,7 @@ impl WgpuRenderer {
         let opacity = self.effects.breadcrumb.opacity.clamp(0.0, 1.0);
 
         // Detect title changes and start fade animations
+        let now = self.clock.now();
         if self.effects.title_fade.enabled {
             for info in &frame_glyphs.window_infos {
                 if info.is_minibuffer || info.buffer_file_name.is_empty() {
@@ -1302,14 +1920,14 @@ impl WgpuRenderer {
                         bounds: info.bounds,
                         old_text,
                         new_text: new_text.clone(),
-                        started: std::time::Instant::now(),
+                        started: now,
                         duration: std::time::Duration::from_millis(self.effects.title_fade.duration_ms as u64),
                     });
                 }
                 self.prev_breadcrumb_text.insert(wid, new_text.clone());
             }
             // Clean up expired fades
-            self.active_title_fades.retain(|f| f.started.elapsed() < f.duration);
+            self.active_title_fades.retain(|f| now.duration_since(f.started) < f.duration);
             if !self.active_title_fades.is_empty() {
                 self.needs_continuous_redraw = true;
             }
@@ -1333,7 +1951,7 @@ impl WgpuRenderer {
 
             if let Some(fade) = active_fade {
                 // Crossfade: render old text fading out, new text fading in
-                let t = (fade.started.elapsed().as_secs_f32() / fade.duration.as_secs_f32()).min(1.0);
+                let t = (now.duration_since(fade.started).as_secs_f32() / fade.duration.as_secs_f32()).min(1.0);
                 // Ease-out quadratic
                 let eased = t * (2.0 - t);
                 let new_alpha = eased;
@@ -1556,10 +2174,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -1648,10 +2263,7 @@ impl WgpuRenderer {
 
         let logical_w = surface_width as f32 / self.scale_factor;
         let logical_h = surface_height as f32 / self.scale_factor;
-        let uniforms = Uniforms {
-            screen_size: [logical_w, logical_h],
-            _padding: [0.0, 0.0],
-        };
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
         self.queue
             .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
@@ -1692,4 +2304,69 @@ impl WgpuRenderer {
         }
         self.queue.submit(Some(encoder.finish()));
     }
+
+    /// Draw the debug overlay over the frame: the background rects of the
+    /// last frame, translucent so overlapping ones show darker, or the text
+    /// rows rebuilt on it, outlined
+    pub fn render_debug_overlay(&self, view: &wgpu::TextureView, surface_width: u32, surface_height: u32) {
+        use crate::core::scene_dump::DebugOverlay;
+        use wgpu::util::DeviceExt;
+
+        if self.effects.debug_overlay.mode == DebugOverlay::None || self.debug_rects.is_empty() {
+            return;
+        }
+        let logical_w = surface_width as f32 / self.scale_factor;
+        let logical_h = surface_height as f32 / self.scale_factor;
+        let uniforms = self.frame_uniforms(logical_w, logical_h);
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let mut rect_vertices: Vec<RectVertex> = Vec::new();
+        if self.effects.debug_overlay.mode == DebugOverlay::Overdraw {
+            let shade = Color::new(1.0, 0.0, 0.0, 0.15).srgb_to_linear();
+            for r in &self.debug_rects {
+                self.add_rect(&mut rect_vertices, r.x, r.y, r.width, r.height, &shade);
+            }
+        } else {
+            let fill = Color::new(1.0, 0.8, 0.0, 0.2).srgb_to_linear();
+            let edge = Color::new(1.0, 0.8, 0.0, 0.9).srgb_to_linear();
+            for r in &self.debug_rects {
+                self.add_rect(&mut rect_vertices, r.x, r.y, r.width, r.height, &fill);
+                self.add_rect(&mut rect_vertices, r.x, r.y, r.width, 1.0, &edge);
+                self.add_rect(&mut rect_vertices, r.x, r.y + r.height - 1.0, r.width, 1.0, &edge);
+                self.add_rect(&mut rect_vertices, r.x, r.y, 1.0, r.height, &edge);
+                self.add_rect(&mut rect_vertices, r.x + r.width - 1.0, r.y, 1.0, r.height, &edge);
+            }
+        }
+
+        let rect_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Debug Overlay Buffer"),
+            contents: bytemuck::cast_slice(&rect_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Debug Overlay Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Debug Overlay Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.rect_pipeline);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            pass.set_vertex_buffer(0, rect_buffer.slice(..));
+            pass.draw(0..rect_vertices.len() as u32, 0..1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
 }
@@ -0,0 +1,154 @@
+//! Per-row cache of text vertices.
+//!
+//! Building a glyph's vertices takes an atlas lookup and several effect
+//! checks per character, redone every frame for text that rarely
+//! changes.  Rows of text are hashed by their glyphs; a row whose hash
+//! matches last frame's reuses the vertices built then, as long as the
+//! glyph atlas has not moved anything since and no effect animates the
+//! row.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use rayon::prelude::*;
+
+use super::super::glyph_atlas::{ComposedGlyphKey, GlyphKey};
+use super::super::vertex::GlyphVertex;
+use crate::core::frame_glyphs::FrameGlyph;
+use crate::core::prepare::worth_parallel;
+
+/// A row of text: overlay flag and the bits of its y position
+pub(super) type RowKey = (bool, u32);
+
+/// Vertices of one row, in the lists the text pass draws from
+#[derive(Debug, Default, Clone)]
+pub(super) struct RowVertices {
+    pub mask: Vec<(GlyphKey, [GlyphVertex; 6])>,
+    pub color: Vec<(GlyphKey, [GlyphVertex; 6])>,
+    pub composed_mask: Vec<(ComposedGlyphKey, [GlyphVertex; 6])>,
+    pub composed_color: Vec<(ComposedGlyphKey, [GlyphVertex; 6])>,
+}
+
+impl RowVertices {
+    /// Area covered by the row's glyphs, None for an empty row
+    pub fn bounds(&self) -> Option<crate::core::types::Rect> {
+        let positions = self.mask.iter().map(|(_, v)| v)
+            .chain(self.color.iter().map(|(_, v)| v))
+            .chain(self.composed_mask.iter().map(|(_, v)| v))
+            .chain(self.composed_color.iter().map(|(_, v)| v))
+            .flatten()
+            .map(|v| v.position);
+        let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for [x, y] in positions {
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+        }
+        (x1 >= x0).then(|| crate::core::types::Rect::new(x0, y0, x1 - x0, y1 - y0))
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct RowCache {
+    /// Atlas layout epoch and scale factor bits the rows were built with
+    built_for: (u64, u32),
+    rows: HashMap<RowKey, (u64, RowVertices)>,
+    /// Rows reused and rebuilt on the last frame
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl RowCache {
+    /// Forget rows built for another atlas layout or scale factor
+    pub fn validate(&mut self, atlas_epoch: u64, scale_factor: f32) {
+        let built_for = (atlas_epoch, scale_factor.to_bits());
+        if self.built_for != built_for {
+            self.rows.clear();
+            self.built_for = built_for;
+        }
+    }
+
+    /// Vertices of `row` if they were built for the same `hash`
+    pub fn get(&self, row: &RowKey, hash: u64) -> Option<&RowVertices> {
+        self.rows.get(row).filter(|(h, _)| *h == hash).map(|(_, v)| v)
+    }
+
+    pub fn insert(&mut self, row: RowKey, hash: u64, vertices: RowVertices) {
+        self.rows.insert(row, (hash, vertices));
+    }
+
+    /// Drop rows of the `overlay` pass not in `seen`
+    pub fn retain(&mut self, overlay: bool, seen: &HashMap<RowKey, u64>) {
+        self.rows.retain(|k, _| k.0 != overlay || seen.contains_key(k));
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+}
+
+pub(super) fn row_key(y: f32, overlay: bool) -> RowKey {
+    (overlay, y.to_bits())
+}
+
+/// Hash the char glyphs of every row of the `overlay` pass, rows in
+/// parallel on large frames
+pub(super) fn hash_rows(glyphs: &[FrameGlyph], overlay: bool) -> HashMap<RowKey, u64> {
+    let mut rows: HashMap<RowKey, Vec<&FrameGlyph>> = HashMap::new();
+    let mut count = 0;
+    for glyph in glyphs {
+        if let FrameGlyph::Char { y, is_overlay, .. } = glyph {
+            if *is_overlay == overlay {
+                rows.entry(row_key(*y, overlay)).or_default().push(glyph);
+                count += 1;
+            }
+        }
+    }
+    if worth_parallel(count) {
+        rows.into_par_iter().map(|(k, row)| (k, hash_row(&row))).collect()
+    } else {
+        rows.into_iter().map(|(k, row)| (k, hash_row(&row))).collect()
+    }
+}
+
+fn hash_row(row: &[&FrameGlyph]) -> u64 {
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    for glyph in row {
+        if let FrameGlyph::Char {
+            char, composed, x, width, height, ascent, fg, face_id, font_size, ..
+        } = glyph {
+            char.hash(&mut h);
+            composed.hash(&mut h);
+            face_id.hash(&mut h);
+            for v in [*x, *width, *height, *ascent, *font_size, fg.r, fg.g, fg.b, fg.a] {
+                v.to_bits().hash(&mut h);
+            }
+        }
+    }
+    h.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_glyphs::FrameGlyphBuffer;
+    use crate::core::types::Color;
+
+    #[test]
+    fn test_hash_rows() {
+        let mut frame = FrameGlyphBuffer::new();
+        frame.set_face(1, Color::WHITE, None, false, false, 0, None, 0, None, 0, None);
+        frame.add_char('a', 0.0, 0.0, 8.0, 16.0, 12.0, false);
+        frame.add_char('b', 0.0, 16.0, 8.0, 16.0, 12.0, false);
+        frame.add_char('m', 0.0, 32.0, 8.0, 16.0, 12.0, true);
+        let before = hash_rows(&frame.glyphs, false);
+        assert_eq!(before.len(), 2);
+        assert_eq!(hash_rows(&frame.glyphs, true).len(), 1);
+
+        // Changing one row changes only its hash
+        frame.glyphs.remove(1);
+        frame.add_char('c', 0.0, 16.0, 8.0, 16.0, 12.0, false);
+        let after = hash_rows(&frame.glyphs, false);
+        let (row0, row1) = (row_key(0.0, false), row_key(16.0, false));
+        assert_eq!(before[&row0], after[&row0]);
+        assert_ne!(before[&row1], after[&row1]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Shader effect methods for WgpuRenderer.
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use super::WgpuRenderer;
+use super::super::vertex::ShaderEffectUniforms;
+use crate::core::error_report::{self, ErrorKind};
+use crate::core::shader_effect;
+use crate::core::types::Rect;
+
+impl WgpuRenderer {
+    /// Build the pipeline of the effect `name` from `source`.  Failures
+    /// are caught by an error scope and reported instead of reaching the
+    /// device's error handler.
+    fn build_shader_effect(&self, name: &str, source: &str) -> Option<wgpu::RenderPipeline> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader Effect"),
+            source: wgpu::ShaderSource::Wgsl(shader_effect::wrap(source).into()),
+        });
+        let layout = self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shader Effect Pipeline Layout"),
+            bind_group_layouts: &[self.image_cache.bind_group_layout(), &self.shader_effect_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shader Effect Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.surface_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        match pollster::block_on(self.device.pop_error_scope()) {
+            None => Some(pipeline),
+            Some(error) => {
+                error_report::error(ErrorKind::Shader, None, format!("shader effect {}: {}", name, error));
+                None
+            }
+        }
+    }
+
+    /// Drop the pipeline of the effect `name`, so it is built again from
+    /// its current source
+    pub fn forget_shader_effect(&mut self, name: &str) {
+        self.shader_effects.remove(name);
+    }
+
+    /// Draw `effects` (target rectangle, effect name, parameters) over
+    /// `target` in order.  Pipelines are built from `sources`, or the
+    /// built-in effects, the first time an effect is drawn.  Effects that
+    /// are unknown or fail to compile are skipped.  Every effect reads the
+    /// frame as it was before any of them.
+    ///
+    /// Returns whether a drawn effect changes over time.
+    pub fn render_shader_effects(
+        &mut self,
+        target: &wgpu::Texture,
+        view: &wgpu::TextureView,
+        effects: &[(Rect, &str, [f32; 4])],
+        sources: &HashMap<String, String>,
+        strength: f32,
+    ) -> bool {
+        // The frame is read from a copy of the target
+        if !target.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return false;
+        }
+        for (_, name, _) in effects {
+            if self.shader_effects.contains_key(*name) {
+                continue;
+            }
+            let Some(source) = sources.get(*name).map(String::as_str).or_else(|| shader_effect::builtin(name)) else {
+                continue;
+            };
+            let built = self.build_shader_effect(name, source).map(|p| (p, shader_effect::is_animated(source)));
+            self.shader_effects.insert(name.to_string(), built);
+        }
+
+        self.ensure_frame_copy(target);
+        let screen_size = [target.width() as f32 / self.scale_factor, target.height() as f32 / self.scale_factor];
+        let frame = Rect::new(0.0, 0.0, screen_size[0], screen_size[1]);
+        let time = (self.clock.now().duration_since(self.shader_effect_epoch).as_secs_f64() % 3600.0) as f32;
+        let mut animated = false;
+        let mut draws = Vec::new();
+        for (rect, name, params) in effects {
+            let (Some(Some((pipeline, animates))), Some(region)) =
+                (self.shader_effects.get(*name), rect.intersection(&frame))
+            else {
+                continue;
+            };
+            if region.width < 1.0 || region.height < 1.0 {
+                continue;
+            }
+            animated |= *animates;
+            let uniforms = ShaderEffectUniforms {
+                screen_size,
+                time,
+                strength: strength.clamp(0.0, 1.0),
+                region: [region.x, region.y, region.width, region.height],
+                params: *params,
+            };
+            let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Shader Effect Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Shader Effect Bind Group"),
+                layout: &self.shader_effect_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            draws.push((pipeline, bind_group));
+        }
+        if draws.is_empty() {
+            return false;
+        }
+
+        let (copy, frame_bind_group) = self.frame_copy.as_ref().expect("created above");
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Shader Effect Encoder"),
+        });
+        encoder.copy_texture_to_texture(target.as_image_copy(), copy.as_image_copy(), target.size());
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shader Effect Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_bind_group(0, frame_bind_group, &[]);
+            for (pipeline, bind_group) in &draws {
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(1, bind_group, &[]);
+                render_pass.draw(0..6, 0..1);
+            }
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+        animated
+    }
+}
@@ -381,6 +381,15 @@ impl WgpuRenderer {
                     eased_t, elapsed_secs, direction, bounds, surface_width, surface_height,
                 );
             }
+
+            ScrollEffect::SlideLeft | ScrollEffect::SlideRight | ScrollEffect::Push => {
+                let x_dir = if effect == ScrollEffect::SlideRight { 1.0 } else { -1.0 };
+                self.render_scroll_slide_horizontal(
+                    surface_view, old_bind_group, new_bind_group,
+                    eased_t, x_dir, effect == ScrollEffect::Push,
+                    bounds, surface_width, surface_height,
+                );
+            }
         }
     }
 
@@ -428,7 +437,23 @@ impl WgpuRenderer {
         new_vertices: &[GlyphVertex],
         sx: u32, sy: u32, sw: u32, sh: u32,
     ) {
-        use wgpu::util::DeviceExt;
+        self.submit_scroll_two_quad_pass_with(
+            &self.image_pipeline, surface_view, old_bind_group, new_bind_group,
+            old_vertices, new_vertices, sx, sy, sw, sh,
+        );
+    }
+
+    /// Helper: like `submit_scroll_two_quad_pass`, drawing with `pipeline`.
+    fn submit_scroll_two_quad_pass_with(
+        &self,
+        pipeline: &wgpu::RenderPipeline,
+        surface_view: &wgpu::TextureView,
+        old_bind_group: &wgpu::BindGroup,
+        new_bind_group: &wgpu::BindGroup,
+        old_vertices: &[GlyphVertex],
+        new_vertices: &[GlyphVertex],
+        sx: u32, sy: u32, sw: u32, sh: u32,
+    ) {
         let old_vb = self.create_scroll_vb(old_vertices);
         let new_vb = self.create_scroll_vb(new_vertices);
 
@@ -451,7 +476,7 @@ impl WgpuRenderer {
                 occlusion_query_set: None,
             });
             rp.set_scissor_rect(sx, sy, sw, sh);
-            rp.set_pipeline(&self.image_pipeline);
+            rp.set_pipeline(pipeline);
             rp.set_bind_group(0, &self.uniform_bind_group, &[]);
 
             rp.set_bind_group(1, old_bind_group, &[]);
@@ -465,6 +490,54 @@ impl WgpuRenderer {
         self.queue.submit(std::iter::once(encoder.finish()));
     }
 
+    /// Slide scroll moving the text `distance` logical pixels up (negative:
+    /// down), with both snapshots streaked `blur` pixels along the motion
+    /// (velocity motion blur, 0 for none).
+    pub fn render_scroll_shift(
+        &self,
+        surface_view: &wgpu::TextureView,
+        old_bind_group: &wgpu::BindGroup,
+        new_bind_group: &wgpu::BindGroup,
+        eased_t: f32,
+        distance: f32,
+        bounds: &crate::core::types::Rect,
+        blur: f32,
+        surface_width: u32,
+        surface_height: u32,
+    ) {
+        let (sx, sy, sw, sh, _w, h, uv_l, uv_t, uv_r, uv_b) =
+            match self.scroll_scissor_and_uv(bounds, surface_width, surface_height) {
+                Some(v) => v,
+                None => return,
+            };
+        // The motion blur shader reads the streak length from the red channel
+        let c = [blur / h, 1.0, 1.0, 1.0];
+
+        let make_quad = |y_off: f32| -> [GlyphVertex; 6] {
+            let x0 = bounds.x;
+            let x1 = bounds.x + bounds.width;
+            let y0 = bounds.y + y_off;
+            let y1 = bounds.y + bounds.height + y_off;
+            [
+                GlyphVertex { position: [x0, y0], tex_coords: [uv_l, uv_t], color: c },
+                GlyphVertex { position: [x1, y0], tex_coords: [uv_r, uv_t], color: c },
+                GlyphVertex { position: [x1, y1], tex_coords: [uv_r, uv_b], color: c },
+                GlyphVertex { position: [x0, y0], tex_coords: [uv_l, uv_t], color: c },
+                GlyphVertex { position: [x1, y1], tex_coords: [uv_r, uv_b], color: c },
+                GlyphVertex { position: [x0, y1], tex_coords: [uv_l, uv_b], color: c },
+            ]
+        };
+
+        let (old_off, new_off) = crate::core::scroll_animation::scroll_shift_offsets(eased_t, distance);
+        let old_verts = make_quad(old_off);
+        let new_verts = make_quad(new_off);
+        let pipeline = if blur > 0.0 { &self.motion_blur_pipeline } else { &self.image_pipeline };
+        self.submit_scroll_two_quad_pass_with(
+            pipeline, surface_view, old_bind_group, new_bind_group,
+            &old_verts, &new_verts, sx, sy, sw, sh,
+        );
+    }
+
     /// Crossfade scroll: alpha blend old → new within content bounds.
     fn render_scroll_crossfade(
         &self,
@@ -509,6 +582,62 @@ impl WgpuRenderer {
         );
     }
 
+    /// Text scale change: both snapshots are scaled from the window's top
+    /// left corner so text grows or shrinks continuously from the old size
+    /// (`ratio` is new over old) while the old snapshot fades out.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_text_scale_transition(
+        &self,
+        surface_view: &wgpu::TextureView,
+        old_bind_group: &wgpu::BindGroup,
+        new_bind_group: &wgpu::BindGroup,
+        t: f32,
+        ratio: f32,
+        bounds: &crate::core::types::Rect,
+        surface_width: u32,
+        surface_height: u32,
+    ) {
+        let (sx, sy, sw, sh, _w, _h, uv_l, uv_t, uv_r, uv_b) =
+            match self.scroll_scissor_and_uv(bounds, surface_width, surface_height) {
+                Some(v) => v,
+                None => return,
+            };
+        let (old_scale, new_scale) = crate::core::scroll_animation::text_scale_factors(t, ratio);
+
+        let make_quad = |scale: f32, alpha: f32| -> [GlyphVertex; 6] {
+            let x0 = bounds.x;
+            let y0 = bounds.y;
+            let x1 = bounds.x + bounds.width * scale;
+            let y1 = bounds.y + bounds.height * scale;
+            [
+                GlyphVertex { position: [x0, y0], tex_coords: [uv_l, uv_t], color: [1.0, 1.0, 1.0, alpha] },
+                GlyphVertex { position: [x1, y0], tex_coords: [uv_r, uv_t], color: [1.0, 1.0, 1.0, alpha] },
+                GlyphVertex { position: [x1, y1], tex_coords: [uv_r, uv_b], color: [1.0, 1.0, 1.0, alpha] },
+                GlyphVertex { position: [x0, y0], tex_coords: [uv_l, uv_t], color: [1.0, 1.0, 1.0, alpha] },
+                GlyphVertex { position: [x1, y1], tex_coords: [uv_r, uv_b], color: [1.0, 1.0, 1.0, alpha] },
+                GlyphVertex { position: [x0, y1], tex_coords: [uv_l, uv_b], color: [1.0, 1.0, 1.0, alpha] },
+            ]
+        };
+
+        // The larger snapshot covers the whole window and is drawn opaque;
+        // the smaller one crossfades on top of it
+        if ratio >= 1.0 {
+            let old_verts = make_quad(old_scale, 1.0);
+            let new_verts = make_quad(new_scale, t);
+            self.submit_scroll_two_quad_pass(
+                surface_view, old_bind_group, new_bind_group,
+                &old_verts, &new_verts, sx, sy, sw, sh,
+            );
+        } else {
+            let new_verts = make_quad(new_scale, 1.0);
+            let old_verts = make_quad(old_scale, 1.0 - t);
+            self.submit_scroll_two_quad_pass(
+                surface_view, new_bind_group, old_bind_group,
+                &new_verts, &old_verts, sx, sy, sw, sh,
+            );
+        }
+    }
+
     /// ScaleZoom: old shrinks to 95% and fades; new zooms from 95% to 100%.
     fn render_scroll_scale_zoom(
         &self,
@@ -745,6 +874,52 @@ impl WgpuRenderer {
         );
     }
 
+    /// Horizontal slide: old and new content move sideways by the window
+    /// width, or for a push the new content covers the still old one.
+    fn render_scroll_slide_horizontal(
+        &self,
+        surface_view: &wgpu::TextureView,
+        old_bind_group: &wgpu::BindGroup,
+        new_bind_group: &wgpu::BindGroup,
+        t: f32,
+        x_dir: f32,
+        push: bool,
+        bounds: &crate::core::types::Rect,
+        surface_width: u32,
+        surface_height: u32,
+    ) {
+        let (sx, sy, sw, sh, _w, _h, uv_l, uv_t, uv_r, uv_b) =
+            match self.scroll_scissor_and_uv(bounds, surface_width, surface_height) {
+                Some(v) => v,
+                None => return,
+            };
+
+        let (old_x, new_x) =
+            crate::core::scroll_animation::slide_x_offsets(t, bounds.width, x_dir, push);
+
+        let make_quad = |x_off: f32| -> [GlyphVertex; 6] {
+            let x0 = bounds.x + x_off;
+            let y0 = bounds.y;
+            let x1 = bounds.x + bounds.width + x_off;
+            let y1 = bounds.y + bounds.height;
+            [
+                GlyphVertex { position: [x0, y0], tex_coords: [uv_l, uv_t], color: [1.0, 1.0, 1.0, 1.0] },
+                GlyphVertex { position: [x1, y0], tex_coords: [uv_r, uv_t], color: [1.0, 1.0, 1.0, 1.0] },
+                GlyphVertex { position: [x1, y1], tex_coords: [uv_r, uv_b], color: [1.0, 1.0, 1.0, 1.0] },
+                GlyphVertex { position: [x0, y0], tex_coords: [uv_l, uv_t], color: [1.0, 1.0, 1.0, 1.0] },
+                GlyphVertex { position: [x1, y1], tex_coords: [uv_r, uv_b], color: [1.0, 1.0, 1.0, 1.0] },
+                GlyphVertex { position: [x0, y1], tex_coords: [uv_l, uv_b], color: [1.0, 1.0, 1.0, 1.0] },
+            ]
+        };
+
+        let old_verts = make_quad(old_x);
+        let new_verts = make_quad(new_x);
+        self.submit_scroll_two_quad_pass(
+            surface_view, old_bind_group, new_bind_group,
+            &old_verts, &new_verts, sx, sy, sw, sh,
+        );
+    }
+
     /// Tilt: subtle perspective tilt during scroll.
     fn render_scroll_tilt(
         &self,
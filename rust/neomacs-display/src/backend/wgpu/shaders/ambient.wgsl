@@ -0,0 +1,119 @@
+// Ambient background layer: procedural gradient noise, snow or matrix
+// rain, drawn over window backgrounds and under text.  Everything is a
+// function of time, so the layer keeps no state between frames.
+
+struct Ambient {
+    // rgb, opacity
+    color: vec4<f32>,
+    screen_size: vec2<f32>,
+    time: f32,
+    // 1 = gradient, 2 = snow, 3 = matrix rain
+    kind: u32,
+    density: f32,
+    speed: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> ambient: Ambient;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Logical pixels from the top left corner
+    @location(0) pixel: vec2<f32>,
+}
+
+// One triangle covering the frame
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.pixel = uv * ambient.screen_size;
+    return out;
+}
+
+fn hash(p: vec2<f32>) -> f32 {
+    let q = fract(p * vec2<f32>(123.34, 456.21));
+    let r = q + dot(q, q + 45.32);
+    return fract(r.x * r.y);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = hash(i);
+    let b = hash(i + vec2<f32>(1.0, 0.0));
+    let c = hash(i + vec2<f32>(0.0, 1.0));
+    let d = hash(i + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn gradient(p: vec2<f32>) -> f32 {
+    let t = ambient.time * ambient.speed * 0.03;
+    let scale = 1.0 / (200.0 + 400.0 * (1.0 - ambient.density));
+    var q = p * scale + vec2<f32>(t, t * 0.7);
+    var sum = 0.0;
+    var amp = 0.5;
+    for (var i = 0; i < 4; i++) {
+        sum += value_noise(q) * amp;
+        q = q * 2.03 + vec2<f32>(t * 0.5, -t * 0.3);
+        amp *= 0.5;
+    }
+    return sum;
+}
+
+fn snow(p: vec2<f32>) -> f32 {
+    var alpha = 0.0;
+    for (var layer = 0; layer < 3; layer++) {
+        let depth = f32(layer);
+        let cell = 70.0 - depth * 18.0;
+        let fall = ambient.time * ambient.speed * (18.0 + depth * 14.0);
+        var q = p + vec2<f32>(0.0, -fall);
+        q.x += sin(ambient.time * 0.6 + q.y * 0.015 + depth) * 8.0;
+        let id = floor(q / cell) + vec2<f32>(depth * 17.0, depth * 31.0);
+        if hash(id) > ambient.density {
+            continue;
+        }
+        let center = (floor(q / cell) + 0.2 + 0.6 * vec2<f32>(hash(id + 3.1), hash(id + 7.7))) * cell;
+        let radius = 1.0 + depth * 0.8;
+        let d = distance(q, center);
+        alpha += (1.0 - smoothstep(radius - 0.75, radius + 0.75, d)) * (0.5 + depth * 0.25);
+    }
+    return min(alpha, 1.0);
+}
+
+fn matrix_rain(p: vec2<f32>) -> f32 {
+    let size = 14.0;
+    let column = floor(p.x / size);
+    if hash(vec2<f32>(column, 7.0)) > ambient.density {
+        return 0.0;
+    }
+    let h = hash(vec2<f32>(column, 1.0));
+    let trail = ambient.screen_size.y * (0.2 + 0.3 * h);
+    let fall = ambient.time * ambient.speed * (60.0 + 90.0 * h);
+    let head = (fall + h * 1000.0) % (ambient.screen_size.y + trail);
+    let behind = head - p.y;
+    if behind < 0.0 || behind > trail {
+        return 0.0;
+    }
+    // Blocks flicker like changing glyphs
+    let row = floor(p.y / size);
+    let cell = fract(p / size);
+    let sub = floor(cell * 3.0);
+    let glyph = hash(vec2<f32>(column * 3.0 + sub.x, row * 3.0 + sub.y + floor(ambient.time * 6.0 * h)));
+    let inside = step(0.15, cell.x) * step(cell.x, 0.85) * step(0.1, cell.y) * step(cell.y, 0.9);
+    return step(0.45, glyph) * inside * (1.0 - behind / trail);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var alpha = 0.0;
+    switch ambient.kind {
+        case 1u: { alpha = gradient(in.pixel); }
+        case 2u: { alpha = snow(in.pixel); }
+        case 3u: { alpha = matrix_rain(in.pixel); }
+        default: {}
+    }
+    return vec4<f32>(ambient.color.rgb, alpha * ambient.color.a);
+}
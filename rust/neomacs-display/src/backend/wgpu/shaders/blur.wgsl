@@ -0,0 +1,41 @@
+// One direction of a separable Gaussian blur.
+//
+// Reads `src` with textureLoad and writes `dst`, which has the same size.
+// Run once with direction (1, 0) and once with (0, 1) for a full blur.
+// Taps beyond the edge repeat the edge texel, so blurred regions do not
+// darken towards their borders.
+
+struct Params {
+    direction: vec2<i32>,
+    radius: i32,
+    sigma: f32,
+}
+
+@group(0) @binding(0)
+var src: texture_2d<f32>;
+
+@group(0) @binding(1)
+var dst: texture_storage_2d<rgba16float, write>;
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(dst));
+    let p = vec2<i32>(id.xy);
+    if (p.x >= size.x || p.y >= size.y) {
+        return;
+    }
+
+    let denom = 2.0 * params.sigma * params.sigma;
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -params.radius; i <= params.radius; i = i + 1) {
+        let q = clamp(p + params.direction * i, vec2<i32>(0), size - vec2<i32>(1));
+        let w = exp(-f32(i * i) / denom);
+        sum = sum + textureLoad(src, q, 0) * w;
+        total = total + w;
+    }
+    textureStore(dst, p, sum / total);
+}
@@ -0,0 +1,91 @@
+// Whole-frame color filter: copies a frame texture through a 3x3 color
+// matrix in linear RGB (night light, grayscale, color vision modes), and
+// through the smart invert outside the regions it keeps
+
+struct Uniforms {
+    screen_size: vec2<f32>,
+    text_gamma: vec2<f32>,
+    antialias: f32,
+    // Rows of the color matrix, w unused
+    color_matrix: array<vec4<f32>, 3>,
+    // x: smart invert on, y: number of regions kept
+    invert: vec4<f32>,
+    // Regions the invert leaves alone, as (x, y, right, bottom)
+    invert_keep: array<vec4<f32>, 16>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var t_frame: texture_2d<f32>;
+@group(1) @binding(1)
+var s_frame: sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let x = (in.position.x / uniforms.screen_size.x) * 2.0 - 1.0;
+    let y = 1.0 - (in.position.y / uniforms.screen_size.y) * 2.0;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    out.tex_coords = in.tex_coords;
+    return out;
+}
+
+fn srgb_encode(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+fn srgb_decode(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+fn kept(pos: vec2<f32>) -> bool {
+    let count = u32(uniforms.invert.y);
+    for (var i = 0u; i < count; i++) {
+        let r = uniforms.invert_keep[i];
+        if (pos.x >= r.x && pos.y >= r.y && pos.x < r.z && pos.y < r.w) {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Inverts lightness in the encoded space, where it looks even, then turns
+// the hue back half a circle (CSS hue-rotate(180deg)) so red stays red.
+// The hue matrix's rows sum to 1, so H * (1 - c) is 1 - H * c.
+fn smart_invert(c: vec3<f32>) -> vec3<f32> {
+    let e = srgb_encode(clamp(c, vec3<f32>(0.0), vec3<f32>(1.0)));
+    let turned = vec3<f32>(
+        dot(vec3<f32>(-0.574, 1.430, 0.144), e),
+        dot(vec3<f32>(0.426, 0.430, 0.144), e),
+        dot(vec3<f32>(0.426, 1.430, -0.856), e),
+    );
+    return srgb_decode(clamp(1.0 - turned, vec3<f32>(0.0), vec3<f32>(1.0)));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let c = textureSample(t_frame, s_frame, in.tex_coords);
+    var src = c.rgb;
+    if (uniforms.invert.x > 0.5 && !kept(in.tex_coords * uniforms.screen_size)) {
+        src = smart_invert(src);
+    }
+    let rgb = vec3<f32>(
+        dot(uniforms.color_matrix[0].xyz, src),
+        dot(uniforms.color_matrix[1].xyz, src),
+        dot(uniforms.color_matrix[2].xyz, src),
+    );
+    return vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), c.a);
+}
@@ -14,6 +14,8 @@ struct VertexOutput {
 
 struct Uniforms {
     screen_size: vec2<f32>,
+    // [gamma, contrast] coverage correction for dark text (1.0, 0.0 = off)
+    text_gamma: vec2<f32>,
 }
 
 @group(0) @binding(0)
@@ -35,8 +37,28 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     return out;
 }
 
+// Blending happens in linear light (sRGB target), which thins dark text on
+// light backgrounds.  Boost coverage in proportion to text darkness.
+fn adjust_coverage(coverage: f32, color: vec3<f32>) -> f32 {
+    let gamma = max(uniforms.text_gamma.x, 0.01);
+    let darkness = 1.0 - clamp(dot(color, vec3<f32>(0.2126, 0.7152, 0.0722)), 0.0, 1.0);
+    let adjusted = pow(coverage, 1.0 / gamma);
+    return clamp(mix(coverage, adjusted, darkness) * (1.0 + uniforms.text_gamma.y), 0.0, 1.0);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let alpha = textureSample(glyph_texture, glyph_sampler, in.tex_coords).r;
+    let coverage = textureSample(glyph_texture, glyph_sampler, in.tex_coords).r;
+    let alpha = adjust_coverage(coverage, in.color.rgb);
+    return vec4<f32>(in.color.rgb, in.color.a * alpha);
+}
+
+// Signed distance field glyphs: 0.5 marks the outline.  Smoothing over one
+// device pixel keeps edges sharp however far the glyph is scaled.
+@fragment
+fn fs_sdf(in: VertexOutput) -> @location(0) vec4<f32> {
+    let d = textureSample(glyph_texture, glyph_sampler, in.tex_coords).r - 0.5;
+    let coverage = clamp(d / max(fwidth(d), 1e-4) + 0.5, 0.0, 1.0);
+    let alpha = adjust_coverage(coverage, in.color.rgb);
     return vec4<f32>(in.color.rgb, in.color.a * alpha);
 }
@@ -44,6 +44,29 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return tex_color * in.color;
 }
 
+// Copy of the frame with its saturation scaled by the vertex color's red
+// channel (0 = gray, 1 = unchanged)
+@fragment
+fn fs_desaturate(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tex_color = textureSample(t_image, s_image, in.tex_coords);
+    let luma = dot(tex_color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return vec4<f32>(mix(vec3<f32>(luma), tex_color.rgb, in.color.r), 1.0);
+}
+
+// Streak along the texture's vertical axis for scroll motion blur.  The
+// vertex color's red channel is the streak length in texture coordinates,
+// alpha the opacity.
+@fragment
+fn fs_motion_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    let taps = 9;
+    var sum = vec4<f32>(0.0);
+    for (var i = 0; i < taps; i++) {
+        let offset = (f32(i) / f32(taps - 1) - 0.5) * in.color.r;
+        sum += textureSample(t_image, s_image, in.tex_coords + vec2<f32>(0.0, offset));
+    }
+    return vec4<f32>(sum.rgb / f32(taps), sum.a / f32(taps) * in.color.a);
+}
+
 @fragment
 fn fs_main_opaque(in: VertexOutput) -> @location(0) vec4<f32> {
     // Sample from texture, force alpha=1.0 (for XRGB/BGRX DMA-BUF textures
@@ -51,3 +74,36 @@ fn fs_main_opaque(in: VertexOutput) -> @location(0) vec4<f32> {
     let tex_color = textureSample(t_image, s_image, in.tex_coords);
     return vec4<f32>(tex_color.rgb * in.color.rgb, in.color.a);
 }
+
+// Color correction of a video frame, as rows of an affine map of
+// gamma-encoded RGB: corrected = dot(row, vec4(rgb, 1.0))
+struct VideoColor {
+    rows: array<vec4<f32>, 3>,
+}
+
+@group(2) @binding(0)
+var<uniform> video_color: VideoColor;
+
+fn srgb_encode(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+fn srgb_decode(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+// Video frame whose range or matrix the decoder got wrong.  The texture
+// is sRGB so sampling linearizes; the correction works on the encoded
+// values the converter produced.
+@fragment
+fn fs_video(in: VertexOutput) -> @location(0) vec4<f32> {
+    let tex_color = textureSample(t_image, s_image, in.tex_coords);
+    let e = vec4<f32>(srgb_encode(tex_color.rgb), 1.0);
+    let corrected = vec3<f32>(
+        dot(video_color.rows[0], e),
+        dot(video_color.rows[1], e),
+        dot(video_color.rows[2], e),
+    );
+    let rgb = srgb_decode(clamp(corrected, vec3<f32>(0.0), vec3<f32>(1.0)));
+    return vec4<f32>(rgb, tex_color.a) * in.color;
+}
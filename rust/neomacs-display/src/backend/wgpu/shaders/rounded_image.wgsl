@@ -0,0 +1,73 @@
+// Textured quad with SDF-rounded corners and opacity.
+//
+// Used for floating layers (images, WebKit views).  Vertices use the
+// RoundedRectVertex layout: texture coordinates are derived from the
+// fragment position inside [rect_min, rect_max], color.a is the layer
+// opacity, and params = [texture_alpha_weight, corner_radius].  A weight
+// of 0 ignores texture alpha (XRGB/BGRX WebKit buffers), 1 honours it.
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) rect_min: vec2<f32>,
+    @location(3) rect_max: vec2<f32>,
+    @location(4) params: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) rect_min: vec2<f32>,
+    @location(2) rect_max: vec2<f32>,
+    @location(3) params: vec2<f32>,
+    @location(4) frag_pos: vec2<f32>,
+}
+
+struct Uniforms {
+    screen_size: vec2<f32>,
+    text_gamma: vec2<f32>,
+    antialias: f32,         // smoothed edge width in device pixels
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(1) @binding(0)
+var t_image: texture_2d<f32>;
+@group(1) @binding(1)
+var s_image: sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let x = (in.position.x / uniforms.screen_size.x) * 2.0 - 1.0;
+    let y = 1.0 - (in.position.y / uniforms.screen_size.y) * 2.0;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    out.color = in.color;
+    out.rect_min = in.rect_min;
+    out.rect_max = in.rect_max;
+    out.params = in.params;
+    out.frag_pos = in.position;
+    return out;
+}
+
+fn sd_rounded_box(p: vec2<f32>, b: vec2<f32>, r: f32) -> f32 {
+    let q = abs(p) - b + vec2<f32>(r);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = max(in.rect_max - in.rect_min, vec2<f32>(1.0));
+    let uv = clamp((in.frag_pos - in.rect_min) / size, vec2<f32>(0.0), vec2<f32>(1.0));
+    let tex = textureSample(t_image, s_image, uv);
+
+    let half_size = size * 0.5;
+    let radius = min(in.params.y, min(half_size.x, half_size.y));
+    let d = sd_rounded_box(in.frag_pos - (in.rect_min + half_size), half_size, radius);
+    let aa = max(uniforms.antialias * fwidth(d), 1e-4) * 0.5;
+    let mask = 1.0 - smoothstep(-aa, aa, d);
+
+    let tex_alpha = mix(1.0, tex.a, in.params.x);
+    return vec4<f32>(tex.rgb * in.color.rgb, tex_alpha * in.color.a * mask);
+}
@@ -1,7 +1,8 @@
 // SDF-based rounded rectangle border shader.
 //
 // Renders anti-aliased rounded rectangle outlines using a signed distance
-// field computed per-fragment.  Each quad carries the logical box bounds,
+// field computed per-fragment.  A rect whose corner radius is half its
+// size is a circle, which draws rings.  Each quad carries the logical box bounds,
 // border width, and corner radius as vertex attributes so the fragment
 // shader can evaluate the SDF without any extra textures or buffers.
 
@@ -24,6 +25,8 @@ struct VertexOutput {
 
 struct Uniforms {
     screen_size: vec2<f32>,
+    text_gamma: vec2<f32>,
+    antialias: f32,         // smoothed edge width in device pixels
 }
 
 @group(0) @binding(0)
@@ -74,14 +77,17 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let d_inner = sd_rounded_box(pos - center, half_size - vec2<f32>(border_width), inner_radius);
 
     // Anti-aliased alpha: 1 inside outer, 0 outside; subtract inner hole.
-    let outer_alpha = 1.0 - smoothstep(-0.5, 0.5, d_outer);
+    // fwidth gives the logical size of a device pixel, so the smoothed
+    // band has the same device width at any scale factor.
+    let aa = max(uniforms.antialias * fwidth(d_outer), 1e-4) * 0.5;
+    let outer_alpha = 1.0 - smoothstep(-aa, aa, d_outer);
 
     // When border_width <= 0, render as filled rounded rect (no inner cutout).
     if (border_width <= 0.0) {
         return vec4<f32>(in.color.rgb, in.color.a * outer_alpha);
     }
 
-    let inner_alpha = 1.0 - smoothstep(-0.5, 0.5, d_inner);
+    let inner_alpha = 1.0 - smoothstep(-aa, aa, d_inner);
     let border_alpha = outer_alpha - inner_alpha;
 
     return vec4<f32>(in.color.rgb, in.color.a * border_alpha);
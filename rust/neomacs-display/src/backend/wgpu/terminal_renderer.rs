@@ -0,0 +1,259 @@
+//! Drawing terminal grids with the frame glyph pipeline.
+//!
+//! A terminal snapshot (`TerminalContent`) becomes background stretches,
+//! character glyphs and a cursor in the frame's glyph list, so cells are
+//! rasterized through the shared glyph atlas and batched with the rest of
+//! the frame.  Cell attributes are resolved here: inverse video, dim and
+//! hidden text, double-width characters, the underline styles and colors
+//! of SGR 4:x and 58, and the cursor shape the program asked for.
+
+use alacritty_terminal::term::cell::Flags as CellFlags;
+use alacritty_terminal::vte::ansi::CursorShape;
+
+use rayon::prelude::*;
+
+use crate::core::frame_glyphs::FrameGlyph;
+use crate::core::prepare::worth_parallel;
+use crate::core::types::{Color, Rect};
+use crate::terminal::content::{RenderCell, TerminalContent};
+use crate::terminal::CellSize;
+
+/// How much of the foreground is kept for dim (SGR 2) text
+const DIM_FACTOR: f32 = 0.66;
+
+/// Rows of a large grid converted by one task of the rayon pool
+const ROWS_PER_BAND: usize = 16;
+
+/// Puts terminal cells into a frame glyph list
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalRenderer {
+    pub cell: CellSize,
+    /// Face whose font draws the characters
+    pub face_id: u32,
+    /// Draw in the overlay layer, above the frame's text
+    pub is_overlay: bool,
+    /// Multiplied into every color's alpha
+    pub opacity: f32,
+    /// Cells only partly inside are left out
+    pub clip: Option<Rect>,
+}
+
+/// Glyph underline style of a cell, as `FrameGlyph::Char` numbers them
+fn underline_style(flags: CellFlags) -> u8 {
+    if flags.contains(CellFlags::UNDERCURL) {
+        2
+    } else if flags.contains(CellFlags::DOUBLE_UNDERLINE) {
+        3
+    } else if flags.contains(CellFlags::DOTTED_UNDERLINE) {
+        4
+    } else if flags.contains(CellFlags::DASHED_UNDERLINE) {
+        5
+    } else if flags.contains(CellFlags::UNDERLINE) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Foreground and background a cell is drawn with
+fn cell_colors(cell: &RenderCell) -> (Color, Color) {
+    let (mut fg, bg) = if cell.flags.contains(CellFlags::INVERSE) {
+        (cell.bg, cell.fg)
+    } else {
+        (cell.fg, cell.bg)
+    };
+    if cell.flags.contains(CellFlags::DIM) {
+        fg = Color::new(
+            fg.r * DIM_FACTOR + bg.r * (1.0 - DIM_FACTOR),
+            fg.g * DIM_FACTOR + bg.g * (1.0 - DIM_FACTOR),
+            fg.b * DIM_FACTOR + bg.b * (1.0 - DIM_FACTOR),
+            fg.a,
+        );
+    }
+    (fg, bg)
+}
+
+impl TerminalRenderer {
+    fn visible(&self, x: f32, y: f32, width: f32) -> bool {
+        self.clip.is_none_or(|c| {
+            x >= c.x - 0.5 && y >= c.y - 0.5
+                && x + width <= c.right() + 0.5 && y + self.cell.height <= c.bottom() + 0.5
+        })
+    }
+
+    fn faded(&self, mut color: Color) -> Color {
+        color.a *= self.opacity;
+        color
+    }
+
+    /// Push the glyphs of one cell of `content`
+    fn push_cell(&self, content: &TerminalContent, cell: &RenderCell, x: f32, y: f32, out: &mut Vec<FrameGlyph>) {
+        let CellSize { width: cell_w, height: cell_h, ascent, font_size } = self.cell;
+        let cursor = &content.cursor;
+        let block_cursor = cursor.visible && cursor.shape == CursorShape::Block;
+
+        let cx = x + cell.col as f32 * cell_w;
+        let cy = y + cell.row as f32 * cell_h;
+        let wide = cell.flags.contains(CellFlags::WIDE_CHAR);
+        let width = if wide { 2.0 * cell_w } else { cell_w };
+        if !self.visible(cx, cy, width) {
+            return;
+        }
+
+        let (mut fg, mut bg) = cell_colors(cell);
+        if block_cursor && cell.row == cursor.row && cell.col == cursor.col {
+            (fg, bg) = (bg, content.default_fg);
+        }
+
+        if bg != content.default_bg {
+            out.push(FrameGlyph::Stretch {
+                x: cx, y: cy, width, height: cell_h,
+                bg: self.faded(bg), face_id: 0, is_overlay: self.is_overlay,
+            });
+        }
+
+        let blank = cell.c == ' ' || cell.c == '\0';
+        let underline = underline_style(cell.flags);
+        let strike = cell.flags.contains(CellFlags::STRIKEOUT);
+        if cell.flags.contains(CellFlags::HIDDEN) || (blank && underline == 0 && !strike) {
+            return;
+        }
+        out.push(FrameGlyph::Char {
+            char: if blank { ' ' } else { cell.c },
+            composed: None,
+            x: cx, y: cy,
+            width, height: cell_h,
+            ascent, fg: self.faded(fg),
+            bg: None, face_id: self.face_id,
+            bold: cell.flags.contains(CellFlags::BOLD),
+            italic: cell.flags.contains(CellFlags::ITALIC),
+            font_size,
+            underline,
+            underline_color: cell.underline_color.map(|c| self.faded(c)),
+            strike_through: u8::from(strike),
+            strike_through_color: None,
+            overline: 0, overline_color: None,
+            is_overlay: self.is_overlay,
+        });
+    }
+
+    /// Push glyphs for `content` with its top left corner at (`x`, `y`).
+    /// The terminal's default background is left to the caller.  Large
+    /// grids are converted in bands of rows on the rayon pool.
+    pub fn push(&self, content: &TerminalContent, x: f32, y: f32, out: &mut Vec<FrameGlyph>) {
+        let CellSize { width: cell_w, height: cell_h, .. } = self.cell;
+        let cursor = &content.cursor;
+        let block_cursor = cursor.visible && cursor.shape == CursorShape::Block;
+
+        if worth_parallel(content.cells.len()) {
+            let band = content.cols.max(1) * ROWS_PER_BAND;
+            let bands: Vec<Vec<FrameGlyph>> = content.cells
+                .par_chunks(band)
+                .map(|cells| {
+                    let mut glyphs = Vec::with_capacity(cells.len() * 2);
+                    for cell in cells {
+                        self.push_cell(content, cell, x, y, &mut glyphs);
+                    }
+                    glyphs
+                })
+                .collect();
+            out.extend(bands.into_iter().flatten());
+        } else {
+            for cell in &content.cells {
+                self.push_cell(content, cell, x, y, out);
+            }
+        }
+
+        // The block cursor was drawn with its cell
+        let cx = x + cursor.col as f32 * cell_w;
+        let cy = y + cursor.row as f32 * cell_h;
+        if !cursor.visible || block_cursor || !self.visible(cx, cy, cell_w) {
+            return;
+        }
+        let color = self.faded(content.default_fg);
+        let bar = (cell_w / 8.0).round().max(2.0);
+        let stretch = |x, y, width, height| FrameGlyph::Stretch {
+            x, y, width, height, bg: color, face_id: 0, is_overlay: self.is_overlay,
+        };
+        match cursor.shape {
+            CursorShape::Beam => out.push(stretch(cx, cy, bar, cell_h)),
+            CursorShape::Underline => out.push(stretch(cx, cy + cell_h - bar, cell_w, bar)),
+            CursorShape::Hidden => {}
+            _ => out.push(FrameGlyph::Border { x: cx, y: cy, width: cell_w, height: cell_h, color }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::content::RenderCursor;
+
+    fn content(cells: Vec<RenderCell>, shape: CursorShape) -> TerminalContent {
+        TerminalContent {
+            cells,
+            cols: 4,
+            rows: 1,
+            cursor: RenderCursor { col: 0, row: 0, visible: true, shape },
+            default_bg: Color::BLACK,
+            default_fg: Color::WHITE,
+        }
+    }
+
+    fn cell(col: usize, c: char, flags: CellFlags) -> RenderCell {
+        RenderCell { col, row: 0, c, fg: Color::WHITE, bg: Color::BLACK, flags, underline_color: None }
+    }
+
+    fn renderer() -> TerminalRenderer {
+        TerminalRenderer { cell: CellSize::default(), face_id: 0, is_overlay: true, opacity: 1.0, clip: None }
+    }
+
+    #[test]
+    fn test_cell_attributes() {
+        let cells = vec![
+            cell(1, 'a', CellFlags::INVERSE),
+            cell(2, '好', CellFlags::WIDE_CHAR | CellFlags::UNDERCURL),
+            cell(3, 'x', CellFlags::HIDDEN),
+        ];
+        let mut out = Vec::new();
+        renderer().push(&content(cells, CursorShape::Underline), 0.0, 0.0, &mut out);
+
+        // Inverse: white background, black glyph
+        assert!(matches!(out[0], FrameGlyph::Stretch { x: 8.0, bg: Color::WHITE, .. }));
+        assert!(matches!(out[1], FrameGlyph::Char { char: 'a', fg: Color::BLACK, .. }));
+        assert!(matches!(out[2], FrameGlyph::Char { char: '好', width: 16.0, underline: 2, .. }));
+        // Hidden text draws nothing; the underline cursor is a bar
+        assert_eq!(out.len(), 4);
+        assert!(matches!(out[3], FrameGlyph::Stretch { x: 0.0, width: 8.0, height: 2.0, .. }));
+    }
+
+    #[test]
+    fn test_block_cursor_inverts_its_cell() {
+        let mut out = Vec::new();
+        renderer().push(&content(vec![cell(0, 'a', CellFlags::empty())], CursorShape::Block), 0.0, 0.0, &mut out);
+        assert_eq!(out.len(), 2);
+        assert!(matches!(out[0], FrameGlyph::Stretch { bg: Color::WHITE, .. }));
+        assert!(matches!(out[1], FrameGlyph::Char { fg: Color::BLACK, .. }));
+    }
+
+    #[test]
+    fn test_large_grid_keeps_cell_order() {
+        let (cols, rows) = (200, 30);
+        let mut cells = Vec::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                let flags = if (row + col) % 5 == 0 { CellFlags::INVERSE } else { CellFlags::empty() };
+                cells.push(RenderCell { row, ..cell(col, 'x', flags) });
+            }
+        }
+        let content = TerminalContent { cols, rows, ..content(cells, CursorShape::Hidden) };
+        let mut out = Vec::new();
+        renderer().push(&content, 0.0, 0.0, &mut out);
+
+        let mut serial = Vec::new();
+        for cell in &content.cells {
+            renderer().push_cell(&content, cell, 0.0, 0.0, &mut serial);
+        }
+        assert_eq!(format!("{:?}", out), format!("{:?}", serial));
+    }
+}
@@ -164,5 +164,43 @@ impl RoundedRectVertex {
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct Uniforms {
     pub screen_size: [f32; 2],
+    /// Text coverage correction `[gamma, contrast]` (see `TextGammaConfig`)
+    pub text_gamma: [f32; 2],
+    /// Edge smoothing width of shapes in device pixels (see `AntialiasConfig`)
+    pub antialias: f32,
+    pub _padding: [f32; 3],
+    /// Rows of the whole-frame color matrix (see `ColorFilterConfig`)
+    pub color_matrix: [[f32; 4]; 3],
+    /// Smart invert switch and regions left alone (see `SmartInvertConfig`)
+    pub invert: [f32; 4],
+    pub invert_keep: [[f32; 4]; crate::effect_config::SMART_INVERT_MAX_KEEP],
+}
+
+/// Parameters of the ambient background shader (see `AmbientConfig`).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct AmbientUniforms {
+    /// RGB and opacity
+    pub color: [f32; 4],
+    pub screen_size: [f32; 2],
+    /// Seconds since the renderer started, wrapped every hour
+    pub time: f32,
+    /// `AmbientEffect::shader_kind`
+    pub kind: u32,
+    pub density: f32,
+    pub speed: f32,
     pub _padding: [f32; 2],
 }
+
+/// The `effect` uniform of a shader effect (see `core::shader_effect`).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct ShaderEffectUniforms {
+    pub screen_size: [f32; 2],
+    /// Seconds since the renderer started, wrapped every hour
+    pub time: f32,
+    pub strength: f32,
+    /// x, y, width and height of the target in logical pixels
+    pub region: [f32; 4],
+    pub params: [f32; 4],
+}
@@ -4,15 +4,22 @@
 //! falling back to CPU decode + copy otherwise.
 
 use std::collections::HashMap;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 #[cfg(target_os = "linux")]
 use std::os::unix::io::RawFd;
 
 use gstreamer as gst;
+use crate::core::audio_levels;
+use crate::core::decode_scheduler::{Decode, DecodeScheduler};
+use crate::core::error_report::{self, ErrorKind};
+use crate::core::media_probe::{self, MediaProbe};
+use crate::core::video_color::{ColorCorrection, ColorMatrix, ColorRange, VideoColorimetry};
 use gstreamer::prelude::*;
 use gstreamer_video as gst_video;
 use gstreamer_app as gst_app;
+use wgpu::util::DeviceExt;
+use super::audio_mixer::AudioMixer;
 #[cfg(target_os = "linux")]
 use gstreamer_allocators as gst_allocators;
 
@@ -65,6 +72,9 @@ pub struct DecodedFrame {
     pub pts: u64,
     /// Duration in nanoseconds
     pub duration: u64,
+    /// What the renderer must do to the RGB to get the stream's colors,
+    /// when the converter did not read its range and matrix right
+    pub color: ColorCorrection,
 }
 
 /// Cached video with GStreamer pipeline
@@ -80,16 +90,47 @@ pub struct CachedVideo {
     pub texture: Option<wgpu::Texture>,
     pub texture_view: Option<wgpu::TextureView>,
     pub bind_group: Option<wgpu::BindGroup>,
+    /// Color correction of the frames, and the bind group passing it to
+    /// the video shader (None when there is nothing to correct)
+    pub color: ColorCorrection,
+    pub color_bind_group: Option<wgpu::BindGroup>,
     /// Frame count
     pub frame_count: u64,
     /// Loop count (-1 = infinite)
     pub loop_count: i32,
+    /// Timestamp of the newest frame, in nanoseconds
+    pub position: u64,
+    /// Audio level, 0.0-1.0
+    pub volume: f32,
 }
 
+/// Pipelines of the videos being decoded, for seeking and position queries
+type PipelineMap = Arc<Mutex<HashMap<u32, gst::glib::WeakRef<gst::Pipeline>>>>;
+
+/// Audio level wanted for each video, for audio streams found after it was set
+type VolumeMap = Arc<Mutex<HashMap<u32, f32>>>;
+
+/// How often `level` and `spectrum` report, in nanoseconds
+const AUDIO_LEVEL_INTERVAL: u64 = 100_000_000;
+/// Frequency bands `spectrum` splits the audio into
+const AUDIO_SPECTRUM_BANDS: u32 = 32;
+/// Pull timeouts (100 ms each) without a first frame before a playing
+/// pipeline is reported as stalled
+const STALL_TIMEOUTS: u64 = 100;
+/// How long `probe` waits for a file to preroll
+const PROBE_TIMEOUT_SECONDS: u64 = 5;
+/// How vapostproc converts to RGB, whatever the stream says
+const VAPOSTPROC_COLORIMETRY: VideoColorimetry = VideoColorimetry {
+    range: ColorRange::Full,
+    matrix: ColorMatrix::Bt709,
+};
+
 /// Request to load a video
 struct LoadRequest {
     id: u32,
     path: String,
+    /// Decoded frames the appsink may hold
+    frame_buffers: u32,
 }
 
 /// Video pipeline with frame extraction
@@ -108,10 +149,23 @@ pub struct VideoCache {
     load_tx: mpsc::Sender<LoadRequest>,
     /// Channel to receive decoded frames
     frame_rx: mpsc::Receiver<DecodedFrame>,
+    /// Channel to receive the IDs of videos that reached the end
+    ended_rx: mpsc::Receiver<u32>,
     /// Bind group layout for video textures (created in init_gpu)
     bind_group_layout: Option<wgpu::BindGroupLayout>,
     /// Sampler for video textures (created in init_gpu)
     sampler: Option<wgpu::Sampler>,
+    /// Bind group layout for the color correction uniform (created in init_gpu)
+    color_layout: Option<wgpu::BindGroupLayout>,
+    /// Decoded frames buffered per video
+    frame_buffers: u32,
+    /// Pipelines registered by the decoder thread
+    pipelines: PipelineMap,
+    volumes: VolumeMap,
+    /// Shared audio output, when the mixer elements are installed
+    mixer: Option<Arc<AudioMixer>>,
+    /// Which videos may decode, from the renderer's visibility hints
+    scheduler: DecodeScheduler,
 }
 
 impl VideoCache {
@@ -119,15 +173,22 @@ impl VideoCache {
     pub fn new() -> Self {
         // Initialize GStreamer
         if let Err(e) = gst::init() {
-            log::error!("Failed to initialize GStreamer: {}", e);
+            error_report::error(ErrorKind::Video, None, format!("cannot initialize GStreamer: {}", e));
         }
 
         let (load_tx, load_rx) = mpsc::channel::<LoadRequest>();
         let (frame_tx, frame_rx) = mpsc::channel::<DecodedFrame>();
+        let (ended_tx, ended_rx) = mpsc::channel::<u32>();
+        let pipelines = PipelineMap::default();
+        let volumes = VolumeMap::default();
+        let mixer = AudioMixer::new().map(Arc::new);
 
         // Spawn decoder thread
+        let decoder_pipelines = pipelines.clone();
+        let decoder_volumes = volumes.clone();
+        let decoder_mixer = mixer.clone();
         thread::spawn(move || {
-            Self::decoder_thread(load_rx, frame_tx);
+            Self::decoder_thread(load_rx, frame_tx, ended_tx, decoder_pipelines, decoder_volumes, decoder_mixer);
         });
 
         Self {
@@ -135,8 +196,15 @@ impl VideoCache {
             next_id: 1,
             load_tx,
             frame_rx,
+            ended_rx,
             bind_group_layout: None,
             sampler: None,
+            color_layout: None,
+            frame_buffers: 2,
+            pipelines,
+            volumes,
+            mixer,
+            scheduler: DecodeScheduler::default(),
         }
     }
 
@@ -173,15 +241,59 @@ impl VideoCache {
             ..Default::default()
         });
 
+        let color_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Video Color Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
         self.bind_group_layout = Some(bind_group_layout);
         self.sampler = Some(sampler);
+        self.color_layout = Some(color_layout);
         log::info!("VideoCache: GPU resources initialized");
     }
 
+    /// Move to a new device after the old one was lost.  Pipelines keep
+    /// playing; each video gets a new texture with its next frame.
+    pub fn reset_gpu(&mut self, device: &wgpu::Device) {
+        for video in self.videos.values_mut() {
+            video.texture = None;
+            video.texture_view = None;
+            video.bind_group = None;
+            video.color_bind_group = None;
+        }
+        self.init_gpu(device);
+    }
+
+    /// Layout of the color correction bind group (group 2 of the video
+    /// pipeline), once `init_gpu` ran
+    pub fn color_bind_group_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.color_layout.as_ref()
+    }
+
+    /// Set how many decoded frames are buffered for videos loaded from now on
+    pub fn set_frame_buffers(&mut self, count: u32) {
+        self.frame_buffers = count.max(1);
+    }
+
+    /// Let at most `count` videos decode at once (0 for no limit)
+    pub fn set_max_active(&mut self, count: u32) {
+        self.scheduler.set_max_active(count as usize);
+    }
+
     /// Load a video file
     pub fn load_file(&mut self, path: &str) -> u32 {
         let id = self.next_id;
         self.next_id += 1;
+        self.scheduler.add(id);
 
         // Create placeholder entry
         self.videos.insert(id, CachedVideo {
@@ -192,14 +304,19 @@ impl VideoCache {
             texture: None,
             texture_view: None,
             bind_group: None,
+            color: ColorCorrection::IDENTITY,
+            color_bind_group: None,
             frame_count: 0,
             loop_count: 0,
+            position: 0,
+            volume: 1.0,
         });
 
         // Send load request
         let _ = self.load_tx.send(LoadRequest {
             id,
             path: path.to_string(),
+            frame_buffers: self.frame_buffers,
         });
 
         log::info!("VideoCache: queued video {} for loading: {}", id, path);
@@ -227,6 +344,11 @@ impl VideoCache {
             video.state = VideoState::Playing;
             log::debug!("VideoCache: play video {}", id);
         }
+        self.scheduler.set_playing(id, true);
+        // A parked video starts when the scheduler gives it a slot
+        if !self.scheduler.is_parked(id) {
+            self.set_pipeline_state(id, gst::State::Playing);
+        }
     }
 
     /// Pause video
@@ -235,6 +357,8 @@ impl VideoCache {
             video.state = VideoState::Paused;
             log::debug!("VideoCache: pause video {}", id);
         }
+        self.scheduler.set_playing(id, false);
+        self.set_pipeline_state(id, gst::State::Paused);
     }
 
     /// Stop video
@@ -243,6 +367,18 @@ impl VideoCache {
             video.state = VideoState::Stopped;
             log::debug!("VideoCache: stop video {}", id);
         }
+        self.scheduler.set_playing(id, false);
+        self.set_pipeline_state(id, gst::State::Paused);
+    }
+
+    /// Move the pipeline of video `id` to `state`, so paused videos also
+    /// stop their sound
+    fn set_pipeline_state(&self, id: u32, state: gst::State) {
+        if let Some(pipeline) = self.pipeline(id) {
+            if let Err(e) = pipeline.set_state(state) {
+                log::warn!("VideoCache: cannot set video {} to {:?}: {}", id, state, e);
+            }
+        }
     }
 
     /// Set loop count (-1 for infinite)
@@ -252,15 +388,141 @@ impl VideoCache {
         }
     }
 
+    /// Pipeline of video `id`, while it is being decoded
+    fn pipeline(&self, id: u32) -> Option<gst::Pipeline> {
+        self.pipelines.lock().unwrap_or_else(|e| e.into_inner()).get(&id)?.upgrade()
+    }
+
+    /// Length of video `id` in nanoseconds, once known
+    pub fn duration(&self, id: u32) -> Option<u64> {
+        let duration = self.pipeline(id)?.query_duration::<gst::ClockTime>()?.nseconds();
+        (duration > 0).then_some(duration)
+    }
+
+    /// How far video `id` has played, from 0.0 to 1.0.  `None` until the
+    /// length of the video is known.
+    pub fn progress(&self, id: u32) -> Option<f32> {
+        let video = self.videos.get(&id)?;
+        let duration = self.duration(id)?;
+        Some((video.position as f64 / duration as f64).clamp(0.0, 1.0) as f32)
+    }
+
+    /// Parts of video `id` available for playback, as (start, end)
+    /// fractions of its length.  Empty when the pipeline does not report
+    /// buffering.
+    pub fn buffered(&self, id: u32) -> Vec<(f32, f32)> {
+        let Some(pipeline) = self.pipeline(id) else {
+            return Vec::new();
+        };
+        let mut query = gst::query::Buffering::new(gst::Format::Percent);
+        if !pipeline.query(&mut query) {
+            return Vec::new();
+        }
+        let max = gst::ffi::GST_FORMAT_PERCENT_MAX as f32;
+        let mut ranges: Vec<(f32, f32)> = query
+            .ranges()
+            .into_iter()
+            .map(|(start, stop)| (start.value() as f32 / max, stop.value() as f32 / max))
+            .filter(|(start, stop)| stop > start)
+            .collect();
+        if ranges.is_empty() {
+            // No ranges, only how far from the start the data reaches
+            let (start, stop, _) = query.range();
+            if stop.value() > start.value() {
+                ranges.push((start.value() as f32 / max, stop.value() as f32 / max));
+            }
+        }
+        ranges
+    }
+
+    /// Set the audio level of video `id`, 0.0-1.0.  It applies to the
+    /// pipeline's `volume` element when it has one, and is kept for the
+    /// controls either way.
+    pub fn set_volume(&mut self, id: u32, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        if let Some(video) = self.videos.get_mut(&id) {
+            video.volume = volume;
+        }
+        self.volumes.lock().unwrap_or_else(|e| e.into_inner()).insert(id, volume);
+        if let Some(element) = self.pipeline(id).and_then(|p| p.by_name("volume")) {
+            element.set_property("volume", volume as f64);
+        }
+    }
+
+    /// Jump to `fraction` (0.0-1.0) of video `id`
+    pub fn seek(&mut self, id: u32, fraction: f32) {
+        let Some(pipeline) = self.pipeline(id) else {
+            return;
+        };
+        let Some(duration) = pipeline.query_duration::<gst::ClockTime>() else {
+            return;
+        };
+        let target = (duration.nseconds() as f64 * fraction.clamp(0.0, 1.0) as f64) as u64;
+        match pipeline.seek_simple(
+            gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+            gst::ClockTime::from_nseconds(target),
+        ) {
+            Ok(()) => {
+                if let Some(video) = self.videos.get_mut(&id) {
+                    video.position = target;
+                }
+            }
+            Err(e) => log::warn!("VideoCache: cannot seek video {}: {}", id, e),
+        }
+    }
+
     /// Remove video from cache
     pub fn remove(&mut self, id: u32) {
         self.videos.remove(&id);
+        self.scheduler.remove(id);
+        self.volumes.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        audio_levels::remove(id);
+        // Tell the decoder thread to tear the pipeline down, sound and all
+        if let Some(pipeline) = self.pipeline(id) {
+            let _ = pipeline.post_message(gst::message::Application::new(
+                gst::Structure::new_empty("neomacs-remove"),
+            ));
+        }
         log::debug!("VideoCache: removed video {}", id);
     }
 
-    /// Check if any video is currently in Playing state
+    /// Number of videos with a frame texture
+    pub fn texture_count(&self) -> usize {
+        self.videos.values().filter(|v| v.texture.is_some()).count()
+    }
+
+    /// Bytes of GPU memory held by video frame textures
+    pub fn memory_usage(&self) -> usize {
+        self.videos
+            .values()
+            .filter(|v| v.texture.is_some())
+            .map(|v| v.width as usize * v.height as usize * 4)
+            .sum()
+    }
+
+    /// Check if any video is currently in Playing state (and not parked)
     pub fn has_playing_videos(&self) -> bool {
-        self.videos.values().any(|v| v.state == VideoState::Playing)
+        self.videos
+            .values()
+            .any(|v| v.state == VideoState::Playing && !self.scheduler.is_parked(v.id))
+    }
+
+    /// Whether video `id` is paused by the scheduler, showing its last
+    /// frame as a poster
+    pub fn is_parked(&self, id: u32) -> bool {
+        self.scheduler.is_parked(id)
+    }
+
+    /// Take the videos drawn this frame, with the screen area of each,
+    /// and park or resume pipelines to match (call once per frame)
+    pub fn update_visibility(&mut self, visible: &[(u32, f32)]) {
+        for (id, decode) in self.scheduler.update(visible) {
+            log::debug!("VideoCache: video {} {:?}", id, decode);
+            match decode {
+                Decode::Active => self.set_pipeline_state(id, gst::State::Playing),
+                Decode::Parked => self.set_pipeline_state(id, gst::State::Paused),
+            }
+        }
     }
 
     /// Process pending decoded frames using stored GPU resources (call each frame)
@@ -290,6 +552,18 @@ impl VideoCache {
         self.sampler = Some(sampler);
     }
 
+    /// Mark videos that reached the end of their stream and return their IDs
+    pub fn take_ended(&mut self) -> Vec<u32> {
+        let mut ended = Vec::new();
+        while let Ok(id) = self.ended_rx.try_recv() {
+            if let Some(video) = self.videos.get_mut(&id) {
+                video.state = VideoState::EndOfStream;
+                ended.push(id);
+            }
+        }
+        ended
+    }
+
     /// Process pending decoded frames (call each frame)
     /// Uses the provided bind_group_layout and sampler from image_cache
     /// to ensure compatibility with the shared image/video rendering pipeline.
@@ -412,6 +686,32 @@ impl VideoCache {
                 #[cfg(not(target_os = "linux"))]
                 let dmabuf_imported = false;
 
+                // (Re)build the correction uniform when it changed or was
+                // lost with the device
+                let stale_color = video.color != frame.color
+                    || (video.color_bind_group.is_none() && !frame.color.is_identity());
+                if stale_color {
+                    video.color = frame.color;
+                    video.color_bind_group = match self.color_layout {
+                        Some(ref layout) if !frame.color.is_identity() => {
+                            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                                label: Some("Video Color Buffer"),
+                                contents: bytemuck::cast_slice(&frame.color.to_uniform()),
+                                usage: wgpu::BufferUsages::UNIFORM,
+                            });
+                            Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                                label: Some("Video Color Bind Group"),
+                                layout,
+                                entries: &[wgpu::BindGroupEntry {
+                                    binding: 0,
+                                    resource: buffer.as_entire_binding(),
+                                }],
+                            }))
+                        }
+                        _ => None,
+                    };
+                }
+
                 // Fall back to CPU copy if DMA-BUF import failed or not available
                 if !dmabuf_imported && !frame.data.is_empty() {
                     if let Some(ref texture) = video.texture {
@@ -438,6 +738,7 @@ impl VideoCache {
                 }
 
                 video.frame_count += 1;
+                video.position = frame.pts;
                 log::trace!("VideoCache: updated video {} frame {}", frame.video_id, video.frame_count);
             }
         }
@@ -566,206 +867,517 @@ impl VideoCache {
     fn decoder_thread(
         rx: mpsc::Receiver<LoadRequest>,
         tx: mpsc::Sender<DecodedFrame>,
+        ended_tx: mpsc::Sender<u32>,
+        pipelines: PipelineMap,
+        volumes: VolumeMap,
+        mixer: Option<Arc<AudioMixer>>,
     ) {
         log::debug!("Video decoder thread started");
 
+        // Each pipeline gets a thread of its own to watch its bus, so
+        // videos decode side by side and the scheduler decides which play
         while let Ok(request) = rx.recv() {
-            log::info!("Decoder thread: loading video {}: {}", request.id, request.path);
+            let id = request.id;
+            let tx = tx.clone();
+            let ended_tx = ended_tx.clone();
+            let pipelines = pipelines.clone();
+            let volumes = volumes.clone();
+            let mixer = mixer.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("video-{}", id))
+                .spawn(move || Self::run_pipeline(request, tx, ended_tx, pipelines, volumes, mixer));
+            if let Err(e) = spawned {
+                error_report::error(ErrorKind::Video, Some(id), format!("cannot start decoder thread: {}", e));
+            }
+        }
 
-            // Strip file:// prefix if present (filesrc needs raw paths)
-            let path = if request.path.starts_with("file://") {
-                &request.path[7..]
-            } else {
-                &request.path
-            };
+        log::debug!("Video decoder thread exiting");
+    }
 
-            // Check if VA-API hardware acceleration is available
-            let has_vapostproc = gst::ElementFactory::find("vapostproc").is_some();
-
-            // Create GStreamer pipeline with video and audio
-            // decodebin will auto-select VA-API hardware decoders when available
-            // since they have higher rank than software decoders
-            //
-            // NOTE: vapostproc does YUV→RGB conversion but doesn't respect downstream
-            // colorimetry caps (GitLab issue #80). For BT.2020 content (10-bit VP9/AV1),
-            // colors may be slightly off. Proper fix would require shader-based color
-            // matrix conversion.
-            let pipeline_str = if has_vapostproc {
-                // VA-API hardware acceleration pipeline with true zero-copy:
-                // - decodebin auto-selects VA-API decoders (higher rank)
-                // - vapostproc does GPU-based color conversion to BGRA on VA surface
-                // - Output stays in VA memory for DMA-BUF export
-                // - Vulkan HAL imports DMA-BUF directly as texture (zero-copy)
-                // NOTE: Audio is handled separately - not included here to avoid
-                // pipeline stall when video has no audio track
-                log::info!("Using VA-API hardware acceleration pipeline with zero-copy DMA-BUF");
-                format!(
-                    "filesrc location=\"{}\" ! decodebin ! \
-                     queue max-size-buffers=3 ! vapostproc ! \
-                     video/x-raw(memory:VAMemory),format=BGRA ! appsink name=sink",
-                    path.replace("\"", "\\\"")
-                )
-            } else {
-                // Software fallback pipeline
-                // NOTE: Audio is handled separately - not included here to avoid
-                // pipeline stall when video has no audio track
-                log::info!("VA-API not available, using software decoding");
-                format!(
-                    "filesrc location=\"{}\" ! decodebin ! \
-                     queue ! videoconvert ! video/x-raw,format=RGBA ! appsink name=sink",
-                    path.replace("\"", "\\\"")
-                )
-            };
+    /// Decode the video of `request` until it ends, fails or is removed
+    fn run_pipeline(
+        request: LoadRequest,
+        tx: mpsc::Sender<DecodedFrame>,
+        ended_tx: mpsc::Sender<u32>,
+        pipelines: PipelineMap,
+        volumes: VolumeMap,
+        mixer: Option<Arc<AudioMixer>>,
+    ) {
+        log::info!("Decoder thread: loading video {}: {}", request.id, request.path);
 
-            log::debug!("Creating GStreamer pipeline: {}", pipeline_str);
+        // Strip file:// prefix if present (filesrc needs raw paths)
+        let path = if request.path.starts_with("file://") {
+            &request.path[7..]
+        } else {
+            &request.path
+        };
 
-            match gst::parse::launch(&pipeline_str) {
-                Ok(pipeline) => {
-                    log::debug!("Pipeline created successfully");
-                    let pipeline = pipeline.dynamic_cast::<gst::Pipeline>().unwrap();
+        // Check if VA-API hardware acceleration is available
+        let has_vapostproc = gst::ElementFactory::find("vapostproc").is_some();
+
+        // Create GStreamer pipeline with video and audio
+        // decodebin will auto-select VA-API hardware decoders when available
+        // since they have higher rank than software decoders
+        //
+        // NOTE: vapostproc does YUV→RGB conversion but doesn't respect downstream
+        // colorimetry caps (GitLab issue #80): it always converts as full-range
+        // BT.709.  The frame puller reads what the stream really is from the
+        // caps going into vapostproc, and the video shader corrects the rest.
+        // videoconvert does respect them, so the software path asks for sRGB
+        // and gets full-range RGB whatever the source.
+        let pipeline_str = if has_vapostproc {
+            // VA-API hardware acceleration pipeline with true zero-copy:
+            // - decodebin auto-selects VA-API decoders (higher rank)
+            // - vapostproc does GPU-based color conversion to BGRA on VA surface
+            // - Output stays in VA memory for DMA-BUF export
+            // - Vulkan HAL imports DMA-BUF directly as texture (zero-copy)
+            // NOTE: Audio is linked from pad-added - not included here to avoid
+            // pipeline stall when video has no audio track
+            log::info!("Using VA-API hardware acceleration pipeline with zero-copy DMA-BUF");
+            format!(
+                "filesrc location=\"{}\" ! decodebin name=dec ! video/x-raw(ANY) ! \
+                 queue max-size-buffers=3 ! vapostproc name=postproc ! \
+                 video/x-raw(memory:VAMemory),format=BGRA ! appsink name=sink",
+                path.replace("\"", "\\\"")
+            )
+        } else {
+            // Software fallback pipeline
+            // NOTE: Audio is linked from pad-added - not included here to avoid
+            // pipeline stall when video has no audio track
+            log::info!("VA-API not available, using software decoding");
+            format!(
+                "filesrc location=\"{}\" ! decodebin name=dec ! video/x-raw(ANY) ! \
+                 queue ! videoconvert ! video/x-raw,format=RGBA,colorimetry=sRGB ! appsink name=sink",
+                path.replace("\"", "\\\"")
+            )
+        };
 
-                    // Get appsink
-                    let appsink = pipeline
-                        .by_name("sink")
-                        .expect("Could not get appsink")
-                        .dynamic_cast::<gst_app::AppSink>()
-                        .expect("Could not cast to AppSink");
+        log::debug!("Creating GStreamer pipeline: {}", pipeline_str);
 
-                    // Configure appsink for pull mode (polling with try_pull_sample)
-                    appsink.set_max_buffers(2);
-                    appsink.set_drop(true);
+        match gst::parse::launch(&pipeline_str) {
+            Ok(pipeline) => {
+                log::debug!("Pipeline created successfully");
+                let pipeline = pipeline.dynamic_cast::<gst::Pipeline>().unwrap();
+                pipelines.lock().unwrap_or_else(|e| e.into_inner())
+                    .insert(request.id, pipeline.downgrade());
 
-                    let video_id = request.id;
-                    let tx_clone = tx.clone();
+                // Get appsink
+                let appsink = pipeline
+                    .by_name("sink")
+                    .expect("Could not get appsink")
+                    .dynamic_cast::<gst_app::AppSink>()
+                    .expect("Could not cast to AppSink");
 
-                    // Start playing
-                    log::debug!("Setting pipeline to Playing state");
-                    if let Err(e) = pipeline.set_state(gst::State::Playing) {
-                        log::error!("Failed to start pipeline: {:?}", e);
-                    } else {
-                        log::info!("Pipeline started successfully for video {}", request.id);
-                    }
+                // Configure appsink for pull mode (polling with try_pull_sample)
+                appsink.set_max_buffers(request.frame_buffers);
+                appsink.set_drop(true);
 
-                    // Spawn frame pulling thread
-                    let appsink_clone = appsink.clone();
+                let video_id = request.id;
+                let tx_clone = tx.clone();
+
+                // The video branch only takes video pads, so an audio
+                // stream gets its own branch once decodebin finds one
+                if let Some(decodebin) = pipeline.by_name("dec") {
                     let pipeline_weak = pipeline.downgrade();
-                    let using_vaapi = has_vapostproc;
-                    std::thread::spawn(move || {
-                        log::info!("Frame puller thread started for video {}", video_id);
-
-                        // Wait for pipeline to reach PLAYING state
-                        if let Some(pipeline) = pipeline_weak.upgrade() {
-                            let (res, state, _) = pipeline.state(gst::ClockTime::from_seconds(5));
-                            log::info!("Video {} pipeline state: {:?}, result: {:?}", video_id, state, res);
+                    let volumes = volumes.clone();
+                    let mixer = mixer.clone();
+                    decodebin.connect_pad_added(move |_, pad| {
+                        let Some(pipeline) = pipeline_weak.upgrade() else {
+                            return;
+                        };
+                        if pad.is_linked() || !Self::is_audio_pad(pad) {
+                            return;
+                        }
+                        let volume = volumes.lock().unwrap_or_else(|e| e.into_inner())
+                            .get(&video_id).copied().unwrap_or(1.0);
+                        if let Err(e) = Self::link_audio(&pipeline, pad, volume, video_id, mixer.as_deref()) {
+                            log::warn!("Video {}: cannot play audio: {}", video_id, e);
                         }
-                        let mut frame_count = 0u64;
-                        let mut timeout_count = 0u64;
-
-                        loop {
-                            // Try to pull a sample with 100ms timeout
-                            match appsink_clone.try_pull_sample(gst::ClockTime::from_mseconds(100)) {
-                                Some(sample) => {
-                                    timeout_count = 0;
-                                    frame_count += 1;
-                                    if let Some(buffer) = sample.buffer() {
-                                        // Get video info from caps
-                                        if let Some(caps) = sample.caps() {
-                                            if let Ok(info) = gst_video::VideoInfo::from_caps(caps) {
-                                                let width = info.width();
-                                                let height = info.height();
-
-                                                // Try to get DMA-BUF info for zero-copy path
+                    });
+                }
+
+                // Start playing
+                log::debug!("Setting pipeline to Playing state");
+                if let Err(e) = pipeline.set_state(gst::State::Playing) {
+                    error_report::error(ErrorKind::Video, Some(request.id), format!("cannot start playback: {}", e));
+                } else {
+                    log::info!("Pipeline started successfully for video {}", request.id);
+                }
+
+                // Spawn frame pulling thread
+                let appsink_clone = appsink.clone();
+                let pipeline_weak = pipeline.downgrade();
+                let using_vaapi = has_vapostproc;
+                std::thread::spawn(move || {
+                    log::info!("Frame puller thread started for video {}", video_id);
+
+                    // Wait for pipeline to reach PLAYING state
+                    if let Some(pipeline) = pipeline_weak.upgrade() {
+                        let (res, state, _) = pipeline.state(gst::ClockTime::from_seconds(5));
+                        log::info!("Video {} pipeline state: {:?}, result: {:?}", video_id, state, res);
+                    }
+                    let mut frame_count = 0u64;
+                    let mut timeout_count = 0u64;
+                    // Correction for the current size, recomputed when
+                    // the stream changes
+                    let mut color = (0, 0, ColorCorrection::IDENTITY);
+
+                    loop {
+                        // Try to pull a sample with 100ms timeout
+                        match appsink_clone.try_pull_sample(gst::ClockTime::from_mseconds(100)) {
+                            Some(sample) => {
+                                timeout_count = 0;
+                                frame_count += 1;
+                                if let Some(buffer) = sample.buffer() {
+                                    // Get video info from caps
+                                    if let Some(caps) = sample.caps() {
+                                        if let Ok(info) = gst_video::VideoInfo::from_caps(caps) {
+                                            let width = info.width();
+                                            let height = info.height();
+                                            if using_vaapi && (color.0, color.1) != (width, height) {
+                                                let source = pipeline_weak
+                                                    .upgrade()
+                                                    .and_then(|p| Self::source_colorimetry(&p, width, height))
+                                                    .unwrap_or_else(|| VideoColorimetry::guess(width, height));
+                                                let correction = ColorCorrection::between(VAPOSTPROC_COLORIMETRY, source);
+                                                log::debug!("Video {} colorimetry {:?}, correction {:?}", video_id, source, correction);
+                                                color = (width, height, correction);
+                                            }
+
+                                            // Try to get DMA-BUF info for zero-copy path
+                                            #[cfg(target_os = "linux")]
+                                            let dmabuf_info = Self::try_extract_dmabuf(buffer, &info);
+                                            #[cfg(not(target_os = "linux"))]
+                                            let dmabuf_info: Option<()> = None;
+
+                                            let has_dmabuf = dmabuf_info.is_some();
+                                            if frame_count <= 5 || frame_count % 60 == 0 {
+                                                log::info!("Frame #{} for video {}, {}x{}, format={:?}, DMA-BUF: {}",
+                                                    frame_count, video_id, width, height, info.format(), has_dmabuf);
+                                            }
+
+                                            // Map buffer and extract pixel data
+                                            // For DMA-BUF zero-copy, we still need the data for fallback
+                                            // TODO: Skip mapping when DMA-BUF import to wgpu works
+                                            let data = if let Ok(map) = buffer.map_readable() {
+                                                map.as_slice().to_vec()
+                                            } else if has_dmabuf {
+                                                // DMA-BUF memory may not be mappable - this is expected
+                                                log::debug!("DMA-BUF memory not mappable (expected for zero-copy)");
+                                                Vec::new()
+                                            } else {
+                                                log::warn!("Failed to map buffer and no DMA-BUF available");
+                                                Vec::new()
+                                            };
+
+                                            if tx_clone.send(DecodedFrame {
+                                                id: frame_count as u32,
+                                                video_id,
+                                                width,
+                                                height,
+                                                data,
                                                 #[cfg(target_os = "linux")]
-                                                let dmabuf_info = Self::try_extract_dmabuf(buffer, &info);
-                                                #[cfg(not(target_os = "linux"))]
-                                                let dmabuf_info: Option<()> = None;
-
-                                                let has_dmabuf = dmabuf_info.is_some();
-                                                if frame_count <= 5 || frame_count % 60 == 0 {
-                                                    log::info!("Frame #{} for video {}, {}x{}, format={:?}, DMA-BUF: {}",
-                                                        frame_count, video_id, width, height, info.format(), has_dmabuf);
-                                                }
-
-                                                // Map buffer and extract pixel data
-                                                // For DMA-BUF zero-copy, we still need the data for fallback
-                                                // TODO: Skip mapping when DMA-BUF import to wgpu works
-                                                let data = if let Ok(map) = buffer.map_readable() {
-                                                    map.as_slice().to_vec()
-                                                } else if has_dmabuf {
-                                                    // DMA-BUF memory may not be mappable - this is expected
-                                                    log::debug!("DMA-BUF memory not mappable (expected for zero-copy)");
-                                                    Vec::new()
-                                                } else {
-                                                    log::warn!("Failed to map buffer and no DMA-BUF available");
-                                                    Vec::new()
-                                                };
-
-                                                if tx_clone.send(DecodedFrame {
-                                                    id: frame_count as u32,
-                                                    video_id,
-                                                    width,
-                                                    height,
-                                                    data,
-                                                    #[cfg(target_os = "linux")]
-                                                    dmabuf: dmabuf_info,
-                                                    pts: buffer.pts().map(|p| p.nseconds()).unwrap_or(0),
-                                                    duration: buffer.duration().map(|d| d.nseconds()).unwrap_or(0),
-                                                }).is_err() {
-                                                    log::debug!("Frame receiver dropped, stopping puller");
-                                                    break;
-                                                }
+                                                dmabuf: dmabuf_info,
+                                                pts: buffer.pts().map(|p| p.nseconds()).unwrap_or(0),
+                                                duration: buffer.duration().map(|d| d.nseconds()).unwrap_or(0),
+                                                color: color.2,
+                                            }).is_err() {
+                                                log::debug!("Frame receiver dropped, stopping puller");
+                                                break;
                                             }
                                         }
                                     }
                                 }
-                                None => {
-                                    timeout_count += 1;
-                                    // Check if EOS
-                                    if appsink_clone.is_eos() {
-                                        log::info!("Video {} reached EOS after {} frames", video_id, frame_count);
-                                        break;
-                                    }
-                                    // Log occasional timeout status
-                                    if timeout_count == 1 || timeout_count % 50 == 0 {
-                                        log::debug!("Video {} pull timeout #{}, frames so far: {}", video_id, timeout_count, frame_count);
+                            }
+                            None => {
+                                timeout_count += 1;
+                                // A pipeline that should be playing but has
+                                // not decoded anything is stuck, typically
+                                // on a decoder that cannot keep up or a
+                                // broken hardware path
+                                if frame_count == 0 && timeout_count == STALL_TIMEOUTS {
+                                    let stalled = pipeline_weak.upgrade().is_some_and(|p| {
+                                        p.current_state() == gst::State::Playing
+                                            || p.pending_state() != gst::State::VoidPending
+                                    });
+                                    if stalled {
+                                        error_report::error(ErrorKind::Video, Some(video_id), format!(
+                                            "no frame decoded after {} s; the pipeline is stalled",
+                                            STALL_TIMEOUTS / 10,
+                                        ));
                                     }
                                 }
+                                // Check if EOS
+                                if appsink_clone.is_eos() {
+                                    log::info!("Video {} reached EOS after {} frames", video_id, frame_count);
+                                    break;
+                                }
+                                // Log occasional timeout status
+                                if timeout_count == 1 || timeout_count % 50 == 0 {
+                                    log::debug!("Video {} pull timeout #{}, frames so far: {}", video_id, timeout_count, frame_count);
+                                }
                             }
                         }
-                        log::debug!("Frame puller thread exiting for video {}", video_id);
-                    });
-
-                    // Wait for EOS or error on bus
-                    let bus = pipeline.bus().unwrap();
-                    for msg in bus.iter_timed(gst::ClockTime::NONE) {
-                        match msg.view() {
-                            gst::MessageView::Eos(..) => {
-                                log::debug!("Video {} bus: end of stream", video_id);
-                                break;
+                    }
+                    log::debug!("Frame puller thread exiting for video {}", video_id);
+                });
+
+                // Wait for EOS or error on bus
+                let bus = pipeline.bus().unwrap();
+                for msg in bus.iter_timed(gst::ClockTime::NONE) {
+                    match msg.view() {
+                        gst::MessageView::Eos(..) => {
+                            log::debug!("Video {} bus: end of stream", video_id);
+                            let _ = ended_tx.send(video_id);
+                            break;
+                        }
+                        gst::MessageView::Error(err) => {
+                            log::debug!("Video {} error details: {:?}", video_id, err.debug());
+                            error_report::error(ErrorKind::Video, Some(video_id), err.error().to_string());
+                            break;
+                        }
+                        gst::MessageView::Element(element) => {
+                            if let Some(s) = element.structure() {
+                                if let Some(missing) = Self::missing_plugin(s) {
+                                    error_report::error(ErrorKind::Video, Some(video_id), missing);
+                                } else {
+                                    Self::publish_audio_levels(video_id, s);
+                                }
                             }
-                            gst::MessageView::Error(err) => {
-                                log::error!(
-                                    "Video {} error: {} ({:?})",
-                                    video_id,
-                                    err.error(),
-                                    err.debug()
-                                );
+                        }
+                        gst::MessageView::Application(app) => {
+                            if app.structure().is_some_and(|s| s.has_name("neomacs-remove")) {
+                                log::debug!("Video {} removed, stopping pipeline", video_id);
                                 break;
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
+                }
+
+                // Cleanup
+                pipelines.lock().unwrap_or_else(|e| e.into_inner()).remove(&video_id);
+                let _ = pipeline.set_state(gst::State::Null);
+                if let Some(ref mixer) = mixer {
+                    mixer.remove_source(video_id);
+                }
+            }
+            Err(e) => {
+                // Usually a missing GStreamer plugin
+                error_report::error(ErrorKind::Video, Some(request.id), format!("cannot create pipeline: {}", e));
+            }
+        }
+    }
+
+    /// Range and matrix of the YUV going into vapostproc, from its caps;
+    /// what the caps leave open is guessed from the size
+    fn source_colorimetry(pipeline: &gst::Pipeline, width: u32, height: u32) -> Option<VideoColorimetry> {
+        let caps = pipeline.by_name("postproc")?.static_pad("sink")?.current_caps()?;
+        let colorimetry = gst_video::VideoInfo::from_caps(&caps).ok()?.colorimetry();
+        let guess = VideoColorimetry::guess(width, height);
+        let range = match colorimetry.range() {
+            gst_video::VideoColorRange::Range0_255 => ColorRange::Full,
+            gst_video::VideoColorRange::Range16_235 => ColorRange::Limited,
+            _ => guess.range,
+        };
+        let matrix = match colorimetry.matrix() {
+            gst_video::VideoColorMatrix::Bt601 | gst_video::VideoColorMatrix::Fcc => ColorMatrix::Bt601,
+            gst_video::VideoColorMatrix::Bt709 | gst_video::VideoColorMatrix::Smpte240m => ColorMatrix::Bt709,
+            gst_video::VideoColorMatrix::Bt2020 => ColorMatrix::Bt2020,
+            _ => guess.matrix,
+        };
+        Some(VideoColorimetry { range, matrix })
+    }
 
-                    // Cleanup
-                    let _ = pipeline.set_state(gst::State::Null);
+    /// What a `missing-plugin` message asks for, in words; None for
+    /// other element messages
+    fn missing_plugin(s: &gst::StructureRef) -> Option<String> {
+        if !s.has_name("missing-plugin") {
+            return None;
+        }
+        let kind = s.get::<String>("type").unwrap_or_default();
+        let detail = match s.value("detail") {
+            Ok(v) => v
+                .get::<gst::Caps>()
+                .map(|caps| caps.to_string())
+                .or_else(|_| v.get::<String>())
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        let name = s.get::<String>("name").ok();
+        Some(media_probe::describe_missing(&kind, &detail, name.as_deref()))
+    }
+
+    /// Find out whether the file or URI `path` can be played: open it with
+    /// uridecodebin, decode up to the first frame of each stream, and note
+    /// what was found and what plugins were missing.  Blocks for up to
+    /// `PROBE_TIMEOUT_SECONDS`.
+    pub fn probe(path: &str) -> MediaProbe {
+        if let Err(e) = gst::init() {
+            return MediaProbe::Failed(format!("cannot initialize GStreamer: {}", e));
+        }
+        let uri = if path.contains("://") {
+            path.to_string()
+        } else {
+            match gst::glib::filename_to_uri(path, None) {
+                Ok(uri) => uri.to_string(),
+                Err(e) => return MediaProbe::Failed(e.to_string()),
+            }
+        };
+        let pipeline = gst::Pipeline::new();
+        let decodebin = match gst::ElementFactory::make("uridecodebin").property("uri", &uri).build() {
+            Ok(d) => d,
+            Err(e) => return MediaProbe::Failed(e.to_string()),
+        };
+        if let Err(e) = pipeline.add(&decodebin) {
+            return MediaProbe::Failed(e.to_string());
+        }
+
+        // Each decoded stream goes to a fakesink, so the pipeline prerolls
+        let found: Arc<Mutex<(bool, bool)>> = Arc::default();
+        let pipeline_weak = pipeline.downgrade();
+        let found_streams = found.clone();
+        decodebin.connect_pad_added(move |_, pad| {
+            let Some(pipeline) = pipeline_weak.upgrade() else { return };
+            let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+            let Some(media) = caps.structure(0).map(|s| s.name().to_string()) else { return };
+            {
+                let mut found = found_streams.lock().unwrap_or_else(|e| e.into_inner());
+                found.0 |= media.starts_with("video/");
+                found.1 |= media.starts_with("audio/");
+            }
+            let Ok(sink) = gst::ElementFactory::make("fakesink").build() else { return };
+            if pipeline.add(&sink).is_ok() {
+                let _ = sink.sync_state_with_parent();
+                if let Some(sink_pad) = sink.static_pad("sink") {
+                    let _ = pad.link(&sink_pad);
                 }
-                Err(e) => {
-                    log::error!("Failed to create pipeline for video {}: {}", request.id, e);
+            }
+        });
+
+        let mut missing = Vec::new();
+        let mut failure = None;
+        let bus = pipeline.bus().expect("pipeline without bus");
+        if let Err(e) = pipeline.set_state(gst::State::Paused) {
+            // The bus says why, e.g. that the file does not exist
+            failure = Some(match bus.pop_filtered(&[gst::MessageType::Error]).as_ref().map(|m| m.view()) {
+                Some(gst::MessageView::Error(err)) => err.error().to_string(),
+                _ => e.to_string(),
+            });
+        }
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(PROBE_TIMEOUT_SECONDS);
+        while failure.is_none() {
+            let left = deadline.saturating_duration_since(std::time::Instant::now());
+            if left.is_zero() {
+                failure = Some(format!("could not decode within {} s", PROBE_TIMEOUT_SECONDS));
+                break;
+            }
+            let Some(msg) = bus.timed_pop_filtered(
+                gst::ClockTime::from_nseconds(left.as_nanos() as u64),
+                &[gst::MessageType::Error, gst::MessageType::AsyncDone, gst::MessageType::Element],
+            ) else {
+                continue;
+            };
+            match msg.view() {
+                gst::MessageView::AsyncDone(..) => break,
+                gst::MessageView::Error(err) => failure = Some(err.error().to_string()),
+                gst::MessageView::Element(element) => {
+                    if let Some(m) = element.structure().and_then(Self::missing_plugin) {
+                        missing.push(m);
+                    }
                 }
+                _ => {}
             }
         }
+        let _ = pipeline.set_state(gst::State::Null);
+
+        let (video, audio) = *found.lock().unwrap_or_else(|e| e.into_inner());
+        let playable = video || audio;
+        if !missing.is_empty() {
+            MediaProbe::MissingPlugins { playable: playable && failure.is_none(), missing }
+        } else if let Some(e) = failure {
+            MediaProbe::Failed(e)
+        } else if !playable {
+            MediaProbe::Failed("no audio or video streams".into())
+        } else {
+            MediaProbe::Playable { video, audio }
+        }
+    }
 
-        log::debug!("Video decoder thread exiting");
+    fn is_audio_pad(pad: &gst::Pad) -> bool {
+        let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+        caps.structure(0).is_some_and(|s| s.name().starts_with("audio/"))
+    }
+
+    /// Play decodebin's audio `pad` through `level` and `spectrum`, whose
+    /// reports the bus loop publishes to `audio_levels`, into the shared
+    /// `mixer` or, without one, an audio sink of the pipeline's own
+    fn link_audio(
+        pipeline: &gst::Pipeline,
+        pad: &gst::Pad,
+        volume: f32,
+        id: u32,
+        mixer: Option<&AudioMixer>,
+    ) -> Result<(), gst::glib::BoolError> {
+        let make = gst::ElementFactory::make;
+        let queue = make("queue").build()?;
+        let convert = make("audioconvert").build()?;
+        let level = make("level")
+            .property("interval", AUDIO_LEVEL_INTERVAL)
+            .property("post-messages", true)
+            .build()?;
+        let spectrum = make("spectrum")
+            .property("bands", AUDIO_SPECTRUM_BANDS)
+            .property("threshold", audio_levels::SILENCE_DB as i32)
+            .property("interval", AUDIO_LEVEL_INTERVAL)
+            .property("post-messages", true)
+            .build()?;
+        let volume = make("volume").name("volume").property("volume", volume as f64).build()?;
+        let mix_convert = make("audioconvert").build()?;
+        let resample = make("audioresample").build()?;
+        let mixed = mixer.and_then(|mixer| match mixer.add_source(id) {
+            Ok(appsink) => Some(appsink.upcast::<gst::Element>()),
+            Err(e) => {
+                log::warn!("Video {}: cannot mix audio: {}", id, e);
+                None
+            }
+        });
+        // Without an audio device the levels are still worth having
+        let sink = match mixed {
+            Some(sink) => sink,
+            None => make("autoaudiosink")
+                .build()
+                .or_else(|_| make("fakesink").property("sync", true).build())?,
+        };
+
+        let elements = [&queue, &convert, &level, &spectrum, &volume, &mix_convert, &resample, &sink];
+        pipeline.add_many(elements)?;
+        gst::Element::link_many(elements)?;
+        for element in elements {
+            element.sync_state_with_parent()?;
+        }
+        let sink_pad = queue
+            .static_pad("sink")
+            .ok_or_else(|| gst::glib::bool_error!("queue has no sink pad"))?;
+        pad.link(&sink_pad)
+            .map_err(|e| gst::glib::bool_error!("cannot link audio pad: {:?}", e))?;
+        Ok(())
+    }
+
+    /// Publish the report of a `level` or `spectrum` element of video `id`
+    fn publish_audio_levels(id: u32, s: &gst::StructureRef) {
+        if s.has_name("level") {
+            let channels = |field: &str| -> Vec<f32> {
+                s.get::<gst::glib::ValueArray>(field)
+                    .map(|values| values.iter().filter_map(|v| v.get::<f64>().ok()).map(|v| v as f32).collect())
+                    .unwrap_or_default()
+            };
+            audio_levels::publish_level(id, channels("rms"), channels("peak"));
+        } else if s.has_name("spectrum") {
+            if let Ok(magnitude) = s.get::<gst::List>("magnitude") {
+                audio_levels::publish_spectrum(id, magnitude.iter().filter_map(|v| v.get::<f32>().ok()).collect());
+            }
+        }
     }
 }
 
@@ -18,6 +18,8 @@ pub struct CachedWebKitView {
 /// Cache of WebKit view textures for wgpu rendering.
 pub struct WgpuWebKitCache {
     views: HashMap<u32, CachedWebKitView>,
+    /// Load progress (0.0 - 1.0) of views still loading a page
+    loading: HashMap<u32, f32>,
     bind_group_layout: wgpu::BindGroupLayout,
     sampler: wgpu::Sampler,
 }
@@ -58,6 +60,7 @@ impl WgpuWebKitCache {
 
         Self {
             views: HashMap::new(),
+            loading: HashMap::new(),
             bind_group_layout,
             sampler,
         }
@@ -196,13 +199,28 @@ impl WgpuWebKitCache {
         self.views.get(&view_id).map(|v| &v.bind_group)
     }
 
+    /// Record the load progress of a view, or None once it has loaded.
+    pub fn set_loading(&mut self, view_id: u32, progress: Option<f32>) {
+        match progress {
+            Some(progress) => self.loading.insert(view_id, progress.clamp(0.0, 1.0)),
+            None => self.loading.remove(&view_id),
+        };
+    }
+
+    /// Load progress of a view that is still loading.
+    pub fn loading(&self, view_id: u32) -> Option<f32> {
+        self.loading.get(&view_id).copied()
+    }
+
     /// Remove a view.
     pub fn remove(&mut self, view_id: u32) {
         self.views.remove(&view_id);
+        self.loading.remove(&view_id);
     }
 
     /// Clear all cached views.
     pub fn clear(&mut self) {
         self.views.clear();
+        self.loading.clear();
     }
 }
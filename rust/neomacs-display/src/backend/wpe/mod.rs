@@ -26,12 +26,18 @@ mod dmabuf;
 #[cfg(feature = "wpe-webkit")]
 mod view_cache;
 
+#[cfg(feature = "wpe-webkit")]
+mod profile;
+
 #[cfg(feature = "wpe-webkit")]
 pub use backend::WpeBackend;
 
 #[cfg(feature = "wpe-webkit")]
 pub use view_cache::WebKitViewCache;
 
+#[cfg(feature = "wpe-webkit")]
+pub use profile::{WebKitProfiles, WebKitProfileConfig, DEFAULT_PROFILE};
+
 #[cfg(feature = "wpe-webkit")]
 pub use view::{WpeWebView, WpeViewState, DmaBufData, RawPixelData, set_new_window_callback, NewWindowCallback, set_load_callback, LoadCallback};
 
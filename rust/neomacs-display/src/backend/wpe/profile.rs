@@ -0,0 +1,278 @@
+//! Named WebKit data profiles.
+//!
+//! A profile owns a `WebKitNetworkSession` with its own data directory
+//! (cookies, local storage, IndexedDB) and cache directory, so views
+//! created in different profiles never share login state.  Ephemeral
+//! profiles keep everything in memory and forget it when destroyed.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::path::PathBuf;
+use std::ptr;
+
+use crate::core::error::{DisplayError, DisplayResult};
+
+use super::sys::webkit as wk;
+use super::sys::platform as plat;
+
+/// Name of the profile used when a view does not ask for one.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Cookie database file name inside a profile's data directory.
+const COOKIE_DB_NAME: &str = "cookies.sqlite";
+
+/// Configuration for a named WebKit profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebKitProfileConfig {
+    /// Profile name (used to select the profile when creating views)
+    pub name: String,
+    /// Directory for persistent website data, None = derive from name
+    pub data_dir: Option<PathBuf>,
+    /// Directory for the HTTP/resource cache, None = derive from name
+    pub cache_dir: Option<PathBuf>,
+    /// Keep all website data in memory only (private browsing)
+    pub ephemeral: bool,
+}
+
+impl WebKitProfileConfig {
+    /// Create a persistent profile with default directories.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            data_dir: None,
+            cache_dir: None,
+            ephemeral: false,
+        }
+    }
+
+    /// Resolved data directory for this profile.
+    ///
+    /// Defaults to `$XDG_DATA_HOME/neomacs/webkit/<name>`.
+    pub fn resolved_data_dir(&self) -> PathBuf {
+        self.data_dir.clone().unwrap_or_else(|| {
+            xdg_dir("XDG_DATA_HOME", ".local/share")
+                .join("neomacs")
+                .join("webkit")
+                .join(encode_name(&self.name))
+        })
+    }
+
+    /// Resolved cache directory for this profile.
+    ///
+    /// Defaults to `$XDG_CACHE_HOME/neomacs/webkit/<name>`.
+    pub fn resolved_cache_dir(&self) -> PathBuf {
+        self.cache_dir.clone().unwrap_or_else(|| {
+            xdg_dir("XDG_CACHE_HOME", ".cache")
+                .join("neomacs")
+                .join("webkit")
+                .join(encode_name(&self.name))
+        })
+    }
+}
+
+/// Resolve an XDG base directory, falling back to `$HOME/<fallback>`.
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var_os("HOME").unwrap_or_else(|| "/tmp".into());
+            PathBuf::from(home).join(fallback)
+        }
+    }
+}
+
+/// Encode a profile name as a single path component.
+///
+/// ASCII letters, digits, '-', '_' and '.' stand for themselves and every
+/// other byte is written as %XX, as is a leading '.', so the encoding can
+/// be reversed: no two names share a directory, and none is "." or "..".
+fn encode_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for (i, byte) in name.bytes().enumerate() {
+        let plain = byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || (byte == b'.' && i > 0);
+        if plain {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// A live profile: its configuration and the network session backing it.
+struct WebKitProfile {
+    config: WebKitProfileConfig,
+    /// Owned reference to the session, created lazily on first use
+    session: *mut wk::WebKitNetworkSession,
+}
+
+/// Registry of named profiles, owned by the render thread.
+pub struct WebKitProfiles {
+    profiles: HashMap<String, WebKitProfile>,
+}
+
+impl WebKitProfiles {
+    pub fn new() -> Self {
+        let mut profiles = HashMap::new();
+        profiles.insert(
+            DEFAULT_PROFILE.to_string(),
+            WebKitProfile {
+                config: WebKitProfileConfig::new(DEFAULT_PROFILE),
+                session: ptr::null_mut(),
+            },
+        );
+        Self { profiles }
+    }
+
+    /// Define (or redefine) a profile.
+    ///
+    /// Redefining a profile that already has a live session keeps the old
+    /// session for existing views; new views pick up the new settings.
+    /// Profiles must have a name.
+    pub fn define(&mut self, config: WebKitProfileConfig) -> DisplayResult<()> {
+        if config.name.is_empty() {
+            return Err(DisplayError::WebKit("WebKit profile needs a name".into()));
+        }
+        log::info!(
+            "WebKit profile '{}': ephemeral={} data={:?} cache={:?}",
+            config.name, config.ephemeral, config.data_dir, config.cache_dir
+        );
+        if let Some(old) = self.profiles.remove(&config.name) {
+            old.release();
+        }
+        self.profiles.insert(
+            config.name.clone(),
+            WebKitProfile { config, session: ptr::null_mut() },
+        );
+        Ok(())
+    }
+
+    /// Whether a profile with this name has been defined.
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    /// Get the network session for a profile, creating it on first use.
+    ///
+    /// `None` selects the default profile.
+    pub fn session_for(&mut self, name: Option<&str>) -> DisplayResult<*mut wk::WebKitNetworkSession> {
+        let name = name.unwrap_or(DEFAULT_PROFILE);
+        let profile = self.profiles.get_mut(name)
+            .ok_or_else(|| DisplayError::WebKit(format!("Unknown WebKit profile '{}'", name)))?;
+
+        if profile.session.is_null() {
+            profile.session = unsafe { create_session(&profile.config)? };
+        }
+        Ok(profile.session)
+    }
+}
+
+impl Default for WebKitProfiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebKitProfile {
+    fn release(self) {
+        if !self.session.is_null() {
+            unsafe { plat::g_object_unref(self.session as *mut _) };
+        }
+    }
+}
+
+impl Drop for WebKitProfiles {
+    fn drop(&mut self) {
+        for (_, profile) in self.profiles.drain() {
+            profile.release();
+        }
+    }
+}
+
+/// Create a network session for the given profile configuration.
+unsafe fn create_session(config: &WebKitProfileConfig) -> DisplayResult<*mut wk::WebKitNetworkSession> {
+    if config.ephemeral {
+        let session = wk::webkit_network_session_new_ephemeral();
+        if session.is_null() {
+            return Err(DisplayError::WebKit("Failed to create ephemeral network session".into()));
+        }
+        log::info!("WebKit profile '{}': created ephemeral session", config.name);
+        return Ok(session);
+    }
+
+    let data_dir = config.resolved_data_dir();
+    let cache_dir = config.resolved_cache_dir();
+    for dir in [&data_dir, &cache_dir] {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            log::warn!("WebKit profile '{}': cannot create {:?}: {}", config.name, dir, e);
+        }
+    }
+
+    let to_cstring = |p: &PathBuf| {
+        CString::new(p.to_string_lossy().as_bytes())
+            .map_err(|_| DisplayError::WebKit(format!("Invalid profile path {:?}", p)))
+    };
+    let c_data = to_cstring(&data_dir)?;
+    let c_cache = to_cstring(&cache_dir)?;
+
+    let session = wk::webkit_network_session_new(c_data.as_ptr(), c_cache.as_ptr());
+    if session.is_null() {
+        return Err(DisplayError::WebKit(format!(
+            "Failed to create network session for profile '{}'", config.name
+        )));
+    }
+
+    // Persist cookies in a SQLite database so logins survive restarts.
+    let cookie_manager = wk::webkit_network_session_get_cookie_manager(session);
+    if !cookie_manager.is_null() {
+        let c_cookies = to_cstring(&data_dir.join(COOKIE_DB_NAME))?;
+        wk::webkit_cookie_manager_set_persistent_storage(
+            cookie_manager,
+            c_cookies.as_ptr(),
+            wk::WebKitCookiePersistentStorage_WEBKIT_COOKIE_PERSISTENT_STORAGE_SQLITE,
+        );
+    }
+
+    log::info!(
+        "WebKit profile '{}': created persistent session (data={:?}, cache={:?})",
+        config.name, data_dir, cache_dir
+    );
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_dirs() {
+        let mut config = WebKitProfileConfig::new("work/../mail");
+        assert!(config.resolved_data_dir().ends_with("neomacs/webkit/work%2F..%2Fmail"));
+        assert!(config.resolved_cache_dir().ends_with("neomacs/webkit/work%2F..%2Fmail"));
+
+        config.data_dir = Some(PathBuf::from("/tmp/profile-data"));
+        assert_eq!(config.resolved_data_dir(), PathBuf::from("/tmp/profile-data"));
+
+        assert_eq!(encode_name(".."), "%2E.");
+        assert_eq!(encode_name("my.work"), "my.work");
+        assert_eq!(encode_name("café"), "caf%C3%A9");
+    }
+
+    #[test]
+    fn test_profile_names_do_not_collide() {
+        let names = [
+            "work/../mail", "work_.._mail", "work%2F..%2Fmail", "..", "%2E.", ".", "default",
+            "a b", "a_b", "a%20b", "Work", "work",
+        ];
+        let dirs: std::collections::HashSet<String> = names.iter().map(|n| encode_name(n)).collect();
+        assert_eq!(dirs.len(), names.len());
+        for dir in &dirs {
+            assert!(dir != "." && dir != ".." && !dir.contains('/'));
+        }
+
+        let mut profiles = WebKitProfiles::new();
+        assert!(profiles.define(WebKitProfileConfig::new("")).is_err());
+        assert!(profiles.define(WebKitProfileConfig::new("..")).is_ok());
+        assert!(profiles.contains("..") && !profiles.contains(""));
+    }
+}
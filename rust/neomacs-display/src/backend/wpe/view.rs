@@ -214,6 +214,9 @@ pub struct WpeWebView {
 
     /// Whether the view needs redraw
     needs_redraw: bool,
+
+    /// Whether WebKit was last told the view is visible
+    visible: bool,
 }
 
 impl WpeWebView {
@@ -225,6 +228,20 @@ impl WpeWebView {
     /// * `width` - Initial width
     /// * `height` - Initial height
     pub fn new(view_id: u32, platform_display: &WpePlatformDisplay, width: u32, height: u32) -> DisplayResult<Self> {
+        Self::new_with_session(view_id, platform_display, ptr::null_mut(), width, height)
+    }
+
+    /// Create a new WPE WebKit view bound to a specific network session.
+    ///
+    /// The session determines where cookies, local storage and cache live
+    /// (see `WebKitProfiles`).  A null session uses WebKit's default session.
+    pub fn new_with_session(
+        view_id: u32,
+        platform_display: &WpePlatformDisplay,
+        session: *mut wk::WebKitNetworkSession,
+        width: u32,
+        height: u32,
+    ) -> DisplayResult<Self> {
         log::info!("WpeWebView::new (Platform API) called with id={}, {}x{}", view_id, width, height);
 
         let display = platform_display.raw();
@@ -238,8 +255,12 @@ impl WpeWebView {
         log::debug!("WpeWebView::new: DmaBufExporter created");
 
         unsafe {
-            // Create WebKitNetworkSession (required for WPE Platform)
-            let network_session = wk::webkit_network_session_get_default();
+            // Use the profile's WebKitNetworkSession, or the default one (required for WPE Platform)
+            let network_session = if session.is_null() {
+                wk::webkit_network_session_get_default()
+            } else {
+                session
+            };
             log::debug!("WpeWebView::new: network_session={:?}", network_session);
 
             // Create WebKitWebContext
@@ -251,11 +272,15 @@ impl WpeWebView {
             // falling back to wpe_display_get_default() which may differ on multi-GPU systems.
             log::debug!("WpeWebView::new: creating WebKitWebView with WPE Platform display {:?}...", display);
 
+            // "network-session" is also construct-only; it selects the profile's data directories.
             let display_prop = CString::new("display").unwrap();
+            let session_prop = CString::new("network-session").unwrap();
             let web_view = plat::g_object_new(
                 wk::webkit_web_view_get_type(),
                 display_prop.as_ptr(),
                 display as *mut libc::c_void,
+                session_prop.as_ptr(),
+                network_session as *mut libc::c_void,
                 ptr::null::<libc::c_char>(),
             ) as *mut wk::WebKitWebView;
             log::debug!("WpeWebView::new: web_view={:?}", web_view);
@@ -407,6 +432,7 @@ impl WpeWebView {
                 load_changed_handler_id,
                 dmabuf_exporter,
                 needs_redraw: false,
+                visible: true,
             })
         }
     }
@@ -599,6 +625,18 @@ impl WpeWebView {
         self.needs_redraw
     }
 
+    /// Tell WebKit whether the view can be seen.  Hidden views stop
+    /// running animation frames and throttle their timers.
+    pub fn set_visible(&mut self, visible: bool) {
+        if visible == self.visible {
+            return;
+        }
+        self.visible = visible;
+        unsafe {
+            plat::wpe_view_set_visible(self.wpe_view, if visible { 1 } else { 0 });
+        }
+    }
+
     /// Clear redraw flag
     pub fn clear_redraw_flag(&mut self) {
         self.needs_redraw = false;
@@ -0,0 +1,32 @@
+//! Standalone display server for out-of-process frontends.
+//!
+//! Usage: `neomacs-display-server [--stdio | SOCKET]`
+//!
+//! Listens on SOCKET (default `$XDG_RUNTIME_DIR/neomacs-display.sock`) and
+//! serves the JSON-RPC protocol described in `neomacs_display::remote`.
+//! With `--stdio`, serves a single client on stdin/stdout instead; this is
+//! how Emacs runs it as an isolated renderer process.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+fn main() {
+    let _ = env_logger::try_init();
+    let arg = std::env::args_os().nth(1);
+    if arg.as_deref() == Some("--stdio".as_ref()) {
+        let out: neomacs_display::remote::protocol::SharedWriter =
+            Arc::new(Mutex::new(std::io::stdout()));
+        if let Err(e) = neomacs_display::remote::serve_io(std::io::stdin().lock(), out) {
+            eprintln!("neomacs-display-server: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let path = arg
+        .map(PathBuf::from)
+        .unwrap_or_else(neomacs_display::remote::default_socket_path);
+    if let Err(e) = neomacs_display::remote::serve(&path) {
+        eprintln!("neomacs-display-server: {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,41 @@
+//! Viewer for frames recorded with `neomacs-replay-start`.
+//!
+//! Usage: `neomacs-replay [--loop] [--speed N] FILE`
+//!
+//! Draws the recorded frames in a window at the pace they were recorded,
+//! N times faster with `--speed`, and keeps the last one on screen until
+//! the window is closed.  See `neomacs_display::remote::replay`.
+
+use std::path::PathBuf;
+
+use neomacs_display::remote::replay::{self, PlayOptions};
+
+fn usage() -> ! {
+    eprintln!("usage: neomacs-replay [--loop] [--speed N] FILE");
+    std::process::exit(2);
+}
+
+fn main() {
+    let _ = env_logger::try_init();
+    let mut options = PlayOptions::default();
+    let mut path = None;
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--loop") => options.repeat = true,
+            Some("--speed") => {
+                options.speed = match args.next().and_then(|s| s.to_str()?.parse().ok()) {
+                    Some(speed) if speed > 0.0 => speed,
+                    _ => usage(),
+                }
+            }
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => usage(),
+        }
+    }
+    let Some(path) = path else { usage() };
+    if let Err(e) = replay::play(&path, options) {
+        eprintln!("neomacs-replay: {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,442 @@
+//! Accessibility snapshot of a rendered frame.
+//!
+//! Text is drawn on the GPU, so assistive technology cannot read it from
+//! the window.  This module rebuilds what is on screen from the
+//! `FrameGlyphBuffer` — per-window text lines, cursor position and role —
+//! so a platform bridge can publish it to screen readers.
+//!
+//! The same snapshot linearizes to plain text, one section per window
+//! with the cursor and the selected text marked, for text-only mirrors
+//! and for tests asserting on what is actually on screen.
+
+use std::ops::Range;
+
+use super::frame_glyphs::{FrameGlyph, FrameGlyphBuffer, WindowInfo};
+use super::types::{Color, Rect};
+
+/// Marks the cursor in exported text
+pub const CURSOR_MARK: char = '\u{2038}';
+
+/// Mark the start and end of selected text in exported text
+pub const SELECTION_START: char = '\u{ab}';
+pub const SELECTION_END: char = '\u{bb}';
+
+/// Role of an accessible region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessibleRole {
+    /// Buffer text of an ordinary window
+    TextArea,
+    /// The minibuffer / echo area
+    Minibuffer,
+    /// A window's mode line
+    ModeLine,
+}
+
+impl AccessibleRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TextArea => "text-area",
+            Self::Minibuffer => "minibuffer",
+            Self::ModeLine => "mode-line",
+        }
+    }
+}
+
+/// One visual line of text
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibleLine {
+    pub text: String,
+    /// Frame-absolute bounds of the line
+    pub bounds: Rect,
+    /// Selected characters, as ranges of columns
+    pub selected: Vec<Range<usize>>,
+}
+
+impl AccessibleLine {
+    /// The text with the selection marked, and the cursor at `cursor`
+    pub fn marked(&self, cursor: Option<usize>) -> String {
+        let mut out = String::with_capacity(self.text.len() + 4);
+        let mut chars = self.text.chars();
+        for column in 0.. {
+            if self.selected.iter().any(|r| r.end == column) {
+                out.push(SELECTION_END);
+            }
+            if cursor == Some(column) {
+                out.push(CURSOR_MARK);
+            }
+            if self.selected.iter().any(|r| r.start == column) {
+                out.push(SELECTION_START);
+            }
+            match chars.next() {
+                Some(c) => out.push(c),
+                None => break,
+            }
+        }
+        out
+    }
+}
+
+/// Cursor location inside a region, in visual lines and characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessibleCursor {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An accessible text region (window body or mode line)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibleRegion {
+    /// Emacs window this region belongs to
+    pub window_id: i64,
+    pub role: AccessibleRole,
+    /// Label for the region (buffer file name, if any)
+    pub name: String,
+    pub bounds: Rect,
+    pub lines: Vec<AccessibleLine>,
+    pub cursor: Option<AccessibleCursor>,
+    /// Whether this region belongs to the selected window
+    pub focused: bool,
+}
+
+impl AccessibleRegion {
+    /// Full text of the region, lines joined with newlines
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(&line.text);
+        }
+        out
+    }
+
+    /// Text of the region with the cursor and selection marked
+    pub fn marked_text(&self) -> String {
+        let mut out = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let cursor = self.cursor.filter(|c| c.line == i).map(|c| c.column);
+            out.push_str(&line.marked(cursor));
+        }
+        out
+    }
+}
+
+/// How selected text is drawn: the region face, or a face merged from it,
+/// which keeps its background
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionFace {
+    pub face_id: u32,
+    pub background: Option<Color>,
+}
+
+impl SelectionFace {
+    fn matches(&self, face_id: u32, bg: Option<&Color>) -> bool {
+        face_id == self.face_id || self.background.is_some_and(|s| bg == Some(&s))
+    }
+}
+
+/// Accessible contents of a whole frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccessibilitySnapshot {
+    pub regions: Vec<AccessibleRegion>,
+}
+
+/// A positioned text fragment collected from the glyph buffer
+struct Fragment<'a> {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    text: FragmentText<'a>,
+    selected: bool,
+}
+
+enum FragmentText<'a> {
+    Char(char),
+    Composed(&'a str),
+    /// Stretch glyph, rendered as this many spaces
+    Space(usize),
+}
+
+impl AccessibilitySnapshot {
+    /// Rebuild the accessible text of every window from the frame's glyphs.
+    pub fn from_frame(frame: &FrameGlyphBuffer) -> Self {
+        Self::from_frame_selecting(frame, None)
+    }
+
+    /// Like `from_frame`, also noting the text drawn in `selection`
+    pub fn from_frame_selecting(frame: &FrameGlyphBuffer, selection: Option<SelectionFace>) -> Self {
+        let selected = |face_id: u32, bg: Option<&Color>| selection.is_some_and(|s| s.matches(face_id, bg));
+        let char_width = frame.char_width.max(1.0);
+        let mut fragments: Vec<Fragment> = Vec::new();
+        let mut cursors: Vec<Rect> = Vec::new();
+
+        for glyph in &frame.glyphs {
+            match glyph {
+                FrameGlyph::Char { char, composed, x, y, width, height, bg, face_id, .. } => {
+                    let text = match composed {
+                        Some(s) => FragmentText::Composed(s),
+                        None => FragmentText::Char(*char),
+                    };
+                    fragments.push(Fragment {
+                        x: *x, y: *y, width: *width, height: *height,
+                        text,
+                        selected: selected(*face_id, bg.as_ref()),
+                    });
+                }
+                FrameGlyph::Stretch { x, y, width, height, bg, face_id, .. } => {
+                    let count = (*width / char_width).round() as usize;
+                    if count > 0 {
+                        fragments.push(Fragment {
+                            x: *x, y: *y, width: *width, height: *height,
+                            text: FragmentText::Space(count),
+                            selected: selected(*face_id, Some(bg)),
+                        });
+                    }
+                }
+                FrameGlyph::Cursor { x, y, width, height, .. } => {
+                    cursors.push(Rect::new(*x, *y, *width, *height));
+                }
+                _ => {}
+            }
+        }
+
+        let mut regions = Vec::new();
+        for info in &frame.window_infos {
+            let body_height = (info.bounds.height - info.mode_line_height).max(0.0);
+            let body = Rect::new(info.bounds.x, info.bounds.y, info.bounds.width, body_height);
+            let role = if info.is_minibuffer { AccessibleRole::Minibuffer } else { AccessibleRole::TextArea };
+            regions.push(build_region(info, role, body, &fragments, &cursors));
+
+            if info.mode_line_height > 0.0 {
+                let mode_line = Rect::new(
+                    info.bounds.x,
+                    info.bounds.y + body_height,
+                    info.bounds.width,
+                    info.mode_line_height,
+                );
+                let mut region = build_region(info, AccessibleRole::ModeLine, mode_line, &fragments, &[]);
+                region.focused = false;
+                regions.push(region);
+            }
+        }
+
+        Self { regions }
+    }
+
+    /// The region holding keyboard focus (selected window body), if any
+    pub fn focused(&self) -> Option<&AccessibleRegion> {
+        self.regions.iter().find(|r| r.focused)
+    }
+
+    /// The frame as plain text: for each region a header line naming its
+    /// window, role and buffer, then its lines with the cursor and the
+    /// selection marked
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for region in &self.regions {
+            out.push_str(&format!("-- window {} {}", region.window_id, region.role.as_str()));
+            if region.focused {
+                out.push_str(" focused");
+            }
+            if !region.name.is_empty() {
+                out.push_str(&format!(" {:?}", region.name));
+            }
+            out.push('\n');
+            for (i, line) in region.lines.iter().enumerate() {
+                let cursor = region.cursor.filter(|c| c.line == i).map(|c| c.column);
+                out.push_str(&line.marked(cursor));
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// Whether the top-left corner of a glyph lies inside `area`
+fn starts_in(area: &Rect, x: f32, y: f32) -> bool {
+    x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+}
+
+fn build_region(
+    info: &WindowInfo,
+    role: AccessibleRole,
+    area: Rect,
+    fragments: &[Fragment],
+    cursors: &[Rect],
+) -> AccessibleRegion {
+    let mut inside: Vec<&Fragment> = fragments
+        .iter()
+        .filter(|f| starts_in(&area, f.x, f.y))
+        .collect();
+    inside.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    // Group fragments into visual lines by row position
+    let mut lines: Vec<AccessibleLine> = Vec::new();
+    let mut line_columns: Vec<Vec<(f32, usize)>> = Vec::new();
+    for fragment in inside {
+        let same_row = lines.last().is_some_and(|l| (l.bounds.y - fragment.y).abs() < 0.5);
+        if !same_row {
+            lines.push(AccessibleLine {
+                text: String::new(),
+                bounds: Rect::new(fragment.x, fragment.y, 0.0, fragment.height),
+                selected: Vec::new(),
+            });
+            line_columns.push(Vec::new());
+        }
+        let line = lines.last_mut().unwrap();
+        let columns = line_columns.last_mut().unwrap();
+        let start = line.text.chars().count();
+        columns.push((fragment.x, start));
+        let len = match fragment.text {
+            FragmentText::Char(c) => {
+                line.text.push(c);
+                1
+            }
+            FragmentText::Composed(s) => {
+                line.text.push_str(s);
+                s.chars().count()
+            }
+            FragmentText::Space(n) => {
+                line.text.extend(std::iter::repeat_n(' ', n));
+                n
+            }
+        };
+        if fragment.selected {
+            match line.selected.last_mut() {
+                Some(range) if range.end == start => range.end += len,
+                _ => line.selected.push(start..start + len),
+            }
+        }
+        line.bounds.width = fragment.x + fragment.width - line.bounds.x;
+        line.bounds.height = line.bounds.height.max(fragment.height);
+    }
+
+    // Trailing padding is layout, not content
+    for line in &mut lines {
+        let trimmed = line.text.trim_end_matches(' ').len();
+        line.text.truncate(trimmed);
+        let len = line.text.chars().count();
+        for range in &mut line.selected {
+            range.end = range.end.min(len);
+        }
+        line.selected.retain(|range| !range.is_empty());
+    }
+
+    let cursor = cursors
+        .iter()
+        .find(|c| starts_in(&area, c.x, c.y))
+        .map(|c| {
+            let line = lines
+                .iter()
+                .position(|l| c.y < l.bounds.y + l.bounds.height)
+                .unwrap_or(lines.len().saturating_sub(1));
+            let column = line_columns
+                .get(line)
+                .and_then(|cols| cols.iter().rev().find(|(x, _)| *x <= c.x + 0.5))
+                .map_or(0, |&(_, col)| col)
+                .min(lines.get(line).map_or(0, |l| l.text.chars().count()));
+            AccessibleCursor { line, column }
+        });
+
+    AccessibleRegion {
+        window_id: info.window_id,
+        role,
+        name: info.buffer_file_name.clone(),
+        bounds: area,
+        lines,
+        cursor,
+        focused: info.selected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::Color;
+
+    fn add_text(frame: &mut FrameGlyphBuffer, text: &str, x: f32, y: f32, is_overlay: bool) {
+        for (i, c) in text.chars().enumerate() {
+            frame.add_char(c, x + i as f32 * 8.0, y, 8.0, 16.0, 12.0, is_overlay);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_lines_cursor_and_mode_line() {
+        let mut frame = FrameGlyphBuffer::with_size(160.0, 64.0);
+        frame.char_width = 8.0;
+        frame.add_window_info(1, 10, 1, 20, 20, 0.0, 0.0, 160.0, 48.0, 16.0,
+                              true, false, 16.0, "/tmp/a.txt".into(), false);
+        add_text(&mut frame, "hello", 0.0, 0.0, false);
+        frame.add_stretch(40.0, 0.0, 120.0, 16.0, Color::BLACK, 0, false);
+        add_text(&mut frame, "world", 0.0, 16.0, false);
+        add_text(&mut frame, "-UU- a.txt", 0.0, 32.0, true);
+        frame.add_cursor(1, 24.0, 16.0, 8.0, 16.0, 0, Color::WHITE);
+
+        let snapshot = AccessibilitySnapshot::from_frame(&frame);
+        assert_eq!(snapshot.regions.len(), 2);
+
+        let body = snapshot.focused().unwrap();
+        assert_eq!(body.role, AccessibleRole::TextArea);
+        assert_eq!(body.text(), "hello\nworld");
+        assert_eq!(body.cursor, Some(AccessibleCursor { line: 1, column: 3 }));
+        assert_eq!(body.name, "/tmp/a.txt");
+
+        let mode_line = &snapshot.regions[1];
+        assert_eq!(mode_line.role, AccessibleRole::ModeLine);
+        assert_eq!(mode_line.text(), "-UU- a.txt");
+        assert!(mode_line.cursor.is_none());
+        assert!(!mode_line.focused);
+    }
+
+    #[test]
+    fn test_text_export_marks_cursor_and_selection() {
+        let region_bg = Color::new(0.2, 0.3, 0.8, 1.0);
+        let face = |frame: &mut FrameGlyphBuffer, id: u32, bg: Option<Color>| {
+            frame.set_face(id, Color::WHITE, bg, false, false, 0, None, 0, None, 0, None);
+        };
+        let mut frame = FrameGlyphBuffer::with_size(160.0, 64.0);
+        frame.char_width = 8.0;
+        frame.add_window_info(1, 10, 1, 20, 20, 0.0, 0.0, 160.0, 48.0, 16.0,
+                              true, false, 16.0, "/tmp/a.txt".into(), false);
+        frame.add_window_info(2, 11, 1, 1, 1, 0.0, 48.0, 160.0, 16.0, 0.0,
+                              false, true, 16.0, String::new(), false);
+        face(&mut frame, 1, None);
+        add_text(&mut frame, "he", 0.0, 0.0, false);
+        face(&mut frame, 5, Some(region_bg));
+        add_text(&mut frame, "llo", 16.0, 0.0, false);
+        frame.add_stretch(40.0, 0.0, 120.0, 16.0, region_bg, 5, false);
+        // The region merged with another face keeps its background
+        face(&mut frame, 7, Some(region_bg));
+        add_text(&mut frame, "wo", 0.0, 16.0, false);
+        face(&mut frame, 1, None);
+        add_text(&mut frame, "rld", 16.0, 16.0, false);
+        add_text(&mut frame, "-UU- a.txt", 0.0, 32.0, true);
+        add_text(&mut frame, "M-x", 0.0, 48.0, false);
+        frame.add_cursor(1, 24.0, 16.0, 8.0, 16.0, 0, Color::WHITE);
+
+        let selection = SelectionFace { face_id: 5, background: Some(region_bg) };
+        let snapshot = AccessibilitySnapshot::from_frame_selecting(&frame, Some(selection));
+        let body = snapshot.focused().unwrap();
+        assert_eq!(body.lines[0].selected, vec![2..5]);
+        assert_eq!(body.marked_text(), "he\u{ab}llo\u{bb}\n\u{ab}wo\u{bb}r\u{2038}ld");
+        assert_eq!(
+            snapshot.to_text(),
+            "-- window 1 text-area focused \"/tmp/a.txt\"\n\
+             he«llo»\n\
+             «wo»r‸ld\n\
+             -- window 1 mode-line \"/tmp/a.txt\"\n\
+             -UU- a.txt\n\
+             -- window 2 minibuffer\n\
+             M-x\n"
+        );
+
+        // Without a selection face nothing is marked selected
+        let plain = AccessibilitySnapshot::from_frame(&frame);
+        assert_eq!(plain.focused().unwrap().marked_text(), "hello\nwor\u{2038}ld");
+    }
+}
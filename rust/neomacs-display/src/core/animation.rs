@@ -1,7 +1,11 @@
 //! Animation system for smooth scrolling, cursor blink, etc.
 
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use super::clock::{system_clock, SharedClock};
+use super::error::{DisplayError, DisplayResult};
+
 /// Easing functions for animations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Easing {
@@ -9,6 +13,11 @@ pub enum Easing {
     EaseIn,
     EaseOut,
     EaseInOut,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    /// Hold the start value, then jump at the end of the segment
+    Step,
 }
 
 impl Easing {
@@ -26,7 +35,233 @@ impl Easing {
                     1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
                 }
             }
+            Easing::EaseInCubic => t * t * t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Step => if t >= 1.0 { 1.0 } else { 0.0 },
+        }
+    }
+
+    /// Parse an easing name (Lisp symbol style, e.g. "ease-out-cubic")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "linear" => Some(Easing::Linear),
+            "ease-in" | "ease-in-quad" => Some(Easing::EaseIn),
+            "ease-out" | "ease-out-quad" => Some(Easing::EaseOut),
+            "ease-in-out" | "ease-in-out-quad" => Some(Easing::EaseInOut),
+            "ease-in-cubic" => Some(Easing::EaseInCubic),
+            "ease-out-cubic" => Some(Easing::EaseOutCubic),
+            "ease-in-out-cubic" => Some(Easing::EaseInOutCubic),
+            "step" => Some(Easing::Step),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "ease-in",
+            Easing::EaseOut => "ease-out",
+            Easing::EaseInOut => "ease-in-out",
+            Easing::EaseInCubic => "ease-in-cubic",
+            Easing::EaseOutCubic => "ease-out-cubic",
+            Easing::EaseInOutCubic => "ease-in-out-cubic",
+            Easing::Step => "step",
+        }
+    }
+}
+
+/// A keyframe: the property has `value` at normalized `time` (0.0-1.0).
+/// `easing` shapes the segment arriving at this keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,
+    pub value: f32,
+    pub easing: Easing,
+}
+
+/// A keyframed curve over normalized time.
+///
+/// Curves written as strings use space-separated `TIME:VALUE[:EASING]`
+/// keyframes, e.g. `"0:0 0.7:1.1:ease-out 1:1:ease-in-out"` for an
+/// overshoot.  A bare easing name such as `"ease-out-cubic"` is shorthand
+/// for `"0:0 1:1:ease-out-cubic"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCurve {
+    keyframes: Vec<Keyframe>,
+}
+
+impl AnimationCurve {
+    /// Build a curve from keyframes (sorted by time, at least one required).
+    pub fn new(mut keyframes: Vec<Keyframe>) -> DisplayResult<Self> {
+        if keyframes.is_empty() {
+            return Err(DisplayError::Animation("curve has no keyframes".into()));
+        }
+        if let Some(k) = keyframes.iter().find(|k| !k.time.is_finite() || !k.value.is_finite()) {
+            return Err(DisplayError::Animation(format!("non-finite keyframe {}:{}", k.time, k.value)));
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Self { keyframes })
+    }
+
+    /// A 0-to-1 curve with a single easing
+    pub fn from_easing(easing: Easing) -> Self {
+        Self {
+            keyframes: vec![
+                Keyframe { time: 0.0, value: 0.0, easing: Easing::Linear },
+                Keyframe { time: 1.0, value: 1.0, easing },
+            ],
+        }
+    }
+
+    /// Parse the string form (see the type documentation)
+    pub fn parse(spec: &str) -> DisplayResult<Self> {
+        let spec = spec.trim();
+        if let Some(easing) = Easing::from_name(spec) {
+            return Ok(Self::from_easing(easing));
+        }
+
+        let keyframes = spec
+            .split_whitespace()
+            .map(|token| {
+                let mut parts = token.split(':');
+                let mut number = |what: &str| {
+                    parts.next()
+                        .and_then(|s| s.parse::<f32>().ok())
+                        .ok_or_else(|| DisplayError::Animation(format!("bad {} in keyframe '{}'", what, token)))
+                };
+                let time = number("time")?;
+                let value = number("value")?;
+                let easing = match parts.next() {
+                    None => Easing::Linear,
+                    Some(name) => Easing::from_name(name)
+                        .ok_or_else(|| DisplayError::Animation(format!("unknown easing '{}'", name)))?,
+                };
+                if parts.next().is_some() {
+                    return Err(DisplayError::Animation(format!("trailing data in keyframe '{}'", token)));
+                }
+                Ok(Keyframe { time, value, easing })
+            })
+            .collect::<DisplayResult<Vec<_>>>()?;
+        Self::new(keyframes)
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Value at normalized time `t`; held constant outside the keyframes
+    pub fn sample(&self, t: f32) -> f32 {
+        let first = &self.keyframes[0];
+        if t <= first.time {
+            return first.value;
+        }
+        for pair in self.keyframes.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if t <= b.time {
+                let span = b.time - a.time;
+                let local = if span > 0.0 { (t - a.time) / span } else { 1.0 };
+                return a.value + (b.value - a.value) * b.easing.apply(local);
+            }
+        }
+        self.keyframes[self.keyframes.len() - 1].value
+    }
+
+    /// Serialize to the string form accepted by `parse`
+    pub fn to_spec(&self) -> String {
+        self.keyframes
+            .iter()
+            .map(|k| format!("{}:{}:{}", k.time, k.value, k.easing.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Animation targets that consult a named curve, if one is defined
+pub const CURVE_TARGETS: &[&str] = &["cursor", "cursor-size", "crossfade", "scroll"];
+
+/// Named curves overriding built-in easing (set from Lisp)
+#[derive(Debug, Clone, Default)]
+pub struct AnimationCurves {
+    curves: HashMap<String, AnimationCurve>,
+}
+
+impl AnimationCurves {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define curve `name` from its string form, or remove it when `spec`
+    /// is empty.  Unknown names are accepted so hosts can use them for
+    /// their own `KeyframeAnimation`s.
+    pub fn set_option(&mut self, name: &str, spec: &str) -> DisplayResult<()> {
+        if spec.trim().is_empty() {
+            self.curves.remove(name);
+        } else {
+            self.curves.insert(name.to_string(), AnimationCurve::parse(spec)?);
         }
+        Ok(())
+    }
+
+    pub fn set(&mut self, name: &str, curve: Option<AnimationCurve>) {
+        match curve {
+            Some(curve) => { self.curves.insert(name.to_string(), curve); }
+            None => { self.curves.remove(name); }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AnimationCurve> {
+        self.curves.get(name)
+    }
+
+    /// String form of curve `name` (for Lisp integration)
+    pub fn get_option(&self, name: &str) -> Option<String> {
+        self.curves.get(name).map(AnimationCurve::to_spec)
+    }
+
+    /// Map linear progress `t` through curve `name`, if defined
+    pub fn remap(&self, name: &str, t: f32) -> Option<f32> {
+        self.curves.get(name).map(|c| c.sample(t))
+    }
+}
+
+/// An f32 property animated along a keyframed curve
+#[derive(Debug, Clone)]
+pub struct KeyframeAnimation {
+    pub curve: AnimationCurve,
+    pub duration: Duration,
+    pub start_time: Instant,
+    pub completed: bool,
+}
+
+impl KeyframeAnimation {
+    pub fn new(curve: AnimationCurve, duration: Duration) -> Self {
+        Self {
+            curve,
+            duration,
+            start_time: Instant::now(),
+            completed: false,
+        }
+    }
+
+    /// Property value at time `now`
+    pub fn value_at(&mut self, now: Instant) -> f32 {
+        let elapsed = now.duration_since(self.start_time);
+        if elapsed >= self.duration {
+            self.completed = true;
+            return self.curve.sample(1.0);
+        }
+        self.curve.sample(elapsed.as_secs_f32() / self.duration.as_secs_f32())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed
     }
 }
 
@@ -91,6 +326,204 @@ impl Animation {
     }
 }
 
+/// Kind of floating element a property animation targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatingKind {
+    Image = 0,
+    WebKit = 1,
+    Terminal = 2,
+}
+
+impl FloatingKind {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Image),
+            1 => Some(Self::WebKit),
+            2 => Some(Self::Terminal),
+            _ => None,
+        }
+    }
+
+    /// Whether this kind of element has `property`.  Terminals are sized
+    /// by their grid and drawn without corner rounding.
+    pub fn supports(&self, property: FloatingProperty) -> bool {
+        match self {
+            Self::Terminal => matches!(
+                property,
+                FloatingProperty::X | FloatingProperty::Y | FloatingProperty::Opacity
+            ),
+            _ => true,
+        }
+    }
+}
+
+/// Animatable property of a floating element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatingProperty {
+    X,
+    Y,
+    Width,
+    Height,
+    Opacity,
+    CornerRadius,
+}
+
+impl FloatingProperty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "x" => Some(Self::X),
+            "y" => Some(Self::Y),
+            "width" => Some(Self::Width),
+            "height" => Some(Self::Height),
+            "opacity" | "alpha" => Some(Self::Opacity),
+            "corner-radius" | "radius" => Some(Self::CornerRadius),
+            _ => None,
+        }
+    }
+}
+
+/// One running floating-element property animation
+#[derive(Debug, Clone)]
+struct FloatingPropertyAnimation {
+    kind: FloatingKind,
+    id: u32,
+    property: FloatingProperty,
+    animation: Animation,
+    /// Id reported when the animation ends, or 0
+    notify: u32,
+}
+
+/// Property animations of floating images, WebKit views and terminals,
+/// driven by the render thread's tick so hosts only send the target value.
+#[derive(Debug, Clone, Default)]
+pub struct FloatingAnimations {
+    active: Vec<FloatingPropertyAnimation>,
+    /// Notify ids of animations that ended since `take_finished`
+    finished: Vec<u32>,
+}
+
+impl FloatingAnimations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Animate `property` of element (`kind`, `id`) from `from` to `to`,
+    /// replacing any animation already running on that property.
+    pub fn start(
+        &mut self,
+        kind: FloatingKind,
+        id: u32,
+        property: FloatingProperty,
+        from: f32,
+        to: f32,
+        duration: Duration,
+        easing: Easing,
+    ) {
+        self.end_where(|a| a.kind == kind && a.id == id && a.property == property);
+        self.active.push(FloatingPropertyAnimation {
+            kind,
+            id,
+            property,
+            animation: Animation::new(from, to, duration, easing),
+            notify: 0,
+        });
+    }
+
+    /// Report `notify` when the animation of `property` ends, replaced or
+    /// cancelled included; at once if none is running
+    pub fn notify(&mut self, kind: FloatingKind, id: u32, property: FloatingProperty, notify: u32) {
+        match self.active.iter_mut().find(|a| a.kind == kind && a.id == id && a.property == property) {
+            Some(a) => a.notify = notify,
+            None => self.finished.push(notify),
+        }
+    }
+
+    /// Stop all animations of an element (e.g. when it is removed)
+    pub fn cancel(&mut self, kind: FloatingKind, id: u32) {
+        self.end_where(|a| a.kind == kind && a.id == id);
+    }
+
+    fn end_where(&mut self, ended: impl Fn(&FloatingPropertyAnimation) -> bool) {
+        let finished = &mut self.finished;
+        self.active.retain(|a| {
+            if !ended(a) {
+                return true;
+            }
+            if a.notify != 0 {
+                finished.push(a.notify);
+            }
+            false
+        });
+    }
+
+    /// Number of properties being animated
+    pub fn running(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Notify ids of the animations that ended since the last call
+    pub fn take_finished(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Current value of every animated property at `now`.  Finished
+    /// animations report their final value once and are then dropped.
+    pub fn tick(&mut self, now: Instant) -> Vec<(FloatingKind, u32, FloatingProperty, f32)> {
+        let values = self
+            .active
+            .iter_mut()
+            .map(|a| (a.kind, a.id, a.property, a.animation.value_at(now)))
+            .collect();
+        self.end_where(|a| a.animation.is_complete());
+        values
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.active.is_empty()
+    }
+}
+
+/// Animations made of several parts, such as a transition of every
+/// window, each reported by its notify id once no part is running
+#[derive(Debug, Clone)]
+pub struct CompletionWatch<K> {
+    pending: Vec<(u32, Vec<K>)>,
+}
+
+impl<K> Default for CompletionWatch<K> {
+    fn default() -> Self {
+        Self { pending: Vec::new() }
+    }
+}
+
+impl<K> CompletionWatch<K> {
+    /// Watch the `parts` of the animation reported as `notify`; an
+    /// animation without parts is reported on the next `finished`
+    pub fn watch(&mut self, notify: u32, parts: Vec<K>) {
+        if notify != 0 {
+            self.pending.push((notify, parts));
+        }
+    }
+
+    /// Notify ids of the watched animations no part of which is still
+    /// `running`
+    pub fn finished(&mut self, mut running: impl FnMut(&K) -> bool) -> Vec<u32> {
+        let mut done = Vec::new();
+        self.pending.retain_mut(|(notify, parts)| {
+            parts.retain(&mut running);
+            if parts.is_empty() {
+                done.push(*notify);
+            }
+            !parts.is_empty()
+        });
+        done
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
 /// Animation manager handles all active animations
 #[derive(Debug)]
 pub struct AnimationManager {
@@ -104,6 +537,9 @@ pub struct AnimationManager {
 
     /// Frame time tracking
     last_frame_time: Option<Instant>,
+
+    /// Time source for scrolling and blinking
+    clock: SharedClock,
 }
 
 impl Default for AnimationManager {
@@ -114,12 +550,18 @@ impl Default for AnimationManager {
 
 impl AnimationManager {
     pub fn new() -> Self {
+        Self::with_clock(system_clock())
+    }
+
+    /// A manager reading the time from `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
             scroll_animations: Vec::new(),
             cursor_blink_on: true,
-            last_cursor_toggle: Instant::now(),
+            last_cursor_toggle: clock.now(),
             cursor_blink_interval: Duration::from_millis(530),
             last_frame_time: None,
+            clock,
         }
     }
 
@@ -128,19 +570,20 @@ impl AnimationManager {
         // Remove any existing scroll animation for this window
         self.scroll_animations.retain(|(id, _)| *id != window_id);
 
-        let animation = Animation::new(
+        let mut animation = Animation::new(
             from,
             to,
             Duration::from_millis(150),
             Easing::EaseOut,
         );
+        animation.start_time = self.clock.now();
 
         self.scroll_animations.push((window_id, animation));
     }
 
     /// Get current scroll offset for a window (returns None if no animation)
     pub fn get_scroll_offset(&mut self, window_id: i32) -> Option<f32> {
-        let now = Instant::now();
+        let now = self.clock.now();
 
         for (id, anim) in &mut self.scroll_animations {
             if *id == window_id {
@@ -153,7 +596,7 @@ impl AnimationManager {
 
     /// Update all animations, returns true if any animation is active
     pub fn tick(&mut self) -> bool {
-        let now = Instant::now();
+        let now = self.clock.now();
         self.last_frame_time = Some(now);
 
         // Update cursor blink
@@ -177,7 +620,7 @@ impl AnimationManager {
     /// Reset cursor blink (call when cursor moves)
     pub fn reset_cursor_blink(&mut self) {
         self.cursor_blink_on = true;
-        self.last_cursor_toggle = Instant::now();
+        self.last_cursor_toggle = self.clock.now();
     }
 
     /// Set cursor blink interval
@@ -222,4 +665,111 @@ mod tests {
         assert_eq!(v3, 100.0);
         assert!(anim.is_complete());
     }
+
+    #[test]
+    fn test_scroll_steps_with_scripted_clock() {
+        let clock = crate::core::clock::ScriptedClock::new();
+        let mut manager = AnimationManager::with_clock(clock.shared());
+        manager.animate_scroll(1, 0.0, 100.0);
+        assert_eq!(manager.get_scroll_offset(1), Some(0.0));
+
+        // Same steps, same offsets, however slow the machine
+        let mut offsets = Vec::new();
+        for _ in 0..3 {
+            clock.advance(Duration::from_millis(50));
+            offsets.push(manager.get_scroll_offset(1).unwrap());
+            manager.tick();
+        }
+        let expected = Easing::EaseOut.apply(1.0 / 3.0) * 100.0;
+        assert!((offsets[0] - expected).abs() < 1e-3);
+        assert!(offsets[0] < offsets[1]);
+        assert_eq!(offsets[2], 100.0);
+        assert!(!manager.has_active_animations());
+
+        // Blinking follows the same clock
+        assert!(manager.cursor_visible());
+        clock.advance(Duration::from_millis(530));
+        manager.tick();
+        assert!(!manager.cursor_visible());
+    }
+
+    #[test]
+    fn test_curve_parse_and_sample() {
+        let curve = AnimationCurve::parse("0:0 0.5:1.2:ease-out 1:1:ease-in-out").unwrap();
+        assert_eq!(curve.sample(-1.0), 0.0);
+        assert_eq!(curve.sample(0.5), 1.2);
+        assert_eq!(curve.sample(1.0), 1.0);
+        assert!(curve.sample(0.25) > 0.6); // ease-out is ahead of linear
+        assert_eq!(AnimationCurve::parse(&curve.to_spec()).unwrap(), curve);
+
+        let step = AnimationCurve::parse("step").unwrap();
+        assert_eq!(step.sample(0.99), 0.0);
+        assert_eq!(step.sample(1.0), 1.0);
+
+        assert!(AnimationCurve::parse("0:0 1:x").is_err());
+        assert!(AnimationCurve::parse("0:0 1:1:bouncy").is_err());
+        assert!(AnimationCurve::parse("").is_err());
+    }
+
+    #[test]
+    fn test_floating_animations() {
+        let mut anims = FloatingAnimations::new();
+        anims.start(FloatingKind::Image, 1, FloatingProperty::Opacity, 1.0, 0.0,
+                    Duration::from_millis(100), Easing::Linear);
+        anims.start(FloatingKind::Image, 1, FloatingProperty::Opacity, 1.0, 0.5,
+                    Duration::from_millis(100), Easing::Linear);
+        let start = anims.active[0].animation.start_time;
+
+        let values = anims.tick(start + Duration::from_millis(50));
+        assert_eq!(values.len(), 1);
+        assert!((values[0].3 - 0.75).abs() < 1e-4);
+
+        let values = anims.tick(start + Duration::from_millis(200));
+        assert_eq!(values[0].3, 0.5);
+        assert!(!anims.is_active());
+
+        assert!(!FloatingKind::Terminal.supports(FloatingProperty::Width));
+        assert_eq!(FloatingProperty::from_name("corner_radius"), Some(FloatingProperty::CornerRadius));
+    }
+
+    #[test]
+    fn test_animation_completion() {
+        let mut anims = FloatingAnimations::new();
+        let start_anim = |anims: &mut FloatingAnimations, to: f32, notify: u32| {
+            anims.start(FloatingKind::Image, 1, FloatingProperty::X, 0.0, to,
+                        Duration::from_millis(100), Easing::Linear);
+            anims.notify(FloatingKind::Image, 1, FloatingProperty::X, notify);
+        };
+        start_anim(&mut anims, 10.0, 7);
+        // Replacing an animation ends it
+        start_anim(&mut anims, 20.0, 8);
+        assert_eq!(anims.take_finished(), vec![7]);
+        let start = anims.active[0].animation.start_time;
+        anims.tick(start + Duration::from_millis(50));
+        assert!(anims.take_finished().is_empty());
+        anims.tick(start + Duration::from_millis(200));
+        assert_eq!(anims.take_finished(), vec![8]);
+        // Nothing running: reported at once
+        anims.notify(FloatingKind::Image, 1, FloatingProperty::Y, 9);
+        assert_eq!(anims.take_finished(), vec![9]);
+
+        let mut watch = CompletionWatch::default();
+        watch.watch(1, vec![10, 20]);
+        watch.watch(2, Vec::new());
+        watch.watch(0, vec![30]);
+        assert_eq!(watch.finished(|p| *p == 20), vec![2]);
+        assert_eq!(watch.finished(|_| false), vec![1]);
+        assert!(watch.is_empty());
+    }
+
+    #[test]
+    fn test_named_curves() {
+        let mut curves = AnimationCurves::new();
+        assert_eq!(curves.remap("cursor", 0.5), None);
+        curves.set_option("cursor", "linear").unwrap();
+        assert_eq!(curves.remap("cursor", 0.5), Some(0.5));
+        assert_eq!(curves.get_option("cursor").as_deref(), Some("0:0:linear 1:1:linear"));
+        curves.set_option("cursor", "").unwrap();
+        assert!(curves.get("cursor").is_none());
+    }
 }
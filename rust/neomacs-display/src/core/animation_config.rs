@@ -158,6 +158,12 @@ pub struct ScrollAnimationConfig {
 
     /// Easing/physics model for scroll timing
     pub easing: ScrollEasing,
+
+    /// Blur content along the scroll direction while it moves fast
+    pub motion_blur: bool,
+
+    /// Length of the motion blur relative to the distance moved per frame
+    pub motion_blur_strength: f32,
 }
 
 impl Default for ScrollAnimationConfig {
@@ -168,6 +174,8 @@ impl Default for ScrollAnimationConfig {
             threshold_lines: 1,
             effect: ScrollEffect::default(),
             easing: ScrollEasing::default(),
+            motion_blur: false,
+            motion_blur_strength: 1.0,
         }
     }
 }
@@ -251,6 +259,16 @@ impl AnimationConfig {
                 self.scroll.easing = ScrollEasing::from_str(value);
                 true
             }
+            "scroll-motion-blur" => {
+                self.scroll.motion_blur = parse_bool(value);
+                true
+            }
+            "scroll-motion-blur-strength" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.scroll.motion_blur_strength = v.clamp(0.0, 4.0);
+                }
+                true
+            }
 
             _ => false,
         }
@@ -270,6 +288,8 @@ impl AnimationConfig {
             "scroll-animation" => Some(bool_str(self.scroll.enabled)),
             "scroll-effect" => Some(self.scroll.effect.as_str().to_string()),
             "scroll-easing" => Some(self.scroll.easing.as_str().to_string()),
+            "scroll-motion-blur" => Some(bool_str(self.scroll.motion_blur)),
+            "scroll-motion-blur-strength" => Some(self.scroll.motion_blur_strength.to_string()),
             _ => None,
         }
     }
@@ -314,5 +334,10 @@ mod tests {
         
         assert!(config.set_option("buffer-transition-effect", "page-curl"));
         assert_eq!(config.buffer_transition.effect, BufferTransitionEffect::PageCurl);
+
+        assert!(config.set_option("scroll-motion-blur", "t"));
+        assert!(config.scroll.motion_blur);
+        assert!(config.set_option("scroll-motion-blur-strength", "10"));
+        assert_eq!(config.scroll.motion_blur_strength, 4.0);
     }
 }
@@ -0,0 +1,118 @@
+//! Audio levels of playing media.
+//!
+//! The decoder thread runs the audio of each video through GStreamer's
+//! `level` and `spectrum` elements and publishes what they report here,
+//! keyed by video id.  The Emacs side reads the latest values over FFI,
+//! or asks for an event whenever they change, to draw VU meters or make
+//! the mode-line follow the music.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+/// Levels below this are drawn as silence
+pub const SILENCE_DB: f32 = -60.0;
+
+/// Latest levels of one stream, in dB (0 is full scale)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioLevels {
+    /// Average level of each channel over the last interval
+    pub rms: Vec<f32>,
+    /// Peak level of each channel over the last interval
+    pub peak: Vec<f32>,
+    /// Magnitude of each frequency band, lowest first
+    pub bands: Vec<f32>,
+    /// Set when new values arrive, cleared by `take_updated`
+    updated: bool,
+}
+
+static LEVELS: Lazy<Mutex<HashMap<u32, AudioLevels>>> = Lazy::new(Default::default);
+
+fn with_levels<R>(f: impl FnOnce(&mut HashMap<u32, AudioLevels>) -> R) -> R {
+    f(&mut LEVELS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Record the channel levels a `level` element reported for video `id`
+pub fn publish_level(id: u32, rms: Vec<f32>, peak: Vec<f32>) {
+    with_levels(|levels| {
+        let entry = levels.entry(id).or_default();
+        entry.rms = rms;
+        entry.peak = peak;
+        entry.updated = true;
+    });
+}
+
+/// Record the band magnitudes a `spectrum` element reported for video `id`
+pub fn publish_spectrum(id: u32, bands: Vec<f32>) {
+    with_levels(|levels| {
+        let entry = levels.entry(id).or_default();
+        entry.bands = bands;
+        entry.updated = true;
+    });
+}
+
+/// Latest levels of video `id`, once its audio has played
+pub fn get(id: u32) -> Option<AudioLevels> {
+    with_levels(|levels| levels.get(&id).cloned())
+}
+
+/// Forget video `id`
+pub fn remove(id: u32) {
+    with_levels(|levels| {
+        levels.remove(&id);
+    });
+}
+
+/// Ids of the videos whose levels changed since the last call
+pub fn take_updated() -> Vec<u32> {
+    with_levels(|levels| {
+        let mut ids: Vec<u32> = levels
+            .iter_mut()
+            .filter_map(|(id, l)| std::mem::take(&mut l.updated).then_some(*id))
+            .collect();
+        ids.sort_unstable();
+        ids
+    })
+}
+
+/// `db` on a 0.0-1.0 scale, for meters: `SILENCE_DB` and below are 0
+pub fn loudness(db: f32) -> f32 {
+    if db.is_nan() {
+        return 0.0;
+    }
+    (1.0 - db / SILENCE_DB).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_levels_publish_and_take_updated() {
+        // Ids no other test uses, as the table is shared
+        publish_level(9001, vec![-20.0, -30.0], vec![-6.0, -10.0]);
+        publish_spectrum(9001, vec![-40.0; 4]);
+        publish_spectrum(9002, vec![-50.0; 4]);
+        let levels = get(9001).unwrap();
+        assert_eq!(levels.peak, vec![-6.0, -10.0]);
+        assert_eq!(levels.bands.len(), 4);
+
+        let updated = take_updated();
+        assert!(updated.contains(&9001) && updated.contains(&9002));
+        assert!(!take_updated().contains(&9001));
+
+        remove(9001);
+        remove(9002);
+        assert_eq!(get(9001), None);
+    }
+
+    #[test]
+    fn test_loudness() {
+        assert_eq!(loudness(0.0), 1.0);
+        assert_eq!(loudness(-30.0), 0.5);
+        assert_eq!(loudness(-90.0), 0.0);
+        assert_eq!(loudness(f32::NEG_INFINITY), 0.0);
+        assert_eq!(loudness(6.0), 1.0);
+    }
+}
@@ -0,0 +1,175 @@
+//! QR codes and barcodes drawn as images.
+//!
+//! Sharing a link to a phone or setting up two-factor authentication
+//! wants a code on screen.  Codes are generated here as a grid of dark
+//! and light modules and uploaded as an image of one pixel per module;
+//! images are shown with nearest sampling when scaled up, so the code
+//! stays sharp at any size.  QR codes come from `qrcodegen`; linear
+//! barcodes are Code 128, set B, which covers printable ASCII.
+
+use qrcodegen::{QrCode, QrCodeEcc};
+
+/// Light modules around a QR code, as its specification asks
+pub const QR_QUIET_ZONE: usize = 4;
+
+/// Light modules on each side of a barcode
+pub const BARCODE_QUIET_ZONE: usize = 10;
+
+/// Kind of code to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeKind {
+    /// QR code, with error correction recovering about 7, 15, 25 or 30%
+    /// of the code
+    Qr(QrCodeEcc),
+    /// Code 128 barcode
+    Code128,
+}
+
+impl CodeKind {
+    /// The kind named `name`: "qr" (medium correction), "qr-low",
+    /// "qr-quartile", "qr-high" or "code128"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "qr" | "qr-medium" => Some(Self::Qr(QrCodeEcc::Medium)),
+            "qr-low" => Some(Self::Qr(QrCodeEcc::Low)),
+            "qr-quartile" => Some(Self::Qr(QrCodeEcc::Quartile)),
+            "qr-high" => Some(Self::Qr(QrCodeEcc::High)),
+            "code128" => Some(Self::Code128),
+            _ => None,
+        }
+    }
+}
+
+/// Modules of a code, quiet zone included; dark modules are true
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeMatrix {
+    pub width: usize,
+    pub height: usize,
+    pub modules: Vec<bool>,
+}
+
+impl CodeMatrix {
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.width + x]
+    }
+
+    /// One pixel per module in `dark` and `light` (0xRRGGBB), as RGBA
+    pub fn to_rgba(&self, dark: u32, light: u32) -> Vec<u8> {
+        let [_, dr, dg, db] = dark.to_be_bytes();
+        let [_, lr, lg, lb] = light.to_be_bytes();
+        self.modules
+            .iter()
+            .flat_map(|&d| if d { [dr, dg, db, 255] } else { [lr, lg, lb, 255] })
+            .collect()
+    }
+}
+
+/// Generate a code of `kind` for `text`
+pub fn generate(kind: CodeKind, text: &str) -> Result<CodeMatrix, String> {
+    match kind {
+        CodeKind::Qr(ecc) => qr(text, ecc),
+        CodeKind::Code128 => code128(text),
+    }
+}
+
+fn qr(text: &str, ecc: QrCodeEcc) -> Result<CodeMatrix, String> {
+    let code = QrCode::encode_text(text, ecc).map_err(|_| "Text too long for a QR code".to_string())?;
+    let size = code.size() as usize;
+    let width = size + 2 * QR_QUIET_ZONE;
+    let mut modules = vec![false; width * width];
+    for y in 0..size {
+        for x in 0..size {
+            modules[(y + QR_QUIET_ZONE) * width + x + QR_QUIET_ZONE] = code.get_module(x as i32, y as i32);
+        }
+    }
+    Ok(CodeMatrix { width, height: width, modules })
+}
+
+/// Bar and space widths of each Code 128 symbol, in modules
+const CODE128_PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212", "221213",
+    "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221", "223211", "221132",
+    "221231", "213212", "223112", "312131", "311222", "321122", "321221", "312212", "322112", "322211",
+    "212123", "212321", "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+    "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121", "313121", "211331",
+    "231131", "213113", "213311", "213131", "311123", "311321", "331121", "312113", "312311", "332111",
+    "314111", "221411", "431111", "111224", "111422", "121124", "121421", "141122", "141221", "112214",
+    "112412", "122114", "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+    "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311", "113141",
+    "114131", "311141", "411131", "211412", "211214", "211232",
+];
+
+const CODE128_START_B: usize = 104;
+const CODE128_STOP: &str = "2331112";
+
+/// Symbol values of `text` in Code 128 set B, start and checksum
+/// included, stop excluded
+fn code128_symbols(text: &str) -> Result<Vec<usize>, String> {
+    let mut symbols = vec![CODE128_START_B];
+    for ch in text.chars() {
+        if !(' '..='~').contains(&ch) {
+            return Err(format!("Cannot encode {:?} in a barcode", ch));
+        }
+        symbols.push(ch as usize - ' ' as usize);
+    }
+    let checksum = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| s * i.max(1))
+        .sum::<usize>()
+        % 103;
+    symbols.push(checksum);
+    Ok(symbols)
+}
+
+fn code128(text: &str) -> Result<CodeMatrix, String> {
+    if text.is_empty() {
+        return Err("Nothing to encode".to_string());
+    }
+    let symbols = code128_symbols(text)?;
+    let mut modules = vec![false; BARCODE_QUIET_ZONE];
+    let patterns = symbols.iter().map(|&s| CODE128_PATTERNS[s]).chain([CODE128_STOP]);
+    for pattern in patterns {
+        // Widths alternate bar, space, bar...
+        for (i, w) in pattern.bytes().enumerate() {
+            modules.extend(std::iter::repeat_n(i % 2 == 0, (w - b'0') as usize));
+        }
+    }
+    modules.extend(std::iter::repeat_n(false, BARCODE_QUIET_ZONE));
+    Ok(CodeMatrix { width: modules.len(), height: 1, modules })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_barcode_code128() {
+        for pattern in CODE128_PATTERNS {
+            let modules: u32 = pattern.bytes().map(|w| (w - b'0') as u32).sum();
+            assert_eq!(modules, 11, "{}", pattern);
+        }
+        // Start B, P J J 1 2 3 C, and a checksum of 879 mod 103
+        assert_eq!(code128_symbols("PJJ123C").unwrap(), vec![104, 48, 42, 42, 17, 18, 19, 35, 55]);
+
+        let code = generate(CodeKind::Code128, "PJJ123C").unwrap();
+        assert_eq!(code.width, 2 * BARCODE_QUIET_ZONE + 11 * 9 + 13);
+        assert!(!code.is_dark(0, 0) && code.is_dark(BARCODE_QUIET_ZONE, 0));
+        assert!(generate(CodeKind::Code128, "caf\u{e9}").is_err());
+        assert_eq!(code.to_rgba(0x000000, 0xffffff)[..4], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_barcode_qr() {
+        let code = generate(CodeKind::parse("qr").unwrap(), "https://example.org").unwrap();
+        assert_eq!(code.width, code.height);
+        assert_eq!(code.modules.len(), code.width * code.height);
+        // Finder pattern in the top-left corner, inside the quiet zone
+        let q = QR_QUIET_ZONE;
+        assert!(!code.is_dark(q - 1, q - 1));
+        assert!(code.is_dark(q, q) && code.is_dark(q + 6, q + 6) && !code.is_dark(q + 1, q + 1));
+        assert!(CodeKind::parse("ean13").is_none());
+        assert!(generate(CodeKind::Qr(QrCodeEcc::High), &"x".repeat(5000)).is_err());
+    }
+}
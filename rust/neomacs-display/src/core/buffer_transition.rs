@@ -2,6 +2,9 @@
 
 use std::time::{Duration, Instant};
 
+use super::clock::{system_clock, SharedClock};
+use super::scroll_animation::ScrollEffect;
+
 /// Buffer transition animation effect
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BufferTransitionEffect {
@@ -30,7 +33,12 @@ pub enum BufferTransitionEffect {
 
 impl BufferTransitionEffect {
     pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
+        Self::from_name(s).unwrap_or(Self::Crossfade)
+    }
+
+    /// The effect named `s`, or None for a name of no effect
+    pub fn from_name(s: &str) -> Option<Self> {
+        Some(match s.to_lowercase().as_str() {
             "none" => Self::None,
             "crossfade" | "fade" => Self::Crossfade,
             "slide-left" | "slide" => Self::SlideLeft,
@@ -41,7 +49,24 @@ impl BufferTransitionEffect {
             "push" | "stack" => Self::Push,
             "blur" => Self::Blur,
             "page" | "page-curl" | "book" => Self::PageCurl,
-            _ => Self::Crossfade,
+            _ => return None,
+        })
+    }
+
+    /// The effect the wgpu renderer draws this transition with, None
+    /// when there is nothing to animate.  Up and down slides both become
+    /// its vertical slide.
+    pub fn scroll_effect(self) -> Option<ScrollEffect> {
+        match self {
+            Self::None => None,
+            Self::Crossfade => Some(ScrollEffect::Crossfade),
+            Self::SlideLeft => Some(ScrollEffect::SlideLeft),
+            Self::SlideRight => Some(ScrollEffect::SlideRight),
+            Self::SlideUp | Self::SlideDown => Some(ScrollEffect::Slide),
+            Self::Push => Some(ScrollEffect::Push),
+            Self::ScaleFade => Some(ScrollEffect::ScaleZoom),
+            Self::Blur => Some(ScrollEffect::MotionBlur),
+            Self::PageCurl => Some(ScrollEffect::PageCurl),
         }
     }
 }
@@ -120,20 +145,34 @@ pub struct BufferTransition {
     
     /// Old buffer snapshot height
     pub old_height: f32,
+
+    /// Time source the progress is measured with
+    clock: SharedClock,
 }
 
 impl BufferTransition {
     pub fn new(effect: BufferTransitionEffect, direction: TransitionDirection, duration: Duration) -> Self {
+        Self::with_clock(effect, direction, duration, system_clock())
+    }
+
+    /// A transition starting now by `clock`, and timed by it
+    pub fn with_clock(
+        effect: BufferTransitionEffect,
+        direction: TransitionDirection,
+        duration: Duration,
+        clock: SharedClock,
+    ) -> Self {
         Self {
             effect,
             direction,
             progress: 0.0,
             duration,
-            start_time: Instant::now(),
+            start_time: clock.now(),
             easing: TransitionEasing::EaseOut,
             completed: false,
             old_width: 0.0,
             old_height: 0.0,
+            clock,
         }
     }
     
@@ -143,7 +182,7 @@ impl BufferTransition {
             return false;
         }
         
-        let elapsed = Instant::now().duration_since(self.start_time);
+        let elapsed = self.clock.now().duration_since(self.start_time);
         let raw_progress = elapsed.as_secs_f32() / self.duration.as_secs_f32();
         
         if raw_progress >= 1.0 {
@@ -162,7 +201,7 @@ impl BufferTransition {
             return false;
         }
         
-        let elapsed = Instant::now().duration_since(self.start_time);
+        let elapsed = self.clock.now().duration_since(self.start_time);
         let raw_progress = elapsed.as_secs_f32() / self.duration.as_secs_f32();
         
         if raw_progress >= 1.0 {
@@ -269,6 +308,9 @@ pub struct BufferTransitionAnimator {
     
     /// Last content hash (for auto-detection)
     last_content_hash: u64,
+
+    /// Time source handed to the transitions started
+    clock: SharedClock,
 }
 
 impl Default for BufferTransitionAnimator {
@@ -279,6 +321,11 @@ impl Default for BufferTransitionAnimator {
 
 impl BufferTransitionAnimator {
     pub fn new() -> Self {
+        Self::with_clock(system_clock())
+    }
+
+    /// An animator timing its transitions by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
             default_effect: BufferTransitionEffect::Crossfade,
             default_duration: Duration::from_millis(200),
@@ -287,6 +334,7 @@ impl BufferTransitionAnimator {
             snapshot_id: 0,
             auto_detect: true,
             last_content_hash: 0,
+            clock,
         }
     }
     
@@ -302,10 +350,11 @@ impl BufferTransitionAnimator {
             return;
         }
         
-        self.active_transition = Some(BufferTransition::new(
+        self.active_transition = Some(BufferTransition::with_clock(
             effect,
             direction,
             self.default_duration,
+            self.clock.clone(),
         ));
     }
     
@@ -427,3 +476,41 @@ impl PageCurlParams {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::ScriptedClock;
+
+    #[test]
+    fn test_transition_steps_with_scripted_clock() {
+        let clock = ScriptedClock::new();
+        let mut animator = BufferTransitionAnimator::with_clock(clock.shared());
+        animator.start_transition();
+        assert!(animator.update());
+        assert_eq!(animator.get_transition().unwrap().progress, 0.0);
+
+        clock.advance(Duration::from_millis(100));
+        assert!(animator.update());
+        let progress = animator.get_transition().unwrap().progress;
+        assert_eq!(progress, TransitionEasing::EaseOut.apply(0.5));
+
+        clock.advance(Duration::from_millis(100));
+        assert!(!animator.update());
+        assert!(!animator.is_active());
+    }
+
+    #[test]
+    fn test_effects_map_onto_wgpu_effects() {
+        let effect = |name| BufferTransitionEffect::from_name(name).and_then(|e| e.scroll_effect());
+        assert_eq!(effect("scale-fade"), Some(ScrollEffect::ScaleZoom));
+        assert_eq!(effect("slide-left"), Some(ScrollEffect::SlideLeft));
+        assert_eq!(effect("slide-right"), Some(ScrollEffect::SlideRight));
+        assert_eq!(effect("slide-up"), Some(ScrollEffect::Slide));
+        assert_eq!(effect("push"), Some(ScrollEffect::Push));
+        assert_eq!(effect("page-curl"), Some(ScrollEffect::PageCurl));
+        assert_eq!(effect("none"), None);
+        assert_eq!(BufferTransitionEffect::from_name("wave"), None);
+        assert_eq!(BufferTransitionEffect::from_str("wave"), BufferTransitionEffect::Crossfade);
+    }
+}
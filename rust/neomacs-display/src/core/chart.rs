@@ -0,0 +1,296 @@
+//! Charts of numeric series drawn by the GPU.
+//!
+//! Org tables, profilers and CSV modes want a quick look at a column of
+//! numbers without going through gnuplot.  A chart is given an image id
+//! so it sits in the text like an image, but is drawn as colored shapes
+//! at the size of its glyph: a sparkline is a chart one line high.  New
+//! values animate from the old ones, so a chart updated live moves
+//! instead of jumping.
+
+use std::time::{Duration, Instant};
+
+use super::types::{ease_in_out_cubic, Color, Rect};
+
+/// How the values of a chart are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChartKind {
+    /// A line through the values
+    Line,
+    /// A line with the area below it filled
+    Area,
+    /// One bar per value, rising from zero
+    Bar,
+}
+
+impl ChartKind {
+    pub const ALL: [ChartKind; 3] = [Self::Line, Self::Area, Self::Bar];
+
+    /// The kind named `name`, None for unknown names
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == name)
+    }
+
+    /// The kind numbered `index` in `ALL`, as C passes it
+    pub fn from_index(index: i32) -> Option<Self> {
+        usize::try_from(index).ok().and_then(|i| Self::ALL.get(i).copied())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Area => "area",
+            Self::Bar => "bar",
+        }
+    }
+}
+
+/// Look of a chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartStyle {
+    pub kind: ChartKind,
+    /// Color of the line or bars
+    pub color: Color,
+    /// Color below the line of an area chart
+    pub fill: Color,
+    /// Drawn behind the chart, if any
+    pub background: Option<Color>,
+    /// Width of the line in logical pixels
+    pub line_width: f32,
+    /// Value at the bottom and top of the chart; None fits the values
+    pub range: (Option<f32>, Option<f32>),
+    /// Part of each bar's slot left empty, 0-1
+    pub bar_gap: f32,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        let color = Color::new(0.2, 0.5, 1.0, 1.0);
+        Self {
+            kind: ChartKind::Line,
+            color,
+            fill: Color { a: 0.3, ..color },
+            background: None,
+            line_width: 1.5,
+            range: (None, None),
+            bar_gap: 0.2,
+        }
+    }
+}
+
+/// A filled quadrilateral, corners in order around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartQuad {
+    pub corners: [[f32; 2]; 4],
+    pub color: Color,
+}
+
+impl ChartQuad {
+    fn rect(x: f32, y: f32, width: f32, height: f32, color: Color) -> Self {
+        Self { corners: [[x, y], [x + width, y], [x + width, y + height], [x, y + height]], color }
+    }
+
+    /// Two triangles covering the quad
+    pub fn triangles(&self) -> [[f32; 2]; 6] {
+        let [a, b, c, d] = self.corners;
+        [a, b, c, a, c, d]
+    }
+}
+
+/// A chart and the animation of its last update
+#[derive(Debug, Clone)]
+pub struct Chart {
+    pub style: ChartStyle,
+    values: Vec<f32>,
+    /// Values shown when the update started
+    from: Vec<f32>,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Chart {
+    pub fn new(values: Vec<f32>, style: ChartStyle) -> Self {
+        Self { style, from: values.clone(), values, started: Instant::now(), duration: Duration::ZERO }
+    }
+
+    /// Show `values`, moving from those shown at `now` over `duration`.
+    /// Values added at the end grow from zero, or from the edge of the
+    /// chart nearest it.
+    pub fn update(&mut self, values: Vec<f32>, duration: Duration, now: Instant) {
+        self.from = self.values_at(now);
+        self.values = values;
+        self.started = now;
+        self.duration = duration;
+    }
+
+    pub fn animating(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) < self.duration
+    }
+
+    /// Values shown at `now`
+    pub fn values_at(&self, now: Instant) -> Vec<f32> {
+        if !self.animating(now) {
+            return self.values.clone();
+        }
+        let t = now.saturating_duration_since(self.started).as_secs_f32() / self.duration.as_secs_f32();
+        let t = ease_in_out_cubic(t);
+        let base = self.baseline(&self.values);
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let from = self.from.get(i).copied().unwrap_or(base);
+                from + (v - from) * t
+            })
+            .collect()
+    }
+
+    /// Value at the bottom and top of the chart when showing `values`
+    fn range(&self, values: &[f32]) -> (f32, f32) {
+        let finite = || values.iter().copied().filter(|v| v.is_finite());
+        let mut lo = self.style.range.0.unwrap_or_else(|| finite().fold(f32::INFINITY, f32::min));
+        let mut hi = self.style.range.1.unwrap_or_else(|| finite().fold(f32::NEG_INFINITY, f32::max));
+        if self.style.kind == ChartKind::Bar {
+            // Bars rise from zero unless the range says otherwise
+            if self.style.range.0.is_none() {
+                lo = lo.min(0.0);
+            }
+            if self.style.range.1.is_none() {
+                hi = hi.max(0.0);
+            }
+        }
+        if !lo.is_finite() || !hi.is_finite() {
+            return (0.0, 1.0);
+        }
+        let (mut lo, mut hi) = (lo.min(hi), lo.max(hi));
+        if hi <= lo {
+            // A flat series sits in the middle
+            lo -= 1.0;
+            hi += 1.0;
+        }
+        (lo, hi)
+    }
+
+    fn baseline(&self, values: &[f32]) -> f32 {
+        let (lo, hi) = self.range(values);
+        0.0f32.clamp(lo, hi)
+    }
+
+    /// Shapes drawing the chart over `area` at `now`, back to front
+    pub fn quads(&self, area: Rect, now: Instant) -> Vec<ChartQuad> {
+        let mut quads = Vec::new();
+        if area.width <= 0.0 || area.height <= 0.0 {
+            return quads;
+        }
+        if let Some(bg) = self.style.background {
+            quads.push(ChartQuad::rect(area.x, area.y, area.width, area.height, bg));
+        }
+        let values = self.values_at(now);
+        if values.is_empty() {
+            return quads;
+        }
+        let (lo, hi) = self.range(&values);
+        let style = &self.style;
+
+        if style.kind == ChartKind::Bar {
+            let slot = area.width / values.len() as f32;
+            let gap = slot * style.bar_gap.clamp(0.0, 1.0);
+            let y_of = |v: f32| area.bottom() - (v.clamp(lo, hi) - lo) / (hi - lo) * area.height;
+            let base = y_of(self.baseline(&values));
+            for (i, v) in values.iter().enumerate().filter(|(_, v)| v.is_finite()) {
+                let (y0, y1) = (y_of(*v).min(base), y_of(*v).max(base));
+                let x = area.x + i as f32 * slot + gap / 2.0;
+                if y1 > y0 {
+                    quads.push(ChartQuad::rect(x, y0, slot - gap, y1 - y0, style.color));
+                }
+            }
+            return quads;
+        }
+
+        // Keep the line inside the chart
+        let pad = style.line_width.max(0.0) / 2.0;
+        let inner = Rect::new(area.x + pad, area.y + pad, area.width - 2.0 * pad, area.height - 2.0 * pad);
+        let step = if values.len() > 1 { inner.width / (values.len() - 1) as f32 } else { 0.0 };
+        let points: Vec<[f32; 2]> = values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(i, &v)| {
+                let x = if values.len() > 1 { inner.x + i as f32 * step } else { inner.x + inner.width / 2.0 };
+                [x, inner.bottom() - (v.clamp(lo, hi) - lo) / (hi - lo) * inner.height]
+            })
+            .collect();
+
+        if style.kind == ChartKind::Area {
+            let bottom = area.bottom();
+            for pair in points.windows(2) {
+                let ([x0, y0], [x1, y1]) = (pair[0], pair[1]);
+                quads.push(ChartQuad { corners: [[x0, y0], [x1, y1], [x1, bottom], [x0, bottom]], color: style.fill });
+            }
+        }
+        let half = style.line_width.max(0.5) / 2.0;
+        if let [[x, y]] = points[..] {
+            quads.push(ChartQuad::rect(x - half, y - half, 2.0 * half, 2.0 * half, style.color));
+        }
+        for pair in points.windows(2) {
+            let ([x0, y0], [x1, y1]) = (pair[0], pair[1]);
+            let len = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt().max(f32::EPSILON);
+            // Offset across the segment, half the line width each way
+            let (nx, ny) = (-(y1 - y0) / len * half, (x1 - x0) / len * half);
+            quads.push(ChartQuad {
+                corners: [[x0 + nx, y0 + ny], [x1 + nx, y1 + ny], [x1 - nx, y1 - ny], [x0 - nx, y0 - ny]],
+                color: style.color,
+            });
+        }
+        quads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_bars_rise_from_zero() {
+        let style = ChartStyle { kind: ChartKind::Bar, bar_gap: 0.5, ..Default::default() };
+        let chart = Chart::new(vec![1.0, 4.0, -2.0, f32::NAN], style);
+        let quads = chart.quads(Rect::new(0.0, 0.0, 80.0, 60.0), Instant::now());
+        assert_eq!(quads.len(), 3);
+        // Range -2..4 puts zero 40 pixels down; slots are 20 wide
+        assert_eq!(quads[0], ChartQuad::rect(5.0, 30.0, 10.0, 10.0, style.color));
+        assert_eq!(quads[1].corners[0], [25.0, 0.0]);
+        assert_eq!(quads[2].corners[0], [45.0, 40.0]);
+        assert_eq!(quads[2].corners[2], [55.0, 60.0]);
+
+        for kind in ChartKind::ALL {
+            assert_eq!(ChartKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(ChartKind::from_index(2), Some(ChartKind::Bar));
+        assert_eq!(ChartKind::parse("pie"), None);
+    }
+
+    #[test]
+    fn test_chart_update_animates_values() {
+        let style = ChartStyle { line_width: 2.0, background: Some(Color::rgb(0.0, 0.0, 0.0)), ..Default::default() };
+        let mut chart = Chart::new(vec![1.0, 1.0], style);
+        let now = Instant::now();
+        let area = Rect::new(10.0, 10.0, 102.0, 22.0);
+        let quads = chart.quads(area, now);
+        assert_eq!(quads.len(), 2);
+        // A flat series is drawn across the middle, inside the padding
+        assert_eq!(quads[1].corners, [[11.0, 22.0], [111.0, 22.0], [111.0, 20.0], [11.0, 20.0]]);
+
+        chart.update(vec![2.0, 0.0, 1.0], Duration::from_millis(100), now);
+        assert!(chart.animating(now));
+        assert_eq!(chart.values_at(now), vec![1.0, 1.0, 0.0]);
+        let halfway = chart.values_at(now + Duration::from_millis(50));
+        for (v, expected) in halfway.iter().zip([1.5, 0.5, 0.5]) {
+            assert!((v - expected).abs() < 1e-3, "{:?}", halfway);
+        }
+        assert_eq!(chart.values_at(now + Duration::from_millis(100)), vec![2.0, 0.0, 1.0]);
+        assert!(!chart.animating(now + Duration::from_millis(100)));
+        // One segment per pair of values, over the background
+        assert_eq!(chart.quads(area, now + Duration::from_secs(1)).len(), 3);
+    }
+}
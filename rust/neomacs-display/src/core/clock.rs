@@ -0,0 +1,98 @@
+//! Time source for animations.
+//!
+//! Animators read the time from a `Clock` rather than calling
+//! `Instant::now()` themselves.  The display uses the system clock; tests
+//! hand them a `ScriptedClock` and step it frame by frame, so the state
+//! after each step is the same on every run, and a recorded session can
+//! be replayed with the timing it was recorded with.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Where animators get the current time from
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// A clock shared by the animators of one display
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The system clock, as animators use by default
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// A clock that only moves when told to.  Clones share the same time, so
+/// a test keeps one and hands `shared()` to the animators under test.
+#[derive(Debug, Clone)]
+pub struct ScriptedClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for ScriptedClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptedClock {
+    /// A clock stopped at the current time
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    pub fn starting_at(start: Instant) -> Self {
+        Self { now: Arc::new(Mutex::new(start)) }
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+
+    /// Move the clock to `to`, which must not be before its time
+    pub fn set(&self, to: Instant) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now = (*now).max(to);
+    }
+
+    /// This clock, for handing to animators
+    pub fn shared(&self) -> SharedClock {
+        Arc::new(self.clone())
+    }
+}
+
+impl Clock for ScriptedClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_clock_moves_only_when_told() {
+        let clock = ScriptedClock::new();
+        let shared = clock.shared();
+        let t0 = shared.now();
+        assert_eq!(shared.now(), t0);
+        clock.advance(Duration::from_millis(16));
+        assert_eq!(shared.now(), t0 + Duration::from_millis(16));
+        // Never backwards
+        clock.set(t0);
+        assert_eq!(shared.now(), t0 + Duration::from_millis(16));
+    }
+}
@@ -0,0 +1,141 @@
+//! How often dynamic content may ask for frames.
+//!
+//! Terminals with new output, playing videos and WebKit views that
+//! painted all make the render thread draw again.  Content scrolled out
+//! of view has nothing to show, and a frame without focus is rarely
+//! watched closely, so the render thread asks this throttle when such
+//! content may next cause a frame: at once when visible in a focused
+//! frame, at a reduced rate in an unfocused one, and not at all when
+//! off screen or when the whole window is hidden.  Content drawn for
+//! other reasons still comes up to date with that frame.  Under load
+//! (see `core::quality`) videos are held to the unfocused rate as well.
+
+use std::time::{Duration, Instant};
+
+/// Redraw interval of terminals and WebKit views in unfocused frames
+pub const UNFOCUSED_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Redraw interval of videos in unfocused frames, about 30 fps, as they
+/// are still watched
+pub const UNFOCUSED_VIDEO_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Dynamic content that requests frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    Terminal,
+    Video,
+    WebKit,
+}
+
+/// How often content may cause a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateRate {
+    /// Every frame it has something new for
+    Full,
+    /// At most once per interval
+    Reduced(Duration),
+    /// Never; it catches up when something else is drawn
+    Paused,
+}
+
+/// Update rates of the content of one frame
+#[derive(Debug, Clone)]
+pub struct ContentThrottle {
+    focused: bool,
+    /// Window minimized or fully covered
+    occluded: bool,
+    /// Videos limited to the unfocused rate even when focused
+    reduced_video: bool,
+    /// When the last frame was drawn
+    last_frame: Instant,
+}
+
+impl Default for ContentThrottle {
+    fn default() -> Self {
+        Self { focused: true, occluded: false, reduced_video: false, last_frame: Instant::now() }
+    }
+}
+
+impl ContentThrottle {
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn set_occluded(&mut self, occluded: bool) {
+        self.occluded = occluded;
+    }
+
+    pub fn set_reduced_video(&mut self, reduced: bool) {
+        self.reduced_video = reduced;
+    }
+
+    /// Whether the window can be seen at all
+    pub fn window_visible(&self) -> bool {
+        !self.occluded
+    }
+
+    /// Note that a frame was drawn at `now`
+    pub fn frame_drawn(&mut self, now: Instant) {
+        self.last_frame = now;
+    }
+
+    /// How often content of `kind` may cause frames, `visible` saying
+    /// whether any of it is on screen
+    pub fn rate(&self, kind: ContentKind, visible: bool) -> UpdateRate {
+        if self.occluded || !visible {
+            UpdateRate::Paused
+        } else if self.focused && !(self.reduced_video && kind == ContentKind::Video) {
+            UpdateRate::Full
+        } else {
+            UpdateRate::Reduced(match kind {
+                ContentKind::Video => UNFOCUSED_VIDEO_INTERVAL,
+                ContentKind::Terminal | ContentKind::WebKit => UNFOCUSED_INTERVAL,
+            })
+        }
+    }
+
+    /// When content of `kind` that has something new may cause the next
+    /// frame, or None if it may not
+    pub fn next_frame(&self, kind: ContentKind, visible: bool) -> Option<Instant> {
+        match self.rate(kind, visible) {
+            UpdateRate::Full => Some(self.last_frame),
+            UpdateRate::Reduced(interval) => Some(self.last_frame + interval),
+            UpdateRate::Paused => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_throttle_rates() {
+        let mut throttle = ContentThrottle::default();
+        assert_eq!(throttle.rate(ContentKind::Terminal, true), UpdateRate::Full);
+        assert_eq!(throttle.rate(ContentKind::WebKit, false), UpdateRate::Paused);
+
+        throttle.set_focused(false);
+        assert_eq!(throttle.rate(ContentKind::Terminal, true), UpdateRate::Reduced(UNFOCUSED_INTERVAL));
+        assert_eq!(throttle.rate(ContentKind::Video, true), UpdateRate::Reduced(UNFOCUSED_VIDEO_INTERVAL));
+
+        throttle.set_occluded(true);
+        assert_eq!(throttle.rate(ContentKind::Video, true), UpdateRate::Paused);
+
+        let mut throttle = ContentThrottle::default();
+        throttle.set_reduced_video(true);
+        assert_eq!(throttle.rate(ContentKind::Video, true), UpdateRate::Reduced(UNFOCUSED_VIDEO_INTERVAL));
+        assert_eq!(throttle.rate(ContentKind::Terminal, true), UpdateRate::Full);
+    }
+
+    #[test]
+    fn test_content_throttle_next_frame() {
+        let mut throttle = ContentThrottle::default();
+        let now = Instant::now();
+        throttle.frame_drawn(now);
+        assert_eq!(throttle.next_frame(ContentKind::Terminal, true), Some(now));
+        throttle.set_focused(false);
+        assert_eq!(throttle.next_frame(ContentKind::Terminal, true), Some(now + UNFOCUSED_INTERVAL));
+        assert_eq!(throttle.next_frame(ContentKind::Terminal, false), None);
+    }
+}
@@ -3,6 +3,8 @@
 use std::time::{Duration, Instant};
 use std::collections::VecDeque;
 
+use super::clock::{system_clock, SharedClock};
+
 /// Cursor animation mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CursorAnimationMode {
@@ -212,6 +214,9 @@ pub struct CursorAnimator {
     
     /// Whether animation is active (cursor is moving)
     animating: bool,
+
+    /// Time source for movement, blinking and effects
+    clock: SharedClock,
 }
 
 impl Default for CursorAnimator {
@@ -222,7 +227,12 @@ impl Default for CursorAnimator {
 
 impl CursorAnimator {
     pub fn new() -> Self {
-        let now = Instant::now();
+        Self::with_clock(system_clock())
+    }
+
+    /// An animator reading the time from `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        let now = clock.now();
         Self {
             mode: CursorAnimationMode::Smooth,
             target_x: 0.0,
@@ -253,6 +263,7 @@ impl CursorAnimator {
             particle_size: 4.0,
             glow_intensity: 0.3,
             animating: false,
+            clock,
         }
     }
     
@@ -280,9 +291,9 @@ impl CursorAnimator {
         
         // Reset blink when cursor moves
         self.blink_on = true;
-        self.last_blink_toggle = Instant::now();
+        self.last_blink_toggle = self.clock.now();
         
-        let now = Instant::now();
+        let now = self.clock.now();
         let dx = self.target_x - self.last_target_x;
         let dy = self.target_y - self.last_target_y;
         let distance = (dx * dx + dy * dy).sqrt();
@@ -322,7 +333,7 @@ impl CursorAnimator {
     }
     
     fn spawn_railgun_particles(&mut self, dx: f32, dy: f32, distance: f32) {
-        let now = Instant::now();
+        let now = self.clock.now();
         let norm_dx = -dx / distance; // Opposite direction
         let norm_dy = -dy / distance;
         
@@ -354,7 +365,7 @@ impl CursorAnimator {
     }
     
     fn spawn_pixiedust_particles(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
         
         for i in 0..self.particle_count {
             // Random direction
@@ -384,7 +395,7 @@ impl CursorAnimator {
         self.trail.push_back(TrailPoint {
             x: self.current_x + self.current_width / 2.0,
             y: self.current_y + self.current_height / 2.0,
-            time: Instant::now(),
+            time: self.clock.now(),
         });
         
         while self.trail.len() > self.max_trail_length {
@@ -393,7 +404,7 @@ impl CursorAnimator {
     }
     
     fn spawn_sonicboom(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
         self.rings.push(Ring {
             x: self.target_x + self.target_width / 2.0,
             y: self.target_y + self.target_height / 2.0,
@@ -407,7 +418,7 @@ impl CursorAnimator {
     }
     
     fn spawn_ripple(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
         // Spawn multiple concentric rings
         for i in 0..3 {
             self.rings.push(Ring {
@@ -426,7 +437,7 @@ impl CursorAnimator {
     /// Update animation state - call each frame
     /// Returns true if animation is still active (needs redraw)
     pub fn update(&mut self) -> bool {
-        let now = Instant::now();
+        let now = self.clock.now();
         let dt = now.duration_since(self.last_update).as_secs_f32();
         self.last_update = now;
         
@@ -518,7 +529,7 @@ impl CursorAnimator {
 
     /// Update with explicit delta time (for external time management)
     pub fn update_with_dt(&mut self, dt: f32) -> bool {
-        let now = Instant::now();
+        let now = self.clock.now();
         
         // Update cursor blink
         if now.duration_since(self.last_blink_toggle) >= self.blink_interval {
@@ -577,3 +588,33 @@ impl CursorAnimator {
         self.animating || !self.particles.is_empty() || !self.rings.is_empty() || !self.trail.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::ScriptedClock;
+
+    #[test]
+    fn test_cursor_and_particles_step_deterministically() {
+        let clock = ScriptedClock::new();
+        let mut cursor = CursorAnimator::with_clock(clock.shared());
+        cursor.set_mode(CursorAnimationMode::Railgun);
+        cursor.set_target(100.0, 0.0, 8.0, 16.0, 0, [1.0; 4]);
+        assert_eq!(cursor.particles.len(), 15);
+
+        // One 16ms frame moves the cursor by the same amount every run
+        clock.advance(Duration::from_millis(16));
+        assert!(cursor.update());
+        let expected = 100.0 * (1.0 - (-15.0f32 * 0.016).exp());
+        assert!((cursor.current_x - expected).abs() < 1e-3);
+
+        // Particles live 400ms at most; the cursor has long arrived by then
+        for _ in 0..30 {
+            clock.advance(Duration::from_millis(16));
+            cursor.update();
+        }
+        assert_eq!(cursor.current_x, 100.0);
+        assert!(cursor.particles.is_empty());
+        assert!(!cursor.is_animating());
+    }
+}
@@ -0,0 +1,488 @@
+//! Cursor prediction to hide input latency.
+//!
+//! Over TRAMP, or while slow Lisp runs, a key can take a noticeable time
+//! to show its effect.  With the `cursor-prediction` display option on,
+//! the render thread moves the cursor as soon as it sees a key whose
+//! effect it can tell from the frame on screen -- arrows and
+//! C-f/C-b/C-n/C-p over text, typing, Backspace -- and the frames that
+//! follow from Emacs either confirm the guess or put the cursor back
+//! where it belongs, like mosh's local echo.
+//!
+//! A guess is only made where the frame shows the cursor can go: a
+//! character to step over, a row of text above or below in the same
+//! window.  Up on the top row or Down on the bottom row of a window with
+//! more text beyond is taken to scroll the window by a line, as Emacs
+//! does with `scroll-conservatively' set; the render thread then draws
+//! the window's text moved by a line until a frame shows whether it did.
+//! Moves that would wrap to another line are left to Emacs, and other
+//! keys stop predicting until the next frame.  Like mosh, the predictor
+//! stops showing its guesses after a few wrong ones (such as scrolls
+//! Emacs recenters instead), goes on making them unseen, and shows them
+//! again once they have been right a few times in a row.
+
+use std::time::{Duration, Instant};
+
+use super::frame_glyphs::{FrameGlyph, FrameGlyphBuffer, WindowInfo};
+use super::types::{Point, Rect};
+
+/// Guesses no frame has confirmed after this long are dropped
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Wrong guesses in a row after which guesses are no longer shown
+const MISSES_TO_HIDE: u32 = 2;
+
+/// Right guesses in a row after which they are shown again
+const HITS_TO_SHOW: u32 = 3;
+
+/// Positions closer than this are the same
+const EPSILON: f32 = 0.5;
+
+/// A cursor move the predictor knows the effect of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// A character typed, moving the cursor one column right
+    Insert,
+}
+
+impl Move {
+    /// The move made by `keysym` with the given modifiers, if known
+    pub fn from_key(keysym: u32, ctrl: bool, meta: bool) -> Option<Self> {
+        if meta {
+            return None;
+        }
+        match (keysym, ctrl) {
+            (0xff51, _) | (0x62, true) | (0xff08, false) => Some(Self::Left),
+            (0xff53, _) | (0x66, true) => Some(Self::Right),
+            (0xff52, _) | (0x70, true) => Some(Self::Up),
+            (0xff54, _) | (0x6e, true) => Some(Self::Down),
+            // Wider characters take a width the frame does not tell
+            (0x20..=0x7e, false) => Some(Self::Insert),
+            _ => None,
+        }
+    }
+}
+
+fn same(a: f32, b: f32) -> bool {
+    (a - b).abs() < EPSILON
+}
+
+/// What a key is guessed to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guess {
+    /// Where the cursor goes
+    pub cursor: Rect,
+    /// Text area of the cursor's window
+    pub area: Rect,
+    /// Pixels the text of `area` moves up (down when negative)
+    pub scroll: f32,
+}
+
+/// The window showing `cursor`
+fn window_at(frame: &FrameGlyphBuffer, cursor: Rect) -> Option<&WindowInfo> {
+    let center = Point::new(cursor.x + cursor.width / 2.0, cursor.y + cursor.height / 2.0);
+    frame.window_infos.iter().find(|info| info.bounds.contains(center))
+}
+
+/// Text area of the window showing `cursor`: its bounds without the
+/// mode line, or the whole frame if no window is known
+fn text_area(frame: &FrameGlyphBuffer, cursor: Rect) -> Rect {
+    window_at(frame, cursor)
+        .map(|info| {
+            let b = info.bounds;
+            Rect::new(b.x, b.y, b.width, (b.height - info.mode_line_height).max(0.0))
+        })
+        .unwrap_or_else(|| Rect::new(0.0, 0.0, frame.width, frame.height))
+}
+
+/// Text characters of `frame` inside `area`, as (x, y, width)
+fn text_chars(frame: &FrameGlyphBuffer, area: Rect) -> impl Iterator<Item = (f32, f32, f32)> + '_ {
+    frame.glyphs.iter().filter_map(move |g| match *g {
+        FrameGlyph::Char { x, y, width, height, is_overlay: false, .. }
+            if x >= area.x && x + width <= area.right() + EPSILON
+                && y >= area.y && y + height <= area.bottom() + EPSILON =>
+        {
+            Some((x, y, width))
+        }
+        _ => None,
+    })
+}
+
+/// Where `mv` takes a cursor at `cursor` in `frame`, if the frame shows it
+pub fn next_position(mv: Move, cursor: Rect, frame: &FrameGlyphBuffer) -> Option<Rect> {
+    let area = text_area(frame, cursor);
+    let row = |y: f32| text_chars(frame, area).filter(move |&(_, cy, _)| same(cy, y));
+    // The cursor at `x` in row `y`: on a character, or past the last one
+    let at = |x: f32, y: f32| {
+        let mut row = row(y);
+        match row.find(|&(cx, _, cw)| same(cx, x) || same(cx + cw, x)) {
+            Some((cx, _, cw)) if same(cx, x) => Some(Rect::new(x, y, cw, cursor.height)),
+            Some(_) => Some(Rect::new(x, y, frame.char_width, cursor.height)),
+            None => None,
+        }
+    };
+    match mv {
+        Move::Right => {
+            let (cx, _, cw) = row(cursor.y).find(|&(cx, _, _)| same(cx, cursor.x))?;
+            at(cx + cw, cursor.y)
+        }
+        Move::Left => {
+            let (cx, _, _) = row(cursor.y).find(|&(cx, _, cw)| same(cx + cw, cursor.x))?;
+            at(cx, cursor.y)
+        }
+        Move::Up | Move::Down => {
+            // The nearest row of text above or below, keeping the column
+            let rows = text_chars(frame, area).map(|(_, cy, _)| cy);
+            let y = if mv == Move::Up {
+                rows.filter(|&cy| cy < cursor.y - EPSILON).reduce(f32::max)?
+            } else {
+                rows.filter(|&cy| cy > cursor.y + EPSILON).reduce(f32::min)?
+            };
+            at(cursor.x, y)
+        }
+        Move::Insert => {
+            let x = cursor.x + frame.char_width;
+            (frame.char_width > 0.0 && x + frame.char_width <= area.right() + EPSILON)
+                .then(|| Rect::new(x, cursor.y, cursor.width, cursor.height))
+        }
+    }
+}
+
+/// What `mv` does with the cursor at `cursor` in `frame`, if the frame
+/// shows it: a move within the text, or a scroll by a line when the
+/// cursor would leave the window and there is text beyond
+pub fn predict(mv: Move, cursor: Rect, frame: &FrameGlyphBuffer) -> Option<Guess> {
+    let area = text_area(frame, cursor);
+    if let Some(next) = next_position(mv, cursor, frame) {
+        return Some(Guess { cursor: next, area, scroll: 0.0 });
+    }
+    let info = window_at(frame, cursor)?;
+    let line = cursor.height;
+    let scroll = match mv {
+        Move::Down if cursor.bottom() + line > area.bottom() + EPSILON && info.window_end < info.buffer_size => line,
+        Move::Up if cursor.y - line < area.y - EPSILON && info.window_start > 1 => -line,
+        _ => return None,
+    };
+    Some(Guess { cursor, area, scroll })
+}
+
+/// Move the text of `area` in `frame` up by `dy` pixels (down when
+/// negative), dropping what leaves the area, to draw a predicted scroll
+pub fn scroll_text(frame: &mut FrameGlyphBuffer, area: Rect, dy: f32) {
+    let in_area = |x: f32, y: f32, width: f32, height: f32| {
+        x >= area.x - EPSILON
+            && x + width <= area.right() + EPSILON
+            && y >= area.y - EPSILON
+            && y + height <= area.bottom() + EPSILON
+    };
+    frame.glyphs.retain_mut(|g| match g {
+        FrameGlyph::Char { x, y, width, height, is_overlay: false, .. }
+        | FrameGlyph::Stretch { x, y, width, height, is_overlay: false, .. }
+        | FrameGlyph::Image { x, y, width, height, .. }
+            if in_area(*x, *y, *width, *height) =>
+        {
+            *y -= dy;
+            in_area(*x, *y, *width, *height)
+        }
+        _ => true,
+    });
+}
+
+/// Guesses of where keys not yet shown by Emacs will put the cursor
+#[derive(Debug, Clone)]
+pub struct CursorPredictor {
+    enabled: bool,
+    /// What each key predicted does, oldest first
+    pending: Vec<Guess>,
+    /// When the last guess was made
+    made: Option<Instant>,
+    /// Where the last frame put the cursor
+    frame_cursor: Option<Rect>,
+    /// The window the cursor was in in the last frame and its start
+    frame_start: Option<(i64, i64)>,
+    /// A key was not understood; guessing waits for the next frame
+    blocked: bool,
+    /// Whether guesses are shown rather than only checked
+    shown: bool,
+    /// Right and wrong guesses in a row
+    hits: u32,
+    misses: u32,
+}
+
+impl Default for CursorPredictor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pending: Vec::new(),
+            made: None,
+            frame_cursor: None,
+            frame_start: None,
+            blocked: false,
+            shown: true,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl CursorPredictor {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        *self = Self { enabled, ..Self::default() };
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The guessed cursor position to draw, if any
+    pub fn showing(&self) -> Option<Rect> {
+        self.pending.last().map(|g| g.cursor).filter(|_| self.shown)
+    }
+
+    /// The text area and distance the guesses not yet shown by a frame
+    /// scroll, if they are to be drawn
+    pub fn scroll_showing(&self) -> Option<(Rect, f32)> {
+        let scrolls = self.pending.iter().filter(|g| g.scroll != 0.0);
+        let area = scrolls.clone().next()?.area;
+        self.shown.then(|| (area, scrolls.map(|g| g.scroll).sum()))
+    }
+
+    /// A key was pressed with the cursor drawn from `frame` at `cursor`;
+    /// `key` is the move it makes, if known.  Returns what it is guessed
+    /// to do, if the guess is to be shown.  `frame` is drawn with the
+    /// scrolls guessed so far.
+    pub fn key(&mut self, key: Option<Move>, cursor: Rect, frame: &FrameGlyphBuffer, now: Instant) -> Option<Guess> {
+        if !self.enabled || self.blocked {
+            return None;
+        }
+        let base = self.pending.last().map_or(cursor, |g| g.cursor);
+        match key.and_then(|mv| predict(mv, base, frame)) {
+            Some(guess) => {
+                if self.pending.is_empty() {
+                    self.frame_cursor = Some(cursor);
+                }
+                self.pending.push(guess);
+                self.made = Some(now);
+                self.shown.then_some(guess)
+            }
+            None => {
+                self.interrupt();
+                None
+            }
+        }
+    }
+
+    /// Something the predictor cannot follow happened, such as a click:
+    /// drop the guesses, without counting them as wrong
+    pub fn interrupt(&mut self) {
+        self.pending.clear();
+        self.blocked = true;
+    }
+
+    /// A new frame from Emacs put the cursor at `cursor`
+    pub fn frame_arrived(&mut self, cursor: Rect, frame: &FrameGlyphBuffer) {
+        self.blocked = false;
+        let start = window_at(frame, cursor).map(|info| (info.window_id, info.window_start));
+        let scrolled = self.frame_start.is_some() && self.frame_start != start;
+        let moved = scrolled || self.frame_cursor.is_none_or(|old| !same(old.x, cursor.x) || !same(old.y, cursor.y));
+        self.frame_cursor = Some(cursor);
+        self.frame_start = start;
+        if self.pending.is_empty() || !moved {
+            // Emacs has not got to the keys yet
+            return;
+        }
+        // A guess is shown if the cursor is where it went and the window
+        // scrolled exactly when a guess up to it scrolled.  Scrolls keep
+        // the cursor in place, so the last of them that matches is taken.
+        let matches = |i: usize| {
+            let p = self.pending[i].cursor;
+            same(p.x, cursor.x)
+                && same(p.y, cursor.y)
+                && self.pending[..=i].iter().any(|g| g.scroll != 0.0) == scrolled
+        };
+        let hit = if scrolled {
+            (0..self.pending.len()).rev().find(|&i| matches(i))
+        } else {
+            (0..self.pending.len()).find(|&i| matches(i))
+        };
+        match hit {
+            Some(i) => {
+                self.pending.drain(..=i);
+                self.hits += 1;
+                self.misses = 0;
+                if self.hits >= HITS_TO_SHOW {
+                    self.shown = true;
+                }
+            }
+            None => self.miss(),
+        }
+    }
+
+    /// Drop guesses no frame has confirmed in time.  Returns whether any
+    /// were dropped.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let late = self.made.is_some_and(|made| now.saturating_duration_since(made) > TIMEOUT);
+        if late && !self.pending.is_empty() {
+            self.miss();
+            return true;
+        }
+        false
+    }
+
+    fn miss(&mut self) {
+        self.pending.clear();
+        self.hits = 0;
+        self.misses += 1;
+        if self.misses >= MISSES_TO_HIDE {
+            self.shown = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CW: f32 = 8.0;
+    const CH: f32 = 16.0;
+
+    /// "abc" over "ab" in a window 10 columns wide and 3 rows high
+    fn frame() -> FrameGlyphBuffer {
+        frame_of_buffer(1, 6, 6)
+    }
+
+    /// `frame` showing characters `start` to `end` of a buffer of `size`
+    fn frame_of_buffer(start: i64, end: i64, size: i64) -> FrameGlyphBuffer {
+        let mut frame = FrameGlyphBuffer::with_size(200.0, 100.0);
+        frame.char_width = CW;
+        frame.char_height = CH;
+        frame.add_window_info(1, 1, start, end, size, 0.0, 0.0, 10.0 * CW, 4.0 * CH, CH, true, false, CH, String::new(), false);
+        for (row, text) in ["abc", "ab"].iter().enumerate() {
+            for (col, c) in text.chars().enumerate() {
+                frame.add_char(c, col as f32 * CW, row as f32 * CH, CW, CH, 12.0, false);
+            }
+        }
+        // The mode line is not text to move into
+        frame.add_char('-', 0.0, 3.0 * CH, CW, CH, 12.0, true);
+        frame
+    }
+
+    fn cell(col: f32, row: f32) -> Rect {
+        Rect::new(col * CW, row * CH, CW, CH)
+    }
+
+    #[test]
+    fn test_prediction_follows_the_text() {
+        let frame = frame();
+        let next = |mv, col, row| next_position(mv, cell(col, row), &frame).map(|r| (r.x / CW, r.y / CH));
+        assert_eq!(next(Move::Right, 0.0, 0.0), Some((1.0, 0.0)));
+        assert_eq!(next(Move::Right, 2.0, 0.0), Some((3.0, 0.0)));
+        // Past the end of the line, or before its start, Emacs changes lines
+        assert_eq!(next(Move::Right, 3.0, 0.0), None);
+        assert_eq!(next(Move::Left, 0.0, 1.0), None);
+        assert_eq!(next(Move::Left, 2.0, 1.0), Some((1.0, 1.0)));
+        assert_eq!(next(Move::Down, 2.0, 0.0), Some((2.0, 1.0)));
+        // The line below is shorter, and the last one would scroll
+        assert_eq!(next(Move::Down, 3.0, 0.0), None);
+        assert_eq!(next(Move::Down, 0.0, 1.0), None);
+        assert_eq!(next(Move::Up, 1.0, 1.0), Some((1.0, 0.0)));
+        assert_eq!(next(Move::Insert, 2.0, 1.0), Some((3.0, 1.0)));
+        assert_eq!(next(Move::Insert, 9.0, 0.0), None);
+
+        assert_eq!(Move::from_key(0x66, true, false), Some(Move::Right));
+        assert_eq!(Move::from_key(0x66, false, false), Some(Move::Insert));
+        assert_eq!(Move::from_key(0x66, false, true), None);
+        assert_eq!(Move::from_key(0xff0d, false, false), None);
+    }
+
+    #[test]
+    fn test_prediction_confirmed_or_rolled_back() {
+        let frame = frame();
+        let now = Instant::now();
+        let mut predictor = CursorPredictor::default();
+        assert_eq!(predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now), None);
+        predictor.set_enabled(true);
+
+        assert_eq!(predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now).map(|g| g.cursor), Some(cell(1.0, 0.0)));
+        assert_eq!(predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now).map(|g| g.cursor), Some(cell(2.0, 0.0)));
+        // A frame from before the keys, then one showing the first
+        predictor.frame_arrived(cell(0.0, 0.0), &frame);
+        predictor.frame_arrived(cell(1.0, 0.0), &frame);
+        assert_eq!(predictor.showing(), Some(cell(2.0, 0.0)));
+        predictor.frame_arrived(cell(2.0, 0.0), &frame);
+        assert_eq!(predictor.showing(), None);
+
+        // Two wrong guesses hide the next ones
+        for _ in 0..2 {
+            predictor.key(Some(Move::Down), cell(2.0, 0.0), &frame, now);
+            predictor.frame_arrived(cell(0.0, 2.0), &frame);
+            predictor.frame_arrived(cell(2.0, 0.0), &frame);
+        }
+        assert_eq!(predictor.key(Some(Move::Right), cell(2.0, 0.0), &frame, now), None);
+        assert!(!predictor.expire(now + Duration::from_secs(1)));
+        assert!(predictor.expire(now + Duration::from_secs(3)));
+        // Right ones show them again
+        for _ in 0..3 {
+            predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now);
+            predictor.frame_arrived(cell(1.0, 0.0), &frame);
+            predictor.frame_arrived(cell(0.0, 0.0), &frame);
+        }
+        assert_eq!(predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now).map(|g| g.cursor), Some(cell(1.0, 0.0)));
+
+        // An unknown key drops the guess and waits for a frame
+        assert_eq!(predictor.key(None, cell(0.0, 0.0), &frame, now), None);
+        assert_eq!(predictor.showing(), None);
+        assert_eq!(predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now), None);
+        predictor.frame_arrived(cell(0.0, 0.0), &frame);
+        assert!(predictor.key(Some(Move::Right), cell(0.0, 0.0), &frame, now).is_some());
+    }
+
+    #[test]
+    fn test_scroll_predicted_at_window_edges() {
+        let frame = frame_of_buffer(3, 8, 20);
+        let area = Rect::new(0.0, 0.0, 10.0 * CW, 3.0 * CH);
+        let scroll = |mv, col, row| predict(mv, cell(col, row), &frame).map(|g| (g.cursor, g.scroll));
+        // The bottom row scrolls down, the top row up, the cursor stays
+        assert_eq!(scroll(Move::Down, 0.0, 2.0), Some((cell(0.0, 2.0), CH)));
+        assert_eq!(scroll(Move::Up, 1.0, 0.0), Some((cell(1.0, 0.0), -CH)));
+        assert_eq!(scroll(Move::Down, 1.0, 0.0), Some((cell(1.0, 1.0), 0.0)));
+        // Nothing beyond the window: no scroll
+        assert_eq!(predict(Move::Down, cell(0.0, 2.0), &frame_of_buffer(1, 6, 6)), None);
+        assert_eq!(predict(Move::Up, cell(0.0, 0.0), &frame_of_buffer(1, 6, 6)), None);
+
+        // The text moves up a row, the first row leaves, the mode line stays
+        let mut scrolled = frame.clone();
+        scroll_text(&mut scrolled, area, CH);
+        let rows: Vec<(char, f32)> = scrolled
+            .glyphs
+            .iter()
+            .filter_map(|g| match *g {
+                FrameGlyph::Char { char, y, .. } => Some((char, y / CH)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rows, [('a', 0.0), ('b', 0.0), ('-', 3.0)]);
+
+        // Confirmed once a frame shows the window scrolled with the cursor
+        // in place; rolled back when Emacs recenters instead
+        let now = Instant::now();
+        let mut predictor = CursorPredictor::default();
+        predictor.set_enabled(true);
+        predictor.frame_arrived(cell(0.0, 2.0), &frame);
+        let guess = predictor.key(Some(Move::Down), cell(0.0, 2.0), &frame, now).unwrap();
+        assert_eq!(guess.scroll, CH);
+        assert_eq!(predictor.scroll_showing(), Some((area, CH)));
+        predictor.frame_arrived(cell(0.0, 2.0), &frame);
+        assert_eq!(predictor.scroll_showing(), Some((area, CH)));
+        predictor.frame_arrived(cell(0.0, 2.0), &frame_of_buffer(4, 9, 20));
+        assert_eq!((predictor.showing(), predictor.scroll_showing()), (None, None));
+
+        predictor.key(Some(Move::Down), cell(0.0, 2.0), &frame, now);
+        predictor.frame_arrived(cell(0.0, 1.0), &frame_of_buffer(6, 11, 20));
+        assert_eq!((predictor.showing(), predictor.scroll_showing()), (None, None));
+        assert_eq!(predictor.misses, 1);
+    }
+}
@@ -0,0 +1,178 @@
+//! Which videos get to decode.
+//!
+//! Each video has its own GStreamer pipeline, so a buffer full of inline
+//! videos would decode all of them at once, most of them off screen.  The
+//! scheduler keeps at most `max_active` of the videos the user wants
+//! playing actually playing: those on screen, biggest first.  The rest
+//! are parked — their pipeline paused and their last frame left up as a
+//! poster — until the renderer reports them visible again and a slot is
+//! free.
+
+use std::collections::HashMap;
+
+/// Pipelines playing at once unless the `video-max-active` option says
+/// otherwise
+pub const DEFAULT_MAX_ACTIVE: usize = 4;
+
+/// Frames a video may go undrawn before it is parked, so scrolling past
+/// one, or a video loaded but not displayed yet, keeps playing
+pub const PARK_AFTER_FRAMES: u64 = 30;
+
+/// What the scheduler wants from the pipeline of a video
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decode {
+    /// Playing, if the user wants it to play
+    Active,
+    /// Paused on its last frame to free the decoder
+    Parked,
+}
+
+#[derive(Debug)]
+struct Scheduled {
+    /// The user wants it playing
+    playing: bool,
+    /// Pixels of it drawn this frame
+    area: f32,
+    /// Frame it was last drawn in
+    last_visible: u64,
+    decode: Decode,
+}
+
+/// Decode slots of the videos of the display
+#[derive(Debug)]
+pub struct DecodeScheduler {
+    /// 0 for no limit
+    max_active: usize,
+    /// Frames seen so far
+    frame: u64,
+    videos: HashMap<u32, Scheduled>,
+}
+
+impl DecodeScheduler {
+    pub fn new(max_active: usize) -> Self {
+        Self { max_active, frame: 0, videos: HashMap::new() }
+    }
+
+    /// Let `max_active` videos play at once (0 for no limit); applies
+    /// from the next `update`
+    pub fn set_max_active(&mut self, max_active: usize) {
+        self.max_active = max_active;
+    }
+
+    /// A video was loaded; it starts playing, as if just drawn
+    pub fn add(&mut self, id: u32) {
+        self.videos.insert(id, Scheduled {
+            playing: true,
+            area: 0.0,
+            last_visible: self.frame,
+            decode: Decode::Active,
+        });
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.videos.remove(&id);
+    }
+
+    /// The user played or paused video `id`
+    pub fn set_playing(&mut self, id: u32, playing: bool) {
+        if let Some(video) = self.videos.get_mut(&id) {
+            video.playing = playing;
+        }
+    }
+
+    pub fn is_parked(&self, id: u32) -> bool {
+        self.videos.get(&id).is_some_and(|v| v.decode == Decode::Parked)
+    }
+
+    /// Take the videos the renderer drew this frame, with the area each
+    /// covers on screen, and return the videos whose pipelines must
+    /// change: parked ones to resume, active ones to park.
+    pub fn update(&mut self, visible: &[(u32, f32)]) -> Vec<(u32, Decode)> {
+        self.frame += 1;
+        for video in self.videos.values_mut() {
+            video.area = 0.0;
+        }
+        for &(id, area) in visible {
+            if let Some(video) = self.videos.get_mut(&id) {
+                video.area += area.max(0.0);
+                video.last_visible = self.frame;
+            }
+        }
+
+        // Videos that may play, best first: biggest on screen, those
+        // already playing before equals (so equal videos don't swap
+        // slots every frame), then the most recently seen
+        let frame = self.frame;
+        let mut candidates: Vec<(&u32, &Scheduled)> = self
+            .videos
+            .iter()
+            .filter(|(_, v)| v.playing && frame - v.last_visible <= PARK_AFTER_FRAMES)
+            .collect();
+        candidates.sort_by(|(a_id, a), (b_id, b)| {
+            b.area
+                .total_cmp(&a.area)
+                .then_with(|| (b.decode == Decode::Active).cmp(&(a.decode == Decode::Active)))
+                .then_with(|| b.last_visible.cmp(&a.last_visible))
+                .then_with(|| a_id.cmp(b_id))
+        });
+        let limit = if self.max_active == 0 { usize::MAX } else { self.max_active };
+        let active: Vec<u32> = candidates.into_iter().take(limit).map(|(id, _)| *id).collect();
+
+        let mut changes = Vec::new();
+        for (&id, video) in self.videos.iter_mut() {
+            // Paused videos hold no decoder either way; they keep their
+            // slot state until the user plays them again
+            if !video.playing {
+                continue;
+            }
+            let decode = if active.contains(&id) { Decode::Active } else { Decode::Parked };
+            if decode != video.decode {
+                video.decode = decode;
+                changes.push((id, decode));
+            }
+        }
+        changes.sort_unstable_by_key(|(id, _)| *id);
+        changes
+    }
+}
+
+impl Default for DecodeScheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ACTIVE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_scheduler_limits_and_prefers_visible() {
+        let mut scheduler = DecodeScheduler::new(2);
+        for id in 1..=3 {
+            scheduler.add(id);
+        }
+        // Three on screen, two slots: the smallest is parked
+        assert_eq!(scheduler.update(&[(1, 100.0), (2, 300.0), (3, 200.0)]), vec![(1, Decode::Parked)]);
+        assert!(scheduler.is_parked(1));
+        // Nothing changes while the picture stays the same
+        assert!(scheduler.update(&[(1, 100.0), (2, 300.0), (3, 200.0)]).is_empty());
+        // Pausing video 2 frees its slot
+        scheduler.set_playing(2, false);
+        assert_eq!(scheduler.update(&[(1, 100.0), (2, 300.0), (3, 200.0)]), vec![(1, Decode::Active)]);
+    }
+
+    #[test]
+    fn test_decode_scheduler_parks_off_screen_videos() {
+        let mut scheduler = DecodeScheduler::new(0);
+        scheduler.add(1);
+        scheduler.add(2);
+        // Video 2 scrolls out of view; it keeps playing for a while
+        for _ in 0..PARK_AFTER_FRAMES {
+            assert!(scheduler.update(&[(1, 50.0)]).is_empty());
+        }
+        assert_eq!(scheduler.update(&[(1, 50.0)]), vec![(2, Decode::Parked)]);
+        // And resumes when it comes back
+        assert_eq!(scheduler.update(&[(1, 50.0), (2, 10.0)]), vec![(2, Decode::Active)]);
+    }
+}
@@ -0,0 +1,229 @@
+//! Display settings read from a TOML file.
+//!
+//! Lets users configure the renderer without writing Lisp.  The file is
+//! `$NEOMACS_DISPLAY_CONFIG`, or `$XDG_CONFIG_HOME/neomacs/display.toml`
+//! when that is unset.  Tables only group settings; every key is the name
+//! of a display option, the same names `neomacs-set-animation-option`
+//! accepts:
+//!
+//! ```toml
+//! [animation]
+//! cursor-animation = true
+//! scroll-effect = "slide"
+//!
+//! [terminal]
+//! terminal-palette = ["#000000", "#cd0000", "#00cd00", "#cdcd00"]
+//!
+//! [display]
+//! vsync = false
+//! ```
+//!
+//! Options are declared in `core::option_registry`, which also validates
+//! the values and lets values set at runtime take precedence over the
+//! file.  The file is read again when it changes and on SIGUSR1.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::core::error::{DisplayError, DisplayResult};
+use crate::core::option_registry::option_spec;
+
+/// How often the file's modification time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Option values read from a configuration file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayConfig {
+    options: BTreeMap<String, String>,
+}
+
+impl DisplayConfig {
+    /// Parse TOML text.  Values are converted to the strings the runtime
+    /// options use: booleans become "t"/"nil" and arrays are joined with
+    /// spaces.  Unknown option names are skipped with a warning.
+    pub fn parse(text: &str) -> DisplayResult<Self> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| DisplayError::Config(e.to_string()))?;
+        let mut config = Self::default();
+        config.collect(&table);
+        Ok(config)
+    }
+
+    /// Read and parse the file at `path`
+    pub fn load(path: &Path) -> DisplayResult<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| DisplayError::Config(format!("{}: {}", path.display(), e)))?;
+        Self::parse(&text)
+    }
+
+    /// The configuration file location, if one can be determined
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("NEOMACS_DISPLAY_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let base = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("neomacs").join("display.toml"))
+    }
+
+    /// Get the value of option `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn collect(&mut self, table: &toml::Table) {
+        for (key, value) in table {
+            if let toml::Value::Table(group) = value {
+                self.collect(group);
+            } else if option_spec(key).is_none() {
+                log::warn!("display config: unknown option {:?}", key);
+            } else if let Some(value) = option_string(value) {
+                self.options.insert(key.clone(), value);
+            } else {
+                log::warn!("display config: unsupported value for {:?}", key);
+            }
+        }
+    }
+}
+
+/// Convert a TOML value to the string form of an option value
+fn option_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(if *b { "t" } else { "nil" }.to_string()),
+        toml::Value::Array(items) => {
+            let items: Option<Vec<String>> = items.iter().map(option_string).collect();
+            Some(items?.join(" "))
+        }
+        toml::Value::Datetime(_) | toml::Value::Table(_) => None,
+    }
+}
+
+/// Set by SIGUSR1 to ask for the configuration file to be read again
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Notices when the configuration file should be read again
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl ConfigWatcher {
+    /// Watch the file at `path`
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let modified = path.as_deref().and_then(modified_time);
+        Self { path, modified, last_poll: Instant::now() }
+    }
+
+    /// The watched file
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Whether the file changed or a reload was requested by signal since
+    /// the last call
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let signalled = RELOAD_REQUESTED.swap(false, Ordering::AcqRel);
+        if !signalled && now.duration_since(self.last_poll) < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = now;
+        let modified = self.path.as_deref().and_then(modified_time);
+        let changed = modified != self.modified;
+        self.modified = modified;
+        signalled || changed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Handler that was installed for SIGUSR1 before ours
+#[cfg(unix)]
+static mut PREVIOUS_SIGUSR1: Option<libc::sigaction> = None;
+
+#[cfg(unix)]
+extern "C" fn on_sigusr1(sig: libc::c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+    RELOAD_REQUESTED.store(true, Ordering::Release);
+    // Emacs turns SIGUSR1 into a `sigusr1' event, so keep its handler running
+    // SAFETY: PREVIOUS_SIGUSR1 is written once, before our handler is installed
+    unsafe {
+        let Some(previous) = PREVIOUS_SIGUSR1 else { return };
+        if previous.sa_sigaction == libc::SIG_DFL || previous.sa_sigaction == libc::SIG_IGN {
+            return;
+        }
+        if previous.sa_flags & libc::SA_SIGINFO != 0 {
+            let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                std::mem::transmute(previous.sa_sigaction);
+            handler(sig, info, context);
+        } else {
+            let handler: extern "C" fn(libc::c_int) = std::mem::transmute(previous.sa_sigaction);
+            handler(sig);
+        }
+    }
+}
+
+/// Reload the configuration file on SIGUSR1, chaining to the handler
+/// already installed for it
+#[cfg(unix)]
+pub fn install_reload_signal() {
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+    if INSTALLED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    // SAFETY: plain sigaction calls; the previous action is saved before
+    // the new handler can run
+    unsafe {
+        let mut previous: libc::sigaction = std::mem::zeroed();
+        if libc::sigaction(libc::SIGUSR1, std::ptr::null(), &mut previous) != 0 {
+            return;
+        }
+        PREVIOUS_SIGUSR1 = Some(previous);
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sigusr1 as *const () as libc::sighandler_t;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+    }
+}
+
+/// Reloading by signal is only available on Unix
+#[cfg(not(unix))]
+pub fn install_reload_signal() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flattens_groups() {
+        let config = DisplayConfig::parse(
+            r##"
+            vsync = false
+            [animation]
+            cursor-animation-speed = 20
+            scroll-effect = "slide"
+            [terminal]
+            terminal-palette = ["#000000", "#ff0000"]
+            not-an-option = 1
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.get("vsync"), Some("nil"));
+        assert_eq!(config.get("cursor-animation-speed"), Some("20"));
+        assert_eq!(config.get("scroll-effect"), Some("slide"));
+        assert_eq!(config.get("terminal-palette"), Some("#000000 #ff0000"));
+        assert_eq!(config.get("not-an-option"), None);
+        assert!(DisplayConfig::parse("vsync = ").is_err());
+    }
+}
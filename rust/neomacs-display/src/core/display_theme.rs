@@ -0,0 +1,130 @@
+//! Colors of the display itself.
+//!
+//! Emacs gives the color of everything its frames show, but the display
+//! draws some things on its own: the title bar, web views and videos with
+//! no picture yet, and terminals.  Themes may also want the cursor and
+//! window dividers in colors of their own rather than those the faces
+//! give.  A `DisplayTheme` holds all of these so a host switches them in
+//! one step, optionally crossfading, as when it follows the system
+//! between dark and light.  Colors are linear, as in frames.
+
+use super::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
+use super::types::Color;
+
+/// Whether colors are light on dark or dark on light
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+impl Appearance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+
+    /// The appearance of text over `background`
+    pub fn of_background(background: Color) -> Self {
+        // Mid-gray in sRGB is about 0.2 in linear light
+        if background.luminance() > 0.2 {
+            Self::Light
+        } else {
+            Self::Dark
+        }
+    }
+}
+
+/// Colors the display uses besides those of faces
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayTheme {
+    /// Default background, for the title bar and terminals
+    pub background: Color,
+    /// Default foreground, for the title bar and terminals
+    pub foreground: Color,
+    /// Color of the cursor, instead of the cursor face's
+    pub cursor: Option<Color>,
+    /// Color of window dividers, instead of the divider faces'
+    pub divider: Option<Color>,
+    /// Area of a web view or video that has nothing to show yet
+    pub placeholder: Color,
+    /// Colors 0-15 of terminals, instead of the standard ones
+    pub terminal_palette: Option<[Color; 16]>,
+}
+
+impl Default for DisplayTheme {
+    fn default() -> Self {
+        Self {
+            background: Color::new(0.12, 0.12, 0.14, 1.0).srgb_to_linear(),
+            foreground: Color::new(0.8, 0.8, 0.82, 1.0).srgb_to_linear(),
+            cursor: None,
+            divider: None,
+            placeholder: Color::new(0.5, 0.5, 0.5, 0.15),
+            terminal_palette: None,
+        }
+    }
+}
+
+impl DisplayTheme {
+    pub fn appearance(&self) -> Appearance {
+        Appearance::of_background(self.background)
+    }
+
+    /// Give the cursor and dividers of `frame` the theme's colors, where
+    /// it has any
+    pub fn apply(&self, frame: &mut FrameGlyphBuffer) {
+        if self.cursor.is_none() && self.divider.is_none() {
+            return;
+        }
+        for glyph in frame.glyphs.iter_mut() {
+            match glyph {
+                FrameGlyph::Cursor { color, .. } => {
+                    if let Some(cursor) = self.cursor {
+                        *color = cursor;
+                    }
+                }
+                FrameGlyph::Border { color, .. } => {
+                    if let Some(divider) = self.divider {
+                        *color = divider;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let (Some(cursor), Some(inverse)) = (self.cursor, frame.cursor_inverse.as_mut()) {
+            inverse.cursor_bg = cursor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_theme_appearance() {
+        assert_eq!(DisplayTheme::default().appearance(), Appearance::Dark);
+        let light = DisplayTheme { background: Color::WHITE, ..DisplayTheme::default() };
+        assert_eq!(light.appearance(), Appearance::Light);
+        assert_eq!(Appearance::of_background(Color::from_pixel(0x808080)), Appearance::Light);
+        assert_eq!(Appearance::of_background(Color::from_pixel(0x303030)), Appearance::Dark);
+    }
+
+    #[test]
+    fn test_display_theme_recolors_cursor_and_dividers() {
+        let mut frame = FrameGlyphBuffer::with_size(100.0, 100.0);
+        frame.add_cursor(1, 0.0, 0.0, 8.0, 16.0, 0, Color::WHITE);
+        frame.add_border(50.0, 0.0, 1.0, 100.0, Color::BLACK);
+
+        // A theme without its own colors leaves the faces' alone
+        DisplayTheme::default().apply(&mut frame);
+        assert!(matches!(frame.glyphs[0], FrameGlyph::Cursor { color, .. } if color == Color::WHITE));
+
+        let theme = DisplayTheme { cursor: Some(Color::RED), divider: Some(Color::GREEN), ..DisplayTheme::default() };
+        theme.apply(&mut frame);
+        assert!(matches!(frame.glyphs[0], FrameGlyph::Cursor { color, .. } if color == Color::RED));
+        assert!(matches!(frame.glyphs[1], FrameGlyph::Border { color, .. } if color == Color::GREEN));
+    }
+}
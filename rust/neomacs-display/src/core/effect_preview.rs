@@ -0,0 +1,318 @@
+//! Previews of transitions and cursor animations for theme authors.
+//!
+//! `neomacs-export-effect-preview` plays one effect offscreen over a
+//! sample frame, rather than the buffers on screen, and writes what it
+//! renders as an animated GIF or a directory of numbered PNGs, to try an
+//! effect or show it in documentation.  Rendering happens on the render
+//! thread; this module holds the parts that need no GPU: naming the
+//! effect, when each frame is taken and how long it shows, and the
+//! sample frame itself.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::frame_glyphs::FrameGlyphBuffer;
+use super::scroll_animation::ScrollEffect;
+use super::types::{Color, CursorAnimStyle, Rect};
+
+/// Longest effect a preview plays
+pub const MAX_DURATION: Duration = Duration::from_secs(10);
+
+/// Highest frame rate of a preview
+pub const MAX_FPS: u32 = 60;
+
+/// How long the first and last frames stay up, so a looping GIF shows
+/// where the effect starts and where it ends
+pub const HOLD: Duration = Duration::from_millis(500);
+
+/// Size of a cell of the sample frame
+pub const CELL_WIDTH: f32 = 9.0;
+pub const CELL_HEIGHT: f32 = 18.0;
+const ASCENT: f32 = 14.0;
+const FONT_SIZE: f32 = 14.0;
+
+/// Window id of the sample frame's cursor
+pub const SAMPLE_WINDOW: i32 = 1;
+
+/// Cells (column, row) the cursor of a cursor preview moves between
+pub const CURSOR_CELLS: [(usize, usize); 2] = [(4, 2), (22, 9)];
+
+/// Lines scrolled between the two sample frames of a transition
+pub const TRANSITION_SCROLL: usize = 6;
+
+const SAMPLE_TEXT: &[&str] = &[
+    ";; Sample buffer for effect previews",
+    "(defun neomacs-preview-greet (name)",
+    "  \"Say hello to NAME.\"",
+    "  (let ((greeting (format \"Hello, %s!\" name)))",
+    "    (message \"%s\" greeting)",
+    "    greeting))",
+    "",
+    ";; Walk a list of names",
+    "(dolist (name '(\"Ada\" \"Grace\" \"Alan\"))",
+    "  (when (stringp name)",
+    "    (neomacs-preview-greet name)))",
+    "",
+    "(defvar neomacs-preview-count 0",
+    "  \"How many greetings went out.\")",
+    "",
+    "(setq neomacs-preview-count",
+    "      (1+ neomacs-preview-count))",
+    "",
+    ";; Transitions scroll this text by a few lines",
+    "(if (> neomacs-preview-count 2)",
+    "    (message \"Busy day\")",
+    "  (message \"Quiet day\"))",
+];
+
+const KEYWORDS: &[&str] = &["defun", "defvar", "let", "if", "when", "dolist", "setq"];
+
+const BACKGROUND: Color = Color::rgb(0.11, 0.12, 0.15);
+const FOREGROUND: Color = Color::rgb(0.85, 0.87, 0.9);
+const KEYWORD: Color = Color::rgb(0.78, 0.57, 0.92);
+const STRING: Color = Color::rgb(0.6, 0.8, 0.5);
+const COMMENT: Color = Color::rgb(0.45, 0.5, 0.58);
+const MODE_LINE: Color = Color::rgb(0.22, 0.24, 0.3);
+/// Color of the sample frame's cursor
+pub const CURSOR_COLOR: Color = Color::rgb(0.35, 0.65, 1.0);
+
+/// The effect a preview plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewEffect {
+    /// A buffer switch or scroll transition from one sample frame to the
+    /// other
+    Transition(ScrollEffect),
+    /// The cursor moving between the two `CURSOR_CELLS`
+    Cursor(CursorAnimStyle),
+}
+
+impl PreviewEffect {
+    /// The effect named `name` of `kind`, "transition" or "cursor", by
+    /// the names `neomacs-set-animation-config` takes; None for unknown
+    /// names rather than a default, as a preview of the wrong effect
+    /// would mislead
+    pub fn parse(kind: &str, name: &str) -> Option<Self> {
+        match kind {
+            "transition" => ScrollEffect::ALL
+                .iter()
+                .find(|e| e.as_str() == name)
+                .map(|&e| Self::Transition(e)),
+            "cursor" => {
+                let style = match name {
+                    "exponential" => CursorAnimStyle::Exponential,
+                    "spring" => CursorAnimStyle::CriticallyDampedSpring,
+                    "ease-out-quad" => CursorAnimStyle::EaseOutQuad,
+                    "ease-out-cubic" => CursorAnimStyle::EaseOutCubic,
+                    "ease-out-expo" => CursorAnimStyle::EaseOutExpo,
+                    "ease-in-out-cubic" => CursorAnimStyle::EaseInOutCubic,
+                    "linear" => CursorAnimStyle::Linear,
+                    _ => return None,
+                };
+                Some(Self::Cursor(style))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Where the frames of a preview go
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewOutput {
+    /// One looping GIF
+    Gif(PathBuf),
+    /// frame-0000.png, frame-0001.png, ... in a directory
+    PngFrames(PathBuf),
+}
+
+impl PreviewOutput {
+    /// A GIF for paths ending in .gif, else a directory of PNGs
+    pub fn for_path(path: &Path) -> Self {
+        let is_gif = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+        if is_gif {
+            Self::Gif(path.to_path_buf())
+        } else {
+            Self::PngFrames(path.to_path_buf())
+        }
+    }
+
+    /// File frame `index` goes to when writing PNGs
+    pub fn png_frame_path(dir: &Path, index: usize) -> PathBuf {
+        dir.join(format!("frame-{:04}.png", index))
+    }
+}
+
+/// When the frames of a preview are taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewTimeline {
+    pub duration: Duration,
+    pub fps: u32,
+}
+
+impl PreviewTimeline {
+    /// A timeline of `duration` at `fps`, both kept within the limits
+    pub fn new(duration: Duration, fps: u32) -> Self {
+        Self { duration: duration.min(MAX_DURATION), fps: fps.clamp(1, MAX_FPS) }
+    }
+
+    /// Frames taken, the first at the start of the effect and the last
+    /// at its end
+    pub fn frame_count(&self) -> usize {
+        let ms = self.duration.as_millis() as u64;
+        (ms * self.fps as u64).div_ceil(1000) as usize + 1
+    }
+
+    /// Time into the effect of frame `index`
+    pub fn frame_time(&self, index: usize) -> Duration {
+        let ms = index as u64 * 1000 / self.fps as u64;
+        Duration::from_millis(ms).min(self.duration)
+    }
+
+    /// How long each frame shows, in milliseconds.  GIFs count time in
+    /// hundredths of a second, so these are multiples of 10, rounded so
+    /// that together they last exactly the effect plus the two holds.
+    pub fn delays_ms(&self) -> Vec<u32> {
+        let count = self.frame_count();
+        let centis = |i: usize| (self.frame_time(i).as_millis() as u32 + 5) / 10;
+        let hold = HOLD.as_millis() as u32;
+        (0..count)
+            .map(|i| {
+                let shown = if i + 1 < count { (centis(i + 1) - centis(i)) * 10 } else { 0 };
+                let held = if i == 0 || i + 1 == count { hold } else { 0 };
+                shown + held
+            })
+            .collect()
+    }
+}
+
+/// Rectangle of cell (`col`, `row`) of the sample frame
+pub fn cell_rect(col: usize, row: usize) -> Rect {
+    Rect::new(col as f32 * CELL_WIDTH, row as f32 * CELL_HEIGHT, CELL_WIDTH, CELL_HEIGHT)
+}
+
+/// A frame of `width` x `height` logical pixels showing the sample text
+/// from line `scroll` on, with a mode line and a box cursor on cell
+/// `cursor`.  It draws with face 0, so the text appears in the default
+/// face's font.
+pub fn sample_frame(width: f32, height: f32, scroll: usize, cursor: (usize, usize)) -> FrameGlyphBuffer {
+    let mut frame = FrameGlyphBuffer::with_size(width, height);
+    frame.background = BACKGROUND;
+    frame.char_width = CELL_WIDTH;
+    frame.char_height = CELL_HEIGHT;
+    frame.font_pixel_size = FONT_SIZE;
+    frame.set_font_size(FONT_SIZE);
+    frame.add_background(0.0, 0.0, width, height, BACKGROUND);
+
+    let rows = ((height / CELL_HEIGHT) as usize).max(2);
+    let cols = (width / CELL_WIDTH) as usize;
+    for row in 0..rows - 1 {
+        let line = SAMPLE_TEXT[(scroll + row) % SAMPLE_TEXT.len()];
+        add_line(&mut frame, line, row, cols);
+    }
+
+    // Mode line on the last row
+    let mode_row = rows - 1;
+    let y = mode_row as f32 * CELL_HEIGHT;
+    frame.add_stretch(0.0, y, width, CELL_HEIGHT, MODE_LINE, 0, false);
+    set_color(&mut frame, FOREGROUND, Some(MODE_LINE));
+    let mode_line = format!(" -:---  *effect-preview*   L{}   (Emacs-Lisp)", scroll + 1);
+    for (col, c) in mode_line.chars().take(cols).enumerate() {
+        frame.add_char(c, col as f32 * CELL_WIDTH, y, CELL_WIDTH, CELL_HEIGHT, ASCENT, false);
+    }
+
+    let rect = cell_rect(cursor.0, cursor.1);
+    frame.add_cursor(SAMPLE_WINDOW, rect.x, rect.y, rect.width, rect.height, 0, CURSOR_COLOR);
+    frame
+}
+
+/// Draw `line` on `row`, colored as Lisp code
+fn add_line(frame: &mut FrameGlyphBuffer, line: &str, row: usize, cols: usize) {
+    let y = row as f32 * CELL_HEIGHT;
+    let chars: Vec<char> = line.chars().collect();
+    let mut in_string = false;
+    let mut col = 0;
+    while col < chars.len().min(cols) {
+        let c = chars[col];
+        let color = if in_string || c == '"' {
+            if c == '"' {
+                in_string = !in_string;
+            }
+            STRING
+        } else if c == ';' {
+            // The rest of the line is a comment
+            set_color(frame, COMMENT, None);
+            for (i, &c) in chars.iter().enumerate().take(cols).skip(col) {
+                frame.add_char(c, i as f32 * CELL_WIDTH, y, CELL_WIDTH, CELL_HEIGHT, ASCENT, false);
+            }
+            return;
+        } else if col > 0 && chars[col - 1] == '(' {
+            let word: String = chars[col..].iter().take_while(|c| c.is_alphanumeric() || **c == '-').collect();
+            if KEYWORDS.contains(&word.as_str()) {
+                set_color(frame, KEYWORD, None);
+                for (i, c) in word.chars().enumerate() {
+                    let x = (col + i) as f32 * CELL_WIDTH;
+                    frame.add_char(c, x, y, CELL_WIDTH, CELL_HEIGHT, ASCENT, false);
+                }
+                col += word.chars().count();
+                continue;
+            }
+            FOREGROUND
+        } else {
+            FOREGROUND
+        };
+        if c != ' ' {
+            set_color(frame, color, None);
+            frame.add_char(c, col as f32 * CELL_WIDTH, y, CELL_WIDTH, CELL_HEIGHT, ASCENT, false);
+        }
+        col += 1;
+    }
+}
+
+fn set_color(frame: &mut FrameGlyphBuffer, fg: Color, bg: Option<Color>) {
+    frame.set_face(0, fg, bg, false, false, 0, None, 0, None, 0, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_glyphs::FrameGlyph;
+
+    #[test]
+    fn test_effect_preview_names_and_outputs() {
+        assert_eq!(
+            PreviewEffect::parse("transition", "page-curl"),
+            Some(PreviewEffect::Transition(ScrollEffect::PageCurl))
+        );
+        assert_eq!(
+            PreviewEffect::parse("cursor", "spring"),
+            Some(PreviewEffect::Cursor(CursorAnimStyle::CriticallyDampedSpring))
+        );
+        assert_eq!(PreviewEffect::parse("transition", "no-such-effect"), None);
+        assert_eq!(PreviewEffect::parse("cursor", "page-curl"), None);
+
+        assert_eq!(
+            PreviewOutput::for_path(Path::new("/tmp/curl.GIF")),
+            PreviewOutput::Gif(PathBuf::from("/tmp/curl.GIF"))
+        );
+        assert_eq!(
+            PreviewOutput::for_path(Path::new("/tmp/curl")),
+            PreviewOutput::PngFrames(PathBuf::from("/tmp/curl"))
+        );
+    }
+
+    #[test]
+    fn test_effect_preview_timeline_and_sample() {
+        let timeline = PreviewTimeline::new(Duration::from_millis(200), 30);
+        assert_eq!(timeline.frame_count(), 7);
+        assert_eq!(timeline.frame_time(6), Duration::from_millis(200));
+        let delays = timeline.delays_ms();
+        assert!(delays.iter().all(|d| d % 10 == 0));
+        assert_eq!(delays.iter().sum::<u32>(), 200 + 2 * HOLD.as_millis() as u32);
+
+        let frame = sample_frame(400.0, 300.0, 0, CURSOR_CELLS[1]);
+        let cursors: Vec<_> = frame.glyphs.iter().filter(|g| matches!(g, FrameGlyph::Cursor { .. })).collect();
+        assert_eq!(cursors.len(), 1);
+        assert!(frame.glyphs.iter().any(|g| matches!(g, FrameGlyph::Char { char: 'd', .. })));
+    }
+}
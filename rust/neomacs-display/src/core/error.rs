@@ -17,6 +17,9 @@ pub enum DisplayError {
     #[error("Invalid glyph: {0}")]
     InvalidGlyph(String),
 
+    #[error("Invalid frame: {0}")]
+    InvalidFrame(String),
+
     #[error("Image loading failed: {0}")]
     ImageLoad(String),
 
@@ -31,6 +34,21 @@ pub enum DisplayError {
 
     #[error("FFI error: {0}")]
     Ffi(String),
+
+    #[error("Animation curve error: {0}")]
+    Animation(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Session error: {0}")]
+    Session(String),
+
+    #[error("Stale {kind} handle: {id}")]
+    StaleHandle { kind: &'static str, id: u32 },
+
+    #[error("Out of {kind} handles")]
+    HandlesExhausted { kind: &'static str },
 }
 
 /// Result type alias
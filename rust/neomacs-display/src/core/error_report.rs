@@ -0,0 +1,200 @@
+//! Errors reported to the host.
+//!
+//! Failures that happen away from the call that caused them (an image that
+//! does not decode on a worker thread, a missing GStreamer plugin, a shader
+//! that does not compile) are recorded here instead of only being logged.
+//! Each report has an id, a severity and a message, and the host can list
+//! them to show in the echo area.  The render thread announces new reports
+//! with an `InputEvent::DisplayError`.
+//!
+//! The log is process-wide so any thread can report without access to the
+//! render thread's state.  It keeps the most recent `CAPACITY` reports, and
+//! a failure identical to one already kept only bumps that report's count.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+/// Number of reports kept
+const CAPACITY: usize = 100;
+
+/// How serious a reported failure is
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info = 0,
+    Warning = 1,
+    Error = 2,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Subsystem a failure came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Image,
+    Video,
+    WebKit,
+    Terminal,
+    Pdf,
+    Font,
+    Shader,
+    Gpu,
+    Config,
+    /// The render thread itself, e.g. a stalled frame
+    Render,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+            Self::WebKit => "webkit",
+            Self::Terminal => "terminal",
+            Self::Pdf => "pdf",
+            Self::Font => "font",
+            Self::Shader => "shader",
+            Self::Gpu => "gpu",
+            Self::Config => "config",
+            Self::Render => "render",
+        }
+    }
+}
+
+/// One recorded failure
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReport {
+    pub id: u32,
+    pub severity: Severity,
+    pub kind: ErrorKind,
+    /// Image, video, view or terminal id the failure concerns, if any
+    pub resource: Option<u32>,
+    pub message: String,
+    /// Number of times this failure happened
+    pub count: u32,
+}
+
+#[derive(Debug, Default)]
+struct ErrorLog {
+    reports: VecDeque<ErrorReport>,
+    last_id: u32,
+}
+
+impl ErrorLog {
+    fn record(&mut self, severity: Severity, kind: ErrorKind, resource: Option<u32>, message: String) -> u32 {
+        if let Some(r) = self.reports.iter_mut().find(|r| {
+            r.kind == kind && r.resource == resource && r.message == message
+        }) {
+            r.count += 1;
+            r.severity = r.severity.max(severity);
+            return r.id;
+        }
+        self.last_id += 1;
+        if self.reports.len() == CAPACITY {
+            self.reports.pop_front();
+        }
+        self.reports.push_back(ErrorReport {
+            id: self.last_id,
+            severity,
+            kind,
+            resource,
+            message,
+            count: 1,
+        });
+        self.last_id
+    }
+}
+
+static LOG: Lazy<Mutex<ErrorLog>> = Lazy::new(|| Mutex::new(ErrorLog::default()));
+
+fn lock() -> std::sync::MutexGuard<'static, ErrorLog> {
+    LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Record a failure and write it to the log.  Returns the report id.
+pub fn report(
+    severity: Severity,
+    kind: ErrorKind,
+    resource: Option<u32>,
+    message: impl Into<String>,
+) -> u32 {
+    let message = message.into();
+    let level = match severity {
+        Severity::Info => log::Level::Info,
+        Severity::Warning => log::Level::Warn,
+        Severity::Error => log::Level::Error,
+    };
+    match resource {
+        Some(id) => log::log!(level, "{} {}: {}", kind.as_str(), id, message),
+        None => log::log!(level, "{}: {}", kind.as_str(), message),
+    }
+    lock().record(severity, kind, resource, message)
+}
+
+/// Record an error-severity failure.
+pub fn error(kind: ErrorKind, resource: Option<u32>, message: impl Into<String>) -> u32 {
+    report(Severity::Error, kind, resource, message)
+}
+
+/// Record a warning-severity failure.
+pub fn warning(kind: ErrorKind, resource: Option<u32>, message: impl Into<String>) -> u32 {
+    report(Severity::Warning, kind, resource, message)
+}
+
+/// Id of the newest report, or 0 if nothing has been reported.
+pub fn last_id() -> u32 {
+    lock().last_id
+}
+
+/// The oldest kept report newer than `after_id`.
+pub fn next_after(after_id: u32) -> Option<ErrorReport> {
+    lock().reports.iter().find(|r| r.id > after_id).cloned()
+}
+
+/// All kept reports newer than `after_id`, oldest first.
+pub fn reports_since(after_id: u32) -> Vec<ErrorReport> {
+    lock().reports.iter().filter(|r| r.id > after_id).cloned().collect()
+}
+
+/// Forget all reports.  Ids keep increasing.
+pub fn clear() {
+    lock().reports.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_failures_share_a_report() {
+        let mut log = ErrorLog::default();
+        let a = log.record(Severity::Warning, ErrorKind::Image, Some(3), "bad header".into());
+        let b = log.record(Severity::Error, ErrorKind::Image, Some(3), "bad header".into());
+        let c = log.record(Severity::Warning, ErrorKind::Image, Some(4), "bad header".into());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(log.reports[0].count, 2);
+        assert_eq!(log.reports[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_log_keeps_newest_reports() {
+        let mut log = ErrorLog::default();
+        for i in 0..(CAPACITY as u32 + 5) {
+            log.record(Severity::Info, ErrorKind::Font, None, format!("font {}", i));
+        }
+        assert_eq!(log.reports.len(), CAPACITY);
+        assert_eq!(log.reports.front().unwrap().id, 6);
+        assert_eq!(log.last_id, CAPACITY as u32 + 5);
+    }
+}
@@ -1,11 +1,14 @@
 //! Face (text styling) types.
 
-use crate::core::types::Color;
+use std::collections::HashMap;
+
+use crate::core::types::{Color, Rect};
 use bitflags::bitflags;
 
 bitflags! {
     /// Face attributes flags
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
     pub struct FaceAttributes: u32 {
         const BOLD = 1 << 0;
         const ITALIC = 1 << 1;
@@ -17,9 +20,34 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Groups of face attributes a face specifies itself rather than
+    /// inheriting, as with Emacs's `unspecified` attribute values
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+    pub struct FaceFields: u32 {
+        const FOREGROUND = 1 << 0;
+        const BACKGROUND = 1 << 1;
+        const FAMILY = 1 << 2;
+        /// Font size and font metrics
+        const HEIGHT = 1 << 3;
+        /// Font weight and the BOLD flag
+        const WEIGHT = 1 << 4;
+        /// The ITALIC flag
+        const SLANT = 1 << 5;
+        const UNDERLINE = 1 << 6;
+        const OVERLINE = 1 << 7;
+        const STRIKE_THROUGH = 1 << 8;
+        const BOX = 1 << 9;
+        const INVERSE = 1 << 10;
+        const BACKGROUND_STYLE = 1 << 11;
+    }
+}
+
 /// Underline style
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnderlineStyle {
     #[default]
     None,
@@ -33,6 +61,7 @@ pub enum UnderlineStyle {
 /// Box type for face
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub enum BoxType {
     #[default]
     None,
@@ -41,9 +70,81 @@ pub enum BoxType {
     Sunken3D,
 }
 
+/// Background drawn instead of a face's flat background color
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub enum BackgroundStyle {
+    /// Linear gradient from `from` to `to`.  `angle` is in degrees
+    /// clockwise from left-to-right, so 90 runs top to bottom.
+    Gradient { from: Color, to: Color, angle: f32 },
+    /// Image (an image cache id) scaled to cover the background
+    Image { image_id: u32 },
+}
+
+impl BackgroundStyle {
+    /// Gradient colors at the top-left, top-right, bottom-right and
+    /// bottom-left corners of `rect`, which the gradient spans; None for
+    /// images.  A linear gradient is affine, so interpolating these across
+    /// the rect reproduces it exactly.
+    pub fn gradient_corners(&self, rect: &Rect) -> Option<[Color; 4]> {
+        let Self::Gradient { from, to, angle } = *self else {
+            return None;
+        };
+        let (sin, cos) = angle.to_radians().sin_cos();
+        // Half the length of the gradient line across the rect
+        let half = (rect.width * cos.abs() + rect.height * sin.abs()) / 2.0;
+        let at = |dx: f32, dy: f32| {
+            let t = if half > 0.0 { ((dx * cos + dy * sin) / half + 1.0) / 2.0 } else { 0.0 };
+            let t = t.clamp(0.0, 1.0);
+            Color::new(
+                from.r + (to.r - from.r) * t,
+                from.g + (to.g - from.g) * t,
+                from.b + (to.b - from.b) * t,
+                from.a + (to.a - from.a) * t,
+            )
+        };
+        let (hw, hh) = (rect.width / 2.0, rect.height / 2.0);
+        Some([at(-hw, -hh), at(hw, -hh), at(hw, hh), at(-hw, hh)])
+    }
+}
+
+/// Merge background cells of `(face_id, rect)` into runs of adjacent cells
+/// with the same face on the same row, so a styled background spans the
+/// whole run rather than repeating per cell.
+pub fn merge_background_runs(mut cells: Vec<(u32, Rect)>) -> Vec<(u32, Rect)> {
+    cells.sort_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)));
+    let mut runs: Vec<(u32, Rect)> = Vec::new();
+    for (face_id, cell) in cells {
+        if let Some((run_face, run)) = runs.last_mut() {
+            if *run_face == face_id
+                && (run.y - cell.y).abs() < 0.5
+                && (run.height - cell.height).abs() < 0.5
+                && cell.x <= run.right() + 0.5
+            {
+                run.width = run.right().max(cell.right()) - run.x;
+                continue;
+            }
+        }
+        runs.push((face_id, cell));
+    }
+    runs
+}
+
+/// Texture coordinates `[u0, v0, u1, v1]` showing the middle of an
+/// `image_w` x `image_h` image scaled to cover a `width` x `height` area
+pub fn cover_tex_coords(width: f32, height: f32, image_w: f32, image_h: f32) -> [f32; 4] {
+    if width <= 0.0 || height <= 0.0 || image_w <= 0.0 || image_h <= 0.0 {
+        return [0.0, 0.0, 1.0, 1.0];
+    }
+    let scale = (width / image_w).max(height / image_h);
+    let (u, v) = (width / (image_w * scale), height / (image_h * scale));
+    [(1.0 - u) / 2.0, (1.0 - v) / 2.0, (1.0 + u) / 2.0, (1.0 + v) / 2.0]
+}
+
 /// A face defines text styling (colors, font, decorations)
 #[repr(C)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub struct Face {
     /// Face ID
     pub id: u32,
@@ -99,6 +200,9 @@ pub struct Face {
     pub underline_position: i32,
     /// Underline thickness (font->underline_thickness)
     pub underline_thickness: i32,
+
+    /// Gradient or image drawn instead of `background`
+    pub background_style: Option<BackgroundStyle>,
 }
 
 impl Default for Face {
@@ -123,6 +227,7 @@ impl Default for Face {
             font_descent: 0,
             underline_position: 1,
             underline_thickness: 1,
+            background_style: None,
         }
     }
 }
@@ -171,6 +276,172 @@ impl Face {
         desc.push_str(&format!(" {}", self.font_size as i32));
         desc
     }
+
+    /// Whether glyphs rasterized for this face would look different
+    /// drawn with `other`: colors and decorations are applied at draw time
+    pub fn same_glyphs(&self, other: &Face) -> bool {
+        self.font_family == other.font_family
+            && self.font_size == other.font_size
+            && self.font_weight == other.font_weight
+            && self.is_bold() == other.is_bold()
+            && self.is_italic() == other.is_italic()
+    }
+
+    /// Copy the attribute groups in `fields` from `other`
+    pub fn merge_from(&mut self, other: &Face, fields: FaceFields) {
+        let mut copy_flags = |field: FaceFields, flags: FaceAttributes| {
+            if fields.contains(field) {
+                self.attributes.remove(flags);
+                self.attributes.insert(other.attributes & flags);
+            }
+        };
+        copy_flags(FaceFields::WEIGHT, FaceAttributes::BOLD);
+        copy_flags(FaceFields::SLANT, FaceAttributes::ITALIC);
+        copy_flags(FaceFields::UNDERLINE, FaceAttributes::UNDERLINE);
+        copy_flags(FaceFields::OVERLINE, FaceAttributes::OVERLINE);
+        copy_flags(FaceFields::STRIKE_THROUGH, FaceAttributes::STRIKE_THROUGH);
+        copy_flags(FaceFields::BOX, FaceAttributes::BOX);
+        copy_flags(FaceFields::INVERSE, FaceAttributes::INVERSE);
+        if fields.contains(FaceFields::FOREGROUND) {
+            self.foreground = other.foreground;
+        }
+        if fields.contains(FaceFields::BACKGROUND) {
+            self.background = other.background;
+        }
+        if fields.contains(FaceFields::FAMILY) {
+            self.font_family.clone_from(&other.font_family);
+        }
+        if fields.contains(FaceFields::HEIGHT) {
+            self.font_size = other.font_size;
+            self.font_ascent = other.font_ascent;
+            self.font_descent = other.font_descent;
+        }
+        if fields.contains(FaceFields::WEIGHT) {
+            self.font_weight = other.font_weight;
+        }
+        if fields.contains(FaceFields::UNDERLINE) {
+            self.underline_style = other.underline_style;
+            self.underline_color = other.underline_color;
+            self.underline_position = other.underline_position;
+            self.underline_thickness = other.underline_thickness;
+        }
+        if fields.contains(FaceFields::OVERLINE) {
+            self.overline_color = other.overline_color;
+        }
+        if fields.contains(FaceFields::STRIKE_THROUGH) {
+            self.strike_through_color = other.strike_through_color;
+        }
+        if fields.contains(FaceFields::BOX) {
+            self.box_type = other.box_type;
+            self.box_color = other.box_color;
+            self.box_line_width = other.box_line_width;
+            self.box_corner_radius = other.box_corner_radius;
+        }
+        if fields.contains(FaceFields::BACKGROUND_STYLE) {
+            self.background_style = other.background_style;
+        }
+    }
+}
+
+/// How glyphs drawn from a fallback font (color emoji, symbols) are fitted
+/// to the face's own font
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FallbackMetrics {
+    /// Fit fallback glyphs to the text font at all
+    pub enabled: bool,
+    /// Extra scale applied on top of the fitted size
+    pub scale: f32,
+    /// Extra upward shift in logical pixels
+    pub baseline_offset: f32,
+}
+
+impl Default for FallbackMetrics {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scale: 1.0,
+            baseline_offset: 0.0,
+        }
+    }
+}
+
+impl FallbackMetrics {
+    /// Scale and upward shift (in em) that fit a fallback font with
+    /// `fallback` (ascent, descent) into a text font with `text`
+    /// (ascent, descent), both in em.  The fallback box is stretched to the
+    /// text font's height and its top aligned with the text ascent.
+    pub fn fit(&self, text: (f32, f32), fallback: (f32, f32)) -> (f32, f32) {
+        let fallback_height = fallback.0 + fallback.1;
+        if !self.enabled || fallback_height <= 0.0 {
+            return (1.0, 0.0);
+        }
+        let scale = ((text.0 + text.1) / fallback_height * self.scale).clamp(0.5, 2.0);
+        (scale, text.0 - fallback.0 * scale)
+    }
+}
+
+/// Deepest chain of parent faces followed when merging, which also cuts
+/// inheritance cycles short
+const MAX_INHERIT_DEPTH: usize = 10;
+
+/// A change to one face in a [`FaceDelta`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub enum FaceUpdate {
+    /// Face added or modified, with the attribute groups it specifies and
+    /// the faces it inherits from when it was inserted with
+    /// `insert_inheriting`
+    Set { face: Box<Face>, inherit: Option<(FaceFields, Vec<u32>)> },
+    /// Face removed
+    Remove(u32),
+}
+
+impl FaceUpdate {
+    /// ID of the face changed
+    pub fn id(&self) -> u32 {
+        match self {
+            FaceUpdate::Set { face, .. } => face.id,
+            FaceUpdate::Remove(id) => *id,
+        }
+    }
+}
+
+/// The faces changed between two generations of a [`FaceCache`], so a
+/// copy of the cache can be kept in sync without resending every face.
+/// A delta from generation 0 carries every face.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceDelta {
+    /// Generation the delta applies on top of
+    pub base: u64,
+    /// Generation after applying it
+    pub generation: u64,
+    pub updates: Vec<FaceUpdate>,
+}
+
+impl FaceDelta {
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty()
+    }
+
+    /// Fold in `next`, the delta following this one, so applying the
+    /// result equals applying both.  A delta from generation 0 replaces
+    /// everything before it.
+    pub fn then(&mut self, next: FaceDelta) {
+        if next.base == 0 {
+            *self = next;
+            return;
+        }
+        for update in next.updates {
+            let id = update.id();
+            self.updates.retain(|u| u.id() != id);
+            // A delta carrying every face has no use for removals
+            if self.base != 0 || !matches!(update, FaceUpdate::Remove(_)) {
+                self.updates.push(update);
+            }
+        }
+        self.generation = next.generation;
+    }
 }
 
 /// Face cache for efficient lookup
@@ -178,6 +449,16 @@ impl Face {
 pub struct FaceCache {
     faces: Vec<Face>,
     next_id: u32,
+    /// Bumped by every change to the faces
+    generation: u64,
+    /// Generation at which each face was last added, modified or removed
+    changed: HashMap<u32, u64>,
+    /// Faces inserted with `insert_inheriting`: the attribute groups they
+    /// specify and the faces they inherit the rest from, highest
+    /// precedence first.  Faces missing here specify everything.
+    inheritance: HashMap<u32, (FaceFields, Vec<u32>)>,
+    /// Merged faces by id, cleared whenever a face changes
+    merged: HashMap<u32, Face>,
 }
 
 impl FaceCache {
@@ -185,6 +466,10 @@ impl FaceCache {
         Self {
             faces: Vec::new(),
             next_id: 1, // 0 is reserved for default
+            generation: 0,
+            changed: HashMap::new(),
+            inheritance: HashMap::new(),
+            merged: HashMap::new(),
         }
     }
 
@@ -207,6 +492,116 @@ impl FaceCache {
 
     /// Add or update a face, returns the face ID
     pub fn insert(&mut self, face: Face) -> u32 {
+        if self.get(face.id) == Some(&face) && !self.inheritance.contains_key(&face.id) {
+            return face.id;
+        }
+        self.inheritance.remove(&face.id);
+        self.store(face)
+    }
+
+    /// Add or update a face that sets only the attribute groups in
+    /// `specified` and inherits the rest from `parents`, earlier parents
+    /// taking precedence, then from the default face.  Returns the face ID.
+    pub fn insert_inheriting(&mut self, face: Face, specified: FaceFields, parents: Vec<u32>) -> u32 {
+        let inherit = (specified, parents);
+        if self.get(face.id) == Some(&face) && self.inheritance.get(&face.id) == Some(&inherit) {
+            return face.id;
+        }
+        self.inheritance.insert(face.id, inherit);
+        self.store(face)
+    }
+
+    /// Remove a face; returns whether it was present
+    pub fn remove(&mut self, id: u32) -> bool {
+        let Some(index) = self.faces.iter().position(|f| f.id == id) else {
+            return false;
+        };
+        self.faces.swap_remove(index);
+        self.inheritance.remove(&id);
+        self.touch(id);
+        true
+    }
+
+    /// Current generation, bumped by every change
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Faces changed after generation `base`, oldest first
+    pub fn delta_since(&self, base: u64) -> FaceDelta {
+        let mut changed: Vec<(u64, u32)> = self
+            .changed
+            .iter()
+            .filter(|(_, gen)| **gen > base)
+            .map(|(id, gen)| (*gen, *id))
+            .collect();
+        changed.sort_unstable();
+        let updates = changed
+            .into_iter()
+            .map(|(_, id)| match self.get(id) {
+                Some(face) => FaceUpdate::Set {
+                    face: Box::new(face.clone()),
+                    inherit: self.inheritance.get(&id).cloned(),
+                },
+                None => FaceUpdate::Remove(id),
+            })
+            .collect();
+        FaceDelta { base, generation: self.generation, updates }
+    }
+
+    /// Apply a delta made by another cache's `delta_since`.  Returns the
+    /// faces whose merged attributes may have changed, including faces
+    /// inheriting from changed ones, or None without applying anything
+    /// when the delta does not start at this cache's generation.  A delta
+    /// from generation 0 replaces every face.
+    pub fn apply_delta(&mut self, delta: &FaceDelta) -> Option<Vec<u32>> {
+        if delta.base != 0 && delta.base != self.generation {
+            return None;
+        }
+        let mut changed: Vec<u32> = Vec::new();
+        if delta.base == 0 {
+            changed.extend(self.faces.drain(..).map(|f| f.id));
+            self.inheritance.clear();
+            self.merged.clear();
+        }
+        for update in &delta.updates {
+            match update {
+                FaceUpdate::Set { face, inherit: Some((specified, parents)) } => {
+                    changed.push(self.insert_inheriting((**face).clone(), *specified, parents.clone()));
+                }
+                FaceUpdate::Set { face, inherit: None } => changed.push(self.insert((**face).clone())),
+                FaceUpdate::Remove(id) => {
+                    self.remove(*id);
+                    changed.push(*id);
+                }
+            }
+        }
+        // Faces inheriting from changed faces change with them
+        let mut i = 0;
+        while i < changed.len() {
+            let id = changed[i];
+            for (child, (_, parents)) in &self.inheritance {
+                if parents.contains(&id) && !changed.contains(child) {
+                    changed.push(*child);
+                }
+            }
+            i += 1;
+        }
+        changed.sort_unstable();
+        changed.dedup();
+        self.generation = delta.generation;
+        Some(changed)
+    }
+
+    /// Record a change to face `id`
+    fn touch(&mut self, id: u32) {
+        self.merged.clear();
+        self.generation += 1;
+        self.changed.insert(id, self.generation);
+    }
+
+    fn store(&mut self, face: Face) -> u32 {
+        self.touch(face.id);
         let id = face.id;
         if let Some(existing) = self.faces.iter_mut().find(|f| f.id == face.id) {
             *existing = face;
@@ -220,6 +615,43 @@ impl FaceCache {
     pub fn default_face(&self) -> Option<&Face> {
         self.get(0)
     }
+
+    /// Face `id` with its inherited attributes resolved
+    pub fn merged(&mut self, id: u32) -> Option<&Face> {
+        if !self.merged.contains_key(&id) {
+            let (mut face, specified) = self.resolve(id, 0)?;
+            if id != 0 {
+                if let Some((default, _)) = self.resolve(0, 0) {
+                    face.merge_from(&default, FaceFields::all() - specified);
+                }
+            }
+            self.merged.insert(id, face);
+        }
+        self.merged.get(&id)
+    }
+
+    /// Face `id` merged with its parents, and the attribute groups that
+    /// chain specifies
+    fn resolve(&self, id: u32, depth: usize) -> Option<(Face, FaceFields)> {
+        let face = self.get(id)?;
+        let Some((own, parents)) = self.inheritance.get(&id) else {
+            return Some((face.clone(), FaceFields::all()));
+        };
+        let mut merged = face.clone();
+        let mut specified = *own;
+        if depth < MAX_INHERIT_DEPTH {
+            for parent in parents {
+                if specified.is_all() {
+                    break;
+                }
+                if let Some((parent_face, parent_specified)) = self.resolve(*parent, depth + 1) {
+                    merged.merge_from(&parent_face, parent_specified - specified);
+                    specified |= parent_specified;
+                }
+            }
+        }
+        Some((merged, specified))
+    }
 }
 
 #[cfg(test)]
@@ -246,4 +678,182 @@ mod tests {
         assert!(desc.contains("Italic"));
         assert!(desc.contains("14"));
     }
+
+    #[test]
+    fn test_face_cache_merged_inheritance() {
+        let mut cache = FaceCache::new();
+        let mut default = Face::new(0);
+        default.font_family = "Mono".to_string();
+        default.font_size = 14.0;
+        cache.insert(default);
+
+        let mut bold = Face::new(1);
+        bold.font_weight = 700;
+        bold.attributes = FaceAttributes::BOLD;
+        bold.foreground = Color::new(1.0, 0.0, 0.0, 1.0);
+        cache.insert_inheriting(bold, FaceFields::WEIGHT | FaceFields::FOREGROUND, vec![]);
+
+        let mut italic = Face::new(2);
+        italic.attributes = FaceAttributes::ITALIC;
+        italic.foreground = Color::new(0.0, 0.0, 1.0, 1.0);
+        cache.insert_inheriting(italic, FaceFields::SLANT | FaceFields::FOREGROUND, vec![]);
+
+        // Own attributes win, then parents in order, then the default face
+        let mut child = Face::new(3);
+        child.font_size = 20.0;
+        cache.insert_inheriting(child, FaceFields::HEIGHT, vec![2, 1]);
+        let merged = cache.merged(3).unwrap();
+        assert!(merged.is_bold() && merged.is_italic());
+        assert_eq!(merged.foreground, Color::new(0.0, 0.0, 1.0, 1.0));
+        assert_eq!(merged.font_size, 20.0);
+        assert_eq!(merged.font_family, "Mono");
+
+        // Updating a parent invalidates merged children
+        let mut italic = Face::new(2);
+        italic.foreground = Color::new(0.0, 1.0, 0.0, 1.0);
+        cache.insert_inheriting(italic, FaceFields::FOREGROUND, vec![]);
+        let merged = cache.merged(3).unwrap();
+        assert!(!merged.is_italic());
+        assert_eq!(merged.foreground, Color::new(0.0, 1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_face_cache_delta() {
+        let mut sender = FaceCache::new();
+        let mut receiver = FaceCache::new();
+        let mut red = Face::new(1);
+        red.foreground = Color::new(1.0, 0.0, 0.0, 1.0);
+        sender.insert(red.clone());
+        sender.insert(Face::new(2));
+        sender.insert_inheriting(Face::new(3), FaceFields::empty(), vec![1]);
+
+        let full = sender.delta_since(0);
+        assert_eq!(full.updates.len(), 3);
+        assert_eq!(receiver.apply_delta(&full), Some(vec![1, 2, 3]));
+        assert_eq!(receiver.merged(3).unwrap().foreground, red.foreground);
+
+        // Re-inserting an identical face is not a change
+        let base = sender.generation();
+        sender.insert(red.clone());
+        assert!(sender.delta_since(base).is_empty());
+
+        // Only changed faces are sent; dependents are reported
+        red.foreground = Color::new(0.0, 0.0, 1.0, 1.0);
+        sender.insert(red.clone());
+        sender.remove(2);
+        let delta = sender.delta_since(base);
+        assert_eq!(delta.updates.len(), 2);
+        assert_eq!(delta.updates[1], FaceUpdate::Remove(2));
+        assert_eq!(receiver.apply_delta(&delta), Some(vec![1, 2, 3]));
+        assert!(receiver.get(2).is_none());
+        assert_eq!(receiver.merged(3).unwrap().foreground, red.foreground);
+
+        // A delta from the wrong generation is refused
+        assert_eq!(receiver.apply_delta(&delta), None);
+        assert_eq!(receiver.generation(), sender.generation());
+    }
+
+    #[test]
+    fn test_face_delta_then() {
+        let mut sender = FaceCache::new();
+        sender.insert(Face::new(1));
+        sender.insert(Face::new(2));
+        let mut full = sender.delta_since(0);
+
+        // Two deltas folded apply like both in turn
+        let base = sender.generation();
+        let mut red = Face::new(1);
+        red.foreground = Color::new(1.0, 0.0, 0.0, 1.0);
+        sender.insert(red.clone());
+        let mut folded = sender.delta_since(base);
+        let middle = sender.generation();
+        sender.remove(2);
+        sender.insert(Face::new(3));
+        folded.then(sender.delta_since(middle));
+        assert_eq!(folded.base, base);
+        assert_eq!(folded.generation, sender.generation());
+        assert_eq!(folded.updates.len(), 3);
+
+        let mut receiver = FaceCache::new();
+        receiver.apply_delta(&full);
+        assert!(receiver.apply_delta(&folded).is_some());
+        assert_eq!(receiver.get(1).unwrap().foreground, red.foreground);
+        assert!(receiver.get(2).is_none());
+        assert!(receiver.get(3).is_some());
+
+        // Folded into a full delta, removed faces are dropped
+        full.then(folded);
+        assert_eq!(full.base, 0);
+        assert_eq!(full.updates.len(), 2);
+        assert_eq!(full.generation, sender.generation());
+        let mut fresh = FaceCache::new();
+        fresh.apply_delta(&full);
+        assert!(fresh.get(2).is_none());
+        assert_eq!(fresh.get(1).unwrap().foreground, red.foreground);
+
+        // A full delta replaces whatever came before
+        let mut stale = sender.delta_since(base);
+        stale.then(sender.delta_since(0));
+        assert_eq!(stale, sender.delta_since(0));
+    }
+
+    #[test]
+    fn test_face_cache_inheritance_cycle() {
+        let mut cache = FaceCache::new();
+        cache.insert_inheriting(Face::new(1), FaceFields::empty(), vec![2]);
+        cache.insert_inheriting(Face::new(2), FaceFields::empty(), vec![1]);
+        assert!(cache.merged(1).is_some());
+        assert!(cache.merged(9).is_none());
+    }
+
+    #[test]
+    fn test_background_gradient_corners() {
+        let from = Color::new(0.0, 0.0, 0.0, 1.0);
+        let to = Color::new(1.0, 1.0, 1.0, 1.0);
+        let rect = Rect::new(10.0, 10.0, 200.0, 20.0);
+        // Left to right: left corners `from`, right corners `to`
+        let [tl, tr, br, bl] = BackgroundStyle::Gradient { from, to, angle: 0.0 }
+            .gradient_corners(&rect).unwrap();
+        assert_eq!((tl, bl), (from, from));
+        assert_eq!((tr, br), (to, to));
+        // Top to bottom
+        let [tl, tr, br, _] = BackgroundStyle::Gradient { from, to, angle: 90.0 }
+            .gradient_corners(&rect).unwrap();
+        assert!(tl.r < 1e-5 && tr.r < 1e-5 && (br.r - 1.0).abs() < 1e-5);
+        assert!(BackgroundStyle::Image { image_id: 1 }.gradient_corners(&rect).is_none());
+    }
+
+    #[test]
+    fn test_merge_background_runs() {
+        let cell = |face, x, y| (face, Rect::new(x, y, 8.0, 16.0));
+        let runs = merge_background_runs(vec![
+            cell(1, 8.0, 0.0), cell(1, 0.0, 0.0), cell(2, 16.0, 0.0), cell(1, 0.0, 16.0),
+        ]);
+        assert_eq!(runs, vec![
+            (1, Rect::new(0.0, 0.0, 16.0, 16.0)),
+            (2, Rect::new(16.0, 0.0, 8.0, 16.0)),
+            (1, Rect::new(0.0, 16.0, 8.0, 16.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_cover_tex_coords() {
+        // A square image over a wide strip shows its middle band
+        assert_eq!(cover_tex_coords(400.0, 100.0, 100.0, 100.0), [0.0, 0.375, 1.0, 0.625]);
+        assert_eq!(cover_tex_coords(100.0, 100.0, 50.0, 50.0), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_fallback_fit_matches_text_height() {
+        // Emoji font 1.2em tall (0.95 + 0.25), text font 1.0em (0.8 + 0.2)
+        let (scale, shift) = FallbackMetrics::default().fit((0.8, 0.2), (0.95, 0.25));
+        assert!((scale - 1.0 / 1.2).abs() < 1e-4);
+        // Fallback top (0.95 * scale) lands on the text ascent
+        assert!((0.95 * scale + shift - 0.8).abs() < 1e-4);
+
+        let off = FallbackMetrics { enabled: false, ..Default::default() };
+        assert_eq!(off.fit((0.8, 0.2), (0.95, 0.25)), (1.0, 0.0));
+        let huge = FallbackMetrics { scale: 10.0, ..Default::default() };
+        assert_eq!(huge.fit((0.8, 0.2), (0.8, 0.2)).0, 2.0);
+    }
 }
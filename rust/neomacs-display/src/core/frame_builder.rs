@@ -0,0 +1,344 @@
+//! Checked construction of frame glyph buffers.
+//!
+//! The C matrix walker fills a [`FrameGlyphBuffer`] through its low-level
+//! `add_*` calls.  Other hosts, such as remote clients, and tests build
+//! frames with [`FrameBuilder`] instead: it validates coordinates, groups
+//! glyphs by window and drops glyphs hidden by a later one at the same
+//! place.
+
+use std::collections::HashMap;
+
+use crate::core::error::{DisplayError, DisplayResult};
+use crate::core::face::{Face, FaceAttributes};
+use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer, WindowInfo};
+use crate::core::types::{Color, Rect};
+
+/// Slack allowed when checking that glyphs lie inside the frame or window
+const EPSILON: f32 = 0.5;
+
+/// Glyphs of one window, or of the frame outside any window
+#[derive(Debug, Default)]
+struct Bucket {
+    /// Window background, for windows
+    background: Option<FrameGlyph>,
+    /// Stretch glyphs, drawn under the content
+    backgrounds: Vec<FrameGlyph>,
+    /// Char and image glyphs
+    content: Vec<FrameGlyph>,
+    /// Index in `content` of the glyph in each cell
+    cells: HashMap<Cell, usize>,
+}
+
+impl Bucket {
+    fn push_background(&mut self, glyph: FrameGlyph, rect: Rect, overlay: bool) {
+        // A background covering earlier ones hides them
+        self.backgrounds.retain(|g| match g {
+            FrameGlyph::Stretch { x, y, width, height, is_overlay, .. } => {
+                *is_overlay != overlay || !covers(&rect, &Rect::new(*x, *y, *width, *height))
+            }
+            _ => true,
+        });
+        self.backgrounds.push(glyph);
+    }
+
+    fn push_content(&mut self, glyph: FrameGlyph) {
+        // A glyph drawn in the same cell replaces the earlier one
+        let Some(cell) = glyph_cell(&glyph) else {
+            self.content.push(glyph);
+            return;
+        };
+        match self.cells.get(&cell) {
+            Some(&index) => self.content[index] = glyph,
+            None => {
+                self.cells.insert(cell, self.content.len());
+                self.content.push(glyph);
+            }
+        }
+    }
+
+    fn drain_into(self, glyphs: &mut Vec<FrameGlyph>) {
+        glyphs.extend(self.background);
+        glyphs.extend(self.backgrounds);
+        glyphs.extend(self.content);
+    }
+}
+
+/// Whether `outer` covers all of `inner`
+fn covers(outer: &Rect, inner: &Rect) -> bool {
+    outer.x <= inner.x + 0.01
+        && outer.y <= inner.y + 0.01
+        && outer.right() >= inner.right() - 0.01
+        && outer.bottom() >= inner.bottom() - 0.01
+}
+
+/// Kind, overlay flag and rounded position identifying the cell a content
+/// glyph occupies
+type Cell = (u8, bool, i32, i32);
+
+fn glyph_cell(glyph: &FrameGlyph) -> Option<Cell> {
+    let cell = |kind, overlay, x: f32, y: f32| Some((kind, overlay, (x * 4.0).round() as i32, (y * 4.0).round() as i32));
+    match glyph {
+        FrameGlyph::Char { x, y, is_overlay, .. } => cell(0, *is_overlay, *x, *y),
+        FrameGlyph::Image { x, y, .. } => cell(1, false, *x, *y),
+        _ => None,
+    }
+}
+
+/// Builds a [`FrameGlyphBuffer`] from validated runs of text, backgrounds
+/// and images, grouped by window
+#[derive(Debug)]
+pub struct FrameBuilder {
+    frame: FrameGlyphBuffer,
+    /// Finished windows, in the order they were begun
+    windows: Vec<Bucket>,
+    /// Window being filled and its bounds
+    window: Option<(Bucket, Rect)>,
+    /// Glyphs outside any window, such as dividers
+    outside: Bucket,
+    overlay: bool,
+}
+
+impl FrameBuilder {
+    /// Start a `width` x `height` frame cleared to `background`
+    pub fn new(width: f32, height: f32, background: Color) -> DisplayResult<Self> {
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return Err(DisplayError::InvalidFrame(format!("frame size {}x{}", width, height)));
+        }
+        let mut frame = FrameGlyphBuffer::with_size(width, height);
+        frame.background = background;
+        Ok(Self {
+            frame,
+            windows: Vec::new(),
+            window: None,
+            outside: Bucket::default(),
+            overlay: false,
+        })
+    }
+
+    /// Start collecting the glyphs of the window described by `info`,
+    /// filled with `background`
+    pub fn begin_window(&mut self, info: WindowInfo, background: Color) -> DisplayResult<()> {
+        if self.window.is_some() {
+            return Err(DisplayError::InvalidFrame("window begun inside another window".into()));
+        }
+        let bounds = info.bounds;
+        self.check_rect(&bounds)?;
+        if self.frame.window_infos.iter().any(|w| w.window_id == info.window_id) {
+            return Err(DisplayError::InvalidFrame(format!("window {} added twice", info.window_id)));
+        }
+        self.frame.window_regions.push(bounds);
+        self.frame.window_infos.push(info);
+        let bucket = Bucket {
+            background: Some(FrameGlyph::Background { bounds, color: background }),
+            ..Bucket::default()
+        };
+        self.window = Some((bucket, bounds));
+        Ok(())
+    }
+
+    /// Finish the window begun last
+    pub fn end_window(&mut self) -> DisplayResult<()> {
+        let (bucket, _) = self
+            .window
+            .take()
+            .ok_or_else(|| DisplayError::InvalidFrame("end_window without begin_window".into()))?;
+        self.windows.push(bucket);
+        self.overlay = false;
+        Ok(())
+    }
+
+    /// Mark the glyphs pushed from now on as mode-line or echo area
+    /// content, drawn over everything else
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    /// Fill `rect` with the background of face `face_id`
+    pub fn push_background(&mut self, rect: Rect, color: Color, face_id: u32) -> DisplayResult<()> {
+        self.check_rect(&rect)?;
+        let overlay = self.overlay;
+        let glyph = FrameGlyph::Stretch {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+            bg: color,
+            face_id,
+            is_overlay: overlay,
+        };
+        self.bucket().push_background(glyph, rect, overlay);
+        Ok(())
+    }
+
+    /// Add `text` in `face` from (`x`, `y`), each character `advance`
+    /// wide, on a row `height` tall with its baseline `ascent` below `y`
+    #[allow(clippy::too_many_arguments)]
+    pub fn push_char_run(
+        &mut self,
+        x: f32,
+        y: f32,
+        text: &str,
+        advance: f32,
+        height: f32,
+        ascent: f32,
+        face: &Face,
+    ) -> DisplayResult<()> {
+        let count = text.chars().count() as f32;
+        self.check_rect(&Rect::new(x, y, advance * count, height))?;
+        if !(ascent.is_finite() && (0.0..=height + EPSILON).contains(&ascent)) {
+            return Err(DisplayError::InvalidGlyph(format!("ascent {} for row height {}", ascent, height)));
+        }
+        self.frame.face_fonts.insert(face.id, face.font_family.as_str().into());
+        self.frame.faces.entry(face.id).or_insert_with(|| face.clone());
+        let overlay = self.overlay;
+        let underline = face.underline_style as u8;
+        let strike_through = face.attributes.contains(FaceAttributes::STRIKE_THROUGH);
+        let overline = face.attributes.contains(FaceAttributes::OVERLINE);
+        for (i, ch) in text.chars().enumerate() {
+            let glyph = FrameGlyph::Char {
+                char: ch,
+                composed: None,
+                x: x + advance * i as f32,
+                y,
+                width: advance,
+                height,
+                ascent,
+                fg: face.foreground,
+                bg: None,
+                face_id: face.id,
+                bold: face.is_bold(),
+                italic: face.is_italic(),
+                font_size: face.font_size,
+                underline,
+                underline_color: face.underline_color,
+                strike_through: strike_through as u8,
+                strike_through_color: face.strike_through_color,
+                overline: overline as u8,
+                overline_color: face.overline_color,
+                is_overlay: overlay,
+            };
+            self.bucket().push_content(glyph);
+        }
+        Ok(())
+    }
+
+    /// Add image `image_id` drawn into `rect`
+    pub fn push_image(&mut self, image_id: u32, rect: Rect) -> DisplayResult<()> {
+        self.check_rect(&rect)?;
+        let glyph = FrameGlyph::Image {
+            image_id,
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        };
+        self.bucket().push_content(glyph);
+        Ok(())
+    }
+
+    /// The finished frame, with glyphs grouped by window
+    pub fn build(mut self) -> DisplayResult<FrameGlyphBuffer> {
+        if self.window.is_some() {
+            return Err(DisplayError::InvalidFrame("window not ended".into()));
+        }
+        let glyphs = &mut self.frame.glyphs;
+        for bucket in self.windows {
+            bucket.drain_into(glyphs);
+        }
+        self.outside.drain_into(glyphs);
+        Ok(self.frame)
+    }
+
+    fn bucket(&mut self) -> &mut Bucket {
+        match self.window {
+            Some((ref mut bucket, _)) => bucket,
+            None => &mut self.outside,
+        }
+    }
+
+    /// Check that `rect` is finite, not negative and inside the frame and
+    /// the current window
+    fn check_rect(&self, rect: &Rect) -> DisplayResult<()> {
+        let finite = [rect.x, rect.y, rect.width, rect.height].iter().all(|v| v.is_finite());
+        if !finite || rect.width < 0.0 || rect.height < 0.0 {
+            return Err(DisplayError::InvalidGlyph(format!("bad rect {:?}", rect)));
+        }
+        let frame = Rect::new(-EPSILON, -EPSILON, self.frame.width + 2.0 * EPSILON, self.frame.height + 2.0 * EPSILON);
+        if !covers(&frame, rect) {
+            return Err(DisplayError::InvalidGlyph(format!("{:?} outside the frame", rect)));
+        }
+        if let Some((_, bounds)) = &self.window {
+            let window = Rect::new(bounds.x - EPSILON, bounds.y - EPSILON, bounds.width + 2.0 * EPSILON, bounds.height + 2.0 * EPSILON);
+            if !covers(&window, rect) {
+                return Err(DisplayError::InvalidGlyph(format!("{:?} outside window {:?}", rect, bounds)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: i64, bounds: Rect) -> WindowInfo {
+        WindowInfo {
+            window_id: id,
+            buffer_id: 1,
+            window_start: 1,
+            window_end: 1,
+            buffer_size: 1,
+            bounds,
+            mode_line_height: 0.0,
+            selected: id == 1,
+            is_minibuffer: false,
+            char_height: 16.0,
+            buffer_file_name: String::new(),
+            modified: false,
+        }
+    }
+
+    #[test]
+    fn test_frame_builder_groups_and_dedups() {
+        let mut b = FrameBuilder::new(200.0, 100.0, Color::BLACK).unwrap();
+        let face = Face::new(3);
+        b.begin_window(window(1, Rect::new(0.0, 0.0, 100.0, 100.0)), Color::BLACK).unwrap();
+        b.push_char_run(0.0, 0.0, "ab", 8.0, 16.0, 12.0, &face).unwrap();
+        b.push_background(Rect::new(0.0, 0.0, 8.0, 16.0), Color::WHITE, 3).unwrap();
+        // Covers the previous background and redraws the `b` cell
+        b.push_background(Rect::new(0.0, 0.0, 16.0, 16.0), Color::WHITE, 3).unwrap();
+        b.push_char_run(8.0, 0.0, "c", 8.0, 16.0, 12.0, &face).unwrap();
+        b.end_window().unwrap();
+        b.begin_window(window(2, Rect::new(100.0, 0.0, 100.0, 100.0)), Color::BLACK).unwrap();
+        b.push_image(7, Rect::new(100.0, 0.0, 50.0, 50.0)).unwrap();
+        b.end_window().unwrap();
+        let frame = b.build().unwrap();
+
+        let kinds: Vec<&str> = frame.glyphs.iter().map(|g| match g {
+            FrameGlyph::Background { .. } => "window",
+            FrameGlyph::Stretch { .. } => "bg",
+            FrameGlyph::Char { char, .. } => if *char == 'a' { "a" } else { "c" },
+            FrameGlyph::Image { .. } => "image",
+            _ => "other",
+        }).collect();
+        assert_eq!(kinds, ["window", "bg", "a", "c", "window", "image"]);
+        assert_eq!(frame.window_infos.len(), 2);
+        assert!(frame.faces.contains_key(&3));
+    }
+
+    #[test]
+    fn test_frame_builder_validation() {
+        let mut b = FrameBuilder::new(200.0, 100.0, Color::BLACK).unwrap();
+        let face = Face::new(0);
+        assert!(b.push_image(1, Rect::new(190.0, 0.0, 20.0, 10.0)).is_err());
+        assert!(b.push_background(Rect::new(f32::NAN, 0.0, 1.0, 1.0), Color::WHITE, 0).is_err());
+        assert!(b.end_window().is_err());
+        b.begin_window(window(1, Rect::new(0.0, 0.0, 100.0, 100.0)), Color::BLACK).unwrap();
+        assert!(b.begin_window(window(2, Rect::new(100.0, 0.0, 100.0, 100.0)), Color::BLACK).is_err());
+        // Runs past the window edge are rejected
+        assert!(b.push_char_run(96.0, 0.0, "xy", 8.0, 16.0, 12.0, &face).is_err());
+        assert!(b.push_char_run(0.0, 0.0, "x", 8.0, 16.0, 20.0, &face).is_err());
+        assert!(FrameBuilder::new(0.0, 10.0, Color::BLACK).is_err());
+        assert!(b.build().is_err());
+    }
+}
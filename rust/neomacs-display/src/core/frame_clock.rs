@@ -0,0 +1,129 @@
+//! Frame clock predicting when a frame reaches the screen.
+//!
+//! Animations look smoothest when they are sampled at the time the frame
+//! is shown rather than the time it is rendered, which can be a refresh
+//! interval or more apart.  winit and wgpu do not expose the compositor's
+//! presentation feedback, so the clock learns it from what it can see:
+//! the monitor refresh rate, the spacing of consecutive presents (with
+//! FIFO presentation these follow the display's vblank) and how long a
+//! frame takes from the start of rendering to present.
+
+use std::time::{Duration, Instant};
+
+/// Refresh rate assumed until the monitor reports one
+const DEFAULT_REFRESH_HZ: f64 = 60.0;
+
+/// Estimates display timing from observed presents
+#[derive(Debug, Clone)]
+pub struct FrameClock {
+    /// Estimated refresh interval
+    refresh: Duration,
+    /// When the last frame was presented
+    last_present: Option<Instant>,
+    /// When rendering of the current frame started
+    frame_start: Option<Instant>,
+    /// Smoothed time from the start of rendering to the present call.
+    /// Not scanout latency: compositor delay after present is not seen
+    render_to_present: Duration,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self {
+            refresh: Duration::from_secs_f64(1.0 / DEFAULT_REFRESH_HZ),
+            last_present: None,
+            frame_start: None,
+            render_to_present: Duration::ZERO,
+        }
+    }
+}
+
+impl FrameClock {
+    /// Use the refresh rate reported by the monitor, in millihertz
+    pub fn set_refresh_rate_millihertz(&mut self, mhz: u32) {
+        if mhz > 0 {
+            self.refresh = Duration::from_secs_f64(1000.0 / mhz as f64);
+        }
+    }
+
+    /// Note that rendering of a frame starts at `now`
+    pub fn begin_frame(&mut self, now: Instant) {
+        self.frame_start = Some(now);
+    }
+
+    /// Note that the frame begun last was presented at `now`
+    pub fn frame_presented(&mut self, now: Instant) {
+        if let Some(start) = self.frame_start.take() {
+            let sample = now.saturating_duration_since(start);
+            self.render_to_present = self.render_to_present.mul_f64(0.9) + sample.mul_f64(0.1);
+        }
+        if let Some(last) = self.last_present {
+            // Back-to-back frames reveal the real refresh interval; longer
+            // gaps are idle time and tell nothing
+            let interval = now.saturating_duration_since(last);
+            let r = interval.as_secs_f64() / self.refresh.as_secs_f64();
+            if (0.75..1.25).contains(&r) {
+                self.refresh = self.refresh.mul_f64(0.9) + interval.mul_f64(0.1);
+            }
+        }
+        self.last_present = Some(now);
+    }
+
+    /// Estimated refresh interval of the display
+    pub fn refresh_interval(&self) -> Duration {
+        self.refresh
+    }
+
+    /// Smoothed time from the start of rendering to present
+    pub fn render_to_present(&self) -> Duration {
+        self.render_to_present
+    }
+
+    /// When a frame whose rendering starts at `now` is expected to be
+    /// shown: the first vblank after it can be presented
+    pub fn predicted_presentation(&self, now: Instant) -> Instant {
+        let ready = now + self.render_to_present;
+        let Some(last) = self.last_present else { return ready };
+        let since = ready.saturating_duration_since(last);
+        let frames = (since.as_secs_f64() / self.refresh.as_secs_f64()).ceil().max(1.0);
+        last + self.refresh.mul_f64(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prediction_lands_on_next_vblank() {
+        let mut clock = FrameClock::default();
+        clock.set_refresh_rate_millihertz(100_000); // 10ms
+        let t0 = Instant::now();
+        clock.begin_frame(t0);
+        clock.frame_presented(t0);
+
+        // Rendering 3ms after a vblank is shown at the next one
+        let predicted = clock.predicted_presentation(t0 + Duration::from_millis(3));
+        assert_eq!(predicted, t0 + Duration::from_millis(10));
+        // Right after a present, the next vblank is still a full interval away
+        assert_eq!(clock.predicted_presentation(t0), t0 + Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_refresh_interval_learned_from_presents() {
+        let mut clock = FrameClock::default();
+        let t0 = Instant::now();
+        let mut t = t0;
+        for _ in 0..200 {
+            clock.begin_frame(t);
+            t += Duration::from_micros(16_000);
+            clock.frame_presented(t);
+        }
+        assert!((clock.refresh_interval().as_secs_f64() - 0.016).abs() < 0.0002);
+        assert!((clock.render_to_present().as_secs_f64() - 0.016).abs() < 0.0002);
+
+        // A long idle gap is not taken as a refresh interval
+        clock.frame_presented(t + Duration::from_secs(1));
+        assert!((clock.refresh_interval().as_secs_f64() - 0.016).abs() < 0.0002);
+    }
+}
@@ -4,12 +4,15 @@
 //! Emacs's current_matrix and rebuilds this buffer from scratch. No
 //! incremental overlap tracking is needed.
 
-use crate::core::face::Face;
+use crate::core::face::{Face, FaceDelta};
+use crate::core::nine_patch::{ChromeElement, ChromeRegion};
 use crate::core::types::{Color, Rect};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A single glyph to render
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrameGlyph {
     /// Character glyph with text
     Char {
@@ -17,7 +20,8 @@ pub enum FrameGlyph {
         char: char,
         /// Composed text for multi-codepoint grapheme clusters (emoji ZWJ, combining marks).
         /// When Some, the renderer uses this instead of `char` for glyph lookup.
-        composed: Option<Box<str>>,
+        /// Shared so that copying the frame for the render thread is cheap.
+        composed: Option<Arc<str>>,
         /// Frame-absolute X position
         x: f32,
         /// Frame-absolute Y position
@@ -165,8 +169,28 @@ impl FrameGlyph {
     }
 }
 
+/// A region whose backdrop is blurred before overlay content is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlurRegion {
+    /// Stable ID, so the blurred backdrop can be reused across frames
+    pub id: u64,
+    pub bounds: Rect,
+    /// Corner radius of the blurred area
+    pub corner_radius: f32,
+}
+
+/// An image drawn under overlay backgrounds, scaled to cover `bounds`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackdropImage {
+    pub image_id: u32,
+    pub bounds: Rect,
+}
+
 /// Inverse video info for the character under a filled box cursor
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub struct CursorInverseInfo {
     pub x: f32,
     pub y: f32,
@@ -178,8 +202,26 @@ pub struct CursorInverseInfo {
     pub cursor_fg: Color,
 }
 
+impl CursorInverseInfo {
+    /// Whether a glyph cell starts at the cursor position
+    pub fn is_at(&self, x: f32, y: f32) -> bool {
+        (x - self.x).abs() < 1.0 && (y - self.y).abs() < 1.0
+    }
+
+    /// The animated cursor rect, if the cursor is still moving toward its
+    /// static position.  Text under it must be re-rendered inverted.
+    pub fn moving_rect(&self, anim: &crate::core::types::AnimatedCursor) -> Option<Rect> {
+        let moved = (anim.x - self.x).abs() > 0.5
+            || (anim.y - self.y).abs() > 0.5
+            || (anim.width - self.width).abs() > 0.5
+            || (anim.height - self.height).abs() > 0.5;
+        moved.then(|| Rect::new(anim.x, anim.y, anim.width, anim.height))
+    }
+}
+
 /// Per-window metadata for animation transition detection
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub struct WindowInfo {
     /// Window pointer as i64 (unique window identifier)
     pub window_id: i64,
@@ -212,6 +254,7 @@ pub struct WindowInfo {
 /// With matrix-based rendering, this buffer is cleared and rebuilt from scratch
 /// each frame by the C-side matrix walker. No incremental state management needed.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameGlyphBuffer {
     /// Frame dimensions
     pub width: f32,
@@ -245,25 +288,52 @@ pub struct FrameGlyphBuffer {
     pub layout_changed: bool,
 
     /// Current face attributes (set before adding char glyphs)
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_face_id: u32,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_fg: Color,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_bg: Option<Color>,
-    current_font_family: String,
+    #[cfg_attr(feature = "remote", serde(skip))]
+    current_font_family: Arc<str>,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_bold: bool,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_italic: bool,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_font_size: f32,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_underline: u8,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_underline_color: Option<Color>,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_strike_through: u8,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_strike_through_color: Option<Color>,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_overline: u8,
+    #[cfg_attr(feature = "remote", serde(skip))]
     current_overline_color: Option<Color>,
 
     /// Font family cache: face_id -> font_family
-    pub face_fonts: HashMap<u32, String>,
+    pub face_fonts: HashMap<u32, Arc<str>>,
 
     /// Full face data: face_id -> Face (includes box, underline, etc.)
     pub faces: HashMap<u32, Face>,
+
+    /// Faces changed since the previous frame sent to the render thread
+    #[cfg_attr(feature = "remote", serde(default))]
+    pub face_delta: FaceDelta,
+
+    /// Regions whose backdrop is blurred under overlay content
+    pub blur_regions: Vec<BlurRegion>,
+
+    /// Images drawn behind overlay content, such as terminal backgrounds
+    pub backdrop_images: Vec<BackdropImage>,
+
+    /// Tab bars and child frames, for drawing their nine-patch images
+    #[cfg_attr(feature = "remote", serde(default))]
+    pub chrome_regions: Vec<ChromeRegion>,
 }
 
 impl FrameGlyphBuffer {
@@ -284,7 +354,7 @@ impl FrameGlyphBuffer {
             current_face_id: 0,
             current_fg: Color::WHITE,
             current_bg: None,
-            current_font_family: "monospace".to_string(),
+            current_font_family: "monospace".into(),
             current_bold: false,
             current_italic: false,
             current_font_size: 14.0,
@@ -296,6 +366,10 @@ impl FrameGlyphBuffer {
             current_overline_color: None,
             face_fonts: HashMap::new(),
             faces: HashMap::new(),
+            face_delta: FaceDelta::default(),
+            blur_regions: Vec::new(),
+            backdrop_images: Vec::new(),
+            chrome_regions: Vec::new(),
         }
     }
 
@@ -315,6 +389,23 @@ impl FrameGlyphBuffer {
         self.window_regions.clear();
         self.window_infos.clear();
         self.cursor_inverse = None;
+        self.blur_regions.clear();
+        self.backdrop_images.clear();
+        self.chrome_regions.clear();
+    }
+
+    /// Mark `bounds` as chrome of `element`
+    pub fn add_chrome_region(&mut self, element: ChromeElement, bounds: Rect) {
+        self.chrome_regions.push(ChromeRegion { element, bounds });
+    }
+
+    /// Ask for the backdrop of `region` to be blurred, replacing any
+    /// earlier request with the same ID
+    pub fn request_blur(&mut self, region: BlurRegion) {
+        match self.blur_regions.iter_mut().find(|r| r.id == region.id) {
+            Some(existing) => *existing = region,
+            None => self.blur_regions.push(region),
+        }
     }
 
     /// Start new frame - prepare for new content (compatibility shim)
@@ -353,7 +444,11 @@ impl FrameGlyphBuffer {
         self.current_face_id = face_id;
         self.current_fg = fg;
         self.current_bg = bg;
-        self.current_font_family = font_family.to_string();
+        // Faces keep their family across frames; only a new one allocates
+        if self.face_fonts.get(&face_id).is_none_or(|f| **f != *font_family) {
+            self.face_fonts.insert(face_id, font_family.into());
+        }
+        self.current_font_family = self.face_fonts[&face_id].clone();
         self.current_bold = bold;
         self.current_italic = italic;
         self.current_font_size = font_size;
@@ -363,7 +458,6 @@ impl FrameGlyphBuffer {
         self.current_strike_through_color = strike_through_color;
         self.current_overline = overline;
         self.current_overline_color = overline_color;
-        self.face_fonts.insert(face_id, font_family.to_string());
     }
 
     /// Set current face attributes for subsequent char glyphs
@@ -384,9 +478,16 @@ impl FrameGlyphBuffer {
         self.current_overline_color = overline_color;
     }
 
+    /// Recolor the current face for a syntax highlight span
+    pub fn set_face_highlight(&mut self, fg: Color, bold: bool, italic: bool) {
+        self.current_fg = fg;
+        self.current_bold = bold;
+        self.current_italic = italic;
+    }
+
     /// Get font family for a face_id
     pub fn get_face_font(&self, face_id: u32) -> &str {
-        self.face_fonts.get(&face_id).map(|s| s.as_str()).unwrap_or("monospace")
+        self.face_fonts.get(&face_id).map(|s| &**s).unwrap_or("monospace")
     }
 
     /// Get current font family
@@ -574,3 +675,27 @@ impl FrameGlyphBuffer {
         self.glyphs.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::AnimatedCursor;
+
+    #[test]
+    fn test_cursor_inverse_moving_rect() {
+        let inv = CursorInverseInfo {
+            x: 80.0, y: 32.0, width: 8.0, height: 16.0,
+            cursor_bg: Color::WHITE, cursor_fg: Color::BLACK,
+        };
+        let mut anim = AnimatedCursor {
+            window_id: 1, x: 80.2, y: 32.0, width: 8.0, height: 16.0, corners: None,
+        };
+        assert!(inv.moving_rect(&anim).is_none());
+        assert!(inv.is_at(80.4, 32.0));
+
+        anim.x = 60.0;
+        let rect = inv.moving_rect(&anim).unwrap();
+        assert_eq!((rect.x, rect.width), (60.0, 8.0));
+        assert!(!inv.is_at(60.0, 32.0));
+    }
+}
@@ -0,0 +1,64 @@
+//! Statistics of the most recently rendered frame.
+//!
+//! The render thread publishes these after presenting each frame; the
+//! Emacs side reads them over FFI to tune redisplay, e.g. turning off
+//! expensive effects when frames run late, and to put numbers in bug
+//! reports.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// What went into the last frame and how long it took
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    /// Frames rendered since the display started
+    pub frames: u64,
+    /// Glyphs in the frame, terminal cells included
+    pub glyphs: usize,
+    pub windows: usize,
+    /// Cell background rects left after the overdraw pass
+    pub background_rects: usize,
+    /// Text rows reused from the row cache, and rebuilt
+    pub row_cache_hits: usize,
+    pub row_cache_misses: usize,
+    /// Updating terminals, media and faces before drawing
+    pub prepare_time: Duration,
+    /// Recording and submitting the draw commands
+    pub draw_time: Duration,
+    /// Handing the frame to the compositor
+    pub present_time: Duration,
+    /// Frames from Emacs replaced by a newer one before being drawn, in total
+    pub skipped_frames: u64,
+    /// Frames that took longer than a refresh interval, in total
+    pub late_frames: u64,
+}
+
+impl FrameStats {
+    pub fn total_time(&self) -> Duration {
+        self.prepare_time + self.draw_time + self.present_time
+    }
+}
+
+static LAST_STATS: Mutex<FrameStats> = Mutex::new(FrameStats {
+    frames: 0,
+    glyphs: 0,
+    windows: 0,
+    background_rects: 0,
+    row_cache_hits: 0,
+    row_cache_misses: 0,
+    prepare_time: Duration::ZERO,
+    draw_time: Duration::ZERO,
+    present_time: Duration::ZERO,
+    skipped_frames: 0,
+    late_frames: 0,
+});
+
+/// Record the statistics of a presented frame.
+pub fn publish(stats: FrameStats) {
+    *LAST_STATS.lock().unwrap_or_else(|e| e.into_inner()) = stats;
+}
+
+/// Statistics of the most recently presented frame.
+pub fn last_stats() -> FrameStats {
+    *LAST_STATS.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,115 @@
+//! Whole-frame zoom for presentations and screen sharing.
+//!
+//! The frame is drawn at its normal size and then magnified as a post
+//! transform: the part of it around a focus point is stretched over the
+//! window.  The focus point stays where it is on screen, so zooming around
+//! the cursor keeps the cursor under the viewer's eye.  Changes of zoom
+//! are eased over a short duration.
+
+use std::time::{Duration, Instant};
+
+use super::scroll_animation::ScrollEasing;
+use super::types::Rect;
+
+/// Smallest and largest zoom accepted
+pub const MIN_ZOOM: f32 = 1.0;
+pub const MAX_ZOOM: f32 = 8.0;
+
+/// Zoom level of the frame and its animation
+#[derive(Debug, Clone)]
+pub struct FrameZoom {
+    from: f32,
+    target: f32,
+    start: Instant,
+    duration: Duration,
+    /// Fixed focus point in logical pixels, or None to follow the cursor
+    pub focus: Option<(f32, f32)>,
+}
+
+impl Default for FrameZoom {
+    fn default() -> Self {
+        Self {
+            from: 1.0,
+            target: 1.0,
+            start: Instant::now(),
+            duration: Duration::ZERO,
+            focus: None,
+        }
+    }
+}
+
+impl FrameZoom {
+    /// Zoom to `scale` over `duration`, starting from the current level
+    pub fn set(&mut self, scale: f32, focus: Option<(f32, f32)>, duration: Duration, now: Instant) {
+        self.from = self.scale(now);
+        self.target = scale.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.start = now;
+        self.duration = duration;
+        self.focus = focus;
+    }
+
+    /// Zoom level at `now`
+    pub fn scale(&self, now: Instant) -> f32 {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            now.saturating_duration_since(self.start).as_secs_f32() / self.duration.as_secs_f32()
+        };
+        self.from + (self.target - self.from) * ScrollEasing::EaseOutQuad.apply(t)
+    }
+
+    /// Whether the zoom level is still changing
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.from != self.target && now.saturating_duration_since(self.start) < self.duration
+    }
+
+    /// The part of a `width` x `height` frame that fills the window at
+    /// `now`, magnified around `focus`.  None when the frame is not zoomed.
+    pub fn source_rect(&self, now: Instant, width: f32, height: f32, focus: (f32, f32)) -> Option<Rect> {
+        let scale = self.scale(now);
+        if scale <= MIN_ZOOM + 0.001 {
+            return None;
+        }
+        let (fx, fy) = (focus.0.clamp(0.0, width), focus.1.clamp(0.0, height));
+        Some(Rect::new(
+            fx * (1.0 - 1.0 / scale),
+            fy * (1.0 - 1.0 / scale),
+            width / scale,
+            height / scale,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_keeps_focus_in_place() {
+        let now = Instant::now();
+        let mut zoom = FrameZoom::default();
+        assert!(zoom.source_rect(now, 800.0, 600.0, (0.0, 0.0)).is_none());
+
+        zoom.set(2.0, None, Duration::ZERO, now);
+        let r = zoom.source_rect(now, 800.0, 600.0, (400.0, 150.0)).unwrap();
+        assert_eq!((r.width, r.height), (400.0, 300.0));
+        // The focus sits at the same fraction of the source as of the window
+        assert_eq!((r.x, r.y), (200.0, 75.0));
+        // Zooming around a corner keeps the corner
+        let r = zoom.source_rect(now, 800.0, 600.0, (800.0, 600.0)).unwrap();
+        assert_eq!((r.right(), r.bottom()), (800.0, 600.0));
+    }
+
+    #[test]
+    fn test_zoom_animates_to_target() {
+        let now = Instant::now();
+        let mut zoom = FrameZoom::default();
+        zoom.set(20.0, None, Duration::from_millis(200), now);
+        assert_eq!(zoom.scale(now), 1.0);
+        assert!(zoom.is_animating(now + Duration::from_millis(100)));
+        let mid = zoom.scale(now + Duration::from_millis(100));
+        assert!(mid > 1.0 && mid < MAX_ZOOM);
+        assert_eq!(zoom.scale(now + Duration::from_millis(300)), MAX_ZOOM);
+        assert!(!zoom.is_animating(now + Duration::from_millis(300)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Generational resource handles.
+//!
+//! Image, video, WebKit view, terminal and PDF ids handed to Emacs pack a
+//! slot index and a generation into one `u32`.  When a resource is freed
+//! its slot can be reused, but the generation is bumped first, so an id
+//! kept by Lisp after the resource went away no longer matches and is
+//! rejected with [`DisplayError::StaleHandle`] instead of silently
+//! addressing whatever took its slot.
+//!
+//! The low `INDEX_BITS` bits are the slot index (starting at 1, so 0 is
+//! never a valid id) and the remaining bits the generation.  The first
+//! ids handed out are therefore 1, 2, 3, ... as before.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::error::{DisplayError, DisplayResult};
+use super::error_report::ErrorKind;
+
+/// Bits of an id used for the slot index
+const INDEX_BITS: u32 = 20;
+const INDEX_MASK: u32 = (1 << INDEX_BITS) - 1;
+const GENERATION_MASK: u32 = u32::MAX >> INDEX_BITS;
+
+/// Freed slots are only reused once this many are waiting, so a stale id
+/// stays detectable for a while even before its generation wraps.
+const MIN_FREE_SLOTS: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    generation: u32,
+    live: bool,
+}
+
+#[derive(Debug)]
+struct Slots {
+    slots: Vec<Slot>,
+    /// Indices of freed slots, oldest first
+    free: VecDeque<u32>,
+}
+
+fn split(id: u32) -> (u32, u32) {
+    (id & INDEX_MASK, id >> INDEX_BITS)
+}
+
+fn join(index: u32, generation: u32) -> u32 {
+    (generation << INDEX_BITS) | index
+}
+
+impl Slots {
+    const fn new() -> Self {
+        Self { slots: Vec::new(), free: VecDeque::new() }
+    }
+
+    /// `None` once every index is live
+    fn alloc(&mut self) -> Option<u32> {
+        let full = self.slots.len() as u32 >= INDEX_MASK;
+        if self.free.len() > MIN_FREE_SLOTS || full {
+            if let Some(index) = self.free.pop_front() {
+                let slot = &mut self.slots[index as usize - 1];
+                slot.live = true;
+                return Some(join(index, slot.generation));
+            }
+        }
+        if full {
+            return None;
+        }
+        self.slots.push(Slot { generation: 0, live: true });
+        Some(join(self.slots.len() as u32, 0))
+    }
+
+    fn slot(&self, id: u32) -> Option<&Slot> {
+        let (index, generation) = split(id);
+        if index == 0 {
+            return None;
+        }
+        self.slots
+            .get(index as usize - 1)
+            .filter(|s| s.live && s.generation == generation)
+    }
+
+    fn free(&mut self, id: u32) -> bool {
+        if self.slot(id).is_none() {
+            return false;
+        }
+        let index = split(id).0;
+        let slot = &mut self.slots[index as usize - 1];
+        slot.live = false;
+        slot.generation = (slot.generation + 1) & GENERATION_MASK;
+        self.free.push_back(index);
+        true
+    }
+
+    fn claim(&mut self, id: u32) -> bool {
+        let (index, generation) = split(id);
+        if index == 0 {
+            return false;
+        }
+        let i = index as usize - 1;
+        if i >= self.slots.len() {
+            let first_new = self.slots.len() as u32 + 1;
+            self.slots.resize(i + 1, Slot { generation: 0, live: false });
+            self.free.extend(first_new..index);
+        }
+        let slot = &mut self.slots[i];
+        if slot.live {
+            return false;
+        }
+        slot.live = true;
+        slot.generation = generation;
+        self.free.retain(|&f| f != index);
+        true
+    }
+}
+
+/// Allocator and liveness table for one kind of resource
+pub struct HandleTable {
+    kind: ErrorKind,
+    slots: Mutex<Slots>,
+}
+
+impl HandleTable {
+    pub const fn new(kind: ErrorKind) -> Self {
+        Self { kind, slots: Mutex::new(Slots::new()) }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Slots> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn stale(&self, id: u32) -> DisplayError {
+        DisplayError::StaleHandle { kind: self.kind.as_str(), id }
+    }
+
+    /// Resource kind, for error reports
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Allocate a fresh id.  Fails with `HandlesExhausted`, reported
+    /// once per call, when every index is in use.
+    pub fn alloc(&self) -> DisplayResult<u32> {
+        self.lock().alloc().ok_or_else(|| {
+            let e = DisplayError::HandlesExhausted { kind: self.kind.as_str() };
+            super::error_report::error(self.kind, None, e.to_string());
+            e
+        })
+    }
+
+    /// Mark an id chosen by someone else (a remote client) as live.
+    pub fn claim(&self, id: u32) -> DisplayResult<()> {
+        if self.lock().claim(id) {
+            Ok(())
+        } else {
+            Err(DisplayError::Ffi(format!("{} id {} is invalid or in use", self.kind.as_str(), id)))
+        }
+    }
+
+    /// Whether `id` refers to a resource that has not been freed.
+    pub fn is_live(&self, id: u32) -> bool {
+        self.lock().slot(id).is_some()
+    }
+
+    /// Fail with `StaleHandle` unless `id` is live.
+    pub fn check(&self, id: u32) -> DisplayResult<()> {
+        if self.is_live(id) {
+            Ok(())
+        } else {
+            Err(self.stale(id))
+        }
+    }
+
+    /// Release `id`.  Fails with `StaleHandle` if it was already released
+    /// or never allocated.
+    pub fn free(&self, id: u32) -> DisplayResult<()> {
+        if self.lock().free(id) {
+            Ok(())
+        } else {
+            Err(self.stale(id))
+        }
+    }
+}
+
+/// Image ids, shared by file, data, PDF page and math images
+pub static IMAGES: HandleTable = HandleTable::new(ErrorKind::Image);
+/// Video ids
+pub static VIDEOS: HandleTable = HandleTable::new(ErrorKind::Video);
+/// WebKit view ids
+pub static WEBKIT_VIEWS: HandleTable = HandleTable::new(ErrorKind::WebKit);
+/// Terminal ids
+pub static TERMINALS: HandleTable = HandleTable::new(ErrorKind::Terminal);
+/// PDF document ids
+pub static PDFS: HandleTable = HandleTable::new(ErrorKind::Pdf);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freed_ids_become_stale() {
+        let table = HandleTable::new(ErrorKind::Image);
+        let a = table.alloc().unwrap();
+        let b = table.alloc().unwrap();
+        assert_eq!((a, b), (1, 2));
+        assert!(table.check(0).is_err());
+
+        table.free(a).unwrap();
+        assert!(matches!(table.check(a), Err(DisplayError::StaleHandle { id: 1, .. })));
+        assert!(table.free(a).is_err());
+        assert!(table.check(b).is_ok());
+
+        // Once enough slots are waiting, the oldest is reused under a new
+        // generation and the old id stays stale.
+        let ids: Vec<u32> = (0..=MIN_FREE_SLOTS).map(|_| table.alloc().unwrap()).collect();
+        for &id in &ids {
+            table.free(id).unwrap();
+        }
+        let reused = table.alloc().unwrap();
+        assert_eq!(split(reused), (1, 1));
+        assert!(table.check(reused).is_ok());
+        assert!(table.check(a).is_err());
+    }
+
+    #[test]
+    fn test_claimed_ids() {
+        let table = HandleTable::new(ErrorKind::Terminal);
+        table.claim(5).unwrap();
+        assert!(table.claim(5).is_err());
+        assert!(table.is_live(5));
+        // Slots skipped by the claim are still handed out
+        assert_eq!(table.alloc().unwrap(), 6);
+        table.free(5).unwrap();
+        assert!(table.claim(5).is_ok());
+    }
+
+    #[test]
+    fn test_alloc_fails_when_indices_run_out() {
+        let mut slots = Slots::new();
+        slots.slots = vec![Slot { generation: 0, live: true }; INDEX_MASK as usize];
+        assert_eq!(slots.alloc(), None);
+
+        // A freed slot is reused at once rather than overflowing the index
+        let id = join(7, 0);
+        assert!(slots.free(id));
+        assert_eq!(slots.alloc(), Some(join(7, 1)));
+        assert_eq!(slots.alloc(), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Maintenance work run between frames while the display is idle.
+//!
+//! Some cache upkeep is too slow to do in the middle of an animation
+//! without a visible hitch: repacking the glyph atlas, uploading large
+//! decoded images, destroying the textures of finished transitions and
+//! trimming terminal scrollback.  The render thread defers these to idle
+//! time and asks the scheduler which one to run.  It hands out at most
+//! one task per event loop iteration, round robin, and only once nothing
+//! has been drawn for `IDLE_DELAY`, so the work is spread out and never
+//! competes with a frame.
+
+use std::time::{Duration, Instant};
+
+/// How long the display must have been idle before maintenance starts
+pub const IDLE_DELAY: Duration = Duration::from_millis(100);
+
+/// A unit of idle-time maintenance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleTask {
+    /// Destroy the textures of finished transitions
+    ReleaseSnapshots,
+    /// Upload decoded images that were deferred to keep frames short
+    UploadImages,
+    /// Repack fragmented glyph atlas pages
+    DefragmentAtlas,
+    /// Drop old scrollback of terminals that are not scrolled back
+    TrimScrollback,
+}
+
+/// Tasks in the order they are offered, with the minimum time between runs
+const TASKS: [(IdleTask, Duration); 4] = [
+    (IdleTask::ReleaseSnapshots, Duration::ZERO),
+    (IdleTask::UploadImages, Duration::ZERO),
+    (IdleTask::DefragmentAtlas, Duration::from_millis(250)),
+    (IdleTask::TrimScrollback, Duration::from_secs(30)),
+];
+
+/// Decides which maintenance task to run next
+#[derive(Debug, Clone, Default)]
+pub struct IdleScheduler {
+    /// When the display last stopped drawing, or None while busy
+    idle_since: Option<Instant>,
+    last_run: [Option<Instant>; TASKS.len()],
+    next: usize,
+}
+
+impl IdleScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note that a frame is being drawn or an animation is running.
+    pub fn mark_busy(&mut self) {
+        self.idle_since = None;
+    }
+
+    /// The next task due to run, if the display has been idle long enough.
+    pub fn next_task(&mut self, now: Instant) -> Option<IdleTask> {
+        let idle_since = *self.idle_since.get_or_insert(now);
+        if now.duration_since(idle_since) < IDLE_DELAY {
+            return None;
+        }
+        for _ in 0..TASKS.len() {
+            let i = self.next;
+            self.next = (self.next + 1) % TASKS.len();
+            let (task, interval) = TASKS[i];
+            if self.last_run[i].is_none_or(|t| now.duration_since(t) >= interval) {
+                self.last_run[i] = Some(now);
+                return Some(task);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tasks_wait_for_idle_and_rotate() {
+        let mut idle = IdleScheduler::new();
+        let start = Instant::now();
+        assert_eq!(idle.next_task(start), None);
+        assert_eq!(idle.next_task(start + IDLE_DELAY / 2), None);
+
+        let t = start + IDLE_DELAY;
+        let first: Vec<_> = (0..4).filter_map(|_| idle.next_task(t)).collect();
+        assert_eq!(first, TASKS.map(|(task, _)| task).to_vec());
+
+        // Only the tasks without a minimum interval come round again
+        let again: Vec<_> = (0..3).filter_map(|_| idle.next_task(t)).collect();
+        assert_eq!(again, vec![IdleTask::ReleaseSnapshots, IdleTask::UploadImages, IdleTask::ReleaseSnapshots]);
+
+        // Drawing a frame restarts the idle delay
+        idle.mark_busy();
+        assert_eq!(idle.next_task(t + Duration::from_secs(60)), None);
+    }
+}
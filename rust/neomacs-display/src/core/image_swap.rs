@@ -0,0 +1,143 @@
+//! Animated replacement of the picture of an image id.
+//!
+//! Dashboards, wallpapers and slides update an image in place by giving
+//! its id the picture of another, freshly loaded image.  Rather than
+//! popping to the new picture, the glyph crossfades or slides from the
+//! old one over a short time; both are drawn in the glyph's bounds, in
+//! the parts `layers` gives.
+
+use std::time::{Duration, Instant};
+
+use super::types::ease_in_out_cubic;
+
+/// How the old picture gives way to the new
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSwapEffect {
+    /// The new picture fades in as the old fades out
+    Crossfade,
+    /// The new picture pushes the old out to the left
+    SlideLeft,
+    /// The new picture pushes the old out to the right
+    SlideRight,
+    /// The new picture pushes the old out upwards
+    SlideUp,
+    /// The new picture pushes the old out downwards
+    SlideDown,
+}
+
+impl ImageSwapEffect {
+    pub const ALL: [ImageSwapEffect; 5] =
+        [Self::Crossfade, Self::SlideLeft, Self::SlideRight, Self::SlideUp, Self::SlideDown];
+
+    /// The effect named `name`, None for unknown names
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == name)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Crossfade => "crossfade",
+            Self::SlideLeft => "slide-left",
+            Self::SlideRight => "slide-right",
+            Self::SlideUp => "slide-up",
+            Self::SlideDown => "slide-down",
+        }
+    }
+}
+
+/// A swap under way
+#[derive(Debug, Clone, Copy)]
+pub struct ImageSwap {
+    pub effect: ImageSwapEffect,
+    pub started: Instant,
+    pub duration: Duration,
+}
+
+impl ImageSwap {
+    /// How far the new picture has come in, 0-1, eased
+    pub fn progress(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let t = now.saturating_duration_since(self.started).as_secs_f32() / self.duration.as_secs_f32();
+        ease_in_out_cubic(t.min(1.0))
+    }
+
+    pub fn finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.duration
+    }
+}
+
+/// One of the two pictures drawn during a swap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapLayer {
+    /// Whether this is the picture going out
+    pub old: bool,
+    /// Part of the glyph covered, as fractions (x0, y0, x1, y1)
+    pub rect: (f32, f32, f32, f32),
+    /// Part of the picture shown there, in texture coordinates
+    pub uv: (f32, f32, f32, f32),
+    pub alpha: f32,
+}
+
+/// The two pictures of a swap by `effect` at progress `t`, old first
+pub fn layers(effect: ImageSwapEffect, t: f32) -> [SwapLayer; 2] {
+    let t = t.clamp(0.0, 1.0);
+    let full = (0.0, 0.0, 1.0, 1.0);
+    let layer = |old, rect, uv, alpha| SwapLayer { old, rect, uv, alpha };
+    match effect {
+        ImageSwapEffect::Crossfade => [layer(true, full, full, 1.0 - t), layer(false, full, full, t)],
+        ImageSwapEffect::SlideLeft => [
+            layer(true, (0.0, 0.0, 1.0 - t, 1.0), (t, 0.0, 1.0, 1.0), 1.0),
+            layer(false, (1.0 - t, 0.0, 1.0, 1.0), (0.0, 0.0, t, 1.0), 1.0),
+        ],
+        ImageSwapEffect::SlideRight => [
+            layer(true, (t, 0.0, 1.0, 1.0), (0.0, 0.0, 1.0 - t, 1.0), 1.0),
+            layer(false, (0.0, 0.0, t, 1.0), (1.0 - t, 0.0, 1.0, 1.0), 1.0),
+        ],
+        ImageSwapEffect::SlideUp => [
+            layer(true, (0.0, 0.0, 1.0, 1.0 - t), (0.0, t, 1.0, 1.0), 1.0),
+            layer(false, (0.0, 1.0 - t, 1.0, 1.0), (0.0, 0.0, 1.0, t), 1.0),
+        ],
+        ImageSwapEffect::SlideDown => [
+            layer(true, (0.0, t, 1.0, 1.0), (0.0, 0.0, 1.0, 1.0 - t), 1.0),
+            layer(false, (0.0, 0.0, 1.0, t), (0.0, 1.0 - t, 1.0, 1.0), 1.0),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_swap_layers_start_old_and_end_new() {
+        for effect in ImageSwapEffect::ALL {
+            assert_eq!(ImageSwapEffect::parse(effect.as_str()), Some(effect));
+            for (t, shown_old) in [(0.0, true), (1.0, false)] {
+                for l in layers(effect, t) {
+                    let area = (l.rect.2 - l.rect.0) * (l.rect.3 - l.rect.1) * l.alpha;
+                    let expected = if l.old == shown_old { 1.0 } else { 0.0 };
+                    assert!((area - expected).abs() < 1e-6, "{:?} at {}", effect, t);
+                }
+            }
+        }
+        assert_eq!(ImageSwapEffect::parse("wipe"), None);
+    }
+
+    #[test]
+    fn test_image_swap_slides_share_the_glyph() {
+        let [old, new] = layers(ImageSwapEffect::SlideLeft, 0.25);
+        // Side by side, each showing as much picture as glyph it covers
+        assert_eq!(old.rect.2, new.rect.0);
+        assert_eq!(old.rect.2 - old.rect.0, old.uv.2 - old.uv.0);
+        assert_eq!(new.rect.2 - new.rect.0, new.uv.2 - new.uv.0);
+
+        let now = Instant::now();
+        let swap = ImageSwap { effect: ImageSwapEffect::Crossfade, started: now, duration: Duration::from_millis(200) };
+        assert_eq!(swap.progress(now), 0.0);
+        assert!(!swap.finished(now));
+        assert_eq!(swap.progress(now + Duration::from_millis(300)), 1.0);
+        assert!(swap.finished(now + Duration::from_millis(200)));
+    }
+}
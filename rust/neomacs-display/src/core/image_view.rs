@@ -0,0 +1,132 @@
+//! Zoom and pan of images opened for viewing.
+//!
+//! An image made interactive is zoomed with the wheel around the point
+//! under the pointer, panned by dragging and fitted again with a double
+//! click, all within the bounds of its glyph: the glyph keeps its size
+//! and shows a smaller part of the image.  An `ImageTransform` says which
+//! part, as the center and zoom of a window onto the image in texture
+//! coordinates.
+
+use std::time::Duration;
+
+/// The whole image fits the glyph
+pub const MIN_ZOOM: f32 = 1.0;
+
+/// Closest zoom, beyond which single pixels fill the glyph anyway
+pub const MAX_ZOOM: f32 = 32.0;
+
+/// Zoom factor of one wheel line
+pub const WHEEL_STEP: f32 = 1.25;
+
+/// Pixels of touchpad scrolling that count as one wheel line
+pub const PIXELS_PER_LINE: f32 = 40.0;
+
+/// Clicks closer together than this fit the image again
+pub const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// The part of an image a glyph shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageTransform {
+    /// 1 shows the whole image, 2 half its width and height, and so on
+    pub zoom: f32,
+    /// Center of the part shown, 0-1 across and down the image
+    pub center: (f32, f32),
+}
+
+impl Default for ImageTransform {
+    fn default() -> Self {
+        Self { zoom: MIN_ZOOM, center: (0.5, 0.5) }
+    }
+}
+
+impl ImageTransform {
+    /// Zoom factor of a wheel turn of `lines` lines, up zooming in
+    pub fn wheel_factor(lines: f32) -> f32 {
+        WHEEL_STEP.powf(lines)
+    }
+
+    /// Whether the whole image is shown
+    pub fn is_fit(&self) -> bool {
+        self.zoom <= MIN_ZOOM
+    }
+
+    /// Texture coordinates of the part shown, as (u0, v0, u1, v1)
+    pub fn uv_rect(&self) -> (f32, f32, f32, f32) {
+        let half = 0.5 / self.zoom;
+        let (cx, cy) = self.center;
+        (cx - half, cy - half, cx + half, cy + half)
+    }
+
+    /// Multiply the zoom by `factor`, keeping the point of the image at
+    /// `at`, a fraction of the glyph's width and height, where it is
+    pub fn zoom_at(&mut self, factor: f32, at: (f32, f32)) {
+        let (u0, v0, u1, v1) = self.uv_rect();
+        let (u, v) = (u0 + at.0 * (u1 - u0), v0 + at.1 * (v1 - v0));
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let span = 1.0 / self.zoom;
+        self.center = (u - at.0 * span + span / 2.0, v - at.1 * span + span / 2.0);
+        self.clamp();
+    }
+
+    /// Drag the image by `delta`, a fraction of the glyph's width and
+    /// height
+    pub fn pan(&mut self, delta: (f32, f32)) {
+        self.center.0 -= delta.0 / self.zoom;
+        self.center.1 -= delta.1 / self.zoom;
+        self.clamp();
+    }
+
+    /// Show the whole image again
+    pub fn fit(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Keep the part shown inside the image
+    fn clamp(&mut self) {
+        let half = 0.5 / self.zoom;
+        self.center.0 = self.center.0.clamp(half, 1.0 - half);
+        self.center.1 = self.center.1.clamp(half, 1.0 - half);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn test_image_transform_zooms_around_point() {
+        let mut t = ImageTransform::default();
+        assert_eq!(t.uv_rect(), (0.0, 0.0, 1.0, 1.0));
+
+        // The top-left quarter point stays under the pointer
+        t.zoom_at(2.0, (0.25, 0.25));
+        let (u0, v0, u1, v1) = t.uv_rect();
+        assert!(close(u0 + 0.25 * (u1 - u0), 0.25) && close(v0 + 0.25 * (v1 - v0), 0.25));
+        assert!(close(u1 - u0, 0.5));
+
+        t.zoom_at(1000.0, (0.5, 0.5));
+        assert_eq!(t.zoom, MAX_ZOOM);
+        t.fit();
+        assert!(t.is_fit());
+        t.zoom_at(0.5, (0.0, 0.0));
+        assert_eq!(t, ImageTransform::default());
+    }
+
+    #[test]
+    fn test_image_transform_pans_within_image() {
+        let mut t = ImageTransform::default();
+        t.pan((0.3, 0.3));
+        assert_eq!(t, ImageTransform::default());
+
+        t.zoom_at(4.0, (0.5, 0.5));
+        t.pan((0.5, 0.0));
+        assert!(close(t.center.0, 0.5 - 0.5 / 4.0));
+        t.pan((10.0, -10.0));
+        let (u0, _, _, v1) = t.uv_rect();
+        assert!(close(u0, 0.0) && close(v1, 1.0));
+    }
+}
@@ -0,0 +1,242 @@
+//! End-to-end input latency measurement.
+//!
+//! With the `input-latency` display option on, every key and mouse
+//! button press is timestamped when the backend hands it to Emacs, again
+//! when Emacs drains it, when Emacs sends the first frame after that --
+//! the frame showing the resulting cursor move or text change -- and when
+//! the render thread presents that frame.  Latency percentiles of each
+//! stage and of the whole path are kept over the last `SAMPLES` presses,
+//! to compare the GPU pipeline with stock Emacs redisplay.
+//!
+//! Frames are matched by count: Emacs numbers the frames it sends, the
+//! render thread counts those it receives, and the channel between them
+//! keeps their order.  Frames are counted even while measuring is off so
+//! the two counts always agree.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Presses whose latency is kept
+pub const SAMPLES: usize = 1000;
+
+/// Presses not presented after this long are dropped, e.g. ones that
+/// changed nothing on screen
+const PENDING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Part of the path from a press to the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Backend to Emacs draining the event
+    InputToHost,
+    /// Emacs draining the event to sending a frame
+    HostToFrame,
+    /// Frame sent to presented
+    FrameToScreen,
+    /// The whole path
+    Total,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Self::InputToHost, Self::HostToFrame, Self::FrameToScreen, Self::Total];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InputToHost => "input-to-host",
+            Self::HostToFrame => "host-to-frame",
+            Self::FrameToScreen => "frame-to-screen",
+            Self::Total => "total",
+        }
+    }
+}
+
+/// Latency percentiles of a stage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Percentiles of `samples`, nearest-rank
+pub fn percentiles(samples: impl IntoIterator<Item = Duration>) -> Percentiles {
+    let mut sorted: Vec<Duration> = samples.into_iter().collect();
+    sorted.sort_unstable();
+    let Some(&max) = sorted.last() else {
+        return Percentiles::default();
+    };
+    let rank = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+    Percentiles { count: sorted.len(), p50: rank(50), p90: rank(90), p99: rank(99), max }
+}
+
+/// A press on its way to the screen
+#[derive(Debug, Clone, Copy)]
+struct Pending {
+    input: Instant,
+    host: Option<Instant>,
+    /// When the frame showing it was sent, and its number
+    frame: Option<(Instant, u64)>,
+}
+
+/// Presses in flight and the latencies of those presented
+#[derive(Debug, Default)]
+pub struct LatencyLog {
+    pending: VecDeque<Pending>,
+    /// Per stage, in `Stage::ALL` order
+    samples: [VecDeque<Duration>; 4],
+}
+
+impl LatencyLog {
+    pub const fn new() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            samples: [VecDeque::new(), VecDeque::new(), VecDeque::new(), VecDeque::new()],
+        }
+    }
+
+    /// A press was handed to Emacs
+    pub fn input_sent(&mut self, now: Instant) {
+        while self.pending.front().is_some_and(|p| now.duration_since(p.input) > PENDING_TIMEOUT) {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(Pending { input: now, host: None, frame: None });
+    }
+
+    /// Emacs drained the oldest press it had not drained yet
+    pub fn input_received(&mut self, now: Instant) {
+        if let Some(p) = self.pending.iter_mut().find(|p| p.host.is_none()) {
+            p.host = Some(now);
+        }
+    }
+
+    /// Emacs sent frame number `frame`, which shows the presses it drained
+    pub fn frame_sent(&mut self, frame: u64, now: Instant) {
+        for p in self.pending.iter_mut().filter(|p| p.host.is_some() && p.frame.is_none()) {
+            p.frame = Some((now, frame));
+        }
+    }
+
+    /// The frames up to number `received` were taken by the render thread
+    /// and the last of them presented at `now`
+    pub fn frame_presented(&mut self, received: u64, now: Instant) {
+        while let Some(&Pending { input, host: Some(host), frame: Some((sent, frame)) }) = self.pending.front() {
+            if frame > received {
+                break;
+            }
+            self.pending.pop_front();
+            let stages = [host - input, sent - host, now.saturating_duration_since(sent), now - input];
+            for (samples, latency) in self.samples.iter_mut().zip(stages) {
+                if samples.len() == SAMPLES {
+                    samples.pop_front();
+                }
+                samples.push_back(latency);
+            }
+        }
+    }
+
+    pub fn percentiles(&self, stage: Stage) -> Percentiles {
+        let i = Stage::ALL.iter().position(|&s| s == stage).unwrap_or(0);
+        percentiles(self.samples[i].iter().copied())
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static FRAMES_SENT: AtomicU64 = AtomicU64::new(0);
+static FRAMES_RECEIVED: AtomicU64 = AtomicU64::new(0);
+static LOG: Mutex<LatencyLog> = Mutex::new(LatencyLog::new());
+
+fn log() -> std::sync::MutexGuard<'static, LatencyLog> {
+    LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start measuring afresh, or stop
+pub fn set_enabled(enabled: bool) {
+    *log() = LatencyLog::new();
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Called by the backend as it hands a press to Emacs
+pub fn input_sent() {
+    if enabled() {
+        log().input_sent(Instant::now());
+    }
+}
+
+/// Called by Emacs as it drains a press
+pub fn input_received() {
+    if enabled() {
+        log().input_received(Instant::now());
+    }
+}
+
+/// Called by Emacs after sending a frame
+pub fn frame_sent() {
+    let frame = FRAMES_SENT.fetch_add(1, Ordering::Relaxed) + 1;
+    if enabled() {
+        log().frame_sent(frame, Instant::now());
+    }
+}
+
+/// Called by the render thread for every frame it takes
+pub fn frame_received() {
+    FRAMES_RECEIVED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Called by the render thread after presenting a frame
+pub fn frame_presented() {
+    if enabled() {
+        log().frame_presented(FRAMES_RECEIVED.load(Ordering::Relaxed), Instant::now());
+    }
+}
+
+/// Latency percentiles of `stage` over the recent presses
+pub fn report(stage: Stage) -> Percentiles {
+    log().percentiles(stage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_latency_percentiles() {
+        assert_eq!(percentiles([]), Percentiles::default());
+        let p = percentiles((1..=100).rev().map(|ms| ms * MS));
+        assert_eq!((p.count, p.p50, p.p90, p.p99, p.max), (100, 50 * MS, 90 * MS, 99 * MS, 100 * MS));
+        let p = percentiles([7 * MS]);
+        assert_eq!((p.p50, p.p99, p.max), (7 * MS, 7 * MS, 7 * MS));
+    }
+
+    #[test]
+    fn test_latency_stages() {
+        let mut log = LatencyLog::new();
+        let t = Instant::now();
+        log.input_sent(t);
+        log.input_sent(t + MS);
+        log.input_received(t + 2 * MS);
+        // Frame 1 only shows the first press
+        log.frame_sent(1, t + 10 * MS);
+        log.input_received(t + 11 * MS);
+        log.frame_sent(2, t + 20 * MS);
+        log.frame_presented(1, t + 25 * MS);
+        assert_eq!(log.percentiles(Stage::Total).count, 1);
+        assert_eq!(log.percentiles(Stage::InputToHost).max, 2 * MS);
+        assert_eq!(log.percentiles(Stage::HostToFrame).max, 8 * MS);
+        assert_eq!(log.percentiles(Stage::FrameToScreen).max, 15 * MS);
+        assert_eq!(log.percentiles(Stage::Total).max, 25 * MS);
+
+        log.frame_presented(2, t + 30 * MS);
+        let total = log.percentiles(Stage::Total);
+        assert_eq!((total.count, total.p50, total.max), (2, 25 * MS, 29 * MS));
+        assert!(log.pending.is_empty());
+    }
+}
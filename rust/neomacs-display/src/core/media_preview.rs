@@ -0,0 +1,125 @@
+//! Previews of media files for seek bars.
+//!
+//! A waveform image of the audio of a file, and thumbnails of its video
+//! taken at even intervals, both uploaded as ordinary images so a host
+//! can show what is where before seeking.  Decoding happens on the
+//! media preview worker; this module holds the parts that don't need
+//! GStreamer: reducing samples to peaks, drawing them, and choosing
+//! where to take thumbnails.
+
+/// Samples reduced to one (min, max) pair before drawing
+const BLOCK_SAMPLES: usize = 256;
+
+/// Running (min, max) of the audio of a file, block by block, so an hour
+/// of sound takes a few hundred kilobytes however wide the image ends up
+#[derive(Debug, Clone, Default)]
+pub struct WaveformPeaks {
+    blocks: Vec<(f32, f32)>,
+    /// Samples in the last, partial block
+    filled: usize,
+}
+
+impl WaveformPeaks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add mono samples in -1.0..1.0
+    pub fn push(&mut self, samples: &[f32]) {
+        for &s in samples {
+            if self.filled == 0 {
+                self.blocks.push((s, s));
+            } else if let Some(block) = self.blocks.last_mut() {
+                block.0 = block.0.min(s);
+                block.1 = block.1.max(s);
+            }
+            self.filled = (self.filled + 1) % BLOCK_SAMPLES;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// The (min, max) of each of `width` columns spanning the whole sound
+    pub fn columns(&self, width: u32) -> Vec<(f32, f32)> {
+        let n = self.blocks.len();
+        (0..width as usize)
+            .map(|x| {
+                if n == 0 {
+                    return (0.0, 0.0);
+                }
+                let start = x * n / width as usize;
+                let end = ((x + 1) * n / width as usize).max(start + 1).min(n);
+                self.blocks[start..end]
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(lo, hi), &(a, b)| (lo.min(a), hi.max(b)))
+            })
+            .collect()
+    }
+}
+
+/// RGBA pixels of a `width` x `height` waveform of `peaks` in `color`
+/// (0xAARRGGBB, alpha 0 meaning opaque) on a transparent background.
+/// Silence still draws a line along the middle.
+pub fn waveform_rgba(peaks: &WaveformPeaks, width: u32, height: u32, color: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let alpha = match (color >> 24) as u8 {
+        0 => 255,
+        a => a,
+    };
+    let rgba = [(color >> 16) as u8, (color >> 8) as u8, color as u8, alpha];
+    let mut data = vec![0u8; w * h * 4];
+    let mid = (h as f32 - 1.0) / 2.0;
+    for (x, (lo, hi)) in peaks.columns(width).into_iter().enumerate() {
+        let top = (mid - hi.clamp(-1.0, 1.0) * mid).floor() as usize;
+        let bottom = (mid - lo.clamp(-1.0, 1.0) * mid).ceil() as usize;
+        for y in top.min(h.saturating_sub(1))..=bottom.min(h.saturating_sub(1)) {
+            let i = (y * w + x) * 4;
+            data[i..i + 4].copy_from_slice(&rgba);
+        }
+    }
+    data
+}
+
+/// Where to take `count` thumbnails of something `duration` nanoseconds
+/// long: the middle of each of `count` equal parts, so the first is not
+/// a black fade-in and each stands for the part of the seek bar under it
+pub fn thumbnail_times(duration: u64, count: u32) -> Vec<u64> {
+    let count = count as u64;
+    (0..count).map(|i| ((2 * i + 1) as u128 * duration as u128 / (2 * count) as u128) as u64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waveform_peaks_and_image() {
+        let mut peaks = WaveformPeaks::new();
+        // A loud first half and a silent second half
+        let loud: Vec<f32> = (0..BLOCK_SAMPLES * 4).map(|i| if i % 2 == 0 { 0.5 } else { -1.0 }).collect();
+        peaks.push(&loud);
+        peaks.push(&vec![0.0; BLOCK_SAMPLES * 4]);
+        assert_eq!(peaks.columns(2), vec![(-1.0, 0.5), (0.0, 0.0)]);
+        // More columns than blocks repeat blocks rather than leaving gaps
+        assert_eq!(peaks.columns(16).len(), 16);
+        assert_eq!(peaks.columns(16)[15], (0.0, 0.0));
+
+        let (w, h) = (2, 5);
+        let data = waveform_rgba(&peaks, w, h, 0x00ff0000);
+        let alpha = |x: usize, y: usize| data[(y * w as usize + x) * 4 + 3];
+        // The loud column reaches the bottom, and half way up from the middle
+        assert_eq!((0..h as usize).map(|y| alpha(0, y)).collect::<Vec<_>>(), vec![0, 255, 255, 255, 255]);
+        // The silent one is a line along the middle
+        assert_eq!((0..h as usize).map(|y| alpha(1, y)).collect::<Vec<_>>(), vec![0, 0, 255, 0, 0]);
+        assert_eq!(&data[(2 * w as usize) * 4..(2 * w as usize) * 4 + 3], &[255, 0, 0]);
+    }
+
+    #[test]
+    fn test_thumbnail_times() {
+        assert_eq!(thumbnail_times(100, 4), vec![12, 37, 62, 87]);
+        assert_eq!(thumbnail_times(3_600_000_000_000, 1), vec![1_800_000_000_000]);
+        assert!(thumbnail_times(100, 0).is_empty());
+    }
+}
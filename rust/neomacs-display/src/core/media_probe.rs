@@ -0,0 +1,122 @@
+//! Why a media file can or cannot be played.
+//!
+//! When GStreamer lacks the plugin for a format, decodebin posts a
+//! `missing-plugin` element message and the video never gets a frame, so
+//! all the user sees is a placeholder.  The decoder thread turns these
+//! messages into display errors naming the codec, and `probe_media`
+//! answers ahead of time whether a file will play.  This module holds the
+//! parts that don't need GStreamer.
+
+/// Human-readable name of a media type, as found in the caps of a
+/// missing decoder or demuxer
+pub fn codec_name(media_type: &str) -> Option<&'static str> {
+    Some(match media_type {
+        "video/x-h264" => "H.264 video",
+        "video/x-h265" => "H.265 (HEVC) video",
+        "video/x-vp8" => "VP8 video",
+        "video/x-vp9" => "VP9 video",
+        "video/x-av1" => "AV1 video",
+        "video/mpeg" => "MPEG video",
+        "video/x-theora" => "Theora video",
+        "video/x-divx" | "video/x-xvid" => "DivX / Xvid video",
+        "video/x-wmv" => "Windows Media video",
+        "video/x-prores" => "ProRes video",
+        "audio/mpeg" => "MPEG audio (MP3 / AAC)",
+        "audio/x-opus" => "Opus audio",
+        "audio/x-vorbis" => "Vorbis audio",
+        "audio/x-flac" => "FLAC audio",
+        "audio/x-ac3" | "audio/ac3" => "Dolby Digital (AC-3) audio",
+        "audio/x-eac3" => "Dolby Digital Plus (E-AC-3) audio",
+        "audio/x-dts" => "DTS audio",
+        "audio/x-wma" => "Windows Media audio",
+        "audio/x-alac" => "Apple Lossless audio",
+        "video/quicktime" => "MP4 / QuickTime container",
+        "video/x-matroska" => "Matroska / WebM container",
+        "video/webm" | "audio/webm" => "WebM container",
+        "video/x-msvideo" => "AVI container",
+        "video/mpegts" => "MPEG transport stream",
+        "application/ogg" => "Ogg container",
+        "video/x-flv" => "Flash video container",
+        "video/x-ms-asf" => "ASF / Windows Media container",
+        _ => return None,
+    })
+}
+
+/// What a `missing-plugin` message asks for, in words.  `kind` is its
+/// `type` field ("decoder", "demuxer"...), `detail` its caps or element
+/// name as a string, and `name` the description GStreamer gave, if any.
+pub fn describe_missing(kind: &str, detail: &str, name: Option<&str>) -> String {
+    // Caps strings start with the media type: "video/x-h265, stream-format=..."
+    let media_type = detail.split([',', ';']).next().unwrap_or("").trim();
+    let what = codec_name(media_type)
+        .map(str::to_string)
+        .or_else(|| name.filter(|n| !n.is_empty()).map(str::to_string))
+        .unwrap_or_else(|| detail.to_string());
+    match kind {
+        "decoder" | "encoder" | "urisource" | "urisink" => format!("no {} for {}", kind, what),
+        _ => format!("missing GStreamer element for {}", what),
+    }
+}
+
+/// The verdict on a media file
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaProbe {
+    /// Everything in the file can be decoded
+    Playable { video: bool, audio: bool },
+    /// Some streams need plugins that are not installed; the file may
+    /// still play without them, e.g. video without its sound
+    MissingPlugins { playable: bool, missing: Vec<String> },
+    /// The file cannot be opened or decoded at all
+    Failed(String),
+}
+
+impl MediaProbe {
+    pub fn is_playable(&self) -> bool {
+        match self {
+            MediaProbe::Playable { .. } => true,
+            MediaProbe::MissingPlugins { playable, .. } => *playable,
+            MediaProbe::Failed(_) => false,
+        }
+    }
+
+    /// Why the file won't play (fully), if it won't
+    pub fn reason(&self) -> Option<String> {
+        match self {
+            MediaProbe::Playable { .. } => None,
+            MediaProbe::MissingPlugins { missing, .. } => {
+                Some(format!("{} (install the GStreamer plugins that provide them)", missing.join("; ")))
+            }
+            MediaProbe::Failed(e) => Some(e.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_missing() {
+        assert_eq!(
+            describe_missing("decoder", "video/x-h265, stream-format=(string)hvc1", None),
+            "no decoder for H.265 (HEVC) video"
+        );
+        assert_eq!(
+            describe_missing("decoder", "video/x-foo", Some("Foo video")),
+            "no decoder for Foo video"
+        );
+        assert_eq!(describe_missing("element", "vapostproc", None), "missing GStreamer element for vapostproc");
+    }
+
+    #[test]
+    fn test_media_probe_reason() {
+        assert_eq!(MediaProbe::Playable { video: true, audio: false }.reason(), None);
+        let partly = MediaProbe::MissingPlugins {
+            playable: true,
+            missing: vec!["no decoder for DTS audio".into()],
+        };
+        assert!(partly.is_playable());
+        assert!(partly.reason().unwrap().starts_with("no decoder for DTS audio"));
+        assert!(!MediaProbe::Failed("not found".into()).is_playable());
+    }
+}
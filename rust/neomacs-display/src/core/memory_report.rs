@@ -0,0 +1,75 @@
+//! Where the display engine's memory goes.
+//!
+//! `gpu_budget` adds up the GPU caches to decide what to evict; this is
+//! the fuller picture for people, asked for by `M-x neomacs-display-memory`.
+//! The render thread measures each subsystem on request -- the texture
+//! caches in video memory, and in main memory the terminal grids with
+//! their scrollback and the cell snapshots drawn from them -- and answers
+//! with a `MemoryReport`.  Byte counts are estimates from sizes the
+//! subsystems keep, not allocator statistics.
+
+/// Memory held by one subsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUse {
+    pub subsystem: &'static str,
+    /// Glyphs, images, videos, snapshots or terminals held
+    pub count: usize,
+    pub bytes: usize,
+    /// Whether the bytes are video memory rather than main memory
+    pub gpu: bool,
+}
+
+/// Memory held by each subsystem, in the order they were measured
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub entries: Vec<MemoryUse>,
+}
+
+impl MemoryReport {
+    /// Add what `subsystem` holds, to what it was already reported to
+    /// hold if it was measured in parts
+    pub fn add(&mut self, subsystem: &'static str, count: usize, bytes: usize, gpu: bool) {
+        match self.entries.iter_mut().find(|e| e.subsystem == subsystem && e.gpu == gpu) {
+            Some(entry) => {
+                entry.count += count;
+                entry.bytes += bytes;
+            }
+            None => self.entries.push(MemoryUse { subsystem, count, bytes, gpu }),
+        }
+    }
+
+    /// Bytes of video memory, if `gpu`, or of main memory held in all
+    pub fn total(&self, gpu: bool) -> usize {
+        self.entries.iter().filter(|e| e.gpu == gpu).map(|e| e.bytes).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_report_merges_parts() {
+        let mut report = MemoryReport::default();
+        report.add("glyph-atlas", 120, 4 << 20, true);
+        report.add("terminal-grids", 1, 300_000, false);
+        report.add("terminal-grids", 2, 500_000, false);
+        assert_eq!(
+            report.entries,
+            vec![
+                MemoryUse { subsystem: "glyph-atlas", count: 120, bytes: 4 << 20, gpu: true },
+                MemoryUse { subsystem: "terminal-grids", count: 3, bytes: 800_000, gpu: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_memory_report_totals() {
+        let mut report = MemoryReport::default();
+        assert_eq!((report.total(true), report.total(false)), (0, 0));
+        report.add("images", 2, 1000, true);
+        report.add("videos", 1, 500, true);
+        report.add("terminal-snapshots", 1, 70, false);
+        assert_eq!((report.total(true), report.total(false)), (1500, 70));
+    }
+}
@@ -0,0 +1,167 @@
+//! Minimap line summaries.
+//!
+//! The minimap draws each buffer line as a colored block whose offset and
+//! length follow the line's indentation and text.  Lisp summarizes lines
+//! once and then sends only the lines touched by each change; the store
+//! splices them in and shifts the start positions of the lines after the
+//! edit, so the full buffer never has to be rescanned.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Summary of one buffer line, laid out for FFI
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MinimapLine {
+    /// Buffer position of the first character of the line
+    pub start: i64,
+    /// Columns of leading whitespace
+    pub indent: u16,
+    /// Columns of text after the indentation
+    pub length: u16,
+    /// Dominant foreground color as 0xRRGGBB (sRGB)
+    pub color: u32,
+}
+
+/// Line summaries for every buffer shown in a minimap, keyed by buffer id
+#[derive(Debug, Clone, Default)]
+pub struct MinimapStore {
+    buffers: HashMap<u64, Vec<MinimapLine>>,
+}
+
+impl MinimapStore {
+    /// Replace `removed` lines starting at line `first` with `lines`, then
+    /// shift the start of every following line by `pos_delta` characters.
+    pub fn update(
+        &mut self,
+        buffer_id: u64,
+        first: usize,
+        removed: usize,
+        lines: &[MinimapLine],
+        pos_delta: i64,
+    ) {
+        let summary = self.buffers.entry(buffer_id).or_default();
+        let first = first.min(summary.len());
+        let end = first.saturating_add(removed).min(summary.len());
+        summary.splice(first..end, lines.iter().copied());
+        if pos_delta != 0 {
+            for line in &mut summary[first + lines.len()..] {
+                line.start += pos_delta;
+            }
+        }
+    }
+
+    /// Drop the summary of a buffer (killed, or minimap turned off)
+    pub fn forget(&mut self, buffer_id: u64) {
+        self.buffers.remove(&buffer_id);
+    }
+
+    /// Line summaries of a buffer, empty if none were sent
+    pub fn lines(&self, buffer_id: u64) -> &[MinimapLine] {
+        self.buffers.get(&buffer_id).map_or(&[], |v| v.as_slice())
+    }
+}
+
+/// Index of the line containing buffer position `pos`
+pub fn minimap_line_at_pos(lines: &[MinimapLine], pos: i64) -> usize {
+    lines.partition_point(|l| l.start <= pos).saturating_sub(1)
+}
+
+/// Vertical placement of line blocks within a minimap strip.
+///
+/// When the buffer has more lines than fit, the strip scrolls in
+/// proportion to the window so the viewport indicator stays inside it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinimapLayout {
+    /// First line drawn at the top of the strip
+    pub first_line: usize,
+    /// Number of lines that fit in the strip
+    pub capacity: usize,
+    pub line_height: f32,
+}
+
+impl MinimapLayout {
+    pub fn new(
+        total: usize,
+        view_first: usize,
+        view_lines: usize,
+        height: f32,
+        line_height: f32,
+    ) -> Self {
+        let line_height = line_height.max(0.5);
+        let capacity = (height / line_height).floor().max(1.0) as usize;
+        let first_line = if total <= capacity {
+            0
+        } else {
+            let scrollable = total.saturating_sub(view_lines).max(1);
+            let frac = view_first.min(scrollable) as f32 / scrollable as f32;
+            (frac * (total - capacity) as f32).round() as usize
+        };
+        Self { first_line, capacity, line_height }
+    }
+
+    /// Lines drawn in the strip
+    pub fn visible(&self, total: usize) -> Range<usize> {
+        self.first_line..(self.first_line + self.capacity).min(total)
+    }
+
+    /// Offset of `line` from the top of the strip (negative above it)
+    pub fn line_y(&self, line: usize) -> f32 {
+        (line as f32 - self.first_line as f32) * self.line_height
+    }
+
+    /// Line to show at the top of the window when the viewport indicator
+    /// is dragged so that its center sits at offset `y` in the strip.
+    pub fn drag_target(
+        total: usize,
+        view_lines: usize,
+        height: f32,
+        line_height: f32,
+        y: f32,
+    ) -> usize {
+        let line_height = line_height.max(0.5);
+        let view_h = view_lines as f32 * line_height;
+        let top = y - view_h / 2.0;
+        let last = total.saturating_sub(1);
+        if total as f32 * line_height <= height {
+            ((top / line_height).max(0.0) as usize).min(last)
+        } else {
+            let track = (height - view_h).max(1.0);
+            let frac = (top / track).clamp(0.0, 1.0);
+            ((frac * total.saturating_sub(view_lines) as f32).round() as usize).min(last)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(start: i64) -> MinimapLine {
+        MinimapLine { start, indent: 0, length: 10, color: 0 }
+    }
+
+    #[test]
+    fn test_incremental_update_shifts_following_lines() {
+        let mut store = MinimapStore::default();
+        store.update(1, 0, 0, &[line(1), line(12), line(23)], 0);
+        // Edit on line 1 inserts 5 characters and splits it in two
+        store.update(1, 1, 1, &[line(12), line(20)], 5);
+        let starts: Vec<i64> = store.lines(1).iter().map(|l| l.start).collect();
+        assert_eq!(starts, vec![1, 12, 20, 28]);
+        assert_eq!(minimap_line_at_pos(store.lines(1), 25), 2);
+        store.forget(1);
+        assert!(store.lines(1).is_empty());
+    }
+
+    #[test]
+    fn test_drag_target_round_trips_layout() {
+        let (total, view_lines, height, lh) = (1000, 40, 400.0, 2.0);
+        let target = MinimapLayout::drag_target(total, view_lines, height, lh, 200.0);
+        let layout = MinimapLayout::new(total, target, view_lines, height, lh);
+        // The viewport indicator lands centered under the pointer
+        let center = layout.line_y(target) + view_lines as f32 * lh / 2.0;
+        assert!((center - 200.0).abs() <= lh, "center {}", center);
+        assert_eq!(layout.visible(total).len(), 200);
+    }
+}
@@ -7,10 +7,50 @@ pub mod face;
 pub mod error;
 pub mod animation;
 pub mod frame_glyphs;
+pub mod frame_builder;
 pub mod cursor_animation;
 pub mod buffer_transition;
 pub mod animation_config;
 pub mod scroll_animation;
+pub mod accessibility;
+pub mod selection;
+pub mod overdraw;
+pub mod prepare;
+pub mod minimap;
+pub mod frame_clock;
+pub mod clock;
+pub mod frame_stats;
+pub mod session;
+pub mod display_config;
+pub mod option_registry;
+pub mod error_report;
+pub mod handle;
+pub mod idle_scheduler;
+pub mod frame_zoom;
+pub mod pip;
+pub mod video_controls;
+pub mod audio_levels;
+pub mod media_preview;
+pub mod media_probe;
+pub mod video_color;
+pub mod decode_scheduler;
+pub mod content_throttle;
+pub mod effect_preview;
+pub mod display_theme;
+pub mod placeholder;
+pub mod image_view;
+pub mod image_swap;
+pub mod nine_patch;
+pub mod chart;
+pub mod shader_effect;
+pub mod scene_dump;
+pub mod memory_report;
+pub mod watchdog;
+pub mod quality;
+pub mod input_latency;
+pub mod cursor_prediction;
+#[cfg(feature = "barcode")]
+pub mod barcode;
 
 pub use types::*;
 pub use scene::*;
@@ -19,7 +59,11 @@ pub use face::*;
 pub use error::*;
 pub use animation::*;
 pub use frame_glyphs::*;
+pub use frame_builder::*;
 pub use cursor_animation::*;
 pub use buffer_transition::*;
 pub use animation_config::*;
 pub use scroll_animation::*;
+pub use accessibility::*;
+pub use selection::*;
+pub use minimap::*;
@@ -0,0 +1,147 @@
+//! Nine-patch images for the chrome around content.
+//!
+//! Tab bars, tooltips, popup menus and child frames may be drawn with an
+//! image from the theme instead of a flat background and border.  The
+//! image is cut into nine parts by four insets: the corners keep their
+//! size, the edges stretch along their length and the center stretches
+//! both ways, so one image fits chrome of any size without distortion.
+
+use super::types::Rect;
+
+/// Chrome that can be drawn with a nine-patch image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChromeElement {
+    TabBar,
+    Tooltip,
+    PopupMenu,
+    /// A frame shown inside another
+    ChildFrame,
+}
+
+impl ChromeElement {
+    pub const ALL: [ChromeElement; 4] = [Self::TabBar, Self::Tooltip, Self::PopupMenu, Self::ChildFrame];
+
+    /// The element named `name`, None for unknown names
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == name)
+    }
+
+    /// The element numbered `index` in `ALL`, as C passes it
+    pub fn from_index(index: i32) -> Option<Self> {
+        usize::try_from(index).ok().and_then(|i| Self::ALL.get(i).copied())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TabBar => "tab-bar",
+            Self::Tooltip => "tooltip",
+            Self::PopupMenu => "popup-menu",
+            Self::ChildFrame => "child-frame",
+        }
+    }
+}
+
+/// Where chrome of some element is on screen this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChromeRegion {
+    pub element: ChromeElement,
+    pub bounds: Rect,
+}
+
+/// An image and where it is cut
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NinePatch {
+    pub image_id: u32,
+    /// Sizes of the fixed borders in image pixels: top, right, bottom, left
+    pub insets: [f32; 4],
+    /// Screen pixels per image pixel of the borders
+    pub scale: f32,
+}
+
+impl NinePatch {
+    /// The parts of an image of `width` x `height` pixels drawn over
+    /// `area`, each as the rect covered and its texture coordinates
+    /// (u0, v0, u1, v1).  Borders larger than the image or the area
+    /// shrink together, leaving no center.
+    pub fn slices(&self, area: Rect, width: u32, height: u32) -> Vec<(Rect, (f32, f32, f32, f32))> {
+        if area.width <= 0.0 || area.height <= 0.0 || width == 0 || height == 0 {
+            return Vec::new();
+        }
+        let (w, h) = (width as f32, height as f32);
+        let [top, right, bottom, left] = self.insets.map(|i| i.max(0.0));
+        let (left, right) = fit(left, right, w);
+        let (top, bottom) = fit(top, bottom, h);
+        let scale = if self.scale > 0.0 { self.scale } else { 1.0 };
+        let (screen_left, screen_right) = fit(left * scale, right * scale, area.width);
+        let (screen_top, screen_bottom) = fit(top * scale, bottom * scale, area.height);
+
+        let xs = [area.x, area.x + screen_left, area.right() - screen_right, area.right()];
+        let ys = [area.y, area.y + screen_top, area.bottom() - screen_bottom, area.bottom()];
+        let us = [0.0, left / w, 1.0 - right / w, 1.0];
+        let vs = [0.0, top / h, 1.0 - bottom / h, 1.0];
+        let mut slices = Vec::with_capacity(9);
+        for row in 0..3 {
+            for col in 0..3 {
+                let rect = Rect::new(xs[col], ys[row], xs[col + 1] - xs[col], ys[row + 1] - ys[row]);
+                if rect.width > 0.0 && rect.height > 0.0 {
+                    slices.push((rect, (us[col], vs[row], us[col + 1], vs[row + 1])));
+                }
+            }
+        }
+        slices
+    }
+}
+
+/// `a` and `b` scaled down together to add up to at most `total`
+fn fit(a: f32, b: f32, total: f32) -> (f32, f32) {
+    let sum = a + b;
+    if sum > total && sum > 0.0 {
+        (a * total / sum, b * total / sum)
+    } else {
+        (a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nine_patch_keeps_corners_and_stretches_center() {
+        let patch = NinePatch { image_id: 1, insets: [8.0, 8.0, 8.0, 8.0], scale: 1.0 };
+        for area in [Rect::new(0.0, 0.0, 40.0, 30.0), Rect::new(10.0, 20.0, 400.0, 120.0)] {
+            let slices = patch.slices(area, 32, 32);
+            assert_eq!(slices.len(), 9);
+            // Top-left corner: same size whatever the area
+            assert_eq!(slices[0], (Rect::new(area.x, area.y, 8.0, 8.0), (0.0, 0.0, 0.25, 0.25)));
+            // Center fills what the borders leave
+            let (center, uv) = slices[4];
+            assert_eq!((center.width, center.height), (area.width - 16.0, area.height - 16.0));
+            assert_eq!(uv, (0.25, 0.25, 0.75, 0.75));
+        }
+        let covered: f32 = patch.slices(Rect::new(0.0, 0.0, 100.0, 50.0), 32, 32)
+            .iter()
+            .map(|(r, _)| r.width * r.height)
+            .sum();
+        assert!((covered - 5000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_nine_patch_borders_shrink_to_fit() {
+        let patch = NinePatch { image_id: 1, insets: [10.0, 30.0, 10.0, 10.0], scale: 2.0 };
+        // Borders of 20 and 60 screen pixels in an area 40 wide
+        let slices = patch.slices(Rect::new(0.0, 0.0, 40.0, 100.0), 64, 64);
+        assert_eq!(slices.len(), 6);
+        assert_eq!(slices[0].0.width, 10.0);
+        assert_eq!(slices[1].0.width, 30.0);
+        assert!(patch.slices(Rect::new(0.0, 0.0, 0.0, 10.0), 64, 64).is_empty());
+
+        for element in ChromeElement::ALL {
+            assert_eq!(ChromeElement::parse(element.as_str()), Some(element));
+        }
+        assert_eq!(ChromeElement::from_index(3), Some(ChromeElement::ChildFrame));
+        assert_eq!(ChromeElement::from_index(-1), None);
+    }
+}
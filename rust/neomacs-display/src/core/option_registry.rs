@@ -0,0 +1,463 @@
+//! Registry of runtime display options.
+//!
+//! Every setting the display engine exposes to Lisp and to the display
+//! config file (see `core::display_config`) is declared once in this file,
+//! with its type, default and documentation.  The registry validates new
+//! values, keeps values read from the config file apart from those set at
+//! runtime, and calls back on every change of an effective value so the
+//! render thread can apply it.  `list_options` describes every option, which
+//! lets the Lisp layer generate a defcustom for each.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::core::display_config::DisplayConfig;
+use crate::core::error::{DisplayError, DisplayResult};
+use crate::core::quality::QualityLevel;
+use crate::core::scene_dump::DebugOverlay;
+use crate::core::scroll_animation::{ScrollEasing, ScrollEffect};
+use crate::core::placeholder::PlaceholderStyle;
+use crate::core::types::Color;
+use crate::effect_config::{AmbientEffect, ColorFilter};
+
+/// Type of a display option and the values it accepts
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionType {
+    /// "t"/"nil", also "true"/"false", "on"/"off", "yes"/"no", "1"/"0"
+    Bool,
+    /// Integer in `min..=max`
+    Integer { min: i64, max: i64 },
+    /// Number in `min..=max`
+    Float { min: f64, max: f64 },
+    /// One of a fixed set of names
+    Choice(Vec<&'static str>),
+    /// `#rrggbb` or `#rrggbbaa`
+    Color,
+    /// Up to `max` space-separated colors
+    ColorList { max: usize },
+}
+
+impl OptionType {
+    /// Name of the type, as reported to Lisp
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bool => "boolean",
+            Self::Integer { .. } => "integer",
+            Self::Float { .. } => "float",
+            Self::Choice(_) => "choice",
+            Self::Color => "color",
+            Self::ColorList { .. } => "color-list",
+        }
+    }
+
+    /// Parse and validate the string form of a value
+    pub fn parse(&self, s: &str) -> Result<OptionValue, String> {
+        let s = s.trim();
+        match self {
+            Self::Bool => match s.to_lowercase().as_str() {
+                "t" | "true" | "1" | "yes" | "on" => Ok(OptionValue::Bool(true)),
+                "nil" | "false" | "0" | "no" | "off" | "" => Ok(OptionValue::Bool(false)),
+                _ => Err(format!("{:?} is not a boolean", s)),
+            },
+            Self::Integer { min, max } => {
+                let v: i64 = s.parse().map_err(|_| format!("{:?} is not an integer", s))?;
+                if (*min..=*max).contains(&v) {
+                    Ok(OptionValue::Integer(v))
+                } else {
+                    Err(format!("{} is outside {}..{}", v, min, max))
+                }
+            }
+            Self::Float { min, max } => {
+                let v: f64 = s.parse().map_err(|_| format!("{:?} is not a number", s))?;
+                if (*min..=*max).contains(&v) {
+                    Ok(OptionValue::Float(v))
+                } else {
+                    Err(format!("{} is outside {}..{}", v, min, max))
+                }
+            }
+            Self::Choice(choices) => {
+                let name = s.to_lowercase().replace('_', "-");
+                choices
+                    .iter()
+                    .find(|c| **c == name)
+                    .map(|c| OptionValue::Choice(c))
+                    .ok_or_else(|| format!("{:?} is not one of {}", s, choices.join(", ")))
+            }
+            Self::Color => Color::from_hex(s)
+                .map(OptionValue::Color)
+                .ok_or_else(|| format!("{:?} is not a #rrggbb color", s)),
+            Self::ColorList { max } => {
+                let colors = s
+                    .split_whitespace()
+                    .map(|c| Color::from_hex(c).ok_or_else(|| format!("{:?} is not a #rrggbb color", c)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if colors.len() > *max {
+                    return Err(format!("more than {} colors", max));
+                }
+                Ok(OptionValue::Colors(colors))
+            }
+        }
+    }
+}
+
+/// A validated option value
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Choice(&'static str),
+    Color(Color),
+    Colors(Vec<Color>),
+}
+
+impl fmt::Display for OptionValue {
+    /// The string form `OptionType::parse` accepts
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => f.write_str(if *b { "t" } else { "nil" }),
+            Self::Integer(v) => write!(f, "{}", v),
+            Self::Float(v) => write!(f, "{}", v),
+            Self::Choice(c) => f.write_str(c),
+            Self::Color(c) => f.write_str(&c.to_hex()),
+            Self::Colors(colors) => {
+                let colors: Vec<String> = colors.iter().map(Color::to_hex).collect();
+                f.write_str(&colors.join(" "))
+            }
+        }
+    }
+}
+
+/// Declaration of one display option
+#[derive(Debug, Clone)]
+pub struct OptionSpec {
+    pub name: &'static str,
+    /// Subsystem the option belongs to: "animation", "rendering",
+    /// "terminal", "video", "cache" or "display"
+    pub group: &'static str,
+    pub ty: OptionType,
+    /// String form of the default value
+    pub default: &'static str,
+    pub doc: &'static str,
+}
+
+impl OptionSpec {
+    /// The default value
+    pub fn default_value(&self) -> OptionValue {
+        self.ty.parse(self.default).expect("option default is valid")
+    }
+}
+
+fn spec(
+    name: &'static str,
+    group: &'static str,
+    ty: OptionType,
+    default: &'static str,
+    doc: &'static str,
+) -> OptionSpec {
+    OptionSpec { name, group, ty, default, doc }
+}
+
+static OPTIONS: Lazy<Vec<OptionSpec>> = Lazy::new(|| {
+    use OptionType::*;
+    vec![
+        // Animations
+        spec("animation", "animation", Bool, "t",
+             "Enable cursor, buffer transition and scroll animations."),
+        spec("cursor-animation", "animation", Bool, "t",
+             "Animate the cursor moving between positions."),
+        spec("cursor-animation-speed", "animation", Float { min: 1.0, max: 100.0 }, "15",
+             "Speed of the cursor animation; higher is faster."),
+        spec("cursor-prediction", "animation", Bool, "nil",
+             "Move the cursor as soon as a cursor motion or typed key is pressed, \
+              before Emacs redraws, and move it back if Emacs puts it elsewhere."),
+        spec("buffer-transition", "animation", Bool, "t",
+             "Crossfade windows when they switch buffers."),
+        spec("buffer-transition-duration", "animation", Integer { min: 0, max: 2000 }, "200",
+             "Duration of buffer crossfades in milliseconds."),
+        spec("scroll-animation", "animation", Bool, "t",
+             "Animate scrolling."),
+        spec("scroll-animation-duration", "animation", Integer { min: 0, max: 2000 }, "150",
+             "Duration of scroll animations in milliseconds."),
+        spec("text-scale-animation", "animation", Bool, "t",
+             "Animate changes of text size, such as text-scale-adjust."),
+        spec("text-scale-animation-duration", "animation", Integer { min: 0, max: 2000 }, "200",
+             "Duration of text size animations in milliseconds."),
+        spec("scroll-effect", "animation",
+             Choice(ScrollEffect::ALL.iter().map(ScrollEffect::as_str).collect()), "slide",
+             "Visual effect of scroll animations."),
+        spec("scroll-easing", "animation",
+             Choice(ScrollEasing::ALL.iter().map(ScrollEasing::as_str).collect()), "ease-out-quad",
+             "Timing curve of scroll animations."),
+        spec("ambient-effect", "animation",
+             Choice(AmbientEffect::ALL.iter().map(AmbientEffect::as_str).collect()), "none",
+             "Animated layer behind the text: gradient noise, snow or matrix rain."),
+        spec("ambient-color", "animation", Color, "#99b3e6",
+             "Color of the ambient layer."),
+        spec("ambient-opacity", "animation", Float { min: 0.0, max: 1.0 }, "0.08",
+             "Opacity of the ambient layer."),
+        spec("ambient-speed", "animation", Float { min: 0.0, max: 10.0 }, "1",
+             "Speed of the ambient layer's motion."),
+        spec("ambient-density", "animation", Float { min: 0.0, max: 1.0 }, "0.5",
+             "How much of the frame the ambient layer's flakes, columns or clouds fill."),
+        spec("ambient-fps", "animation", Integer { min: 1, max: 30 }, "20",
+             "Frames per second drawn to animate the ambient layer; it pauses while unfocused."),
+        spec("scroll-motion-blur", "animation", Bool, "nil",
+             "Blur text along the scroll direction while slide animations move fast."),
+        spec("scroll-motion-blur-strength", "animation", Float { min: 0.0, max: 4.0 }, "1",
+             "Length of the scroll motion blur relative to the distance moved per frame."),
+        // Rendering
+        spec("text-gamma", "rendering", Float { min: 0.1, max: 4.0 }, "1.7",
+             "Gamma applied to glyph coverage; higher values make text bolder."),
+        spec("text-contrast", "rendering", Float { min: 0.0, max: 1.0 }, "0",
+             "Extra contrast applied to glyph coverage."),
+        spec("antialias-width", "rendering", Float { min: 0.0, max: 4.0 }, "1",
+             "Width in device pixels of the smoothed edges of rounded shapes and rings; 0 for hard edges."),
+        spec("debug-overlay", "rendering",
+             Choice(DebugOverlay::ALL.iter().map(DebugOverlay::as_str).collect()), "none",
+             "Draw background rects over the frame to show overdraw, or the text rows rebuilt each frame."),
+        spec("quality", "rendering",
+             Choice(std::iter::once("auto").chain(QualityLevel::ALL.iter().map(QualityLevel::as_str)).collect()),
+             "auto",
+             "Features dropped to keep up: auto steps down through no-particles, \
+              no-transitions, reduced-video and no-blur while frames run late, and \
+              back up when they have time to spare; the other values pin a level."),
+        spec("input-latency", "rendering", Bool, "nil",
+             "Time key and mouse presses from the window system to the frame showing \
+              their effect; see neomacs-input-latency for the percentiles."),
+        spec("glyph-sdf", "rendering", Bool, "nil",
+             "Store text glyphs as signed distance fields so they stay sharp while zooming."),
+        spec("color-filter", "rendering",
+             Choice(ColorFilter::ALL.iter().map(ColorFilter::as_str).collect()), "none",
+             "Color transform of the whole frame: night light, grayscale or a color vision mode."),
+        spec("color-filter-strength", "rendering", Float { min: 0.0, max: 1.0 }, "1",
+             "How strongly the color filter applies, from 0 (off) to 1."),
+        spec("smart-invert", "rendering", Bool, "nil",
+             "Invert the frame's colors for a dark look, keeping hues, but not those of \
+              images, videos and web views."),
+        spec("shader-effects", "rendering", Bool, "t",
+             "Draw the shader effects applied to regions, windows and floating images."),
+        spec("shader-effect-strength", "rendering", Float { min: 0.0, max: 1.0 }, "1",
+             "How strongly shader effects apply, from 0 (off) to 1."),
+        spec("vsync", "rendering", Bool, "t",
+             "Present frames in sync with the display refresh."),
+        // Terminal
+        spec("terminal-foreground", "terminal", Color, "#ffffff",
+             "Default foreground color of terminals."),
+        spec("terminal-background", "terminal", Color, "#000000",
+             "Default background color of terminals."),
+        spec("terminal-palette", "terminal", ColorList { max: 16 },
+             "#000000 #cd0000 #00cd00 #cdcd00 #0000ee #cd00cd #00cdcd #e5e5e5 \
+              #7f7f7f #ff0000 #00ff00 #ffff00 #5c5cff #ff00ff #00ffff #ffffff",
+             "The 16 standard terminal colors; missing entries keep their value."),
+        spec("terminal-idle-scrollback", "terminal", Integer { min: 0, max: 100000 }, "5000",
+             "Scrollback lines kept when terminals are trimmed while the display is idle."),
+        spec("terminal-visual-bell", "terminal",
+             Choice(vec!["none", "flash", "invert"]), "flash",
+             "How a terminal shows BEL: flash the terminal, invert its colors, or none."),
+        spec("terminal-bell-duration-ms", "terminal", Integer { min: 0, max: 2000 }, "150",
+             "How long the visual bell of a terminal lasts."),
+        spec("terminal-bell-interval-ms", "terminal", Integer { min: 0, max: 10000 }, "500",
+             "Bells closer together than this are ignored."),
+        spec("terminal-bell-urgency", "terminal", Bool, "t",
+             "Ask the window manager for attention when a terminal rings while unfocused."),
+        spec("terminal-output-kb-per-frame", "terminal", Integer { min: 0, max: 65536 }, "256",
+             "Terminal output parsed per frame, shared by busy terminals; 0 is unlimited."),
+        // Video
+        spec("video-frame-buffers", "video", Integer { min: 1, max: 16 }, "2",
+             "Decoded frames buffered per video; applies to videos loaded afterwards."),
+        spec("video-max-active", "video", Integer { min: 0, max: 64 }, "4",
+             "Videos decoding at once; the others, and videos off screen, pause \
+              on their last frame.  0 is unlimited."),
+        spec("video-controls", "video", Bool, "nil",
+             "Show play/pause, seek, time and volume controls over videos under the mouse."),
+        // Placeholders
+        spec("placeholder-image", "placeholder",
+             Choice(PlaceholderStyle::ALL.iter().map(PlaceholderStyle::as_str).collect()), "shimmer",
+             "What images show while they load: none, solid, spinner, shimmer or message."),
+        spec("placeholder-video", "placeholder",
+             Choice(PlaceholderStyle::ALL.iter().map(PlaceholderStyle::as_str).collect()), "spinner",
+             "What videos show until their first frame: none, solid, spinner, shimmer or message."),
+        spec("placeholder-webkit", "placeholder",
+             Choice(PlaceholderStyle::ALL.iter().map(PlaceholderStyle::as_str).collect()), "solid",
+             "What web views show until they first paint: none, solid, spinner, shimmer or message."),
+        // Caches
+        spec("image-cache-mb", "cache", Integer { min: 1, max: 4096 }, "64",
+             "Memory budget of the image texture cache in megabytes."),
+        spec("gpu-memory-mb", "cache", Integer { min: 16, max: 16384 }, "512",
+             "GPU memory budget shared by glyphs, images, videos and transition \
+              snapshots, in megabytes."),
+        // Display
+        spec("stall-threshold-ms", "display", Integer { min: 0, max: 10000 }, "100",
+             "Report a render-thread phase running longer than this as a display \
+              warning, with the phase and how far it got.  0 turns the watchdog off."),
+        spec("backend", "display", Choice(vec!["wayland", "x11"]), "wayland",
+             "Windowing system to use on Linux; only read at startup.  Wayland \
+              falls back to X11 when no Wayland display is available."),
+    ]
+});
+
+/// All display options, in declaration order
+pub fn list_options() -> &'static [OptionSpec] {
+    &OPTIONS
+}
+
+/// The declaration of option `name`
+pub fn option_spec(name: &str) -> Option<&'static OptionSpec> {
+    OPTIONS.iter().find(|spec| spec.name == name)
+}
+
+/// Called with the option name and its new effective value
+pub type OptionCallback = Box<dyn FnMut(&'static str, &OptionValue) + Send>;
+
+/// Current values of the display options
+#[derive(Default)]
+pub struct OptionRegistry {
+    file: BTreeMap<&'static str, OptionValue>,
+    runtime: BTreeMap<&'static str, OptionValue>,
+    callbacks: Vec<OptionCallback>,
+}
+
+/// Display options shared between the Emacs and render threads
+pub type SharedOptionRegistry = Arc<Mutex<OptionRegistry>>;
+
+impl OptionRegistry {
+    /// Start from the options in `file`
+    pub fn new(file: &DisplayConfig) -> Self {
+        Self { file: Self::file_values(file), ..Self::default() }
+    }
+
+    /// Call `callback` whenever the effective value of an option changes
+    pub fn on_change(&mut self, callback: OptionCallback) {
+        self.callbacks.push(callback);
+    }
+
+    /// Effective value of option `name`: set at runtime, else from the
+    /// file, else the default
+    pub fn get(&self, name: &str) -> Option<OptionValue> {
+        let spec = option_spec(name)?;
+        self.runtime
+            .get(spec.name)
+            .or_else(|| self.file.get(spec.name))
+            .cloned()
+            .or_else(|| Some(spec.default_value()))
+    }
+
+    /// Set option `name` at runtime from the string form of its value,
+    /// overriding the file
+    pub fn set(&mut self, name: &str, value: &str) -> DisplayResult<()> {
+        let spec = option_spec(name)
+            .ok_or_else(|| DisplayError::Config(format!("unknown display option {:?}", name)))?;
+        let value = spec
+            .ty
+            .parse(value)
+            .map_err(|e| DisplayError::Config(format!("{}: {}", name, e)))?;
+        let old = self.get(spec.name);
+        self.runtime.insert(spec.name, value.clone());
+        if old.as_ref() != Some(&value) {
+            self.notify(spec.name, &value);
+        }
+        Ok(())
+    }
+
+    /// Replace the values read from the config file.  Options dropped from
+    /// the file go back to their default unless set at runtime.
+    pub fn set_file(&mut self, file: &DisplayConfig) {
+        let old: Vec<_> = OPTIONS.iter().map(|spec| self.get(spec.name)).collect();
+        self.file = Self::file_values(file);
+        for (spec, old) in OPTIONS.iter().zip(old) {
+            if let Some(value) = self.get(spec.name).filter(|v| Some(v) != old.as_ref()) {
+                self.notify(spec.name, &value);
+            }
+        }
+    }
+
+    /// Options set in the file or at runtime, with their effective values
+    pub fn explicit_values(&self) -> Vec<(&'static str, OptionValue)> {
+        OPTIONS
+            .iter()
+            .filter(|spec| self.runtime.contains_key(spec.name) || self.file.contains_key(spec.name))
+            .filter_map(|spec| Some((spec.name, self.get(spec.name)?)))
+            .collect()
+    }
+
+    /// Options set at runtime, with their values
+    pub fn runtime_values(&self) -> Vec<(&'static str, OptionValue)> {
+        self.runtime.iter().map(|(&name, value)| (name, value.clone())).collect()
+    }
+
+    fn notify(&mut self, name: &'static str, value: &OptionValue) {
+        for callback in &mut self.callbacks {
+            callback(name, value);
+        }
+    }
+
+    /// Parse the values in `file`, skipping invalid ones with a warning
+    fn file_values(file: &DisplayConfig) -> BTreeMap<&'static str, OptionValue> {
+        let mut values = BTreeMap::new();
+        for spec in OPTIONS.iter() {
+            let Some(text) = file.get(spec.name) else { continue };
+            match spec.ty.parse(text) {
+                Ok(value) => {
+                    values.insert(spec.name, value);
+                }
+                Err(e) => log::warn!("display config: {}: {}", spec.name, e),
+            }
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid() {
+        for spec in list_options() {
+            let value = spec.default_value();
+            assert_eq!(spec.ty.parse(&value.to_string()), Ok(value), "{}", spec.name);
+        }
+        assert_eq!(
+            option_spec("scroll-effect").unwrap().ty.parse("Page_Curl"),
+            Ok(OptionValue::Choice("page-curl"))
+        );
+        assert!(option_spec("scroll-effect").unwrap().ty.parse("sideways").is_err());
+        assert!(option_spec("image-cache-mb").unwrap().ty.parse("0").is_err());
+        assert!(option_spec("terminal-palette").unwrap().ty.parse("#000000 red").is_err());
+    }
+
+    #[test]
+    fn test_runtime_values_override_file() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let file = DisplayConfig::parse("vsync = false\ntext-gamma = 1.5").unwrap();
+        let mut registry = OptionRegistry::new(&file);
+        let log = Arc::clone(&changes);
+        registry.on_change(Box::new(move |name, value| {
+            log.lock().unwrap().push((name, value.to_string()));
+        }));
+
+        assert_eq!(registry.get("image-cache-mb"), Some(OptionValue::Integer(64)));
+        assert!(registry.set("vsync", "maybe").is_err());
+        assert!(registry.set("no-such-option", "t").is_err());
+        registry.set("vsync", "on").unwrap();
+        registry.set("vsync", "t").unwrap();
+        assert_eq!(registry.get("vsync"), Some(OptionValue::Bool(true)));
+
+        // Reloading reports only changes that are not overridden, and
+        // options dropped from the file return to their default
+        let file = DisplayConfig::parse("vsync = false\nbackend = \"x11\"").unwrap();
+        registry.set_file(&file);
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![("vsync", "t".to_string()), ("text-gamma", "1.7".to_string()), ("backend", "x11".to_string())]
+        );
+        assert_eq!(
+            registry.explicit_values(),
+            vec![("vsync", OptionValue::Bool(true)), ("backend", OptionValue::Choice("x11"))]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Background overdraw elimination.
+//!
+//! Every window is filled with its Background glyph, then every glyph cell
+//! with a background draws its own rect on top, most of them in the very
+//! color of the window under them.  This pass drops cell rects that repaint
+//! their window's background and merges the rest into runs of one color,
+//! so far fewer and smaller rects reach the GPU.
+
+use super::types::{Color, Rect};
+
+/// Tolerance for treating cell edges as touching, in logical pixels
+const EPSILON: f32 = 0.5;
+
+/// What the pass saved on one frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OverdrawStats {
+    /// Cell rects before the pass
+    pub rects_in: usize,
+    /// Rects left to draw
+    pub rects_out: usize,
+    /// Pixels the cell rects covered before the pass
+    pub area_in: f32,
+    /// Pixels the remaining rects cover
+    pub area_out: f32,
+}
+
+impl std::ops::AddAssign for OverdrawStats {
+    fn add_assign(&mut self, other: Self) {
+        self.rects_in += other.rects_in;
+        self.rects_out += other.rects_out;
+        self.area_in += other.area_in;
+        self.area_out += other.area_out;
+    }
+}
+
+impl OverdrawStats {
+    /// Share of the cell background pixels no longer drawn, 0 to 1
+    pub fn area_saved(&self) -> f32 {
+        if self.area_in > 0.0 {
+            (1.0 - self.area_out / self.area_in).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+fn same_color(a: &Color, b: &Color) -> bool {
+    (a.r - b.r).abs() < 1e-4
+        && (a.g - b.g).abs() < 1e-4
+        && (a.b - b.b).abs() < 1e-4
+        && (a.a - b.a).abs() < 1e-4
+}
+
+/// Whether `inner` lies within `outer`, give or take `EPSILON`
+pub(crate) fn inside(outer: &Rect, inner: &Rect) -> bool {
+    inner.x >= outer.x - EPSILON
+        && inner.y >= outer.y - EPSILON
+        && inner.right() <= outer.right() + EPSILON
+        && inner.bottom() <= outer.bottom() + EPSILON
+}
+
+/// Reduce the cell backgrounds `cells`, drawn over the window backgrounds
+/// `windows`, to the rects that change what is on screen.  Cells are
+/// assumed not to overlap each other, as cells of a glyph matrix do not.
+pub fn eliminate_overdraw(windows: &[(Rect, Color)], cells: Vec<(Rect, Color)>) -> (Vec<(Rect, Color)>, OverdrawStats) {
+    let mut stats = OverdrawStats {
+        rects_in: cells.len(),
+        area_in: cells.iter().map(|(r, _)| r.width * r.height).sum(),
+        ..OverdrawStats::default()
+    };
+
+    // Cells repainting the background of the window they sit in
+    let mut cells: Vec<(Rect, Color)> = cells
+        .into_iter()
+        .filter(|(r, _)| r.width > 0.0 && r.height > 0.0)
+        .filter(|(r, c)| {
+            // The last window containing the cell is the one on top
+            !windows
+                .iter()
+                .rev()
+                .find(|(w, _)| inside(w, r))
+                .is_some_and(|(_, wc)| same_color(wc, c))
+        })
+        .collect();
+
+    // Runs of one color along each row
+    cells.sort_by(|a, b| a.0.y.total_cmp(&b.0.y).then(a.0.x.total_cmp(&b.0.x)));
+    let mut runs: Vec<(Rect, Color)> = Vec::with_capacity(cells.len());
+    for (cell, color) in cells {
+        if let Some((run, run_color)) = runs.last_mut() {
+            if same_color(run_color, &color)
+                && (run.y - cell.y).abs() < EPSILON
+                && (run.height - cell.height).abs() < EPSILON
+                && (cell.x - run.right()).abs() < EPSILON
+            {
+                run.width = cell.right() - run.x;
+                continue;
+            }
+        }
+        runs.push((cell, color));
+    }
+
+    // Runs of the same span stacked on consecutive rows
+    runs.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.y.total_cmp(&b.0.y)));
+    let mut merged: Vec<(Rect, Color)> = Vec::with_capacity(runs.len());
+    for (run, color) in runs {
+        if let Some((block, block_color)) = merged.last_mut() {
+            if same_color(block_color, &color)
+                && (block.x - run.x).abs() < EPSILON
+                && (block.width - run.width).abs() < EPSILON
+                && (run.y - block.bottom()).abs() < EPSILON
+            {
+                block.height = run.bottom() - block.y;
+                continue;
+            }
+        }
+        merged.push((run, color));
+    }
+
+    stats.rects_out = merged.len();
+    stats.area_out = merged.iter().map(|(r, _)| r.width * r.height).sum();
+    (merged, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eliminate_overdraw() {
+        let bg = Color::new(0.1, 0.1, 0.1, 1.0);
+        let hl = Color::new(0.3, 0.2, 0.1, 1.0);
+        let window = (Rect::new(0.0, 0.0, 100.0, 64.0), bg);
+        let mut cells = Vec::new();
+        for row in 0..2 {
+            for col in 0..10 {
+                // Columns 2..6 highlighted, the rest in the window color
+                let color = if (2..6).contains(&col) { hl } else { bg };
+                cells.push((Rect::new(col as f32 * 8.0, row as f32 * 16.0, 8.0, 16.0), color));
+            }
+        }
+        let (rects, stats) = eliminate_overdraw(&[window], cells);
+        assert_eq!(rects, vec![(Rect::new(16.0, 0.0, 32.0, 32.0), hl)]);
+        assert_eq!((stats.rects_in, stats.rects_out), (20, 1));
+        assert!((stats.area_saved() - 0.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_overdraw_keeps_distinct_cells() {
+        let bg = Color::new(0.1, 0.1, 0.1, 1.0);
+        let a = Color::new(1.0, 0.0, 0.0, 1.0);
+        let b = Color::new(0.0, 1.0, 0.0, 1.0);
+        // A gap and a color change both break runs; cells outside any
+        // window are kept
+        let cells = vec![
+            (Rect::new(0.0, 0.0, 8.0, 16.0), a),
+            (Rect::new(8.0, 0.0, 8.0, 16.0), b),
+            (Rect::new(24.0, 0.0, 8.0, 16.0), b),
+            (Rect::new(200.0, 0.0, 8.0, 16.0), bg),
+        ];
+        let (rects, stats) = eliminate_overdraw(&[(Rect::new(0.0, 0.0, 100.0, 16.0), bg)], cells);
+        assert_eq!(rects.len(), 4);
+        assert_eq!(stats.area_saved(), 0.0);
+    }
+}
@@ -0,0 +1,309 @@
+//! Picture-in-picture video player.
+//!
+//! A video detached from its buffer into a small player pinned to a
+//! corner of the frame.  The player belongs to the render thread rather
+//! than to any window, so it stays up across buffer switches and window
+//! configuration changes.  Dragging the video moves the player; on
+//! release it snaps to the nearest corner.  The grip on its top edge
+//! resizes it, and while the pointer is over it a control bar with
+//! play/pause, a seek bar and a close button is shown.
+
+use crate::core::types::{Point, Rect};
+
+/// Gap between the player and the frame edges
+pub const PIP_MARGIN: f32 = 16.0;
+/// Height of the control bar along the bottom of the player
+pub const PIP_CONTROLS_HEIGHT: f32 = 28.0;
+/// Side of the buttons and of the resize grip
+const BUTTON_SIZE: f32 = 20.0;
+/// Narrowest the player can be resized to
+const MIN_WIDTH: f32 = 160.0;
+
+/// Frame corner the player is pinned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl PipCorner {
+    /// 0 = top-left, 1 = top-right, 2 = bottom-left, anything else bottom-right
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => PipCorner::TopLeft,
+            1 => PipCorner::TopRight,
+            2 => PipCorner::BottomLeft,
+            _ => PipCorner::BottomRight,
+        }
+    }
+
+    fn is_left(self) -> bool {
+        matches!(self, PipCorner::TopLeft | PipCorner::BottomLeft)
+    }
+
+    fn is_top(self) -> bool {
+        matches!(self, PipCorner::TopLeft | PipCorner::TopRight)
+    }
+
+    /// Corner of the frame quadrant holding (`x`, `y`)
+    fn nearest(x: f32, y: f32, frame: Rect) -> Self {
+        let left = x < frame.x + frame.width / 2.0;
+        let top = y < frame.y + frame.height / 2.0;
+        match (top, left) {
+            (true, true) => PipCorner::TopLeft,
+            (true, false) => PipCorner::TopRight,
+            (false, true) => PipCorner::BottomLeft,
+            (false, false) => PipCorner::BottomRight,
+        }
+    }
+}
+
+/// Part of the player under the pointer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PipPart {
+    /// The video itself, which moves the player
+    Video,
+    PlayPause,
+    /// The seek bar, at this fraction of the video
+    Seek(f32),
+    Close,
+    /// The resize grip
+    Resize,
+}
+
+/// Where the control bar pieces of a player at a given rect are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipControls {
+    pub bar: Rect,
+    pub play: Rect,
+    pub seek: Rect,
+    pub close: Rect,
+    pub grip: Rect,
+}
+
+/// What the pointer is doing with the button held down
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Drag {
+    /// Moving the player; its top-left corner and where it was grabbed
+    Move { x: f32, y: f32, grab_x: f32, grab_y: f32 },
+    /// Resizing from the width it had when the grip was pressed at `from_x`
+    Resize { from_x: f32, width: f32 },
+    Seek,
+    /// A button, released without effect
+    Click,
+}
+
+/// A picture-in-picture player
+#[derive(Debug, Clone)]
+pub struct PipPlayer {
+    pub video_id: u32,
+    pub corner: PipCorner,
+    pub width: f32,
+    /// Height over width of the video
+    pub aspect: f32,
+    /// Whether the pointer is over the player
+    pub hovered: bool,
+    drag: Option<Drag>,
+}
+
+impl PipPlayer {
+    pub fn new(video_id: u32, corner: PipCorner, width: f32) -> Self {
+        Self {
+            video_id,
+            corner,
+            width: width.max(MIN_WIDTH),
+            aspect: 9.0 / 16.0,
+            hovered: false,
+            drag: None,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        self.width * self.aspect
+    }
+
+    /// Area of the player in a frame of bounds `frame`
+    pub fn rect(&self, frame: Rect) -> Rect {
+        let (w, h) = (self.width, self.height());
+        if let Some(Drag::Move { x, y, .. }) = self.drag {
+            return Rect::new(x, y, w, h);
+        }
+        let x = if self.corner.is_left() {
+            frame.x + PIP_MARGIN
+        } else {
+            frame.right() - PIP_MARGIN - w
+        };
+        let y = if self.corner.is_top() {
+            frame.y + PIP_MARGIN
+        } else {
+            frame.bottom() - PIP_MARGIN - h
+        };
+        Rect::new(x, y, w, h)
+    }
+
+    /// Whether the control bar is drawn
+    pub fn controls_shown(&self) -> bool {
+        self.hovered || self.drag.is_some()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Layout of the control bar of a player at `rect`.  The grip sits on
+    /// the top edge, at the side facing the middle of the frame.
+    pub fn controls(&self, rect: Rect) -> PipControls {
+        let bar = Rect::new(
+            rect.x, rect.bottom() - PIP_CONTROLS_HEIGHT, rect.width, PIP_CONTROLS_HEIGHT,
+        );
+        let pad = (PIP_CONTROLS_HEIGHT - BUTTON_SIZE) / 2.0;
+        let button_y = bar.y + pad;
+        let play = Rect::new(bar.x + pad, button_y, BUTTON_SIZE, BUTTON_SIZE);
+        let close = Rect::new(bar.right() - pad - BUTTON_SIZE, button_y, BUTTON_SIZE, BUTTON_SIZE);
+        let seek_x = play.right() + pad * 2.0;
+        let seek = Rect::new(seek_x, button_y, (close.x - pad * 2.0 - seek_x).max(0.0), BUTTON_SIZE);
+        let grip_x = if self.corner.is_left() { rect.right() - BUTTON_SIZE } else { rect.x };
+        let grip = Rect::new(grip_x, rect.y, BUTTON_SIZE, BUTTON_SIZE);
+        PipControls { bar, play, seek, close, grip }
+    }
+
+    /// Part of the player at (`x`, `y`), if any
+    pub fn hit(&self, x: f32, y: f32, frame: Rect) -> Option<PipPart> {
+        let rect = self.rect(frame);
+        let p = Point::new(x, y);
+        if !rect.contains(p) {
+            return None;
+        }
+        let c = self.controls(rect);
+        Some(if c.grip.contains(p) {
+            PipPart::Resize
+        } else if c.play.contains(p) {
+            PipPart::PlayPause
+        } else if c.close.contains(p) {
+            PipPart::Close
+        } else if c.seek.contains(p) {
+            PipPart::Seek(seek_fraction(x, c.seek))
+        } else {
+            PipPart::Video
+        })
+    }
+
+    /// Button pressed at (`x`, `y`).  Returns the part pressed, which
+    /// then takes the pointer until `release`.
+    pub fn press(&mut self, x: f32, y: f32, frame: Rect) -> Option<PipPart> {
+        let part = self.hit(x, y, frame)?;
+        let rect = self.rect(frame);
+        self.drag = Some(match part {
+            PipPart::Video => Drag::Move { x: rect.x, y: rect.y, grab_x: x - rect.x, grab_y: y - rect.y },
+            PipPart::Resize => Drag::Resize { from_x: x, width: self.width },
+            PipPart::Seek(_) => Drag::Seek,
+            PipPart::PlayPause | PipPart::Close => Drag::Click,
+        });
+        Some(part)
+    }
+
+    /// Pointer moved to (`x`, `y`).  Returns the seek position while the
+    /// seek bar is dragged.
+    pub fn motion(&mut self, x: f32, y: f32, frame: Rect) -> Option<f32> {
+        match self.drag {
+            Some(Drag::Move { grab_x, grab_y, .. }) => {
+                let max_x = (frame.right() - self.width).max(frame.x);
+                let max_y = (frame.bottom() - self.height()).max(frame.y);
+                self.drag = Some(Drag::Move {
+                    x: (x - grab_x).clamp(frame.x, max_x),
+                    y: (y - grab_y).clamp(frame.y, max_y),
+                    grab_x,
+                    grab_y,
+                });
+            }
+            Some(Drag::Resize { from_x, width }) => {
+                // The grip faces the middle of the frame, so moving it
+                // toward the middle makes the player bigger
+                let grow = if self.corner.is_left() { x - from_x } else { from_x - x };
+                let max_w = (frame.width - 2.0 * PIP_MARGIN)
+                    .min((frame.height - 2.0 * PIP_MARGIN) / self.aspect)
+                    .max(MIN_WIDTH);
+                self.width = (width + grow).clamp(MIN_WIDTH, max_w);
+            }
+            Some(Drag::Seek) => {
+                let rect = self.rect(frame);
+                return Some(seek_fraction(x, self.controls(rect).seek));
+            }
+            Some(Drag::Click) | None => {}
+        }
+        self.hovered = self.rect(frame).contains(Point::new(x, y));
+        None
+    }
+
+    /// Button released.  A moved player is pinned to the corner nearest
+    /// to its middle.  Returns whether the player had the pointer.
+    pub fn release(&mut self, frame: Rect) -> bool {
+        let Some(drag) = self.drag.take() else {
+            return false;
+        };
+        if let Drag::Move { x, y, .. } = drag {
+            let (cx, cy) = (x + self.width / 2.0, y + self.height() / 2.0);
+            self.corner = PipCorner::nearest(cx, cy, frame);
+        }
+        true
+    }
+}
+
+fn seek_fraction(x: f32, seek: Rect) -> f32 {
+    if seek.width <= 0.0 {
+        return 0.0;
+    }
+    ((x - seek.x) / seek.width).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Rect = Rect::new(0.0, 0.0, 1200.0, 800.0);
+
+    #[test]
+    fn test_pip_drag_snaps_to_nearest_corner() {
+        let mut pip = PipPlayer::new(1, PipCorner::BottomRight, 320.0);
+        let r = pip.rect(FRAME);
+        assert_eq!((r.right(), r.bottom()), (1200.0 - PIP_MARGIN, 800.0 - PIP_MARGIN));
+
+        // Grab the middle of the video and drag it to the top left
+        let (gx, gy) = (r.x + r.width / 2.0, r.y + 40.0);
+        assert_eq!(pip.press(gx, gy, FRAME), Some(PipPart::Video));
+        pip.motion(200.0, 150.0, FRAME);
+        assert_eq!(pip.rect(FRAME).x, 200.0 - r.width / 2.0);
+        assert!(pip.release(FRAME));
+        assert_eq!(pip.corner, PipCorner::TopLeft);
+        assert_eq!(pip.rect(FRAME).x, PIP_MARGIN);
+        assert!(!pip.release(FRAME));
+    }
+
+    #[test]
+    fn test_pip_controls_and_resize() {
+        let mut pip = PipPlayer::new(1, PipCorner::BottomRight, 320.0);
+        let r = pip.rect(FRAME);
+        let c = pip.controls(r);
+        assert_eq!(pip.hit(c.play.x + 1.0, c.play.y + 1.0, FRAME), Some(PipPart::PlayPause));
+        assert_eq!(pip.hit(c.close.x + 1.0, c.close.y + 1.0, FRAME), Some(PipPart::Close));
+        let mid = c.seek.x + c.seek.width / 2.0;
+        assert_eq!(pip.hit(mid, c.seek.y + 1.0, FRAME), Some(PipPart::Seek(0.5)));
+        assert_eq!(pip.hit(0.0, 0.0, FRAME), None);
+
+        // Seeking follows the pointer past the ends of the bar
+        pip.press(mid, c.seek.y + 1.0, FRAME);
+        assert_eq!(pip.motion(0.0, c.seek.y, FRAME), Some(0.0));
+        pip.release(FRAME);
+
+        // The grip of a right-pinned player is on its left; dragging it
+        // left grows the player, which stays pinned
+        assert_eq!(c.grip.x, r.x);
+        assert_eq!(pip.press(r.x + 2.0, r.y + 2.0, FRAME), Some(PipPart::Resize));
+        pip.motion(r.x - 78.0, r.y, FRAME);
+        pip.release(FRAME);
+        assert_eq!(pip.width, 400.0);
+        assert_eq!(pip.rect(FRAME).right(), r.right());
+    }
+}
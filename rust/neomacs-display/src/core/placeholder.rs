@@ -0,0 +1,253 @@
+//! What images, videos and web views show before they can be drawn.
+//!
+//! An image still decoding, a video without its first frame and a web
+//! view that has not painted yet each show a placeholder, in a style set
+//! per kind of content by the `placeholder-*` display options: a plain
+//! area, a spinner, a shimmer sweeping across, or an icon with a message.
+//! Content that failed always shows a warning icon, an outline and a
+//! message whatever the style, so it is not mistaken for content still
+//! on its way.
+
+use super::types::Rect;
+
+/// Seconds a spinner takes to go round once
+pub const SPINNER_PERIOD: f32 = 1.0;
+
+/// Dots of a spinner
+pub const SPINNER_DOTS: usize = 8;
+
+/// Seconds a shimmer takes to sweep across
+pub const SHIMMER_PERIOD: f32 = 1.5;
+
+/// Icon of content on its way
+pub const LOADING_ICON: char = '\u{25cc}';
+
+/// Icon of content that failed
+pub const BROKEN_ICON: char = '\u{26a0}';
+
+/// Content that can show a placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderContent {
+    Image,
+    Video,
+    WebKit,
+}
+
+impl PlaceholderContent {
+    /// Name of the content in messages
+    pub fn noun(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+            Self::WebKit => "page",
+        }
+    }
+}
+
+/// Why content is not drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderState {
+    /// Still being loaded or decoded
+    Loading,
+    /// Failed and will not come
+    Broken,
+}
+
+/// How content on its way is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderStyle {
+    /// Nothing; the text behind shows through
+    None,
+    /// An area of the placeholder color
+    Solid,
+    /// Dots going round in the middle
+    Spinner,
+    /// A lighter band sweeping across
+    Shimmer,
+    /// An icon and what is loading
+    Message,
+}
+
+impl PlaceholderStyle {
+    pub const ALL: [PlaceholderStyle; 5] =
+        [Self::None, Self::Solid, Self::Spinner, Self::Shimmer, Self::Message];
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "solid" => Self::Solid,
+            "spinner" => Self::Spinner,
+            "shimmer" => Self::Shimmer,
+            "message" => Self::Message,
+            _ => Self::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Solid => "solid",
+            Self::Spinner => "spinner",
+            Self::Shimmer => "shimmer",
+            Self::Message => "message",
+        }
+    }
+}
+
+/// Loading style of each kind of content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaceholderStyles {
+    pub image: PlaceholderStyle,
+    pub video: PlaceholderStyle,
+    pub webkit: PlaceholderStyle,
+}
+
+impl Default for PlaceholderStyles {
+    fn default() -> Self {
+        Self {
+            image: PlaceholderStyle::Shimmer,
+            video: PlaceholderStyle::Spinner,
+            webkit: PlaceholderStyle::Solid,
+        }
+    }
+}
+
+impl PlaceholderStyles {
+    pub fn get(&self, content: PlaceholderContent) -> PlaceholderStyle {
+        match content {
+            PlaceholderContent::Image => self.image,
+            PlaceholderContent::Video => self.video,
+            PlaceholderContent::WebKit => self.webkit,
+        }
+    }
+
+    pub fn set(&mut self, content: PlaceholderContent, style: PlaceholderStyle) {
+        match content {
+            PlaceholderContent::Image => self.image = style,
+            PlaceholderContent::Video => self.video = style,
+            PlaceholderContent::WebKit => self.webkit = style,
+        }
+    }
+}
+
+/// What to draw for one placeholder
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaceholderShapes {
+    /// Areas of the placeholder color, each with a factor on its alpha
+    pub rects: Vec<(Rect, f32)>,
+    /// Icon and message, centered in the placeholder where they fit
+    pub label: Option<(char, String)>,
+    /// Whether the shapes change with time
+    pub animated: bool,
+}
+
+/// The shapes of a placeholder over `area` for content in `state`,
+/// `time` seconds into its animation
+pub fn layout(
+    content: PlaceholderContent,
+    state: PlaceholderState,
+    style: PlaceholderStyle,
+    area: Rect,
+    time: f32,
+) -> PlaceholderShapes {
+    let mut shapes = PlaceholderShapes::default();
+    if area.width <= 0.0 || area.height <= 0.0 {
+        return shapes;
+    }
+
+    if state == PlaceholderState::Broken {
+        shapes.rects.push((area, 1.0));
+        let edge = 1.0_f32.min(area.width / 2.0).min(area.height / 2.0);
+        shapes.rects.extend([
+            (Rect::new(area.x, area.y, area.width, edge), 4.0),
+            (Rect::new(area.x, area.bottom() - edge, area.width, edge), 4.0),
+            (Rect::new(area.x, area.y, edge, area.height), 4.0),
+            (Rect::new(area.right() - edge, area.y, edge, area.height), 4.0),
+        ]);
+        shapes.label = Some((BROKEN_ICON, format!("Cannot show {}", content.noun())));
+        return shapes;
+    }
+
+    match style {
+        PlaceholderStyle::None => {}
+        PlaceholderStyle::Solid => shapes.rects.push((area, 1.0)),
+        PlaceholderStyle::Spinner => {
+            shapes.rects.push((area, 1.0));
+            let size = area.width.min(area.height);
+            let radius = (size * 0.15).clamp(6.0, 24.0);
+            let dot = (radius * 0.35).max(2.0);
+            if size >= (radius + dot) * 2.0 {
+                let (cx, cy) = (area.x + area.width / 2.0, area.y + area.height / 2.0);
+                let head = (time / SPINNER_PERIOD).fract() * SPINNER_DOTS as f32;
+                for i in 0..SPINNER_DOTS {
+                    let angle = i as f32 / SPINNER_DOTS as f32 * std::f32::consts::TAU;
+                    // Dots fade behind the head as it goes round
+                    let behind = (head - i as f32).rem_euclid(SPINNER_DOTS as f32);
+                    let alpha = 1.0 + 4.0 * (1.0 - behind / SPINNER_DOTS as f32);
+                    let x = cx + radius * angle.sin() - dot / 2.0;
+                    let y = cy - radius * angle.cos() - dot / 2.0;
+                    shapes.rects.push((Rect::new(x, y, dot, dot), alpha));
+                }
+            }
+            shapes.animated = true;
+        }
+        PlaceholderStyle::Shimmer => {
+            shapes.rects.push((area, 1.0));
+            let band = (area.width * 0.3).max(8.0);
+            let start = area.x - band + (time / SHIMMER_PERIOD).fract() * (area.width + band);
+            // Three strips, brightest in the middle, for a soft band
+            for (offset, width, alpha) in [(0.0, 0.25, 1.6), (0.25, 0.5, 2.2), (0.75, 0.25, 1.6)] {
+                let strip = Rect::new(start + band * offset, area.y, band * width, area.height);
+                if let Some(strip) = strip.intersection(&area) {
+                    shapes.rects.push((strip, alpha));
+                }
+            }
+            shapes.animated = true;
+        }
+        PlaceholderStyle::Message => {
+            shapes.rects.push((area, 1.0));
+            shapes.label = Some((LOADING_ICON, format!("Loading {}", content.noun())));
+        }
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholder_broken_content_stands_out() {
+        let area = Rect::new(10.0, 10.0, 200.0, 100.0);
+        for style in PlaceholderStyle::ALL {
+            assert_eq!(PlaceholderStyle::from_str(style.as_str()), style);
+            let shapes = layout(PlaceholderContent::Image, PlaceholderState::Broken, style, area, 0.0);
+            assert_eq!(shapes.label, Some((BROKEN_ICON, "Cannot show image".to_string())));
+            assert!(!shapes.animated);
+        }
+        let loading = layout(PlaceholderContent::Image, PlaceholderState::Loading,
+                             PlaceholderStyle::Message, area, 0.0);
+        assert_eq!(loading.label, Some((LOADING_ICON, "Loading image".to_string())));
+        assert!(layout(PlaceholderContent::Video, PlaceholderState::Loading,
+                       PlaceholderStyle::None, area, 0.0).rects.is_empty());
+    }
+
+    #[test]
+    fn test_placeholder_animations_stay_inside() {
+        let area = Rect::new(0.0, 0.0, 160.0, 90.0);
+        let spinner = |t| layout(PlaceholderContent::Video, PlaceholderState::Loading,
+                                 PlaceholderStyle::Spinner, area, t);
+        let (a, b) = (spinner(0.0), spinner(SPINNER_PERIOD / 2.0));
+        assert!(a.animated);
+        assert_eq!(a.rects.len(), 1 + SPINNER_DOTS);
+        assert_ne!(a.rects, b.rects);
+
+        for t in [0.0, 0.3, 0.9, 1.4] {
+            let shimmer = layout(PlaceholderContent::Image, PlaceholderState::Loading,
+                                 PlaceholderStyle::Shimmer, area, t);
+            for (rect, _) in shimmer.rects.iter().chain(spinner(t).rects.iter()) {
+                assert!(rect.x >= area.x && rect.right() <= area.right() + 0.001);
+                assert!(rect.y >= area.y && rect.bottom() <= area.bottom() + 0.001);
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Parallel frame preparation.
+//!
+//! Work that only reads the frame's glyphs, and splits along windows or
+//! rows, runs here on the rayon pool before the renderer emits vertices on
+//! its own thread.  Small frames are prepared on the calling thread, where
+//! handing work to the pool would cost more than it saves.
+
+use rayon::prelude::*;
+
+use super::overdraw::{eliminate_overdraw, inside, OverdrawStats};
+use super::types::{Color, Rect};
+
+/// Frames with fewer items than this are prepared serially
+pub const PARALLEL_THRESHOLD: usize = 2048;
+
+/// Whether `items` pieces of work are worth spreading over the pool
+pub fn worth_parallel(items: usize) -> bool {
+    items >= PARALLEL_THRESHOLD && rayon::current_num_threads() > 1
+}
+
+/// `eliminate_overdraw` run on the cells of each window concurrently.
+/// A cell belongs to the last window containing it, the one on top; cells
+/// outside every window form a group of their own.
+pub fn prepare_backgrounds(windows: &[(Rect, Color)], cells: Vec<(Rect, Color)>) -> (Vec<(Rect, Color)>, OverdrawStats) {
+    if windows.len() < 2 || !worth_parallel(cells.len()) {
+        return eliminate_overdraw(windows, cells);
+    }
+
+    let mut groups: Vec<Vec<(Rect, Color)>> = vec![Vec::new(); windows.len() + 1];
+    for cell in cells {
+        let group = windows
+            .iter()
+            .rposition(|(w, _)| inside(w, &cell.0))
+            .unwrap_or(windows.len());
+        groups[group].push(cell);
+    }
+
+    let prepared: Vec<(Vec<(Rect, Color)>, OverdrawStats)> = groups
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, cells)| eliminate_overdraw(windows.get(i).map(std::slice::from_ref).unwrap_or(&[]), cells))
+        .collect();
+
+    let mut rects = Vec::new();
+    let mut stats = OverdrawStats::default();
+    for (group, group_stats) in prepared {
+        rects.extend(group);
+        stats += group_stats;
+    }
+    (rects, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_backgrounds_matches_serial() {
+        let bg = Color::new(0.1, 0.1, 0.1, 1.0);
+        let hl = Color::new(0.3, 0.2, 0.1, 1.0);
+        let windows = [
+            (Rect::new(0.0, 0.0, 400.0, 800.0), bg),
+            (Rect::new(400.0, 0.0, 400.0, 800.0), hl),
+        ];
+        let mut cells = Vec::new();
+        for row in 0..50 {
+            for col in 0..100 {
+                let color = if col % 7 < 3 { hl } else { bg };
+                cells.push((Rect::new(col as f32 * 8.0, row as f32 * 16.0, 8.0, 16.0), color));
+            }
+        }
+        let (serial, serial_stats) = eliminate_overdraw(&windows, cells.clone());
+        let (parallel, stats) = prepare_backgrounds(&windows, cells);
+        assert_eq!((stats.rects_in, stats.area_in), (serial_stats.rects_in, serial_stats.area_in));
+        assert_eq!(stats.area_out, serial_stats.area_out);
+
+        // Same rects, possibly in another order
+        let key = |r: &(Rect, Color)| (r.0.x.to_bits(), r.0.y.to_bits());
+        let (mut a, mut b) = (serial, parallel);
+        a.sort_by_key(key);
+        b.sort_by_key(key);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,272 @@
+//! Adaptive quality under load.
+//!
+//! When frames keep taking longer than a refresh interval, the display
+//! drops its most expensive features one step at a time rather than
+//! stutter: first cursor particles, then buffer and scroll transitions,
+//! then redraws for video beyond 30 fps, and last the backdrop blur.
+//! Each step keeps the ones before it.  When frames have had ample
+//! headroom for a while the last step is undone, and if that brings the
+//! slow frames back, the next attempt waits twice as long.
+//!
+//! The `quality` display option pins a level instead; "auto" lets the
+//! controller choose.  The level in effect is kept process-wide so Emacs
+//! can show it.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use crate::effect_config::EffectsConfig;
+
+/// Frames looked at before deciding to step down
+const WINDOW: u32 = 60;
+
+/// Frames of a window over budget that make the display step down
+const SLOW_TO_STEP_DOWN: u32 = 15;
+
+/// Frames in a row within half the budget that make it step back up,
+/// before any backing off
+const FAST_TO_STEP_UP: u32 = 300;
+
+/// Longest wait before stepping up, in frames
+const MAX_FAST_TO_STEP_UP: u32 = 4800;
+
+/// Features in effect, each level dropping one more than the last
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum QualityLevel {
+    #[default]
+    Full = 0,
+    /// No cursor particles
+    NoParticles = 1,
+    /// No buffer, scroll or text-scale transitions either
+    NoTransitions = 2,
+    /// Videos redraw at most 30 times a second
+    ReducedVideo = 3,
+    /// No backdrop blur behind floating content
+    NoBlur = 4,
+}
+
+impl QualityLevel {
+    pub const ALL: [QualityLevel; 5] =
+        [Self::Full, Self::NoParticles, Self::NoTransitions, Self::ReducedVideo, Self::NoBlur];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.as_str() == s)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::NoParticles => "no-particles",
+            Self::NoTransitions => "no-transitions",
+            Self::ReducedVideo => "reduced-video",
+            Self::NoBlur => "no-blur",
+        }
+    }
+
+    fn from_u8(n: u8) -> Self {
+        Self::ALL.get(n as usize).copied().unwrap_or_default()
+    }
+
+    /// The next level down, if any
+    pub fn lower(self) -> Option<Self> {
+        Self::ALL.get(self as usize + 1).copied()
+    }
+
+    /// The next level up, if any
+    pub fn raise(self) -> Option<Self> {
+        (self as usize).checked_sub(1).map(|i| Self::ALL[i])
+    }
+
+    pub fn transitions(self) -> bool {
+        self < Self::NoTransitions
+    }
+
+    pub fn full_rate_video(self) -> bool {
+        self < Self::ReducedVideo
+    }
+
+    /// Turn off in `effects` what this level drops
+    pub fn apply(self, effects: &mut EffectsConfig) {
+        if self >= Self::NoParticles {
+            effects.cursor_particles.enabled = false;
+            effects.cursor_orbit_particles.enabled = false;
+        }
+        if self >= Self::NoBlur {
+            effects.backdrop_blur.enabled = false;
+        }
+    }
+}
+
+/// Picks the quality level from how long frames take
+#[derive(Debug, Clone)]
+pub struct QualityController {
+    level: QualityLevel,
+    /// Level set with the `quality` option instead of measured
+    pinned: Option<QualityLevel>,
+    /// Frames, and frames over budget, in the current window
+    frames: u32,
+    slow: u32,
+    /// Frames in a row within half the budget
+    fast: u32,
+    /// Fast frames needed to step up
+    fast_to_step_up: u32,
+    /// Whether the last change was a step up, which slow frames would undo
+    raised: bool,
+}
+
+impl Default for QualityController {
+    fn default() -> Self {
+        Self {
+            level: QualityLevel::Full,
+            pinned: None,
+            frames: 0,
+            slow: 0,
+            fast: 0,
+            fast_to_step_up: FAST_TO_STEP_UP,
+            raised: false,
+        }
+    }
+}
+
+impl QualityController {
+    pub fn level(&self) -> QualityLevel {
+        self.level
+    }
+
+    pub fn pinned(&self) -> Option<QualityLevel> {
+        self.pinned
+    }
+
+    /// Pin the level, or let frame times choose it again.  Returns the
+    /// new level if it changed.
+    pub fn set_pinned(&mut self, pinned: Option<QualityLevel>) -> Option<QualityLevel> {
+        self.pinned = pinned;
+        self.fast = 0;
+        self.change(pinned.unwrap_or(QualityLevel::Full))
+    }
+
+    fn change(&mut self, level: QualityLevel) -> Option<QualityLevel> {
+        // Each level is judged on its own frames
+        self.frames = 0;
+        self.slow = 0;
+        let changed = level != self.level;
+        self.raised = level < self.level;
+        self.level = level;
+        changed.then_some(level)
+    }
+
+    /// Account for a frame that took `time` to build against a budget of
+    /// `budget`.  Returns the new level if it changed.
+    pub fn frame_rendered(&mut self, time: Duration, budget: Duration) -> Option<QualityLevel> {
+        if self.pinned.is_some() {
+            return None;
+        }
+        self.frames += 1;
+        if time > budget {
+            self.slow += 1;
+            self.fast = 0;
+        } else if time <= budget / 2 {
+            self.fast += 1;
+        } else {
+            self.fast = 0;
+        }
+
+        if self.slow >= SLOW_TO_STEP_DOWN {
+            self.frames = 0;
+            self.slow = 0;
+            // Stepping up did not hold, so wait longer next time
+            if self.raised {
+                self.fast_to_step_up = (self.fast_to_step_up * 2).min(MAX_FAST_TO_STEP_UP);
+            }
+            let lower = self.level.lower()?;
+            return self.change(lower);
+        }
+        if self.frames >= WINDOW {
+            // A full window with few slow frames: the level holds
+            if self.raised {
+                self.fast_to_step_up = FAST_TO_STEP_UP;
+                self.raised = false;
+            }
+            self.frames = 0;
+            self.slow = 0;
+        }
+        if self.fast >= self.fast_to_step_up {
+            self.fast = 0;
+            let raise = self.level.raise()?;
+            return self.change(raise);
+        }
+        None
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(QualityLevel::Full as u8);
+
+/// Record the level in effect
+pub fn publish(level: QualityLevel) {
+    CURRENT.store(level as u8, Ordering::Relaxed);
+}
+
+/// The level in effect
+pub fn current() -> QualityLevel {
+    QualityLevel::from_u8(CURRENT.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(16);
+    const SLOW: Duration = Duration::from_millis(30);
+    const FAST: Duration = Duration::from_millis(4);
+
+    fn run(controller: &mut QualityController, frames: u32, time: Duration) -> Vec<QualityLevel> {
+        (0..frames).filter_map(|_| controller.frame_rendered(time, BUDGET)).collect()
+    }
+
+    #[test]
+    fn test_quality_steps_down_and_back_up() {
+        let mut controller = QualityController::default();
+        // An occasional slow frame is fine
+        for _ in 0..10 {
+            assert!(run(&mut controller, 55, FAST).is_empty());
+            assert!(run(&mut controller, 5, SLOW).is_empty());
+        }
+
+        use QualityLevel::*;
+        assert_eq!(run(&mut controller, 15, SLOW), vec![NoParticles]);
+        assert_eq!(run(&mut controller, 60, SLOW), vec![NoTransitions, ReducedVideo, NoBlur]);
+        assert_eq!(run(&mut controller, 100, SLOW), vec![]);
+        assert_eq!(controller.level(), NoBlur);
+
+        assert_eq!(run(&mut controller, 299, FAST), vec![]);
+        assert_eq!(run(&mut controller, 1, FAST), vec![ReducedVideo]);
+        // Slow again: back down, and the next step up waits twice as long
+        assert_eq!(run(&mut controller, 15, SLOW), vec![NoBlur]);
+        assert_eq!(run(&mut controller, 300, FAST), vec![]);
+        assert_eq!(run(&mut controller, 300, FAST), vec![ReducedVideo]);
+        // This time it holds, and the wait is back to normal
+        assert_eq!(run(&mut controller, 300, FAST), vec![NoTransitions]);
+        let levels = QualityLevel::ALL.iter().map(QualityLevel::as_str).collect::<Vec<_>>();
+        assert_eq!(levels, ["full", "no-particles", "no-transitions", "reduced-video", "no-blur"]);
+    }
+
+    #[test]
+    fn test_quality_pinned() {
+        let mut controller = QualityController::default();
+        assert_eq!(controller.set_pinned(QualityLevel::from_str("no-transitions")), Some(QualityLevel::NoTransitions));
+        assert!(run(&mut controller, 1000, SLOW).is_empty());
+        assert!(run(&mut controller, 1000, FAST).is_empty());
+        assert_eq!(controller.set_pinned(None), Some(QualityLevel::Full));
+        assert_eq!(controller.set_pinned(None), None);
+        assert_eq!(QualityLevel::from_str("auto"), None);
+
+        let mut effects = EffectsConfig::default();
+        effects.cursor_particles.enabled = true;
+        effects.backdrop_blur.enabled = true;
+        QualityLevel::NoTransitions.apply(&mut effects);
+        assert!(!effects.cursor_particles.enabled && effects.backdrop_blur.enabled);
+        QualityLevel::NoBlur.apply(&mut effects);
+        assert!(!effects.backdrop_blur.enabled);
+    }
+}
@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use crate::core::types::{Color, Rect, Transform, Point};
 use crate::core::glyph::{GlyphRow, GlyphString};
 use crate::core::face::Face;
+use crate::core::animation::FloatingProperty;
 
 /// Scene graph node types
 #[derive(Debug, Clone)]
@@ -285,6 +286,36 @@ pub struct FloatingVideo {
     pub height: f32,
 }
 
+/// Looping video drawn beneath the text of a window, or of the whole frame
+#[derive(Debug, Clone)]
+pub struct BackgroundVideo {
+    /// Window the video fills, or `None` for the frame
+    pub window_id: Option<i64>,
+    pub video_id: u32,
+    /// How much the video is darkened (0.0 = as is, 1.0 = black)
+    pub dim: f32,
+}
+
+impl BackgroundVideo {
+    /// Area the video covers: the window's text area without its
+    /// mode-line, or the whole frame.  `None` when the window is not shown.
+    pub fn rect(&self, windows: &[crate::core::frame_glyphs::WindowInfo], frame: Rect) -> Option<Rect> {
+        let Some(window_id) = self.window_id else {
+            return Some(frame);
+        };
+        windows.iter().find(|w| w.window_id == window_id).map(|w| {
+            let b = w.bounds;
+            Rect::new(b.x, b.y, b.width, (b.height - w.mode_line_height).max(0.0))
+        })
+    }
+
+    /// Vertex color that darkens the video by `dim`
+    pub fn tint(&self) -> [f32; 4] {
+        let v = 1.0 - self.dim.clamp(0.0, 1.0);
+        [v, v, v, 1.0]
+    }
+}
+
 /// Floating image layer for rendering image at a specific screen position
 #[derive(Debug, Clone)]
 pub struct FloatingImage {
@@ -293,6 +324,10 @@ pub struct FloatingImage {
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// Opacity (0.0-1.0)
+    pub opacity: f32,
+    /// Corner radius in pixels (0 = square)
+    pub corner_radius: f32,
 }
 
 /// Floating WebKit view for rendering web content at a specific screen position
@@ -303,8 +338,33 @@ pub struct FloatingWebKit {
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// Opacity (0.0-1.0)
+    pub opacity: f32,
+    /// Corner radius in pixels (0 = square)
+    pub corner_radius: f32,
 }
 
+/// Mutable access to an animatable property of a floating layer
+macro_rules! floating_property_mut {
+    ($ty:ty) => {
+        impl $ty {
+            pub fn property_mut(&mut self, property: FloatingProperty) -> &mut f32 {
+                match property {
+                    FloatingProperty::X => &mut self.x,
+                    FloatingProperty::Y => &mut self.y,
+                    FloatingProperty::Width => &mut self.width,
+                    FloatingProperty::Height => &mut self.height,
+                    FloatingProperty::Opacity => &mut self.opacity,
+                    FloatingProperty::CornerRadius => &mut self.corner_radius,
+                }
+            }
+        }
+    };
+}
+
+floating_property_mut!(FloatingImage);
+floating_property_mut!(FloatingWebKit);
+
 impl Scene {
     /// Create a new empty scene
     pub fn new(width: f32, height: f32) -> Self {
@@ -385,7 +445,11 @@ impl Scene {
 
     /// Add a floating image at screen position
     pub fn add_floating_image(&mut self, image_id: u32, x: f32, y: f32, width: f32, height: f32) {
-        self.floating_images.push(FloatingImage { image_id, x, y, width, height });
+        self.floating_images.push(FloatingImage {
+            image_id, x, y, width, height,
+            opacity: 1.0,
+            corner_radius: 0.0,
+        });
         self.mark_dirty();
     }
 
@@ -403,7 +467,11 @@ impl Scene {
 
     /// Add a floating WebKit view at screen position
     pub fn add_floating_webkit(&mut self, webkit_id: u32, x: f32, y: f32, width: f32, height: f32) {
-        self.floating_webkits.push(FloatingWebKit { webkit_id, x, y, width, height });
+        self.floating_webkits.push(FloatingWebKit {
+            webkit_id, x, y, width, height,
+            opacity: 1.0,
+            corner_radius: 0.0,
+        });
         self.mark_dirty();
     }
 
@@ -512,4 +580,20 @@ mod tests {
         assert_eq!(dirty.right(), 150.0);
         assert_eq!(dirty.bottom(), 150.0);
     }
+
+    #[test]
+    fn test_background_video_rect() {
+        let mut glyphs = crate::core::frame_glyphs::FrameGlyphBuffer::new();
+        glyphs.add_window_info(7, 1, 1, 100, 100, 0.0, 20.0, 400.0, 300.0, 18.0, true, false,
+                               16.0, String::new(), false);
+        let frame = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let window = BackgroundVideo { window_id: Some(7), video_id: 1, dim: 0.25 };
+        let r = window.rect(&glyphs.window_infos, frame).unwrap();
+        assert_eq!((r.y, r.height), (20.0, 282.0));
+        assert_eq!(window.tint(), [0.75, 0.75, 0.75, 1.0]);
+        let gone = BackgroundVideo { window_id: Some(8), ..window.clone() };
+        assert!(gone.rect(&glyphs.window_infos, frame).is_none());
+        let whole = BackgroundVideo { window_id: None, ..window };
+        assert_eq!(whole.rect(&glyphs.window_infos, frame).unwrap().width, 800.0);
+    }
 }
@@ -0,0 +1,280 @@
+//! Structure of the current frame, for debugging.
+//!
+//! When something is drawn wrong or slowly, the first question is what
+//! the display engine is holding: which windows it was sent, how many
+//! glyphs of each kind, which floating layers sit on top of each other,
+//! what is still animating and how big the caches have grown.  The
+//! render thread collects this into a `SceneDump` on request and hands
+//! it back as JSON, to be read in Lisp or pasted into a bug report.
+//!
+//! The debug overlay shows the same frame on screen: the background
+//! rects it draws, shaded so pixels painted more than once stand out, or
+//! the text rows it had to rebuild.
+
+use std::fmt::Write;
+
+use super::frame_glyphs::FrameGlyph;
+use super::types::Rect;
+
+/// What the debug overlay draws over the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugOverlay {
+    #[default]
+    None,
+    /// Background rects, translucent, darker where they overlap
+    Overdraw,
+    /// Text rows rebuilt on the last frame rather than reused
+    Damage,
+}
+
+impl DebugOverlay {
+    pub const ALL: [DebugOverlay; 3] = [Self::None, Self::Overdraw, Self::Damage];
+
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "overdraw" => Self::Overdraw,
+            "damage" => Self::Damage,
+            _ => Self::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Overdraw => "overdraw",
+            Self::Damage => "damage",
+        }
+    }
+}
+
+/// A window of the frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowDump {
+    pub id: i64,
+    pub bounds: Rect,
+    pub selected: bool,
+    pub minibuffer: bool,
+    pub buffer_file_name: String,
+}
+
+/// A layer drawn over the windows
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatingDump {
+    pub kind: &'static str,
+    /// Image, view or video id, for layers that have one
+    pub id: Option<u32>,
+    pub rect: Option<Rect>,
+    pub opacity: Option<f32>,
+}
+
+/// Structure of the current frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneDump {
+    /// Size in physical pixels, and physical pixels per logical pixel
+    pub width: u32,
+    pub height: u32,
+    pub scale: f32,
+    /// Frames rendered since the display started
+    pub frames: u64,
+    pub windows: Vec<WindowDump>,
+    /// Glyphs of each kind
+    pub glyphs: Vec<(&'static str, usize)>,
+    /// Floating layers, bottom to top
+    pub floating: Vec<FloatingDump>,
+    /// Running animations of each kind
+    pub animations: Vec<(&'static str, usize)>,
+    /// Entries or bytes held by each cache
+    pub caches: Vec<(&'static str, usize)>,
+}
+
+/// Kind of `glyph`, as the dump names it
+pub fn glyph_kind(glyph: &FrameGlyph) -> &'static str {
+    match glyph {
+        FrameGlyph::Char { .. } => "char",
+        FrameGlyph::Stretch { .. } => "stretch",
+        FrameGlyph::Image { .. } => "image",
+        FrameGlyph::Video { .. } => "video",
+        FrameGlyph::WebKit { .. } => "webkit",
+        FrameGlyph::Cursor { .. } => "cursor",
+        FrameGlyph::Background { .. } => "background",
+        FrameGlyph::Border { .. } => "border",
+        FrameGlyph::ScrollBar { .. } => "scroll-bar",
+        #[cfg(feature = "neo-term")]
+        FrameGlyph::Terminal { .. } => "terminal",
+    }
+}
+
+/// Number of glyphs of each kind in `glyphs`, in order of first
+/// appearance
+pub fn glyph_counts(glyphs: &[FrameGlyph]) -> Vec<(&'static str, usize)> {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for kind in glyphs.iter().map(glyph_kind) {
+        match counts.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((kind, 1)),
+        }
+    }
+    counts
+}
+
+/// `s` as a JSON string
+fn json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// `v` as a JSON number; JSON has no NaN or infinities
+fn json_number(out: &mut String, v: f32) {
+    if v.is_finite() {
+        let _ = write!(out, "{}", v);
+    } else {
+        out.push_str("null");
+    }
+}
+
+fn json_rect(out: &mut String, r: &Rect) {
+    out.push('[');
+    for (i, v) in [r.x, r.y, r.width, r.height].into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json_number(out, v);
+    }
+    out.push(']');
+}
+
+/// `counts` as a JSON object
+fn json_counts(out: &mut String, counts: &[(&str, usize)]) {
+    out.push('{');
+    for (i, (name, n)) in counts.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json_string(out, name);
+        let _ = write!(out, ":{}", n);
+    }
+    out.push('}');
+}
+
+impl SceneDump {
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(out, "{{\"width\":{},\"height\":{},\"scale\":", self.width, self.height);
+        json_number(&mut out, self.scale);
+        let _ = write!(out, ",\"frames\":{},\"windows\":[", self.frames);
+        for (i, w) in self.windows.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{{\"id\":{},\"bounds\":", w.id);
+            json_rect(&mut out, &w.bounds);
+            let _ = write!(out, ",\"selected\":{},\"minibuffer\":{},\"file\":", w.selected, w.minibuffer);
+            json_string(&mut out, &w.buffer_file_name);
+            out.push('}');
+        }
+        out.push_str("],\"glyphs\":");
+        json_counts(&mut out, &self.glyphs);
+        out.push_str(",\"floating\":[");
+        for (z, f) in self.floating.iter().enumerate() {
+            if z > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"kind\":");
+            json_string(&mut out, f.kind);
+            let _ = write!(out, ",\"z\":{}", z);
+            if let Some(id) = f.id {
+                let _ = write!(out, ",\"id\":{}", id);
+            }
+            if let Some(ref rect) = f.rect {
+                out.push_str(",\"rect\":");
+                json_rect(&mut out, rect);
+            }
+            if let Some(opacity) = f.opacity {
+                out.push_str(",\"opacity\":");
+                json_number(&mut out, opacity);
+            }
+            out.push('}');
+        }
+        out.push_str("],\"animations\":");
+        json_counts(&mut out, &self.animations);
+        out.push_str(",\"caches\":");
+        json_counts(&mut out, &self.caches);
+        out.push('}');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::Color;
+
+    #[test]
+    fn test_scene_dump_glyph_counts() {
+        let stretch = FrameGlyph::Stretch {
+            x: 0.0,
+            y: 0.0,
+            width: 8.0,
+            height: 16.0,
+            bg: Color::rgb(0.0, 0.0, 0.0),
+            face_id: 0,
+            is_overlay: false,
+        };
+        let background = FrameGlyph::Background {
+            bounds: Rect::new(0.0, 0.0, 80.0, 16.0),
+            color: Color::rgb(1.0, 1.0, 1.0),
+        };
+        let glyphs = vec![background, stretch.clone(), stretch];
+        assert_eq!(glyph_counts(&glyphs), vec![("background", 1), ("stretch", 2)]);
+        assert!(glyph_counts(&[]).is_empty());
+
+        for overlay in DebugOverlay::ALL {
+            assert_eq!(DebugOverlay::from_str(overlay.as_str()), overlay);
+        }
+    }
+
+    #[test]
+    fn test_scene_dump_json() {
+        let dump = SceneDump {
+            width: 800,
+            height: 600,
+            scale: 2.0,
+            frames: 7,
+            windows: vec![WindowDump {
+                id: 1,
+                bounds: Rect::new(0.0, 0.0, 400.0, 300.0),
+                selected: true,
+                minibuffer: false,
+                buffer_file_name: "/tmp/\"a\"\n.txt".into(),
+            }],
+            glyphs: vec![("char", 12)],
+            floating: vec![
+                FloatingDump { kind: "image", id: Some(3), rect: Some(Rect::new(1.0, 2.0, 3.0, 4.5)), opacity: Some(f32::NAN) },
+                FloatingDump { kind: "tooltip", id: None, rect: None, opacity: None },
+            ],
+            animations: vec![],
+            caches: vec![("glyph-atlas", 40)],
+        };
+        assert_eq!(
+            dump.to_json(),
+            concat!(
+                r#"{"width":800,"height":600,"scale":2,"frames":7,"#,
+                r#""windows":[{"id":1,"bounds":[0,0,400,300],"selected":true,"minibuffer":false,"file":"/tmp/\"a\"\n.txt"}],"#,
+                r#""glyphs":{"char":12},"#,
+                r#""floating":[{"kind":"image","z":0,"id":3,"rect":[1,2,3,4.5],"opacity":null},{"kind":"tooltip","z":1}],"#,
+                r#""animations":{},"caches":{"glyph-atlas":40}}"#,
+            )
+        );
+    }
+}
@@ -18,6 +18,8 @@
 //!    CRTScanlines, DepthOfField)
 //! - **Creative effects**: Special rendering techniques
 //!   (TypewriterReveal)
+//! - **Horizontal transitions**: Sideways moves for buffer switches
+//!   (SlideLeft, SlideRight, Push)
 //!
 //! Each effect is selected via [`ScrollEffect`] enum. Physics-based timing
 //! is controlled separately via [`ScrollEasing`].
@@ -102,11 +104,22 @@ pub enum ScrollEffect {
 
     /// New lines appear character-by-character left-to-right.
     TypewriterReveal,
+
+    // ── Horizontal transitions (vertex x offsets) ───────────────────────
+
+    /// Old content slides out to the left, new content comes from the right.
+    SlideLeft,
+
+    /// Old content slides out to the right, new content comes from the left.
+    SlideRight,
+
+    /// New content comes from the right and covers the old, which stays.
+    Push,
 }
 
 impl ScrollEffect {
     /// Number of defined scroll effects.
-    pub const COUNT: usize = 21;
+    pub const COUNT: usize = 24;
 
     /// All effects in definition order.
     pub const ALL: [ScrollEffect; Self::COUNT] = [
@@ -131,6 +144,9 @@ impl ScrollEffect {
         Self::CRTScanlines,
         Self::DepthOfField,
         Self::TypewriterReveal,
+        Self::SlideLeft,
+        Self::SlideRight,
+        Self::Push,
     ];
 
     /// Parse from string (for Lisp integration).
@@ -161,6 +177,9 @@ impl ScrollEffect {
             "crt-scanlines" | "crtscanlines" | "crt" | "scanlines" => Self::CRTScanlines,
             "depth-of-field" | "depthoffield" | "dof" => Self::DepthOfField,
             "typewriter-reveal" | "typewriterreveal" | "typewriter" => Self::TypewriterReveal,
+            "slide-left" | "slideleft" => Self::SlideLeft,
+            "slide-right" | "slideright" => Self::SlideRight,
+            "push" | "stack" => Self::Push,
             _ => Self::Slide,
         }
     }
@@ -189,6 +208,9 @@ impl ScrollEffect {
             Self::CRTScanlines => "crt-scanlines",
             Self::DepthOfField => "depth-of-field",
             Self::TypewriterReveal => "typewriter-reveal",
+            Self::SlideLeft => "slide-left",
+            Self::SlideRight => "slide-right",
+            Self::Push => "push",
         }
     }
 
@@ -257,6 +279,15 @@ pub enum ScrollEasing {
 }
 
 impl ScrollEasing {
+    /// All easings in definition order.
+    pub const ALL: [ScrollEasing; 5] = [
+        Self::EaseOutQuad,
+        Self::EaseOutCubic,
+        Self::Spring,
+        Self::Linear,
+        Self::EaseInOutCubic,
+    ];
+
     /// Apply easing to a normalized time parameter t ∈ [0, 1].
     ///
     /// For non-spring easings this is a simple function.
@@ -670,6 +701,66 @@ pub fn card_flip_transform(t: f32) -> (f32, f32) {
     (scale_y, alpha)
 }
 
+/// Scales of the old and new snapshots of a text scale change.
+///
+/// `ratio` is the new text size over the old.  The old snapshot grows or
+/// shrinks toward the new size while the new one starts at the old size,
+/// so text keeps a continuous size as one fades into the other.
+pub fn text_scale_factors(t: f32, ratio: f32) -> (f32, f32) {
+    let t = t.clamp(0.0, 1.0);
+    let ratio = ratio.max(0.01);
+    let old = 1.0 + (ratio - 1.0) * t;
+    (old, old / ratio)
+}
+
+/// Vertical offsets of the old and new snapshots of a scroll that moves
+/// the text `distance` pixels up (negative: down), `eased_t` of the way.
+///
+/// The new snapshot is drawn over the old one; where it has not arrived
+/// yet the old one shows the text that is still to come into view.
+pub fn scroll_shift_offsets(eased_t: f32, distance: f32) -> (f32, f32) {
+    (-distance * eased_t, distance * (1.0 - eased_t))
+}
+
+/// Horizontal offsets of the old and new snapshots of a sideways
+/// transition `eased_t` of the way across a window `width` wide.
+///
+/// `direction` is -1 for a move to the left and 1 for one to the right.
+/// A push leaves the old snapshot in place under the arriving new one.
+pub fn slide_x_offsets(eased_t: f32, width: f32, direction: f32, push: bool) -> (f32, f32) {
+    let old = if push { 0.0 } else { direction * width * eased_t };
+    (old, -direction * width * (1.0 - eased_t))
+}
+
+/// Longest motion blur streak in logical pixels
+pub const MAX_MOTION_BLUR: f32 = 48.0;
+
+/// Length of the motion blur streak of a scroll at `t`.
+///
+/// The content moves `distance` pixels over `duration_secs` following
+/// `easing`; the streak is how far it travels during one 60 Hz frame,
+/// scaled by `strength`.  Streaks under a pixel are dropped, so only fast
+/// scrolling blurs.
+pub fn motion_blur_length(
+    distance: f32,
+    duration_secs: f32,
+    t: f32,
+    easing: ScrollEasing,
+    strength: f32,
+) -> f32 {
+    if duration_secs <= 0.0 || strength <= 0.0 {
+        return 0.0;
+    }
+    let h = 0.01;
+    let (a, b) = ((t - h).max(0.0), (t + h).min(1.0));
+    if b <= a {
+        return 0.0;
+    }
+    let velocity = distance * (easing.apply(b) - easing.apply(a)) / (b - a) / duration_secs;
+    let length = (velocity.abs() / 60.0 * strength).min(MAX_MOTION_BLUR);
+    if length < 1.0 { 0.0 } else { length }
+}
+
 // ─── Post-processing parameter computation ──────────────────────────────
 
 /// Parameters for post-processing shader effects.
@@ -741,6 +832,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_text_scale_factors() {
+        // Old snapshot at its size, new one drawn at the old text size
+        assert_eq!(text_scale_factors(0.0, 1.25), (1.0, 0.8));
+        // Both end at the new text size
+        assert_eq!(text_scale_factors(1.0, 1.25), (1.25, 1.0));
+        let (old, new) = text_scale_factors(0.5, 2.0);
+        assert_eq!(old, 1.5);
+        assert_eq!(new, 0.75);
+    }
+
+    #[test]
+    fn test_scroll_shift_offsets() {
+        // Two 20px lines up: the new text starts two lines low
+        assert_eq!(scroll_shift_offsets(0.0, 40.0), (0.0, 40.0));
+        assert_eq!(scroll_shift_offsets(0.5, 40.0), (-20.0, 20.0));
+        assert_eq!(scroll_shift_offsets(1.0, -40.0), (40.0, 0.0));
+    }
+
+    #[test]
+    fn test_slide_x_offsets() {
+        // Sliding left: the new text comes in from the right edge
+        assert_eq!(slide_x_offsets(0.0, 400.0, -1.0, false), (0.0, 400.0));
+        assert_eq!(slide_x_offsets(0.5, 400.0, -1.0, false), (-200.0, 200.0));
+        assert_eq!(slide_x_offsets(1.0, 400.0, 1.0, false), (400.0, 0.0));
+        // A push leaves the old text where it is
+        assert_eq!(slide_x_offsets(0.5, 400.0, -1.0, true), (0.0, 200.0));
+    }
+
+    #[test]
+    fn test_motion_blur_length() {
+        let easing = ScrollEasing::EaseOutQuad;
+        // A page in 150ms moves fast at first and settles at the end
+        let start = motion_blur_length(600.0, 0.15, 0.0, easing, 1.0);
+        assert!(start > 40.0 && start <= MAX_MOTION_BLUR);
+        assert_eq!(motion_blur_length(600.0, 0.15, 1.0, easing, 1.0), 0.0);
+        // A slow scroll does not blur at all
+        assert_eq!(motion_blur_length(20.0, 0.5, 0.5, easing, 1.0), 0.0);
+        assert_eq!(motion_blur_length(600.0, 0.15, 0.0, easing, 0.0), 0.0);
+    }
+
     #[test]
     fn test_scroll_effect_roundtrip() {
         for effect in ScrollEffect::ALL.iter() {
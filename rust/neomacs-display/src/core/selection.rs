@@ -0,0 +1,122 @@
+//! Merged selection highlight runs.
+//!
+//! A region highlight arrives as one background per glyph cell, which
+//! renders as stripes at fractional positions and cannot have rounded
+//! ends.  This module merges adjacent highlighted cells on each visual
+//! line into a single run and decides which corners of each run may be
+//! rounded: corners that touch the run on the line above or below are
+//! kept square so wrapped lines join into one shape.
+
+use super::types::Rect;
+
+/// Tolerance for treating cell edges as touching, in logical pixels
+const EPSILON: f32 = 0.5;
+
+/// One merged highlight rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionRun {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Corners that may be rounded: top-left, top-right, bottom-right, bottom-left
+    pub rounded: [bool; 4],
+}
+
+impl SelectionRun {
+    fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Whether `other` covers the horizontal position `edge` from a line
+    /// directly adjacent to this one.
+    fn joins_at(&self, other: &SelectionRun, edge: f32) -> bool {
+        other.x <= edge + EPSILON && other.right() >= edge - EPSILON
+    }
+}
+
+/// Merge highlighted cells into per-line runs with corner rounding flags.
+pub fn merge_selection_runs(cells: &[Rect]) -> Vec<SelectionRun> {
+    let mut sorted: Vec<&Rect> = cells.iter().filter(|c| c.width > 0.0 && c.height > 0.0).collect();
+    sorted.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let mut runs: Vec<SelectionRun> = Vec::new();
+    for cell in sorted {
+        if let Some(run) = runs.last_mut() {
+            let same_row = (run.y - cell.y).abs() < EPSILON;
+            if same_row && cell.x <= run.right() + EPSILON {
+                let right = run.right().max(cell.x + cell.width);
+                run.width = right - run.x;
+                run.height = run.height.max(cell.height);
+                continue;
+            }
+        }
+        runs.push(SelectionRun {
+            x: cell.x,
+            y: cell.y,
+            width: cell.width,
+            height: cell.height,
+            rounded: [true; 4],
+        });
+    }
+
+    let snapshot = runs.clone();
+    for run in &mut runs {
+        let above: Vec<&SelectionRun> = snapshot
+            .iter()
+            .filter(|o| (o.bottom() - run.y).abs() < EPSILON)
+            .collect();
+        let below: Vec<&SelectionRun> = snapshot
+            .iter()
+            .filter(|o| (o.y - run.bottom()).abs() < EPSILON)
+            .collect();
+        let (left, right) = (run.x + EPSILON, run.right() - EPSILON);
+        run.rounded = [
+            !above.iter().any(|o| run.joins_at(o, left)),
+            !above.iter().any(|o| run.joins_at(o, right)),
+            !below.iter().any(|o| run.joins_at(o, right)),
+            !below.iter().any(|o| run.joins_at(o, left)),
+        ];
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(y: f32, from: usize, to: usize) -> Vec<Rect> {
+        (from..to).map(|c| Rect::new(c as f32 * 8.0, y, 8.0, 16.0)).collect()
+    }
+
+    #[test]
+    fn test_merge_wrapped_selection() {
+        // Selection from column 4 on line 0 through column 6 on line 2
+        let mut cells = row(0.0, 4, 10);
+        cells.extend(row(16.0, 0, 10));
+        cells.extend(row(32.0, 0, 6));
+        let runs = merge_selection_runs(&cells);
+        assert_eq!(runs.len(), 3);
+
+        assert_eq!((runs[0].x, runs[0].width), (32.0, 48.0));
+        // First line: rounded on top, bottom joins the full middle line
+        assert_eq!(runs[0].rounded, [true, true, false, false]);
+        // Middle line: top-left sticks out past the first line's start
+        assert_eq!(runs[1].rounded, [true, false, true, false]);
+        // Last line: joined on top, rounded at the bottom
+        assert_eq!(runs[2].rounded, [false, false, true, true]);
+    }
+
+    #[test]
+    fn test_separate_runs_on_one_line() {
+        let mut cells = row(0.0, 0, 2);
+        cells.extend(row(0.0, 5, 7));
+        let runs = merge_selection_runs(&cells);
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(|r| r.rounded == [true; 4]));
+    }
+}
@@ -0,0 +1,376 @@
+//! Saving and restoring the display session.
+//!
+//! Some of what is on screen lives only in the display engine and is not
+//! rebuilt by redisplay: terminals and what they printed, images and
+//! videos loaded from files, WebKit views and the pages they show, the
+//! floating layers and display options set at runtime.  The render thread
+//! records what it is asked to create and writes it out on request, with
+//! current sizes and positions, as a TOML file:
+//!
+//! ```toml
+//! [options]
+//! cursor-animation = "t"
+//!
+//! [[terminal]]
+//! id = 3
+//! cols = 80
+//! rows = 24
+//! mode = 2
+//! float = [40.0, 60.0, 0.9]
+//! scrollback = "$ make\n..."
+//!
+//! [[webkit]]
+//! id = 1
+//! width = 800
+//! height = 600
+//! url = "https://example.org/"
+//! floating = [100.0, 100.0, 800.0, 600.0]
+//! ```
+//!
+//! After a crash or restart the file is read back and every resource
+//! recreated under a fresh id; the caller gets the old ids mapped to the
+//! new ones to rebind its buffers.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::error::{DisplayError, DisplayResult};
+
+/// Lines of terminal output, scrollback included, kept in a session
+pub const SCROLLBACK_LINES: usize = 5000;
+
+/// Position and size of a floating layer, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatingRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TerminalSession {
+    pub cols: u16,
+    pub rows: u16,
+    /// `TerminalMode` number: 0 window, 1 inline, 2 floating
+    pub mode: u8,
+    pub shell: Option<String>,
+    /// Position and opacity of a floating terminal
+    pub float: Option<(f32, f32, f32)>,
+    /// Text of the last `SCROLLBACK_LINES` lines
+    pub scrollback: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageSession {
+    pub path: String,
+    pub max_width: u32,
+    pub max_height: u32,
+    pub floating: Option<FloatingRect>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoSession {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebKitSession {
+    pub width: u32,
+    pub height: u32,
+    pub profile: Option<String>,
+    pub url: Option<String>,
+    pub floating: Option<FloatingRect>,
+}
+
+/// Resources of a display session, by the id they had in it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Session {
+    pub terminals: BTreeMap<u32, TerminalSession>,
+    pub images: BTreeMap<u32, ImageSession>,
+    pub videos: BTreeMap<u32, VideoSession>,
+    pub webkits: BTreeMap<u32, WebKitSession>,
+    /// Display options set at runtime, by name
+    pub options: BTreeMap<String, String>,
+}
+
+impl Session {
+    /// Where sessions are kept unless told otherwise:
+    /// `$XDG_STATE_HOME/neomacs/display-session.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_STATE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("state"),
+        };
+        Some(base.join("neomacs").join("display-session.toml"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terminals.is_empty()
+            && self.images.is_empty()
+            && self.videos.is_empty()
+            && self.webkits.is_empty()
+            && self.options.is_empty()
+    }
+
+    /// Write the session to `path`, replacing the previous one only once
+    /// the new one is complete
+    pub fn save(&self, path: &Path) -> DisplayResult<()> {
+        let err = |e: std::io::Error| DisplayError::Session(format!("{}: {}", path.display(), e));
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(err)?;
+        }
+        let tmp = path.with_extension("toml.tmp");
+        std::fs::write(&tmp, self.to_toml()).map_err(err)?;
+        std::fs::rename(&tmp, path).map_err(err)
+    }
+
+    pub fn load(path: &Path) -> DisplayResult<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| DisplayError::Session(format!("{}: {}", path.display(), e)))?;
+        Self::parse(&text)
+    }
+
+    pub fn to_toml(&self) -> String {
+        use toml::Value;
+
+        let mut root = toml::Table::new();
+        if !self.options.is_empty() {
+            let options = self.options.iter().map(|(k, v)| (k.clone(), Value::from(v.as_str()))).collect();
+            root.insert("options".into(), Value::Table(options));
+        }
+        let mut section = |name: &str, entries: Vec<toml::Table>| {
+            if !entries.is_empty() {
+                root.insert(name.into(), Value::Array(entries.into_iter().map(Value::Table).collect()));
+            }
+        };
+
+        section("terminal", self.terminals.iter().map(|(&id, t)| {
+            let mut e = entry(id);
+            e.insert("cols".into(), Value::from(t.cols as i64));
+            e.insert("rows".into(), Value::from(t.rows as i64));
+            e.insert("mode".into(), Value::from(t.mode as i64));
+            if let Some(ref shell) = t.shell {
+                e.insert("shell".into(), Value::from(shell.as_str()));
+            }
+            if let Some((x, y, opacity)) = t.float {
+                e.insert("float".into(), floats(&[x, y, opacity]));
+            }
+            e.insert("scrollback".into(), Value::from(t.scrollback.as_str()));
+            e
+        }).collect());
+
+        section("image", self.images.iter().map(|(&id, i)| {
+            let mut e = entry(id);
+            e.insert("path".into(), Value::from(i.path.as_str()));
+            e.insert("max-width".into(), Value::from(i.max_width as i64));
+            e.insert("max-height".into(), Value::from(i.max_height as i64));
+            if let Some(r) = i.floating {
+                e.insert("floating".into(), floats(&[r.x, r.y, r.width, r.height]));
+            }
+            e
+        }).collect());
+
+        section("video", self.videos.iter().map(|(&id, v)| {
+            let mut e = entry(id);
+            e.insert("path".into(), Value::from(v.path.as_str()));
+            e
+        }).collect());
+
+        section("webkit", self.webkits.iter().map(|(&id, w)| {
+            let mut e = entry(id);
+            e.insert("width".into(), Value::from(w.width as i64));
+            e.insert("height".into(), Value::from(w.height as i64));
+            if let Some(ref profile) = w.profile {
+                e.insert("profile".into(), Value::from(profile.as_str()));
+            }
+            if let Some(ref url) = w.url {
+                e.insert("url".into(), Value::from(url.as_str()));
+            }
+            if let Some(r) = w.floating {
+                e.insert("floating".into(), floats(&[r.x, r.y, r.width, r.height]));
+            }
+            e
+        }).collect());
+
+        root.to_string()
+    }
+
+    /// Parse a session file.  Entries missing an id or a required field
+    /// are skipped with a warning rather than failing the whole session.
+    pub fn parse(text: &str) -> DisplayResult<Self> {
+        let root: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| DisplayError::Session(e.to_string()))?;
+        let mut session = Self::default();
+
+        if let Some(options) = root.get("options").and_then(|v| v.as_table()) {
+            for (name, value) in options {
+                match value.as_str() {
+                    Some(value) => { session.options.insert(name.clone(), value.to_string()); }
+                    None => log::warn!("session: option {} is not a string", name),
+                }
+            }
+        }
+
+        for (id, e) in entries(&root, "terminal") {
+            let (Some(cols), Some(rows)) = (int(e, "cols"), int(e, "rows")) else {
+                log::warn!("session: terminal {} has no size", id);
+                continue;
+            };
+            let float = match float_list(e, "float").as_deref() {
+                Some(&[x, y, opacity]) => Some((x, y, opacity)),
+                _ => None,
+            };
+            session.terminals.insert(id, TerminalSession {
+                cols: cols as u16,
+                rows: rows as u16,
+                mode: int(e, "mode").unwrap_or(0) as u8,
+                shell: string(e, "shell"),
+                float,
+                scrollback: string(e, "scrollback").unwrap_or_default(),
+            });
+        }
+
+        for (id, e) in entries(&root, "image") {
+            let Some(path) = string(e, "path") else {
+                log::warn!("session: image {} has no path", id);
+                continue;
+            };
+            session.images.insert(id, ImageSession {
+                path,
+                max_width: int(e, "max-width").unwrap_or(0),
+                max_height: int(e, "max-height").unwrap_or(0),
+                floating: rect(e, "floating"),
+            });
+        }
+
+        for (id, e) in entries(&root, "video") {
+            match string(e, "path") {
+                Some(path) => { session.videos.insert(id, VideoSession { path }); }
+                None => log::warn!("session: video {} has no path", id),
+            }
+        }
+
+        for (id, e) in entries(&root, "webkit") {
+            let (Some(width), Some(height)) = (int(e, "width"), int(e, "height")) else {
+                log::warn!("session: webkit view {} has no size", id);
+                continue;
+            };
+            session.webkits.insert(id, WebKitSession {
+                width,
+                height,
+                profile: string(e, "profile"),
+                url: string(e, "url"),
+                floating: rect(e, "floating"),
+            });
+        }
+
+        Ok(session)
+    }
+}
+
+fn entry(id: u32) -> toml::Table {
+    let mut e = toml::Table::new();
+    e.insert("id".into(), toml::Value::from(id as i64));
+    e
+}
+
+fn floats(values: &[f32]) -> toml::Value {
+    toml::Value::Array(values.iter().map(|&v| toml::Value::from(v as f64)).collect())
+}
+
+/// Tables of the array `name`, with their ids
+fn entries<'a>(root: &'a toml::Table, name: &str) -> Vec<(u32, &'a toml::Table)> {
+    let Some(array) = root.get(name).and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    array
+        .iter()
+        .filter_map(|v| v.as_table())
+        .filter_map(|e| match int(e, "id") {
+            Some(id) if id > 0 => Some((id, e)),
+            _ => {
+                log::warn!("session: {} entry without an id", name);
+                None
+            }
+        })
+        .collect()
+}
+
+fn int(e: &toml::Table, key: &str) -> Option<u32> {
+    e.get(key)?.as_integer().and_then(|v| u32::try_from(v).ok())
+}
+
+fn string(e: &toml::Table, key: &str) -> Option<String> {
+    e.get(key)?.as_str().map(str::to_string)
+}
+
+fn float_list(e: &toml::Table, key: &str) -> Option<Vec<f32>> {
+    e.get(key)?
+        .as_array()?
+        .iter()
+        .map(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)).map(|f| f as f32))
+        .collect()
+}
+
+fn rect(e: &toml::Table, key: &str) -> Option<FloatingRect> {
+    match float_list(e, key).as_deref() {
+        Some(&[x, y, width, height]) => Some(FloatingRect { x, y, width, height }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let mut session = Session::default();
+        session.options.insert("cursor-animation".into(), "t".into());
+        session.terminals.insert(3, TerminalSession {
+            cols: 80,
+            rows: 24,
+            mode: 2,
+            shell: Some("/bin/zsh".into()),
+            float: Some((40.0, 60.0, 0.5)),
+            scrollback: "$ echo \"hi\"\nhi\n".into(),
+        });
+        session.images.insert(7, ImageSession {
+            path: "/tmp/a b.png".into(),
+            max_width: 640,
+            max_height: 0,
+            floating: Some(FloatingRect { x: 1.0, y: 2.0, width: 30.0, height: 40.0 }),
+        });
+        session.videos.insert(2, VideoSession { path: "/tmp/v.webm".into() });
+        session.webkits.insert(1, WebKitSession {
+            width: 800,
+            height: 600,
+            profile: None,
+            url: Some("https://example.org/".into()),
+            floating: None,
+        });
+        assert_eq!(Session::parse(&session.to_toml()).unwrap(), session);
+    }
+
+    #[test]
+    fn test_session_skips_broken_entries() {
+        let text = r#"
+            [[terminal]]
+            cols = 80
+            rows = 24
+
+            [[image]]
+            id = 4
+
+            [[video]]
+            id = 5
+            path = "/tmp/v.mp4"
+        "#;
+        let session = Session::parse(text).unwrap();
+        assert!(session.terminals.is_empty() && session.images.is_empty());
+        assert_eq!(session.videos[&5].path, "/tmp/v.mp4");
+        assert!(Session::parse("[[terminal]").is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Named fragment shaders applied to parts of the frame.
+//!
+//! A package registers a WGSL function by name -- a CRT look for a
+//! terminal window, a vignette around a floating image -- and applies it
+//! to a region, a window or a floating image.  The function is given the
+//! frame under its target and returns the color to show there:
+//!
+//! ```wgsl
+//! fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32>
+//! ```
+//!
+//! `uv` runs 0-1 across the target and `pos` is in logical pixels.  The
+//! prelude gives it `frame(uv)` to read the frame elsewhere in the target
+//! and the `effect` uniform: `time` in seconds, `region` as x, y, width
+//! and height, and four free `params`.  Sources are checked by naga
+//! before they reach the GPU, so a shader that does not compile is
+//! reported to its caller and never drawn instead of losing the device.
+
+use super::types::Rect;
+
+/// Where an applied effect draws
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShaderTarget {
+    /// A rectangle of the frame, in logical pixels
+    Region(Rect),
+    /// The bounds of a window
+    Window(i64),
+    /// A floating image, by image id
+    Floating(u32),
+}
+
+/// An effect drawn over a target
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedShaderEffect {
+    pub name: String,
+    pub target: ShaderTarget,
+    /// `effect.params` of the shader
+    pub params: [f32; 4],
+}
+
+/// Apply `effect`, replacing the effect of the same name on its target.
+/// Effects are drawn in the order they were first applied.
+pub fn apply(effects: &mut Vec<AppliedShaderEffect>, effect: AppliedShaderEffect) {
+    match effects.iter_mut().find(|e| e.name == effect.name && e.target == effect.target) {
+        Some(existing) => *existing = effect,
+        None => effects.push(effect),
+    }
+}
+
+/// Remove the effects named `name` from `target`; None matches any
+pub fn remove(effects: &mut Vec<AppliedShaderEffect>, name: Option<&str>, target: Option<ShaderTarget>) {
+    effects.retain(|e| {
+        !(name.is_none_or(|n| e.name == n) && target.is_none_or(|t| e.target == t))
+    });
+}
+
+/// Effects available without registering them.  A zero parameter
+/// stands for its default.
+pub const BUILTIN_EFFECTS: &[(&str, &str)] = &[
+    (
+        "vignette",
+        // params.x: darkness of the corners
+        r#"
+fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32> {
+    let amount = select(effect.params.x, 0.6, effect.params.x <= 0.0);
+    let d = distance(uv, vec2<f32>(0.5));
+    return vec4<f32>(color.rgb * (1.0 - smoothstep(0.3, 0.75, d) * amount), color.a);
+}
+"#,
+    ),
+    (
+        "crt",
+        // Curved glass, color fringes, scanlines and a slight flicker;
+        // params.x: darkness of the scanlines
+        r#"
+fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32> {
+    let c = uv - vec2<f32>(0.5);
+    let bent = vec2<f32>(0.5) + c * (1.0 + dot(c, c) * 0.15);
+    if any(bent < vec2<f32>(0.0)) || any(bent > vec2<f32>(1.0)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    let fringe = vec2<f32>(1.5 / effect.region.z, 0.0);
+    var rgb = vec3<f32>(frame(bent + fringe).r, frame(bent).g, frame(bent - fringe).b);
+    let dark = select(effect.params.x, 0.25, effect.params.x <= 0.0);
+    rgb *= 1.0 - dark * (0.5 + 0.5 * sin(pos.y * 3.14159));
+    rgb *= 0.97 + 0.03 * sin(effect.time * 6.0);
+    return vec4<f32>(rgb, color.a);
+}
+"#,
+    ),
+    (
+        "glow",
+        // Bloom around bright parts; params.x: radius in pixels,
+        // params.y: intensity
+        r#"
+fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32> {
+    let radius = select(effect.params.x, 4.0, effect.params.x <= 0.0);
+    let intensity = select(effect.params.y, 0.6, effect.params.y <= 0.0);
+    let step = radius / 2.0 / effect.region.zw;
+    var sum = vec3<f32>(0.0);
+    for (var i = -2; i <= 2; i++) {
+        for (var j = -2; j <= 2; j++) {
+            let s = frame(uv + vec2<f32>(f32(i), f32(j)) * step).rgb;
+            sum += max(s - vec3<f32>(0.6), vec3<f32>(0.0));
+        }
+    }
+    return vec4<f32>(color.rgb + sum / 10.0 * intensity, color.a);
+}
+"#,
+    ),
+];
+
+/// Source of the built-in effect `name`
+pub fn builtin(name: &str) -> Option<&'static str> {
+    BUILTIN_EFFECTS.iter().find(|(n, _)| *n == name).map(|(_, s)| *s)
+}
+
+const PRELUDE: &str = r#"
+struct Effect {
+    screen_size: vec2<f32>,
+    time: f32,
+    // How much of the effect shows, 0-1
+    strength: f32,
+    region: vec4<f32>,
+    params: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var t_frame: texture_2d<f32>;
+@group(0) @binding(1)
+var s_frame: sampler;
+@group(1) @binding(0)
+var<uniform> effect: Effect;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) pos: vec2<f32>,
+}
+
+// Frame color at uv across the region
+fn frame(uv: vec2<f32>) -> vec4<f32> {
+    let pos = effect.region.xy + uv * effect.region.zw;
+    return textureSampleLevel(t_frame, s_frame, pos / effect.screen_size, 0.0);
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(0.0, 1.0),
+    );
+    let uv = corners[index];
+    let pos = effect.region.xy + uv * effect.region.zw;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(
+        pos.x / effect.screen_size.x * 2.0 - 1.0,
+        1.0 - pos.y / effect.screen_size.y * 2.0,
+        0.0,
+        1.0,
+    );
+    out.uv = uv;
+    out.pos = pos;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let original = frame(in.uv);
+    let shaded = clamp(shade(original, in.uv, in.pos), vec4<f32>(0.0), vec4<f32>(1.0));
+    return mix(original, shaded, effect.strength);
+}
+"#;
+
+/// Complete shader module for the `shade` function in `source`
+pub fn wrap(source: &str) -> String {
+    format!("{}\n{}", PRELUDE, source)
+}
+
+/// Whether the effect in `source` changes over time, needing redraws
+pub fn is_animated(source: &str) -> bool {
+    source.contains("effect.time")
+}
+
+/// Check that `source` compiles, returning the compiler's message if not
+#[cfg(feature = "winit-backend")]
+pub fn validate(source: &str) -> Result<(), String> {
+    use wgpu::naga;
+    let wrapped = wrap(source);
+    let module = naga::front::wgsl::parse_str(&wrapped).map_err(|e| e.emit_to_string(&wrapped))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(&wrapped))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shader_effect_apply_and_remove() {
+        let region = ShaderTarget::Region(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let effect = |name: &str, target, x| AppliedShaderEffect { name: name.into(), target, params: [x, 0.0, 0.0, 0.0] };
+        let mut effects = Vec::new();
+        apply(&mut effects, effect("crt", ShaderTarget::Window(1), 0.0));
+        apply(&mut effects, effect("vignette", ShaderTarget::Window(1), 0.0));
+        apply(&mut effects, effect("crt", region, 0.0));
+        // Applying again updates the parameters in place
+        apply(&mut effects, effect("crt", ShaderTarget::Window(1), 0.5));
+        assert_eq!(effects.len(), 3);
+        assert_eq!(effects[0].params[0], 0.5);
+
+        remove(&mut effects, Some("crt"), None);
+        assert_eq!(effects, vec![effect("vignette", ShaderTarget::Window(1), 0.0)]);
+        apply(&mut effects, effect("glow", ShaderTarget::Floating(7), 0.0));
+        remove(&mut effects, None, Some(ShaderTarget::Window(1)));
+        assert_eq!(effects.len(), 1);
+        remove(&mut effects, None, None);
+        assert!(effects.is_empty());
+    }
+
+    #[cfg(feature = "winit-backend")]
+    #[test]
+    fn test_shader_effect_validation() {
+        for (name, source) in BUILTIN_EFFECTS {
+            assert_eq!(validate(source), Ok(()), "{}", name);
+        }
+        assert!(is_animated(builtin("crt").unwrap()));
+        assert!(!is_animated(builtin("vignette").unwrap()));
+        assert!(builtin("sepia").is_none());
+
+        let typo = "fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32> { return colour; }";
+        assert!(validate(typo).unwrap_err().contains("colour"));
+        let wrong_type = "fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32> { return uv; }";
+        assert!(validate(wrong_type).is_err());
+        assert!(validate("").is_err());
+    }
+}
@@ -5,6 +5,7 @@ use std::ops::{Add, Sub, Mul};
 /// RGBA color with f32 components (0.0 - 1.0)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -30,6 +31,30 @@ impl Color {
         }
     }
 
+    /// Parse `#rrggbb` or `#rrggbbaa`, keeping the sRGB components as
+    /// `from_u8` does
+    pub fn from_hex(s: &str) -> Option<Self> {
+        let hex = s.strip_prefix('#')?;
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return None;
+        }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let a = if hex.len() == 8 { byte(6)? } else { 255 };
+        Some(Self::from_u8(byte(0)?, byte(2)?, byte(4)?, a))
+    }
+
+    /// Format as `#rrggbb`, or `#rrggbbaa` when not opaque; the inverse of
+    /// `from_hex`
+    pub fn to_hex(&self) -> String {
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let (r, g, b, a) = (byte(self.r), byte(self.g), byte(self.b), byte(self.a));
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
     /// Convert from Emacs pixel value (0xAARRGGBB or 0x00RRGGBB).
     /// Performs sRGB→linear conversion since Emacs colors are sRGB
     /// and the GPU surface uses an sRGB format (expects linear values).
@@ -44,7 +69,7 @@ impl Color {
     }
 
     /// Convert a single sRGB component (0.0-1.0) to linear space.
-    fn srgb_component_to_linear(c: f32) -> f32 {
+    pub fn srgb_component_to_linear(c: f32) -> f32 {
         if c <= 0.04045 {
             c / 12.92
         } else {
@@ -52,6 +77,20 @@ impl Color {
         }
     }
 
+    /// Convert a single linear component (0.0-1.0) to sRGB space.
+    pub fn linear_component_to_srgb(c: f32) -> f32 {
+        if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    }
+
+    /// Relative luminance of a linear color (Rec. 709 weights)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
     /// Convert this color from sRGB to linear space.
     /// Use when colors come from Emacs (sRGB) and need to be used with
     /// an sRGB surface format where the GPU expects linear values.
@@ -128,6 +167,7 @@ impl Size {
 /// Rectangle with position and size
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -174,6 +214,16 @@ impl Rect {
             && self.bottom() > other.y
     }
 
+    /// The overlap of two rectangles, or None if they do not overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Some(Self::new(x, y, self.right().min(other.right()) - x, self.bottom().min(other.bottom()) - y))
+    }
+
     pub const ZERO: Self = Self::new(0.0, 0.0, 0.0, 0.0);
 }
 
@@ -269,6 +319,15 @@ pub fn ease_linear(t: f32) -> f32 {
     t
 }
 
+/// Round a logical coordinate to the nearest device pixel at `scale`, so
+/// text stays crisp at fractional scales such as 1.25 or 1.5.
+pub fn snap_to_device(v: f32, scale: f32) -> f32 {
+    if scale <= 0.0 {
+        return v;
+    }
+    (v * scale).round() / scale
+}
+
 /// 2D transform matrix
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -304,6 +363,39 @@ impl Default for Transform {
     }
 }
 
+/// How an image is filtered when drawn at another size than its texture
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageSampling {
+    /// Smooth when scaling down, show real pixels when scaling up
+    #[default]
+    Auto,
+    /// Always filter linearly
+    Smooth,
+    /// Always sample the nearest pixel
+    Nearest,
+}
+
+impl ImageSampling {
+    /// From the C encoding: 1 smooth, 0 nearest, anything else auto
+    pub fn from_i32(value: i32) -> Self {
+        match value {
+            1 => Self::Smooth,
+            0 => Self::Nearest,
+            _ => Self::Auto,
+        }
+    }
+
+    /// Whether a `tex_width` x `tex_height` texture drawn at `width` x
+    /// `height` is filtered linearly
+    pub fn smooth(self, tex_width: u32, tex_height: u32, width: f32, height: f32) -> bool {
+        match self {
+            Self::Smooth => true,
+            Self::Nearest => false,
+            Self::Auto => width < tex_width as f32 || height < tex_height as f32,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -321,10 +413,49 @@ mod tests {
         assert!((color.a - 1.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_color_from_hex() {
+        assert_eq!(Color::from_hex("#ff0080"), Some(Color::from_u8(255, 0, 128, 255)));
+        assert_eq!(Color::from_hex("#00000080"), Some(Color::from_u8(0, 0, 0, 128)));
+        assert_eq!(Color::from_hex("ff0080"), None);
+        assert_eq!(Color::from_hex("#ff00"), None);
+        assert_eq!(Color::from_hex("#gg0000"), None);
+        assert_eq!(Color::from_u8(255, 0, 128, 255).to_hex(), "#ff0080");
+        assert_eq!(Color::from_u8(0, 0, 0, 128).to_hex(), "#00000080");
+    }
+
+    #[test]
+    fn test_snap_to_device_fractional_scale() {
+        // 10.3 * 1.25 = 12.875 device pixels, snapped to 13
+        assert!((snap_to_device(10.3, 1.25) - 10.4).abs() < 1e-5);
+        assert!((snap_to_device(7.0, 1.5) * 1.5).fract().abs() < 1e-5);
+        assert_eq!(snap_to_device(3.3, 0.0), 3.3);
+    }
+
     #[test]
     fn test_rect_contains() {
         let rect = Rect::new(10.0, 10.0, 100.0, 50.0);
         assert!(rect.contains(Point::new(50.0, 30.0)));
         assert!(!rect.contains(Point::new(5.0, 30.0)));
     }
+
+    #[test]
+    fn test_rect_intersection() {
+        let rect = Rect::new(10.0, 10.0, 100.0, 50.0);
+        assert_eq!(
+            rect.intersection(&Rect::new(60.0, 40.0, 100.0, 100.0)),
+            Some(Rect::new(60.0, 40.0, 50.0, 20.0))
+        );
+        assert_eq!(rect.intersection(&Rect::new(200.0, 10.0, 5.0, 5.0)), None);
+    }
+
+    #[test]
+    fn test_image_sampling() {
+        assert!(ImageSampling::Auto.smooth(100, 100, 50.0, 50.0));
+        assert!(!ImageSampling::Auto.smooth(100, 100, 100.0, 100.0));
+        assert!(!ImageSampling::Auto.smooth(16, 16, 64.0, 64.0));
+        assert!(ImageSampling::Smooth.smooth(16, 16, 64.0, 64.0));
+        assert!(!ImageSampling::Nearest.smooth(100, 100, 50.0, 50.0));
+        assert_eq!(ImageSampling::from_i32(-1), ImageSampling::Auto);
+    }
 }
@@ -0,0 +1,185 @@
+//! Colorimetry of video frames.
+//!
+//! Decoded video is YCbCr, usually limited range (luma 16-235) with the
+//! BT.601, BT.709 or BT.2020 matrix.  Frames reach the renderer already
+//! converted to RGB; when the converter assumed other values than the
+//! stream has, e.g. full range for limited-range BT.709, colors come out
+//! washed out or shifted.  `ColorCorrection` undoes such a conversion and
+//! redoes it with the stream's values, as one affine map of the RGB the
+//! converter produced, which the video shader applies per pixel.
+
+/// Range of the code values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRange {
+    /// 0-255
+    Full,
+    /// Luma 16-235, chroma 16-240
+    Limited,
+}
+
+/// Matrix between R'G'B' and Y'CbCr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMatrix {
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+impl ColorMatrix {
+    /// Luma weights of red and blue
+    fn kr_kb(self) -> (f32, f32) {
+        match self {
+            ColorMatrix::Bt601 => (0.299, 0.114),
+            ColorMatrix::Bt709 => (0.2126, 0.0722),
+            ColorMatrix::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+}
+
+/// How a stream's YCbCr values are to be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoColorimetry {
+    pub range: ColorRange,
+    pub matrix: ColorMatrix,
+}
+
+impl VideoColorimetry {
+    /// What a stream of this size most likely uses when it does not say:
+    /// limited range, BT.601 up to SD, BT.2020 from UHD, BT.709 between
+    pub fn guess(width: u32, height: u32) -> Self {
+        let matrix = if height >= 2160 || width >= 3840 {
+            ColorMatrix::Bt2020
+        } else if height > 576 {
+            ColorMatrix::Bt709
+        } else {
+            ColorMatrix::Bt601
+        };
+        Self { range: ColorRange::Limited, matrix }
+    }
+
+    /// Normalized Y'CbCr (Y' 0..1, Cb/Cr -0.5..0.5) to code values / 255
+    fn quantize(self, [y, cb, cr]: [f32; 3]) -> [f32; 3] {
+        match self.range {
+            ColorRange::Full => [y, cb + 128.0 / 255.0, cr + 128.0 / 255.0],
+            ColorRange::Limited => [
+                (16.0 + 219.0 * y) / 255.0,
+                (128.0 + 224.0 * cb) / 255.0,
+                (128.0 + 224.0 * cr) / 255.0,
+            ],
+        }
+    }
+
+    fn dequantize(self, [y, cb, cr]: [f32; 3]) -> [f32; 3] {
+        match self.range {
+            ColorRange::Full => [y, cb - 128.0 / 255.0, cr - 128.0 / 255.0],
+            ColorRange::Limited => [
+                (255.0 * y - 16.0) / 219.0,
+                (255.0 * cb - 128.0) / 224.0,
+                (255.0 * cr - 128.0) / 224.0,
+            ],
+        }
+    }
+
+    fn rgb_to_ycbcr(self, [r, g, b]: [f32; 3]) -> [f32; 3] {
+        let (kr, kb) = self.matrix.kr_kb();
+        let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+        [y, (b - y) / (2.0 * (1.0 - kb)), (r - y) / (2.0 * (1.0 - kr))]
+    }
+
+    fn ycbcr_to_rgb(self, [y, cb, cr]: [f32; 3]) -> [f32; 3] {
+        let (kr, kb) = self.matrix.kr_kb();
+        let r = y + 2.0 * (1.0 - kr) * cr;
+        let b = y + 2.0 * (1.0 - kb) * cb;
+        let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+        [r, g, b]
+    }
+}
+
+/// Affine map of gamma-encoded RGB: `matrix * rgb + offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorCorrection {
+    /// Rows of the 3x3 matrix
+    pub matrix: [[f32; 3]; 3],
+    pub offset: [f32; 3],
+}
+
+impl ColorCorrection {
+    pub const IDENTITY: Self = Self {
+        matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        offset: [0.0; 3],
+    };
+
+    /// Correction for RGB that a converter made reading the stream as
+    /// `converted_as`, when it is really `source`
+    pub fn between(converted_as: VideoColorimetry, source: VideoColorimetry) -> Self {
+        let redo = |rgb: [f32; 3]| {
+            let codes = converted_as.quantize(converted_as.rgb_to_ycbcr(rgb));
+            source.ycbcr_to_rgb(source.dequantize(codes))
+        };
+        let offset = redo([0.0; 3]);
+        let mut matrix = [[0.0; 3]; 3];
+        for col in 0..3 {
+            let mut unit = [0.0; 3];
+            unit[col] = 1.0;
+            let mapped = redo(unit);
+            for row in 0..3 {
+                matrix[row][col] = mapped[row] - offset[row];
+            }
+        }
+        Self { matrix, offset }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        (0..3).all(|r| {
+            close(self.offset[r], 0.0) && (0..3).all(|c| close(self.matrix[r][c], Self::IDENTITY.matrix[r][c]))
+        })
+    }
+
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let m = &self.matrix;
+        [0, 1, 2].map(|r| m[r][0] * rgb[0] + m[r][1] * rgb[1] + m[r][2] * rgb[2] + self.offset[r])
+    }
+
+    /// The map as three vec4 rows (matrix row, offset), as the video
+    /// shader's uniform takes it
+    pub fn to_uniform(&self) -> [[f32; 4]; 3] {
+        [0, 1, 2].map(|r| [self.matrix[r][0], self.matrix[r][1], self.matrix[r][2], self.offset[r]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-3), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_limited_range_read_as_full_is_expanded() {
+        let full = VideoColorimetry { range: ColorRange::Full, matrix: ColorMatrix::Bt709 };
+        let limited = VideoColorimetry { range: ColorRange::Limited, matrix: ColorMatrix::Bt709 };
+        let fix = ColorCorrection::between(full, limited);
+        // Limited black and white, shown as dark gray and light gray, go
+        // back to black and white
+        assert_close(fix.apply([16.0 / 255.0; 3]), [0.0; 3]);
+        assert_close(fix.apply([235.0 / 255.0; 3]), [1.0; 3]);
+        assert!(ColorCorrection::between(limited, limited).is_identity());
+    }
+
+    #[test]
+    fn test_matrix_correction_keeps_grays() {
+        let bt601 = VideoColorimetry { range: ColorRange::Limited, matrix: ColorMatrix::Bt601 };
+        let bt709 = VideoColorimetry { range: ColorRange::Limited, matrix: ColorMatrix::Bt709 };
+        let fix = ColorCorrection::between(bt601, bt709);
+        assert!(!fix.is_identity());
+        assert_close(fix.apply([0.5; 3]), [0.5; 3]);
+        // Red read with the wrong matrix is off, and the fix changes it
+        assert!((fix.apply([1.0, 0.0, 0.0])[1] - 0.0).abs() > 0.01);
+
+        assert_eq!(VideoColorimetry::guess(720, 480).matrix, ColorMatrix::Bt601);
+        assert_eq!(VideoColorimetry::guess(1920, 1080).matrix, ColorMatrix::Bt709);
+        assert_eq!(VideoColorimetry::guess(3840, 2160).matrix, ColorMatrix::Bt2020);
+    }
+}
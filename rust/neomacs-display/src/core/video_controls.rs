@@ -0,0 +1,240 @@
+//! On-screen controls for inline videos.
+//!
+//! With the `video-controls` option on, hovering a video glyph shows a
+//! control bar along its bottom edge: play/pause, a scrubber showing the
+//! buffered ranges and the play position, the time, and a volume slider.
+//! The render thread hit-tests the pointer against the bar and drives the
+//! video cache, so hosts need not build controls out of text properties.
+
+use crate::core::types::{Point, Rect};
+
+/// Height of the control bar
+pub const VIDEO_CONTROLS_HEIGHT: f32 = 32.0;
+/// Side of the play/pause button
+const BUTTON_SIZE: f32 = 22.0;
+/// Width kept for the "12:34 / 56:78" label
+const TIME_WIDTH: f32 = 104.0;
+const VOLUME_WIDTH: f32 = 64.0;
+/// Videos narrower than this only get play/pause and the scrubber
+const FULL_BAR_MIN_WIDTH: f32 = 320.0;
+const PAD: f32 = 6.0;
+
+/// A control of the bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VideoControl {
+    PlayPause,
+    /// The scrubber, at this fraction of the video
+    Scrub(f32),
+    /// The volume slider, at this level
+    Volume(f32),
+}
+
+/// What the host should do with the video
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VideoAction {
+    TogglePlay(u32),
+    Seek(u32, f32),
+    SetVolume(u32, f32),
+}
+
+/// Playback state shown by the controls
+#[derive(Debug, Clone, Default)]
+pub struct VideoStatus {
+    pub playing: bool,
+    /// Play position as a fraction of the length, once known
+    pub progress: Option<f32>,
+    /// Parts available for playback, as (start, end) fractions
+    pub buffered: Vec<(f32, f32)>,
+    /// "position / length" label, once the length is known
+    pub time: Option<String>,
+    pub volume: f32,
+}
+
+/// Where the controls of a video at a given rect are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoControlsLayout {
+    pub bar: Rect,
+    pub play: Rect,
+    pub scrub: Rect,
+    /// Area of the time label, when the video is wide enough
+    pub time: Option<Rect>,
+    /// Area of the volume slider, when the video is wide enough
+    pub volume: Option<Rect>,
+}
+
+impl VideoControlsLayout {
+    pub fn new(video: Rect) -> Self {
+        let height = VIDEO_CONTROLS_HEIGHT.min(video.height);
+        let bar = Rect::new(video.x, video.bottom() - height, video.width, height);
+        let y = bar.y + (height - BUTTON_SIZE).max(0.0) / 2.0;
+        let play = Rect::new(bar.x + PAD, y, BUTTON_SIZE, BUTTON_SIZE);
+        let mut right = bar.right() - PAD;
+        let (time, volume) = if video.width >= FULL_BAR_MIN_WIDTH {
+            let volume = Rect::new(right - VOLUME_WIDTH, y, VOLUME_WIDTH, BUTTON_SIZE);
+            let time = Rect::new(volume.x - PAD - TIME_WIDTH, y, TIME_WIDTH, BUTTON_SIZE);
+            right = time.x;
+            (Some(time), Some(volume))
+        } else {
+            (None, None)
+        };
+        let scrub_x = play.right() + PAD * 2.0;
+        let scrub = Rect::new(scrub_x, y, (right - PAD * 2.0 - scrub_x).max(0.0), BUTTON_SIZE);
+        Self { bar, play, scrub, time, volume }
+    }
+
+    /// Control at (`x`, `y`), if any
+    pub fn hit(&self, x: f32, y: f32) -> Option<VideoControl> {
+        let p = Point::new(x, y);
+        if self.play.contains(p) {
+            Some(VideoControl::PlayPause)
+        } else if self.scrub.contains(p) {
+            Some(VideoControl::Scrub(fraction(x, self.scrub)))
+        } else {
+            self.volume.filter(|v| v.contains(p)).map(|v| VideoControl::Volume(fraction(x, v)))
+        }
+    }
+}
+
+fn fraction(x: f32, track: Rect) -> f32 {
+    if track.width <= 0.0 {
+        return 0.0;
+    }
+    ((x - track.x) / track.width).clamp(0.0, 1.0)
+}
+
+/// `position` and `duration` in nanoseconds as "m:ss / m:ss", with hours
+/// when the video is an hour or longer
+pub fn format_time(position: u64, duration: u64) -> String {
+    let long = duration >= 3_600_000_000_000;
+    let fmt = |ns: u64| {
+        let s = ns / 1_000_000_000;
+        if long {
+            format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+        } else {
+            format!("{}:{:02}", s / 60, s % 60)
+        }
+    };
+    format!("{} / {}", fmt(position), fmt(duration))
+}
+
+/// Control held down with the pointer
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Drag {
+    Scrub,
+    Volume,
+    /// The play/pause button, released without effect
+    Click,
+}
+
+/// Which video shows its controls, and what the pointer is doing with them
+#[derive(Debug, Clone, Default)]
+pub struct VideoControls {
+    pub enabled: bool,
+    /// Video under the pointer
+    pub hovered: Option<u32>,
+    drag: Option<(u32, Drag)>,
+}
+
+impl VideoControls {
+    /// Rect of the shown video among `videos`, as (video id, rect) pairs
+    pub fn shown(&self, videos: &[(u32, Rect)]) -> Option<(u32, Rect)> {
+        let id = self.drag.map(|(id, _)| id).or(self.hovered)?;
+        videos.iter().copied().find(|(v, _)| *v == id)
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Pointer moved to (`x`, `y`).  Returns whether the shown controls
+    /// changed, and what a dragged slider asks for.
+    pub fn motion(&mut self, x: f32, y: f32, videos: &[(u32, Rect)]) -> (bool, Option<VideoAction>) {
+        if let Some((id, drag)) = self.drag {
+            let Some((_, rect)) = videos.iter().find(|(v, _)| *v == id) else {
+                return (false, None);
+            };
+            let layout = VideoControlsLayout::new(*rect);
+            let action = match drag {
+                Drag::Scrub => Some(VideoAction::Seek(id, fraction(x, layout.scrub))),
+                Drag::Volume => layout.volume.map(|v| VideoAction::SetVolume(id, fraction(x, v))),
+                Drag::Click => None,
+            };
+            return (true, action);
+        }
+        let hovered = if self.enabled {
+            videos.iter().find(|(_, r)| r.contains(Point::new(x, y))).map(|(id, _)| *id)
+        } else {
+            None
+        };
+        let changed = hovered != self.hovered;
+        self.hovered = hovered;
+        (changed, None)
+    }
+
+    /// Button pressed at (`x`, `y`).  `None` when it is not on the
+    /// controls, so the press belongs to the host.
+    pub fn press(&mut self, x: f32, y: f32, videos: &[(u32, Rect)]) -> Option<VideoAction> {
+        let (id, rect) = self.shown(videos)?;
+        let action = match VideoControlsLayout::new(rect).hit(x, y)? {
+            VideoControl::PlayPause => {
+                self.drag = Some((id, Drag::Click));
+                VideoAction::TogglePlay(id)
+            }
+            VideoControl::Scrub(f) => {
+                self.drag = Some((id, Drag::Scrub));
+                VideoAction::Seek(id, f)
+            }
+            VideoControl::Volume(v) => {
+                self.drag = Some((id, Drag::Volume));
+                VideoAction::SetVolume(id, v)
+            }
+        };
+        Some(action)
+    }
+
+    /// Button released.  Returns whether a control was held.
+    pub fn release(&mut self) -> bool {
+        self.drag.take().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_controls_layout_and_time() {
+        let wide = VideoControlsLayout::new(Rect::new(0.0, 0.0, 640.0, 360.0));
+        assert_eq!(wide.bar.y, 360.0 - VIDEO_CONTROLS_HEIGHT);
+        let volume = wide.volume.unwrap();
+        assert!(wide.scrub.right() < wide.time.unwrap().x);
+        assert_eq!(wide.hit(volume.right() - 0.5, volume.y + 1.0), Some(VideoControl::Volume(1.0 - 0.5 / VOLUME_WIDTH)));
+        assert_eq!(wide.hit(wide.play.x + 1.0, wide.play.y + 1.0), Some(VideoControl::PlayPause));
+        assert_eq!(wide.hit(10.0, 10.0), None);
+
+        let narrow = VideoControlsLayout::new(Rect::new(0.0, 0.0, 200.0, 120.0));
+        assert!(narrow.time.is_none() && narrow.volume.is_none());
+
+        assert_eq!(format_time(65_000_000_000, 200_000_000_000), "1:05 / 3:20");
+        assert_eq!(format_time(61_000_000_000, 3_700_000_000_000), "0:01:01 / 1:01:40");
+    }
+
+    #[test]
+    fn test_video_controls_scrub_drag() {
+        let videos = [(3, Rect::new(100.0, 100.0, 640.0, 360.0))];
+        let mut controls = VideoControls { enabled: true, ..Default::default() };
+        assert_eq!(controls.motion(150.0, 150.0, &videos), (true, None));
+        assert_eq!(controls.hovered, Some(3));
+
+        let scrub = VideoControlsLayout::new(videos[0].1).scrub;
+        let y = scrub.y + 1.0;
+        assert_eq!(controls.press(scrub.x, y, &videos), Some(VideoAction::Seek(3, 0.0)));
+        // The drag keeps seeking past the end of the scrubber and off the video
+        assert_eq!(controls.motion(2000.0, 0.0, &videos), (true, Some(VideoAction::Seek(3, 1.0))));
+        assert!(controls.release());
+        assert_eq!(controls.motion(2000.0, 0.0, &videos), (true, None));
+        assert_eq!(controls.hovered, None);
+        // Not on the controls: the press is the host's
+        assert_eq!(controls.press(150.0, 150.0, &videos), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Watchdog for render-thread stalls.
+//!
+//! When the display stops updating for a second, all the user sees is a
+//! frozen window.  The render thread tells a `Watchdog` which phase of its
+//! loop it is in (taking commands, picking up a frame, preparing,
+//! drawing, presenting) and drops finer markers inside the phases, such
+//! as "terminals" or "surface".  A watchdog thread checks every few
+//! milliseconds, and when a phase runs past the threshold it records a
+//! display warning naming the phase and the last marker, while the stall
+//! is still going on.  The stack of another thread cannot be read
+//! portably, so the markers stand in for it.  When the phase finally
+//! ends, its full duration is logged.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::error_report::{self, ErrorKind};
+
+/// Default threshold in milliseconds
+pub const DEFAULT_THRESHOLD_MS: u64 = 100;
+
+/// A phase that ran past the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stall {
+    pub phase: &'static str,
+    /// Last marker dropped in the phase; the phase itself if none was
+    pub marker: &'static str,
+    pub elapsed: Duration,
+}
+
+/// What the render thread is doing
+#[derive(Debug)]
+struct Progress {
+    phase: &'static str,
+    marker: &'static str,
+    since: Instant,
+    active: bool,
+    /// Whether this run of the phase was reported as stalled
+    reported: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    progress: Mutex<Progress>,
+    /// 0 turns the watchdog off
+    threshold_ms: AtomicU64,
+    stopped: AtomicBool,
+}
+
+/// Render-thread side of the watchdog; clones watch the same thread
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    shared: Arc<Shared>,
+}
+
+impl Watchdog {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                progress: Mutex::new(Progress {
+                    phase: "",
+                    marker: "",
+                    since: Instant::now(),
+                    active: false,
+                    reported: false,
+                }),
+                threshold_ms: AtomicU64::new(threshold.as_millis() as u64),
+                stopped: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    fn progress(&self) -> std::sync::MutexGuard<'_, Progress> {
+        self.shared.progress.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Report phases longer than `threshold`; zero turns the watchdog off
+    pub fn set_threshold(&self, threshold: Duration) {
+        self.shared.threshold_ms.store(threshold.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn threshold(&self) -> Option<Duration> {
+        match self.shared.threshold_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    /// End the current phase, if any, and start `phase`
+    pub fn enter(&self, phase: &'static str) {
+        let mut progress = self.progress();
+        Self::finish(&progress);
+        *progress = Progress { phase, marker: phase, since: Instant::now(), active: true, reported: false };
+    }
+
+    /// Note how far the current phase has got
+    pub fn mark(&self, marker: &'static str) {
+        self.progress().marker = marker;
+    }
+
+    /// End the current phase
+    pub fn leave(&self) {
+        let mut progress = self.progress();
+        Self::finish(&progress);
+        progress.active = false;
+    }
+
+    /// Log how long a phase reported as stalled took in the end
+    fn finish(progress: &Progress) {
+        if progress.active && progress.reported {
+            log::warn!(
+                "Render thread stalled {}ms in {} ({})",
+                progress.since.elapsed().as_millis(),
+                progress.phase,
+                progress.marker
+            );
+        }
+    }
+
+    /// The phase running at `now`, if it has run past the threshold and
+    /// was not returned already
+    pub fn check(&self, now: Instant) -> Option<Stall> {
+        let threshold = self.threshold()?;
+        let mut progress = self.progress();
+        let elapsed = now.saturating_duration_since(progress.since);
+        if !progress.active || progress.reported || elapsed < threshold {
+            return None;
+        }
+        progress.reported = true;
+        Some(Stall { phase: progress.phase, marker: progress.marker, elapsed })
+    }
+
+    /// Start the thread that checks for stalls and reports them as
+    /// display warnings.  It exits when `stop` is called.
+    pub fn spawn(&self) -> std::io::Result<std::thread::JoinHandle<()>> {
+        let watchdog = self.clone();
+        std::thread::Builder::new().name("render-watchdog".into()).spawn(move || {
+            while !watchdog.shared.stopped.load(Ordering::Relaxed) {
+                // A few checks per threshold; rarely while turned off
+                let interval = watchdog.threshold().map_or(Duration::from_millis(250), |t| {
+                    (t / 4).max(Duration::from_millis(5))
+                });
+                std::thread::sleep(interval);
+                if let Some(stall) = watchdog.check(Instant::now()) {
+                    let threshold = watchdog.threshold().unwrap_or(stall.elapsed);
+                    let message = format!(
+                        "Render thread stalled for over {}ms in {} ({})",
+                        threshold.as_millis(),
+                        stall.phase,
+                        stall.marker
+                    );
+                    log::warn!("{}", message);
+                    error_report::warning(ErrorKind::Render, None, message);
+                }
+            }
+        })
+    }
+
+    /// Make the watchdog thread exit
+    pub fn stop(&self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(DEFAULT_THRESHOLD_MS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog_reports_a_stall_once() {
+        let watchdog = Watchdog::new(Duration::from_millis(100));
+        let start = Instant::now();
+        assert_eq!(watchdog.check(start + Duration::from_secs(1)), None);
+
+        watchdog.enter("draw");
+        watchdog.mark("surface");
+        let late = Instant::now() + Duration::from_millis(150);
+        assert_eq!(watchdog.check(Instant::now()), None);
+        let stall = watchdog.check(late).unwrap();
+        assert_eq!((stall.phase, stall.marker), ("draw", "surface"));
+        assert!(stall.elapsed >= Duration::from_millis(150));
+        assert_eq!(watchdog.check(late), None);
+
+        // The next phase is timed afresh
+        watchdog.enter("present");
+        assert_eq!(watchdog.check(Instant::now()), None);
+        assert_eq!(watchdog.check(Instant::now() + Duration::from_millis(200)).unwrap().marker, "present");
+        watchdog.leave();
+        assert_eq!(watchdog.check(Instant::now() + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_watchdog_turned_off() {
+        let watchdog = Watchdog::new(Duration::from_millis(100));
+        watchdog.set_threshold(Duration::ZERO);
+        watchdog.enter("commands");
+        assert_eq!(watchdog.check(Instant::now() + Duration::from_secs(10)), None);
+        watchdog.set_threshold(Duration::from_millis(50));
+        assert!(watchdog.check(Instant::now() + Duration::from_secs(10)).is_some());
+    }
+}
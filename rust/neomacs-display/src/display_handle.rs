@@ -0,0 +1,128 @@
+//! Thread-safe handle for driving the render thread.
+//!
+//! The FFI entry points in `ffi` assume they are called from the Emacs
+//! main thread.  A [`DisplayHandle`] wraps the render command channel so
+//! any host thread can queue work; commands are applied by the render
+//! thread at the start of its next frame, in the order they were sent.
+
+use std::time::Duration;
+
+use crossbeam_channel::{SendTimeoutError, Sender};
+
+use crate::core::error::{DisplayError, DisplayResult};
+use crate::core::scroll_animation::ScrollEffect;
+use crate::thread_comm::RenderCommand;
+
+/// How long `send` waits for room in a full command queue.
+const SEND_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Cloneable, `Send + Sync` sender of render commands.
+#[derive(Clone)]
+pub struct DisplayHandle {
+    cmd_tx: Sender<RenderCommand>,
+}
+
+impl DisplayHandle {
+    pub fn new(cmd_tx: Sender<RenderCommand>) -> Self {
+        Self { cmd_tx }
+    }
+
+    /// Queue a command for the render thread.
+    ///
+    /// Blocks briefly if the queue is full rather than dropping the
+    /// command, and fails once the render thread has shut down.
+    pub fn send(&self, cmd: RenderCommand) -> DisplayResult<()> {
+        self.cmd_tx.send_timeout(cmd, SEND_TIMEOUT).map_err(|e| match e {
+            SendTimeoutError::Timeout(_) => {
+                DisplayError::Backend("render command queue is full".into())
+            }
+            SendTimeoutError::Disconnected(_) => {
+                DisplayError::Backend("render thread has exited".into())
+            }
+        })
+    }
+
+    /// Queue an image file load and return the id it will be stored under.
+    pub fn load_image_file(&self, path: &str, max_width: u32, max_height: u32) -> DisplayResult<u32> {
+        let id = crate::core::handle::IMAGES.alloc()?;
+        self.send(RenderCommand::ImageLoadFile {
+            id,
+            path: path.to_string(),
+            max_width,
+            max_height,
+        })?;
+        Ok(id)
+    }
+
+    /// Crossfade `window_id` (or every window when `None`) from its
+    /// current contents to whatever the next frame draws.
+    pub fn start_transition(
+        &self,
+        window_id: Option<i64>,
+        effect: ScrollEffect,
+        duration: Duration,
+    ) -> DisplayResult<()> {
+        self.send(RenderCommand::StartTransition {
+            window_id,
+            effect,
+            duration_ms: duration.as_millis().min(u32::MAX as u128) as u32,
+            notify: 0,
+        })
+    }
+
+    /// Zoom the whole frame to `scale` over `duration`, around `focus` in
+    /// logical pixels or, with `None`, around the cursor.
+    pub fn set_frame_zoom(
+        &self,
+        scale: f32,
+        focus: Option<(f32, f32)>,
+        duration: Duration,
+    ) -> DisplayResult<()> {
+        self.send(RenderCommand::SetFrameZoom {
+            scale,
+            focus,
+            duration_ms: duration.as_millis().min(u32::MAX as u128) as u32,
+            notify: 0,
+        })
+    }
+
+    /// Queue input bytes for a terminal.
+    #[cfg(feature = "neo-term")]
+    pub fn terminal_write(&self, id: u32, data: &[u8]) -> DisplayResult<()> {
+        self.send(RenderCommand::TerminalWrite { id, data: data.to_vec() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thread_comm::ThreadComms;
+
+    #[test]
+    fn test_commands_from_other_threads_arrive_in_order() {
+        let (emacs, render) = ThreadComms::new().unwrap().split();
+        let handle = DisplayHandle::new(emacs.cmd_tx.clone());
+
+        let worker = handle.clone();
+        std::thread::spawn(move || {
+            worker.load_image_file("/tmp/a.png", 0, 0).unwrap();
+            worker
+                .start_transition(Some(7), ScrollEffect::Crossfade, Duration::from_millis(250))
+                .unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(
+            render.cmd_rx.try_recv(),
+            Ok(RenderCommand::ImageLoadFile { ref path, .. }) if path == "/tmp/a.png"
+        ));
+        assert!(matches!(
+            render.cmd_rx.try_recv(),
+            Ok(RenderCommand::StartTransition { window_id: Some(7), duration_ms: 250, .. })
+        ));
+
+        drop(render);
+        assert!(handle.send(RenderCommand::Shutdown).is_err());
+    }
+}
@@ -6,6 +6,8 @@
 
 use std::time::Duration;
 
+use crate::core::types::Rect;
+
 /// Macro for defining effect config structs with Default implementations.
 ///
 macro_rules! effect_config {
@@ -31,6 +33,15 @@ macro_rules! effect_config {
     };
 }
 
+effect_config!(
+    /// Edge smoothing of shape primitives (rounded rects, rings, borders).
+    /// `width` is the width of the smoothed band in device pixels, so
+    /// edges look the same at any scale factor; 0 draws hard edges.
+    AntialiasConfig {
+        width: f32 = 1.0,
+    }
+);
+
 effect_config!(
     /// Configuration for the accent strip effect.
     AccentStripConfig {
@@ -39,6 +50,73 @@ effect_config!(
     }
 );
 
+/// Animated layer drawn behind the text of every window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmbientEffect {
+    #[default]
+    None,
+    /// Slowly drifting gradient noise
+    Gradient,
+    /// Falling snowflakes in three depths
+    Snow,
+    /// Columns of falling glyph-like blocks
+    MatrixRain,
+}
+
+impl AmbientEffect {
+    /// All effects in definition order.
+    pub const ALL: [AmbientEffect; 4] = [Self::None, Self::Gradient, Self::Snow, Self::MatrixRain];
+
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "gradient" | "noise" => Self::Gradient,
+            "snow" => Self::Snow,
+            "matrix-rain" | "matrix" | "rain" => Self::MatrixRain,
+            _ => Self::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Gradient => "gradient",
+            Self::Snow => "snow",
+            Self::MatrixRain => "matrix-rain",
+        }
+    }
+
+    /// Number the ambient shader selects the effect by
+    pub fn shader_kind(&self) -> u32 {
+        *self as u32
+    }
+}
+
+effect_config!(
+    /// Ambient background layer.  The shader is procedural, so frames are
+    /// only drawn for it `fps` times a second, and not at all while the
+    /// window is unfocused.
+    AmbientConfig {
+        effect: AmbientEffect = AmbientEffect::None,
+        color: (f32, f32, f32) = (0.6, 0.7, 0.9),
+        opacity: f32 = 0.08,
+        speed: f32 = 1.0,
+        density: f32 = 0.5,
+        fps: u32 = 20,
+    }
+);
+
+impl AmbientConfig {
+    /// Whether frames need the ambient layer
+    pub fn is_active(&self) -> bool {
+        self.effect != AmbientEffect::None && self.opacity > 0.0
+    }
+
+    /// Time between frames drawn only to advance the layer
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.fps.clamp(1, 30)
+    }
+}
+
 effect_config!(
     /// Configuration for the argyle pattern effect.
     ArgylePatternConfig {
@@ -62,6 +140,15 @@ effect_config!(
     }
 );
 
+effect_config!(
+    /// Configuration for blurring the backdrop of floating content.
+    BackdropBlurConfig {
+        enabled: bool = false,
+        radius: f32 = 12.0,
+        passes: u32 = 2,
+    }
+);
+
 effect_config!(
     /// Configuration for the basket weave effect.
     BasketWeaveConfig {
@@ -174,6 +261,175 @@ effect_config!(
     }
 );
 
+/// Whole-frame color transform, applied as the last pass of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    /// Warm tint with less blue, for the evening
+    NightLight,
+    Grayscale,
+    /// Simulate red-blind vision
+    Protanopia,
+    /// Simulate green-blind vision
+    Deuteranopia,
+    /// Simulate blue-blind vision
+    Tritanopia,
+    /// Shift colors red-blind viewers confuse toward ones they can tell apart
+    ProtanopiaCompensate,
+    /// Shift colors green-blind viewers confuse toward ones they can tell apart
+    DeuteranopiaCompensate,
+}
+
+/// Machado et al. (2009) dichromacy simulation in linear RGB, severity 1
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.148371],
+    [0.004733, 0.691367, 0.303900],
+];
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Daltonization: the color information a dichromat loses (`I - sim`) is
+/// moved into the channels they still see
+fn compensate(sim: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    const SHIFT: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+    let mut out = IDENTITY;
+    for (r, row) in out.iter_mut().enumerate() {
+        for (c, v) in row.iter_mut().enumerate() {
+            *v += (0..3).map(|k| SHIFT[r][k] * (IDENTITY[k][c] - sim[k][c])).sum::<f32>();
+        }
+    }
+    out
+}
+
+impl ColorFilter {
+    /// All filters in definition order.
+    pub const ALL: [ColorFilter; 8] = [
+        Self::None,
+        Self::NightLight,
+        Self::Grayscale,
+        Self::Protanopia,
+        Self::Deuteranopia,
+        Self::Tritanopia,
+        Self::ProtanopiaCompensate,
+        Self::DeuteranopiaCompensate,
+    ];
+
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "night-light" | "night" | "warm" => Self::NightLight,
+            "grayscale" | "greyscale" | "gray" | "grey" => Self::Grayscale,
+            "protanopia" => Self::Protanopia,
+            "deuteranopia" => Self::Deuteranopia,
+            "tritanopia" => Self::Tritanopia,
+            "protanopia-compensate" => Self::ProtanopiaCompensate,
+            "deuteranopia-compensate" => Self::DeuteranopiaCompensate,
+            _ => Self::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::NightLight => "night-light",
+            Self::Grayscale => "grayscale",
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+            Self::ProtanopiaCompensate => "protanopia-compensate",
+            Self::DeuteranopiaCompensate => "deuteranopia-compensate",
+        }
+    }
+
+    /// Linear RGB transform of the filter at full strength
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+        match self {
+            Self::None => IDENTITY,
+            Self::NightLight => [[1.0, 0.0, 0.0], [0.0, 0.78, 0.0], [0.0, 0.0, 0.55]],
+            Self::Grayscale => [LUMA, LUMA, LUMA],
+            Self::Protanopia => PROTANOPIA,
+            Self::Deuteranopia => DEUTERANOPIA,
+            Self::Tritanopia => TRITANOPIA,
+            Self::ProtanopiaCompensate => compensate(PROTANOPIA),
+            Self::DeuteranopiaCompensate => compensate(DEUTERANOPIA),
+        }
+    }
+}
+
+effect_config!(
+    /// Whole-frame color filter.  `strength` blends between the unfiltered
+    /// frame (0) and the full filter (1).
+    ColorFilterConfig {
+        filter: ColorFilter = ColorFilter::None,
+        strength: f32 = 1.0,
+    }
+);
+
+impl ColorFilterConfig {
+    /// Whether frames need the color filter pass
+    pub fn is_active(&self) -> bool {
+        self.filter != ColorFilter::None && self.strength > 0.0
+    }
+
+    /// Rows of the shader's color matrix, padded to vec4
+    pub fn rows(&self) -> [[f32; 4]; 3] {
+        let m = self.filter.matrix();
+        let t = self.strength.clamp(0.0, 1.0);
+        let mut rows = [[0.0; 4]; 3];
+        for (r, row) in rows.iter_mut().enumerate() {
+            for c in 0..3 {
+                row[c] = IDENTITY[r][c] + (m[r][c] - IDENTITY[r][c]) * t;
+            }
+        }
+        rows
+    }
+}
+
+/// Most regions the smart invert pass leaves alone
+pub const SMART_INVERT_MAX_KEEP: usize = 16;
+
+effect_config!(
+    /// Dark mode for content whose faces do not adapt: colors of the whole
+    /// frame are inverted and their hues turned back, except over images,
+    /// video and web views, which keep their own colors.
+    SmartInvertConfig {
+        enabled: bool = false,
+    }
+);
+
+impl SmartInvertConfig {
+    /// The shader's parameters: `[on, regions kept, 0, 0]`, and the regions
+    /// kept as `[x, y, right, bottom]` in logical pixels.  Past the most
+    /// the shader takes, the last region grows to cover the rest.
+    pub fn params(&self, keep: &[Rect]) -> ([f32; 4], [[f32; 4]; SMART_INVERT_MAX_KEEP]) {
+        let mut regions = [[0.0; 4]; SMART_INVERT_MAX_KEEP];
+        if !self.enabled {
+            return ([0.0; 4], regions);
+        }
+        for (i, r) in keep.iter().enumerate() {
+            let slot = &mut regions[i.min(SMART_INVERT_MAX_KEEP - 1)];
+            if i < SMART_INVERT_MAX_KEEP {
+                *slot = [r.x, r.y, r.right(), r.bottom()];
+            } else {
+                *slot = [slot[0].min(r.x), slot[1].min(r.y), slot[2].max(r.right()), slot[3].max(r.bottom())];
+            }
+        }
+        let count = keep.len().min(SMART_INVERT_MAX_KEEP);
+        ([1.0, count as f32, 0.0, 0.0], regions)
+    }
+}
+
 effect_config!(
     /// Configuration for the concentric rings effect.
     ConcentricRingsConfig {
@@ -433,6 +689,15 @@ effect_config!(
     }
 );
 
+effect_config!(
+    /// Text inversion under a filled box cursor.  `complement_fg` (sRGB)
+    /// overrides the face-derived inverse text color.
+    CursorInversionConfig {
+        enabled: bool = true,
+        complement_fg: Option<(f32, f32, f32)> = None,
+    }
+);
+
 effect_config!(
     /// Configuration for the cursor lighthouse effect.
     CursorLighthouseConfig {
@@ -771,6 +1036,14 @@ effect_config!(
     }
 );
 
+effect_config!(
+    /// What the debug overlay draws over the frame (see
+    /// `core::scene_dump`).
+    DebugOverlayConfig {
+        mode: crate::core::scene_dump::DebugOverlay = crate::core::scene_dump::DebugOverlay::None,
+    }
+);
+
 effect_config!(
     /// Configuration for the depth shadow effect.
     DepthShadowConfig {
@@ -969,13 +1242,28 @@ effect_config!(
 );
 
 effect_config!(
-    /// Configuration for the inactive dim effect.
+    /// Configuration for the inactive dim effect.  Non-selected windows
+    /// are covered with black at `opacity` and their colors kept at
+    /// `saturation` (1 leaves them unchanged, 0 makes them gray).
     InactiveDimConfig {
         enabled: bool = false,
         opacity: f32 = 0.15,
+        saturation: f32 = 1.0,
     }
 );
 
+impl InactiveDimConfig {
+    /// Overlay opacity and saturation of a window `amount` of the way
+    /// (0 selected, 1 inactive) through its fade
+    pub fn at(&self, amount: f32) -> (f32, f32) {
+        let amount = amount.clamp(0.0, 1.0);
+        (
+            self.opacity.clamp(0.0, 1.0) * amount,
+            1.0 - (1.0 - self.saturation.clamp(0.0, 1.0)) * amount,
+        )
+    }
+}
+
 effect_config!(
     /// Configuration for the inactive tint effect.
     InactiveTintConfig {
@@ -1068,6 +1356,7 @@ effect_config!(
     MinimapConfig {
         enabled: bool = false,
         width: f32 = 80.0,
+        line_height: f32 = 2.0,
     }
 );
 
@@ -1302,6 +1591,25 @@ effect_config!(
     }
 );
 
+effect_config!(
+    /// Region highlight drawn as merged, optionally rounded runs.
+    SelectionRunsConfig {
+        enabled: bool = false,
+        face_id: u32 = 0,
+        radius: f32 = 4.0,
+    }
+);
+
+effect_config!(
+    /// Registered fragment shaders applied to regions, windows and
+    /// floating images (see `core::shader_effect`).  `strength` blends
+    /// between the plain frame (0) and the full effects (1).
+    ShaderEffectsConfig {
+        enabled: bool = true,
+        strength: f32 = 1.0,
+    }
+);
+
 effect_config!(
     /// Configuration for the show whitespace effect.
     ShowWhitespaceConfig {
@@ -1383,6 +1691,39 @@ effect_config!(
     }
 );
 
+effect_config!(
+    /// Coverage correction for text blended in linear light.
+    ///
+    /// Linear-space blending makes dark text on light backgrounds look thin
+    /// and fringy.  Coverage is raised to `1 / gamma` in proportion to how
+    /// dark the text is (stem darkening), then scaled by `1 + contrast`.
+    /// Off until Lisp opts in, so text keeps its plain linear coverage.
+    TextGammaConfig {
+        enabled: bool = false,
+        gamma: f32 = 1.7,
+        contrast: f32 = 0.0,
+    }
+);
+
+impl TextGammaConfig {
+    /// Glyph shader parameters `[gamma, contrast]`; identity when disabled.
+    pub fn params(&self) -> [f32; 2] {
+        if self.enabled {
+            [self.gamma.max(0.01), self.contrast.max(0.0)]
+        } else {
+            [1.0, 0.0]
+        }
+    }
+
+    /// CPU mirror of the glyph shader's coverage adjustment.
+    pub fn adjust_coverage(&self, coverage: f32, fg: &crate::core::types::Color) -> f32 {
+        let [gamma, contrast] = self.params();
+        let adjusted = coverage.powf(1.0 / gamma);
+        let darkness = 1.0 - fg.luminance().clamp(0.0, 1.0);
+        ((coverage + (adjusted - coverage) * darkness) * (1.0 + contrast)).clamp(0.0, 1.0)
+    }
+}
+
 effect_config!(
     /// Configuration for the theme transition effect.
     ThemeTransitionConfig {
@@ -1573,8 +1914,11 @@ effect_config!(
 #[derive(Clone, Debug, Default)]
 pub struct EffectsConfig {
     pub accent_strip: AccentStripConfig,
+    pub ambient: AmbientConfig,
+    pub antialias: AntialiasConfig,
     pub argyle_pattern: ArgylePatternConfig,
     pub aurora: AuroraConfig,
+    pub backdrop_blur: BackdropBlurConfig,
     pub basket_weave: BasketWeaveConfig,
     pub bg_gradient: BgGradientConfig,
     pub bg_pattern: BgPatternConfig,
@@ -1586,6 +1930,7 @@ pub struct EffectsConfig {
     pub chevron_pattern: ChevronPatternConfig,
     pub circuit_trace: CircuitTraceConfig,
     pub click_halo: ClickHaloConfig,
+    pub color_filter: ColorFilterConfig,
     pub concentric_rings: ConcentricRingsConfig,
     pub constellation: ConstellationConfig,
     pub corner_fold: CornerFoldConfig,
@@ -1610,6 +1955,7 @@ pub struct EffectsConfig {
     pub cursor_glow: CursorGlowConfig,
     pub cursor_gravity_well: CursorGravityWellConfig,
     pub cursor_heartbeat: CursorHeartbeatConfig,
+    pub cursor_inversion: CursorInversionConfig,
     pub cursor_lighthouse: CursorLighthouseConfig,
     pub cursor_lightning: CursorLightningConfig,
     pub cursor_magnetism: CursorMagnetismConfig,
@@ -1641,6 +1987,7 @@ pub struct EffectsConfig {
     pub cursor_trail_fade: CursorTrailFadeConfig,
     pub cursor_wake: CursorWakeConfig,
     pub cursor_water_drop: CursorWaterDropConfig,
+    pub debug_overlay: DebugOverlayConfig,
     pub depth_shadow: DepthShadowConfig,
     pub diamond_lattice: DiamondLatticeConfig,
     pub dot_matrix: DotMatrixConfig,
@@ -1694,14 +2041,18 @@ pub struct EffectsConfig {
     pub scroll_progress: ScrollProgressConfig,
     pub scroll_velocity_fade: ScrollVelocityFadeConfig,
     pub search_pulse: SearchPulseConfig,
+    pub selection_runs: SelectionRunsConfig,
+    pub shader_effects: ShaderEffectsConfig,
     pub show_whitespace: ShowWhitespaceConfig,
     pub sine_wave: SineWaveConfig,
+    pub smart_invert: SmartInvertConfig,
     pub spiral_vortex: SpiralVortexConfig,
     pub stained_glass: StainedGlassConfig,
     pub sunburst_pattern: SunburstPatternConfig,
     pub target_reticle: TargetReticleConfig,
     pub tessellation: TessellationConfig,
     pub text_fade_in: TextFadeInConfig,
+    pub text_gamma: TextGammaConfig,
     pub theme_transition: ThemeTransitionConfig,
     pub title_fade: TitleFadeConfig,
     pub topo_contour: TopoContourConfig,
@@ -1722,3 +2073,116 @@ pub struct EffectsConfig {
     pub zen_mode: ZenModeConfig,
     pub zigzag_pattern: ZigzagPatternConfig,
 }
+
+impl EffectsConfig {
+    /// Switch to the cursor effect of a Neovide-style animation mode,
+    /// turning off the effects of the other modes.  Smooth motion itself
+    /// is configured separately.
+    pub fn set_cursor_animation_mode(&mut self, mode: crate::core::cursor_animation::CursorAnimationMode) {
+        use crate::core::cursor_animation::CursorAnimationMode as Mode;
+        self.cursor_particles.enabled = mode == Mode::Railgun;
+        self.cursor_comet.enabled = mode == Mode::Torpedo;
+        self.cursor_pixel_dust.enabled = mode == Mode::Pixiedust;
+        self.cursor_shockwave.enabled = mode == Mode::Sonicboom;
+        self.cursor_ripple_ring.enabled = mode == Mode::Ripple;
+        self.cursor_glow.enabled = mode == Mode::Wireframe;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::Color;
+
+    #[test]
+    fn test_ambient_effect() {
+        for effect in AmbientEffect::ALL {
+            assert_eq!(AmbientEffect::from_str(effect.as_str()), effect);
+        }
+        let mut ambient = AmbientConfig::default();
+        assert!(!ambient.is_active());
+        ambient.effect = AmbientEffect::Snow;
+        assert!(ambient.is_active());
+        // The frame rate is capped however high it is set
+        ambient.fps = 240;
+        assert_eq!(ambient.frame_interval(), Duration::from_secs(1) / 30);
+        ambient.fps = 0;
+        assert_eq!(ambient.frame_interval(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_inactive_dim_fades_opacity_and_saturation() {
+        let dim = InactiveDimConfig { enabled: true, opacity: 0.2, saturation: 0.4 };
+        assert_eq!(dim.at(0.0), (0.0, 1.0));
+        for (amount, expected) in [(1.0, (0.2, 0.4)), (0.5, (0.1, 0.7))] {
+            let (opacity, saturation) = dim.at(amount);
+            assert!((opacity - expected.0).abs() < 1e-6 && (saturation - expected.1).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_color_filter_rows() {
+        // Every filter keeps white white except the tints
+        for filter in ColorFilter::ALL {
+            let rows = ColorFilterConfig { filter, strength: 1.0 }.rows();
+            let white: Vec<f32> = rows.iter().map(|r| r[0] + r[1] + r[2]).collect();
+            if filter == ColorFilter::NightLight {
+                assert!(white[2] < white[0]);
+            } else {
+                for v in white {
+                    assert!((v - 1.0).abs() < 0.01, "{:?}: {}", filter, v);
+                }
+            }
+            assert_eq!(ColorFilter::from_str(filter.as_str()), filter);
+        }
+        // Zero strength is the identity
+        let off = ColorFilterConfig { filter: ColorFilter::Grayscale, strength: 0.0 };
+        assert!(!off.is_active());
+        assert_eq!(off.rows()[1], [0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_smart_invert_params() {
+        let rect = |i: usize| Rect::new(i as f32 * 10.0, 0.0, 5.0, 5.0);
+        let off = SmartInvertConfig::default();
+        assert_eq!(off.params(&[rect(0)]).0, [0.0; 4]);
+
+        let on = SmartInvertConfig { enabled: true };
+        let (invert, regions) = on.params(&[rect(0), rect(1)]);
+        assert_eq!(invert, [1.0, 2.0, 0.0, 0.0]);
+        assert_eq!(regions[1], [10.0, 0.0, 15.0, 5.0]);
+        // Regions past the most taken are covered by the last one
+        let many: Vec<Rect> = (0..20).map(rect).collect();
+        let (invert, regions) = on.params(&many);
+        assert_eq!(invert[1], SMART_INVERT_MAX_KEEP as f32);
+        assert_eq!(regions[SMART_INVERT_MAX_KEEP - 1], [150.0, 0.0, 195.0, 5.0]);
+    }
+
+    #[test]
+    fn test_text_gamma_darkens_only_dark_text() {
+        let gamma = TextGammaConfig { enabled: true, ..TextGammaConfig::default() };
+        let dark = gamma.adjust_coverage(0.5, &Color::BLACK);
+        let light = gamma.adjust_coverage(0.5, &Color::WHITE);
+        assert!(dark > 0.6);
+        assert!((light - 0.5).abs() < 1e-6);
+        // Full and zero coverage are fixed points
+        assert_eq!(gamma.adjust_coverage(1.0, &Color::BLACK), 1.0);
+        assert_eq!(gamma.adjust_coverage(0.0, &Color::BLACK), 0.0);
+
+        let off = TextGammaConfig { enabled: false, ..gamma };
+        assert_eq!(off.adjust_coverage(0.5, &Color::BLACK), 0.5);
+    }
+
+    #[test]
+    fn test_cursor_animation_mode_selects_one_effect() {
+        use crate::core::cursor_animation::CursorAnimationMode;
+
+        let mut effects = EffectsConfig::default();
+        effects.set_cursor_animation_mode(CursorAnimationMode::from_str("sonicboom"));
+        assert!(effects.cursor_shockwave.enabled);
+        effects.set_cursor_animation_mode(CursorAnimationMode::Railgun);
+        assert!(effects.cursor_particles.enabled && !effects.cursor_shockwave.enabled);
+        effects.set_cursor_animation_mode(CursorAnimationMode::Smooth);
+        assert!(!effects.cursor_particles.enabled);
+    }
+}
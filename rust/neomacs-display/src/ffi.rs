@@ -29,6 +29,21 @@ use crate::backend::wgpu::{
     NEOMACS_EVENT_MENU_SELECTION,
     NEOMACS_EVENT_FILE_DROP,
     NEOMACS_EVENT_TERMINAL_TITLE_CHANGED,
+    NEOMACS_EVENT_VIDEO_ENDED,
+    NEOMACS_EVENT_WEBKIT_TITLE_CHANGED,
+    NEOMACS_EVENT_WEBKIT_URL_CHANGED,
+    NEOMACS_EVENT_WEBKIT_PROGRESS_CHANGED,
+    NEOMACS_EVENT_WEBKIT_LOAD_FINISHED,
+    NEOMACS_EVENT_ANIMATION_FINISHED,
+    NEOMACS_EVENT_DISPLAY_ERROR,
+    NEOMACS_EVENT_TERMINAL_BELL,
+    NEOMACS_EVENT_TMUX_PANE_ADDED,
+    NEOMACS_EVENT_TMUX_PANE_CLOSED,
+    NEOMACS_EVENT_TMUX_EXITED,
+    NEOMACS_EVENT_ANIMATION_COMPLETED,
+    NEOMACS_EVENT_PIP_CLOSED,
+    NEOMACS_EVENT_AUDIO_LEVELS,
+    NEOMACS_EVENT_APPEARANCE_CHANGED,
 };
 
 /// Resize callback function type for C FFI
@@ -46,10 +61,23 @@ static mut RESIZE_CALLBACK_USER_DATA: *mut std::ffi::c_void = std::ptr::null_mut
 #[cfg(feature = "winit-backend")]
 static DROPPED_FILES: std::sync::Mutex<Vec<Vec<String>>> = std::sync::Mutex::new(Vec::new());
 
-/// Pending terminal title changes (populated by drain_input, consumed by C)
-/// Each entry is (terminal_id, new_title).
+/// Set by drain_input when the render thread asks for every face again
 #[cfg(feature = "winit-backend")]
-static TERMINAL_TITLES: std::sync::Mutex<Vec<(u32, String)>> = std::sync::Mutex::new(Vec::new());
+static FACES_OUT_OF_SYNC: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Text carried by pending events: terminal and WebKit titles, WebKit URLs
+/// (populated by drain_input, consumed by C).
+/// Each entry is (event kind, id, text).
+#[cfg(feature = "winit-backend")]
+static EVENT_TEXTS: std::sync::Mutex<Vec<(u32, u32, String)>> = std::sync::Mutex::new(Vec::new());
+
+/// Queue `text` for `neomacs_display_get_event_text`
+#[cfg(feature = "winit-backend")]
+fn push_event_text(kind: u32, id: u32, text: String) {
+    if let Ok(mut queue) = EVENT_TEXTS.lock() {
+        queue.push((kind, id, text));
+    }
+}
 
 use crate::backend::tty::TtyBackend;
 use crate::core::types::{Color, Rect};
@@ -82,9 +110,55 @@ pub struct NeomacsDisplay {
     frame_counter: u64,     // Frame counter for tracking row updates
     current_render_window_id: u32, // Winit window ID being rendered to (0 = legacy rendering)
     faces: HashMap<u32, Face>,
+    /// Background styles keyed by the 0xRRGGBB background they replace
+    face_background_styles: HashMap<u32, BackgroundStyle>,
+    /// Attribute groups faces set themselves and the faces they inherit
+    /// the rest from, for faces registered as inheriting
+    face_inheritance: HashMap<u32, (FaceFields, Vec<u32>)>,
+    /// Faces as last registered, versioned so frames carry only changes
+    face_cache: FaceCache,
+    /// Face cache generation the render thread has been sent
+    faces_sent: u64,
+    /// Link areas from HTML fragments added this frame
+    #[cfg(feature = "html-renderer")]
+    html_links: Vec<crate::layout::html::HtmlLink>,
+    /// Layout of the visible character grid, mirrored for hit-testing
+    char_grid: Option<crate::text::CharGridLayout>,
+    /// Minimap line summaries, mirrored for viewport dragging
+    minimap: crate::core::minimap::MinimapStore,
+    /// Typeset TeX math snippets and the images they were uploaded as
+    #[cfg(feature = "math")]
+    math: crate::layout::math::MathCache,
+    /// Loaded icon sets and the images made from their icons
+    #[cfg(feature = "icons")]
+    icons: crate::layout::icons::IconRegistry,
+    /// Commands of each canvas, by image id, kept to redraw it
+    #[cfg(feature = "canvas")]
+    canvases: HashMap<u32, crate::layout::canvas::Canvas>,
+    /// Syntax highlight spans produced on a background thread
+    highlight: crate::layout::highlight::HighlightService,
 }
 
 impl NeomacsDisplay {
+    /// Copy of the frame glyphs to send to the render thread, carrying the
+    /// faces changed since the last one sent.  The glyphs are copied into a
+    /// list the render thread handed back, so a frame allocates no new one.
+    #[cfg(feature = "winit-backend")]
+    fn frame_to_send(&mut self, comms: &EmacsComms) -> FrameGlyphBuffer {
+        let mut glyphs = comms.recycle_rx.try_recv().unwrap_or_default();
+        glyphs.extend_from_slice(&self.frame_glyphs.glyphs);
+        let own = std::mem::take(&mut self.frame_glyphs.glyphs);
+        let mut frame = self.frame_glyphs.clone();
+        self.frame_glyphs.glyphs = own;
+        frame.glyphs = glyphs;
+        if FACES_OUT_OF_SYNC.swap(false, std::sync::atomic::Ordering::Relaxed) {
+            self.faces_sent = 0;
+        }
+        frame.face_delta = self.face_cache.delta_since(self.faces_sent);
+        self.faces_sent = frame.face_delta.generation;
+        frame
+    }
+
     fn get_backend(&mut self) -> Option<&mut dyn DisplayBackend> {
         match self.backend_type {
             BackendType::Tty => self.tty_backend.as_mut().map(|b| b as &mut dyn DisplayBackend),
@@ -195,6 +269,9 @@ pub unsafe extern "C" fn neomacs_display_begin_frame(handle: *mut NeomacsDisplay
         display.frame_glyphs.background = display.scene.background;
         display.frame_glyphs.clear_all();
     }
+
+    #[cfg(feature = "html-renderer")]
+    display.html_links.clear();
 }
 
 /// Add a window to the current frame
@@ -832,7 +909,21 @@ pub unsafe extern "C" fn neomacs_display_end_row(handle: *mut NeomacsDisplay) {
 // Face Management
 // ============================================================================
 
-use crate::core::face::{Face, FaceAttributes, UnderlineStyle, BoxType};
+use crate::core::face::{BackgroundStyle, Face, FaceAttributes, FaceCache, FaceFields, UnderlineStyle, BoxType};
+
+/// Attribute groups for `neomacs_display_set_face_inheritance`
+pub const NEOMACS_FACE_FIELD_FOREGROUND: u32 = FaceFields::FOREGROUND.bits();
+pub const NEOMACS_FACE_FIELD_BACKGROUND: u32 = FaceFields::BACKGROUND.bits();
+pub const NEOMACS_FACE_FIELD_FAMILY: u32 = FaceFields::FAMILY.bits();
+pub const NEOMACS_FACE_FIELD_HEIGHT: u32 = FaceFields::HEIGHT.bits();
+pub const NEOMACS_FACE_FIELD_WEIGHT: u32 = FaceFields::WEIGHT.bits();
+pub const NEOMACS_FACE_FIELD_SLANT: u32 = FaceFields::SLANT.bits();
+pub const NEOMACS_FACE_FIELD_UNDERLINE: u32 = FaceFields::UNDERLINE.bits();
+pub const NEOMACS_FACE_FIELD_OVERLINE: u32 = FaceFields::OVERLINE.bits();
+pub const NEOMACS_FACE_FIELD_STRIKE_THROUGH: u32 = FaceFields::STRIKE_THROUGH.bits();
+pub const NEOMACS_FACE_FIELD_BOX: u32 = FaceFields::BOX.bits();
+pub const NEOMACS_FACE_FIELD_INVERSE: u32 = FaceFields::INVERSE.bits();
+pub const NEOMACS_FACE_FIELD_BACKGROUND_STYLE: u32 = FaceFields::BACKGROUND_STYLE.bits();
 
 /// Register or update a face
 /// Colors are in 0xRRGGBB format
@@ -1009,13 +1100,21 @@ pub unsafe extern "C" fn neomacs_display_set_face(
         font_descent: font_descent as i32,
         underline_position: if ul_position > 0 { ul_position as i32 } else { 1 },
         underline_thickness: if ul_thickness > 0 { ul_thickness as i32 } else { 1 },
+        background_style: display.face_background_styles.get(&(background & 0xFFFFFF)).copied(),
     };
 
     // Store face for later lookup during rendering
     display.faces.insert(face_id, face.clone());
 
-    // Also store in frame glyph buffer so render thread gets full face data
-    display.frame_glyphs.faces.insert(face_id, face.clone());
+    // The render thread gets the face with the next frame, unless unchanged
+    match display.face_inheritance.get(&face_id) {
+        Some((specified, parents)) => {
+            display.face_cache.insert_inheriting(face.clone(), *specified, parents.clone());
+        }
+        None => {
+            display.face_cache.insert(face.clone());
+        }
+    }
 
     // Hybrid path: set current face attributes for frame glyph buffer
     if display.use_hybrid {
@@ -1044,6 +1143,86 @@ pub unsafe extern "C" fn neomacs_display_set_face(
     display.get_target_scene().set_face(face.clone());
 }
 
+/// Forget a face Emacs freed, so the render thread drops it too
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_remove_face(handle: *mut NeomacsDisplay, face_id: u32) {
+    if handle.is_null() {
+        return;
+    }
+    let display = &mut *handle;
+    display.faces.remove(&face_id);
+    display.face_inheritance.remove(&face_id);
+    display.face_cache.remove(face_id);
+}
+
+/// Make face `face_id` set only the attribute groups in `specified`
+/// (`NEOMACS_FACE_FIELD_*` bits) and inherit the rest from the
+/// `n_parents` faces at `parents`, earlier ones first, then from the
+/// default face.  Holds for the face as registered with
+/// `neomacs_display_set_face`, before or after this call, so the render
+/// thread gets the face's own attributes and its parents' ids rather than
+/// attributes already merged.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_face_inheritance(
+    handle: *mut NeomacsDisplay,
+    face_id: u32,
+    specified: u32,
+    parents: *const u32,
+    n_parents: c_int,
+) {
+    if handle.is_null() {
+        return;
+    }
+    let display = &mut *handle;
+    let parents = if parents.is_null() || n_parents <= 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(parents, n_parents as usize).to_vec()
+    };
+    let specified = FaceFields::from_bits_truncate(specified);
+    if let Some(face) = display.face_cache.get(face_id).cloned() {
+        display.face_cache.insert_inheriting(face, specified, parents.clone());
+    }
+    display.face_inheritance.insert(face_id, (specified, parents));
+}
+
+/// Draw faces whose background is `background` (0xRRGGBB) with a
+/// gradient or image instead of the flat color.
+/// kind: 0=flat (remove style), 1=gradient from `from` to `to` (0xRRGGBB)
+/// at `angle` degrees clockwise from left-to-right, 2=image `image_id`.
+/// Takes effect for faces (re)registered after the call.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_face_background_style(
+    handle: *mut NeomacsDisplay,
+    background: u32,
+    kind: c_int,
+    from: u32,
+    to: u32,
+    angle: f32,
+    image_id: u32,
+) {
+    if handle.is_null() {
+        return;
+    }
+    let display = &mut *handle;
+    let rgb = |c: u32| Color {
+        r: ((c >> 16) & 0xFF) as f32 / 255.0,
+        g: ((c >> 8) & 0xFF) as f32 / 255.0,
+        b: (c & 0xFF) as f32 / 255.0,
+        a: 1.0,
+    }.srgb_to_linear();
+    let style = match kind {
+        1 => Some(BackgroundStyle::Gradient { from: rgb(from), to: rgb(to), angle }),
+        2 if image_id != 0 => Some(BackgroundStyle::Image { image_id }),
+        _ => None,
+    };
+    let key = background & 0xFFFFFF;
+    match style {
+        Some(style) => { display.face_background_styles.insert(key, style); }
+        None => { display.face_background_styles.remove(&key); }
+    }
+}
+
 /// Set the frame/scene background color
 /// Color is in 0xRRGGBB format
 #[no_mangle]
@@ -1166,6 +1345,151 @@ pub unsafe extern "C" fn neomacs_display_add_video_glyph(
     }
 }
 
+/// Add an inline terminal glyph to the current row.  `y_offset` is the
+/// distance from the top of the row to the top of the terminal, which
+/// sits on the row's baseline.  The terminal's grid is fitted to the
+/// given pixel size when it is drawn.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_add_terminal_glyph(
+    handle: *mut NeomacsDisplay,
+    terminal_id: u32,
+    y_offset: c_int,
+    pixel_width: c_int,
+    pixel_height: c_int,
+) {
+    if handle.is_null() {
+        return;
+    }
+
+    let display = &mut *handle;
+    // Only the hybrid path draws terminals
+    if display.use_hybrid {
+        display.frame_glyphs.add_terminal(
+            terminal_id,
+            display.current_row_x as f32,
+            (display.current_row_y + y_offset.max(0)) as f32,
+            pixel_width as f32,
+            pixel_height as f32,
+        );
+    }
+    display.current_row_x += pixel_width;
+}
+
+// ============================================================================
+// Lightweight HTML Rendering
+// ============================================================================
+
+/// Lay out an HTML fragment into the current frame at (x, y), wrapping at
+/// WIDTH.  Content below y + HEIGHT is cut off, unless HEIGHT is 0.
+///
+/// Text uses FACE_ID for body text and the given faces for bold, italic,
+/// code and link runs (pass FACE_ID again to reuse the body face).  Images
+/// are referenced as `<img src="neomacs-image:ID">` using IDs returned by
+/// the image loading API.  Returns the height consumed in pixels, or -1 on
+/// error.  Link areas can be queried with `neomacs_display_html_link_at`
+/// until the next frame begins.
+#[cfg(feature = "html-renderer")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_add_html(
+    handle: *mut NeomacsDisplay,
+    html: *const c_char,
+    x: c_int,
+    y: c_int,
+    width: c_int,
+    height: c_int,
+    face_id: u32,
+    bold_face_id: u32,
+    italic_face_id: u32,
+    code_face_id: u32,
+    link_face_id: u32,
+) -> c_int {
+    use crate::layout::html::{render_html, HtmlImage, HtmlStyle};
+
+    if handle.is_null() || html.is_null() {
+        return -1;
+    }
+    let display = &mut *handle;
+    let html = CStr::from_ptr(html).to_string_lossy();
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let base = display.faces.get(&face_id);
+        let font_size = base.map(|f| f.font_size).filter(|s| *s > 0.0)
+            .unwrap_or(display.frame_glyphs.font_pixel_size);
+        let scale = font_size / display.frame_glyphs.font_pixel_size.max(1.0);
+        let mut style = HtmlStyle::with_face(
+            face_id,
+            font_size,
+            display.frame_glyphs.char_height * scale,
+            display.frame_glyphs.char_width * scale,
+            base.map(|f| f.foreground).unwrap_or(Color::WHITE),
+        );
+        style.bold_face_id = bold_face_id;
+        style.italic_face_id = italic_face_id;
+        style.code_face_id = code_face_id;
+        style.link_face_id = link_face_id;
+        if let Some(link) = display.faces.get(&link_face_id).filter(|_| link_face_id != face_id) {
+            style.link_fg = link.foreground;
+        }
+        if let Some(code) = display.faces.get(&code_face_id).filter(|_| code_face_id != face_id) {
+            style.code_bg = code.background;
+        }
+
+        let image_dims = THREADED_STATE.as_ref().map(|s| Arc::clone(&s.image_dimensions));
+        let first = display.frame_glyphs.glyphs.len();
+        let mut layout = render_html(
+            &html, x as f32, y as f32, width as f32, &style, &mut display.frame_glyphs,
+            |src| {
+                let id: u32 = src.strip_prefix("neomacs-image:")?.parse().ok()?;
+                let (w, h) = image_dims.as_ref()?.lock().ok()?.get(&id).copied()?;
+                Some(HtmlImage { image_id: id, width: w as f32, height: h as f32 })
+            },
+        );
+        if height > 0 {
+            let bottom = (y + height) as f32;
+            let added = display.frame_glyphs.glyphs.split_off(first);
+            display.frame_glyphs.glyphs.extend(added.into_iter().filter(|glyph| match glyph {
+                FrameGlyph::Char { y, height, .. }
+                | FrameGlyph::Stretch { y, height, .. }
+                | FrameGlyph::Image { y, height, .. } => y + height <= bottom,
+                _ => true,
+            }));
+            layout.links.retain(|link| link.bounds.y + link.bounds.height <= bottom);
+        }
+        display.html_links.extend(layout.links);
+        layout.height.ceil() as c_int
+    }));
+
+    match result {
+        Ok(height) => height,
+        Err(e) => {
+            eprintln!("PANIC in neomacs_display_add_html: {:?}", e);
+            -1
+        }
+    }
+}
+
+/// Return the link target of HTML content at frame position (x, y), or NULL.
+/// The caller must free the result with `neomacs_display_free_string`.
+#[cfg(feature = "html-renderer")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_html_link_at(
+    handle: *mut NeomacsDisplay,
+    x: c_int,
+    y: c_int,
+) -> *mut c_char {
+    if handle.is_null() {
+        return ptr::null_mut();
+    }
+    let display = &*handle;
+    let point = crate::core::types::Point::new(x as f32, y as f32);
+    display.html_links.iter()
+        .find(|l| l.bounds.contains(point))
+        .and_then(|l| CString::new(l.href.as_str()).ok())
+        .map(|s| s.into_raw())
+        .unwrap_or(ptr::null_mut())
+}
+
 /// Load a video from file path (async - uses GStreamer)
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_load_video(
@@ -1187,7 +1511,9 @@ pub unsafe extern "C" fn neomacs_display_load_video(
     // Threaded path: send command to render thread
     #[cfg(all(feature = "winit-backend", feature = "video"))]
     if let Some(ref state) = THREADED_STATE {
-        let id = VIDEO_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::VIDEOS.alloc() else {
+            return 0;
+        };
         let cmd = RenderCommand::VideoCreate {
             id,
             path: path_str.to_string(),
@@ -1218,6 +1544,9 @@ pub unsafe extern "C" fn neomacs_display_video_play(
     // Threaded path
     #[cfg(all(feature = "winit-backend", feature = "video"))]
     if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::VIDEOS, video_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
         let cmd = RenderCommand::VideoPlay { id: video_id };
         let _ = state.emacs_comms.cmd_tx.try_send(cmd);
         return 0;
@@ -1248,6 +1577,9 @@ pub unsafe extern "C" fn neomacs_display_video_pause(
     // Threaded path
     #[cfg(all(feature = "winit-backend", feature = "video"))]
     if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::VIDEOS, video_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
         let cmd = RenderCommand::VideoPause { id: video_id };
         let _ = state.emacs_comms.cmd_tx.try_send(cmd);
         return 0;
@@ -1278,6 +1610,9 @@ pub unsafe extern "C" fn neomacs_display_video_stop(
     // Threaded path: stop maps to destroy
     #[cfg(all(feature = "winit-backend", feature = "video"))]
     if let Some(ref state) = THREADED_STATE {
+        if !free_handle(&crate::core::handle::VIDEOS, video_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
         let cmd = RenderCommand::VideoDestroy { id: video_id };
         let _ = state.emacs_comms.cmd_tx.try_send(cmd);
         return 0;
@@ -1299,6 +1634,237 @@ pub unsafe extern "C" fn neomacs_display_video_stop(
     -1
 }
 
+/// Show a video in the picture-in-picture player: a small player pinned
+/// to a frame corner (0 top-left, 1 top-right, 2 bottom-left, 3
+/// bottom-right), `width` logical pixels wide, that stays up across
+/// buffer switches.  `video_id` 0 closes the player.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_video_pip(
+    handle: *mut NeomacsDisplay,
+    video_id: u32,
+    corner: u8,
+    width: f32,
+) -> c_int {
+    #[cfg(all(feature = "winit-backend", feature = "video"))]
+    if let Some(ref state) = THREADED_STATE {
+        if video_id != 0 && !live_handle(&crate::core::handle::VIDEOS, video_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::VideoPip { id: video_id, corner, width };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    -1
+}
+
+/// Draw the waveform of the audio of the media file PATH as an image of
+/// `width` x `height` pixels in `color` (0xAARRGGBB, alpha 0 for opaque)
+/// on a transparent background, for seek bars (async).  Returns the image
+/// ID, or 0 on failure.  Files without audio fail as display errors.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_media_waveform(
+    handle: *mut NeomacsDisplay,
+    path: *const c_char,
+    width: c_int,
+    height: c_int,
+    color: u32,
+) -> u32 {
+    if path.is_null() || width <= 0 || height <= 0 {
+        return 0;
+    }
+    let path = CStr::from_ptr(path).to_string_lossy().into_owned();
+
+    #[cfg(all(feature = "winit-backend", feature = "video"))]
+    if let Some(ref state) = THREADED_STATE {
+        let Ok(image_id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
+        let (width, height) = (width as u32, height as u32);
+        if let Ok(mut dims) = state.image_dimensions.lock() {
+            dims.insert(image_id, (width, height));
+        }
+        let cmd = RenderCommand::MediaWaveform { path, width, height, color, image_id };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return image_id;
+    }
+
+    0
+}
+
+/// Take `count` thumbnails `width` pixels wide of the video of the media
+/// file PATH, at even intervals, for seek previews (async).  Their image
+/// IDs go to `image_ids` (room for `count`).  Their height follows the
+/// shape of the video and is known once each is ready, as with images
+/// loaded from files.  Returns the number of IDs written, or -1.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_media_thumbnails(
+    handle: *mut NeomacsDisplay,
+    path: *const c_char,
+    count: c_int,
+    width: c_int,
+    image_ids: *mut u32,
+) -> c_int {
+    if path.is_null() || image_ids.is_null() || count <= 0 || width <= 0 {
+        return -1;
+    }
+    let path = CStr::from_ptr(path).to_string_lossy().into_owned();
+
+    #[cfg(all(feature = "winit-backend", feature = "video"))]
+    if let Some(ref state) = THREADED_STATE {
+        let Ok(ids) = (0..count).map(|_| crate::core::handle::IMAGES.alloc()).collect::<Result<Vec<u32>, _>>() else {
+            return -1;
+        };
+        std::ptr::copy_nonoverlapping(ids.as_ptr(), image_ids, ids.len());
+        let cmd = RenderCommand::MediaThumbnails { path, width: width as u32, image_ids: ids };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return count;
+    }
+
+    -1
+}
+
+/// Find out whether the media file or URI PATH can be played, decoding
+/// up to the first frame of each stream.  Blocks for up to five seconds.
+/// Returns 1 if it can, 0 if not, and -1 without video support.  When
+/// `reason` is not NULL it is set to why the file won't play fully (a
+/// missing codec, say), or NULL; free it with `neomacs_display_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_probe_media(
+    path: *const c_char,
+    reason: *mut *mut c_char,
+) -> c_int {
+    if !reason.is_null() {
+        *reason = ptr::null_mut();
+    }
+    if path.is_null() {
+        return -1;
+    }
+
+    #[cfg(feature = "video")]
+    {
+        let path = CStr::from_ptr(path).to_string_lossy();
+        let probe = crate::backend::wgpu::VideoCache::probe(&path);
+        if !reason.is_null() {
+            if let Some(why) = probe.reason() {
+                *reason = CString::new(why).map_or(ptr::null_mut(), CString::into_raw);
+            }
+        }
+        return probe.is_playable() as c_int;
+    }
+
+    -1
+}
+
+/// Report the audio levels of a video with an `audio-levels` event each
+/// time they change (about ten times a second while it plays), or stop
+/// reporting them when `enabled` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_video_audio_events(
+    handle: *mut NeomacsDisplay,
+    video_id: u32,
+    enabled: c_int,
+) -> c_int {
+    #[cfg(all(feature = "winit-backend", feature = "video"))]
+    if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::VIDEOS, video_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::VideoAudioEvents { id: video_id, enabled: enabled != 0 };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    -1
+}
+
+/// Copy the latest per-channel levels of a video's audio, in dB, into
+/// `rms` and `peak` (each room for `max_channels`).  Returns the number of
+/// channels, or -1 if the video has not played any audio yet.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_audio_levels(
+    video_id: u32,
+    rms: *mut f32,
+    peak: *mut f32,
+    max_channels: c_int,
+) -> c_int {
+    let Some(levels) = crate::core::audio_levels::get(video_id) else {
+        return -1;
+    };
+    let n = levels.rms.len().min(levels.peak.len()).min(max_channels.max(0) as usize);
+    if !rms.is_null() {
+        std::ptr::copy_nonoverlapping(levels.rms.as_ptr(), rms, n);
+    }
+    if !peak.is_null() {
+        std::ptr::copy_nonoverlapping(levels.peak.as_ptr(), peak, n);
+    }
+    n as c_int
+}
+
+/// Copy the latest spectrum of a video's audio, band magnitudes in dB from
+/// the lowest frequency up, into `bands` (room for `max_bands`).  Returns
+/// the number of bands, or -1 if the video has not played any audio yet.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_audio_spectrum(
+    video_id: u32,
+    bands: *mut f32,
+    max_bands: c_int,
+) -> c_int {
+    let Some(levels) = crate::core::audio_levels::get(video_id) else {
+        return -1;
+    };
+    let n = levels.bands.len().min(max_bands.max(0) as usize);
+    if !bands.is_null() {
+        std::ptr::copy_nonoverlapping(levels.bands.as_ptr(), bands, n);
+    }
+    n as c_int
+}
+
+/// Show a video, looping, beneath the text of a window (`window_id` 0 for
+/// the whole frame), darkened by `dim` (0.0-1.0).  `video_id` 0 removes
+/// the window's background video.  Background videos pause while the
+/// frame is unfocused.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_background_video(
+    handle: *mut NeomacsDisplay,
+    window_id: i64,
+    video_id: u32,
+    dim: f32,
+) -> c_int {
+    let window_id = (window_id != 0).then_some(window_id);
+    let dim = dim.clamp(0.0, 1.0);
+
+    // Threaded path
+    #[cfg(all(feature = "winit-backend", feature = "video"))]
+    if let Some(ref state) = THREADED_STATE {
+        if video_id != 0 && !live_handle(&crate::core::handle::VIDEOS, video_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::SetBackgroundVideo { window_id, video_id, dim };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    let display = match handle.as_mut() {
+        Some(d) => d,
+        None => return -1,
+    };
+
+    #[cfg(all(feature = "winit-backend", feature = "video"))]
+    if let Some(ref mut backend) = display.winit_backend {
+        if let Some(renderer) = backend.renderer_mut() {
+            if video_id != 0 {
+                renderer.video_set_loop(video_id, -1);
+                renderer.video_play(video_id);
+            }
+            renderer.set_background_video(window_id, video_id, dim);
+            return 0;
+        }
+    }
+
+    -1
+}
+
 /// Set video loop mode (-1 for infinite)
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_video_set_loop(
@@ -1536,7 +2102,9 @@ pub unsafe extern "C" fn neomacs_display_load_image_file_scaled(
     // Threaded path: send command to render thread
     #[cfg(feature = "winit-backend")]
     if let Some(ref state) = THREADED_STATE {
-        let id = IMAGE_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
         let cmd = RenderCommand::ImageLoadFile {
             id,
             path: path_str.to_string(),
@@ -1600,6 +2168,11 @@ pub unsafe extern "C" fn neomacs_display_get_image_size(
     // Threaded path: check shared map
     #[cfg(feature = "winit-backend")]
     if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::IMAGES, image_id) {
+            *width = 0;
+            *height = 0;
+            return NEOMACS_STALE_HANDLE;
+        }
         if let Ok(dims) = state.image_dimensions.lock() {
             if let Some(&(w, h)) = dims.get(&image_id) {
                 *width = w as c_int;
@@ -1666,9 +2239,17 @@ pub unsafe extern "C" fn neomacs_display_free_image(
     handle: *mut NeomacsDisplay,
     image_id: u32,
 ) -> c_int {
+    #[cfg(feature = "canvas")]
+    if let Some(display) = handle.as_mut() {
+        display.canvases.remove(&image_id);
+    }
+
     // Threaded path: send command to render thread
     #[cfg(feature = "winit-backend")]
     if let Some(ref state) = THREADED_STATE {
+        if !free_handle(&crate::core::handle::IMAGES, image_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
         let cmd = RenderCommand::ImageFree { id: image_id };
         let _ = state.emacs_comms.cmd_tx.try_send(cmd);
         return 0;
@@ -1682,6 +2263,9 @@ pub unsafe extern "C" fn neomacs_display_free_image(
     #[cfg(feature = "winit-backend")]
     if let Some(ref mut backend) = display.winit_backend {
         if let Some(renderer) = backend.renderer_mut() {
+            if !free_handle(&crate::core::handle::IMAGES, image_id) {
+                return NEOMACS_STALE_HANDLE;
+            }
             renderer.free_image(image_id);
             return 0;
         }
@@ -1689,9 +2273,429 @@ pub unsafe extern "C" fn neomacs_display_free_image(
     -1
 }
 
-/// Set a floating video at a specific screen position
+/// Set how an image is filtered when drawn at another size: 1 always
+/// smooth, 0 nearest pixel, -1 smooth only when scaled down.
 #[no_mangle]
-pub unsafe extern "C" fn neomacs_display_set_floating_video(
+pub unsafe extern "C" fn neomacs_display_set_image_sampling(
+    handle: *mut NeomacsDisplay,
+    image_id: u32,
+    sampling: c_int,
+) -> c_int {
+    let sampling = crate::core::types::ImageSampling::from_i32(sampling);
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::IMAGES, image_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::ImageSetSampling { id: image_id, sampling };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    if handle.is_null() {
+        return -1;
+    }
+    let display = &mut *handle;
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref mut backend) = display.winit_backend {
+        if let Some(renderer) = backend.renderer_mut() {
+            renderer.set_image_sampling(image_id, sampling);
+            return 0;
+        }
+    }
+    -1
+}
+
+/// Let an image be zoomed with the wheel, panned by dragging and fitted
+/// again with a double click wherever it is shown, or stop that and show
+/// all of it again.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_image_interactive(
+    handle: *mut NeomacsDisplay,
+    image_id: u32,
+    interactive: c_int,
+) -> c_int {
+    let interactive = interactive != 0;
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::IMAGES, image_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::ImageSetInteractive { id: image_id, interactive };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    if handle.is_null() {
+        return -1;
+    }
+    let display = &mut *handle;
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref mut backend) = display.winit_backend {
+        if let Some(renderer) = backend.renderer_mut() {
+            renderer.set_image_interactive(image_id, interactive);
+            return 0;
+        }
+    }
+    -1
+}
+
+/// Draw chrome `element` ("tab-bar", "tooltip", "popup-menu" or
+/// "child-frame") with image `image_id` cut into nine parts: corners of
+/// `top`/`right`/`bottom`/`left` image pixels keep their size, edges and
+/// center stretch.  `scale` is the size of image pixels on screen.  An
+/// `image_id` of 0 gives the element its own look again.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_chrome_image(
+    handle: *mut NeomacsDisplay,
+    element: *const c_char,
+    image_id: u32,
+    top: c_int,
+    right: c_int,
+    bottom: c_int,
+    left: c_int,
+    scale: c_double,
+) -> c_int {
+    use crate::core::nine_patch::{ChromeElement, NinePatch};
+
+    if element.is_null() {
+        return -1;
+    }
+    let Some(element) = CStr::from_ptr(element).to_str().ok().and_then(ChromeElement::parse) else {
+        return -1;
+    };
+    let patch = (image_id != 0).then(|| NinePatch {
+        image_id,
+        insets: [top, right, bottom, left].map(|i| i.max(0) as f32),
+        scale: if scale > 0.0 { scale as f32 } else { 1.0 },
+    });
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        if patch.is_some() && !live_handle(&crate::core::handle::IMAGES, image_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::SetChromeImage { element, patch };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    if handle.is_null() {
+        return -1;
+    }
+    let display = &mut *handle;
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref mut backend) = display.winit_backend {
+        if let Some(renderer) = backend.renderer_mut() {
+            renderer.set_chrome_image(element, patch);
+            return 0;
+        }
+    }
+    -1
+}
+
+/// Make an image id showing a chart of the `count` values at `values`,
+/// drawn by the GPU at `width` x `height` logical pixels.  `kind` is 0
+/// for a line, 1 for a filled area, 2 for bars.  Colors are 0xAARRGGBB;
+/// a `fill` of 0 is `color` at 30% and a `background` of 0 is none.
+/// `min` and `max` fix the values at the bottom and top of the chart;
+/// NaN fits them to the values.
+///
+/// Returns the image ID, or 0 on failure.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_chart_create(
+    _handle: *mut NeomacsDisplay,
+    kind: c_int,
+    values: *const f32,
+    count: c_int,
+    width: c_int,
+    height: c_int,
+    color: u32,
+    fill: u32,
+    background: u32,
+    line_width: f32,
+    min: f32,
+    max: f32,
+) -> u32 {
+    use crate::core::chart::{ChartKind, ChartStyle};
+
+    let Some(kind) = ChartKind::from_index(kind) else {
+        return 0;
+    };
+    if width <= 0 || height <= 0 {
+        return 0;
+    }
+    let values = chart_values(values, count);
+    let color = Color::from_pixel(color);
+    let style = ChartStyle {
+        kind,
+        color,
+        fill: if fill == 0 { Color { a: color.a * 0.3, ..color } } else { Color::from_pixel(fill) },
+        background: (background != 0).then(|| Color::from_pixel(background)),
+        line_width: if line_width > 0.0 { line_width } else { ChartStyle::default().line_width },
+        range: ((!min.is_nan()).then_some(min), (!max.is_nan()).then_some(max)),
+        ..ChartStyle::default()
+    };
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+            return 0;
+        };
+        if let Ok(mut dims) = state.image_dimensions.lock() {
+            dims.insert(id, (width as u32, height as u32));
+        }
+        let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ChartSet { id, values, style });
+        return id;
+    }
+    0
+}
+
+/// Move chart `image_id` to the `count` values at `values` over
+/// `duration_ms`.  Values past the old ones grow from zero.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_chart_update(
+    handle: *mut NeomacsDisplay,
+    image_id: u32,
+    values: *const f32,
+    count: c_int,
+    duration_ms: u32,
+) -> c_int {
+    let values = chart_values(values, count);
+    let duration = std::time::Duration::from_millis(duration_ms as u64);
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::IMAGES, image_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ChartUpdate { id: image_id, values, duration });
+        return 0;
+    }
+
+    if handle.is_null() {
+        return -1;
+    }
+    let display = &mut *handle;
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref mut backend) = display.winit_backend {
+        if let Some(renderer) = backend.renderer_mut() {
+            if !live_handle(&crate::core::handle::IMAGES, image_id) {
+                return NEOMACS_STALE_HANDLE;
+            }
+            renderer.update_chart(image_id, values, duration);
+            return 0;
+        }
+    }
+    -1
+}
+
+/// The `count` values at `values`, none if the pointer is null
+unsafe fn chart_values(values: *const f32, count: c_int) -> Vec<f32> {
+    if values.is_null() || count <= 0 {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(values, count as usize).to_vec()
+}
+
+/// Register the shader effect `name` with the WGSL `shade` function in
+/// `source` (see `core::shader_effect`), replacing an effect of that
+/// name.  The source is compiled first, so it never reaches the GPU if
+/// it does not.
+///
+/// Returns 0 on success, or -1 with the compiler's message stored in
+/// `out_error` (free with `neomacs_display_free_string`).
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_shader_effect_register(
+    _handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    source: *const c_char,
+    out_error: *mut *mut c_char,
+) -> c_int {
+    if name.is_null() || source.is_null() {
+        return -1;
+    }
+    let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+    let source = CStr::from_ptr(source).to_string_lossy().into_owned();
+    if let Err(message) = crate::core::shader_effect::validate(&source) {
+        if !out_error.is_null() {
+            *out_error = CString::new(message.replace('\0', " ")).map_or(ptr::null_mut(), CString::into_raw);
+        }
+        return -1;
+    }
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ShaderEffectRegister { name, source });
+    0
+}
+
+/// The shader effect target of `kind`: 0 the region `x`, `y`, `width`,
+/// `height`, 1 the window `id`, 2 the floating image `id`
+#[cfg(feature = "winit-backend")]
+fn shader_target(kind: c_int, id: i64, x: f32, y: f32, width: f32, height: f32) -> Option<crate::core::shader_effect::ShaderTarget> {
+    use crate::core::shader_effect::ShaderTarget;
+    match kind {
+        0 => Some(ShaderTarget::Region(crate::core::types::Rect::new(x, y, width, height))),
+        1 => Some(ShaderTarget::Window(id)),
+        2 => u32::try_from(id).ok().map(ShaderTarget::Floating),
+        _ => None,
+    }
+}
+
+/// Draw the shader effect `name` over a target (see `shader_target`)
+/// with the 4 `params` (null for zeros), replacing the effect of that
+/// name there.  Effects that are not registered or built in draw
+/// nothing.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_shader_effect_apply(
+    _handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    kind: c_int,
+    id: i64,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    params: *const f32,
+) -> c_int {
+    if name.is_null() {
+        return -1;
+    }
+    let Some(target) = shader_target(kind, id, x, y, width, height) else {
+        return -1;
+    };
+    let params = if params.is_null() { [0.0; 4] } else { *(params as *const [f32; 4]) };
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    let effect = crate::core::shader_effect::AppliedShaderEffect {
+        name: CStr::from_ptr(name).to_string_lossy().into_owned(),
+        target,
+        params,
+    };
+    let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ShaderEffectApply { effect });
+    0
+}
+
+/// Remove the shader effects named `name` (any if null) from a target
+/// (see `shader_target`; any if `kind` is -1).
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_shader_effect_remove(
+    _handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    kind: c_int,
+    id: i64,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> c_int {
+    let target = shader_target(kind, id, x, y, width, height);
+    if target.is_none() && kind != -1 {
+        return -1;
+    }
+    let name = (!name.is_null()).then(|| CStr::from_ptr(name).to_string_lossy().into_owned());
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ShaderEffectRemove { name, target });
+    0
+}
+
+/// Give image `image_id` the picture of image `new_image_id`, moving
+/// from the old picture to the new by `effect` ("crossfade",
+/// "slide-left", "slide-right", "slide-up" or "slide-down") over
+/// `duration_ms`.  `new_image_id` is consumed: it is freed and must not
+/// be used again.  If it is still loading, the swap happens once it has.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_swap_image(
+    handle: *mut NeomacsDisplay,
+    image_id: u32,
+    new_image_id: u32,
+    effect: *const c_char,
+    duration_ms: u32,
+) -> c_int {
+    if effect.is_null() || image_id == new_image_id {
+        return -1;
+    }
+    let Some(effect) = CStr::from_ptr(effect).to_str().ok()
+        .and_then(crate::core::image_swap::ImageSwapEffect::parse) else {
+        return -1;
+    };
+    let duration = std::time::Duration::from_millis(duration_ms as u64);
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::IMAGES, image_id)
+            || !free_handle(&crate::core::handle::IMAGES, new_image_id)
+        {
+            return NEOMACS_STALE_HANDLE;
+        }
+        let cmd = RenderCommand::ImageSwap { id: image_id, new_id: new_image_id, effect, duration };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return 0;
+    }
+
+    if handle.is_null() {
+        return -1;
+    }
+    let display = &mut *handle;
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref mut backend) = display.winit_backend {
+        if let Some(renderer) = backend.renderer_mut() {
+            if !live_handle(&crate::core::handle::IMAGES, image_id)
+                || !free_handle(&crate::core::handle::IMAGES, new_image_id)
+            {
+                return NEOMACS_STALE_HANDLE;
+            }
+            renderer.swap_image(image_id, new_image_id, effect, duration);
+            return 0;
+        }
+    }
+    -1
+}
+
+/// Mark a rect of the frame being built as chrome of element `element`,
+/// an index into tab bar, tooltip, popup menu and child frame, so its
+/// nine-patch image is drawn there
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_add_chrome_region(
+    handle: *mut NeomacsDisplay,
+    element: c_int,
+    x: c_int,
+    y: c_int,
+    width: c_int,
+    height: c_int,
+) {
+    if handle.is_null() {
+        return;
+    }
+    let Some(element) = crate::core::nine_patch::ChromeElement::from_index(element) else {
+        return;
+    };
+    let display = &mut *handle;
+    if display.use_hybrid {
+        display.frame_glyphs.add_chrome_region(
+            element,
+            Rect::new(x as f32, y as f32, width as f32, height as f32),
+        );
+    }
+}
+
+/// Set a floating video at a specific screen position
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_floating_video(
     handle: *mut NeomacsDisplay,
     video_id: u32,
     x: c_int,
@@ -1761,6 +2765,17 @@ pub unsafe extern "C" fn neomacs_display_set_floating_image(
         width as f32,
         height as f32,
     );
+
+    if let Some(ref state) = THREADED_STATE {
+        let cmd = RenderCommand::ImageSetFloating {
+            id: image_id,
+            x: x as f32,
+            y: y as f32,
+            width: width as f32,
+            height: height as f32,
+        };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
 }
 
 /// Remove a floating image
@@ -1775,6 +2790,11 @@ pub unsafe extern "C" fn neomacs_display_clear_floating_image(
 
     let display = &mut *handle;
     display.get_target_scene().remove_floating_image(image_id);
+
+    if let Some(ref state) = THREADED_STATE {
+        let cmd = RenderCommand::ImageRemoveFloating { id: image_id };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
 }
 
 /// Clear a rectangular area of the display
@@ -2141,6 +3161,280 @@ pub unsafe extern "C" fn neomacs_display_hide_tooltip(
     }
 }
 
+/// Show a character grid overlay (emoji picker, glyph table).
+///
+/// `items` is a newline-separated list of cell strings; each may be a
+/// multi-codepoint sequence.  Only the `rows` visible rows are drawn.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_show_char_grid(
+    handle: *mut NeomacsDisplay,
+    x: f32,
+    y: f32,
+    columns: u32,
+    rows: u32,
+    cell_size: f32,
+    items: *const c_char,
+    fg_r: f32, fg_g: f32, fg_b: f32,
+    bg_r: f32, bg_g: f32, bg_b: f32,
+    sel_r: f32, sel_g: f32, sel_b: f32,
+) {
+    if items.is_null() {
+        return;
+    }
+    let items: Vec<String> = match CStr::from_ptr(items).to_str() {
+        Ok(s) => s.split('\n').filter(|s| !s.is_empty()).map(String::from).collect(),
+        Err(_) => return,
+    };
+    if let Some(display) = handle.as_mut() {
+        display.char_grid = Some(crate::text::CharGridLayout::new(
+            x, y, columns as usize, rows as usize, cell_size, items.len(),
+        ));
+    }
+    let cmd = RenderCommand::ShowCharGrid {
+        x, y, columns, rows, cell_size, items,
+        fg: (fg_r, fg_g, fg_b),
+        bg: (bg_r, bg_g, bg_b),
+        selected_bg: (sel_r, sel_g, sel_b),
+    };
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
+/// Update the selected cell (-1 = none) and first visible row of the
+/// character grid.  The grid scrolls to keep the selection visible.
+/// Returns the first visible row after clamping, or -1 if no grid is shown.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_update_char_grid(
+    handle: *mut NeomacsDisplay,
+    selected: c_int,
+    scroll_row: u32,
+) -> c_int {
+    let Some(layout) = handle.as_mut().and_then(|d| d.char_grid.as_mut()) else {
+        return -1;
+    };
+    layout.apply(selected, scroll_row);
+    let cmd = RenderCommand::UpdateCharGrid { selected, scroll_row };
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+    layout.scroll_row as c_int
+}
+
+/// Hide the character grid overlay.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_hide_char_grid(
+    handle: *mut NeomacsDisplay,
+) {
+    if let Some(display) = handle.as_mut() {
+        display.char_grid = None;
+    }
+    let cmd = RenderCommand::HideCharGrid;
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
+/// Index of the character grid cell at (x, y), or -1 if none.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_char_grid_index_at(
+    handle: *mut NeomacsDisplay,
+    x: f32,
+    y: f32,
+) -> c_int {
+    handle.as_ref()
+        .and_then(|d| d.char_grid.as_ref())
+        .and_then(|layout| layout.index_at(x, y))
+        .map_or(-1, |i| i as c_int)
+}
+
+/// Replace `removed` minimap lines of a buffer starting at line `first`
+/// with `count` new line summaries, shifting the start positions of the
+/// following lines by `pos_delta`.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_minimap_update(
+    handle: *mut NeomacsDisplay,
+    buffer_id: u64,
+    first: u32,
+    removed: u32,
+    pos_delta: i64,
+    lines: *const crate::core::minimap::MinimapLine,
+    count: u32,
+) {
+    let lines = if lines.is_null() || count == 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(lines, count as usize).to_vec()
+    };
+    if let Some(display) = handle.as_mut() {
+        display.minimap.update(buffer_id, first as usize, removed as usize, &lines, pos_delta);
+    }
+    let cmd = RenderCommand::MinimapUpdate { buffer_id, first, removed, pos_delta, lines };
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
+/// Drop the minimap line summaries of a buffer.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_minimap_forget(
+    handle: *mut NeomacsDisplay,
+    buffer_id: u64,
+) {
+    if let Some(display) = handle.as_mut() {
+        display.minimap.forget(buffer_id);
+    }
+    let cmd = RenderCommand::MinimapForget { buffer_id };
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
+/// Buffer position to scroll a window to when its minimap viewport is
+/// dragged to offset `y` in a strip `height` pixels tall.  Buffers without
+/// line summaries map `y` proportionally onto `buffer_size` characters.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_minimap_target(
+    handle: *mut NeomacsDisplay,
+    buffer_id: u64,
+    buffer_size: i64,
+    y: f32,
+    height: f32,
+    view_lines: u32,
+    line_height: f32,
+) -> i64 {
+    use crate::core::minimap::MinimapLayout;
+    let Some(display) = handle.as_ref() else {
+        return -1;
+    };
+    let lines = display.minimap.lines(buffer_id);
+    if lines.is_empty() {
+        let frac = (y / height.max(1.0)).clamp(0.0, 1.0);
+        return 1 + (frac * (buffer_size - 1).max(0) as f32) as i64;
+    }
+    let line = MinimapLayout::drag_target(lines.len(), view_lines as usize, height, line_height, y);
+    lines[line].start
+}
+
+/// Queue `len` bytes of UTF-8 `text`, starting at buffer position `base`,
+/// for syntax highlighting as `language` on the background thread.
+///
+/// Returns 1 if queued, 0 if the language is not supported.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_highlight_request(
+    handle: *mut NeomacsDisplay,
+    buffer_id: u64,
+    language: *const c_char,
+    text: *const u8,
+    len: usize,
+    base: i64,
+) -> c_int {
+    let Some(display) = handle.as_mut() else { return 0 };
+    if language.is_null() || (text.is_null() && len > 0) {
+        return 0;
+    }
+    let Ok(language) = CStr::from_ptr(language).to_str() else { return 0 };
+    let text = if len == 0 {
+        String::new()
+    } else {
+        String::from_utf8_lossy(std::slice::from_raw_parts(text, len)).into_owned()
+    };
+    display.highlight.request(buffer_id, language, text, base) as c_int
+}
+
+/// Shift highlight spans after the text in `[beg, old_end)` became
+/// `[beg, new_end)`.  Spans overlapping the edit are dropped.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_highlight_edit(
+    handle: *mut NeomacsDisplay,
+    buffer_id: u64,
+    beg: i64,
+    old_end: i64,
+    new_end: i64,
+) {
+    if let Some(display) = handle.as_mut() {
+        display.highlight.store_mut().edit(buffer_id, beg, old_end, new_end);
+    }
+}
+
+/// Drop the highlight spans of a buffer.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_highlight_forget(
+    handle: *mut NeomacsDisplay,
+    buffer_id: u64,
+) {
+    if let Some(display) = handle.as_mut() {
+        display.highlight.store_mut().forget(buffer_id);
+    }
+}
+
+/// Set the color (0xRRGGBB) and weight of a highlight category, or
+/// leave it in the default face when `enabled` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_highlight_set_face(
+    handle: *mut NeomacsDisplay,
+    kind: c_int,
+    enabled: c_int,
+    fg: u32,
+    bold: c_int,
+    italic: c_int,
+) {
+    use crate::layout::highlight::{HighlightFace, HighlightKind};
+    let Some(display) = handle.as_mut() else { return };
+    let Some(kind) = u8::try_from(kind).ok().and_then(HighlightKind::from_u8) else {
+        return;
+    };
+    let face = (enabled != 0).then(|| HighlightFace {
+        fg: Color::from_pixel(fg),
+        bold: bold != 0,
+        italic: italic != 0,
+    });
+    display.highlight.store_mut().set_face(kind, face);
+}
+
+/// Merge finished highlight jobs.  Returns the number of jobs still
+/// pending and stores the number merged in `out_merged`.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_highlight_poll(
+    handle: *mut NeomacsDisplay,
+    out_merged: *mut c_int,
+) -> c_int {
+    let Some(display) = handle.as_mut() else { return 0 };
+    let (merged, pending) = display.highlight.poll();
+    if !out_merged.is_null() {
+        *out_merged = merged as c_int;
+    }
+    pending as c_int
+}
+
+/// Load grouped emoji data as tab-separated lines
+/// (`category TAB subgroup TAB emoji TAB name`).
+///
+/// `path` may be NULL to search `$NEOMACS_EMOJI_DATA` and the system
+/// `emoji-test.txt`.  Free the result with `neomacs_display_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_emoji_data(
+    path: *const c_char,
+) -> *mut c_char {
+    let path = if path.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(path).to_str() {
+            Ok(s) => Some(std::path::PathBuf::from(s)),
+            Err(_) => return ptr::null_mut(),
+        }
+    };
+    let data = crate::text::EmojiData::load(path.as_deref());
+    CString::new(data.to_tsv()).map_or(ptr::null_mut(), CString::into_raw)
+}
+
 /// Trigger visual bell flash effect.
 #[cfg(feature = "winit-backend")]
 #[no_mangle]
@@ -2361,10 +3655,12 @@ pub unsafe extern "C" fn neomacs_display_set_show_whitespace(
             let _ = state.emacs_comms.cmd_tx.try_send(cmd);
         }
 }
-/// Configure inactive window dimming (threaded mode)
-effect_setter!(neomacs_display_set_inactive_dim(enabled: c_int, opacity: c_int) |effects| {
+/// Configure inactive window dimming (threaded mode).  OPACITY and
+/// SATURATION are percentages.
+effect_setter!(neomacs_display_set_inactive_dim(enabled: c_int, opacity: c_int, saturation: c_int) |effects| {
         effects.inactive_dim.enabled = enabled != 0;
                     effects.inactive_dim.opacity = opacity as f32 / 100.0;
+                    effects.inactive_dim.saturation = (saturation as f32 / 100.0).clamp(0.0, 1.0);
 });
 
 /// Configure cursor glow effect (threaded mode)
@@ -2389,9 +3685,10 @@ effect_setter!(neomacs_display_set_focus_mode(enabled: c_int, opacity: c_int) |e
 });
 
 /// Configure minimap (code overview column)
-effect_setter!(neomacs_display_set_minimap(enabled: c_int, width: c_int) |effects| {
+effect_setter!(neomacs_display_set_minimap(enabled: c_int, width: c_int, line_height: c_int) |effects| {
         effects.minimap.enabled = enabled != 0;
                     effects.minimap.width = width as f32;
+                    effects.minimap.line_height = line_height.max(1) as f32;
 });
 
 /// Configure typing ripple effect
@@ -2806,6 +4103,30 @@ effect_setter!(neomacs_display_set_resize_padding(enabled: c_int, duration_ms: c
                     effects.resize_padding.max = max_padding as f32;
 });
 
+/// Configure text coverage correction (gamma ×100, contrast in percent)
+effect_setter!(neomacs_display_set_text_gamma(enabled: c_int, gamma_x100: c_int, contrast_pct: c_int) |effects| {
+        effects.text_gamma.enabled = enabled != 0;
+                    effects.text_gamma.gamma = (gamma_x100 as f32 / 100.0).clamp(0.5, 4.0);
+                    effects.text_gamma.contrast = (contrast_pct as f32 / 100.0).clamp(0.0, 1.0);
+});
+
+/// Configure text inversion under the filled box cursor
+effect_setter!(neomacs_display_set_cursor_inversion(enabled: c_int, has_color: c_int, r: c_int, g: c_int, b: c_int) |effects| {
+        effects.cursor_inversion.enabled = enabled != 0;
+                    effects.cursor_inversion.complement_fg = if has_color != 0 {
+                        Some((r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+                    } else {
+                        None
+                    };
+});
+
+/// Configure region highlight as merged rounded selection runs
+effect_setter!(neomacs_display_set_selection_runs(enabled: c_int, face_id: c_int, radius: c_int) |effects| {
+        effects.selection_runs.enabled = enabled != 0;
+                    effects.selection_runs.face_id = face_id.max(0) as u32;
+                    effects.selection_runs.radius = radius.max(0) as f32;
+});
+
 /// Configure cursor error pulse (brief color flash on bell)
 effect_setter!(neomacs_display_set_cursor_error_pulse(enabled: c_int, r: c_int, g: c_int, b: c_int, duration_ms: c_int) |effects| {
         effects.cursor_error_pulse.enabled = enabled != 0;
@@ -2995,6 +4316,27 @@ pub unsafe extern "C" fn neomacs_display_set_cursor_animation(
     }
 }
 
+/// Select a Neovide-style cursor animation mode by name: "none",
+/// "smooth", "railgun", "torpedo", "pixiedust", "sonicboom", "ripple" or
+/// "wireframe".  Each mode enables the matching cursor effect.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_cursor_animation_mode(
+    _handle: *mut NeomacsDisplay,
+    mode: *const c_char,
+) {
+    if mode.is_null() {
+        return;
+    }
+    let name = CStr::from_ptr(mode).to_string_lossy();
+    let mode = crate::core::cursor_animation::CursorAnimationMode::from_str(&name);
+    let cmd = RenderCommand::UpdateEffect(EffectUpdater(Box::new(move |effects| {
+        effects.set_cursor_animation_mode(mode);
+    })));
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
 /// Configure all animation settings
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_set_animation_config(
@@ -3034,6 +4376,91 @@ pub unsafe extern "C" fn neomacs_display_set_animation_config(
     }
 }
 
+/// Define a named keyframe animation curve from its string form
+/// (`"TIME:VALUE[:EASING] ..."` or a bare easing name).  A NULL or empty
+/// `spec` removes the curve.  Curves named "cursor", "cursor-size",
+/// "crossfade" and "scroll" replace the easing of those animations.
+/// Returns 0 on success, -1 if `spec` does not parse.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_animation_curve(
+    _handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    spec: *const c_char,
+) -> c_int {
+    use crate::core::animation::AnimationCurve;
+    if name.is_null() {
+        return -1;
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(s) => s.to_string(),
+        Err(_) => return -1,
+    };
+    let spec = if spec.is_null() { "" } else { CStr::from_ptr(spec).to_str().unwrap_or("") };
+    let curve = if spec.trim().is_empty() {
+        None
+    } else {
+        match AnimationCurve::parse(spec) {
+            Ok(curve) => Some(curve),
+            Err(e) => {
+                log::warn!("Animation curve '{}': {}", name, e);
+                return -1;
+            }
+        }
+    };
+    let cmd = RenderCommand::SetAnimationCurve { name, curve };
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+    0
+}
+
+/// Animate a property of a floating image, WebKit view or terminal.
+///
+/// KIND is 0 (image), 1 (webkit) or 2 (terminal).  PROPERTY is one of
+/// "x", "y", "width", "height", "opacity" or "corner-radius"; EASING is an
+/// easing name (NULL means ease-out).  A zero duration jumps to TO.  A
+/// nonzero NOTIFY is sent back in an animation-completed event when the
+/// animation ends.
+/// Returns 0 on success, -1 on an unknown or unsupported argument.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_animate_floating(
+    _handle: *mut NeomacsDisplay,
+    kind: c_int,
+    id: u32,
+    property: *const c_char,
+    to: f32,
+    duration_ms: u32,
+    easing: *const c_char,
+    notify: u32,
+) -> c_int {
+    use crate::core::animation::{Easing, FloatingKind, FloatingProperty};
+    if property.is_null() || !to.is_finite() {
+        return -1;
+    }
+    let Some(kind) = u8::try_from(kind).ok().and_then(FloatingKind::from_u8) else {
+        return -1;
+    };
+    let Some(property) = CStr::from_ptr(property).to_str().ok().and_then(FloatingProperty::from_name) else {
+        return -1;
+    };
+    if !kind.supports(property) {
+        return -1;
+    }
+    let easing = if easing.is_null() {
+        Easing::EaseOut
+    } else {
+        match CStr::from_ptr(easing).to_str().ok().and_then(Easing::from_name) {
+            Some(e) => e,
+            None => return -1,
+        }
+    };
+    let cmd = RenderCommand::AnimateFloating { kind, id, property, to, duration_ms, easing, notify };
+    if let Some(ref state) = THREADED_STATE {
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+    0
+}
+
 /// Check if animations are active
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_has_animations(handle: *mut NeomacsDisplay) -> c_int {
@@ -3117,21 +4544,31 @@ thread_local! {
     static WPE_BACKEND: RefCell<Option<WpeBackend>> = const { RefCell::new(None) };
 }
 
-/// Atomic counter for generating image IDs in threaded mode
-#[cfg(feature = "winit-backend")]
-static IMAGE_ID_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
-
-/// Atomic counter for generating WebKit view IDs in threaded mode
-#[cfg(feature = "wpe-webkit")]
-static WEBKIT_VIEW_ID_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
+/// Returned by resource calls given an id whose resource was freed
+pub const NEOMACS_STALE_HANDLE: c_int = -2;
 
-/// Atomic counter for generating video IDs in threaded mode
-#[cfg(feature = "video")]
-static VIDEO_ID_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
+/// Check a resource id passed in from Emacs.  A stale id is recorded in
+/// the error log so the mistake is visible instead of silently ignored.
+fn live_handle(table: &crate::core::handle::HandleTable, id: u32) -> bool {
+    match table.check(id) {
+        Ok(()) => true,
+        Err(e) => {
+            crate::core::error_report::warning(table.kind(), Some(id), e.to_string());
+            false
+        }
+    }
+}
 
-/// Atomic counter for generating terminal IDs in threaded mode
-#[cfg(feature = "neo-term")]
-static TERMINAL_ID_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
+/// Release a resource id, recording a double free like a stale id.
+fn free_handle(table: &crate::core::handle::HandleTable, id: u32) -> bool {
+    match table.free(id) {
+        Ok(()) => true,
+        Err(e) => {
+            crate::core::error_report::warning(table.kind(), Some(id), e.to_string());
+            false
+        }
+    }
+}
 
 // ============================================================================
 // Terminal (neo-term) FFI
@@ -3151,7 +4588,9 @@ pub unsafe extern "C" fn neomacs_display_terminal_create(
     shell: *const c_char,
 ) -> u32 {
     if let Some(ref state) = THREADED_STATE {
-        let id = TERMINAL_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let Ok(id) = crate::core::handle::TERMINALS.alloc() else {
+            return 0;
+        };
         let shell_str = if shell.is_null() {
             None
         } else {
@@ -3171,25 +4610,216 @@ pub unsafe extern "C" fn neomacs_display_terminal_create(
     0
 }
 
+/// Create a terminal running a shell (or `command`, if not NULL) on an
+/// SSH host.  `destination` is `[user@]host[:port]`; `identity`, if not
+/// NULL, is a private key file tried after the SSH agent.  The host must
+/// already be in ~/.ssh/known_hosts.
+///
+/// Returns terminal ID (>0 on success, 0 on failure).  Connecting
+/// happens in the background; if it fails the terminal exits.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_create_ssh(
+    cols: u16,
+    rows: u16,
+    mode: u8,
+    destination: *const c_char,
+    command: *const c_char,
+    identity: *const c_char,
+) -> u32 {
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    let opt_str = |p: *const c_char| {
+        (!p.is_null()).then(|| std::ffi::CStr::from_ptr(p).to_string_lossy().into_owned())
+    };
+    let Some(destination) = opt_str(destination) else {
+        return 0;
+    };
+    #[cfg(feature = "neo-term-ssh")]
+    {
+        let Some(mut target) = crate::terminal::transport::SshTarget::parse(&destination) else {
+            crate::core::error_report::error(
+                crate::core::error_report::ErrorKind::Terminal,
+                None,
+                format!("bad SSH destination: {}", destination),
+            );
+            return 0;
+        };
+        target.command = opt_str(command);
+        target.identity = opt_str(identity).map(std::path::PathBuf::from);
+        let Ok(id) = crate::core::handle::TERMINALS.alloc() else {
+            return 0;
+        };
+        let cmd = RenderCommand::TerminalCreateSsh { id, cols, rows, mode, target };
+        if state.emacs_comms.cmd_tx.try_send(cmd).is_err() {
+            let _ = crate::core::handle::TERMINALS.free(id);
+            return 0;
+        }
+        id
+    }
+    #[cfg(not(feature = "neo-term-ssh"))]
+    {
+        let _ = (state, cols, rows, mode, command, identity);
+        crate::core::error_report::error(
+            crate::core::error_report::ErrorKind::Terminal,
+            None,
+            format!("cannot connect to {}: built without SSH support", destination),
+        );
+        0
+    }
+}
+
+/// Returned by terminal writes while the terminal's input queue is over
+/// its limit.  Nothing was queued; retry once `terminal_input_status`
+/// shows the queue has drained.
+pub const NEOMACS_TERMINAL_BUSY: c_int = -3;
+
+/// Whether a terminal's input queue is over its limit.
+#[cfg(feature = "neo-term")]
+fn terminal_input_full(state: &ThreadedState, terminal_id: u32) -> bool {
+    state
+        .shared_terminals
+        .lock()
+        .ok()
+        .and_then(|shared| shared.get(&terminal_id).map(|t| t.input.is_full()))
+        .unwrap_or(false)
+}
+
+/// Queue `bytes` as input for a terminal.
+#[cfg(feature = "neo-term")]
+fn queue_terminal_input(terminal_id: u32, bytes: Vec<u8>) -> c_int {
+    let Some(state) = (unsafe { THREADED_STATE.as_ref() }) else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    if terminal_input_full(state, terminal_id) {
+        return NEOMACS_TERMINAL_BUSY;
+    }
+    let cmd = RenderCommand::TerminalWrite {
+        id: terminal_id,
+        data: bytes,
+    };
+    match state.emacs_comms.cmd_tx.try_send(cmd) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// Write input data to a terminal (keyboard input from user).
+///
+/// Never blocks: the data is queued and written to the PTY by a worker
+/// thread.  Returns 0 on success, `NEOMACS_TERMINAL_BUSY` if too much
+/// input is already queued, `NEOMACS_STALE_HANDLE` for a destroyed
+/// terminal, -1 on other failures.
 #[cfg(feature = "neo-term")]
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_terminal_write(
     terminal_id: u32,
     data: *const u8,
     len: usize,
-) {
+) -> c_int {
     if data.is_null() || len == 0 {
-        return;
+        return 0;
     }
-    if let Some(ref state) = THREADED_STATE {
-        let bytes = std::slice::from_raw_parts(data, len).to_vec();
-        let cmd = RenderCommand::TerminalWrite {
-            id: terminal_id,
-            data: bytes,
-        };
-        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    let bytes = std::slice::from_raw_parts(data, len).to_vec();
+    queue_terminal_input(terminal_id, bytes)
+}
+
+/// Write `count` pieces of input to a terminal in one batch.
+/// `chunks[i]` points to `lens[i]` bytes.  Returns as
+/// `neomacs_display_terminal_write`; either all pieces are queued or none.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_write_many(
+    terminal_id: u32,
+    chunks: *const *const u8,
+    lens: *const usize,
+    count: usize,
+) -> c_int {
+    if chunks.is_null() || lens.is_null() || count == 0 {
+        return 0;
+    }
+    let chunks = std::slice::from_raw_parts(chunks, count);
+    let lens = std::slice::from_raw_parts(lens, count);
+    let mut bytes = Vec::with_capacity(lens.iter().sum());
+    for (&chunk, &len) in chunks.iter().zip(lens) {
+        if !chunk.is_null() && len > 0 {
+            bytes.extend_from_slice(std::slice::from_raw_parts(chunk, len));
+        }
+    }
+    if bytes.is_empty() {
+        return 0;
+    }
+    queue_terminal_input(terminal_id, bytes)
+}
+
+/// Input queue state of a terminal
+#[repr(C)]
+pub struct NeomacsTerminalInputStatus {
+    /// Bytes queued but not yet written to the PTY
+    pub pending: u64,
+    /// Queue size above which writes return `NEOMACS_TERMINAL_BUSY`
+    pub limit: u64,
+    /// Bytes written to the PTY so far
+    pub written: u64,
+}
+
+/// Fill `status` with a terminal's input queue state.  Returns 0 on
+/// success, `NEOMACS_STALE_HANDLE` for a destroyed terminal, -1 if the
+/// terminal is not running (yet).
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_input_status(
+    terminal_id: u32,
+    status: *mut NeomacsTerminalInputStatus,
+) -> c_int {
+    if status.is_null() {
+        return -1;
+    }
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let Some(input) = state
+        .shared_terminals
+        .lock()
+        .ok()
+        .and_then(|shared| shared.get(&terminal_id).map(|t| t.input.status()))
+    else {
+        return -1;
+    };
+    *status = NeomacsTerminalInputStatus {
+        pending: input.pending as u64,
+        limit: input.limit as u64,
+        written: input.written,
+    };
+    0
+}
+
+/// Drop a terminal's queued input that has not been written to the PTY
+/// yet, e.g. to abort a large paste.  Returns the number of bytes
+/// dropped, `NEOMACS_STALE_HANDLE` for a destroyed terminal, -1 if the
+/// terminal is not running.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_cancel_input(terminal_id: u32) -> i64 {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE as i64;
     }
+    state
+        .shared_terminals
+        .lock()
+        .ok()
+        .and_then(|shared| shared.get(&terminal_id).map(|t| t.input.cancel() as i64))
+        .unwrap_or(-1)
 }
 
 /// Resize a terminal.
@@ -3201,6 +4831,9 @@ pub unsafe extern "C" fn neomacs_display_terminal_resize(
     rows: u16,
 ) {
     if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+            return;
+        }
         let cmd = RenderCommand::TerminalResize {
             id: terminal_id,
             cols,
@@ -3217,11 +4850,49 @@ pub unsafe extern "C" fn neomacs_display_terminal_destroy(
     terminal_id: u32,
 ) {
     if let Some(ref state) = THREADED_STATE {
+        if !free_handle(&crate::core::handle::TERMINALS, terminal_id) {
+            return;
+        }
         let cmd = RenderCommand::TerminalDestroy { id: terminal_id };
         let _ = state.emacs_comms.cmd_tx.try_send(cmd);
     }
 }
 
+/// Stop drawing a terminal while keeping its shell and grid alive.
+/// The terminal keeps its id and still accepts input.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_detach(terminal_id: u32) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    match state.emacs_comms.cmd_tx.try_send(RenderCommand::TerminalDetach { id: terminal_id }) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Draw a detached terminal again.  `mode`: 0=Window, 1=Inline,
+/// 2=Floating.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_attach(terminal_id: u32, mode: u8) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let cmd = RenderCommand::TerminalAttach { id: terminal_id, mode };
+    match state.emacs_comms.cmd_tx.try_send(cmd) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// Set floating terminal position and opacity.
 #[cfg(feature = "neo-term")]
 #[no_mangle]
@@ -3232,6 +4903,9 @@ pub unsafe extern "C" fn neomacs_display_terminal_set_float(
     opacity: f32,
 ) {
     if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+            return;
+        }
         let cmd = RenderCommand::TerminalSetFloat {
             id: terminal_id,
             x,
@@ -3242,6 +4916,96 @@ pub unsafe extern "C" fn neomacs_display_terminal_set_float(
     }
 }
 
+/// Set the font of a terminal.  `family` NULL or empty and `size` <= 0
+/// follow the frame font.  Cell metrics are measured from the font and
+/// reported to the terminal's programs as its pixel size.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_set_font(
+    terminal_id: u32,
+    family: *const c_char,
+    size: f32,
+) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let family = if family.is_null() {
+        None
+    } else {
+        std::ffi::CStr::from_ptr(family)
+            .to_str()
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let cmd = RenderCommand::TerminalSetFont {
+        id: terminal_id,
+        family,
+        size: (size > 0.0).then_some(size),
+    };
+    match state.emacs_comms.cmd_tx.try_send(cmd) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Set what is drawn behind a terminal's cells.  `kind` 0 is the solid
+/// theme background, 1 the cached image `image_id` darkened by `amount`
+/// (0.0-1.0), 2 an acrylic background at opacity `amount`, blurred if
+/// `blur` is nonzero.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_set_background(
+    terminal_id: u32,
+    kind: c_int,
+    image_id: u32,
+    amount: f32,
+    blur: c_int,
+) -> c_int {
+    use crate::terminal::TerminalBackground;
+
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let amount = amount.clamp(0.0, 1.0);
+    let background = match kind {
+        0 => TerminalBackground::Solid,
+        1 => TerminalBackground::Image { image_id, dim: amount },
+        2 => TerminalBackground::Acrylic { opacity: amount, blur: blur != 0 },
+        _ => return -1,
+    };
+    let cmd = RenderCommand::TerminalSetBackground { id: terminal_id, background };
+    match state.emacs_comms.cmd_tx.try_send(cmd) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Set whether a terminal guesses the echo of typed input: `mode` 0 never,
+/// 1 always, 2 while the echo is slow to come back.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_set_local_echo(terminal_id: u32, mode: c_int) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let mode = crate::terminal::local_echo::EchoMode::from_u8(mode.clamp(0, 2) as u8);
+    let cmd = RenderCommand::TerminalSetLocalEcho { id: terminal_id, mode };
+    match state.emacs_comms.cmd_tx.try_send(cmd) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// Get visible text from a terminal.
 ///
 /// Returns a malloc'd C string (caller must free with `free()`).
@@ -3254,10 +5018,13 @@ pub unsafe extern "C" fn neomacs_display_terminal_get_text(
     #[cfg(feature = "winit-backend")]
     {
         if let Some(ref state) = THREADED_STATE {
+            if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+                return std::ptr::null_mut();
+            }
             if let Ok(shared) = state.shared_terminals.lock() {
-                if let Some(term_arc) = shared.get(&terminal_id) {
+                if let Some(terminal) = shared.get(&terminal_id) {
                     use alacritty_terminal::grid::Dimensions;
-                    let term = term_arc.lock();
+                    let term = terminal.term.lock();
                     let grid = term.grid();
                     let cols = grid.columns();
                     let rows = grid.screen_lines();
@@ -3278,6 +5045,156 @@ pub unsafe extern "C" fn neomacs_display_terminal_get_text(
     std::ptr::null_mut()
 }
 
+/// Get the screen lines of a terminal changed since the last call, for
+/// mirroring into a buffer.  With `all` nonzero every line is returned.
+///
+/// Returns a malloc'd C string (caller must free with `free()`): the
+/// number of screen lines on the first line, then one `ROW\tTEXT` line
+/// per changed row, where TEXT carries SGR escape sequences for colors
+/// and attributes.  Returns NULL on failure.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_changed_lines(
+    terminal_id: u32,
+    all: c_int,
+) -> *mut c_char {
+    use std::fmt::Write;
+
+    let Some(ref state) = THREADED_STATE else {
+        return std::ptr::null_mut();
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return std::ptr::null_mut();
+    }
+    let Some(terminal) = state.shared_terminals.lock().ok().and_then(|s| s.get(&terminal_id).cloned()) else {
+        return std::ptr::null_mut();
+    };
+    let mut mirror = terminal.mirror.lock().unwrap_or_else(|e| e.into_inner());
+    if all != 0 {
+        mirror.reset();
+    }
+    let term = terminal.term.lock();
+    let rows = alacritty_terminal::grid::Dimensions::screen_lines(term.grid());
+    let lines = mirror.changed_lines(&*term);
+    drop(term);
+
+    let mut out = format!("{}\n", rows);
+    for (row, text) in lines {
+        let _ = writeln!(out, "{}\t{}", row, text);
+    }
+    match CString::new(out) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Get the input being typed at the shell prompt of a terminal.
+///
+/// Returns a malloc'd C string (caller must free with `free()`):
+/// `ROW\tCOL\tCURSOR\n` followed by the input text, where ROW and COL
+/// are where input starts on the screen (ROW negative in scrollback) and
+/// CURSOR is the cursor's offset in the text in characters.  Returns
+/// NULL if the shell is not known to be reading input.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_input_region(terminal_id: u32) -> *mut c_char {
+    let Some(ref state) = THREADED_STATE else {
+        return std::ptr::null_mut();
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return std::ptr::null_mut();
+    }
+    let Some(terminal) = state.shared_terminals.lock().ok().and_then(|s| s.get(&terminal_id).cloned()) else {
+        return std::ptr::null_mut();
+    };
+    // Same lock order as the reader thread: terminal, then prompt
+    let term = terminal.term.lock();
+    let region = terminal.prompt.lock().unwrap_or_else(|e| e.into_inner()).input_region(&*term);
+    drop(term);
+    let Some(region) = region else {
+        return std::ptr::null_mut();
+    };
+    let out = format!("{}\t{}\t{}\n{}", region.row, region.col, region.cursor, region.text);
+    match CString::new(out) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Turn guessing prompts by their look on or off for a terminal whose
+/// shell sends no OSC 133 marks.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_terminal_set_prompt_heuristics(terminal_id: u32, on: c_int) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::TERMINALS, terminal_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let Some(terminal) = state.shared_terminals.lock().ok().and_then(|s| s.get(&terminal_id).cloned()) else {
+        return -1;
+    };
+    terminal.prompt.lock().unwrap_or_else(|e| e.into_inner()).heuristics = on != 0;
+    0
+}
+
+/// Ids of tmux control-mode clients
+#[cfg(feature = "neo-term")]
+static NEXT_TMUX_CLIENT: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
+
+/// Run `command`, which must start tmux in control mode (`tmux -C ...`,
+/// possibly through ssh), and give each of its panes a terminal.
+/// Returns the client id (0 on failure).  Pane terminals are announced
+/// with TMUX_PANE_ADDED events.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_tmux_connect(command: *const c_char) -> u32 {
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    if command.is_null() {
+        return 0;
+    }
+    let command = std::ffi::CStr::from_ptr(command).to_string_lossy().into_owned();
+    let id = NEXT_TMUX_CLIENT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    match state.emacs_comms.cmd_tx.try_send(RenderCommand::TmuxConnect { id, command }) {
+        Ok(()) => id,
+        Err(_) => 0,
+    }
+}
+
+/// Send a command line to a tmux client.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_tmux_command(client: u32, command: *const c_char) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    if command.is_null() {
+        return -1;
+    }
+    let command = std::ffi::CStr::from_ptr(command).to_string_lossy().into_owned();
+    match state.emacs_comms.cmd_tx.try_send(RenderCommand::TmuxCommand { id: client, command }) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Detach a tmux client.  The tmux session keeps running; the pane
+/// terminals are closed with TMUX_PANE_CLOSED events.
+#[cfg(feature = "neo-term")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_tmux_disconnect(client: u32) -> c_int {
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    match state.emacs_comms.cmd_tx.try_send(RenderCommand::TmuxDisconnect { id: client }) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// Callback type for webkit new window requests
 pub type WebKitNewWindowCallback = extern "C" fn(u32, *const c_char, *const c_char) -> bool;
 
@@ -3440,18 +5357,39 @@ unsafe fn egl_get_current_display() -> *mut libc::c_void {
 /// Create a new WebKit view (threaded mode only)
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_webkit_create(
+    handle: *mut NeomacsDisplay,
+    width: c_int,
+    height: c_int,
+) -> u32 {
+    neomacs_display_webkit_create_with_profile(handle, width, height, ptr::null())
+}
+
+/// Create a new WebKit view in a named profile (threaded mode only).
+/// A NULL or empty profile name selects the default profile.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_webkit_create_with_profile(
     _handle: *mut NeomacsDisplay,
     width: c_int,
     height: c_int,
+    profile: *const c_char,
 ) -> u32 {
     #[cfg(feature = "wpe-webkit")]
     {
         if let Some(ref state) = THREADED_STATE {
-            let id = WEBKIT_VIEW_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let Ok(id) = crate::core::handle::WEBKIT_VIEWS.alloc() else {
+                return 0;
+            };
+            let profile = if profile.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(profile).to_string_lossy().into_owned())
+                    .filter(|p| !p.is_empty())
+            };
             let cmd = RenderCommand::WebKitCreate {
                 id,
                 width: width as u32,
                 height: height as u32,
+                profile,
             };
             let _ = state.emacs_comms.cmd_tx.try_send(cmd);
             return id;
@@ -3462,12 +5400,60 @@ pub unsafe extern "C" fn neomacs_display_webkit_create(
 
     #[cfg(not(feature = "wpe-webkit"))]
     {
-        let _ = (width, height);
+        let _ = (width, height, profile);
         log::warn!("WebKit support not compiled");
         0
     }
 }
 
+/// Define a named WebKit profile (threaded mode only).
+///
+/// `data_dir` and `cache_dir` may be NULL to use the XDG defaults for the
+/// profile name.  When `ephemeral` is non-zero the directories are ignored
+/// and all website data is kept in memory only.
+/// Returns 0 on success, -1 on failure.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_webkit_define_profile(
+    _handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    data_dir: *const c_char,
+    cache_dir: *const c_char,
+    ephemeral: c_int,
+) -> c_int {
+    if name.is_null() {
+        return -1;
+    }
+
+    #[cfg(feature = "wpe-webkit")]
+    {
+        let opt_str = |p: *const c_char| {
+            if p.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(p).to_string_lossy().into_owned()).filter(|s| !s.is_empty())
+            }
+        };
+        if let Some(ref state) = THREADED_STATE {
+            let cmd = RenderCommand::WebKitDefineProfile {
+                name: CStr::from_ptr(name).to_string_lossy().into_owned(),
+                data_dir: opt_str(data_dir),
+                cache_dir: opt_str(cache_dir),
+                ephemeral: ephemeral != 0,
+            };
+            let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+            return 0;
+        }
+        log::error!("webkit_define_profile: threaded mode not initialized");
+        return -1;
+    }
+
+    #[cfg(not(feature = "wpe-webkit"))]
+    {
+        let _ = (data_dir, cache_dir, ephemeral);
+        -1
+    }
+}
+
 /// Destroy a WebKit view (threaded mode only)
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_webkit_destroy(
@@ -3477,6 +5463,9 @@ pub unsafe extern "C" fn neomacs_display_webkit_destroy(
     #[cfg(feature = "wpe-webkit")]
     {
         if let Some(ref state) = THREADED_STATE {
+            if !free_handle(&crate::core::handle::WEBKIT_VIEWS, view_id) {
+                return NEOMACS_STALE_HANDLE;
+            }
             let cmd = RenderCommand::WebKitDestroy { id: view_id };
             let _ = state.emacs_comms.cmd_tx.try_send(cmd);
             return 0;
@@ -3506,6 +5495,9 @@ pub unsafe extern "C" fn neomacs_display_webkit_load_uri(
     #[cfg(feature = "wpe-webkit")]
     {
         if let Some(ref state) = THREADED_STATE {
+            if !live_handle(&crate::core::handle::WEBKIT_VIEWS, view_id) {
+                return NEOMACS_STALE_HANDLE;
+            }
             let url = CStr::from_ptr(uri).to_string_lossy().into_owned();
             let cmd = RenderCommand::WebKitLoadUri { id: view_id, url };
             let _ = state.emacs_comms.cmd_tx.try_send(cmd);
@@ -4156,57 +6148,723 @@ pub unsafe extern "C" fn neomacs_display_add_wpe_glyph(
 }
 
 // ============================================================================
-// Window Management FFI Functions
+// PDF Documents
 // ============================================================================
 
-/// Create a new window with the specified dimensions and title.
+/// Open a PDF document (async).
 ///
-/// Returns the window ID. The window will be created during the next poll_events call.
-/// Returns 0 if the backend is not available.
+/// Returns a document ID, or 0 on failure.  Poll
+/// `neomacs_display_pdf_page_count` until the document is ready.
+#[cfg(feature = "pdf")]
 #[no_mangle]
-pub extern "C" fn neomacs_display_create_window(
+pub unsafe extern "C" fn neomacs_display_pdf_open(
     _handle: *mut NeomacsDisplay,
-    _width: i32,
-    _height: i32,
-    _title: *const c_char,
+    path: *const c_char,
+    password: *const c_char,
 ) -> u32 {
-    // In threaded mode, the main window is created automatically by the render thread
-    // Return window ID 1 (the main window)
-    #[cfg(feature = "winit-backend")]
-    unsafe {
-        if THREADED_STATE.is_some() {
-            return 1; // Main window ID
-        }
+    if path.is_null() {
+        return 0;
     }
+    let path_str = match CStr::from_ptr(path).to_str() {
+        Ok(s) => s.to_string(),
+        Err(_) => return 0,
+    };
+    let password = if password.is_null() {
+        None
+    } else {
+        CStr::from_ptr(password).to_str().ok().map(|s| s.to_string())
+    };
 
+    if let Some(ref state) = THREADED_STATE {
+        let Ok(id) = crate::core::handle::PDFS.alloc() else {
+            return 0;
+        };
+        let cmd = RenderCommand::PdfOpen { id, path: path_str, password };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return id;
+    }
     0
 }
 
-/// Destroy a window by its ID.
+/// Get the number of pages in a PDF document.
+///
+/// Returns the page count, 0 while the document is still loading,
+/// or -1 if it failed to open or is unknown.
+#[cfg(feature = "pdf")]
 #[no_mangle]
-pub extern "C" fn neomacs_display_destroy_window(handle: *mut NeomacsDisplay, window_id: u32) {
-    let display = unsafe { &mut *handle };
+pub unsafe extern "C" fn neomacs_display_pdf_page_count(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+) -> c_int {
+    use crate::backend::wgpu::PdfState;
 
-    #[cfg(feature = "winit-backend")]
-    if let Some(ref mut backend) = display.winit_backend {
-        backend.destroy_window(window_id);
+    if let Some(ref state) = THREADED_STATE {
+        if !live_handle(&crate::core::handle::PDFS, doc_id) {
+            return NEOMACS_STALE_HANDLE;
+        }
+        if let Ok(docs) = state.shared_pdfs.lock() {
+            return match docs.get(&doc_id) {
+                Some(info) => match info.state {
+                    PdfState::Ready => info.page_count() as c_int,
+                    PdfState::Loading => 0,
+                    PdfState::Failed(_) => -1,
+                },
+                None => -1,
+            };
+        }
     }
+    -1
 }
 
-/// Show or hide a window.
+/// Get the size of a PDF page in points (1/72 inch).
+/// Returns 0 on success, -1 if the page is unknown.
+#[cfg(feature = "pdf")]
 #[no_mangle]
-pub extern "C" fn neomacs_display_show_window(
-    handle: *mut NeomacsDisplay,
-    window_id: u32,
-    visible: bool,
-) {
-    let display = unsafe { &mut *handle };
-
-    #[cfg(feature = "winit-backend")]
-    if let Some(ref backend) = display.winit_backend {
-        if let Some(state) = backend.get_window(window_id) {
-            state.window.set_visible(visible);
-        }
+pub unsafe extern "C" fn neomacs_display_pdf_page_size(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+    page: c_int,
+    width: *mut f32,
+    height: *mut f32,
+) -> c_int {
+    if width.is_null() || height.is_null() || page < 0 {
+        return -1;
+    }
+    if let Some(ref state) = THREADED_STATE {
+        if let Ok(docs) = state.shared_pdfs.lock() {
+            if let Some(size) = docs.get(&doc_id).and_then(|d| d.pages.get(page as usize)) {
+                *width = size.width;
+                *height = size.height;
+                return 0;
+            }
+        }
+    }
+    -1
+}
+
+/// Rasterize a PDF page as an image (async).
+///
+/// ZOOM is logical pixels per point; the page is rasterized at the
+/// display scale factor on top of that.  Returns an image ID usable with
+/// `neomacs_display_add_image_glyph`, or 0 on failure.  The image size is
+/// available immediately through `neomacs_display_get_image_size`.
+#[cfg(feature = "pdf")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pdf_render_page(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+    page: c_int,
+    zoom: f32,
+) -> u32 {
+    if page < 0 || zoom <= 0.0 {
+        return 0;
+    }
+    if let Some(ref state) = THREADED_STATE {
+        let size = match state.shared_pdfs.lock() {
+            Ok(docs) => docs.get(&doc_id).and_then(|d| d.pages.get(page as usize).copied()),
+            Err(_) => None,
+        };
+        let Some(size) = size else { return 0 };
+
+        let Ok(image_id) = crate::core::handle::IMAGES.alloc() else {
+
+            return 0;
+
+        };
+        // Publish the expected size now so layout does not wait for the
+        // raster; it is clamped as the raster is
+        if let Ok(mut dims) = state.image_dimensions.lock() {
+            dims.insert(image_id, size.pixels(zoom));
+        }
+        let cmd = RenderCommand::PdfRenderPage { id: doc_id, page: page as u32, zoom, image_id };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+        return image_id;
+    }
+    0
+}
+
+/// Typeset a TeX math snippet and upload it as an image.
+///
+/// SIZE is the font size in logical pixels and COLOR the foreground as
+/// 0xRRGGBB; DISPLAY selects display style.  Layout runs synchronously so
+/// the logical size and the ascent (percentage of the height above the
+/// baseline) are written to the out parameters immediately; the image
+/// rasterizes on the render thread.  Identical snippets reuse their image.
+///
+/// Returns the image ID, or 0 if the snippet does not typeset; the error
+/// is then stored in `out_error` (free with `neomacs_display_free_string`).
+#[cfg(feature = "math")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_math_render(
+    handle: *mut NeomacsDisplay,
+    tex: *const c_char,
+    display: c_int,
+    size: f32,
+    color: u32,
+    out_width: *mut c_int,
+    out_height: *mut c_int,
+    out_ascent: *mut c_int,
+    out_error: *mut *mut c_char,
+) -> u32 {
+    use crate::layout::math::MathImage;
+
+    let (Some(display_ref), false) = (handle.as_mut(), tex.is_null()) else {
+        return 0;
+    };
+    let Ok(tex) = CStr::from_ptr(tex).to_str() else {
+        return 0;
+    };
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    let display_style = display != 0;
+
+    let image = match display_ref.math.get(tex, display_style, size, color) {
+        Some(image) => image,
+        None => match display_ref.math.typeset(tex, display_style, size, color).and_then(|typeset| {
+            let image_id = crate::core::handle::IMAGES.alloc().map_err(|e| e.to_string())?;
+            Ok((typeset, image_id))
+        }) {
+            Ok((typeset, image_id)) => {
+                let image = MathImage {
+                    image_id,
+                    width: (typeset.width.ceil() as u32).max(1),
+                    height: (typeset.height.ceil() as u32).max(1),
+                    ascent: typeset.ascent_percent(),
+                };
+                if let Ok(mut dims) = state.image_dimensions.lock() {
+                    dims.insert(image.image_id, (image.width, image.height));
+                }
+                let cmd = RenderCommand::MathRasterize {
+                    image_id: image.image_id,
+                    frame: typeset.frame,
+                    source: crate::layout::math::MathSource {
+                        tex: tex.to_string(),
+                        display: display_style,
+                        size,
+                        color,
+                    },
+                };
+                let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+                display_ref.math.insert(tex, display_style, size, color, image);
+                image
+            }
+            Err(message) => {
+                if !out_error.is_null() {
+                    *out_error = CString::new(message.replace('\0', " "))
+                        .map_or(ptr::null_mut(), CString::into_raw);
+                }
+                return 0;
+            }
+        },
+    };
+
+    if !out_width.is_null() {
+        *out_width = image.width as c_int;
+    }
+    if !out_height.is_null() {
+        *out_height = image.height as c_int;
+    }
+    if !out_ascent.is_null() {
+        *out_ascent = image.ascent as c_int;
+    }
+    image.image_id
+}
+
+/// Free all math snippet images and empty the snippet cache.
+#[cfg(feature = "math")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_math_clear(handle: *mut NeomacsDisplay) {
+    let Some(display) = handle.as_mut() else { return };
+    let ids = display.math.clear();
+    if let Some(ref state) = THREADED_STATE {
+        for id in ids {
+            let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ImageFree { id });
+        }
+    }
+}
+
+/// Load icon set `name` from `path`, a directory of NAME.svg files or an
+/// icon font, replacing any set of that name.  The icons of a font are
+/// named by `codepoints`, a file of "NAME HEX" lines, or when it is NULL
+/// by the font's glyph names.
+///
+/// Returns the number of icons in the set, or -1 with the error stored
+/// in `out_error` (free with `neomacs_display_free_string`).
+#[cfg(feature = "icons")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_icon_set_load(
+    handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    path: *const c_char,
+    codepoints: *const c_char,
+    out_error: *mut *mut c_char,
+) -> c_int {
+    let (Some(display), false, false) = (handle.as_mut(), name.is_null(), path.is_null()) else {
+        return -1;
+    };
+    let (Ok(name), Ok(path)) = (CStr::from_ptr(name).to_str(), CStr::from_ptr(path).to_str()) else {
+        return -1;
+    };
+    let codepoints = (!codepoints.is_null())
+        .then(|| CStr::from_ptr(codepoints).to_str().ok())
+        .flatten()
+        .map(std::path::Path::new);
+
+    match display.icons.load(name, std::path::Path::new(path), codepoints) {
+        Ok(count) => count.min(c_int::MAX as usize) as c_int,
+        Err(message) => {
+            if !out_error.is_null() {
+                *out_error = CString::new(message.replace('\0', " "))
+                    .map_or(ptr::null_mut(), CString::into_raw);
+            }
+            -1
+        }
+    }
+}
+
+/// Make an image of icon `name` ("SET:ICON", or an ICON of any loaded
+/// set) `size` logical pixels high, in `color` (0xRRGGBB) or in its own
+/// colors when `own_colors` is nonzero.  The logical width and height
+/// are written to the out parameters at once; the icon rasterizes on the
+/// render thread.  Identical requests reuse their image.
+///
+/// Returns the image ID, or 0 if no loaded set has the icon.
+#[cfg(feature = "icons")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_icon(
+    handle: *mut NeomacsDisplay,
+    name: *const c_char,
+    size: f32,
+    color: u32,
+    own_colors: c_int,
+    out_width: *mut c_int,
+    out_height: *mut c_int,
+) -> u32 {
+    use crate::layout::icons::IconImage;
+
+    let (Some(display), false) = (handle.as_mut(), name.is_null()) else {
+        return 0;
+    };
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return 0;
+    };
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    let size = size.max(1.0);
+    let color = (own_colors == 0).then_some(color & 0xFFFFFF);
+
+    let image = match display.icons.get(name, size, color) {
+        Some(image) => image,
+        None => {
+            let Some((source, aspect)) = display.icons.find(name) else {
+                return 0;
+            };
+            let Ok(image_id) = crate::core::handle::IMAGES.alloc() else {
+                return 0;
+            };
+            let image = IconImage {
+                image_id,
+                width: ((size * aspect).round() as u32).max(1),
+                height: size.round() as u32,
+            };
+            if let Ok(mut dims) = state.image_dimensions.lock() {
+                dims.insert(image.image_id, (image.width, image.height));
+            }
+            let cmd = RenderCommand::IconRasterize {
+                image_id: image.image_id,
+                source,
+                width: image.width,
+                height: image.height,
+                color,
+            };
+            let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+            display.icons.insert(name, size, color, image);
+            image
+        }
+    };
+
+    if !out_width.is_null() {
+        *out_width = image.width as c_int;
+    }
+    if !out_height.is_null() {
+        *out_height = image.height as c_int;
+    }
+    image.image_id
+}
+
+/// Free all icon images, keeping the loaded icon sets.
+#[cfg(feature = "icons")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_icon_clear(handle: *mut NeomacsDisplay) {
+    let Some(display) = handle.as_mut() else { return };
+    let ids = display.icons.clear();
+    if let Some(ref state) = THREADED_STATE {
+        for id in ids {
+            let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ImageFree { id });
+        }
+    }
+}
+
+/// Make an image of a code of `kind` ("qr", "qr-low", "qr-quartile",
+/// "qr-high" or "code128") for `text`, in `dark` and `light` (0xRRGGBB).
+/// Each module is `module_size` logical pixels wide; barcodes are
+/// `bar_height` logical pixels high.  The logical width and height are
+/// written to the out parameters.
+///
+/// Returns the image ID, or 0 if the text cannot be encoded; the reason
+/// is then stored in `out_error` (free with `neomacs_display_free_string`).
+#[cfg(feature = "barcode")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_barcode_image(
+    _handle: *mut NeomacsDisplay,
+    kind: *const c_char,
+    text: *const c_char,
+    module_size: f32,
+    bar_height: f32,
+    dark: u32,
+    light: u32,
+    out_width: *mut c_int,
+    out_height: *mut c_int,
+    out_error: *mut *mut c_char,
+) -> u32 {
+    use crate::core::barcode::{self, CodeKind};
+
+    let fail = |message: String| {
+        if !out_error.is_null() {
+            *out_error = CString::new(message.replace('\0', " ")).map_or(ptr::null_mut(), CString::into_raw);
+        }
+        0
+    };
+    if kind.is_null() || text.is_null() {
+        return 0;
+    }
+    let Ok(kind_name) = CStr::from_ptr(kind).to_str() else {
+        return 0;
+    };
+    let Some(kind) = CodeKind::parse(kind_name) else {
+        return fail(format!("Unknown code kind: {}", kind_name));
+    };
+    let Ok(text) = CStr::from_ptr(text).to_str() else {
+        return fail("Text is not valid UTF-8".to_string());
+    };
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    let code = match barcode::generate(kind, text) {
+        Ok(code) => code,
+        Err(message) => return fail(message),
+    };
+
+    let module_size = module_size.max(1.0);
+    let width = (code.width as f32 * module_size).round() as u32;
+    let height = match kind {
+        CodeKind::Code128 => bar_height.max(1.0).round() as u32,
+        CodeKind::Qr(_) => width,
+    };
+    let id = match crate::core::handle::IMAGES.alloc() {
+        Ok(id) => id,
+        Err(e) => return fail(e.to_string()),
+    };
+    if let Ok(mut dims) = state.image_dimensions.lock() {
+        dims.insert(id, (width, height));
+    }
+    let data = code.to_rgba(dark & 0xFFFFFF, light & 0xFFFFFF);
+    let cmd = RenderCommand::ImageLoadRgba { id, width: code.width as u32, height: code.height as u32, data };
+    let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    // One texel per module: sample nearest so edges stay sharp
+    let sampling = crate::core::types::ImageSampling::Nearest;
+    let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ImageSetSampling { id, sampling });
+
+    if !out_width.is_null() {
+        *out_width = width as c_int;
+    }
+    if !out_height.is_null() {
+        *out_height = height as c_int;
+    }
+    id
+}
+
+/// Make an empty canvas of `width` x `height` logical pixels, shown
+/// under the returned image ID, or 0 on failure.  Draw on it with
+/// `neomacs_display_canvas_op` and `neomacs_display_canvas_flush`; free
+/// it with `neomacs_display_free_image`.
+#[cfg(feature = "canvas")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_canvas_create(
+    handle: *mut NeomacsDisplay,
+    width: c_int,
+    height: c_int,
+) -> u32 {
+    let Some(display) = handle.as_mut() else {
+        return 0;
+    };
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    if width <= 0 || height <= 0 {
+        return 0;
+    }
+    let Ok(id) = crate::core::handle::IMAGES.alloc() else {
+        return 0;
+    };
+    if let Ok(mut dims) = state.image_dimensions.lock() {
+        dims.insert(id, (width as u32, height as u32));
+    }
+    display.canvases.insert(id, crate::layout::canvas::Canvas::new(width as u32, height as u32));
+    id
+}
+
+/// Add the command `name` to canvas `image_id`, with the `nargs`
+/// numbers at `args`, `color` (0xAARRGGBB, alpha 0 opaque) and `text`,
+/// which may be null.  With `name` null, all commands are dropped
+/// instead.  Nothing is drawn until the canvas is flushed.
+///
+/// Returns 0 on success, or -1 with the reason stored in `out_error`
+/// (free with `neomacs_display_free_string`).
+#[cfg(feature = "canvas")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_canvas_op(
+    handle: *mut NeomacsDisplay,
+    image_id: u32,
+    name: *const c_char,
+    args: *const f32,
+    nargs: c_int,
+    color: u32,
+    text: *const c_char,
+    out_error: *mut *mut c_char,
+) -> c_int {
+    let Some(display) = handle.as_mut() else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::IMAGES, image_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let Some(canvas) = display.canvases.get_mut(&image_id) else {
+        return -1;
+    };
+    if name.is_null() {
+        canvas.ops.clear();
+        return 0;
+    }
+    let args = if args.is_null() || nargs <= 0 { &[][..] } else { std::slice::from_raw_parts(args, nargs as usize) };
+    let text = (!text.is_null()).then(|| CStr::from_ptr(text).to_string_lossy());
+    let name = CStr::from_ptr(name).to_string_lossy();
+    match crate::layout::canvas::CanvasOp::parse(&name, args, color, text.as_deref()) {
+        Ok(op) => {
+            canvas.ops.push(op);
+            0
+        }
+        Err(message) => {
+            if !out_error.is_null() {
+                *out_error = CString::new(message.replace('\0', " ")).map_or(ptr::null_mut(), CString::into_raw);
+            }
+            -1
+        }
+    }
+}
+
+/// Redraw canvas `image_id` with all its commands (async).
+#[cfg(feature = "canvas")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_canvas_flush(handle: *mut NeomacsDisplay, image_id: u32) -> c_int {
+    let Some(display) = handle.as_mut() else {
+        return -1;
+    };
+    if !live_handle(&crate::core::handle::IMAGES, image_id) {
+        return NEOMACS_STALE_HANDLE;
+    }
+    let Some(ref state) = THREADED_STATE else {
+        return -1;
+    };
+    let Some(canvas) = display.canvases.get(&image_id) else {
+        return -1;
+    };
+    let cmd = RenderCommand::CanvasRasterize { image_id, canvas: canvas.clone() };
+    let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    0
+}
+
+/// Request text and link extraction for a PDF page (async).
+/// Results become available through `neomacs_display_pdf_page_text`
+/// and `neomacs_display_pdf_link_info`.
+#[cfg(feature = "pdf")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pdf_extract_page(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+    page: c_int,
+) {
+    if page < 0 {
+        return;
+    }
+    if let Some(ref state) = THREADED_STATE {
+        let cmd = RenderCommand::PdfExtractPage { id: doc_id, page: page as u32 };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
+/// Get the extracted text of a PDF page.
+///
+/// Returns a C string (free with `neomacs_display_free_string`), or NULL
+/// if the page has not been extracted yet.
+#[cfg(feature = "pdf")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pdf_page_text(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+    page: c_int,
+) -> *mut c_char {
+    if page < 0 {
+        return ptr::null_mut();
+    }
+    if let Some(ref state) = THREADED_STATE {
+        if let Ok(docs) = state.shared_pdfs.lock() {
+            if let Some(content) = docs.get(&doc_id).and_then(|d| d.content.get(&(page as u32))) {
+                // Interior NULs cannot cross the C boundary
+                let text = content.text.replace('\0', "");
+                return CString::new(text).map(|s| s.into_raw()).unwrap_or(ptr::null_mut());
+            }
+        }
+    }
+    ptr::null_mut()
+}
+
+/// Get the number of links on an extracted PDF page.
+/// Returns -1 if the page has not been extracted yet.
+#[cfg(feature = "pdf")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pdf_link_count(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+    page: c_int,
+) -> c_int {
+    if page < 0 {
+        return -1;
+    }
+    if let Some(ref state) = THREADED_STATE {
+        if let Ok(docs) = state.shared_pdfs.lock() {
+            if let Some(content) = docs.get(&doc_id).and_then(|d| d.content.get(&(page as u32))) {
+                return content.links.len() as c_int;
+            }
+        }
+    }
+    -1
+}
+
+/// Get a link rectangle on an extracted PDF page.
+///
+/// The rectangle is in points from the top-left corner of the page.
+/// DEST_PAGE receives the 0-based target page or -1.  Returns the link
+/// URI as a C string (empty for internal links; free with
+/// `neomacs_display_free_string`), or NULL if INDEX is out of range.
+#[cfg(feature = "pdf")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pdf_link_info(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+    page: c_int,
+    index: c_int,
+    x: *mut f32,
+    y: *mut f32,
+    width: *mut f32,
+    height: *mut f32,
+    dest_page: *mut c_int,
+) -> *mut c_char {
+    if page < 0 || index < 0 || x.is_null() || y.is_null()
+        || width.is_null() || height.is_null() || dest_page.is_null()
+    {
+        return ptr::null_mut();
+    }
+    if let Some(ref state) = THREADED_STATE {
+        if let Ok(docs) = state.shared_pdfs.lock() {
+            let link = docs
+                .get(&doc_id)
+                .and_then(|d| d.content.get(&(page as u32)))
+                .and_then(|c| c.links.get(index as usize));
+            if let Some(link) = link {
+                *x = link.x;
+                *y = link.y;
+                *width = link.width;
+                *height = link.height;
+                *dest_page = link.dest_page.map(|p| p as c_int).unwrap_or(-1);
+                let uri = link.uri.as_deref().unwrap_or("").replace('\0', "");
+                return CString::new(uri).map(|s| s.into_raw()).unwrap_or(ptr::null_mut());
+            }
+        }
+    }
+    ptr::null_mut()
+}
+
+/// Close a PDF document.  Page images must be freed separately.
+#[cfg(feature = "pdf")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pdf_close(
+    _handle: *mut NeomacsDisplay,
+    doc_id: u32,
+) {
+    if let Some(ref state) = THREADED_STATE {
+        if !free_handle(&crate::core::handle::PDFS, doc_id) {
+            return;
+        }
+        let cmd = RenderCommand::PdfClose { id: doc_id };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
+// ============================================================================
+// Window Management FFI Functions
+// ============================================================================
+
+/// Create a new window with the specified dimensions and title.
+///
+/// Returns the window ID. The window will be created during the next poll_events call.
+/// Returns 0 if the backend is not available.
+#[no_mangle]
+pub extern "C" fn neomacs_display_create_window(
+    _handle: *mut NeomacsDisplay,
+    _width: i32,
+    _height: i32,
+    _title: *const c_char,
+) -> u32 {
+    // In threaded mode, the main window is created automatically by the render thread
+    // Return window ID 1 (the main window)
+    #[cfg(feature = "winit-backend")]
+    unsafe {
+        if THREADED_STATE.is_some() {
+            return 1; // Main window ID
+        }
+    }
+
+    0
+}
+
+/// Destroy a window by its ID.
+#[no_mangle]
+pub extern "C" fn neomacs_display_destroy_window(handle: *mut NeomacsDisplay, window_id: u32) {
+    let display = unsafe { &mut *handle };
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref mut backend) = display.winit_backend {
+        backend.destroy_window(window_id);
+    }
+}
+
+/// Show or hide a window.
+#[no_mangle]
+pub extern "C" fn neomacs_display_show_window(
+    handle: *mut NeomacsDisplay,
+    window_id: u32,
+    visible: bool,
+) {
+    let display = unsafe { &mut *handle };
+
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref backend) = display.winit_backend {
+        if let Some(state) = backend.get_window(window_id) {
+            state.window.set_visible(visible);
+        }
     }
 }
 
@@ -4304,8 +6962,10 @@ pub extern "C" fn neomacs_display_end_frame_window(
             // Matrix-based full-frame rendering: always send the complete frame.
             // The buffer was cleared at begin_frame and rebuilt by the matrix walker,
             // so it always contains the complete visible state.
-            let frame = display.frame_glyphs.clone();
-            let _ = state.emacs_comms.frame_tx.try_send(frame);
+            let frame = display.frame_to_send(&state.emacs_comms);
+            if state.emacs_comms.frame_tx.try_send(frame).is_ok() {
+                crate::core::input_latency::frame_sent();
+            }
         } else if let Some(ref mut backend) = display.winit_backend {
             backend.end_frame_for_window(
                 window_id,
@@ -4390,10 +7050,12 @@ pub unsafe extern "C" fn neomacs_rust_layout_frame(
             divider_last_fg,
         };
 
+        display.highlight.poll();
         engine.layout_frame(
             frame_ptr,
             &frame_params,
             &mut display.frame_glyphs,
+            display.highlight.store(),
         );
     }));
 
@@ -4423,46 +7085,651 @@ pub unsafe extern "C" fn neomacs_layout_charpos_at_pixel(
     crate::layout::hit_test_charpos_at_pixel(px, py)
 }
 
-/// Query buffer character position for a specific window at
-/// window-relative pixel coordinates.
-/// Returns charpos, or -1 if not found.
-///
-/// # Safety
-/// Must be called on the Emacs thread.
+/// Query buffer character position for a specific window at
+/// window-relative pixel coordinates.
+/// Returns charpos, or -1 if not found.
+///
+/// # Safety
+/// Must be called on the Emacs thread.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_layout_window_charpos(
+    window_id: i64,
+    wx: f32,
+    wy: f32,
+) -> i64 {
+    crate::layout::hit_test_window_charpos(window_id, wx, wy)
+}
+
+// Note: Event Polling FFI Functions have been removed
+// Events are now delivered via the threaded mode wakeup mechanism
+// Use neomacs_display_drain_input() instead, optionally with
+// neomacs_display_set_event_callback()
+
+// ============================================================================
+// Animation FFI functions
+// ============================================================================
+
+/// Set a display option (see `core::option_registry`), overriding the
+/// display config file.  Returns 1 if the option exists and the value is
+/// valid for it.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_animation_option(
+    _handle: *mut NeomacsDisplay,
+    key: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    if key.is_null() || value.is_null() {
+        return 0;
+    }
+    let name = CStr::from_ptr(key).to_string_lossy();
+    let value = CStr::from_ptr(value).to_string_lossy();
+    let Some(ref state) = THREADED_STATE else {
+        return 0;
+    };
+    // The registry's change callback forwards the new value to the render thread
+    let result = match state.display_options.lock() {
+        Ok(mut options) => options.set(&name, &value),
+        Err(_) => return 0,
+    };
+    match result {
+        Ok(()) => 1,
+        Err(e) => {
+            warn!("neomacs_display_set_animation_option: {}", e);
+            0
+        }
+    }
+}
+
+/// Get the effective value of a display option, or NULL if there is no
+/// such option.  Free the result with `neomacs_display_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_get_animation_option(
+    _handle: *mut NeomacsDisplay,
+    key: *const c_char,
+) -> *mut c_char {
+    if key.is_null() {
+        return ptr::null_mut();
+    }
+    let name = CStr::from_ptr(key).to_string_lossy();
+    let value = THREADED_STATE.as_ref().and_then(|state| {
+        let options = state.display_options.lock().ok()?;
+        CString::new(options.get(&name)?.to_string()).ok()
+    });
+    value.map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Display option description for C FFI.  The strings live as long as
+/// the process.
+#[repr(C)]
+pub struct NeomacsOptionInfo {
+    pub name: *const c_char,
+    pub group: *const c_char,
+    /// "boolean", "integer", "float", "choice", "color" or "color-list"
+    pub type_name: *const c_char,
+    pub default_value: *const c_char,
+    pub doc: *const c_char,
+    /// Space-separated names for "choice" options, else NULL
+    pub choices: *const c_char,
+    /// Range of "integer" and "float" options; the maximum number of
+    /// colors in `max` for "color-list"
+    pub min: c_double,
+    pub max: c_double,
+}
+
+/// C strings of one option description
+struct OptionInfoStrings {
+    name: CString,
+    group: CString,
+    type_name: CString,
+    default_value: CString,
+    doc: CString,
+    choices: Option<CString>,
+}
+
+static OPTION_INFO: once_cell::sync::Lazy<Vec<OptionInfoStrings>> = once_cell::sync::Lazy::new(|| {
+    use crate::core::option_registry::{list_options, OptionType};
+    let cstring = |s: &str| CString::new(s).unwrap_or_default();
+    list_options()
+        .iter()
+        .map(|spec| OptionInfoStrings {
+            name: cstring(spec.name),
+            group: cstring(spec.group),
+            type_name: cstring(spec.ty.name()),
+            default_value: cstring(spec.default),
+            doc: cstring(spec.doc),
+            choices: match &spec.ty {
+                OptionType::Choice(choices) => Some(cstring(&choices.join(" "))),
+                _ => None,
+            },
+        })
+        .collect()
+});
+
+/// Get the number of display options
+#[no_mangle]
+pub extern "C" fn neomacs_display_option_count() -> c_int {
+    crate::core::option_registry::list_options().len() as c_int
+}
+
+/// Describe the display option at `index`.
+/// Returns 1 on success, 0 on failure.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_option_info(
+    index: c_int,
+    info: *mut NeomacsOptionInfo,
+) -> c_int {
+    use crate::core::option_registry::{list_options, OptionType};
+    if info.is_null() || index < 0 {
+        return 0;
+    }
+    let (Some(spec), Some(strings)) =
+        (list_options().get(index as usize), OPTION_INFO.get(index as usize))
+    else {
+        return 0;
+    };
+    let (min, max) = match spec.ty {
+        OptionType::Integer { min, max } => (min as c_double, max as c_double),
+        OptionType::Float { min, max } => (min, max),
+        OptionType::ColorList { max } => (0.0, max as c_double),
+        _ => (0.0, 0.0),
+    };
+    (*info).name = strings.name.as_ptr();
+    (*info).group = strings.group.as_ptr();
+    (*info).type_name = strings.type_name.as_ptr();
+    (*info).default_value = strings.default_value.as_ptr();
+    (*info).doc = strings.doc.as_ptr();
+    (*info).choices = strings.choices.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+    (*info).min = min;
+    (*info).max = max;
+    1
+}
+
+/// Error report for C FFI (see `core::error_report`)
+#[repr(C)]
+pub struct NeomacsErrorInfo {
+    pub id: u32,
+    /// "info", "warning" or "error"; static
+    pub severity: *const c_char,
+    /// Subsystem, e.g. "image", "video", "font", "shader"; static
+    pub kind: *const c_char,
+    /// Id of the image, video, view or terminal concerned, or 0
+    pub resource: u32,
+    /// Number of times the failure happened
+    pub count: u32,
+    /// Free with `neomacs_display_free_string`
+    pub message: *mut c_char,
+}
+
+/// Describe the oldest kept error report with an id greater than
+/// `after_id`.  Returns 1 if there is one, 0 otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_next_error(
+    after_id: u32,
+    info: *mut NeomacsErrorInfo,
+) -> c_int {
+    use crate::core::error_report::{next_after, ErrorKind, Severity};
+    if info.is_null() {
+        return 0;
+    }
+    let Some(report) = next_after(after_id) else {
+        return 0;
+    };
+    let severity = match report.severity {
+        Severity::Info => c"info",
+        Severity::Warning => c"warning",
+        Severity::Error => c"error",
+    };
+    let kind = match report.kind {
+        ErrorKind::Image => c"image",
+        ErrorKind::Video => c"video",
+        ErrorKind::WebKit => c"webkit",
+        ErrorKind::Terminal => c"terminal",
+        ErrorKind::Pdf => c"pdf",
+        ErrorKind::Font => c"font",
+        ErrorKind::Shader => c"shader",
+        ErrorKind::Gpu => c"gpu",
+        ErrorKind::Config => c"config",
+        ErrorKind::Render => c"render",
+    };
+    (*info).id = report.id;
+    (*info).severity = severity.as_ptr();
+    (*info).kind = kind.as_ptr();
+    (*info).resource = report.resource.unwrap_or(0);
+    (*info).count = report.count;
+    (*info).message = CString::new(report.message).map_or(ptr::null_mut(), CString::into_raw);
+    1
+}
+
+/// Forget all error reports
+#[no_mangle]
+pub extern "C" fn neomacs_display_clear_errors() {
+    crate::core::error_report::clear();
+}
+
+/// GPU memory usage for C FFI (see `backend::wgpu::gpu_budget`), in bytes
+#[repr(C)]
+pub struct NeomacsGpuMemoryUsage {
+    pub glyphs: u64,
+    pub images: u64,
+    pub visible_images: u64,
+    pub videos: u64,
+    pub snapshots: u64,
+    pub budget: u64,
+}
+
+/// Fill INFO with the GPU memory measured after the last frame.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_gpu_memory_usage(info: *mut NeomacsGpuMemoryUsage) {
+    let Some(info) = info.as_mut() else { return };
+    let usage = crate::backend::wgpu::gpu_budget::last_usage();
+    *info = NeomacsGpuMemoryUsage {
+        glyphs: usage.glyphs as u64,
+        images: usage.images as u64,
+        visible_images: usage.visible_images as u64,
+        videos: usage.videos as u64,
+        snapshots: usage.snapshots as u64,
+        budget: usage.budget as u64,
+    };
+}
+
+/// Input latency percentiles for C FFI (see `core::input_latency`), in
+/// microseconds
+#[repr(C)]
+pub struct NeomacsLatencyStats {
+    pub count: u64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// Fill INFO with the latency percentiles of STAGE over the recent
+/// presses: 0 from the window system to Emacs, 1 from Emacs to the frame
+/// it sends, 2 from that frame to the screen, 3 the whole path.
+/// Returns 0 for an unknown stage.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_input_latency(stage: c_int, info: *mut NeomacsLatencyStats) -> c_int {
+    use crate::core::input_latency::{report, Stage};
+    let (Some(&stage), Some(info)) = (Stage::ALL.get(stage as usize), info.as_mut()) else {
+        return 0;
+    };
+    let p = report(stage);
+    *info = NeomacsLatencyStats {
+        count: p.count as u64,
+        p50_us: p.p50.as_micros() as u64,
+        p90_us: p.p90.as_micros() as u64,
+        p99_us: p.p99.as_micros() as u64,
+        max_us: p.max.as_micros() as u64,
+    };
+    1
+}
+
+/// The quality level in effect (see `core::quality`): "full",
+/// "no-particles", "no-transitions", "reduced-video" or "no-blur".
+/// The string is static.
+#[no_mangle]
+pub extern "C" fn neomacs_display_quality_level() -> *const c_char {
+    use crate::core::quality::{current, QualityLevel};
+    let level = match current() {
+        QualityLevel::Full => c"full",
+        QualityLevel::NoParticles => c"no-particles",
+        QualityLevel::NoTransitions => c"no-transitions",
+        QualityLevel::ReducedVideo => c"reduced-video",
+        QualityLevel::NoBlur => c"no-blur",
+    };
+    level.as_ptr()
+}
+
+/// Statistics of the last rendered frame for C FFI (see `core::frame_stats`)
+#[repr(C)]
+pub struct NeomacsFrameStats {
+    pub frames: u64,
+    pub glyphs: u64,
+    pub windows: u64,
+    pub background_rects: u64,
+    pub row_cache_hits: u64,
+    pub row_cache_misses: u64,
+    /// Phase times in microseconds
+    pub prepare_us: u64,
+    pub draw_us: u64,
+    pub present_us: u64,
+    pub skipped_frames: u64,
+    pub late_frames: u64,
+}
+
+/// Fill INFO with the statistics of the last frame presented.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_frame_stats(info: *mut NeomacsFrameStats) {
+    let Some(info) = info.as_mut() else { return };
+    let stats = crate::core::frame_stats::last_stats();
+    *info = NeomacsFrameStats {
+        frames: stats.frames,
+        glyphs: stats.glyphs as u64,
+        windows: stats.windows as u64,
+        background_rects: stats.background_rects as u64,
+        row_cache_hits: stats.row_cache_hits as u64,
+        row_cache_misses: stats.row_cache_misses as u64,
+        prepare_us: stats.prepare_time.as_micros() as u64,
+        draw_us: stats.draw_time.as_micros() as u64,
+        present_us: stats.present_time.as_micros() as u64,
+        skipped_frames: stats.skipped_frames,
+        late_frames: stats.late_frames,
+    };
+}
+
+/// The structure of the current frame as JSON: windows, glyphs of each
+/// kind, floating layers bottom to top, running animations and cache
+/// sizes (see `core::scene_dump`).  Waits up to a second for the render
+/// thread.  Returns NULL if it did not answer; free the result with
+/// `neomacs_display_free_string`.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_scene_dump(_handle: *mut NeomacsDisplay) -> *mut c_char {
+    let Some(ref state) = THREADED_STATE else {
+        return ptr::null_mut();
+    };
+    let (reply, answer) = crossbeam_channel::bounded(1);
+    if state.emacs_comms.cmd_tx.try_send(RenderCommand::DumpScene { reply }).is_err() {
+        return ptr::null_mut();
+    }
+    match answer.recv_timeout(std::time::Duration::from_secs(1)) {
+        Ok(json) => CString::new(json).map_or(ptr::null_mut(), CString::into_raw),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// The current frame as plain text: for each window body, minibuffer and
+/// mode line a header line, then its visual lines with the cursor and the
+/// text drawn in face SELECTION_FACE marked (see `core::accessibility`).
+/// SELECTION_FACE 0 marks no selection.  Waits up to a second for the
+/// render thread.  Returns NULL if it did not answer; free the result
+/// with `neomacs_display_free_string`.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_frame_text(
+    _handle: *mut NeomacsDisplay,
+    selection_face: c_int,
+) -> *mut c_char {
+    let Some(ref state) = THREADED_STATE else {
+        return ptr::null_mut();
+    };
+    let (reply, answer) = crossbeam_channel::bounded(1);
+    let command = RenderCommand::FrameText { selection_face: selection_face.max(0) as u32, reply };
+    if state.emacs_comms.cmd_tx.try_send(command).is_err() {
+        return ptr::null_mut();
+    }
+    match answer.recv_timeout(std::time::Duration::from_secs(1)) {
+        Ok(text) => CString::new(text).map_or(ptr::null_mut(), CString::into_raw),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Memory held by a subsystem for C FFI (see `core::memory_report`)
+#[repr(C)]
+pub struct NeomacsMemoryUse {
+    /// e.g. "glyph-atlas", "images", "terminal-grids"; free with
+    /// `neomacs_display_free_string`
+    pub subsystem: *mut c_char,
+    /// Glyphs, images, videos, snapshots or terminals held
+    pub count: u64,
+    pub bytes: u64,
+    /// 1 for video memory, 0 for main memory
+    pub gpu: c_int,
+}
+
+/// Fill ENTRIES with up to MAX subsystems and the memory each holds.
+/// Waits up to a second for the render thread.  Returns the number of
+/// entries filled, or -1 if the render thread did not answer.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_memory_report(
+    _handle: *mut NeomacsDisplay,
+    entries: *mut NeomacsMemoryUse,
+    max: c_int,
+) -> c_int {
+    let Some(ref state) = THREADED_STATE else { return -1 };
+    if entries.is_null() || max <= 0 {
+        return 0;
+    }
+    let (reply, answer) = crossbeam_channel::bounded(1);
+    if state.emacs_comms.cmd_tx.try_send(RenderCommand::MemoryReport { reply }).is_err() {
+        return -1;
+    }
+    let Ok(report) = answer.recv_timeout(std::time::Duration::from_secs(1)) else {
+        return -1;
+    };
+    let entries = std::slice::from_raw_parts_mut(entries, max as usize);
+    for (slot, entry) in entries.iter_mut().zip(&report.entries) {
+        *slot = NeomacsMemoryUse {
+            subsystem: CString::new(entry.subsystem).map_or(ptr::null_mut(), CString::into_raw),
+            count: entry.count as u64,
+            bytes: entry.bytes as u64,
+            gpu: entry.gpu as c_int,
+        };
+    }
+    report.entries.len().min(max as usize) as c_int
+}
+
+/// PATH as a session file path; NULL means the default one
+unsafe fn session_path(path: *const c_char) -> Option<std::path::PathBuf> {
+    if path.is_null() {
+        crate::core::session::Session::default_path()
+    } else {
+        Some(CStr::from_ptr(path).to_string_lossy().into_owned().into())
+    }
+}
+
+/// Save the terminals, images, videos and WebKit views created so far,
+/// with their floating positions and the display options set at runtime,
+/// to PATH (NULL: the default session file, see `core::session`).  The
+/// file is written in the background; failures are reported as display
+/// errors.  Returns 1 if the save was queued.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_session_save(
+    _handle: *mut NeomacsDisplay,
+    path: *const c_char,
+) -> c_int {
+    let Some(ref state) = THREADED_STATE else { return 0 };
+    let Some(path) = session_path(path) else { return 0 };
+    state.emacs_comms.cmd_tx.try_send(RenderCommand::SaveSession { path }).is_ok() as c_int
+}
+
+/// Record the frames the display receives from now on, with the images
+/// they show, to the replay file PATH for `neomacs-replay` (see
+/// `remote::replay`).  Failures to write are reported as display errors.
+/// Returns 1 if recording was queued.
+#[cfg(feature = "remote")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_replay_start(
+    _handle: *mut NeomacsDisplay,
+    path: *const c_char,
+) -> c_int {
+    let Some(ref state) = THREADED_STATE else { return 0 };
+    if path.is_null() {
+        return 0;
+    }
+    let path = CStr::from_ptr(path).to_string_lossy().into_owned().into();
+    state.emacs_comms.cmd_tx.try_send(RenderCommand::ReplayStart { path }).is_ok() as c_int
+}
+
+/// Finish the replay being recorded, if any.
+#[cfg(feature = "remote")]
 #[no_mangle]
-pub unsafe extern "C" fn neomacs_layout_window_charpos(
-    window_id: i64,
-    wx: f32,
-    wy: f32,
-) -> i64 {
-    crate::layout::hit_test_window_charpos(window_id, wx, wy)
+pub unsafe extern "C" fn neomacs_display_replay_stop(_handle: *mut NeomacsDisplay) {
+    let Some(ref state) = THREADED_STATE else { return };
+    let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::ReplayStop);
 }
 
-// Note: Event Polling FFI Functions have been removed
-// Events are now delivered via the threaded mode wakeup mechanism
-// Use neomacs_display_drain_input() instead
+/// Play an effect offscreen over a sample frame and write its frames to
+/// PATH: an animated GIF if PATH ends in .gif, else frame-NNNN.png files
+/// in the directory PATH.  KIND is "transition" or "cursor" and NAME an
+/// effect or cursor style as `neomacs-set-animation-config` names them.
+/// The effect lasts `duration_ms` and is sampled at `fps`.  The frames
+/// are encoded in the background; failures are reported as display
+/// errors.  Returns 1 if the export was queued, 0 if the display is not
+/// running, -1 for an unknown KIND or NAME.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_export_effect_preview(
+    _handle: *mut NeomacsDisplay,
+    kind: *const c_char,
+    name: *const c_char,
+    path: *const c_char,
+    duration_ms: u32,
+    fps: u32,
+) -> c_int {
+    use crate::core::effect_preview::{PreviewEffect, PreviewTimeline};
 
-// ============================================================================
-// Animation FFI functions (stubs - no GTK4 backend)
-// ============================================================================
+    if kind.is_null() || name.is_null() || path.is_null() {
+        return -1;
+    }
+    let kind = CStr::from_ptr(kind).to_string_lossy();
+    let name = CStr::from_ptr(name).to_string_lossy();
+    let Some(effect) = PreviewEffect::parse(&kind, &name) else { return -1 };
+    let Some(ref state) = THREADED_STATE else { return 0 };
+    let path = CStr::from_ptr(path).to_string_lossy().into_owned().into();
+    let timeline = PreviewTimeline::new(std::time::Duration::from_millis(duration_ms as u64), fps);
+    let cmd = RenderCommand::ExportEffectPreview { effect, path, timeline };
+    state.emacs_comms.cmd_tx.try_send(cmd).is_ok() as c_int
+}
+
+/// `NeomacsDisplayTheme::set` bits of the optional colors
+pub const NEOMACS_THEME_CURSOR: u32 = 1 << 0;
+pub const NEOMACS_THEME_DIVIDER: u32 = 1 << 1;
+pub const NEOMACS_THEME_PLACEHOLDER: u32 = 1 << 2;
+pub const NEOMACS_THEME_TERMINAL_PALETTE: u32 = 1 << 3;
+
+/// Display theme for C FFI (see `core::display_theme`).  Colors are
+/// 0xAARRGGBB pixels, alpha 0 meaning opaque; `set` says which of the
+/// optional ones are given.
+#[repr(C)]
+pub struct NeomacsDisplayTheme {
+    pub background: u32,
+    pub foreground: u32,
+    pub cursor: u32,
+    pub divider: u32,
+    pub placeholder: u32,
+    pub terminal_palette: [u32; 16],
+    pub set: u32,
+}
 
-/// Set an animation configuration option (stub)
+/// Switch the display's own colors to THEME at once, crossfading from
+/// the old ones over `fade_ms` if not 0.  Returns 1 if the switch was
+/// queued.
 #[no_mangle]
-pub unsafe extern "C" fn neomacs_display_set_animation_option(
+pub unsafe extern "C" fn neomacs_display_set_theme(
     _handle: *mut NeomacsDisplay,
-    _key: *const c_char,
-    _value: *const c_char,
+    theme: *const NeomacsDisplayTheme,
+    fade_ms: u32,
 ) -> c_int {
-    0
+    use crate::core::display_theme::DisplayTheme;
+
+    let Some(theme) = theme.as_ref() else { return 0 };
+    let Some(ref state) = THREADED_STATE else { return 0 };
+    let given = |bit: u32, pixel: u32| (theme.set & bit != 0).then(|| Color::from_pixel(pixel));
+    let theme = DisplayTheme {
+        background: Color::from_pixel(theme.background),
+        foreground: Color::from_pixel(theme.foreground),
+        cursor: given(NEOMACS_THEME_CURSOR, theme.cursor),
+        divider: given(NEOMACS_THEME_DIVIDER, theme.divider),
+        placeholder: given(NEOMACS_THEME_PLACEHOLDER, theme.placeholder)
+            .unwrap_or(DisplayTheme::default().placeholder),
+        terminal_palette: (theme.set & NEOMACS_THEME_TERMINAL_PALETTE != 0)
+            .then(|| theme.terminal_palette.map(Color::from_pixel)),
+    };
+    let fade = (fade_ms > 0).then(|| std::time::Duration::from_millis(fade_ms as u64));
+    state.emacs_comms.cmd_tx.try_send(RenderCommand::SetDisplayTheme { theme: Box::new(theme), fade }).is_ok() as c_int
 }
 
-/// Get an animation configuration option (stub)
+/// Recreate the session saved in PATH (NULL: the default session file)
+/// under fresh ids.  Terminals start a new shell showing the saved
+/// output.  Returns one `KIND OLD-ID NEW-ID` line per resource recreated,
+/// KIND being terminal, image, video or webkit, or NULL if the file
+/// cannot be read.  Free the result with `neomacs_display_free_string`.
 #[no_mangle]
-pub unsafe extern "C" fn neomacs_display_get_animation_option(
-    _handle: *mut NeomacsDisplay,
-    _key: *const c_char,
+pub unsafe extern "C" fn neomacs_display_session_restore(
+    handle: *mut NeomacsDisplay,
+    path: *const c_char,
 ) -> *mut c_char {
-    ptr::null_mut()
+    use std::fmt::Write;
+
+    let Some(ref state) = THREADED_STATE else { return ptr::null_mut() };
+    let Some(path) = session_path(path) else { return ptr::null_mut() };
+    let session = match crate::core::session::Session::load(&path) {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("neomacs_display_session_restore: {}", e);
+            return ptr::null_mut();
+        }
+    };
+    let cstr = |s: &str| CString::new(s).unwrap_or_default();
+    let mut ids = String::new();
+
+    for (name, value) in &session.options {
+        neomacs_display_set_animation_option(handle, cstr(name).as_ptr(), cstr(value).as_ptr());
+    }
+    for (old, image) in &session.images {
+        let path = cstr(&image.path);
+        let id = neomacs_display_load_image_file_scaled(
+            handle, path.as_ptr(), image.max_width as c_int, image.max_height as c_int,
+        );
+        if id == 0 {
+            continue;
+        }
+        if let Some(r) = image.floating {
+            neomacs_display_set_floating_image(
+                handle, id, r.x as c_int, r.y as c_int, r.width as c_int, r.height as c_int,
+            );
+        }
+        let _ = writeln!(ids, "image {} {}", old, id);
+    }
+    for (old, video) in &session.videos {
+        let id = neomacs_display_load_video(handle, cstr(&video.path).as_ptr());
+        if id != 0 {
+            let _ = writeln!(ids, "video {} {}", old, id);
+        }
+    }
+    for (old, webkit) in &session.webkits {
+        let profile = webkit.profile.as_deref().map(cstr);
+        let id = neomacs_display_webkit_create_with_profile(
+            handle, webkit.width as c_int, webkit.height as c_int,
+            profile.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+        );
+        if id == 0 {
+            continue;
+        }
+        if let Some(ref url) = webkit.url {
+            neomacs_display_webkit_load_uri(handle, id, cstr(url).as_ptr());
+        }
+        if let Some(r) = webkit.floating {
+            neomacs_display_set_floating_webkit(
+                handle, id, r.x as c_int, r.y as c_int, r.width as c_int, r.height as c_int,
+            );
+        }
+        let _ = writeln!(ids, "webkit {} {}", old, id);
+    }
+    #[cfg(feature = "neo-term")]
+    for (old, terminal) in &session.terminals {
+        let shell = terminal.shell.as_deref().map(cstr);
+        let id = neomacs_display_terminal_create(
+            terminal.cols, terminal.rows, terminal.mode,
+            shell.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+        );
+        if id == 0 {
+            continue;
+        }
+        if !terminal.scrollback.is_empty() {
+            let data = format!("{}\r\n", terminal.scrollback.replace('\n', "\r\n")).into_bytes();
+            let _ = state.emacs_comms.cmd_tx.try_send(RenderCommand::TerminalReplay { id, data });
+        }
+        if let Some((x, y, opacity)) = terminal.float {
+            neomacs_display_terminal_set_float(id, x, y, opacity);
+        }
+        let _ = writeln!(ids, "terminal {} {}", old, id);
+    }
+    #[cfg(not(feature = "neo-term"))]
+    let _ = state;
+
+    CString::new(ids).map_or(ptr::null_mut(), |s| s.into_raw())
 }
 
 /// Free a string returned by neomacs_display_get_animation_option
@@ -4490,13 +7757,84 @@ pub unsafe extern "C" fn neomacs_display_animation_active(
     0
 }
 
-/// Trigger a buffer transition animation (stub)
+/// Crossfade every window into the next frame using EFFECT, the name of
+/// a buffer transition effect or else of a scroll effect; "none" switches
+/// at once.  A nonzero NOTIFY is sent back in an animation-completed
+/// event once every window's crossfade ended.
+/// Returns 1 if the transition was queued.
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_display_start_buffer_transition(
     _handle: *mut NeomacsDisplay,
-    _effect: *const c_char,
-    _duration_ms: c_int,
+    effect: *const c_char,
+    duration_ms: c_int,
+    notify: u32,
+) -> c_int {
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        use crate::core::buffer_transition::BufferTransitionEffect;
+        use crate::core::scroll_animation::ScrollEffect;
+        let effect = if effect.is_null() {
+            Some(ScrollEffect::Crossfade)
+        } else {
+            let name = CStr::from_ptr(effect).to_string_lossy();
+            match BufferTransitionEffect::from_name(&name) {
+                Some(effect) => effect.scroll_effect(),
+                None => Some(ScrollEffect::from_str(&name)),
+            }
+        };
+        // No effect still ends the transition, and tells NOTIFY so
+        let duration_ms = if effect.is_some() { duration_ms.max(0) as u32 } else { 0 };
+        let cmd = RenderCommand::StartTransition {
+            window_id: None,
+            effect: effect.unwrap_or(ScrollEffect::Crossfade),
+            duration_ms,
+            notify,
+        };
+        return state.emacs_comms.cmd_tx.try_send(cmd).is_ok() as c_int;
+    }
+    0
+}
+
+/// Zoom the whole frame to SCALE (1.0 = normal) over DURATION_MS, around
+/// (X, Y) in logical pixels, or around the cursor when X or Y is negative.
+/// A nonzero NOTIFY is sent back in an animation-completed event when the
+/// zoom ends.
+/// Returns 1 if the zoom was queued.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_frame_zoom(
+    _handle: *mut NeomacsDisplay,
+    scale: f32,
+    x: c_int,
+    y: c_int,
+    duration_ms: c_int,
+    notify: u32,
+) -> c_int {
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        let cmd = RenderCommand::SetFrameZoom {
+            scale,
+            focus: (x >= 0 && y >= 0).then_some((x as f32, y as f32)),
+            duration_ms: duration_ms.max(0) as u32,
+            notify,
+        };
+        return state.emacs_comms.cmd_tx.try_send(cmd).is_ok() as c_int;
+    }
+    0
+}
+
+/// Announce that the selected window's next scroll moves its text PIXELS
+/// up (negative: down), so the scroll animation slides just that far.
+/// Returns 1 if the hint was queued.
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_scroll_hint(
+    _handle: *mut NeomacsDisplay,
+    pixels: c_int,
 ) -> c_int {
+    #[cfg(feature = "winit-backend")]
+    if let Some(ref state) = THREADED_STATE {
+        let cmd = RenderCommand::ScrollHint { pixels: pixels as f32 };
+        return state.emacs_comms.cmd_tx.try_send(cmd).is_ok() as c_int;
+    }
     0
 }
 
@@ -4547,9 +7885,16 @@ struct ThreadedState {
     image_dimensions: Arc<Mutex<HashMap<u32, (u32, u32)>>>,
     /// Shared storage for monitor info from winit
     shared_monitors: SharedMonitorInfo,
-    /// Shared terminal handles for cross-thread text extraction
+    /// Caret offsets inside composed glyphs, published as they are shaped
+    cluster_offsets: crate::text::clusters::SharedClusterOffsets,
+    /// Display options from the config file and `neomacs-set-animation-option`
+    display_options: crate::core::option_registry::SharedOptionRegistry,
+    /// Shared terminal handles for text extraction and input backpressure
     #[cfg(feature = "neo-term")]
     shared_terminals: crate::terminal::SharedTerminals,
+    /// Shared PDF document info (page sizes, text, links)
+    #[cfg(feature = "pdf")]
+    shared_pdfs: crate::backend::wgpu::SharedPdfDocuments,
 }
 
 /// Initialize display in threaded mode
@@ -4590,22 +7935,57 @@ pub unsafe extern "C" fn neomacs_display_init_threaded(
     // Create shared monitor info storage (with condvar for sync)
     let shared_monitors: SharedMonitorInfo = Arc::new((Mutex::new(Vec::new()), std::sync::Condvar::new()));
 
+    // Create shared caret offsets for cursor placement inside ligatures
+    let cluster_offsets: crate::text::clusters::SharedClusterOffsets =
+        Arc::new(Mutex::new(HashMap::new()));
+
     // Create shared terminal handles for cross-thread text extraction
     #[cfg(feature = "neo-term")]
     let shared_terminals: crate::terminal::SharedTerminals =
         Arc::new(Mutex::new(HashMap::new()));
 
-    // Spawn render thread with shared maps
-    let render_thread = RenderThread::spawn(
-        render_comms,
-        width,
-        height,
-        title,
-        Arc::clone(&image_dimensions),
-        Arc::clone(&shared_monitors),
-        #[cfg(feature = "neo-term")]
-        Arc::clone(&shared_terminals),
-    );
+    // Display options from the config file, forwarded to the render thread
+    let display_options = crate::render_thread::load_display_options(&emacs_comms.cmd_tx);
+
+    // Create shared PDF document info for page/text/link queries
+    #[cfg(feature = "pdf")]
+    let shared_pdfs: crate::backend::wgpu::SharedPdfDocuments =
+        Arc::new(Mutex::new(HashMap::new()));
+
+    // Spawn render thread with shared maps, or a supervisor for a renderer
+    // process when NEOMACS_RENDER_PROCESS asks for one
+    #[cfg(all(feature = "remote", unix))]
+    let render_process = crate::render_process::server_from_env();
+    #[cfg(not(all(feature = "remote", unix)))]
+    let render_process = None::<std::path::PathBuf>;
+    let render_thread = match render_process {
+        #[cfg(all(feature = "remote", unix))]
+        Some(server) => {
+            log::info!("Rendering in child process {}", server.display());
+            crate::render_process::spawn(
+                render_comms,
+                width,
+                height,
+                title,
+                Arc::clone(&image_dimensions),
+                server,
+            )
+        }
+        _ => RenderThread::spawn(
+            render_comms,
+            width,
+            height,
+            title,
+            Arc::clone(&image_dimensions),
+            Arc::clone(&shared_monitors),
+            Arc::clone(&cluster_offsets),
+            Arc::clone(&display_options),
+            #[cfg(feature = "neo-term")]
+            Arc::clone(&shared_terminals),
+            #[cfg(feature = "pdf")]
+            Arc::clone(&shared_pdfs),
+        ),
+    };
 
     // Create a NeomacsDisplay handle for C code to use with frame operations
     // This is a lightweight handle that doesn't own the backend (render thread does)
@@ -4630,6 +8010,21 @@ pub unsafe extern "C" fn neomacs_display_init_threaded(
         frame_counter: 0,
         current_render_window_id: 0,
         faces: HashMap::new(),
+        face_background_styles: HashMap::new(),
+        face_inheritance: HashMap::new(),
+        face_cache: FaceCache::new(),
+        faces_sent: 0,
+        #[cfg(feature = "html-renderer")]
+        html_links: Vec::new(),
+        char_grid: None,
+        minimap: crate::core::minimap::MinimapStore::default(),
+        #[cfg(feature = "math")]
+        math: crate::layout::math::MathCache::default(),
+        #[cfg(feature = "icons")]
+        icons: crate::layout::icons::IconRegistry::default(),
+        #[cfg(feature = "canvas")]
+        canvases: HashMap::new(),
+        highlight: crate::layout::highlight::HighlightService::default(),
     });
     let display_ptr = Box::into_raw(display);
 
@@ -4639,13 +8034,107 @@ pub unsafe extern "C" fn neomacs_display_init_threaded(
         display_handle: display_ptr,
         image_dimensions,
         shared_monitors,
+        cluster_offsets,
+        display_options,
         #[cfg(feature = "neo-term")]
         shared_terminals,
+        #[cfg(feature = "pdf")]
+        shared_pdfs,
     });
 
     wakeup_fd
 }
 
+// ============================================================================
+// Ligature Caret FFI
+// ============================================================================
+
+/// Run a query against the caret offsets of a composed glyph, if the
+/// render thread has shaped it.
+#[cfg(feature = "winit-backend")]
+unsafe fn with_cluster_offsets<T>(
+    text: *const c_char,
+    face_id: u32,
+    font_size: f32,
+    f: impl FnOnce(&crate::text::clusters::ClusterOffsets) -> T,
+) -> Option<T> {
+    if text.is_null() {
+        return None;
+    }
+    let text = CStr::from_ptr(text).to_str().ok()?;
+    let state = THREADED_STATE.as_ref()?;
+    let map = state.cluster_offsets.lock().ok()?;
+    let key = crate::text::clusters::ClusterKey {
+        text: text.into(),
+        face_id,
+        font_size_bits: font_size.to_bits(),
+    };
+    map.get(&key).map(f)
+}
+
+/// X offset, from the glyph origin, of the caret before character `index`
+/// of the composed (ligature) glyph drawing `text` in `face_id` at
+/// `font_size`.  Returns -1 if that glyph has not been shaped yet.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_x_for_char_index(
+    text: *const c_char,
+    face_id: u32,
+    font_size: f32,
+    index: c_int,
+) -> f32 {
+    with_cluster_offsets(text, face_id, font_size, |o| {
+        o.x_for_char_index(index.max(0) as usize)
+    })
+    .unwrap_or(-1.0)
+}
+
+/// Character index of the caret position closest to offset `x` inside the
+/// composed glyph drawing `text`, from 0 to its character count.
+/// Returns -1 if that glyph has not been shaped yet.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_char_index_for_x(
+    text: *const c_char,
+    face_id: u32,
+    font_size: f32,
+    x: f32,
+) -> c_int {
+    with_cluster_offsets(text, face_id, font_size, |o| o.char_index_for_x(x) as c_int)
+        .unwrap_or(-1)
+}
+
+// ============================================================================
+// Fallback Glyph Fitting FFI
+// ============================================================================
+
+/// Set how glyphs from fallback fonts (color emoji, symbols) are fitted to
+/// the text font of face `face_id`, or of every face without its own
+/// setting when `face_id` is negative.  The glyph is resized to the text
+/// font's height times `scale` and raised by `baseline_offset` logical
+/// pixels.  `enabled` = 0 draws fallback glyphs at their natural size;
+/// `enabled` < 0 drops the face's own setting.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_fallback_metrics(
+    _handle: *mut NeomacsDisplay,
+    face_id: c_int,
+    enabled: c_int,
+    scale: f32,
+    baseline_offset: f32,
+) {
+    let face_id = (face_id >= 0).then_some(face_id as u32);
+    let metrics = (enabled >= 0).then_some(crate::core::face::FallbackMetrics {
+        enabled: enabled != 0,
+        scale: if scale > 0.0 { scale } else { 1.0 },
+        baseline_offset,
+    });
+    if let Some(ref state) = THREADED_STATE {
+        let cmd = RenderCommand::SetFallbackMetrics { face_id, metrics };
+        let _ = state.emacs_comms.cmd_tx.try_send(cmd);
+    }
+}
+
 // ============================================================================
 // Monitor Info FFI
 // ============================================================================
@@ -4793,6 +8282,16 @@ pub unsafe extern "C" fn neomacs_display_drain_input(
     while count < max_events {
         match state.emacs_comms.input_rx.try_recv() {
             Ok(event) => {
+                // Handled here: the next frame sent carries every face
+                if matches!(event, InputEvent::FacesOutOfSync) {
+                    FACES_OUT_OF_SYNC.store(true, std::sync::atomic::Ordering::Relaxed);
+                    continue;
+                }
+                if matches!(event, InputEvent::Key { pressed: true, .. }
+                    | InputEvent::MouseButton { pressed: true, .. })
+                {
+                    crate::core::input_latency::input_received();
+                }
                 let out = &mut *events.add(count as usize);
                 *out = NeomacsInputEvent::default();
 
@@ -4870,15 +8369,62 @@ pub unsafe extern "C" fn neomacs_display_drain_input(
                         out.width = width;
                         out.height = height;
                     }
-                    // WebKit events are handled separately via callbacks
                     #[cfg(feature = "wpe-webkit")]
-                    InputEvent::WebKitTitleChanged { .. }
-                    | InputEvent::WebKitUrlChanged { .. }
-                    | InputEvent::WebKitProgressChanged { .. }
-                    | InputEvent::WebKitLoadFinished { .. } => {
-                        // Skip these in the event queue - they're handled via webkit-specific API
-                        continue;
+                    InputEvent::WebKitTitleChanged { id, title } => {
+                        out.kind = NEOMACS_EVENT_WEBKIT_TITLE_CHANGED;
+                        out.keysym = id;  // reuse keysym field for view ID
+                        push_event_text(out.kind, id, title);
+                    }
+                    #[cfg(feature = "wpe-webkit")]
+                    InputEvent::WebKitUrlChanged { id, url } => {
+                        out.kind = NEOMACS_EVENT_WEBKIT_URL_CHANGED;
+                        out.keysym = id;
+                        push_event_text(out.kind, id, url);
+                    }
+                    #[cfg(feature = "wpe-webkit")]
+                    InputEvent::WebKitProgressChanged { id, progress } => {
+                        out.kind = NEOMACS_EVENT_WEBKIT_PROGRESS_CHANGED;
+                        out.keysym = id;
+                        out.x = (progress * 100.0).round() as i32;  // percent
+                    }
+                    #[cfg(feature = "wpe-webkit")]
+                    InputEvent::WebKitLoadFinished { id } => {
+                        out.kind = NEOMACS_EVENT_WEBKIT_LOAD_FINISHED;
+                        out.keysym = id;
+                    }
+                    #[cfg(feature = "video")]
+                    InputEvent::VideoEnded { id } => {
+                        out.kind = NEOMACS_EVENT_VIDEO_ENDED;
+                        out.keysym = id;  // reuse keysym field for video ID
+                    }
+                    InputEvent::AnimationFinished { window_id, scroll } => {
+                        out.kind = NEOMACS_EVENT_ANIMATION_FINISHED;
+                        // Reuse timestamp field for the window pointer
+                        out.timestamp = window_id as u64;
+                        out.button = scroll as u32;
+                    }
+                    InputEvent::AnimationCompleted { id } => {
+                        out.kind = NEOMACS_EVENT_ANIMATION_COMPLETED;
+                        out.keysym = id;  // notify id given at start
+                    }
+                    InputEvent::PipClosed { id } => {
+                        out.kind = NEOMACS_EVENT_PIP_CLOSED;
+                        out.keysym = id;  // video ID
                     }
+                    #[cfg(feature = "video")]
+                    InputEvent::AudioLevels { id } => {
+                        out.kind = NEOMACS_EVENT_AUDIO_LEVELS;
+                        out.keysym = id;  // video ID
+                    }
+                    InputEvent::DisplayError { id } => {
+                        out.kind = NEOMACS_EVENT_DISPLAY_ERROR;
+                        out.keysym = id;  // newest error report id
+                    }
+                    InputEvent::AppearanceChanged { dark } => {
+                        out.kind = NEOMACS_EVENT_APPEARANCE_CHANGED;
+                        out.keysym = dark as u32;
+                    }
+                    InputEvent::FacesOutOfSync => unreachable!("handled before the match"),
                     // Terminal events
                     #[cfg(feature = "neo-term")]
                     InputEvent::TerminalExited { id } => {
@@ -4886,12 +8432,36 @@ pub unsafe extern "C" fn neomacs_display_drain_input(
                         out.keysym = id;  // reuse keysym field for terminal ID
                     }
                     #[cfg(feature = "neo-term")]
+                    InputEvent::TerminalBell { id } => {
+                        out.kind = NEOMACS_EVENT_TERMINAL_BELL;
+                        out.keysym = id;
+                    }
+                    #[cfg(feature = "neo-term")]
+                    InputEvent::TmuxPaneAdded { client, pane, window, id, cols, rows } => {
+                        out.kind = NEOMACS_EVENT_TMUX_PANE_ADDED;
+                        out.keysym = id;
+                        out.modifiers = client;
+                        out.x = pane as i32;
+                        out.y = window as i32;
+                        out.width = cols as u32;
+                        out.height = rows as u32;
+                    }
+                    #[cfg(feature = "neo-term")]
+                    InputEvent::TmuxPaneClosed { client, id } => {
+                        out.kind = NEOMACS_EVENT_TMUX_PANE_CLOSED;
+                        out.keysym = id;
+                        out.modifiers = client;
+                    }
+                    #[cfg(feature = "neo-term")]
+                    InputEvent::TmuxExited { client } => {
+                        out.kind = NEOMACS_EVENT_TMUX_EXITED;
+                        out.keysym = client;
+                    }
+                    #[cfg(feature = "neo-term")]
                     InputEvent::TerminalTitleChanged { id, title } => {
                         out.kind = NEOMACS_EVENT_TERMINAL_TITLE_CHANGED;
                         out.keysym = id;
-                        if let Ok(mut queue) = TERMINAL_TITLES.lock() {
-                            queue.push((id, title));
-                        }
+                        push_event_text(out.kind, id, title);
                     }
                     InputEvent::MenuSelection { index } => {
                         out.kind = NEOMACS_EVENT_MENU_SELECTION;
@@ -4968,20 +8538,143 @@ pub unsafe extern "C" fn neomacs_display_free_dropped_path(path: *mut c_char) {
 pub unsafe extern "C" fn neomacs_display_get_terminal_title(
     terminal_id: u32,
 ) -> *mut c_char {
-    let mut queue = match TERMINAL_TITLES.lock() {
+    neomacs_display_get_event_text(NEOMACS_EVENT_TERMINAL_TITLE_CHANGED, terminal_id)
+}
+
+/// Get the text of the oldest pending event of `kind` for `id`: the new
+/// title or URL of NEOMACS_EVENT_TERMINAL_TITLE_CHANGED,
+/// NEOMACS_EVENT_WEBKIT_TITLE_CHANGED and NEOMACS_EVENT_WEBKIT_URL_CHANGED.
+/// Returns a C string that must be freed with
+/// `neomacs_display_free_dropped_path`, or NULL.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_get_event_text(kind: u32, id: u32) -> *mut c_char {
+    let mut queue = match EVENT_TEXTS.lock() {
         Ok(q) => q,
         Err(_) => return std::ptr::null_mut(),
     };
-    // Find and remove the first entry matching terminal_id
-    if let Some(pos) = queue.iter().position(|(id, _)| *id == terminal_id) {
-        let (_id, title) = queue.remove(pos);
-        match std::ffi::CString::new(title) {
-            Ok(cstr) => cstr.into_raw(),
-            Err(_) => std::ptr::null_mut(),
+    // Find and remove the first entry matching kind and id
+    match queue.iter().position(|(k, i, _)| *k == kind && *i == id) {
+        Some(pos) => {
+            let (_, _, text) = queue.remove(pos);
+            CString::new(text).map_or(std::ptr::null_mut(), CString::into_raw)
         }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Number of events waiting to be drained with `neomacs_display_drain_input`
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_pending_events() -> c_int {
+    THREADED_STATE
+        .as_ref()
+        .map_or(0, |state| state.emacs_comms.input_rx.len() as c_int)
+}
+
+/// Register `callback` to be called with `user_data` whenever an event is
+/// queued, or unregister with NULL.  The callback runs on the render thread
+/// and should only schedule a `neomacs_display_drain_input` on the host's
+/// own thread.  Emacs selects on the wakeup fd instead and needs no callback.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_set_event_callback(
+    callback: Option<extern "C" fn(*mut c_void)>,
+    user_data: *mut c_void,
+) {
+    let Some(ref state) = THREADED_STATE else {
+        return;
+    };
+    if let Ok(mut notify) = state.emacs_comms.event_notify.lock() {
+        *notify = callback.map(|func| crate::thread_comm::EventNotify { func, user_data });
+    }
+}
+
+/// Get a thread-safe command handle for the running render thread.
+///
+/// Unlike the other entry points, the `neomacs_display_handle_*` functions
+/// may be called from any thread.  Returns NULL before `init_threaded`.
+/// Free with `neomacs_display_handle_free`.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_get_handle() -> *mut crate::display_handle::DisplayHandle {
+    match THREADED_STATE.as_ref() {
+        Some(state) => Box::into_raw(Box::new(crate::display_handle::DisplayHandle::new(
+            state.emacs_comms.cmd_tx.clone(),
+        ))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Free a handle returned by `neomacs_display_get_handle`.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_handle_free(handle: *mut crate::display_handle::DisplayHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Queue an image load from any thread.  Returns the image id, or 0 on error.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_handle_load_image_file(
+    handle: *const crate::display_handle::DisplayHandle,
+    path: *const c_char,
+    max_width: c_int,
+    max_height: c_int,
+) -> u32 {
+    if handle.is_null() || path.is_null() {
+        return 0;
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return 0;
+    };
+    (*handle)
+        .load_image_file(path, max_width.max(0) as u32, max_height.max(0) as u32)
+        .unwrap_or_else(|e| {
+            warn!("handle_load_image_file: {}", e);
+            0
+        })
+}
+
+/// Queue a transition from any thread.  WINDOW_ID 0 means every window;
+/// EFFECT NULL means crossfade.  Returns 1 on success.
+#[cfg(feature = "winit-backend")]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_handle_start_transition(
+    handle: *const crate::display_handle::DisplayHandle,
+    window_id: i64,
+    effect: *const c_char,
+    duration_ms: c_int,
+) -> c_int {
+    if handle.is_null() {
+        return 0;
+    }
+    let effect = if effect.is_null() {
+        crate::core::scroll_animation::ScrollEffect::Crossfade
     } else {
-        std::ptr::null_mut()
+        crate::core::scroll_animation::ScrollEffect::from_str(&CStr::from_ptr(effect).to_string_lossy())
+    };
+    let window_id = (window_id != 0).then_some(window_id);
+    let duration = std::time::Duration::from_millis(duration_ms.max(0) as u64);
+    (*handle).start_transition(window_id, effect, duration).is_ok() as c_int
+}
+
+/// Queue terminal input from any thread.  Returns 1 on success.
+#[cfg(all(feature = "winit-backend", feature = "neo-term"))]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_display_handle_terminal_write(
+    handle: *const crate::display_handle::DisplayHandle,
+    terminal_id: u32,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    if handle.is_null() || (data.is_null() && len > 0) {
+        return 0;
     }
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(data, len) };
+    (*handle).terminal_write(terminal_id, bytes).is_ok() as c_int
 }
 
 /// Send frame glyphs to render thread
@@ -4992,7 +8685,7 @@ pub unsafe extern "C" fn neomacs_display_send_frame(handle: *mut NeomacsDisplay)
         return;
     }
 
-    let display = &*handle;
+    let display = &mut *handle;
 
     let state = match THREADED_STATE.as_ref() {
         Some(s) => s,
@@ -5000,8 +8693,10 @@ pub unsafe extern "C" fn neomacs_display_send_frame(handle: *mut NeomacsDisplay)
     };
 
     // Clone frame glyphs and send to render thread
-    let frame = display.frame_glyphs.clone();
-    let _ = state.emacs_comms.frame_tx.try_send(frame);
+    let frame = display.frame_to_send(&state.emacs_comms);
+    if state.emacs_comms.frame_tx.try_send(frame).is_ok() {
+        crate::core::input_latency::frame_sent();
+    }
 }
 
 /// Send command to render thread
@@ -5025,6 +8720,7 @@ pub unsafe extern "C" fn neomacs_display_send_command(
             id,
             width: param1,
             height: param2,
+            profile: None,
         },
         2 => {
             let url = if str_param.is_null() {
@@ -5995,6 +9691,13 @@ effect_setter!(neomacs_display_set_cursor_bubble(enabled: c_int, r: c_int, g: c_
                     effects.cursor_bubble.opacity = opacity as f32 / 100.0;
 });
 
+/// Configure blurring of the backdrop behind floating content
+effect_setter!(neomacs_display_set_backdrop_blur(enabled: c_int, radius: c_int, passes: c_int) |effects| {
+        effects.backdrop_blur.enabled = enabled != 0;
+                    effects.backdrop_blur.radius = radius.max(1) as f32;
+                    effects.backdrop_blur.passes = passes.clamp(1, 3) as u32;
+});
+
 
 /// Shutdown threaded display
 #[cfg(feature = "winit-backend")]
@@ -6106,7 +9809,7 @@ pub unsafe extern "C" fn neomacs_clipboard_free_text(text: *mut c_char) {
 
 /// Set primary selection text.  The text is a UTF-8 C string.
 /// Returns 0 on success, -1 on failure.
-#[cfg(feature = "winit-backend")]
+#[cfg(all(feature = "winit-backend", target_os = "linux"))]
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_primary_selection_set_text(text: *const c_char) -> c_int {
     use arboard::{LinuxClipboardKind, SetExtLinux};
@@ -6141,7 +9844,7 @@ pub unsafe extern "C" fn neomacs_primary_selection_set_text(text: *const c_char)
 /// Get primary selection text.  Returns a newly allocated UTF-8 C string
 /// that the caller must free with neomacs_clipboard_free_text(),
 /// or NULL if the selection is empty or an error occurred.
-#[cfg(feature = "winit-backend")]
+#[cfg(all(feature = "winit-backend", target_os = "linux"))]
 #[no_mangle]
 pub unsafe extern "C" fn neomacs_primary_selection_get_text() -> *mut c_char {
     use arboard::{GetExtLinux, LinuxClipboardKind};
@@ -6165,3 +9868,17 @@ pub unsafe extern "C" fn neomacs_primary_selection_get_text() -> *mut c_char {
         }
     }
 }
+
+/// There is no primary selection outside X11 and Wayland
+#[cfg(all(feature = "winit-backend", not(target_os = "linux")))]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_primary_selection_set_text(_text: *const c_char) -> c_int {
+    -1
+}
+
+/// There is no primary selection outside X11 and Wayland
+#[cfg(all(feature = "winit-backend", not(target_os = "linux")))]
+#[no_mangle]
+pub unsafe extern "C" fn neomacs_primary_selection_get_text() -> *mut c_char {
+    ptr::null_mut()
+}
@@ -0,0 +1,294 @@
+//! Canvases: pictures drawn from a list of 2D commands.
+//!
+//! Lisp builds a canvas out of paths, strokes, fills, text, images and
+//! transforms, much as with an HTML canvas, and shows it under an image
+//! id, in the text or floating.  The commands are kept, so a canvas can
+//! be added to or redrawn, and are rasterized on the render thread at
+//! the display's scale, so curves and text stay sharp.  Text is drawn
+//! from glyph outlines of the fonts the display already has.
+
+use std::path::PathBuf;
+
+use cosmic_text::{Attrs, Buffer, Command, Family, FontSystem, Metrics, Shaping, SwashCache};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, PixmapPaint, Stroke, Transform};
+
+/// One drawing command.  Colors are 0xAARRGGBB with straight alpha; an
+/// alpha of 0 is opaque, as for colors named without one.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub enum CanvasOp {
+    /// Fill the whole canvas, whatever the transform
+    Clear(u32),
+    /// Start a new path, dropping the current one
+    BeginPath,
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    /// Quadratic curve through a control point to (x, y)
+    QuadTo(f32, f32, f32, f32),
+    /// Cubic curve through two control points to (x, y)
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    ClosePath,
+    /// Add a rectangle x, y, width, height to the path
+    Rect(f32, f32, f32, f32),
+    /// Add a circle around (x, y) to the path
+    Circle(f32, f32, f32),
+    /// Fill the path, keeping it
+    Fill(u32),
+    /// Stroke the path with lines `width` wide, keeping it
+    Stroke { color: u32, width: f32 },
+    /// Text `size` pixels high with its baseline starting at (x, y)
+    Text { x: f32, y: f32, size: f32, color: u32, text: String },
+    /// An image file stretched over x, y, width, height
+    Image { path: PathBuf, x: f32, y: f32, width: f32, height: f32 },
+    /// Remember the transform, to go back to it with `Restore`
+    Save,
+    Restore,
+    Translate(f32, f32),
+    Scale(f32, f32),
+    /// Rotate clockwise by degrees
+    Rotate(f32),
+}
+
+impl CanvasOp {
+    /// The command `name` with numeric arguments `args`, `color` and
+    /// `text` as C passes them
+    pub fn parse(name: &str, args: &[f32], color: u32, text: Option<&str>) -> Result<Self, String> {
+        let arg = |i: usize| {
+            args.get(i).copied().ok_or_else(|| format!("{} needs {} numbers, got {}", name, i + 1, args.len()))
+        };
+        let text = || text.map(str::to_string).ok_or_else(|| format!("{} needs a string", name));
+        Ok(match name {
+            "clear" => Self::Clear(color),
+            "begin-path" => Self::BeginPath,
+            "move-to" => Self::MoveTo(arg(0)?, arg(1)?),
+            "line-to" => Self::LineTo(arg(0)?, arg(1)?),
+            "quad-to" => Self::QuadTo(arg(0)?, arg(1)?, arg(2)?, arg(3)?),
+            "cubic-to" => Self::CubicTo(arg(0)?, arg(1)?, arg(2)?, arg(3)?, arg(4)?, arg(5)?),
+            "close-path" => Self::ClosePath,
+            "rect" => Self::Rect(arg(0)?, arg(1)?, arg(2)?, arg(3)?),
+            "circle" => Self::Circle(arg(0)?, arg(1)?, arg(2)?),
+            "fill" => Self::Fill(color),
+            "stroke" => Self::Stroke { color, width: args.first().copied().unwrap_or(1.0) },
+            "text" => Self::Text { x: arg(0)?, y: arg(1)?, size: arg(2)?, color, text: text()? },
+            "image" => Self::Image {
+                path: PathBuf::from(text()?),
+                x: arg(0)?,
+                y: arg(1)?,
+                width: arg(2)?,
+                height: arg(3)?,
+            },
+            "save" => Self::Save,
+            "restore" => Self::Restore,
+            "translate" => Self::Translate(arg(0)?, arg(1)?),
+            "scale" => {
+                let sx = arg(0)?;
+                Self::Scale(sx, args.get(1).copied().unwrap_or(sx))
+            }
+            "rotate" => Self::Rotate(arg(0)?),
+            _ => return Err(format!("Unknown canvas command: {}", name)),
+        })
+    }
+}
+
+/// A canvas of `width` x `height` logical pixels and its commands
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub ops: Vec<CanvasOp>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, ops: Vec::new() }
+    }
+}
+
+fn paint(color: u32) -> Paint<'static> {
+    let [a, r, g, b] = color.to_be_bytes();
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, if a == 0 { 255 } else { a });
+    paint.anti_alias = true;
+    paint
+}
+
+/// Outlines of `text` laid out from the baseline at (x, y)
+fn text_path(
+    text: &str,
+    x: f32,
+    y: f32,
+    size: f32,
+    fonts: &mut FontSystem,
+    swash: &mut SwashCache,
+) -> Option<tiny_skia::Path> {
+    let mut buffer = Buffer::new(fonts, Metrics::new(size, size * 1.2));
+    buffer.set_size(fonts, None, None);
+    buffer.set_text(fonts, text, Attrs::new().family(Family::SansSerif), Shaping::Advanced);
+    buffer.shape_until_scroll(fonts, false);
+
+    let mut path = PathBuilder::new();
+    let mut first_line = None;
+    for run in buffer.layout_runs() {
+        let top = *first_line.get_or_insert(run.line_y);
+        for glyph in run.glyphs {
+            let key = glyph.physical((0.0, 0.0), 1.0).cache_key;
+            let ox = x + glyph.x + glyph.font_size * glyph.x_offset;
+            let oy = y + run.line_y - top + glyph.y - glyph.font_size * glyph.y_offset;
+            let Some(commands) = swash.get_outline_commands(fonts, key) else {
+                continue;
+            };
+            // Outlines have y up
+            for command in commands {
+                match *command {
+                    Command::MoveTo(p) => path.move_to(ox + p.x, oy - p.y),
+                    Command::LineTo(p) => path.line_to(ox + p.x, oy - p.y),
+                    Command::QuadTo(c, p) => path.quad_to(ox + c.x, oy - c.y, ox + p.x, oy - p.y),
+                    Command::CurveTo(c1, c2, p) => {
+                        path.cubic_to(ox + c1.x, oy - c1.y, ox + c2.x, oy - c2.y, ox + p.x, oy - p.y)
+                    }
+                    Command::Close => path.close(),
+                }
+            }
+        }
+    }
+    path.finish()
+}
+
+/// The image file at `path` as a pixmap
+fn load_image(path: &std::path::Path) -> Option<Pixmap> {
+    let image = image::open(path).ok()?.to_rgba8();
+    let (w, h) = image.dimensions();
+    let mut data = image.into_raw();
+    for px in data.chunks_exact_mut(4) {
+        let a = px[3] as u16;
+        for c in &mut px[..3] {
+            *c = ((*c as u16 * a + 127) / 255) as u8;
+        }
+    }
+    Pixmap::from_vec(data, tiny_skia::IntSize::from_wh(w, h)?)
+}
+
+/// Pixels of `canvas` at `scale` device pixels per logical pixel, as
+/// width, height and straight RGBA
+pub fn rasterize(canvas: &Canvas, scale: f32, fonts: &mut FontSystem, swash: &mut SwashCache) -> (u32, u32, Vec<u8>) {
+    let w = ((canvas.width as f32 * scale).round() as u32).max(1);
+    let h = ((canvas.height as f32 * scale).round() as u32).max(1);
+    let Some(mut pixmap) = Pixmap::new(w, h) else {
+        return (w, h, vec![0; (w * h * 4) as usize]);
+    };
+
+    let mut transform = Transform::from_scale(scale, scale);
+    let mut saved = Vec::new();
+    let mut path = PathBuilder::new();
+    for op in &canvas.ops {
+        match op {
+            CanvasOp::Clear(color) => {
+                let [a, r, g, b] = color.to_be_bytes();
+                pixmap.fill(tiny_skia::Color::from_rgba8(r, g, b, if a == 0 { 255 } else { a }));
+            }
+            CanvasOp::BeginPath => path = PathBuilder::new(),
+            CanvasOp::MoveTo(x, y) => path.move_to(*x, *y),
+            CanvasOp::LineTo(x, y) => path.line_to(*x, *y),
+            CanvasOp::QuadTo(cx, cy, x, y) => path.quad_to(*cx, *cy, *x, *y),
+            CanvasOp::CubicTo(c1x, c1y, c2x, c2y, x, y) => path.cubic_to(*c1x, *c1y, *c2x, *c2y, *x, *y),
+            CanvasOp::ClosePath => path.close(),
+            CanvasOp::Rect(x, y, width, height) => {
+                if let Some(rect) = tiny_skia::Rect::from_xywh(*x, *y, *width, *height) {
+                    path.push_rect(rect);
+                }
+            }
+            CanvasOp::Circle(x, y, r) => path.push_circle(*x, *y, *r),
+            CanvasOp::Fill(color) => {
+                if let Some(p) = path.clone().finish() {
+                    pixmap.fill_path(&p, &paint(*color), FillRule::Winding, transform, None);
+                }
+            }
+            CanvasOp::Stroke { color, width } => {
+                if let Some(p) = path.clone().finish() {
+                    let stroke = Stroke {
+                        width: *width,
+                        line_cap: tiny_skia::LineCap::Round,
+                        line_join: tiny_skia::LineJoin::Round,
+                        ..Stroke::default()
+                    };
+                    pixmap.stroke_path(&p, &paint(*color), &stroke, transform, None);
+                }
+            }
+            CanvasOp::Text { x, y, size, color, text } => {
+                if let Some(p) = text_path(text, *x, *y, size.max(1.0), fonts, swash) {
+                    pixmap.fill_path(&p, &paint(*color), FillRule::Winding, transform, None);
+                }
+            }
+            CanvasOp::Image { path: file, x, y, width, height } => match load_image(file) {
+                Some(image) => {
+                    let placed = transform
+                        .pre_translate(*x, *y)
+                        .pre_scale(width / image.width() as f32, height / image.height() as f32);
+                    let paint = PixmapPaint { quality: tiny_skia::FilterQuality::Bilinear, ..PixmapPaint::default() };
+                    pixmap.draw_pixmap(0, 0, image.as_ref(), &paint, placed, None);
+                }
+                None => log::warn!("Canvas image {} could not be loaded", file.display()),
+            },
+            CanvasOp::Save => saved.push(transform),
+            CanvasOp::Restore => transform = saved.pop().unwrap_or(Transform::from_scale(scale, scale)),
+            CanvasOp::Translate(x, y) => transform = transform.pre_translate(*x, *y),
+            CanvasOp::Scale(sx, sy) => transform = transform.pre_scale(*sx, *sy),
+            CanvasOp::Rotate(degrees) => transform = transform.pre_concat(Transform::from_rotate(*degrees)),
+        }
+    }
+
+    let mut data = Vec::with_capacity((w * h * 4) as usize);
+    for px in pixmap.pixels() {
+        let c = px.demultiply();
+        data.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+    (w, h, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas_parse_commands() {
+        assert_eq!(CanvasOp::parse("move-to", &[1.0, 2.0], 0, None), Ok(CanvasOp::MoveTo(1.0, 2.0)));
+        assert_eq!(CanvasOp::parse("scale", &[2.0], 0, None), Ok(CanvasOp::Scale(2.0, 2.0)));
+        assert_eq!(
+            CanvasOp::parse("stroke", &[], 0xFF0000, None),
+            Ok(CanvasOp::Stroke { color: 0xFF0000, width: 1.0 })
+        );
+        assert_eq!(
+            CanvasOp::parse("text", &[0.0, 10.0, 12.0], 0, Some("hi")),
+            Ok(CanvasOp::Text { x: 0.0, y: 10.0, size: 12.0, color: 0, text: "hi".into() })
+        );
+        assert!(CanvasOp::parse("rect", &[0.0, 0.0, 4.0], 0, None).is_err());
+        assert!(CanvasOp::parse("text", &[0.0, 0.0, 12.0], 0, None).is_err());
+        assert!(CanvasOp::parse("spiral", &[], 0, None).is_err());
+    }
+
+    #[test]
+    fn test_canvas_rasterize_fill_and_transform() {
+        let mut canvas = Canvas::new(8, 4);
+        canvas.ops = vec![
+            CanvasOp::Clear(0xFF_FFFFFF),
+            CanvasOp::Save,
+            CanvasOp::Translate(4.0, 0.0),
+            CanvasOp::Rect(0.0, 0.0, 4.0, 4.0),
+            // Alpha 0 is opaque
+            CanvasOp::Fill(0x00_FF0000),
+            CanvasOp::Restore,
+            CanvasOp::BeginPath,
+            CanvasOp::Rect(0.0, 0.0, 1.0, 1.0),
+            CanvasOp::Fill(0x80_0000FF),
+        ];
+        let mut fonts = FontSystem::new_with_locale_and_db("en-US".into(), Default::default());
+        let (w, h, data) = rasterize(&canvas, 2.0, &mut fonts, &mut SwashCache::new());
+        assert_eq!((w, h), (16, 8));
+        let pixel = |x: u32, y: u32| &data[((y * w + x) * 4) as usize..][..4];
+        assert_eq!(pixel(0, 0)[2], 255);
+        assert!(pixel(0, 0)[0] < 255);
+        assert_eq!(pixel(4, 4), [255, 255, 255, 255]);
+        assert_eq!(pixel(12, 4), [255, 0, 0, 255]);
+    }
+}
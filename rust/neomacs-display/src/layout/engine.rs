@@ -11,6 +11,7 @@ use crate::core::frame_glyphs::FrameGlyphBuffer;
 use crate::core::types::{Color, Rect};
 use super::types::*;
 use super::emacs_ffi::*;
+use super::highlight::{HighlightFace, HighlightStore};
 
 // ============================================================================
 // Hit-test infrastructure: maps pixel coordinates to buffer char positions.
@@ -192,6 +193,7 @@ impl LayoutEngine {
         frame: EmacsFrame,
         frame_params: &FrameParams,
         frame_glyphs: &mut FrameGlyphBuffer,
+        highlights: &HighlightStore,
     ) {
         // Set up frame dimensions
         frame_glyphs.width = frame_params.width;
@@ -313,7 +315,7 @@ impl LayoutEngine {
             );
 
             // Layout this window's content
-            self.layout_window(&params, &wp, frame, frame_glyphs);
+            self.layout_window(&params, &wp, frame, frame_glyphs, highlights);
 
             // Draw window dividers or simple vertical border
             let right_edge = params.bounds.x + params.bounds.width;
@@ -450,6 +452,7 @@ impl LayoutEngine {
         wp: &WindowParamsFFI,
         frame: EmacsFrame,
         frame_glyphs: &mut FrameGlyphBuffer,
+        highlights: &HighlightStore,
     ) {
         let buffer = wp.buffer_ptr;
         let window = wp.window_ptr;
@@ -577,6 +580,9 @@ impl LayoutEngine {
         let mut next_face_check: i64 = 0;
         let mut face_fg = default_fg;
         let mut face_bg = default_bg;
+        // Background syntax highlight applied on top of the current face
+        let mut current_highlight: Option<HighlightFace> = None;
+        let use_highlights = !highlights.is_empty();
 
         // Invisible text state: next charpos where we need to re-check
         let mut next_invis_check: i64 = window_start;
@@ -1530,9 +1536,16 @@ impl LayoutEngine {
                     &mut self.face_data as *mut FaceDataFFI,
                     &mut next_check,
                 );
+                // Offloaded highlight spans only color text that Lisp
+                // left in the default face
+                let (highlight, highlight_limit) = if use_highlights && fid == 0 {
+                    highlights.lookup(params.buffer_id, charpos)
+                } else {
+                    (None, i64::MAX)
+                };
 
                 if fid >= 0 {
-                    if fid != current_face_id {
+                    if fid != current_face_id || highlight != current_highlight {
                         // Close previous box face region if active
                         if box_active {
                             let box_end_x = content_x + x_offset;
@@ -1571,6 +1584,11 @@ impl LayoutEngine {
                             face_ascent = ascent;
                         }
                         self.apply_face(&self.face_data, frame_glyphs);
+                        current_highlight = highlight;
+                        if let Some(hl) = highlight {
+                            face_fg = hl.fg;
+                            frame_glyphs.set_face_highlight(hl.fg, hl.bold, hl.italic);
+                        }
 
                         // Debug: check all face properties
                         if charpos < window_start + 5 {
@@ -1593,6 +1611,7 @@ impl LayoutEngine {
                     }
                     // next_check is 0 when face_at_buffer_position returns no limit
                     next_face_check = if next_check > charpos { next_check } else { charpos + 1 };
+                    next_face_check = next_face_check.min(highlight_limit);
                 } else {
                     // Fallback to default face
                     next_face_check = charpos + 1;
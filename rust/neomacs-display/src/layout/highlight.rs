@@ -0,0 +1,438 @@
+//! Syntax highlighting offloaded to a background thread.
+//!
+//! Fontifying huge files in Lisp blocks the editor.  Instead the host can
+//! send the raw text of a buffer (or of a single code block inside it)
+//! together with a language name; a worker thread parses it with
+//! tree-sitter and returns face spans.  The layout engine merges those
+//! spans into text that Lisp left in the default face, so regular
+//! font-lock always takes precedence.
+
+use std::collections::HashMap;
+
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::core::types::Color;
+
+/// Highlight category a tree-sitter capture maps to
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    Keyword = 0,
+    String = 1,
+    Comment = 2,
+    Function = 3,
+    Type = 4,
+    Constant = 5,
+    Number = 6,
+    Variable = 7,
+    Property = 8,
+    Operator = 9,
+    Punctuation = 10,
+    Attribute = 11,
+    Label = 12,
+    Escape = 13,
+    Constructor = 14,
+    Builtin = 15,
+}
+
+impl HighlightKind {
+    pub const COUNT: usize = 16;
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        use HighlightKind::*;
+        const ALL: [HighlightKind; HighlightKind::COUNT] = [
+            Keyword, String, Comment, Function, Type, Constant, Number, Variable,
+            Property, Operator, Punctuation, Attribute, Label, Escape, Constructor,
+            Builtin,
+        ];
+        ALL.get(v as usize).copied()
+    }
+
+    /// Map a capture name from a highlights query (`keyword.control`,
+    /// `function.builtin`, ...) to a category.
+    pub fn from_capture(name: &str) -> Option<Self> {
+        use HighlightKind::*;
+        let mut parts = name.split('.');
+        let head = parts.next()?;
+        let builtin = name.ends_with(".builtin");
+        Some(match head {
+            "keyword" | "conditional" | "repeat" | "include" | "exception" => Keyword,
+            "string" if name.contains("escape") => Escape,
+            "string" | "character" => String,
+            "escape" => Escape,
+            "comment" => Comment,
+            "function" | "method" if builtin => Builtin,
+            "function" | "method" => Function,
+            "type" | "constant" | "variable" if builtin => Builtin,
+            "type" => Type,
+            "constant" => Constant,
+            "number" | "float" | "boolean" => Number,
+            "variable" | "parameter" => Variable,
+            "property" | "field" => Property,
+            "operator" => Operator,
+            "punctuation" => Punctuation,
+            "attribute" => Attribute,
+            "label" | "tag" => Label,
+            "constructor" => Constructor,
+            _ => return None,
+        })
+    }
+}
+
+/// A highlighted character range `[start, end)` in buffer positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightSpan {
+    pub start: i64,
+    pub end: i64,
+    pub kind: HighlightKind,
+}
+
+/// Colors and weight used for one highlight category
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HighlightFace {
+    pub fg: Color,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+#[derive(Debug, Default)]
+struct BufferHighlights {
+    /// Bumped on every edit; results computed before an edit are stale
+    edits: u64,
+    /// Non-overlapping spans sorted by start
+    spans: Vec<HighlightSpan>,
+}
+
+/// Face spans per buffer, as read by the layout engine
+#[derive(Debug, Default)]
+pub struct HighlightStore {
+    buffers: HashMap<u64, BufferHighlights>,
+    faces: [Option<HighlightFace>; HighlightKind::COUNT],
+}
+
+impl HighlightStore {
+    /// Set (or clear with `None`) the face drawn for a category
+    pub fn set_face(&mut self, kind: HighlightKind, face: Option<HighlightFace>) {
+        self.faces[kind as usize] = face;
+    }
+
+    /// Replace the spans in `[start, end)` of a buffer with `spans`
+    pub fn replace_range(&mut self, buffer_id: u64, start: i64, end: i64, spans: Vec<HighlightSpan>) {
+        let entry = self.buffers.entry(buffer_id).or_default();
+        let first = entry.spans.partition_point(|s| s.end <= start);
+        let last = entry.spans.partition_point(|s| s.start < end);
+        entry.spans.splice(first..last.max(first), spans);
+    }
+
+    /// Adjust spans after the text in `[beg, old_end)` became `[beg, new_end)`.
+    /// Spans touching the edit are dropped until the next highlight result.
+    pub fn edit(&mut self, buffer_id: u64, beg: i64, old_end: i64, new_end: i64) {
+        let Some(entry) = self.buffers.get_mut(&buffer_id) else { return };
+        entry.edits += 1;
+        let delta = new_end - old_end;
+        entry.spans.retain_mut(|s| {
+            if s.end <= beg {
+                true
+            } else if s.start >= old_end {
+                s.start += delta;
+                s.end += delta;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Drop all spans of a buffer
+    pub fn forget(&mut self, buffer_id: u64) {
+        self.buffers.remove(&buffer_id);
+    }
+
+    fn edits(&self, buffer_id: u64) -> u64 {
+        self.buffers.get(&buffer_id).map_or(0, |b| b.edits)
+    }
+
+    /// Highlight face at `pos`, and the next position where that may change
+    pub fn lookup(&self, buffer_id: u64, pos: i64) -> (Option<HighlightFace>, i64) {
+        let Some(entry) = self.buffers.get(&buffer_id) else {
+            return (None, i64::MAX);
+        };
+        let idx = entry.spans.partition_point(|s| s.end <= pos);
+        match entry.spans.get(idx) {
+            Some(span) if span.start <= pos => (self.faces[span.kind as usize], span.end),
+            Some(span) => (None, span.start),
+            None => (None, i64::MAX),
+        }
+    }
+
+    /// Whether any buffer has spans (lets layout skip lookups entirely)
+    pub fn is_empty(&self) -> bool {
+        self.buffers.values().all(|b| b.spans.is_empty())
+    }
+}
+
+struct HighlightJob {
+    buffer_id: u64,
+    edits: u64,
+    language: String,
+    text: String,
+    base: i64,
+}
+
+struct HighlightResult {
+    buffer_id: u64,
+    edits: u64,
+    base: i64,
+    end: i64,
+    spans: Result<Vec<HighlightSpan>, String>,
+}
+
+/// Highlight store plus the worker thread that fills it
+#[derive(Default)]
+pub struct HighlightService {
+    store: HighlightStore,
+    jobs: Option<Sender<HighlightJob>>,
+    results: Option<Receiver<HighlightResult>>,
+    pending: usize,
+}
+
+impl HighlightService {
+    pub fn store(&self) -> &HighlightStore {
+        &self.store
+    }
+
+    pub fn store_mut(&mut self) -> &mut HighlightStore {
+        &mut self.store
+    }
+
+    /// Queue `text`, which starts at buffer position `base`, for
+    /// highlighting as `language`.  Returns false for unknown languages.
+    pub fn request(&mut self, buffer_id: u64, language: &str, text: String, base: i64) -> bool {
+        if !language_supported(language) {
+            return false;
+        }
+        if self.jobs.is_none() && !self.spawn_worker() {
+            return false;
+        }
+        let job = HighlightJob {
+            buffer_id,
+            edits: self.store.edits(buffer_id),
+            language: language.to_string(),
+            text,
+            base,
+        };
+        match self.jobs.as_ref().map(|tx| tx.send(job)) {
+            Some(Ok(())) => {
+                self.pending += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Merge finished results into the store.
+    /// Returns (results merged, jobs still pending).
+    pub fn poll(&mut self) -> (usize, usize) {
+        let Some(rx) = self.results.as_ref() else { return (0, 0) };
+        let mut merged = 0;
+        while let Ok(result) = rx.try_recv() {
+            self.pending = self.pending.saturating_sub(1);
+            match result.spans {
+                // Text moved since the job was queued; wait for a fresh one
+                Ok(_) if result.edits != self.store.edits(result.buffer_id) => {}
+                Ok(spans) => {
+                    self.store.replace_range(result.buffer_id, result.base, result.end, spans);
+                    merged += 1;
+                }
+                Err(e) => log::warn!("highlight: buffer {:#x}: {}", result.buffer_id, e),
+            }
+        }
+        (merged, self.pending)
+    }
+
+    #[cfg(feature = "highlight")]
+    fn spawn_worker(&mut self) -> bool {
+        let (job_tx, job_rx) = crossbeam_channel::unbounded::<HighlightJob>();
+        let (result_tx, result_rx) = crossbeam_channel::unbounded();
+        let spawned = std::thread::Builder::new()
+            .name("neomacs-highlight".into())
+            .spawn(move || {
+                let mut highlighter = Highlighter::default();
+                while let Ok(job) = job_rx.recv() {
+                    let end = job.base + job.text.chars().count() as i64;
+                    let spans = highlighter
+                        .highlight(&job.language, &job.text)
+                        .map(|spans| {
+                            spans
+                                .into_iter()
+                                .map(|s| HighlightSpan {
+                                    start: s.start + job.base,
+                                    end: s.end + job.base,
+                                    ..s
+                                })
+                                .collect()
+                        });
+                    let result = HighlightResult {
+                        buffer_id: job.buffer_id,
+                        edits: job.edits,
+                        base: job.base,
+                        end,
+                        spans,
+                    };
+                    if result_tx.send(result).is_err() {
+                        break;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            log::error!("highlight: failed to start worker: {}", e);
+            return false;
+        }
+        self.jobs = Some(job_tx);
+        self.results = Some(result_rx);
+        true
+    }
+
+    #[cfg(not(feature = "highlight"))]
+    fn spawn_worker(&mut self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "highlight")]
+fn grammar(language: &str) -> Option<(tree_sitter::Language, &'static str)> {
+    Some(match language {
+        "rust" => (tree_sitter_rust::LANGUAGE.into(), tree_sitter_rust::HIGHLIGHTS_QUERY),
+        "c" => (tree_sitter_c::LANGUAGE.into(), tree_sitter_c::HIGHLIGHT_QUERY),
+        "python" => (tree_sitter_python::LANGUAGE.into(), tree_sitter_python::HIGHLIGHTS_QUERY),
+        "javascript" | "js" => (
+            tree_sitter_javascript::LANGUAGE.into(),
+            tree_sitter_javascript::HIGHLIGHT_QUERY,
+        ),
+        "bash" | "sh" => (tree_sitter_bash::LANGUAGE.into(), tree_sitter_bash::HIGHLIGHT_QUERY),
+        "json" => (tree_sitter_json::LANGUAGE.into(), tree_sitter_json::HIGHLIGHTS_QUERY),
+        _ => return None,
+    })
+}
+
+/// Whether `language` has a built-in grammar
+pub fn language_supported(language: &str) -> bool {
+    #[cfg(feature = "highlight")]
+    {
+        grammar(language).is_some()
+    }
+    #[cfg(not(feature = "highlight"))]
+    {
+        let _ = language;
+        false
+    }
+}
+
+/// Parser and compiled queries, reused across jobs
+#[cfg(feature = "highlight")]
+#[derive(Default)]
+pub struct Highlighter {
+    parser: Option<tree_sitter::Parser>,
+    queries: HashMap<String, (tree_sitter::Language, tree_sitter::Query, Vec<Option<HighlightKind>>)>,
+}
+
+#[cfg(feature = "highlight")]
+impl Highlighter {
+    /// Highlight `text`; span positions are character offsets into it
+    pub fn highlight(&mut self, language: &str, text: &str) -> Result<Vec<HighlightSpan>, String> {
+        use tree_sitter::{Parser, Query, QueryCursor};
+
+        if !self.queries.contains_key(language) {
+            let (lang, source) =
+                grammar(language).ok_or_else(|| format!("unknown language {}", language))?;
+            let query = Query::new(&lang, source).map_err(|e| e.to_string())?;
+            let kinds = query
+                .capture_names()
+                .iter()
+                .map(|name| HighlightKind::from_capture(name))
+                .collect();
+            self.queries.insert(language.to_string(), (lang, query, kinds));
+        }
+        let (lang, query, kinds) = &self.queries[language];
+        let parser = self.parser.get_or_insert_with(Parser::new);
+        parser.set_language(lang).map_err(|e| e.to_string())?;
+        let tree = parser.parse(text, None).ok_or("parse cancelled")?;
+
+        // Captures arrive ordered by start; the first capture of a range
+        // wins, matching the precedence of patterns in highlights queries.
+        let mut byte_spans: Vec<(usize, usize, HighlightKind)> = Vec::new();
+        let mut covered = 0;
+        let mut cursor = QueryCursor::new();
+        for (m, idx) in cursor.captures(query, tree.root_node(), text.as_bytes()) {
+            let capture = m.captures[idx];
+            let Some(kind) = kinds[capture.index as usize] else { continue };
+            let range = capture.node.byte_range();
+            if range.start >= covered && range.end > range.start {
+                covered = range.end;
+                byte_spans.push((range.start, range.end, kind));
+            }
+        }
+
+        // Spans are sorted and disjoint, so one forward pass converts
+        // byte offsets to character offsets.
+        let mut spans = Vec::with_capacity(byte_spans.len());
+        let (mut byte, mut chars) = (0usize, 0i64);
+        let mut advance = |to: usize| {
+            chars += text[byte..to].chars().count() as i64;
+            byte = to;
+            chars
+        };
+        for (start, end, kind) in byte_spans {
+            let start = advance(start);
+            let end = advance(end);
+            spans.push(HighlightSpan { start, end, kind });
+        }
+        Ok(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: i64, end: i64) -> HighlightSpan {
+        HighlightSpan { start, end, kind: HighlightKind::Keyword }
+    }
+
+    #[test]
+    fn test_store_lookup_and_edit() {
+        let mut store = HighlightStore::default();
+        let face = HighlightFace { fg: Color::from_pixel(0xFF0000), bold: true, italic: false };
+        store.set_face(HighlightKind::Keyword, Some(face));
+        store.replace_range(7, 1, 100, vec![span(1, 3), span(10, 14), span(20, 22)]);
+
+        assert_eq!(store.lookup(7, 2), (Some(face), 3));
+        assert_eq!(store.lookup(7, 5), (None, 10));
+        assert_eq!(store.lookup(8, 5), (None, i64::MAX));
+
+        // Typing 2 characters at 12 drops that span and shifts the next
+        store.edit(7, 12, 12, 14);
+        assert_eq!(store.lookup(7, 11), (None, 22));
+        assert_eq!(store.lookup(7, 23), (Some(face), 24));
+
+        // A code-block result only replaces spans inside its range
+        store.replace_range(7, 0, 5, vec![span(4, 5)]);
+        assert_eq!(store.lookup(7, 1), (None, 4));
+        assert_eq!(store.lookup(7, 22), (Some(face), 24));
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_highlight_rust_uses_char_offsets() {
+        let mut highlighter = Highlighter::default();
+        let text = "// héllo\nfn main() { let s = \"x\"; }";
+        let spans = highlighter.highlight("rust", text).unwrap();
+        let kind_at = |pos: i64| spans.iter().find(|s| s.start <= pos && pos < s.end).map(|s| s.kind);
+        assert_eq!(kind_at(0), Some(HighlightKind::Comment));
+        // "fn" starts at character 9 even though "é" is two bytes
+        assert_eq!(kind_at(9), Some(HighlightKind::Keyword));
+        let string_start = text.chars().position(|c| c == '"').unwrap() as i64;
+        assert_eq!(kind_at(string_start), Some(HighlightKind::String));
+        assert!(highlighter.highlight("cobol", text).is_err());
+    }
+}
@@ -0,0 +1,890 @@
+//! Lightweight HTML renderer for simple rich content.
+//!
+//! Handles the subset of HTML produced by Markdown converters and found in
+//! plain email bodies (headings, paragraphs, lists, emphasis, inline and
+//! preformatted code, links, block quotes, rules and images) and lays it
+//! out directly as `FrameGlyphBuffer` primitives.  This lets previews be
+//! shown without the `wpe-webkit` feature.
+//!
+//! Layout is deliberately simple: advances come from the frame's default
+//! character cell width scaled by the font size, words wrap greedily, and
+//! unknown tags are treated as transparent containers.
+
+use crate::core::frame_glyphs::FrameGlyphBuffer;
+use crate::core::types::{Color, Rect};
+
+/// Faces and metrics used for rendering HTML content.
+///
+/// Face IDs refer to faces already registered with the frame; bold and
+/// italic text are rendered with separate faces because the glyph atlas
+/// selects the font by face ID.
+#[derive(Debug, Clone)]
+pub struct HtmlStyle {
+    /// Face for normal body text
+    pub face_id: u32,
+    /// Face for bold text (`<b>`, `<strong>`, headings)
+    pub bold_face_id: u32,
+    /// Face for italic text (`<i>`, `<em>`)
+    pub italic_face_id: u32,
+    /// Face for inline and preformatted code
+    pub code_face_id: u32,
+    /// Face for link text
+    pub link_face_id: u32,
+    /// Body font size in pixels
+    pub font_size: f32,
+    /// Body line height in pixels
+    pub line_height: f32,
+    /// Advance of one character at `font_size`
+    pub char_width: f32,
+    /// Body text color
+    pub fg: Color,
+    /// Link text color
+    pub link_fg: Color,
+    /// Background of code spans and `<pre>` blocks
+    pub code_bg: Color,
+    /// Color of block quote bars and horizontal rules
+    pub rule_color: Color,
+}
+
+impl HtmlStyle {
+    /// Build a style using a single face for everything.
+    pub fn with_face(face_id: u32, font_size: f32, line_height: f32, char_width: f32, fg: Color) -> Self {
+        Self {
+            face_id,
+            bold_face_id: face_id,
+            italic_face_id: face_id,
+            code_face_id: face_id,
+            link_face_id: face_id,
+            font_size,
+            line_height,
+            char_width,
+            fg,
+            link_fg: Color::rgb(0.25, 0.5, 1.0),
+            code_bg: Color::new(0.5, 0.5, 0.5, 0.15),
+            rule_color: Color::new(0.5, 0.5, 0.5, 0.6),
+        }
+    }
+}
+
+/// An image referenced by `<img src=...>`, resolved by the host.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HtmlImage {
+    /// Image ID in the renderer's image cache
+    pub image_id: u32,
+    /// Natural width in pixels
+    pub width: f32,
+    /// Natural height in pixels
+    pub height: f32,
+}
+
+/// A clickable link area produced by layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlLink {
+    /// Frame-absolute bounds of one line fragment of the link
+    pub bounds: Rect,
+    /// Link target (`href` attribute)
+    pub href: String,
+}
+
+/// Result of laying out an HTML fragment.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlLayout {
+    /// Total height consumed, in pixels
+    pub height: f32,
+    /// Link fragments, in document order
+    pub links: Vec<HtmlLink>,
+}
+
+impl HtmlLayout {
+    /// Find the link target under a frame-absolute point.
+    pub fn link_at(&self, x: f32, y: f32) -> Option<&str> {
+        self.links.iter()
+            .find(|l| l.bounds.contains(crate::core::types::Point::new(x, y)))
+            .map(|l| l.href.as_str())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tokenizer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open { name: String, attrs: Vec<(String, String)>, self_closing: bool },
+    Close(String),
+    Text(String),
+}
+
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = match after.find("-->") {
+                Some(end) => &after[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = match rest.find('>') {
+                Some(end) => &rest[end + 1..],
+                None => "",
+            };
+            continue;
+        }
+        if rest.starts_with('<') {
+            let is_tag = rest[1..].chars().next()
+                .map(|c| c.is_ascii_alphabetic() || c == '/')
+                .unwrap_or(false);
+            if is_tag {
+                let end = rest.find('>').unwrap_or(rest.len());
+                let inner = &rest[1..end];
+                rest = if end < rest.len() { &rest[end + 1..] } else { "" };
+                let token = parse_tag(inner);
+                // Raw text elements: skip everything up to the closing tag.
+                if let Token::Open { ref name, self_closing: false, .. } = token {
+                    if name == "script" || name == "style" {
+                        let close = format!("</{}", name);
+                        let lower = rest.to_ascii_lowercase();
+                        rest = match lower.find(&close) {
+                            Some(pos) => match rest[pos..].find('>') {
+                                Some(gt) => &rest[pos + gt + 1..],
+                                None => "",
+                            },
+                            None => "",
+                        };
+                        continue;
+                    }
+                }
+                tokens.push(token);
+                continue;
+            }
+        }
+        let end = rest.char_indices().skip(1)
+            .find(|&(_, c)| c == '<')
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        tokens.push(Token::Text(decode_entities(&rest[..end])));
+        rest = &rest[end..];
+    }
+    tokens
+}
+
+fn parse_tag(inner: &str) -> Token {
+    let inner = inner.trim();
+    if let Some(name) = inner.strip_prefix('/') {
+        return Token::Close(name.trim().to_ascii_lowercase());
+    }
+    let self_closing = inner.ends_with('/');
+    let inner = inner.trim_end_matches('/');
+    let name_end = inner.find(|c: char| c.is_whitespace()).unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let quote = after_eq.chars().next();
+            if let Some(q @ ('"' | '\'')) = quote {
+                let body = &after_eq[1..];
+                let close = body.find(q).unwrap_or(body.len());
+                value = decode_entities(&body[..close]);
+                rest = if close < body.len() { &body[close + 1..] } else { "" };
+            } else {
+                let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                value = decode_entities(&after_eq[..end]);
+                rest = &after_eq[end..];
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+        rest = rest.trim_start();
+    }
+
+    Token::Open { name, attrs, self_closing }
+}
+
+/// Decode the HTML character references that appear in practice.
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let semi = rest.find(';').filter(|&p| p <= 10);
+        let decoded = semi.and_then(|semi| {
+            let entity = &rest[1..semi];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{00A0}'),
+                "mdash" => Some('\u{2014}'),
+                "ndash" => Some('\u{2013}'),
+                "hellip" => Some('\u{2026}'),
+                "copy" => Some('\u{00A9}'),
+                _ => {
+                    let num = entity.strip_prefix('#')?;
+                    let code = match num.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => num.parse().ok()?,
+                    };
+                    char::from_u32(code)
+                }
+            };
+            c.map(|c| (c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Layout
+// ---------------------------------------------------------------------------
+
+/// Inline style in effect for a run of text.
+#[derive(Debug, Clone, Copy, Default)]
+struct Inline {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strike: bool,
+    code: bool,
+    /// Index into `Layouter::hrefs` when inside a link
+    link: Option<usize>,
+    /// Font scale relative to the body size
+    scale: f32,
+}
+
+/// An item waiting on the current line.
+#[derive(Debug, Clone)]
+enum LineItem {
+    Char { c: char, x: f32, width: f32, style: Inline },
+    Image { image: HtmlImage, x: f32, width: f32, height: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ListState {
+    ordered: bool,
+    next: u32,
+}
+
+struct Layouter<'a> {
+    style: &'a HtmlStyle,
+    buf: &'a mut FrameGlyphBuffer,
+    x0: f32,
+    width: f32,
+    /// Top of the fragment (frame-absolute)
+    y0: f32,
+    y: f32,
+    cursor_x: f32,
+    line: Vec<LineItem>,
+    /// Current line contains only whitespace so far
+    line_blank: bool,
+    /// Pending collapsed whitespace before the next word
+    pending_space: bool,
+    /// Vertical gap requested before the next block
+    pending_gap: f32,
+    indent: f32,
+    quote_depth: u32,
+    pre_depth: u32,
+    inline: Vec<Inline>,
+    lists: Vec<ListState>,
+    hrefs: Vec<String>,
+    links: Vec<HtmlLink>,
+}
+
+impl<'a> Layouter<'a> {
+    fn current(&self) -> Inline {
+        *self.inline.last().expect("inline stack is never empty")
+    }
+
+    fn push_inline(&mut self, f: impl FnOnce(&mut Inline)) {
+        let mut next = self.current();
+        f(&mut next);
+        self.inline.push(next);
+    }
+
+    fn pop_inline(&mut self) {
+        if self.inline.len() > 1 {
+            self.inline.pop();
+        }
+    }
+
+    fn char_advance(&self, style: Inline) -> f32 {
+        self.style.char_width * style.scale
+    }
+
+    fn line_start(&self) -> f32 {
+        self.x0 + self.indent
+    }
+
+    fn right_edge(&self) -> f32 {
+        self.x0 + self.width
+    }
+
+    /// End the current block: flush the line and request a gap.
+    fn block_break(&mut self, gap_lines: f32) {
+        self.flush_line();
+        let gap = self.style.line_height * gap_lines;
+        // No leading gap before the first block.
+        if self.y > self.y0 {
+            self.pending_gap = self.pending_gap.max(gap);
+        }
+        self.pending_space = false;
+    }
+
+    fn place_item_width(&mut self, width: f32) -> f32 {
+        if self.cursor_x + width > self.right_edge() && !self.line.is_empty() {
+            self.flush_line();
+        }
+        let x = self.cursor_x;
+        self.cursor_x += width;
+        x
+    }
+
+    fn add_word(&mut self, word: &str) {
+        let style = self.current();
+        let adv = self.char_advance(style);
+        let word_width = word.chars().count() as f32 * adv;
+
+        if self.pending_space && !self.line.is_empty() {
+            if self.cursor_x + adv + word_width > self.right_edge() {
+                self.flush_line();
+            } else {
+                let x = self.cursor_x;
+                self.line.push(LineItem::Char { c: ' ', x, width: adv, style });
+                self.cursor_x += adv;
+            }
+        }
+        self.pending_space = false;
+
+        if self.cursor_x + word_width > self.right_edge() && !self.line.is_empty() {
+            self.flush_line();
+        }
+        for c in word.chars() {
+            // Words longer than the line are broken at the edge.
+            let x = self.place_item_width(adv);
+            self.line.push(LineItem::Char { c, x, width: adv, style });
+        }
+        self.line_blank = false;
+    }
+
+    fn add_text(&mut self, text: &str) {
+        if self.pre_depth > 0 {
+            self.add_preformatted(text);
+            return;
+        }
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() && c != '\u{00A0}' {
+                if !word.is_empty() {
+                    self.add_word(&word);
+                    word.clear();
+                }
+                self.pending_space = true;
+            } else {
+                word.push(c);
+            }
+        }
+        if !word.is_empty() {
+            self.add_word(&word);
+        }
+    }
+
+    fn add_preformatted(&mut self, text: &str) {
+        let style = self.current();
+        let adv = self.char_advance(style);
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    self.flush_line();
+                    self.line_blank = false;
+                }
+                '\r' => {}
+                '\t' => {
+                    let col = ((self.cursor_x - self.line_start()) / adv).round() as u32;
+                    let spaces = 8 - col % 8;
+                    for _ in 0..spaces {
+                        let x = self.place_item_width(adv);
+                        self.line.push(LineItem::Char { c: ' ', x, width: adv, style });
+                    }
+                }
+                _ => {
+                    let x = self.place_item_width(adv);
+                    self.line.push(LineItem::Char { c, x, width: adv, style });
+                }
+            }
+        }
+        self.line_blank = false;
+    }
+
+    fn add_image(&mut self, image: HtmlImage, attr_w: Option<f32>, attr_h: Option<f32>) {
+        let max_w = (self.right_edge() - self.line_start()).max(1.0);
+        let (mut w, mut h) = match (attr_w, attr_h) {
+            (Some(w), Some(h)) => (w, h),
+            (Some(w), None) if image.width > 0.0 => (w, image.height * w / image.width),
+            (None, Some(h)) if image.height > 0.0 => (image.width * h / image.height, h),
+            _ => (image.width, image.height),
+        };
+        if w > max_w {
+            h *= max_w / w;
+            w = max_w;
+        }
+        if w <= 0.0 || h <= 0.0 {
+            return;
+        }
+        if self.pending_space && !self.line.is_empty() {
+            self.cursor_x += self.char_advance(self.current());
+        }
+        self.pending_space = false;
+        let x = self.place_item_width(w);
+        self.line.push(LineItem::Image { image, x, width: w, height: h });
+        self.line_blank = false;
+    }
+
+    fn add_rule(&mut self) {
+        self.block_break(0.5);
+        self.apply_pending_gap();
+        let h = self.style.line_height;
+        let x = self.line_start();
+        let w = (self.right_edge() - x).max(0.0);
+        self.buf.add_border(x, self.y + (h / 2.0).floor(), w, 1.0, self.style.rule_color);
+        self.y += h;
+        self.block_break(0.5);
+    }
+
+    fn apply_pending_gap(&mut self) {
+        if self.pending_gap > 0.0 {
+            self.y += self.pending_gap;
+            self.pending_gap = 0.0;
+        }
+    }
+
+    /// Emit the current line into the glyph buffer and advance to the next.
+    fn flush_line(&mut self) {
+        let line_start = self.line_start();
+        if self.line.is_empty() {
+            self.cursor_x = line_start;
+            if !self.line_blank && self.pre_depth > 0 {
+                // Empty line inside <pre>
+                self.apply_pending_gap();
+                self.emit_line_decorations(self.style.line_height);
+                self.y += self.style.line_height;
+            }
+            self.line_blank = true;
+            return;
+        }
+        self.apply_pending_gap();
+
+        // Line metrics: tallest text run and tallest image.
+        let mut ascent: f32 = 0.0;
+        let mut descent: f32 = 0.0;
+        for item in &self.line {
+            match item {
+                LineItem::Char { style, .. } => {
+                    let lh = self.style.line_height * style.scale;
+                    let a = self.style.font_size * style.scale * 0.8 + (lh - self.style.font_size * style.scale) / 2.0;
+                    ascent = ascent.max(a);
+                    descent = descent.max(lh - a);
+                }
+                LineItem::Image { height, .. } => {
+                    ascent = ascent.max(*height);
+                }
+            }
+        }
+        let line_h = (ascent + descent).max(self.style.line_height).ceil();
+        let ascent = ascent.round();
+        let y = self.y;
+
+        self.emit_line_decorations(line_h);
+
+        let items = std::mem::take(&mut self.line);
+        let mut link_run: Option<(usize, f32, f32)> = None;
+        for item in &items {
+            match *item {
+                LineItem::Char { c, x, width, style } => {
+                    let face_id = self.face_for(style);
+                    let fg = if style.link.is_some() { self.style.link_fg } else { self.style.fg };
+                    let bg = if style.code && self.pre_depth == 0 { Some(self.style.code_bg) } else { None };
+                    self.buf.set_face(
+                        face_id, fg, bg,
+                        style.bold, style.italic,
+                        style.underline as u8, None,
+                        style.strike as u8, None,
+                        0, None,
+                    );
+                    self.buf.set_font_size(self.style.font_size * style.scale);
+                    self.buf.add_char(c, x, y, width, line_h, ascent, false);
+
+                    link_run = self.extend_link_run(link_run, style.link, x, width, y, line_h);
+                }
+                LineItem::Image { image, x, width, height } => {
+                    link_run = self.extend_link_run(link_run, None, x, width, y, line_h);
+                    self.buf.add_image(image.image_id, x, y + ascent - height, width, height);
+                }
+            }
+        }
+        self.extend_link_run(link_run, None, 0.0, 0.0, y, line_h);
+
+        self.y += line_h;
+        self.cursor_x = line_start;
+        self.line_blank = true;
+    }
+
+    /// Draw block quote bars and the `<pre>` background for one line.
+    fn emit_line_decorations(&mut self, line_h: f32) {
+        let indent_step = self.style.char_width * 2.0;
+        let quote_x0 = self.x0 + self.indent - self.quote_depth as f32 * indent_step;
+        for d in 0..self.quote_depth {
+            let bar_x = quote_x0 + d as f32 * indent_step + 2.0;
+            self.buf.add_border(bar_x, self.y, 3.0, line_h, self.style.rule_color);
+        }
+        if self.pre_depth > 0 {
+            let x = self.line_start();
+            let w = (self.right_edge() - x).max(0.0);
+            self.buf.add_stretch(x, self.y, w, line_h, self.style.code_bg, self.style.code_face_id, false);
+        }
+    }
+
+    /// Track the extent of consecutive glyphs belonging to the same link.
+    fn extend_link_run(
+        &mut self,
+        run: Option<(usize, f32, f32)>,
+        link: Option<usize>,
+        x: f32, width: f32, y: f32, line_h: f32,
+    ) -> Option<(usize, f32, f32)> {
+        match (run, link) {
+            (Some((id, start, _)), Some(l)) if id == l => Some((id, start, x + width)),
+            (prev, next) => {
+                if let Some((id, start, end)) = prev {
+                    self.links.push(HtmlLink {
+                        bounds: Rect::new(start, y, end - start, line_h),
+                        href: self.hrefs[id].clone(),
+                    });
+                }
+                next.map(|l| (l, x, x + width))
+            }
+        }
+    }
+
+    fn face_for(&self, style: Inline) -> u32 {
+        if style.code {
+            self.style.code_face_id
+        } else if style.link.is_some() {
+            self.style.link_face_id
+        } else if style.bold {
+            self.style.bold_face_id
+        } else if style.italic {
+            self.style.italic_face_id
+        } else {
+            self.style.face_id
+        }
+    }
+
+    fn start_list_item(&mut self) {
+        self.flush_line();
+        let marker = match self.lists.last_mut() {
+            Some(list) if list.ordered => {
+                let n = list.next;
+                list.next += 1;
+                format!("{}.", n)
+            }
+            Some(_) => "\u{2022}".to_string(),
+            None => "\u{2022}".to_string(),
+        };
+        // Hang the marker in the indent to the left of the item text.
+        let style = self.current();
+        let adv = self.char_advance(style);
+        let marker_w = (marker.chars().count() as f32 + 1.0) * adv;
+        let mut x = (self.line_start() - marker_w).max(self.x0);
+        for c in marker.chars() {
+            self.line.push(LineItem::Char { c, x, width: adv, style: Inline { link: None, ..style } });
+            x += adv;
+        }
+        self.cursor_x = self.line_start();
+        self.pending_space = false;
+    }
+}
+
+fn attr<'t>(attrs: &'t [(String, String)], key: &str) -> Option<&'t str> {
+    attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+fn attr_px(attrs: &[(String, String)], key: &str) -> Option<f32> {
+    attr(attrs, key)
+        .map(|v| v.trim_end_matches("px"))
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|v| *v > 0.0)
+}
+
+fn heading_scale(name: &str) -> Option<f32> {
+    match name {
+        "h1" => Some(1.8),
+        "h2" => Some(1.5),
+        "h3" => Some(1.25),
+        "h4" => Some(1.1),
+        "h5" => Some(1.0),
+        "h6" => Some(0.9),
+        _ => None,
+    }
+}
+
+/// Lay out `html` into `buf` at frame-absolute (`x`, `y`) within `width`.
+///
+/// `resolve_image` maps an `<img>` `src` to a loaded image; images it
+/// cannot resolve are replaced by their `alt` text.
+pub fn render_html(
+    html: &str,
+    x: f32,
+    y: f32,
+    width: f32,
+    style: &HtmlStyle,
+    buf: &mut FrameGlyphBuffer,
+    mut resolve_image: impl FnMut(&str) -> Option<HtmlImage>,
+) -> HtmlLayout {
+    let saved_font_size = buf.font_size();
+    let mut l = Layouter {
+        style,
+        buf,
+        x0: x,
+        width: width.max(style.char_width),
+        y0: y,
+        y,
+        cursor_x: x,
+        line: Vec::new(),
+        line_blank: true,
+        pending_space: false,
+        pending_gap: 0.0,
+        indent: 0.0,
+        quote_depth: 0,
+        pre_depth: 0,
+        inline: vec![Inline { scale: 1.0, ..Inline::default() }],
+        lists: Vec::new(),
+        hrefs: Vec::new(),
+        links: Vec::new(),
+    };
+    let indent_step = style.char_width * 2.0;
+
+    for token in tokenize(html) {
+        match token {
+            Token::Text(text) => l.add_text(&text),
+            Token::Open { name, attrs, self_closing } => match name.as_str() {
+                "br" => {
+                    if l.line.is_empty() {
+                        // Blank line: advance by one line height
+                        l.apply_pending_gap();
+                        l.y += style.line_height;
+                    } else {
+                        l.flush_line();
+                    }
+                    l.pending_space = false;
+                }
+                "hr" => l.add_rule(),
+                "img" => {
+                    let src = attr(&attrs, "src").unwrap_or("");
+                    match resolve_image(src) {
+                        Some(image) => l.add_image(image, attr_px(&attrs, "width"), attr_px(&attrs, "height")),
+                        None => {
+                            if let Some(alt) = attr(&attrs, "alt").filter(|a| !a.is_empty()) {
+                                let alt = format!("[{}]", alt);
+                                l.push_inline(|s| s.italic = true);
+                                l.add_text(&alt);
+                                l.pop_inline();
+                            }
+                        }
+                    }
+                }
+                "p" | "div" | "section" | "article" | "header" | "footer" | "table" | "tr" => {
+                    l.block_break(if name == "p" { 0.5 } else { 0.0 });
+                }
+                "blockquote" => {
+                    l.block_break(0.5);
+                    l.quote_depth += 1;
+                    l.indent += indent_step;
+                    l.cursor_x = l.line_start();
+                }
+                "pre" => {
+                    l.block_break(0.5);
+                    l.pre_depth += 1;
+                    l.push_inline(|s| s.code = true);
+                }
+                "ul" | "ol" => {
+                    l.block_break(if l.lists.is_empty() { 0.5 } else { 0.0 });
+                    let start = attr(&attrs, "start").and_then(|s| s.parse().ok()).unwrap_or(1);
+                    l.lists.push(ListState { ordered: name == "ol", next: start });
+                    l.indent += indent_step;
+                    l.cursor_x = l.line_start();
+                }
+                "li" => l.start_list_item(),
+                "b" | "strong" | "th" => l.push_inline(|s| s.bold = true),
+                "i" | "em" | "cite" | "var" => l.push_inline(|s| s.italic = true),
+                "u" | "ins" => l.push_inline(|s| s.underline = true),
+                "s" | "del" | "strike" => l.push_inline(|s| s.strike = true),
+                "code" | "kbd" | "samp" | "tt" => l.push_inline(|s| s.code = true),
+                "a" => {
+                    let href = attr(&attrs, "href").map(|h| h.to_string());
+                    let idx = href.map(|h| {
+                        l.hrefs.push(h);
+                        l.hrefs.len() - 1
+                    });
+                    l.push_inline(|s| {
+                        s.link = idx;
+                        s.underline = idx.is_some() || s.underline;
+                    });
+                }
+                "td" => {
+                    l.pending_space = true;
+                }
+                _ => {
+                    if let Some(scale) = heading_scale(&name) {
+                        l.block_break(0.6);
+                        l.push_inline(|s| {
+                            s.bold = true;
+                            s.scale = scale;
+                        });
+                    } else if !self_closing && is_inline_container(&name) {
+                        l.push_inline(|_| {});
+                    }
+                }
+            },
+            Token::Close(name) => match name.as_str() {
+                "p" => l.block_break(0.5),
+                "div" | "section" | "article" | "header" | "footer" | "table" | "tr" => l.block_break(0.0),
+                "blockquote" => {
+                    l.block_break(0.5);
+                    if l.quote_depth > 0 {
+                        l.quote_depth -= 1;
+                        l.indent = (l.indent - indent_step).max(0.0);
+                    }
+                }
+                "pre" => {
+                    l.flush_line();
+                    l.pre_depth = l.pre_depth.saturating_sub(1);
+                    l.pop_inline();
+                    l.block_break(0.5);
+                }
+                "ul" | "ol" => {
+                    l.flush_line();
+                    if l.lists.pop().is_some() {
+                        l.indent = (l.indent - indent_step).max(0.0);
+                    }
+                    if l.lists.is_empty() {
+                        l.block_break(0.5);
+                    }
+                }
+                "li" => l.flush_line(),
+                "b" | "strong" | "th" | "i" | "em" | "cite" | "var" | "u" | "ins"
+                | "s" | "del" | "strike" | "code" | "kbd" | "samp" | "tt" | "a" => l.pop_inline(),
+                _ => {
+                    if heading_scale(&name).is_some() {
+                        l.flush_line();
+                        l.pop_inline();
+                        l.block_break(0.4);
+                    } else if is_inline_container(&name) {
+                        l.pop_inline();
+                    }
+                }
+            },
+        }
+    }
+    l.flush_line();
+
+    let height = l.y - l.y0;
+    let links = std::mem::take(&mut l.links);
+    l.buf.set_font_size(saved_font_size);
+    HtmlLayout { height, links }
+}
+
+/// Tags that only wrap inline content and carry no styling of their own.
+fn is_inline_container(name: &str) -> bool {
+    matches!(name, "span" | "font" | "small" | "big" | "abbr" | "mark" | "sub" | "sup" | "label")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_glyphs::FrameGlyph;
+
+    fn style() -> HtmlStyle {
+        let mut s = HtmlStyle::with_face(1, 10.0, 10.0, 10.0, Color::WHITE);
+        s.bold_face_id = 2;
+        s.link_face_id = 5;
+        s
+    }
+
+    fn text_of(buf: &FrameGlyphBuffer) -> String {
+        buf.glyphs.iter().filter_map(|g| match g {
+            FrameGlyph::Char { char, .. } => Some(*char),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_entities_and_whitespace() {
+        let mut buf = FrameGlyphBuffer::new();
+        render_html("<p>a &amp;   b&#33; \u{e9} 1<2</p>", 0.0, 0.0, 200.0, &style(), &mut buf, |_| None);
+        assert_eq!(text_of(&buf), "a & b! \u{e9} 1<2");
+    }
+
+    #[test]
+    fn test_wrap_and_bold_face() {
+        let mut buf = FrameGlyphBuffer::new();
+        let layout = render_html("aaa <b>bbb</b> ccc", 0.0, 0.0, 70.0, &style(), &mut buf, |_| None);
+        // "aaa bbb" fits in 7 cells, "ccc" wraps
+        assert_eq!(layout.height, 20.0);
+        let bold: Vec<u32> = buf.glyphs.iter().filter_map(|g| match g {
+            FrameGlyph::Char { char: 'b', face_id, bold, .. } => {
+                assert!(*bold);
+                Some(*face_id)
+            }
+            _ => None,
+        }).collect();
+        assert_eq!(bold, vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn test_links_and_images() {
+        let mut buf = FrameGlyphBuffer::new();
+        let layout = render_html(
+            "<a href=\"https://x\">go</a> <img src=\"pic\" width=20 height=10><img src=\"missing\" alt=\"m\">",
+            0.0, 0.0, 200.0, &style(), &mut buf,
+            |src| (src == "pic").then_some(HtmlImage { image_id: 7, width: 40.0, height: 20.0 }),
+        );
+        assert_eq!(layout.links.len(), 1);
+        assert_eq!(layout.link_at(5.0, 5.0), Some("https://x"));
+        assert!(buf.glyphs.iter().any(|g| matches!(g, FrameGlyph::Image { image_id: 7, width, .. } if *width == 20.0)));
+        assert!(text_of(&buf).ends_with("[m]"));
+    }
+
+    #[test]
+    fn test_script_is_skipped() {
+        let mut buf = FrameGlyphBuffer::new();
+        render_html("<style>p{}</style><script>if (a<b) x()</script>ok", 0.0, 0.0, 200.0, &style(), &mut buf, |_| None);
+        assert_eq!(text_of(&buf), "ok");
+    }
+}
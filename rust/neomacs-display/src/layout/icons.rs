@@ -0,0 +1,348 @@
+//! Named icons from SVG icon sets and icon fonts.
+//!
+//! Mode lines and tab bars want small, crisp icons in the colors of the
+//! text around them, without shipping a PNG for every size.  An icon set
+//! is loaded once under a name: a directory of SVG files, one per icon
+//! and named after it, or an icon font whose icons are named by a
+//! codepoints file or by the font's own glyph names.  Icons are then
+//! asked for by name, size and color.  As with TeX math, the size is
+//! known at once on the Emacs thread while the render thread rasterizes
+//! the icon at the display scale factor; each distinct request is kept
+//! as one image.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use resvg::tiny_skia;
+use resvg::usvg::{self, TreeParsing};
+
+/// What an icon is drawn from, as sent to the render thread
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub enum IconSource {
+    /// An SVG document
+    Svg(Arc<[u8]>),
+    /// A glyph of an icon font
+    Glyph { font: Arc<[u8]>, glyph: u16 },
+}
+
+/// A loaded icon set
+enum IconSet {
+    /// Directory of NAME.svg files
+    Svg { dir: PathBuf },
+    /// Icon font and the character of each icon
+    Font { data: Arc<[u8]>, codepoints: HashMap<String, char> },
+}
+
+/// A cached icon image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IconImage {
+    pub image_id: u32,
+    /// Logical size
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct IconKey {
+    name: String,
+    size_bits: u32,
+    color: Option<u32>,
+}
+
+/// Loaded icon sets and the images made from their icons.
+#[derive(Default)]
+pub struct IconRegistry {
+    /// Sets in the order loaded
+    sets: Vec<(String, IconSet)>,
+    /// Icons found so far, with their width over height
+    found: HashMap<String, (IconSource, f32)>,
+    images: HashMap<IconKey, IconImage>,
+}
+
+impl IconRegistry {
+    /// Load icon set `name` from `path`, a directory of SVG files or an
+    /// icon font, replacing any set of that name.  The icons of a font
+    /// are named by `codepoints`, a file of "NAME HEX" lines, or else by
+    /// the font's glyph names.  Returns the number of icons in the set.
+    pub fn load(&mut self, name: &str, path: &Path, codepoints: Option<&Path>) -> Result<usize, String> {
+        if name.is_empty() || name.contains(':') {
+            return Err(format!("Invalid icon set name: {}", name));
+        }
+        let (set, count) = if path.is_dir() {
+            let count = std::fs::read_dir(path)
+                .map_err(|e| format!("{}: {}", path.display(), e))?
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "svg"))
+                .count();
+            (IconSet::Svg { dir: path.to_path_buf() }, count)
+        } else {
+            let data: Arc<[u8]> = std::fs::read(path)
+                .map_err(|e| format!("{}: {}", path.display(), e))?
+                .into();
+            let face = ttf_parser::Face::parse(&data, 0)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            let codepoints = match codepoints {
+                Some(file) => parse_codepoints(
+                    &std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?,
+                ),
+                None => glyph_names(&face),
+            };
+            if codepoints.is_empty() {
+                return Err(format!("{}: no named icons", path.display()));
+            }
+            let count = codepoints.len();
+            (IconSet::Font { data: data.clone(), codepoints }, count)
+        };
+        self.sets.retain(|(existing, _)| existing != name);
+        self.sets.push((name.to_string(), set));
+        self.found.clear();
+        Ok(count)
+    }
+
+    /// The source of icon `name` and its width over height.  `name` is
+    /// "SET:ICON", or an ICON looked for in each set, latest loaded first.
+    pub fn find(&mut self, name: &str) -> Option<(IconSource, f32)> {
+        if let Some(found) = self.found.get(name) {
+            return Some(found.clone());
+        }
+        let (set_name, icon) = split_name(name)?;
+        let found = self
+            .sets
+            .iter()
+            .rev()
+            .filter(|(n, _)| set_name.is_none_or(|s| s == n))
+            .find_map(|(_, set)| find_in_set(set, icon))?;
+        self.found.insert(name.to_string(), found.clone());
+        Some(found)
+    }
+
+    /// Look up a previously made icon image
+    pub fn get(&self, name: &str, size: f32, color: Option<u32>) -> Option<IconImage> {
+        self.images.get(&Self::key(name, size, color)).copied()
+    }
+
+    /// Remember the image an icon was uploaded as
+    pub fn insert(&mut self, name: &str, size: f32, color: Option<u32>, image: IconImage) {
+        self.images.insert(Self::key(name, size, color), image);
+    }
+
+    /// Forget all icon images, returning their image IDs to free
+    pub fn clear(&mut self) -> Vec<u32> {
+        self.images.drain().map(|(_, image)| image.image_id).collect()
+    }
+
+    fn key(name: &str, size: f32, color: Option<u32>) -> IconKey {
+        IconKey { name: name.to_string(), size_bits: size.to_bits(), color }
+    }
+}
+
+/// Split "SET:ICON" into its parts, or an ICON of any set.  None for
+/// names that could leave an SVG set's directory.
+pub fn split_name(name: &str) -> Option<(Option<&str>, &str)> {
+    let (set, icon) = match name.split_once(':') {
+        Some((set, icon)) => (Some(set), icon),
+        None => (None, name),
+    };
+    let valid = !icon.is_empty() && icon != ".." && !icon.contains(['/', '\\']);
+    valid.then_some((set, icon))
+}
+
+/// Icon names and characters of a codepoints file: "NAME HEX" per line,
+/// HEX optionally prefixed by "0x" or "U+"
+pub fn parse_codepoints(text: &str) -> HashMap<String, char> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next().filter(|n| !n.starts_with('#'))?;
+            let hex = fields.next()?;
+            let hex = hex.trim_start_matches("0x").trim_start_matches("U+");
+            let ch = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+            Some((name.to_string(), ch))
+        })
+        .collect()
+}
+
+/// Icon names and characters from the glyph names of `face`
+fn glyph_names(face: &ttf_parser::Face) -> HashMap<String, char> {
+    let mut names = HashMap::new();
+    let Some(cmap) = face.tables().cmap else {
+        return names;
+    };
+    for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+        subtable.codepoints(|cp| {
+            let name = subtable.glyph_index(cp).and_then(|gid| face.glyph_name(gid));
+            if let (Some(name), Some(ch)) = (name, char::from_u32(cp)) {
+                names.entry(name.to_string()).or_insert(ch);
+            }
+        });
+    }
+    names
+}
+
+fn find_in_set(set: &IconSet, icon: &str) -> Option<(IconSource, f32)> {
+    match set {
+        IconSet::Svg { dir } => {
+            let data: Arc<[u8]> = std::fs::read(dir.join(format!("{}.svg", icon))).ok()?.into();
+            let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).ok()?;
+            let aspect = tree.size.width() / tree.size.height();
+            Some((IconSource::Svg(data), aspect))
+        }
+        IconSet::Font { data, codepoints } => {
+            let face = ttf_parser::Face::parse(data, 0).ok()?;
+            let glyph = face.glyph_index(*codepoints.get(icon)?)?;
+            let advance = face.glyph_hor_advance(glyph).unwrap_or(face.units_per_em());
+            let aspect = advance as f32 / glyph_height(&face);
+            Some((IconSource::Glyph { font: data.clone(), glyph: glyph.0 }, aspect))
+        }
+    }
+}
+
+/// Height of a glyph box in font units, from descender to ascender
+fn glyph_height(face: &ttf_parser::Face) -> f32 {
+    let height = face.ascender() as f32 - face.descender() as f32;
+    if height > 0.0 { height } else { face.units_per_em() as f32 }
+}
+
+/// Rasterize an icon at `width` x `height` logical pixels to
+/// straight-alpha RGBA, in `color` (0xRRGGBB) or, when None, in its own
+/// colors (white for font glyphs).
+///
+/// Returns `(width, height, pixels)` in physical pixels.
+pub fn rasterize(source: &IconSource, width: u32, height: u32, scale: f32, color: Option<u32>) -> (u32, u32, Vec<u8>) {
+    let w = ((width as f32 * scale).round() as u32).max(1);
+    let h = ((height as f32 * scale).round() as u32).max(1);
+    let Some(mut pixmap) = tiny_skia::Pixmap::new(w, h) else {
+        return (w, h, vec![0; (w * h * 4) as usize]);
+    };
+    match source {
+        IconSource::Svg(data) => {
+            if let Ok(mut tree) = usvg::Tree::from_data(data, &usvg::Options::default()) {
+                tree.calculate_bounding_boxes();
+                let transform = tiny_skia::Transform::from_scale(
+                    w as f32 / tree.size.width(),
+                    h as f32 / tree.size.height(),
+                );
+                resvg::render(&tree, transform, &mut pixmap.as_mut());
+            }
+        }
+        IconSource::Glyph { font, glyph } => {
+            if let Ok(face) = ttf_parser::Face::parse(font, 0) {
+                let units = h as f32 / glyph_height(&face);
+                let mut outline = GlyphOutline {
+                    path: tiny_skia::PathBuilder::new(),
+                    scale: units,
+                    baseline: face.ascender() as f32 * units,
+                };
+                face.outline_glyph(ttf_parser::GlyphId(*glyph), &mut outline);
+                if let Some(path) = outline.path.finish() {
+                    let mut paint = tiny_skia::Paint::default();
+                    paint.set_color_rgba8(255, 255, 255, 255);
+                    paint.anti_alias = true;
+                    pixmap.fill_path(
+                        &path, &paint, tiny_skia::FillRule::Winding, tiny_skia::Transform::identity(), None,
+                    );
+                }
+            }
+        }
+    }
+    let mut data = Vec::with_capacity((w * h * 4) as usize);
+    for px in pixmap.pixels() {
+        let c = px.demultiply();
+        data.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+    if let Some(color) = color {
+        tint(&mut data, color);
+    }
+    (w, h, data)
+}
+
+/// Give every pixel of straight-alpha RGBA `data` the color `color`
+/// (0xRRGGBB), keeping its alpha
+pub fn tint(data: &mut [u8], color: u32) {
+    let [_, r, g, b] = color.to_be_bytes();
+    for px in data.chunks_exact_mut(4) {
+        px[..3].copy_from_slice(&[r, g, b]);
+    }
+}
+
+/// Turns a glyph outline in font units, y up, into a path in pixels
+struct GlyphOutline {
+    path: tiny_skia::PathBuilder,
+    scale: f32,
+    baseline: f32,
+}
+
+impl GlyphOutline {
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (x * self.scale, self.baseline - y * self.scale)
+    }
+}
+
+impl ttf_parser::OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.path.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.path.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
+        self.path.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
+        self.path.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.path.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_names_and_codepoints() {
+        assert_eq!(split_name("mdi:home"), Some((Some("mdi"), "home")));
+        assert_eq!(split_name("home"), Some((None, "home")));
+        assert_eq!(split_name("mdi:../secret"), None);
+        assert_eq!(split_name(".."), None);
+        assert_eq!(split_name("mdi:"), None);
+
+        let codepoints = parse_codepoints("# Material\nhome e88a\nsearch 0xe8b6\nstar U+2B50\nbad zz\n\n");
+        assert_eq!(codepoints.len(), 3);
+        assert_eq!(codepoints["home"], '\u{e88a}');
+        assert_eq!(codepoints["search"], '\u{e8b6}');
+        assert_eq!(codepoints["star"], '\u{2b50}');
+    }
+
+    #[test]
+    fn test_icon_svg_rasterizes_in_color() {
+        let svg: Arc<[u8]> = br#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="12">
+            <rect x="0" y="0" width="12" height="12" fill="red"/></svg>"#
+            .as_slice()
+            .into();
+        let source = IconSource::Svg(svg);
+        let (w, h, data) = rasterize(&source, 32, 16, 2.0, Some(0x00ff00));
+        assert_eq!((w, h), (64, 32));
+        // Left half covered, in the color asked for; right half clear
+        let px = |x: u32, y: u32| &data[((y * w + x) * 4) as usize..][..4];
+        assert_eq!(px(10, 10), [0, 255, 0, 255]);
+        assert_eq!(px(50, 10)[3], 0);
+
+        let (_, _, own) = rasterize(&source, 32, 16, 1.0, None);
+        assert_eq!(&own[..4], [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,790 @@
+//! Inline TeX math typesetting.
+//!
+//! Org and other modes embed TeX math snippets that are normally rendered
+//! by running `latex` and `dvipng` for each fragment.  This module
+//! typesets them in-process instead: the TeX snippet is translated into
+//! Typst math syntax and laid out with the embedded Typst engine and its
+//! bundled math fonts.  Layout runs on the Emacs thread so the size and
+//! baseline are known synchronously; the resulting frame is rasterized by
+//! the render thread at the display scale factor.
+//!
+//! Units: the font size is given in logical pixels and one Typst point is
+//! treated as one logical pixel.
+
+use std::collections::HashMap;
+
+use comemo::Prehashed;
+use typst::diag::{FileError, FileResult};
+use typst::foundations::{Bytes, Datetime};
+use typst::layout::{Abs, Frame, FrameItem};
+use typst::syntax::{FileId, Source};
+use typst::text::{Font, FontBook};
+use typst::visualize::Color;
+use typst::{Library, World};
+
+/// What a snippet is typeset from, the arguments of
+/// [`MathCache::typeset`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "remote", derive(serde::Serialize, serde::Deserialize))]
+pub struct MathSource {
+    pub tex: String,
+    pub display: bool,
+    pub size: f32,
+    pub color: u32,
+}
+
+/// A typeset snippet, ready for rasterization
+#[derive(Debug, Clone)]
+pub struct TypesetMath {
+    pub frame: Frame,
+    /// Size in logical pixels
+    pub width: f32,
+    pub height: f32,
+    /// Distance from the top edge to the baseline, in logical pixels
+    pub baseline: f32,
+}
+
+impl TypesetMath {
+    /// Image ascent as a percentage of the height (Emacs `:ascent`)
+    pub fn ascent_percent(&self) -> u32 {
+        if self.height <= 0.0 {
+            return 100;
+        }
+        ((self.baseline / self.height) * 100.0).round().clamp(0.0, 100.0) as u32
+    }
+}
+
+/// Rasterize a typeset snippet to straight-alpha RGBA.
+///
+/// Returns `(width, height, pixels)` in physical pixels.
+pub fn rasterize(frame: &Frame, scale: f32) -> (u32, u32, Vec<u8>) {
+    let pixmap = typst_render::render(frame, scale.max(0.1), Color::from_u8(0, 0, 0, 0));
+    let (width, height) = (pixmap.width(), pixmap.height());
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for px in pixmap.pixels() {
+        let c = px.demultiply();
+        data.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+    (width, height, data)
+}
+
+/// Typst world holding the standard library and the bundled fonts
+struct MathWorld {
+    library: Prehashed<Library>,
+    book: Prehashed<FontBook>,
+    fonts: Vec<Font>,
+    source: Source,
+}
+
+impl World for MathWorld {
+    fn library(&self) -> &Prehashed<Library> {
+        &self.library
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        &self.book
+    }
+
+    fn main(&self) -> Source {
+        self.source.clone()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.source.id() {
+            Ok(self.source.clone())
+        } else {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.fonts.get(index).cloned()
+    }
+
+    fn today(&self, _offset: Option<i64>) -> Option<Datetime> {
+        None
+    }
+}
+
+/// Cache key: snippet, style and size
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MathKey {
+    tex: String,
+    display: bool,
+    /// Font size in 1/64 logical pixels
+    size: u32,
+    color: u32,
+}
+
+/// A cached snippet image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MathImage {
+    pub image_id: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Percentage of the height above the baseline
+    pub ascent: u32,
+}
+
+/// Typesets TeX snippets and remembers the images made from them.
+///
+/// Fonts are loaded on first use.  The cache maps each distinct snippet,
+/// size and color to the image it was uploaded as, so redisplay and
+/// repeated fragments do not typeset again.
+#[derive(Default)]
+pub struct MathCache {
+    world: Option<MathWorld>,
+    images: HashMap<MathKey, MathImage>,
+}
+
+impl MathCache {
+    /// Look up a previously rendered snippet
+    pub fn get(&self, tex: &str, display: bool, size: f32, color: u32) -> Option<MathImage> {
+        self.images.get(&Self::key(tex, display, size, color)).copied()
+    }
+
+    /// Remember the image a snippet was uploaded as
+    pub fn insert(&mut self, tex: &str, display: bool, size: f32, color: u32, image: MathImage) {
+        self.images.insert(Self::key(tex, display, size, color), image);
+    }
+
+    /// Forget all cached snippets, returning their image IDs to free
+    pub fn clear(&mut self) -> Vec<u32> {
+        self.images.drain().map(|(_, img)| img.image_id).collect()
+    }
+
+    /// Typeset a TeX math snippet at `size` logical pixels in `color`
+    /// (0xRRGGBB).  `display` selects display style (`\[...\]`) over
+    /// inline style (`$...$`).
+    pub fn typeset(
+        &mut self,
+        tex: &str,
+        display: bool,
+        size: f32,
+        color: u32,
+    ) -> Result<TypesetMath, String> {
+        let body = tex_to_typst(tex);
+        let body = if display { format!("display({})", body) } else { body };
+        let text = format!(
+            "#set page(width: auto, height: auto, margin: 0pt, fill: none)\n\
+             #set par(leading: 0pt)\n\
+             #set text(size: {size}pt, fill: rgb({r}, {g}, {b}), \
+             top-edge: \"bounds\", bottom-edge: \"bounds\")\n\
+             ${body}$#box(width: 0pt, height: 0pt)[#h(0pt)]\n",
+            size = size.max(1.0),
+            r = (color >> 16) & 0xFF,
+            g = (color >> 8) & 0xFF,
+            b = color & 0xFF,
+        );
+
+        let world = self.world.get_or_insert_with(|| {
+            let fonts: Vec<Font> = typst_assets::fonts()
+                .flat_map(|data| Font::iter(Bytes::from_static(data)))
+                .collect();
+            MathWorld {
+                library: Prehashed::new(Library::builder().build()),
+                book: Prehashed::new(FontBook::from_fonts(&fonts)),
+                fonts,
+                source: Source::detached(""),
+            }
+        });
+        world.source = Source::detached(text);
+
+        let mut tracer = typst::eval::Tracer::new();
+        let document = typst::compile(world, &mut tracer).map_err(|errors| {
+            errors
+                .iter()
+                .map(|e| e.message.to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        })?;
+        let page = document.pages.into_iter().next().ok_or("empty document")?;
+        let frame = page.frame;
+        let size = frame.size();
+        let baseline = marker_baseline(&frame).unwrap_or(frame.baseline());
+        Ok(TypesetMath {
+            width: size.x.to_pt() as f32,
+            height: size.y.to_pt() as f32,
+            baseline: baseline.to_pt() as f32,
+            frame,
+        })
+    }
+
+    fn key(tex: &str, display: bool, size: f32, color: u32) -> MathKey {
+        MathKey {
+            tex: tex.to_string(),
+            display,
+            size: (size * 64.0).round().max(0.0) as u32,
+            color: color & 0xFF_FFFF,
+        }
+    }
+}
+
+/// Baseline of the snippet: the empty marker box set after the equation
+/// sits on it
+fn marker_baseline(frame: &Frame) -> Option<Abs> {
+    frame.items().rev().find_map(|(pos, item)| match item {
+        FrameItem::Group(group) if group.frame.width().to_pt() == 0.0
+            && group.frame.height().to_pt() == 0.0 => Some(pos.y),
+        _ => None,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// TeX to Typst math translation
+// ---------------------------------------------------------------------------
+
+/// Translate a TeX math snippet (without `$` delimiters) to Typst math.
+///
+/// Covers the commands common in notes and papers: fractions, roots,
+/// scripts, Greek letters, operators, relations, arrows, accents, font
+/// styles, `\text`, `\left`/`\right` and matrix-like environments.
+/// Unknown commands are passed through by name, which Typst resolves for
+/// most symbols that share their TeX name.
+pub fn tex_to_typst(tex: &str) -> String {
+    let mut parser = TexParser { chars: tex.chars().collect(), pos: 0 };
+    join(&parser.sequence(Stop::Eof))
+}
+
+/// Item of a translated sequence
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Atom(String),
+    /// Alignment point `&`
+    Align,
+    /// Row break `\\`
+    Row,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stop {
+    Eof,
+    Brace,
+    Bracket,
+    Right,
+    End,
+}
+
+struct TexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+fn join(pieces: &[Piece]) -> String {
+    pieces
+        .iter()
+        .map(|p| match p {
+            Piece::Atom(s) => s.as_str(),
+            Piece::Align => "&",
+            Piece::Row => "\\",
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Wrap a script or argument so Typst treats it as one unit; Typst drops
+/// the parentheses when rendering
+fn group(s: &str) -> String {
+    if s.chars().count() == 1 || s.chars().all(|c| c.is_ascii_digit()) {
+        s.to_string()
+    } else {
+        format!("({})", s)
+    }
+}
+
+fn escape_char(c: char) -> String {
+    match c {
+        ',' | ';' | '/' | '*' | '#' | '$' | '"' | '@' | '\\' | '[' | ']' | '{' | '}' => {
+            format!("\\{}", c)
+        }
+        _ => c.to_string(),
+    }
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl TexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Read a command name after a backslash (letters, or one symbol)
+    fn command_name(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            if let Some(c) = self.peek() {
+                self.pos += 1;
+                return c.to_string();
+            }
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Raw text of a balanced `{...}` group (for `\text` and environments)
+    fn raw_group(&mut self) -> String {
+        self.skip_ws();
+        if self.peek() != Some('{') {
+            return self.peek().map(|c| { self.pos += 1; c.to_string() }).unwrap_or_default();
+        }
+        self.pos += 1;
+        let start = self.pos;
+        let mut depth = 1;
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' => self.pos += 1,
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        let end = self.pos.min(self.chars.len());
+        self.pos = (self.pos + 1).min(self.chars.len());
+        self.chars[start..end].iter().collect()
+    }
+
+    /// One argument: a braced group or a single token
+    fn argument(&mut self) -> String {
+        self.skip_ws();
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                join(&self.sequence(Stop::Brace))
+            }
+            Some('\\') => {
+                self.pos += 1;
+                let name = self.command_name();
+                self.command(&name)
+            }
+            Some(c) => {
+                self.pos += 1;
+                escape_char(c)
+            }
+            None => "\"\"".to_string(),
+        }
+    }
+
+    /// Optional `[...]` argument
+    fn optional_argument(&mut self) -> Option<String> {
+        self.skip_ws();
+        if self.peek() == Some('[') {
+            self.pos += 1;
+            Some(join(&self.sequence(Stop::Bracket)))
+        } else {
+            None
+        }
+    }
+
+    /// Delimiter after `\left` or `\right`
+    fn delimiter(&mut self) -> String {
+        self.skip_ws();
+        match self.peek() {
+            Some('\\') => {
+                self.pos += 1;
+                match self.command_name().as_str() {
+                    "{" | "lbrace" => "\\{".into(),
+                    "}" | "rbrace" => "\\}".into(),
+                    "|" | "Vert" => "||".into(),
+                    "langle" => "angle.l".into(),
+                    "rangle" => "angle.r".into(),
+                    "lfloor" => "floor.l".into(),
+                    "rfloor" => "floor.r".into(),
+                    "lceil" => "ceil.l".into(),
+                    "rceil" => "ceil.r".into(),
+                    _ => String::new(),
+                }
+            }
+            Some('.') => {
+                self.pos += 1;
+                String::new()
+            }
+            Some(c) => {
+                self.pos += 1;
+                match c {
+                    '[' => "[".into(),
+                    ']' => "]".into(),
+                    _ => c.to_string(),
+                }
+            }
+            None => String::new(),
+        }
+    }
+
+    fn sequence(&mut self, stop: Stop) -> Vec<Piece> {
+        let mut pieces: Vec<Piece> = Vec::new();
+        loop {
+            self.skip_ws();
+            let Some(c) = self.peek() else { break };
+            match c {
+                '}' => {
+                    self.pos += 1;
+                    if stop == Stop::Brace {
+                        break;
+                    }
+                }
+                ']' if stop == Stop::Bracket => {
+                    self.pos += 1;
+                    break;
+                }
+                '^' | '_' => {
+                    self.pos += 1;
+                    let script = group(&self.argument());
+                    match pieces.last_mut() {
+                        Some(Piece::Atom(base)) => {
+                            base.push(c);
+                            base.push_str(&script);
+                        }
+                        _ => pieces.push(Piece::Atom(format!("\"\"{}{}", c, script))),
+                    }
+                }
+                '&' => {
+                    self.pos += 1;
+                    pieces.push(Piece::Align);
+                }
+                '{' => {
+                    self.pos += 1;
+                    let inner = join(&self.sequence(Stop::Brace));
+                    if !inner.is_empty() {
+                        pieces.push(Piece::Atom(inner));
+                    }
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let name = self.command_name();
+                    match name.as_str() {
+                        "\\" | "cr" => pieces.push(Piece::Row),
+                        "right" if stop == Stop::Right => {
+                            let close = self.delimiter();
+                            pieces.push(Piece::Atom(close));
+                            break;
+                        }
+                        "end" if stop == Stop::End => {
+                            self.raw_group();
+                            break;
+                        }
+                        _ => {
+                            let atom = self.command(&name);
+                            if !atom.is_empty() {
+                                pieces.push(Piece::Atom(atom));
+                            }
+                        }
+                    }
+                }
+                '0'..='9' => {
+                    let start = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                        self.pos += 1;
+                    }
+                    pieces.push(Piece::Atom(self.chars[start..self.pos].iter().collect()));
+                }
+                '~' => {
+                    self.pos += 1;
+                    pieces.push(Piece::Atom("space".into()));
+                }
+                '\'' => {
+                    self.pos += 1;
+                    match pieces.last_mut() {
+                        Some(Piece::Atom(base)) => base.push('\''),
+                        _ => pieces.push(Piece::Atom("prime".into())),
+                    }
+                }
+                _ => {
+                    self.pos += 1;
+                    pieces.push(Piece::Atom(escape_char(c)));
+                }
+            }
+        }
+        pieces
+    }
+
+    /// Translate one command (the backslash and name already consumed)
+    fn command(&mut self, name: &str) -> String {
+        match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let num = self.argument();
+                let den = self.argument();
+                format!("frac({}, {})", num, den)
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let n = self.argument();
+                let k = self.argument();
+                format!("binom({}, {})", n, k)
+            }
+            "sqrt" => match self.optional_argument() {
+                Some(index) => {
+                    let radicand = self.argument();
+                    format!("root({}, {})", index, radicand)
+                }
+                None => format!("sqrt({})", self.argument()),
+            },
+            "text" | "textrm" | "textnormal" | "mbox" => {
+                format!("\"{}\"", escape_text(&self.raw_group()))
+            }
+            "textbf" => format!("bold(\"{}\")", escape_text(&self.raw_group())),
+            "textit" => format!("italic(\"{}\")", escape_text(&self.raw_group())),
+            "operatorname" => format!("op(\"{}\")", escape_text(&self.raw_group())),
+            "mathrm" => format!("upright({})", self.argument()),
+            "mathbf" | "boldsymbol" | "bm" => format!("bold({})", self.argument()),
+            "mathit" => format!("italic({})", self.argument()),
+            "mathbb" => format!("bb({})", self.argument()),
+            "mathcal" => format!("cal({})", self.argument()),
+            "mathfrak" => format!("frak({})", self.argument()),
+            "mathsf" => format!("sans({})", self.argument()),
+            "mathtt" => format!("mono({})", self.argument()),
+            "hat" | "widehat" => format!("hat({})", self.argument()),
+            "tilde" | "widetilde" => format!("tilde({})", self.argument()),
+            "bar" => format!("macron({})", self.argument()),
+            "vec" => format!("arrow({})", self.argument()),
+            "dot" => format!("dot({})", self.argument()),
+            "ddot" => format!("dot.double({})", self.argument()),
+            "overline" => format!("overline({})", self.argument()),
+            "underline" => format!("underline({})", self.argument()),
+            "overbrace" => format!("overbrace({})", self.argument()),
+            "underbrace" => format!("underbrace({})", self.argument()),
+            "left" => {
+                let open = self.delimiter();
+                let inner = join(&self.sequence(Stop::Right));
+                format!("lr({} {})", open, inner)
+            }
+            // A stray \right outside \left...\right
+            "right" => self.delimiter(),
+            "begin" => {
+                let env = self.raw_group();
+                let env = env.trim_end_matches('*');
+                if env == "array" {
+                    // Column spec
+                    self.raw_group();
+                }
+                let pieces = self.sequence(Stop::End);
+                environment(env, &pieces)
+            }
+            "limits" | "nolimits" | "displaystyle" | "textstyle" | "scriptstyle" | "!"
+            | "nonumber" => String::new(),
+            "," | ":" | ">" => "thin".into(),
+            ";" => "med".into(),
+            " " => "space".into(),
+            "quad" => "quad".into(),
+            "qquad" => "wide".into(),
+            "{" | "lbrace" => "\\{".into(),
+            "}" | "rbrace" => "\\}".into(),
+            "|" | "Vert" => "||".into(),
+            "%" | "#" | "&" | "_" | "$" => escape_char(name.chars().next().unwrap_or(' ')),
+            _ => symbol(name).map_or_else(|| name.to_string(), str::to_string),
+        }
+    }
+}
+
+/// Translate a `\begin{env}...\end{env}` body
+fn environment(env: &str, pieces: &[Piece]) -> String {
+    let mut rows: Vec<Vec<String>> = vec![vec![String::new()]];
+    for piece in pieces {
+        match piece {
+            Piece::Row => rows.push(vec![String::new()]),
+            Piece::Align => rows.last_mut().unwrap().push(String::new()),
+            Piece::Atom(s) => {
+                let cell = rows.last_mut().unwrap().last_mut().unwrap();
+                if !cell.is_empty() {
+                    cell.push(' ');
+                }
+                cell.push_str(s);
+            }
+        }
+    }
+    // A trailing \\ leaves an empty row
+    if rows.len() > 1 && rows.last().is_some_and(|r| r.iter().all(String::is_empty)) {
+        rows.pop();
+    }
+
+    let delim = match env {
+        "pmatrix" => Some("\"(\""),
+        "bmatrix" => Some("\"[\""),
+        "Bmatrix" => Some("\"{\""),
+        "vmatrix" => Some("\"|\""),
+        "Vmatrix" => Some("\"||\""),
+        "matrix" | "smallmatrix" | "array" => Some("#none"),
+        _ => None,
+    };
+    if let Some(delim) = delim {
+        let body = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|c| if c.is_empty() { "\"\"" } else { c.as_str() })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        return format!("mat(delim: {}, {})", delim, body);
+    }
+
+    let lines: Vec<String> = rows.iter().map(|row| row.join(" & ")).collect();
+    if env == "cases" {
+        format!("cases({})", lines.join(", "))
+    } else {
+        lines.join(" \\ ")
+    }
+}
+
+/// Typst names of TeX symbol commands that differ from their TeX name
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "cdot" => "dot.op",
+        "times" => "times",
+        "div" => "div",
+        "pm" => "plus.minus",
+        "mp" => "minus.plus",
+        "ast" => "ast",
+        "star" => "star",
+        "circ" => "compose",
+        "bullet" => "bullet",
+        "oplus" => "plus.circle",
+        "otimes" => "times.circle",
+        "leq" | "le" => "lt.eq",
+        "geq" | "ge" => "gt.eq",
+        "neq" | "ne" => "eq.not",
+        "ll" => "lt.double",
+        "gg" => "gt.double",
+        "approx" => "approx",
+        "sim" => "tilde.op",
+        "simeq" => "tilde.eq",
+        "cong" => "tilde.equiv",
+        "equiv" => "equiv",
+        "propto" => "prop",
+        "infty" => "infinity",
+        "partial" => "diff",
+        "nabla" => "nabla",
+        "hbar" => "planck.reduce",
+        "ell" => "ell",
+        "Re" => "Re",
+        "Im" => "Im",
+        "aleph" => "aleph",
+        "sum" => "sum",
+        "prod" => "product",
+        "coprod" => "product.co",
+        "int" => "integral",
+        "iint" => "integral.double",
+        "iiint" => "integral.triple",
+        "oint" => "integral.cont",
+        "bigcup" => "union.big",
+        "bigcap" => "sect.big",
+        "to" | "rightarrow" => "arrow.r",
+        "gets" | "leftarrow" => "arrow.l",
+        "leftrightarrow" => "arrow.l.r",
+        "Rightarrow" | "implies" => "arrow.r.double",
+        "Leftarrow" => "arrow.l.double",
+        "Leftrightarrow" | "iff" => "arrow.l.r.double",
+        "longrightarrow" => "arrow.r.long",
+        "longleftarrow" => "arrow.l.long",
+        "mapsto" => "arrow.r.bar",
+        "uparrow" => "arrow.t",
+        "downarrow" => "arrow.b",
+        "in" => "in",
+        "notin" => "in.not",
+        "ni" => "in.rev",
+        "subset" => "subset",
+        "supset" => "supset",
+        "subseteq" => "subset.eq",
+        "supseteq" => "supset.eq",
+        "cup" => "union",
+        "cap" => "sect",
+        "setminus" => "without",
+        "emptyset" | "varnothing" => "emptyset",
+        "forall" => "forall",
+        "exists" => "exists",
+        "nexists" => "exists.not",
+        "neg" | "lnot" => "not",
+        "land" | "wedge" => "and",
+        "lor" | "vee" => "or",
+        "top" => "top",
+        "bot" | "perp" => "bot",
+        "parallel" => "parallel",
+        "angle" => "angle",
+        "ldots" | "dots" => "dots.h",
+        "cdots" => "dots.h.c",
+        "vdots" => "dots.v",
+        "ddots" => "dots.down",
+        "langle" => "angle.l",
+        "rangle" => "angle.r",
+        "lfloor" => "floor.l",
+        "rfloor" => "floor.r",
+        "lceil" => "ceil.l",
+        "rceil" => "ceil.r",
+        "mid" => "divides",
+        "prime" => "prime",
+        "degree" => "degree",
+        "varepsilon" => "epsilon",
+        "epsilon" => "epsilon.alt",
+        "vartheta" => "theta.alt",
+        "varphi" => "phi",
+        "phi" => "phi.alt",
+        "varrho" => "rho.alt",
+        "varsigma" => "sigma.alt",
+        "varpi" => "pi.alt",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tex_to_typst() {
+        assert_eq!(tex_to_typst(r"\frac{a+b}{2}"), "frac(a + b, 2)");
+        assert_eq!(tex_to_typst(r"x^{2n}_i"), "x^(2 n)_i");
+        assert_eq!(tex_to_typst(r"\sqrt[3]{x} \le \alpha"), "root(3, x) lt.eq alpha");
+        assert_eq!(tex_to_typst(r"\left( a, b \right]"), r"lr(( a \, b ])");
+        assert_eq!(tex_to_typst(r"\text{if } x"), "\"if \" x");
+        assert_eq!(
+            tex_to_typst(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix}"),
+            "mat(delim: \"(\", 1, 0; 0, 1)"
+        );
+    }
+
+    #[test]
+    fn test_typeset_reports_baseline() {
+        let mut cache = MathCache::default();
+        let inline = cache.typeset(r"x", false, 16.0, 0xFFFFFF).unwrap();
+        let frac = cache.typeset(r"\frac{1}{2}", false, 16.0, 0xFFFFFF).unwrap();
+        assert!(inline.width > 0.0 && inline.height > 0.0);
+        // A fraction hangs below the baseline, a single letter barely does
+        assert!(frac.height > inline.height);
+        assert!(frac.ascent_percent() < inline.ascent_percent());
+        assert!(frac.baseline > 0.0 && frac.baseline < frac.height);
+        let (w, h, data) = rasterize(&frac.frame, 2.0);
+        assert_eq!(data.len(), (w * h * 4) as usize);
+        for tex in [
+            r"\int_0^\infty e^{-x^2}\, dx = \frac{\sqrt{\pi}}{2}",
+            r"f'(x) = \lim_{h \to 0} \frac{f(x+h) - f(x)}{h}",
+            r"\left\{ x \in \mathbb{R}^n \mid \|x\| \leq 1 \right\}",
+            r"|x| = \begin{cases} x & x \geq 0 \\ -x & \text{otherwise} \end{cases}",
+            r"\begin{bmatrix} a & b \\ c & d \end{bmatrix} \hat{v} \cdot \nabla f",
+            r"\sin^2\theta + \cos^2\theta = 1, \quad \sqrt[n]{a/b}",
+        ] {
+            assert!(cache.typeset(tex, true, 16.0, 0).is_ok(), "{} -> {}", tex, tex_to_typst(tex));
+        }
+        // Commands Typst does not know are reported, not rendered blank
+        assert!(cache.typeset(r"\nosuchcommand", false, 16.0, 0).is_err());
+    }
+}
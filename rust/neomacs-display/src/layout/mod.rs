@@ -17,6 +17,15 @@
 pub mod types;
 pub mod engine;
 pub mod emacs_ffi;
+pub mod highlight;
+#[cfg(feature = "html-renderer")]
+pub mod html;
+#[cfg(feature = "math")]
+pub mod math;
+#[cfg(feature = "icons")]
+pub mod icons;
+#[cfg(feature = "canvas")]
+pub mod canvas;
 
 pub use types::*;
 pub use engine::*;
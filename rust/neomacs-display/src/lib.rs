@@ -21,6 +21,15 @@ pub mod layout;
 #[cfg(feature = "winit-backend")]
 pub mod render_thread;
 
+#[cfg(feature = "winit-backend")]
+pub mod display_handle;
+
+#[cfg(all(feature = "remote", unix))]
+pub mod remote;
+
+#[cfg(all(feature = "remote", unix))]
+pub mod render_process;
+
 #[cfg(feature = "neo-term")]
 pub mod terminal;
 
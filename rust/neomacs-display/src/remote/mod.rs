@@ -0,0 +1,55 @@
+//! Out-of-process control protocol.
+//!
+//! Lets a process other than Emacs drive the display engine over a Unix
+//! socket, using newline-delimited JSON-RPC 2.0 (see [`protocol`]).  A
+//! client first calls `create_frame`, which opens the window and starts a
+//! render thread, then submits glyph buffers and manages resources:
+//!
+//! | method             | params                                  | result |
+//! |--------------------|-----------------------------------------|--------|
+//! | `create_frame`     | `width`, `height`, `title`?             | null   |
+//! | `submit_frame`     | `frame` (a serialized `FrameGlyphBuffer`) | null |
+//! | `load_image`       | `id`?, `path`, `max_width`?, `max_height`? | id  |
+//! | `image_size`       | `id`                                    | `{width, height}` or null |
+//! | `free_image`       | `id`                                    | null   |
+//! | `load_image_rgba`  | `id`, `width`, `height`, `data` (bytes) | null   |
+//! | `rasterize_math`   | `image_id`, `source` (a `MathSource`)   | null   |
+//! | `rasterize_icon`   | `image_id`, `source`, `width`, `height`, `color`? | null |
+//! | `rasterize_canvas` | `image_id`, `canvas`                    | null   |
+//! | `set_chart`        | `id`, `values`, `style`?                | null   |
+//! | `update_chart`     | `id`, `values`, `duration_ms`?          | null   |
+//! | `register_shader_effect` | `name`, `source`                  | null   |
+//! | `open_pdf`         | `id`, `path`, `password`?               | null   |
+//! | `render_pdf_page`  | `id`, `page`, `zoom`, `image_id`        | null   |
+//! | `close_pdf`        | `id`                                    | null   |
+//! | `define_webkit_profile` | `name`, `data_dir`?, `cache_dir`?, `ephemeral`? | null |
+//! | `create_webkit`    | `id`, `width`, `height`, `profile`?     | null   |
+//! | `load_webkit_uri`  | `id`, `url`                             | null   |
+//! | `resize_webkit`    | `id`, `width`, `height`                 | null   |
+//! | `destroy_webkit`   | `id`                                    | null   |
+//! | `create_video`     | `id`?, `path`                           | id     |
+//! | `play_video`, `pause_video`, `destroy_video` | `id`          | null   |
+//! | `create_terminal`  | `id`?, `cols`, `rows`, `shell`?         | id     |
+//! | `write_terminal`   | `id`, `data` (bytes or string)          | null   |
+//! | `resize_terminal`  | `id`, `cols`, `rows`                    | null   |
+//! | `destroy_terminal` | `id`                                    | null   |
+//! | `set_option`       | `name`, `value`                         | null   |
+//! | `get_option`       | `name`                                  | string |
+//! | `start_transition` | `window_id`?, `effect`?, `duration_ms`  | null   |
+//! | `shutdown`         |                                         | null   |
+//!
+//! Resource ids are allocated by the server unless the client passes its
+//! own `id`, which lets a client keep its ids across server restarts.
+//!
+//! Input events are sent to the client as `input_event` notifications
+//! whose params are the serialized [`InputEvent`](crate::thread_comm::InputEvent),
+//! tagged by `type`.
+//!
+//! [`replay`] records what the render thread receives, for the
+//! `neomacs-replay` viewer.
+
+pub mod protocol;
+pub mod replay;
+mod session;
+
+pub use session::{default_socket_path, serve, serve_io, serve_stream, Session};
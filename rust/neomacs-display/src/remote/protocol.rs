@@ -0,0 +1,127 @@
+//! JSON-RPC 2.0 message framing.
+//!
+//! Every message is one JSON object on a single line.  Requests carry an
+//! `id` and get exactly one response; requests without an `id` are
+//! notifications and get none.  The server sends its own notifications
+//! (input events) interleaved with responses.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Method was valid but the display engine could not carry it out
+pub const SERVER_ERROR: i64 = -32000;
+/// Resource id refers to an image, video or terminal that was freed
+pub const STALE_HANDLE: i64 = -32001;
+
+/// Output half of a connection, shared with the event forwarder.
+pub type SharedWriter = Arc<Mutex<dyn Write + Send>>;
+
+/// A decoded request or client notification
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("unknown method: {}", method))
+    }
+
+    pub fn server(message: impl std::fmt::Display) -> Self {
+        Self::new(SERVER_ERROR, message.to_string())
+    }
+}
+
+impl From<crate::core::error::DisplayError> for RpcError {
+    fn from(e: crate::core::error::DisplayError) -> Self {
+        match e {
+            crate::core::error::DisplayError::StaleHandle { .. } => Self::new(STALE_HANDLE, e.to_string()),
+            e => Self::server(e),
+        }
+    }
+}
+
+/// Parse one line into a request.
+pub fn parse_request(line: &str) -> Result<Request, RpcError> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))?;
+    serde_json::from_value(value).map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()))
+}
+
+/// Decode method parameters into `T`.
+pub fn decode_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Methods without parameters accept an omitted `params`
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Encode the response to request `id`.
+pub fn response(id: &Value, result: Result<Value, RpcError>) -> String {
+    let msg = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+    };
+    msg.to_string()
+}
+
+/// Encode a server notification.
+pub fn notification(method: &str, params: Value) -> String {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params }).to_string()
+}
+
+/// Write one message and its terminating newline.
+pub fn write_message(out: &SharedWriter, msg: &str) -> std::io::Result<()> {
+    let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+    out.write_all(msg.as_bytes())?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_parsing_and_errors() {
+        let req = parse_request(r#"{"jsonrpc":"2.0","id":3,"method":"free_image","params":{"id":7}}"#).unwrap();
+        assert_eq!(req.id, Some(json!(3)));
+        assert_eq!(req.method, "free_image");
+
+        let note = parse_request(r#"{"jsonrpc":"2.0","method":"shutdown"}"#).unwrap();
+        assert!(note.id.is_none() && note.params.is_null());
+
+        assert_eq!(parse_request("{").unwrap_err().code, PARSE_ERROR);
+        assert_eq!(parse_request(r#"{"id":1}"#).unwrap_err().code, INVALID_REQUEST);
+
+        let reply: Value = serde_json::from_str(&response(&json!(3), Err(RpcError::method_not_found("x")))).unwrap();
+        assert_eq!(reply["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert_eq!(reply["id"], json!(3));
+    }
+}
@@ -0,0 +1,322 @@
+//! Recorded frames for rendering bug reports.
+//!
+//! A screenshot shows that something is drawn wrong but not what the
+//! display engine was asked to draw.  A replay holds the glyph buffers the
+//! render thread received, with the images they show, so the frames can
+//! be drawn again by `neomacs-replay` on another machine, without Emacs.
+//!
+//! The file starts with [`MAGIC`], followed by records of one byte of
+//! kind, the time since recording started in milliseconds (u32), the
+//! payload length (u32), all little-endian, and the payload:
+//!
+//! | kind | payload |
+//! |------|---------|
+//! | `F`  | a `FrameGlyphBuffer` as JSON |
+//! | `I`  | id, max width, max height (u32 each), then the image file |
+//! | `R`  | id, width, height (u32 each), then RGBA pixels |
+//! | `X`  | id of a freed image (u32) |
+//!
+//! Videos, WebKit views and terminals are not recorded; their glyphs are
+//! drawn as empty areas.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::RecvTimeoutError;
+
+use crate::core::frame_glyphs::FrameGlyphBuffer;
+use crate::render_thread::{RenderThread, SharedImageDimensions, SharedMonitorInfo};
+use crate::thread_comm::{InputEvent, RenderCommand, ThreadComms};
+
+/// First bytes of a replay file
+pub const MAGIC: &[u8] = b"NEOMACS-REPLAY 1\n";
+
+/// One recorded event
+#[derive(Debug, Clone)]
+pub enum ReplayRecord {
+    Frame(Box<FrameGlyphBuffer>),
+    /// An image loaded from a file, with the file's contents
+    ImageFile { id: u32, max_width: u32, max_height: u32, data: Vec<u8> },
+    /// An image uploaded as decoded pixels
+    ImageRgba { id: u32, width: u32, height: u32, data: Vec<u8> },
+    ImageFree { id: u32 },
+}
+
+/// Writes records after the magic
+pub struct ReplayWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> ReplayWriter<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        Ok(Self { out })
+    }
+
+    /// Append `record`, which happened `at` after recording started
+    pub fn write(&mut self, at: Duration, record: &ReplayRecord) -> io::Result<()> {
+        let (kind, payload) = match record {
+            ReplayRecord::Frame(frame) => (b'F', serde_json::to_vec(frame)?),
+            ReplayRecord::ImageFile { id, max_width, max_height, data } => {
+                (b'I', [&id.to_le_bytes()[..], &max_width.to_le_bytes(), &max_height.to_le_bytes(), data].concat())
+            }
+            ReplayRecord::ImageRgba { id, width, height, data } => {
+                (b'R', [&id.to_le_bytes()[..], &width.to_le_bytes(), &height.to_le_bytes(), data].concat())
+            }
+            ReplayRecord::ImageFree { id } => (b'X', id.to_le_bytes().to_vec()),
+        };
+        let len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "replay record over 4 GiB"))?;
+        let ms = u32::try_from(at.as_millis()).unwrap_or(u32::MAX);
+        self.out.write_all(&[kind])?;
+        self.out.write_all(&ms.to_le_bytes())?;
+        self.out.write_all(&len.to_le_bytes())?;
+        self.out.write_all(&payload)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Reads the records of a replay
+pub struct ReplayReader<R: Read> {
+    input: R,
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// The three u32 leading an image payload, and the bytes after them
+fn image_header(payload: &[u8]) -> io::Result<([u32; 3], Vec<u8>)> {
+    if payload.len() < 12 {
+        return Err(invalid("truncated image record"));
+    }
+    let word = |i: usize| u32::from_le_bytes(payload[i * 4..i * 4 + 4].try_into().unwrap());
+    Ok(([word(0), word(1), word(2)], payload[12..].to_vec()))
+}
+
+impl<R: Read> ReplayReader<R> {
+    /// Check the magic at the start of `input`
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut magic = vec![0; MAGIC.len()];
+        input.read_exact(&mut magic).map_err(|_| invalid("not a Neomacs replay"))?;
+        if magic != MAGIC {
+            return Err(invalid("not a Neomacs replay"));
+        }
+        Ok(Self { input })
+    }
+
+    /// The next record and its time, None at the end of the file
+    pub fn next_record(&mut self) -> io::Result<Option<(Duration, ReplayRecord)>> {
+        let mut head = [0u8; 9];
+        match self.input.read(&mut head[..1])? {
+            0 => return Ok(None),
+            _ => self.input.read_exact(&mut head[1..]).map_err(|_| invalid("truncated record"))?,
+        }
+        let at = Duration::from_millis(u32::from_le_bytes(head[1..5].try_into().unwrap()) as u64);
+        let len = u32::from_le_bytes(head[5..9].try_into().unwrap()) as usize;
+        let mut payload = Vec::new();
+        (&mut self.input).take(len as u64).read_to_end(&mut payload)?;
+        if payload.len() != len {
+            return Err(invalid("truncated record"));
+        }
+        let record = match head[0] {
+            b'F' => ReplayRecord::Frame(Box::new(serde_json::from_slice(&payload).map_err(|e| invalid(e.to_string()))?)),
+            b'I' => {
+                let ([id, max_width, max_height], data) = image_header(&payload)?;
+                ReplayRecord::ImageFile { id, max_width, max_height, data }
+            }
+            b'R' => {
+                let ([id, width, height], data) = image_header(&payload)?;
+                ReplayRecord::ImageRgba { id, width, height, data }
+            }
+            b'X' => {
+                let id = payload.try_into().map_err(|_| invalid("bad image free record"))?;
+                ReplayRecord::ImageFree { id: u32::from_le_bytes(id) }
+            }
+            kind => return Err(invalid(format!("unknown record kind {:#04x}", kind))),
+        };
+        Ok(Some((at, record)))
+    }
+}
+
+/// How `play` shows a replay
+#[derive(Debug, Clone, Copy)]
+pub struct PlayOptions {
+    /// 2.0 plays twice as fast
+    pub speed: f32,
+    /// Start again from the first frame after the last
+    pub repeat: bool,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self { speed: 1.0, repeat: false }
+    }
+}
+
+/// Show the replay in `path` in a window, at the pace it was recorded,
+/// until the window is closed.
+pub fn play(path: &Path, options: PlayOptions) -> io::Result<()> {
+    let mut records = Vec::new();
+    let mut reader = ReplayReader::new(io::BufReader::new(std::fs::File::open(path)?))?;
+    while let Some(record) = reader.next_record()? {
+        records.push(record);
+    }
+    let (width, height) = records
+        .iter()
+        .find_map(|(_, r)| match r {
+            ReplayRecord::Frame(f) => Some((f.width.max(1.0) as u32, f.height.max(1.0) as u32)),
+            _ => None,
+        })
+        .ok_or_else(|| invalid("the replay has no frames"))?;
+
+    // Image files are loaded by path, like the originals
+    let images = std::env::temp_dir().join(format!("neomacs-replay-{}", std::process::id()));
+    std::fs::create_dir_all(&images)?;
+
+    let comms = ThreadComms::new()?;
+    let (emacs, render) = comms.split();
+    let image_dimensions: SharedImageDimensions = Arc::new(Mutex::new(HashMap::new()));
+    let shared_monitors: SharedMonitorInfo = Arc::new((Mutex::new(Vec::new()), std::sync::Condvar::new()));
+    let display_options = crate::render_thread::load_display_options(&emacs.cmd_tx);
+    let render_thread = RenderThread::spawn(
+        render,
+        width,
+        height,
+        format!("Neomacs replay: {}", path.display()),
+        image_dimensions,
+        shared_monitors,
+        Arc::new(Mutex::new(HashMap::new())),
+        display_options,
+        #[cfg(feature = "neo-term")]
+        Arc::new(Mutex::new(HashMap::new())),
+        #[cfg(feature = "pdf")]
+        Arc::new(Mutex::new(HashMap::new())),
+    );
+
+    let speed = if options.speed > 0.0 { options.speed } else { 1.0 };
+    let mut closed = false;
+    'replay: loop {
+        let started = Instant::now();
+        for (at, record) in &records {
+            // Sleep until the record is due, watching for the window closing
+            let due = started + at.div_f32(speed);
+            loop {
+                match emacs.input_rx.recv_deadline(due) {
+                    Ok(InputEvent::WindowClose) | Err(RecvTimeoutError::Disconnected) => {
+                        closed = true;
+                        break 'replay;
+                    }
+                    Ok(_) => emacs.wakeup_clear.clear(),
+                    Err(RecvTimeoutError::Timeout) => break,
+                }
+            }
+            let sent = match record {
+                ReplayRecord::Frame(frame) => emacs.frame_tx.send((**frame).clone()).is_ok(),
+                ReplayRecord::ImageFile { id, max_width, max_height, data } => {
+                    let file = images.join(format!("image-{}", id));
+                    std::fs::write(&file, data)?;
+                    emacs.cmd_tx.send(RenderCommand::ImageLoadFile {
+                        id: *id,
+                        path: file.to_string_lossy().into_owned(),
+                        max_width: *max_width,
+                        max_height: *max_height,
+                    }).is_ok()
+                }
+                ReplayRecord::ImageRgba { id, width, height, data } => emacs.cmd_tx.send(RenderCommand::ImageLoadRgba {
+                    id: *id,
+                    width: *width,
+                    height: *height,
+                    data: data.clone(),
+                }).is_ok(),
+                ReplayRecord::ImageFree { id } => emacs.cmd_tx.send(RenderCommand::ImageFree { id: *id }).is_ok(),
+            };
+            if !sent {
+                closed = true;
+                break 'replay;
+            }
+        }
+        if !options.repeat {
+            break;
+        }
+    }
+    // Keep showing the last frame until the window is closed
+    while !closed {
+        match emacs.input_rx.recv() {
+            Ok(InputEvent::WindowClose) | Err(_) => closed = true,
+            Ok(_) => emacs.wakeup_clear.clear(),
+        }
+    }
+
+    let _ = emacs.cmd_tx.send(RenderCommand::Shutdown);
+    render_thread.join();
+    let _ = std::fs::remove_dir_all(&images);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_glyphs::FrameGlyph;
+    use crate::core::types::Color;
+
+    #[test]
+    fn test_replay_round_trips() {
+        let mut frame = FrameGlyphBuffer::new();
+        frame.width = 640.0;
+        frame.glyphs.push(FrameGlyph::Stretch {
+            x: 0.0,
+            y: 0.0,
+            width: 8.0,
+            height: 16.0,
+            bg: Color::rgb(0.0, 0.0, 1.0),
+            face_id: 2,
+            is_overlay: false,
+        });
+        let mut writer = ReplayWriter::new(Vec::new()).unwrap();
+        writer.write(Duration::ZERO, &ReplayRecord::ImageFile {
+            id: 5,
+            max_width: 100,
+            max_height: 0,
+            data: b"\x89PNG".to_vec(),
+        }).unwrap();
+        writer.write(Duration::from_millis(16), &ReplayRecord::Frame(Box::new(frame))).unwrap();
+        writer.write(Duration::from_millis(40), &ReplayRecord::ImageFree { id: 5 }).unwrap();
+
+        let mut reader = ReplayReader::new(&writer.out[..]).unwrap();
+        let Some((at, ReplayRecord::ImageFile { id: 5, max_width: 100, max_height: 0, data })) =
+            reader.next_record().unwrap()
+        else {
+            panic!("expected an image file");
+        };
+        assert_eq!((at, &data[..]), (Duration::ZERO, &b"\x89PNG"[..]));
+        let Some((at, ReplayRecord::Frame(frame))) = reader.next_record().unwrap() else {
+            panic!("expected a frame");
+        };
+        assert_eq!((at, frame.width), (Duration::from_millis(16), 640.0));
+        assert!(matches!(frame.glyphs[0], FrameGlyph::Stretch { face_id: 2, .. }));
+        assert!(matches!(reader.next_record().unwrap(), Some((_, ReplayRecord::ImageFree { id: 5 }))));
+        assert!(reader.next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replay_rejects_bad_files() {
+        assert!(ReplayReader::new(&b"PNG"[..]).is_err());
+        assert!(ReplayReader::new(&b"NEOMACS-REPLAY 2\n"[..]).is_err());
+
+        let mut writer = ReplayWriter::new(Vec::new()).unwrap();
+        writer.write(Duration::ZERO, &ReplayRecord::ImageRgba { id: 1, width: 1, height: 1, data: vec![0; 4] }).unwrap();
+        // Cut off in the middle of the pixels
+        let bytes = &writer.out[..writer.out.len() - 2];
+        assert!(ReplayReader::new(bytes).unwrap().next_record().is_err());
+        let mut unknown = MAGIC.to_vec();
+        unknown.extend_from_slice(&[b'Z', 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(ReplayReader::new(&unknown[..]).unwrap().next_record().is_err());
+    }
+}
@@ -0,0 +1,737 @@
+//! A client connection and the render thread it drives.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::protocol::{
+    decode_params, notification, parse_request, response, write_message, RpcError, SharedWriter,
+};
+use crate::core::frame_glyphs::FrameGlyphBuffer;
+use crate::core::handle;
+use crate::core::option_registry::SharedOptionRegistry;
+use crate::core::scroll_animation::ScrollEffect;
+use crate::display_handle::DisplayHandle;
+use crate::render_thread::{RenderThread, SharedImageDimensions, SharedMonitorInfo};
+use crate::thread_comm::{RenderCommand, ThreadComms};
+
+/// Socket used when none is given: `$XDG_RUNTIME_DIR/neomacs-display.sock`,
+/// falling back to the temp directory.
+pub fn default_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("neomacs-display.sock")
+}
+
+/// Listen on `path` and serve clients one at a time.  Each client gets its
+/// own frame, which is closed when it disconnects.
+pub fn serve(path: &Path) -> std::io::Result<()> {
+    // A socket left over from a previous server would make bind fail
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)?;
+    log::info!("remote: listening on {}", path.display());
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = serve_stream(stream) {
+                    log::warn!("remote: connection error: {}", e);
+                }
+            }
+            Err(e) => log::warn!("remote: accept failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Run a session over a connected stream until the client disconnects.
+pub fn serve_stream(stream: UnixStream) -> std::io::Result<()> {
+    let out: SharedWriter = Arc::new(Mutex::new(stream.try_clone()?));
+    serve_io(BufReader::new(stream), out)
+}
+
+/// Run a session reading requests from `input` until end of file.
+pub fn serve_io(input: impl BufRead, out: SharedWriter) -> std::io::Result<()> {
+    let mut session = Session::new(Arc::clone(&out));
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = session.handle_line(&line) {
+            write_message(&out, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+/// The render thread started by `create_frame`
+struct Frontend {
+    frame_tx: Sender<FrameGlyphBuffer>,
+    handle: DisplayHandle,
+    image_dimensions: SharedImageDimensions,
+    display_options: SharedOptionRegistry,
+    render_thread: RenderThread,
+    forwarder: JoinHandle<()>,
+}
+
+/// Protocol state for one client.
+pub struct Session {
+    out: SharedWriter,
+    frontend: Option<Frontend>,
+    /// Typesets the snippets of `rasterize_math`
+    #[cfg(feature = "math")]
+    math: crate::layout::math::MathCache,
+}
+
+#[derive(Deserialize)]
+struct CreateFrameParams {
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SubmitFrameParams {
+    frame: FrameGlyphBuffer,
+}
+
+#[derive(Deserialize)]
+struct LoadImageParams {
+    /// Id to store the image under; allocated by the server if omitted
+    #[serde(default)]
+    id: Option<u32>,
+    path: String,
+    #[serde(default)]
+    max_width: u32,
+    #[serde(default)]
+    max_height: u32,
+}
+
+#[derive(Deserialize)]
+struct IdParams {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct LoadImageRgbaParams {
+    id: u32,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "math")]
+#[derive(Deserialize)]
+struct RasterizeMathParams {
+    image_id: u32,
+    source: crate::layout::math::MathSource,
+}
+
+#[cfg(feature = "icons")]
+#[derive(Deserialize)]
+struct RasterizeIconParams {
+    image_id: u32,
+    source: crate::layout::icons::IconSource,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    color: Option<u32>,
+}
+
+#[cfg(feature = "canvas")]
+#[derive(Deserialize)]
+struct RasterizeCanvasParams {
+    image_id: u32,
+    canvas: crate::layout::canvas::Canvas,
+}
+
+#[derive(Deserialize)]
+struct SetChartParams {
+    id: u32,
+    values: Vec<f32>,
+    #[serde(default)]
+    style: crate::core::chart::ChartStyle,
+}
+
+#[derive(Deserialize)]
+struct UpdateChartParams {
+    id: u32,
+    values: Vec<f32>,
+    #[serde(default)]
+    duration_ms: u64,
+}
+
+#[derive(Deserialize)]
+struct RegisterShaderEffectParams {
+    name: String,
+    source: String,
+}
+
+#[cfg(feature = "pdf")]
+#[derive(Deserialize)]
+struct OpenPdfParams {
+    id: u32,
+    path: String,
+    #[serde(default)]
+    password: Option<String>,
+}
+
+#[cfg(feature = "pdf")]
+#[derive(Deserialize)]
+struct RenderPdfPageParams {
+    id: u32,
+    page: u32,
+    zoom: f32,
+    image_id: u32,
+}
+
+#[derive(Deserialize)]
+struct DefineWebKitProfileParams {
+    name: String,
+    #[serde(default)]
+    data_dir: Option<String>,
+    #[serde(default)]
+    cache_dir: Option<String>,
+    #[serde(default)]
+    ephemeral: bool,
+}
+
+#[derive(Deserialize)]
+struct CreateWebKitParams {
+    id: u32,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LoadWebKitUriParams {
+    id: u32,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct ResizeWebKitParams {
+    id: u32,
+    width: u32,
+    height: u32,
+}
+
+#[cfg(feature = "video")]
+#[derive(Deserialize)]
+struct CreateVideoParams {
+    #[serde(default)]
+    id: Option<u32>,
+    path: String,
+}
+
+#[cfg(feature = "neo-term")]
+#[derive(Deserialize)]
+struct CreateTerminalParams {
+    #[serde(default)]
+    id: Option<u32>,
+    cols: u16,
+    rows: u16,
+    #[serde(default)]
+    shell: Option<String>,
+}
+
+#[cfg(feature = "neo-term")]
+#[derive(Deserialize)]
+struct WriteTerminalParams {
+    id: u32,
+    data: TerminalInput,
+}
+
+/// Input for a terminal: raw bytes, or text for clients that only have
+/// strings
+#[cfg(feature = "neo-term")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TerminalInput {
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+#[cfg(feature = "neo-term")]
+impl TerminalInput {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            TerminalInput::Bytes(bytes) => bytes,
+            TerminalInput::Text(text) => text.as_bytes(),
+        }
+    }
+}
+
+#[cfg(feature = "neo-term")]
+#[derive(Deserialize)]
+struct ResizeTerminalParams {
+    id: u32,
+    cols: u16,
+    rows: u16,
+}
+
+#[derive(Deserialize)]
+struct SetOptionParams {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct GetOptionParams {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct StartTransitionParams {
+    #[serde(default)]
+    window_id: Option<i64>,
+    #[serde(default)]
+    effect: Option<String>,
+    duration_ms: u64,
+}
+
+/// Claim image `id` for a request drawing it, unless it is already
+/// live: a canvas or chart is drawn again under the same id.
+fn claim_image(id: u32) -> Result<(), RpcError> {
+    if !handle::IMAGES.is_live(id) {
+        handle::IMAGES.claim(id)?;
+    }
+    Ok(())
+}
+
+/// Use the id a client chose, or allocate one.
+#[cfg(any(feature = "video", feature = "neo-term"))]
+fn new_id(table: &handle::HandleTable, id: Option<u32>) -> Result<u32, RpcError> {
+    match id {
+        Some(id) => {
+            table.claim(id)?;
+            Ok(id)
+        }
+        None => Ok(table.alloc()?),
+    }
+}
+
+impl Session {
+    pub fn new(out: SharedWriter) -> Self {
+        Self {
+            out,
+            frontend: None,
+            #[cfg(feature = "math")]
+            math: Default::default(),
+        }
+    }
+
+    /// Handle one request line, returning the response line to send
+    /// (None for client notifications).
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        match parse_request(line) {
+            Ok(req) => {
+                let result = self.dispatch(&req.method, req.params);
+                if let Err(ref e) = result {
+                    log::debug!("remote: {} failed: {}", req.method, e.message);
+                }
+                req.id.map(|id| response(&id, result))
+            }
+            Err(e) => Some(response(&Value::Null, Err(e))),
+        }
+    }
+
+    fn frontend(&self) -> Result<&Frontend, RpcError> {
+        self.frontend
+            .as_ref()
+            .ok_or_else(|| RpcError::server("no frame; call create_frame first"))
+    }
+
+    fn dispatch(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "create_frame" => {
+                let p: CreateFrameParams = decode_params(params)?;
+                self.create_frame(p)?;
+            }
+            "submit_frame" => {
+                let p: SubmitFrameParams = decode_params(params)?;
+                self.frontend()?
+                    .frame_tx
+                    .send(p.frame)
+                    .map_err(|_| RpcError::server("render thread has exited"))?;
+            }
+            "load_image" => {
+                let p: LoadImageParams = decode_params(params)?;
+                let handle = &self.frontend()?.handle;
+                let id = match p.id {
+                    Some(id) => {
+                        handle::IMAGES.claim(id)?;
+                        handle.send(RenderCommand::ImageLoadFile {
+                            id,
+                            path: p.path,
+                            max_width: p.max_width,
+                            max_height: p.max_height,
+                        })?;
+                        id
+                    }
+                    None => handle.load_image_file(&p.path, p.max_width, p.max_height)?,
+                };
+                return Ok(json!(id));
+            }
+            "image_size" => {
+                let p: IdParams = decode_params(params)?;
+                handle::IMAGES.check(p.id)?;
+                let dims = self.frontend()?.image_dimensions.lock().unwrap().get(&p.id).copied();
+                return Ok(match dims {
+                    Some((width, height)) => json!({ "width": width, "height": height }),
+                    None => Value::Null,
+                });
+            }
+            "free_image" => {
+                let p: IdParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::IMAGES.free(p.id)?;
+                frontend.handle.send(RenderCommand::ImageFree { id: p.id })?;
+            }
+            "load_image_rgba" => {
+                let p: LoadImageRgbaParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                claim_image(p.id)?;
+                frontend.handle.send(RenderCommand::ImageLoadRgba {
+                    id: p.id,
+                    width: p.width,
+                    height: p.height,
+                    data: p.data,
+                })?;
+            }
+            #[cfg(feature = "math")]
+            "rasterize_math" => {
+                let p: RasterizeMathParams = decode_params(params)?;
+                self.frontend()?;
+                let s = &p.source;
+                let typeset = self.math.typeset(&s.tex, s.display, s.size, s.color).map_err(RpcError::server)?;
+                claim_image(p.image_id)?;
+                self.frontend()?.handle.send(RenderCommand::MathRasterize {
+                    image_id: p.image_id,
+                    frame: typeset.frame,
+                    source: p.source,
+                })?;
+            }
+            #[cfg(feature = "icons")]
+            "rasterize_icon" => {
+                let p: RasterizeIconParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                claim_image(p.image_id)?;
+                frontend.handle.send(RenderCommand::IconRasterize {
+                    image_id: p.image_id,
+                    source: p.source,
+                    width: p.width,
+                    height: p.height,
+                    color: p.color,
+                })?;
+            }
+            #[cfg(feature = "canvas")]
+            "rasterize_canvas" => {
+                let p: RasterizeCanvasParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                claim_image(p.image_id)?;
+                frontend.handle.send(RenderCommand::CanvasRasterize { image_id: p.image_id, canvas: p.canvas })?;
+            }
+            "set_chart" => {
+                let p: SetChartParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                claim_image(p.id)?;
+                frontend.handle.send(RenderCommand::ChartSet { id: p.id, values: p.values, style: p.style })?;
+            }
+            "update_chart" => {
+                let p: UpdateChartParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::IMAGES.check(p.id)?;
+                frontend.handle.send(RenderCommand::ChartUpdate {
+                    id: p.id,
+                    values: p.values,
+                    duration: Duration::from_millis(p.duration_ms),
+                })?;
+            }
+            "register_shader_effect" => {
+                let p: RegisterShaderEffectParams = decode_params(params)?;
+                self.frontend()?
+                    .handle
+                    .send(RenderCommand::ShaderEffectRegister { name: p.name, source: p.source })?;
+            }
+            #[cfg(feature = "pdf")]
+            "open_pdf" => {
+                let p: OpenPdfParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::PDFS.claim(p.id)?;
+                frontend.handle.send(RenderCommand::PdfOpen { id: p.id, path: p.path, password: p.password })?;
+            }
+            #[cfg(feature = "pdf")]
+            "render_pdf_page" => {
+                let p: RenderPdfPageParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::PDFS.check(p.id)?;
+                claim_image(p.image_id)?;
+                frontend.handle.send(RenderCommand::PdfRenderPage {
+                    id: p.id,
+                    page: p.page,
+                    zoom: p.zoom,
+                    image_id: p.image_id,
+                })?;
+            }
+            #[cfg(feature = "pdf")]
+            "close_pdf" => {
+                let p: IdParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::PDFS.free(p.id)?;
+                frontend.handle.send(RenderCommand::PdfClose { id: p.id })?;
+            }
+            "define_webkit_profile" => {
+                let p: DefineWebKitProfileParams = decode_params(params)?;
+                self.frontend()?.handle.send(RenderCommand::WebKitDefineProfile {
+                    name: p.name,
+                    data_dir: p.data_dir,
+                    cache_dir: p.cache_dir,
+                    ephemeral: p.ephemeral,
+                })?;
+            }
+            "create_webkit" => {
+                let p: CreateWebKitParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::WEBKIT_VIEWS.claim(p.id)?;
+                frontend.handle.send(RenderCommand::WebKitCreate {
+                    id: p.id,
+                    width: p.width,
+                    height: p.height,
+                    profile: p.profile,
+                })?;
+            }
+            "load_webkit_uri" => {
+                let p: LoadWebKitUriParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::WEBKIT_VIEWS.check(p.id)?;
+                frontend.handle.send(RenderCommand::WebKitLoadUri { id: p.id, url: p.url })?;
+            }
+            "resize_webkit" => {
+                let p: ResizeWebKitParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::WEBKIT_VIEWS.check(p.id)?;
+                frontend.handle.send(RenderCommand::WebKitResize { id: p.id, width: p.width, height: p.height })?;
+            }
+            "destroy_webkit" => {
+                let p: IdParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::WEBKIT_VIEWS.free(p.id)?;
+                frontend.handle.send(RenderCommand::WebKitDestroy { id: p.id })?;
+            }
+            #[cfg(feature = "video")]
+            "create_video" => {
+                let p: CreateVideoParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                let id = new_id(&handle::VIDEOS, p.id)?;
+                frontend.handle.send(RenderCommand::VideoCreate { id, path: p.path })?;
+                return Ok(json!(id));
+            }
+            #[cfg(feature = "video")]
+            "play_video" | "pause_video" | "destroy_video" => {
+                let IdParams { id } = decode_params(params)?;
+                let frontend = self.frontend()?;
+                if method == "destroy_video" {
+                    handle::VIDEOS.free(id)?;
+                } else {
+                    handle::VIDEOS.check(id)?;
+                }
+                let cmd = match method {
+                    "play_video" => RenderCommand::VideoPlay { id },
+                    "pause_video" => RenderCommand::VideoPause { id },
+                    _ => RenderCommand::VideoDestroy { id },
+                };
+                frontend.handle.send(cmd)?;
+            }
+            #[cfg(feature = "neo-term")]
+            "create_terminal" => {
+                let p: CreateTerminalParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                let id = new_id(&handle::TERMINALS, p.id)?;
+                frontend.handle.send(RenderCommand::TerminalCreate {
+                    id,
+                    cols: p.cols,
+                    rows: p.rows,
+                    mode: 0,
+                    shell: p.shell,
+                })?;
+                return Ok(json!(id));
+            }
+            #[cfg(feature = "neo-term")]
+            "write_terminal" => {
+                let p: WriteTerminalParams = decode_params(params)?;
+                handle::TERMINALS.check(p.id)?;
+                self.frontend()?.handle.terminal_write(p.id, p.data.as_bytes())?;
+            }
+            #[cfg(feature = "neo-term")]
+            "resize_terminal" => {
+                let p: ResizeTerminalParams = decode_params(params)?;
+                handle::TERMINALS.check(p.id)?;
+                self.frontend()?.handle.send(RenderCommand::TerminalResize {
+                    id: p.id,
+                    cols: p.cols,
+                    rows: p.rows,
+                })?;
+            }
+            #[cfg(feature = "neo-term")]
+            "destroy_terminal" => {
+                let p: IdParams = decode_params(params)?;
+                let frontend = self.frontend()?;
+                handle::TERMINALS.free(p.id)?;
+                frontend.handle.send(RenderCommand::TerminalDestroy { id: p.id })?;
+            }
+            "set_option" => {
+                let p: SetOptionParams = decode_params(params)?;
+                self.frontend()?.display_options.lock().unwrap().set(&p.name, &p.value)?;
+            }
+            "get_option" => {
+                let p: GetOptionParams = decode_params(params)?;
+                let value = self.frontend()?.display_options.lock().unwrap().get(&p.name);
+                return match value {
+                    Some(value) => Ok(json!(value.to_string())),
+                    None => Err(RpcError::server(format!("unknown option: {}", p.name))),
+                };
+            }
+            "start_transition" => {
+                let p: StartTransitionParams = decode_params(params)?;
+                let effect = p.effect.as_deref().map_or(ScrollEffect::Crossfade, ScrollEffect::from_str);
+                self.frontend()?.handle.start_transition(
+                    p.window_id,
+                    effect,
+                    Duration::from_millis(p.duration_ms),
+                )?;
+            }
+            "shutdown" => self.shutdown(),
+            _ => return Err(RpcError::method_not_found(method)),
+        }
+        Ok(Value::Null)
+    }
+
+    fn create_frame(&mut self, p: CreateFrameParams) -> Result<(), RpcError> {
+        if self.frontend.is_some() {
+            return Err(RpcError::server("frame already created"));
+        }
+        let comms = ThreadComms::new().map_err(RpcError::server)?;
+        let (emacs, render) = comms.split();
+
+        let image_dimensions: SharedImageDimensions = Arc::new(Mutex::new(HashMap::new()));
+        let shared_monitors: SharedMonitorInfo =
+            Arc::new((Mutex::new(Vec::new()), std::sync::Condvar::new()));
+        let display_options = crate::render_thread::load_display_options(&emacs.cmd_tx);
+
+        let render_thread = RenderThread::spawn(
+            render,
+            p.width,
+            p.height,
+            p.title.unwrap_or_else(|| "Neomacs".to_string()),
+            Arc::clone(&image_dimensions),
+            shared_monitors,
+            Arc::new(Mutex::new(HashMap::new())),
+            Arc::clone(&display_options),
+            #[cfg(feature = "neo-term")]
+            Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "pdf")]
+            Arc::new(Mutex::new(HashMap::new())),
+        );
+
+        let frame_tx = emacs.frame_tx.clone();
+        let handle = DisplayHandle::new(emacs.cmd_tx.clone());
+
+        // Forward input events until the render thread exits or the
+        // client goes away
+        let out = Arc::clone(&self.out);
+        let forwarder = std::thread::spawn(move || {
+            while let Ok(event) = emacs.input_rx.recv() {
+                emacs.wakeup_clear.clear();
+                let params = serde_json::to_value(&event).unwrap_or(Value::Null);
+                if write_message(&out, &notification("input_event", params)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.frontend = Some(Frontend {
+            frame_tx,
+            handle,
+            image_dimensions,
+            display_options,
+            render_thread,
+            forwarder,
+        });
+        Ok(())
+    }
+
+    /// Close the frame, waiting for the render thread to exit.
+    fn shutdown(&mut self) {
+        if let Some(frontend) = self.frontend.take() {
+            let _ = frontend.handle.send(RenderCommand::Shutdown);
+            frontend.render_thread.join();
+            let _ = frontend.forwarder.join();
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame_glyphs::FrameGlyph;
+    use crate::core::types::Color;
+
+    #[test]
+    fn test_requests_before_create_frame() {
+        let out: SharedWriter = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let mut session = Session::new(out);
+
+        let reply = session
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"load_image","params":{"path":"/tmp/x.png"}}"#)
+            .unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["error"]["code"], json!(super::super::protocol::SERVER_ERROR));
+
+        let reply = session.handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"submit_frame"}"#).unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["error"]["code"], json!(super::super::protocol::INVALID_PARAMS));
+
+        // Notifications get no reply, even when they fail
+        assert!(session.handle_line(r#"{"jsonrpc":"2.0","method":"bogus"}"#).is_none());
+        assert!(session.handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#).is_some());
+    }
+
+    #[test]
+    fn test_frame_glyph_buffer_round_trips() {
+        let mut frame = FrameGlyphBuffer::new();
+        frame.width = 800.0;
+        frame.glyphs.push(FrameGlyph::Stretch {
+            x: 1.0,
+            y: 2.0,
+            width: 8.0,
+            height: 16.0,
+            bg: Color::new(0.1, 0.2, 0.3, 1.0),
+            face_id: 4,
+            is_overlay: false,
+        });
+
+        let json = serde_json::to_string(&json!({ "frame": frame })).unwrap();
+        let p: SubmitFrameParams = decode_params(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(p.frame.width, 800.0);
+        assert!(matches!(p.frame.glyphs[0], FrameGlyph::Stretch { face_id: 4, .. }));
+    }
+}
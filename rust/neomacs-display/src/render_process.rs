@@ -0,0 +1,640 @@
+//! Renderer running in a child process.
+//!
+//! When enabled (see [`server_from_env`]), Emacs does not start the render
+//! thread itself.  A supervisor thread takes its place on the other end of
+//! the `ThreadComms` channels and forwards frames and commands to
+//! `neomacs-display-server --stdio` over the `remote` protocol, relaying
+//! input events back.  If the child exits without being asked to (a GPU
+//! driver or renderer crash), the supervisor starts a new one and replays
+//! the frame, images, charts, shader effects, PDF documents, WebKit views,
+//! videos, terminals and options, so the editor keeps running with the
+//! same resource ids.
+//!
+//! Only commands the protocol covers cross the process boundary; others
+//! are dropped with a warning, once per kind of command.  Images
+//! loaded from files and videos are passed by path; images drawn by the
+//! renderer (math, icons, canvases, PDF pages) are passed as what they are
+//! drawn from, and only images uploaded as pixels carry pixel data.
+//! Terminal sessions are restarted and WebKit views reload their page
+//! rather than being resumed after a crash.
+
+use std::collections::{BTreeMap, HashSet};
+use std::mem::Discriminant;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{select, unbounded, Receiver};
+use serde_json::{json, Value};
+
+use crate::core::face::FaceDelta;
+use crate::core::frame_glyphs::FrameGlyphBuffer;
+use crate::render_thread::{RenderThread, SharedImageDimensions};
+use crate::thread_comm::{InputEvent, RenderCommand, RenderComms};
+
+/// Crashes within `CRASH_WINDOW` after which the supervisor gives up.
+const MAX_CRASHES: usize = 5;
+const CRASH_WINDOW: Duration = Duration::from_secs(60);
+/// Delay before restarting a crashed renderer.
+const RESTART_DELAY: Duration = Duration::from_millis(500);
+
+/// The server binary to run, from `NEOMACS_RENDER_PROCESS`, or None to
+/// render in-process.  `1` runs `neomacs-display-server` from PATH; any
+/// other value (except `0`) is taken as the path to the binary.
+pub fn server_from_env() -> Option<PathBuf> {
+    match std::env::var_os("NEOMACS_RENDER_PROCESS") {
+        Some(v) if v.is_empty() || v == "0" => None,
+        Some(v) if v == "1" => Some(PathBuf::from("neomacs-display-server")),
+        Some(v) => Some(PathBuf::from(v)),
+        None => None,
+    }
+}
+
+/// Start the supervisor thread.  It takes the place of the render thread:
+/// joining it waits for the child to shut down.
+pub fn spawn(
+    comms: RenderComms,
+    width: u32,
+    height: u32,
+    title: String,
+    image_dimensions: SharedImageDimensions,
+    server: PathBuf,
+) -> RenderThread {
+    let handle = std::thread::spawn(move || {
+        let mut supervisor = Supervisor {
+            comms,
+            image_dimensions,
+            server,
+            replay: Replay::new(width, height, title),
+            next_request: 1,
+        };
+        supervisor.run();
+    });
+    RenderThread::from_handle(handle)
+}
+
+/// Everything needed to bring a fresh renderer to the current state, as
+/// protocol requests.
+struct Replay {
+    frame: Value,
+    /// The request that last drew each image
+    images: BTreeMap<u32, (&'static str, Value)>,
+    shader_effects: BTreeMap<String, Value>,
+    /// Each document and whether it is still open: closed documents are
+    /// kept while images show their pages
+    pdfs: BTreeMap<u32, (Value, bool)>,
+    webkit_profiles: BTreeMap<String, Value>,
+    /// Each view and the URL it last loaded
+    webkit_views: BTreeMap<u32, (Value, Option<String>)>,
+    videos: BTreeMap<u32, Value>,
+    terminals: BTreeMap<u32, Value>,
+    options: BTreeMap<String, Value>,
+    last_glyphs: Option<Value>,
+    /// Every face sent so far, as one delta from generation 0: a new
+    /// renderer starts with no faces
+    faces: FaceDelta,
+    /// Kinds of command already reported as not forwarded
+    dropped: HashSet<Discriminant<RenderCommand>>,
+}
+
+impl Replay {
+    fn new(width: u32, height: u32, title: String) -> Self {
+        Self {
+            frame: json!({ "width": width, "height": height, "title": title }),
+            images: BTreeMap::new(),
+            shader_effects: BTreeMap::new(),
+            pdfs: BTreeMap::new(),
+            webkit_profiles: BTreeMap::new(),
+            webkit_views: BTreeMap::new(),
+            videos: BTreeMap::new(),
+            terminals: BTreeMap::new(),
+            options: BTreeMap::new(),
+            last_glyphs: None,
+            faces: FaceDelta::default(),
+            dropped: HashSet::new(),
+        }
+    }
+
+    /// Requests recreating the current state, in dependency order.
+    fn requests(&self) -> Vec<(&'static str, Value)> {
+        let mut reqs = vec![("create_frame", self.frame.clone())];
+        reqs.extend(self.options.values().map(|p| ("set_option", p.clone())));
+        reqs.extend(self.shader_effects.values().map(|p| ("register_shader_effect", p.clone())));
+        reqs.extend(self.pdfs.values().map(|(p, _)| ("open_pdf", p.clone())));
+        reqs.extend(self.images.values().map(|(method, p)| (*method, p.clone())));
+        reqs.extend(
+            self.pdfs
+                .iter()
+                .filter(|(_, (_, open))| !open)
+                .map(|(id, _)| ("close_pdf", json!({ "id": id }))),
+        );
+        reqs.extend(self.webkit_profiles.values().map(|p| ("define_webkit_profile", p.clone())));
+        for (id, (p, url)) in &self.webkit_views {
+            reqs.push(("create_webkit", p.clone()));
+            if let Some(url) = url {
+                reqs.push(("load_webkit_uri", json!({ "id": id, "url": url })));
+            }
+        }
+        reqs.extend(self.videos.values().map(|p| ("create_video", p.clone())));
+        reqs.extend(self.terminals.values().map(|p| ("create_terminal", p.clone())));
+        if let Some(ref glyphs) = self.last_glyphs {
+            let mut glyphs = glyphs.clone();
+            glyphs["face_delta"] = serde_json::to_value(&self.faces).unwrap_or(Value::Null);
+            reqs.push(("submit_frame", json!({ "frame": glyphs })));
+        }
+        reqs
+    }
+
+    /// Record `frame` as the one to show after a restart, with the faces
+    /// it changes, and return the request submitting it.
+    fn submit_frame(&mut self, frame: &FrameGlyphBuffer) -> Value {
+        self.faces.then(frame.face_delta.clone());
+        let glyphs = serde_json::to_value(frame).unwrap_or(Value::Null);
+        let params = json!({ "frame": glyphs });
+        self.last_glyphs = Some(glyphs);
+        params
+    }
+
+    /// Record `method` as the request drawing image `id`, and return it.
+    fn draw_image(&mut self, id: u32, method: &'static str, params: Value) -> (&'static str, Value) {
+        self.images.insert(id, (method, params.clone()));
+        (method, params)
+    }
+
+    /// Forget closed documents no image shows a page of any more.
+    fn forget_closed_pdfs(&mut self) {
+        let images = &self.images;
+        self.pdfs.retain(|id, (_, open)| {
+            *open || images.values().any(|(method, p)| *method == "render_pdf_page" && p["id"] == json!(id))
+        });
+    }
+
+    /// Translate a render command into a request, recording any resource
+    /// it creates or frees.  Returns None for commands the protocol does
+    /// not carry.
+    fn translate(&mut self, cmd: RenderCommand) -> Option<(&'static str, Value)> {
+        let req = match cmd {
+            RenderCommand::ImageLoadFile { id, path, max_width, max_height } => self.draw_image(
+                id,
+                "load_image",
+                json!({ "id": id, "path": path, "max_width": max_width, "max_height": max_height }),
+            ),
+            RenderCommand::ImageLoadRgba { id, width, height, data } => self.draw_image(
+                id,
+                "load_image_rgba",
+                json!({ "id": id, "width": width, "height": height, "data": data }),
+            ),
+            RenderCommand::ImageFree { id } => {
+                self.images.remove(&id);
+                self.forget_closed_pdfs();
+                ("free_image", json!({ "id": id }))
+            }
+            #[cfg(feature = "math")]
+            RenderCommand::MathRasterize { image_id, source, .. } => self.draw_image(
+                image_id,
+                "rasterize_math",
+                json!({ "image_id": image_id, "source": source }),
+            ),
+            #[cfg(feature = "icons")]
+            RenderCommand::IconRasterize { image_id, source, width, height, color } => self.draw_image(
+                image_id,
+                "rasterize_icon",
+                json!({ "image_id": image_id, "source": source, "width": width, "height": height, "color": color }),
+            ),
+            #[cfg(feature = "canvas")]
+            RenderCommand::CanvasRasterize { image_id, canvas } => self.draw_image(
+                image_id,
+                "rasterize_canvas",
+                json!({ "image_id": image_id, "canvas": canvas }),
+            ),
+            RenderCommand::ChartSet { id, values, style } => self.draw_image(
+                id,
+                "set_chart",
+                json!({ "id": id, "values": values, "style": style }),
+            ),
+            RenderCommand::ChartUpdate { id, values, duration } => {
+                if let Some(("set_chart", p)) = self.images.get_mut(&id) {
+                    p["values"] = json!(values);
+                }
+                (
+                    "update_chart",
+                    json!({ "id": id, "values": values, "duration_ms": duration.as_millis() as u64 }),
+                )
+            }
+            RenderCommand::ShaderEffectRegister { name, source } => {
+                let p = json!({ "name": name, "source": source });
+                self.shader_effects.insert(name, p.clone());
+                ("register_shader_effect", p)
+            }
+            #[cfg(feature = "pdf")]
+            RenderCommand::PdfOpen { id, path, password } => {
+                let p = json!({ "id": id, "path": path, "password": password });
+                self.pdfs.insert(id, (p.clone(), true));
+                ("open_pdf", p)
+            }
+            #[cfg(feature = "pdf")]
+            RenderCommand::PdfRenderPage { id, page, zoom, image_id } => self.draw_image(
+                image_id,
+                "render_pdf_page",
+                json!({ "id": id, "page": page, "zoom": zoom, "image_id": image_id }),
+            ),
+            #[cfg(feature = "pdf")]
+            RenderCommand::PdfClose { id } => {
+                if let Some((_, open)) = self.pdfs.get_mut(&id) {
+                    *open = false;
+                }
+                self.forget_closed_pdfs();
+                ("close_pdf", json!({ "id": id }))
+            }
+            RenderCommand::WebKitDefineProfile { name, data_dir, cache_dir, ephemeral } => {
+                let p = json!({ "name": name, "data_dir": data_dir, "cache_dir": cache_dir, "ephemeral": ephemeral });
+                self.webkit_profiles.insert(name, p.clone());
+                ("define_webkit_profile", p)
+            }
+            RenderCommand::WebKitCreate { id, width, height, profile } => {
+                let p = json!({ "id": id, "width": width, "height": height, "profile": profile });
+                self.webkit_views.insert(id, (p.clone(), None));
+                ("create_webkit", p)
+            }
+            RenderCommand::WebKitLoadUri { id, url } => {
+                if let Some((_, last)) = self.webkit_views.get_mut(&id) {
+                    *last = Some(url.clone());
+                }
+                ("load_webkit_uri", json!({ "id": id, "url": url }))
+            }
+            RenderCommand::WebKitResize { id, width, height } => {
+                if let Some((p, _)) = self.webkit_views.get_mut(&id) {
+                    p["width"] = json!(width);
+                    p["height"] = json!(height);
+                }
+                ("resize_webkit", json!({ "id": id, "width": width, "height": height }))
+            }
+            RenderCommand::WebKitDestroy { id } => {
+                self.webkit_views.remove(&id);
+                ("destroy_webkit", json!({ "id": id }))
+            }
+            #[cfg(feature = "video")]
+            RenderCommand::VideoCreate { id, path } => {
+                let p = json!({ "id": id, "path": path });
+                self.videos.insert(id, p.clone());
+                ("create_video", p)
+            }
+            #[cfg(feature = "video")]
+            RenderCommand::VideoPlay { id } => ("play_video", json!({ "id": id })),
+            #[cfg(feature = "video")]
+            RenderCommand::VideoPause { id } => ("pause_video", json!({ "id": id })),
+            #[cfg(feature = "video")]
+            RenderCommand::VideoDestroy { id } => {
+                self.videos.remove(&id);
+                ("destroy_video", json!({ "id": id }))
+            }
+            #[cfg(feature = "neo-term")]
+            RenderCommand::TerminalCreate { id, cols, rows, shell, .. } => {
+                let p = json!({ "id": id, "cols": cols, "rows": rows, "shell": shell });
+                self.terminals.insert(id, p.clone());
+                ("create_terminal", p)
+            }
+            #[cfg(feature = "neo-term")]
+            RenderCommand::TerminalWrite { id, data } => {
+                // Raw bytes: PTY input need not be valid UTF-8
+                ("write_terminal", json!({ "id": id, "data": data }))
+            }
+            #[cfg(feature = "neo-term")]
+            RenderCommand::TerminalResize { id, cols, rows } => {
+                if let Some(p) = self.terminals.get_mut(&id) {
+                    p["cols"] = json!(cols);
+                    p["rows"] = json!(rows);
+                }
+                ("resize_terminal", json!({ "id": id, "cols": cols, "rows": rows }))
+            }
+            #[cfg(feature = "neo-term")]
+            RenderCommand::TerminalDestroy { id } => {
+                self.terminals.remove(&id);
+                ("destroy_terminal", json!({ "id": id }))
+            }
+            RenderCommand::SetDisplayOption { name, value } => {
+                let p = json!({ "name": name, "value": value.to_string() });
+                self.options.insert(name.to_string(), p.clone());
+                ("set_option", p)
+            }
+            RenderCommand::StartTransition { window_id, effect, duration_ms, .. } => (
+                "start_transition",
+                json!({ "window_id": window_id, "effect": effect.as_str(), "duration_ms": duration_ms }),
+            ),
+            RenderCommand::Shutdown => ("shutdown", Value::Null),
+            other => {
+                if self.dropped.insert(std::mem::discriminant(&other)) {
+                    let name = format!("{:?}", other);
+                    log::warn!(
+                        "render process: {} is not supported out of process, dropping it",
+                        name.split([' ', '(', '{']).next().unwrap_or(&name)
+                    );
+                }
+                return None;
+            }
+        };
+        Some(req)
+    }
+}
+
+/// Why a connection to the renderer ended
+enum Disconnect {
+    Shutdown,
+    Crashed,
+}
+
+struct Supervisor {
+    comms: RenderComms,
+    image_dimensions: SharedImageDimensions,
+    server: PathBuf,
+    replay: Replay,
+    next_request: u64,
+}
+
+impl Supervisor {
+    fn run(&mut self) {
+        let mut crashes: Vec<Instant> = Vec::new();
+        loop {
+            let started = self
+                .start_child()
+                .map(|(child, stdin, events)| self.serve(child, stdin, events));
+            match started {
+                Ok(Disconnect::Shutdown) => return,
+                Ok(Disconnect::Crashed) => log::error!("render process crashed, restarting"),
+                Err(e) => log::error!("render process {}: {}", self.server.display(), e),
+            }
+
+            let now = Instant::now();
+            crashes.retain(|t| now.duration_since(*t) < CRASH_WINDOW);
+            crashes.push(now);
+            if crashes.len() >= MAX_CRASHES {
+                log::error!("render process crashed {} times in {:?}, giving up", crashes.len(), CRASH_WINDOW);
+                return;
+            }
+            std::thread::sleep(RESTART_DELAY);
+        }
+    }
+
+    fn start_child(&mut self) -> std::io::Result<(Child, ChildStdin, Receiver<InputEvent>)> {
+        let mut child = Command::new(&self.server)
+            .arg("--stdio")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+
+        // Read responses and events on their own thread; the channel
+        // disconnects when the child's stdout closes.
+        let (event_tx, event_rx) = unbounded();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                let Ok(msg) = serde_json::from_str::<Value>(&line) else { continue };
+                if let Some(err) = msg.get("error") {
+                    log::warn!("render process: request {} failed: {}", msg["id"], err["message"]);
+                } else if msg["method"] == "input_event" {
+                    match serde_json::from_value::<InputEvent>(msg["params"].clone()) {
+                        Ok(event) => {
+                            if event_tx.send(event).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => log::warn!("render process: bad input event: {}", e),
+                    }
+                }
+            }
+        });
+
+        for (method, params) in self.replay.requests() {
+            self.write_request(&mut stdin, method, params)?;
+        }
+        Ok((child, stdin, event_rx))
+    }
+
+    fn write_request(&mut self, stdin: &mut ChildStdin, method: &str, params: Value) -> std::io::Result<()> {
+        let id = self.next_request;
+        self.next_request += 1;
+        let msg = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(stdin, "{}", msg)?;
+        stdin.flush()
+    }
+
+    /// Forward traffic until the child exits or Emacs shuts down.
+    fn serve(&mut self, mut child: Child, mut stdin: ChildStdin, events: Receiver<InputEvent>) -> Disconnect {
+        let cmd_rx = self.comms.cmd_rx.clone();
+        let frame_rx = self.comms.frame_rx.clone();
+        let result = loop {
+            select! {
+                recv(cmd_rx) -> cmd => {
+                    let Ok(cmd) = cmd else { break Disconnect::Shutdown };
+                    let shutdown = matches!(cmd, RenderCommand::Shutdown);
+                    if let Some((method, params)) = self.replay.translate(cmd) {
+                        if self.write_request(&mut stdin, method, params).is_err() && !shutdown {
+                            break Disconnect::Crashed;
+                        }
+                    }
+                    if shutdown {
+                        break Disconnect::Shutdown;
+                    }
+                }
+                recv(frame_rx) -> frame => {
+                    let Ok(mut frame) = frame else { break Disconnect::Shutdown };
+                    // Only the newest frame matters, but it has to carry
+                    // the face changes of the frames skipped
+                    while let Ok(mut newer) = frame_rx.try_recv() {
+                        frame.face_delta.then(std::mem::take(&mut newer.face_delta));
+                        newer.face_delta = std::mem::take(&mut frame.face_delta);
+                        frame = newer;
+                    }
+                    let params = self.replay.submit_frame(&frame);
+                    if self.write_request(&mut stdin, "submit_frame", params).is_err() {
+                        break Disconnect::Crashed;
+                    }
+                }
+                recv(events) -> event => {
+                    let Ok(event) = event else { break Disconnect::Crashed };
+                    match event {
+                        InputEvent::ImageDimensionsReady { id, width, height } => {
+                            if let Ok(mut dims) = self.image_dimensions.lock() {
+                                dims.insert(id, (width, height));
+                            }
+                        }
+                        InputEvent::WindowResize { width, height } => {
+                            self.replay.frame["width"] = json!(width);
+                            self.replay.frame["height"] = json!(height);
+                        }
+                        _ => {}
+                    }
+                    self.comms.send_input(event);
+                }
+            }
+        };
+
+        drop(stdin);
+        match result {
+            Disconnect::Shutdown => {
+                let _ = child.wait();
+            }
+            Disconnect::Crashed => {
+                let _ = child.kill();
+                match child.wait() {
+                    Ok(status) => log::warn!("render process exited: {}", status),
+                    Err(e) => log::warn!("render process wait failed: {}", e),
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::face::{Face, FaceCache};
+
+    #[test]
+    fn test_replay_tracks_live_resources() {
+        let mut replay = Replay::new(800, 600, "t".into());
+        for id in [1, 2] {
+            replay.translate(RenderCommand::ImageLoadFile {
+                id,
+                path: format!("/tmp/{}.png", id),
+                max_width: 0,
+                max_height: 0,
+            });
+        }
+        replay.translate(RenderCommand::ImageFree { id: 1 });
+        assert!(replay.translate(RenderCommand::VisualBell).is_none());
+
+        let reqs = replay.requests();
+        let methods: Vec<_> = reqs.iter().map(|(m, _)| *m).collect();
+        assert_eq!(methods, ["create_frame", "load_image"]);
+        assert_eq!(reqs[1].1["id"], json!(2));
+    }
+
+    #[test]
+    fn test_replay_restores_every_resource_kind() {
+        let mut replay = Replay::new(800, 600, "t".into());
+        let mut commands = vec![
+            RenderCommand::ImageLoadFile { id: 1, path: "/tmp/1.png".into(), max_width: 0, max_height: 0 },
+            RenderCommand::ImageLoadRgba { id: 2, width: 1, height: 1, data: vec![255; 4] },
+            RenderCommand::ChartSet { id: 3, values: vec![1.0, 2.0], style: Default::default() },
+            RenderCommand::ChartUpdate { id: 3, values: vec![3.0], duration: Duration::ZERO },
+            RenderCommand::ShaderEffectRegister { name: "wave".into(), source: "// wgsl".into() },
+            RenderCommand::WebKitDefineProfile { name: "work".into(), data_dir: None, cache_dir: None, ephemeral: true },
+            RenderCommand::WebKitCreate { id: 4, width: 640, height: 480, profile: Some("work".into()) },
+            RenderCommand::WebKitLoadUri { id: 4, url: "https://www.gnu.org/".into() },
+            RenderCommand::WebKitResize { id: 4, width: 800, height: 600 },
+        ];
+        #[cfg(feature = "pdf")]
+        commands.extend([
+            RenderCommand::PdfOpen { id: 5, path: "/tmp/a.pdf".into(), password: None },
+            RenderCommand::PdfRenderPage { id: 5, page: 0, zoom: 1.0, image_id: 6 },
+            RenderCommand::PdfClose { id: 5 },
+        ]);
+        #[cfg(feature = "icons")]
+        commands.push(RenderCommand::IconRasterize {
+            image_id: 7,
+            source: crate::layout::icons::IconSource::Svg(b"<svg/>".as_slice().into()),
+            width: 16,
+            height: 16,
+            color: None,
+        });
+        #[cfg(feature = "canvas")]
+        commands.push(RenderCommand::CanvasRasterize { image_id: 8, canvas: crate::layout::canvas::Canvas::new(4, 4) });
+        #[cfg(feature = "math")]
+        {
+            let source = crate::layout::math::MathSource { tex: "x^2".into(), display: false, size: 14.0, color: 0 };
+            let typeset = crate::layout::math::MathCache::default().typeset("x^2", false, 14.0, 0).unwrap();
+            commands.push(RenderCommand::MathRasterize { image_id: 9, frame: typeset.frame, source });
+        }
+        for cmd in commands {
+            assert!(replay.translate(cmd).is_some());
+        }
+
+        let reqs = replay.requests();
+        let find = |method: &str| reqs.iter().find(|(m, _)| *m == method).map(|(_, p)| p.clone());
+        assert_eq!(find("set_chart").unwrap()["values"], json!([3.0]));
+        assert_eq!(find("create_webkit").unwrap()["width"], json!(800));
+        assert_eq!(find("load_webkit_uri").unwrap()["url"], json!("https://www.gnu.org/"));
+        assert_eq!(find("load_image_rgba").unwrap()["data"], json!([255, 255, 255, 255]));
+        assert!(find("register_shader_effect").is_some());
+        assert!(find("define_webkit_profile").is_some());
+        #[cfg(feature = "pdf")]
+        {
+            // The closed document stays while its page is shown
+            let position = |method: &str| reqs.iter().position(|(m, _)| *m == method).unwrap();
+            assert!(position("open_pdf") < position("render_pdf_page"));
+            assert!(position("render_pdf_page") < position("close_pdf"));
+        }
+        #[cfg(feature = "icons")]
+        assert!(find("rasterize_icon").is_some());
+        #[cfg(feature = "canvas")]
+        assert!(find("rasterize_canvas").is_some());
+        #[cfg(feature = "math")]
+        assert_eq!(find("rasterize_math").unwrap()["source"]["tex"], json!("x^2"));
+
+        // A fresh renderer understands every request; without a frame
+        // each fails only for want of one
+        let out: crate::remote::protocol::SharedWriter = std::sync::Arc::new(std::sync::Mutex::new(Vec::<u8>::new()));
+        let mut session = crate::remote::Session::new(out);
+        for (id, (method, params)) in reqs.iter().enumerate().skip(1) {
+            if *method == "submit_frame" {
+                continue;
+            }
+            let line = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string();
+            let reply: Value = serde_json::from_str(&session.handle_line(&line).unwrap()).unwrap();
+            assert_eq!(
+                reply["error"]["code"],
+                json!(crate::remote::protocol::SERVER_ERROR),
+                "{}: {}",
+                method,
+                reply
+            );
+        }
+
+        // Freeing the page lets the closed document go
+        #[cfg(feature = "pdf")]
+        {
+            replay.translate(RenderCommand::ImageFree { id: 6 });
+            assert!(replay.requests().iter().all(|(m, _)| *m != "open_pdf" && *m != "close_pdf"));
+        }
+    }
+
+    #[cfg(feature = "neo-term")]
+    #[test]
+    fn test_terminal_input_keeps_raw_bytes() {
+        let mut replay = Replay::new(800, 600, "t".into());
+        let (method, params) =
+            replay.translate(RenderCommand::TerminalWrite { id: 1, data: vec![0xff, b'a'] }).unwrap();
+        assert_eq!(method, "write_terminal");
+        assert_eq!(params["data"], json!([255, 97]));
+    }
+
+    #[test]
+    fn test_replay_resends_every_face() {
+        let mut cache = FaceCache::new();
+        let mut replay = Replay::new(800, 600, "t".into());
+        let mut frame = FrameGlyphBuffer::new();
+        cache.insert(Face::new(1));
+        frame.face_delta = cache.delta_since(0);
+        replay.submit_frame(&frame);
+
+        let base = cache.generation();
+        cache.insert(Face::new(2));
+        frame.face_delta = cache.delta_since(base);
+        replay.submit_frame(&frame);
+
+        // A fresh renderer gets the last frame with every face
+        let reqs = replay.requests();
+        let (method, params) = reqs.last().unwrap();
+        assert_eq!(*method, "submit_frame");
+        let sent: FrameGlyphBuffer = serde_json::from_value(params["frame"].clone()).unwrap();
+        assert_eq!(sent.face_delta.base, 0);
+        let mut fresh = FaceCache::new();
+        assert_eq!(fresh.apply_delta(&sent.face_delta), Some(vec![1, 2]));
+        assert_eq!(fresh.generation(), cache.generation());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Owns winit event loop, wgpu, GLib/WebKit. Runs at native VSync.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 
@@ -16,17 +16,31 @@ use winit::window::{Window, WindowId};
 use winit::platform::x11::EventLoopBuilderExtX11;
 #[cfg(target_os = "linux")]
 use winit::platform::wayland::EventLoopBuilderExtWayland;
+#[cfg(target_os = "windows")]
+use winit::platform::windows::EventLoopBuilderExtWindows;
+#[cfg(target_os = "macos")]
+use winit::platform::macos::{OptionAsAlt, WindowAttributesExtMacOS};
 
 use crate::backend::wgpu::{
-    WgpuGlyphAtlas, WgpuRenderer,
+    DeviceLossWatch, WgpuGlyphAtlas, WgpuRenderer,
     NEOMACS_CTRL_MASK, NEOMACS_META_MASK, NEOMACS_SHIFT_MASK, NEOMACS_SUPER_MASK,
 };
-use crate::core::face::Face;
-use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
+use crate::core::animation::{FloatingKind, FloatingProperty};
+use crate::core::clock::SharedClock;
+use crate::core::face::{Face, FaceCache, FaceDelta};
+use crate::core::display_config::{ConfigWatcher, DisplayConfig};
+use crate::core::effect_preview::{PreviewEffect, PreviewOutput, PreviewTimeline};
+use crate::core::error_report::{self, ErrorKind};
+use crate::core::option_registry::{OptionValue, SharedOptionRegistry};
+use crate::core::frame_clock::FrameClock;
+use crate::core::frame_stats::{self, FrameStats};
+use crate::core::idle_scheduler::{IdleScheduler, IdleTask};
+use crate::core::frame_glyphs::{BackdropImage, BlurRegion, FrameGlyph, FrameGlyphBuffer};
 use crate::core::types::{
     AnimatedCursor, Color, CursorAnimStyle, Rect,
     ease_out_quad, ease_out_cubic, ease_out_expo, ease_in_out_cubic, ease_linear,
 };
+use crate::text::clusters::SharedClusterOffsets;
 use crate::thread_comm::{InputEvent, PopupMenuItem, RenderCommand, RenderComms};
 
 #[cfg(all(feature = "wpe-webkit", wpe_platform_available))]
@@ -40,6 +54,16 @@ use crate::backend::wpe::{WpeBackend, WpeWebView};
 /// Shared storage for image dimensions accessible from both threads
 pub type SharedImageDimensions = Arc<Mutex<HashMap<u32, (u32, u32)>>>;
 
+/// A terminal whose SSH connection is being made
+#[cfg(feature = "neo-term-ssh")]
+struct PendingSshTerminal {
+    id: u32,
+    cols: u16,
+    rows: u16,
+    mode: u8,
+    result: std::sync::mpsc::Receiver<Result<crate::terminal::transport::Transport, String>>,
+}
+
 /// Monitor information collected from winit
 #[derive(Debug, Clone)]
 pub struct MonitorInfo {
@@ -57,6 +81,28 @@ pub struct MonitorInfo {
 /// The Condvar is notified once monitors have been populated.
 pub type SharedMonitorInfo = Arc<(Mutex<Vec<MonitorInfo>>, std::sync::Condvar)>;
 
+/// Load display options from the config file (if there is one).
+///
+/// Changes made through the returned registry, or by reloading the file,
+/// are forwarded to the render thread over `cmd_tx`.
+pub fn load_display_options(cmd_tx: &crossbeam_channel::Sender<RenderCommand>) -> SharedOptionRegistry {
+    let display_config = match DisplayConfig::default_path() {
+        Some(path) if path.exists() => DisplayConfig::load(&path).unwrap_or_else(|e| {
+            error_report::warning(ErrorKind::Config, None, format!("ignoring display config: {}", e));
+            DisplayConfig::default()
+        }),
+        _ => DisplayConfig::default(),
+    };
+    let mut registry = crate::core::option_registry::OptionRegistry::new(&display_config);
+    let cmd_tx = cmd_tx.clone();
+    registry.on_change(Box::new(move |name, value| {
+        let value = value.clone();
+        let _ = cmd_tx.try_send(RenderCommand::SetDisplayOption { name, value });
+    }));
+    crate::core::display_config::install_reload_signal();
+    Arc::new(Mutex::new(registry))
+}
+
 /// Render thread state
 pub struct RenderThread {
     handle: Option<JoinHandle<()>>,
@@ -71,15 +117,21 @@ impl RenderThread {
         title: String,
         image_dimensions: SharedImageDimensions,
         shared_monitors: SharedMonitorInfo,
+        cluster_offsets: SharedClusterOffsets,
+        display_options: SharedOptionRegistry,
         #[cfg(feature = "neo-term")]
         shared_terminals: crate::terminal::SharedTerminals,
+        #[cfg(feature = "pdf")]
+        shared_pdfs: crate::backend::wgpu::SharedPdfDocuments,
     ) -> Self {
         let handle = thread::spawn(move || {
             run_render_loop(
                 comms, width, height, title, image_dimensions,
-                shared_monitors,
+                shared_monitors, cluster_offsets, display_options,
                 #[cfg(feature = "neo-term")]
                 shared_terminals,
+                #[cfg(feature = "pdf")]
+                shared_pdfs,
             );
         });
 
@@ -88,6 +140,12 @@ impl RenderThread {
         }
     }
 
+    /// Wrap a thread that stands in for the render thread (see
+    /// `render_process`).
+    pub(crate) fn from_handle(handle: JoinHandle<()>) -> Self {
+        Self { handle: Some(handle) }
+    }
+
     /// Wait for render thread to finish
     pub fn join(mut self) {
         if let Some(handle) = self.handle.take() {
@@ -106,6 +164,9 @@ struct CrossfadeTransition {
     old_texture: wgpu::Texture,
     old_view: wgpu::TextureView,
     old_bind_group: wgpu::BindGroup,
+    /// New text size over old when the window's text was rescaled; the
+    /// snapshots are then scaled instead of drawn with `effect`
+    text_scale: Option<f32>,
 }
 
 /// State for an active scroll slide transition
@@ -114,6 +175,8 @@ struct ScrollTransition {
     duration: std::time::Duration,
     bounds: Rect,
     direction: i32, // +1 = scroll down (content up), -1 = scroll up
+    /// Pixels the text moves up (negative: down), for the slide effects
+    distance: f32,
     effect: crate::core::scroll_animation::ScrollEffect,
     easing: crate::core::scroll_animation::ScrollEasing,
     old_texture: wgpu::Texture,
@@ -128,7 +191,9 @@ enum WebKitImportPolicy {
     PixelsFirst,
     /// Prefer DMA-BUF import first, fallback to raw pixels.
     DmaBufFirst,
-    /// Default compatibility mode (currently PixelsFirst).
+    /// Default compatibility mode (currently PixelsFirst; see
+    /// docs/plans/2026-02-03-wpe-webkit-wgpu-design.md for why zero-copy
+    /// import is not the default yet).
     Auto,
 }
 
@@ -493,9 +558,13 @@ struct CursorState {
 
     // Animation (smooth motion)
     anim_enabled: bool,
+    // Target is a predicted position, drawn even without animation
+    predicted: bool,
     anim_speed: f32,
     anim_style: CursorAnimStyle,
     anim_duration: f32, // seconds, for non-Exponential styles
+    // Custom keyframe curve replacing the easing of duration-based styles
+    anim_curve: Option<crate::core::animation::AnimationCurve>,
     target: Option<CursorTarget>,
     current_x: f32,
     current_y: f32,
@@ -524,6 +593,7 @@ struct CursorState {
     // Size transition (independent of position animation)
     size_transition_enabled: bool,
     size_transition_duration: f32, // seconds
+    size_curve: Option<crate::core::animation::AnimationCurve>,
     size_animating: bool,
     size_start_w: f32,
     size_start_h: f32,
@@ -534,27 +604,36 @@ struct CursorState {
 
 impl Default for CursorState {
     fn default() -> Self {
+        Self::starting_at(std::time::Instant::now())
+    }
+}
+
+impl CursorState {
+    /// A cursor whose blink and animations start at `now`
+    fn starting_at(now: std::time::Instant) -> Self {
         Self {
             blink_on: true,
             blink_enabled: true,
-            last_blink_toggle: std::time::Instant::now(),
+            last_blink_toggle: now,
             blink_interval: std::time::Duration::from_millis(500),
             anim_enabled: true,
+            predicted: false,
             anim_speed: 15.0,
             anim_style: CursorAnimStyle::CriticallyDampedSpring,
             anim_duration: 0.15,
+            anim_curve: None,
             target: None,
             current_x: 0.0,
             current_y: 0.0,
             current_w: 0.0,
             current_h: 0.0,
             animating: false,
-            last_anim_time: std::time::Instant::now(),
+            last_anim_time: now,
             start_x: 0.0,
             start_y: 0.0,
             start_w: 0.0,
             start_h: 0.0,
-            anim_start_time: std::time::Instant::now(),
+            anim_start_time: now,
             velocity_x: 0.0,
             velocity_y: 0.0,
             velocity_w: 0.0,
@@ -568,17 +647,16 @@ impl Default for CursorState {
             prev_target_cy: 0.0,
             size_transition_enabled: false,
             size_transition_duration: 0.15,
+            size_curve: None,
             size_animating: false,
             size_start_w: 0.0,
             size_start_h: 0.0,
             size_target_w: 0.0,
             size_target_h: 0.0,
-            size_anim_start: std::time::Instant::now(),
+            size_anim_start: now,
         }
     }
-}
 
-impl CursorState {
     /// Compute the 4 target corners for a cursor based on its style.
     /// Returns [TL, TR, BR, BL] as (x, y) tuples.
     fn target_corners(target: &CursorTarget) -> [(f32, f32); 4] {
@@ -618,8 +696,122 @@ impl CursorState {
         }
     }
 
-    /// Tick cursor animation, returns true if position changed (needs redraw)
-    fn tick_animation(&mut self) -> bool {
+    /// Where the renderer should draw the cursor instead of where the
+    /// frame puts it, while animation is on or a position is predicted
+    fn animated(&self) -> Option<AnimatedCursor> {
+        if !self.anim_enabled && !self.predicted {
+            return None;
+        }
+        let target = self.target.as_ref()?;
+        let corners = if self.anim_style == CursorAnimStyle::CriticallyDampedSpring
+            && self.animating
+        {
+            Some([
+                (self.corner_springs[0].x, self.corner_springs[0].y),
+                (self.corner_springs[1].x, self.corner_springs[1].y),
+                (self.corner_springs[2].x, self.corner_springs[2].y),
+                (self.corner_springs[3].x, self.corner_springs[3].y),
+            ])
+        } else {
+            None
+        };
+        Some(AnimatedCursor {
+            window_id: target.window_id,
+            x: self.current_x,
+            y: self.current_y,
+            width: self.current_w,
+            height: self.current_h,
+            corners,
+        })
+    }
+
+    /// Put the cursor on `target` at once
+    fn snap_to(&mut self, target: &CursorTarget) {
+        self.current_x = target.x;
+        self.current_y = target.y;
+        self.current_w = target.width;
+        self.current_h = target.height;
+        self.animating = false;
+        // Snap corner springs to target corners
+        let corners = CursorState::target_corners(target);
+        for i in 0..4 {
+            self.corner_springs[i].x = corners[i].0;
+            self.corner_springs[i].y = corners[i].1;
+            self.corner_springs[i].vx = 0.0;
+            self.corner_springs[i].vy = 0.0;
+            self.corner_springs[i].target_x = corners[i].0;
+            self.corner_springs[i].target_y = corners[i].1;
+        }
+        self.prev_target_cx = target.x + target.width / 2.0;
+        self.prev_target_cy = target.y + target.height / 2.0;
+    }
+
+    /// Start moving the cursor from where it is to `target`, as of `now`
+    fn start_motion(&mut self, target: &CursorTarget, now: std::time::Instant) {
+        self.animating = true;
+        self.last_anim_time = now;
+        // Capture start position for easing/linear/spring styles
+        self.start_x = self.current_x;
+        self.start_y = self.current_y;
+        self.start_w = self.current_w;
+        self.start_h = self.current_h;
+        self.anim_start_time = now;
+        // For spring: reset velocities
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.velocity_w = 0.0;
+        self.velocity_h = 0.0;
+
+        // Set up 4-corner springs for trail effect (spring style only)
+        if self.anim_style == CursorAnimStyle::CriticallyDampedSpring {
+            let new_corners = CursorState::target_corners(target);
+            let new_cx = target.x + target.width / 2.0;
+            let new_cy = target.y + target.height / 2.0;
+            let old_cx = self.prev_target_cx;
+            let old_cy = self.prev_target_cy;
+
+            // Travel direction (normalized)
+            let dx = new_cx - old_cx;
+            let dy = new_cy - old_cy;
+            let len = (dx * dx + dy * dy).sqrt();
+            let (dir_x, dir_y) = if len > 0.001 {
+                (dx / len, dy / len)
+            } else {
+                (1.0, 0.0)
+            };
+
+            // Corner direction vectors from center: TL(-1,-1), TR(1,-1), BR(1,1), BL(-1,1)
+            let corner_dirs: [(f32, f32); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+            // Compute dot products and rank corners
+            let mut dots: [(f32, usize); 4] = corner_dirs.iter().enumerate()
+                .map(|(i, (cx, cy))| (cx * dir_x + cy * dir_y, i))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            dots.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            // dots[0] = most trailing (lowest dot), dots[3] = most leading (highest dot)
+
+            let base_dur = self.anim_duration; // seconds
+            for (rank, &(_dot, corner_idx)) in dots.iter().enumerate() {
+                let factor = 1.0 - self.trail_size * (rank as f32 / 3.0);
+                let duration_i = (base_dur * factor).max(0.01);
+                let omega_i = 4.0 / duration_i;
+
+                self.corner_springs[corner_idx].target_x = new_corners[corner_idx].0;
+                self.corner_springs[corner_idx].target_y = new_corners[corner_idx].1;
+                self.corner_springs[corner_idx].omega = omega_i;
+                // Don't reset velocity — preserve momentum from in-flight animation
+            }
+
+            self.prev_target_cx = new_cx;
+            self.prev_target_cy = new_cy;
+        }
+    }
+
+    /// Tick cursor animation to time `now`, returns true if position
+    /// changed (needs redraw)
+    fn tick_animation(&mut self, now: std::time::Instant) -> bool {
         if !self.anim_enabled || !self.animating {
             return false;
         }
@@ -628,8 +820,7 @@ impl CursorState {
             None => return false,
         };
 
-        let now = std::time::Instant::now();
-        let dt = now.duration_since(self.last_anim_time).as_secs_f32();
+        let dt = now.saturating_duration_since(self.last_anim_time).as_secs_f32();
         self.last_anim_time = now;
 
         match self.anim_style {
@@ -697,14 +888,15 @@ impl CursorState {
                 }
             }
             style => {
-                let elapsed = now.duration_since(self.anim_start_time).as_secs_f32();
+                let elapsed = now.saturating_duration_since(self.anim_start_time).as_secs_f32();
                 let raw_t = (elapsed / self.anim_duration).min(1.0);
-                let t = match style {
-                    CursorAnimStyle::EaseOutQuad => ease_out_quad(raw_t),
-                    CursorAnimStyle::EaseOutCubic => ease_out_cubic(raw_t),
-                    CursorAnimStyle::EaseOutExpo => ease_out_expo(raw_t),
-                    CursorAnimStyle::EaseInOutCubic => ease_in_out_cubic(raw_t),
-                    CursorAnimStyle::Linear => ease_linear(raw_t),
+                let t = match (&self.anim_curve, style) {
+                    (Some(curve), _) => curve.sample(raw_t),
+                    (None, CursorAnimStyle::EaseOutQuad) => ease_out_quad(raw_t),
+                    (None, CursorAnimStyle::EaseOutCubic) => ease_out_cubic(raw_t),
+                    (None, CursorAnimStyle::EaseOutExpo) => ease_out_expo(raw_t),
+                    (None, CursorAnimStyle::EaseInOutCubic) => ease_in_out_cubic(raw_t),
+                    (None, CursorAnimStyle::Linear) => ease_linear(raw_t),
                     _ => raw_t,
                 };
                 self.current_x = self.start_x + (target.x - self.start_x) * t;
@@ -730,13 +922,16 @@ impl CursorState {
     }
 
     /// Tick cursor size transition, returns true if size changed (needs redraw).
-    fn tick_size_animation(&mut self) -> bool {
+    fn tick_size_animation(&mut self, now: std::time::Instant) -> bool {
         if !self.size_transition_enabled || !self.size_animating {
             return false;
         }
-        let elapsed = self.size_anim_start.elapsed().as_secs_f32();
+        let elapsed = now.saturating_duration_since(self.size_anim_start).as_secs_f32();
         let raw_t = (elapsed / self.size_transition_duration).min(1.0);
-        let t = raw_t * (2.0 - raw_t); // ease-out-quad
+        let t = match self.size_curve {
+            Some(ref curve) => curve.sample(raw_t),
+            None => raw_t * (2.0 - raw_t), // ease-out-quad
+        };
         self.current_w = self.size_start_w
             + (self.size_target_w - self.size_start_w) * t;
         self.current_h = self.size_start_h
@@ -749,10 +944,21 @@ impl CursorState {
         true
     }
 
-    /// Reset blink to visible (e.g. when new frame arrives)
-    fn reset_blink(&mut self) {
+    /// Reset blink to visible at `now` (e.g. when new frame arrives)
+    fn reset_blink(&mut self, now: std::time::Instant) {
         self.blink_on = true;
-        self.last_blink_toggle = std::time::Instant::now();
+        self.last_blink_toggle = now;
+    }
+
+    /// Toggle the blink when its interval has passed at `now`, returns
+    /// true if it did
+    fn tick_blink(&mut self, now: std::time::Instant) -> bool {
+        if now.saturating_duration_since(self.last_blink_toggle) < self.blink_interval {
+            return false;
+        }
+        self.blink_on = !self.blink_on;
+        self.last_blink_toggle = now;
+        true
     }
 }
 
@@ -768,8 +974,14 @@ struct TransitionState {
     crossfade_easing: crate::core::scroll_animation::ScrollEasing,
     scroll_enabled: bool,
     scroll_duration: std::time::Duration,
+    text_scale_enabled: bool,
+    text_scale_duration: std::time::Duration,
     scroll_effect: crate::core::scroll_animation::ScrollEffect,
     scroll_easing: crate::core::scroll_animation::ScrollEasing,
+    scroll_motion_blur: bool,
+    scroll_motion_blur_strength: f32,
+    // Distance announced for the selected window's next scroll, and when
+    scroll_hint: Option<(f32, std::time::Instant)>,
 
     // Double-buffer offscreen textures
     offscreen_a: Option<(wgpu::Texture, wgpu::TextureView, wgpu::BindGroup)>,
@@ -782,6 +994,28 @@ struct TransitionState {
 
     // Per-window metadata from previous frame (for transition detection)
     prev_window_infos: HashMap<i64, crate::core::frame_glyphs::WindowInfo>,
+
+    // Explicitly requested crossfade, started on the next frame
+    requested: Option<RequestedTransition>,
+
+    // Snapshots of finished transitions, destroyed at idle time
+    finished_snapshots: Vec<wgpu::Texture>,
+}
+
+/// A crossfade requested through `RenderCommand::StartTransition`.
+struct RequestedTransition {
+    window_id: Option<i64>,
+    effect: crate::core::scroll_animation::ScrollEffect,
+    duration: std::time::Duration,
+    notify: u32,
+}
+
+/// Part of an animation the host asked to hear the end of
+#[derive(Debug, Clone, Copy)]
+enum WatchedAnimation {
+    /// Crossfade of a window, told from a later one by its start time
+    Crossfade(i64, std::time::Instant),
+    Zoom,
 }
 
 impl Default for TransitionState {
@@ -795,12 +1029,19 @@ impl Default for TransitionState {
             scroll_duration: std::time::Duration::from_millis(150),
             scroll_effect: crate::core::scroll_animation::ScrollEffect::default(),
             scroll_easing: crate::core::scroll_animation::ScrollEasing::default(),
+            scroll_motion_blur: false,
+            scroll_motion_blur_strength: 1.0,
+            scroll_hint: None,
+            text_scale_enabled: true,
+            text_scale_duration: std::time::Duration::from_millis(200),
             offscreen_a: None,
             offscreen_b: None,
             current_is_a: true,
             crossfades: HashMap::new(),
             scroll_slides: HashMap::new(),
             prev_window_infos: HashMap::new(),
+            requested: None,
+            finished_snapshots: Vec::new(),
         }
     }
 }
@@ -810,6 +1051,47 @@ impl TransitionState {
     fn has_active(&self) -> bool {
         !self.crossfades.is_empty() || !self.scroll_slides.is_empty()
     }
+
+    /// Bytes held by the offscreen buffers and snapshots, and the part of
+    /// that held by snapshots of active transitions
+    fn memory_usage(&self) -> (usize, usize) {
+        let bytes = |t: &wgpu::Texture| t.width() as usize * t.height() as usize * 4;
+        let snapshots: usize = self.crossfades.values().map(|t| bytes(&t.old_texture))
+            .chain(self.scroll_slides.values().map(|t| bytes(&t.old_texture)))
+            .sum();
+        let buffers: usize = [&self.offscreen_a, &self.offscreen_b]
+            .iter()
+            .filter_map(|o| o.as_ref())
+            .map(|(tex, _, _)| bytes(tex))
+            .sum();
+        (buffers + snapshots, snapshots)
+    }
+
+    /// Finish the oldest transitions early until `bytes` of snapshots
+    /// have been freed.  Returns the bytes freed.
+    fn drop_snapshots(&mut self, bytes: usize) -> usize {
+        let size = |t: &wgpu::Texture| t.width() as usize * t.height() as usize * 4;
+        let mut oldest: Vec<(std::time::Instant, bool, i64, usize)> = self
+            .crossfades
+            .iter()
+            .map(|(&id, t)| (t.started, false, id, size(&t.old_texture)))
+            .chain(self.scroll_slides.iter().map(|(&id, t)| (t.started, true, id, size(&t.old_texture))))
+            .collect();
+        oldest.sort_by_key(|&(started, ..)| started);
+        let mut freed = 0;
+        for (_, is_scroll, id, size) in oldest {
+            if freed >= bytes {
+                break;
+            }
+            if is_scroll {
+                self.scroll_slides.remove(&id);
+            } else {
+                self.crossfades.remove(&id);
+            }
+            freed += size;
+        }
+        freed
+    }
 }
 
 /// FPS counter and frame time tracking state.
@@ -877,9 +1159,15 @@ struct RenderApp {
     device: Option<Arc<wgpu::Device>>,
     queue: Option<Arc<wgpu::Queue>>,
     glyph_atlas: Option<WgpuGlyphAtlas>,
+    // Set when the GPU device is lost and everything above must be rebuilt
+    device_loss: DeviceLossWatch,
 
     // Face cache built from frame data
     faces: HashMap<u32, Face>,
+    // Faces from the frames' face deltas, mirroring the FFI side
+    face_cache: FaceCache,
+    // Whether Emacs was asked for every face after a delta did not fit
+    faces_requested: bool,
 
     // Display scale factor (physical pixels / logical pixels)
     scale_factor: f64,
@@ -895,12 +1183,59 @@ struct RenderApp {
     // Shared image dimensions (written here, read from main thread)
     image_dimensions: SharedImageDimensions,
 
+    // Caret offsets inside composed glyphs (written by the glyph atlas)
+    cluster_offsets: SharedClusterOffsets,
+
+    // Display options from the config file and runtime, and the file watch
+    display_options: SharedOptionRegistry,
+    config_watcher: ConfigWatcher,
+    // Newest `error_report` id announced to Emacs
+    announced_error: u32,
+    /// Told which phase of the loop is running, to report stalls
+    watchdog: crate::core::watchdog::Watchdog,
+    /// Picks the cache maintenance to run between frames
+    idle: IdleScheduler,
+    /// Whether the window has keyboard focus
+    window_focused: bool,
+    /// Update rates of terminals, videos and WebKit views
+    throttle: crate::core::content_throttle::ContentThrottle,
+    /// Features dropped while frames run late
+    quality: crate::core::quality::QualityController,
+    /// Guesses of where keys will put the cursor before Emacs redraws
+    cursor_predictor: crate::core::cursor_prediction::CursorPredictor,
+    /// The frame as Emacs drew it while a predicted scroll is drawn
+    unscrolled_frame: Option<FrameGlyphBuffer>,
+    /// Colors the display uses besides those of faces
+    theme: crate::core::display_theme::DisplayTheme,
+    /// Interactive image being panned, with the pointer position last seen
+    image_drag: Option<(u32, (f32, f32))>,
+    /// Interactive image last clicked and when, to notice double clicks
+    image_click: Option<(u32, std::time::Instant)>,
+    /// When a frame was last drawn to advance the ambient layer
+    ambient_last_frame: std::time::Instant,
+    /// GPU memory budget shared by the texture caches, in bytes
+    gpu_memory_budget: usize,
+    // Present with vsync (FIFO) rather than immediately
+    vsync: bool,
+    // Store mask glyphs as signed distance fields
+    glyph_sdf: bool,
+    // Whole-frame zoom
+    zoom: crate::core::frame_zoom::FrameZoom,
+    // Texture frames are drawn into while zoomed or color filtered
+    post_target: Option<(wgpu::Texture, wgpu::TextureView, wgpu::BindGroup)>,
+
+    // Fallback glyph fitting received before the glyph atlas exists
+    pending_fallback_metrics: Vec<(Option<u32>, Option<crate::core::face::FallbackMetrics>)>,
+
     // Frame dirty flag: set when new frame data arrives, cleared after render
     frame_dirty: bool,
 
     // Cursor state (blink, animation, size transition)
     cursor: CursorState,
 
+    // Time source of the cursor, transitions, zoom and renderer effects
+    clock: SharedClock,
+
     // All visual effect configurations
     effects: crate::effect_config::EffectsConfig,
 
@@ -917,15 +1252,62 @@ struct RenderApp {
     #[cfg(feature = "wpe-webkit")]
     webkit_import_policy: WebKitImportPolicy,
 
+    // Named WebKit data profiles (network sessions with their own storage)
+    #[cfg(feature = "wpe-webkit")]
+    webkit_profiles: crate::backend::wpe::WebKitProfiles,
+
     // Floating WebKit overlays (position/size from C side, rendered on render thread)
     #[cfg(feature = "wpe-webkit")]
     floating_webkits: Vec<crate::core::scene::FloatingWebKit>,
 
+    /// Floating image overlays
+    floating_images: Vec<crate::core::scene::FloatingImage>,
+
+    /// Shader effects drawn over regions, windows and floating images
+    shader_effects: Vec<crate::core::shader_effect::AppliedShaderEffect>,
+    /// Sources of registered shader effects, kept to rebuild their
+    /// pipelines with a new device
+    shader_effect_sources: HashMap<String, String>,
+
+    /// Running property animations of floating elements
+    floating_animations: crate::core::animation::FloatingAnimations,
+    /// Transitions and zooms that report their end to the host
+    animation_watch: crate::core::animation::CompletionWatch<WatchedAnimation>,
+    /// Resources created from Emacs, as a saved session lists them
+    session: crate::core::session::Session,
+    /// Replay being recorded, and when it started
+    #[cfg(feature = "remote")]
+    replay: Option<(crate::remote::replay::ReplayWriter<std::io::BufWriter<std::fs::File>>, std::time::Instant)>,
+    /// Whether the next frame recorded has to carry every face: the
+    /// frames of a replay started mid-session change faces it never had
+    #[cfg(feature = "remote")]
+    replay_needs_faces: bool,
+    /// Picture-in-picture video player
+    pip: Option<crate::core::pip::PipPlayer>,
+    /// Control bar over the inline video under the pointer
+    video_controls: crate::core::video_controls::VideoControls,
+    /// Videos whose audio level changes are sent to the host
+    audio_event_videos: HashSet<u32>,
+
     // Terminal manager (neo-term)
     #[cfg(feature = "neo-term")]
     terminal_manager: crate::terminal::TerminalManager,
     #[cfg(feature = "neo-term")]
     shared_terminals: crate::terminal::SharedTerminals,
+    /// tmux control-mode clients, whose panes are terminals
+    #[cfg(feature = "neo-term")]
+    tmux_clients: HashMap<u32, crate::terminal::tmux::TmuxClient>,
+    /// SSH terminals still connecting
+    #[cfg(feature = "neo-term-ssh")]
+    pending_ssh: Vec<PendingSshTerminal>,
+
+    // PDF documents (pages are uploaded into the renderer's image cache)
+    #[cfg(feature = "pdf")]
+    pdf_cache: crate::backend::wgpu::PdfCache,
+
+    // Waveforms and thumbnails of media files (uploaded as images)
+    #[cfg(feature = "video")]
+    media_preview: crate::backend::wgpu::MediaPreviewCache,
 
     // Active popup menu (shown by x-popup-menu)
     popup_menu: Option<PopupMenuState>,
@@ -933,6 +1315,16 @@ struct RenderApp {
     // Active tooltip overlay
     tooltip: Option<TooltipState>,
 
+    // Active character grid overlay
+    char_grid: Option<CharGridState>,
+
+    /// Named keyframe curves overriding built-in animation easing
+    animation_curves: crate::core::animation::AnimationCurves,
+
+    /// Screen reader bridge for the main window
+    #[cfg(feature = "accessibility")]
+    accessibility: Option<crate::backend::wgpu::AccessibilityBridge>,
+
     // Visual bell state (flash overlay)
     visual_bell_start: Option<std::time::Instant>,
 
@@ -948,6 +1340,10 @@ struct RenderApp {
     chrome: WindowChrome,
     // FPS counter state
     fps: FpsCounter,
+    /// Predicted presentation times for animation sampling
+    frame_clock: FrameClock,
+    /// Statistics of the last frame, published for the Emacs side
+    frame_stats: FrameStats,
     /// Extra line spacing in pixels (added between rows)
     extra_line_spacing: f32,
     /// Extra letter spacing in pixels (added between characters)
@@ -967,6 +1363,27 @@ struct RenderApp {
     monitors_populated: bool,
 }
 
+/// State for a character grid overlay (emoji picker etc.)
+pub(crate) struct CharGridState {
+    /// Grid geometry and scroll position
+    pub(crate) layout: crate::text::CharGridLayout,
+    /// Cell contents (each may be a multi-codepoint sequence)
+    pub(crate) items: Vec<String>,
+    /// Selected item index
+    pub(crate) selected: Option<usize>,
+    /// Colors (sRGB)
+    pub(crate) fg: (f32, f32, f32),
+    pub(crate) bg: (f32, f32, f32),
+    pub(crate) selected_bg: (f32, f32, f32),
+}
+
+impl CharGridState {
+    /// Apply a selection/scroll update, keeping the selection visible
+    pub(crate) fn update(&mut self, selected: i32, scroll_row: u32) {
+        self.selected = self.layout.apply(selected, scroll_row);
+    }
+}
+
 /// State for a tooltip displayed as GPU overlay
 pub(crate) struct TooltipState {
     /// Position (logical pixels, near mouse cursor)
@@ -1016,11 +1433,16 @@ impl RenderApp {
         title: String,
         image_dimensions: SharedImageDimensions,
         shared_monitors: SharedMonitorInfo,
+        cluster_offsets: SharedClusterOffsets,
+        display_options: SharedOptionRegistry,
         #[cfg(feature = "neo-term")]
         shared_terminals: crate::terminal::SharedTerminals,
+        #[cfg(feature = "pdf")]
+        shared_pdfs: crate::backend::wgpu::SharedPdfDocuments,
     ) -> Self {
         #[cfg(feature = "wpe-webkit")]
         let webkit_import_policy = WebKitImportPolicy::from_env();
+        let clock = crate::core::clock::system_clock();
 
         Self {
             comms,
@@ -1036,13 +1458,38 @@ impl RenderApp {
             device: None,
             queue: None,
             glyph_atlas: None,
+            device_loss: DeviceLossWatch::default(),
             faces: HashMap::new(),
+            face_cache: FaceCache::new(),
+            faces_requested: false,
             modifiers: 0,
             mouse_pos: (0.0, 0.0),
             mouse_hidden_for_typing: false,
             image_dimensions,
+            cluster_offsets,
+            display_options,
+            config_watcher: ConfigWatcher::new(DisplayConfig::default_path()),
+            announced_error: 0,
+            watchdog: crate::core::watchdog::Watchdog::default(),
+            idle: IdleScheduler::new(),
+            window_focused: true,
+            throttle: Default::default(),
+            quality: Default::default(),
+            cursor_predictor: Default::default(),
+            unscrolled_frame: None,
+            theme: Default::default(),
+            image_drag: None,
+            image_click: None,
+            ambient_last_frame: std::time::Instant::now(),
+            gpu_memory_budget: crate::backend::wgpu::gpu_budget::DEFAULT_BUDGET_MB * 1024 * 1024,
+            vsync: true,
+            glyph_sdf: false,
+            zoom: Default::default(),
+            post_target: None,
+            pending_fallback_metrics: Vec::new(),
             frame_dirty: false,
-            cursor: CursorState::default(),
+            cursor: CursorState::starting_at(clock.now()),
+            clock,
             effects: crate::effect_config::EffectsConfig::default(),
             transitions: TransitionState::default(),
             #[cfg(feature = "wpe-webkit")]
@@ -1052,13 +1499,40 @@ impl RenderApp {
             #[cfg(feature = "wpe-webkit")]
             webkit_import_policy,
             #[cfg(feature = "wpe-webkit")]
+            webkit_profiles: crate::backend::wpe::WebKitProfiles::new(),
+            #[cfg(feature = "wpe-webkit")]
             floating_webkits: Vec::new(),
+            floating_images: Vec::new(),
+            shader_effects: Vec::new(),
+            shader_effect_sources: HashMap::new(),
+            floating_animations: crate::core::animation::FloatingAnimations::new(),
+            animation_watch: Default::default(),
+            session: Default::default(),
+            #[cfg(feature = "remote")]
+            replay: None,
+            #[cfg(feature = "remote")]
+            replay_needs_faces: false,
+            pip: None,
+            video_controls: Default::default(),
+            audio_event_videos: HashSet::new(),
             #[cfg(feature = "neo-term")]
             terminal_manager: crate::terminal::TerminalManager::new(),
             #[cfg(feature = "neo-term")]
             shared_terminals,
+            #[cfg(feature = "neo-term")]
+            tmux_clients: HashMap::new(),
+            #[cfg(feature = "neo-term-ssh")]
+            pending_ssh: Vec::new(),
+            #[cfg(feature = "pdf")]
+            pdf_cache: crate::backend::wgpu::PdfCache::new(shared_pdfs),
+            #[cfg(feature = "video")]
+            media_preview: crate::backend::wgpu::MediaPreviewCache::new(),
             popup_menu: None,
             tooltip: None,
+            char_grid: None,
+            animation_curves: crate::core::animation::AnimationCurves::new(),
+            #[cfg(feature = "accessibility")]
+            accessibility: None,
             visual_bell_start: None,
             ime_enabled: false,
             ime_preedit_active: false,
@@ -1066,6 +1540,8 @@ impl RenderApp {
             scroll_indicators_enabled: true,
             chrome: WindowChrome::default(),
             fps: FpsCounter::default(),
+            frame_clock: FrameClock::default(),
+            frame_stats: FrameStats::default(),
             extra_line_spacing: 0.0,
             extra_letter_spacing: 0.0,
             prev_selected_window_id: 0,
@@ -1139,6 +1615,7 @@ impl RenderApp {
             }
         };
 
+        let device_loss = DeviceLossWatch::new(&device);
         let device = Arc::new(device);
         let queue = Arc::new(queue);
 
@@ -1157,12 +1634,14 @@ impl RenderApp {
         } else {
             caps.alpha_modes[0]
         };
+        // Copies out of the surface feed the backdrop blur where supported
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (caps.usages & wgpu::TextureUsages::COPY_SRC),
             format,
             width: self.width,
             height: self.height,
-            present_mode: wgpu::PresentMode::Fifo, // VSync
+            present_mode: Self::present_mode(self.vsync),
             alpha_mode,
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
@@ -1170,7 +1649,7 @@ impl RenderApp {
         surface.configure(&device, &config);
 
         // Create renderer with existing device and surface format
-        let renderer = WgpuRenderer::with_device(
+        let mut renderer = WgpuRenderer::with_device(
             device.clone(), queue.clone(),
             self.width, self.height,
             format,
@@ -1178,7 +1657,12 @@ impl RenderApp {
         );
 
         // Create glyph atlas with scale factor for crisp HiDPI text
-        let glyph_atlas = WgpuGlyphAtlas::new_with_scale(&device, self.scale_factor as f32);
+        let mut glyph_atlas = WgpuGlyphAtlas::new_with_scale(&device, self.scale_factor as f32);
+        glyph_atlas.set_cluster_offsets(Arc::clone(&self.cluster_offsets));
+        glyph_atlas.set_sdf(self.glyph_sdf);
+        for (face_id, metrics) in self.pending_fallback_metrics.drain(..) {
+            glyph_atlas.set_fallback_metrics(face_id, metrics);
+        }
 
         log::info!(
             "wgpu initialized: {}x{}, format: {:?}",
@@ -1191,12 +1675,15 @@ impl RenderApp {
         self.surface_config = Some(config);
         self.device = Some(device.clone());
         self.queue = Some(queue);
+        renderer.theme = self.theme.clone();
+        renderer.set_clock(self.clock.clone());
         self.renderer = Some(renderer);
         self.glyph_atlas = Some(glyph_atlas);
+        self.device_loss = device_loss;
 
-        // Initialize WPE backend for WebKit
+        // Initialize WPE backend for WebKit (kept across device recovery)
         #[cfg(feature = "wpe-webkit")]
-        {
+        if self.wpe_backend.is_none() {
             use crate::backend::wgpu::get_render_node_from_adapter_info;
 
             // Get DRM render node from adapter to ensure WebKit uses the same GPU
@@ -1212,7 +1699,7 @@ impl RenderApp {
                     self.wpe_backend = Some(backend);
                 }
                 Err(e) => {
-                    log::warn!("Failed to initialize WPE backend: {:?}", e);
+                    error_report::warning(ErrorKind::WebKit, None, format!("cannot initialize WPE backend: {:?}", e));
                 }
             }
         }
@@ -1222,6 +1709,307 @@ impl RenderApp {
         log::info!("Video cache initialized");
     }
 
+    /// Surface present mode for the `vsync` option
+    fn present_mode(vsync: bool) -> wgpu::PresentMode {
+        if vsync {
+            wgpu::PresentMode::Fifo
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        }
+    }
+
+    /// Apply display option `name` (see `core::option_registry`)
+    fn apply_display_option(&mut self, name: &str, value: &OptionValue) {
+        use crate::core::scroll_animation::{ScrollEasing, ScrollEffect};
+        use std::time::Duration;
+        match (name, value) {
+            ("animation", &OptionValue::Bool(on)) => {
+                self.cursor.anim_enabled = on;
+                self.transitions.crossfade_enabled = on;
+                self.transitions.scroll_enabled = on;
+                self.transitions.text_scale_enabled = on;
+            }
+            ("cursor-animation", &OptionValue::Bool(on)) => self.cursor.anim_enabled = on,
+            ("cursor-prediction", &OptionValue::Bool(on)) => self.cursor_predictor.set_enabled(on),
+            ("cursor-animation-speed", &OptionValue::Float(v)) => self.cursor.anim_speed = v as f32,
+            ("buffer-transition", &OptionValue::Bool(on)) => self.transitions.crossfade_enabled = on,
+            ("buffer-transition-duration", &OptionValue::Integer(ms)) => {
+                self.transitions.crossfade_duration = Duration::from_millis(ms as u64);
+            }
+            ("scroll-animation", &OptionValue::Bool(on)) => self.transitions.scroll_enabled = on,
+            ("text-scale-animation", &OptionValue::Bool(on)) => self.transitions.text_scale_enabled = on,
+            ("text-scale-animation-duration", &OptionValue::Integer(ms)) => {
+                self.transitions.text_scale_duration = Duration::from_millis(ms as u64);
+            }
+            ("scroll-animation-duration", &OptionValue::Integer(ms)) => {
+                self.transitions.scroll_duration = Duration::from_millis(ms as u64);
+            }
+            ("scroll-effect", OptionValue::Choice(effect)) => {
+                self.transitions.scroll_effect = ScrollEffect::from_str(effect);
+            }
+            ("scroll-easing", OptionValue::Choice(easing)) => {
+                self.transitions.scroll_easing = ScrollEasing::from_str(easing);
+            }
+            ("scroll-motion-blur", &OptionValue::Bool(on)) => self.transitions.scroll_motion_blur = on,
+            ("scroll-motion-blur-strength", &OptionValue::Float(v)) => {
+                self.transitions.scroll_motion_blur_strength = v as f32;
+            }
+            ("text-gamma" | "text-contrast", &OptionValue::Float(v)) => {
+                if name == "text-gamma" {
+                    self.effects.text_gamma.gamma = v as f32;
+                } else {
+                    self.effects.text_gamma.contrast = v as f32;
+                }
+                self.sync_renderer_effects();
+            }
+            ("color-filter", OptionValue::Choice(filter)) => {
+                self.effects.color_filter.filter = crate::effect_config::ColorFilter::from_str(filter);
+                self.sync_renderer_effects();
+                self.frame_dirty = true;
+            }
+            ("ambient-effect" | "ambient-color" | "ambient-opacity" | "ambient-speed"
+             | "ambient-density" | "ambient-fps", value) => {
+                let ambient = &mut self.effects.ambient;
+                match value {
+                    OptionValue::Choice(effect) => {
+                        ambient.effect = crate::effect_config::AmbientEffect::from_str(effect);
+                    }
+                    &OptionValue::Color(c) => ambient.color = (c.r, c.g, c.b),
+                    &OptionValue::Float(v) if name == "ambient-opacity" => ambient.opacity = v as f32,
+                    &OptionValue::Float(v) if name == "ambient-speed" => ambient.speed = v as f32,
+                    &OptionValue::Float(v) => ambient.density = v as f32,
+                    &OptionValue::Integer(fps) => ambient.fps = fps as u32,
+                    _ => return,
+                }
+                self.sync_renderer_effects();
+                self.frame_dirty = true;
+            }
+            ("color-filter-strength", &OptionValue::Float(v)) => {
+                self.effects.color_filter.strength = v as f32;
+                self.sync_renderer_effects();
+                self.frame_dirty = true;
+            }
+            ("smart-invert", &OptionValue::Bool(on)) => {
+                self.effects.smart_invert.enabled = on;
+                self.sync_renderer_effects();
+                self.frame_dirty = true;
+            }
+            ("antialias-width", &OptionValue::Float(v)) => {
+                self.effects.antialias.width = v as f32;
+                self.sync_renderer_effects();
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-foreground", &OptionValue::Color(color)) => {
+                let mut theme = self.terminal_manager.theme().clone();
+                theme.foreground = color;
+                self.terminal_manager.set_theme(theme);
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-background", &OptionValue::Color(color)) => {
+                let mut theme = self.terminal_manager.theme().clone();
+                theme.background = color;
+                self.terminal_manager.set_theme(theme);
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-palette", OptionValue::Colors(colors)) => {
+                let mut theme = self.terminal_manager.theme().clone();
+                for (slot, color) in theme.palette.iter_mut().zip(colors) {
+                    *slot = *color;
+                }
+                self.terminal_manager.set_theme(theme);
+            }
+            ("glyph-sdf", &OptionValue::Bool(on)) => {
+                self.glyph_sdf = on;
+                if let Some(atlas) = self.glyph_atlas.as_mut() {
+                    atlas.set_sdf(on);
+                    self.frame_dirty = true;
+                }
+            }
+            ("input-latency", &OptionValue::Bool(on)) => crate::core::input_latency::set_enabled(on),
+            ("quality", OptionValue::Choice(level)) => {
+                let pinned = crate::core::quality::QualityLevel::from_str(level);
+                if let Some(level) = self.quality.set_pinned(pinned) {
+                    self.apply_quality(level);
+                }
+            }
+            ("debug-overlay", OptionValue::Choice(mode)) => {
+                self.effects.debug_overlay.mode = crate::core::scene_dump::DebugOverlay::from_str(mode);
+                self.sync_renderer_effects();
+                self.frame_dirty = true;
+            }
+            ("shader-effects", &OptionValue::Bool(on)) => {
+                self.effects.shader_effects.enabled = on;
+                self.frame_dirty = true;
+            }
+            ("shader-effect-strength", &OptionValue::Float(v)) => {
+                self.effects.shader_effects.strength = v as f32;
+                self.frame_dirty = true;
+            }
+            ("vsync", &OptionValue::Bool(on)) => {
+                self.vsync = on;
+                if let (Some(surface), Some(config), Some(device)) =
+                    (&self.surface, &mut self.surface_config, &self.device)
+                {
+                    config.present_mode = Self::present_mode(self.vsync);
+                    surface.configure(device, config);
+                }
+            }
+            #[cfg(feature = "video")]
+            ("video-frame-buffers", &OptionValue::Integer(count)) => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.set_video_frame_buffers(count as u32);
+                }
+            }
+            #[cfg(feature = "video")]
+            ("video-max-active", &OptionValue::Integer(count)) => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.set_video_max_active(count as u32);
+                }
+            }
+            ("video-controls", &OptionValue::Bool(on)) => {
+                self.video_controls.enabled = on;
+                if !on {
+                    self.video_controls.hovered = None;
+                }
+                self.frame_dirty = true;
+            }
+            ("placeholder-image" | "placeholder-video" | "placeholder-webkit", OptionValue::Choice(style)) => {
+                use crate::core::placeholder::{PlaceholderContent, PlaceholderStyle};
+                let content = match name {
+                    "placeholder-image" => PlaceholderContent::Image,
+                    "placeholder-video" => PlaceholderContent::Video,
+                    _ => PlaceholderContent::WebKit,
+                };
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.placeholders.set(content, PlaceholderStyle::from_str(style));
+                }
+                self.frame_dirty = true;
+            }
+            ("image-cache-mb", &OptionValue::Integer(mb)) => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.set_image_cache_limit(mb as usize * 1024 * 1024);
+                }
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-idle-scrollback", &OptionValue::Integer(lines)) => {
+                self.terminal_manager.set_idle_scrollback(lines as usize);
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-visual-bell", OptionValue::Choice(style)) => {
+                self.terminal_manager.bell.visual = crate::terminal::bell::VisualBell::from_str(style);
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-bell-duration-ms", &OptionValue::Integer(ms)) => {
+                self.terminal_manager.bell.duration = std::time::Duration::from_millis(ms as u64);
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-bell-interval-ms", &OptionValue::Integer(ms)) => {
+                self.terminal_manager.bell.min_interval = std::time::Duration::from_millis(ms as u64);
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-bell-urgency", &OptionValue::Bool(on)) => {
+                self.terminal_manager.bell.urgency = on;
+            }
+            #[cfg(feature = "neo-term")]
+            ("terminal-output-kb-per-frame", &OptionValue::Integer(kb)) => {
+                self.terminal_manager.output_limiter().set_bytes_per_frame(kb as usize * 1024);
+            }
+            ("gpu-memory-mb", &OptionValue::Integer(mb)) => {
+                self.gpu_memory_budget = mb as usize * 1024 * 1024;
+            }
+            ("stall-threshold-ms", &OptionValue::Integer(ms)) => {
+                self.watchdog.set_threshold(std::time::Duration::from_millis(ms as u64));
+            }
+            // Only read when the event loop is created
+            ("backend", _) => {}
+            _ => log::debug!("Display option {:?} = {} not applied", name, value),
+        }
+        self.frame_dirty = true;
+    }
+
+    /// Apply every display option set in the config file or at runtime
+    fn apply_all_display_options(&mut self) {
+        let options = match self.display_options.lock() {
+            Ok(options) => options.explicit_values(),
+            Err(_) => return,
+        };
+        for (name, value) in options {
+            self.apply_display_option(name, &value);
+        }
+    }
+
+    /// Read the display config file again.  The registry queues a
+    /// `SetDisplayOption` command for every option whose value changed.
+    fn reload_display_config(&mut self) {
+        let Some(path) = self.config_watcher.path() else {
+            return;
+        };
+        let config = match DisplayConfig::load(path) {
+            Ok(config) => config,
+            Err(e) => {
+                error_report::warning(ErrorKind::Config, None, format!("not reloading display config: {}", e));
+                return;
+            }
+        };
+        log::info!("Reloading display config from {}", path.display());
+        if let Ok(mut options) = self.display_options.lock() {
+            options.set_file(&config);
+        }
+    }
+
+    /// Rebuild the device, surface, renderer and glyph atlas after the GPU
+    /// device was lost, carrying over everything with a CPU-side source
+    fn recover_lost_device(&mut self) {
+        let Some(window) = self.window.clone() else {
+            return;
+        };
+        log::warn!("GPU device lost, recreating device and surface");
+
+        let lost_renderer = self.renderer.take();
+        let lost_atlas = self.glyph_atlas.take();
+        self.surface = None;
+        self.surface_config = None;
+        self.queue = None;
+        self.device = None;
+        // Transitions hold textures of the lost device
+        self.post_target = None;
+        self.transitions.offscreen_a = None;
+        self.transitions.offscreen_b = None;
+        self.transitions.crossfades.clear();
+        self.transitions.scroll_slides.clear();
+
+        self.init_wgpu(window);
+
+        let (Some(renderer), Some(atlas)) = (&mut self.renderer, &mut self.glyph_atlas) else {
+            log::error!("Could not recreate the GPU device; rendering is suspended");
+            return;
+        };
+        if let Some(lost) = lost_renderer {
+            renderer.adopt_from(lost);
+        }
+        if let Some(lost) = lost_atlas {
+            atlas.adopt_settings(&lost);
+        }
+        self.frame_dirty = true;
+    }
+
+    /// The part of the frame magnified over the window, or None when the
+    /// frame is not zoomed.  Without a fixed focus the zoom follows the
+    /// cursor.
+    fn zoom_source_rect(&self) -> Option<crate::core::types::Rect> {
+        let sf = self.scale_factor as f32;
+        let focus = self.zoom.focus.unwrap_or((
+            self.cursor.current_x + self.cursor.current_w / 2.0,
+            self.cursor.current_y + self.cursor.current_h / 2.0,
+        ));
+        self.zoom.source_rect(
+            self.clock.now(),
+            self.width as f32 / sf,
+            self.height as f32 / sf,
+            focus,
+        )
+    }
+
     /// Handle surface resize
     fn handle_resize(&mut self, width: u32, height: u32) {
         if width == 0 || height == 0 {
@@ -1246,6 +2034,7 @@ impl RenderApp {
         }
 
         // Invalidate offscreen textures (they reference old size)
+        self.post_target = None;
         self.transitions.offscreen_a = None;
         self.transitions.offscreen_b = None;
         // Cancel active transitions (they reference old-sized textures)
@@ -1255,7 +2044,7 @@ impl RenderApp {
         // Trigger resize padding transition
         if self.effects.resize_padding.enabled {
             if let Some(renderer) = self.renderer.as_mut() {
-                renderer.trigger_resize_padding(std::time::Instant::now());
+                renderer.trigger_resize_padding(self.clock.now());
             }
         }
 
@@ -1273,6 +2062,9 @@ impl RenderApp {
         let mut should_exit = false;
 
         while let Ok(cmd) = self.comms.cmd_rx.try_recv() {
+            self.record_session(&cmd);
+            #[cfg(feature = "remote")]
+            self.record_replay(&cmd);
             match cmd {
                 RenderCommand::Shutdown => {
                     log::info!("Render thread received shutdown command");
@@ -1305,64 +2097,200 @@ impl RenderApp {
                         log::warn!("Renderer not initialized, cannot load image {}", id);
                     }
                 }
+                RenderCommand::SetFallbackMetrics { face_id, metrics } => {
+                    match self.glyph_atlas {
+                        Some(ref mut atlas) => atlas.set_fallback_metrics(face_id, metrics),
+                        None => self.pending_fallback_metrics.push((face_id, metrics)),
+                    }
+                    self.frame_dirty = true;
+                }
+                RenderCommand::ImageLoadRgba { id, width, height, data } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.upload_image_rgba(id, width, height, data);
+                        self.frame_dirty = true;
+                    }
+                }
                 RenderCommand::ImageFree { id } => {
                     log::debug!("Freeing image {}", id);
                     if let Some(ref mut renderer) = self.renderer {
                         renderer.free_image(id);
                     }
                 }
-                RenderCommand::WebKitCreate { id, width, height } => {
-                    log::info!("Creating WebKit view: id={}, {}x{}", id, width, height);
-                    #[cfg(feature = "wpe-webkit")]
-                    if let Some(ref backend) = self.wpe_backend {
-                        if let Some(platform_display) = backend.platform_display() {
-                            match WpeWebView::new(id, platform_display, width, height) {
-                                Ok(view) => {
-                                    self.webkit_views.insert(id, view);
-                                    log::info!("WebKit view {} created successfully", id);
-                                }
-                                Err(e) => log::error!("Failed to create WebKit view {}: {:?}", id, e),
-                            }
-                        } else {
-                            log::error!("WPE platform display not available");
-                        }
-                    } else {
-                        log::warn!("WPE backend not initialized, cannot create WebKit view");
+                RenderCommand::ImageSetSampling { id, sampling } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.set_image_sampling(id, sampling);
+                        self.frame_dirty = true;
                     }
                 }
-                RenderCommand::WebKitLoadUri { id, url } => {
-                    log::info!("Loading URL in WebKit view {}: {}", id, url);
-                    #[cfg(feature = "wpe-webkit")]
-                    if let Some(view) = self.webkit_views.get_mut(&id) {
-                        if let Err(e) = view.load_uri(&url) {
-                            log::error!("Failed to load URL in view {}: {:?}", id, e);
-                        }
-                    } else {
-                        log::warn!("WebKit view {} not found", id);
+                RenderCommand::ImageSetInteractive { id, interactive } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.set_image_interactive(id, interactive);
+                        self.frame_dirty = true;
+                    }
+                    if !interactive && self.image_drag.is_some_and(|(drag_id, _)| drag_id == id) {
+                        self.image_drag = None;
                     }
                 }
-                RenderCommand::WebKitResize { id, width, height } => {
-                    log::debug!("Resizing WebKit view {}: {}x{}", id, width, height);
-                    #[cfg(feature = "wpe-webkit")]
-                    if let Some(view) = self.webkit_views.get_mut(&id) {
-                        view.resize(width, height);
+                RenderCommand::SetChromeImage { element, patch } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.set_chrome_image(element, patch);
+                        self.frame_dirty = true;
                     }
                 }
-                RenderCommand::WebKitDestroy { id } => {
-                    log::info!("Destroying WebKit view {}", id);
-                    #[cfg(feature = "wpe-webkit")]
-                    {
-                        self.webkit_views.remove(&id);
-                        // Clean up the renderer's webkit cache
-                        if let Some(ref mut renderer) = self.renderer {
-                            renderer.remove_webkit_view(id);
-                        }
+                RenderCommand::ChartSet { id, values, style } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.set_chart(id, values, style);
+                        self.frame_dirty = true;
                     }
                 }
-                RenderCommand::WebKitClick { id, x, y, button } => {
-                    log::debug!("WebKit click view {} at ({}, {}), button {}", id, x, y, button);
-                    #[cfg(feature = "wpe-webkit")]
-                    if let Some(view) = self.webkit_views.get(&id) {
+                RenderCommand::ChartUpdate { id, values, duration } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.update_chart(id, values, duration);
+                        self.frame_dirty = true;
+                    }
+                }
+                RenderCommand::ImageSwap { id, new_id, effect, duration } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.swap_image(id, new_id, effect, duration);
+                        self.frame_dirty = true;
+                    }
+                }
+                RenderCommand::ShaderEffectRegister { name, source } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        renderer.forget_shader_effect(&name);
+                    }
+                    self.shader_effect_sources.insert(name, source);
+                    self.frame_dirty = true;
+                }
+                RenderCommand::ShaderEffectApply { effect } => {
+                    crate::core::shader_effect::apply(&mut self.shader_effects, effect);
+                    self.frame_dirty = true;
+                }
+                RenderCommand::ShaderEffectRemove { name, target } => {
+                    crate::core::shader_effect::remove(&mut self.shader_effects, name.as_deref(), target);
+                    self.frame_dirty = true;
+                }
+                #[cfg(feature = "math")]
+                RenderCommand::MathRasterize { image_id, frame, .. } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        let scale = self.scale_factor as f32;
+                        let (w, h, data) = crate::layout::math::rasterize(&frame, scale);
+                        renderer.upload_image_rgba(image_id, w, h, data);
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "icons")]
+                RenderCommand::IconRasterize { image_id, source, width, height, color } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        let scale = self.scale_factor as f32;
+                        let (w, h, data) = crate::layout::icons::rasterize(&source, width, height, scale, color);
+                        renderer.upload_image_rgba(image_id, w, h, data);
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "canvas")]
+                RenderCommand::CanvasRasterize { image_id, canvas } => {
+                    let scale = self.scale_factor as f32;
+                    if let (Some(ref mut renderer), Some(ref mut atlas)) = (&mut self.renderer, &mut self.glyph_atlas) {
+                        let (fonts, swash) = atlas.fonts_mut();
+                        let (w, h, data) = crate::layout::canvas::rasterize(&canvas, scale, fonts, swash);
+                        renderer.upload_image_rgba(image_id, w, h, data);
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "pdf")]
+                RenderCommand::PdfOpen { id, path, password } => {
+                    log::info!("Opening PDF {}: {}", id, path);
+                    self.pdf_cache.open(id, &path, password);
+                }
+                #[cfg(feature = "pdf")]
+                RenderCommand::PdfRenderPage { id, page, zoom, image_id } => {
+                    let scale = self.scale_factor as f32;
+                    self.pdf_cache.render_page(id, page, zoom, scale, image_id);
+                }
+                #[cfg(feature = "pdf")]
+                RenderCommand::PdfExtractPage { id, page } => {
+                    self.pdf_cache.extract_page(id, page);
+                }
+                #[cfg(feature = "pdf")]
+                RenderCommand::PdfClose { id } => {
+                    self.pdf_cache.close(id);
+                }
+                RenderCommand::WebKitCreate { id, width, height, profile } => {
+                    log::info!("Creating WebKit view: id={}, {}x{}, profile={:?}", id, width, height, profile);
+                    #[cfg(feature = "wpe-webkit")]
+                    if let Some(ref backend) = self.wpe_backend {
+                        let session = match self.webkit_profiles.session_for(profile.as_deref()) {
+                            Ok(session) => session,
+                            Err(e) => {
+                                error_report::warning(ErrorKind::WebKit, Some(id), format!("{}, using default session", e));
+                                std::ptr::null_mut()
+                            }
+                        };
+                        if let Some(platform_display) = backend.platform_display() {
+                            match WpeWebView::new_with_session(id, platform_display, session, width, height) {
+                                Ok(view) => {
+                                    self.webkit_views.insert(id, view);
+                                    log::info!("WebKit view {} created successfully", id);
+                                }
+                                Err(e) => {
+                                    error_report::error(ErrorKind::WebKit, Some(id), format!("cannot create view: {:?}", e));
+                                }
+                            }
+                        } else {
+                            log::error!("WPE platform display not available");
+                        }
+                    } else {
+                        log::warn!("WPE backend not initialized, cannot create WebKit view");
+                    }
+                }
+                RenderCommand::WebKitDefineProfile { name, data_dir, cache_dir, ephemeral } => {
+                    #[cfg(feature = "wpe-webkit")]
+                    {
+                        let mut config = crate::backend::wpe::WebKitProfileConfig::new(&name);
+                        config.data_dir = data_dir.map(std::path::PathBuf::from);
+                        config.cache_dir = cache_dir.map(std::path::PathBuf::from);
+                        config.ephemeral = ephemeral;
+                        if let Err(e) = self.webkit_profiles.define(config) {
+                            error_report::error(ErrorKind::WebKit, None, e.to_string());
+                        }
+                    }
+                    #[cfg(not(feature = "wpe-webkit"))]
+                    let _ = (name, data_dir, cache_dir, ephemeral);
+                }
+                RenderCommand::WebKitLoadUri { id, url } => {
+                    log::info!("Loading URL in WebKit view {}: {}", id, url);
+                    #[cfg(feature = "wpe-webkit")]
+                    if let Some(view) = self.webkit_views.get_mut(&id) {
+                        if let Err(e) = view.load_uri(&url) {
+                            error_report::error(ErrorKind::WebKit, Some(id), format!("cannot load URL: {:?}", e));
+                        }
+                    } else {
+                        log::warn!("WebKit view {} not found", id);
+                    }
+                }
+                RenderCommand::WebKitResize { id, width, height } => {
+                    log::debug!("Resizing WebKit view {}: {}x{}", id, width, height);
+                    #[cfg(feature = "wpe-webkit")]
+                    if let Some(view) = self.webkit_views.get_mut(&id) {
+                        view.resize(width, height);
+                    }
+                }
+                RenderCommand::WebKitDestroy { id } => {
+                    log::info!("Destroying WebKit view {}", id);
+                    #[cfg(feature = "wpe-webkit")]
+                    {
+                        self.webkit_views.remove(&id);
+                        // Clean up the renderer's webkit cache
+                        if let Some(ref mut renderer) = self.renderer {
+                            renderer.remove_webkit_view(id);
+                        }
+                    }
+                }
+                RenderCommand::WebKitClick { id, x, y, button } => {
+                    log::debug!("WebKit click view {} at ({}, {}), button {}", id, x, y, button);
+                    #[cfg(feature = "wpe-webkit")]
+                    if let Some(view) = self.webkit_views.get(&id) {
                         view.click(x, y, button);
                     }
                 }
@@ -1419,10 +2347,20 @@ impl RenderApp {
                     log::info!("WebKit set floating: id={} at ({},{}) {}x{}", id, x, y, width, height);
                     #[cfg(feature = "wpe-webkit")]
                     {
-                        self.floating_webkits.retain(|w| w.webkit_id != id);
-                        self.floating_webkits.push(crate::core::scene::FloatingWebKit {
-                            webkit_id: id, x, y, width, height,
-                        });
+                        // Keep opacity/corner radius (possibly mid-animation) on moves
+                        match self.floating_webkits.iter_mut().find(|w| w.webkit_id == id) {
+                            Some(fw) => {
+                                fw.x = x;
+                                fw.y = y;
+                                fw.width = width;
+                                fw.height = height;
+                            }
+                            None => self.floating_webkits.push(crate::core::scene::FloatingWebKit {
+                                webkit_id: id, x, y, width, height,
+                                opacity: 1.0,
+                                corner_radius: 0.0,
+                            }),
+                        }
                         self.frame_dirty = true;
                     }
                 }
@@ -1433,6 +2371,45 @@ impl RenderApp {
                         self.floating_webkits.retain(|w| w.webkit_id != id);
                         self.frame_dirty = true;
                     }
+                    self.floating_animations.cancel(FloatingKind::WebKit, id);
+                }
+                RenderCommand::ImageSetFloating { id, x, y, width, height } => {
+                    match self.floating_images.iter_mut().find(|i| i.image_id == id) {
+                        Some(fi) => {
+                            fi.x = x;
+                            fi.y = y;
+                            fi.width = width;
+                            fi.height = height;
+                        }
+                        None => self.floating_images.push(crate::core::scene::FloatingImage {
+                            image_id: id, x, y, width, height,
+                            opacity: 1.0,
+                            corner_radius: 0.0,
+                        }),
+                    }
+                    self.frame_dirty = true;
+                }
+                RenderCommand::ImageRemoveFloating { id } => {
+                    self.floating_images.retain(|i| i.image_id != id);
+                    self.floating_animations.cancel(FloatingKind::Image, id);
+                    self.frame_dirty = true;
+                }
+                RenderCommand::AnimateFloating { kind, id, property, to, duration_ms, easing, notify } => {
+                    match self.floating_property_mut(kind, id, property) {
+                        Some(value) if duration_ms == 0 => *value = to,
+                        Some(value) => {
+                            let from = *value;
+                            self.floating_animations.start(
+                                kind, id, property, from, to,
+                                std::time::Duration::from_millis(duration_ms as u64), easing,
+                            );
+                        }
+                        None => log::debug!("AnimateFloating: no {:?} {} with {:?}", kind, id, property),
+                    }
+                    if notify != 0 {
+                        self.floating_animations.notify(kind, id, property, notify);
+                    }
+                    self.frame_dirty = true;
                 }
                 RenderCommand::VideoCreate { id, path } => {
                     log::info!("Loading video {}: {}", id, path);
@@ -1442,6 +2419,32 @@ impl RenderApp {
                         log::info!("Video loaded with id {} (requested id was {})", video_id, id);
                     }
                 }
+                RenderCommand::SaveSession { path } => {
+                    self.save_session(path);
+                }
+                RenderCommand::DumpScene { reply } => {
+                    let _ = reply.try_send(self.scene_dump().to_json());
+                }
+                RenderCommand::FrameText { selection_face, reply } => {
+                    let _ = reply.try_send(self.frame_text(selection_face));
+                }
+                RenderCommand::MemoryReport { reply } => {
+                    let _ = reply.try_send(self.memory_report());
+                }
+                #[cfg(feature = "remote")]
+                RenderCommand::ReplayStart { path } => {
+                    self.start_replay(path);
+                }
+                #[cfg(feature = "remote")]
+                RenderCommand::ReplayStop => {
+                    self.stop_replay();
+                }
+                RenderCommand::ExportEffectPreview { effect, path, timeline } => {
+                    self.export_effect_preview(effect, path, timeline);
+                }
+                RenderCommand::SetDisplayTheme { theme, fade } => {
+                    self.set_display_theme(*theme, fade);
+                }
                 RenderCommand::VideoPlay { id } => {
                     log::debug!("Playing video {}", id);
                     #[cfg(feature = "video")]
@@ -1462,6 +2465,48 @@ impl RenderApp {
                     if let Some(ref mut renderer) = self.renderer {
                         renderer.video_stop(id);
                     }
+                    if self.pip.as_ref().is_some_and(|pip| pip.video_id == id) {
+                        self.pip = None;
+                        self.frame_dirty = true;
+                    }
+                    self.audio_event_videos.remove(&id);
+                }
+                #[cfg(feature = "video")]
+                RenderCommand::MediaWaveform { path, width, height, color, image_id } => {
+                    self.media_preview.waveform(&path, width, height, color, image_id);
+                }
+                #[cfg(feature = "video")]
+                RenderCommand::MediaThumbnails { path, width, image_ids } => {
+                    self.media_preview.thumbnails(&path, width, image_ids);
+                }
+                RenderCommand::VideoAudioEvents { id, enabled } => {
+                    if enabled {
+                        self.audio_event_videos.insert(id);
+                    } else {
+                        self.audio_event_videos.remove(&id);
+                    }
+                }
+                RenderCommand::VideoPip { id, corner, width } => {
+                    use crate::core::pip::{PipCorner, PipPlayer};
+                    self.pip = (id != 0).then(|| PipPlayer::new(id, PipCorner::from_u8(corner), width));
+                    self.frame_dirty = true;
+                }
+                RenderCommand::SetBackgroundVideo { window_id, video_id, dim } => {
+                    if let Some(ref mut renderer) = self.renderer {
+                        #[cfg(feature = "video")]
+                        if video_id != 0 {
+                            // A backdrop, not something to listen to
+                            renderer.video_set_loop(video_id, -1);
+                            renderer.video_set_volume(video_id, 0.0);
+                            if self.window_focused {
+                                renderer.video_play(video_id);
+                            } else {
+                                renderer.video_pause(video_id);
+                            }
+                        }
+                        renderer.set_background_video(window_id, video_id, dim);
+                    }
+                    self.frame_dirty = true;
                 }
                 RenderCommand::SetMouseCursor { cursor_type } => {
                     if let Some(ref window) = self.window {
@@ -1500,6 +2545,10 @@ impl RenderApp {
                     if let Some(ref window) = self.window {
                         window.set_title(&title);
                     }
+                    #[cfg(feature = "accessibility")]
+                    if let Some(ref mut bridge) = self.accessibility {
+                        bridge.set_title(&title);
+                    }
                     if !self.chrome.decorations_enabled {
                         self.frame_dirty = true;
                     }
@@ -1569,6 +2618,44 @@ impl RenderApp {
                         self.cursor.animating = false;
                     }
                 }
+                RenderCommand::StartTransition { window_id, effect, duration_ms, notify } => {
+                    if let Some(old) = self.transitions.requested.take() {
+                        // Superseded before it started
+                        self.animation_watch.watch(old.notify, Vec::new());
+                    }
+                    self.transitions.requested = Some(RequestedTransition {
+                        window_id,
+                        effect,
+                        duration: std::time::Duration::from_millis(duration_ms as u64),
+                        notify,
+                    });
+                    self.frame_dirty = true;
+                }
+                RenderCommand::ScrollHint { pixels } => {
+                    self.transitions.scroll_hint = Some((pixels, self.clock.now()));
+                }
+                RenderCommand::SetFrameZoom { scale, focus, duration_ms, notify } => {
+                    self.zoom.set(
+                        scale,
+                        focus,
+                        std::time::Duration::from_millis(duration_ms as u64),
+                        self.clock.now(),
+                    );
+                    self.animation_watch.watch(notify, vec![WatchedAnimation::Zoom]);
+                    self.frame_dirty = true;
+                }
+                RenderCommand::SetDisplayOption { name, value } => {
+                    self.apply_display_option(name, &value);
+                }
+                RenderCommand::SetAnimationCurve { name, curve } => {
+                    log::debug!("Animation curve '{}': {:?}", name, curve.as_ref().map(|c| c.to_spec()));
+                    match name.as_str() {
+                        "cursor" => self.cursor.anim_curve = curve.clone(),
+                        "cursor-size" => self.cursor.size_curve = curve.clone(),
+                        _ => {}
+                    }
+                    self.animation_curves.set(&name, curve);
+                }
                 RenderCommand::SetAnimationConfig {
                     cursor_enabled, cursor_speed,
                     cursor_style, cursor_duration_ms,
@@ -1628,36 +2715,51 @@ impl RenderApp {
                 }
                 #[cfg(feature = "neo-term")]
                 RenderCommand::TerminalCreate { id, cols, rows, mode, shell } => {
-                    let term_mode = match mode {
-                        1 => crate::terminal::TerminalMode::Inline,
-                        2 => crate::terminal::TerminalMode::Floating,
-                        _ => crate::terminal::TerminalMode::Window,
-                    };
+                    let term_mode = crate::terminal::TerminalMode::from_u8(mode);
                     match crate::terminal::TerminalView::new(
                         id, cols, rows, term_mode, shell.as_deref(),
+                        self.terminal_manager.output_limiter(),
                     ) {
                         Ok(view) => {
-                            // Register term Arc in shared map for cross-thread access
+                            // Register term and input queue in shared map for cross-thread access
                             if let Ok(mut shared) = self.shared_terminals.lock() {
-                                shared.insert(id, view.term.clone());
+                                shared.insert(id, crate::terminal::SharedTerminal {
+                                    term: view.term.clone(),
+                                    input: view.input.clone(),
+                                    mirror: Default::default(),
+                                    prompt: view.prompt.clone(),
+                                });
                             }
                             self.terminal_manager.terminals.insert(id, view);
                             log::info!("Terminal {} created ({}x{}, {:?})", id, cols, rows, term_mode);
                         }
                         Err(e) => {
-                            log::error!("Failed to create terminal {}: {}", id, e);
+                            error_report::error(ErrorKind::Terminal, Some(id), format!("cannot create terminal: {}", e));
                         }
                     }
                 }
+                #[cfg(feature = "neo-term-ssh")]
+                RenderCommand::TerminalCreateSsh { id, cols, rows, mode, target } => {
+                    let size = crate::terminal::CellSize::default().window_size(cols, rows);
+                    log::info!("Terminal {} connecting to {}@{}:{}", id, target.user, target.host, target.port);
+                    let result = crate::terminal::transport::Transport::ssh_in_background(target, size);
+                    self.pending_ssh.push(PendingSshTerminal { id, cols, rows, mode, result });
+                }
                 #[cfg(feature = "neo-term")]
                 RenderCommand::TerminalWrite { id, data } => {
                     if let Some(view) = self.terminal_manager.get_mut(id) {
-                        if let Err(e) = view.write(&data) {
-                            log::warn!("Terminal {} write error: {}", id, e);
+                        if view.write_typed(&data) {
+                            self.frame_dirty = true;
                         }
                     }
                 }
                 #[cfg(feature = "neo-term")]
+                RenderCommand::TerminalReplay { id, data } => {
+                    if let Some(view) = self.terminal_manager.get(id) {
+                        view.replay(&data);
+                    }
+                }
+                #[cfg(feature = "neo-term")]
                 RenderCommand::TerminalResize { id, cols, rows } => {
                     if let Some(view) = self.terminal_manager.get_mut(id) {
                         view.resize(cols, rows);
@@ -1669,9 +2771,27 @@ impl RenderApp {
                         shared.remove(&id);
                     }
                     self.terminal_manager.destroy(id);
+                    for client in self.tmux_clients.values_mut() {
+                        if let Some(pane) = client.panes.iter().find(|&(_, &t)| t == id).map(|(&p, _)| p) {
+                            client.remove_pane(pane);
+                            client.panes.remove(&pane);
+                        }
+                    }
                     log::info!("Terminal {} destroyed", id);
                 }
                 #[cfg(feature = "neo-term")]
+                RenderCommand::TerminalDetach { id } => {
+                    if self.terminal_manager.detach(id) {
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TerminalAttach { id, mode } => {
+                    if self.terminal_manager.attach(id, crate::terminal::TerminalMode::from_u8(mode)) {
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "neo-term")]
                 RenderCommand::TerminalSetFloat { id, x, y, opacity } => {
                     if let Some(view) = self.terminal_manager.get_mut(id) {
                         view.float_x = x;
@@ -1679,6 +2799,64 @@ impl RenderApp {
                         view.float_opacity = opacity;
                     }
                 }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TerminalSetFont { id, family, size } => {
+                    if let Some(view) = self.terminal_manager.get_mut(id) {
+                        let font = crate::terminal::TerminalFont { family, size };
+                        if view.font != font {
+                            // Glyphs are cached by face id, which stays the same
+                            if view.font.family != font.family {
+                                if let Some(ref mut atlas) = self.glyph_atlas {
+                                    atlas.clear();
+                                }
+                            }
+                            view.font = font;
+                            self.frame_dirty = true;
+                        }
+                    }
+                }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TerminalSetBackground { id, background } => {
+                    if let Some(view) = self.terminal_manager.get_mut(id) {
+                        view.background = background;
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TerminalSetLocalEcho { id, mode } => {
+                    if let Some(view) = self.terminal_manager.get_mut(id) {
+                        view.local_echo.set_mode(mode);
+                        self.frame_dirty = true;
+                    }
+                }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TmuxConnect { id, command } => {
+                    match crate::terminal::tmux::TmuxClient::spawn(id, &command) {
+                        Ok(client) => {
+                            self.tmux_clients.insert(id, client);
+                            log::info!("tmux client {} started: {}", id, command);
+                        }
+                        Err(e) => {
+                            error_report::error(ErrorKind::Terminal, None, format!("cannot run {}: {}", command, e));
+                            self.comms.send_input(InputEvent::TmuxExited { client: id });
+                        }
+                    }
+                }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TmuxCommand { id, command } => {
+                    if let Some(client) = self.tmux_clients.get(&id) {
+                        if let Err(e) = client.command(&command) {
+                            error_report::error(ErrorKind::Terminal, None, format!("tmux client {}: {}", id, e));
+                        }
+                    }
+                }
+                #[cfg(feature = "neo-term")]
+                RenderCommand::TmuxDisconnect { id } => {
+                    if let Some(client) = self.tmux_clients.get(&id) {
+                        client.detach();
+                    }
+                    self.close_tmux_client(id);
+                }
                 RenderCommand::ShowPopupMenu { x, y, items, title, fg, bg } => {
                     log::info!("ShowPopupMenu at ({}, {}) with {} items", x, y, items.len());
                     let (fs, lh) = self.glyph_atlas.as_ref()
@@ -1715,12 +2893,52 @@ impl RenderApp {
                     self.tooltip = None;
                     self.frame_dirty = true;
                 }
+                RenderCommand::ShowCharGrid { x, y, columns, rows, cell_size, items, fg, bg, selected_bg } => {
+                    log::debug!("ShowCharGrid at ({}, {}) with {} items", x, y, items.len());
+                    let layout = crate::text::CharGridLayout::new(
+                        x, y, columns as usize, rows as usize, cell_size, items.len(),
+                    );
+                    self.char_grid = Some(CharGridState {
+                        layout,
+                        items,
+                        selected: None,
+                        fg,
+                        bg,
+                        selected_bg,
+                    });
+                    self.frame_dirty = true;
+                }
+                RenderCommand::UpdateCharGrid { selected, scroll_row } => {
+                    if let Some(ref mut grid) = self.char_grid {
+                        grid.update(selected, scroll_row);
+                        self.frame_dirty = true;
+                    }
+                }
+                RenderCommand::HideCharGrid => {
+                    self.char_grid = None;
+                    self.frame_dirty = true;
+                }
+                RenderCommand::MinimapUpdate { buffer_id, first, removed, pos_delta, lines } => {
+                    if let Some(renderer) = self.renderer.as_mut() {
+                        renderer.minimap.update(
+                            buffer_id, first as usize, removed as usize, &lines, pos_delta,
+                        );
+                        self.frame_dirty = true;
+                    }
+                }
+                RenderCommand::MinimapForget { buffer_id } => {
+                    if let Some(renderer) = self.renderer.as_mut() {
+                        renderer.minimap.forget(buffer_id);
+                        self.frame_dirty = true;
+                    }
+                }
                 RenderCommand::VisualBell => {
-                    self.visual_bell_start = Some(std::time::Instant::now());
+                    let now = self.clock.now();
+                    self.visual_bell_start = Some(now);
                     // Trigger cursor error pulse if enabled
                     if self.effects.cursor_error_pulse.enabled {
                         if let Some(renderer) = self.renderer.as_mut() {
-                            renderer.trigger_cursor_error_pulse(std::time::Instant::now());
+                            renderer.trigger_cursor_error_pulse(now);
                         }
                     }
                     // Trigger edge snap indicator if enabled
@@ -1738,7 +2956,7 @@ impl RenderApp {
                                                 info.mode_line_height,
                                                 at_top,
                                                 at_bottom,
-                                                std::time::Instant::now(),
+                                                now,
                                             );
                                         }
                                     }
@@ -1761,9 +2979,7 @@ impl RenderApp {
                 }
                 RenderCommand::UpdateEffect(updater) => {
                     (updater.0)(&mut self.effects);
-                    if let Some(renderer) = self.renderer.as_mut() {
-                        renderer.effects = self.effects.clone();
-                    }
+                    self.sync_renderer_effects();
                     self.frame_dirty = true;
                 }
                 RenderCommand::SetScrollIndicators { enabled } => {
@@ -1816,119 +3032,650 @@ impl RenderApp {
         should_exit
     }
 
-    /// Get latest frame from Emacs (non-blocking)
-    fn poll_frame(&mut self) {
-        // Get the newest frame, discarding older ones
-        while let Ok(frame) = self.comms.frame_rx.try_recv() {
-            self.current_frame = Some(frame);
-            self.frame_dirty = true;
-            // Reset blink to visible when new frame arrives (cursor just moved/redrawn)
-            self.cursor.reset_blink();
+    /// Mutable reference to an animatable property of a floating element
+    fn floating_property_mut(
+        &mut self,
+        kind: FloatingKind,
+        id: u32,
+        property: FloatingProperty,
+    ) -> Option<&mut f32> {
+        if !kind.supports(property) {
+            return None;
         }
-
-        // Extract active cursor target for animation
-        if let Some(ref frame) = self.current_frame {
-            let active_cursor = frame.glyphs.iter().find_map(|g| match g {
-                FrameGlyph::Cursor { window_id, x, y, width, height, style, color }
-                    if *style != 3 => Some(CursorTarget {
-                        window_id: *window_id,
-                        x: *x, y: *y,
-                        width: *width, height: *height,
-                        style: *style,
-                        color: *color,
-                    }),
+        match kind {
+            FloatingKind::Image => self.floating_images.iter_mut()
+                .find(|i| i.image_id == id)
+                .map(|i| i.property_mut(property)),
+            #[cfg(feature = "wpe-webkit")]
+            FloatingKind::WebKit => self.floating_webkits.iter_mut()
+                .find(|w| w.webkit_id == id)
+                .map(|w| w.property_mut(property)),
+            #[cfg(not(feature = "wpe-webkit"))]
+            FloatingKind::WebKit => None,
+            #[cfg(feature = "neo-term")]
+            FloatingKind::Terminal => self.terminal_manager.get_mut(id).and_then(|view| match property {
+                FloatingProperty::X => Some(&mut view.float_x),
+                FloatingProperty::Y => Some(&mut view.float_y),
+                FloatingProperty::Opacity => Some(&mut view.float_opacity),
                 _ => None,
-            });
+            }),
+            #[cfg(not(feature = "neo-term"))]
+            FloatingKind::Terminal => None,
+        }
+    }
 
-            if let Some(new_target) = active_cursor {
-                let had_target = self.cursor.target.is_some();
-                let target_moved = self.cursor.target.as_ref().map_or(true, |old| {
-                    (old.x - new_target.x).abs() > 0.5
-                    || (old.y - new_target.y).abs() > 0.5
-                    || (old.width - new_target.width).abs() > 0.5
-                    || (old.height - new_target.height).abs() > 0.5
+    /// Keep track of the resources `cmd` creates or frees
+    fn record_session(&mut self, cmd: &RenderCommand) {
+        use crate::core::session::{ImageSession, VideoSession, WebKitSession};
+
+        let session = &mut self.session;
+        match cmd {
+            RenderCommand::ImageLoadFile { id, path, max_width, max_height } => {
+                session.images.insert(*id, ImageSession {
+                    path: path.clone(),
+                    max_width: *max_width,
+                    max_height: *max_height,
+                    floating: None,
                 });
-
-                if !had_target || !self.cursor.anim_enabled {
-                    // First appearance or animation disabled: snap
-                    self.cursor.current_x = new_target.x;
-                    self.cursor.current_y = new_target.y;
-                    self.cursor.current_w = new_target.width;
-                    self.cursor.current_h = new_target.height;
-                    self.cursor.animating = false;
-                    // Snap corner springs to target corners
-                    let corners = CursorState::target_corners(&new_target);
-                    for i in 0..4 {
-                        self.cursor.corner_springs[i].x = corners[i].0;
-                        self.cursor.corner_springs[i].y = corners[i].1;
-                        self.cursor.corner_springs[i].vx = 0.0;
-                        self.cursor.corner_springs[i].vy = 0.0;
-                        self.cursor.corner_springs[i].target_x = corners[i].0;
-                        self.cursor.corner_springs[i].target_y = corners[i].1;
-                    }
-                    self.cursor.prev_target_cx = new_target.x + new_target.width / 2.0;
-                    self.cursor.prev_target_cy = new_target.y + new_target.height / 2.0;
-                } else if target_moved {
-                    let now = std::time::Instant::now();
-                    self.cursor.animating = true;
-                    self.cursor.last_anim_time = now;
-                    // Capture start position for easing/linear/spring styles
-                    self.cursor.start_x = self.cursor.current_x;
-                    self.cursor.start_y = self.cursor.current_y;
-                    self.cursor.start_w = self.cursor.current_w;
-                    self.cursor.start_h = self.cursor.current_h;
-                    self.cursor.anim_start_time = now;
-                    // For spring: reset velocities
-                    self.cursor.velocity_x = 0.0;
-                    self.cursor.velocity_y = 0.0;
-                    self.cursor.velocity_w = 0.0;
-                    self.cursor.velocity_h = 0.0;
-
-                    // Set up 4-corner springs for trail effect (spring style only)
-                    if self.cursor.anim_style == CursorAnimStyle::CriticallyDampedSpring {
-                        let new_corners = CursorState::target_corners(&new_target);
-                        let new_cx = new_target.x + new_target.width / 2.0;
-                        let new_cy = new_target.y + new_target.height / 2.0;
-                        let old_cx = self.cursor.prev_target_cx;
-                        let old_cy = self.cursor.prev_target_cy;
-
-                        // Travel direction (normalized)
-                        let dx = new_cx - old_cx;
-                        let dy = new_cy - old_cy;
-                        let len = (dx * dx + dy * dy).sqrt();
-                        let (dir_x, dir_y) = if len > 0.001 {
-                            (dx / len, dy / len)
-                        } else {
-                            (1.0, 0.0)
-                        };
-
-                        // Corner direction vectors from center: TL(-1,-1), TR(1,-1), BR(1,1), BL(-1,1)
-                        let corner_dirs: [(f32, f32); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
-
-                        // Compute dot products and rank corners
-                        let mut dots: [(f32, usize); 4] = corner_dirs.iter().enumerate()
-                            .map(|(i, (cx, cy))| (cx * dir_x + cy * dir_y, i))
-                            .collect::<Vec<_>>()
-                            .try_into()
-                            .unwrap();
-                        dots.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-                        // dots[0] = most trailing (lowest dot), dots[3] = most leading (highest dot)
-
-                        let base_dur = self.cursor.anim_duration; // seconds
-                        for (rank, &(_dot, corner_idx)) in dots.iter().enumerate() {
-                            let factor = 1.0 - self.cursor.trail_size * (rank as f32 / 3.0);
-                            let duration_i = (base_dur * factor).max(0.01);
-                            let omega_i = 4.0 / duration_i;
-
-                            self.cursor.corner_springs[corner_idx].target_x = new_corners[corner_idx].0;
-                            self.cursor.corner_springs[corner_idx].target_y = new_corners[corner_idx].1;
-                            self.cursor.corner_springs[corner_idx].omega = omega_i;
-                            // Don't reset velocity — preserve momentum from in-flight animation
-                        }
-
-                        self.cursor.prev_target_cx = new_cx;
-                        self.cursor.prev_target_cy = new_cy;
-                    }
+            }
+            RenderCommand::ImageFree { id } => { session.images.remove(id); }
+            RenderCommand::VideoCreate { id, path } => {
+                session.videos.insert(*id, VideoSession { path: path.clone() });
+            }
+            RenderCommand::VideoDestroy { id } => { session.videos.remove(id); }
+            RenderCommand::WebKitCreate { id, width, height, profile } => {
+                session.webkits.insert(*id, WebKitSession {
+                    width: *width,
+                    height: *height,
+                    profile: profile.clone(),
+                    ..Default::default()
+                });
+            }
+            RenderCommand::WebKitLoadUri { id, url } => {
+                if let Some(saved) = session.webkits.get_mut(id) {
+                    saved.url = Some(url.clone());
+                }
+            }
+            RenderCommand::WebKitDestroy { id } => { session.webkits.remove(id); }
+            #[cfg(feature = "neo-term")]
+            RenderCommand::TerminalCreate { id, cols, rows, mode, shell } => {
+                session.terminals.insert(*id, crate::core::session::TerminalSession {
+                    cols: *cols,
+                    rows: *rows,
+                    mode: *mode,
+                    shell: shell.clone(),
+                    ..Default::default()
+                });
+            }
+            #[cfg(feature = "neo-term")]
+            RenderCommand::TerminalDestroy { id } => { session.terminals.remove(id); }
+            _ => {}
+        }
+    }
+
+    /// Hand the effects to the renderer, less those the quality level
+    /// drops
+    fn sync_renderer_effects(&mut self) {
+        if let Some(renderer) = self.renderer.as_mut() {
+            renderer.effects = self.effects.clone();
+            self.quality.level().apply(&mut renderer.effects);
+        }
+    }
+
+    /// Turn off, or back on, what `level` drops
+    fn apply_quality(&mut self, level: crate::core::quality::QualityLevel) {
+        self.sync_renderer_effects();
+        self.throttle.set_reduced_video(!level.full_rate_video());
+        if !level.transitions() {
+            self.transitions.drop_snapshots(usize::MAX);
+            self.transitions.prev_window_infos.clear();
+            self.transitions.requested = None;
+        }
+        crate::core::quality::publish(level);
+        self.frame_dirty = true;
+    }
+
+    /// Step the quality level down or up after a frame whose building
+    /// took `work`, surface waits excluded
+    fn update_quality(&mut self, work: std::time::Duration) {
+        use crate::core::error_report::Severity;
+
+        let budget = self.frame_clock.refresh_interval();
+        let previous = self.quality.level();
+        let Some(level) = self.quality.frame_rendered(work, budget) else {
+            return;
+        };
+        let message = if level > previous {
+            format!("Frames running late: display quality lowered to {}", level.as_str())
+        } else {
+            format!("Display quality raised to {}", level.as_str())
+        };
+        error_report::report(Severity::Info, ErrorKind::Render, None, message);
+        self.apply_quality(level);
+    }
+
+    /// Structure of the current frame, for debugging
+    fn scene_dump(&self) -> crate::core::scene_dump::SceneDump {
+        use crate::core::scene_dump::{glyph_counts, FloatingDump, SceneDump, WindowDump};
+
+        let mut dump = SceneDump {
+            width: self.width,
+            height: self.height,
+            scale: self.scale_factor as f32,
+            frames: self.frame_stats.frames,
+            ..Default::default()
+        };
+        if let Some(ref frame) = self.current_frame {
+            dump.windows = frame
+                .window_infos
+                .iter()
+                .map(|w| WindowDump {
+                    id: w.window_id,
+                    bounds: w.bounds,
+                    selected: w.selected,
+                    minibuffer: w.is_minibuffer,
+                    buffer_file_name: w.buffer_file_name.clone(),
+                })
+                .collect();
+            dump.glyphs = glyph_counts(&frame.glyphs);
+        }
+
+        // Floating layers in the order they are drawn
+        let layer = |kind, id, rect, opacity| FloatingDump { kind, id, rect, opacity };
+        for fi in &self.floating_images {
+            let rect = Rect::new(fi.x, fi.y, fi.width, fi.height);
+            dump.floating.push(layer("image", Some(fi.image_id), Some(rect), Some(fi.opacity)));
+        }
+        #[cfg(feature = "wpe-webkit")]
+        for fw in &self.floating_webkits {
+            let rect = Rect::new(fw.x, fw.y, fw.width, fw.height);
+            dump.floating.push(layer("webkit", Some(fw.webkit_id), Some(rect), Some(fw.opacity)));
+        }
+        #[cfg(feature = "video")]
+        if let Some(ref pip) = self.pip {
+            let rect = pip.rect(self.logical_frame_rect());
+            dump.floating.push(layer("picture-in-picture", Some(pip.video_id), Some(rect), None));
+        }
+        if self.popup_menu.is_some() {
+            dump.floating.push(layer("popup-menu", None, None, None));
+        }
+        if self.char_grid.is_some() {
+            dump.floating.push(layer("char-grid", None, None, None));
+        }
+        if let Some(ref tip) = self.tooltip {
+            let (x, y, w, h) = tip.bounds;
+            dump.floating.push(layer("tooltip", None, Some(Rect::new(x, y, w, h)), None));
+        }
+
+        let now = std::time::Instant::now();
+        dump.animations = vec![
+            ("cursor", (self.cursor.animating || self.cursor.size_animating) as usize),
+            ("crossfades", self.transitions.crossfades.len()),
+            ("scroll-slides", self.transitions.scroll_slides.len()),
+            ("floating-properties", self.floating_animations.running()),
+        ];
+        if let Some(ref renderer) = self.renderer {
+            let charts = renderer.charts.values().filter(|c| c.animating(now)).count();
+            dump.animations.push(("charts", charts));
+            dump.animations.push(("continuous-redraw", renderer.needs_continuous_redraw as usize));
+            let (hits, misses) = renderer.row_cache_stats();
+            let (image_bytes, _) = renderer.image_memory_usage(&HashSet::new());
+            dump.caches.push(("image-bytes", image_bytes));
+            #[cfg(feature = "video")]
+            dump.caches.push(("video-bytes", renderer.video_memory_usage()));
+            dump.caches.push(("text-rows-reused", hits));
+            dump.caches.push(("text-rows-rebuilt", misses));
+            dump.caches.push(("background-rects", renderer.overdraw_stats.rects_out));
+        }
+        if let Some(ref atlas) = self.glyph_atlas {
+            dump.caches.push(("glyph-atlas-glyphs", atlas.len()));
+            dump.caches.push(("glyph-atlas-bytes", atlas.memory_usage()));
+        }
+        dump
+    }
+
+    /// The current frame as plain text, with text drawn in the face
+    /// `selection_face` marked selected (see `core::accessibility`)
+    fn frame_text(&self, selection_face: u32) -> String {
+        use crate::core::accessibility::{AccessibilitySnapshot, SelectionFace};
+
+        let Some(ref frame) = self.current_frame else {
+            return String::new();
+        };
+        let selection = (selection_face > 0).then(|| SelectionFace {
+            face_id: selection_face,
+            background: self.faces.get(&selection_face).map(|f| f.background),
+        });
+        AccessibilitySnapshot::from_frame_selecting(frame, selection).to_text()
+    }
+
+    /// Memory held by the texture caches, terminals and snapshots
+    fn memory_report(&self) -> crate::core::memory_report::MemoryReport {
+        let mut report = crate::core::memory_report::MemoryReport::default();
+        if let Some(ref atlas) = self.glyph_atlas {
+            report.add("glyph-atlas", atlas.len(), atlas.memory_usage(), true);
+        }
+        if let Some(ref renderer) = self.renderer {
+            let (images, videos) = renderer.texture_counts();
+            let (image_bytes, _) = renderer.image_memory_usage(&HashSet::new());
+            report.add("images", images, image_bytes, true);
+            report.add("videos", videos, self.video_memory_usage(), true);
+        }
+        let (buffers_and_snapshots, snapshots) = self.transitions.memory_usage();
+        let buffers = [&self.transitions.offscreen_a, &self.transitions.offscreen_b]
+            .iter()
+            .filter(|o| o.is_some())
+            .count();
+        report.add("offscreen-buffers", buffers, buffers_and_snapshots - snapshots, true);
+        let transitions = self.transitions.crossfades.len() + self.transitions.scroll_slides.len();
+        report.add("transition-snapshots", transitions, snapshots, true);
+        #[cfg(feature = "neo-term")]
+        {
+            let (terminals, grids, contents) = self.terminal_manager.memory_usage();
+            report.add("terminal-grids", terminals, grids, false);
+            report.add("terminal-snapshots", terminals, contents, false);
+        }
+        report
+    }
+
+    /// Start recording a replay to `path`, with the images already loaded
+    /// from files.  The first frame recorded carries every face.
+    #[cfg(feature = "remote")]
+    fn start_replay(&mut self, path: std::path::PathBuf) {
+        use crate::remote::replay::{ReplayRecord, ReplayWriter};
+
+        self.stop_replay();
+        let writer = std::fs::File::create(&path).and_then(|f| ReplayWriter::new(std::io::BufWriter::new(f)));
+        match writer {
+            Ok(writer) => {
+                log::info!("Recording replay to {}", path.display());
+                self.replay = Some((writer, std::time::Instant::now()));
+                self.replay_needs_faces = true;
+            }
+            Err(e) => {
+                error_report::error(ErrorKind::Config, None, format!("{}: {}", path.display(), e));
+                return;
+            }
+        }
+        let images: Vec<_> = self.session.images.iter()
+            .map(|(id, image)| (*id, image.path.clone(), image.max_width, image.max_height))
+            .collect();
+        for (id, path, max_width, max_height) in images {
+            if let Ok(data) = std::fs::read(&path) {
+                self.write_replay(ReplayRecord::ImageFile { id, max_width, max_height, data });
+            }
+        }
+    }
+
+    /// Finish the replay being recorded, if any
+    #[cfg(feature = "remote")]
+    fn stop_replay(&mut self) {
+        if let Some((mut writer, _)) = self.replay.take() {
+            if let Err(e) = writer.flush() {
+                error_report::error(ErrorKind::Config, None, format!("replay: {}", e));
+            }
+        }
+    }
+
+    /// Append `record` to the replay being recorded.  Recording stops at
+    /// the first write that fails.
+    #[cfg(feature = "remote")]
+    fn write_replay(&mut self, record: crate::remote::replay::ReplayRecord) {
+        let Some((ref mut writer, started)) = self.replay else { return };
+        if let Err(e) = writer.write(started.elapsed(), &record) {
+            error_report::error(ErrorKind::Config, None, format!("replay: {}", e));
+            self.replay = None;
+        }
+    }
+
+    /// Record the images `cmd` loads or frees in the replay
+    #[cfg(feature = "remote")]
+    fn record_replay(&mut self, cmd: &RenderCommand) {
+        use crate::remote::replay::ReplayRecord;
+
+        if self.replay.is_none() {
+            return;
+        }
+        let record = match cmd {
+            RenderCommand::ImageLoadFile { id, path, max_width, max_height } => match std::fs::read(path) {
+                Ok(data) => ReplayRecord::ImageFile { id: *id, max_width: *max_width, max_height: *max_height, data },
+                Err(_) => return,
+            },
+            RenderCommand::ImageLoadRgba { id, width, height, data } => {
+                ReplayRecord::ImageRgba { id: *id, width: *width, height: *height, data: data.clone() }
+            }
+            RenderCommand::ImageFree { id } => ReplayRecord::ImageFree { id: *id },
+            _ => return,
+        };
+        self.write_replay(record);
+    }
+
+    /// Write the recorded resources, with their current state, to `path`.
+    /// The file is written on another thread; failures are reported as
+    /// display errors.
+    fn save_session(&mut self, path: std::path::PathBuf) {
+        use crate::core::session::FloatingRect;
+
+        let mut session = self.session.clone();
+        for (id, image) in session.images.iter_mut() {
+            image.floating = self.floating_images.iter().find(|f| f.image_id == *id)
+                .map(|f| FloatingRect { x: f.x, y: f.y, width: f.width, height: f.height });
+        }
+        #[cfg(feature = "wpe-webkit")]
+        for (id, webkit) in session.webkits.iter_mut() {
+            webkit.floating = self.floating_webkits.iter().find(|f| f.webkit_id == *id)
+                .map(|f| FloatingRect { x: f.x, y: f.y, width: f.width, height: f.height });
+        }
+        #[cfg(feature = "neo-term")]
+        session.terminals.retain(|id, saved| {
+            use alacritty_terminal::grid::Dimensions;
+            let Some(view) = self.terminal_manager.get(*id) else { return false };
+            let term = view.term.lock();
+            saved.cols = term.grid().columns() as u16;
+            saved.rows = term.grid().screen_lines() as u16;
+            saved.scrollback = crate::terminal::content::extract_history(
+                &*term, crate::core::session::SCROLLBACK_LINES,
+            );
+            saved.mode = view.mode.as_u8();
+            saved.float = (view.mode == crate::terminal::TerminalMode::Floating)
+                .then_some((view.float_x, view.float_y, view.float_opacity));
+            true
+        });
+        if let Ok(options) = self.display_options.lock() {
+            session.options = options.runtime_values().into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+        }
+
+        std::thread::spawn(move || match session.save(&path) {
+            Ok(()) => log::info!("Session saved to {}", path.display()),
+            Err(e) => { error_report::error(ErrorKind::Config, None, e.to_string()); }
+        });
+    }
+
+    /// Switch every color of the display's own to those of `theme` in one
+    /// frame, crossfading from the last frame over `fade` when given
+    fn set_display_theme(&mut self, theme: crate::core::display_theme::DisplayTheme, fade: Option<std::time::Duration>) {
+        if let Some(duration) = fade {
+            if let Some((tex, view, bg_group)) = self.snapshot_prev_texture() {
+                let bounds = Rect::new(0.0, 0.0, self.width as f32 / self.scale_factor as f32,
+                    self.height as f32 / self.scale_factor as f32);
+                self.transitions.crossfades.insert(-1, CrossfadeTransition {
+                    started: self.clock.now(),
+                    duration,
+                    bounds,
+                    effect: self.transitions.crossfade_effect,
+                    easing: self.transitions.crossfade_easing,
+                    old_texture: tex,
+                    old_view: view,
+                    old_bind_group: bg_group,
+                    text_scale: None,
+                });
+            }
+        }
+
+        // Terminal colors are sRGB, not linear
+        #[cfg(feature = "neo-term")]
+        {
+            let srgb = |c: Color| Color::new(
+                Color::linear_component_to_srgb(c.r),
+                Color::linear_component_to_srgb(c.g),
+                Color::linear_component_to_srgb(c.b),
+                c.a,
+            );
+            let mut terminal = self.terminal_manager.theme().clone();
+            terminal.foreground = srgb(theme.foreground);
+            terminal.background = srgb(theme.background);
+            terminal.palette = match theme.terminal_palette {
+                Some(palette) => palette.map(srgb),
+                None => crate::terminal::colors::TerminalTheme::default().palette,
+            };
+            self.terminal_manager.set_theme(terminal);
+        }
+
+        if let Some(frame) = self.current_frame.as_mut() {
+            theme.apply(frame);
+        }
+        if let Some(renderer) = self.renderer.as_mut() {
+            renderer.theme = theme.clone();
+        }
+        log::info!("Display theme set ({})", theme.appearance().as_str());
+        self.theme = theme;
+        self.frame_dirty = true;
+    }
+
+    /// Play `effect` offscreen over the sample frames, rather than the
+    /// buffers shown, and write the frames to `path`.  They are rendered
+    /// here and encoded on another thread as they come; the display waits
+    /// meanwhile.  Failures are reported as display errors.
+    fn export_effect_preview(&mut self, effect: PreviewEffect, path: std::path::PathBuf, timeline: PreviewTimeline) {
+        use crate::core::effect_preview::{cell_rect, sample_frame, CURSOR_CELLS, CURSOR_COLOR, SAMPLE_WINDOW, TRANSITION_SCROLL};
+
+        let (Some(renderer), Some(glyph_atlas)) = (self.renderer.as_mut(), self.glyph_atlas.as_mut()) else {
+            return;
+        };
+        let (width, height) = (self.width, self.height);
+        let logical_w = width as f32 / self.scale_factor as f32;
+        let logical_h = height as f32 / self.scale_factor as f32;
+        let no_mouse = (-1.0, -1.0);
+
+        // A few frames in flight, so a slow encoder holds back rendering
+        // rather than filling memory with full-size frames
+        let (frames_tx, frames_rx) = std::sync::mpsc::sync_channel(4);
+        let output = PreviewOutput::for_path(&path);
+        let delays = timeline.delays_ms();
+        std::thread::spawn(move || match write_effect_preview(&output, width, height, &delays, frames_rx) {
+            Ok(count) => log::info!("Effect preview: {} frames written to {}", count, path.display()),
+            Err(e) => {
+                error_report::error(ErrorKind::Config, None, format!("cannot write effect preview {}: {}", path.display(), e));
+            }
+        });
+
+        let (out_texture, out_view) = renderer.create_offscreen_texture(width, height);
+        match effect {
+            PreviewEffect::Transition(effect) => {
+                // The sample scrolls by a few lines, so slides and curls
+                // have different text on either side
+                let (_old_texture, old_view) = renderer.create_offscreen_texture(width, height);
+                let (_new_texture, new_view) = renderer.create_offscreen_texture(width, height);
+                for (view, scroll) in [(&old_view, 0), (&new_view, TRANSITION_SCROLL)] {
+                    let frame = sample_frame(logical_w, logical_h, scroll, CURSOR_CELLS[0]);
+                    renderer.render_frame_glyphs(
+                        view, None, &frame, glyph_atlas, &self.faces,
+                        width, height, true, None, no_mouse, None,
+                    );
+                }
+                let old_bg = renderer.create_texture_bind_group(&old_view);
+                let new_bg = renderer.create_texture_bind_group(&new_view);
+                let bounds = Rect::new(0.0, 0.0, logical_w, logical_h);
+                let duration = timeline.duration.as_secs_f32();
+                for i in 0..timeline.frame_count() {
+                    let elapsed = timeline.frame_time(i).as_secs_f32();
+                    let t = if duration > 0.0 { elapsed / duration } else { 1.0 };
+                    renderer.blit_texture_to_view(&new_bg, &out_view, width, height);
+                    renderer.render_scroll_effect(
+                        &out_view, &old_bg, &new_bg, t, elapsed, 1, &bounds,
+                        effect, self.transitions.crossfade_easing, width, height,
+                    );
+                    let Some(pixels) = renderer.read_texture_rgba(&out_texture) else { break };
+                    if frames_tx.send(pixels).is_err() {
+                        break;
+                    }
+                }
+            }
+            PreviewEffect::Cursor(style) => {
+                // The cursor jumps between two cells with the style's
+                // motion and the speed and trail set for the real one
+                let target = |(col, row)| {
+                    let rect = cell_rect(col, row);
+                    CursorTarget {
+                        window_id: SAMPLE_WINDOW,
+                        x: rect.x,
+                        y: rect.y,
+                        width: rect.width,
+                        height: rect.height,
+                        style: 0,
+                        color: CURSOR_COLOR,
+                    }
+                };
+                let mut cursor = CursorState {
+                    anim_style: style,
+                    anim_speed: self.cursor.anim_speed,
+                    anim_duration: timeline.duration.as_secs_f32().max(0.001),
+                    trail_size: self.cursor.trail_size,
+                    ..CursorState::default()
+                };
+                let to = target(CURSOR_CELLS[1]);
+                cursor.snap_to(&target(CURSOR_CELLS[0]));
+                let start = std::time::Instant::now();
+                cursor.start_motion(&to, start);
+                cursor.target = Some(to);
+
+                let frame = sample_frame(logical_w, logical_h, 0, CURSOR_CELLS[1]);
+                for i in 0..timeline.frame_count() {
+                    cursor.tick_animation(start + timeline.frame_time(i));
+                    renderer.render_frame_glyphs(
+                        &out_view, None, &frame, glyph_atlas, &self.faces,
+                        width, height, true, cursor.animated(), no_mouse, None,
+                    );
+                    let Some(pixels) = renderer.read_texture_rgba(&out_texture) else { break };
+                    if frames_tx.send(pixels).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        // The renderer drew the sample; the real frame has to come back
+        self.frame_dirty = true;
+    }
+
+    /// Apply the faces changed since the previous frame, dropping cached
+    /// glyphs only for faces whose font changed.  A delta that does not
+    /// follow the faces held makes Emacs send all of them again.
+    fn apply_face_delta(&mut self, delta: &FaceDelta) {
+        let Some(changed) = self.face_cache.apply_delta(delta) else {
+            if !self.faces_requested {
+                log::warn!(
+                    "face delta {}..{} does not follow generation {}, resyncing faces",
+                    delta.base, delta.generation, self.face_cache.generation()
+                );
+                self.faces_requested = true;
+                self.comms.send_input(InputEvent::FacesOutOfSync);
+            }
+            return;
+        };
+        self.faces_requested = false;
+        for id in changed {
+            let new = self.face_cache.merged(id).cloned();
+            let old = match new {
+                Some(face) => self.faces.insert(id, face),
+                None => self.faces.remove(&id),
+            };
+            let font_changed = match (&old, self.faces.get(&id)) {
+                (Some(old), Some(new)) => !old.same_glyphs(new),
+                (None, _) => false,
+                (Some(_), None) => true,
+            };
+            if font_changed {
+                if let Some(ref mut atlas) = self.glyph_atlas {
+                    atlas.invalidate_face(id);
+                }
+            }
+        }
+    }
+
+    /// Get latest frame from Emacs (non-blocking)
+    fn poll_frame(&mut self) {
+        // Get the newest frame, discarding older ones
+        let mut received: u64 = 0;
+        while let Ok(frame) = self.comms.frame_rx.try_recv() {
+            received += 1;
+            crate::core::input_latency::frame_received();
+            // Every frame's face changes count, even for frames skipped
+            if !frame.face_delta.is_empty() {
+                self.apply_face_delta(&frame.face_delta);
+            }
+            #[cfg(feature = "remote")]
+            if self.replay.is_some() {
+                let mut recorded = Box::new(frame.clone());
+                if std::mem::take(&mut self.replay_needs_faces) {
+                    recorded.face_delta = self.face_cache.delta_since(0);
+                }
+                self.write_replay(crate::remote::replay::ReplayRecord::Frame(recorded));
+            }
+            let mut frame = frame;
+            self.theme.apply(&mut frame);
+            if let Some(old) = self.current_frame.replace(frame) {
+                self.comms.recycle_glyphs(old.glyphs);
+            }
+            self.unscrolled_frame = None;
+            self.frame_dirty = true;
+            // Reset blink to visible when new frame arrives (cursor just moved/redrawn)
+            self.cursor.reset_blink(self.clock.now());
+        }
+        self.frame_stats.skipped_frames += received.saturating_sub(1);
+        // Publish the newest frame's text to screen readers (no-op unless
+        // one is listening)
+        #[cfg(feature = "accessibility")]
+        if let (true, Some(bridge), Some(frame)) =
+            (received > 0, self.accessibility.as_mut(), self.current_frame.as_ref())
+        {
+            bridge.update(frame, self.scale_factor);
+        }
+        if self.cursor_predictor.expire(self.clock.now()) {
+            self.drop_predicted_scroll();
+            self.frame_dirty = true;
+        }
+
+        // Extract active cursor target for animation
+        if let Some(ref frame) = self.current_frame {
+            let active_cursor = frame.glyphs.iter().find_map(|g| match g {
+                FrameGlyph::Cursor { window_id, x, y, width, height, style, color }
+                    if *style != 3 => Some(CursorTarget {
+                        window_id: *window_id,
+                        x: *x, y: *y,
+                        width: *width, height: *height,
+                        style: *style,
+                        color: *color,
+                    }),
+                _ => None,
+            });
+
+            if let Some(new_target) = active_cursor {
+                if self.cursor_predictor.enabled() {
+                    if received > 0 {
+                        self.cursor_predictor.frame_arrived(
+                            Rect::new(new_target.x, new_target.y, new_target.width, new_target.height),
+                            frame,
+                        );
+                        // Scrolls guessed past this frame are drawn over it
+                        if let Some((area, dy)) = self.cursor_predictor.scroll_showing() {
+                            self.show_predicted_scroll(area, dy);
+                        }
+                    }
+                    // Keep drawing the guess until a frame shows it or
+                    // rules it out; then the frame's cursor takes over
+                    if self.cursor_predictor.showing().is_some() {
+                        return;
+                    }
+                    if self.cursor.predicted {
+                        self.cursor.predicted = false;
+                        self.frame_dirty = true;
+                    }
+                }
+                let had_target = self.cursor.target.is_some();
+                let target_moved = self.cursor.target.as_ref().map_or(true, |old| {
+                    (old.x - new_target.x).abs() > 0.5
+                    || (old.y - new_target.y).abs() > 0.5
+                    || (old.width - new_target.width).abs() > 0.5
+                    || (old.height - new_target.height).abs() > 0.5
+                });
+
+                if !had_target || !self.cursor.anim_enabled {
+                    // First appearance or animation disabled: snap
+                    self.cursor.snap_to(&new_target);
+                } else if target_moved {
+                    self.cursor.start_motion(&new_target, self.clock.now());
                 }
 
                 // Spawn typing ripple when cursor moves (if enabled)
@@ -1972,7 +3719,7 @@ impl RenderApp {
                         self.cursor.size_animating = true;
                         self.cursor.size_start_w = self.cursor.current_w;
                         self.cursor.size_start_h = self.cursor.current_h;
-                        self.cursor.size_anim_start = std::time::Instant::now();
+                        self.cursor.size_anim_start = self.clock.now();
                     }
                     self.cursor.size_target_w = new_target.width;
                     self.cursor.size_target_h = new_target.height;
@@ -1989,6 +3736,72 @@ impl RenderApp {
 
 
 
+    /// Move the cursor to where the key `keysym` will put it, when that
+    /// can be told from the frame on screen
+    fn predict_cursor(&mut self, keysym: u32) {
+        if !self.cursor_predictor.enabled() {
+            return;
+        }
+        let (Some(frame), Some(target)) = (self.current_frame.as_ref(), self.cursor.target.clone()) else {
+            return;
+        };
+        let ctrl = self.modifiers & NEOMACS_CTRL_MASK != 0;
+        let meta = self.modifiers & (NEOMACS_META_MASK | NEOMACS_SUPER_MASK) != 0;
+        let mv = crate::core::cursor_prediction::Move::from_key(keysym, ctrl, meta);
+        let cursor = Rect::new(target.x, target.y, target.width, target.height);
+        let now = self.clock.now();
+        let Some(guess) = self.cursor_predictor.key(mv, cursor, frame, now) else {
+            return;
+        };
+        if guess.scroll != 0.0 {
+            self.show_predicted_scroll(guess.area, guess.scroll);
+        }
+        let guess = guess.cursor;
+        let guess = CursorTarget { x: guess.x, y: guess.y, width: guess.width, height: guess.height, ..target };
+        if self.cursor.anim_enabled {
+            self.cursor.start_motion(&guess, now);
+        } else {
+            self.cursor.snap_to(&guess);
+        }
+        self.cursor.target = Some(guess);
+        self.cursor.predicted = true;
+        self.cursor.reset_blink(now);
+        self.frame_dirty = true;
+    }
+
+    /// Draw the text of `area` moved up by `dy` (down when negative) for a
+    /// guessed scroll, keeping the frame Emacs drew to go back to
+    fn show_predicted_scroll(&mut self, area: Rect, dy: f32) {
+        let Some(frame) = self.current_frame.as_mut() else {
+            return;
+        };
+        if self.unscrolled_frame.is_none() {
+            self.unscrolled_frame = Some(frame.clone());
+        }
+        crate::core::cursor_prediction::scroll_text(frame, area, dy);
+        self.frame_dirty = true;
+    }
+
+    /// Go back to the frame Emacs drew, if a guessed scroll is drawn
+    fn drop_predicted_scroll(&mut self) {
+        if let Some(frame) = self.unscrolled_frame.take() {
+            self.current_frame = Some(frame);
+            self.frame_dirty = true;
+        }
+    }
+
+    /// Stop predicting until the next frame, as after a click
+    fn interrupt_prediction(&mut self) {
+        self.cursor_predictor.interrupt();
+        self.drop_predicted_scroll();
+    }
+
+    /// The time animations are sampled at: when the next frame is
+    /// expected on screen, not when it is rendered
+    fn animation_time(&self) -> std::time::Instant {
+        self.frame_clock.predicted_presentation(self.clock.now())
+    }
+
     /// Update cursor blink state, returns true if blink toggled
     fn tick_cursor_blink(&mut self) -> bool {
         if !self.cursor.blink_enabled || self.current_frame.is_none() {
@@ -2001,21 +3814,17 @@ impl RenderApp {
         if !has_cursor {
             return false;
         }
-        let now = std::time::Instant::now();
-        if now.duration_since(self.cursor.last_blink_toggle) >= self.cursor.blink_interval {
-            let was_off = !self.cursor.blink_on;
-            self.cursor.blink_on = !self.cursor.blink_on;
-            self.cursor.last_blink_toggle = now;
-            // Trigger wake animation when cursor becomes visible after blink-off
-            if was_off && self.cursor.blink_on && self.effects.cursor_wake.enabled {
-                if let Some(renderer) = self.renderer.as_mut() {
-                    renderer.trigger_cursor_wake(now);
-                }
+        let now = self.clock.now();
+        if !self.cursor.tick_blink(now) {
+            return false;
+        }
+        // Trigger wake animation when cursor becomes visible after blink-off
+        if self.cursor.blink_on && self.effects.cursor_wake.enabled {
+            if let Some(renderer) = self.renderer.as_mut() {
+                renderer.trigger_cursor_wake(now);
             }
-            true
-        } else {
-            false
         }
+        true
     }
 
     /// Pump GLib events (non-blocking) and update webkit views
@@ -2047,6 +3856,10 @@ impl RenderApp {
             let old_progress = view.progress;
 
             view.update();
+            if let Some(ref mut renderer) = self.renderer {
+                let loading = view.state == crate::backend::wpe::WpeViewState::Loading;
+                renderer.set_webkit_loading(*id, loading.then_some(view.progress as f32));
+            }
 
             // Send state change events
             if view.title != old_title {
@@ -2058,6 +3871,9 @@ impl RenderApp {
                 }
             }
             if view.url != old_url {
+                if let Some(saved) = self.session.webkits.get_mut(id) {
+                    saved.url = Some(view.url.clone());
+                }
                 self.comms.send_input(InputEvent::WebKitUrlChanged {
                     id: *id,
                     url: view.url.clone(),
@@ -2068,6 +3884,9 @@ impl RenderApp {
                     id: *id,
                     progress: view.progress,
                 });
+                if view.progress >= 1.0 {
+                    self.comms.send_input(InputEvent::WebKitLoadFinished { id: *id });
+                }
             }
         }
     }
@@ -2181,58 +4000,429 @@ impl RenderApp {
         }
     }
 
-    #[cfg(not(all(feature = "wpe-webkit", target_os = "linux")))]
-    fn process_webkit_frames(&mut self) {}
+    #[cfg(not(all(feature = "wpe-webkit", target_os = "linux")))]
+    fn process_webkit_frames(&mut self) {}
+
+    /// Process pending video frames
+    #[cfg(feature = "video")]
+    fn process_video_frames(&mut self) {
+        log::trace!("process_video_frames called");
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.process_pending_videos();
+            for id in renderer.take_ended_videos() {
+                self.comms.send_input(InputEvent::VideoEnded { id });
+            }
+        }
+        for id in crate::core::audio_levels::take_updated() {
+            if self.audio_event_videos.contains(&id) {
+                self.comms.send_input(InputEvent::AudioLevels { id });
+            }
+        }
+    }
+
+    #[cfg(not(feature = "video"))]
+    fn process_video_frames(&mut self) {}
+
+    /// Check if any video is currently playing (needs continuous rendering)
+    #[cfg(feature = "video")]
+    fn has_playing_videos(&self) -> bool {
+        self.renderer.as_ref().map_or(false, |r| r.has_playing_videos())
+    }
+
+    #[cfg(not(feature = "video"))]
+    fn has_playing_videos(&self) -> bool { false }
+
+    /// Bytes held by video frame textures
+    #[cfg(feature = "video")]
+    fn video_memory_usage(&self) -> usize {
+        self.renderer.as_ref().map_or(0, |r| r.video_memory_usage())
+    }
+
+    #[cfg(not(feature = "video"))]
+    fn video_memory_usage(&self) -> usize { 0 }
+
+    /// Run one piece of idle-time cache maintenance.
+    fn run_idle_task(&mut self, task: IdleTask) {
+        match task {
+            IdleTask::ReleaseSnapshots => {
+                if self.transitions.finished_snapshots.is_empty() {
+                    return;
+                }
+                for texture in self.transitions.finished_snapshots.drain(..) {
+                    texture.destroy();
+                }
+                if let Some(device) = self.device.as_ref() {
+                    let _ = device.poll(wgpu::Maintain::Poll);
+                }
+            }
+            IdleTask::UploadImages => {
+                if self.renderer.as_mut().is_some_and(|r| r.flush_pending_images()) {
+                    self.frame_dirty = true;
+                }
+            }
+            IdleTask::DefragmentAtlas => {
+                if let (Some(atlas), Some(device), Some(queue)) =
+                    (self.glyph_atlas.as_mut(), self.device.as_ref(), self.queue.as_ref())
+                {
+                    atlas.defragment(device, queue);
+                }
+            }
+            IdleTask::TrimScrollback => {
+                #[cfg(feature = "neo-term")]
+                {
+                    let trimmed = self.terminal_manager.trim_scrollback();
+                    if trimmed > 0 {
+                        log::debug!("Trimmed scrollback of {} terminals", trimmed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Measure GPU memory held by the caches and, if it is over budget,
+    /// free snapshots, then offscreen images, then visible images, then
+    /// unused glyphs.
+    fn enforce_memory_budget(&mut self) {
+        use crate::backend::wgpu::gpu_budget::{self, GpuMemoryUsage};
+
+        let mut visible: HashSet<u32> = self.floating_images.iter().map(|i| i.image_id).collect();
+        if let Some(ref frame) = self.current_frame {
+            visible.extend(frame.glyphs.iter().filter_map(|g| match g {
+                FrameGlyph::Image { image_id, .. } => Some(*image_id),
+                _ => None,
+            }));
+            visible.extend(frame.backdrop_images.iter().map(|b| b.image_id));
+        }
+        if let Some(ref renderer) = self.renderer {
+            visible.extend(renderer.chrome_images.values().map(|p| p.image_id));
+        }
+        let (images, visible_images) = self
+            .renderer
+            .as_ref()
+            .map_or((0, 0), |r| r.image_memory_usage(&visible));
+        let (snapshots, evictable_snapshots) = self.transitions.memory_usage();
+        let usage = GpuMemoryUsage {
+            glyphs: self.glyph_atlas.as_ref().map_or(0, |a| a.memory_usage()),
+            images,
+            visible_images,
+            videos: self.video_memory_usage(),
+            snapshots,
+            budget: self.gpu_memory_budget,
+        };
+        gpu_budget::publish(usage);
+
+        let plan = gpu_budget::plan_eviction(&usage, evictable_snapshots);
+        if plan.is_empty() {
+            return;
+        }
+        log::debug!("GPU memory {}MB over {}MB budget: {:?}",
+            usage.total() / (1024 * 1024), usage.budget / (1024 * 1024), plan);
+        if plan.snapshots > 0 {
+            self.transitions.drop_snapshots(plan.snapshots);
+        }
+        if let Some(renderer) = self.renderer.as_mut() {
+            if plan.offscreen_images > 0 {
+                renderer.evict_images(plan.offscreen_images, &visible);
+            }
+            if plan.visible_images > 0 {
+                renderer.evict_images(plan.visible_images, &HashSet::new());
+            }
+        }
+        if plan.glyphs {
+            if let Some(atlas) = self.glyph_atlas.as_mut() {
+                atlas.trim();
+            }
+        }
+        self.frame_dirty = true;
+    }
+
+    /// Ids of the WebKit views drawn in the current frame, inline or floating
+    #[cfg(feature = "wpe-webkit")]
+    fn on_screen_webkits(&self) -> HashSet<u32> {
+        let inline = self.current_frame.iter().flat_map(|frame| frame.glyphs.iter()).filter_map(|g| match g {
+            FrameGlyph::WebKit { webkit_id, .. } => Some(*webkit_id),
+            _ => None,
+        });
+        inline.chain(self.floating_webkits.iter().map(|w| w.webkit_id)).collect()
+    }
+
+    /// Whether WebKit views painted something new, and if so whether any
+    /// of those is on screen
+    #[cfg(feature = "wpe-webkit")]
+    fn webkit_activity(&self) -> Option<bool> {
+        let mut painted = self.webkit_views.iter().filter(|(_, v)| v.needs_redraw()).peekable();
+        painted.peek()?;
+        let on_screen = self.on_screen_webkits();
+        Some(painted.any(|(id, _)| on_screen.contains(id)))
+    }
+
+    #[cfg(not(feature = "wpe-webkit"))]
+    fn webkit_activity(&self) -> Option<bool> { None }
+
+    /// Let WebKit know which views can be seen, so the others stop
+    /// animating
+    #[cfg(feature = "wpe-webkit")]
+    fn update_webkit_visibility(&mut self) {
+        let on_screen = self.on_screen_webkits();
+        let window_visible = self.throttle.window_visible();
+        for (id, view) in self.webkit_views.iter_mut() {
+            view.set_visible(window_visible && on_screen.contains(id));
+        }
+    }
+
+    #[cfg(not(feature = "wpe-webkit"))]
+    fn update_webkit_visibility(&mut self) {}
+
+    /// Whether terminals have new content waiting from their extraction
+    /// workers, and if so whether any of those is on screen.  Inline
+    /// terminals are on screen when the frame draws them; the others fill
+    /// a window or float, and so count as on screen.  Tmux and SSH
+    /// activity is always handled at once.
+    #[cfg(feature = "neo-term")]
+    fn terminal_activity(&self) -> Option<bool> {
+        use crate::terminal::TerminalMode;
+        if self.tmux_clients.values().any(|client| client.has_events()) || self.has_pending_ssh() {
+            return Some(true);
+        }
+        let mut pending = self.terminal_manager.terminals.iter()
+            .filter(|(_, view)| view.has_pending_content())
+            .peekable();
+        pending.peek()?;
+        let inline: HashSet<u32> = self.current_frame.iter()
+            .flat_map(|frame| frame.glyphs.iter())
+            .filter_map(|g| match g {
+                FrameGlyph::Terminal { terminal_id, .. } => Some(*terminal_id),
+                _ => None,
+            })
+            .collect();
+        Some(pending.any(|(id, view)| view.mode != TerminalMode::Inline || inline.contains(id)))
+    }
+
+    #[cfg(not(feature = "neo-term"))]
+    fn terminal_activity(&self) -> Option<bool> { None }
+
+    /// Whether SSH terminals are still connecting.
+    #[cfg(feature = "neo-term-ssh")]
+    fn has_pending_ssh(&self) -> bool {
+        !self.pending_ssh.is_empty()
+    }
+
+    #[cfg(all(feature = "neo-term", not(feature = "neo-term-ssh")))]
+    fn has_pending_ssh(&self) -> bool { false }
+
+    /// Create the terminals of SSH connections that completed.  A failed
+    /// connection is reported and its terminal exits.
+    #[cfg(feature = "neo-term-ssh")]
+    fn poll_ssh_connects(&mut self) {
+        let mut i = 0;
+        while i < self.pending_ssh.len() {
+            let result = match self.pending_ssh[i].result.try_recv() {
+                Ok(result) => result,
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    i += 1;
+                    continue;
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => Err("connection thread died".into()),
+            };
+            let PendingSshTerminal { id, cols, rows, mode, .. } = self.pending_ssh.swap_remove(i);
+            let term_mode = crate::terminal::TerminalMode::from_u8(mode);
+            let view = result.and_then(|transport| {
+                crate::terminal::TerminalView::with_transport(
+                    id, cols, rows, term_mode, transport,
+                    self.terminal_manager.output_limiter(),
+                )
+                .map_err(|e| e.to_string())
+            });
+            match view {
+                Ok(view) => {
+                    if let Ok(mut shared) = self.shared_terminals.lock() {
+                        shared.insert(id, crate::terminal::SharedTerminal {
+                            term: view.term.clone(),
+                            input: view.input.clone(),
+                            mirror: Default::default(),
+                            prompt: view.prompt.clone(),
+                        });
+                    }
+                    self.terminal_manager.terminals.insert(id, view);
+                    self.frame_dirty = true;
+                    log::info!("Terminal {} connected ({}x{}, {:?})", id, cols, rows, term_mode);
+                }
+                Err(e) => {
+                    error_report::error(ErrorKind::Terminal, Some(id), format!("cannot connect terminal: {}", e));
+                    self.comms.send_input(InputEvent::TerminalExited { id });
+                }
+            }
+        }
+    }
+
+    /// Create terminals for new tmux panes and report closed ones.
+    #[cfg(feature = "neo-term")]
+    fn poll_tmux_clients(&mut self) {
+        use crate::terminal::tmux::TmuxEvent;
 
-    /// Process pending video frames
-    #[cfg(feature = "video")]
-    fn process_video_frames(&mut self) {
-        log::trace!("process_video_frames called");
-        if let Some(ref mut renderer) = self.renderer {
-            renderer.process_pending_videos();
+        let ids: Vec<u32> = self.tmux_clients.keys().copied().collect();
+        for client_id in ids {
+            let Some(client) = self.tmux_clients.get(&client_id) else {
+                continue;
+            };
+            for event in client.poll() {
+                match event {
+                    TmuxEvent::Layout { window, panes } => {
+                        // Panes of this window missing from its layout are gone
+                        let gone: Vec<u32> = self.tmux_clients.get(&client_id)
+                            .map(|c| c.panes_of(window))
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|&pane| !panes.iter().any(|p| p.pane == pane))
+                            .collect();
+                        for pane in gone {
+                            self.close_tmux_pane(client_id, pane);
+                        }
+                        for layout in panes {
+                            self.add_tmux_pane(client_id, window, layout);
+                        }
+                    }
+                    TmuxEvent::WindowClosed { window } => {
+                        let panes = self.tmux_clients.get(&client_id)
+                            .map(|c| c.panes_of(window))
+                            .unwrap_or_default();
+                        for pane in panes {
+                            self.close_tmux_pane(client_id, pane);
+                        }
+                    }
+                    TmuxEvent::Exited => {
+                        self.close_tmux_client(client_id);
+                        break;
+                    }
+                }
+            }
         }
     }
 
-    #[cfg(not(feature = "video"))]
-    fn process_video_frames(&mut self) {}
-
-    /// Check if any video is currently playing (needs continuous rendering)
-    #[cfg(feature = "video")]
-    fn has_playing_videos(&self) -> bool {
-        self.renderer.as_ref().map_or(false, |r| r.has_playing_videos())
+    /// Give a tmux pane a terminal, unless it has one.
+    #[cfg(feature = "neo-term")]
+    fn add_tmux_pane(&mut self, client_id: u32, window: u32, layout: crate::terminal::tmux::PaneLayout) {
+        let Some(client) = self.tmux_clients.get_mut(&client_id) else {
+            return;
+        };
+        if client.panes.contains_key(&layout.pane) {
+            // Panes can move between windows
+            client.set_pane_window(layout.pane, window);
+            return;
+        }
+        let Ok(id) = crate::core::handle::TERMINALS.alloc() else {
+            return;
+        };
+        let mode = crate::terminal::TerminalMode::Inline;
+        let view = match crate::terminal::TerminalView::for_tmux_pane(
+            id, layout.cols, layout.rows, mode, client.control(layout.pane),
+        ) {
+            Ok(view) => view,
+            Err(e) => {
+                let _ = crate::core::handle::TERMINALS.free(id);
+                error_report::error(ErrorKind::Terminal, None, format!("cannot create terminal for tmux pane: {}", e));
+                return;
+            }
+        };
+        if let Err(e) = client.add_pane(layout.pane, view.term.clone(), view.event_proxy.clone()) {
+            log::warn!("tmux client {}: cannot capture pane %{}: {}", client_id, layout.pane, e);
+        }
+        client.panes.insert(layout.pane, id);
+        client.set_pane_window(layout.pane, window);
+        if let Ok(mut shared) = self.shared_terminals.lock() {
+            shared.insert(id, crate::terminal::SharedTerminal {
+                term: view.term.clone(),
+                input: view.input.clone(),
+                mirror: Default::default(),
+                prompt: view.prompt.clone(),
+            });
+        }
+        self.terminal_manager.terminals.insert(id, view);
+        self.comms.send_input(InputEvent::TmuxPaneAdded {
+            client: client_id,
+            pane: layout.pane,
+            window,
+            id,
+            cols: layout.cols,
+            rows: layout.rows,
+        });
     }
 
-    #[cfg(not(feature = "video"))]
-    fn has_playing_videos(&self) -> bool { false }
-
-    /// Check if any WebKit view needs redraw
-    #[cfg(feature = "wpe-webkit")]
-    fn has_webkit_needing_redraw(&self) -> bool {
-        self.webkit_views.values().any(|v| v.needs_redraw())
+    /// Stop feeding a tmux pane's terminal and tell Emacs, which
+    /// destroys the terminal.
+    #[cfg(feature = "neo-term")]
+    fn close_tmux_pane(&mut self, client_id: u32, pane: u32) {
+        let Some(client) = self.tmux_clients.get_mut(&client_id) else {
+            return;
+        };
+        client.remove_pane(pane);
+        if let Some(id) = client.panes.remove(&pane) {
+            self.comms.send_input(InputEvent::TmuxPaneClosed { client: client_id, id });
+        }
     }
 
-    #[cfg(not(feature = "wpe-webkit"))]
-    fn has_webkit_needing_redraw(&self) -> bool { false }
-
-    /// Check if any terminal has pending content from PTY reader threads.
+    /// Drop a tmux client after closing all its panes.
     #[cfg(feature = "neo-term")]
-    fn has_terminal_activity(&self) -> bool {
-        for view in self.terminal_manager.terminals.values() {
-            if view.event_proxy.peek_wakeup() || view.dirty {
-                return true;
-            }
+    fn close_tmux_client(&mut self, client_id: u32) {
+        let panes: Vec<u32> = self.tmux_clients.get(&client_id)
+            .map(|c| c.panes.keys().copied().collect())
+            .unwrap_or_default();
+        for pane in panes {
+            self.close_tmux_pane(client_id, pane);
+        }
+        if self.tmux_clients.remove(&client_id).is_some() {
+            self.comms.send_input(InputEvent::TmuxExited { client: client_id });
         }
-        false
     }
 
-    #[cfg(not(feature = "neo-term"))]
-    fn has_terminal_activity(&self) -> bool { false }
-
     /// Process pending image uploads (decode → GPU texture)
     fn process_pending_images(&mut self) {
         if let Some(ref mut renderer) = self.renderer {
             renderer.process_pending_images();
         }
+        #[cfg(feature = "pdf")]
+        self.process_pending_pdf_pages();
+        #[cfg(feature = "video")]
+        self.process_pending_media_previews();
+    }
+
+    /// Upload finished waveforms and thumbnails and notify Emacs of their size
+    #[cfg(feature = "video")]
+    fn process_pending_media_previews(&mut self) {
+        let previews = self.media_preview.take_rendered();
+        if previews.is_empty() {
+            return;
+        }
+        let Some(ref mut renderer) = self.renderer else { return };
+        for preview in previews {
+            let (id, w, h) = (preview.image_id, preview.width, preview.height);
+            renderer.upload_image_rgba(id, w, h, preview.data);
+            if let Ok(mut dims) = self.image_dimensions.lock() {
+                dims.insert(id, (w, h));
+            }
+            self.comms.send_input(InputEvent::ImageDimensionsReady { id, width: w, height: h });
+        }
+        self.frame_dirty = true;
+    }
+
+    /// Upload rasterized PDF pages and notify Emacs of their size
+    #[cfg(feature = "pdf")]
+    fn process_pending_pdf_pages(&mut self) {
+        let pages = self.pdf_cache.take_rendered();
+        if pages.is_empty() {
+            return;
+        }
+        let Some(ref mut renderer) = self.renderer else { return };
+        for page in pages {
+            let (id, w, h) = (page.image_id, page.logical_width, page.logical_height);
+            renderer.upload_image_rgba(id, page.width, page.height, page.data);
+            if let Ok(mut dims) = self.image_dimensions.lock() {
+                dims.insert(id, (w, h));
+            }
+            self.comms.send_input(InputEvent::ImageDimensionsReady { id, width: w, height: h });
+            log::debug!("Uploaded PDF page as image {} ({}x{})", id, w, h);
+        }
+        self.frame_dirty = true;
     }
 
     /// Ensure offscreen textures exist (lazily created)
@@ -2322,7 +4512,37 @@ impl RenderApp {
             None => return,
         };
 
-        let now = std::time::Instant::now();
+        let now = self.clock.now();
+
+        if let Some(req) = self.transitions.requested.take() {
+            let mut parts = Vec::new();
+            for info in &frame.window_infos {
+                if info.is_minibuffer || info.bounds.height < 50.0 {
+                    continue;
+                }
+                if req.window_id.is_some_and(|id| id != info.window_id) {
+                    continue;
+                }
+                self.transitions.crossfades.remove(&info.window_id);
+                self.transitions.scroll_slides.remove(&info.window_id);
+                if let Some((tex, view, bg)) = self.snapshot_prev_texture() {
+                    log::debug!("Starting requested transition for window {} (effect={:?})", info.window_id, req.effect);
+                    self.transitions.crossfades.insert(info.window_id, CrossfadeTransition {
+                        started: now,
+                        duration: req.duration,
+                        bounds: info.bounds,
+                        effect: req.effect,
+                        easing: self.transitions.crossfade_easing,
+                        old_texture: tex,
+                        old_view: view,
+                        old_bind_group: bg,
+                        text_scale: None,
+                    });
+                    parts.push(WatchedAnimation::Crossfade(info.window_id, now));
+                }
+            }
+            self.animation_watch.watch(req.notify, parts);
+        }
 
         for info in &frame.window_infos {
             if let Some(prev) = self.transitions.prev_window_infos.get(&info.window_id) {
@@ -2352,6 +4572,7 @@ impl RenderApp {
                                     old_texture: tex,
                                     old_view: view,
                                     old_bind_group: bg,
+                                    text_scale: None,
                                 });
                             }
                         }
@@ -2375,6 +4596,38 @@ impl RenderApp {
                                     old_texture: tex,
                                     old_view: view,
                                     old_bind_group: bg,
+                                    text_scale: None,
+                                });
+                            }
+                        }
+                    } else if prev.char_height > 0.0
+                        && info.char_height > 0.0
+                        && (prev.char_height - info.char_height).abs() > 0.5
+                        && !info.is_minibuffer
+                    {
+                        // Text scale change (text-scale-adjust) → scale the
+                        // old text into the new size (content area only)
+                        let content = Rect::new(
+                            info.bounds.x, info.bounds.y,
+                            info.bounds.width, info.bounds.height - info.mode_line_height,
+                        );
+                        if self.transitions.text_scale_enabled && content.height >= 50.0 {
+                            self.transitions.crossfades.remove(&info.window_id);
+                            self.transitions.scroll_slides.remove(&info.window_id);
+
+                            if let Some((tex, view, bg)) = self.snapshot_prev_texture() {
+                                log::debug!("Starting text scale transition for window {} ({} → {})",
+                                    info.window_id, prev.char_height, info.char_height);
+                                self.transitions.crossfades.insert(info.window_id, CrossfadeTransition {
+                                    started: now,
+                                    duration: self.transitions.text_scale_duration,
+                                    bounds: content,
+                                    effect: self.transitions.crossfade_effect,
+                                    easing: self.transitions.crossfade_easing,
+                                    old_texture: tex,
+                                    old_view: view,
+                                    old_bind_group: bg,
+                                    text_scale: Some(info.char_height / prev.char_height),
                                 });
                             }
                         }
@@ -2421,15 +4674,29 @@ impl RenderApp {
                                 info.bounds.width, content_height,
                             );
 
+                            // A distance announced for this scroll (typewriter
+                            // recentering) slides just that far
+                            let hint = self.transitions.scroll_hint.take()
+                                .filter(|&(px, at)| info.selected
+                                    && now.duration_since(at) < std::time::Duration::from_secs(1)
+                                    && px.signum() == dir as f32
+                                    && px.abs() < content_height)
+                                .map(|(px, _)| px);
+                            let (distance, effect) = match hint {
+                                Some(px) => (px, crate::core::scroll_animation::ScrollEffect::Slide),
+                                None => (dir as f32 * content_height, self.transitions.scroll_effect),
+                            };
+
                             if let Some((tex, view, bg)) = self.snapshot_prev_texture() {
-                                log::debug!("Starting scroll slide for window {} (dir={}, effect={:?}, content_h={})",
-                                    info.window_id, dir, self.transitions.scroll_effect, content_height);
+                                log::debug!("Starting scroll slide for window {} (dir={}, effect={:?}, distance={})",
+                                    info.window_id, dir, effect, distance);
                                 self.transitions.scroll_slides.insert(info.window_id, ScrollTransition {
                                     started: now,
                                     duration: self.transitions.scroll_duration,
                                     bounds: content_bounds,
                                     direction: dir,
-                                    effect: self.transitions.scroll_effect,
+                                    distance,
+                                    effect,
                                     easing: self.transitions.scroll_easing,
                                     old_texture: tex,
                                     old_view: view,
@@ -2455,6 +4722,7 @@ impl RenderApp {
                                     old_texture: tex,
                                     old_view: view,
                                     old_bind_group: bg,
+                                    text_scale: None,
                                 });
                             }
                         }
@@ -2514,6 +4782,7 @@ impl RenderApp {
                                         old_texture: tex,
                                         old_view: view,
                                         old_bind_group: bg,
+                                        text_scale: None,
                                     });
                                 }
                             }
@@ -2550,6 +4819,7 @@ impl RenderApp {
                         old_texture: tex,
                         old_view: view,
                         old_bind_group: bg,
+                        text_scale: None,
                     });
                 }
             }
@@ -2598,6 +4868,7 @@ impl RenderApp {
                                 old_texture: tex,
                                 old_view: view,
                                 old_bind_group: bg_group,
+                                text_scale: None,
                             });
                         }
                     }
@@ -2615,7 +4886,8 @@ impl RenderApp {
 
     /// Render active transitions on top of the surface
     fn render_transitions(&mut self, surface_view: &wgpu::TextureView) {
-        let now = std::time::Instant::now();
+        use crate::core::scroll_animation::{motion_blur_length, ScrollEasing, ScrollEffect};
+        let now = self.animation_time();
         let renderer = match self.renderer.as_ref() {
             Some(r) => r,
             None => return,
@@ -2633,28 +4905,48 @@ impl RenderApp {
             let elapsed = now.duration_since(transition.started);
             let raw_t = (elapsed.as_secs_f32() / transition.duration.as_secs_f32()).min(1.0);
             let elapsed_secs = elapsed.as_secs_f32();
+            let (t, easing) = match self.animation_curves.remap("crossfade", raw_t) {
+                Some(t) => (t, crate::core::scroll_animation::ScrollEasing::Linear),
+                None => (raw_t, transition.easing),
+            };
 
             // SAFETY: current_bg is valid for the duration of this function
-            renderer.render_scroll_effect(
-                surface_view,
-                &transition.old_bind_group,
-                unsafe { &*current_bg },
-                raw_t,
-                elapsed_secs,
-                1, // direction: forward
-                &transition.bounds,
-                transition.effect,
-                transition.easing,
-                self.width,
-                self.height,
-            );
+            if let Some(ratio) = transition.text_scale {
+                renderer.render_text_scale_transition(
+                    surface_view,
+                    &transition.old_bind_group,
+                    unsafe { &*current_bg },
+                    easing.apply(t),
+                    ratio,
+                    &transition.bounds,
+                    self.width,
+                    self.height,
+                );
+            } else {
+                renderer.render_scroll_effect(
+                    surface_view,
+                    &transition.old_bind_group,
+                    unsafe { &*current_bg },
+                    t,
+                    elapsed_secs,
+                    1, // direction: forward
+                    &transition.bounds,
+                    transition.effect,
+                    easing,
+                    self.width,
+                    self.height,
+                );
+            }
 
             if raw_t >= 1.0 {
                 completed_crossfades.push(wid);
             }
         }
         for wid in completed_crossfades {
-            self.transitions.crossfades.remove(&wid);
+            if let Some(t) = self.transitions.crossfades.remove(&wid) {
+                self.transitions.finished_snapshots.push(t.old_texture);
+            }
+            self.comms.send_input(InputEvent::AnimationFinished { window_id: wid, scroll: false });
         }
 
         // Render scroll slides
@@ -2663,72 +4955,174 @@ impl RenderApp {
             let elapsed = now.duration_since(transition.started);
             let raw_t = (elapsed.as_secs_f32() / transition.duration.as_secs_f32()).min(1.0);
             let elapsed_secs = elapsed.as_secs_f32();
+            let (t, easing) = match self.animation_curves.remap("scroll", raw_t) {
+                Some(t) => (t, crate::core::scroll_animation::ScrollEasing::Linear),
+                None => (raw_t, transition.easing),
+            };
 
-            renderer.render_scroll_effect(
-                surface_view,
-                &transition.old_bind_group,
-                unsafe { &*current_bg },
-                raw_t,
-                elapsed_secs,
-                transition.direction,
-                &transition.bounds,
-                transition.effect,
-                transition.easing,
-                self.width,
-                self.height,
-            );
+            // Slides move by their distance and streak along their motion
+            // while they move fast; the motion-blur effect always does
+            let strength = self.transitions.scroll_motion_blur_strength;
+            let shift = match transition.effect {
+                ScrollEffect::Slide => Some((
+                    ScrollEasing::EaseOutQuad,
+                    if self.transitions.scroll_motion_blur { strength } else { 0.0 },
+                )),
+                ScrollEffect::MotionBlur => Some((easing, strength)),
+                _ => None,
+            };
+
+            if let Some((e, strength)) = shift {
+                let blur = motion_blur_length(
+                    transition.distance.abs(),
+                    transition.duration.as_secs_f32(),
+                    t,
+                    e,
+                    strength,
+                );
+                renderer.render_scroll_shift(
+                    surface_view,
+                    &transition.old_bind_group,
+                    unsafe { &*current_bg },
+                    e.apply(t),
+                    transition.distance,
+                    &transition.bounds,
+                    blur,
+                    self.width,
+                    self.height,
+                );
+            } else {
+                renderer.render_scroll_effect(
+                    surface_view,
+                    &transition.old_bind_group,
+                    unsafe { &*current_bg },
+                    t,
+                    elapsed_secs,
+                    transition.direction,
+                    &transition.bounds,
+                    transition.effect,
+                    easing,
+                    self.width,
+                    self.height,
+                );
+            }
 
             if raw_t >= 1.0 {
                 completed_scrolls.push(wid);
             }
         }
         for wid in completed_scrolls {
-            self.transitions.scroll_slides.remove(&wid);
+            if let Some(t) = self.transitions.scroll_slides.remove(&wid) {
+                self.transitions.finished_snapshots.push(t.old_texture);
+            }
+            self.comms.send_input(InputEvent::AnimationFinished { window_id: wid, scroll: true });
         }
     }
 
     /// Update terminal content and expand Terminal glyphs into renderable cells.
     #[cfg(feature = "neo-term")]
     fn update_terminals(&mut self) {
+        use crate::backend::wgpu::TerminalRenderer;
         use crate::terminal::TerminalMode;
 
         // Get frame font metrics for terminal cell sizing.
         // These come from FRAME_COLUMN_WIDTH / FRAME_LINE_HEIGHT / FRAME_FONT->pixel_size.
-        let (cell_w, cell_h, font_size, frame_w, frame_h) = if let Some(ref frame) = self.current_frame {
-            (frame.char_width, frame.char_height, frame.font_pixel_size,
-             frame.width, frame.height)
+        let (frame_cell, frame_w, frame_h) = if let Some(ref frame) = self.current_frame {
+            let cell = crate::terminal::CellSize {
+                width: frame.char_width,
+                height: frame.char_height,
+                ascent: frame.char_height * 0.8,
+                font_size: frame.font_pixel_size,
+            };
+            (cell, frame.width, frame.height)
         } else {
-            (8.0, 16.0, 14.0, self.width as f32, self.height as f32)
+            (crate::terminal::CellSize::default(), self.width as f32, self.height as f32)
         };
-        let ascent = cell_h * 0.8;
+        let frame_family = self.current_frame.as_ref()
+            .and_then(|f| f.face_fonts.get(&0).map(|f| f.to_string()))
+            .or_else(|| self.faces.get(&0).map(|f| f.font_family.clone()))
+            .unwrap_or_else(|| "monospace".to_string());
+
+        // Terminals with their own font get cells measured from that font
+        let mut font_faces = Vec::new();
+        for id in self.terminal_manager.ids() {
+            if let Some(view) = self.terminal_manager.get_mut(id) {
+                let cell = if view.font.is_default() {
+                    frame_cell
+                } else {
+                    let family = view.font.family.clone().unwrap_or_else(|| frame_family.clone());
+                    let font_size = view.font.size.unwrap_or(frame_cell.font_size);
+                    font_faces.push((view.font_face_id(), family.clone()));
+                    match self.glyph_atlas.as_mut().and_then(|a| a.cell_metrics(&family, font_size)) {
+                        Some((width, height, ascent)) => crate::terminal::CellSize {
+                            width, height, ascent, font_size,
+                        },
+                        None => {
+                            let scale = font_size / frame_cell.font_size.max(1.0);
+                            crate::terminal::CellSize {
+                                width: frame_cell.width * scale,
+                                height: frame_cell.height * scale,
+                                ascent: frame_cell.ascent * scale,
+                                font_size,
+                            }
+                        }
+                    }
+                };
+                view.set_cell_size(cell);
+            }
+        }
+        if let Some(ref mut frame) = self.current_frame {
+            frame.face_fonts.extend(font_faces.into_iter().map(|(id, family)| (id, family.into())));
+        }
 
         // Auto-resize Window-mode terminals to fit the frame area.
         // Reserve space for mode-line (~1 row) and echo area (~1 row).
-        let term_area_height = (frame_h - cell_h * 2.0).max(cell_h);
-        let target_cols = (frame_w / cell_w).floor() as u16;
-        let target_rows = (term_area_height / cell_h).floor() as u16;
-
-        if target_cols > 0 && target_rows > 0 {
-            for id in self.terminal_manager.ids() {
-                if let Some(view) = self.terminal_manager.get_mut(id) {
-                    if view.mode != TerminalMode::Window {
-                        continue;
-                    }
-                    // Resize if grid dimensions changed
-                    if let Some(content) = view.content() {
-                        if content.cols as u16 != target_cols || content.rows as u16 != target_rows {
-                            view.resize(target_cols, target_rows);
-                        }
+        for id in self.terminal_manager.ids() {
+            if let Some(view) = self.terminal_manager.get_mut(id) {
+                if view.mode != TerminalMode::Window {
+                    continue;
+                }
+                let cell = view.cell;
+                let term_area_height = (frame_h - frame_cell.height * 2.0).max(cell.height);
+                let target_cols = (frame_w / cell.width).floor() as u16;
+                let target_rows = (term_area_height / cell.height).floor() as u16;
+                if target_cols == 0 || target_rows == 0 {
+                    continue;
+                }
+                // Resize if grid dimensions changed
+                if let Some(content) = view.content() {
+                    if content.cols as u16 != target_cols || content.rows as u16 != target_rows {
+                        view.resize(target_cols, target_rows);
                     }
                 }
             }
         }
 
+        // Fit inline terminals' grids to the space redisplay gave them
+        let inline: Vec<_> = self.current_frame.iter()
+            .flat_map(|frame| frame.glyphs.iter())
+            .filter_map(|g| match g {
+                FrameGlyph::Terminal { terminal_id, width, height, .. } => Some((*terminal_id, *width, *height)),
+                _ => None,
+            })
+            .collect();
+        for (id, width, height) in inline {
+            if let Some(view) = self.terminal_manager.get_attached_mut(id) {
+                if view.mode == TerminalMode::Inline {
+                    view.fit_to(width, height);
+                }
+            }
+        }
+
         // Update all terminal content (check for PTY data)
         self.terminal_manager.update_all();
 
+        self.poll_tmux_clients();
+        #[cfg(feature = "neo-term-ssh")]
+        self.poll_ssh_connects();
+
         // Check for exited terminals and notify Emacs
-        for id in self.terminal_manager.ids() {
+        for id in self.terminal_manager.ids().into_iter().chain(self.terminal_manager.detached_ids()) {
             if let Some(view) = self.terminal_manager.get_mut(id) {
                 if view.event_proxy.is_exited() && !view.exit_notified {
                     view.exit_notified = true;
@@ -2737,23 +5131,55 @@ impl RenderApp {
             }
         }
 
+        // Bells: notify Emacs, and the window manager if we lack focus
+        let now = std::time::Instant::now();
+        let rung = self.terminal_manager.ring_bells(now);
+        for &id in &rung {
+            self.comms.send_input(InputEvent::TerminalBell { id });
+        }
+        if !rung.is_empty() && self.terminal_manager.bell.urgency && !self.window_focused {
+            if let Some(ref window) = self.window {
+                window.request_user_attention(Some(winit::window::UserAttentionType::Informational));
+            }
+        }
+        let bell = self.terminal_manager.bell;
+
         // Expand FrameGlyph::Terminal entries (placed by C redisplay) into cells
+        let mut backdrops = Vec::new();
+        let mut blurs = Vec::new();
         if let Some(ref mut frame) = self.current_frame {
             let mut extra_glyphs = Vec::new();
 
             for glyph in &frame.glyphs {
                 if let FrameGlyph::Terminal { terminal_id, x, y, width, height } = glyph {
-                    if let Some(view) = self.terminal_manager.get(*terminal_id) {
-                        if let Some(content) = view.content() {
+                    if let Some(view) = self.terminal_manager.get_attached(*terminal_id) {
+                        // Clip to the text area of the window the terminal
+                        // is in, which a partly visible line extends past
+                        let bounds = Rect::new(*x, *y, *width, *height);
+                        let area = frame.window_infos.iter()
+                            .find(|w| w.bounds.contains(bounds.origin()))
+                            .map(|w| Rect { height: w.bounds.height - w.mode_line_height, ..w.bounds })
+                            .unwrap_or(bounds);
+                        let Some(clip) = bounds.intersection(&area) else {
+                            continue;
+                        };
+                        if let Some(content) = Self::terminal_draw_content(view, &bell, now) {
+                            // A backdrop has to go under the cells, which
+                            // needs the overlay layer
+                            let is_overlay = view.background != crate::terminal::TerminalBackground::Solid;
+                            let bg = Self::terminal_background(
+                                view, &content, clip, 1.0, &mut backdrops, &mut blurs,
+                            );
                             extra_glyphs.push(FrameGlyph::Stretch {
-                                x: *x, y: *y, width: *width, height: *height,
-                                bg: content.default_bg, face_id: 0, is_overlay: false,
+                                x: clip.x, y: clip.y, width: clip.width, height: clip.height,
+                                bg, face_id: 0, is_overlay,
                             });
 
-                            Self::expand_terminal_cells(
-                                content, *x, *y, cell_w, cell_h, ascent, font_size,
-                                false, 1.0, &mut extra_glyphs,
-                            );
+                            TerminalRenderer {
+                                cell: view.cell, face_id: view.font_face_id(),
+                                is_overlay, opacity: 1.0, clip: Some(clip),
+                            }.push(&content, *x, *y, &mut extra_glyphs);
+                            Self::push_bell_flash(view, &bell, now, clip, &mut extra_glyphs);
                         }
                     }
                 }
@@ -2773,21 +5199,27 @@ impl RenderApp {
                     if view.mode != TerminalMode::Window {
                         continue;
                     }
-                    if let Some(content) = view.content() {
+                    if let Some(content) = Self::terminal_draw_content(view, &bell, now) {
                         let x = 0.0_f32;
                         let y = 0.0_f32;
-                        let width = content.cols as f32 * cell_w;
-                        let height = content.rows as f32 * cell_h;
+                        let width = content.cols as f32 * view.cell.width;
+                        let height = content.rows as f32 * view.cell.height;
 
                         // Terminal background
+                        let bg = Self::terminal_background(
+                            view, &content, Rect::new(x, y, width, height), 1.0,
+                            &mut backdrops, &mut blurs,
+                        );
                         win_glyphs.push(FrameGlyph::Stretch {
-                            x, y, width, height,
-                            bg: content.default_bg, face_id: 0, is_overlay: true,
+                            x, y, width, height, bg, face_id: 0, is_overlay: true,
                         });
 
-                        Self::expand_terminal_cells(
-                            content, x, y, cell_w, cell_h, ascent, font_size,
-                            true, 1.0, &mut win_glyphs,
+                        TerminalRenderer {
+                            cell: view.cell, face_id: view.font_face_id(),
+                            is_overlay: true, opacity: 1.0, clip: None,
+                        }.push(&content, x, y, &mut win_glyphs);
+                        Self::push_bell_flash(
+                            view, &bell, now, Rect::new(x, y, width, height), &mut win_glyphs,
                         );
                     }
                 }
@@ -2807,21 +5239,26 @@ impl RenderApp {
                     if view.mode != TerminalMode::Floating {
                         continue;
                     }
-                    if let Some(content) = view.content() {
+                    if let Some(content) = Self::terminal_draw_content(view, &bell, now) {
                         let x = view.float_x;
                         let y = view.float_y;
-                        let width = content.cols as f32 * cell_w;
-                        let height = content.rows as f32 * cell_h;
+                        let width = content.cols as f32 * view.cell.width;
+                        let height = content.rows as f32 * view.cell.height;
 
-                        let mut bg = content.default_bg;
-                        bg.a = view.float_opacity;
+                        let bg = Self::terminal_background(
+                            view, &content, Rect::new(x, y, width, height), view.float_opacity,
+                            &mut backdrops, &mut blurs,
+                        );
                         float_glyphs.push(FrameGlyph::Stretch {
                             x, y, width, height, bg, face_id: 0, is_overlay: true,
                         });
 
-                        Self::expand_terminal_cells(
-                            content, x, y, cell_w, cell_h, ascent, font_size,
-                            true, view.float_opacity, &mut float_glyphs,
+                        TerminalRenderer {
+                            cell: view.cell, face_id: view.font_face_id(),
+                            is_overlay: true, opacity: view.float_opacity, clip: None,
+                        }.push(&content, x, y, &mut float_glyphs);
+                        Self::push_bell_flash(
+                            view, &bell, now, Rect::new(x, y, width, height), &mut float_glyphs,
                         );
                     }
                 }
@@ -2831,72 +5268,96 @@ impl RenderApp {
                 frame.glyphs.extend(float_glyphs);
                 self.frame_dirty = true;
             }
+            frame.backdrop_images.extend(backdrops);
+            for region in blurs {
+                frame.request_blur(region);
+            }
+        }
+    }
+
+    /// Content to draw for a terminal: its latest content with any
+    /// guessed echo drawn in, and colors inverted while an inverting
+    /// visual bell is showing.
+    #[cfg(feature = "neo-term")]
+    fn terminal_draw_content<'a>(
+        view: &'a crate::terminal::TerminalView,
+        bell: &crate::terminal::bell::BellConfig,
+        now: std::time::Instant,
+    ) -> Option<std::borrow::Cow<'a, crate::terminal::content::TerminalContent>> {
+        let content = view.content()?;
+        let content = match view.local_echo.overlay(content) {
+            Some(echoed) => std::borrow::Cow::Owned(echoed),
+            None => std::borrow::Cow::Borrowed(content),
+        };
+        if bell.visual == crate::terminal::bell::VisualBell::Invert && view.bell.is_showing(now) {
+            Some(std::borrow::Cow::Owned(content.inverted()))
+        } else {
+            Some(content)
         }
     }
 
-    /// Expand terminal content cells into FrameGlyph entries.
+    /// Color of a terminal's background stretch over `bounds`, queueing
+    /// the backdrop image or blur its background needs.  `opacity` is the
+    /// floating opacity, 1.0 for other modes.
     #[cfg(feature = "neo-term")]
-    fn expand_terminal_cells(
+    fn terminal_background(
+        view: &crate::terminal::TerminalView,
         content: &crate::terminal::content::TerminalContent,
-        origin_x: f32,
-        origin_y: f32,
-        cell_w: f32,
-        cell_h: f32,
-        ascent: f32,
-        font_size: f32,
-        is_overlay: bool,
+        bounds: Rect,
         opacity: f32,
-        out: &mut Vec<FrameGlyph>,
-    ) {
-        use alacritty_terminal::term::cell::Flags as CellFlags;
-
-        for cell in &content.cells {
-            let cx = origin_x + cell.col as f32 * cell_w;
-            let cy = origin_y + cell.row as f32 * cell_h;
-
-            if cell.bg != content.default_bg {
-                let mut bg = cell.bg;
+        backdrops: &mut Vec<BackdropImage>,
+        blurs: &mut Vec<BlurRegion>,
+    ) -> Color {
+        use crate::terminal::TerminalBackground;
+
+        let mut bg = content.default_bg;
+        let blur = match view.background {
+            TerminalBackground::Solid => {
                 bg.a *= opacity;
-                out.push(FrameGlyph::Stretch {
-                    x: cx, y: cy, width: cell_w, height: cell_h,
-                    bg, face_id: 0, is_overlay,
-                });
+                bg.a < 1.0
             }
-
-            if cell.c != ' ' && cell.c != '\0' {
-                let mut fg = cell.fg;
-                fg.a *= opacity;
-                out.push(FrameGlyph::Char {
-                    char: cell.c,
-                    composed: None,
-                    x: cx, y: cy,
-                    width: cell_w, height: cell_h,
-                    ascent, fg,
-                    bg: None, face_id: 0,
-                    bold: cell.flags.contains(CellFlags::BOLD),
-                    italic: cell.flags.contains(CellFlags::ITALIC),
-                    font_size,
-                    underline: if cell.flags.contains(CellFlags::UNDERLINE) { 1 } else { 0 },
-                    underline_color: None,
-                    strike_through: if cell.flags.contains(CellFlags::STRIKEOUT) { 1 } else { 0 },
-                    strike_through_color: None,
-                    overline: 0, overline_color: None,
-                    is_overlay,
-                });
+            TerminalBackground::Image { image_id, dim } => {
+                backdrops.push(BackdropImage { image_id, bounds });
+                bg.a = dim * opacity;
+                false
             }
+            TerminalBackground::Acrylic { opacity: alpha, blur } => {
+                bg.a = alpha * opacity;
+                blur
+            }
+        };
+        if blur {
+            blurs.push(BlurRegion {
+                // Keep terminal IDs clear of other blur requests
+                id: (1 << 32) | view.id as u64,
+                bounds,
+                corner_radius: 0.0,
+            });
         }
+        bg
+    }
 
-        // Terminal cursor
-        if content.cursor.visible {
-            let cx = origin_x + content.cursor.col as f32 * cell_w;
-            let cy = origin_y + content.cursor.row as f32 * cell_h;
-            let mut fg = content.default_fg;
-            fg.a *= opacity;
-            out.push(FrameGlyph::Border {
-                x: cx, y: cy, width: cell_w, height: cell_h,
-                color: fg,
-            });
+    /// Overlay a terminal's `bounds` with its fading bell flash.
+    #[cfg(feature = "neo-term")]
+    fn push_bell_flash(
+        view: &crate::terminal::TerminalView,
+        bell: &crate::terminal::bell::BellConfig,
+        now: std::time::Instant,
+        bounds: Rect,
+        out: &mut Vec<FrameGlyph>,
+    ) {
+        if bell.visual != crate::terminal::bell::VisualBell::Flash {
+            return;
         }
+        let (Some(intensity), Some(content)) = (view.bell.intensity(now), view.content()) else {
+            return;
+        };
+        let mut color = content.default_fg;
+        color.a = 0.4 * intensity;
+        out.push(FrameGlyph::Stretch {
+            x: bounds.x, y: bounds.y, width: bounds.width, height: bounds.height,
+            bg: color, face_id: 0, is_overlay: true,
+        });
     }
 
     /// Apply extra line spacing and letter spacing to glyph positions.
@@ -2965,6 +5426,10 @@ impl RenderApp {
     }
 
     fn render(&mut self) {
+        if self.device_loss.is_lost() {
+            self.recover_lost_device();
+        }
+
         // Early return checks
         if self.current_frame.is_none()
             || self.surface.is_none()
@@ -2974,6 +5439,11 @@ impl RenderApp {
             return;
         }
 
+        let render_start = std::time::Instant::now();
+        self.watchdog.enter("prepare");
+        self.frame_clock.begin_frame(render_start);
+        self.throttle.frame_drawn(render_start);
+
         // FPS tracking
         if self.fps.enabled {
             self.fps.render_start = std::time::Instant::now();
@@ -2988,17 +5458,22 @@ impl RenderApp {
         }
 
         // Update terminals (expand terminal glyphs into renderable cells)
+        self.watchdog.mark("terminals");
         #[cfg(feature = "neo-term")]
         self.update_terminals();
 
         // Process webkit frames (import DMA-BUF to textures)
+        self.watchdog.mark("webkit");
         self.process_webkit_frames();
 
         // Process video frames
+        self.watchdog.mark("videos");
         self.process_video_frames();
 
         // Process pending image uploads (decoded images → GPU textures)
+        self.watchdog.mark("images");
         self.process_pending_images();
+        self.watchdog.mark("faces");
 
         // Update faces from frame data (the frame carries the full face map
         // set by the FFI side, including box/underline/overline attributes).
@@ -3010,7 +5485,9 @@ impl RenderApp {
             // Also update font families from the per-glyph font cache
             for (face_id, font_family) in &frame.face_fonts {
                 if let Some(face) = self.faces.get_mut(face_id) {
-                    face.font_family = font_family.clone();
+                    if face.font_family != **font_family {
+                        face.font_family = font_family.to_string();
+                    }
                 }
             }
             // Build/update Face entries from per-glyph data. This handles the
@@ -3038,7 +5515,9 @@ impl RenderApp {
                         face.attributes.remove(crate::core::face::FaceAttributes::ITALIC);
                     }
                     if let Some(family) = frame.face_fonts.get(face_id) {
-                        face.font_family = family.clone();
+                        if face.font_family != **family {
+                            face.font_family = family.to_string();
+                        }
                     }
                 }
             }
@@ -3056,9 +5535,13 @@ impl RenderApp {
         }
 
         // Get surface texture
+        let draw_start = std::time::Instant::now();
+        self.watchdog.enter("draw");
+        self.watchdog.mark("surface");
         let Some(surface) = self.surface.as_ref() else {
             return;
         };
+        let acquire_start = std::time::Instant::now();
         let output = match surface.get_current_texture() {
             Ok(output) => output,
             Err(wgpu::SurfaceError::Lost) => {
@@ -3077,38 +5560,42 @@ impl RenderApp {
             }
         };
 
-        let surface_view = output
+        let acquired = std::time::Instant::now();
+        let output_view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        // Build animated cursor override if applicable
-        let animated_cursor = if let (true, Some(target)) =
-            (self.cursor.anim_enabled, self.cursor.target.as_ref())
-        {
-            let corners = if self.cursor.anim_style == CursorAnimStyle::CriticallyDampedSpring
-                && self.cursor.animating
-            {
-                Some([
-                    (self.cursor.corner_springs[0].x, self.cursor.corner_springs[0].y),
-                    (self.cursor.corner_springs[1].x, self.cursor.corner_springs[1].y),
-                    (self.cursor.corner_springs[2].x, self.cursor.corner_springs[2].y),
-                    (self.cursor.corner_springs[3].x, self.cursor.corner_springs[3].y),
-                ])
-            } else {
-                None
-            };
-            Some(AnimatedCursor {
-                window_id: target.window_id,
-                x: self.cursor.current_x,
-                y: self.cursor.current_y,
-                width: self.cursor.current_w,
-                height: self.cursor.current_h,
-                corners,
-            })
-        } else {
-            None
+        // While zoomed, color filtered or inverted, draw into a frame-sized
+        // texture and copy it onto the surface at the end, magnifying the
+        // part around the zoom focus
+        let zoom_rect = self.zoom_source_rect();
+        let post_process = zoom_rect.is_some()
+            || self.effects.color_filter.is_active()
+            || self.effects.smart_invert.enabled;
+        if self.effects.smart_invert.enabled {
+            let keep = self.smart_invert_keep();
+            if let Some(renderer) = self.renderer.as_mut() {
+                renderer.smart_invert_keep = keep;
+            }
+        }
+        if !post_process {
+            self.post_target = None;
+        } else if self.post_target.is_none() {
+            if let Some(renderer) = self.renderer.as_ref() {
+                let (tex, view) = renderer.create_offscreen_texture(self.width, self.height);
+                let bg = renderer.create_texture_bind_group(&view);
+                self.post_target = Some((tex, view, bg));
+            }
+        }
+        let post_target = if post_process { self.post_target.take() } else { None };
+        let (surface_view, surface_texture) = match &post_target {
+            Some((tex, view, _)) => (view, tex),
+            None => (&output_view, &output.texture),
         };
 
+        // Build animated cursor override if applicable
+        let animated_cursor = self.cursor.animated();
+
         // Build background gradient option
         let bg_gradient = if self.effects.bg_gradient.enabled {
             Some((self.effects.bg_gradient.top, self.effects.bg_gradient.bottom))
@@ -3117,8 +5604,12 @@ impl RenderApp {
         };
 
         // Check if we need offscreen rendering (for transitions)
-        let need_offscreen = self.transitions.crossfade_enabled || self.transitions.scroll_enabled;
+        let need_offscreen = self.quality.level().transitions()
+            && (self.transitions.crossfade_enabled
+                || self.transitions.scroll_enabled
+                || self.transitions.text_scale_enabled);
 
+        self.watchdog.mark("glyphs");
         if need_offscreen {
             // Swap: previous ← current
             self.transitions.current_is_a = !self.transitions.current_is_a;
@@ -3134,10 +5625,16 @@ impl RenderApp {
                 let renderer = self.renderer.as_mut().expect("checked in render");
                 let glyph_atlas = self.glyph_atlas.as_mut().expect("checked in render");
                 renderer.set_idle_dim_alpha(self.idle_dim_current_alpha);
+                let backdrop = if self.transitions.current_is_a {
+                    self.transitions.offscreen_a.as_ref()
+                } else {
+                    self.transitions.offscreen_b.as_ref()
+                };
 
                 // SAFETY: current_view is valid for the duration of this block
                 renderer.render_frame_glyphs(
                     unsafe { &*current_view },
+                    backdrop.map(|(texture, _, _)| texture),
                     frame,
                     glyph_atlas,
                     &self.faces,
@@ -3160,14 +5657,15 @@ impl RenderApp {
                 let renderer = self.renderer.as_ref().expect("checked in render");
                 renderer.blit_texture_to_view(
                     unsafe { &*current_bg },
-                    &surface_view,
+                    surface_view,
                     self.width,
                     self.height,
                 );
             }
 
             // Composite active transitions on top
-            self.render_transitions(&surface_view);
+            self.watchdog.mark("transitions");
+            self.render_transitions(surface_view);
         } else {
             // Simple path: render directly to surface
             let frame = self.current_frame.as_ref().expect("checked in render");
@@ -3176,7 +5674,8 @@ impl RenderApp {
             renderer.set_idle_dim_alpha(self.idle_dim_current_alpha);
 
             renderer.render_frame_glyphs(
-                &surface_view,
+                surface_view,
+                Some(surface_texture),
                 frame,
                 glyph_atlas,
                 &self.faces,
@@ -3190,11 +5689,12 @@ impl RenderApp {
         }
 
         // Render breadcrumb/path bar overlay
+        self.watchdog.mark("overlays");
         if self.effects.breadcrumb.enabled {
             if let (Some(ref mut renderer), Some(ref mut glyph_atlas), Some(ref frame)) =
                 (&mut self.renderer, &mut self.glyph_atlas, &self.current_frame)
             {
-                renderer.render_breadcrumbs(&surface_view, frame, glyph_atlas);
+                renderer.render_breadcrumbs(surface_view, frame, glyph_atlas);
             }
         }
 
@@ -3204,7 +5704,7 @@ impl RenderApp {
                 (&self.renderer, &self.current_frame)
             {
                 renderer.render_scroll_indicators(
-                    &surface_view, &frame.window_infos,
+                    surface_view, &frame.window_infos,
                     self.width, self.height,
                 );
             }
@@ -3215,10 +5715,17 @@ impl RenderApp {
             if let (Some(ref renderer), Some(ref mut glyph_atlas), Some(ref frame)) =
                 (&self.renderer, &mut self.glyph_atlas, &self.current_frame)
             {
-                renderer.render_window_watermarks(&surface_view, frame, glyph_atlas);
+                renderer.render_window_watermarks(surface_view, frame, glyph_atlas);
             }
         }
 
+        // Placeholders over content with nothing to show yet
+        if let (Some(ref mut renderer), Some(ref mut glyph_atlas), Some(ref frame)) =
+            (&mut self.renderer, &mut self.glyph_atlas, &self.current_frame)
+        {
+            renderer.render_placeholders(surface_view, frame, glyph_atlas, self.width, self.height);
+        }
+
         // Render custom title bar when decorations are disabled (not in fullscreen)
         log::debug!("CSD state: decorations_enabled={} is_fullscreen={} titlebar_height={}",
             self.chrome.decorations_enabled, self.chrome.is_fullscreen, self.chrome.titlebar_height);
@@ -3229,7 +5736,7 @@ impl RenderApp {
                 let frame_bg = self.current_frame.as_ref()
                     .map(|f| (f.background.r, f.background.g, f.background.b));
                 renderer.render_custom_titlebar(
-                    &surface_view,
+                    surface_view,
                     &self.chrome.title,
                     self.chrome.titlebar_height,
                     self.chrome.titlebar_hover,
@@ -3241,20 +5748,104 @@ impl RenderApp {
             }
         }
 
+        // Controls of the inline video under the pointer
+        #[cfg(feature = "video")]
+        if let Some((id, rect)) = self.video_controls.shown(&self.frame_videos()) {
+            if let (Some(ref renderer), Some(ref mut glyph_atlas)) =
+                (&self.renderer, &mut self.glyph_atlas)
+            {
+                use crate::core::video_controls::{format_time, VideoControlsLayout, VideoStatus};
+                let video = renderer.get_video(id);
+                let duration = renderer.video_duration(id);
+                let status = VideoStatus {
+                    playing: renderer.get_video_state(id) == Some(crate::backend::wgpu::VideoState::Playing),
+                    progress: renderer.video_progress(id),
+                    buffered: renderer.video_buffered(id),
+                    time: duration.zip(video).map(|(d, v)| format_time(v.position, d)),
+                    volume: video.map_or(1.0, |v| v.volume),
+                };
+                renderer.render_video_controls(surface_view, &VideoControlsLayout::new(rect), &status, glyph_atlas);
+            }
+        }
+
+        // Render floating images
+        if !self.floating_images.is_empty() {
+            if let Some(ref renderer) = self.renderer {
+                renderer.render_floating_images(surface_view, &self.floating_images);
+            }
+        }
+
         // Render floating WebKit overlays on top of everything
         #[cfg(feature = "wpe-webkit")]
         if !self.floating_webkits.is_empty() {
             if let Some(ref renderer) = self.renderer {
-                renderer.render_floating_webkits(&surface_view, &self.floating_webkits);
+                renderer.render_floating_webkits(surface_view, &self.floating_webkits);
+            }
+        }
+
+        // Shader effects over the frame and floating layers
+        if self.effects.shader_effects.enabled && !self.shader_effects.is_empty() {
+            use crate::core::{shader_effect::ShaderTarget, types::Rect};
+            let windows = self.current_frame.as_ref().map(|f| f.window_infos.as_slice()).unwrap_or_default();
+            let effects: Vec<(Rect, &str, [f32; 4])> = self
+                .shader_effects
+                .iter()
+                .filter_map(|e| {
+                    let rect = match e.target {
+                        ShaderTarget::Region(rect) => rect,
+                        ShaderTarget::Window(id) => windows.iter().find(|w| w.window_id == id)?.bounds,
+                        ShaderTarget::Floating(id) => {
+                            let fi = self.floating_images.iter().find(|fi| fi.image_id == id)?;
+                            Rect::new(fi.x, fi.y, fi.width, fi.height)
+                        }
+                    };
+                    Some((rect, e.name.as_str(), e.params))
+                })
+                .collect();
+            if let Some(ref mut renderer) = self.renderer {
+                let strength = self.effects.shader_effects.strength;
+                if renderer.render_shader_effects(surface_texture, surface_view, &effects, &self.shader_effect_sources, strength) {
+                    self.frame_dirty = true;
+                }
+            }
+        }
+
+        // Picture-in-picture player above the floating layers
+        #[cfg(feature = "video")]
+        {
+            let frame = self.logical_frame_rect();
+            if let (Some(pip), Some(renderer)) = (self.pip.as_mut(), self.renderer.as_ref()) {
+                use crate::backend::wgpu::VideoState;
+                if let Some((w, h)) = renderer.get_video_size(pip.video_id).filter(|(w, h)| *w > 0 && *h > 0) {
+                    pip.aspect = h as f32 / w as f32;
+                }
+                let playing = renderer.get_video_state(pip.video_id) == Some(VideoState::Playing);
+                let progress = if pip.controls_shown() { renderer.video_progress(pip.video_id) } else { None };
+                renderer.render_pip(surface_view, pip, pip.rect(frame), playing, progress);
             }
         }
 
+        // Let the decode scheduler know which videos are on screen, and
+        // WebKit which views are
+        #[cfg(feature = "video")]
+        self.update_video_visibility();
+        self.update_webkit_visibility();
+
         // Render popup menu overlay (topmost layer)
         if let Some(ref menu) = self.popup_menu {
             if let (Some(ref renderer), Some(ref mut glyph_atlas)) =
                 (&self.renderer, &mut self.glyph_atlas)
             {
-                renderer.render_popup_menu(&surface_view, menu, glyph_atlas, self.width, self.height);
+                renderer.render_popup_menu(surface_view, menu, glyph_atlas, self.width, self.height);
+            }
+        }
+
+        // Render character grid overlay (emoji picker etc.)
+        if let Some(ref grid) = self.char_grid {
+            if let (Some(ref renderer), Some(ref mut glyph_atlas)) =
+                (&self.renderer, &mut self.glyph_atlas)
+            {
+                renderer.render_char_grid(surface_view, grid, glyph_atlas, self.width, self.height);
             }
         }
 
@@ -3263,7 +5854,7 @@ impl RenderApp {
             if let (Some(ref renderer), Some(ref mut glyph_atlas)) =
                 (&self.renderer, &mut self.glyph_atlas)
             {
-                renderer.render_tooltip(&surface_view, tip, glyph_atlas, self.width, self.height);
+                renderer.render_tooltip(surface_view, tip, glyph_atlas, self.width, self.height);
             }
         }
 
@@ -3273,7 +5864,7 @@ impl RenderApp {
                 (&self.renderer, &mut self.glyph_atlas, &self.cursor.target)
             {
                 renderer.render_ime_preedit(
-                    &surface_view,
+                    surface_view,
                     &self.ime_preedit_text,
                     target.x,
                     target.y,
@@ -3287,13 +5878,13 @@ impl RenderApp {
 
         // Render visual bell flash overlay (above everything)
         if let Some(start) = self.visual_bell_start {
-            let elapsed = start.elapsed().as_secs_f32();
+            let elapsed = self.clock.now().saturating_duration_since(start).as_secs_f32();
             let duration = 0.15; // 150ms flash
             if elapsed < duration {
                 let alpha = (1.0 - elapsed / duration) * 0.3; // max 30% opacity, fading out
                 if let Some(ref renderer) = self.renderer {
                     renderer.render_visual_bell(
-                        &surface_view,
+                        surface_view,
                         self.width, self.height,
                         alpha,
                     );
@@ -3304,6 +5895,13 @@ impl RenderApp {
             }
         }
 
+        // Overdraw or damage of the frame, for debugging
+        if self.effects.debug_overlay.mode != crate::core::scene_dump::DebugOverlay::None {
+            if let Some(ref renderer) = self.renderer {
+                renderer.render_debug_overlay(surface_view, self.width, self.height);
+            }
+        }
+
         // Render FPS counter overlay (topmost) with profiling stats
         if self.fps.enabled {
             // Measure frame time
@@ -3321,17 +5919,25 @@ impl RenderApp {
             let transition_count = self.transitions.crossfades.len() + self.transitions.scroll_slides.len();
 
             // Build multi-line stats text
-            let stats_lines = vec![
-                format!("{:.0} FPS | {:.1}ms", self.fps.display_value, self.fps.frame_time_ms),
+            let mut stats_lines = vec![
+                format!("{:.0} FPS | {:.1}ms | {:.1}ms render->present", self.fps.display_value,
+                    self.fps.frame_time_ms, self.frame_clock.render_to_present().as_secs_f32() * 1000.0),
                 format!("{}g {}w {}t  {}x{}", glyph_count, window_count,
                     transition_count, self.width, self.height),
             ];
+            if let Some(ref renderer) = self.renderer {
+                let o = renderer.overdraw_stats;
+                stats_lines.push(format!("bg {}->{} rects, {:.0}% area saved",
+                    o.rects_in, o.rects_out, o.area_saved() * 100.0));
+                let (hits, misses) = renderer.row_cache_stats();
+                stats_lines.push(format!("text rows {} reused, {} rebuilt", hits, misses));
+            }
 
             if let (Some(ref renderer), Some(ref mut glyph_atlas)) =
                 (&self.renderer, &mut self.glyph_atlas)
             {
                 renderer.render_fps_overlay(
-                    &surface_view,
+                    surface_view,
                     &stats_lines,
                     glyph_atlas,
                     self.width,
@@ -3366,7 +5972,7 @@ impl RenderApp {
             if let (Some(ref renderer), Some(ref mut glyph_atlas), Some(ref frame)) =
                 (&self.renderer, &mut self.glyph_atlas, &self.current_frame)
             {
-                renderer.render_typing_speed(&surface_view, frame, glyph_atlas, self.displayed_wpm);
+                renderer.render_typing_speed(surface_view, frame, glyph_atlas, self.displayed_wpm);
             }
             // Keep redrawing while WPM is decaying
             if self.displayed_wpm > 0.5 || !self.key_press_times.is_empty() {
@@ -3378,7 +5984,7 @@ impl RenderApp {
         if !self.chrome.decorations_enabled && !self.chrome.is_fullscreen && self.chrome.corner_radius > 0.0 {
             if let Some(ref renderer) = self.renderer {
                 renderer.render_corner_mask(
-                    &surface_view,
+                    surface_view,
                     self.chrome.corner_radius,
                     self.width,
                     self.height,
@@ -3386,8 +5992,65 @@ impl RenderApp {
             }
         }
 
-        // Present the frame
+        if let Some(target) = post_target {
+            if let Some(ref renderer) = self.renderer {
+                let sf = self.scale_factor as f32;
+                let rect = zoom_rect.unwrap_or(crate::core::types::Rect::new(
+                    0.0, 0.0, self.width as f32 / sf, self.height as f32 / sf,
+                ));
+                renderer.blit_post_processed(&target.2, &output_view, self.width, self.height, rect);
+            }
+            self.post_target = Some(target);
+        }
+
+        // Present the frame.  On Wayland this also requests a frame
+        // callback, so the next frame is paced by the compositor.
+        let present_start = std::time::Instant::now();
+        self.watchdog.enter("present");
+        if let Some(ref window) = self.window {
+            window.pre_present_notify();
+        }
         output.present();
+        let presented = std::time::Instant::now();
+        self.frame_clock.frame_presented(presented);
+        crate::core::input_latency::frame_presented();
+        self.publish_frame_stats(render_start, draw_start, present_start, presented);
+        // Waiting for a surface is pacing, not work
+        let work = acquire_start.saturating_duration_since(render_start)
+            + presented.saturating_duration_since(acquired);
+        self.update_quality(work);
+
+        self.watchdog.enter("memory-budget");
+        self.enforce_memory_budget();
+    }
+
+    /// Publish the statistics of the frame just presented, rendering of
+    /// which started at `render_start`, drawing at `draw_start` and
+    /// presenting at `present_start`.
+    fn publish_frame_stats(
+        &mut self,
+        render_start: std::time::Instant,
+        draw_start: std::time::Instant,
+        present_start: std::time::Instant,
+        presented: std::time::Instant,
+    ) {
+        let stats = &mut self.frame_stats;
+        stats.frames += 1;
+        if let Some(ref frame) = self.current_frame {
+            stats.glyphs = frame.glyphs.len();
+            stats.windows = frame.window_infos.len();
+        }
+        if let Some(ref renderer) = self.renderer {
+            stats.background_rects = renderer.overdraw_stats.rects_out;
+            (stats.row_cache_hits, stats.row_cache_misses) = renderer.row_cache_stats();
+        }
+        stats.prepare_time = draw_start.saturating_duration_since(render_start);
+        stats.draw_time = present_start.saturating_duration_since(draw_start);
+        stats.present_time = presented.saturating_duration_since(present_start);
+        if stats.total_time() > self.frame_clock.refresh_interval() {
+            stats.late_frames += 1;
+        }
+        frame_stats::publish(*stats);
     }
 
     /// Set the window icon from the embedded Neomacs logo PNG.
@@ -3482,6 +6145,270 @@ impl RenderApp {
         }
     }
 
+    /// Bounds of the frame in logical pixels
+    fn logical_frame_rect(&self) -> crate::core::types::Rect {
+        let sf = self.scale_factor as f32;
+        crate::core::types::Rect::new(0.0, 0.0, self.width as f32 / sf, self.height as f32 / sf)
+    }
+
+    /// Where images, videos and web views are drawn this frame, which the
+    /// smart invert leaves alone
+    fn smart_invert_keep(&self) -> Vec<Rect> {
+        let mut keep: Vec<Rect> = self.current_frame.iter()
+            .flat_map(|frame| frame.glyphs.iter())
+            .filter_map(|g| match *g {
+                FrameGlyph::Image { x, y, width, height, .. }
+                | FrameGlyph::Video { x, y, width, height, .. }
+                | FrameGlyph::WebKit { x, y, width, height, .. } => Some(Rect::new(x, y, width, height)),
+                _ => None,
+            })
+            .collect();
+        keep.extend(self.floating_images.iter().map(|i| Rect::new(i.x, i.y, i.width, i.height)));
+        #[cfg(feature = "wpe-webkit")]
+        keep.extend(self.floating_webkits.iter().map(|w| Rect::new(w.x, w.y, w.width, w.height)));
+        if let Some(ref pip) = self.pip {
+            keep.push(pip.rect(self.logical_frame_rect()));
+        }
+        keep
+    }
+
+    /// Left button pressed or released over the picture-in-picture
+    /// player.  Returns whether the player took the event.
+    fn pip_mouse_button(&mut self, pressed: bool) -> bool {
+        use crate::core::pip::PipPart;
+
+        let frame = self.logical_frame_rect();
+        let (x, y) = self.mouse_pos;
+        let Some(ref mut pip) = self.pip else {
+            return false;
+        };
+        if !pressed {
+            let had_pointer = pip.release(frame);
+            self.frame_dirty |= had_pointer;
+            return had_pointer;
+        }
+        let Some(part) = pip.press(x, y, frame) else {
+            return false;
+        };
+        let id = pip.video_id;
+        match part {
+            PipPart::PlayPause => {
+                #[cfg(feature = "video")]
+                if let Some(ref mut renderer) = self.renderer {
+                    use crate::backend::wgpu::VideoState;
+                    if renderer.get_video_state(id) == Some(VideoState::Playing) {
+                        renderer.video_pause(id);
+                    } else {
+                        renderer.video_play(id);
+                    }
+                }
+            }
+            PipPart::Seek(fraction) => {
+                #[cfg(feature = "video")]
+                if let Some(ref mut renderer) = self.renderer {
+                    renderer.video_seek(id, fraction);
+                }
+            }
+            PipPart::Close => {
+                self.pip = None;
+                self.comms.send_input(InputEvent::PipClosed { id });
+            }
+            PipPart::Video | PipPart::Resize => {}
+        }
+        self.frame_dirty = true;
+        true
+    }
+
+    /// Pointer moved to (`x`, `y`).  Returns whether the
+    /// picture-in-picture player has the pointer, as while it is dragged.
+    fn pip_motion(&mut self, x: f32, y: f32) -> bool {
+        let frame = self.logical_frame_rect();
+        let Some(ref mut pip) = self.pip else {
+            return false;
+        };
+        let was_hovered = pip.hovered;
+        let seek = pip.motion(x, y, frame);
+        let dragging = pip.is_dragging();
+        let id = pip.video_id;
+        if dragging || pip.hovered != was_hovered {
+            self.frame_dirty = true;
+        }
+        #[cfg(feature = "video")]
+        if let (Some(fraction), Some(renderer)) = (seek, self.renderer.as_mut()) {
+            renderer.video_seek(id, fraction);
+        }
+        dragging
+    }
+
+    /// Report the videos drawn this frame, with their screen area, so
+    /// off-screen ones are parked and visible ones resume
+    #[cfg(feature = "video")]
+    fn update_video_visibility(&mut self) {
+        let frame = self.logical_frame_rect();
+        let Some(ref mut renderer) = self.renderer else {
+            return;
+        };
+        let mut visible = match self.current_frame {
+            Some(ref frame_glyphs) => renderer.visible_videos(frame_glyphs, frame),
+            None => Vec::new(),
+        };
+        if let Some(ref pip) = self.pip {
+            let rect = pip.rect(frame);
+            visible.push((pip.video_id, rect.width * rect.height));
+        }
+        renderer.update_video_visibility(&visible);
+    }
+
+    /// Video glyphs of the current frame, as (video id, rect) pairs
+    fn frame_videos(&self) -> Vec<(u32, crate::core::types::Rect)> {
+        let Some(ref frame) = self.current_frame else {
+            return Vec::new();
+        };
+        frame.glyphs.iter().filter_map(|g| match g {
+            FrameGlyph::Video { video_id, x, y, width, height } => {
+                Some((*video_id, crate::core::types::Rect::new(*x, *y, *width, *height)))
+            }
+            _ => None,
+        }).collect()
+    }
+
+    /// Pointer moved to (`x`, `y`) with video controls on.  Returns
+    /// whether the controls have the pointer, as while a slider is dragged.
+    fn video_controls_motion(&mut self, x: f32, y: f32) -> bool {
+        if !self.video_controls.enabled && !self.video_controls.is_dragging() {
+            return false;
+        }
+        let videos = self.frame_videos();
+        let (changed, action) = self.video_controls.motion(x, y, &videos);
+        self.frame_dirty |= changed;
+        if let Some(action) = action {
+            self.apply_video_action(action);
+        }
+        self.video_controls.is_dragging()
+    }
+
+    /// Left button pressed or released with video controls on.  Returns
+    /// whether the controls took the event.
+    fn video_controls_button(&mut self, pressed: bool) -> bool {
+        if !pressed {
+            let held = self.video_controls.release();
+            self.frame_dirty |= held;
+            return held;
+        }
+        if !self.video_controls.enabled {
+            return false;
+        }
+        let (x, y) = self.mouse_pos;
+        let videos = self.frame_videos();
+        let Some(action) = self.video_controls.press(x, y, &videos) else {
+            return false;
+        };
+        self.apply_video_action(action);
+        true
+    }
+
+    /// The interactive image glyph at (`x`, `y`), as its image id and rect
+    fn interactive_image_at(&mut self, x: f32, y: f32) -> Option<(u32, crate::core::types::Rect)> {
+        let frame = self.current_frame.as_ref()?;
+        let renderer = self.renderer.as_mut()?;
+        frame.glyphs.iter().rev().find_map(|g| match g {
+            FrameGlyph::Image { image_id, x: gx, y: gy, width, height }
+                if x >= *gx && x < gx + width && y >= *gy && y < gy + height
+                    && renderer.image_transform_mut(*image_id).is_some() =>
+            {
+                Some((*image_id, crate::core::types::Rect::new(*gx, *gy, *width, *height)))
+            }
+            _ => None,
+        })
+    }
+
+    /// Wheel turned by `lines` over the pointer.  Returns whether an
+    /// interactive image took it and zoomed.
+    fn image_view_wheel(&mut self, lines: f32) -> bool {
+        use crate::core::image_view::ImageTransform;
+        let (x, y) = self.mouse_pos;
+        let Some((id, rect)) = self.interactive_image_at(x, y) else {
+            return false;
+        };
+        let at = ((x - rect.x) / rect.width, (y - rect.y) / rect.height);
+        if let Some(transform) = self.renderer.as_mut().and_then(|r| r.image_transform_mut(id)) {
+            transform.zoom_at(ImageTransform::wheel_factor(lines), at);
+        }
+        self.frame_dirty = true;
+        true
+    }
+
+    /// Left button pressed or released.  Returns whether an interactive
+    /// image took it: a press starts panning it, a second press soon
+    /// after fits it again.
+    fn image_view_button(&mut self, pressed: bool) -> bool {
+        use crate::core::image_view::DOUBLE_CLICK;
+        if !pressed {
+            return self.image_drag.take().is_some();
+        }
+        let (x, y) = self.mouse_pos;
+        let Some((id, _)) = self.interactive_image_at(x, y) else {
+            return false;
+        };
+        let now = std::time::Instant::now();
+        let double = matches!(self.image_click, Some((last, at)) if last == id && now - at < DOUBLE_CLICK);
+        if double {
+            if let Some(transform) = self.renderer.as_mut().and_then(|r| r.image_transform_mut(id)) {
+                transform.fit();
+            }
+            self.image_click = None;
+            self.frame_dirty = true;
+        } else {
+            self.image_click = Some((id, now));
+        }
+        self.image_drag = Some((id, (x, y)));
+        true
+    }
+
+    /// Pointer moved to (`x`, `y`).  Returns whether it is panning an
+    /// interactive image.
+    fn image_view_motion(&mut self, x: f32, y: f32) -> bool {
+        let Some((id, (last_x, last_y))) = self.image_drag else {
+            return false;
+        };
+        self.image_drag = Some((id, (x, y)));
+        // Pan by the size of the glyph the image is shown in
+        let rect = self.current_frame.as_ref().and_then(|frame| {
+            frame.glyphs.iter().find_map(|g| match g {
+                FrameGlyph::Image { image_id, width, height, .. } if *image_id == id => Some((*width, *height)),
+                _ => None,
+            })
+        });
+        if let (Some((width, height)), Some(transform)) =
+            (rect, self.renderer.as_mut().and_then(|r| r.image_transform_mut(id)))
+        {
+            if width > 0.0 && height > 0.0 {
+                transform.pan(((x - last_x) / width, (y - last_y) / height));
+                self.frame_dirty = true;
+            }
+        }
+        true
+    }
+
+    fn apply_video_action(&mut self, action: crate::core::video_controls::VideoAction) {
+        use crate::core::video_controls::VideoAction;
+        self.frame_dirty = true;
+        #[cfg(feature = "video")]
+        if let Some(ref mut renderer) = self.renderer {
+            match action {
+                VideoAction::TogglePlay(id) => {
+                    if renderer.get_video_state(id) == Some(crate::backend::wgpu::VideoState::Playing) {
+                        renderer.video_pause(id);
+                    } else {
+                        renderer.video_play(id);
+                    }
+                }
+                VideoAction::Seek(id, fraction) => renderer.video_seek(id, fraction),
+                VideoAction::SetVolume(id, volume) => renderer.video_set_volume(id, volume),
+            }
+        }
+    }
+
     /// Title bar button width in logical pixels.
     const TITLEBAR_BUTTON_WIDTH: f32 = 46.0;
 
@@ -3521,6 +6448,12 @@ impl ApplicationHandler for RenderApp {
                 .with_title(&self.title)
                 .with_inner_size(winit::dpi::LogicalSize::new(self.width, self.height))
                 .with_transparent(true);
+            // Let Option act as Meta instead of composing characters
+            #[cfg(target_os = "macos")]
+            let attrs = attrs.with_option_as_alt(OptionAsAlt::Both);
+            // The accessibility adapter must attach before the window is shown
+            #[cfg(feature = "accessibility")]
+            let attrs = attrs.with_visible(false);
 
             match event_loop.create_window(attrs) {
                 Ok(window) => {
@@ -3529,6 +6462,9 @@ impl ApplicationHandler for RenderApp {
                     // Read scale factor once at launch
                     self.scale_factor = window.scale_factor();
                     log::info!("Display scale factor: {}", self.scale_factor);
+                    if let Some(mhz) = window.current_monitor().and_then(|m| m.refresh_rate_millihertz()) {
+                        self.frame_clock.set_refresh_rate_millihertz(mhz);
+                    }
 
                     // Update width/height to physical pixels for surface config
                     let phys = window.inner_size();
@@ -3538,13 +6474,29 @@ impl ApplicationHandler for RenderApp {
 
                     // Initialize wgpu with the window
                     self.init_wgpu(window.clone());
+                    self.apply_all_display_options();
 
                     // Enable IME input for CJK and compose support
                     window.set_ime_allowed(true);
 
+                    // Tell Lisp the system appearance to start with
+                    if let Some(theme) = window.theme() {
+                        self.comms.send_input(InputEvent::AppearanceChanged {
+                            dark: theme == winit::window::Theme::Dark,
+                        });
+                    }
+
                     // Set window icon from embedded Emacs icon
                     Self::set_window_icon(&window);
 
+                    #[cfg(feature = "accessibility")]
+                    {
+                        self.accessibility = Some(crate::backend::wgpu::AccessibilityBridge::new(
+                            &window, &self.title,
+                        ));
+                        window.set_visible(true);
+                    }
+
                     self.window = Some(window);
                 }
                 Err(e) => {
@@ -3603,6 +6555,11 @@ impl ApplicationHandler for RenderApp {
         _window_id: WindowId,
         event: WindowEvent,
     ) {
+        #[cfg(feature = "accessibility")]
+        if let (Some(bridge), Some(window)) = (self.accessibility.as_mut(), self.window.as_ref()) {
+            bridge.process_event(window, &event);
+        }
+
         match event {
             WindowEvent::CloseRequested => {
                 log::info!("Window close requested");
@@ -3626,8 +6583,36 @@ impl ApplicationHandler for RenderApp {
                 });
             }
 
+            WindowEvent::Occluded(occluded) => {
+                self.throttle.set_occluded(occluded);
+                self.update_webkit_visibility();
+                if !occluded {
+                    self.frame_dirty = true;
+                }
+            }
+
+            WindowEvent::ThemeChanged(theme) => {
+                log::info!("System appearance changed to {:?}", theme);
+                self.comms.send_input(InputEvent::AppearanceChanged {
+                    dark: theme == winit::window::Theme::Dark,
+                });
+            }
+
             WindowEvent::Focused(focused) => {
+                self.window_focused = focused;
+                self.throttle.set_focused(focused);
                 self.comms.send_input(InputEvent::WindowFocus { focused });
+                // Background videos only play while the frame has focus
+                #[cfg(feature = "video")]
+                if let Some(ref mut renderer) = self.renderer {
+                    for id in renderer.background_video_ids() {
+                        if focused {
+                            renderer.video_play(id);
+                        } else {
+                            renderer.video_pause(id);
+                        }
+                    }
+                }
             }
 
             WindowEvent::KeyboardInput {
@@ -3737,11 +6722,17 @@ impl ApplicationHandler for RenderApp {
                         if self.effects.idle_dim.enabled {
                             self.last_activity_time = std::time::Instant::now();
                         }
+                        if state == ElementState::Pressed {
+                            crate::core::input_latency::input_sent();
+                        }
                         self.comms.send_input(InputEvent::Key {
                             keysym,
                             modifiers: self.modifiers,
                             pressed: state == ElementState::Pressed,
                         });
+                        if state == ElementState::Pressed {
+                            self.predict_cursor(keysym);
+                        }
                     }
                 }
             }
@@ -3847,6 +6838,18 @@ impl ApplicationHandler for RenderApp {
                     if let Some(ref window) = self.window {
                         let _ = window.drag_window();
                     }
+                } else if button == MouseButton::Left
+                    && self.pip_mouse_button(state == ElementState::Pressed)
+                {
+                    // Handled by the picture-in-picture player
+                } else if button == MouseButton::Left
+                    && self.video_controls_button(state == ElementState::Pressed)
+                {
+                    // Handled by the controls of an inline video
+                } else if button == MouseButton::Left
+                    && self.image_view_button(state == ElementState::Pressed)
+                {
+                    // Panning or fitting an interactive image
                 } else {
                     let btn = match button {
                         MouseButton::Left => 1,
@@ -3856,6 +6859,10 @@ impl ApplicationHandler for RenderApp {
                         MouseButton::Forward => 5,
                         MouseButton::Other(n) => n as u32,
                     };
+                    if state == ElementState::Pressed {
+                        crate::core::input_latency::input_sent();
+                        self.interrupt_prediction();
+                    }
                     self.comms.send_input(InputEvent::MouseButton {
                         button: btn,
                         x: self.mouse_pos.0,
@@ -3866,7 +6873,7 @@ impl ApplicationHandler for RenderApp {
                     // Click halo effect on press
                     if state == ElementState::Pressed && self.effects.click_halo.enabled {
                         if let Some(renderer) = self.renderer.as_mut() {
-                            renderer.trigger_click_halo(self.mouse_pos.0, self.mouse_pos.1, std::time::Instant::now());
+                            renderer.trigger_click_halo(self.mouse_pos.0, self.mouse_pos.1, self.clock.now());
                         }
                         self.frame_dirty = true;
                     }
@@ -3953,7 +6960,10 @@ impl ApplicationHandler for RenderApp {
                             }
                         }
                     }
-                } else {
+                } else if !self.pip_motion(lx, ly)
+                    && !self.video_controls_motion(lx, ly)
+                    && !self.image_view_motion(lx, ly)
+                {
                     self.comms.send_input(InputEvent::MouseMove {
                         x: lx,
                         y: ly,
@@ -3974,6 +6984,11 @@ impl ApplicationHandler for RenderApp {
                          true)
                     }
                 };
+                // Interactive images zoom instead of scrolling the window
+                let lines = if pixel_precise { dy / crate::core::image_view::PIXELS_PER_LINE } else { dy };
+                if lines != 0.0 && self.image_view_wheel(lines) {
+                    return;
+                }
                 self.comms.send_input(InputEvent::MouseScroll {
                     delta_x: dx,
                     delta_y: dy,
@@ -3986,6 +7001,7 @@ impl ApplicationHandler for RenderApp {
 
             WindowEvent::RedrawRequested => {
                 self.render();
+                self.watchdog.leave();
                 self.frame_dirty = false;
             }
 
@@ -4020,9 +7036,11 @@ impl ApplicationHandler for RenderApp {
                         // Send each committed character as an individual
                         // key event to Emacs (no modifiers — IME already
                         // composed the final characters)
+                        self.interrupt_prediction();
                         for ch in text.chars() {
                             let keysym = ch as u32;
                             if keysym != 0 {
+                                crate::core::input_latency::input_sent();
                                 self.comms.send_input(InputEvent::Key {
                                     keysym,
                                     modifiers: 0,
@@ -4077,10 +7095,19 @@ impl ApplicationHandler for RenderApp {
                 if let Some(ref mut renderer) = self.renderer {
                     renderer.set_scale_factor(scale_factor as f32);
                 }
-                // Clear glyph atlas so text re-rasterizes at new DPI
+                // Switch the glyph atlas to glyphs rasterized for the new
+                // scale, keeping the old ones for when the window moves back
                 if let Some(ref mut atlas) = self.glyph_atlas {
                     atlas.set_scale_factor(scale_factor as f32);
                 }
+                // A new scale usually means a new monitor, maybe with
+                // another refresh rate
+                if let Some(mhz) = self.window.as_ref()
+                    .and_then(|w| w.current_monitor())
+                    .and_then(|m| m.refresh_rate_millihertz())
+                {
+                    self.frame_clock.set_refresh_rate_millihertz(mhz);
+                }
                 self.frame_dirty = true;
                 // The Resized event will follow, which handles surface reconfiguration
             }
@@ -4091,13 +7118,28 @@ impl ApplicationHandler for RenderApp {
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         // Check for shutdown
+        self.watchdog.enter("commands");
         if self.process_commands() {
+            self.watchdog.leave();
             event_loop.exit();
             return;
         }
 
         // Get latest frame from Emacs
+        self.watchdog.enter("frame");
         self.poll_frame();
+        self.watchdog.enter("animations");
+
+        if self.config_watcher.poll(std::time::Instant::now()) {
+            self.reload_display_config();
+        }
+
+        // Tell Emacs about newly reported errors
+        let last_error = error_report::last_id();
+        if last_error != self.announced_error {
+            self.announced_error = last_error;
+            self.comms.send_input(InputEvent::DisplayError { id: last_error });
+        }
 
         // Pump GLib for WebKit
         self.pump_glib();
@@ -4107,16 +7149,44 @@ impl ApplicationHandler for RenderApp {
             self.frame_dirty = true;
         }
 
+        let present_at = self.animation_time();
+
         // Tick cursor animation
-        if self.cursor.tick_animation() {
+        if self.cursor.tick_animation(present_at) {
             self.frame_dirty = true;
         }
 
         // Tick cursor size transition (runs after position animation, overrides w/h)
-        if self.cursor.tick_size_animation() {
+        if self.cursor.tick_size_animation(present_at) {
+            self.frame_dirty = true;
+        }
+
+        // Tick floating element property animations
+        if self.floating_animations.is_active() {
+            for (kind, id, property, v) in self.floating_animations.tick(present_at) {
+                if let Some(value) = self.floating_property_mut(kind, id, property) {
+                    *value = v;
+                }
+            }
             self.frame_dirty = true;
         }
 
+        // Tell the host about animations it asked to hear the end of
+        let mut completed = self.floating_animations.take_finished();
+        if !self.animation_watch.is_empty() {
+            let now = self.clock.now();
+            let (crossfades, zoom) = (&self.transitions.crossfades, &self.zoom);
+            completed.extend(self.animation_watch.finished(|part| match *part {
+                WatchedAnimation::Crossfade(wid, started) => {
+                    crossfades.get(&wid).is_some_and(|t| t.started == started)
+                }
+                WatchedAnimation::Zoom => zoom.is_animating(now),
+            }));
+        }
+        for id in completed {
+            self.comms.send_input(InputEvent::AnimationCompleted { id });
+        }
+
         // Tick idle dimming
         if self.effects.idle_dim.enabled {
             let idle_time = self.last_activity_time.elapsed();
@@ -4164,13 +7234,34 @@ impl ApplicationHandler for RenderApp {
             self.frame_dirty = true;
         }
 
-        // Check for terminal PTY activity
-        if self.has_terminal_activity() {
+        // Keep dirty while the frame zoom changes
+        if self.zoom.is_animating(self.clock.now()) {
+            self.frame_dirty = true;
+        }
+
+        // Advance the ambient layer at its own low frame rate, and not at
+        // all while the window is in the background
+        let ambient_due = (self.effects.ambient.is_active() && self.window_focused)
+            .then(|| self.ambient_last_frame + self.effects.ambient.frame_interval());
+        if ambient_due.is_some_and(|due| std::time::Instant::now() >= due) {
+            self.ambient_last_frame = std::time::Instant::now();
             self.frame_dirty = true;
         }
 
-        // Determine if continuous rendering is needed
-        let has_active_content = self.has_webkit_needing_redraw() || self.has_playing_videos();
+        // Terminal output, playing videos and WebKit paints ask for frames
+        // as often as the content throttle lets them: at once when on
+        // screen in a focused frame, less often in an unfocused one, and
+        // not at all when off screen or hidden.
+        use crate::core::content_throttle::ContentKind;
+        let content_due = [
+            (ContentKind::Terminal, self.terminal_activity()),
+            (ContentKind::Video, self.has_playing_videos().then_some(true)),
+            (ContentKind::WebKit, self.webkit_activity()),
+        ]
+        .into_iter()
+        .filter_map(|(kind, visible)| self.throttle.next_frame(kind, visible?))
+        .min();
+        let has_active_content = content_due.is_some_and(|due| std::time::Instant::now() >= due);
 
         // Request redraw when we have new frame data, cursor blink toggled,
         // or webkit/video content changed
@@ -4180,12 +7271,23 @@ impl ApplicationHandler for RenderApp {
             }
         }
 
+        // Run cache maintenance while nothing is being drawn
+        if self.frame_dirty || has_active_content || self.transitions.has_active() {
+            self.idle.mark_busy();
+        } else if let Some(task) = self.idle.next_task(std::time::Instant::now()) {
+            self.watchdog.enter("idle-task");
+            self.run_idle_task(task);
+        }
+        self.watchdog.leave();
+
         // Use WaitUntil with smart timeouts instead of Poll to save CPU.
         // Window events (key, mouse, resize) still wake immediately.
         let now = std::time::Instant::now();
-        let next_wake = if self.frame_dirty || has_active_content
+        let mut next_wake = if self.frame_dirty || has_active_content
             || self.cursor.animating || self.cursor.size_animating
             || self.idle_dim_active || self.transitions.has_active()
+            || self.floating_animations.is_active()
+            || !self.animation_watch.is_empty()
         {
             // Active rendering: cap at ~240fps to avoid spinning
             now + std::time::Duration::from_millis(4)
@@ -4196,11 +7298,68 @@ impl ApplicationHandler for RenderApp {
             // Fully idle: poll for new Emacs frames at 60fps
             now + std::time::Duration::from_millis(16)
         };
+        if self.effects.ambient.is_active() && self.window_focused {
+            next_wake = next_wake.min(self.ambient_last_frame + self.effects.ambient.frame_interval());
+        }
+        if let Some(due) = content_due {
+            next_wake = next_wake.min(due.max(now));
+        }
         event_loop.set_control_flow(ControlFlow::WaitUntil(next_wake));
     }
 }
 
+/// Encode the RGBA frames of an effect preview, `width` x `height`, as
+/// they arrive on `frames`, showing frame i for `delays[i]` ms in a GIF.
+/// Returns the number of frames written.
+fn write_effect_preview(
+    output: &PreviewOutput,
+    width: u32,
+    height: u32,
+    delays: &[u32],
+    frames: std::sync::mpsc::Receiver<Vec<u8>>,
+) -> Result<usize, String> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+
+    let mut count = 0;
+    match output {
+        PreviewOutput::Gif(path) => {
+            let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+            let mut encoder = GifEncoder::new_with_speed(std::io::BufWriter::new(file), 10);
+            encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+            for (pixels, &delay) in frames.iter().zip(delays) {
+                let image = image::RgbaImage::from_raw(width, height, pixels)
+                    .ok_or_else(|| "frame of the wrong size".to_string())?;
+                let delay = image::Delay::from_numer_denom_ms(delay, 1);
+                encoder
+                    .encode_frame(image::Frame::from_parts(image, 0, 0, delay))
+                    .map_err(|e| e.to_string())?;
+                count += 1;
+            }
+        }
+        PreviewOutput::PngFrames(dir) => {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            for pixels in frames.iter() {
+                let path = PreviewOutput::png_frame_path(dir, count);
+                image::save_buffer(&path, &pixels, width, height, image::ColorType::Rgba8)
+                    .map_err(|e| e.to_string())?;
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
 /// Run the render loop (called on render thread)
+/// Whether the current thread is the process's main thread
+#[cfg(target_os = "macos")]
+fn is_main_thread() -> bool {
+    extern "C" {
+        fn pthread_main_np() -> std::os::raw::c_int;
+    }
+    // SAFETY: pthread_main_np has no preconditions
+    unsafe { pthread_main_np() != 0 }
+}
+
 fn run_render_loop(
     comms: RenderComms,
     width: u32,
@@ -4208,8 +7367,12 @@ fn run_render_loop(
     title: String,
     image_dimensions: SharedImageDimensions,
     shared_monitors: SharedMonitorInfo,
+    cluster_offsets: SharedClusterOffsets,
+    display_options: SharedOptionRegistry,
     #[cfg(feature = "neo-term")]
     shared_terminals: crate::terminal::SharedTerminals,
+    #[cfg(feature = "pdf")]
+    shared_pdfs: crate::backend::wgpu::SharedPdfDocuments,
 ) {
     log::info!("Render thread starting");
 
@@ -4236,15 +7399,34 @@ fn run_render_loop(
     #[cfg(target_os = "linux")]
     let event_loop = {
         let mut builder = EventLoopBuilder::new();
-        // Try Wayland first, fall back to X11
-        if std::env::var("WAYLAND_DISPLAY").is_ok() {
+        // Try Wayland first, fall back to X11.  NEOMACS_WINIT_BACKEND=x11
+        // forces X11, e.g. to run under XWayland.
+        // The display config's `backend` option does the same.
+        let backend = std::env::var("NEOMACS_WINIT_BACKEND").ok().or_else(|| {
+            let options = display_options.lock().ok()?;
+            options.get("backend").map(|backend| backend.to_string())
+        });
+        let force_x11 = backend.as_deref() == Some("x11");
+        if std::env::var("WAYLAND_DISPLAY").is_ok() && !force_x11 {
             EventLoopBuilderExtWayland::with_any_thread(&mut builder, true);
         } else {
+            EventLoopBuilderExtX11::with_x11(&mut builder);
             EventLoopBuilderExtX11::with_any_thread(&mut builder, true);
         }
         builder.build().expect("Failed to create event loop")
     };
-    #[cfg(not(target_os = "linux"))]
+    #[cfg(target_os = "windows")]
+    let event_loop = EventLoopBuilder::new()
+        .with_any_thread(true)
+        .build()
+        .expect("Failed to create event loop");
+    // AppKit only runs on the main thread, which belongs to Emacs
+    #[cfg(target_os = "macos")]
+    if !is_main_thread() {
+        log::error!("Render thread: macOS needs the event loop on the main thread; no window created");
+        return;
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
     let event_loop = EventLoop::new().expect("Failed to create event loop");
 
     // Start with WaitUntil to avoid busy-polling; about_to_wait() adjusts dynamically
@@ -4254,14 +7436,22 @@ fn run_render_loop(
 
     let mut app = RenderApp::new(
         comms, width, height, title, image_dimensions,
-        shared_monitors,
+        shared_monitors, cluster_offsets, display_options,
         #[cfg(feature = "neo-term")]
         shared_terminals,
+        #[cfg(feature = "pdf")]
+        shared_pdfs,
     );
 
+    let watchdog = app.watchdog.clone();
+    if let Err(e) = watchdog.spawn() {
+        log::warn!("Render thread: no stall watchdog: {}", e);
+    }
+
     if let Err(e) = event_loop.run_app(&mut app) {
         log::error!("Event loop error: {:?}", e);
     }
+    watchdog.stop();
 
     log::info!("Render thread exiting");
 }
@@ -4269,7 +7459,32 @@ fn run_render_loop(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::thread_comm::ThreadComms;
+    use crate::core::clock::{Clock, ScriptedClock};
+    use crate::thread_comm::{EmacsComms, ThreadComms};
+    use std::time::Duration;
+
+    /// Render thread state without a window, timed by `clock`
+    fn scripted_app(clock: &ScriptedClock) -> (EmacsComms, RenderApp) {
+        let comms = ThreadComms::new().expect("Failed to create ThreadComms");
+        let (emacs, render) = comms.split();
+        let mut app = RenderApp::new(
+            render, 800, 600, String::new(),
+            Default::default(), Default::default(), Default::default(), Default::default(),
+            #[cfg(feature = "neo-term")]
+            Default::default(),
+            #[cfg(feature = "pdf")]
+            Default::default(),
+        );
+        app.clock = clock.shared();
+        app.cursor = CursorState::starting_at(clock.now());
+        (emacs, app)
+    }
+
+    fn frame_with_cursor(x: f32) -> FrameGlyphBuffer {
+        let mut frame = FrameGlyphBuffer::with_size(800.0, 600.0);
+        frame.add_cursor(1, x, 0.0, 8.0, 16.0, 0, Color::WHITE);
+        frame
+    }
 
     #[test]
     fn test_translate_key_named() {
@@ -4329,4 +7544,71 @@ mod tests {
         assert!(emacs.input_rx.is_empty());
         assert!(render.cmd_rx.is_empty());
     }
+
+    #[test]
+    fn test_cursor_motion_follows_the_app_clock() {
+        let clock = ScriptedClock::new();
+        let (emacs, mut app) = scripted_app(&clock);
+        app.cursor.anim_style = CursorAnimStyle::Linear;
+        app.cursor.anim_duration = 0.1;
+        emacs.frame_tx.send(frame_with_cursor(0.0)).unwrap();
+        app.poll_frame();
+        emacs.frame_tx.send(frame_with_cursor(100.0)).unwrap();
+        app.poll_frame();
+        assert!(app.cursor.animating);
+
+        clock.advance(Duration::from_millis(50));
+        assert!(app.cursor.tick_animation(app.animation_time()));
+        assert!((app.cursor.current_x - 50.0).abs() < 1e-3);
+        clock.advance(Duration::from_millis(50));
+        assert!(app.cursor.tick_animation(app.animation_time()));
+        assert_eq!(app.cursor.current_x, 100.0);
+        assert!(!app.cursor.animating);
+    }
+
+    #[test]
+    fn test_cursor_spring_steps_deterministically() {
+        let run = || {
+            let clock = ScriptedClock::new();
+            let (emacs, mut app) = scripted_app(&clock);
+            emacs.frame_tx.send(frame_with_cursor(0.0)).unwrap();
+            app.poll_frame();
+            emacs.frame_tx.send(frame_with_cursor(200.0)).unwrap();
+            app.poll_frame();
+            let mut steps = Vec::new();
+            while app.cursor.animating {
+                clock.advance(Duration::from_millis(16));
+                app.cursor.tick_animation(app.animation_time());
+                steps.push(app.cursor.current_x);
+            }
+            steps
+        };
+
+        // Every run of the same frames lands on the same positions
+        let steps = run();
+        assert_eq!(steps, run());
+        assert!(steps.len() > 1);
+        assert!(steps.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(steps.last(), Some(&200.0));
+    }
+
+    #[test]
+    fn test_cursor_blink_follows_the_app_clock() {
+        let clock = ScriptedClock::new();
+        let (emacs, mut app) = scripted_app(&clock);
+        clock.advance(Duration::from_secs(1));
+        emacs.frame_tx.send(frame_with_cursor(0.0)).unwrap();
+        app.poll_frame();
+
+        // A new frame shows the cursor for a whole interval
+        clock.advance(Duration::from_millis(499));
+        assert!(!app.tick_cursor_blink());
+        assert!(app.cursor.blink_on);
+        clock.advance(Duration::from_millis(1));
+        assert!(app.tick_cursor_blink());
+        assert!(!app.cursor.blink_on);
+        clock.advance(Duration::from_millis(500));
+        assert!(app.tick_cursor_blink());
+        assert!(app.cursor.blink_on);
+    }
 }
@@ -0,0 +1,133 @@
+//! Terminal bell: visual flash, rate limiting and host notification.
+//!
+//! When a program in a terminal writes BEL, the terminal region briefly
+//! flashes (or has its colors inverted) and Emacs is told so it can run
+//! hooks; if the Neomacs window is not focused the window manager is
+//! also asked for attention (taskbar/dock urgency).  Programs that ring
+//! the bell in a tight loop are rate limited so the terminal does not
+//! strobe.
+
+use std::time::{Duration, Instant};
+
+/// How a bell is shown in the terminal region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VisualBell {
+    /// No visual effect
+    None,
+    /// Overlay the region with the foreground color, fading out
+    #[default]
+    Flash,
+    /// Swap foreground and background colors while the bell lasts
+    Invert,
+}
+
+impl VisualBell {
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "none" | "off" | "nil" => Self::None,
+            "invert" | "reverse" => Self::Invert,
+            _ => Self::Flash,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Flash => "flash",
+            Self::Invert => "invert",
+        }
+    }
+}
+
+/// Bell settings shared by all terminals
+#[derive(Debug, Clone, Copy)]
+pub struct BellConfig {
+    pub visual: VisualBell,
+    /// How long the visual bell lasts
+    pub duration: Duration,
+    /// Bells closer together than this are ignored
+    pub min_interval: Duration,
+    /// Ask the window manager for attention when the window is unfocused
+    pub urgency: bool,
+}
+
+impl Default for BellConfig {
+    fn default() -> Self {
+        Self {
+            visual: VisualBell::Flash,
+            duration: Duration::from_millis(150),
+            min_interval: Duration::from_millis(500),
+            urgency: true,
+        }
+    }
+}
+
+/// Bell state of one terminal
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BellState {
+    last_ring: Option<Instant>,
+    /// Start and end of the visual bell being shown
+    showing: Option<(Instant, Instant)>,
+}
+
+impl BellState {
+    /// Handle a BEL at `now`.  Returns false if it was dropped by the
+    /// rate limiter, in which case nothing should be shown or sent.
+    pub fn ring(&mut self, now: Instant, config: &BellConfig) -> bool {
+        if self
+            .last_ring
+            .is_some_and(|t| now.duration_since(t) < config.min_interval)
+        {
+            return false;
+        }
+        self.last_ring = Some(now);
+        if config.visual != VisualBell::None && !config.duration.is_zero() {
+            self.showing = Some((now, now + config.duration));
+        }
+        true
+    }
+
+    /// Whether the visual bell is still being shown at `now`.
+    pub fn is_showing(&self, now: Instant) -> bool {
+        self.showing.is_some_and(|(_, end)| now < end)
+    }
+
+    /// Strength of the visual bell at `now`, fading from 1 to 0, or None
+    /// once it is over.
+    pub fn intensity(&self, now: Instant) -> Option<f32> {
+        let (start, end) = self.showing?;
+        if now >= end {
+            return None;
+        }
+        let total = end.duration_since(start).as_secs_f32();
+        let left = end.duration_since(now).as_secs_f32();
+        Some((left / total).clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bell_rate_limit_and_fade() {
+        let config = BellConfig::default();
+        let mut bell = BellState::default();
+        let t = Instant::now();
+
+        assert!(bell.ring(t, &config));
+        assert_eq!(bell.intensity(t), Some(1.0));
+        let half = bell.intensity(t + config.duration / 2).unwrap();
+        assert!((half - 0.5).abs() < 0.01);
+        assert!(!bell.is_showing(t + config.duration));
+
+        // A bell storm is collapsed into one bell per interval
+        assert!(!bell.ring(t + config.min_interval / 2, &config));
+        assert!(bell.ring(t + config.min_interval, &config));
+
+        let quiet = BellConfig { visual: VisualBell::None, ..config };
+        let mut bell = BellState::default();
+        assert!(bell.ring(t, &quiet));
+        assert!(!bell.is_showing(t));
+    }
+}
@@ -85,6 +85,45 @@ pub fn ansi_to_color(
     }
 }
 
+/// Default colors and the 16 standard colors used for terminal content
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalTheme {
+    pub foreground: Color,
+    pub background: Color,
+    pub palette: [Color; 16],
+}
+
+impl Default for TerminalTheme {
+    fn default() -> Self {
+        let mut palette = [Color::BLACK; 16];
+        palette.copy_from_slice(&COLOR_256[..16]);
+        Self {
+            foreground: Color::WHITE,
+            background: Color::BLACK,
+            palette,
+        }
+    }
+}
+
+impl TerminalTheme {
+    /// Resolve `color` through the theme; colors beyond the standard 16
+    /// come from the fixed 256-color palette
+    pub fn color(&self, color: &AnsiColor) -> Color {
+        match color {
+            AnsiColor::Indexed(idx) if (*idx as usize) < 16 => self.palette[*idx as usize],
+            AnsiColor::Named(named) => {
+                let idx = *named as usize;
+                if idx < 16 {
+                    self.palette[idx]
+                } else {
+                    named_to_color(*named, &self.foreground, &self.background)
+                }
+            }
+            _ => ansi_to_color(color, &self.foreground, &self.background),
+        }
+    }
+}
+
 /// Convert a named ANSI color to neomacs Color.
 fn named_to_color(named: NamedColor, default_fg: &Color, default_bg: &Color) -> Color {
     match named {
@@ -136,6 +175,18 @@ mod tests {
         assert!((c.g - 64.0 / 255.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_theme_overrides_standard_colors() {
+        let mut theme = TerminalTheme::default();
+        theme.palette[1] = Color::WHITE;
+        theme.background = Color::WHITE;
+        assert_eq!(theme.color(&AnsiColor::Named(NamedColor::Red)), Color::WHITE);
+        assert_eq!(theme.color(&AnsiColor::Indexed(1)), Color::WHITE);
+        assert_eq!(theme.color(&AnsiColor::Named(NamedColor::Background)), Color::WHITE);
+        // The extended palette is not themed
+        assert_eq!(theme.color(&AnsiColor::Indexed(16)), COLOR_256[16]);
+    }
+
     #[test]
     fn test_indexed_color() {
         let fg = Color::WHITE;
@@ -148,6 +199,42 @@ mod tests {
         assert!(white.r > 0.99);
     }
 
+    #[test]
+    fn test_every_color_converts_in_range() {
+        let (fg, bg) = (Color::WHITE, Color::BLACK);
+        let theme = TerminalTheme::default();
+        for idx in 0..=255u8 {
+            let c = ansi_to_color(&AnsiColor::Indexed(idx), &fg, &bg);
+            assert!([c.r, c.g, c.b].iter().all(|v| (0.0..=1.0).contains(v)), "index {}", idx);
+            assert_eq!(c.a, 1.0);
+            // The default theme is the standard palette
+            assert_eq!(theme.color(&AnsiColor::Indexed(idx)), c);
+
+            // 24-bit colors survive the conversion to floats and back
+            let rgb = alacritty_terminal::vte::ansi::Rgb { r: idx, g: 255 - idx, b: idx.wrapping_mul(7) };
+            let c = ansi_to_color(&AnsiColor::Spec(rgb), &fg, &bg);
+            let back = |v: f32| (v * 255.0).round() as u8;
+            assert_eq!((back(c.r), back(c.g), back(c.b)), (rgb.r, rgb.g, rgb.b));
+        }
+
+        // The grayscale ramp is gray and gets lighter
+        for pair in COLOR_256[232..].windows(2) {
+            assert!(pair[0].r == pair[0].g && pair[0].g == pair[0].b);
+            assert!(pair[1].r > pair[0].r);
+        }
+
+        use NamedColor::*;
+        let standard = [
+            Black, Red, Green, Yellow, Blue, Magenta, Cyan, White,
+            BrightBlack, BrightRed, BrightGreen, BrightYellow, BrightBlue, BrightMagenta, BrightCyan, BrightWhite,
+        ];
+        for (idx, named) in standard.into_iter().enumerate() {
+            let c = theme.color(&AnsiColor::Indexed(idx as u8));
+            assert_eq!(theme.color(&AnsiColor::Named(named)), c);
+            assert_eq!(ansi_to_color(&AnsiColor::Named(named), &fg, &bg), c);
+        }
+    }
+
     #[test]
     fn test_256_palette_initialized() {
         // Check that the 6x6x6 cube is populated
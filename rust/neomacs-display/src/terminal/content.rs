@@ -1,14 +1,16 @@
 //! Terminal content extraction — snapshot of terminal state for rendering.
 //!
-//! Each frame, the render thread extracts a `TerminalContent` from the
-//! `alacritty_terminal::Term` and converts cells to rendering primitives.
+//! Whenever a terminal has new output, its extraction worker (see
+//! `extract`) copies a `TerminalContent` out of the
+//! `alacritty_terminal::Term`, converting cells to rendering primitives.
 
 use crate::core::types::Color;
 use alacritty_terminal::grid::Dimensions;
 use alacritty_terminal::index::{Column, Line, Point};
 use alacritty_terminal::term::cell::Flags as CellFlags;
 use alacritty_terminal::term::Term;
-use super::colors::ansi_to_color;
+use alacritty_terminal::vte::ansi::CursorShape;
+use super::colors::TerminalTheme;
 
 /// A single cell ready for GPU rendering.
 #[derive(Debug, Clone)]
@@ -25,6 +27,8 @@ pub struct RenderCell {
     pub bg: Color,
     /// Cell flags (bold, italic, underline, etc.).
     pub flags: CellFlags,
+    /// Underline color set with SGR 58, if any.
+    pub underline_color: Option<Color>,
 }
 
 /// Cursor state for rendering.
@@ -33,6 +37,8 @@ pub struct RenderCursor {
     pub col: usize,
     pub row: usize,
     pub visible: bool,
+    /// Shape requested with DECSCUSR.
+    pub shape: CursorShape,
 }
 
 /// Snapshot of terminal state for one frame.
@@ -55,15 +61,34 @@ impl TerminalContent {
     /// Extract renderable content from an alacritty Term.
     pub fn from_term<T: alacritty_terminal::event::EventListener>(
         term: &Term<T>,
+        theme: &TerminalTheme,
     ) -> Self {
+        let mut content = TerminalContent {
+            cells: Vec::new(),
+            cols: 0,
+            rows: 0,
+            cursor: RenderCursor { col: 0, row: 0, visible: false, shape: CursorShape::Block },
+            default_bg: theme.background,
+            default_fg: theme.foreground,
+        };
+        content.fill_from_term(term, theme);
+        content
+    }
+
+    /// Overwrite this snapshot with the current state of `term`, reusing
+    /// the cell buffer.
+    pub fn fill_from_term<T: alacritty_terminal::event::EventListener>(
+        &mut self,
+        term: &Term<T>,
+        theme: &TerminalTheme,
+    ) {
         let grid = term.grid();
         let num_cols = grid.columns();
         let num_lines = grid.screen_lines();
 
-        let default_fg = Color::WHITE;
-        let default_bg = Color::BLACK;
-
-        let mut cells = Vec::with_capacity(num_cols * num_lines);
+        let cells = &mut self.cells;
+        cells.clear();
+        cells.reserve(num_cols * num_lines);
 
         for row_idx in 0..num_lines {
             let line = Line(row_idx as i32);
@@ -77,8 +102,8 @@ impl TerminalContent {
                     continue;
                 }
 
-                let fg = ansi_to_color(&cell.fg, &default_fg, &default_bg);
-                let bg = ansi_to_color(&cell.bg, &default_fg, &default_bg);
+                let fg = theme.color(&cell.fg);
+                let bg = theme.color(&cell.bg);
 
                 cells.push(RenderCell {
                     col: col_idx,
@@ -87,6 +112,7 @@ impl TerminalContent {
                     fg,
                     bg,
                     flags: cell.flags,
+                    underline_color: cell.underline_color().map(|c| theme.color(&c)),
                 });
             }
         }
@@ -96,19 +122,42 @@ impl TerminalContent {
             col: cursor_point.column.0,
             row: cursor_point.line.0 as usize,
             visible: term.mode().contains(alacritty_terminal::term::TermMode::SHOW_CURSOR),
+            shape: term.cursor_style().shape,
         };
 
-        TerminalContent {
-            cells,
-            cols: num_cols,
-            rows: num_lines,
-            cursor,
-            default_bg,
-            default_fg,
+        self.cols = num_cols;
+        self.rows = num_lines;
+        self.cursor = cursor;
+        self.default_bg = theme.background;
+        self.default_fg = theme.foreground;
+    }
+
+    /// A copy with foreground and background colors swapped, for the
+    /// inverting visual bell.
+    pub fn inverted(&self) -> Self {
+        let mut content = self.clone();
+        for cell in &mut content.cells {
+            std::mem::swap(&mut cell.fg, &mut cell.bg);
         }
+        std::mem::swap(&mut content.default_fg, &mut content.default_bg);
+        content
+    }
+
+    /// Bytes held by the cell buffer
+    pub fn memory_usage(&self) -> usize {
+        self.cells.capacity() * std::mem::size_of::<RenderCell>()
     }
 }
 
+/// Bytes held by the grid of `term`, scrollback included.  Cells with
+/// combining characters hold a little more.
+pub fn grid_memory_usage<T>(term: &Term<T>) -> usize {
+    let grid = term.grid();
+    (grid.screen_lines() + grid.history_size())
+        * grid.columns()
+        * std::mem::size_of::<alacritty_terminal::term::cell::Cell>()
+}
+
 /// Extract text from a terminal grid region as a String.
 pub fn extract_text<T: alacritty_terminal::event::EventListener>(
     term: &Term<T>,
@@ -147,9 +196,37 @@ pub fn extract_text<T: alacritty_terminal::event::EventListener>(
         .join("\n")
 }
 
+/// Text of the last `max_lines` lines of the terminal, scrollback
+/// included, oldest first.  Trailing blank lines are dropped.
+pub fn extract_history<T: alacritty_terminal::event::EventListener>(
+    term: &Term<T>,
+    max_lines: usize,
+) -> String {
+    let grid = term.grid();
+    let num_cols = grid.columns();
+    let bottom = grid.screen_lines() as i32;
+    let top = (-(grid.history_size() as i32)).max(bottom - max_lines as i32);
+
+    let mut lines: Vec<String> = (top..bottom)
+        .map(|row| {
+            let line: String = (0..num_cols)
+                .map(|col| &grid[Point::new(Line(row), Column(col))])
+                .filter(|cell| !cell.flags.contains(CellFlags::WIDE_CHAR_SPACER))
+                .map(|cell| cell.c)
+                .collect();
+            line.trim_end().to_string()
+        })
+        .collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terminal::view::TermGridSize;
 
     #[test]
     fn test_render_cell_creation() {
@@ -160,6 +237,7 @@ mod tests {
             fg: Color::WHITE,
             bg: Color::BLACK,
             flags: CellFlags::empty(),
+            underline_color: None,
         };
         assert_eq!(cell.c, 'A');
         assert_eq!(cell.col, 0);
@@ -171,7 +249,7 @@ mod tests {
             cells: vec![],
             cols: 80,
             rows: 24,
-            cursor: RenderCursor { col: 0, row: 0, visible: true },
+            cursor: RenderCursor { col: 0, row: 0, visible: true, shape: CursorShape::Block },
             default_bg: Color::BLACK,
             default_fg: Color::WHITE,
         };
@@ -179,4 +257,46 @@ mod tests {
         assert_eq!(content.rows, 24);
         assert!(content.cursor.visible);
     }
+
+    #[test]
+    fn test_history_is_the_output_wrapped() {
+        use alacritty_terminal::event::VoidListener;
+        use alacritty_terminal::term::Config;
+        use alacritty_terminal::vte::ansi;
+
+        let mut state: u32 = 0x1234_5678;
+        let mut next = move |n: u32| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) % n
+        };
+        for _ in 0..50 {
+            let mut term = Term::new(Config::default(), &TermGridSize::new(12, 5), VoidListener);
+            let mut parser = ansi::Processor::<ansi::StdSyncHandler>::new();
+            let mut expected = Vec::new();
+            for _ in 0..next(20) {
+                // Printable ASCII with spaces, up to three rows long
+                let line: String = (0..next(37))
+                    .map(|_| if next(5) == 0 { ' ' } else { char::from(b'!' + next(94) as u8) })
+                    .collect();
+                parser.advance(&mut term, format!("{}\r\n", line).as_bytes());
+                let chars: Vec<char> = line.chars().collect();
+                if chars.is_empty() {
+                    expected.push(String::new());
+                }
+                for row in chars.chunks(12) {
+                    expected.push(row.iter().collect::<String>().trim_end().to_string());
+                }
+            }
+            while expected.last().is_some_and(|l| l.is_empty()) {
+                expected.pop();
+            }
+            assert_eq!(extract_history(&term, 1000), expected.join("\n"));
+
+            // The bottom rows of the screen, and part of the top one
+            let rows = term.screen_lines();
+            let text = extract_text(&term, 0, 3, rows - 1, 11);
+            assert!(text.lines().count() <= rows);
+            assert!(text.lines().next().is_none_or(|l| l.chars().count() <= 9));
+        }
+    }
 }
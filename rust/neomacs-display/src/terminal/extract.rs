@@ -0,0 +1,212 @@
+//! Terminal content extraction on a worker thread.
+//!
+//! Copying a terminal's grid into a `TerminalContent` means holding the
+//! `Term` lock while walking every cell.  Doing that on the render thread
+//! lets a terminal flooded with output (`cat largefile`) stall frames,
+//! since the PTY reader holds the same lock while it parses.  Each
+//! terminal therefore has a worker that extracts content whenever the
+//! reader signals new output, and hands the result to the render thread
+//! through a double buffer: the worker fills the back buffer and
+//! publishes it, the render thread swaps it in and returns its previous
+//! copy for the worker to reuse.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::term::Term;
+use parking_lot::FairMutex;
+
+use super::colors::TerminalTheme;
+use super::content::TerminalContent;
+use super::TerminalId;
+
+/// Minimum time between two extractions of the same terminal.  Output
+/// arriving faster than this is coalesced.
+const MIN_EXTRACT_INTERVAL: Duration = Duration::from_millis(4);
+
+struct Request {
+    pending: bool,
+    shutdown: bool,
+    theme: TerminalTheme,
+}
+
+#[derive(Default)]
+struct Buffers {
+    /// Newest content, not yet taken by the render thread
+    ready: Option<TerminalContent>,
+    /// A previous copy handed back for reuse
+    spare: Option<TerminalContent>,
+}
+
+struct Shared {
+    request: Mutex<Request>,
+    cond: Condvar,
+    buffers: Mutex<Buffers>,
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Wakes a terminal's extraction worker.  Held by the event proxy so the
+/// PTY reader can trigger extraction directly.
+#[derive(Clone)]
+pub struct ExtractTrigger(Arc<Shared>);
+
+impl ExtractTrigger {
+    pub fn new() -> Self {
+        Self(Arc::new(Shared {
+            request: Mutex::new(Request {
+                pending: false,
+                shutdown: false,
+                theme: TerminalTheme::default(),
+            }),
+            cond: Condvar::new(),
+            buffers: Mutex::new(Buffers::default()),
+        }))
+    }
+
+    /// Ask for the content to be extracted again.
+    pub fn request(&self) {
+        lock(&self.0.request).pending = true;
+        self.0.cond.notify_one();
+    }
+}
+
+impl Default for ExtractTrigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Owns a terminal's extraction worker
+pub struct ContentExtractor {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ContentExtractor {
+    /// Start extracting `term` whenever `trigger` fires.
+    pub fn spawn<T: EventListener + Send + 'static>(
+        id: TerminalId,
+        trigger: &ExtractTrigger,
+        term: Arc<FairMutex<Term<T>>>,
+    ) -> std::io::Result<Self> {
+        let shared = Arc::clone(&trigger.0);
+        let worker = Arc::clone(&shared);
+        let thread = thread::Builder::new()
+            .name(format!("neo-term-{}-extract", id))
+            .spawn(move || extraction_worker(&worker, &term))?;
+        Ok(Self { shared, thread: Some(thread) })
+    }
+
+    /// Ask for the content to be extracted again with `theme`.
+    pub fn request(&self, theme: &TerminalTheme) {
+        let mut request = lock(&self.shared.request);
+        if request.theme != *theme {
+            request.theme = theme.clone();
+        }
+        request.pending = true;
+        drop(request);
+        self.shared.cond.notify_one();
+    }
+
+    /// Whether new content is waiting to be taken.
+    pub fn has_ready(&self) -> bool {
+        lock(&self.shared.buffers).ready.is_some()
+    }
+
+    /// Take the newest extracted content, if any arrived since last time.
+    pub fn take(&self) -> Option<TerminalContent> {
+        lock(&self.shared.buffers).ready.take()
+    }
+
+    /// Hand back a copy the render thread no longer draws from.
+    pub fn recycle(&self, content: TerminalContent) {
+        lock(&self.shared.buffers).spare = Some(content);
+    }
+}
+
+impl Drop for ContentExtractor {
+    fn drop(&mut self) {
+        lock(&self.shared.request).shutdown = true;
+        self.shared.cond.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn extraction_worker<T: EventListener>(shared: &Shared, term: &FairMutex<Term<T>>) {
+    let mut last = Instant::now() - MIN_EXTRACT_INTERVAL;
+    loop {
+        let theme = {
+            let mut request = lock(&shared.request);
+            while !request.pending && !request.shutdown {
+                request = shared.cond.wait(request).unwrap_or_else(|e| e.into_inner());
+            }
+            if request.shutdown {
+                return;
+            }
+            request.pending = false;
+            request.theme.clone()
+        };
+
+        let since = last.elapsed();
+        if since < MIN_EXTRACT_INTERVAL {
+            thread::sleep(MIN_EXTRACT_INTERVAL - since);
+        }
+        last = Instant::now();
+
+        let spare = lock(&shared.buffers).spare.take();
+        let content = {
+            let term = term.lock();
+            match spare {
+                Some(mut content) => {
+                    content.fill_from_term(&term, &theme);
+                    content
+                }
+                None => TerminalContent::from_term(&term, &theme),
+            }
+        };
+        let mut buffers = lock(&shared.buffers);
+        // An untaken older copy becomes the spare
+        if let Some(old) = buffers.ready.replace(content) {
+            buffers.spare = Some(old);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::view::TermGridSize;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi;
+
+    #[test]
+    fn test_worker_extracts_on_request() {
+        let term = Arc::new(FairMutex::new(Term::new(Config::default(), &TermGridSize::new(10, 4), VoidListener)));
+        ansi::Processor::<ansi::StdSyncHandler>::new().advance(&mut *term.lock(), b"hi");
+
+        let trigger = ExtractTrigger::new();
+        let extractor = ContentExtractor::spawn(1, &trigger, Arc::clone(&term)).unwrap();
+        extractor.request(&TerminalTheme::default());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let content = loop {
+            if let Some(content) = extractor.take() {
+                break content;
+            }
+            assert!(Instant::now() < deadline, "no content extracted");
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!((content.cols, content.rows), (10, 4));
+        let text: String = content.cells.iter().take(2).map(|c| c.c).collect();
+        assert_eq!(text, "hi");
+        assert!(extractor.take().is_none());
+    }
+}
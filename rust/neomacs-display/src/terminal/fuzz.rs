@@ -0,0 +1,135 @@
+//! Arbitrary program output through the terminal path.
+//!
+//! A terminal draws whatever bytes a program writes, so no escape
+//! sequence, however malformed, may take the display down.  `feed` runs
+//! one input the way real output goes -- through the VT parser into the
+//! grid, into the snapshot the render thread draws, the glyphs it draws
+//! it with and the text Emacs extracts -- and panics when the result
+//! breaks an invariant.  It is the body of the `terminal` target under
+//! `fuzz/` (`cargo fuzz run terminal`) and of a test feeding it
+//! pseudo-random bytes.
+
+use alacritty_terminal::event::{EventListener, VoidListener};
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::term::{Config, Term};
+use alacritty_terminal::vte::ansi;
+
+use super::colors::TerminalTheme;
+use super::content::{extract_history, extract_text, TerminalContent};
+use super::view::TermGridSize;
+
+/// Scrollback of fuzzed terminals, which bounds their memory
+pub const HISTORY: usize = 100;
+
+/// Grid size picked by two input bytes, up to 100 x 40, made the way
+/// terminal views make theirs
+fn grid_size(cols: u8, rows: u8) -> TermGridSize {
+    TermGridSize::new(1 + cols as u16 % 100, 1 + rows as u16 % 40)
+}
+
+/// Feed `data` to a terminal as program output and check what is drawn.
+/// The first bytes choose the grid size, where the output is split and
+/// the size the terminal is resized to between the two parts.
+pub fn feed(data: &[u8]) {
+    let [cols, rows, split, new_cols, new_rows, output @ ..] = data else {
+        return;
+    };
+    let config = Config { scrolling_history: HISTORY, ..Default::default() };
+    let mut term = Term::new(config, &grid_size(*cols, *rows), VoidListener);
+    let mut parser = ansi::Processor::<ansi::StdSyncHandler>::new();
+
+    let (first, second) = output.split_at(*split as usize * output.len() / 255);
+    parser.advance(&mut term, first);
+    check(&term);
+    // Programs keep writing while the window is resized
+    term.resize(grid_size(*new_cols, *new_rows));
+    check(&term);
+    parser.advance(&mut term, second);
+    check(&term);
+}
+
+/// Panic if the snapshot, glyphs or text of `term` are out of bounds
+fn check<T: EventListener>(term: &Term<T>) {
+    let (cols, rows) = (term.columns(), term.screen_lines());
+    assert!(term.grid().history_size() <= HISTORY, "scrollback past its limit");
+
+    let content = TerminalContent::from_term(term, &TerminalTheme::default());
+    assert_eq!((content.cols, content.rows), (cols, rows));
+    assert!(content.cells.len() <= cols * rows);
+    assert!(content.cells.iter().all(|c| c.col < cols && c.row < rows), "cell outside the grid");
+    assert!(content.cursor.col < cols && content.cursor.row < rows, "cursor outside the grid");
+
+    #[cfg(feature = "winit-backend")]
+    {
+        use crate::backend::wgpu::TerminalRenderer;
+        use crate::terminal::CellSize;
+
+        let renderer = TerminalRenderer {
+            cell: CellSize::default(),
+            face_id: 0,
+            is_overlay: false,
+            opacity: 1.0,
+            clip: None,
+        };
+        let mut glyphs = Vec::new();
+        renderer.push(&content, 0.0, 0.0, &mut glyphs);
+        // At most a background and a character per cell, and the cursor
+        assert!(glyphs.len() <= 2 * content.cells.len() + 1);
+    }
+
+    let text = extract_text(term, 0, 0, rows - 1, cols - 1);
+    assert!(text.lines().count() <= rows);
+    assert!(text.lines().all(|l| l.chars().count() <= cols));
+    let history = extract_history(term, HISTORY + rows);
+    assert!(history.lines().count() <= HISTORY + rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes terminal output is made of, escape sequences weighted up
+    const ALPHABET: &[u8] = b"\x1b\x1b\x1b[[[]]\x07\x08\t\n\r\x0e\x0f\x90\x9b\x9c\xe5\xa5\xbd\xf0\x9f\x98\x80\
+        0123456789;;;:?>!#$%&'()*+,-./@ABCDEFGHIJKLMPSTXZ\\^_`abcdfghklmnpqrstux~ ";
+
+    #[test]
+    fn test_hostile_sequences() {
+        let cases: &[&[u8]] = &[
+            b"\x1b[999999999@\x1b[999999999L\x1b[999999999P\x1b[999999999M",
+            b"\x1b[99999;99999H\x1b[99999;99999r\x1b[S\x1b[999999T",
+            b"\x1b#8\x1b[?1049h\x1b[2J\x1b[?1049l\x1b[?6h\x1b[5;2r\x1b[1;1H\x1b[99B",
+            b"\x1b[38:2::255:0:0m\x1b[58:5:999m\x1b[4:9m\x1b[48;5;256mX",
+            b"\xe5\xa5\xbd\xe5\xa5\xbd\xe5\xa5\xbd\xcc\x81\xcc\x81\xcc\x81\xef\xbf",
+            b"\x1b]8;;http://x\x1b\\link\x1b]8;;\x07\x1b]52;c;!!\x07\x1bP+q\x1b\\",
+        ];
+        for output in cases {
+            // The narrowest grid, then a normal one
+            for size in [[0, 0, 128, 79, 23], [79, 23, 40, 0, 0]] {
+                feed(&[&size[..], output].concat());
+            }
+        }
+        feed(&[]);
+    }
+
+    #[test]
+    fn test_pseudo_random_output() {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..300 {
+            let len = 5 + next() as usize % 2000;
+            let data: Vec<u8> = (0..len)
+                .map(|i| {
+                    let r = next();
+                    // Sizes and split points take any value
+                    if i < 5 || r % 16 == 0 { (r >> 8) as u8 } else { ALPHABET[(r >> 8) as usize % ALPHABET.len()] }
+                })
+                .collect();
+            feed(&data);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Buffered, non-blocking terminal input.
+//!
+//! Writing to a PTY blocks once the kernel buffer is full, which happens
+//! as soon as the child stops reading: pasting megabytes into a slow REPL
+//! used to stall whichever thread did the write.  Input is instead
+//! appended to a ring buffer and a per-terminal writer thread drains it
+//! into the PTY in small chunks.  Callers never block; they can ask how
+//! much is still queued and refuse more input (backpressure) or drop
+//! what has not been written yet (cancel).
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+use super::TerminalId;
+
+/// Queued input above which writes from Emacs are refused
+pub const DEFAULT_INPUT_LIMIT: usize = 4 * 1024 * 1024;
+
+/// Largest single write to the PTY.  Cancelling takes effect between
+/// chunks.
+const INPUT_CHUNK: usize = 4096;
+
+/// Bytes waiting to be written to a PTY
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+    bytes: VecDeque<u8>,
+    limit: usize,
+    /// Total bytes handed to the PTY so far
+    written: u64,
+}
+
+impl InputBuffer {
+    pub fn new(limit: usize) -> Self {
+        Self { bytes: VecDeque::new(), limit, written: 0 }
+    }
+
+    /// Queue `data`.  Never refuses input: the limit only decides when
+    /// `is_full` starts reporting backpressure.
+    pub fn push(&mut self, data: &[u8]) {
+        self.bytes.extend(data);
+    }
+
+    /// Queue several pieces of input in order.
+    pub fn write_many(&mut self, chunks: &[&[u8]]) {
+        self.bytes.reserve(chunks.iter().map(|c| c.len()).sum());
+        for chunk in chunks {
+            self.bytes.extend(*chunk);
+        }
+    }
+
+    /// Bytes queued but not yet written
+    pub fn pending(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether callers should hold back further input.
+    pub fn is_full(&self) -> bool {
+        self.bytes.len() >= self.limit
+    }
+
+    /// Drop everything not yet written.  Returns the number of bytes
+    /// dropped.
+    pub fn cancel(&mut self) -> usize {
+        let dropped = self.bytes.len();
+        self.bytes.clear();
+        dropped
+    }
+
+    /// Move the next chunk to write into `out`.
+    fn take_chunk(&mut self, out: &mut Vec<u8>) {
+        let n = self.bytes.len().min(INPUT_CHUNK);
+        out.clear();
+        out.extend(self.bytes.drain(..n));
+        self.written += n as u64;
+    }
+}
+
+struct State {
+    buffer: InputBuffer,
+    closed: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+/// Backpressure snapshot of a terminal's input queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputStatus {
+    pub pending: usize,
+    pub limit: usize,
+    pub written: u64,
+}
+
+/// Handle to a terminal's input queue.  Cheap to clone; the writer
+/// thread exits once the queue is closed.
+#[derive(Clone)]
+pub struct InputQueue(Arc<Shared>);
+
+impl InputQueue {
+    /// Start a writer thread draining into `writer`.
+    pub fn spawn(
+        id: TerminalId,
+        writer: Box<dyn Write + Send>,
+        limit: usize,
+    ) -> std::io::Result<Self> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State { buffer: InputBuffer::new(limit), closed: false }),
+            cond: Condvar::new(),
+        });
+        let worker = Arc::clone(&shared);
+        thread::Builder::new()
+            .name(format!("neo-term-{}-input", id))
+            .spawn(move || input_writer(id, &worker, writer))?;
+        Ok(Self(shared))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.0.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue `data` without blocking.
+    pub fn push(&self, data: &[u8]) {
+        let mut state = self.lock();
+        if !state.closed {
+            state.buffer.push(data);
+            self.0.cond.notify_one();
+        }
+    }
+
+    /// Queue several pieces of input in order, taking the lock once.
+    pub fn write_many(&self, chunks: &[&[u8]]) {
+        let mut state = self.lock();
+        if !state.closed {
+            state.buffer.write_many(chunks);
+            self.0.cond.notify_one();
+        }
+    }
+
+    /// Whether callers should hold back further input.
+    pub fn is_full(&self) -> bool {
+        self.lock().buffer.is_full()
+    }
+
+    pub fn status(&self) -> InputStatus {
+        let state = self.lock();
+        InputStatus {
+            pending: state.buffer.pending(),
+            limit: state.buffer.limit,
+            written: state.buffer.written,
+        }
+    }
+
+    /// Drop queued input not yet written.  Returns the bytes dropped.
+    pub fn cancel(&self) -> usize {
+        self.lock().buffer.cancel()
+    }
+
+    /// Stop the writer thread, dropping queued input.  Does not wait
+    /// for a write in progress, which may be blocked on the PTY.
+    pub fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.buffer.cancel();
+        self.0.cond.notify_one();
+    }
+}
+
+fn input_writer(id: TerminalId, shared: &Shared, mut writer: Box<dyn Write + Send>) {
+    let mut chunk = Vec::with_capacity(INPUT_CHUNK);
+    loop {
+        {
+            let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            while state.buffer.pending() == 0 && !state.closed {
+                state = shared.cond.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            if state.closed {
+                return;
+            }
+            state.buffer.take_chunk(&mut chunk);
+        }
+        if let Err(e) = writer.write_all(&chunk).and_then(|()| writer.flush()) {
+            log::warn!("Terminal {} write error: {}", id, e);
+            let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.closed = true;
+            state.buffer.cancel();
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_input_buffer_backpressure_and_cancel() {
+        let mut buffer = InputBuffer::new(8);
+        buffer.push(b"hello");
+        assert!(!buffer.is_full());
+        buffer.write_many(&[b" ", b"world"]);
+        assert_eq!(buffer.pending(), 11);
+        assert!(buffer.is_full());
+
+        let mut chunk = Vec::new();
+        buffer.take_chunk(&mut chunk);
+        assert_eq!(chunk, b"hello world");
+        assert_eq!(buffer.written, 11);
+
+        buffer.push(&[0; INPUT_CHUNK + 10]);
+        buffer.take_chunk(&mut chunk);
+        assert_eq!(chunk.len(), INPUT_CHUNK);
+        assert_eq!(buffer.cancel(), 10);
+        assert_eq!(buffer.pending(), 0);
+    }
+
+    struct ChannelWriter(mpsc::Sender<Vec<u8>>);
+
+    impl Write for ChannelWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.send(buf.to_vec()).map_err(|_| std::io::ErrorKind::BrokenPipe)?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_queue_drains_in_order() {
+        let (tx, rx) = mpsc::channel();
+        let queue = InputQueue::spawn(1, Box::new(ChannelWriter(tx)), DEFAULT_INPUT_LIMIT).unwrap();
+        queue.push(b"ls");
+        queue.write_many(&[b" -l", b"\r"]);
+
+        let mut received = Vec::new();
+        while received.len() < 6 {
+            received.extend(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+        }
+        assert_eq!(received, b"ls -l\r");
+
+        queue.close();
+        queue.push(b"ignored");
+        assert_eq!(queue.status().pending, 0);
+    }
+}
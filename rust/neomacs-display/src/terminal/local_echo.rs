@@ -0,0 +1,327 @@
+//! Predictive local echo for high-latency shells.
+//!
+//! Over a slow SSH link every typed character takes a round trip before
+//! the shell's echo shows it.  Like mosh, a terminal can guess the echo:
+//! printable characters typed at the end of the cursor line are drawn at
+//! once, underlined as provisional, and dropped as the real echo shows
+//! them.  If the echo turns out different, or never comes, the guesses are
+//! thrown away and the screen shows what the program drew.
+//!
+//! Guessing is only safe while the program echoes input as typed.  After
+//! Return or any other control key a new line of input begins that may
+//! not be echoed at all -- a password prompt, or a full-screen program
+//! taking commands -- so guesses after one stay hidden until the echo
+//! confirms the first of them.  In `Auto` mode they are also hidden
+//! while the echo comes back quickly, as measured by the time confirmed
+//! guesses took.
+
+use std::time::{Duration, Instant};
+
+use alacritty_terminal::term::cell::Flags as CellFlags;
+
+use super::content::{RenderCell, TerminalContent};
+
+/// Smoothed round trip above which `Auto` shows guesses
+const SHOW_ABOVE: Duration = Duration::from_millis(30);
+
+/// Smoothed round trip below which `Auto` hides them again
+const HIDE_BELOW: Duration = Duration::from_millis(20);
+
+/// Shortest wait for an echo before guesses are dropped
+const MIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether a terminal guesses its echo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EchoMode {
+    Off,
+    On,
+    /// Show guesses only while the round trip is slow
+    #[default]
+    Auto,
+}
+
+impl EchoMode {
+    /// Mode from its FFI number: 0 Off, 1 On, 2 Auto.
+    pub fn from_u8(mode: u8) -> Self {
+        match mode {
+            0 => Self::Off,
+            1 => Self::On,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// A character guessed to be echoed at a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Guess {
+    row: usize,
+    col: usize,
+    c: char,
+    made: Instant,
+}
+
+/// Guessed echo of a terminal's input
+#[derive(Debug, Clone, Default)]
+pub struct LocalEcho {
+    mode: EchoMode,
+    /// Characters not echoed yet, in the order typed
+    guesses: Vec<Guess>,
+    /// Input the guesses cannot follow was sent; guessing waits for the
+    /// next content
+    blocked: bool,
+    /// Whether the echo confirmed a guess since input last went off the
+    /// line being typed
+    confirmed: bool,
+    /// Smoothed time from a guess to its echo
+    srtt: Option<Duration>,
+    /// Whether `Auto` finds the round trip slow
+    slow: bool,
+}
+
+fn cell_at(content: &TerminalContent, row: usize, col: usize) -> Option<&RenderCell> {
+    let i = content.cells.binary_search_by_key(&(row, col), |cell| (cell.row, cell.col)).ok()?;
+    content.cells.get(i)
+}
+
+impl LocalEcho {
+    pub fn mode(&self) -> EchoMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: EchoMode) {
+        self.mode = mode;
+        self.guesses.clear();
+    }
+
+    /// Smoothed round trip of the echo, once measured
+    pub fn round_trip(&self) -> Option<Duration> {
+        self.srtt
+    }
+
+    /// Whether guesses are drawn
+    pub fn showing(&self) -> bool {
+        let shown = match self.mode {
+            EchoMode::Off => false,
+            EchoMode::On => self.confirmed,
+            EchoMode::Auto => self.confirmed && self.slow,
+        };
+        shown && !self.guesses.is_empty()
+    }
+
+    /// Time to wait for an echo: a few round trips, at least `MIN_TIMEOUT`
+    fn timeout(&self) -> Duration {
+        self.srtt.map_or(MIN_TIMEOUT, |srtt| (srtt * 4).max(MIN_TIMEOUT))
+    }
+
+    /// Guess the echo of `data`, sent to the program while the screen
+    /// showed `content`.  Returns whether the guesses drawn changed.
+    pub fn typed(&mut self, data: &[u8], content: Option<&TerminalContent>, now: Instant) -> bool {
+        if self.mode == EchoMode::Off {
+            return false;
+        }
+        let Some(content) = content else {
+            return false;
+        };
+        let shown = self.showing();
+        for &byte in data {
+            if self.blocked {
+                break;
+            }
+            let (row, col) = self
+                .guesses
+                .last()
+                .map_or((content.cursor.row, content.cursor.col), |g| (g.row, g.col + 1));
+            // Printable ASCII at the end of the line, not filling it; other
+            // characters take a width or do things that cannot be told
+            let at_end = (col..content.cols).all(|col| cell_at(content, row, col).is_none_or(|cell| cell.c == ' '));
+            if (0x20..0x7f).contains(&byte) && col + 1 < content.cols && at_end {
+                self.guesses.push(Guess { row, col, c: byte as char, made: now });
+            } else {
+                // Return, editing keys and escape sequences start a new
+                // line of input, or move the cursor elsewhere
+                self.guesses.clear();
+                self.blocked = true;
+                self.confirmed = false;
+            }
+        }
+        shown || self.showing()
+    }
+
+    /// New content arrived from the program: drop the guesses it shows
+    /// echoed, and all of them if it shows something else.  Returns
+    /// whether the guesses drawn changed.
+    pub fn content_arrived(&mut self, content: &TerminalContent, now: Instant) -> bool {
+        self.blocked = false;
+        let shown = self.showing();
+        let before = self.guesses.len();
+        while let Some(&guess) = self.guesses.first() {
+            let cursor = &content.cursor;
+            match cell_at(content, guess.row, guess.col) {
+                Some(cell) if cell.c == guess.c => {
+                    self.guesses.remove(0);
+                    self.confirmed = true;
+                    self.sample(now.saturating_duration_since(guess.made));
+                }
+                // Not echoed yet
+                _ if cursor.row == guess.row && cursor.col <= guess.col => break,
+                _ => {
+                    self.miss();
+                    break;
+                }
+            }
+        }
+        shown && self.guesses.len() != before
+    }
+
+    /// Drop guesses the echo of which is overdue.  Returns whether any
+    /// drawn were dropped.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        if !self.expired(now) {
+            return false;
+        }
+        let shown = self.showing();
+        self.miss();
+        shown
+    }
+
+    /// Whether the oldest guess has waited too long for its echo
+    pub fn expired(&self, now: Instant) -> bool {
+        self.guesses.first().is_some_and(|g| now.saturating_duration_since(g.made) > self.timeout())
+    }
+
+    fn miss(&mut self) {
+        self.guesses.clear();
+        self.confirmed = false;
+    }
+
+    fn sample(&mut self, rtt: Duration) {
+        // As TCP smooths its round trip estimate
+        let srtt = self.srtt.map_or(rtt, |srtt| (srtt * 7 + rtt) / 8);
+        self.srtt = Some(srtt);
+        if srtt > SHOW_ABOVE {
+            self.slow = true;
+        } else if srtt < HIDE_BELOW {
+            self.slow = false;
+        }
+    }
+
+    /// A copy of `content` with the guesses drawn in, underlined, and the
+    /// cursor after them; None when no guesses are drawn
+    pub fn overlay(&self, content: &TerminalContent) -> Option<TerminalContent> {
+        if !self.showing() {
+            return None;
+        }
+        let mut content = content.clone();
+        for guess in &self.guesses {
+            let Ok(i) = content.cells.binary_search_by_key(&(guess.row, guess.col), |cell| (cell.row, cell.col))
+            else {
+                continue;
+            };
+            let cell = &mut content.cells[i];
+            cell.c = guess.c;
+            cell.flags.insert(CellFlags::UNDERLINE);
+            content.cursor.row = guess.row;
+            content.cursor.col = guess.col + 1;
+        }
+        Some(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::colors::TerminalTheme;
+    use crate::terminal::view::TermGridSize;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::{Config, Term};
+    use alacritty_terminal::vte::ansi;
+
+    struct Screen {
+        term: Term<VoidListener>,
+        parser: ansi::Processor<ansi::StdSyncHandler>,
+    }
+
+    impl Screen {
+        fn new() -> Self {
+            Self { term: Term::new(Config::default(), &TermGridSize::new(10, 3), VoidListener), parser: ansi::Processor::new() }
+        }
+
+        fn print(&mut self, output: &[u8]) -> TerminalContent {
+            self.parser.advance(&mut self.term, output);
+            TerminalContent::from_term(&self.term, &TerminalTheme::default())
+        }
+    }
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_echo_confirmed_before_shown() {
+        let mut screen = Screen::new();
+        let t = Instant::now();
+        let content = screen.print(b"$ ");
+        let mut echo = LocalEcho::default();
+        echo.set_mode(EchoMode::On);
+
+        // Nothing is shown until the echo is known to come
+        assert!(!echo.typed(b"ls", Some(&content), t));
+        assert!(echo.overlay(&content).is_none());
+        let content = screen.print(b"l");
+        echo.content_arrived(&content, t + 50 * MS);
+        assert_eq!(echo.round_trip(), Some(50 * MS));
+        let shown = echo.overlay(&content).unwrap();
+        let cell = cell_at(&shown, 0, 3).unwrap();
+        assert_eq!(cell.c, 's');
+        assert!(cell.flags.contains(CellFlags::UNDERLINE));
+        assert_eq!((shown.cursor.row, shown.cursor.col), (0, 4));
+
+        let content = screen.print(b"s");
+        assert!(echo.content_arrived(&content, t + 60 * MS));
+        assert!(echo.overlay(&content).is_none());
+
+        // Return takes the guesses back and starts over: the next line of
+        // input may be a password
+        assert!(echo.typed(b" -l", Some(&content), t));
+        assert!(echo.typed(b"\rpw", Some(&content), t));
+        assert!(echo.overlay(&content).is_none());
+        let content = screen.print(b" -l\r\npw: ");
+        echo.content_arrived(&content, t + 100 * MS);
+        assert!(!echo.typed(b"abc", Some(&content), t + 110 * MS));
+        assert!(!echo.expired(t + 1000 * MS));
+        assert!(echo.expired(t + 2000 * MS));
+        assert!(!echo.expire(t + 2000 * MS));
+        assert!(echo.guesses.is_empty());
+    }
+
+    #[test]
+    fn test_echo_auto_and_misses() {
+        let mut screen = Screen::new();
+        let t = Instant::now();
+        let mut content = screen.print(b"> ");
+        let mut echo = LocalEcho::default();
+        assert_eq!(echo.mode(), EchoMode::Auto);
+
+        // A fast echo: guesses are checked but not drawn
+        for (i, c) in b"abc".iter().enumerate() {
+            echo.typed(&[*c], Some(&content), t);
+            content = screen.print(&[*c]);
+            echo.content_arrived(&content, t + 5 * MS);
+            assert!(echo.guesses.is_empty(), "guess {} confirmed", i);
+        }
+        assert!(!echo.typed(b"d", Some(&content), t));
+        content = screen.print(b"d");
+        echo.content_arrived(&content, t + 300 * MS);
+        // The slow echo made the round trip slow enough to draw guesses
+        assert!(echo.typed(b"e", Some(&content), t + 300 * MS));
+        assert!(echo.overlay(&content).is_some());
+
+        // The program drew something else: the guess goes
+        content = screen.print(b"X");
+        assert!(echo.content_arrived(&content, t + 400 * MS));
+        assert!(echo.overlay(&content).is_none());
+        assert!(!echo.typed(b"f", Some(&content), t + 400 * MS));
+
+        echo.set_mode(EchoMode::from_u8(0));
+        assert!(!echo.typed(b"g", Some(&content), t));
+        assert!(echo.guesses.is_empty());
+    }
+}
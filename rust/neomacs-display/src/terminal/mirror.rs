@@ -0,0 +1,160 @@
+//! Incremental copy of a terminal's screen for the host.
+//!
+//! Emacs can mirror a terminal into an ordinary buffer so it can be
+//! searched and saved.  Copying the whole screen on every update would be
+//! wasteful, so a `GridMirror` remembers a hash of each screen line as
+//! last handed out and `changed_lines` returns only the lines that differ
+//! since, each serialized as its text with SGR escape sequences for the
+//! colors and attributes (the form `ansi-color-apply` understands).
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::cell::{Cell, Flags as CellFlags};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::vte::ansi::{Color, NamedColor};
+
+/// Screen lines as last handed to the host
+#[derive(Debug, Default)]
+pub struct GridMirror {
+    hashes: Vec<u64>,
+}
+
+impl GridMirror {
+    /// Forget what the host has, so the next call returns every line.
+    pub fn reset(&mut self) {
+        self.hashes.clear();
+    }
+
+    /// Screen lines changed since the last call, as (row, SGR text).
+    pub fn changed_lines<T: EventListener>(&mut self, term: &Term<T>) -> Vec<(usize, String)> {
+        let grid = term.grid();
+        let rows = grid.screen_lines();
+        let cols = grid.columns();
+        self.hashes.resize(rows, 0);
+
+        let mut changed = Vec::new();
+        for row in 0..rows {
+            let cells = (0..cols).map(|col| &grid[Point::new(Line(row as i32), Column(col))]);
+            let text = sgr_line(cells);
+            let mut hasher = DefaultHasher::new();
+            text.hash(&mut hasher);
+            // Never 0, so a fresh or reset mirror sends every line
+            let hash = hasher.finish() | 1;
+            if self.hashes[row] != hash {
+                self.hashes[row] = hash;
+                changed.push((row, text));
+            }
+        }
+        changed
+    }
+}
+
+/// SGR attributes of one cell
+#[derive(Clone, Copy, PartialEq)]
+struct Attrs {
+    fg: Color,
+    bg: Color,
+    flags: CellFlags,
+}
+
+const DEFAULT_ATTRS: Attrs = Attrs {
+    fg: Color::Named(NamedColor::Foreground),
+    bg: Color::Named(NamedColor::Background),
+    flags: CellFlags::empty(),
+};
+
+const SGR_FLAGS: [(CellFlags, u8); 6] = [
+    (CellFlags::BOLD, 1),
+    (CellFlags::DIM, 2),
+    (CellFlags::ITALIC, 3),
+    (CellFlags::UNDERLINE, 4),
+    (CellFlags::INVERSE, 7),
+    (CellFlags::STRIKEOUT, 9),
+];
+
+const SGR_MASK: CellFlags = CellFlags::BOLD
+    .union(CellFlags::DIM)
+    .union(CellFlags::ITALIC)
+    .union(CellFlags::UNDERLINE)
+    .union(CellFlags::INVERSE)
+    .union(CellFlags::STRIKEOUT);
+
+fn push_color(out: &mut String, color: Color, base: u8) {
+    match color {
+        Color::Named(n) if (n as usize) < 8 => write!(out, ";{}", base + n as u8).unwrap(),
+        Color::Named(n) if (n as usize) < 16 => write!(out, ";{}", base + 60 + n as u8 - 8).unwrap(),
+        Color::Named(_) => {}
+        Color::Indexed(i) => write!(out, ";{};5;{}", base + 8, i).unwrap(),
+        Color::Spec(rgb) => write!(out, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b).unwrap(),
+    }
+}
+
+/// Serialize a line of cells as text with SGR sequences.  Trailing blank
+/// cells without attributes are left out.
+fn sgr_line<'a>(cells: impl Iterator<Item = &'a Cell>) -> String {
+    let cells: Vec<&Cell> = cells.filter(|c| !c.flags.contains(CellFlags::WIDE_CHAR_SPACER)).collect();
+    let attrs = |c: &Cell| Attrs { fg: c.fg, bg: c.bg, flags: c.flags & SGR_MASK };
+    let end = cells
+        .iter()
+        .rposition(|c| c.c != ' ' || attrs(c) != DEFAULT_ATTRS)
+        .map_or(0, |i| i + 1);
+
+    let mut out = String::new();
+    let mut current = DEFAULT_ATTRS;
+    for cell in &cells[..end] {
+        let a = attrs(cell);
+        if a != current {
+            // Reset, then set everything the cell has
+            out.push_str("\x1b[0");
+            for (flag, code) in SGR_FLAGS {
+                if a.flags.contains(flag) {
+                    write!(out, ";{}", code).unwrap();
+                }
+            }
+            push_color(&mut out, a.fg, 30);
+            push_color(&mut out, a.bg, 40);
+            out.push('m');
+            current = a;
+        }
+        out.push(if cell.c == '\0' { ' ' } else { cell.c });
+    }
+    if current != DEFAULT_ATTRS {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::view::TermGridSize;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi;
+
+    #[test]
+    fn test_only_changed_lines_with_sgr() {
+        let mut term = Term::new(Config::default(), &TermGridSize::new(10, 3), VoidListener);
+        let mut parser = ansi::Processor::<ansi::StdSyncHandler>::new();
+        parser.advance(&mut term, b"plain\r\n\x1b[1;31mred\x1b[0m ok");
+
+        let mut mirror = GridMirror::default();
+        let lines = mirror.changed_lines(&term);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], (0, "plain".to_string()));
+        assert_eq!(lines[1], (1, "\x1b[0;1;31mred\x1b[0m ok".to_string()));
+        assert_eq!(lines[2], (2, String::new()));
+
+        assert!(mirror.changed_lines(&term).is_empty());
+        parser.advance(&mut term, b"\r\n\x1b[38;5;200mx");
+        assert_eq!(mirror.changed_lines(&term), vec![(2, "\x1b[0;38;5;200mx\x1b[0m".to_string())]);
+
+        mirror.reset();
+        assert_eq!(mirror.changed_lines(&term).len(), 3);
+    }
+}
@@ -3,25 +3,42 @@
 //! Uses `alacritty_terminal` for VT parsing and terminal state,
 //! renders cells directly via the wgpu pipeline.
 
+pub mod bell;
 pub mod colors;
 pub mod content;
+pub mod extract;
+pub mod fuzz;
+pub mod input;
+pub mod local_echo;
+pub mod mirror;
+pub mod output;
+pub mod prompt;
+pub mod tmux;
+pub mod transport;
 pub mod view;
 
 pub use content::TerminalContent;
-pub use view::{TerminalManager, TerminalView};
+pub use view::{CellSize, TermGridSize, TerminalBackground, TerminalFont, TerminalManager, TerminalView};
 
 /// Unique identifier for a terminal instance.
 pub type TerminalId = u32;
 
+/// Parts of a terminal the Emacs thread uses directly.
+#[derive(Clone)]
+pub struct SharedTerminal {
+    /// Terminal state, for cross-thread text extraction.
+    pub term: std::sync::Arc<parking_lot::FairMutex<alacritty_terminal::term::Term<view::NeomacsEventProxy>>>,
+    /// Input queue, for backpressure and cancelling.
+    pub input: input::InputQueue,
+    /// Screen lines last handed to Emacs for mirroring.
+    pub mirror: std::sync::Arc<std::sync::Mutex<mirror::GridMirror>>,
+    /// Prompt tracking, for reading the input being typed.
+    pub prompt: std::sync::Arc<std::sync::Mutex<prompt::PromptState>>,
+}
+
 /// Shared terminal state accessible from both Emacs and render threads.
-/// Maps terminal ID to its Arc<FairMutex<Term>> for cross-thread text extraction.
 pub type SharedTerminals = std::sync::Arc<
-    std::sync::Mutex<
-        std::collections::HashMap<
-            TerminalId,
-            std::sync::Arc<parking_lot::FairMutex<alacritty_terminal::term::Term<view::NeomacsEventProxy>>>,
-        >,
-    >,
+    std::sync::Mutex<std::collections::HashMap<TerminalId, SharedTerminal>>,
 >;
 
 /// Terminal display mode.
@@ -34,3 +51,23 @@ pub enum TerminalMode {
     /// Terminal floats on top of all content (renderer-level compositing).
     Floating,
 }
+
+impl TerminalMode {
+    /// Mode from its FFI number: 0 Window, 1 Inline, 2 Floating.
+    pub fn from_u8(mode: u8) -> Self {
+        match mode {
+            1 => Self::Inline,
+            2 => Self::Floating,
+            _ => Self::Window,
+        }
+    }
+
+    /// The FFI number of the mode, as `from_u8` takes it.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Window => 0,
+            Self::Inline => 1,
+            Self::Floating => 2,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Pacing of terminal output.
+//!
+//! A command that spews output (`yes`, `cat` of a huge log) keeps its PTY
+//! reader parsing as fast as it can, which starves the render thread of
+//! CPU and of the `Term` lock.  Readers therefore draw from a byte budget
+//! shared by all terminals and refilled every frame: each terminal that
+//! produced output recently gets an equal share, and a reader that used
+//! its share sleeps until the next frame, leaving the child blocked on a
+//! full PTY.
+//!
+//! A reader that is far behind (much more output already queued in the
+//! PTY than one read) also stops publishing every intermediate screen:
+//! it parses on and only wakes the content extractor once it has caught
+//! up or parsed `MAX_UNPUBLISHED` bytes, so states nobody would see long
+//! enough to read are never extracted.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::TerminalId;
+
+/// Output queued in the PTY beyond which a reader counts as far behind
+pub const FAR_BEHIND: usize = 64 * 1024;
+
+/// Most output parsed without publishing the screen while far behind
+pub const MAX_UNPUBLISHED: usize = 1024 * 1024;
+
+/// Default budget shared by all terminals each frame
+pub const DEFAULT_BYTES_PER_FRAME: usize = 256 * 1024;
+
+const FRAME: Duration = Duration::from_millis(16);
+
+struct State {
+    /// Bytes all terminals may parse per frame; 0 is unlimited
+    bytes_per_frame: usize,
+    frame_start: Instant,
+    used: HashMap<TerminalId, usize>,
+    /// Terminals that produced output in the previous frame
+    last_active: usize,
+}
+
+impl State {
+    fn consume(&mut self, id: TerminalId, bytes: usize, now: Instant) -> Duration {
+        if self.bytes_per_frame == 0 {
+            return Duration::ZERO;
+        }
+        if now.duration_since(self.frame_start) >= FRAME {
+            self.last_active = self.used.len();
+            self.used.clear();
+            self.frame_start = now;
+        }
+        let used = self.used.entry(id).or_insert(0);
+        *used += bytes;
+        let used = *used;
+        let active = self.used.len().max(self.last_active).max(1);
+        if used >= self.bytes_per_frame / active {
+            (self.frame_start + FRAME).saturating_duration_since(now)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Output budget shared by the PTY readers of all terminals.  Cheap to
+/// clone.
+#[derive(Clone)]
+pub struct OutputLimiter(Arc<Mutex<State>>);
+
+impl OutputLimiter {
+    pub fn new(bytes_per_frame: usize) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            bytes_per_frame,
+            frame_start: Instant::now(),
+            used: HashMap::new(),
+            last_active: 0,
+        })))
+    }
+
+    /// Change the budget; 0 removes the limit.
+    pub fn set_bytes_per_frame(&self, bytes: usize) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).bytes_per_frame = bytes;
+    }
+
+    /// Record that terminal `id` read `bytes` at `now`.  Returns how long
+    /// its reader should wait before reading more.
+    pub fn consume(&self, id: TerminalId, bytes: usize, now: Instant) -> Duration {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).consume(id, bytes, now)
+    }
+}
+
+impl Default for OutputLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_BYTES_PER_FRAME)
+    }
+}
+
+/// Whether a reader that parsed `unpublished` bytes since it last woke
+/// the extractor, with `behind` bytes still queued, should wake it now.
+pub fn should_publish(unpublished: usize, behind: usize) -> bool {
+    unpublished > 0 && (behind < FAR_BEHIND || unpublished >= MAX_UNPUBLISHED)
+}
+
+/// Bytes queued in the PTY and not read yet.
+pub fn queued_bytes(file: &std::fs::File) -> usize {
+    use std::os::unix::io::AsRawFd;
+
+    let mut n: libc::c_int = 0;
+    // SAFETY: FIONREAD stores a c_int through the pointer
+    let ok = unsafe { libc::ioctl(file.as_raw_fd(), libc::FIONREAD, &mut n) } == 0;
+    if ok { n.max(0) as usize } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_is_shared_fairly() {
+        let limiter = OutputLimiter::new(1000);
+        let t = Instant::now();
+
+        // Alone, a terminal gets the whole budget
+        assert!(limiter.consume(1, 900, t).is_zero());
+        assert!(!limiter.consume(1, 100, t).is_zero());
+
+        // With two busy terminals each gets half
+        let t = t + FRAME;
+        assert!(limiter.consume(1, 400, t).is_zero());
+        assert!(!limiter.consume(2, 500, t).is_zero());
+        let wait = limiter.consume(1, 100, t + FRAME / 2);
+        assert!(wait > Duration::ZERO && wait <= FRAME / 2);
+
+        limiter.set_bytes_per_frame(0);
+        assert!(limiter.consume(1, 1 << 30, t).is_zero());
+    }
+
+    #[test]
+    fn test_far_behind_skips_intermediate_screens() {
+        assert!(!should_publish(0, 0));
+        assert!(should_publish(4096, 0));
+        assert!(!should_publish(4096, FAR_BEHIND));
+        assert!(should_publish(MAX_UNPUBLISHED, FAR_BEHIND));
+    }
+}
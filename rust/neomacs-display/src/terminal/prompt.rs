@@ -0,0 +1,309 @@
+//! Prompt tracking for REPL-style input editing.
+//!
+//! Shells that support semantic prompts mark each command with OSC 133:
+//! `A` before the prompt, `B` where input starts, `C` when the command
+//! starts printing and `D;EXIT` when it finished.  The reader thread
+//! spots these marks while feeding output to the terminal and records
+//! where input starts, so the host can read and replace just the input
+//! being typed, editing it with Emacs commands, while the output above
+//! stays an ordinary grid.  For shells that send no marks, an opt-in
+//! heuristic takes the first `$ `, `# `, `> ` and the like on the cursor
+//! line as the end of the prompt.
+
+use std::sync::{Mutex, MutexGuard};
+
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::grid::{Dimensions, Grid};
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::cell::{Cell, Flags as CellFlags};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::vte::ansi;
+
+/// Where the shell is in its command cycle, as told by OSC 133
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Phase {
+    #[default]
+    Idle,
+    /// Drawing the prompt
+    Prompt,
+    /// Reading input
+    Input,
+    /// Running a command
+    Output,
+}
+
+/// Prompt tracking state of a terminal
+#[derive(Debug, Default)]
+pub struct PromptState {
+    /// Find prompts by their look while the shell sends no marks
+    pub heuristics: bool,
+    /// Whether the shell has sent OSC 133 marks
+    pub marked: bool,
+    pub phase: Phase,
+    /// Where input started at the last `B` mark, as rows below the start
+    /// of its logical line and column.  Relative, so it survives
+    /// scrolling.
+    input_start: (usize, usize),
+    /// Exit status from the last `D` mark
+    pub last_exit: Option<i32>,
+}
+
+/// The input being typed at a prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputRegion {
+    /// Screen row where input starts; negative in scrollback
+    pub row: i32,
+    pub col: usize,
+    /// Input text, wrapped rows joined, trailing blanks dropped
+    pub text: String,
+    /// Cursor position in `text`, in characters
+    pub cursor: usize,
+}
+
+fn lock(state: &Mutex<PromptState>) -> MutexGuard<'_, PromptState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// First row of the logical (unwrapped) line containing `line`
+fn logical_start(grid: &Grid<Cell>, mut line: Line) -> Line {
+    let top = -(grid.history_size() as i32);
+    let last_col = Column(grid.columns() - 1);
+    while line.0 > top && grid[Point::new(line - 1, last_col)].flags.contains(CellFlags::WRAPLINE) {
+        line -= 1;
+    }
+    line
+}
+
+/// Last row of the logical line containing `line`
+fn logical_end(grid: &Grid<Cell>, mut line: Line) -> Line {
+    let bottom = grid.screen_lines() as i32 - 1;
+    let last_col = Column(grid.columns() - 1);
+    while line.0 < bottom && grid[Point::new(line, last_col)].flags.contains(CellFlags::WRAPLINE) {
+        line += 1;
+    }
+    line
+}
+
+/// Characters that end a prompt when followed by a space
+const PROMPT_ENDS: [char; 7] = ['$', '#', '%', '>', '❯', 'λ', '»'];
+
+/// Where input starts on the cursor's logical line, by the look of the
+/// prompt before the cursor.
+fn heuristic_start(grid: &Grid<Cell>, first: Line, cursor: Point) -> Option<Point> {
+    let mut cells = Vec::new();
+    for line in first.0..=cursor.line.0 {
+        let end = if line == cursor.line.0 { cursor.column.0 } else { grid.columns() };
+        for col in 0..end {
+            let point = Point::new(Line(line), Column(col));
+            if !grid[point].flags.contains(CellFlags::WIDE_CHAR_SPACER) {
+                cells.push((grid[point].c, point));
+            }
+        }
+    }
+    let end = cells
+        .windows(2)
+        .position(|w| PROMPT_ENDS.contains(&w[0].0) && w[1].0 == ' ')?;
+    Some(cells.get(end + 2).map_or(cursor, |&(_, point)| point))
+}
+
+impl PromptState {
+    fn apply<T: EventListener>(&mut self, mark: &[u8], term: &Term<T>) {
+        let grid = term.grid();
+        let cursor = grid.cursor.point;
+        self.marked = true;
+        match mark.first() {
+            Some(b'A') => self.phase = Phase::Prompt,
+            Some(b'B') => {
+                self.phase = Phase::Input;
+                let first = logical_start(grid, cursor.line);
+                self.input_start = ((cursor.line.0 - first.0) as usize, cursor.column.0);
+            }
+            Some(b'C') => self.phase = Phase::Output,
+            Some(b'D') => {
+                self.phase = Phase::Idle;
+                self.last_exit = mark
+                    .get(2..)
+                    .and_then(|p| std::str::from_utf8(p).ok()?.split(';').next()?.parse().ok());
+            }
+            _ => {}
+        }
+    }
+
+    /// The input being typed, or None if the shell is not reading input
+    /// (or, without marks and heuristics, if that is unknown).
+    pub fn input_region<T: EventListener>(&self, term: &Term<T>) -> Option<InputRegion> {
+        let grid = term.grid();
+        let cursor = grid.cursor.point;
+        let first = logical_start(grid, cursor.line);
+        let start = if self.marked {
+            if self.phase != Phase::Input {
+                return None;
+            }
+            Point::new(first + self.input_start.0, Column(self.input_start.1))
+        } else if self.heuristics {
+            heuristic_start(grid, first, cursor)?
+        } else {
+            return None;
+        };
+        if start > cursor {
+            return None;
+        }
+
+        let mut chars = Vec::new();
+        let mut cursor_at = None;
+        for line in start.line.0..=logical_end(grid, cursor.line).0 {
+            let from = if line == start.line.0 { start.column.0 } else { 0 };
+            for col in from..grid.columns() {
+                let point = Point::new(Line(line), Column(col));
+                if point == cursor {
+                    cursor_at = Some(chars.len());
+                }
+                let cell = &grid[point];
+                if !cell.flags.contains(CellFlags::WIDE_CHAR_SPACER) {
+                    chars.push(if cell.c == '\0' { ' ' } else { cell.c });
+                }
+            }
+        }
+        let cursor_at = cursor_at.unwrap_or(chars.len());
+        let used = chars.iter().rposition(|&c| c != ' ').map_or(0, |i| i + 1);
+        chars.truncate(used.max(cursor_at));
+        Some(InputRegion {
+            row: start.line.0,
+            col: start.column.0,
+            text: chars.into_iter().collect(),
+            cursor: cursor_at,
+        })
+    }
+}
+
+const OSC_133: &[u8] = b"\x1b]133;";
+
+/// Longest mark parameters kept; longer ones are not marks we know
+const MAX_PARAMS: usize = 64;
+
+/// Finds OSC 133 marks in terminal output, across reads
+#[derive(Default)]
+pub struct PromptScanner {
+    /// Bytes of `OSC_133` matched so far
+    matched: usize,
+    /// Parameters of the mark being read
+    params: Vec<u8>,
+    /// An ESC, maybe starting the string terminator, ended the parameters
+    escape: bool,
+}
+
+impl PromptScanner {
+    /// Feed `data` to `term` through `processor`, recording OSC 133
+    /// marks in `state` with the cursor where they were printed.
+    pub fn advance<T: EventListener>(
+        &mut self,
+        processor: &mut ansi::Processor,
+        term: &mut Term<T>,
+        data: &[u8],
+        state: &Mutex<PromptState>,
+    ) {
+        let mut fed = 0;
+        let mut i = 0;
+        while i < data.len() {
+            if self.matched == 0 {
+                match data[i..].iter().position(|&b| b == 0x1b) {
+                    Some(skip) => i += skip,
+                    None => break,
+                }
+            }
+            if let Some(mark) = self.step(data[i]) {
+                processor.advance(term, &data[fed..=i]);
+                fed = i + 1;
+                lock(state).apply(&mark, term);
+            }
+            i += 1;
+        }
+        processor.advance(term, &data[fed..]);
+    }
+
+    /// Scan one byte.  Returns the parameters of a mark it completes.
+    fn step(&mut self, b: u8) -> Option<Vec<u8>> {
+        if self.matched < OSC_133.len() {
+            self.matched = if b == OSC_133[self.matched] {
+                self.matched + 1
+            } else {
+                usize::from(b == 0x1b)
+            };
+            return None;
+        }
+        match b {
+            0x07 => Some(self.finish()),
+            b'\\' if self.escape => Some(self.finish()),
+            _ if self.escape => {
+                // ESC that is not a terminator starts another sequence
+                self.finish();
+                self.matched = 1;
+                self.step(b)
+            }
+            0x1b => {
+                self.escape = true;
+                None
+            }
+            _ if self.params.len() >= MAX_PARAMS => {
+                self.finish();
+                None
+            }
+            _ => {
+                self.params.push(b);
+                None
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Vec<u8> {
+        self.matched = 0;
+        self.escape = false;
+        std::mem::take(&mut self.params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::view::TermGridSize;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::Config;
+
+    #[test]
+    fn test_osc133_marks_input_across_reads() {
+        let mut term = Term::new(Config::default(), &TermGridSize::new(10, 4), VoidListener);
+        let mut processor = ansi::Processor::new();
+        let mut scanner = PromptScanner::default();
+        let state = Mutex::new(PromptState::default());
+
+        // Mark split between reads, prompt wrapping onto a second row
+        let output: &[&[u8]] = &[b"out\r\n\x1b]133;A\x07long~/dir/ ", b"$ \x1b]1", b"33;B\x1b\\ls -l"];
+        for chunk in output {
+            scanner.advance(&mut processor, &mut term, chunk, &state);
+        }
+        let region = lock(&state).input_region(&term).unwrap();
+        assert_eq!(region, InputRegion { row: 2, col: 3, text: "ls -l".into(), cursor: 5 });
+
+        // Move the cursor back over "-l"
+        scanner.advance(&mut processor, &mut term, b"\x1b[2D", &state);
+        assert_eq!(lock(&state).input_region(&term).unwrap().cursor, 3);
+
+        scanner.advance(&mut processor, &mut term, b"\r\n\x1b]133;C\x07hi\r\n\x1b]133;D;1\x07", &state);
+        let state = lock(&state);
+        assert_eq!((state.phase, state.last_exit), (Phase::Idle, Some(1)));
+        assert!(state.input_region(&term).is_none());
+    }
+
+    #[test]
+    fn test_heuristic_prompt() {
+        let mut term = Term::new(Config::default(), &TermGridSize::new(10, 4), VoidListener);
+        let mut processor: ansi::Processor = ansi::Processor::new();
+        processor.advance(&mut term, b">>> 1 + 1");
+
+        let mut state = PromptState::default();
+        assert!(state.input_region(&term).is_none());
+        state.heuristics = true;
+        let region = state.input_region(&term).unwrap();
+        assert_eq!((region.row, region.col, region.text.as_str()), (0, 4, "1 + 1"));
+    }
+}
@@ -0,0 +1,527 @@
+//! tmux control-mode client.
+//!
+//! Instead of running tmux inside a terminal and drawing its status line
+//! and pane borders, neo-term can talk to tmux in control mode
+//! (`tmux -C`), where tmux sends pane output and layout changes as text
+//! notifications and accepts ordinary commands.  Each tmux pane gets its
+//! own `TerminalView`, fed with that pane's output, so panes of a remote
+//! session sit in Emacs windows like any other terminal.
+//!
+//! Keystrokes for a pane are sent as `send-keys -H`, and resizing a pane
+//! view resizes the tmux pane (or its window, if it is the only pane).
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+
+use alacritty_terminal::event::{Event as TermEvent, EventListener, OnResize, WindowSize};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::vte::ansi;
+use parking_lot::FairMutex;
+
+use super::view::NeomacsEventProxy;
+use super::TerminalId;
+
+/// tmux pane id, the N of `%N`
+pub type PaneId = u32;
+/// tmux window id, the N of `@N`
+pub type WindowId = u32;
+
+/// A line from tmux in control mode
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    /// Start of the reply to a command
+    Begin,
+    /// End of a reply
+    End,
+    /// End of a reply to a command that failed
+    Error,
+    Output { pane: PaneId, data: Vec<u8> },
+    WindowAdd { window: WindowId },
+    WindowClose { window: WindowId },
+    LayoutChange { window: WindowId, layout: String },
+    /// tmux is detaching this client
+    Exit,
+    /// A notification neo-term does not use
+    Other,
+}
+
+fn parse_id(s: &str, sigil: char) -> Option<u32> {
+    s.strip_prefix(sigil)?.parse().ok()
+}
+
+/// Parse a notification line.  Returns None for lines that are not
+/// notifications, such as the lines of a command reply.
+pub fn parse_line(line: &str) -> Option<Notification> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    if !line.starts_with('%') {
+        return None;
+    }
+    let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let mut args = rest.splitn(2, ' ');
+    let mut arg = || args.next().unwrap_or("");
+    Some(match name {
+        "%begin" => Notification::Begin,
+        "%end" => Notification::End,
+        "%error" => Notification::Error,
+        "%output" => {
+            let pane = parse_id(arg(), '%')?;
+            Notification::Output { pane, data: unescape(arg()) }
+        }
+        "%window-add" => Notification::WindowAdd { window: parse_id(arg(), '@')? },
+        "%window-close" | "%unlinked-window-close" => {
+            Notification::WindowClose { window: parse_id(arg(), '@')? }
+        }
+        "%layout-change" => {
+            let window = parse_id(arg(), '@')?;
+            // The visible layout and flags may follow; the first is enough
+            let layout = arg().split(' ').next().unwrap_or("").to_string();
+            Notification::LayoutChange { window, layout }
+        }
+        "%exit" => Notification::Exit,
+        _ => Notification::Other,
+    })
+}
+
+/// Undo the octal escaping of `%output` data (`\ooo` for control
+/// characters and backslash).
+pub fn unescape(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).filter(|d| d.iter().all(|b| (b'0'..=b'7').contains(b)));
+        match (bytes[i], octal) {
+            (b'\\', Some(d)) => {
+                out.push(d.iter().fold(0u8, |n, b| n.wrapping_mul(8) + (b - b'0')));
+                i += 4;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Size and position of a pane within its window, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaneLayout {
+    pub pane: PaneId,
+    pub cols: u16,
+    pub rows: u16,
+    pub x: u16,
+    pub y: u16,
+}
+
+fn parse_num(s: &[u8], i: &mut usize) -> Option<u16> {
+    let start = *i;
+    while s.get(*i).is_some_and(u8::is_ascii_digit) {
+        *i += 1;
+    }
+    std::str::from_utf8(&s[start..*i]).ok()?.parse().ok()
+}
+
+fn expect(s: &[u8], i: &mut usize, c: u8) -> Option<()> {
+    (s.get(*i) == Some(&c)).then(|| *i += 1)
+}
+
+fn parse_cell(s: &[u8], i: &mut usize, out: &mut Vec<PaneLayout>) -> Option<()> {
+    let cols = parse_num(s, i)?;
+    expect(s, i, b'x')?;
+    let rows = parse_num(s, i)?;
+    expect(s, i, b',')?;
+    let x = parse_num(s, i)?;
+    expect(s, i, b',')?;
+    let y = parse_num(s, i)?;
+    match s.get(*i) {
+        Some(b',') => {
+            *i += 1;
+            let pane = parse_num(s, i)? as PaneId;
+            out.push(PaneLayout { pane, cols, rows, x, y });
+        }
+        Some(&open @ (b'{' | b'[')) => {
+            let close = if open == b'{' { b'}' } else { b']' };
+            *i += 1;
+            loop {
+                parse_cell(s, i, out)?;
+                match s.get(*i) {
+                    Some(b',') => *i += 1,
+                    Some(&c) if c == close => {
+                        *i += 1;
+                        break;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// The panes of a tmux window layout string such as
+/// `b25f,160x48,0,0{80x48,0,0,1,79x48,81,0,2}`.  Returns an empty list
+/// if the layout cannot be parsed.
+pub fn parse_layout(layout: &str) -> Vec<PaneLayout> {
+    // Skip the checksum
+    let body = match layout.split_once(',') {
+        Some((sum, rest)) if !sum.contains('x') => rest,
+        _ => layout,
+    };
+    let mut panes = Vec::new();
+    let mut i = 0;
+    match parse_cell(body.as_bytes(), &mut i, &mut panes) {
+        Some(()) => panes,
+        None => Vec::new(),
+    }
+}
+
+/// Lists each window's id and layout
+const LIST_WINDOWS: &str = "list-windows -F '#{window_id} #{window_layout}'";
+
+/// What the reply to a command sent to tmux is used for
+enum Pending {
+    Ignore,
+    /// `@N layout` lines
+    ListWindows,
+    /// Current screen of a pane, with escapes
+    Capture(PaneId),
+}
+
+/// Where a pane's output goes
+struct PaneSink {
+    term: Arc<FairMutex<Term<NeomacsEventProxy>>>,
+    proxy: NeomacsEventProxy,
+    parser: ansi::Processor,
+}
+
+impl PaneSink {
+    fn feed(&mut self, data: &[u8]) {
+        self.parser.advance(&mut *self.term.lock(), data);
+        self.proxy.send_event(TermEvent::Wakeup);
+    }
+}
+
+struct Shared {
+    stdin: Mutex<ChildStdin>,
+    pending: Mutex<VecDeque<Pending>>,
+    panes: Mutex<HashMap<PaneId, PaneSink>>,
+    /// Panes of each window, from the latest layout
+    windows: Mutex<HashMap<WindowId, Vec<PaneId>>>,
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl Shared {
+    /// Send a command whose reply is handled as `pending`.
+    fn request(&self, pending: Pending, command: &str) -> io::Result<()> {
+        // Holding stdin keeps replies in queue order; queue before writing
+        // because the reply can arrive before write_all returns
+        let mut stdin = lock(&self.stdin);
+        lock(&self.pending).push_back(pending);
+        stdin.write_all(command.as_bytes())?;
+        stdin.write_all(b"\n")?;
+        stdin.flush()
+    }
+}
+
+/// Structural change reported by a tmux client
+#[derive(Debug, Clone, PartialEq)]
+pub enum TmuxEvent {
+    /// The panes of a window, whenever its layout is learned or changes
+    Layout { window: WindowId, panes: Vec<PaneLayout> },
+    WindowClosed { window: WindowId },
+    /// tmux exited or detached the client
+    Exited,
+}
+
+/// Sends events to the client, flagging that there are some
+struct EventSender {
+    tx: mpsc::Sender<TmuxEvent>,
+    pending: Arc<AtomicBool>,
+}
+
+impl EventSender {
+    fn send(&self, event: TmuxEvent) {
+        let _ = self.tx.send(event);
+        self.pending.store(true, Ordering::Release);
+    }
+}
+
+/// A tmux session attached in control mode
+pub struct TmuxClient {
+    shared: Arc<Shared>,
+    child: Child,
+    events: mpsc::Receiver<TmuxEvent>,
+    /// Set when events are waiting in `events`
+    pending: Arc<AtomicBool>,
+    /// Terminal of each pane that has a view
+    pub panes: HashMap<PaneId, TerminalId>,
+    /// Window of each pane that has a view
+    pane_windows: HashMap<PaneId, WindowId>,
+}
+
+impl TmuxClient {
+    /// Run `command` (for example `ssh host tmux -C attach`) through the
+    /// shell and talk to the tmux it starts in control mode.
+    pub fn spawn(id: u32, command: &str) -> io::Result<Self> {
+        let mut child = Command::new("/bin/sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or(io::ErrorKind::BrokenPipe)?;
+        let stdout = child.stdout.take().ok_or(io::ErrorKind::BrokenPipe)?;
+
+        let shared = Arc::new(Shared {
+            stdin: Mutex::new(stdin),
+            // tmux replies to the command it was started with first
+            pending: Mutex::new(VecDeque::from([Pending::Ignore])),
+            panes: Mutex::new(HashMap::new()),
+            windows: Mutex::new(HashMap::new()),
+        });
+        let (tx, events) = mpsc::channel();
+        let pending = Arc::new(AtomicBool::new(false));
+        let sender = EventSender { tx, pending: Arc::clone(&pending) };
+        let reader = Arc::clone(&shared);
+        thread::Builder::new()
+            .name(format!("neo-term-tmux-{}", id))
+            .spawn(move || control_reader(&reader, BufReader::new(stdout), &sender))?;
+
+        shared.request(Pending::ListWindows, LIST_WINDOWS)?;
+        Ok(Self {
+            shared,
+            child,
+            events,
+            pending,
+            panes: HashMap::new(),
+            pane_windows: HashMap::new(),
+        })
+    }
+
+    /// Send a tmux command, such as `split-window -h`.
+    pub fn command(&self, command: &str) -> io::Result<()> {
+        self.shared.request(Pending::Ignore, command)
+    }
+
+    /// Feed `pane`'s output into `term` from now on, starting with the
+    /// pane's current screen.
+    pub fn add_pane(
+        &self,
+        pane: PaneId,
+        term: Arc<FairMutex<Term<NeomacsEventProxy>>>,
+        proxy: NeomacsEventProxy,
+    ) -> io::Result<()> {
+        lock(&self.shared.panes).insert(pane, PaneSink { term, proxy, parser: ansi::Processor::new() });
+        self.shared.request(Pending::Capture(pane), &format!("capture-pane -p -e -t %{}", pane))
+    }
+
+    /// Stop feeding `pane`.
+    pub fn remove_pane(&mut self, pane: PaneId) {
+        lock(&self.shared.panes).remove(&pane);
+        self.pane_windows.remove(&pane);
+    }
+
+    /// Record that `pane` is in `window`.
+    pub fn set_pane_window(&mut self, pane: PaneId, window: WindowId) {
+        self.pane_windows.insert(pane, window);
+    }
+
+    /// Panes with a view that were last seen in `window`.
+    pub fn panes_of(&self, window: WindowId) -> Vec<PaneId> {
+        self.pane_windows.iter().filter(|&(_, &w)| w == window).map(|(&p, _)| p).collect()
+    }
+
+    /// Input and resize handle for `pane`.
+    pub fn control(&self, pane: PaneId) -> PaneControl {
+        PaneControl { pane, shared: Arc::clone(&self.shared) }
+    }
+
+    /// Whether `poll` has something to return.
+    pub fn has_events(&self) -> bool {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// Changes reported since the last call.
+    pub fn poll(&self) -> Vec<TmuxEvent> {
+        self.pending.store(false, Ordering::Release);
+        self.events.try_iter().collect()
+    }
+
+    /// Detach from tmux, leaving the session running.
+    pub fn detach(&self) {
+        let _ = self.command("detach-client");
+    }
+}
+
+impl Drop for TmuxClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn control_reader(shared: &Shared, mut stdout: impl BufRead, tx: &EventSender) {
+    let mut line = Vec::new();
+    // Lines of the command reply being read
+    let mut reply: Option<Vec<String>> = None;
+    loop {
+        line.clear();
+        match stdout.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches('\n');
+
+        if let Some(lines) = reply.as_mut() {
+            match parse_line(text) {
+                Some(Notification::End) => {
+                    let lines = reply.take().unwrap_or_default();
+                    let pending = lock(&shared.pending).pop_front();
+                    handle_reply(shared, pending, lines, tx);
+                }
+                Some(Notification::Error) => {
+                    reply = None;
+                    lock(&shared.pending).pop_front();
+                }
+                _ => lines.push(text.to_string()),
+            }
+            continue;
+        }
+
+        match parse_line(text) {
+            Some(Notification::Begin) => reply = Some(Vec::new()),
+            Some(Notification::Output { pane, data }) => {
+                if let Some(sink) = lock(&shared.panes).get_mut(&pane) {
+                    sink.feed(&data);
+                }
+            }
+            Some(Notification::WindowAdd { .. }) => {
+                let _ = shared.request(Pending::ListWindows, LIST_WINDOWS);
+            }
+            Some(Notification::LayoutChange { window, layout }) => {
+                let panes = parse_layout(&layout);
+                lock(&shared.windows).insert(window, panes.iter().map(|p| p.pane).collect());
+                tx.send(TmuxEvent::Layout { window, panes });
+            }
+            Some(Notification::WindowClose { window }) => {
+                lock(&shared.windows).remove(&window);
+                tx.send(TmuxEvent::WindowClosed { window });
+            }
+            Some(Notification::Exit) => break,
+            _ => {}
+        }
+    }
+    tx.send(TmuxEvent::Exited);
+}
+
+fn handle_reply(shared: &Shared, pending: Option<Pending>, lines: Vec<String>, tx: &EventSender) {
+    match pending {
+        Some(Pending::ListWindows) => {
+            for line in lines {
+                let Some((window, layout)) = line.split_once(' ') else {
+                    continue;
+                };
+                let Some(window) = parse_id(window, '@') else {
+                    continue;
+                };
+                let panes = parse_layout(layout);
+                lock(&shared.windows).insert(window, panes.iter().map(|p| p.pane).collect());
+                tx.send(TmuxEvent::Layout { window, panes });
+            }
+        }
+        Some(Pending::Capture(pane)) => {
+            if let Some(sink) = lock(&shared.panes).get_mut(&pane) {
+                let mut screen = b"\x1b[H\x1b[2J".to_vec();
+                screen.extend(lines.join("\r\n").into_bytes());
+                sink.feed(&screen);
+            }
+        }
+        Some(Pending::Ignore) | None => {}
+    }
+}
+
+/// Sends a pane's input to tmux and resizes it.  Used in place of a PTY
+/// by the pane's `TerminalView`.
+#[derive(Clone)]
+pub struct PaneControl {
+    pane: PaneId,
+    shared: Arc<Shared>,
+}
+
+impl Write for PaneControl {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut command = format!("send-keys -t %{} -H", self.pane);
+        for b in buf {
+            command.push_str(&format!(" {:02x}", b));
+        }
+        self.shared.request(Pending::Ignore, &command)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OnResize for PaneControl {
+    fn on_resize(&mut self, size: WindowSize) {
+        let (cols, rows) = (size.num_cols, size.num_lines);
+        // A window's only pane can only grow with the window
+        let window = lock(&self.shared.windows)
+            .iter()
+            .find(|(_, panes)| panes.as_slice() == [self.pane])
+            .map(|(&window, _)| window);
+        let command = match window {
+            Some(window) => format!("resize-window -t @{} -x {} -y {}", window, cols, rows),
+            None => format!("resize-pane -t %{} -x {} -y {}", self.pane, cols, rows),
+        };
+        let _ = self.shared.request(Pending::Ignore, &command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notifications() {
+        assert_eq!(
+            parse_line("%output %3 ls\\015\\012\\134"),
+            Some(Notification::Output { pane: 3, data: b"ls\r\n\\".to_vec() })
+        );
+        assert_eq!(parse_line("%begin 1363006971 2 1"), Some(Notification::Begin));
+        assert_eq!(
+            parse_line("%layout-change @1 b25f,80x24,0,0,2 b25f,80x24,0,0,2 *"),
+            Some(Notification::LayoutChange { window: 1, layout: "b25f,80x24,0,0,2".into() })
+        );
+        assert_eq!(parse_line("%window-close @4"), Some(Notification::WindowClose { window: 4 }));
+        assert_eq!(parse_line("%sessions-changed"), Some(Notification::Other));
+        assert_eq!(parse_line("@1 b25f,80x24,0,0,2"), None);
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!(
+            parse_layout("b25f,80x24,0,0,2"),
+            vec![PaneLayout { pane: 2, cols: 80, rows: 24, x: 0, y: 0 }]
+        );
+        let panes = parse_layout("4c2a,160x48,0,0{80x48,0,0,1,79x48,81,0[79x24,81,0,5,79x23,81,25,6]}");
+        assert_eq!(panes.iter().map(|p| p.pane).collect::<Vec<_>>(), vec![1, 5, 6]);
+        assert_eq!(panes[2], PaneLayout { pane: 6, cols: 79, rows: 23, x: 81, y: 25 });
+        assert!(parse_layout("garbage").is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! Where a terminal's bytes come from and go to.
+//!
+//! A `TerminalView` only needs a byte stream to read the program's output
+//! from, one to write input to, and a way to tell the program about size
+//! changes.  `Transport` bundles the three, so the same view, input queue
+//! and output pacing work for a local PTY or for an SSH channel to a
+//! remote shell, which does not depend on the local shell setup.
+
+use std::io::{Read, Write};
+
+use alacritty_terminal::event::{OnResize, WindowSize};
+use alacritty_terminal::tty::{self, EventedReadWrite};
+
+use super::output;
+
+/// Reading side of a transport
+pub trait TransportReader: Read + Send {
+    /// Bytes waiting to be read, or 0 if unknown.
+    fn queued(&self) -> usize {
+        0
+    }
+}
+
+impl TransportReader for std::fs::File {
+    fn queued(&self) -> usize {
+        output::queued_bytes(self)
+    }
+}
+
+/// The connection between a terminal and the program it shows
+pub struct Transport {
+    /// Output of the program.  May be non-blocking: `WouldBlock` is
+    /// retried.
+    pub reader: Box<dyn TransportReader>,
+    /// Input to the program
+    pub writer: Box<dyn Write + Send>,
+    /// Keeps the program alive while the terminal exists and tells it
+    /// about size changes.
+    pub control: Box<dyn OnResize + Send>,
+}
+
+impl Transport {
+    /// A local PTY running `shell`, or the user's shell.
+    pub fn local(shell: Option<&str>, size: WindowSize) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut pty_config = tty::Options::default();
+        if let Some(shell_path) = shell {
+            pty_config.shell = Some(tty::Shell::new(shell_path.to_string(), vec![]));
+        }
+
+        // Ensure TERM is set for the child shell process.
+        // In neomacs, the display backend is GPU-based so TERM is typically unset.
+        // alacritty_terminal's child inherits the parent's TERM.
+        if std::env::var("TERM").unwrap_or_default().is_empty() {
+            std::env::set_var("TERM", "xterm-256color");
+        }
+
+        let mut pty = tty::new(&pty_config, size, 0)
+            .map_err(|e| format!("Failed to create PTY: {}", e))?;
+
+        // Clone file handles for concurrent read/write from separate threads.
+        // Both reader() and writer() return &mut File to the same PTY master fd;
+        // try_clone() calls dup(2) to get independent file descriptors.
+        let reader = pty.reader().try_clone()
+            .map_err(|e| format!("Failed to clone PTY reader: {}", e))?;
+        let writer = pty.writer().try_clone()
+            .map_err(|e| format!("Failed to clone PTY writer: {}", e))?;
+
+        Ok(Self {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            control: Box::new(pty),
+        })
+    }
+}
+
+#[cfg(feature = "neo-term-ssh")]
+pub use ssh::SshTarget;
+
+#[cfg(feature = "neo-term-ssh")]
+mod ssh {
+    use std::io::{self, Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use alacritty_terminal::event::{OnResize, WindowSize};
+    use ssh2::{CheckResult, Channel, KnownHostFileKind, Session};
+
+    use super::{Transport, TransportReader};
+
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+    /// Where and how to log in with SSH
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct SshTarget {
+        pub user: String,
+        pub host: String,
+        pub port: u16,
+        /// Command to run instead of the login shell
+        pub command: Option<String>,
+        /// Private key tried after the agent, before the default keys
+        pub identity: Option<PathBuf>,
+    }
+
+    impl SshTarget {
+        /// Parse `[USER@]HOST[:PORT]`.  The user defaults to $USER and
+        /// the port to 22.
+        pub fn parse(destination: &str) -> Option<Self> {
+            let (user, rest) = match destination.rsplit_once('@') {
+                Some((user, rest)) => (user.to_string(), rest),
+                None => (std::env::var("USER").ok()?, destination),
+            };
+            let (host, port) = match rest.rsplit_once(':') {
+                Some((host, port)) if !host.contains(':') => (host, port.parse().ok()?),
+                _ => (rest, 22),
+            };
+            if user.is_empty() || host.is_empty() {
+                return None;
+            }
+            Some(Self { user, host: host.to_string(), port, command: None, identity: None })
+        }
+    }
+
+    /// Retry a call on a non-blocking session until it stops blocking.
+    fn retry<T>(mut f: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        loop {
+            match f() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(2));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    struct SshReader(Channel);
+
+    impl Read for SshReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl TransportReader for SshReader {}
+
+    struct SshWriter(Channel);
+
+    impl Write for SshWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            retry(|| self.0.write(buf))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            retry(|| self.0.flush())
+        }
+    }
+
+    struct SshControl(Channel);
+
+    impl OnResize for SshControl {
+        fn on_resize(&mut self, size: WindowSize) {
+            let cols = size.num_cols as u32;
+            let rows = size.num_lines as u32;
+            let width = cols * size.cell_width as u32;
+            let height = rows * size.cell_height as u32;
+            let result = retry(|| {
+                self.0.request_pty_size(cols, rows, Some(width), Some(height)).map_err(io::Error::from)
+            });
+            if let Err(e) = result {
+                log::warn!("SSH terminal resize failed: {}", e);
+            }
+        }
+    }
+
+    fn home() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(PathBuf::from)
+    }
+
+    /// Refuse hosts whose key is not in ~/.ssh/known_hosts, since there
+    /// is nobody to ask whether to trust it.
+    fn check_host_key(session: &Session, target: &SshTarget) -> Result<(), String> {
+        let (key, _) = session.host_key().ok_or("no host key")?;
+        let mut known = session.known_hosts().map_err(|e| e.to_string())?;
+        if let Some(file) = home().map(|h| h.join(".ssh/known_hosts")) {
+            let _ = known.read_file(&file, KnownHostFileKind::OpenSSH);
+        }
+        match known.check_port(&target.host, target.port, key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::Mismatch => Err(format!("host key of {} has changed", target.host)),
+            CheckResult::NotFound => Err(format!("{} is not in known_hosts; connect with ssh once first", target.host)),
+            CheckResult::Failure => Err("cannot check host key".into()),
+        }
+    }
+
+    fn authenticate(session: &Session, target: &SshTarget) -> Result<(), String> {
+        if session.userauth_agent(&target.user).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+        let defaults = ["id_ed25519", "id_ecdsa", "id_rsa"]
+            .iter()
+            .filter_map(|name| home().map(|h| h.join(".ssh").join(name)));
+        for key in target.identity.iter().cloned().chain(defaults) {
+            if key.exists()
+                && session.userauth_pubkey_file(&target.user, None, &key, None).is_ok()
+                && session.authenticated()
+            {
+                return Ok(());
+            }
+        }
+        Err(format!("cannot log in to {} as {}", target.host, target.user))
+    }
+
+    impl Transport {
+        /// Log in to `target` and run its shell (or command) on a PTY of
+        /// `size`.  Blocks until connected.
+        pub fn ssh(target: &SshTarget, size: WindowSize) -> Result<Self, Box<dyn std::error::Error>> {
+            let addr = (target.host.as_str(), target.port)
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| format!("cannot resolve {}", target.host))?;
+            let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+
+            let mut session = Session::new()?;
+            session.set_tcp_stream(tcp);
+            session.set_timeout(CONNECT_TIMEOUT.as_millis() as u32);
+            session.handshake()?;
+            check_host_key(&session, target)?;
+            authenticate(&session, target)?;
+
+            let mut channel = session.channel_session()?;
+            let (cols, rows) = (size.num_cols as u32, size.num_lines as u32);
+            let pixels = (cols * size.cell_width as u32, rows * size.cell_height as u32);
+            channel.request_pty("xterm-256color", None, Some((cols, rows, pixels.0, pixels.1)))?;
+            match &target.command {
+                Some(command) => channel.exec(command)?,
+                None => channel.shell()?,
+            }
+
+            // The reader polls, so the writer never waits on it for the
+            // session lock
+            session.set_timeout(0);
+            session.set_blocking(false);
+            Ok(Self {
+                reader: Box::new(SshReader(channel.clone())),
+                writer: Box::new(SshWriter(channel.clone())),
+                control: Box::new(SshControl(channel)),
+            })
+        }
+
+        /// `ssh` on a new thread, so a slow host does not block the
+        /// caller.  The transport or the error arrives on the receiver.
+        pub fn ssh_in_background(target: SshTarget, size: WindowSize) -> mpsc::Receiver<Result<Self, String>> {
+            let (tx, rx) = mpsc::channel();
+            let name = format!("neo-term-ssh-{}", target.host);
+            let spawned = std::thread::Builder::new().name(name).spawn({
+                let tx = tx.clone();
+                move || {
+                    let _ = tx.send(Self::ssh(&target, size).map_err(|e| e.to_string()));
+                }
+            });
+            if let Err(e) = spawned {
+                let _ = tx.send(Err(e.to_string()));
+            }
+            rx
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_destination() {
+            let target = SshTarget::parse("alice@example.org:2222").unwrap();
+            assert_eq!((target.user.as_str(), target.host.as_str(), target.port), ("alice", "example.org", 2222));
+            let target = SshTarget::parse("bob@host").unwrap();
+            assert_eq!((target.host.as_str(), target.port), ("host", 22));
+            assert!(SshTarget::parse("alice@").is_none());
+            assert!(SshTarget::parse("alice@host:port").is_none());
+        }
+    }
+}
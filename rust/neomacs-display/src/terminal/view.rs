@@ -1,12 +1,12 @@
 //! TerminalView: manages a single terminal instance (Term + PTY).
 //!
 //! Each TerminalView wraps an `alacritty_terminal::Term`, spawns a PTY
-//! child process (shell), and runs a reader thread to feed PTY output
-//! into the terminal state.
+//! child process (shell) or uses another `Transport`, and runs a reader
+//! thread to feed its output into the terminal state.
 
 use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::sync::Arc;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 
 use parking_lot::FairMutex;
@@ -15,27 +15,38 @@ use alacritty_terminal::event::{Event as TermEvent, EventListener, OnResize, Win
 use alacritty_terminal::grid::Dimensions;
 use alacritty_terminal::index::Column;
 use alacritty_terminal::term::{Config as TermConfig, Term};
-use alacritty_terminal::tty;
-use alacritty_terminal::tty::EventedReadWrite;
 use alacritty_terminal::vte::ansi;
 
+use super::bell::{BellConfig, BellState};
+use super::colors::TerminalTheme;
 use super::content::TerminalContent;
+use super::extract::{ContentExtractor, ExtractTrigger};
+use super::input::{InputQueue, DEFAULT_INPUT_LIMIT};
+use super::local_echo::LocalEcho;
+use super::output::{self, OutputLimiter};
+use super::prompt::{PromptScanner, PromptState};
+use super::tmux::PaneControl;
+use super::transport::Transport;
 use super::{TerminalId, TerminalMode};
 
 /// Grid dimensions for Term::new() and Term::resize().
 ///
 /// alacritty_terminal's `WindowSize` doesn't implement `Dimensions`,
 /// so we provide our own wrapper.
-struct TermGridSize {
+pub struct TermGridSize {
     columns: usize,
     screen_lines: usize,
 }
 
+/// Fewest columns a terminal grid gets: alacritty_terminal panics writing
+/// a double-width character into a grid one column wide
+pub const MIN_COLUMNS: u16 = 2;
+
 impl TermGridSize {
-    fn new(cols: u16, rows: u16) -> Self {
+    pub fn new(cols: u16, rows: u16) -> Self {
         Self {
-            columns: cols as usize,
-            screen_lines: rows as usize,
+            columns: cols.max(MIN_COLUMNS) as usize,
+            screen_lines: rows.max(1) as usize,
         }
     }
 }
@@ -54,6 +65,76 @@ impl Dimensions for TermGridSize {
     }
 }
 
+/// First face id reserved for terminals with their own font, far above
+/// the ids Emacs realizes faces with.
+const TERMINAL_FACE_BASE: u32 = 0xFFF0_0000;
+
+/// Font of a terminal that does not follow the frame font.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TerminalFont {
+    /// Font family, or None for the frame's family.
+    pub family: Option<String>,
+    /// Font size in pixels, or None for the frame's size.
+    pub size: Option<f32>,
+}
+
+impl TerminalFont {
+    /// Whether the terminal simply uses the frame font.
+    pub fn is_default(&self) -> bool {
+        self.family.is_none() && self.size.is_none()
+    }
+}
+
+/// What is drawn behind a terminal's cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TerminalBackground {
+    /// The theme background color
+    #[default]
+    Solid,
+    /// An image from the image cache, scaled to cover the terminal and
+    /// darkened by the background color drawn over it at alpha `dim`.
+    Image { image_id: u32, dim: f32 },
+    /// The editor shows through the background color drawn at
+    /// `opacity`, blurred if `blur` is set.
+    Acrylic { opacity: f32, blur: bool },
+}
+
+/// Pixel size of one terminal cell, and the font drawn in it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellSize {
+    pub width: f32,
+    pub height: f32,
+    pub ascent: f32,
+    pub font_size: f32,
+}
+
+impl Default for CellSize {
+    fn default() -> Self {
+        Self { width: 8.0, height: 16.0, ascent: 12.8, font_size: 14.0 }
+    }
+}
+
+impl CellSize {
+    /// PTY window size for a grid of `cols` x `rows` of these cells, so
+    /// programs that draw images (sixel, kitty) know the pixel size.
+    pub fn window_size(&self, cols: u16, rows: u16) -> WindowSize {
+        WindowSize {
+            num_cols: cols,
+            num_lines: rows,
+            cell_width: self.width.round().max(1.0) as u16,
+            cell_height: self.height.round().max(1.0) as u16,
+        }
+    }
+
+    /// Largest grid of these cells that fits in `width` x `height`
+    /// pixels, or None if not even one cell fits.
+    pub fn grid_for(&self, width: f32, height: f32) -> Option<(u16, u16)> {
+        let cols = (width / self.width).floor().min(u16::MAX as f32) as u16;
+        let rows = (height / self.height).floor().min(u16::MAX as f32) as u16;
+        (cols > 0 && rows > 0).then_some((cols, rows))
+    }
+}
+
 /// Event listener that bridges alacritty events to neomacs.
 #[derive(Clone)]
 pub struct NeomacsEventProxy {
@@ -62,6 +143,10 @@ pub struct NeomacsEventProxy {
     wakeup: Arc<std::sync::atomic::AtomicBool>,
     /// Signals that the terminal child process has exited.
     exited: Arc<std::sync::atomic::AtomicBool>,
+    /// Signals that the terminal received BEL.
+    bell: Arc<std::sync::atomic::AtomicBool>,
+    /// Wakes the content extraction worker.
+    extract: ExtractTrigger,
 }
 
 impl NeomacsEventProxy {
@@ -70,6 +155,8 @@ impl NeomacsEventProxy {
             id,
             wakeup: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             exited: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            bell: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            extract: ExtractTrigger::new(),
         }
     }
 
@@ -83,6 +170,16 @@ impl NeomacsEventProxy {
         self.wakeup.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Check and clear the bell flag.
+    pub fn take_bell(&self) -> bool {
+        self.bell.swap(false, std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Check if a bell is pending without consuming it.
+    pub fn peek_bell(&self) -> bool {
+        self.bell.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     /// Check if the terminal child process has exited.
     pub fn is_exited(&self) -> bool {
         self.exited.load(std::sync::atomic::Ordering::Relaxed)
@@ -94,12 +191,14 @@ impl EventListener for NeomacsEventProxy {
         match event {
             TermEvent::Wakeup => {
                 self.wakeup.store(true, std::sync::atomic::Ordering::Relaxed);
+                self.extract.request();
             }
             TermEvent::Title(title) => {
                 log::debug!("Terminal {}: title changed to '{}'", self.id, title);
             }
             TermEvent::Bell => {
                 log::debug!("Terminal {}: bell", self.id);
+                self.bell.store(true, std::sync::atomic::Ordering::Relaxed);
             }
             TermEvent::Exit => {
                 log::info!("Terminal {}: child process exited", self.id);
@@ -118,13 +217,16 @@ pub struct TerminalView {
     pub term: Arc<FairMutex<Term<NeomacsEventProxy>>>,
     /// Event proxy for wakeup notifications.
     pub event_proxy: NeomacsEventProxy,
-    /// PTY handle - MUST be kept alive to prevent SIGHUP to child shell.
-    /// Also used for on_resize() to send TIOCSWINSZ to the child.
-    pty: tty::Pty,
-    /// PTY master (for writing input to the shell).
-    pty_writer: Box<dyn Write + Send>,
+    /// Transport control - MUST be kept alive to prevent SIGHUP to the
+    /// child shell.  Also used for on_resize() to tell the program about
+    /// size changes (TIOCSWINSZ for a local PTY).
+    control: Box<dyn OnResize + Send>,
+    /// Input queue drained into the PTY master by a writer thread.
+    pub input: InputQueue,
     /// Reader thread handle.
     _reader_thread: Option<JoinHandle<()>>,
+    /// Worker that extracts content off the render thread.
+    extractor: ContentExtractor,
     /// Cached content from last extraction.
     pub last_content: Option<TerminalContent>,
     /// Whether content changed since last render.
@@ -135,6 +237,20 @@ pub struct TerminalView {
     pub float_x: f32,
     pub float_y: f32,
     pub float_opacity: f32,
+    /// Font override set from Lisp.
+    pub font: TerminalFont,
+    /// Cell metrics the terminal is drawn with.
+    pub cell: CellSize,
+    /// Visual bell and rate limiter state.
+    pub bell: BellState,
+    /// What is drawn behind the cells.
+    pub background: TerminalBackground,
+    /// Where the shell's prompt and input are, fed by the reader thread.
+    pub prompt: Arc<Mutex<PromptState>>,
+    /// Guessed echo of typed input, drawn until the program echoes it.
+    pub local_echo: LocalEcho,
+    /// Grid size last given to the terminal, as (cols, rows)
+    grid: (u16, u16),
 }
 
 impl TerminalView {
@@ -145,6 +261,21 @@ impl TerminalView {
         rows: u16,
         mode: TerminalMode,
         shell: Option<&str>,
+        limiter: &OutputLimiter,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let transport = Transport::local(shell, CellSize::default().window_size(cols, rows))?;
+        Self::with_transport(id, cols, rows, mode, transport, limiter)
+    }
+
+    /// Create a terminal showing the program at the other end of
+    /// `transport`.
+    pub fn with_transport(
+        id: TerminalId,
+        cols: u16,
+        rows: u16,
+        mode: TerminalMode,
+        transport: Transport,
+        limiter: &OutputLimiter,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let event_proxy = NeomacsEventProxy::new(id);
 
@@ -154,67 +285,61 @@ impl TerminalView {
 
         let term = Term::new(config, &grid_size, event_proxy.clone());
         let term = Arc::new(FairMutex::new(term));
+        let extractor = ContentExtractor::spawn(id, &event_proxy.extract, Arc::clone(&term))?;
+        let cell = CellSize::default();
 
-        // Create PTY and spawn shell (tty::new needs WindowSize)
-        let window_size = WindowSize {
-            num_cols: cols,
-            num_lines: rows,
-            cell_width: 8,
-            cell_height: 16,
-        };
-
-        let mut pty_config = tty::Options::default();
-        if let Some(shell_path) = shell {
-            pty_config.shell = Some(alacritty_terminal::tty::Shell::new(
-                shell_path.to_string(),
-                vec![],
-            ));
-        }
-
-        // Ensure TERM is set for the child shell process.
-        // In neomacs, the display backend is GPU-based so TERM is typically unset.
-        // alacritty_terminal's child inherits the parent's TERM.
-        if std::env::var("TERM").unwrap_or_default().is_empty() {
-            std::env::set_var("TERM", "xterm-256color");
-        }
-
-        let mut pty = tty::new(&pty_config, window_size, 0)
-            .map_err(|e| format!("Failed to create PTY: {}", e))?;
-
-        // Clone file handles for concurrent read/write from separate threads.
-        // Both reader() and writer() return &mut File to the same PTY master fd;
-        // try_clone() calls dup(2) to get independent file descriptors.
-        let pty_read_file = pty.reader().try_clone()
-            .map_err(|e| format!("Failed to clone PTY reader: {}", e))?;
-        let pty_write_file = pty.writer().try_clone()
-            .map_err(|e| format!("Failed to clone PTY writer: {}", e))?;
+        let Transport { reader: pty_reader, writer, control } = transport;
+        let input = InputQueue::spawn(id, writer, DEFAULT_INPUT_LIMIT)?;
 
         // Spawn reader thread: reads from PTY, feeds into term via ansi::Processor
         let term_clone = Arc::clone(&term);
         let proxy_clone = event_proxy.clone();
+        let limiter = limiter.clone();
+        let prompt = Arc::new(Mutex::new(PromptState::default()));
+        let prompt_clone = Arc::clone(&prompt);
         let reader_thread = thread::Builder::new()
             .name(format!("neo-term-{}-pty", id))
             .spawn(move || {
-                let mut reader = pty_read_file;
+                let mut reader = pty_reader;
                 let mut processor: ansi::Processor = ansi::Processor::new();
+                let mut prompts = PromptScanner::default();
                 let mut buf = [0u8; 4096];
+                // Bytes parsed since content was last signalled
+                let mut unpublished = 0;
                 loop {
                     match reader.read(&mut buf) {
                         Ok(0) => {
                             // PTY closed (child exited)
+                            proxy_clone.send_event(TermEvent::Wakeup);
                             proxy_clone.send_event(TermEvent::Exit);
                             break;
                         }
                         Ok(n) => {
+                            let wait = limiter.consume(id, n, std::time::Instant::now());
                             let mut term = term_clone.lock();
-                            processor.advance(&mut *term, &buf[..n]);
-                            // Signal that content changed
-                            proxy_clone.send_event(TermEvent::Wakeup);
+                            prompts.advance(&mut processor, &mut term, &buf[..n], &prompt_clone);
+                            drop(term);
+                            unpublished += n;
+                            // Signal that content changed, unless far
+                            // behind and the screen is about to change again
+                            if !wait.is_zero()
+                                || output::should_publish(unpublished, reader.queued())
+                            {
+                                proxy_clone.send_event(TermEvent::Wakeup);
+                                unpublished = 0;
+                            }
+                            if !wait.is_zero() {
+                                std::thread::sleep(wait);
+                            }
                         }
                         Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {
                             continue;
                         }
                         Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            if unpublished > 0 {
+                                proxy_clone.send_event(TermEvent::Wakeup);
+                                unpublished = 0;
+                            }
                             // Non-blocking fd, wait and retry
                             std::thread::sleep(std::time::Duration::from_millis(10));
                             continue;
@@ -232,22 +357,91 @@ impl TerminalView {
             mode,
             term,
             event_proxy,
-            pty,
-            pty_writer: Box::new(pty_write_file),
+            control,
+            input,
             _reader_thread: Some(reader_thread),
+            extractor,
+            last_content: None,
+            dirty: true,
+            exit_notified: false,
+            float_x: 0.0,
+            float_y: 0.0,
+            float_opacity: 1.0,
+            font: TerminalFont::default(),
+            cell,
+            bell: BellState::default(),
+            background: TerminalBackground::default(),
+            prompt,
+            local_echo: LocalEcho::default(),
+            grid: (cols, rows),
+        })
+    }
+
+    /// Create a view of a tmux pane.  There is no local child: output is
+    /// fed into `term` by the tmux client, and input and resizes go to
+    /// tmux through `control`.
+    pub fn for_tmux_pane(
+        id: TerminalId,
+        cols: u16,
+        rows: u16,
+        mode: TerminalMode,
+        control: PaneControl,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let event_proxy = NeomacsEventProxy::new(id);
+        let term = Term::new(TermConfig::default(), &TermGridSize::new(cols, rows), event_proxy.clone());
+        let term = Arc::new(FairMutex::new(term));
+        let extractor = ContentExtractor::spawn(id, &event_proxy.extract, Arc::clone(&term))?;
+        let input = InputQueue::spawn(id, Box::new(control.clone()), DEFAULT_INPUT_LIMIT)?;
+
+        Ok(Self {
+            id,
+            mode,
+            term,
+            event_proxy,
+            control: Box::new(control),
+            input,
+            _reader_thread: None,
+            extractor,
             last_content: None,
             dirty: true,
             exit_notified: false,
             float_x: 0.0,
             float_y: 0.0,
             float_opacity: 1.0,
+            font: TerminalFont::default(),
+            cell: CellSize::default(),
+            bell: BellState::default(),
+            background: TerminalBackground::default(),
+            prompt: Default::default(),
+            local_echo: LocalEcho::default(),
+            grid: (cols, rows),
         })
     }
 
-    /// Write input data to the terminal's PTY (keyboard input from user).
-    pub fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
-        self.pty_writer.write_all(data)?;
-        self.pty_writer.flush()
+    /// Queue input data for the terminal's PTY (keyboard input from user).
+    /// Never blocks; see `input` for backpressure.
+    pub fn write(&self, data: &[u8]) {
+        self.input.push(data);
+    }
+
+    /// Queue input typed by the user, guessing its echo.  Returns true
+    /// if the guesses drawn changed.
+    pub fn write_typed(&mut self, data: &[u8]) -> bool {
+        self.write(data);
+        self.local_echo.typed(data, self.last_content.as_ref(), std::time::Instant::now())
+    }
+
+    /// Queue several pieces of input in order.
+    pub fn write_many(&self, chunks: &[&[u8]]) {
+        self.input.write_many(chunks);
+    }
+
+    /// Show `data` as if the program had printed it, e.g. the scrollback
+    /// of a restored session.  Nothing reaches the program.
+    pub fn replay(&self, data: &[u8]) {
+        let mut processor: ansi::Processor = ansi::Processor::new();
+        processor.advance(&mut *self.term.lock(), data);
+        self.event_proxy.send_event(TermEvent::Wakeup);
     }
 
     /// Resize the terminal grid and PTY.
@@ -258,26 +452,104 @@ impl TerminalView {
         drop(term);
 
         // Send TIOCSWINSZ to the PTY so the child process gets SIGWINCH
-        let window_size = WindowSize {
-            num_cols: cols,
-            num_lines: rows,
-            cell_width: 8,
-            cell_height: 16,
-        };
-        self.pty.on_resize(window_size);
+        self.control.on_resize(self.cell.window_size(cols, rows));
+        self.grid = (cols, rows);
         self.dirty = true;
     }
 
-    /// Extract current content for rendering. Returns true if content changed.
-    pub fn update_content(&mut self) -> bool {
-        if self.event_proxy.take_wakeup() || self.dirty {
-            let term = self.term.lock();
-            self.last_content = Some(TerminalContent::from_term(&*term));
-            self.dirty = false;
-            true
+    /// Resize the grid to the most cells that fit in `width` x `height`
+    /// pixels, as allotted by the layout of an inline terminal.  Returns
+    /// true if the grid changed.
+    pub fn fit_to(&mut self, width: f32, height: f32) -> bool {
+        match self.cell.grid_for(width, height) {
+            Some((cols, rows)) if (cols, rows) != self.grid => {
+                self.resize(cols, rows);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Face id the terminal's text is drawn with: the frame's default
+    /// face, or a face reserved for this terminal when it has its own
+    /// font.  Terminal ids keep their slot index in the low 20 bits, so
+    /// live terminals never share a face.
+    pub fn font_face_id(&self) -> u32 {
+        if self.font.is_default() {
+            0
         } else {
-            false
+            TERMINAL_FACE_BASE | (self.id & 0x000F_FFFF)
+        }
+    }
+
+    /// Change the cell metrics.  The PTY is told about the new pixel size
+    /// when it changes, without changing the grid.
+    pub fn set_cell_size(&mut self, cell: CellSize) {
+        if cell == self.cell {
+            return;
+        }
+        let term = self.term.lock();
+        let (cols, rows) = (term.columns() as u16, term.screen_lines() as u16);
+        drop(term);
+        let old = self.cell.window_size(cols, rows);
+        self.cell = cell;
+        let new = cell.window_size(cols, rows);
+        if (old.cell_width, old.cell_height) != (new.cell_width, new.cell_height) {
+            self.control.on_resize(new);
+        }
+    }
+
+    /// Pick up content extracted since the last call, asking the worker
+    /// for a fresh copy first if the view was marked dirty.  Never locks
+    /// the terminal.  Returns true if content changed.
+    pub fn update_content(&mut self, theme: &TerminalTheme) -> bool {
+        if self.dirty {
+            self.extractor.request(theme);
+            self.dirty = false;
         }
+        self.event_proxy.take_wakeup();
+        let now = std::time::Instant::now();
+        let expired = self.local_echo.expire(now);
+        match self.extractor.take() {
+            Some(content) => {
+                self.local_echo.content_arrived(&content, now);
+                if let Some(old) = self.last_content.replace(content) {
+                    self.extractor.recycle(old);
+                }
+                true
+            }
+            None => expired,
+        }
+    }
+
+    /// Whether `update_content` has something to do.
+    pub fn has_pending_content(&self) -> bool {
+        self.dirty
+            || self.extractor.has_ready()
+            || self.event_proxy.peek_bell()
+            || self.bell.is_showing(std::time::Instant::now())
+            || self.local_echo.expired(std::time::Instant::now())
+    }
+
+    /// Drop scrollback beyond the newest `keep` lines, unless the user is
+    /// scrolled back into it.  Later output can grow the history again up
+    /// to the usual limit.  Returns true if anything was dropped.
+    pub fn trim_scrollback(&mut self, keep: usize) -> bool {
+        let mut term = self.term.lock();
+        let grid = term.grid_mut();
+        if grid.display_offset() > 0 || grid.history_size() <= keep {
+            return false;
+        }
+        grid.update_history(keep);
+        grid.update_history(TermConfig::default().scrolling_history);
+        true
+    }
+
+    /// Bytes held by the grid with its scrollback, and by the last
+    /// extracted content
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let grid = super::content::grid_memory_usage(&*self.term.lock());
+        (grid, self.last_content.as_ref().map_or(0, TerminalContent::memory_usage))
     }
 
     /// Get the last extracted content.
@@ -302,20 +574,87 @@ impl TerminalView {
     }
 }
 
+impl Drop for TerminalView {
+    fn drop(&mut self) {
+        self.input.close();
+    }
+}
+
 /// Manages all terminal instances.
 pub struct TerminalManager {
     pub terminals: HashMap<TerminalId, TerminalView>,
+    /// Terminals whose shell and grid are kept alive without being drawn
+    detached: HashMap<TerminalId, TerminalView>,
     next_id: TerminalId,
+    /// Colors used to render all terminals
+    theme: TerminalTheme,
+    /// Scrollback lines kept when trimming at idle time
+    idle_scrollback: usize,
+    /// Bell settings of all terminals
+    pub bell: BellConfig,
+    /// Output budget shared by the PTY readers of all terminals
+    output: OutputLimiter,
 }
 
 impl TerminalManager {
     pub fn new() -> Self {
         Self {
             terminals: HashMap::new(),
+            detached: HashMap::new(),
             next_id: 1,
+            theme: TerminalTheme::default(),
+            idle_scrollback: 5000,
+            bell: BellConfig::default(),
+            output: OutputLimiter::default(),
         }
     }
 
+    /// Output budget shared by the PTY readers, for creating terminals.
+    pub fn output_limiter(&self) -> &OutputLimiter {
+        &self.output
+    }
+
+    /// Get the colors used to render terminals.
+    pub fn theme(&self) -> &TerminalTheme {
+        &self.theme
+    }
+
+    /// Change the colors used to render terminals, redrawing all of them.
+    pub fn set_theme(&mut self, theme: TerminalTheme) {
+        self.theme = theme;
+        for view in self.terminals.values_mut().chain(self.detached.values_mut()) {
+            view.dirty = true;
+        }
+    }
+
+    /// Set how many scrollback lines `trim_scrollback` keeps.
+    pub fn set_idle_scrollback(&mut self, lines: usize) {
+        self.idle_scrollback = lines;
+    }
+
+    /// Handle bells received since the last call.  Returns the terminals
+    /// whose bell got past the rate limiter.
+    pub fn ring_bells(&mut self, now: std::time::Instant) -> Vec<TerminalId> {
+        let config = self.bell;
+        self.terminals
+            .iter_mut()
+            .filter(|(_, view)| view.event_proxy.take_bell())
+            .filter_map(|(&id, view)| view.bell.ring(now, &config).then_some(id))
+            .collect()
+    }
+
+    /// Trim the scrollback of every terminal to the idle limit.  Returns
+    /// how many terminals were trimmed.
+    pub fn trim_scrollback(&mut self) -> usize {
+        let keep = self.idle_scrollback;
+        self.terminals
+            .values_mut()
+            .chain(self.detached.values_mut())
+            .map(|v| v.trim_scrollback(keep))
+            .filter(|&trimmed| trimmed)
+            .count()
+    }
+
     /// Create a new terminal and return its ID.
     pub fn create(
         &mut self,
@@ -326,23 +665,67 @@ impl TerminalManager {
     ) -> Result<TerminalId, Box<dyn std::error::Error>> {
         let id = self.next_id;
         self.next_id += 1;
-        let view = TerminalView::new(id, cols, rows, mode, shell)?;
+        let view = TerminalView::new(id, cols, rows, mode, shell, &self.output)?;
         self.terminals.insert(id, view);
         Ok(id)
     }
 
-    /// Destroy a terminal.
+    /// Destroy a terminal, attached or not.
     pub fn destroy(&mut self, id: TerminalId) -> bool {
-        self.terminals.remove(&id).is_some()
+        self.terminals.remove(&id).or_else(|| self.detached.remove(&id)).is_some()
     }
 
-    /// Get a terminal by ID.
+    /// Stop drawing a terminal while keeping its shell, grid and input
+    /// queue alive, so it can be attached again later, for example in
+    /// another frame.  Returns false if it is not attached.
+    pub fn detach(&mut self, id: TerminalId) -> bool {
+        match self.terminals.remove(&id) {
+            Some(view) => {
+                self.detached.insert(id, view);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Draw a detached terminal again, in `mode`.  It starts out like a
+    /// new view of the same session: floating placement and a bell
+    /// rung while it was detached are reset.  Returns false if it is
+    /// not detached.
+    pub fn attach(&mut self, id: TerminalId, mode: TerminalMode) -> bool {
+        let Some(mut view) = self.detached.remove(&id) else {
+            return false;
+        };
+        view.mode = mode;
+        view.float_x = 0.0;
+        view.float_y = 0.0;
+        view.float_opacity = 1.0;
+        view.event_proxy.take_bell();
+        view.dirty = true;
+        self.terminals.insert(id, view);
+        true
+    }
+
+    /// Get a terminal by ID, attached or not.
     pub fn get(&self, id: TerminalId) -> Option<&TerminalView> {
-        self.terminals.get(&id)
+        self.terminals.get(&id).or_else(|| self.detached.get(&id))
     }
 
-    /// Get a mutable terminal by ID.
+    /// Get a mutable terminal by ID, attached or not.
     pub fn get_mut(&mut self, id: TerminalId) -> Option<&mut TerminalView> {
+        match self.terminals.get_mut(&id) {
+            Some(view) => Some(view),
+            None => self.detached.get_mut(&id),
+        }
+    }
+
+    /// Get a terminal that is being drawn.
+    pub fn get_attached(&self, id: TerminalId) -> Option<&TerminalView> {
+        self.terminals.get(&id)
+    }
+
+    /// Get a mutable terminal that is being drawn.
+    pub fn get_attached_mut(&mut self, id: TerminalId) -> Option<&mut TerminalView> {
         self.terminals.get_mut(&id)
     }
 
@@ -350,18 +733,34 @@ impl TerminalManager {
     pub fn update_all(&mut self) -> Vec<TerminalId> {
         let mut changed = Vec::new();
         for (id, view) in &mut self.terminals {
-            if view.update_content() {
+            if view.update_content(&self.theme) {
                 changed.push(*id);
             }
         }
         changed
     }
 
-    /// Get all terminal IDs.
+    /// Get the IDs of all attached terminals.
     pub fn ids(&self) -> Vec<TerminalId> {
         self.terminals.keys().copied().collect()
     }
 
+    /// Get the IDs of detached terminals.
+    pub fn detached_ids(&self) -> Vec<TerminalId> {
+        self.detached.keys().copied().collect()
+    }
+
+    /// Number of terminals, detached ones included, and the bytes held by
+    /// their grids and by their extracted content (see
+    /// `TerminalView::memory_usage`)
+    pub fn memory_usage(&self) -> (usize, usize, usize) {
+        let views = self.terminals.values().chain(self.detached.values());
+        views.fold((0, 0, 0), |(count, grids, contents), view| {
+            let (grid, content) = view.memory_usage();
+            (count + 1, grids + grid, contents + content)
+        })
+    }
+
     /// Number of active terminals.
     pub fn len(&self) -> usize {
         self.terminals.len()
@@ -381,6 +780,42 @@ impl Default for TerminalManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alacritty_terminal::tty::{self, EventedReadWrite};
+
+    #[test]
+    fn test_cell_size_reaches_pty_window_size() {
+        let cell = CellSize { width: 9.6, height: 19.2, ascent: 15.0, font_size: 16.0 };
+        let size = cell.window_size(80, 24);
+        assert_eq!((size.num_cols, size.num_lines), (80, 24));
+        assert_eq!((size.cell_width, size.cell_height), (10, 19));
+
+        // An inline terminal allotted 80.5 columns by 3.9 rows gets 80x3
+        assert_eq!(cell.grid_for(9.6 * 80.5, 19.2 * 3.9), Some((80, 3)));
+        assert_eq!(cell.grid_for(9.0, 100.0), None);
+
+        let font = TerminalFont { family: None, size: Some(16.0) };
+        assert!(!font.is_default());
+        assert!(TerminalFont::default().is_default());
+    }
+
+    #[test]
+    fn test_detached_terminal_stays_alive() {
+        let mut manager = TerminalManager::new();
+        let id = manager.create(20, 5, TerminalMode::Window, Some("/bin/cat")).unwrap();
+
+        assert!(manager.detach(id));
+        assert!(manager.ids().is_empty());
+        assert!(manager.get_attached(id).is_none());
+        // The session still takes input and commands while detached
+        assert!(manager.get(id).is_some());
+        assert!(!manager.detach(id));
+
+        assert!(manager.attach(id, TerminalMode::Floating));
+        assert_eq!(manager.ids(), vec![id]);
+        assert_eq!(manager.get(id).unwrap().mode, TerminalMode::Floating);
+        assert!(manager.destroy(id));
+        assert!(!manager.attach(id, TerminalMode::Window));
+    }
 
     #[test]
     fn test_alacritty_pty_explicit_cmd() {
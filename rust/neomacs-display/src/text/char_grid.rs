@@ -0,0 +1,155 @@
+//! Virtualized character grid layout.
+//!
+//! Lays out thousands of cells (emoji pickers, glyph tables) in fixed-size
+//! rows and only ever touches the rows inside the viewport, so the cost of
+//! a frame depends on the visible area, not on the number of items.
+
+use std::ops::Range;
+
+/// Geometry of a scrollable character grid (logical pixels)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharGridLayout {
+    /// Top-left corner of the grid
+    pub x: f32,
+    pub y: f32,
+    /// Cells per row
+    pub columns: usize,
+    /// Rows shown at once
+    pub visible_rows: usize,
+    /// Width and height of one square cell
+    pub cell_size: f32,
+    /// Total number of items
+    pub item_count: usize,
+    /// First visible row
+    pub scroll_row: usize,
+}
+
+impl CharGridLayout {
+    pub fn new(x: f32, y: f32, columns: usize, visible_rows: usize, cell_size: f32, item_count: usize) -> Self {
+        Self {
+            x,
+            y,
+            columns: columns.max(1),
+            visible_rows: visible_rows.max(1),
+            cell_size: cell_size.max(1.0),
+            item_count,
+            scroll_row: 0,
+        }
+    }
+
+    /// Total rows needed for all items
+    pub fn total_rows(&self) -> usize {
+        self.item_count.div_ceil(self.columns)
+    }
+
+    /// Largest valid scroll row
+    pub fn max_scroll_row(&self) -> usize {
+        self.total_rows().saturating_sub(self.visible_rows)
+    }
+
+    /// Set the first visible row, clamped to the content
+    pub fn set_scroll_row(&mut self, row: usize) {
+        self.scroll_row = row.min(self.max_scroll_row());
+    }
+
+    /// Scroll the minimum amount needed to make `index` visible
+    pub fn scroll_to(&mut self, index: usize) {
+        if index >= self.item_count {
+            return;
+        }
+        let row = index / self.columns;
+        if row < self.scroll_row {
+            self.scroll_row = row;
+        } else if row >= self.scroll_row + self.visible_rows {
+            self.scroll_row = row + 1 - self.visible_rows;
+        }
+        self.set_scroll_row(self.scroll_row);
+    }
+
+    /// Apply a scroll position and selection from the host.
+    ///
+    /// A negative or out-of-range `selected` clears the selection; a valid
+    /// one is scrolled into view.  Returns the effective selection.
+    pub fn apply(&mut self, selected: i32, scroll_row: u32) -> Option<usize> {
+        self.set_scroll_row(scroll_row as usize);
+        let selected = usize::try_from(selected).ok().filter(|&i| i < self.item_count);
+        if let Some(index) = selected {
+            self.scroll_to(index);
+        }
+        selected
+    }
+
+    /// Pixel size of the visible grid
+    pub fn size(&self) -> (f32, f32) {
+        (
+            self.columns as f32 * self.cell_size,
+            self.visible_rows as f32 * self.cell_size,
+        )
+    }
+
+    /// Indices of the items inside the viewport
+    pub fn visible_range(&self) -> Range<usize> {
+        let start = (self.scroll_row * self.columns).min(self.item_count);
+        let end = ((self.scroll_row + self.visible_rows) * self.columns).min(self.item_count);
+        start..end
+    }
+
+    /// Top-left corner of the cell for `index`, if it is visible
+    pub fn cell_origin(&self, index: usize) -> Option<(f32, f32)> {
+        if !self.visible_range().contains(&index) {
+            return None;
+        }
+        let col = index % self.columns;
+        let row = index / self.columns - self.scroll_row;
+        Some((
+            self.x + col as f32 * self.cell_size,
+            self.y + row as f32 * self.cell_size,
+        ))
+    }
+
+    /// Item under the point (px, py), if any
+    pub fn index_at(&self, px: f32, py: f32) -> Option<usize> {
+        let (w, h) = self.size();
+        if px < self.x || py < self.y || px >= self.x + w || py >= self.y + h {
+            return None;
+        }
+        let col = ((px - self.x) / self.cell_size) as usize;
+        let row = ((py - self.y) / self.cell_size) as usize + self.scroll_row;
+        let index = row * self.columns + col.min(self.columns - 1);
+        (index < self.item_count).then_some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_range_and_scroll() {
+        let mut grid = CharGridLayout::new(0.0, 0.0, 8, 4, 32.0, 3000);
+        assert_eq!(grid.total_rows(), 375);
+        assert_eq!(grid.visible_range(), 0..32);
+
+        grid.scroll_to(100);
+        assert_eq!(grid.scroll_row, 9);
+        assert!(grid.visible_range().contains(&100));
+
+        grid.set_scroll_row(10_000);
+        assert_eq!(grid.scroll_row, grid.max_scroll_row());
+        assert_eq!(grid.visible_range().end, 3000);
+    }
+
+    #[test]
+    fn test_hit_testing() {
+        let mut grid = CharGridLayout::new(10.0, 20.0, 4, 2, 20.0, 10);
+        assert_eq!(grid.index_at(10.0, 20.0), Some(0));
+        assert_eq!(grid.index_at(75.0, 45.0), Some(7));
+        assert_eq!(grid.index_at(5.0, 25.0), None);
+        grid.set_scroll_row(1);
+        // Row 2 has only items 8 and 9
+        assert_eq!(grid.index_at(35.0, 45.0), Some(9));
+        assert_eq!(grid.index_at(55.0, 45.0), None);
+        assert_eq!(grid.cell_origin(9), Some((30.0, 40.0)));
+        assert_eq!(grid.cell_origin(0), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Caret positions inside shaped runs.
+//!
+//! A ligature draws several characters as one glyph, so the cursor and
+//! selection edges for the characters inside it cannot be read off the
+//! glyph boundaries.  `ClusterOffsets` records an x offset for every
+//! character boundary of a shaped run, splitting a multi-character glyph
+//! evenly between the characters it covers.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// One shaped glyph: the byte range of the text it covers, its pen
+/// position and advance, and whether it was laid out right-to-left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedCluster {
+    pub start: usize,
+    pub end: usize,
+    pub x: f32,
+    pub width: f32,
+    pub rtl: bool,
+}
+
+/// X offset of every character boundary in a shaped run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClusterOffsets {
+    /// `boundaries[i]` is the caret x before character `i`; the last entry
+    /// is the caret after the final character.
+    boundaries: Vec<f32>,
+}
+
+impl ClusterOffsets {
+    /// Build offsets for `text` from its shaped glyphs, in any order.
+    pub fn from_clusters(text: &str, clusters: impl IntoIterator<Item = ShapedCluster>) -> Self {
+        // Merge glyphs of the same cluster (base + combining marks)
+        let mut merged: Vec<ShapedCluster> = Vec::new();
+        for c in clusters {
+            if c.end <= c.start || c.end > text.len() {
+                continue;
+            }
+            match merged.iter_mut().find(|m| m.start == c.start) {
+                Some(m) => {
+                    let left = m.x.min(c.x);
+                    let right = (m.x + m.width).max(c.x + c.width);
+                    m.x = left;
+                    m.width = right - left;
+                    m.end = m.end.max(c.end);
+                }
+                None => merged.push(c),
+            }
+        }
+        merged.sort_by_key(|c| c.start);
+
+        let count = text.chars().count();
+        let mut boundaries: Vec<Option<f32>> = vec![None; count + 1];
+        let mut char_index = 0;
+        let mut byte = 0;
+        let mut run_end = 0.0f32;
+        for c in &merged {
+            if c.start < byte || !text.is_char_boundary(c.start) || !text.is_char_boundary(c.end) {
+                continue;
+            }
+            char_index += text[byte..c.start].chars().count();
+            let chars = text[c.start..c.end].chars().count();
+            for j in 0..chars {
+                let frac = j as f32 / chars as f32;
+                let x = if c.rtl { c.x + c.width * (1.0 - frac) } else { c.x + c.width * frac };
+                boundaries[char_index + j] = Some(x);
+            }
+            char_index += chars;
+            byte = c.end;
+            let after = if c.rtl { c.x } else { c.x + c.width };
+            boundaries[char_index] = Some(after);
+            run_end = run_end.max(c.x + c.width);
+        }
+
+        // Characters without a glyph share the previous caret position
+        let mut last = 0.0;
+        let boundaries = boundaries
+            .into_iter()
+            .enumerate()
+            .map(|(i, b)| {
+                last = b.unwrap_or(if i == count { run_end.max(last) } else { last });
+                last
+            })
+            .collect();
+        Self { boundaries }
+    }
+
+    /// Number of characters in the run
+    pub fn len(&self) -> usize {
+        self.boundaries.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Caret x before character `index` (clamped to the end of the run)
+    pub fn x_for_char_index(&self, index: usize) -> f32 {
+        self.boundaries
+            .get(index.min(self.len()))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Character boundary closest to `x`, in `0..=len()`
+    pub fn char_index_for_x(&self, x: f32) -> usize {
+        self.boundaries
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| (*a - x).abs().total_cmp(&(*b - x).abs()))
+            .map_or(0, |(i, _)| i)
+    }
+}
+
+/// Key for shaped-run offsets: the composed text, its face and font size
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ClusterKey {
+    pub text: Box<str>,
+    pub face_id: u32,
+    /// Font size in pixels (as f32 bits)
+    pub font_size_bits: u32,
+}
+
+/// Offsets published by the render thread when it shapes composed
+/// glyphs, readable from the Emacs thread for cursor placement
+pub type SharedClusterOffsets = Arc<Mutex<HashMap<ClusterKey, ClusterOffsets>>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(start: usize, end: usize, x: f32, width: f32) -> ShapedCluster {
+        ShapedCluster { start, end, x, width, rtl: false }
+    }
+
+    #[test]
+    fn test_ligature_splits_advance_between_characters() {
+        // "a=>b": the font draws "=>" as one 16px ligature
+        let text = "a=>b";
+        let offsets = ClusterOffsets::from_clusters(
+            text,
+            [cluster(0, 1, 0.0, 8.0), cluster(1, 3, 8.0, 16.0), cluster(3, 4, 24.0, 8.0)],
+        );
+        assert_eq!(offsets.len(), 4);
+        let xs: Vec<f32> = (0..=4).map(|i| offsets.x_for_char_index(i)).collect();
+        assert_eq!(xs, vec![0.0, 8.0, 16.0, 24.0, 32.0]);
+        assert_eq!(offsets.char_index_for_x(15.0), 2);
+        assert_eq!(offsets.char_index_for_x(100.0), 4);
+        assert_eq!(offsets.x_for_char_index(9), 32.0);
+    }
+
+    #[test]
+    fn test_multibyte_and_combining_marks() {
+        // "é" as e + U+0301 shaped into two glyphs of one cluster, then "→"
+        let text = "e\u{301}\u{2192}";
+        let offsets = ClusterOffsets::from_clusters(
+            text,
+            [cluster(0, 3, 0.0, 8.0), cluster(0, 3, 1.0, 5.0), cluster(3, 6, 8.0, 10.0)],
+        );
+        assert_eq!(offsets.len(), 3);
+        assert_eq!(offsets.x_for_char_index(1), 4.0);
+        assert_eq!(offsets.x_for_char_index(2), 8.0);
+        assert_eq!(offsets.x_for_char_index(3), 18.0);
+    }
+}
@@ -0,0 +1,254 @@
+//! Emoji category data for picker UIs.
+//!
+//! Loads the Unicode `emoji-test.txt` table shipped by most distributions
+//! (unicode-data, unicode-emoji packages), so hosts get grouped, named
+//! emoji without bundling their own tables.  When no table is installed,
+//! a coarse fallback built from the main emoji blocks is used instead.
+
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the emoji table location
+pub const EMOJI_DATA_ENV: &str = "NEOMACS_EMOJI_DATA";
+
+/// Well-known install locations of `emoji-test.txt`
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr/share/unicode/emoji/emoji-test.txt",
+    "/usr/share/unicode-data/emoji/emoji-test.txt",
+    "/usr/share/unicode/emoji-test.txt",
+    "/usr/local/share/unicode/emoji/emoji-test.txt",
+    "/run/current-system/sw/share/unicode/emoji/emoji-test.txt",
+];
+
+/// Fallback categories when no table is installed: (name, first, last)
+const FALLBACK_BLOCKS: &[(&str, u32, u32)] = &[
+    ("Smileys & Emotion", 0x1F600, 0x1F64F),
+    ("People & Body", 0x1F466, 0x1F487),
+    ("Animals & Nature", 0x1F400, 0x1F43F),
+    ("Food & Drink", 0x1F32D, 0x1F37F),
+    ("Travel & Places", 0x1F680, 0x1F6C5),
+    ("Activities", 0x1F3A0, 0x1F3CA),
+    ("Objects", 0x1F4A0, 0x1F4FC),
+    ("Symbols", 0x1F500, 0x1F53D),
+    ("Supplemental", 0x1F90C, 0x1F9FF),
+];
+
+/// One emoji with its metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmojiEntry {
+    /// The emoji itself (may be a multi-codepoint sequence)
+    pub text: String,
+    /// CLDR short name, e.g. "grinning face" (empty for fallback data)
+    pub name: String,
+    /// Subgroup, e.g. "face-smiling"
+    pub subgroup: String,
+}
+
+/// A top-level emoji group, e.g. "Smileys & Emotion"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmojiCategory {
+    pub name: String,
+    pub entries: Vec<EmojiEntry>,
+}
+
+/// Grouped emoji table
+#[derive(Debug, Clone, Default)]
+pub struct EmojiData {
+    pub categories: Vec<EmojiCategory>,
+    /// File the data was loaded from (None = built-in fallback)
+    pub source: Option<PathBuf>,
+}
+
+impl EmojiData {
+    /// Load from `path`, or from `$NEOMACS_EMOJI_DATA` / the system table,
+    /// falling back to the built-in block ranges.
+    pub fn load(path: Option<&Path>) -> Self {
+        let candidates: Vec<PathBuf> = match path {
+            Some(p) => vec![p.to_path_buf()],
+            None => std::env::var_os(EMOJI_DATA_ENV)
+                .map(PathBuf::from)
+                .into_iter()
+                .chain(SYSTEM_PATHS.iter().map(PathBuf::from))
+                .collect(),
+        };
+
+        for candidate in candidates {
+            match std::fs::read_to_string(&candidate) {
+                Ok(text) => {
+                    let mut data = Self::parse(&text);
+                    if !data.categories.is_empty() {
+                        log::info!("Loaded {} emoji from {:?}", data.len(), candidate);
+                        data.source = Some(candidate);
+                        return data;
+                    }
+                }
+                Err(e) => log::debug!("emoji data {:?}: {}", candidate, e),
+            }
+        }
+
+        log::info!("No emoji-test.txt found, using built-in emoji blocks");
+        Self::fallback()
+    }
+
+    /// Parse the `emoji-test.txt` format.
+    ///
+    /// Only fully-qualified sequences are kept; the "Component" group
+    /// (skin tone and hair swatches) is skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut categories: Vec<EmojiCategory> = Vec::new();
+        let mut subgroup = String::new();
+        let mut skip_group = false;
+
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(group) = line.strip_prefix("# group:") {
+                let group = group.trim();
+                skip_group = group == "Component";
+                if !skip_group {
+                    categories.push(EmojiCategory {
+                        name: group.to_string(),
+                        entries: Vec::new(),
+                    });
+                }
+                continue;
+            }
+            if let Some(sub) = line.strip_prefix("# subgroup:") {
+                subgroup = sub.trim().to_string();
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') || skip_group {
+                continue;
+            }
+            let Some(category) = categories.last_mut() else { continue };
+            if let Some(entry) = parse_entry(line, &subgroup) {
+                category.entries.push(entry);
+            }
+        }
+
+        categories.retain(|c| !c.entries.is_empty());
+        Self { categories, source: None }
+    }
+
+    /// Coarse categories built from the main emoji blocks (no names)
+    pub fn fallback() -> Self {
+        let categories = FALLBACK_BLOCKS
+            .iter()
+            .map(|&(name, first, last)| EmojiCategory {
+                name: name.to_string(),
+                entries: (first..=last)
+                    .filter_map(char::from_u32)
+                    .map(|c| EmojiEntry {
+                        text: c.to_string(),
+                        name: String::new(),
+                        subgroup: String::new(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self { categories, source: None }
+    }
+
+    /// Total number of emoji across all categories
+    pub fn len(&self) -> usize {
+        self.categories.iter().map(|c| c.entries.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.iter().all(|c| c.entries.is_empty())
+    }
+
+    /// Serialize as tab-separated lines: `category TAB subgroup TAB emoji TAB name`
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::with_capacity(self.len() * 32);
+        for category in &self.categories {
+            for entry in &category.entries {
+                out.push_str(&category.name);
+                out.push('\t');
+                out.push_str(&entry.subgroup);
+                out.push('\t');
+                out.push_str(&entry.text);
+                out.push('\t');
+                out.push_str(&entry.name);
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// Parse one data line:
+/// `1F600 ; fully-qualified # 😀 E1.0 grinning face`
+fn parse_entry(line: &str, subgroup: &str) -> Option<EmojiEntry> {
+    let (fields, comment) = line.split_once('#')?;
+    let (codepoints, status) = fields.split_once(';')?;
+    if status.trim() != "fully-qualified" {
+        return None;
+    }
+
+    let text: String = codepoints
+        .split_whitespace()
+        .map(|cp| u32::from_str_radix(cp, 16).ok().and_then(char::from_u32))
+        .collect::<Option<String>>()?;
+    if text.is_empty() {
+        return None;
+    }
+
+    // Comment is "<emoji> E<version> <name>"; the name follows the version
+    let name = comment
+        .trim()
+        .split_once(" E")
+        .and_then(|(_, rest)| rest.split_once(' '))
+        .map(|(_, name)| name.trim().to_string())
+        .unwrap_or_default();
+
+    Some(EmojiEntry {
+        text,
+        name,
+        subgroup: subgroup.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# group: Smileys & Emotion
+
+# subgroup: face-smiling
+1F600                                                  ; fully-qualified     # \u{1F600} E1.0 grinning face
+263A FE0F                                              ; fully-qualified     # \u{263A}\u{FE0F} E0.6 smiling face
+263A                                                   ; unqualified         # \u{263A} E0.6 smiling face
+
+# group: Component
+
+# subgroup: skin-tone
+1F3FB                                                  ; component           # \u{1F3FB} E1.0 light skin tone
+
+# group: Flags
+
+# subgroup: country-flag
+1F1EF 1F1F5                                            ; fully-qualified     # \u{1F1EF}\u{1F1F5} E0.6 flag: Japan
+";
+
+    #[test]
+    fn test_parse_emoji_test() {
+        let data = EmojiData::parse(SAMPLE);
+        assert_eq!(data.categories.len(), 2);
+        assert_eq!(data.categories[0].name, "Smileys & Emotion");
+        assert_eq!(data.categories[0].entries.len(), 2);
+        assert_eq!(data.categories[0].entries[0].text, "\u{1F600}");
+        assert_eq!(data.categories[0].entries[0].name, "grinning face");
+        assert_eq!(data.categories[0].entries[0].subgroup, "face-smiling");
+        assert_eq!(data.categories[0].entries[1].text, "\u{263A}\u{FE0F}");
+        assert_eq!(data.categories[1].entries[0].name, "flag: Japan");
+        assert_eq!(data.len(), 3);
+    }
+
+    #[test]
+    fn test_fallback_and_tsv() {
+        let data = EmojiData::fallback();
+        assert!(!data.is_empty());
+        let tsv = EmojiData::parse(SAMPLE).to_tsv();
+        assert!(tsv.starts_with("Smileys & Emotion\tface-smiling\t\u{1F600}\tgrinning face\n"));
+    }
+}
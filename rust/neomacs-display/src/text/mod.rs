@@ -5,5 +5,10 @@
 //! - wgpu textures for GPU upload
 
 mod engine;
+pub mod emoji;
+pub mod char_grid;
+pub mod clusters;
 
 pub use engine::TextEngine;
+pub use emoji::{EmojiCategory, EmojiData, EmojiEntry};
+pub use char_grid::CharGridLayout;
@@ -3,12 +3,25 @@
 //! Provides lock-free channels and wakeup mechanism between Emacs and render threads.
 
 use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+#[cfg(unix)]
 use std::os::unix::io::RawFd;
+#[cfg(windows)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
-use crate::core::frame_glyphs::FrameGlyphBuffer;
+/// C runtime file descriptor, which is what Emacs selects on
+#[cfg(windows)]
+pub type RawFd = std::os::raw::c_int;
+
+use crate::core::frame_glyphs::{FrameGlyph, FrameGlyphBuffer};
 
 /// Input event from render thread to Emacs
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "remote",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
 pub enum InputEvent {
     Key {
         keysym: u32,
@@ -79,6 +92,37 @@ pub enum InputEvent {
     /// Terminal title changed
     #[cfg(feature = "neo-term")]
     TerminalTitleChanged { id: u32, title: String },
+    /// Terminal received BEL (after rate limiting)
+    #[cfg(feature = "neo-term")]
+    TerminalBell { id: u32 },
+    /// A tmux pane got terminal `id`
+    #[cfg(feature = "neo-term")]
+    TmuxPaneAdded { client: u32, pane: u32, window: u32, id: u32, cols: u16, rows: u16 },
+    /// A tmux pane's terminal `id` no longer receives output
+    #[cfg(feature = "neo-term")]
+    TmuxPaneClosed { client: u32, id: u32 },
+    /// A tmux client exited or was detached
+    #[cfg(feature = "neo-term")]
+    TmuxExited { client: u32 },
+    /// Video playback reached the end of the stream
+    #[cfg(feature = "video")]
+    VideoEnded { id: u32 },
+    /// New audio levels of video `id` are in `core::audio_levels`
+    #[cfg(feature = "video")]
+    AudioLevels { id: u32 },
+    /// A buffer crossfade (`scroll` false) or scroll animation finished
+    AnimationFinished { window_id: i64, scroll: bool },
+    /// An animation started with notify id `id` ended
+    AnimationCompleted { id: u32 },
+    /// The picture-in-picture player of video `id` was closed
+    PipClosed { id: u32 },
+    /// A failure was recorded in `core::error_report`; `id` is the newest
+    DisplayError { id: u32 },
+    /// The system switched between dark and light appearance
+    AppearanceChanged { dark: bool },
+    /// The render thread lost track of the faces; the next frame has to
+    /// carry all of them
+    FacesOutOfSync,
     /// Popup menu selection made (index into menu items, -1 = cancelled)
     MenuSelection { index: i32 },
     /// File(s) dropped onto the window
@@ -139,10 +183,94 @@ pub enum RenderCommand {
         max_width: u32,
         max_height: u32,
     },
+    /// Upload decoded RGBA pixels as image `id` (ID pre-allocated)
+    ImageLoadRgba { id: u32, width: u32, height: u32, data: Vec<u8> },
     /// Free an image from cache
     ImageFree { id: u32 },
-    /// Create a WebKit view
-    WebKitCreate { id: u32, width: u32, height: u32 },
+    /// Set how an image is filtered when drawn scaled
+    ImageSetSampling { id: u32, sampling: crate::core::types::ImageSampling },
+    /// Let an image be zoomed and panned with the mouse, or stop it
+    ImageSetInteractive { id: u32, interactive: bool },
+    /// Draw chrome with a nine-patch image, or with its own look again
+    SetChromeImage {
+        element: crate::core::nine_patch::ChromeElement,
+        patch: Option<crate::core::nine_patch::NinePatch>,
+    },
+    /// Draw a chart of `values` in place of image `id`
+    ChartSet { id: u32, values: Vec<f32>, style: crate::core::chart::ChartStyle },
+    /// Move chart `id` to `values` over `duration`
+    ChartUpdate { id: u32, values: Vec<f32>, duration: std::time::Duration },
+    /// Give image `id` the picture of image `new_id`, which goes away
+    ImageSwap {
+        id: u32,
+        new_id: u32,
+        effect: crate::core::image_swap::ImageSwapEffect,
+        duration: std::time::Duration,
+    },
+    /// Set how fallback-font glyphs (emoji, symbols) are fitted to the
+    /// text font, for one face or for all faces when `face_id` is None
+    SetFallbackMetrics {
+        face_id: Option<u32>,
+        metrics: Option<crate::core::face::FallbackMetrics>,
+    },
+    /// Register the shader effect `name` with the WGSL `shade` function
+    /// in `source`, already validated
+    ShaderEffectRegister { name: String, source: String },
+    /// Draw a shader effect over its target, replacing the effect of the
+    /// same name there
+    ShaderEffectApply { effect: crate::core::shader_effect::AppliedShaderEffect },
+    /// Remove the shader effects named `name` from `target`; None matches
+    /// any
+    ShaderEffectRemove {
+        name: Option<String>,
+        target: Option<crate::core::shader_effect::ShaderTarget>,
+    },
+    /// Rasterize a typeset math snippet into image `image_id`.  `source`
+    /// is what `frame` was typeset from, for a renderer in another
+    /// process, which gets the snippet rather than the frame.
+    #[cfg(feature = "math")]
+    MathRasterize {
+        image_id: u32,
+        frame: typst::layout::Frame,
+        source: crate::layout::math::MathSource,
+    },
+    /// Rasterize an icon into image `image_id`, `width` x `height`
+    /// logical pixels, in `color` or its own colors when None
+    #[cfg(feature = "icons")]
+    IconRasterize {
+        image_id: u32,
+        source: crate::layout::icons::IconSource,
+        width: u32,
+        height: u32,
+        color: Option<u32>,
+    },
+    /// Rasterize a canvas into image `image_id`
+    #[cfg(feature = "canvas")]
+    CanvasRasterize { image_id: u32, canvas: crate::layout::canvas::Canvas },
+    /// Open a PDF document
+    #[cfg(feature = "pdf")]
+    PdfOpen { id: u32, path: String, password: Option<String> },
+    /// Rasterize a PDF page into image `image_id` (zoom = logical px per point)
+    #[cfg(feature = "pdf")]
+    PdfRenderPage { id: u32, page: u32, zoom: f32, image_id: u32 },
+    /// Extract text and link rectangles from a PDF page
+    #[cfg(feature = "pdf")]
+    PdfExtractPage { id: u32, page: u32 },
+    /// Close a PDF document
+    #[cfg(feature = "pdf")]
+    PdfClose { id: u32 },
+    /// Create a WebKit view (profile None = default profile)
+    WebKitCreate { id: u32, width: u32, height: u32, profile: Option<String> },
+    /// Define a named WebKit data profile (cookies, storage, cache location)
+    WebKitDefineProfile {
+        name: String,
+        /// Website data directory, None = $XDG_DATA_HOME/neomacs/webkit/<name>
+        data_dir: Option<String>,
+        /// Cache directory, None = $XDG_CACHE_HOME/neomacs/webkit/<name>
+        cache_dir: Option<String>,
+        /// Keep all website data in memory only
+        ephemeral: bool,
+    },
     /// Load URL in WebKit view
     WebKitLoadUri { id: u32, url: String },
     /// Resize WebKit view
@@ -169,12 +297,74 @@ pub enum RenderCommand {
     WebKitSetFloating { id: u32, x: f32, y: f32, width: f32, height: f32 },
     /// Remove floating WebKit overlay
     WebKitRemoveFloating { id: u32 },
+    /// Set floating image overlay position and size
+    ImageSetFloating { id: u32, x: f32, y: f32, width: f32, height: f32 },
+    /// Remove floating image overlay
+    ImageRemoveFloating { id: u32 },
+    /// Animate a property of a floating image/WebKit/terminal to `to`
+    AnimateFloating {
+        kind: crate::core::animation::FloatingKind,
+        id: u32,
+        property: crate::core::animation::FloatingProperty,
+        to: f32,
+        duration_ms: u32,
+        easing: crate::core::animation::Easing,
+        /// Reported with `InputEvent::AnimationCompleted` when done, or 0
+        notify: u32,
+    },
     /// Create video player
     VideoCreate { id: u32, path: String },
+    /// Write the resources of the session to a file
+    SaveSession { path: std::path::PathBuf },
+    /// Send the structure of the current frame as JSON to `reply` (see
+    /// `core::scene_dump`)
+    DumpScene { reply: Sender<String> },
+    /// Send the current frame as plain text per window to `reply`, text
+    /// in face `selection_face` marked selected (see `core::accessibility`)
+    FrameText { selection_face: u32, reply: Sender<String> },
+    /// Send the memory held by each subsystem to `reply` (see
+    /// `core::memory_report`)
+    MemoryReport { reply: Sender<crate::core::memory_report::MemoryReport> },
+    /// Record the frames received from now on, and the images they show,
+    /// to a replay file (see `remote::replay`)
+    #[cfg(feature = "remote")]
+    ReplayStart { path: std::path::PathBuf },
+    /// Finish the replay being recorded
+    #[cfg(feature = "remote")]
+    ReplayStop,
+    /// Play `effect` offscreen over the sample frames and write its
+    /// frames to `path`, a GIF or a directory of PNGs
+    ExportEffectPreview {
+        effect: crate::core::effect_preview::PreviewEffect,
+        path: std::path::PathBuf,
+        timeline: crate::core::effect_preview::PreviewTimeline,
+    },
+    /// Switch the display's own colors at once, crossfading over `fade`
+    /// when given
+    SetDisplayTheme {
+        theme: Box<crate::core::display_theme::DisplayTheme>,
+        fade: Option<std::time::Duration>,
+    },
     /// Control video playback
     VideoPlay { id: u32 },
     VideoPause { id: u32 },
     VideoDestroy { id: u32 },
+    /// Show video `id` in the picture-in-picture player, pinned to
+    /// `corner` (see `PipCorner::from_u8`); `id` 0 closes the player
+    VideoPip { id: u32, corner: u8, width: f32 },
+    /// Draw the waveform of the audio of `path` into image `image_id`
+    #[cfg(feature = "video")]
+    MediaWaveform { path: String, width: u32, height: u32, color: u32, image_id: u32 },
+    /// Take thumbnails `width` wide of the video of `path` at even
+    /// intervals, one per image in `image_ids`
+    #[cfg(feature = "video")]
+    MediaThumbnails { path: String, width: u32, image_ids: Vec<u32> },
+    /// Send `InputEvent::AudioLevels` whenever the audio levels of video
+    /// `id` change, or stop sending them
+    VideoAudioEvents { id: u32, enabled: bool },
+    /// Loop a video beneath the text of a window (`None` for the frame),
+    /// darkened by `dim`; `video_id` 0 removes it
+    SetBackgroundVideo { window_id: Option<i64>, video_id: u32, dim: f32 },
     /// Change the mouse pointer cursor shape (arrow, hand, ibeam, etc.)
     SetMouseCursor { cursor_type: i32 },
     /// Warp (move) the mouse pointer to given pixel position
@@ -195,6 +385,38 @@ pub enum RenderCommand {
     SetCursorBlink { enabled: bool, interval_ms: u32 },
     /// Configure cursor animation (smooth motion)
     SetCursorAnimation { enabled: bool, speed: f32 },
+    /// Define (Some) or remove (None) a named animation curve
+    SetAnimationCurve {
+        name: String,
+        curve: Option<crate::core::animation::AnimationCurve>,
+    },
+    /// Start a crossfade on the next frame, for one window or (with
+    /// `window_id: None`) every non-minibuffer window
+    StartTransition {
+        window_id: Option<i64>,
+        effect: crate::core::scroll_animation::ScrollEffect,
+        duration_ms: u32,
+        /// Reported with `InputEvent::AnimationCompleted` when every
+        /// window's crossfade is done, or 0
+        notify: u32,
+    },
+    /// The selected window's next scroll moves its text `pixels` up
+    /// (negative: down); its slide covers that distance, not a window
+    ScrollHint { pixels: f32 },
+    /// Zoom the whole frame to `scale` over `duration_ms`, around `focus`
+    /// in logical pixels or (with `None`) around the cursor
+    SetFrameZoom {
+        scale: f32,
+        focus: Option<(f32, f32)>,
+        duration_ms: u32,
+        /// Reported with `InputEvent::AnimationCompleted` when done, or 0
+        notify: u32,
+    },
+    /// Apply a display option (see `core::option_registry`)
+    SetDisplayOption {
+        name: &'static str,
+        value: crate::core::option_registry::OptionValue,
+    },
     /// Configure all animations
     SetAnimationConfig {
         cursor_enabled: bool,
@@ -229,9 +451,39 @@ pub enum RenderCommand {
     /// Destroy a terminal
     #[cfg(feature = "neo-term")]
     TerminalDestroy { id: u32 },
+    /// Show saved output in a terminal without sending it to the shell
+    #[cfg(feature = "neo-term")]
+    TerminalReplay { id: u32, data: Vec<u8> },
     /// Set floating terminal position and opacity
     #[cfg(feature = "neo-term")]
     TerminalSetFloat { id: u32, x: f32, y: f32, opacity: f32 },
+    /// Set a terminal's font; None fields follow the frame font
+    #[cfg(feature = "neo-term")]
+    TerminalSetFont { id: u32, family: Option<String>, size: Option<f32> },
+    /// Stop drawing a terminal, keeping its shell and grid alive
+    #[cfg(feature = "neo-term")]
+    TerminalDetach { id: u32 },
+    /// Draw a detached terminal again in `mode` (0=Window, 1=Inline, 2=Floating)
+    #[cfg(feature = "neo-term")]
+    TerminalAttach { id: u32, mode: u8 },
+    /// Set what is drawn behind a terminal's cells
+    #[cfg(feature = "neo-term")]
+    TerminalSetBackground { id: u32, background: crate::terminal::TerminalBackground },
+    /// Set whether a terminal guesses the echo of typed input
+    #[cfg(feature = "neo-term")]
+    TerminalSetLocalEcho { id: u32, mode: crate::terminal::local_echo::EchoMode },
+    /// Create a terminal running a shell on an SSH host
+    #[cfg(feature = "neo-term-ssh")]
+    TerminalCreateSsh { id: u32, cols: u16, rows: u16, mode: u8, target: crate::terminal::transport::SshTarget },
+    /// Start a tmux control-mode client by running `command`
+    #[cfg(feature = "neo-term")]
+    TmuxConnect { id: u32, command: String },
+    /// Send a command to a tmux client
+    #[cfg(feature = "neo-term")]
+    TmuxCommand { id: u32, command: String },
+    /// Detach a tmux client, closing its pane terminals
+    #[cfg(feature = "neo-term")]
+    TmuxDisconnect { id: u32 },
     /// Show a popup menu at position (x, y)
     ShowPopupMenu {
         x: f32,
@@ -254,6 +506,33 @@ pub enum RenderCommand {
     },
     /// Hide the active tooltip
     HideTooltip,
+    /// Show a scrollable character grid overlay (emoji picker, glyph table)
+    ShowCharGrid {
+        x: f32,
+        y: f32,
+        columns: u32,
+        rows: u32,
+        cell_size: f32,
+        items: Vec<String>,
+        fg: (f32, f32, f32),
+        bg: (f32, f32, f32),
+        selected_bg: (f32, f32, f32),
+    },
+    /// Move the character grid selection/scroll without resending items
+    /// (selected < 0 = no selection; the grid scrolls to keep it visible)
+    UpdateCharGrid { selected: i32, scroll_row: u32 },
+    /// Hide the character grid overlay
+    HideCharGrid,
+    /// Splice minimap line summaries into a buffer's line list
+    MinimapUpdate {
+        buffer_id: u64,
+        first: u32,
+        removed: u32,
+        pos_delta: i64,
+        lines: Vec<crate::core::minimap::MinimapLine>,
+    },
+    /// Drop a buffer's minimap line summaries
+    MinimapForget { buffer_id: u64 },
     /// Trigger visual bell flash
     VisualBell,
     /// Request window attention (urgency hint / taskbar flash)
@@ -289,10 +568,16 @@ pub enum RenderCommand {
 pub struct WakeupPipe {
     read_fd: RawFd,
     write_fd: RawFd,
+    /// Bytes written but not yet drained.  Windows pipes cannot be made
+    /// non-blocking through the C runtime, so clearing reads exactly this
+    /// many bytes instead.
+    #[cfg(windows)]
+    pending: Arc<AtomicUsize>,
 }
 
 impl WakeupPipe {
     /// Create a new wakeup pipe
+    #[cfg(unix)]
     pub fn new() -> std::io::Result<Self> {
         let (read, write) = os_pipe::pipe()?;
         use std::os::unix::io::IntoRawFd;
@@ -302,6 +587,23 @@ impl WakeupPipe {
         })
     }
 
+    /// Create a new wakeup pipe
+    #[cfg(windows)]
+    pub fn new() -> std::io::Result<Self> {
+        use std::os::windows::io::IntoRawHandle;
+        let (read, write) = os_pipe::pipe()?;
+        let to_fd = |handle: std::os::windows::io::RawHandle| {
+            // SAFETY: the handle is owned and handed over to the C runtime
+            let fd = unsafe { libc::open_osfhandle(handle as libc::intptr_t, 0) };
+            if fd < 0 { Err(std::io::Error::last_os_error()) } else { Ok(fd) }
+        };
+        Ok(Self {
+            read_fd: to_fd(read.into_raw_handle())?,
+            write_fd: to_fd(write.into_raw_handle())?,
+            pending: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
     /// Get the read fd for Emacs to select() on
     pub fn read_fd(&self) -> RawFd {
         self.read_fd
@@ -309,6 +611,8 @@ impl WakeupPipe {
 
     /// Signal Emacs to wake up (called from render thread)
     pub fn wake(&self) {
+        #[cfg(windows)]
+        self.pending.fetch_add(1, Ordering::AcqRel);
         unsafe {
             libc::write(self.write_fd, [1u8].as_ptr() as *const _, 1);
         }
@@ -316,13 +620,14 @@ impl WakeupPipe {
 
     /// Clear the wakeup signal (called from Emacs thread)
     pub fn clear(&self) {
-        let mut buf = [0u8; 64];
-        unsafe {
-            // Non-blocking read to drain the pipe
-            let flags = libc::fcntl(self.read_fd, libc::F_GETFL);
-            libc::fcntl(self.read_fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
-            while libc::read(self.read_fd, buf.as_mut_ptr() as *mut _, buf.len()) > 0 {}
-            libc::fcntl(self.read_fd, libc::F_SETFL, flags);
+        self.clearer().clear();
+    }
+
+    fn clearer(&self) -> WakeupClear {
+        WakeupClear {
+            fd: self.read_fd,
+            #[cfg(windows)]
+            pending: self.pending.clone(),
         }
     }
 }
@@ -336,12 +641,30 @@ impl Drop for WakeupPipe {
     }
 }
 
+/// Host function called on the render thread whenever an event is queued,
+/// so a host that does not select on the wakeup fd knows when to drain
+#[derive(Debug, Clone, Copy)]
+pub struct EventNotify {
+    pub func: extern "C" fn(*mut std::ffi::c_void),
+    pub user_data: *mut std::ffi::c_void,
+}
+
+// SAFETY: whoever registers the callback promises that it, and its user
+// data, may be used from the render thread
+unsafe impl Send for EventNotify {}
+
+/// Event callback shared by the Emacs side, which sets it, and the render
+/// side, which calls it
+pub type SharedEventNotify = Arc<Mutex<Option<EventNotify>>>;
+
 /// Channel capacities
 // Frame channel: unbounded so try_send never drops frames.
 // The render thread drains all queued frames and keeps only the latest
 // (see poll_frame()), so memory stays bounded in practice.
 const INPUT_CHANNEL_CAPACITY: usize = 256;
 const COMMAND_CHANNEL_CAPACITY: usize = 64;
+// Glyph lists handed back for reuse; any beyond this are simply freed.
+const RECYCLE_CHANNEL_CAPACITY: usize = 2;
 
 /// Communication channels between threads
 pub struct ThreadComms {
@@ -349,6 +672,10 @@ pub struct ThreadComms {
     pub frame_tx: Sender<FrameGlyphBuffer>,
     pub frame_rx: Receiver<FrameGlyphBuffer>,
 
+    /// Emptied glyph lists of displayed frames: Render → Emacs
+    pub recycle_tx: Sender<Vec<FrameGlyph>>,
+    pub recycle_rx: Receiver<Vec<FrameGlyph>>,
+
     /// Commands: Emacs → Render
     pub cmd_tx: Sender<RenderCommand>,
     pub cmd_rx: Receiver<RenderCommand>,
@@ -359,12 +686,16 @@ pub struct ThreadComms {
 
     /// Wakeup pipe: Render → Emacs
     pub wakeup: WakeupPipe,
+
+    /// Optional host callback run with each wakeup
+    pub event_notify: SharedEventNotify,
 }
 
 impl ThreadComms {
     /// Create new thread communication channels
     pub fn new() -> std::io::Result<Self> {
         let (frame_tx, frame_rx) = unbounded();
+        let (recycle_tx, recycle_rx) = bounded(RECYCLE_CHANNEL_CAPACITY);
         let (cmd_tx, cmd_rx) = bounded(COMMAND_CHANNEL_CAPACITY);
         let (input_tx, input_rx) = bounded(INPUT_CHANNEL_CAPACITY);
         let wakeup = WakeupPipe::new()?;
@@ -372,11 +703,14 @@ impl ThreadComms {
         Ok(Self {
             frame_tx,
             frame_rx,
+            recycle_tx,
+            recycle_rx,
             cmd_tx,
             cmd_rx,
             input_tx,
             input_rx,
             wakeup,
+            event_notify: SharedEventNotify::default(),
         })
     }
 
@@ -384,17 +718,21 @@ impl ThreadComms {
     pub fn split(self) -> (EmacsComms, RenderComms) {
         let emacs = EmacsComms {
             frame_tx: self.frame_tx,
+            recycle_rx: self.recycle_rx,
             cmd_tx: self.cmd_tx,
             input_rx: self.input_rx,
             wakeup_read_fd: self.wakeup.read_fd(),
-            wakeup_clear: WakeupClear { fd: self.wakeup.read_fd },
+            wakeup_clear: self.wakeup.clearer(),
+            event_notify: Arc::clone(&self.event_notify),
         };
 
         let render = RenderComms {
             frame_rx: self.frame_rx,
+            recycle_tx: self.recycle_tx,
             cmd_rx: self.cmd_rx,
             input_tx: self.input_tx,
             wakeup: self.wakeup,
+            event_notify: self.event_notify,
         };
 
         (emacs, render)
@@ -404,42 +742,119 @@ impl ThreadComms {
 /// Emacs thread communication handle
 pub struct EmacsComms {
     pub frame_tx: Sender<FrameGlyphBuffer>,
+    pub recycle_rx: Receiver<Vec<FrameGlyph>>,
     pub cmd_tx: Sender<RenderCommand>,
     pub input_rx: Receiver<InputEvent>,
     pub wakeup_read_fd: RawFd,
     pub wakeup_clear: WakeupClear,
+    pub event_notify: SharedEventNotify,
 }
 
 /// Handle for clearing wakeup pipe
 pub struct WakeupClear {
     fd: RawFd,
+    #[cfg(windows)]
+    pending: Arc<AtomicUsize>,
 }
 
 impl WakeupClear {
+    #[cfg(unix)]
     pub fn clear(&self) {
         let mut buf = [0u8; 64];
         unsafe {
+            // Non-blocking read to drain the pipe
             let flags = libc::fcntl(self.fd, libc::F_GETFL);
             libc::fcntl(self.fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
             while libc::read(self.fd, buf.as_mut_ptr() as *mut _, buf.len()) > 0 {}
             libc::fcntl(self.fd, libc::F_SETFL, flags);
         }
     }
+
+    #[cfg(windows)]
+    pub fn clear(&self) {
+        let mut buf = [0u8; 64];
+        // Every counted byte is written or about to be, so these reads
+        // return promptly
+        let mut left = self.pending.swap(0, Ordering::AcqRel);
+        while left > 0 {
+            let want = left.min(buf.len());
+            let n = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut _, want as _) };
+            if n <= 0 {
+                break;
+            }
+            left -= n as usize;
+        }
+    }
 }
 
 /// Render thread communication handle
 pub struct RenderComms {
     pub frame_rx: Receiver<FrameGlyphBuffer>,
+    pub recycle_tx: Sender<Vec<FrameGlyph>>,
     pub cmd_rx: Receiver<RenderCommand>,
     pub input_tx: Sender<InputEvent>,
     pub wakeup: WakeupPipe,
+    pub event_notify: SharedEventNotify,
 }
 
 impl RenderComms {
+    /// Hand the glyph list of a frame no longer displayed back to the
+    /// Emacs side, which fills it with the next frame instead of
+    /// allocating a new one
+    pub fn recycle_glyphs(&self, mut glyphs: Vec<FrameGlyph>) {
+        glyphs.clear();
+        let _ = self.recycle_tx.try_send(glyphs);
+    }
+
     /// Send input event to Emacs and wake it up
     pub fn send_input(&self, event: InputEvent) {
         if self.input_tx.try_send(event).is_ok() {
             self.wakeup.wake();
+            let notify = self.event_notify.lock().ok().and_then(|n| *n);
+            if let Some(notify) = notify {
+                (notify.func)(notify.user_data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn count_notify(user_data: *mut std::ffi::c_void) {
+        assert_eq!(user_data as usize, 42);
+        NOTIFIED.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_event_notify_runs_per_event() {
+        let (emacs, render) = ThreadComms::new().unwrap().split();
+        render.send_input(InputEvent::WindowClose);
+        assert_eq!(NOTIFIED.load(Ordering::SeqCst), 0);
+
+        *emacs.event_notify.lock().unwrap() =
+            Some(EventNotify { func: count_notify, user_data: 42 as *mut _ });
+        render.send_input(InputEvent::WindowFocus { focused: true });
+        render.send_input(InputEvent::WindowClose);
+        assert_eq!(NOTIFIED.load(Ordering::SeqCst), 2);
+        assert_eq!(emacs.input_rx.len(), 3);
+    }
+
+    #[test]
+    fn test_recycled_glyphs_come_back_empty() {
+        let (emacs, render) = ThreadComms::new().unwrap().split();
+        let mut frame = FrameGlyphBuffer::new();
+        frame.add_char('a', 0.0, 0.0, 8.0, 16.0, 12.0, false);
+        for _ in 0..RECYCLE_CHANNEL_CAPACITY + 1 {
+            render.recycle_glyphs(frame.glyphs.clone());
         }
+        // Lists beyond the channel's capacity are dropped
+        assert_eq!(emacs.recycle_rx.len(), RECYCLE_CHANNEL_CAPACITY);
+        let glyphs = emacs.recycle_rx.try_recv().unwrap();
+        assert!(glyphs.is_empty() && glyphs.capacity() > 0);
     }
 }
@@ -80,12 +80,13 @@ fn test_command_channel_webkit_create() {
             id: 42,
             width: 800,
             height: 600,
+            profile: None,
         })
         .unwrap();
 
     let cmd = render.cmd_rx.recv().unwrap();
     match cmd {
-        RenderCommand::WebKitCreate { id, width, height } => {
+        RenderCommand::WebKitCreate { id, width, height, .. } => {
             assert_eq!(id, 42);
             assert_eq!(width, 800);
             assert_eq!(height, 600);
@@ -449,7 +449,10 @@ enum glyph_type
   VIDEO_GLYPH,
 
   /* Glyph is a WebKit browser view (Neomacs).  */
-  WEBKIT_GLYPH
+  WEBKIT_GLYPH,
+
+  /* Glyph is an inline terminal (Neomacs).  */
+  TERMINAL_GLYPH
 };
 
 
@@ -613,6 +616,9 @@ struct glyph
 
     /* WebKit view ID for webkit glyphs (type == WEBKIT_GLYPH).  */
     uint32_t webkit_id;
+
+    /* Terminal ID for terminal glyphs (type == TERMINAL_GLYPH).  */
+    uint32_t terminal_id;
 #endif
 
 #ifdef HAVE_XWIDGETS
@@ -2311,7 +2317,10 @@ enum display_element_type
   IT_VIDEO,
 
   /* WebKit browser view (Neomacs).  */
-  IT_WEBKIT
+  IT_WEBKIT,
+
+  /* Inline terminal (Neomacs).  */
+  IT_TERMINAL
 #endif
 };
 
@@ -2380,6 +2389,7 @@ enum it_method {
 #ifdef HAVE_NEOMACS
   GET_FROM_VIDEO,
   GET_FROM_WEBKIT,
+  GET_FROM_TERMINAL,
 #endif
   NUM_IT_METHODS
 };
@@ -2765,7 +2775,10 @@ struct it
   /* If what == IT_WEBKIT, the WebKit view id.  */
   uint32_t webkit_id;
 
-  /* Width and height for IT_VIDEO or IT_WEBKIT.  */
+  /* If what == IT_TERMINAL, the terminal id.  */
+  uint32_t terminal_id;
+
+  /* Width and height for IT_VIDEO, IT_WEBKIT or IT_TERMINAL.  */
   int video_width;
   int video_height;
 #endif
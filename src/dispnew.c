@@ -6392,6 +6392,11 @@ buffer_posn_from_coords (struct window *w, int *x, int *y, struct display_pos *p
 		       QCwidth, make_fixnum (it.video_width),
 		       QCheight, make_fixnum (it.video_height));
     }
+  else if (it.what == IT_TERMINAL)
+    *object = CALLN (Flist, Qterminal,
+		     QCid, make_fixnum (it.terminal_id),
+		     QCwidth, make_fixnum (it.video_width),
+		     QCheight, make_fixnum (it.video_height));
 #endif
 
   /* IT's vpos counts from the glyph row that includes the window's
@@ -6420,7 +6425,7 @@ buffer_posn_from_coords (struct window *w, int *x, int *y, struct display_pos *p
 	  else
 #endif
 #ifdef HAVE_NEOMACS
-	  if (glyph->type == WEBKIT_GLYPH)
+	  if (glyph->type == WEBKIT_GLYPH || glyph->type == TERMINAL_GLYPH)
 	    {
 	      /* For webkit glyphs, adjust dy for baseline and return
 		 the webkit view dimensions.  */
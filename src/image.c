@@ -12963,6 +12963,7 @@ enum neomacs_keyword_index
   NEOMACS_MAX_WIDTH,
   NEOMACS_MAX_HEIGHT,
   NEOMACS_SCALE,
+  NEOMACS_ASCENT,
   NEOMACS_LAST
 };
 
@@ -12977,6 +12978,7 @@ static const struct image_keyword neomacs_format[NEOMACS_LAST] =
   {":max-width",  IMAGE_POSITIVE_INTEGER_VALUE,         0},
   {":max-height", IMAGE_POSITIVE_INTEGER_VALUE,         0},
   {":scale",      IMAGE_DONT_CHECK_VALUE_TYPE,          0},
+  {":ascent",     IMAGE_ASCENT_VALUE,                   0},
 };
 
 /* Return true if OBJECT is a valid neomacs image specification.  */
@@ -3346,6 +3346,13 @@ WEBKITP (Lisp_Object x)
 {
   return CONSP (x) && EQ (XCAR (x), Qwebkit);
 }
+
+/* Test for terminal (terminal :id N :cols C :rows R).  */
+INLINE bool
+NEOMACS_TERMINALP (Lisp_Object x)
+{
+  return CONSP (x) && EQ (XCAR (x), Qterminal);
+}
 #endif /* HAVE_NEOMACS */
 
 /* Array types.  */
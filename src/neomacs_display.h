@@ -40,6 +40,43 @@
 #define NEOMACS_EVENT_MENU_SELECTION 13
 #define NEOMACS_EVENT_FILE_DROP 14
 #define NEOMACS_EVENT_TERMINAL_TITLE_CHANGED 15
+#define NEOMACS_EVENT_VIDEO_ENDED 16
+#define NEOMACS_EVENT_WEBKIT_TITLE_CHANGED 17
+#define NEOMACS_EVENT_WEBKIT_URL_CHANGED 18
+#define NEOMACS_EVENT_WEBKIT_PROGRESS_CHANGED 19
+#define NEOMACS_EVENT_WEBKIT_LOAD_FINISHED 20
+#define NEOMACS_EVENT_ANIMATION_FINISHED 21
+#define NEOMACS_EVENT_DISPLAY_ERROR 22
+#define NEOMACS_EVENT_TERMINAL_BELL 23
+#define NEOMACS_EVENT_TMUX_PANE_ADDED 24
+#define NEOMACS_EVENT_TMUX_PANE_CLOSED 25
+#define NEOMACS_EVENT_TMUX_EXITED 26
+#define NEOMACS_EVENT_ANIMATION_COMPLETED 27
+#define NEOMACS_EVENT_PIP_CLOSED 28
+#define NEOMACS_EVENT_AUDIO_LEVELS 29
+#define NEOMACS_EVENT_APPEARANCE_CHANGED 30
+
+/**
+ * Attribute groups for neomacs_display_set_face_inheritance
+ */
+#define NEOMACS_FACE_FIELD_FOREGROUND (1 << 0)
+#define NEOMACS_FACE_FIELD_BACKGROUND (1 << 1)
+#define NEOMACS_FACE_FIELD_FAMILY (1 << 2)
+#define NEOMACS_FACE_FIELD_HEIGHT (1 << 3)
+#define NEOMACS_FACE_FIELD_WEIGHT (1 << 4)
+#define NEOMACS_FACE_FIELD_SLANT (1 << 5)
+#define NEOMACS_FACE_FIELD_UNDERLINE (1 << 6)
+#define NEOMACS_FACE_FIELD_OVERLINE (1 << 7)
+#define NEOMACS_FACE_FIELD_STRIKE_THROUGH (1 << 8)
+#define NEOMACS_FACE_FIELD_BOX (1 << 9)
+#define NEOMACS_FACE_FIELD_INVERSE (1 << 10)
+#define NEOMACS_FACE_FIELD_BACKGROUND_STYLE (1 << 11)
+
+/* Returned by resource calls given an id whose resource was freed.  */
+#define NEOMACS_STALE_HANDLE (-2)
+
+/* Returned by terminal writes while too much input is queued.  */
+#define NEOMACS_TERMINAL_BUSY (-3)
 
 #define DRM_FORMAT_ARGB8888 875713089
 
@@ -310,6 +347,41 @@ void neomacs_display_set_face(struct NeomacsDisplay *handle,
                               int ulPosition,
                               int ulThickness);
 
+/**
+ * Forget a face Emacs freed, so the render thread drops it too
+ */
+void neomacs_display_remove_face(struct NeomacsDisplay *handle, uint32_t faceId);
+
+/**
+ * Make face `faceId` set only the attribute groups in `specified`
+ * (`NEOMACS_FACE_FIELD_*` bits) and inherit the rest from the
+ * `nParents` faces at `parents`, earlier ones first, then from the
+ * default face.  Holds for the face as registered with
+ * `neomacs_display_set_face`, before or after this call, so the render
+ * thread gets the face's own attributes and its parents' ids rather than
+ * attributes already merged.
+ */
+void neomacs_display_set_face_inheritance(struct NeomacsDisplay *handle,
+                                          uint32_t faceId,
+                                          uint32_t specified,
+                                          const uint32_t *parents,
+                                          int nParents);
+
+/**
+ * Draw faces whose background is `background` (0xRRGGBB) with a
+ * gradient or image instead of the flat color.
+ * kind: 0=flat (remove style), 1=gradient from `from` to `to` (0xRRGGBB)
+ * at `angle` degrees clockwise from left-to-right, 2=image `imageId`.
+ * Takes effect for faces (re)registered after the call.
+ */
+void neomacs_display_set_face_background_style(struct NeomacsDisplay *handle,
+                                               uint32_t background,
+                                               int kind,
+                                               uint32_t from,
+                                               uint32_t to,
+                                               float angle,
+                                               uint32_t imageId);
+
 /**
  * Set the frame/scene background color
  * Color is in 0xRRGGBB format
@@ -331,6 +403,40 @@ void neomacs_display_add_video_glyph(struct NeomacsDisplay *handle,
                                      int pixelWidth,
                                      int pixelHeight);
 
+/**
+ * Add an inline terminal glyph to the current row.  Y_OFFSET is the
+ * distance from the top of the row to the top of the terminal.
+ */
+void neomacs_display_add_terminal_glyph(struct NeomacsDisplay *handle,
+                                        uint32_t terminalId,
+                                        int yOffset,
+                                        int pixelWidth,
+                                        int pixelHeight);
+
+#if defined(NEOMACS_HTML_RENDERER)
+/**
+ * Render an HTML fragment into the current frame, cut off below
+ * y + height unless height is 0; returns its height or -1
+ */
+int neomacs_display_add_html(struct NeomacsDisplay *handle,
+                             const char *html,
+                             int x,
+                             int y,
+                             int width,
+                             int height,
+                             uint32_t faceId,
+                             uint32_t boldFaceId,
+                             uint32_t italicFaceId,
+                             uint32_t codeFaceId,
+                             uint32_t linkFaceId);
+
+/**
+ * Return the href of the HTML link at (x, y), or NULL.
+ * Free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_html_link_at(struct NeomacsDisplay *handle, int x, int y);
+#endif
+
 /**
  * Load a video from file path (async - uses GStreamer)
  */
@@ -351,6 +457,85 @@ int neomacs_display_video_pause(struct NeomacsDisplay *handle, uint32_t videoId)
  */
 int neomacs_display_video_stop(struct NeomacsDisplay *handle, uint32_t videoId);
 
+/**
+ * Show a video in the picture-in-picture player: a small player pinned
+ * to a frame corner (0 top-left, 1 top-right, 2 bottom-left, 3
+ * bottom-right), `width` logical pixels wide, that stays up across
+ * buffer switches.  `video_id` 0 closes the player.
+ */
+int neomacs_display_video_pip(struct NeomacsDisplay *handle,
+                              uint32_t videoId,
+                              uint8_t corner,
+                              float width);
+
+/**
+ * Draw the waveform of the audio of the media file PATH as an image of
+ * `width` x `height` pixels in `color` (0xAARRGGBB, alpha 0 for opaque)
+ * on a transparent background, for seek bars (async).  Returns the image
+ * ID, or 0 on failure.  Files without audio fail as display errors.
+ */
+uint32_t neomacs_display_media_waveform(struct NeomacsDisplay *handle,
+                                        const char *path,
+                                        int width,
+                                        int height,
+                                        uint32_t color);
+
+/**
+ * Take `count` thumbnails `width` pixels wide of the video of the media
+ * file PATH, at even intervals, for seek previews (async).  Their image
+ * IDs go to `image_ids` (room for `count`).  Their height follows the
+ * shape of the video and is known once each is ready, as with images
+ * loaded from files.  Returns the number of IDs written, or -1.
+ */
+int neomacs_display_media_thumbnails(struct NeomacsDisplay *handle,
+                                     const char *path,
+                                     int count,
+                                     int width,
+                                     uint32_t *imageIds);
+
+/**
+ * Find out whether the media file or URI PATH can be played, decoding
+ * up to the first frame of each stream.  Blocks for up to five seconds.
+ * Returns 1 if it can, 0 if not, and -1 without video support.  When
+ * `reason` is not NULL it is set to why the file won't play fully (a
+ * missing codec, say), or NULL; free it with `neomacs_display_free_string`.
+ */
+int neomacs_display_probe_media(const char *path, char **reason);
+
+/**
+ * Report the audio levels of a video with an `audio-levels` event each
+ * time they change (about ten times a second while it plays), or stop
+ * reporting them when `enabled` is 0.
+ */
+int neomacs_display_video_audio_events(struct NeomacsDisplay *handle,
+                                       uint32_t videoId,
+                                       int enabled);
+
+/**
+ * Copy the latest per-channel levels of a video's audio, in dB, into
+ * `rms` and `peak` (each room for `max_channels`).  Returns the number of
+ * channels, or -1 if the video has not played any audio yet.
+ */
+int neomacs_display_audio_levels(uint32_t videoId, float *rms, float *peak, int maxChannels);
+
+/**
+ * Copy the latest spectrum of a video's audio, band magnitudes in dB from
+ * the lowest frequency up, into `bands` (room for `max_bands`).  Returns
+ * the number of bands, or -1 if the video has not played any audio yet.
+ */
+int neomacs_display_audio_spectrum(uint32_t videoId, float *bands, int maxBands);
+
+/**
+ * Show a video, looping, beneath the text of a window (`window_id` 0 for
+ * the whole frame), darkened by `dim` (0.0-1.0).  `video_id` 0 removes
+ * the window's background video.  Background videos pause while the
+ * frame is unfocused.
+ */
+int neomacs_display_set_background_video(struct NeomacsDisplay *handle,
+                                         int64_t windowId,
+                                         uint32_t videoId,
+                                         float dim);
+
 /**
  * Set video loop mode (-1 for infinite)
  */
@@ -455,6 +640,312 @@ int neomacs_display_query_image_file_size(struct NeomacsDisplay *handle,
  */
 int neomacs_display_free_image(struct NeomacsDisplay *handle, uint32_t imageId);
 
+/**
+ * Set how an image is filtered when drawn at another size: 1 always
+ * smooth, 0 nearest pixel, -1 smooth only when scaled down.
+ */
+int neomacs_display_set_image_sampling(struct NeomacsDisplay *handle,
+                                       uint32_t imageId,
+                                       int sampling);
+
+/**
+ * Let an image be zoomed with the wheel, panned by dragging and fitted
+ * again with a double click wherever it is shown, or stop that and show
+ * all of it again.
+ */
+int neomacs_display_set_image_interactive(struct NeomacsDisplay *handle,
+                                          uint32_t imageId,
+                                          int interactive);
+
+/**
+ * Make an empty canvas of width x height logical pixels, shown under the
+ * returned image ID, or 0 on failure.  Draw on it with
+ * neomacs_display_canvas_op and neomacs_display_canvas_flush; free it
+ * with neomacs_display_free_image.
+ */
+uint32_t neomacs_display_canvas_create(struct NeomacsDisplay *handle,
+                                       int width,
+                                       int height);
+
+/**
+ * Add the command name to canvas imageId, with the nargs numbers at
+ * args, color (0xAARRGGBB, alpha 0 opaque) and text, which may be NULL.
+ * With name NULL, all commands are dropped instead.  Nothing is drawn
+ * until the canvas is flushed.
+ *
+ * Returns 0 on success, or -1 with the reason stored in outError
+ * (free with neomacs_display_free_string).
+ */
+int neomacs_display_canvas_op(struct NeomacsDisplay *handle,
+                              uint32_t imageId,
+                              const char *name,
+                              const float *args,
+                              int nargs,
+                              uint32_t color,
+                              const char *text,
+                              char **outError);
+
+/**
+ * Redraw canvas imageId with all its commands (async).
+ */
+int neomacs_display_canvas_flush(struct NeomacsDisplay *handle, uint32_t imageId);
+
+/**
+ * Make an image id showing a chart of the count values at values, drawn
+ * by the GPU at width x height logical pixels.  kind is 0 for a line,
+ * 1 for a filled area, 2 for bars.  Colors are 0xAARRGGBB; a fill of 0
+ * is color at 30% and a background of 0 is none.  min and max fix the
+ * values at the bottom and top of the chart; NaN fits them to the values.
+ *
+ * Returns the image ID, or 0 on failure.
+ */
+uint32_t neomacs_display_chart_create(struct NeomacsDisplay *_handle,
+                                      int kind,
+                                      const float *values,
+                                      int count,
+                                      int width,
+                                      int height,
+                                      uint32_t color,
+                                      uint32_t fill,
+                                      uint32_t background,
+                                      float lineWidth,
+                                      float min,
+                                      float max);
+
+/**
+ * Move chart imageId to the count values at values over durationMs.
+ * Values past the old ones grow from zero.
+ */
+int neomacs_display_chart_update(struct NeomacsDisplay *handle,
+                                 uint32_t imageId,
+                                 const float *values,
+                                 int count,
+                                 uint32_t durationMs);
+
+/**
+ * Register the shader effect name with the WGSL shade function in
+ * source, replacing an effect of that name.  The source is compiled
+ * first, so it never reaches the GPU if it does not.
+ *
+ * Returns 0 on success, or -1 with the compiler's message stored in
+ * outError (free with neomacs_display_free_string).
+ */
+int neomacs_display_shader_effect_register(struct NeomacsDisplay *_handle,
+                                           const char *name,
+                                           const char *source,
+                                           char **outError);
+
+/**
+ * Draw the shader effect name over a target with the 4 params (NULL for
+ * zeros), replacing the effect of that name there.  kind 0 targets the
+ * region x, y, width, height, 1 the window id, 2 the floating image id.
+ * Effects that are not registered or built in draw nothing.
+ */
+int neomacs_display_shader_effect_apply(struct NeomacsDisplay *_handle,
+                                        const char *name,
+                                        int kind,
+                                        int64_t id,
+                                        float x,
+                                        float y,
+                                        float width,
+                                        float height,
+                                        const float *params);
+
+/**
+ * Remove the shader effects named name (any if NULL) from a target, as
+ * for neomacs_display_shader_effect_apply; any target if kind is -1.
+ */
+int neomacs_display_shader_effect_remove(struct NeomacsDisplay *_handle,
+                                         const char *name,
+                                         int kind,
+                                         int64_t id,
+                                         float x,
+                                         float y,
+                                         float width,
+                                         float height);
+
+/**
+ * Give image imageId the picture of image newImageId, moving from the
+ * old picture to the new by effect ("crossfade", "slide-left",
+ * "slide-right", "slide-up" or "slide-down") over durationMs.
+ * newImageId is consumed and must not be used again.
+ */
+int neomacs_display_swap_image(struct NeomacsDisplay *handle,
+                               uint32_t imageId,
+                               uint32_t newImageId,
+                               const char *effect,
+                               uint32_t durationMs);
+
+/**
+ * Draw chrome ELEMENT ("tab-bar", "tooltip", "popup-menu" or
+ * "child-frame") with image imageId cut into nine parts: corners of
+ * top/right/bottom/left image pixels keep their size, edges and center
+ * stretch.  scale is the size of image pixels on screen.  An imageId
+ * of 0 gives the element its own look again.
+ */
+int neomacs_display_set_chrome_image(struct NeomacsDisplay *handle,
+                                     const char *element,
+                                     uint32_t imageId,
+                                     int top,
+                                     int right,
+                                     int bottom,
+                                     int left,
+                                     double scale);
+
+/* Chrome elements of neomacs_display_add_chrome_region.  */
+#define NEOMACS_CHROME_TAB_BAR 0
+#define NEOMACS_CHROME_TOOLTIP 1
+#define NEOMACS_CHROME_POPUP_MENU 2
+#define NEOMACS_CHROME_CHILD_FRAME 3
+
+/**
+ * Mark a rect of the frame being built as chrome of ELEMENT, one of
+ * NEOMACS_CHROME_*, so its nine-patch image is drawn there.
+ */
+void neomacs_display_add_chrome_region(struct NeomacsDisplay *handle,
+                                       int element,
+                                       int x,
+                                       int y,
+                                       int width,
+                                       int height);
+
+/**
+ * Open a PDF document (async); returns a document ID or 0
+ */
+uint32_t neomacs_display_pdf_open(struct NeomacsDisplay *handle,
+                                  const char *path,
+                                  const char *password);
+
+/**
+ * Number of pages in a PDF document: 0 while loading, -1 on failure
+ */
+int neomacs_display_pdf_page_count(struct NeomacsDisplay *handle, uint32_t docId);
+
+/**
+ * Size of a PDF page in points; returns 0 on success, -1 if unknown
+ */
+int neomacs_display_pdf_page_size(struct NeomacsDisplay *handle,
+                                  uint32_t docId,
+                                  int page,
+                                  float *width,
+                                  float *height);
+
+/**
+ * Rasterize a PDF page (async); returns an image ID or 0
+ */
+uint32_t neomacs_display_pdf_render_page(struct NeomacsDisplay *handle,
+                                         uint32_t docId,
+                                         int page,
+                                         float zoom);
+
+/**
+ * Request text and link extraction for a PDF page (async)
+ */
+void neomacs_display_pdf_extract_page(struct NeomacsDisplay *handle, uint32_t docId, int page);
+
+/**
+ * Extracted text of a PDF page, or NULL if not extracted yet.
+ * Free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_pdf_page_text(struct NeomacsDisplay *handle, uint32_t docId, int page);
+
+/**
+ * Number of links on an extracted PDF page, or -1 if not extracted yet
+ */
+int neomacs_display_pdf_link_count(struct NeomacsDisplay *handle, uint32_t docId, int page);
+
+/**
+ * Link rectangle and target on an extracted PDF page.
+ * Returns the URI (empty for internal links), or NULL if out of range.
+ * Free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_pdf_link_info(struct NeomacsDisplay *handle,
+                                    uint32_t docId,
+                                    int page,
+                                    int index,
+                                    float *x,
+                                    float *y,
+                                    float *width,
+                                    float *height,
+                                    int *destPage);
+
+/**
+ * Close a PDF document
+ */
+void neomacs_display_pdf_close(struct NeomacsDisplay *handle, uint32_t docId);
+
+/**
+ * Typeset a TeX math snippet as an image; returns an image ID or 0.
+ * On failure *outError holds a message to free with
+ * neomacs_display_free_string.
+ */
+uint32_t neomacs_display_math_render(struct NeomacsDisplay *handle,
+                                     const char *tex,
+                                     int display,
+                                     float size,
+                                     uint32_t color,
+                                     int *outWidth,
+                                     int *outHeight,
+                                     int *outAscent,
+                                     char **outError);
+
+/**
+ * Free all math snippet images and empty the snippet cache
+ */
+void neomacs_display_math_clear(struct NeomacsDisplay *handle);
+
+/**
+ * Load icon set NAME from PATH, a directory of NAME.svg files or an icon
+ * font; the icons of a font are named by CODEPOINTS, a file of
+ * "NAME HEX" lines, or when it is NULL by the font's glyph names.
+ * Returns the number of icons, or -1 with *outError holding a message
+ * to free with neomacs_display_free_string.
+ */
+int neomacs_display_icon_set_load(struct NeomacsDisplay *handle,
+                                  const char *name,
+                                  const char *path,
+                                  const char *codepoints,
+                                  char **outError);
+
+/**
+ * Make an image of icon NAME, SIZE logical pixels high, in COLOR
+ * (0xRRGGBB) or in its own colors if ownColors is nonzero; returns an
+ * image ID or 0 if no loaded set has the icon.
+ */
+uint32_t neomacs_display_icon(struct NeomacsDisplay *handle,
+                              const char *name,
+                              float size,
+                              uint32_t color,
+                              int ownColors,
+                              int *outWidth,
+                              int *outHeight);
+
+/**
+ * Free all icon images, keeping the loaded icon sets
+ */
+void neomacs_display_icon_clear(struct NeomacsDisplay *handle);
+
+/**
+ * Make an image of a code of `kind` ("qr", "qr-low", "qr-quartile",
+ * "qr-high" or "code128") for `text`, in `dark` and `light` (0xRRGGBB).
+ * Each module is `module_size` logical pixels wide; barcodes are
+ * `bar_height` logical pixels high.  The logical width and height are
+ * written to the out parameters.
+ *
+ * Returns the image ID, or 0 if the text cannot be encoded; the reason
+ * is then stored in `out_error` (free with `neomacs_display_free_string`).
+ */
+uint32_t neomacs_display_barcode_image(struct NeomacsDisplay *_handle,
+                                       const char *kind,
+                                       const char *text,
+                                       float moduleSize,
+                                       float barHeight,
+                                       uint32_t dark,
+                                       uint32_t light,
+                                       int *outWidth,
+                                       int *outHeight,
+                                       char **outError);
+
 /**
  * Set a floating video at a specific screen position
  */
@@ -606,6 +1097,124 @@ void neomacs_display_show_tooltip(struct NeomacsDisplay *handle,
  */
 void neomacs_display_hide_tooltip(struct NeomacsDisplay *handle);
 
+/**
+ * Show a character grid overlay (emoji picker, glyph table).
+ * ITEMS is a newline-separated list of cell strings.  Only ROWS rows
+ * of COLUMNS cells are visible at once.  Colors are sRGB floats.
+ */
+void neomacs_display_show_char_grid(struct NeomacsDisplay *handle,
+                                    float x, float y,
+                                    uint32_t columns, uint32_t rows,
+                                    float cell_size,
+                                    const char *items,
+                                    float fg_r, float fg_g, float fg_b,
+                                    float bg_r, float bg_g, float bg_b,
+                                    float sel_r, float sel_g, float sel_b);
+
+/**
+ * Set the selected cell (-1 = none) and first visible row of the
+ * character grid.  Returns the first visible row after clamping,
+ * or -1 if no grid is shown.
+ */
+int neomacs_display_update_char_grid(struct NeomacsDisplay *handle,
+                                     int selected, uint32_t scroll_row);
+
+/**
+ * Hide the character grid overlay.
+ */
+void neomacs_display_hide_char_grid(struct NeomacsDisplay *handle);
+
+/**
+ * Index of the character grid cell at (x, y), or -1 if none.
+ */
+int neomacs_display_char_grid_index_at(struct NeomacsDisplay *handle,
+                                       float x, float y);
+
+/**
+ * Summary of one buffer line for the minimap.
+ */
+struct NeomacsMinimapLine {
+  int64_t start;     /* buffer position of the line start */
+  uint16_t indent;   /* columns of leading whitespace */
+  uint16_t length;   /* columns of text after the indentation */
+  uint32_t color;    /* dominant foreground, 0xRRGGBB */
+};
+
+/**
+ * Replace REMOVED minimap lines of a buffer starting at line FIRST with
+ * COUNT new summaries, shifting the following line starts by POS_DELTA.
+ */
+void neomacs_display_minimap_update(struct NeomacsDisplay *handle,
+                                    uint64_t buffer_id,
+                                    uint32_t first, uint32_t removed,
+                                    int64_t pos_delta,
+                                    const struct NeomacsMinimapLine *lines,
+                                    uint32_t count);
+
+/**
+ * Drop the minimap line summaries of a buffer.
+ */
+void neomacs_display_minimap_forget(struct NeomacsDisplay *handle,
+                                    uint64_t buffer_id);
+
+/**
+ * Buffer position to scroll to when the minimap viewport is dragged to
+ * offset Y in a strip HEIGHT pixels tall.
+ */
+int64_t neomacs_display_minimap_target(struct NeomacsDisplay *handle,
+                                       uint64_t buffer_id,
+                                       int64_t buffer_size,
+                                       float y, float height,
+                                       uint32_t view_lines,
+                                       float line_height);
+
+/**
+ * Queue LEN bytes of UTF-8 TEXT, starting at buffer position BASE, for
+ * background syntax highlighting as LANGUAGE.  Returns 0 if the language
+ * is not supported.
+ */
+int neomacs_display_highlight_request(struct NeomacsDisplay *handle,
+                                      uint64_t buffer_id,
+                                      const char *language,
+                                      const uint8_t *text,
+                                      uintptr_t len,
+                                      int64_t base);
+
+/**
+ * Shift highlight spans after text in [BEG, OLD_END) became [BEG, NEW_END)
+ */
+void neomacs_display_highlight_edit(struct NeomacsDisplay *handle,
+                                    uint64_t buffer_id,
+                                    int64_t beg,
+                                    int64_t old_end,
+                                    int64_t new_end);
+
+/**
+ * Drop the highlight spans of a buffer
+ */
+void neomacs_display_highlight_forget(struct NeomacsDisplay *handle,
+                                      uint64_t buffer_id);
+
+/**
+ * Set the color and weight of a highlight category (ENABLED 0 clears it)
+ */
+void neomacs_display_highlight_set_face(struct NeomacsDisplay *handle,
+                                        int kind, int enabled, uint32_t fg,
+                                        int bold, int italic);
+
+/**
+ * Merge finished highlight jobs; returns the number still pending
+ */
+int neomacs_display_highlight_poll(struct NeomacsDisplay *handle,
+                                   int *outMerged);
+
+/**
+ * Load grouped emoji data as lines of "category\tsubgroup\temoji\tname".
+ * PATH may be NULL to use $NEOMACS_EMOJI_DATA or the system emoji-test.txt.
+ * Free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_emoji_data(const char *path);
+
 /**
  * Set the window title (threaded mode - sends to render thread)
  */
@@ -660,6 +1269,12 @@ void neomacs_display_set_cursor_blink(struct NeomacsDisplay *handle,
 void neomacs_display_set_cursor_animation(struct NeomacsDisplay *handle,
                                            int enabled, float speed);
 
+/**
+ * Select a Neovide-style cursor animation mode by name
+ */
+void neomacs_display_set_cursor_animation_mode(struct NeomacsDisplay *handle,
+                                                const char *mode);
+
 /**
  * Configure all animation settings
  */
@@ -672,6 +1287,28 @@ void neomacs_display_set_animation_config(struct NeomacsDisplay *handle,
                                            float trail_size,
                                            uint32_t crossfade_effect, uint32_t crossfade_easing);
 
+/**
+ * Define a named keyframe animation curve ("TIME:VALUE[:EASING] ..." or
+ * a bare easing name).  NULL or empty SPEC removes the curve.
+ * Returns 0 on success, -1 if SPEC does not parse.
+ */
+int neomacs_display_set_animation_curve(struct NeomacsDisplay *handle,
+                                        const char *name, const char *spec);
+
+/**
+ * Animate PROPERTY ("x", "y", "width", "height", "opacity",
+ * "corner-radius") of a floating element toward TO.  KIND is 0 (image),
+ * 1 (webkit) or 2 (terminal); NULL EASING means ease-out.  A nonzero
+ * NOTIFY is sent back in an animation-completed event when it ends.
+ * Returns 0 on success, -1 on an unknown or unsupported argument.
+ */
+int neomacs_display_animate_floating(struct NeomacsDisplay *handle,
+                                     int kind, uint32_t id,
+                                     const char *property, float to,
+                                     uint32_t duration_ms,
+                                     const char *easing,
+                                     uint32_t notify);
+
 /**
  * Add per-window metadata for animation detection
  */
@@ -732,6 +1369,24 @@ int neomacs_display_webkit_init(struct NeomacsDisplay *handle, void *eglDisplay)
  */
 uint32_t neomacs_display_webkit_create(struct NeomacsDisplay *handle, int width, int height);
 
+/**
+ * Create a new WebKit view in a named profile (NULL = default profile)
+ */
+uint32_t neomacs_display_webkit_create_with_profile(struct NeomacsDisplay *handle,
+                                                    int width,
+                                                    int height,
+                                                    const char *profile);
+
+/**
+ * Define a named WebKit profile with its own cookies, storage and cache.
+ * DATA_DIR / CACHE_DIR may be NULL to use XDG defaults.
+ */
+int neomacs_display_webkit_define_profile(struct NeomacsDisplay *handle,
+                                          const char *name,
+                                          const char *dataDir,
+                                          const char *cacheDir,
+                                          int ephemeral);
+
 /**
  * Destroy a WebKit view
  */
@@ -937,23 +1592,273 @@ void neomacs_display_begin_frame_window(struct NeomacsDisplay *handle, uint32_t
  */
 void neomacs_display_end_frame_window(struct NeomacsDisplay *handle, uint32_t windowId);
 
-/* Note: neomacs_display_set_event_callback() and neomacs_display_poll_events() have been removed.
+/* Note: neomacs_display_poll_events() has been removed.
  * Events are now delivered via the threaded mode wakeup mechanism.
- * Use neomacs_display_drain_input() instead.
+ * Use neomacs_display_drain_input() instead, optionally with
+ * neomacs_display_set_event_callback().
  */
 
 /**
- * Set an animation configuration option (stub)
+ * Set a display option.  Returns 1 if the option exists and the value
+ * is valid for it.
  */
 int neomacs_display_set_animation_option(struct NeomacsDisplay *handle,
                                          const char *key,
                                          const char *value);
 
 /**
- * Get an animation configuration option (stub)
+ * Get the effective value of a display option, or NULL if there is no
+ * such option.  Free the result with neomacs_display_free_string.
  */
 char *neomacs_display_get_animation_option(struct NeomacsDisplay *handle, const char *key);
 
+/**
+ * Display option description returned by neomacs_display_option_info.
+ * The strings live as long as the process.
+ */
+struct NeomacsOptionInfo {
+  const char *name;
+  const char *group;
+  /* "boolean", "integer", "float", "choice", "color" or "color-list" */
+  const char *type_name;
+  const char *default_value;
+  const char *doc;
+  /* Space-separated names for "choice" options, else NULL */
+  const char *choices;
+  /* Range of "integer" and "float" options; the maximum number of
+     colors in max for "color-list" */
+  double min;
+  double max;
+};
+
+/**
+ * Get the number of display options.
+ */
+int neomacs_display_option_count(void);
+
+/**
+ * Describe the display option at INDEX.
+ * Returns 1 on success, 0 on failure.
+ */
+int neomacs_display_option_info(int index, struct NeomacsOptionInfo *info);
+
+/**
+ * Error report recorded by the display engine.
+ */
+struct NeomacsErrorInfo {
+  uint32_t id;
+  /* "info", "warning" or "error"; static */
+  const char *severity;
+  /* Subsystem, e.g. "image", "video", "font", "shader"; static */
+  const char *kind;
+  /* Id of the image, video, view or terminal concerned, or 0 */
+  uint32_t resource;
+  /* Number of times the failure happened */
+  uint32_t count;
+  /* Free with neomacs_display_free_string */
+  char *message;
+};
+
+/**
+ * Describe the oldest kept error report with an id greater than AFTER_ID.
+ * Returns 1 if there is one, 0 otherwise.
+ */
+int neomacs_display_next_error(uint32_t afterId, struct NeomacsErrorInfo *info);
+
+/**
+ * Forget all error reports.
+ */
+void neomacs_display_clear_errors(void);
+
+/**
+ * GPU memory held by the display engine's caches, in bytes.
+ */
+struct NeomacsGpuMemoryUsage {
+  uint64_t glyphs;
+  uint64_t images;
+  /* Part of IMAGES drawn in the last frame */
+  uint64_t visibleImages;
+  uint64_t videos;
+  /* Offscreen buffers and transition snapshots */
+  uint64_t snapshots;
+  uint64_t budget;
+};
+
+/**
+ * Fill INFO with the GPU memory measured after the last frame.
+ */
+void neomacs_display_gpu_memory_usage(struct NeomacsGpuMemoryUsage *info);
+
+/**
+ * Input latency percentiles, in microseconds.
+ */
+struct NeomacsLatencyStats {
+  uint64_t count;
+  uint64_t p50Us;
+  uint64_t p90Us;
+  uint64_t p99Us;
+  uint64_t maxUs;
+};
+
+/**
+ * Fill INFO with the latency percentiles of STAGE over the recent
+ * presses: 0 from the window system to Emacs, 1 from Emacs to the frame
+ * it sends, 2 from that frame to the screen, 3 the whole path.
+ * Returns 0 for an unknown stage.
+ */
+int neomacs_display_input_latency(int stage, struct NeomacsLatencyStats *info);
+
+/**
+ * The quality level in effect: "full", "no-particles",
+ * "no-transitions", "reduced-video" or "no-blur".  The string is
+ * static.
+ */
+const char *neomacs_display_quality_level(void);
+
+/**
+ * Statistics of the last rendered frame.  Phase times are in
+ * microseconds; skippedFrames and lateFrames are running totals.
+ */
+struct NeomacsFrameStats {
+  uint64_t frames;
+  uint64_t glyphs;
+  uint64_t windows;
+  /* Cell background rects left after overdraw elimination */
+  uint64_t backgroundRects;
+  uint64_t rowCacheHits;
+  uint64_t rowCacheMisses;
+  uint64_t prepareUs;
+  uint64_t drawUs;
+  uint64_t presentUs;
+  /* Frames replaced by a newer one before being drawn */
+  uint64_t skippedFrames;
+  /* Frames that took longer than a refresh interval */
+  uint64_t lateFrames;
+};
+
+/**
+ * Fill INFO with the statistics of the last frame presented.
+ */
+void neomacs_display_frame_stats(struct NeomacsFrameStats *info);
+
+/**
+ * The structure of the current frame as JSON: windows, glyphs of each
+ * kind, floating layers bottom to top, running animations and cache
+ * sizes.  Waits up to a second for the render thread.  Returns NULL if
+ * it did not answer; free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_scene_dump(struct NeomacsDisplay *_handle);
+
+/**
+ * The current frame as plain text: for each window body, minibuffer and
+ * mode line a header line, then its visual lines with the cursor and the
+ * text drawn in face SELECTION_FACE marked.  SELECTION_FACE 0 marks no
+ * selection.  Waits up to a second for the render thread.  Returns NULL
+ * if it did not answer; free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_frame_text(struct NeomacsDisplay *_handle, int selectionFace);
+
+/**
+ * Memory held by one subsystem of the display engine.
+ */
+struct NeomacsMemoryUse {
+  /* e.g. "glyph-atlas", "images", "terminal-grids"; free with
+     neomacs_display_free_string */
+  char *subsystem;
+  /* Glyphs, images, videos, snapshots or terminals held */
+  uint64_t count;
+  uint64_t bytes;
+  /* 1 for video memory, 0 for main memory */
+  int gpu;
+};
+
+/**
+ * Fill ENTRIES with up to MAX subsystems and the memory each holds.
+ * Waits up to a second for the render thread.  Returns the number of
+ * entries filled, or -1 if the render thread did not answer.
+ */
+int neomacs_display_memory_report(struct NeomacsDisplay *_handle,
+                                  struct NeomacsMemoryUse *entries,
+                                  int max);
+
+/**
+ * Save the terminals, images, videos and WebKit views created so far,
+ * with their floating positions and the display options set at runtime,
+ * to PATH (NULL: the default session file).  The file is written in the
+ * background; failures are reported as display errors.  Returns 1 if the
+ * save was queued.
+ */
+int neomacs_display_session_save(struct NeomacsDisplay *handle, const char *path);
+
+/**
+ * Record the frames the display receives from now on, with the images
+ * they show, to the replay file PATH for the neomacs-replay viewer.
+ * Failures to write are reported as display errors.  Returns 1 if
+ * recording was queued.
+ */
+int neomacs_display_replay_start(struct NeomacsDisplay *_handle, const char *path);
+
+/**
+ * Finish the replay being recorded, if any.
+ */
+void neomacs_display_replay_stop(struct NeomacsDisplay *_handle);
+
+/**
+ * Play an effect offscreen over a sample frame and write its frames to
+ * PATH: an animated GIF if PATH ends in .gif, else frame-NNNN.png files
+ * in the directory PATH.  KIND is "transition" or "cursor" and NAME an
+ * effect or cursor style as `neomacs-set-animation-config` names them.
+ * The effect lasts `duration_ms` and is sampled at `fps`.  The frames
+ * are encoded in the background; failures are reported as display
+ * errors.  Returns 1 if the export was queued, 0 if the display is not
+ * running, -1 for an unknown KIND or NAME.
+ */
+int neomacs_display_export_effect_preview(struct NeomacsDisplay *handle,
+                                          const char *kind,
+                                          const char *name,
+                                          const char *path,
+                                          uint32_t durationMs,
+                                          uint32_t fps);
+
+/* NeomacsDisplayTheme.set bits of the optional colors.  */
+#define NEOMACS_THEME_CURSOR (1 << 0)
+#define NEOMACS_THEME_DIVIDER (1 << 1)
+#define NEOMACS_THEME_PLACEHOLDER (1 << 2)
+#define NEOMACS_THEME_TERMINAL_PALETTE (1 << 3)
+
+/**
+ * Colors the display draws with besides those of faces.  Colors are
+ * 0xAARRGGBB pixels, alpha 0 meaning opaque; `set` says which of the
+ * optional ones are given.
+ */
+struct NeomacsDisplayTheme {
+  uint32_t background;
+  uint32_t foreground;
+  uint32_t cursor;
+  uint32_t divider;
+  /* Area of a web view or video with nothing to show yet */
+  uint32_t placeholder;
+  uint32_t terminalPalette[16];
+  uint32_t set;
+};
+
+/**
+ * Switch the display's own colors to THEME at once, crossfading from
+ * the old ones over `fadeMs` if not 0.  Returns 1 if the switch was
+ * queued.
+ */
+int neomacs_display_set_theme(struct NeomacsDisplay *handle,
+                              const struct NeomacsDisplayTheme *theme,
+                              uint32_t fadeMs);
+
+/**
+ * Recreate the session saved in PATH (NULL: the default session file)
+ * under fresh ids.  Returns one `KIND OLD-ID NEW-ID` line per resource
+ * recreated, KIND being terminal, image, video or webkit, or NULL if the
+ * file cannot be read.  Free the result with neomacs_display_free_string.
+ */
+char *neomacs_display_session_restore(struct NeomacsDisplay *handle, const char *path);
+
 /**
  * Free a string returned by neomacs_display_get_animation_option
  */
@@ -970,11 +1875,37 @@ int neomacs_display_update_animation(struct NeomacsDisplay *handle, double dt);
 int neomacs_display_animation_active(struct NeomacsDisplay *handle);
 
 /**
- * Trigger a buffer transition animation (stub)
+ * Crossfade every window into the next frame using EFFECT, the name of
+ * a buffer transition effect or else of a scroll effect; "none" switches
+ * at once.  A nonzero NOTIFY is sent back in an animation-completed
+ * event once every window's crossfade ended.
+ * Returns 1 if the transition was queued.
  */
 int neomacs_display_start_buffer_transition(struct NeomacsDisplay *handle,
                                             const char *effect,
-                                            int durationMs);
+                                            int durationMs,
+                                            uint32_t notify);
+
+/**
+ * Zoom the whole frame to SCALE (1.0 = normal) over DURATION_MS, around
+ * (X, Y) in logical pixels, or around the cursor when X or Y is negative.
+ * A nonzero NOTIFY is sent back in an animation-completed event when the
+ * zoom ends.
+ * Returns 1 if the zoom was queued.
+ */
+int neomacs_display_set_frame_zoom(struct NeomacsDisplay *handle,
+                                   float scale,
+                                   int x,
+                                   int y,
+                                   int durationMs,
+                                   uint32_t notify);
+
+/**
+ * Announce that the selected window's next scroll moves its text PIXELS
+ * up (negative: down), so the scroll animation slides just that far.
+ * Returns 1 if the hint was queued.
+ */
+int neomacs_display_scroll_hint(struct NeomacsDisplay *handle, int pixels);
 
 /**
  * Prepare for buffer transition (stub)
@@ -999,6 +1930,34 @@ int neomacs_display_has_transition_snapshot(struct NeomacsDisplay *handle);
  */
 int neomacs_display_init_threaded(uint32_t width, uint32_t height, const char *title);
 
+/**
+ * Caret x offset before character INDEX inside the composed (ligature)
+ * glyph drawing TEXT in FACE_ID at FONT_SIZE, or -1 if not shaped yet
+ */
+float neomacs_display_x_for_char_index(const char *text,
+                                       uint32_t faceId,
+                                       float fontSize,
+                                       int index);
+
+/**
+ * Character index of the caret closest to offset X inside the composed
+ * glyph drawing TEXT, or -1 if not shaped yet
+ */
+int neomacs_display_char_index_for_x(const char *text,
+                                     uint32_t faceId,
+                                     float fontSize,
+                                     float x);
+
+/**
+ * Fit fallback-font glyphs (emoji, symbols) of FACE_ID, or of all faces
+ * when FACE_ID < 0, to the text font; ENABLED < 0 drops the face setting
+ */
+void neomacs_display_set_fallback_metrics(struct NeomacsDisplay *handle,
+                                          int faceId,
+                                          int enabled,
+                                          float scale,
+                                          float baselineOffset);
+
 /**
  * Monitor info struct returned by neomacs_display_get_monitor_info.
  */
@@ -1111,10 +2070,59 @@ uint32_t neomacs_display_terminal_create(uint16_t cols, uint16_t rows,
                                           uint8_t mode, const char *shell);
 
 /**
- * Write input data to a terminal (keyboard input from user).
+ * Create a terminal running a shell (or COMMAND, if not NULL) on an SSH
+ * host.  DESTINATION is "[user@]host[:port]"; IDENTITY, if not NULL, is a
+ * private key file tried after the SSH agent.  Connects in the
+ * background; on failure the terminal exits.
+ * Returns terminal ID (>0 on success, 0 on failure).
+ */
+uint32_t neomacs_display_terminal_create_ssh(uint16_t cols, uint16_t rows,
+                                              uint8_t mode,
+                                              const char *destination,
+                                              const char *command,
+                                              const char *identity);
+
+/**
+ * Queue input data for a terminal (keyboard input from user).
+ * Never blocks.  Returns 0 on success, NEOMACS_TERMINAL_BUSY if too much
+ * input is already queued, NEOMACS_STALE_HANDLE for a destroyed
+ * terminal, -1 on other failures.
+ */
+int neomacs_display_terminal_write(uint32_t terminal_id,
+                                    const uint8_t *data, size_t len);
+
+/**
+ * Queue COUNT pieces of input in one batch; CHUNKS[i] holds LENS[i]
+ * bytes.  Returns as neomacs_display_terminal_write.
  */
-void neomacs_display_terminal_write(uint32_t terminal_id,
-                                     const uint8_t *data, size_t len);
+int neomacs_display_terminal_write_many(uint32_t terminal_id,
+                                         const uint8_t *const *chunks,
+                                         const size_t *lens, size_t count);
+
+/**
+ * Input queue state of a terminal.
+ */
+struct NeomacsTerminalInputStatus {
+  /* Bytes queued but not yet written to the PTY */
+  uint64_t pending;
+  /* Queue size above which writes return NEOMACS_TERMINAL_BUSY */
+  uint64_t limit;
+  /* Bytes written to the PTY so far */
+  uint64_t written;
+};
+
+/**
+ * Fill STATUS with a terminal's input queue state.
+ * Returns 0 on success, NEOMACS_STALE_HANDLE or -1 on failure.
+ */
+int neomacs_display_terminal_input_status(uint32_t terminal_id,
+                                           struct NeomacsTerminalInputStatus *status);
+
+/**
+ * Drop queued input not yet written to the PTY.
+ * Returns the number of bytes dropped, NEOMACS_STALE_HANDLE or -1.
+ */
+int64_t neomacs_display_terminal_cancel_input(uint32_t terminal_id);
 
 /**
  * Resize a terminal.
@@ -1127,12 +2135,50 @@ void neomacs_display_terminal_resize(uint32_t terminal_id,
  */
 void neomacs_display_terminal_destroy(uint32_t terminal_id);
 
+/**
+ * Stop drawing a terminal, keeping its shell and grid alive.  Returns 0
+ * on success, NEOMACS_STALE_HANDLE or -1 on failure.
+ */
+int neomacs_display_terminal_detach(uint32_t terminal_id);
+
+/**
+ * Draw a detached terminal again in MODE (0=Window, 1=Inline,
+ * 2=Floating).  Returns 0 on success, NEOMACS_STALE_HANDLE or -1 on
+ * failure.
+ */
+int neomacs_display_terminal_attach(uint32_t terminal_id, uint8_t mode);
+
 /**
  * Set floating terminal position and opacity.
  */
 void neomacs_display_terminal_set_float(uint32_t terminal_id,
                                          float x, float y, float opacity);
 
+/**
+ * Set the font of a terminal.  FAMILY NULL or empty and SIZE <= 0
+ * follow the frame font.  Returns 0 on success, NEOMACS_STALE_HANDLE
+ * or -1 on failure.
+ */
+int neomacs_display_terminal_set_font(uint32_t terminal_id,
+                                       const char *family, float size);
+
+/**
+ * Set what is drawn behind a terminal's cells.  KIND 0 is the solid
+ * theme background, 1 image IMAGE_ID darkened by AMOUNT, 2 an acrylic
+ * background at opacity AMOUNT, blurred if BLUR is nonzero.  Returns 0
+ * on success, NEOMACS_STALE_HANDLE or -1 on failure.
+ */
+int neomacs_display_terminal_set_background(uint32_t terminal_id, int kind,
+                                             uint32_t image_id, float amount,
+                                             int blur);
+
+/**
+ * Set whether a terminal draws typed characters before the program
+ * echoes them.  MODE 0 never, 1 always, 2 while the echo is slow to come
+ * back.  Returns 0 on success, NEOMACS_STALE_HANDLE or -1 on failure.
+ */
+int neomacs_display_terminal_set_local_echo(uint32_t terminal_id, int mode);
+
 /**
  * Get visible text from a terminal.
  * Returns a malloc'd C string (caller must free with free()).
@@ -1140,6 +2186,46 @@ void neomacs_display_terminal_set_float(uint32_t terminal_id,
  */
 char *neomacs_display_terminal_get_text(uint32_t terminal_id);
 
+/**
+ * Get the screen lines of a terminal changed since the last call; with
+ * ALL nonzero, every line.  Returns a malloc'd C string (caller must free
+ * with free()): the number of screen lines, then one "ROW\tTEXT" line
+ * per changed row with SGR sequences in TEXT.  Returns NULL on failure.
+ */
+char *neomacs_display_terminal_changed_lines(uint32_t terminal_id, int all);
+
+/**
+ * Get the input being typed at the shell prompt of a terminal.  Returns a
+ * malloc'd C string (caller must free with free()): "ROW\tCOL\tCURSOR\n"
+ * followed by the input text, or NULL if the shell is not known to be
+ * reading input.
+ */
+char *neomacs_display_terminal_input_region(uint32_t terminal_id);
+
+/**
+ * Turn guessing prompts by their look on or off, for shells that send no
+ * OSC 133 marks.  Returns 0 on success.
+ */
+int neomacs_display_terminal_set_prompt_heuristics(uint32_t terminal_id, int on);
+
+/**
+ * Run COMMAND, which starts tmux in control mode, and give each tmux pane
+ * a terminal, announced with NEOMACS_EVENT_TMUX_PANE_ADDED.  Returns the
+ * client id, or 0 on failure.
+ */
+uint32_t neomacs_display_tmux_connect(const char *command);
+
+/**
+ * Send a tmux command line to a tmux client.  Returns 0 on success.
+ */
+int neomacs_display_tmux_command(uint32_t client, const char *command);
+
+/**
+ * Detach a tmux client, leaving the session running.  Its pane terminals
+ * are closed with NEOMACS_EVENT_TMUX_PANE_CLOSED.  Returns 0 on success.
+ */
+int neomacs_display_tmux_disconnect(uint32_t client);
+
 /* ============================================================================
  * Clipboard API
  * ============================================================================ */
@@ -1260,7 +2346,8 @@ void neomacs_display_set_show_whitespace(
 void neomacs_display_set_inactive_dim(
     struct NeomacsDisplay *handle,
     int enabled,
-    int opacity);
+    int opacity,
+    int saturation);
 
 void neomacs_display_set_cursor_glow(
     struct NeomacsDisplay *handle,
@@ -1283,7 +2370,14 @@ void neomacs_display_set_focus_mode(
 void neomacs_display_set_minimap(
     struct NeomacsDisplay *handle,
     int enabled,
-    int width);
+    int width,
+    int line_height);
+
+void neomacs_display_set_backdrop_blur(
+    struct NeomacsDisplay *handle,
+    int enabled,
+    int radius,
+    int passes);
 
 void neomacs_display_set_typing_ripple(
     struct NeomacsDisplay *handle,
@@ -1457,6 +2551,26 @@ void neomacs_display_set_resize_padding(
     int duration_ms,
     int max_padding);
 
+void neomacs_display_set_text_gamma(
+    struct NeomacsDisplay *handle,
+    int enabled,
+    int gamma_x100,
+    int contrast_pct);
+
+void neomacs_display_set_cursor_inversion(
+    struct NeomacsDisplay *handle,
+    int enabled,
+    int has_color,
+    int r,
+    int g,
+    int b);
+
+void neomacs_display_set_selection_runs(
+    struct NeomacsDisplay *handle,
+    int enabled,
+    int face_id,
+    int radius);
+
 void neomacs_display_set_cursor_error_pulse(
     struct NeomacsDisplay *handle,
     int enabled,
@@ -2525,4 +3639,66 @@ void neomacs_display_free_dropped_path(char *path);
  */
 char *neomacs_display_get_terminal_title(uint32_t terminal_id);
 
+/**
+ * Get the text of the oldest pending event of KIND for ID: the new title
+ * or URL of NEOMACS_EVENT_TERMINAL_TITLE_CHANGED,
+ * NEOMACS_EVENT_WEBKIT_TITLE_CHANGED and NEOMACS_EVENT_WEBKIT_URL_CHANGED.
+ * Returns a C string that must be freed with
+ * neomacs_display_free_dropped_path(), or NULL if none pending.
+ */
+char *neomacs_display_get_event_text(uint32_t kind, uint32_t id);
+
+/**
+ * Number of events waiting to be drained with neomacs_display_drain_input().
+ */
+int neomacs_display_pending_events(void);
+
+/**
+ * Register CALLBACK to be called with USER_DATA whenever an event is
+ * queued, or unregister with NULL.  The callback runs on the render
+ * thread and should only schedule a neomacs_display_drain_input() on the
+ * host's own thread.  Emacs selects on the wakeup fd and needs no callback.
+ */
+void neomacs_display_set_event_callback(void (*callback)(void *), void *user_data);
+
+/**
+ * Thread-safe command handle.  The neomacs_display_handle_* functions may
+ * be called from any thread; commands are applied by the render thread at
+ * the next frame boundary, in the order they were queued.
+ */
+typedef struct DisplayHandle DisplayHandle;
+
+/**
+ * Get a command handle, or NULL before threaded init.
+ * Free with neomacs_display_handle_free().
+ */
+DisplayHandle *neomacs_display_get_handle(void);
+
+void neomacs_display_handle_free(DisplayHandle *handle);
+
+/**
+ * Queue an image load.  Returns the image id, or 0 on error.
+ */
+uint32_t neomacs_display_handle_load_image_file(const DisplayHandle *handle,
+                                                const char *path,
+                                                int maxWidth,
+                                                int maxHeight);
+
+/**
+ * Queue a transition.  WINDOW_ID 0 means every window; EFFECT NULL means
+ * crossfade.  Returns 1 on success.
+ */
+int neomacs_display_handle_start_transition(const DisplayHandle *handle,
+                                            int64_t windowId,
+                                            const char *effect,
+                                            int durationMs);
+
+/**
+ * Queue input bytes for terminal TERMINAL_ID.  Returns 1 on success.
+ */
+int neomacs_display_handle_terminal_write(const DisplayHandle *handle,
+                                          uint32_t terminalId,
+                                          const uint8_t *data,
+                                          uintptr_t len);
+
 #endif  /* NEOMACS_DISPLAY_H */
@@ -26,6 +26,7 @@ along with GNU Emacs.  If not, see <https://www.gnu.org/licenses/>.  */
 #include <dlfcn.h>
 #include <string.h>
 #include <stdint.h>
+#include <math.h>
 #include <signal.h>
 #include <xkbcommon/xkbcommon.h>
 
@@ -65,6 +66,12 @@ static int neomacs_image_cache_count = 0;
 /* Track popup/menu activation for tooltip/auto-select suppression */
 static int neomacs_popup_activated_flag;
 
+/* Minimap strip width in pixels (0 when the minimap is off), the height
+   of one line block, and the window whose viewport is being dragged.  */
+static int neomacs_minimap_width;
+static int neomacs_minimap_line_height = 2;
+static Lisp_Object neomacs_minimap_drag_window;
+
 /* Forward declarations */
 static void neomacs_extract_full_frame (struct frame *f);
 static void neomacs_define_frame_cursor (struct frame *f, Emacs_Cursor cursor);
@@ -957,6 +964,100 @@ neomacs_send_face (void *handle, struct frame *f, struct face *face)
                             ul_position, ul_thickness);
 }
 
+#ifdef NEOMACS_HTML_RENDERER
+/* Send the named face SYMBOL as realized for W, with the attributes it
+   sets itself and the faces it inherits the rest from, so the renderer
+   merges it like Emacs does.  DEPTH counts the faces inheriting it.
+   Returns the face id, or -1 if SYMBOL is not a face.  */
+static int
+neomacs_send_named_face (void *handle, struct window *w, Lisp_Object symbol,
+                         int depth)
+{
+  struct frame *f = XFRAME (w->frame);
+  int id = lookup_named_face (w, f, symbol, false);
+  if (id < 0 || depth > 10)
+    return id;
+
+  Lisp_Object keywords[] = { QCforeground, QCbackground, QCfamily,
+                             QCheight, QCweight, QCslant, QCunderline,
+                             QCoverline, QCstrike_through, QCbox,
+                             QCinverse_video };
+  static const uint32_t fields[] = {
+    NEOMACS_FACE_FIELD_FOREGROUND,
+    NEOMACS_FACE_FIELD_BACKGROUND | NEOMACS_FACE_FIELD_BACKGROUND_STYLE,
+    NEOMACS_FACE_FIELD_FAMILY,
+    NEOMACS_FACE_FIELD_HEIGHT,
+    NEOMACS_FACE_FIELD_WEIGHT,
+    NEOMACS_FACE_FIELD_SLANT,
+    NEOMACS_FACE_FIELD_UNDERLINE,
+    NEOMACS_FACE_FIELD_OVERLINE,
+    NEOMACS_FACE_FIELD_STRIKE_THROUGH,
+    NEOMACS_FACE_FIELD_BOX,
+    NEOMACS_FACE_FIELD_INVERSE,
+  };
+  uint32_t specified = 0;
+  for (int i = 0; i < ARRAYELTS (fields); i++)
+    if (!EQ (Finternal_get_lisp_face_attribute (symbol, keywords[i], w->frame),
+             Qunspecified))
+      specified |= fields[i];
+
+  uint32_t parents[8];
+  int nparents = 0;
+  Lisp_Object inherit
+    = Finternal_get_lisp_face_attribute (symbol, QCinherit, w->frame);
+  if (!CONSP (inherit))
+    inherit = list1 (inherit);
+  for (; CONSP (inherit) && nparents < ARRAYELTS (parents);
+       inherit = XCDR (inherit))
+    {
+      Lisp_Object parent = XCAR (inherit);
+      if (!SYMBOLP (parent) || NILP (parent) || EQ (parent, Qunspecified))
+        continue;
+      int parent_id = neomacs_send_named_face (handle, w, parent, depth + 1);
+      if (parent_id >= 0)
+        parents[nparents++] = parent_id;
+    }
+
+  neomacs_display_set_face_inheritance (handle, id, specified,
+                                        parents, nparents);
+  neomacs_send_face (handle, f, FACE_FROM_ID_OR_NULL (f, id));
+  return id;
+}
+
+/* Lay out HTML over the text area of W.  Body, bold, italic, code and
+   link runs use the faces `default', `bold', `italic', `fixed-pitch' and
+   `link' as realized for W.  */
+static void
+neomacs_add_window_html (struct window *w, Lisp_Object html)
+{
+  struct frame *f = XFRAME (w->frame);
+  void *handle = FRAME_NEOMACS_DISPLAY_INFO (f)->display_handle;
+  static const char *const face_names[]
+    = { "default", "bold", "italic", "fixed-pitch", "link" };
+  uint32_t face_ids[ARRAYELTS (face_names)];
+
+  int default_id = lookup_basic_face (w, f, DEFAULT_FACE_ID);
+  neomacs_send_face (handle, f, FACE_FROM_ID_OR_NULL (f, default_id));
+  face_ids[0] = default_id;
+  /* The other faces are sent with what they inherit, so they keep
+     following `default' and their parents.  */
+  for (int i = 1; i < ARRAYELTS (face_names); i++)
+    {
+      int id = neomacs_send_named_face (handle, w, intern (face_names[i]), 0);
+      face_ids[i] = id < 0 ? default_id : id;
+    }
+
+  int top = WINDOW_TAB_LINE_HEIGHT (w) + WINDOW_HEADER_LINE_HEIGHT (w);
+  neomacs_display_add_html (handle, SSDATA (ENCODE_UTF_8 (html)),
+                            window_box_left (w, TEXT_AREA),
+                            WINDOW_TO_FRAME_PIXEL_Y (w, top),
+                            window_box_width (w, TEXT_AREA),
+                            window_box_height (w),
+                            face_ids[0], face_ids[1], face_ids[2],
+                            face_ids[3], face_ids[4]);
+}
+#endif
+
 /* Callback for foreach_window: extract all visible glyphs from a window's
    current_matrix and send them to the Rust display engine via FFI. */
 static bool
@@ -1058,12 +1159,22 @@ neomacs_extract_window_glyphs (struct window *w, void *user_data)
       mf_face_id = hlinfo->mouse_face_face_id;
     }
 
+  /* A window whose `neomacs-html' parameter is a string shows that HTML
+     in place of its buffer text; its mode, header and tab lines stay.  */
+  Lisp_Object html = Qnil;
+#ifdef NEOMACS_HTML_RENDERER
+  html = window_parameter (w, Qneomacs_html);
+#endif
+
   /* Walk all rows in current_matrix */
   for (int row_idx = 0; row_idx < matrix->nrows; row_idx++)
     {
       struct glyph_row *row = &matrix->rows[row_idx];
       if (!row->enabled_p)
         continue;
+      if (STRINGP (html)
+          && !row->mode_line_p && !row->header_line_p && !row->tab_line_p)
+        continue;
 
       /* Convert window-relative Y to frame-absolute Y */
       int frame_y = WINDOW_TO_FRAME_PIXEL_Y (w, row->y);
@@ -1422,6 +1533,18 @@ neomacs_extract_window_glyphs (struct window *w, void *user_data)
                                                     glyph_height > 0 ? glyph_height : row->height);
                   }
                   break;
+
+                case TERMINAL_GLYPH:
+                  {
+                    /* Top of the terminal, which sits on the baseline */
+                    int glyph_height = glyph->ascent + glyph->descent;
+                    neomacs_display_add_terminal_glyph (handle,
+                                                        glyph->u.terminal_id,
+                                                        row->ascent - glyph->ascent,
+                                                        glyph->pixel_width,
+                                                        glyph_height > 0 ? glyph_height : row->height);
+                  }
+                  break;
 #endif
 
                 default:
@@ -1437,6 +1560,11 @@ neomacs_extract_window_glyphs (struct window *w, void *user_data)
         }
     }
 
+#ifdef NEOMACS_HTML_RENDERER
+  if (STRINGP (html))
+    neomacs_add_window_html (w, html);
+#endif
+
   return true;  /* continue iterating to next window */
 }
 
@@ -4401,8 +4529,21 @@ neomacs_extract_full_frame (struct frame *f)
       struct window *tw = XWINDOW (f->tab_bar_window);
       if (tw->current_matrix)
         neomacs_extract_window_glyphs (tw, NULL);
+      if (WINDOW_PIXEL_HEIGHT (tw) > 0)
+        neomacs_display_add_chrome_region (dpyinfo->display_handle,
+                                           NEOMACS_CHROME_TAB_BAR,
+                                           WINDOW_LEFT_EDGE_X (tw),
+                                           WINDOW_TOP_EDGE_Y (tw),
+                                           WINDOW_PIXEL_WIDTH (tw),
+                                           WINDOW_PIXEL_HEIGHT (tw));
     }
 
+  if (FRAME_PARENT_FRAME (f))
+    neomacs_display_add_chrome_region (dpyinfo->display_handle,
+                                       NEOMACS_CHROME_CHILD_FRAME, 0, 0,
+                                       FRAME_PIXEL_WIDTH (f),
+                                       FRAME_PIXEL_HEIGHT (f));
+
   /* The minibuffer/echo area window is NOT part of the root window tree.
      Extract it separately so echo area text is rendered. */
   {
@@ -6302,6 +6443,16 @@ neomacs_draw_glyph_string (struct glyph_string *s)
                                                 glyph_height > 0 ? glyph_height : s->row->height);
               }
               break;
+            case TERMINAL_GLYPH:
+              {
+                int glyph_height = s->first_glyph->ascent + s->first_glyph->descent;
+                neomacs_display_add_terminal_glyph (dpyinfo->display_handle,
+                                                    s->first_glyph->u.terminal_id,
+                                                    s->row->ascent - s->first_glyph->ascent,
+                                                    s->first_glyph->pixel_width,
+                                                    glyph_height > 0 ? glyph_height : s->row->height);
+              }
+              break;
             default:
               break;
             }
@@ -6412,6 +6563,15 @@ neomacs_draw_glyph_string (struct glyph_string *s)
                                           s->row->height);
           break;
 
+        case TERMINAL_GLYPH:
+          /* Handle inline terminal glyphs */
+          neomacs_display_add_terminal_glyph (dpyinfo->display_handle,
+                                              s->first_glyph->u.terminal_id,
+                                              s->row->ascent - s->first_glyph->ascent,
+                                              s->first_glyph->pixel_width,
+                                              s->first_glyph->ascent + s->first_glyph->descent);
+          break;
+
         default:
           break;
         }
@@ -6509,6 +6669,10 @@ neomacs_draw_glyph_string (struct glyph_string *s)
       /* WebKit glyph rendering is handled by Rust - no Cairo fallback needed */
       break;
 
+    case TERMINAL_GLYPH:
+      /* Inline terminals are drawn by Rust - no Cairo fallback needed */
+      break;
+
     default:
       break;
     }
@@ -6851,6 +7015,17 @@ neomacs_get_or_load_image (struct neomacs_display_info *dpyinfo, struct image *i
       return 0;
     }
 
+  /* Honor :transform-smoothing like the other image backends: without
+     it, smooth when scaling down and show real pixels when scaling up.  */
+  if (CONSP (img->spec))
+    {
+      Lisp_Object smoothing = plist_member (XCDR (img->spec),
+                                            QCtransform_smoothing);
+      if (CONSP (smoothing))
+        neomacs_display_set_image_sampling (dpyinfo->display_handle, gpu_id,
+                                            !NILP (XCAR (XCDR (smoothing))));
+    }
+
   /* Add to cache (evict oldest entry if full) */
   if (neomacs_image_cache_count >= IMAGE_CACHE_SIZE)
     {
@@ -7363,6 +7538,20 @@ neomacs_expose_frame (struct frame *f)
   SET_FRAME_GARBAGED (f);
 }
 
+/* Called when Emacs frees realized FACE of frame F.  The face is sent
+   again if a face with its id is used later, so this only keeps the
+   render thread from holding on to stale faces.  */
+void
+neomacs_free_face (struct frame *f, struct face *face)
+{
+  if (!FRAME_NEOMACS_P (f))
+    return;
+
+  struct neomacs_display_info *dpyinfo = FRAME_NEOMACS_DISPLAY_INFO (f);
+  if (dpyinfo && dpyinfo->display_handle)
+    neomacs_display_remove_face (dpyinfo->display_handle, face->id);
+}
+
 /* Called when frame is fully up to date */
 static void
 neomacs_frame_up_to_date (struct frame *f)
@@ -7929,6 +8118,14 @@ DEFUN ("x-display-grayscale-p", Fx_display_grayscale_p, Sx_display_grayscale_p,
  * Video Playback API
  * ============================================================================ */
 
+/* Signal `neomacs-stale-handle' if RESULT says resource ID was freed.  */
+static void
+neomacs_check_handle (int result, Lisp_Object id)
+{
+  if (result == NEOMACS_STALE_HANDLE)
+    xsignal1 (Qneomacs_stale_handle, id);
+}
+
 DEFUN ("neomacs-video-load", Fneomacs_video_load, Sneomacs_video_load, 1, 1, 0,
        doc: /* Load a video from URI.
 Returns video ID on success, nil on failure.
@@ -7963,6 +8160,7 @@ Returns t on success, nil on failure.  */)
 
   int result = neomacs_display_video_play (dpyinfo->display_handle,
                                            (uint32_t) XFIXNUM (video_id));
+  neomacs_check_handle (result, video_id);
   return result == 0 ? Qt : Qnil;
 }
 
@@ -7979,6 +8177,7 @@ Returns t on success, nil on failure.  */)
 
   int result = neomacs_display_video_pause (dpyinfo->display_handle,
                                             (uint32_t) XFIXNUM (video_id));
+  neomacs_check_handle (result, video_id);
   return result == 0 ? Qt : Qnil;
 }
 
@@ -7995,6 +8194,7 @@ Returns t on success, nil on failure.  */)
 
   int result = neomacs_display_video_stop (dpyinfo->display_handle,
                                            (uint32_t) XFIXNUM (video_id));
+  neomacs_check_handle (result, video_id);
   return result == 0 ? Qt : Qnil;
 }
 
@@ -8068,6 +8268,235 @@ LOOP-COUNT can be:
   return result == 0 ? Qt : Qnil;
 }
 
+DEFUN ("neomacs-video-pip", Fneomacs_video_pip, Sneomacs_video_pip, 1, 3, 0,
+       doc: /* Show VIDEO-ID in a picture-in-picture player.
+The player is pinned to CORNER of the frame, one of the symbols
+`top-left', `top-right', `bottom-left' or `bottom-right' (the default),
+and stays there while buffers are switched.  WIDTH is its width in
+pixels, 320 by default.  The player can be dragged to another corner
+and resized by the grip on its top edge; hovering it shows play/pause,
+seek and close controls.  Closing it from its controls runs the
+`pip-closed' event of `neomacs-display-event-functions'.
+VIDEO-ID nil closes the player.  */)
+  (Lisp_Object video_id, Lisp_Object corner, Lisp_Object width)
+{
+  if (!NILP (video_id))
+    CHECK_FIXNUM (video_id);
+  if (!NILP (corner))
+    CHECK_SYMBOL (corner);
+  if (!NILP (width))
+    CHECK_NUMBER (width);
+
+  uint8_t corner_code = 3;
+  if (EQ (corner, intern ("top-left")))
+    corner_code = 0;
+  else if (EQ (corner, intern ("top-right")))
+    corner_code = 1;
+  else if (EQ (corner, intern ("bottom-left")))
+    corner_code = 2;
+  else if (!NILP (corner) && !EQ (corner, intern ("bottom-right")))
+    error ("Unknown corner: %s", SSDATA (SYMBOL_NAME (corner)));
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_video_pip (
+      dpyinfo->display_handle,
+      NILP (video_id) ? 0 : (uint32_t) XFIXNUM (video_id),
+      corner_code,
+      NILP (width) ? 320.0f : (float) XFLOATINT (width));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-set-background-video", Fneomacs_set_background_video,
+       Sneomacs_set_background_video, 1, 3, 0,
+       doc: /* Loop VIDEO-ID beneath the text of WINDOW.
+If WINDOW is nil, the video fills the whole frame instead.  VIDEO-ID
+nil removes the background video of WINDOW.  DIM, a number from 0.0 to
+1.0, darkens the video to keep the text readable; it defaults to 0.5.
+The video is muted, and paused while the frame does not have focus.  */)
+  (Lisp_Object video_id, Lisp_Object window, Lisp_Object dim)
+{
+  if (!NILP (video_id))
+    CHECK_FIXNUM (video_id);
+  if (!NILP (window))
+    CHECK_LIVE_WINDOW (window);
+  if (!NILP (dim))
+    CHECK_NUMBER (dim);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_set_background_video (
+      dpyinfo->display_handle,
+      NILP (window) ? 0 : (int64_t) (intptr_t) XWINDOW (window),
+      NILP (video_id) ? 0 : (uint32_t) XFIXNUM (video_id),
+      NILP (dim) ? 0.5f : (float) XFLOATINT (dim));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-media-waveform", Fneomacs_media_waveform,
+       Sneomacs_media_waveform, 3, 4, 0,
+       doc: /* Draw the waveform of the audio of media FILE as an image.
+The image is WIDTH by HEIGHT pixels, the sound drawn in COLOR (a color
+string, white by default) on a transparent background, for showing
+under a seek bar.  It is made in the background; the image id is
+returned at once, and the image shows once the whole file has been
+decoded.  Files without audio are reported as display errors.  */)
+  (Lisp_Object file, Lisp_Object width, Lisp_Object height, Lisp_Object color)
+{
+  CHECK_STRING (file);
+  CHECK_FIXNAT (width);
+  CHECK_FIXNAT (height);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  uint32_t pixel = 0xffffff;
+  if (!NILP (color))
+    {
+      CHECK_STRING (color);
+      Emacs_Color c;
+      if (!neomacs_defined_color (NULL, SSDATA (color), &c, false, false))
+        error ("Undefined color: %s", SSDATA (color));
+      pixel = ((c.red >> 8) << 16) | ((c.green >> 8) << 8) | (c.blue >> 8);
+    }
+
+  file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+  uint32_t id = neomacs_display_media_waveform (dpyinfo->display_handle,
+                                                SSDATA (file),
+                                                XFIXNAT (width),
+                                                XFIXNAT (height),
+                                                pixel);
+  return id ? make_fixnum (id) : Qnil;
+}
+
+DEFUN ("neomacs-media-thumbnails", Fneomacs_media_thumbnails,
+       Sneomacs_media_thumbnails, 2, 3, 0,
+       doc: /* Take COUNT thumbnails of the video of media FILE.
+The thumbnails are taken at the middle of COUNT equal parts of the
+video, for previews while seeking, and are WIDTH pixels wide (160 by
+default) with the height the shape of the video gives.  They are made
+in the background; return the list of their image ids at once, in
+order.  Like files loaded with `neomacs-image-load', each shows once it
+is ready.  */)
+  (Lisp_Object file, Lisp_Object count, Lisp_Object width)
+{
+  CHECK_STRING (file);
+  CHECK_FIXNAT (count);
+  if (!NILP (width))
+    CHECK_FIXNAT (width);
+  if (XFIXNAT (count) == 0 || XFIXNAT (count) > 256)
+    args_out_of_range (count, make_fixnum (256));
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int n = XFIXNAT (count);
+  uint32_t ids[256];
+  file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+  n = neomacs_display_media_thumbnails (dpyinfo->display_handle,
+                                        SSDATA (file), n,
+                                        NILP (width) ? 160 : XFIXNAT (width),
+                                        ids);
+  Lisp_Object result = Qnil;
+  for (int i = n - 1; i >= 0; i--)
+    result = Fcons (make_fixnum (ids[i]), result);
+  return result;
+}
+
+DEFUN ("neomacs-media-probe", Fneomacs_media_probe, Sneomacs_media_probe,
+       1, 1, 0,
+       doc: /* Find out whether media FILE can be played.
+FILE may also be a URI.  Return a plist:
+  :playable  non-nil if the file will play, perhaps only in part
+  :reason    why it won't play fully, e.g. the codecs GStreamer has no
+             plugin for, or nil
+The file is decoded up to its first frames, which can take up to five
+seconds.  Without video support, return nil.  */)
+  (Lisp_Object file)
+{
+  CHECK_STRING (file);
+  if (!strstr (SSDATA (file), "://"))
+    file = Fexpand_file_name (file, Qnil);
+  file = ENCODE_FILE (file);
+
+  char *reason = NULL;
+  int playable = neomacs_display_probe_media (SSDATA (file), &reason);
+  if (playable < 0)
+    return Qnil;
+  Lisp_Object why = Qnil;
+  if (reason)
+    {
+      why = build_string (reason);
+      neomacs_display_free_string (reason);
+    }
+  return list4 (intern (":playable"), playable ? Qt : Qnil,
+                intern (":reason"), why);
+}
+
+/* Lisp vector of the first N of VALUES, or nil if N is negative.  */
+static Lisp_Object
+neomacs_float_vector (const float *values, int n)
+{
+  if (n < 0)
+    return Qnil;
+  Lisp_Object v = make_nil_vector (n);
+  for (int i = 0; i < n; i++)
+    ASET (v, i, make_float (values[i]));
+  return v;
+}
+
+DEFUN ("neomacs-video-audio-levels", Fneomacs_video_audio_levels,
+       Sneomacs_video_audio_levels, 1, 1, 0,
+       doc: /* Return the latest audio levels of VIDEO-ID.
+The value is a plist of vectors of floats, in dB where 0.0 is full
+scale and -60.0 or below is silence:
+  :rms       average level of each channel over the last tenth of a second
+  :peak      peak level of each channel over the same time
+  :spectrum  magnitude of each of 32 frequency bands, lowest first
+Return nil if the video has not played any audio yet.  See
+`neomacs-video-audio-events' to be told when the levels change.  */)
+  (Lisp_Object video_id)
+{
+  CHECK_FIXNUM (video_id);
+  uint32_t id = (uint32_t) XFIXNUM (video_id);
+
+  float rms[8], peak[8], bands[64];
+  int channels = neomacs_display_audio_levels (id, rms, peak, 8);
+  int nbands = neomacs_display_audio_spectrum (id, bands, 64);
+  if (channels < 0 && nbands < 0)
+    return Qnil;
+  return list (intern (":rms"), neomacs_float_vector (rms, channels),
+               intern (":peak"), neomacs_float_vector (peak, channels),
+               intern (":spectrum"), neomacs_float_vector (bands, nbands));
+}
+
+DEFUN ("neomacs-video-audio-events", Fneomacs_video_audio_events,
+       Sneomacs_video_audio_events, 1, 2, 0,
+       doc: /* Report audio level changes of VIDEO-ID as events.
+If ENABLE is non-nil, the `audio-levels' event of
+`neomacs-display-event-functions' runs with the video id each time new
+levels are in, about ten times a second while the video plays; read
+them with `neomacs-video-audio-levels'.  If ENABLE is nil, stop.  */)
+  (Lisp_Object video_id, Lisp_Object enable)
+{
+  CHECK_FIXNUM (video_id);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_video_audio_events (dpyinfo->display_handle,
+                                                   (uint32_t) XFIXNUM (video_id),
+                                                   !NILP (enable));
+  return result == 0 ? Qt : Qnil;
+}
+
 DEFUN ("neomacs-video-update", Fneomacs_video_update, Sneomacs_video_update, 1, 1, 0,
        doc: /* Update video state for VIDEO-ID.
 Checks for end-of-stream and handles looping.
@@ -8125,6 +8554,7 @@ Returns (width . height) on success, nil on failure.  */)
   int result = neomacs_display_get_image_size (dpyinfo->display_handle,
                                                 (uint32_t) XFIXNUM (image_id),
                                                 &width, &height);
+  neomacs_check_handle (result, image_id);
 
   if (result != 0)
     return Qnil;
@@ -8145,6 +8575,117 @@ Returns t on success, nil on failure.  */)
 
   int result = neomacs_display_free_image (dpyinfo->display_handle,
                                            (uint32_t) XFIXNUM (image_id));
+  neomacs_check_handle (result, image_id);
+
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-image-set-interactive", Fneomacs_image_set_interactive,
+       Sneomacs_image_set_interactive, 2, 2, 0,
+       doc: /* Let the user zoom and pan image IMAGE-ID where it is shown.
+With INTERACTIVE non-nil, turning the mouse wheel over the image zooms
+it around the pointer, dragging it with the first button pans it, and a
+double click shows all of it again; these events then no longer reach
+Emacs.  The image keeps the size it is displayed at and shows a smaller
+part of itself when zoomed.  With INTERACTIVE nil, the image stops
+taking these events and is shown whole again.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object image_id, Lisp_Object interactive)
+{
+  CHECK_FIXNUM (image_id);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_set_image_interactive
+    (dpyinfo->display_handle, (uint32_t) XFIXNUM (image_id), !NILP (interactive));
+  neomacs_check_handle (result, image_id);
+
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-image-swap", Fneomacs_image_swap, Sneomacs_image_swap, 2, 4, 0,
+       doc: /* Give image IMAGE-ID the picture of image NEW-IMAGE-ID.
+Wherever IMAGE-ID is shown, its old picture gives way to the new one
+by EFFECT over DURATION milliseconds, 300 if nil.  EFFECT is one of
+the symbols `crossfade' (the default), `slide-left', `slide-right',
+`slide-up' and `slide-down'; the slides push the old picture out in
+that direction.  NEW-IMAGE-ID is consumed: it is freed and must not
+be used again.  If it is still loading, the swap happens once it has
+loaded, and not at all if it fails to.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object image_id, Lisp_Object new_image_id, Lisp_Object effect,
+   Lisp_Object duration)
+{
+  CHECK_FIXNUM (image_id);
+  CHECK_FIXNUM (new_image_id);
+  if (!NILP (effect))
+    CHECK_SYMBOL (effect);
+  if (!NILP (duration))
+    CHECK_FIXNAT (duration);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  const char *effect_name
+    = NILP (effect) ? "crossfade" : SSDATA (SYMBOL_NAME (effect));
+  int result = neomacs_display_swap_image
+    (dpyinfo->display_handle, (uint32_t) XFIXNUM (image_id),
+     (uint32_t) XFIXNUM (new_image_id), effect_name,
+     NILP (duration) ? 300 : (uint32_t) XFIXNAT (duration));
+  neomacs_check_handle (result, image_id);
+
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-set-chrome-image", Fneomacs_set_chrome_image,
+       Sneomacs_set_chrome_image, 2, 4, 0,
+       doc: /* Draw chrome ELEMENT with image IMAGE-ID as a nine-patch.
+ELEMENT is one of the symbols `tab-bar', `tooltip', `popup-menu' and
+`child-frame'.  The image is cut into nine parts by SLICE, a list
+\(TOP RIGHT BOTTOM LEFT) of border sizes in image pixels, or one size
+for all four; 8 if nil.  The corners keep their size, the edges
+stretch along their length and the center stretches both ways, so the
+image fits chrome of any size without distortion.  SCALE, a number,
+is the size of image pixels on screen, 1 if nil.  Tooltips and popup
+menus drawn with an image have no shadow, background or border of
+their own.  With IMAGE-ID nil, ELEMENT gets its own look again.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object element, Lisp_Object image_id, Lisp_Object slice,
+   Lisp_Object scale)
+{
+  CHECK_SYMBOL (element);
+  if (!NILP (image_id))
+    CHECK_FIXNUM (image_id);
+
+  int insets[4] = { 8, 8, 8, 8 };
+  if (FIXNUMP (slice))
+    insets[0] = insets[1] = insets[2] = insets[3] = XFIXNUM (slice);
+  else if (!NILP (slice))
+    {
+      Lisp_Object tail = slice;
+      for (int i = 0; i < 4; i++)
+        {
+          CHECK_CONS (tail);
+          CHECK_FIXNAT (XCAR (tail));
+          insets[i] = XFIXNAT (XCAR (tail));
+          tail = XCDR (tail);
+        }
+    }
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_set_chrome_image
+    (dpyinfo->display_handle, SSDATA (SYMBOL_NAME (element)),
+     NILP (image_id) ? 0 : (uint32_t) XFIXNUM (image_id),
+     insets[0], insets[1], insets[2], insets[3],
+     NILP (scale) ? 1.0 : extract_float (scale));
+  if (!NILP (image_id))
+    neomacs_check_handle (result, image_id);
 
   return result == 0 ? Qt : Qnil;
 }
@@ -8325,151 +8866,1306 @@ using gdk-pixbuf to decode the image data.  */)
 
 
 /* ============================================================================
- * WebKit API
+ * PDF Documents API
  * ============================================================================ */
 
-DEFUN ("neomacs-webkit-init", Fneomacs_webkit_init, Sneomacs_webkit_init, 0, 0, 0,
-       doc: /* Initialize WebKit subsystem.
-Must be called before creating WebKit views.
-Returns t on success, nil on failure.  */)
-  (void)
+DEFUN ("neomacs-pdf-open", Fneomacs_pdf_open, Sneomacs_pdf_open, 1, 2, 0,
+       doc: /* Open the PDF document FILE, with optional PASSWORD.
+Returns a document ID, or nil on failure.  The document loads
+asynchronously; poll `neomacs-pdf-page-count' until it is ready.  */)
+  (Lisp_Object file, Lisp_Object password)
 {
+  CHECK_STRING (file);
+  if (!NILP (password))
+    CHECK_STRING (password);
+
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  /* Pass NULL - Rust side will try to get EGL display */
-  int result = neomacs_display_webkit_init (dpyinfo->display_handle, NULL);
-  return result == 0 ? Qt : Qnil;
+  Lisp_Object encoded = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+  uint32_t doc_id
+    = neomacs_display_pdf_open (dpyinfo->display_handle, SSDATA (encoded),
+                                NILP (password) ? NULL : SSDATA (password));
+
+  if (doc_id == 0)
+    return Qnil;
+
+  return make_fixnum (doc_id);
 }
 
-DEFUN ("neomacs-webkit-create", Fneomacs_webkit_create, Sneomacs_webkit_create, 2, 2, 0,
-       doc: /* Create a new WebKit view with WIDTH and HEIGHT.
-Returns view ID on success, nil on failure.  */)
-  (Lisp_Object width, Lisp_Object height)
+DEFUN ("neomacs-pdf-page-count", Fneomacs_pdf_page_count, Sneomacs_pdf_page_count, 1, 1, 0,
+       doc: /* Return the number of pages in PDF document DOC-ID.
+Returns 0 while the document is still loading, nil if it failed to open.  */)
+  (Lisp_Object doc_id)
 {
-  CHECK_FIXNUM (width);
-  CHECK_FIXNUM (height);
+  CHECK_FIXNUM (doc_id);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  uint32_t view_id = neomacs_display_webkit_create (dpyinfo->display_handle,
-                                                    (int) XFIXNUM (width),
-                                                    (int) XFIXNUM (height));
-
-  if (view_id == 0)
+  int count = neomacs_display_pdf_page_count (dpyinfo->display_handle,
+                                              (uint32_t) XFIXNUM (doc_id));
+  neomacs_check_handle (count, doc_id);
+  if (count < 0)
     return Qnil;
 
-  return make_fixnum (view_id);
+  return make_fixnum (count);
 }
 
-DEFUN ("neomacs-webkit-destroy", Fneomacs_webkit_destroy, Sneomacs_webkit_destroy, 1, 1, 0,
-       doc: /* Destroy WebKit view with VIEW-ID.
-Returns t on success, nil on failure.  */)
-  (Lisp_Object view_id)
+DEFUN ("neomacs-pdf-page-size", Fneomacs_pdf_page_size, Sneomacs_pdf_page_size, 2, 2, 0,
+       doc: /* Return the size of PAGE (1-based) in PDF document DOC-ID.
+Returns (WIDTH . HEIGHT) in points (1/72 inch), or nil.  */)
+  (Lisp_Object doc_id, Lisp_Object page)
 {
-  CHECK_FIXNUM (view_id);
+  CHECK_FIXNUM (doc_id);
+  CHECK_FIXNUM (page);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  int result = neomacs_display_webkit_destroy (dpyinfo->display_handle,
-                                               (uint32_t) XFIXNUM (view_id));
-  return result == 0 ? Qt : Qnil;
+  float width = 0, height = 0;
+  if (neomacs_display_pdf_page_size (dpyinfo->display_handle,
+                                     (uint32_t) XFIXNUM (doc_id),
+                                     (int) XFIXNUM (page) - 1,
+                                     &width, &height) != 0)
+    return Qnil;
+
+  return Fcons (make_float (width), make_float (height));
 }
 
-DEFUN ("neomacs-webkit-load-uri", Fneomacs_webkit_load_uri, Sneomacs_webkit_load_uri, 2, 2, 0,
-       doc: /* Load URI in WebKit view VIEW-ID.
-Returns t on success, nil on failure.  */)
-  (Lisp_Object view_id, Lisp_Object uri)
+DEFUN ("neomacs-pdf-render-page", Fneomacs_pdf_render_page, Sneomacs_pdf_render_page, 3, 3, 0,
+       doc: /* Render PAGE (1-based) of PDF document DOC-ID at ZOOM.
+ZOOM is pixels per point; 1.0 renders at 72 dpi.  Returns an image ID
+usable as `:neomacs-id' in an image spec, or nil on failure.  The page
+rasterizes asynchronously; its size is known immediately.  */)
+  (Lisp_Object doc_id, Lisp_Object page, Lisp_Object zoom)
 {
-  CHECK_FIXNUM (view_id);
-  CHECK_STRING (uri);
+  CHECK_FIXNUM (doc_id);
+  CHECK_FIXNUM (page);
+  CHECK_NUMBER (zoom);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  const char *uri_str = SSDATA (uri);
-  int result = neomacs_display_webkit_load_uri (dpyinfo->display_handle,
-                                                (uint32_t) XFIXNUM (view_id),
-                                                uri_str);
-  return result == 0 ? Qt : Qnil;
+  uint32_t image_id
+    = neomacs_display_pdf_render_page (dpyinfo->display_handle,
+                                       (uint32_t) XFIXNUM (doc_id),
+                                       (int) XFIXNUM (page) - 1,
+                                       (float) XFLOATINT (zoom));
+  if (image_id == 0)
+    return Qnil;
+
+  return make_fixnum (image_id);
 }
 
-DEFUN ("neomacs-webkit-go-back", Fneomacs_webkit_go_back, Sneomacs_webkit_go_back, 1, 1, 0,
-       doc: /* Go back in WebKit view VIEW-ID.
-Returns t on success, nil on failure.  */)
-  (Lisp_Object view_id)
+DEFUN ("neomacs-pdf-extract-page", Fneomacs_pdf_extract_page, Sneomacs_pdf_extract_page, 2, 2, 0,
+       doc: /* Start extracting text and links from PAGE (1-based) of DOC-ID.
+Results become available through `neomacs-pdf-page-text' and
+`neomacs-pdf-page-links'.  */)
+  (Lisp_Object doc_id, Lisp_Object page)
 {
-  CHECK_FIXNUM (view_id);
+  CHECK_FIXNUM (doc_id);
+  CHECK_FIXNUM (page);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  int result = neomacs_display_webkit_go_back (dpyinfo->display_handle,
-                                               (uint32_t) XFIXNUM (view_id));
-  return result == 0 ? Qt : Qnil;
+  neomacs_display_pdf_extract_page (dpyinfo->display_handle,
+                                    (uint32_t) XFIXNUM (doc_id),
+                                    (int) XFIXNUM (page) - 1);
+  return Qt;
 }
 
-DEFUN ("neomacs-webkit-go-forward", Fneomacs_webkit_go_forward, Sneomacs_webkit_go_forward, 1, 1, 0,
-       doc: /* Go forward in WebKit view VIEW-ID.
-Returns t on success, nil on failure.  */)
-  (Lisp_Object view_id)
+DEFUN ("neomacs-pdf-page-text", Fneomacs_pdf_page_text, Sneomacs_pdf_page_text, 2, 2, 0,
+       doc: /* Return the text of PAGE (1-based) in PDF document DOC-ID.
+Returns nil until `neomacs-pdf-extract-page' has finished for PAGE.  */)
+  (Lisp_Object doc_id, Lisp_Object page)
 {
-  CHECK_FIXNUM (view_id);
+  CHECK_FIXNUM (doc_id);
+  CHECK_FIXNUM (page);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  int result = neomacs_display_webkit_go_forward (dpyinfo->display_handle,
-                                                  (uint32_t) XFIXNUM (view_id));
-  return result == 0 ? Qt : Qnil;
+  char *text = neomacs_display_pdf_page_text (dpyinfo->display_handle,
+                                              (uint32_t) XFIXNUM (doc_id),
+                                              (int) XFIXNUM (page) - 1);
+  if (!text)
+    return Qnil;
+
+  Lisp_Object result = build_string (text);
+  neomacs_display_free_string (text);
+  return result;
 }
 
-DEFUN ("neomacs-webkit-reload", Fneomacs_webkit_reload, Sneomacs_webkit_reload, 1, 1, 0,
-       doc: /* Reload WebKit view VIEW-ID.
-Returns t on success, nil on failure.  */)
-  (Lisp_Object view_id)
+DEFUN ("neomacs-pdf-page-links", Fneomacs_pdf_page_links, Sneomacs_pdf_page_links, 2, 2, 0,
+       doc: /* Return the links on PAGE (1-based) in PDF document DOC-ID.
+Each element is (X Y WIDTH HEIGHT URI DEST-PAGE), with the rectangle in
+points from the top-left corner of the page.  URI is nil for internal
+links; DEST-PAGE is the 1-based target page or nil.  Returns t when the
+page has not been extracted yet.  */)
+  (Lisp_Object doc_id, Lisp_Object page)
 {
-  CHECK_FIXNUM (view_id);
+  CHECK_FIXNUM (doc_id);
+  CHECK_FIXNUM (page);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  int result = neomacs_display_webkit_reload (dpyinfo->display_handle,
-                                              (uint32_t) XFIXNUM (view_id));
-  return result == 0 ? Qt : Qnil;
+  uint32_t id = (uint32_t) XFIXNUM (doc_id);
+  int page_index = (int) XFIXNUM (page) - 1;
+  int count = neomacs_display_pdf_link_count (dpyinfo->display_handle,
+                                              id, page_index);
+  if (count < 0)
+    return Qt;
+
+  Lisp_Object result = Qnil;
+  for (int i = count - 1; i >= 0; i--)
+    {
+      float x, y, w, h;
+      int dest = -1;
+      char *uri = neomacs_display_pdf_link_info (dpyinfo->display_handle,
+                                                 id, page_index, i,
+                                                 &x, &y, &w, &h, &dest);
+      if (!uri)
+        continue;
+      Lisp_Object uri_obj = *uri ? build_string (uri) : Qnil;
+      neomacs_display_free_string (uri);
+      result = Fcons (list (make_float (x), make_float (y),
+                            make_float (w), make_float (h), uri_obj,
+                            dest >= 0 ? make_fixnum (dest + 1) : Qnil),
+                      result);
+    }
+  return result;
 }
 
-DEFUN ("neomacs-webkit-resize", Fneomacs_webkit_resize, Sneomacs_webkit_resize, 3, 3, 0,
-       doc: /* Resize WebKit view VIEW-ID to WIDTH x HEIGHT pixels.
-Returns t on success, nil on failure.  */)
-  (Lisp_Object view_id, Lisp_Object width, Lisp_Object height)
+DEFUN ("neomacs-pdf-close", Fneomacs_pdf_close, Sneomacs_pdf_close, 1, 1, 0,
+       doc: /* Close PDF document DOC-ID.
+Page images from `neomacs-pdf-render-page' must be freed separately
+with `neomacs-image-free'.  */)
+  (Lisp_Object doc_id)
 {
-  CHECK_FIXNUM (view_id);
-  CHECK_FIXNUM (width);
-  CHECK_FIXNUM (height);
+  CHECK_FIXNUM (doc_id);
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  int result = neomacs_display_webkit_resize (dpyinfo->display_handle,
-                                              (uint32_t) XFIXNUM (view_id),
-                                              (int) XFIXNUM (width),
-                                              (int) XFIXNUM (height));
-  return result == 0 ? Qt : Qnil;
+  neomacs_display_pdf_close (dpyinfo->display_handle,
+                             (uint32_t) XFIXNUM (doc_id));
+  return Qt;
 }
 
-DEFUN ("neomacs-webkit-execute-js", Fneomacs_webkit_execute_js, Sneomacs_webkit_execute_js, 2, 2, 0,
-       doc: /* Execute JavaScript SCRIPT in WebKit view VIEW-ID.
+DEFUN ("neomacs-math-image", Fneomacs_math_image, Sneomacs_math_image, 1, 4, 0,
+       doc: /* Return an image spec showing TeX math snippet TEX.
+TEX is the math body without delimiters, e.g. "\\frac{a}{b}".
+Non-nil DISPLAY typesets in display style.  SIZE is the font size in
+pixels, defaulting to the frame font.  COLOR is a color string,
+defaulting to the frame foreground.  The image carries an `:ascent' so
+its baseline lines up with the surrounding text.  Identical snippets
+share one image.  Signals an error if TEX does not typeset.  */)
+  (Lisp_Object tex, Lisp_Object display, Lisp_Object size, Lisp_Object color)
+{
+  CHECK_STRING (tex);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  struct frame *f = SELECTED_FRAME ();
+  float px = FRAME_FONT (f) ? (float) FRAME_FONT (f)->pixel_size : 14.0f;
+  if (!NILP (size))
+    {
+      CHECK_NUMBER (size);
+      px = (float) XFLOATINT (size);
+    }
+
+  unsigned long fg = FRAME_FOREGROUND_PIXEL (f);
+  uint32_t rgb = ((RED_FROM_ULONG (fg) << 16)
+                  | (GREEN_FROM_ULONG (fg) << 8)
+                  | BLUE_FROM_ULONG (fg));
+  if (!NILP (color))
+    {
+      CHECK_STRING (color);
+      Emacs_Color c;
+      if (!neomacs_defined_color (NULL, SSDATA (color), &c, false, false))
+        error ("Undefined color: %s", SSDATA (color));
+      rgb = ((uint32_t) (c.red >> 8) << 16)
+            | ((uint32_t) (c.green >> 8) << 8)
+            | (uint32_t) (c.blue >> 8);
+    }
+
+  int width = 0, height = 0, ascent = 0;
+  char *message = NULL;
+  uint32_t image_id
+    = neomacs_display_math_render (dpyinfo->display_handle,
+                                   SSDATA (ENCODE_UTF_8 (tex)),
+                                   !NILP (display), px, rgb,
+                                   &width, &height, &ascent, &message);
+  if (image_id == 0)
+    {
+      if (message)
+        {
+          Lisp_Object msg = build_string (message);
+          neomacs_display_free_string (message);
+          error ("Cannot typeset math: %s", SSDATA (msg));
+        }
+      return Qnil;
+    }
+
+  return list (Qimage,
+               QCtype, Qneomacs,
+               intern (":neomacs-id"), make_fixnum (image_id),
+               QCwidth, make_fixnum (width),
+               QCheight, make_fixnum (height),
+               QCascent, make_fixnum (ascent));
+}
+
+DEFUN ("neomacs-math-clear-cache", Fneomacs_math_clear_cache,
+       Sneomacs_math_clear_cache, 0, 0, 0,
+       doc: /* Free all images made by `neomacs-math-image'.
+Specs returned earlier stop displaying until they are requested again.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  neomacs_display_math_clear (dpyinfo->display_handle);
+  return Qt;
+}
+
+
+/* ============================================================================
+ * Icon Sets
+ * ============================================================================ */
+
+DEFUN ("neomacs-icon-set-load", Fneomacs_icon_set_load,
+       Sneomacs_icon_set_load, 2, 3, 0,
+       doc: /* Load icon set NAME from PATH.
+NAME is a string without colons.  PATH is either a directory holding
+one SVG file per icon, named ICON.svg, or an icon font.  The icons of a
+font are named by CODEPOINTS, a file of lines "ICON HEX" as shipped
+with Material icon fonts; if CODEPOINTS is nil, the glyph names of the
+font are used.  Loading a set again under the same NAME replaces it.
+Returns the number of icons in the set; signals an error if it cannot
+be loaded.  */)
+  (Lisp_Object name, Lisp_Object path, Lisp_Object codepoints)
+{
+  CHECK_STRING (name);
+  CHECK_STRING (path);
+  if (!NILP (codepoints))
+    CHECK_STRING (codepoints);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  path = ENCODE_FILE (Fexpand_file_name (path, Qnil));
+  if (!NILP (codepoints))
+    codepoints = ENCODE_FILE (Fexpand_file_name (codepoints, Qnil));
+
+  char *message = NULL;
+  int count = neomacs_display_icon_set_load
+    (dpyinfo->display_handle, SSDATA (ENCODE_UTF_8 (name)), SSDATA (path),
+     NILP (codepoints) ? NULL : SSDATA (codepoints), &message);
+  if (count < 0)
+    {
+      if (message)
+        {
+          Lisp_Object msg = build_string (message);
+          neomacs_display_free_string (message);
+          error ("Cannot load icon set: %s", SSDATA (msg));
+        }
+      return Qnil;
+    }
+  return make_fixnum (count);
+}
+
+DEFUN ("neomacs-icon-image", Fneomacs_icon_image, Sneomacs_icon_image, 1, 3, 0,
+       doc: /* Return an image spec showing icon NAME.
+NAME is "SET:ICON", or an ICON looked for in each set loaded with
+`neomacs-icon-set-load', latest first.  SIZE is the height in pixels,
+defaulting to the frame font.  COLOR is a color string, defaulting to
+the frame foreground, or t to keep the colors of an SVG icon.  The
+icon is rasterized at the display's scale so it stays crisp, and is
+centered on the text around it.  Identical requests share one image.
+Returns nil if no loaded set has the icon.  */)
+  (Lisp_Object name, Lisp_Object size, Lisp_Object color)
+{
+  CHECK_STRING (name);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  struct frame *f = SELECTED_FRAME ();
+  float px = FRAME_FONT (f) ? (float) FRAME_FONT (f)->pixel_size : 14.0f;
+  if (!NILP (size))
+    {
+      CHECK_NUMBER (size);
+      px = (float) XFLOATINT (size);
+    }
+
+  unsigned long fg = FRAME_FOREGROUND_PIXEL (f);
+  uint32_t rgb = ((RED_FROM_ULONG (fg) << 16)
+                  | (GREEN_FROM_ULONG (fg) << 8)
+                  | BLUE_FROM_ULONG (fg));
+  if (STRINGP (color))
+    {
+      Emacs_Color c;
+      if (!neomacs_defined_color (NULL, SSDATA (color), &c, false, false))
+        error ("Undefined color: %s", SSDATA (color));
+      rgb = ((uint32_t) (c.red >> 8) << 16)
+            | ((uint32_t) (c.green >> 8) << 8)
+            | (uint32_t) (c.blue >> 8);
+    }
+  else if (!NILP (color) && !EQ (color, Qt))
+    wrong_type_argument (Qstringp, color);
+
+  int width = 0, height = 0;
+  uint32_t image_id
+    = neomacs_display_icon (dpyinfo->display_handle,
+                            SSDATA (ENCODE_UTF_8 (name)), px, rgb,
+                            EQ (color, Qt), &width, &height);
+  if (image_id == 0)
+    return Qnil;
+
+  return list (Qimage,
+               QCtype, Qneomacs,
+               intern (":neomacs-id"), make_fixnum (image_id),
+               QCwidth, make_fixnum (width),
+               QCheight, make_fixnum (height),
+               QCascent, Qcenter);
+}
+
+DEFUN ("neomacs-icon-clear-cache", Fneomacs_icon_clear_cache,
+       Sneomacs_icon_clear_cache, 0, 0, 0,
+       doc: /* Free all images made by `neomacs-icon-image'.
+Loaded icon sets stay loaded.  Specs returned earlier stop displaying
+until they are requested again.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  neomacs_display_icon_clear (dpyinfo->display_handle);
+  return Qt;
+}
+
+
+/* ============================================================================
+ * QR Codes and Barcodes
+ * ============================================================================ */
+
+/* The 0xRRGGBB value of color string COLOR, or DFLT if COLOR is nil.  */
+static uint32_t
+neomacs_code_color (Lisp_Object color, uint32_t dflt)
+{
+  if (NILP (color))
+    return dflt;
+  CHECK_STRING (color);
+  Emacs_Color c;
+  if (!neomacs_defined_color (NULL, SSDATA (color), &c, false, false))
+    error ("Undefined color: %s", SSDATA (color));
+  return ((uint32_t) (c.red >> 8) << 16)
+         | ((uint32_t) (c.green >> 8) << 8)
+         | (uint32_t) (c.blue >> 8);
+}
+
+DEFUN ("neomacs-html-link-at", Fneomacs_html_link_at,
+       Sneomacs_html_link_at, 2, 2, 0,
+       doc: /* Return the target of the HTML link drawn at X, Y, or nil.
+X and Y are frame pixel coordinates.  Links are those of windows whose
+`neomacs-html' window parameter is a string of HTML, which the display
+engine draws in place of the window's buffer text.  */)
+  (Lisp_Object x, Lisp_Object y)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  CHECK_FIXNUM (x);
+  CHECK_FIXNUM (y);
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+#ifdef NEOMACS_HTML_RENDERER
+  char *href = neomacs_display_html_link_at (dpyinfo->display_handle,
+                                             XFIXNUM (x), XFIXNUM (y));
+  if (href)
+    {
+      Lisp_Object result = build_string (href);
+      neomacs_display_free_string (href);
+      return result;
+    }
+#endif
+  return Qnil;
+}
+
+DEFUN ("neomacs-barcode-image", Fneomacs_barcode_image,
+       Sneomacs_barcode_image, 1, 5, 0,
+       doc: /* Return an image spec showing TEXT as a QR code or barcode.
+KIND is one of the symbols `qr' (the default), `qr-low', `qr-quartile'
+and `qr-high', QR codes recovering from about 15, 7, 25 and 30% damage,
+or `code128', a Code 128 barcode of printable ASCII text.  MODULE-SIZE
+is the width in pixels of the narrowest bar or of a QR square, 4 by
+default.  FOREGROUND and BACKGROUND are color strings, black and white
+by default; scanners read dark codes on a light background best.  A
+light margin is included around the code.  Signals an error if TEXT
+cannot be encoded.  */)
+  (Lisp_Object text, Lisp_Object kind, Lisp_Object module_size,
+   Lisp_Object foreground, Lisp_Object background)
+{
+  CHECK_STRING (text);
+  if (!NILP (kind))
+    CHECK_SYMBOL (kind);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  float module = 4.0f;
+  if (!NILP (module_size))
+    {
+      CHECK_NUMBER (module_size);
+      module = (float) XFLOATINT (module_size);
+    }
+  uint32_t dark = neomacs_code_color (foreground, 0x000000);
+  uint32_t light = neomacs_code_color (background, 0xFFFFFF);
+
+  int width = 0, height = 0;
+  char *message = NULL;
+  uint32_t image_id = neomacs_display_barcode_image
+    (dpyinfo->display_handle,
+     NILP (kind) ? "qr" : SSDATA (SYMBOL_NAME (kind)),
+     SSDATA (ENCODE_UTF_8 (text)), module, module * 15.0f,
+     dark, light, &width, &height, &message);
+  if (image_id == 0)
+    {
+      if (message)
+        {
+          Lisp_Object msg = build_string (message);
+          neomacs_display_free_string (message);
+          error ("Cannot make code: %s", SSDATA (msg));
+        }
+      return Qnil;
+    }
+
+  return list (Qimage,
+               QCtype, Qneomacs,
+               intern (":neomacs-id"), make_fixnum (image_id),
+               QCwidth, make_fixnum (width),
+               QCheight, make_fixnum (height));
+}
+
+
+/* ============================================================================
+ * Charts
+ * ============================================================================ */
+
+/* Copy the numbers of VALUES, a list or vector, into a new array of
+   floats freed with xfree, storing their count in COUNT.  */
+static float *
+neomacs_chart_values (Lisp_Object values, int *count)
+{
+  if (!VECTORP (values))
+    values = Fvconcat (1, &values);
+  ptrdiff_t n = min (ASIZE (values), INT_MAX);
+  float *floats = xnmalloc (max (n, 1), sizeof *floats);
+  for (ptrdiff_t i = 0; i < n; i++)
+    {
+      Lisp_Object v = AREF (values, i);
+      CHECK_NUMBER (v);
+      floats[i] = (float) XFLOATINT (v);
+    }
+  *count = n;
+  return floats;
+}
+
+DEFUN ("neomacs-chart", Fneomacs_chart, Sneomacs_chart, 1, 2, 0,
+       doc: /* Return an image spec showing a chart of VALUES.
+VALUES is a list or vector of numbers.  The chart is drawn by the GPU
+at the size of its glyph, so it stays sharp; a sparkline is a chart one
+line high.  PROPS is a plist of:
+
+  :type        `line' (the default), `area' or `bar'
+  :width       width in pixels, 10 columns of the frame font by default
+  :height      height in pixels, the frame's line height by default
+  :color       color of the line or bars, the frame foreground by default
+  :fill        color below the line of an `area' chart
+  :background  color behind the chart, none by default
+  :line-width  width of the line in pixels, 1.5 by default
+  :min, :max   values at the bottom and top; fitted to VALUES by default
+
+Bars rise from zero.  Use `neomacs-chart-update' to change the values
+shown, and `neomacs-image-free' on the `:neomacs-id' of the spec to
+free the chart.  Returns nil on failure.  */)
+  (Lisp_Object values, Lisp_Object props)
+{
+  CHECK_LIST (props);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  struct frame *f = SELECTED_FRAME ();
+  Lisp_Object type = Fplist_get (props, QCtype, Qnil);
+  int kind = 0;
+  if (EQ (type, Qarea))
+    kind = 1;
+  else if (EQ (type, Qbar))
+    kind = 2;
+  else if (!NILP (type) && !EQ (type, Qline))
+    error ("Unknown chart type: %s", SDATA (Fprin1_to_string (type, Qnil, Qnil)));
+
+  Lisp_Object width = Fplist_get (props, QCwidth, Qnil);
+  Lisp_Object height = Fplist_get (props, QCheight, Qnil);
+  if (!NILP (width))
+    CHECK_FIXNAT (width);
+  if (!NILP (height))
+    CHECK_FIXNAT (height);
+  int w = NILP (width) ? 10 * FRAME_COLUMN_WIDTH (f) : XFIXNAT (width);
+  int h = NILP (height) ? FRAME_LINE_HEIGHT (f) : XFIXNAT (height);
+
+  unsigned long fg = FRAME_FOREGROUND_PIXEL (f);
+  uint32_t fg_rgb = ((RED_FROM_ULONG (fg) << 16)
+                     | (GREEN_FROM_ULONG (fg) << 8)
+                     | BLUE_FROM_ULONG (fg));
+  uint32_t color
+    = 0xFF000000 | neomacs_code_color (Fplist_get (props, QCcolor, Qnil), fg_rgb);
+  Lisp_Object fill = Fplist_get (props, QCfill, Qnil);
+  Lisp_Object background = Fplist_get (props, QCbackground, Qnil);
+  Lisp_Object line_width = Fplist_get (props, QCline_width, Qnil);
+  Lisp_Object lo = Fplist_get (props, QCmin, Qnil);
+  Lisp_Object hi = Fplist_get (props, QCmax, Qnil);
+  if (!NILP (line_width))
+    CHECK_NUMBER (line_width);
+  if (!NILP (lo))
+    CHECK_NUMBER (lo);
+  if (!NILP (hi))
+    CHECK_NUMBER (hi);
+
+  int count;
+  float *floats = neomacs_chart_values (values, &count);
+  uint32_t image_id = neomacs_display_chart_create
+    (dpyinfo->display_handle, kind, floats, count, w, h, color,
+     NILP (fill) ? 0 : 0xFF000000 | neomacs_code_color (fill, 0),
+     NILP (background) ? 0 : 0xFF000000 | neomacs_code_color (background, 0),
+     NILP (line_width) ? 0.0f : (float) XFLOATINT (line_width),
+     NILP (lo) ? NAN : (float) XFLOATINT (lo),
+     NILP (hi) ? NAN : (float) XFLOATINT (hi));
+  xfree (floats);
+  if (image_id == 0)
+    return Qnil;
+
+  return list (Qimage,
+               QCtype, Qneomacs,
+               intern (":neomacs-id"), make_fixnum (image_id),
+               QCwidth, make_fixnum (w),
+               QCheight, make_fixnum (h),
+               QCascent, Qcenter);
+}
+
+DEFUN ("neomacs-chart-update", Fneomacs_chart_update,
+       Sneomacs_chart_update, 2, 3, 0,
+       doc: /* Show VALUES in the chart with image id IMAGE-ID.
+IMAGE-ID is the `:neomacs-id' of a spec from `neomacs-chart'.  The
+chart moves from the values it shows to VALUES over DURATION
+milliseconds, 250 if nil; values past the old ones grow from zero.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object image_id, Lisp_Object values, Lisp_Object duration)
+{
+  CHECK_FIXNUM (image_id);
+  if (!NILP (duration))
+    CHECK_FIXNAT (duration);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int count;
+  float *floats = neomacs_chart_values (values, &count);
+  int result = neomacs_display_chart_update
+    (dpyinfo->display_handle, (uint32_t) XFIXNUM (image_id), floats, count,
+     NILP (duration) ? 250 : min (XFIXNAT (duration), UINT32_MAX));
+  xfree (floats);
+  neomacs_check_handle (result, image_id);
+
+  return result == 0 ? Qt : Qnil;
+}
+
+
+/* ============================================================================
+ * Canvases
+ * ============================================================================ */
+
+static uint32_t neomacs_theme_pixel (Lisp_Object color);
+
+DEFUN ("neomacs-canvas-create", Fneomacs_canvas_create,
+       Sneomacs_canvas_create, 2, 2, 0,
+       doc: /* Return an image spec for an empty canvas WIDTH x HEIGHT pixels.
+Draw on it with `neomacs-canvas-draw' using the `:neomacs-id' of the
+spec.  The canvas can be shown in the text like any image, or floating
+with `neomacs-image-floating'; free it with `neomacs-image-free'.
+Returns nil on failure.  */)
+  (Lisp_Object width, Lisp_Object height)
+{
+  CHECK_FIXNAT (width);
+  CHECK_FIXNAT (height);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  uint32_t image_id = neomacs_display_canvas_create
+    (dpyinfo->display_handle, min (XFIXNAT (width), INT_MAX),
+     min (XFIXNAT (height), INT_MAX));
+  if (image_id == 0)
+    return Qnil;
+
+  return list (Qimage,
+               QCtype, Qneomacs,
+               intern (":neomacs-id"), make_fixnum (image_id),
+               QCwidth, width,
+               QCheight, height);
+}
+
+DEFUN ("neomacs-canvas-draw", Fneomacs_canvas_draw,
+       Sneomacs_canvas_draw, 2, 3, 0,
+       doc: /* Draw COMMANDS on the canvas with image id IMAGE-ID.
+IMAGE-ID is the `:neomacs-id' of a spec from `neomacs-canvas-create'.
+COMMANDS is a list of commands, each a list of a symbol and its
+arguments.  Coordinates are in pixels from the top left corner:
+
+  (clear COLOR)                   fill the whole canvas
+  (begin-path)                    start a new path
+  (move-to X Y)  (line-to X Y)    (close-path)
+  (quad-to CX CY X Y)             quadratic curve
+  (cubic-to C1X C1Y C2X C2Y X Y)  cubic curve
+  (rect X Y WIDTH HEIGHT)         add a rectangle to the path
+  (circle X Y RADIUS)             add a circle to the path
+  (fill COLOR)                    fill the path
+  (stroke COLOR &optional WIDTH)  stroke the path, 1 pixel wide by default
+  (text X Y SIZE COLOR STRING)    STRING with its baseline at X, Y
+  (image X Y WIDTH HEIGHT FILE)   the image in FILE, stretched
+  (save)  (restore)               remember and restore the transform
+  (translate X Y)  (scale SX &optional SY)  (rotate DEGREES)
+
+Colors are names or "#rrggbb" or "#rrggbbaa" strings.  The path stays
+after it is filled or stroked, until `begin-path', and is drawn with the
+transform in effect then.  Commands add to those drawn before, unless
+REPLACE is non-nil: then the canvas starts over with COMMANDS.  The
+canvas is redrawn at the display's scale, so it stays sharp.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object image_id, Lisp_Object commands, Lisp_Object replace)
+{
+  CHECK_FIXNUM (image_id);
+  CHECK_LIST (commands);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+  uint32_t id = (uint32_t) XFIXNUM (image_id);
+
+  int result = 0;
+  if (!NILP (replace))
+    {
+      result = neomacs_display_canvas_op (dpyinfo->display_handle, id, NULL,
+                                          NULL, 0, 0, NULL, NULL);
+      neomacs_check_handle (result, image_id);
+    }
+
+  for (Lisp_Object tail = commands; result == 0 && CONSP (tail);
+       tail = XCDR (tail))
+    {
+      Lisp_Object command = XCAR (tail);
+      CHECK_CONS (command);
+      CHECK_SYMBOL (XCAR (command));
+      bool is_image = !strcmp (SSDATA (SYMBOL_NAME (XCAR (command))), "image");
+
+      /* Numbers are arguments; the first string is the color, or the
+         file of an image, and the next the text.  */
+      float args[8];
+      int nargs = 0;
+      uint32_t color = 0;
+      bool have_color = false;
+      Lisp_Object text = Qnil;
+      for (Lisp_Object arg = XCDR (command); CONSP (arg); arg = XCDR (arg))
+        {
+          Lisp_Object v = XCAR (arg);
+          if (NUMBERP (v))
+            {
+              if (nargs < (int) ARRAYELTS (args))
+                args[nargs++] = (float) XFLOATINT (v);
+            }
+          else if (is_image)
+            text = ENCODE_FILE (Fexpand_file_name (v, Qnil));
+          else if (!have_color)
+            {
+              color = neomacs_theme_pixel (v);
+              have_color = true;
+            }
+          else
+            {
+              CHECK_STRING (v);
+              text = ENCODE_UTF_8 (v);
+            }
+        }
+
+      char *message = NULL;
+      result = neomacs_display_canvas_op
+        (dpyinfo->display_handle, id, SSDATA (SYMBOL_NAME (XCAR (command))),
+         args, nargs, color, NILP (text) ? NULL : SSDATA (text), &message);
+      neomacs_check_handle (result, image_id);
+      if (result != 0 && message)
+        {
+          Lisp_Object msg = build_string (message);
+          neomacs_display_free_string (message);
+          error ("Cannot draw on canvas: %s", SSDATA (msg));
+        }
+    }
+
+  if (result == 0)
+    result = neomacs_display_canvas_flush (dpyinfo->display_handle, id);
+  return result == 0 ? Qt : Qnil;
+}
+
+
+/* ============================================================================
+ * Shader Effects
+ * ============================================================================ */
+
+/* Name of a shader effect given as a symbol or string.  */
+static const char *
+neomacs_shader_name (Lisp_Object name)
+{
+  if (SYMBOLP (name))
+    name = SYMBOL_NAME (name);
+  CHECK_STRING (name);
+  return SSDATA (name);
+}
+
+/* Decode TARGET of a shader effect, a window, the image id of a
+   floating image, or a list (X Y WIDTH HEIGHT), into the kind, ID and
+   RECT of neomacs_display_shader_effect_apply.  */
+static int
+neomacs_shader_target (Lisp_Object target, int64_t *id, float rect[4])
+{
+  *id = 0;
+  rect[0] = rect[1] = rect[2] = rect[3] = 0;
+  if (WINDOWP (target))
+    {
+      CHECK_LIVE_WINDOW (target);
+      *id = (int64_t) (intptr_t) XWINDOW (target);
+      return 1;
+    }
+  if (FIXNATP (target))
+    {
+      *id = XFIXNAT (target);
+      return 2;
+    }
+  for (int i = 0; i < 4; i++)
+    {
+      CHECK_CONS (target);
+      CHECK_NUMBER (XCAR (target));
+      rect[i] = (float) XFLOATINT (XCAR (target));
+      target = XCDR (target);
+    }
+  return 0;
+}
+
+DEFUN ("neomacs-shader-effect-register", Fneomacs_shader_effect_register,
+       Sneomacs_shader_effect_register, 2, 2, 0,
+       doc: /* Register the shader effect NAME with the WGSL in SOURCE.
+NAME is a symbol or string.  An effect of that name is replaced; the
+built-in effects `vignette', `crt' and `glow' can be replaced too.
+SOURCE defines the function
+
+  fn shade(color: vec4<f32>, uv: vec2<f32>, pos: vec2<f32>) -> vec4<f32>
+
+returning the color to show where the frame has COLOR.  UV runs from 0
+to 1 across the target of the effect and POS is in pixels.  The shader
+can call `frame(uv)' to read the frame elsewhere in the target, and
+read `effect.time' in seconds, `effect.region' (x, y, width, height)
+and the four `effect.params' given to `neomacs-shader-effect-apply'.
+
+Signal an error with the compiler's message if SOURCE does not
+compile.  Returns t on success, nil without a display.  */)
+  (Lisp_Object name, Lisp_Object source)
+{
+  CHECK_STRING (source);
+  Lisp_Object encoded = ENCODE_UTF_8 (source);
+  neomacs_shader_name (name);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  char *message = NULL;
+  int result = neomacs_display_shader_effect_register
+    (dpyinfo->display_handle, neomacs_shader_name (name), SSDATA (encoded),
+     &message);
+  if (message)
+    {
+      Lisp_Object msg = build_string (message);
+      neomacs_display_free_string (message);
+      error ("Cannot compile shader effect %s: %s",
+             neomacs_shader_name (name), SSDATA (msg));
+    }
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-shader-effect-apply", Fneomacs_shader_effect_apply,
+       Sneomacs_shader_effect_apply, 2, 3, 0,
+       doc: /* Draw the shader effect NAME over TARGET.
+NAME is an effect registered with `neomacs-shader-effect-register', or
+one of the built-in `vignette', `crt' and `glow'.  TARGET is a window,
+the image id of a floating image, or a list (X Y WIDTH HEIGHT) of
+pixels of the frame.  PARAMS is a list of up to four numbers, the
+`effect.params' of the shader; the built-in effects take zero for
+their default.  Applying an effect again to the same target changes
+its PARAMS.  Effects that do not compile are not drawn.
+
+The options `shader-effects' and `shader-effect-strength' turn effects
+off and fade them.  Returns t on success, nil on failure.  */)
+  (Lisp_Object name, Lisp_Object target, Lisp_Object params)
+{
+  const char *effect = neomacs_shader_name (name);
+  int64_t id;
+  float rect[4];
+  int kind = neomacs_shader_target (target, &id, rect);
+  CHECK_LIST (params);
+  float values[4] = { 0, 0, 0, 0 };
+  int n = 0;
+  for (Lisp_Object tail = params; CONSP (tail) && n < 4; tail = XCDR (tail))
+    {
+      CHECK_NUMBER (XCAR (tail));
+      values[n++] = (float) XFLOATINT (XCAR (tail));
+    }
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_shader_effect_apply
+    (dpyinfo->display_handle, effect, kind, id,
+     rect[0], rect[1], rect[2], rect[3], values);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-shader-effect-remove", Fneomacs_shader_effect_remove,
+       Sneomacs_shader_effect_remove, 0, 2, 0,
+       doc: /* Stop drawing the shader effect NAME over TARGET.
+NAME and TARGET are as for `neomacs-shader-effect-apply'; nil for
+either matches any, so with no arguments all effects are removed.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object name, Lisp_Object target)
+{
+  const char *effect = NILP (name) ? NULL : neomacs_shader_name (name);
+  int64_t id = 0;
+  float rect[4] = { 0, 0, 0, 0 };
+  int kind = NILP (target) ? -1 : neomacs_shader_target (target, &id, rect);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_shader_effect_remove
+    (dpyinfo->display_handle, effect, kind, id,
+     rect[0], rect[1], rect[2], rect[3]);
+  return result == 0 ? Qt : Qnil;
+}
+
+
+/* ============================================================================
+ * Character Grid / Emoji Picker
+ * ============================================================================ */
+
+/* Store the sRGB components of FACE's foreground or background in RGB.  */
+static void
+neomacs_face_color_floats (struct frame *f, Lisp_Object face, bool background,
+                           float rgb[3])
+{
+  int face_id = lookup_named_face (NULL, f, face, false);
+  struct face *fc = face_id >= 0 ? FACE_FROM_ID_OR_NULL (f, face_id) : NULL;
+  if (!fc)
+    return;
+  unsigned long pixel = background ? fc->background : fc->foreground;
+  rgb[0] = RED_FROM_ULONG (pixel) / 255.0f;
+  rgb[1] = GREEN_FROM_ULONG (pixel) / 255.0f;
+  rgb[2] = BLUE_FROM_ULONG (pixel) / 255.0f;
+}
+
+DEFUN ("neomacs-char-grid-show", Fneomacs_char_grid_show,
+       Sneomacs_char_grid_show, 6, 7, 0,
+       doc: /* Show a character grid overlay at X, Y (pixels).
+COLUMNS and ROWS give the visible grid size in cells of CELL-SIZE pixels.
+ITEMS is a list of strings, one per cell; each may be a multi-codepoint
+sequence such as a ZWJ emoji or a flag.  Only the visible rows are
+rasterized, so ITEMS may hold thousands of entries.
+Optional FACE (default `tooltip') supplies the colors; the selected
+cell uses the background of `highlight'.  */)
+  (Lisp_Object x, Lisp_Object y, Lisp_Object columns, Lisp_Object rows,
+   Lisp_Object cell_size, Lisp_Object items, Lisp_Object face)
+{
+  CHECK_FIXNUM (x);
+  CHECK_FIXNUM (y);
+  CHECK_FIXNAT (columns);
+  CHECK_FIXNAT (rows);
+  CHECK_FIXNAT (cell_size);
+  CHECK_LIST (items);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  Lisp_Object joined = Fmapconcat (Qidentity, items, build_string ("\n"));
+  struct frame *f = SELECTED_FRAME ();
+  float fg[3] = { 0.9f, 0.9f, 0.9f };
+  float bg[3] = { 0.15f, 0.15f, 0.18f };
+  float sel[3] = { 0.25f, 0.35f, 0.55f };
+  if (FRAME_NEOMACS_P (f))
+    {
+      Lisp_Object fface = NILP (face) ? intern ("tooltip") : face;
+      neomacs_face_color_floats (f, fface, false, fg);
+      neomacs_face_color_floats (f, fface, true, bg);
+      neomacs_face_color_floats (f, intern ("highlight"), true, sel);
+    }
+
+  neomacs_display_show_char_grid (dpyinfo->display_handle,
+                                  (float) XFIXNUM (x), (float) XFIXNUM (y),
+                                  (uint32_t) XFIXNAT (columns),
+                                  (uint32_t) XFIXNAT (rows),
+                                  (float) XFIXNAT (cell_size),
+                                  SSDATA (ENCODE_UTF_8 (joined)),
+                                  fg[0], fg[1], fg[2],
+                                  bg[0], bg[1], bg[2],
+                                  sel[0], sel[1], sel[2]);
+  return Qt;
+}
+
+DEFUN ("neomacs-char-grid-update", Fneomacs_char_grid_update,
+       Sneomacs_char_grid_update, 2, 2, 0,
+       doc: /* Select cell SELECTED (nil for none) and scroll to SCROLL-ROW.
+The grid scrolls further if needed to keep the selection visible.
+Return the first visible row, or nil if no grid is shown.  */)
+  (Lisp_Object selected, Lisp_Object scroll_row)
+{
+  CHECK_FIXNAT (scroll_row);
+  if (!NILP (selected))
+    CHECK_FIXNAT (selected);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int row = neomacs_display_update_char_grid (dpyinfo->display_handle,
+                                              NILP (selected)
+                                              ? -1 : (int) XFIXNAT (selected),
+                                              (uint32_t) XFIXNAT (scroll_row));
+  return row < 0 ? Qnil : make_fixnum (row);
+}
+
+DEFUN ("neomacs-char-grid-hide", Fneomacs_char_grid_hide,
+       Sneomacs_char_grid_hide, 0, 0, 0,
+       doc: /* Hide the character grid overlay.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  neomacs_display_hide_char_grid (dpyinfo->display_handle);
+  return Qt;
+}
+
+DEFUN ("neomacs-char-grid-index-at", Fneomacs_char_grid_index_at,
+       Sneomacs_char_grid_index_at, 2, 2, 0,
+       doc: /* Return the index of the grid cell at pixel X, Y, or nil.  */)
+  (Lisp_Object x, Lisp_Object y)
+{
+  CHECK_FIXNUM (x);
+  CHECK_FIXNUM (y);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int index = neomacs_display_char_grid_index_at (dpyinfo->display_handle,
+                                                  (float) XFIXNUM (x),
+                                                  (float) XFIXNUM (y));
+  return index < 0 ? Qnil : make_fixnum (index);
+}
+
+DEFUN ("neomacs-emoji-data", Fneomacs_emoji_data, Sneomacs_emoji_data, 0, 1, 0,
+       doc: /* Return grouped emoji data.
+The value is a list of (CATEGORY . ENTRIES), where each entry is
+\(EMOJI NAME SUBGROUP).  Data comes from FILE, or from
+$NEOMACS_EMOJI_DATA or the system Unicode emoji-test.txt when FILE is
+nil; if none is installed, unnamed entries from the main emoji blocks
+are returned.  */)
+  (Lisp_Object file)
+{
+  const char *path = NULL;
+  if (!NILP (file))
+    {
+      CHECK_STRING (file);
+      file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+      path = SSDATA (file);
+    }
+
+  char *tsv = neomacs_display_emoji_data (path);
+  if (!tsv)
+    return Qnil;
+
+  Lisp_Object categories = Qnil, entries = Qnil, current = Qnil;
+  char *line = tsv;
+  while (*line)
+    {
+      char *end = strchr (line, '\n');
+      if (end)
+        *end = '\0';
+
+      char *fields[4] = { line, NULL, NULL, NULL };
+      for (int i = 1; i < 4 && fields[i - 1]; i++)
+        {
+          char *tab = strchr (fields[i - 1], '\t');
+          if (tab)
+            {
+              *tab = '\0';
+              fields[i] = tab + 1;
+            }
+        }
+
+      if (fields[3])
+        {
+          Lisp_Object category = build_string (fields[0]);
+          if (NILP (current) || NILP (Fstring_equal (current, category)))
+            {
+              if (!NILP (current))
+                categories = Fcons (Fcons (current, Fnreverse (entries)),
+                                    categories);
+              current = category;
+              entries = Qnil;
+            }
+          entries = Fcons (list3 (build_string (fields[2]),
+                                  build_string (fields[3]),
+                                  build_string (fields[1])),
+                           entries);
+        }
+
+      if (!end)
+        break;
+      line = end + 1;
+    }
+  if (!NILP (current))
+    categories = Fcons (Fcons (current, Fnreverse (entries)), categories);
+
+  neomacs_display_free_string (tsv);
+  return Fnreverse (categories);
+}
+
+
+/* ============================================================================
+ * WebKit API
+ * ============================================================================ */
+
+DEFUN ("neomacs-webkit-init", Fneomacs_webkit_init, Sneomacs_webkit_init, 0, 0, 0,
+       doc: /* Initialize WebKit subsystem.
+Must be called before creating WebKit views.
+Returns t on success, nil on failure.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  /* Pass NULL - Rust side will try to get EGL display */
+  int result = neomacs_display_webkit_init (dpyinfo->display_handle, NULL);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-create", Fneomacs_webkit_create, Sneomacs_webkit_create, 2, 3, 0,
+       doc: /* Create a new WebKit view with WIDTH and HEIGHT.
+Optional PROFILE is the name of a profile defined with
+`neomacs-webkit-define-profile'; nil uses the default profile.
+Returns view ID on success, nil on failure.  */)
+  (Lisp_Object width, Lisp_Object height, Lisp_Object profile)
+{
+  CHECK_FIXNUM (width);
+  CHECK_FIXNUM (height);
+  if (!NILP (profile))
+    CHECK_STRING (profile);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  uint32_t view_id = neomacs_display_webkit_create_with_profile (
+    dpyinfo->display_handle,
+    (int) XFIXNUM (width),
+    (int) XFIXNUM (height),
+    NILP (profile) ? NULL : SSDATA (profile));
+
+  if (view_id == 0)
+    return Qnil;
+
+  return make_fixnum (view_id);
+}
+
+DEFUN ("neomacs-webkit-define-profile", Fneomacs_webkit_define_profile,
+       Sneomacs_webkit_define_profile, 1, 4, 0,
+       doc: /* Define a WebKit profile named NAME.
+Views created in a profile share cookies, local storage and cache with
+each other, but not with views in other profiles.
+DATA-DIR is the directory for persistent website data and cookies,
+nil means $XDG_DATA_HOME/neomacs/webkit/NAME.
+CACHE-DIR is the cache directory, nil means
+$XDG_CACHE_HOME/neomacs/webkit/NAME.
+EPHEMERAL non-nil keeps all website data in memory only (private mode);
+DATA-DIR and CACHE-DIR are then ignored.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object name, Lisp_Object data_dir, Lisp_Object cache_dir,
+   Lisp_Object ephemeral)
+{
+  CHECK_STRING (name);
+  if (!NILP (data_dir))
+    {
+      CHECK_STRING (data_dir);
+      data_dir = ENCODE_FILE (Fexpand_file_name (data_dir, Qnil));
+    }
+  if (!NILP (cache_dir))
+    {
+      CHECK_STRING (cache_dir);
+      cache_dir = ENCODE_FILE (Fexpand_file_name (cache_dir, Qnil));
+    }
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_webkit_define_profile (
+    dpyinfo->display_handle,
+    SSDATA (name),
+    NILP (data_dir) ? NULL : SSDATA (data_dir),
+    NILP (cache_dir) ? NULL : SSDATA (cache_dir),
+    !NILP (ephemeral));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-destroy", Fneomacs_webkit_destroy, Sneomacs_webkit_destroy, 1, 1, 0,
+       doc: /* Destroy WebKit view with VIEW-ID.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object view_id)
+{
+  CHECK_FIXNUM (view_id);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_webkit_destroy (dpyinfo->display_handle,
+                                               (uint32_t) XFIXNUM (view_id));
+  neomacs_check_handle (result, view_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-load-uri", Fneomacs_webkit_load_uri, Sneomacs_webkit_load_uri, 2, 2, 0,
+       doc: /* Load URI in WebKit view VIEW-ID.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object view_id, Lisp_Object uri)
+{
+  CHECK_FIXNUM (view_id);
+  CHECK_STRING (uri);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  const char *uri_str = SSDATA (uri);
+  int result = neomacs_display_webkit_load_uri (dpyinfo->display_handle,
+                                                (uint32_t) XFIXNUM (view_id),
+                                                uri_str);
+  neomacs_check_handle (result, view_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-go-back", Fneomacs_webkit_go_back, Sneomacs_webkit_go_back, 1, 1, 0,
+       doc: /* Go back in WebKit view VIEW-ID.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object view_id)
+{
+  CHECK_FIXNUM (view_id);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_webkit_go_back (dpyinfo->display_handle,
+                                               (uint32_t) XFIXNUM (view_id));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-go-forward", Fneomacs_webkit_go_forward, Sneomacs_webkit_go_forward, 1, 1, 0,
+       doc: /* Go forward in WebKit view VIEW-ID.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object view_id)
+{
+  CHECK_FIXNUM (view_id);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_webkit_go_forward (dpyinfo->display_handle,
+                                                  (uint32_t) XFIXNUM (view_id));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-reload", Fneomacs_webkit_reload, Sneomacs_webkit_reload, 1, 1, 0,
+       doc: /* Reload WebKit view VIEW-ID.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object view_id)
+{
+  CHECK_FIXNUM (view_id);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_webkit_reload (dpyinfo->display_handle,
+                                              (uint32_t) XFIXNUM (view_id));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-resize", Fneomacs_webkit_resize, Sneomacs_webkit_resize, 3, 3, 0,
+       doc: /* Resize WebKit view VIEW-ID to WIDTH x HEIGHT pixels.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object view_id, Lisp_Object width, Lisp_Object height)
+{
+  CHECK_FIXNUM (view_id);
+  CHECK_FIXNUM (width);
+  CHECK_FIXNUM (height);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_webkit_resize (dpyinfo->display_handle,
+                                              (uint32_t) XFIXNUM (view_id),
+                                              (int) XFIXNUM (width),
+                                              (int) XFIXNUM (height));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-webkit-execute-js", Fneomacs_webkit_execute_js, Sneomacs_webkit_execute_js, 2, 2, 0,
+       doc: /* Execute JavaScript SCRIPT in WebKit view VIEW-ID.
 Returns t on success, nil on failure.  */)
   (Lisp_Object view_id, Lisp_Object script)
 {
@@ -8915,19 +10611,18 @@ Set to nil to fall back to the legacy C matrix extraction engine.  */)
  * ============================================================================ */
 
 DEFUN ("neomacs-set-animation-option", Fneomacs_set_animation_option, Sneomacs_set_animation_option, 2, 2, 0,
-       doc: /* Set OPTION to VALUE for Neomacs animation system.
-OPTION is a string naming the option:
-  \"animation\" - master enable/disable (\"t\" or \"nil\")
-  \"cursor-animation\" - enable cursor animation (\"t\" or \"nil\")
-  \"cursor-animation-mode\" - cursor effect mode (\"smooth\", \"railgun\", \"torpedo\", \"pixiedust\", \"sonicboom\", \"ripple\", \"wireframe\", \"none\")
-  \"cursor-animation-speed\" - cursor animation speed (1-100)
-  \"cursor-glow\" - enable cursor glow (\"t\" or \"nil\")
-  \"cursor-particle-count\" - number of particles (1-100)
-  \"buffer-transition\" - enable buffer transitions (\"t\" or \"nil\")
-  \"buffer-transition-effect\" - transition effect (\"crossfade\", \"slide-left\", \"slide-right\", \"slide-up\", \"slide-down\", \"scale-fade\", \"push\", \"blur\", \"page-curl\", \"none\")
-  \"buffer-transition-duration\" - duration in milliseconds
-VALUE is a string with the new value.
-Returns t on success, nil on failure.  */)
+       doc: /* Set display OPTION to VALUE, overriding the display config file.
+OPTION is a string naming the option, such as \"cursor-animation\",
+\"scroll-effect\", \"text-gamma\", \"terminal-palette\", \"vsync\"
+or \"image-cache-mb\"; `neomacs-display-options' lists all options with
+their types, defaults and documentation.
+VALUE is a string: \"t\" or \"nil\" for boolean options, a number,
+one of the choices, a \"#rrggbb\" color, or space-separated colors.
+The same options can be set in the TOML file named by the environment
+variable NEOMACS_DISPLAY_CONFIG, by default
+$XDG_CONFIG_HOME/neomacs/display.toml.  The file is read again when it
+changes and when Emacs receives SIGUSR1.
+Returns t on success, nil if OPTION is unknown or VALUE is invalid.  */)
   (Lisp_Object option, Lisp_Object value)
 {
   CHECK_STRING (option);
@@ -8940,34 +10635,580 @@ Returns t on success, nil on failure.  */)
   const char *opt = SSDATA (option);
   const char *val = SSDATA (value);
 
-  int result = neomacs_display_set_animation_option (dpyinfo->display_handle, opt, val);
-  return result ? Qt : Qnil;
+  int result = neomacs_display_set_animation_option (dpyinfo->display_handle, opt, val);
+  return result ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-get-animation-option", Fneomacs_get_animation_option, Sneomacs_get_animation_option, 1, 1, 0,
+       doc: /* Get the value of display OPTION.
+OPTION is a string naming the option (see `neomacs-display-options').
+Returns the value set at runtime, else in the display config file, else
+the default, as a string.  Returns nil if OPTION is unknown.  */)
+  (Lisp_Object option)
+{
+  CHECK_STRING (option);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  const char *opt = SSDATA (option);
+  char *value = neomacs_display_get_animation_option (dpyinfo->display_handle, opt);
+
+  if (!value)
+    return Qnil;
+
+  Lisp_Object result = build_string (value);
+  neomacs_display_free_string (value);
+  return result;
+}
+
+DEFUN ("neomacs-display-options", Fneomacs_display_options, Sneomacs_display_options, 0, 0, 0,
+       doc: /* Return a description of every display option.
+Each element is a plist with the keys :name, :group, :type, :default and
+:doc.  :type is one of the symbols `boolean', `integer', `float',
+`choice', `color' and `color-list'.  :default is the default value as a
+string, in the form `neomacs-set-animation-option' accepts.  Integer and
+float options also have :min and :max, choice options have :choices, a
+list of strings, and color-list options have :max, the number of
+colors.  */)
+  (void)
+{
+  Lisp_Object result = Qnil;
+  for (int i = neomacs_display_option_count () - 1; i >= 0; i--)
+    {
+      struct NeomacsOptionInfo info;
+      if (!neomacs_display_option_info (i, &info))
+        continue;
+
+      Lisp_Object extra = Qnil;
+      if (!strcmp (info.type_name, "integer"))
+        extra = list4 (intern (":min"), make_fixnum ((EMACS_INT) info.min),
+                       intern (":max"), make_fixnum ((EMACS_INT) info.max));
+      else if (!strcmp (info.type_name, "float"))
+        extra = list4 (intern (":min"), make_float (info.min),
+                       intern (":max"), make_float (info.max));
+      else if (!strcmp (info.type_name, "color-list"))
+        extra = list2 (intern (":max"), make_fixnum ((EMACS_INT) info.max));
+      else if (info.choices)
+        {
+          Lisp_Object choices = Qnil;
+          const char *p = info.choices;
+          while (*p)
+            {
+              const char *end = strchr (p, ' ');
+              if (!end)
+                end = p + strlen (p);
+              if (end > p)
+                choices = Fcons (make_string (p, end - p), choices);
+              p = *end ? end + 1 : end;
+            }
+          extra = list2 (intern (":choices"), Fnreverse (choices));
+        }
+
+      Lisp_Object option = list (intern (":name"), build_string (info.name),
+                                 intern (":group"), build_string (info.group),
+                                 intern (":type"), intern (info.type_name),
+                                 intern (":default"), build_string (info.default_value),
+                                 intern (":doc"), build_string (info.doc));
+      result = Fcons (nconc2 (option, extra), result);
+    }
+  return result;
+}
+
+DEFUN ("neomacs-display-errors", Fneomacs_display_errors, Sneomacs_display_errors, 0, 1, 0,
+       doc: /* Return errors reported by the display engine, oldest first.
+If AFTER is non-nil, only return errors with an id greater than AFTER.
+Each element is a plist with the keys :id, :severity, :kind, :resource,
+:count and :message.  :severity is one of the symbols `info', `warning'
+and `error'; :kind names the subsystem, such as `image', `video',
+`font' or `shader'.  :resource is the id of the image, video, view or
+terminal concerned, or nil.  :count is how many times the failure
+happened.  The engine keeps only the most recent errors.  */)
+  (Lisp_Object after)
+{
+  uint32_t id = 0;
+  if (!NILP (after))
+    {
+      CHECK_FIXNAT (after);
+      id = XFIXNAT (after);
+    }
+  Lisp_Object result = Qnil;
+  struct NeomacsErrorInfo info;
+  while (neomacs_display_next_error (id, &info))
+    {
+      id = info.id;
+      Lisp_Object message = info.message ? build_string (info.message)
+                                         : empty_unibyte_string;
+      neomacs_display_free_string (info.message);
+      result = Fcons (list (intern (":id"), make_fixnum (info.id),
+                            intern (":severity"), intern (info.severity),
+                            intern (":kind"), intern (info.kind),
+                            intern (":resource"),
+                            info.resource ? make_fixnum (info.resource) : Qnil,
+                            intern (":count"), make_fixnum (info.count),
+                            intern (":message"), message),
+                      result);
+    }
+  return Fnreverse (result);
+}
+
+DEFUN ("neomacs-display-clear-errors", Fneomacs_display_clear_errors, Sneomacs_display_clear_errors, 0, 0, 0,
+       doc: /* Forget the errors reported by the display engine.  */)
+  (void)
+{
+  neomacs_display_clear_errors ();
+  return Qnil;
+}
+
+DEFUN ("neomacs-display-memory-usage", Fneomacs_display_memory_usage, Sneomacs_display_memory_usage, 0, 0, 0,
+       doc: /* Return the GPU memory used by the display engine.
+The value is a plist with the keys :glyphs, :images, :visible-images,
+:videos, :snapshots and :budget, all in bytes, as measured after the
+last frame.  When the total exceeds :budget, transition snapshots are
+freed first, then images not on screen, then visible images and
+finally unused glyphs.  The budget is the `gpu-memory-mb' display
+option.  */)
+  (void)
+{
+  struct NeomacsGpuMemoryUsage usage;
+  neomacs_display_gpu_memory_usage (&usage);
+  return list (intern (":glyphs"), make_uint (usage.glyphs),
+               intern (":images"), make_uint (usage.images),
+               intern (":visible-images"), make_uint (usage.visibleImages),
+               intern (":videos"), make_uint (usage.videos),
+               intern (":snapshots"), make_uint (usage.snapshots),
+               intern (":budget"), make_uint (usage.budget));
+}
+
+DEFUN ("neomacs-display-quality", Fneomacs_display_quality, Sneomacs_display_quality, 0, 0, 0,
+       doc: /* Return the quality level the display engine draws at.
+The value is one of the symbols `full', `no-particles',
+`no-transitions', `reduced-video' and `no-blur'; each level drops one
+more feature than the one before.  With the display option "quality"
+set to "auto" (see `neomacs-set-animation-option'), the engine steps
+down while frames take longer than a refresh interval and back up when
+they have time to spare, reporting each change as an `info' display
+error (see `neomacs-display-errors').  Any other value of the option
+pins the level.  */)
+  (void)
+{
+  return intern (neomacs_display_quality_level ());
+}
+
+DEFUN ("neomacs-input-latency", Fneomacs_input_latency, Sneomacs_input_latency, 0, 0, 0,
+       doc: /* Return the input latency measured by the display engine.
+Measuring is on while the display option "input-latency" is (see
+`neomacs-set-animation-option'); turning it on starts afresh.  Each key
+or mouse button press is timed from the window system to the first
+frame Emacs sends after reading it, and to the screen.
+The value is a plist with a key for each stage of that path:
+  :input-to-host    window system to Emacs reading the event
+  :host-to-frame    Emacs reading the event to sending a frame
+  :frame-to-screen  frame sent to presented
+  :total            the whole path
+Each value is a plist with the keys :count, :p50, :p90, :p99 and :max,
+the percentiles in microseconds over the most recent presses.  */)
+  (void)
+{
+  static const char *const stages[] = {
+    ":input-to-host", ":host-to-frame", ":frame-to-screen", ":total"
+  };
+  Lisp_Object result = Qnil;
+  for (int i = ARRAYELTS (stages) - 1; i >= 0; i--)
+    {
+      struct NeomacsLatencyStats stats;
+      if (!neomacs_display_input_latency (i, &stats))
+        continue;
+      result = Fcons (intern (stages[i]),
+                      Fcons (list (intern (":count"), make_uint (stats.count),
+                                   intern (":p50"), make_uint (stats.p50Us),
+                                   intern (":p90"), make_uint (stats.p90Us),
+                                   intern (":p99"), make_uint (stats.p99Us),
+                                   intern (":max"), make_uint (stats.maxUs)),
+                             result));
+    }
+  return result;
+}
+
+DEFUN ("neomacs-frame-stats", Fneomacs_frame_stats, Sneomacs_frame_stats, 0, 0, 0,
+       doc: /* Return statistics of the last frame the display engine drew.
+The value is a plist:
+  :frames            frames drawn since the display started
+  :glyphs            glyphs in the frame, terminal cells included
+  :windows           windows in the frame
+  :background-rects  cell backgrounds drawn, after merging
+  :row-cache-hits    text rows reused from the previous frame
+  :row-cache-misses  text rows rebuilt
+  :prepare-us        microseconds updating terminals, media and faces
+  :draw-us           microseconds recording and submitting drawing
+  :present-us        microseconds handing the frame to the compositor
+  :skipped-frames    frames replaced by a newer one before being drawn
+  :late-frames       frames that took longer than a refresh interval
+The last two are totals since the display started.  */)
+  (void)
+{
+  struct NeomacsFrameStats stats;
+  neomacs_display_frame_stats (&stats);
+  return listn (22,
+                intern (":frames"), make_uint (stats.frames),
+                intern (":glyphs"), make_uint (stats.glyphs),
+                intern (":windows"), make_uint (stats.windows),
+                intern (":background-rects"), make_uint (stats.backgroundRects),
+                intern (":row-cache-hits"), make_uint (stats.rowCacheHits),
+                intern (":row-cache-misses"), make_uint (stats.rowCacheMisses),
+                intern (":prepare-us"), make_uint (stats.prepareUs),
+                intern (":draw-us"), make_uint (stats.drawUs),
+                intern (":present-us"), make_uint (stats.presentUs),
+                intern (":skipped-frames"), make_uint (stats.skippedFrames),
+                intern (":late-frames"), make_uint (stats.lateFrames));
+}
+
+DEFUN ("neomacs-scene-dump", Fneomacs_scene_dump, Sneomacs_scene_dump, 0, 0, 0,
+       doc: /* Return the structure of the frame the display engine holds.
+The value is a JSON object, as a string, with the frame's size and
+scale, its windows, the number of glyphs of each kind, the floating
+layers from bottom to top with their z-order, the running animations
+and the sizes of the display caches.  Parse it with
+`json-parse-string'.  The option "debug-overlay" (see
+`neomacs-set-animation-option') shows the overdraw or the rebuilt text
+rows of the frame on screen.  Returns nil if the render thread does not
+answer.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  char *json = neomacs_display_scene_dump (dpyinfo->display_handle);
+  if (!json)
+    return Qnil;
+
+  Lisp_Object result = build_string (json);
+  neomacs_display_free_string (json);
+  return result;
+}
+
+DEFUN ("neomacs-frame-text", Fneomacs_frame_text, Sneomacs_frame_text, 0, 0, 0,
+       doc: /* Return the text the display engine shows, window by window.
+The value is a string.  Each window body, minibuffer and mode line
+starts with a header line such as
+
+  -- window 94022 text-area focused "/tmp/notes.txt"
+
+naming the window, the role of the text, whether it holds the keyboard
+focus and the buffer's file, followed by its visual lines as drawn,
+trailing blanks removed.  The cursor is marked with the character CARET
+\(U+2038), and text drawn in the `region' face is enclosed in the
+guillemets U+00AB and U+00BB.  This is the text screen readers are
+given, and lets tests check what is actually on screen.  Returns nil if
+the render thread does not answer.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int fid = lookup_named_face (NULL, NULL, intern ("region"), false);
+  char *text = neomacs_display_frame_text (dpyinfo->display_handle,
+					   fid < 0 ? 0 : fid);
+  if (!text)
+    return Qnil;
+
+  Lisp_Object result = build_string (text);
+  neomacs_display_free_string (text);
+  return result;
+}
+
+DEFUN ("neomacs-display-memory-report", Fneomacs_display_memory_report,
+       Sneomacs_display_memory_report, 0, 0, 0,
+       doc: /* Return the memory held by each subsystem of the display engine.
+Each element is a plist with the keys :subsystem, :count, :bytes and
+:gpu.  :subsystem is a symbol such as `glyph-atlas', `images',
+`videos', `transition-snapshots', `terminal-grids' (with their
+scrollback) or `terminal-snapshots'; :count is how many glyphs,
+images, snapshots or terminals it holds; :gpu is non-nil for video
+memory and nil for main memory.  The sizes are estimates.  See also
+`neomacs-display-memory', which shows this in a buffer.  Returns nil
+if the render thread does not answer.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  struct NeomacsMemoryUse entries[32];
+  int n = neomacs_display_memory_report (dpyinfo->display_handle, entries,
+                                         ARRAYELTS (entries));
+  Lisp_Object result = Qnil;
+  for (int i = n - 1; i >= 0; i--)
+    {
+      Lisp_Object subsystem = entries[i].subsystem
+        ? intern (entries[i].subsystem) : Qnil;
+      neomacs_display_free_string (entries[i].subsystem);
+      result = Fcons (list (intern (":subsystem"), subsystem,
+                            intern (":count"), make_uint (entries[i].count),
+                            intern (":bytes"), make_uint (entries[i].bytes),
+                            intern (":gpu"), entries[i].gpu ? Qt : Qnil),
+                      result);
+    }
+  return result;
+}
+
+DEFUN ("neomacs-session-save", Fneomacs_session_save, Sneomacs_session_save, 0, 1, 0,
+       doc: /* Save the display session to FILE.
+The session lists the terminals with their recent output, the images
+and videos loaded from files, the WebKit views with the page each
+shows, where floating ones are, and the display options set at
+runtime, so that `neomacs-session-restore' can bring them back after a
+crash or restart.  FILE defaults to display-session.toml in the
+neomacs directory under $XDG_STATE_HOME.
+The file is written in the background; errors are reported through
+`neomacs-display-event-functions'.  Returns t if the save was started.  */)
+  (Lisp_Object file)
+{
+  const char *path = NULL;
+  if (!NILP (file))
+    {
+      CHECK_STRING (file);
+      file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+      path = SSDATA (file);
+    }
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  return neomacs_display_session_save (dpyinfo->display_handle, path) ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-replay-start", Fneomacs_replay_start, Sneomacs_replay_start, 1, 1, 0,
+       doc: /* Record the frames drawn from now on to the replay FILE.
+The replay holds what the display engine is asked to draw, with the
+images shown, so that a rendering bug can be seen again on another
+machine with the neomacs-replay program instead of a screenshot.
+Videos, web views and terminals are not recorded.  Stop recording
+with `neomacs-replay-stop'.  Errors writing FILE are reported through
+`neomacs-display-event-functions'.  Returns t if recording started.  */)
+  (Lisp_Object file)
+{
+  CHECK_STRING (file);
+  file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  return neomacs_display_replay_start (dpyinfo->display_handle, SSDATA (file)) ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-replay-stop", Fneomacs_replay_stop, Sneomacs_replay_stop, 0, 0, 0,
+       doc: /* Finish the replay started by `neomacs-replay-start'.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (dpyinfo && dpyinfo->display_handle)
+    neomacs_display_replay_stop (dpyinfo->display_handle);
+  return Qnil;
+}
+
+DEFUN ("neomacs-export-effect-preview", Fneomacs_export_effect_preview,
+       Sneomacs_export_effect_preview, 3, 5, 0,
+       doc: /* Render the animation EFFECT of KIND offscreen and save it to FILE.
+KIND is `transition' for a buffer-switch or scroll transition, EFFECT
+then being one of the SCROLL-EFFECT symbols of
+`neomacs-set-animation-config', or `cursor' for the motion of the
+cursor, EFFECT then being one of its CURSOR-STYLE symbols.
+
+The effect plays over a sample buffer rather than the windows shown:
+transitions go from it to the same text scrolled a few lines, and the
+cursor jumps between two places in it with the speed and trail set for
+the real cursor.  It lasts DURATION milliseconds (400 by default, at
+most 10000), sampled at FPS frames a second (30 by default, at most 60).
+
+If FILE ends in .gif, the frames are saved as an animated GIF that
+loops, holding the first and last frame for half a second.  Otherwise
+FILE is taken as a directory, created if needed, and each frame saved
+in it as frame-0000.png, frame-0001.png and so on.  The display pauses
+while the frames are rendered; they are encoded in the background, and
+errors are reported through `neomacs-display-event-functions'.
+Returns t if the export was started.  */)
+  (Lisp_Object kind, Lisp_Object effect, Lisp_Object file,
+   Lisp_Object duration, Lisp_Object fps)
+{
+  CHECK_SYMBOL (kind);
+  CHECK_SYMBOL (effect);
+  CHECK_STRING (file);
+  if (!NILP (duration))
+    CHECK_FIXNAT (duration);
+  if (!NILP (fps))
+    CHECK_FIXNAT (fps);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+  int result = neomacs_display_export_effect_preview
+    (dpyinfo->display_handle,
+     SSDATA (SYMBOL_NAME (kind)), SSDATA (SYMBOL_NAME (effect)),
+     SSDATA (file),
+     NILP (duration) ? 400 : min (XFIXNAT (duration), UINT32_MAX),
+     NILP (fps) ? 30 : min (XFIXNAT (fps), UINT32_MAX));
+  if (result < 0)
+    error ("Unknown %s effect: %s", SSDATA (SYMBOL_NAME (kind)),
+           SSDATA (SYMBOL_NAME (effect)));
+  return result ? Qt : Qnil;
+}
+
+/* The pixel of COLOR, a color name or a "#rrggbbaa" string whose alpha
+   neomacs_defined_color would drop.  */
+static uint32_t
+neomacs_theme_pixel (Lisp_Object color)
+{
+  CHECK_STRING (color);
+  unsigned int r, g, b, a;
+  if (SBYTES (color) == 9
+      && sscanf (SSDATA (color), "#%2x%2x%2x%2x", &r, &g, &b, &a) == 4)
+    return (max (a, 1) << 24) | (r << 16) | (g << 8) | b;
+  Emacs_Color c;
+  if (!neomacs_defined_color (NULL, SSDATA (color), &c, false, false))
+    error ("Undefined color: %s", SSDATA (color));
+  return ((c.red >> 8) << 16) | ((c.green >> 8) << 8) | (c.blue >> 8);
+}
+
+DEFUN ("neomacs-set-display-theme", Fneomacs_set_display_theme,
+       Sneomacs_set_display_theme, 1, 2, 0,
+       doc: /* Switch the colors the display draws with on its own to THEME.
+THEME is a plist of colors, names or "#rrggbb" strings:
+
+  :background        default background, for the title bar and terminals
+  :foreground        default foreground of terminals
+  :cursor            color of the cursor, instead of the cursor face's
+  :divider           color of window dividers, instead of their faces'
+  :placeholder       area of a web view or video with nothing to show
+                     yet; "#rrggbbaa" gives it an alpha
+  :terminal-palette  list of up to 16 colors for terminal colors 0-15
+
+:background and :foreground are required.  Colors not given return to
+their defaults, so all of them change together in one frame.  With
+FADE, a number of milliseconds, the frame crossfades from the old
+colors to the new.
+
+Meant for switching between dark and light themes, together with the
+`appearance-changed' event of `neomacs-display-event-functions'.
+Returns t if the switch was queued.  */)
+  (Lisp_Object theme, Lisp_Object fade)
+{
+  CHECK_LIST (theme);
+  if (!NILP (fade))
+    CHECK_FIXNAT (fade);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  struct NeomacsDisplayTheme t = { 0 };
+  t.background = neomacs_theme_pixel (Fplist_get (theme, QCbackground, Qnil));
+  t.foreground = neomacs_theme_pixel (Fplist_get (theme, QCforeground, Qnil));
+
+  Lisp_Object cursor = Fplist_get (theme, QCcursor, Qnil);
+  if (!NILP (cursor))
+    {
+      t.cursor = neomacs_theme_pixel (cursor);
+      t.set |= NEOMACS_THEME_CURSOR;
+    }
+  Lisp_Object divider = Fplist_get (theme, QCdivider, Qnil);
+  if (!NILP (divider))
+    {
+      t.divider = neomacs_theme_pixel (divider);
+      t.set |= NEOMACS_THEME_DIVIDER;
+    }
+  Lisp_Object placeholder = Fplist_get (theme, QCplaceholder, Qnil);
+  if (!NILP (placeholder))
+    {
+      t.placeholder = neomacs_theme_pixel (placeholder);
+      t.set |= NEOMACS_THEME_PLACEHOLDER;
+    }
+  Lisp_Object palette = Fplist_get (theme, QCterminal_palette, Qnil);
+  if (!NILP (palette))
+    {
+      CHECK_LIST (palette);
+      /* Colors not given keep the standard ones */
+      static const uint32_t standard[16] = {
+        0x000000, 0xcd0000, 0x00cd00, 0xcdcd00,
+        0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5,
+        0x7f7f7f, 0xff0000, 0x00ff00, 0xffff00,
+        0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+      };
+      for (int i = 0; i < 16; i++)
+        {
+          t.terminalPalette[i] = standard[i];
+          if (CONSP (palette))
+            {
+              t.terminalPalette[i] = neomacs_theme_pixel (XCAR (palette));
+              palette = XCDR (palette);
+            }
+        }
+      t.set |= NEOMACS_THEME_TERMINAL_PALETTE;
+    }
+
+  int queued = neomacs_display_set_theme
+    (dpyinfo->display_handle, &t,
+     NILP (fade) ? 0 : min (XFIXNAT (fade), UINT32_MAX));
+  return queued ? Qt : Qnil;
 }
 
-DEFUN ("neomacs-get-animation-option", Fneomacs_get_animation_option, Sneomacs_get_animation_option, 1, 1, 0,
-       doc: /* Get the value of animation OPTION.
-OPTION is a string naming the option (see `neomacs-set-animation-option').
-Returns the current value as a string, or nil if the option is unknown.  */)
-  (Lisp_Object option)
+DEFUN ("neomacs-session-restore", Fneomacs_session_restore, Sneomacs_session_restore, 0, 1, 0,
+       doc: /* Recreate the display session saved in FILE by `neomacs-session-save'.
+Every resource gets a new id; terminals start a new shell showing the
+output saved.  FILE has the same default as for `neomacs-session-save'.
+Returns a list of (KIND OLD-ID . NEW-ID), KIND being one of `terminal',
+`image', `video' and `webkit', for rebinding buffers that referred to
+the old ids.  Returns nil if the file cannot be read.  */)
+  (Lisp_Object file)
 {
-  CHECK_STRING (option);
+  const char *path = NULL;
+  if (!NILP (file))
+    {
+      CHECK_STRING (file);
+      file = ENCODE_FILE (Fexpand_file_name (file, Qnil));
+      path = SSDATA (file);
+    }
 
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
     return Qnil;
 
-  const char *opt = SSDATA (option);
-  char *value = neomacs_display_get_animation_option (dpyinfo->display_handle, opt);
-
-  if (!value)
+  char *ids = neomacs_display_session_restore (dpyinfo->display_handle, path);
+  if (!ids)
     return Qnil;
 
-  Lisp_Object result = build_string (value);
-  neomacs_display_free_string (value);
-  return result;
+  Lisp_Object result = Qnil;
+  char *line = ids;
+  while (*line)
+    {
+      char *end = strchr (line, '\n');
+      if (end)
+        *end = '\0';
+
+      char kind[16];
+      unsigned int old_id, new_id;
+      if (sscanf (line, "%15s %u %u", kind, &old_id, &new_id) == 3)
+        result = Fcons (Fcons (intern (kind),
+                               Fcons (make_fixnum (old_id),
+                                      make_fixnum (new_id))),
+                        result);
+
+      if (!end)
+        break;
+      line = end + 1;
+    }
+  neomacs_display_free_string (ids);
+  return Fnreverse (result);
 }
 
-DEFUN ("neomacs-start-buffer-transition", Fneomacs_start_buffer_transition, Sneomacs_start_buffer_transition, 1, 2, 0,
+DEFUN ("neomacs-start-buffer-transition", Fneomacs_start_buffer_transition, Sneomacs_start_buffer_transition, 1, 3, 0,
        doc: /* Start a buffer transition animation with EFFECT.
 EFFECT is a string naming the effect:
   \"crossfade\" - simple fade between buffers
@@ -8980,11 +11221,19 @@ EFFECT is a string naming the effect:
   \"blur\" - blur transition
   \"page-curl\" - 3D book page turn effect
   \"none\" - no animation (instant switch)
+The names of the SCROLL-EFFECT values of `neomacs-set-animation-config'
+work as well.  The GPU display draws \"slide-up\" and \"slide-down\"
+as the same vertical slide.
 Optional DURATION is the animation duration in milliseconds (default 300).
+Optional NOTIFY-ID, a positive integer, is reported through the
+`animation-completed' event of `neomacs-display-event-functions' once
+the transition ended in every window.
 Returns t on success, nil on failure.  */)
-  (Lisp_Object effect, Lisp_Object duration)
+  (Lisp_Object effect, Lisp_Object duration, Lisp_Object notify_id)
 {
   CHECK_STRING (effect);
+  if (!NILP (notify_id))
+    CHECK_FIXNAT (notify_id);
 
   int duration_ms = 300;  /* Default */
   if (!NILP (duration))
@@ -9002,7 +11251,76 @@ Returns t on success, nil on failure.  */)
     return Qnil;
 
   const char *eff = SSDATA (effect);
-  int result = neomacs_display_start_buffer_transition (dpyinfo->display_handle, eff, duration_ms);
+  int result = neomacs_display_start_buffer_transition (dpyinfo->display_handle, eff, duration_ms,
+                                                        NILP (notify_id) ? 0 : (uint32_t) XFIXNAT (notify_id));
+  return result ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-set-frame-zoom", Fneomacs_set_frame_zoom, Sneomacs_set_frame_zoom, 1, 5, 0,
+       doc: /* Zoom the whole frame to SCALE, for presentations and screen sharing.
+SCALE is a number from 1.0 (no zoom) to 8.0; the rendered frame is
+magnified as a whole.  Optional X and Y give the point, in pixels,
+that stays in place; when either is nil the zoom follows the cursor.
+Optional DURATION is the animation duration in milliseconds (default 250).
+Optional NOTIFY-ID, a positive integer, is reported through the
+`animation-completed' event of `neomacs-display-event-functions' when
+the zoom ends.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object scale, Lisp_Object x, Lisp_Object y, Lisp_Object duration,
+   Lisp_Object notify_id)
+{
+  CHECK_NUMBER (scale);
+  if (!NILP (notify_id))
+    CHECK_FIXNAT (notify_id);
+
+  int px = -1, py = -1;
+  if (!NILP (x) && !NILP (y))
+    {
+      CHECK_FIXNAT (x);
+      CHECK_FIXNAT (y);
+      px = XFIXNAT (x);
+      py = XFIXNAT (y);
+    }
+
+  int duration_ms = 250;  /* Default */
+  if (!NILP (duration))
+    {
+      CHECK_FIXNUM (duration);
+      duration_ms = XFIXNUM (duration);
+      if (duration_ms < 0)
+        duration_ms = 0;
+      if (duration_ms > 5000)
+        duration_ms = 5000;  /* Cap at 5 seconds */
+    }
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_set_frame_zoom (dpyinfo->display_handle,
+                                               (float) XFLOATINT (scale),
+                                               px, py, duration_ms,
+                                               NILP (notify_id) ? 0 : (uint32_t) XFIXNAT (notify_id));
+  return result ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-scroll-hint", Fneomacs_scroll_hint, Sneomacs_scroll_hint, 1, 1, 0,
+       doc: /* Announce that the next scroll moves the selected window's text by PIXELS.
+PIXELS is positive when the text moves up (the window scrolls forward)
+and negative when it moves down.  The scroll animation of the next
+redisplay then slides the text by that distance instead of a whole
+window, which suits scrolling by a few lines.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object pixels)
+{
+  CHECK_FIXNUM (pixels);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int result = neomacs_display_scroll_hint (dpyinfo->display_handle,
+                                            (int) XFIXNUM (pixels));
   return result ? Qt : Qnil;
 }
 
@@ -9183,6 +11501,80 @@ Colors are strings like \"#rrggbb\".  Pass nil for either to disable.  */)
   return Qt;
 }
 
+DEFUN ("neomacs-set-face-background-style",
+       Fneomacs_set_face_background_style,
+       Sneomacs_set_face_background_style, 2, 2, 0,
+       doc: /* Draw the background of FACE with a gradient or an image.
+STYLE is one of:
+  nil                              -- the face's flat background color
+  (gradient FROM TO &optional ANGLE) -- a linear gradient between the
+                                      color strings FROM and TO; ANGLE is
+                                      in degrees clockwise from
+                                      left-to-right (default 0)
+  (image ID)                       -- image ID from `neomacs-image-load',
+                                      scaled to cover the background
+The style applies to every face whose background color is the one FACE
+has on the selected frame, so faces inheriting it are styled too.
+Returns t on success, nil on failure.  */)
+  (Lisp_Object face, Lisp_Object style)
+{
+  CHECK_SYMBOL (face);
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  struct frame *f = SELECTED_FRAME ();
+  int face_id = lookup_named_face (NULL, f, face, false);
+  struct face *rface = face_id >= 0 ? FACE_FROM_ID_OR_NULL (f, face_id) : NULL;
+  if (!rface)
+    return Qnil;
+  unsigned long bg = rface->background;
+  uint32_t bg_rgb = ((RED_FROM_ULONG (bg) << 16)
+                     | (GREEN_FROM_ULONG (bg) << 8)
+                     | BLUE_FROM_ULONG (bg));
+
+  int kind = 0;
+  uint32_t from_rgb = 0, to_rgb = 0, image_id = 0;
+  float angle = 0.0f;
+  if (CONSP (style) && EQ (XCAR (style), intern ("gradient")))
+    {
+      Lisp_Object from = Fnth (make_fixnum (1), style);
+      Lisp_Object to = Fnth (make_fixnum (2), style);
+      Lisp_Object ang = Fnth (make_fixnum (3), style);
+      CHECK_STRING (from);
+      CHECK_STRING (to);
+      Emacs_Color fc, tc;
+      if (!neomacs_defined_color (NULL, SSDATA (from), &fc, false, false))
+        error ("Undefined color: %s", SSDATA (from));
+      if (!neomacs_defined_color (NULL, SSDATA (to), &tc, false, false))
+        error ("Undefined color: %s", SSDATA (to));
+      from_rgb = (((fc.red >> 8) << 16) | ((fc.green >> 8) << 8)
+                  | (fc.blue >> 8));
+      to_rgb = (((tc.red >> 8) << 16) | ((tc.green >> 8) << 8)
+                | (tc.blue >> 8));
+      if (!NILP (ang))
+        angle = (float) extract_float (ang);
+      kind = 1;
+    }
+  else if (CONSP (style) && EQ (XCAR (style), intern ("image")))
+    {
+      Lisp_Object id = Fnth (make_fixnum (1), style);
+      CHECK_FIXNAT (id);
+      image_id = (uint32_t) XFIXNAT (id);
+      kind = 2;
+    }
+  else if (!NILP (style))
+    error ("Invalid background style");
+
+  neomacs_display_set_face_background_style (dpyinfo->display_handle,
+                                             bg_rgb, kind, from_rgb,
+                                             to_rgb, angle, image_id);
+  /* Faces are re-sent on the next full redisplay */
+  SET_FRAME_GARBAGED (f);
+  return Qt;
+}
+
 DEFUN ("neomacs-set-line-highlight",
        Fneomacs_set_line_highlight,
        Sneomacs_set_line_highlight, 0, 2, 0,
@@ -9251,11 +11643,14 @@ Optional COLOR is a color string (default \"gray50\").  */)
 
 DEFUN ("neomacs-set-inactive-dim",
        Fneomacs_set_inactive_dim,
-       Sneomacs_set_inactive_dim, 0, 2, 0,
+       Sneomacs_set_inactive_dim, 0, 3, 0,
        doc: /* Configure inactive window dimming.
 ENABLED non-nil dims inactive windows with a dark overlay.
-Optional OPACITY is a number 0.0-1.0 for dimming strength (default 0.15).  */)
-  (Lisp_Object enabled, Lisp_Object opacity)
+Optional OPACITY is a number 0.0-1.0 for dimming strength (default 0.15).
+Optional SATURATION is a number 0.0-1.0 for how much color inactive
+windows keep: 1.0 (the default) leaves them unchanged, 0.0 makes them
+gray.  Windows fade in and out of the effect as the selection changes.  */)
+  (Lisp_Object enabled, Lisp_Object opacity, Lisp_Object saturation)
 {
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
@@ -9272,7 +11667,16 @@ Optional OPACITY is a number 0.0-1.0 for dimming strength (default 0.15).  */)
       op = (int)(val * 100.0);
     }
 
-  neomacs_display_set_inactive_dim (dpyinfo->display_handle, on, op);
+  int sat = 100; /* default 1.0 */
+  if (NUMBERP (saturation))
+    {
+      double val = XFLOATINT (saturation);
+      if (val < 0.0) val = 0.0;
+      if (val > 1.0) val = 1.0;
+      sat = (int)(val * 100.0);
+    }
+
+  neomacs_display_set_inactive_dim (dpyinfo->display_handle, on, op, sat);
   return on ? Qt : Qnil;
 }
 
@@ -9416,11 +11820,16 @@ OPACITY is an integer 0-100 for the dimming overlay opacity (default 40).  */)
 
 DEFUN ("neomacs-set-minimap",
        Fneomacs_set_minimap,
-       Sneomacs_set_minimap, 0, 2, 0,
+       Sneomacs_set_minimap, 0, 3, 0,
        doc: /* Configure minimap code overview column.
 ENABLED non-nil shows a minimap on the right side of each window.
-WIDTH is the minimap column width in pixels (default 80).  */)
-  (Lisp_Object enabled, Lisp_Object width)
+WIDTH is the minimap column width in pixels (default 80).
+LINE-HEIGHT is the height of one buffer line in the minimap in pixels
+(default 2).  Buffers with line summaries sent by
+`neomacs-minimap-update-lines' show the whole buffer; other buffers
+show the visible text only.  Dragging in the minimap scrolls the
+window.  */)
+  (Lisp_Object enabled, Lisp_Object width, Lisp_Object line_height)
 {
   struct neomacs_display_info *dpyinfo = neomacs_display_list;
   if (!dpyinfo || !dpyinfo->display_handle)
@@ -9428,11 +11837,320 @@ WIDTH is the minimap column width in pixels (default 80).  */)
 
   int on = !NILP (enabled);
   int w = 80;
+  int lh = 2;
   if (FIXNUMP (width))
     w = XFIXNUM (width);
+  if (FIXNUMP (line_height) && XFIXNUM (line_height) > 0)
+    lh = XFIXNUM (line_height);
 
+  neomacs_minimap_width = on ? w : 0;
+  neomacs_minimap_line_height = lh;
   neomacs_display_set_minimap (
-    dpyinfo->display_handle, on, w);
+    dpyinfo->display_handle, on, w, lh);
+  return on ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-minimap-update-lines",
+       Fneomacs_minimap_update_lines,
+       Sneomacs_minimap_update_lines, 4, 5, 0,
+       doc: /* Update the minimap line summaries of BUFFER.
+Replace REMOVED lines starting at zero-based line FIRST with LINES, a
+vector of summaries, each a vector [START INDENT LENGTH COLOR]: START is
+the buffer position of the line, INDENT and LENGTH the columns of
+leading whitespace and of text after it, and COLOR the foreground as an
+integer 0xRRGGBB.  POS-DELTA, if non-nil, is added to the start of every
+line after the replaced ones, so an edit only needs to resend the lines
+it touched.  */)
+  (Lisp_Object buffer, Lisp_Object first, Lisp_Object removed,
+   Lisp_Object lines, Lisp_Object pos_delta)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  CHECK_BUFFER (buffer);
+  CHECK_FIXNAT (first);
+  CHECK_FIXNAT (removed);
+  CHECK_VECTOR (lines);
+
+  ptrdiff_t count = ASIZE (lines);
+  struct NeomacsMinimapLine *summary
+    = xnmalloc (count > 0 ? count : 1, sizeof *summary);
+  for (ptrdiff_t i = 0; i < count; i++)
+    {
+      Lisp_Object line = AREF (lines, i);
+      memset (&summary[i], 0, sizeof summary[i]);
+      if (!VECTORP (line) || ASIZE (line) < 4)
+        continue;
+      if (FIXNUMP (AREF (line, 0)))
+        summary[i].start = XFIXNUM (AREF (line, 0));
+      if (FIXNATP (AREF (line, 1)))
+        summary[i].indent = min (XFIXNAT (AREF (line, 1)), UINT16_MAX);
+      if (FIXNATP (AREF (line, 2)))
+        summary[i].length = min (XFIXNAT (AREF (line, 2)), UINT16_MAX);
+      if (FIXNATP (AREF (line, 3)))
+        summary[i].color = XFIXNAT (AREF (line, 3)) & 0xFFFFFF;
+    }
+
+  neomacs_display_minimap_update (
+    dpyinfo->display_handle,
+    (uint64_t)(uintptr_t) XBUFFER (buffer),
+    XFIXNAT (first), XFIXNAT (removed),
+    FIXNUMP (pos_delta) ? XFIXNUM (pos_delta) : 0,
+    summary, count);
+  xfree (summary);
+  return Qt;
+}
+
+DEFUN ("neomacs-minimap-forget",
+       Fneomacs_minimap_forget,
+       Sneomacs_minimap_forget, 1, 1, 0,
+       doc: /* Drop the minimap line summaries of BUFFER.
+The minimap of windows showing BUFFER falls back to the visible text.  */)
+  (Lisp_Object buffer)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  CHECK_BUFFER (buffer);
+  neomacs_display_minimap_forget (dpyinfo->display_handle,
+                                  (uint64_t)(uintptr_t) XBUFFER (buffer));
+  return Qnil;
+}
+
+/* Highlight categories, in the order of the display engine's
+   HighlightKind.  */
+static const char *const neomacs_highlight_kinds[] =
+  {
+    "keyword", "string", "comment", "function", "type", "constant",
+    "number", "variable", "property", "operator", "punctuation",
+    "attribute", "label", "escape", "constructor", "builtin",
+  };
+
+DEFUN ("neomacs-highlight-request",
+       Fneomacs_highlight_request,
+       Sneomacs_highlight_request, 2, 4, 0,
+       doc: /* Highlight the syntax of BUFFER on a background thread.
+LANGUAGE is a language name such as "rust", "c", "python",
+"javascript", "bash" or "json".  Optional BEG and END restrict the
+text, e.g. to one code block; they default to the accessible portion.
+The resulting spans color text that has no face of its own once
+`neomacs-highlight-poll' merges them.  Returns nil if LANGUAGE is not
+supported.  */)
+  (Lisp_Object buffer, Lisp_Object language, Lisp_Object beg, Lisp_Object end)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  CHECK_BUFFER (buffer);
+  CHECK_STRING (language);
+
+  specpdl_ref count = SPECPDL_INDEX ();
+  record_unwind_current_buffer ();
+  set_buffer_internal (XBUFFER (buffer));
+  if (NILP (beg))
+    beg = make_fixnum (BEGV);
+  if (NILP (end))
+    end = make_fixnum (ZV);
+  validate_region (&beg, &end);
+  Lisp_Object text
+    = ENCODE_UTF_8 (make_buffer_string (XFIXNUM (beg), XFIXNUM (end), false));
+
+  int queued = neomacs_display_highlight_request (
+    dpyinfo->display_handle,
+    (uint64_t)(uintptr_t) XBUFFER (buffer),
+    SSDATA (language),
+    SDATA (text), SBYTES (text),
+    XFIXNUM (beg));
+  return unbind_to (count, queued ? Qt : Qnil);
+}
+
+DEFUN ("neomacs-highlight-edit",
+       Fneomacs_highlight_edit,
+       Sneomacs_highlight_edit, 4, 4, 0,
+       doc: /* Tell the background highlighter that BUFFER changed.
+The text between BEG and OLD-END was replaced by text ending at
+NEW-END.  Spans after the change shift; spans touching it are dropped
+until the buffer is highlighted again.  */)
+  (Lisp_Object buffer, Lisp_Object beg, Lisp_Object old_end,
+   Lisp_Object new_end)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  CHECK_BUFFER (buffer);
+  CHECK_FIXNUM (beg);
+  CHECK_FIXNUM (old_end);
+  CHECK_FIXNUM (new_end);
+
+  neomacs_display_highlight_edit (dpyinfo->display_handle,
+                                  (uint64_t)(uintptr_t) XBUFFER (buffer),
+                                  XFIXNUM (beg), XFIXNUM (old_end),
+                                  XFIXNUM (new_end));
+  return Qt;
+}
+
+DEFUN ("neomacs-highlight-forget",
+       Fneomacs_highlight_forget,
+       Sneomacs_highlight_forget, 1, 1, 0,
+       doc: /* Drop the background syntax highlights of BUFFER.  */)
+  (Lisp_Object buffer)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  CHECK_BUFFER (buffer);
+  neomacs_display_highlight_forget (dpyinfo->display_handle,
+                                    (uint64_t)(uintptr_t) XBUFFER (buffer));
+  windows_or_buffers_changed = 1;
+  return Qt;
+}
+
+DEFUN ("neomacs-highlight-set-face",
+       Fneomacs_highlight_set_face,
+       Sneomacs_highlight_set_face, 2, 4, 0,
+       doc: /* Set how background highlighting draws category KIND.
+KIND is a symbol: `keyword', `string', `comment', `function', `type',
+`constant', `number', `variable', `property', `operator',
+`punctuation', `attribute', `label', `escape', `constructor' or
+`builtin'.  COLOR is a color string, or nil to leave KIND in the
+default face.  Non-nil BOLD and ITALIC change the weight and slant.  */)
+  (Lisp_Object kind, Lisp_Object color, Lisp_Object bold, Lisp_Object italic)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  CHECK_SYMBOL (kind);
+  int index = -1;
+  for (int i = 0; i < ARRAYELTS (neomacs_highlight_kinds); i++)
+    if (!strcmp (SSDATA (SYMBOL_NAME (kind)), neomacs_highlight_kinds[i]))
+      index = i;
+  if (index < 0)
+    error ("Unknown highlight category: %s", SSDATA (SYMBOL_NAME (kind)));
+
+  uint32_t rgb = 0;
+  if (!NILP (color))
+    {
+      CHECK_STRING (color);
+      Emacs_Color c;
+      if (!neomacs_defined_color (NULL, SSDATA (color), &c, false, false))
+        error ("Undefined color: %s", SSDATA (color));
+      rgb = ((uint32_t) (c.red >> 8) << 16)
+            | ((uint32_t) (c.green >> 8) << 8)
+            | (uint32_t) (c.blue >> 8);
+    }
+
+  neomacs_display_highlight_set_face (dpyinfo->display_handle, index,
+                                      !NILP (color), rgb,
+                                      !NILP (bold), !NILP (italic));
+  windows_or_buffers_changed = 1;
+  return Qt;
+}
+
+DEFUN ("neomacs-highlight-poll",
+       Fneomacs_highlight_poll,
+       Sneomacs_highlight_poll, 0, 0, 0,
+       doc: /* Merge finished background highlight jobs.
+Redisplays if any arrived.  Returns the number of jobs still pending.  */)
+  (void)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return make_fixnum (0);
+
+  int merged = 0;
+  int pending = neomacs_display_highlight_poll (dpyinfo->display_handle,
+                                                &merged);
+  if (merged > 0)
+    windows_or_buffers_changed = 1;
+  return make_fixnum (pending);
+}
+
+DEFUN ("neomacs-set-fallback-glyph-metrics",
+       Fneomacs_set_fallback_glyph_metrics,
+       Sneomacs_set_fallback_glyph_metrics, 2, 4, 0,
+       doc: /* Fit glyphs from fallback fonts to the text font of FACE.
+Color emoji and symbols drawn from another font than FACE's are resized
+to the height of FACE's font and moved onto its baseline.  FACE nil
+configures every face without its own setting.
+
+ENABLED non-nil fits the glyphs, nil draws them at their natural size,
+and `reset' makes FACE follow the setting for all faces again.
+SCALE (default 1.0) multiplies the fitted size.  BASELINE-OFFSET raises
+the glyphs by that many pixels (default 0).
+
+Face IDs belong to the selected frame, so call this again after the
+face is redefined.  */)
+  (Lisp_Object face, Lisp_Object enabled, Lisp_Object scale,
+   Lisp_Object baseline_offset)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int on = EQ (enabled, Qreset) ? -1 : !NILP (enabled);
+  float s = NILP (scale) ? 1.0f : (float) extract_float (scale);
+  float offset = NILP (baseline_offset)
+    ? 0.0f : (float) extract_float (baseline_offset);
+
+  if (NILP (face))
+    neomacs_display_set_fallback_metrics (dpyinfo->display_handle, -1,
+                                          on, s, offset);
+  else
+    {
+      CHECK_SYMBOL (face);
+      struct frame *f = SELECTED_FRAME ();
+      int face_id = lookup_named_face (NULL, f, face, false);
+      if (face_id < 0)
+        return Qnil;
+      struct face *base = FACE_FROM_ID (f, face_id);
+      /* Emoji and symbols get their own realized faces with the
+         fallback font; configure those along with FACE.  */
+      static const int samples[] = { 0, 0x1F600, 0x2605, 0x2713 };
+      for (int i = 0; i < ARRAYELTS (samples); i++)
+        {
+          int id = samples[i]
+            ? face_for_char (f, base, samples[i], -1, Qnil) : face_id;
+          if (i == 0 || id != face_id)
+            neomacs_display_set_fallback_metrics (dpyinfo->display_handle,
+                                                  id, on, s, offset);
+        }
+    }
+
+  windows_or_buffers_changed = 1;
+  return Qt;
+}
+
+DEFUN ("neomacs-set-backdrop-blur",
+       Fneomacs_set_backdrop_blur,
+       Sneomacs_set_backdrop_blur, 0, 3, 0,
+       doc: /* Configure blurring of the content behind floating layers.
+ENABLED non-nil blurs what shows through translucent floating terminals
+and other floating content.
+RADIUS is the blur radius in pixels (default 12).
+PASSES is the number of blur iterations, 1 to 3 (default 2); more passes
+give a smoother result at a higher GPU cost.  */)
+  (Lisp_Object enabled, Lisp_Object radius, Lisp_Object passes)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int on = !NILP (enabled);
+  int r = 12;
+  int p = 2;
+  if (FIXNUMP (radius))
+    r = XFIXNUM (radius);
+  if (FIXNUMP (passes))
+    p = XFIXNUM (passes);
+
+  neomacs_display_set_backdrop_blur (dpyinfo->display_handle, on, r, p);
+  windows_or_buffers_changed = 1;
   return on ? Qt : Qnil;
 }
 
@@ -9883,6 +12601,41 @@ OPACITY is 0-100 for glow intensity (default 30).  */)
   return on ? Qt : Qnil;
 }
 
+DEFUN ("neomacs-set-selection-runs",
+       Fneomacs_set_selection_runs,
+       Sneomacs_set_selection_runs, 0, 3, 0,
+       doc: /* Configure region highlight drawn as merged selection runs.
+ENABLED non-nil draws the highlighted cells of each line as one rectangle
+instead of one per character, with rounded ends; corners where wrapped
+lines meet stay square so the selection reads as a single shape.
+FACE-ID is the numeric face ID of the region face (auto-detected if nil).
+RADIUS is the corner radius in pixels (default 4, 0 for square runs).  */)
+  (Lisp_Object enabled, Lisp_Object face_id, Lisp_Object radius)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int on = !NILP (enabled);
+  int fid = 0;
+  int rad = 4;
+
+  if (FIXNUMP (face_id))
+    fid = XFIXNUM (face_id);
+  else if (on)
+    {
+      /* Auto-detect region face ID */
+      Lisp_Object region_sym = intern ("region");
+      fid = lookup_named_face (NULL, NULL, region_sym, false);
+      if (fid < 0) fid = 0;
+    }
+
+  if (FIXNUMP (radius)) rad = XFIXNUM (radius);
+
+  neomacs_display_set_selection_runs (dpyinfo->display_handle, on, fid, rad);
+  return on ? Qt : Qnil;
+}
+
 DEFUN ("neomacs-set-idle-dim",
        Fneomacs_set_idle_dim,
        Sneomacs_set_idle_dim, 0, 4, 0,
@@ -10253,6 +13006,69 @@ MAX-PADDING is the maximum extra padding in pixels (default 12).  */)
   return on ? Qt : Qnil;
 }
 
+DEFUN ("neomacs-set-text-gamma",
+       Fneomacs_set_text_gamma,
+       Sneomacs_set_text_gamma, 0, 3, 0,
+       doc: /* Configure gamma correction of text edges.
+Text is blended in linear light, which makes dark text on light
+backgrounds look thin.  ENABLED non-nil thickens antialiased edges of
+dark text to compensate; light text is unaffected.
+GAMMA is the coverage gamma applied to dark text (default 1.7; 1.0 means
+no adjustment).  CONTRAST is an extra coverage boost between 0.0 and
+1.0 (default 0.0).  */)
+  (Lisp_Object enabled, Lisp_Object gamma, Lisp_Object contrast)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int on = !NILP (enabled);
+  int g = 170;
+  int c = 0;
+  if (NUMBERP (gamma)) g = (int) (XFLOATINT (gamma) * 100.0);
+  if (NUMBERP (contrast)) c = (int) (XFLOATINT (contrast) * 100.0);
+
+  neomacs_display_set_text_gamma (dpyinfo->display_handle, on, g, c);
+  return on ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-set-cursor-inversion",
+       Fneomacs_set_cursor_inversion,
+       Sneomacs_set_cursor_inversion, 0, 2, 0,
+       doc: /* Configure text inversion under a filled box cursor.
+ENABLED non-nil re-renders the character under a box cursor in an
+inverse color, including while the cursor is animating between
+positions.  COMPLEMENT-COLOR is an RGB hex string used for that text;
+nil derives it from the face of the character under the cursor.  */)
+  (Lisp_Object enabled, Lisp_Object complement_color)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  int on = !NILP (enabled);
+  int has_color = 0;
+  int r = 0, g = 0, b = 0;
+
+  if (STRINGP (complement_color))
+    {
+      const char *s = SSDATA (complement_color);
+      if (s[0] == '#' && strlen (s) == 7)
+        {
+          unsigned int hex;
+          sscanf (s + 1, "%06x", &hex);
+          r = (hex >> 16) & 0xFF;
+          g = (hex >> 8) & 0xFF;
+          b = hex & 0xFF;
+          has_color = 1;
+        }
+    }
+
+  neomacs_display_set_cursor_inversion (dpyinfo->display_handle,
+                                        on, has_color, r, g, b);
+  return on ? Qt : Qnil;
+}
+
 DEFUN ("neomacs-set-cursor-error-pulse",
        Fneomacs_set_cursor_error_pulse,
        Sneomacs_set_cursor_error_pulse, 0, 3, 0,
@@ -14425,6 +17241,27 @@ Higher values make the cursor move faster.  */)
   return anim_enabled ? Qt : Qnil;
 }
 
+DEFUN ("neomacs-set-cursor-animation-mode", Fneomacs_set_cursor_animation_mode, Sneomacs_set_cursor_animation_mode, 1, 1, 0,
+       doc: /* Select the cursor effect shown while the cursor moves.
+MODE is a symbol or string: `none', `smooth', `railgun' (particles
+shoot backward), `torpedo' (comet trail), `pixiedust' (scattering
+sparkles), `sonicboom' (shockwave), `ripple' (expanding rings) or
+`wireframe' (glowing outline).  `none' and `smooth' show no effect;
+smooth motion itself is set with `neomacs-set-cursor-animation'.  */)
+  (Lisp_Object mode)
+{
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  if (SYMBOLP (mode))
+    mode = SYMBOL_NAME (mode);
+  CHECK_STRING (mode);
+  neomacs_display_set_cursor_animation_mode (dpyinfo->display_handle,
+                                             SSDATA (ENCODE_UTF_8 (mode)));
+  return Qt;
+}
+
 DEFUN ("neomacs-set-animation-config", Fneomacs_set_animation_config, Sneomacs_set_animation_config, 8, MANY, 0,
        doc: /* Configure all animation settings in the render thread.
 Arguments: CURSOR-ENABLED CURSOR-SPEED CURSOR-STYLE CURSOR-DURATION
@@ -14469,6 +17306,9 @@ SCROLL-EFFECT is a symbol (or integer index) selecting the scroll animation effe
   `crt-scanlines'        - retro scanline overlay
   `depth-of-field'       - center sharp, edges dim
   `typewriter-reveal'    - lines appear left-to-right
+  `slide-left'           - content slides out to the left
+  `slide-right'          - content slides out to the right
+  `push'                 - new content covers the old from the right
 SCROLL-EASING is a symbol (or integer index) selecting the scroll easing function:
   `ease-out-quad'        - standard deceleration (default)
   `ease-out-cubic'       - stronger deceleration
@@ -14575,6 +17415,9 @@ usage: (neomacs-set-animation-config CURSOR-ENABLED CURSOR-SPEED CURSOR-STYLE CU
       else if (EQ (scroll_effect, Qcrt_scanlines))        seff = 18;
       else if (EQ (scroll_effect, Qdepth_of_field))       seff = 19;
       else if (EQ (scroll_effect, Qtypewriter_reveal))    seff = 20;
+      else if (EQ (scroll_effect, Qslide_left))           seff = 21;
+      else if (EQ (scroll_effect, Qslide_right))          seff = 22;
+      else if (EQ (scroll_effect, Qpush))                 seff = 23;
     }
   else if (FIXNUMP (scroll_effect))
     seff = (uint32_t) XFIXNUM (scroll_effect);
@@ -14621,26 +17464,140 @@ usage: (neomacs-set-animation-config CURSOR-ENABLED CURSOR-SPEED CURSOR-STYLE CU
       else if (EQ (crossfade_effect, Qcrt_scanlines))        ceff = 18;
       else if (EQ (crossfade_effect, Qdepth_of_field))       ceff = 19;
       else if (EQ (crossfade_effect, Qtypewriter_reveal))    ceff = 20;
+      else if (EQ (crossfade_effect, Qslide_left))           ceff = 21;
+      else if (EQ (crossfade_effect, Qslide_right))          ceff = 22;
+      else if (EQ (crossfade_effect, Qpush))                 ceff = 23;
     }
   else if (FIXNUMP (crossfade_effect))
     ceff = (uint32_t) XFIXNUM (crossfade_effect);
 
-  /* Map symbol or integer to crossfade easing ID (same enum as scroll easing) */
-  uint32_t ceas = 0;  /* default: ease-out-quad */
-  if (SYMBOLP (crossfade_easing))
+  /* Map symbol or integer to crossfade easing ID (same enum as scroll easing) */
+  uint32_t ceas = 0;  /* default: ease-out-quad */
+  if (SYMBOLP (crossfade_easing))
+    {
+      if (EQ (crossfade_easing, Qease_out_quad))           ceas = 0;
+      else if (EQ (crossfade_easing, Qease_out_cubic))     ceas = 1;
+      else if (EQ (crossfade_easing, Qspring))              ceas = 2;
+      else if (EQ (crossfade_easing, Qlinear))              ceas = 3;
+      else if (EQ (crossfade_easing, Qease_in_out_cubic))  ceas = 4;
+    }
+  else if (FIXNUMP (crossfade_easing))
+    ceas = (uint32_t) XFIXNUM (crossfade_easing);
+
+  neomacs_display_set_animation_config (dpyinfo->display_handle,
+                                         ce, cs, cst, cd, cfe, cfd, se, sd,
+                                         seff, seas, ts, ceff, ceas);
+  return Qt;
+}
+
+DEFUN ("neomacs-set-animation-curve", Fneomacs_set_animation_curve,
+       Sneomacs_set_animation_curve, 2, 2, 0,
+       doc: /* Define the keyframe animation curve NAME from CURVE.
+NAME is a string or symbol.  The curves `cursor', `cursor-size',
+`crossfade' and `scroll' replace the easing of those animations.
+
+CURVE is a string of space-separated TIME:VALUE[:EASING] keyframes over
+normalized time 0..1, e.g. "0:0 0.7:1.1:ease-out 1:1:ease-in-out" for
+an overshoot; EASING shapes the segment ending at that keyframe.  It may
+also be a single easing name such as "ease-out-cubic", or a list of
+\(TIME VALUE [EASING]) lists.  Easing names are linear, ease-in,
+ease-out, ease-in-out, ease-in-cubic, ease-out-cubic, ease-in-out-cubic
+and step.  nil removes the curve.  */)
+  (Lisp_Object name, Lisp_Object curve)
+{
+  if (SYMBOLP (name))
+    name = SYMBOL_NAME (name);
+  CHECK_STRING (name);
+
+  Lisp_Object spec = curve;
+  if (CONSP (curve))
+    {
+      /* Build the string form from (TIME VALUE [EASING]) keyframes.  */
+      spec = empty_unibyte_string;
+      for (Lisp_Object tail = curve; CONSP (tail); tail = XCDR (tail))
+        {
+          Lisp_Object kf = XCAR (tail);
+          CHECK_CONS (kf);
+          Lisp_Object easing = Fnth (make_fixnum (2), kf);
+          Lisp_Object token
+            = CALLN (Fformat, build_string ("%s:%s%s%s "),
+                     Fnth (make_fixnum (0), kf), Fnth (make_fixnum (1), kf),
+                     build_string (NILP (easing) ? "" : ":"),
+                     NILP (easing) ? empty_unibyte_string : easing);
+          spec = concat2 (spec, token);
+        }
+    }
+  else if (!NILP (curve))
+    {
+      if (SYMBOLP (curve))
+        spec = SYMBOL_NAME (curve);
+      CHECK_STRING (spec);
+    }
+
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  if (neomacs_display_set_animation_curve (dpyinfo->display_handle,
+                                           SSDATA (name),
+                                           NILP (spec) ? NULL : SSDATA (spec)) < 0)
+    error ("Invalid animation curve: %s", NILP (spec) ? "" : SSDATA (spec));
+  return Qt;
+}
+
+DEFUN ("neomacs-animate-floating", Fneomacs_animate_floating,
+       Sneomacs_animate_floating, 5, 7, 0,
+       doc: /* Animate PROPERTY of a floating element toward TO.
+KIND is `image', `webkit' or `terminal' and ID identifies the element.
+PROPERTY is one of `x', `y', `width', `height', `opacity' or
+`corner-radius'; terminals only support `x', `y' and `opacity'.
+DURATION is in milliseconds; 0 sets the value immediately.
+EASING is an easing name such as `ease-in-out-cubic' (default `ease-out').
+A new animation of the same property replaces the running one.
+Optional NOTIFY-ID, a positive integer, is reported through the
+`animation-completed' event of `neomacs-display-event-functions' when
+the animation ends, replaced or not.  */)
+  (Lisp_Object kind, Lisp_Object id, Lisp_Object property, Lisp_Object to,
+   Lisp_Object duration, Lisp_Object easing, Lisp_Object notify_id)
+{
+  int kind_code;
+  CHECK_SYMBOL (kind);
+  if (EQ (kind, intern ("image")))
+    kind_code = 0;
+  else if (EQ (kind, intern ("webkit")))
+    kind_code = 1;
+  else if (EQ (kind, intern ("terminal")))
+    kind_code = 2;
+  else
+    error ("Unknown floating element kind");
+
+  CHECK_FIXNAT (id);
+  if (SYMBOLP (property))
+    property = SYMBOL_NAME (property);
+  CHECK_STRING (property);
+  CHECK_NUMBER (to);
+  CHECK_FIXNAT (duration);
+  if (!NILP (easing))
     {
-      if (EQ (crossfade_easing, Qease_out_quad))           ceas = 0;
-      else if (EQ (crossfade_easing, Qease_out_cubic))     ceas = 1;
-      else if (EQ (crossfade_easing, Qspring))              ceas = 2;
-      else if (EQ (crossfade_easing, Qlinear))              ceas = 3;
-      else if (EQ (crossfade_easing, Qease_in_out_cubic))  ceas = 4;
+      if (SYMBOLP (easing))
+        easing = SYMBOL_NAME (easing);
+      CHECK_STRING (easing);
     }
-  else if (FIXNUMP (crossfade_easing))
-    ceas = (uint32_t) XFIXNUM (crossfade_easing);
+  if (!NILP (notify_id))
+    CHECK_FIXNAT (notify_id);
 
-  neomacs_display_set_animation_config (dpyinfo->display_handle,
-                                         ce, cs, cst, cd, cfe, cfd, se, sd,
-                                         seff, seas, ts, ceff, ceas);
+  struct neomacs_display_info *dpyinfo = neomacs_display_list;
+  if (!dpyinfo || !dpyinfo->display_handle)
+    return Qnil;
+
+  if (neomacs_display_animate_floating (dpyinfo->display_handle, kind_code,
+                                        (uint32_t) XFIXNAT (id),
+                                        SSDATA (property),
+                                        (float) XFLOATINT (to),
+                                        (uint32_t) XFIXNAT (duration),
+                                        NILP (easing) ? NULL : SSDATA (easing),
+                                        NILP (notify_id) ? 0 : (uint32_t) XFIXNAT (notify_id)) < 0)
+    error ("Invalid floating animation: %s", SSDATA (property));
   return Qt;
 }
 
@@ -14679,20 +17636,146 @@ Returns terminal ID on success, nil on failure.  */)
   return make_fixnum (id);
 }
 
+DEFUN ("neomacs-terminal-create-ssh", Fneomacs_terminal_create_ssh, Sneomacs_terminal_create_ssh, 4, 6, 0,
+       doc: /* Create a terminal with COLS columns and ROWS rows on an SSH host.
+MODE is 0 for Window, 1 for Inline, 2 for Floating.  DESTINATION is a
+string "[USER@]HOST[:PORT]".  Optional COMMAND is run instead of the
+login shell.  Optional IDENTITY is a private key file, tried after the
+SSH agent and before the default keys.  HOST must already be in
+~/.ssh/known_hosts.
+
+The connection is made in the background; if it fails, the terminal
+exits and the error is reported.  Returns terminal ID on success, nil
+on failure.  */)
+  (Lisp_Object cols, Lisp_Object rows, Lisp_Object mode,
+   Lisp_Object destination, Lisp_Object command, Lisp_Object identity)
+{
+  CHECK_FIXNUM (cols);
+  CHECK_FIXNUM (rows);
+  CHECK_FIXNUM (mode);
+  CHECK_STRING (destination);
+
+  const char *command_str = NULL;
+  if (!NILP (command))
+    {
+      CHECK_STRING (command);
+      command_str = SSDATA (ENCODE_UTF_8 (command));
+    }
+  const char *identity_str = NULL;
+  if (!NILP (identity))
+    {
+      CHECK_STRING (identity);
+      identity_str = SSDATA (ENCODE_FILE (Fexpand_file_name (identity, Qnil)));
+    }
+
+  uint32_t id = neomacs_display_terminal_create_ssh (
+    (uint16_t) XFIXNUM (cols),
+    (uint16_t) XFIXNUM (rows),
+    (uint8_t) XFIXNUM (mode),
+    SSDATA (ENCODE_UTF_8 (destination)),
+    command_str,
+    identity_str);
+
+  if (id == 0)
+    return Qnil;
+
+  return make_fixnum (id);
+}
+
 DEFUN ("neomacs-terminal-write", Fneomacs_terminal_write, Sneomacs_terminal_write, 2, 2, 0,
        doc: /* Write STRING to terminal TERMINAL-ID.
-STRING is sent as keyboard input to the terminal's PTY.  */)
+STRING is sent as keyboard input to the terminal's PTY.  The write never
+blocks: input is queued and fed to the PTY in the background.  Returns t
+if STRING was queued, or nil if the terminal already has too much input
+queued; see `neomacs-terminal-input-status'.  */)
   (Lisp_Object terminal_id, Lisp_Object string)
 {
   CHECK_FIXNUM (terminal_id);
   CHECK_STRING (string);
 
-  neomacs_display_terminal_write (
+  int result = neomacs_display_terminal_write (
     (uint32_t) XFIXNUM (terminal_id),
     (const uint8_t *) SDATA (string),
     SBYTES (string));
 
-  return Qt;
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-terminal-write-many", Fneomacs_terminal_write_many, Sneomacs_terminal_write_many, 2, 2, 0,
+       doc: /* Write the strings in list STRINGS to terminal TERMINAL-ID.
+Like `neomacs-terminal-write', but queues all of STRINGS in one batch:
+either all are queued and t is returned, or none and the value is nil.  */)
+  (Lisp_Object terminal_id, Lisp_Object strings)
+{
+  CHECK_FIXNUM (terminal_id);
+  CHECK_LIST (strings);
+
+  ptrdiff_t count = list_length (strings);
+  if (count == 0)
+    return Qt;
+
+  USE_SAFE_ALLOCA;
+  const uint8_t **chunks;
+  size_t *lens;
+  SAFE_NALLOCA (chunks, 1, count);
+  SAFE_NALLOCA (lens, 1, count);
+  ptrdiff_t i = 0;
+  for (Lisp_Object tail = strings; CONSP (tail); tail = XCDR (tail), i++)
+    {
+      Lisp_Object string = XCAR (tail);
+      CHECK_STRING (string);
+      chunks[i] = SDATA (string);
+      lens[i] = SBYTES (string);
+    }
+
+  int result = neomacs_display_terminal_write_many (
+    (uint32_t) XFIXNUM (terminal_id), chunks, lens, count);
+  SAFE_FREE ();
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-terminal-input-status", Fneomacs_terminal_input_status, Sneomacs_terminal_input_status, 1, 1, 0,
+       doc: /* Return the input queue state of terminal TERMINAL-ID.
+The value is a plist with the keys :pending (bytes queued but not yet
+written to the PTY), :limit (the queue size above which writes are
+refused) and :written (bytes written so far), or nil if the terminal is
+not running.  */)
+  (Lisp_Object terminal_id)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  struct NeomacsTerminalInputStatus status;
+  int result = neomacs_display_terminal_input_status (
+    (uint32_t) XFIXNUM (terminal_id), &status);
+
+  neomacs_check_handle (result, terminal_id);
+  if (result != 0)
+    return Qnil;
+  return list (intern (":pending"), make_uint (status.pending),
+               intern (":limit"), make_uint (status.limit),
+               intern (":written"), make_uint (status.written));
+}
+
+DEFUN ("neomacs-terminal-cancel-input", Fneomacs_terminal_cancel_input, Sneomacs_terminal_cancel_input, 1, 1, 0,
+       doc: /* Drop input queued for terminal TERMINAL-ID but not yet written.
+Use this to abort a large paste into a program that reads slowly.
+Returns the number of bytes dropped, or nil if the terminal is not
+running.  */)
+  (Lisp_Object terminal_id)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  int64_t result = neomacs_display_terminal_cancel_input (
+    (uint32_t) XFIXNUM (terminal_id));
+
+  if (result == NEOMACS_STALE_HANDLE)
+    xsignal1 (Qneomacs_stale_handle, terminal_id);
+  if (result < 0)
+    return Qnil;
+  return make_int (result);
 }
 
 DEFUN ("neomacs-terminal-resize", Fneomacs_terminal_resize, Sneomacs_terminal_resize, 3, 3, 0,
@@ -14722,6 +17805,36 @@ DEFUN ("neomacs-terminal-destroy", Fneomacs_terminal_destroy, Sneomacs_terminal_
   return Qt;
 }
 
+DEFUN ("neomacs-terminal-detach", Fneomacs_terminal_detach, Sneomacs_terminal_detach, 1, 1, 0,
+       doc: /* Stop drawing terminal TERMINAL-ID without killing it.
+The shell, its screen and scrollback stay alive and the terminal still
+accepts input, so it can be shown again with `neomacs-terminal-attach',
+for example after the frame showing it was closed.  */)
+  (Lisp_Object terminal_id)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  int result = neomacs_display_terminal_detach ((uint32_t) XFIXNUM (terminal_id));
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-terminal-attach", Fneomacs_terminal_attach, Sneomacs_terminal_attach, 2, 2, 0,
+       doc: /* Show detached terminal TERMINAL-ID again in MODE.
+MODE is 0 for Window, 1 for Inline, 2 for Floating.  */)
+  (Lisp_Object terminal_id, Lisp_Object mode)
+{
+  CHECK_FIXNUM (terminal_id);
+  CHECK_FIXNUM (mode);
+
+  int result = neomacs_display_terminal_attach ((uint32_t) XFIXNUM (terminal_id),
+                                                (uint8_t) XFIXNUM (mode));
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
 DEFUN ("neomacs-terminal-set-float", Fneomacs_terminal_set_float, Sneomacs_terminal_set_float, 4, 4, 0,
        doc: /* Set floating position and opacity for terminal TERMINAL-ID.
 X and Y are the screen coordinates, OPACITY is 0.0 to 1.0.  */)
@@ -14741,6 +17854,216 @@ X and Y are the screen coordinates, OPACITY is 0.0 to 1.0.  */)
   return Qt;
 }
 
+DEFUN ("neomacs-terminal-set-font", Fneomacs_terminal_set_font, Sneomacs_terminal_set_font, 3, 3, 0,
+       doc: /* Set the font of terminal TERMINAL-ID to FAMILY at SIZE pixels.
+FAMILY nil uses the frame's font family and SIZE nil its size, so
+with both nil the terminal follows the frame font again.  The cell
+size is measured from the font, and programs in the terminal are
+told the new pixel size.  */)
+  (Lisp_Object terminal_id, Lisp_Object family, Lisp_Object size)
+{
+  CHECK_FIXNUM (terminal_id);
+  if (!NILP (family))
+    CHECK_STRING (family);
+  if (!NILP (size))
+    CHECK_NUMBER (size);
+
+  int result = neomacs_display_terminal_set_font (
+    (uint32_t) XFIXNUM (terminal_id),
+    NILP (family) ? NULL : SSDATA (family),
+    NILP (size) ? 0.0f : (float) XFLOATINT (size));
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-terminal-set-background", Fneomacs_terminal_set_background, Sneomacs_terminal_set_background, 2, 5, 0,
+       doc: /* Set what is drawn behind the cells of terminal TERMINAL-ID.
+STYLE nil draws the theme background color.
+
+STYLE `image' draws image ARG, an ID from `neomacs-image-load',
+scaled to cover the terminal.  The background color is drawn over it
+with opacity AMOUNT (default 0.7) so text stays readable.
+
+STYLE `acrylic' lets the editor show through the background color,
+which is drawn with opacity AMOUNT (default 0.8).  If BLUR is non-nil
+what shows through is blurred.  ARG is ignored.  */)
+  (Lisp_Object terminal_id, Lisp_Object style, Lisp_Object arg,
+   Lisp_Object amount, Lisp_Object blur)
+{
+  CHECK_FIXNUM (terminal_id);
+  if (!NILP (amount))
+    CHECK_NUMBER (amount);
+
+  int kind;
+  uint32_t image_id = 0;
+  float value;
+  if (NILP (style))
+    {
+      kind = 0;
+      value = 1.0f;
+    }
+  else if (EQ (style, Qimage))
+    {
+      CHECK_FIXNAT (arg);
+      kind = 1;
+      image_id = (uint32_t) XFIXNAT (arg);
+      value = NILP (amount) ? 0.7f : (float) XFLOATINT (amount);
+    }
+  else if (EQ (style, Qacrylic))
+    {
+      kind = 2;
+      value = NILP (amount) ? 0.8f : (float) XFLOATINT (amount);
+    }
+  else
+    error ("Unknown terminal background style");
+
+  int result = neomacs_display_terminal_set_background (
+    (uint32_t) XFIXNUM (terminal_id), kind, image_id, value, !NILP (blur));
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-terminal-changed-lines", Fneomacs_terminal_changed_lines, Sneomacs_terminal_changed_lines, 1, 2, 0,
+       doc: /* Return the screen lines of terminal TERMINAL-ID changed since the last call.
+The value is (ROWS . CHANGES), where ROWS is the number of screen lines
+and CHANGES a list of (ROW . TEXT) for each line that changed, ROW
+counting from 0 at the top.  TEXT has SGR escape sequences for colors
+and attributes, as `ansi-color-apply' expects.  With ALL non-nil, every
+line is returned.  Returns nil if the terminal is not found.  */)
+  (Lisp_Object terminal_id, Lisp_Object all)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  char *text = neomacs_display_terminal_changed_lines ((uint32_t) XFIXNUM (terminal_id),
+                                                       !NILP (all));
+  if (!text)
+    return Qnil;
+
+  char *p = text;
+  char *end;
+  EMACS_INT rows = strtol (p, &end, 10);
+  p = *end == '\n' ? end + 1 : end;
+
+  Lisp_Object changes = Qnil;
+  while (*p)
+    {
+      EMACS_INT row = strtol (p, &end, 10);
+      if (*end != '\t')
+        break;
+      char *line = end + 1;
+      char *eol = strchr (line, '\n');
+      if (!eol)
+        eol = line + strlen (line);
+      changes = Fcons (Fcons (make_fixnum (row),
+                              make_string (line, eol - line)),
+                       changes);
+      p = *eol ? eol + 1 : eol;
+    }
+  free (text);
+
+  return Fcons (make_fixnum (rows), Fnreverse (changes));
+}
+
+DEFUN ("neomacs-terminal-input-region", Fneomacs_terminal_input_region, Sneomacs_terminal_input_region, 1, 1, 0,
+       doc: /* Return the input being typed at the prompt of terminal TERMINAL-ID.
+The value is (ROW COL CURSOR TEXT): input starts at screen ROW (negative
+in scrollback) and column COL, TEXT is the input with wrapped rows
+joined, and CURSOR is the cursor's position in TEXT.  Returns nil if
+the shell is not known to be reading input.
+
+Shells that mark their prompts with OSC 133 are tracked exactly; for
+others see `neomacs-terminal-set-prompt-heuristics'.  */)
+  (Lisp_Object terminal_id)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  char *text = neomacs_display_terminal_input_region ((uint32_t) XFIXNUM (terminal_id));
+  if (!text)
+    return Qnil;
+
+  char *end;
+  EMACS_INT row = strtol (text, &end, 10);
+  EMACS_INT col = strtol (end, &end, 10);
+  EMACS_INT cursor = strtol (end, &end, 10);
+  char *input = *end == '\n' ? end + 1 : end;
+  Lisp_Object result = list4 (make_fixnum (row), make_fixnum (col),
+                              make_fixnum (cursor), build_string (input));
+  free (text);
+  return result;
+}
+
+DEFUN ("neomacs-terminal-set-local-echo", Fneomacs_terminal_set_local_echo, Sneomacs_terminal_set_local_echo, 2, 2, 0,
+       doc: /* Set whether terminal TERMINAL-ID guesses the echo of typed input.
+With MODE non-nil, printable characters typed at the end of the line
+are drawn at once, underlined, until the program's echo replaces them;
+guesses the echo contradicts are taken back.  MODE `auto' does this
+only while the echo takes over 30ms to come back.  nil turns it off.  */)
+  (Lisp_Object terminal_id, Lisp_Object mode)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  int value = NILP (mode) ? 0 : EQ (mode, intern ("auto")) ? 2 : 1;
+  int result = neomacs_display_terminal_set_local_echo (
+    (uint32_t) XFIXNUM (terminal_id), value);
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-terminal-set-prompt-heuristics", Fneomacs_terminal_set_prompt_heuristics, Sneomacs_terminal_set_prompt_heuristics, 2, 2, 0,
+       doc: /* Make terminal TERMINAL-ID guess its prompts if ON is non-nil.
+For shells that send no OSC 133 marks, the first "$ ", "# ", "> " or
+similar on the cursor line is taken as the end of the prompt.  */)
+  (Lisp_Object terminal_id, Lisp_Object on)
+{
+  CHECK_FIXNUM (terminal_id);
+
+  int result = neomacs_display_terminal_set_prompt_heuristics (
+    (uint32_t) XFIXNUM (terminal_id), !NILP (on));
+
+  neomacs_check_handle (result, terminal_id);
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-tmux-connect", Fneomacs_tmux_connect, Sneomacs_tmux_connect, 1, 1, 0,
+       doc: /* Run COMMAND and attach to the tmux it starts in control mode.
+COMMAND is run by the shell and must start tmux with -C, for example
+"tmux -C new-session -A -s main" or "ssh host tmux -C attach".  Each
+tmux pane gets a terminal of its own; `neo-term--tmux-pane-added' is
+called for each.  Returns the client id, or nil on failure.  */)
+  (Lisp_Object command)
+{
+  CHECK_STRING (command);
+
+  uint32_t id = neomacs_display_tmux_connect (SSDATA (ENCODE_UTF_8 (command)));
+  return id ? make_fixnum (id) : Qnil;
+}
+
+DEFUN ("neomacs-tmux-command", Fneomacs_tmux_command, Sneomacs_tmux_command, 2, 2, 0,
+       doc: /* Send COMMAND, a tmux command line, to tmux client CLIENT.  */)
+  (Lisp_Object client, Lisp_Object command)
+{
+  CHECK_FIXNUM (client);
+  CHECK_STRING (command);
+
+  int result = neomacs_display_tmux_command ((uint32_t) XFIXNUM (client),
+                                             SSDATA (ENCODE_UTF_8 (command)));
+  return result == 0 ? Qt : Qnil;
+}
+
+DEFUN ("neomacs-tmux-disconnect", Fneomacs_tmux_disconnect, Sneomacs_tmux_disconnect, 1, 1, 0,
+       doc: /* Detach tmux client CLIENT, leaving the tmux session running.
+The terminals of its panes are closed.  */)
+  (Lisp_Object client)
+{
+  CHECK_FIXNUM (client);
+
+  int result = neomacs_display_tmux_disconnect ((uint32_t) XFIXNUM (client));
+  return result == 0 ? Qt : Qnil;
+}
+
 DEFUN ("neomacs-terminal-get-text", Fneomacs_terminal_get_text, Sneomacs_terminal_get_text, 1, 1, 0,
        doc: /* Get visible text from terminal TERMINAL-ID.
 Returns a string, or nil if the terminal is not found.  */)
@@ -14939,6 +18262,105 @@ neomacs_event_target_frame (uint32_t window_id)
   return NULL;
 }
 
+/* Scroll windows from their minimap strip.  Pressing button 1 in the
+   strip starts a drag; the press and every following motion scroll the
+   window so its viewport indicator is centered under the pointer.
+   Return true if EV was consumed.  */
+static bool
+neomacs_minimap_mouse (struct frame *f, struct NeomacsInputEvent *ev)
+{
+  struct neomacs_display_info *dpyinfo = FRAME_NEOMACS_DISPLAY_INFO (f);
+  Lisp_Object window;
+
+  if (ev->kind == NEOMACS_EVENT_MOUSE_RELEASE)
+    {
+      bool dragging = !NILP (neomacs_minimap_drag_window);
+      neomacs_minimap_drag_window = Qnil;
+      return dragging && ev->button == 1;
+    }
+
+  if (ev->kind == NEOMACS_EVENT_MOUSE_MOVE)
+    window = neomacs_minimap_drag_window;
+  else if (neomacs_minimap_width > 0 && ev->button == 1)
+    window = window_from_coordinates (f, ev->x, ev->y, 0,
+                                      false, false, false);
+  else
+    return false;
+
+  if (!WINDOW_LIVE_P (window) || !dpyinfo || !dpyinfo->display_handle)
+    {
+      neomacs_minimap_drag_window = Qnil;
+      return false;
+    }
+
+  struct window *w = XWINDOW (window);
+  if (MINI_WINDOW_P (w) || !BUFFERP (w->contents))
+    return false;
+
+  int left = WINDOW_LEFT_EDGE_X (w) + WINDOW_PIXEL_WIDTH (w)
+             - neomacs_minimap_width;
+  int top = WINDOW_TOP_EDGE_Y (w);
+  int height = WINDOW_PIXEL_HEIGHT (w) - WINDOW_MODE_LINE_HEIGHT (w);
+  if (height <= 0)
+    return false;
+  if (ev->kind == NEOMACS_EVENT_MOUSE_PRESS
+      && (ev->x < left || ev->y >= top + height))
+    return false;
+
+  struct buffer *buf = XBUFFER (w->contents);
+  int view_lines = height / max (FRAME_LINE_HEIGHT (f), 1);
+  int64_t pos = neomacs_display_minimap_target (
+    dpyinfo->display_handle, (uint64_t)(uintptr_t) buf, BUF_Z (buf),
+    (float) (ev->y - top), (float) height, view_lines,
+    (float) neomacs_minimap_line_height);
+  if (pos > 0)
+    {
+      Fset_window_start (window, make_fixnum (pos), Qnil);
+      windows_or_buffers_changed = 1;
+    }
+
+  if (ev->kind == NEOMACS_EVENT_MOUSE_PRESS)
+    neomacs_minimap_drag_window = window;
+  return true;
+}
+
+/* Run `neomacs-display-event-functions' with KIND, a symbol naming a
+   display engine event, ID, the terminal, video or view number or the
+   window it concerns, and ARG.  */
+static void
+neomacs_run_display_event (const char *kind, Lisp_Object id, Lisp_Object arg)
+{
+  Lisp_Object hook = intern ("neomacs-display-event-functions");
+  if (!NILP (Fboundp (hook)) && !NILP (Fsymbol_value (hook)))
+    safe_calln (intern ("run-hook-with-args"), hook, intern (kind), id, arg);
+}
+
+/* Return the text of the pending event of KIND for ID as a Lisp string,
+   or nil.  */
+static Lisp_Object
+neomacs_event_text (uint32_t kind, uint32_t id)
+{
+  char *text = neomacs_display_get_event_text (kind, id);
+  if (!text)
+    return Qnil;
+  Lisp_Object result = build_string (text);
+  neomacs_display_free_dropped_path (text);
+  return result;
+}
+
+/* Return the live window of frame F whose address is PTR, or nil.  */
+static Lisp_Object
+neomacs_window_from_ptr (struct frame *f, uint64_t ptr)
+{
+  Lisp_Object frame;
+  XSETFRAME (frame, f);
+  for (Lisp_Object tail = Fwindow_list (frame, Qt, Qnil);
+       CONSP (tail); tail = XCDR (tail))
+    if ((uint64_t) (intptr_t) XWINDOW (XCAR (tail)) == ptr)
+      return XCAR (tail);
+  return Qnil;
+}
+
 /* Handler called when wakeup_fd is readable */
 static void
 neomacs_display_wakeup_handler (int fd, void *data)
@@ -14983,6 +18405,8 @@ neomacs_display_wakeup_handler (int fd, void *data)
 
         case NEOMACS_EVENT_MOUSE_PRESS:
         case NEOMACS_EVENT_MOUSE_RELEASE:
+          if (neomacs_minimap_mouse (f, ev))
+            break;
           {
             /* Check if click is on a webkit view (floating or inline) */
             struct neomacs_display_info *dpyinfo = FRAME_NEOMACS_DISPLAY_INFO (f);
@@ -15225,6 +18649,11 @@ neomacs_display_wakeup_handler (int fd, void *data)
                 dpyinfo->last_mouse_motion_y = ev->y;
               }
 
+            /* Check if we're dragging a minimap viewport */
+            if (!NILP (neomacs_minimap_drag_window)
+                && neomacs_minimap_mouse (f, ev))
+              break;
+
             /* Check if we're dragging a scroll bar thumb */
             {
               bool scroll_drag_handled = false;
@@ -15383,9 +18812,73 @@ neomacs_display_wakeup_handler (int fd, void *data)
             Lisp_Object handler = intern ("neo-term--handle-exit");
             if (!NILP (Ffboundp (handler)))
               safe_calln (Fsymbol_function (handler), make_fixnum (ev->keysym));
+            neomacs_run_display_event ("terminal-exited",
+                                       make_fixnum (ev->keysym), Qnil);
+          }
+          break;
+
+        case NEOMACS_EVENT_TERMINAL_BELL:
+          {
+            Lisp_Object handler = intern ("neo-term--handle-bell");
+            if (!NILP (Ffboundp (handler)))
+              safe_calln (Fsymbol_function (handler), make_fixnum (ev->keysym));
+            neomacs_run_display_event ("terminal-bell",
+                                       make_fixnum (ev->keysym), Qnil);
+          }
+          break;
+
+        case NEOMACS_EVENT_TMUX_PANE_ADDED:
+          {
+            /* keysym: terminal, modifiers: client, x: pane, y: window,
+               width/height: columns and rows.  */
+            Lisp_Object handler = intern ("neo-term--tmux-pane-added");
+            if (!NILP (Ffboundp (handler)))
+              safe_calln (Fsymbol_function (handler),
+                          make_fixnum (ev->modifiers), make_fixnum (ev->x),
+                          make_fixnum (ev->y), make_fixnum (ev->keysym),
+                          make_fixnum (ev->width), make_fixnum (ev->height));
+            neomacs_run_display_event ("tmux-pane-added",
+                                       make_fixnum (ev->keysym),
+                                       make_fixnum (ev->modifiers));
+          }
+          break;
+
+        case NEOMACS_EVENT_TMUX_PANE_CLOSED:
+          {
+            Lisp_Object handler = intern ("neo-term--tmux-pane-closed");
+            if (!NILP (Ffboundp (handler)))
+              safe_calln (Fsymbol_function (handler), make_fixnum (ev->keysym));
+            neomacs_run_display_event ("tmux-pane-closed",
+                                       make_fixnum (ev->keysym),
+                                       make_fixnum (ev->modifiers));
+          }
+          break;
+
+        case NEOMACS_EVENT_TMUX_EXITED:
+          {
+            Lisp_Object handler = intern ("neo-term--tmux-exited");
+            if (!NILP (Ffboundp (handler)))
+              safe_calln (Fsymbol_function (handler), make_fixnum (ev->keysym));
+            neomacs_run_display_event ("tmux-exited",
+                                       make_fixnum (ev->keysym), Qnil);
           }
           break;
 
+        case NEOMACS_EVENT_ANIMATION_COMPLETED:
+          neomacs_run_display_event ("animation-completed",
+                                     make_fixnum (ev->keysym), Qnil);
+          break;
+
+        case NEOMACS_EVENT_PIP_CLOSED:
+          neomacs_run_display_event ("pip-closed",
+                                     make_fixnum (ev->keysym), Qnil);
+          break;
+
+        case NEOMACS_EVENT_AUDIO_LEVELS:
+          neomacs_run_display_event ("audio-levels",
+                                     make_fixnum (ev->keysym), Qnil);
+          break;
+
         case NEOMACS_EVENT_FILE_DROP:
           {
             /* Retrieve dropped file paths from Rust */
@@ -15421,21 +18914,69 @@ neomacs_display_wakeup_handler (int fd, void *data)
         case NEOMACS_EVENT_TERMINAL_TITLE_CHANGED:
           {
             uint32_t term_id = ev->keysym;
-            char *title
-              = neomacs_display_get_terminal_title (term_id);
-            if (title)
+            Lisp_Object title
+              = neomacs_event_text (NEOMACS_EVENT_TERMINAL_TITLE_CHANGED,
+                                    term_id);
+            if (!NILP (title))
               {
                 Lisp_Object handler
                   = intern ("neo-term--handle-title-changed");
                 if (!NILP (Ffboundp (handler)))
                   safe_calln (Fsymbol_function (handler),
-                              make_fixnum (term_id),
-                              build_string (title));
-                neomacs_display_free_dropped_path (title);
+                              make_fixnum (term_id), title);
+                neomacs_run_display_event ("terminal-title-changed",
+                                           make_fixnum (term_id), title);
               }
           }
           break;
 
+        case NEOMACS_EVENT_VIDEO_ENDED:
+          neomacs_run_display_event ("video-ended",
+                                     make_fixnum (ev->keysym), Qnil);
+          break;
+
+        case NEOMACS_EVENT_WEBKIT_TITLE_CHANGED:
+          neomacs_run_display_event
+            ("webkit-title-changed", make_fixnum (ev->keysym),
+             neomacs_event_text (NEOMACS_EVENT_WEBKIT_TITLE_CHANGED,
+                                 ev->keysym));
+          break;
+
+        case NEOMACS_EVENT_WEBKIT_URL_CHANGED:
+          neomacs_run_display_event
+            ("webkit-url-changed", make_fixnum (ev->keysym),
+             neomacs_event_text (NEOMACS_EVENT_WEBKIT_URL_CHANGED,
+                                 ev->keysym));
+          break;
+
+        case NEOMACS_EVENT_WEBKIT_PROGRESS_CHANGED:
+          neomacs_run_display_event ("webkit-progress-changed",
+                                     make_fixnum (ev->keysym),
+                                     make_fixnum (ev->x));
+          break;
+
+        case NEOMACS_EVENT_WEBKIT_LOAD_FINISHED:
+          neomacs_run_display_event ("webkit-load-finished",
+                                     make_fixnum (ev->keysym), Qnil);
+          break;
+
+        case NEOMACS_EVENT_ANIMATION_FINISHED:
+          neomacs_run_display_event
+            (ev->button ? "scroll-animation-finished"
+                        : "buffer-transition-finished",
+             neomacs_window_from_ptr (f, ev->timestamp), Qnil);
+          break;
+
+        case NEOMACS_EVENT_DISPLAY_ERROR:
+          neomacs_run_display_event ("display-error",
+                                     make_fixnum (ev->keysym), Qnil);
+          break;
+
+        case NEOMACS_EVENT_APPEARANCE_CHANGED:
+          neomacs_run_display_event ("appearance-changed", Qnil,
+                                     intern (ev->keysym ? "dark" : "light"));
+          break;
+
         default:
           break;
         }
@@ -15500,6 +19041,9 @@ syms_of_neomacsterm (void)
 {
   /* Redisplay interface is now statically initialized */
 
+  neomacs_minimap_drag_window = Qnil;
+  staticpro (&neomacs_minimap_drag_window);
+
   defsubr (&Sneomacs_available_p);
   defsubr (&Sneomacs_display_list);
   defsubr (&Sxw_display_color_p);
@@ -15515,19 +19059,59 @@ syms_of_neomacsterm (void)
   defsubr (&Sneomacs_video_update);
   defsubr (&Sneomacs_video_floating);
   defsubr (&Sneomacs_video_floating_clear);
+  defsubr (&Sneomacs_video_pip);
+  defsubr (&Sneomacs_set_background_video);
+  defsubr (&Sneomacs_media_waveform);
+  defsubr (&Sneomacs_media_thumbnails);
+  defsubr (&Sneomacs_media_probe);
+  defsubr (&Sneomacs_video_audio_levels);
+  defsubr (&Sneomacs_video_audio_events);
 
   /* Image functions */
   defsubr (&Sneomacs_image_load);
   defsubr (&Sneomacs_image_size);
   defsubr (&Sneomacs_image_free);
+  defsubr (&Sneomacs_image_set_interactive);
+  defsubr (&Sneomacs_image_swap);
+  defsubr (&Sneomacs_set_chrome_image);
   defsubr (&Sneomacs_image_floating);
   defsubr (&Sneomacs_image_floating_clear);
   defsubr (&Sneomacs_insert_image);
   defsubr (&Sneomacs_insert_image_data);
 
+  /* PDF document functions */
+  defsubr (&Sneomacs_pdf_open);
+  defsubr (&Sneomacs_pdf_page_count);
+  defsubr (&Sneomacs_pdf_page_size);
+  defsubr (&Sneomacs_pdf_render_page);
+  defsubr (&Sneomacs_pdf_extract_page);
+  defsubr (&Sneomacs_pdf_page_text);
+  defsubr (&Sneomacs_pdf_page_links);
+  defsubr (&Sneomacs_pdf_close);
+  defsubr (&Sneomacs_math_image);
+  defsubr (&Sneomacs_math_clear_cache);
+  defsubr (&Sneomacs_icon_set_load);
+  defsubr (&Sneomacs_icon_image);
+  defsubr (&Sneomacs_icon_clear_cache);
+  defsubr (&Sneomacs_barcode_image);
+  defsubr (&Sneomacs_html_link_at);
+  defsubr (&Sneomacs_chart);
+  defsubr (&Sneomacs_chart_update);
+  defsubr (&Sneomacs_canvas_create);
+  defsubr (&Sneomacs_canvas_draw);
+  defsubr (&Sneomacs_shader_effect_register);
+  defsubr (&Sneomacs_shader_effect_apply);
+  defsubr (&Sneomacs_shader_effect_remove);
+  defsubr (&Sneomacs_char_grid_show);
+  defsubr (&Sneomacs_char_grid_update);
+  defsubr (&Sneomacs_char_grid_hide);
+  defsubr (&Sneomacs_char_grid_index_at);
+  defsubr (&Sneomacs_emoji_data);
+
   /* WebKit browser functions */
   defsubr (&Sneomacs_webkit_init);
   defsubr (&Sneomacs_webkit_create);
+  defsubr (&Sneomacs_webkit_define_profile);
   defsubr (&Sneomacs_webkit_destroy);
   defsubr (&Sneomacs_webkit_load_uri);
   defsubr (&Sneomacs_webkit_go_back);
@@ -15554,7 +19138,25 @@ syms_of_neomacsterm (void)
   /* Animation API */
   defsubr (&Sneomacs_set_animation_option);
   defsubr (&Sneomacs_get_animation_option);
+  defsubr (&Sneomacs_display_options);
+  defsubr (&Sneomacs_display_errors);
+  defsubr (&Sneomacs_display_clear_errors);
+  defsubr (&Sneomacs_display_memory_usage);
+  defsubr (&Sneomacs_frame_stats);
+  defsubr (&Sneomacs_scene_dump);
+  defsubr (&Sneomacs_frame_text);
+  defsubr (&Sneomacs_display_quality);
+  defsubr (&Sneomacs_input_latency);
+  defsubr (&Sneomacs_display_memory_report);
+  defsubr (&Sneomacs_session_save);
+  defsubr (&Sneomacs_replay_start);
+  defsubr (&Sneomacs_replay_stop);
+  defsubr (&Sneomacs_export_effect_preview);
+  defsubr (&Sneomacs_set_display_theme);
+  defsubr (&Sneomacs_session_restore);
   defsubr (&Sneomacs_start_buffer_transition);
+  defsubr (&Sneomacs_set_frame_zoom);
+  defsubr (&Sneomacs_scroll_hint);
   defsubr (&Sneomacs_animation_active_p);
   defsubr (&Sneomacs_prepare_buffer_transition);
   defsubr (&Sneomacs_trigger_buffer_transition);
@@ -15573,6 +19175,7 @@ syms_of_neomacsterm (void)
   defsubr (&Sneomacs_set_corner_radius);
   defsubr (&Sneomacs_set_extra_spacing);
   defsubr (&Sneomacs_set_background_gradient);
+  defsubr (&Sneomacs_set_face_background_style);
   defsubr (&Sneomacs_set_scroll_bar_config);
   defsubr (&Sneomacs_set_indent_guides);
   defsubr (&Sneomacs_set_indent_guide_rainbow);
@@ -15584,6 +19187,15 @@ syms_of_neomacsterm (void)
   defsubr (&Sneomacs_set_cursor_pulse);
   defsubr (&Sneomacs_set_focus_mode);
   defsubr (&Sneomacs_set_minimap);
+  defsubr (&Sneomacs_minimap_update_lines);
+  defsubr (&Sneomacs_minimap_forget);
+  defsubr (&Sneomacs_highlight_request);
+  defsubr (&Sneomacs_highlight_edit);
+  defsubr (&Sneomacs_highlight_forget);
+  defsubr (&Sneomacs_highlight_set_face);
+  defsubr (&Sneomacs_highlight_poll);
+  defsubr (&Sneomacs_set_fallback_glyph_metrics);
+  defsubr (&Sneomacs_set_backdrop_blur);
   defsubr (&Sneomacs_set_typing_ripple);
   defsubr (&Sneomacs_set_search_pulse);
   defsubr (&Sneomacs_set_background_pattern);
@@ -15617,6 +19229,8 @@ syms_of_neomacsterm (void)
   defsubr (&Sneomacs_set_cursor_error_pulse);
   defsubr (&Sneomacs_set_window_content_shadow);
   defsubr (&Sneomacs_set_resize_padding);
+  defsubr (&Sneomacs_set_text_gamma);
+  defsubr (&Sneomacs_set_cursor_inversion);
   defsubr (&Sneomacs_set_minibuffer_highlight);
   defsubr (&Sneomacs_set_scroll_velocity_fade);
   defsubr (&Sneomacs_set_click_halo);
@@ -15720,6 +19334,7 @@ syms_of_neomacsterm (void)
   defsubr (&Sneomacs_set_cursor_pendulum);
   defsubr (&Sneomacs_set_mode_line_gradient);
   defsubr (&Sneomacs_set_region_glow);
+  defsubr (&Sneomacs_set_selection_runs);
   defsubr (&Sneomacs_set_window_glow);
   defsubr (&Sneomacs_set_scroll_progress);
   defsubr (&Sneomacs_set_inactive_tint);
@@ -15727,19 +19342,49 @@ syms_of_neomacsterm (void)
   /* Cursor blink */
   defsubr (&Sneomacs_set_cursor_blink);
   defsubr (&Sneomacs_set_cursor_animation);
+  defsubr (&Sneomacs_set_cursor_animation_mode);
   defsubr (&Sneomacs_set_animation_config);
+  defsubr (&Sneomacs_set_animation_curve);
+  defsubr (&Sneomacs_animate_floating);
 
   /* Terminal emulator (neo-term) */
   defsubr (&Sneomacs_terminal_create);
+  defsubr (&Sneomacs_terminal_create_ssh);
   defsubr (&Sneomacs_terminal_write);
+  defsubr (&Sneomacs_terminal_write_many);
+  defsubr (&Sneomacs_terminal_input_status);
+  defsubr (&Sneomacs_terminal_cancel_input);
   defsubr (&Sneomacs_terminal_resize);
   defsubr (&Sneomacs_terminal_destroy);
   defsubr (&Sneomacs_terminal_set_float);
+  defsubr (&Sneomacs_terminal_set_font);
+  defsubr (&Sneomacs_terminal_changed_lines);
+  defsubr (&Sneomacs_terminal_input_region);
+  defsubr (&Sneomacs_terminal_set_prompt_heuristics);
+  defsubr (&Sneomacs_terminal_set_local_echo);
+  defsubr (&Sneomacs_tmux_connect);
+  defsubr (&Sneomacs_tmux_command);
+  defsubr (&Sneomacs_tmux_disconnect);
+  defsubr (&Sneomacs_terminal_detach);
+  defsubr (&Sneomacs_terminal_attach);
+  defsubr (&Sneomacs_terminal_set_background);
   defsubr (&Sneomacs_terminal_get_text);
 
   DEFSYM (Qneomacs, "neomacs");
+  DEFSYM (Qneomacs_stale_handle, "neomacs-stale-handle");
+  DEFSYM (Qneomacs_html, "neomacs-html");
+  define_error (Qneomacs_stale_handle,
+                "Display resource was already freed", Qerror);
   /* Qvideo and Qwebkit are defined in xdisp.c for use in VIDEOP/WEBKITP */
   DEFSYM (QCid, ":id");
+  DEFSYM (QCcursor, ":cursor");
+  DEFSYM (QCdivider, ":divider");
+  DEFSYM (QCplaceholder, ":placeholder");
+  DEFSYM (QCterminal_palette, ":terminal-palette");
+  DEFSYM (QCfill, ":fill");
+  DEFSYM (QCmin, ":min");
+  DEFSYM (QCmax, ":max");
+  DEFSYM (Qarea, "area");
 
   /* Cursor animation style symbols */
   DEFSYM (Qexponential, "exponential");
@@ -15772,6 +19417,10 @@ syms_of_neomacsterm (void)
   DEFSYM (Qcrt_scanlines, "crt-scanlines");
   DEFSYM (Qdepth_of_field, "depth-of-field");
   DEFSYM (Qtypewriter_reveal, "typewriter-reveal");
+  DEFSYM (Qslide_left, "slide-left");
+  DEFSYM (Qslide_right, "slide-right");
+  DEFSYM (Qpush, "push");
+  DEFSYM (Qacrylic, "acrylic");
 
   /* WebKit new window callback */
   DEFVAR_LISP ("neomacs-webkit-new-window-function", Vneomacs_webkit_new_window_function,
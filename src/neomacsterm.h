@@ -358,6 +358,9 @@ extern void neomacs_scroll_run (struct window *, struct run *);
 /* Expose */
 extern void neomacs_expose_frame (struct frame *);
 
+/* Faces */
+extern void neomacs_free_face (struct frame *, struct face *);
+
 /* Event queue for input events */
 extern void neomacs_evq_enqueue (union buffered_input_event *ev);
 
@@ -1191,6 +1191,7 @@ static bool next_element_from_xwidget (struct it *);
 #ifdef HAVE_NEOMACS
 static bool next_element_from_video (struct it *);
 static bool next_element_from_webkit (struct it *);
+static bool next_element_from_terminal (struct it *);
 #endif
 static void load_overlay_strings (struct it *, ptrdiff_t);
 static bool get_next_display_element (struct it *);
@@ -5972,6 +5973,7 @@ handle_display_spec (struct it *it, Lisp_Object spec, Lisp_Object object,
 #ifdef HAVE_NEOMACS
       && !EQ (XCAR (spec), Qvideo)
       && !EQ (XCAR (spec), Qwebkit)
+      && !EQ (XCAR (spec), Qterminal)
 #endif
       && !EQ (XCAR (spec), Qspace)
       && !EQ (XCAR (spec), Qwhen)
@@ -6476,6 +6478,7 @@ handle_single_display_spec (struct it *it, Lisp_Object spec, Lisp_Object object,
 #ifdef HAVE_NEOMACS
 	     || VIDEOP (value)
 	     || WEBKITP (value)
+	     || NEOMACS_TERMINALP (value)
 #endif /* HAVE_NEOMACS */
 	     );
 
@@ -6678,6 +6681,53 @@ handle_single_display_spec (struct it *it, Lisp_Object spec, Lisp_Object object,
 	      retval = 1 + (it->area == TEXT_AREA);
 	    }
 	}
+      /* Handle (terminal :id N :cols C :rows R) display property.
+	 The size can also be given as :width and :height, integer
+	 pixels or (VALUE . em).  Columns and rows are in frame
+	 character cells; the display fits the terminal's grid to the
+	 space it gets.  */
+      else if (NEOMACS_TERMINALP (value))
+	{
+	  Lisp_Object id = plist_get (XCDR (value), QCid);
+	  if (FIXNUMP (id))
+	    {
+	      Lisp_Object width_prop = plist_get (XCDR (value), QCwidth);
+	      Lisp_Object height_prop = plist_get (XCDR (value), QCheight);
+	      Lisp_Object cols = plist_get (XCDR (value), QCcols);
+	      Lisp_Object rows = plist_get (XCDR (value), QCrows);
+	      struct face *face = FACE_FROM_ID (it->f, it->face_id);
+	      int font_height = face && face->font ? FONT_HEIGHT (face->font) : 16;
+
+	      it->what = IT_TERMINAL;
+	      it->terminal_id = XFIXNUM (id);
+
+	      if (FIXNATP (cols))
+		it->video_width = XFIXNAT (cols) * FRAME_COLUMN_WIDTH (it->f);
+	      else if (FIXNUMP (width_prop))
+		it->video_width = XFIXNUM (width_prop);
+	      else if (CONSP (width_prop) && NUMBERP (XCAR (width_prop))
+		       && EQ (XCDR (width_prop), Qem))
+		it->video_width = (int) (XFLOATINT (XCAR (width_prop)) * font_height);
+	      else
+		it->video_width = 80 * FRAME_COLUMN_WIDTH (it->f);
+
+	      if (FIXNATP (rows))
+		it->video_height = XFIXNAT (rows) * FRAME_LINE_HEIGHT (it->f);
+	      else if (FIXNUMP (height_prop))
+		it->video_height = XFIXNUM (height_prop);
+	      else if (CONSP (height_prop) && NUMBERP (XCAR (height_prop))
+		       && EQ (XCDR (height_prop), Qem))
+		it->video_height = (int) (XFLOATINT (XCAR (height_prop)) * font_height);
+	      else
+		it->video_height = 24 * FRAME_LINE_HEIGHT (it->f);
+
+	      it->position = start_pos;
+	      it->object = NILP (object) ? it->w->contents : object;
+	      it->method = GET_FROM_TERMINAL;
+	      *position = start_pos;
+	      retval = 1 + (it->area == TEXT_AREA);
+	    }
+	}
 #endif /* HAVE_NEOMACS */
 #ifdef HAVE_WINDOW_SYSTEM
       else
@@ -7418,6 +7468,7 @@ push_it (struct it *it, struct text_pos *position)
 #ifdef HAVE_NEOMACS
     case GET_FROM_VIDEO:
     case GET_FROM_WEBKIT:
+    case GET_FROM_TERMINAL:
       /* Video/WebKit state is stored in it->video_id/webkit_id */
       break;
 #endif
@@ -7578,6 +7629,7 @@ pop_it (struct it *it)
 #ifdef HAVE_NEOMACS
     case GET_FROM_VIDEO:
     case GET_FROM_WEBKIT:
+    case GET_FROM_TERMINAL:
       /* Video/WebKit state is already in it->video_id/webkit_id */
       break;
 #endif
@@ -8358,6 +8410,7 @@ static next_element_function const get_next_element[NUM_IT_METHODS] =
 #ifdef HAVE_NEOMACS
   next_element_from_video,
   next_element_from_webkit,
+  next_element_from_terminal,
 #endif
 };
 
@@ -9306,6 +9359,7 @@ set_iterator_to_next (struct it *it, bool reseat_p)
 #ifdef HAVE_NEOMACS
     case GET_FROM_VIDEO:
     case GET_FROM_WEBKIT:
+    case GET_FROM_TERMINAL:
 #endif
 
       /* The position etc with which we have to proceed are on
@@ -9809,6 +9863,13 @@ next_element_from_webkit (struct it *it)
   it->what = IT_WEBKIT;
   return true;
 }
+
+static bool
+next_element_from_terminal (struct it *it)
+{
+  it->what = IT_TERMINAL;
+  return true;
+}
 #endif /* HAVE_NEOMACS */
 
 
@@ -31165,6 +31226,28 @@ fill_webkit_glyph_string (struct glyph_string *s)
   s->width = s->first_glyph->pixel_width;
   s->ybase += s->first_glyph->voffset;
 }
+
+static void
+fill_terminal_glyph_string (struct glyph_string *s)
+{
+  eassert (s->first_glyph->type == TERMINAL_GLYPH);
+  s->face = FACE_FROM_ID (s->f, s->first_glyph->face_id);
+  s->font = s->face->font;
+  if (s->hl == DRAW_MOUSE_FACE
+      || (s->hl == DRAW_CURSOR
+	  && MATRIX_ROW (s->w->current_matrix,
+			 s->w->phys_cursor.vpos)->mouse_face_p
+	  && cursor_in_mouse_face_p (s->w)))
+    {
+      Mouse_HLInfo *hlinfo = MOUSE_HL_INFO (s->f);
+      s->face = FACE_FROM_ID_OR_NULL (s->f, hlinfo->mouse_face_face_id);
+      if (!s->face)
+	s->face = FACE_FROM_ID (s->f, MOUSE_FACE_ID);
+      prepare_face_for_display (s->f, s->face);
+    }
+  s->width = s->first_glyph->pixel_width;
+  s->ybase += s->first_glyph->voffset;
+}
 #endif /* HAVE_NEOMACS */
 
 /* Fill glyph string S from a sequence of stretch glyphs.
@@ -31658,6 +31741,8 @@ compute_overhangs_and_x (struct glyph_string *s, int x, bool backward_p)
      eassume (false)
 # define BUILD_WEBKIT_GLYPH_STRING(START, END, HEAD, TAIL, HL, X, LAST_X) \
      eassume (false)
+# define BUILD_TERMINAL_GLYPH_STRING(START, END, HEAD, TAIL, HL, X, LAST_X) \
+     eassume (false)
 #else
 # define BUILD_VIDEO_GLYPH_STRING(START, END, HEAD, TAIL, HL, X, LAST_X) \
      do									\
@@ -31681,6 +31766,17 @@ compute_overhangs_and_x (struct glyph_string *s, int x, bool backward_p)
          s->x = (X);							\
        }								\
      while (false)
+# define BUILD_TERMINAL_GLYPH_STRING(START, END, HEAD, TAIL, HL, X, LAST_X) \
+     do									\
+       {								\
+	 s = alloca (sizeof *s);					\
+	 INIT_GLYPH_STRING (s, NULL, w, row, area, START, HL);		\
+	 fill_terminal_glyph_string (s);				\
+	 append_glyph_string (&(HEAD), &(TAIL), s);			\
+	 ++(START);							\
+         s->x = (X);							\
+       }								\
+     while (false)
 #endif /* HAVE_NEOMACS */
 
 /* Add a glyph string for a sequence of character glyphs to the list
@@ -31858,6 +31954,11 @@ compute_overhangs_and_x (struct glyph_string *s, int x, bool backward_p)
 					 HL, X, LAST_X);		\
 	      break;							\
 									\
+	    case TERMINAL_GLYPH:					\
+	      BUILD_TERMINAL_GLYPH_STRING (START, END, HEAD, TAIL,	\
+					   HL, X, LAST_X);		\
+	      break;							\
+									\
 	    default:							\
 	      emacs_abort ();						\
 	    }								\
@@ -32883,6 +32984,105 @@ produce_webkit_glyph (struct it *it)
 	IT_EXPAND_MATRIX_WIDTH (it, area);
     }
 }
+/* Produce a glyph for a terminal display property.  */
+static void
+produce_terminal_glyph (struct it *it)
+{
+  int glyph_ascent;
+  int width = it->video_width > 0 ? it->video_width : 640;
+  int height = it->video_height > 0 ? it->video_height : 384;
+
+  eassert (it->what == IT_TERMINAL);
+
+  struct face *face = FACE_FROM_ID (it->f, it->face_id);
+  prepare_face_for_display (it->f, face);
+
+  /* Sit on the baseline like text, so the terminal's last row lines
+     up with the text around it.  */
+  int descent = face->font ? min (FONT_DESCENT (face->font), height) : 0;
+  it->ascent = it->phys_ascent = glyph_ascent = height - descent;
+  it->descent = it->phys_descent = descent;
+  it->pixel_width = width;
+
+  if (it->descent < 0)
+    it->descent = 0;
+
+  it->nglyphs = 1;
+
+  if (face->box != FACE_NO_BOX)
+    {
+      if (face->box_horizontal_line_width > 0)
+	{
+	  it->ascent += face->box_horizontal_line_width;
+	  it->descent += face->box_horizontal_line_width;
+	}
+      if (face->box_vertical_line_width > 0)
+	{
+	  if (it->start_of_box_run_p)
+	    it->pixel_width += face->box_vertical_line_width;
+	  it->pixel_width += face->box_vertical_line_width;
+	}
+    }
+
+  take_vertical_position_into_account (it);
+
+  /* Crop wide glyphs at right edge.  */
+  int crop = it->pixel_width - (it->last_visible_x - it->current_x);
+  if (crop > 0 && (it->hpos == 0 || it->pixel_width > it->last_visible_x / 4))
+    it->pixel_width -= crop;
+
+  if (it->glyph_row)
+    {
+      enum glyph_row_area area = it->area;
+      struct glyph *glyph
+	= it->glyph_row->glyphs[area] + it->glyph_row->used[area];
+
+      if (it->glyph_row->reversed_p)
+	{
+	  struct glyph *g;
+	  for (g = glyph - 1; g >= it->glyph_row->glyphs[it->area]; g--)
+	    g[1] = *g;
+	  glyph = it->glyph_row->glyphs[it->area];
+	}
+      if (glyph < it->glyph_row->glyphs[area + 1])
+	{
+	  glyph->charpos = CHARPOS (it->position);
+	  glyph->object = it->object;
+	  glyph->pixel_width = clip_to_bounds (-1, it->pixel_width, SHRT_MAX);
+	  glyph->ascent = glyph_ascent;
+	  glyph->descent = it->descent;
+	  glyph->voffset = it->voffset;
+	  glyph->type = TERMINAL_GLYPH;
+	  glyph->avoid_cursor_p = it->avoid_cursor_p;
+	  glyph->multibyte_p = it->multibyte_p;
+	  if (it->glyph_row->reversed_p && area == TEXT_AREA)
+	    {
+	      glyph->right_box_line_p = it->start_of_box_run_p;
+	      glyph->left_box_line_p = it->end_of_box_run_p;
+	    }
+	  else
+	    {
+	      glyph->left_box_line_p = it->start_of_box_run_p;
+	      glyph->right_box_line_p = it->end_of_box_run_p;
+	    }
+	  glyph->overlaps_vertically_p = 0;
+	  glyph->padding_p = 0;
+	  glyph->glyph_not_available_p = 0;
+	  glyph->face_id = it->face_id;
+	  glyph->u.terminal_id = it->terminal_id;
+	  glyph->font_type = FONT_TYPE_UNKNOWN;
+	  if (it->bidi_p)
+	    {
+	      glyph->resolved_level = it->bidi_it.resolved_level;
+	      eassert ((it->bidi_it.type & 7) == it->bidi_it.type);
+	      glyph->bidi_type = it->bidi_it.type;
+	    }
+	  ++it->glyph_row->used[area];
+	}
+      else
+	IT_EXPAND_MATRIX_WIDTH (it, area);
+    }
+}
 #endif /* HAVE_NEOMACS */
 
 /* Append a stretch glyph to IT->glyph_row.  OBJECT is the source
@@ -34433,6 +34633,8 @@ gui_produce_glyphs (struct it *it)
     produce_video_glyph (it);
   else if (it->what == IT_WEBKIT)
     produce_webkit_glyph (it);
+  else if (it->what == IT_TERMINAL)
+    produce_terminal_glyph (it);
 #endif /* HAVE_NEOMACS */
 
  done:
@@ -38430,6 +38632,8 @@ be let-bound around code that needs to disable messages temporarily. */);
 #ifdef HAVE_NEOMACS
   DEFSYM (Qvideo, "video");
   DEFSYM (Qwebkit, "webkit");
+  DEFSYM (QCcols, ":cols");
+  DEFSYM (QCrows, ":rows");
 #endif
 
   /* Name of the symbol which disables Lisp evaluation in 'display'
@@ -4726,6 +4726,9 @@ free_realized_face (struct frame *f, struct face *face)
 	  free_face_colors (f, face);
 #endif /* HAVE_X_WINDOWS */
 	  image_destroy_bitmap (f, face->stipple);
+#ifdef HAVE_NEOMACS
+	  neomacs_free_face (f, face);
+#endif /* HAVE_NEOMACS */
 	}
 #endif /* HAVE_WINDOW_SYSTEM */
 